@@ -1,6 +1,6 @@
 use crate::{
     eth::subscription::SubscriptionId,
-    types::{EvmMineOptions, Forking, Index},
+    types::{EvmMineOptions, Forking, Index, TransactionConditional},
 };
 use alloy_primitives::{Address, Bytes, TxHash, B256, B64, U256};
 use alloy_rpc_trace_types::geth::{GethDebugTracingOptions, GethDefaultTracingOptions};
@@ -167,6 +167,10 @@ pub enum EthRequest {
     #[cfg_attr(feature = "serde", serde(rename = "eth_sendRawTransaction", with = "sequence"))]
     EthSendRawTransaction(Bytes),
 
+    /// Sends a raw transaction which is only included in a block that meets the conditions
+    #[cfg_attr(feature = "serde", serde(rename = "eth_sendRawTransactionConditional"))]
+    EthSendRawTransactionConditional(Bytes, TransactionConditional),
+
     #[cfg_attr(feature = "serde", serde(rename = "eth_call"))]
     EthCall(
         CallRequest,
@@ -322,6 +326,12 @@ pub enum EthRequest {
         )
     )]
     AutoImpersonateAccount(bool),
+    /// Adds an address whose transactions are posted to the webhook
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_addAddressWatch", with = "sequence"))]
+    AddAddressWatch(Address),
+    /// Removes an address added with `anvil_addAddressWatch`
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_removeAddressWatch", with = "sequence"))]
+    RemoveAddressWatch(Address),
     /// Returns true if automatic mining is enabled, and false.
     #[cfg_attr(
         feature = "serde",
@@ -492,6 +502,18 @@ pub enum EthRequest {
     )]
     LoadState(Bytes),
 
+    /// Evicts the oldest states persisted to disk until at most the given number of states is
+    /// left, returns the number of bytes freed
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_flushStateCache", with = "sequence"))]
+    FlushStateCache(u64),
+
+    /// Returns the states persisted to disk and their sizes
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "anvil_getStateCacheInfo", with = "empty_params")
+    )]
+    GetStateCacheInfo(()),
+
     /// Retrieves the Anvil node configuration params
     #[cfg_attr(feature = "serde", serde(rename = "anvil_nodeInfo", with = "empty_params"))]
     NodeInfo(()),
@@ -842,6 +864,31 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_custom_address_watch() {
+        let s = r#"{"method": "anvil_addAddressWatch", "params":
+["0x70997970C51812dc3A010C7d01b50e0d17dc79C8"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "anvil_removeAddressWatch", "params":
+["0x70997970C51812dc3A010C7d01b50e0d17dc79C8"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_custom_state_cache() {
+        let s = r#"{"method": "anvil_flushStateCache", "params": [10]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert!(matches!(req, EthRequest::FlushStateCache(10)));
+
+        let s = r#"{"method": "anvil_getStateCacheInfo", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_custom_get_automine() {
         let s = r#"{"method": "anvil_getAutomine", "params": []}"#;
@@ -912,6 +959,33 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_send_raw_transaction_conditional() {
+        let s = r#"{"method": "eth_sendRawTransactionConditional", "params": ["0x02f8", {
+            "knownAccounts": {
+                "0x5FbDB2315678afecb367f032d93F642f64180aa3": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000000": "0x0000000000000000000000000000000000000000000000000000000000000001"
+                }
+            },
+            "blockNumberMax": "0x10",
+            "timestampMin": "0x65a8f3c0"
+        }]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        let EthRequest::EthSendRawTransactionConditional(_, conditional) = req else {
+            panic!("unexpected request {req:?}")
+        };
+        assert_eq!(conditional.known_accounts.len(), 2);
+        assert!(conditional.known_accounts.values().any(|account| matches!(
+            account,
+            crate::types::KnownAccount::Slots(slots) if slots.len() == 1
+        )));
+        assert_eq!(conditional.block_number_min, None);
+        assert_eq!(conditional.block_number_max, Some(alloy_primitives::U64::from(16)));
+        assert_eq!(conditional.timestamp_min, Some(alloy_primitives::U64::from(0x65a8f3c0u64)));
+    }
+
     #[test]
     fn test_custom_reset() {
         let s = r#"{"method": "anvil_reset", "params": [{"forking": {"jsonRpcUrl": "https://ethereumpublicnode.com",
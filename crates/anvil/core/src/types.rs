@@ -1,4 +1,4 @@
-use alloy_primitives::{TxHash, B256, U256, U64};
+use alloy_primitives::{Address, TxHash, B256, U256, U64};
 use revm::primitives::SpecId;
 use std::collections::BTreeMap;
 
@@ -111,6 +111,36 @@ impl serde::Serialize for Work {
     }
 }
 
+/// The preconditions of `eth_sendRawTransactionConditional`, which must hold in the block the
+/// transaction is included in
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TransactionConditional {
+    /// The expected storage root or storage slot values of accounts
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub known_accounts: BTreeMap<Address, KnownAccount>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub block_number_min: Option<U64>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub block_number_max: Option<U64>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub timestamp_min: Option<U64>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub timestamp_max: Option<U64>,
+}
+
+/// The expected state of an account of [TransactionConditional::known_accounts]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum KnownAccount {
+    /// The storage root of the account
+    StorageRoot(B256),
+    /// The values of storage slots of the account
+    Slots(BTreeMap<B256, B256>),
+}
+
 /// A hex encoded or decimal index
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Index(usize);
@@ -221,6 +251,31 @@ pub struct AnvilMetadata {
     pub snapshots: BTreeMap<U256, (u64, B256)>,
 }
 
+/// The states of older blocks that are persisted to disk, see `anvil_getStateCacheInfo`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct StateCacheInfo {
+    /// The persisted states, oldest first
+    pub entries: Vec<StateCacheEntry>,
+    /// The total size of the written states in bytes
+    pub total_bytes: u64,
+    /// The maximum number of persisted states
+    pub max_entries: usize,
+    /// The maximum total size of the persisted states in bytes, if any
+    pub max_bytes: Option<u64>,
+}
+
+/// A state that's persisted to disk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct StateCacheEntry {
+    pub block_hash: B256,
+    /// The size of the file in bytes, `None` while it's being written
+    pub size: Option<u64>,
+}
+
 /// Information about the forked network.
 /// See <https://hardhat.org/hardhat-network/docs/reference#hardhat_metadata>
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -12,6 +12,7 @@ use ethers::{
     signers::coins_bip39::{English, Mnemonic},
     utils::WEI_IN_ETHER,
 };
+use eyre::WrapErr;
 use foundry_common::types::ToAlloy;
 use foundry_config::{Chain, Config};
 use futures::FutureExt;
@@ -29,7 +30,7 @@ use std::{
     task::{Context, Poll},
     time::Duration,
 };
-use tokio::time::{Instant, Interval};
+use tokio::time::{Instant, Interval, MissedTickBehavior};
 
 #[derive(Clone, Debug, Parser)]
 pub struct NodeArgs {
@@ -135,6 +136,10 @@ pub struct NodeArgs {
 
     /// Interval in seconds at which the state and block environment is to be dumped to disk.
     ///
+    /// Each dump is written to a temporary file which then replaces the previous dump, so that
+    /// the last complete dump survives a crash. A dump is skipped if the previous one is still
+    /// running.
+    ///
     /// See --state and --dump-state
     #[clap(short, long, value_name = "SECONDS")]
     pub state_interval: Option<u64>,
@@ -166,6 +171,47 @@ pub struct NodeArgs {
     #[clap(long)]
     pub transaction_block_keeper: Option<usize>,
 
+    /// The maximum number of states of older blocks that are persisted to disk.
+    ///
+    /// The most recent states are kept in memory, older ones are written to a temporary directory
+    /// and the oldest of those are deleted beyond this limit. Unlike `--transaction-block-keeper`,
+    /// which limits the blocks and transactions kept in memory, this only limits the states
+    /// historical calls can be made against. `0` doesn't persist any states. Has no effect with
+    /// `--prune-history`, which never persists states.
+    #[clap(long, value_name = "NUM")]
+    pub max_persisted_states: Option<usize>,
+
+    /// The maximum total size of the states persisted to disk, in bytes.
+    ///
+    /// The oldest states are deleted whenever a new state is persisted beyond this size. The
+    /// states are written in the background, so the ones still being written may briefly exceed
+    /// it.
+    #[clap(long, value_name = "BYTES")]
+    pub state_cache_max_bytes: Option<u64>,
+
+    /// The artifacts directory of a foundry project, used to decode custom errors and to locate
+    /// reverts in the sources of the project.
+    ///
+    /// Defaults to the artifacts of the foundry project in the current directory, if any.
+    #[clap(long, value_name = "PATH")]
+    pub artifacts: Option<PathBuf>,
+
+    /// Locate the reverts in the sources of the project, and include the reverts decoded with the
+    /// artifacts in the errors of reverted calls.
+    ///
+    /// Locating reverts records every `REVERT` of the executed transactions, so by default only
+    /// the custom errors of the node logs are decoded, and the errors are the same as geth's.
+    #[clap(long)]
+    pub verbose_errors: bool,
+
+    /// The URL JSON payloads of on-chain events are posted to, e.g. new blocks, transactions of
+    /// watched addresses and contract deployments.
+    ///
+    /// The events and watched addresses are configured in the `[hooks]` section of the foundry
+    /// project in the current directory, if any.
+    #[clap(long, value_name = "URL")]
+    pub hook_url: Option<String>,
+
     #[clap(flatten)]
     pub evm_opts: AnvilEvmArgs,
 
@@ -193,6 +239,12 @@ impl NodeArgs {
             self.evm_opts.compute_units_per_second
         };
 
+        let project = project_config();
+        let mut hooks = project.as_ref().map(|config| config.hooks.clone()).unwrap_or_default();
+        if let Some(url) = self.hook_url {
+            hooks.url = Some(url);
+        }
+
         NodeConfig::default()
             .with_gas_limit(self.evm_opts.gas_limit.map(U256::from))
             .disable_block_gas_limit(self.evm_opts.disable_block_gas_limit)
@@ -232,7 +284,14 @@ impl NodeArgs {
             .set_pruned_history(self.prune_history)
             .with_init_state(self.load_state.or_else(|| self.state.and_then(|s| s.state)))
             .with_transaction_block_keeper(self.transaction_block_keeper)
+            .with_max_persisted_states(self.max_persisted_states)
+            .with_state_cache_max_bytes(self.state_cache_max_bytes)
             .with_optimism(self.evm_opts.optimism)
+            .with_artifacts(
+                self.artifacts.or_else(|| project.map(|config| config.project_paths().artifacts)),
+            )
+            .with_verbose_errors(self.verbose_errors)
+            .with_hooks(hooks)
     }
 
     fn account_generator(&self) -> AccountGenerator {
@@ -523,6 +582,15 @@ impl AnvilEvmArgs {
     }
 }
 
+/// Returns the config of the foundry project in the current directory, if any.
+fn project_config() -> Option<Config> {
+    let root = std::env::current_dir().ok()?;
+    if !root.join(Config::FILE_NAME).exists() {
+        return None
+    }
+    Config::try_from(Config::figment_with_root(root)).ok()
+}
+
 /// Helper type to periodically dump the state of the chain to disk
 struct PeriodicStateDumper {
     in_progress_dump: Option<Pin<Box<dyn Future<Output = ()> + Send + Sync + 'static>>>,
@@ -540,12 +608,17 @@ impl PeriodicStateDumper {
             dump_state
         });
 
-        // periodically flush the state
-        let interval = tokio::time::interval_at(Instant::now() + interval, interval);
+        // periodically flush the state, skipping the ticks missed while a dump is running
+        let mut interval = tokio::time::interval_at(Instant::now() + interval, interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
         Self { in_progress_dump: None, api, dump_state, interval }
     }
 
-    async fn dump(&self) {
+    /// Dumps the state on shutdown, after the periodic dump in progress if any.
+    async fn dump(&mut self) {
+        if let Some(in_progress) = self.in_progress_dump.take() {
+            in_progress.await;
+        }
         if let Some(state) = self.dump_state.clone() {
             Self::dump_state(self.api.clone(), state).await
         }
@@ -553,22 +626,45 @@ impl PeriodicStateDumper {
 
     /// Infallible state dump
     async fn dump_state(api: EthApi, dump_state: PathBuf) {
-        trace!(path=?dump_state, "Dumping state on shutdown");
-        match api.serialized_state().await {
-            Ok(state) => {
-                if let Err(err) = foundry_common::fs::write_json_file(&dump_state, &state) {
-                    error!(?err, "Failed to dump state");
-                } else {
-                    trace!(path=?dump_state, "Dumped state on shutdown");
-                }
-            }
+        trace!(path=?dump_state, "Dumping state");
+        let started = Instant::now();
+        // the state is cloned under a read lock, it's serialized and written without blocking the
+        // node
+        let state = match api.serialized_state().await {
+            Ok(state) => state,
             Err(err) => {
                 error!(?err, "Failed to extract state");
+                return
+            }
+        };
+        let path = dump_state.clone();
+        match tokio::task::spawn_blocking(move || write_state(&path, &state))
+            .await
+            .unwrap_or_else(|err| Err(eyre::Report::new(err)))
+        {
+            Ok(size) => {
+                debug!(path=?dump_state, size, elapsed=?started.elapsed(), "Dumped state")
             }
+            Err(err) => error!(?err, "Failed to dump state"),
         }
     }
 }
 
+/// Writes the state to a temporary file next to `path`, which is then renamed over `path`, so that
+/// a crash while dumping doesn't leave a partially written state behind.
+///
+/// Returns the size of the written state in bytes.
+fn write_state(path: &Path, state: &SerializableState) -> eyre::Result<usize> {
+    let json = serde_json::to_vec(state)?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, &json).wrap_err_with(|| format!("failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path)
+        .wrap_err_with(|| format!("failed to rename {} to {}", tmp.display(), path.display()))?;
+    Ok(json.len())
+}
+
 // An endless future that periodically dumps the state to disk if configured.
 impl Future for PeriodicStateDumper {
     type Output = ();
@@ -581,24 +677,21 @@ impl Future for PeriodicStateDumper {
 
         loop {
             if let Some(mut flush) = this.in_progress_dump.take() {
-                match flush.poll_unpin(cx) {
-                    Poll::Ready(_) => {
-                        this.interval.reset();
-                    }
-                    Poll::Pending => {
-                        this.in_progress_dump = Some(flush);
-                        return Poll::Pending
-                    }
+                if flush.poll_unpin(cx).is_pending() {
+                    this.in_progress_dump = Some(flush);
                 }
             }
 
-            if this.interval.poll_tick(cx).is_ready() {
-                let api = this.api.clone();
-                let path = this.dump_state.clone().expect("exists; see above");
-                this.in_progress_dump = Some(Box::pin(PeriodicStateDumper::dump_state(api, path)));
-            } else {
+            if this.interval.poll_tick(cx).is_pending() {
                 break
             }
+            if this.in_progress_dump.is_some() {
+                debug!("Previous state dump still running, skipping this one");
+                continue
+            }
+            let api = this.api.clone();
+            let path = this.dump_state.clone().expect("exists; see above");
+            this.in_progress_dump = Some(Box::pin(PeriodicStateDumper::dump_state(api, path)));
         }
 
         Poll::Pending
@@ -711,6 +804,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_write_state_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        std::fs::write(&path, "stale").unwrap();
+
+        let size = write_state(&path, &SerializableState::default()).unwrap();
+        assert_eq!(size as u64, std::fs::metadata(&path).unwrap().len());
+        assert!(!dir.path().join("state.json.tmp").exists());
+
+        let state = StateFile::parse_path(dir.path()).unwrap();
+        assert!(state.state.unwrap().accounts.is_empty());
+    }
+
     #[test]
     fn can_parse_hardfork() {
         let args: NodeArgs = NodeArgs::parse_from(["anvil", "--hardfork", "berlin"]);
@@ -743,6 +850,19 @@ mod tests {
         assert_eq!(args.prune_history, Some(Some(100)));
     }
 
+    #[test]
+    fn can_parse_state_cache_limits() {
+        let args: NodeArgs = NodeArgs::parse_from([
+            "anvil",
+            "--max-persisted-states",
+            "50",
+            "--state-cache-max-bytes",
+            "1000000",
+        ]);
+        assert_eq!(args.max_persisted_states, Some(50));
+        assert_eq!(args.state_cache_max_bytes, Some(1_000_000));
+    }
+
     #[test]
     fn can_parse_disable_block_gas_limit() {
         let args: NodeArgs = NodeArgs::parse_from(["anvil", "--disable-block-gas-limit"]);
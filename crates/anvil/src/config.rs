@@ -2,6 +2,7 @@ use crate::{
     cmd::StateFile,
     eth::{
         backend::{
+            artifacts::ProjectArtifacts,
             db::{Db, SerializableState},
             fork::{ClientFork, ClientForkConfig},
             genesis::GenesisConfig,
@@ -34,7 +35,7 @@ use foundry_common::{
     provider::alloy::ProviderBuilder, types::ToAlloy, ALCHEMY_FREE_TIER_CUPS,
     NON_ARCHIVE_NODE_WARNING, REQUEST_TIMEOUT,
 };
-use foundry_config::Config;
+use foundry_config::{Config, HooksConfig};
 use foundry_evm::{
     constants::DEFAULT_CREATE2_DEPLOYER,
     fork::{BlockchainDb, BlockchainDbMeta, SharedBackend},
@@ -170,10 +171,21 @@ pub struct NodeConfig {
     pub init_state: Option<SerializableState>,
     /// max number of blocks with transactions in memory
     pub transaction_block_keeper: Option<usize>,
+    /// max number of states of older blocks that are persisted to disk
+    pub max_persisted_states: Option<usize>,
+    /// max total size of the states that are persisted to disk, in bytes
+    pub state_cache_max_bytes: Option<u64>,
     /// Disable the default CREATE2 deployer
     pub disable_default_create2_deployer: bool,
     /// Enable Optimism deposit transaction
     pub enable_optimism: bool,
+    /// The artifacts directory of a foundry project, to decode reverts with
+    pub artifacts: Option<PathBuf>,
+    /// Locate reverts in the sources and include the reverts decoded with the artifacts in the
+    /// errors of reverted calls
+    pub verbose_errors: bool,
+    /// The webhooks on-chain events are posted to
+    pub hooks: HooksConfig,
 }
 
 impl NodeConfig {
@@ -407,8 +419,13 @@ impl Default for NodeConfig {
             prune_history: Default::default(),
             init_state: None,
             transaction_block_keeper: None,
+            max_persisted_states: None,
+            state_cache_max_bytes: None,
             disable_default_create2_deployer: false,
             enable_optimism: false,
+            artifacts: None,
+            verbose_errors: false,
+            hooks: Default::default(),
         }
     }
 }
@@ -520,6 +537,20 @@ impl NodeConfig {
         self
     }
 
+    /// Sets max number of states of older blocks that are persisted to disk
+    #[must_use]
+    pub fn with_max_persisted_states(mut self, max_persisted_states: Option<usize>) -> Self {
+        self.max_persisted_states = max_persisted_states;
+        self
+    }
+
+    /// Sets max total size of the states that are persisted to disk, in bytes
+    #[must_use]
+    pub fn with_state_cache_max_bytes(mut self, state_cache_max_bytes: Option<u64>) -> Self {
+        self.state_cache_max_bytes = state_cache_max_bytes;
+        self
+    }
+
     /// Sets the base fee
     #[must_use]
     pub fn with_base_fee(mut self, base_fee: Option<U256>) -> Self {
@@ -731,6 +762,28 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the artifacts directory of a foundry project, to decode reverts with
+    #[must_use]
+    pub fn with_artifacts(mut self, artifacts: Option<PathBuf>) -> Self {
+        self.artifacts = artifacts;
+        self
+    }
+
+    /// Sets whether reverts are located in the sources and the errors of reverted calls include
+    /// the reverts decoded with the artifacts
+    #[must_use]
+    pub fn with_verbose_errors(mut self, verbose_errors: bool) -> Self {
+        self.verbose_errors = verbose_errors;
+        self
+    }
+
+    /// Sets the webhooks on-chain events are posted to
+    #[must_use]
+    pub fn with_hooks(mut self, hooks: HooksConfig) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
     /// Sets whether to enable autoImpersonate
     #[must_use]
     pub fn with_auto_impersonate(mut self, enable_auto_impersonate: bool) -> Self {
@@ -858,6 +911,8 @@ impl NodeConfig {
             fees,
             Arc::new(RwLock::new(fork)),
             self.enable_steps_tracing,
+            self.artifacts.as_deref().and_then(ProjectArtifacts::load).map(Arc::new),
+            self.verbose_errors,
             self.prune_history,
             self.transaction_block_keeper,
             self.block_time,
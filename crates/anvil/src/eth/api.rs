@@ -54,7 +54,7 @@ use anvil_core::{
     },
     types::{
         AnvilMetadata, EvmMineOptions, ForkedNetwork, Forking, Index, NodeEnvironment,
-        NodeForkConfig, NodeInfo, Work,
+        NodeForkConfig, NodeInfo, StateCacheInfo, TransactionConditional, Work,
     },
 };
 use anvil_rpc::{error::RpcError, response::ResponseResult};
@@ -223,6 +223,9 @@ impl EthApi {
             EthRequest::EthSendRawTransaction(tx) => {
                 self.send_raw_transaction(tx).await.to_rpc_result()
             }
+            EthRequest::EthSendRawTransactionConditional(tx, conditional) => {
+                self.send_raw_transaction_conditional(tx, conditional).await.to_rpc_result()
+            }
             EthRequest::EthCall(call, block, overrides) => {
                 self.call(call, block, overrides).await.to_rpc_result()
             }
@@ -282,6 +285,10 @@ impl EthApi {
             EthRequest::AutoImpersonateAccount(enable) => {
                 self.anvil_auto_impersonate_account(enable).await.to_rpc_result()
             }
+            EthRequest::AddAddressWatch(addr) => self.anvil_add_address_watch(addr).to_rpc_result(),
+            EthRequest::RemoveAddressWatch(addr) => {
+                self.anvil_remove_address_watch(addr).to_rpc_result()
+            }
             EthRequest::GetAutoMine(()) => self.anvil_get_auto_mine().to_rpc_result(),
             EthRequest::Mine(blocks, interval) => {
                 self.anvil_mine(blocks, interval).await.to_rpc_result()
@@ -321,6 +328,8 @@ impl EthApi {
             }
             EthRequest::DumpState(_) => self.anvil_dump_state().await.to_rpc_result(),
             EthRequest::LoadState(buf) => self.anvil_load_state(buf).await.to_rpc_result(),
+            EthRequest::FlushStateCache(keep) => self.anvil_flush_state_cache(keep).to_rpc_result(),
+            EthRequest::GetStateCacheInfo(_) => self.anvil_get_state_cache_info().to_rpc_result(),
             EthRequest::NodeInfo(_) => self.anvil_node_info().await.to_rpc_result(),
             EthRequest::AnvilMetadata(_) => self.anvil_metadata().await.to_rpc_result(),
             EthRequest::EvmSnapshot(_) => self.evm_snapshot().await.to_rpc_result(),
@@ -926,6 +935,32 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_sendRawTransaction`
     pub async fn send_raw_transaction(&self, tx: Bytes) -> Result<TxHash> {
         node_info!("eth_sendRawTransaction");
+        self.add_raw_transaction(tx, None).await
+    }
+
+    /// Sends signed transaction which is only included if the state satisfies the `conditional`,
+    /// returning its hash.
+    ///
+    /// The preconditions are checked on submission and again when the transaction is included in
+    /// a block, the transaction is dropped if they no longer hold.
+    ///
+    /// Handler for ETH RPC call: `eth_sendRawTransactionConditional`
+    pub async fn send_raw_transaction_conditional(
+        &self,
+        tx: Bytes,
+        conditional: TransactionConditional,
+    ) -> Result<TxHash> {
+        node_info!("eth_sendRawTransactionConditional");
+        self.backend.validate_conditional(&conditional).await?;
+        self.add_raw_transaction(tx, Some(conditional)).await
+    }
+
+    /// Decodes the signed transaction and adds it to the pool
+    async fn add_raw_transaction(
+        &self,
+        tx: Bytes,
+        conditional: Option<TransactionConditional>,
+    ) -> Result<TxHash> {
         let data = tx.as_ref();
         if data.is_empty() {
             return Err(BlockchainError::EmptyRawTransactionData);
@@ -969,6 +1004,7 @@ impl EthApi {
             provides: vec![to_marker(nonce.as_u64(), pending_transaction.sender().to_alloy())],
             pending_transaction,
             priority,
+            conditional,
         };
 
         let tx = self.pool.add_transaction(pool_transaction)?;
@@ -1524,6 +1560,24 @@ impl EthApi {
         Ok(())
     }
 
+    /// Adds an address whose transactions are posted as `transaction` events to the webhook.
+    ///
+    /// Handler for ETH RPC call: `anvil_addAddressWatch`
+    pub fn anvil_add_address_watch(&self, address: Address) -> Result<()> {
+        node_info!("anvil_addAddressWatch");
+        self.backend.watch_address(address);
+        Ok(())
+    }
+
+    /// Removes an address added with `anvil_addAddressWatch`.
+    ///
+    /// Handler for ETH RPC call: `anvil_removeAddressWatch`
+    pub fn anvil_remove_address_watch(&self, address: Address) -> Result<()> {
+        node_info!("anvil_removeAddressWatch");
+        self.backend.unwatch_address(&address);
+        Ok(())
+    }
+
     /// Returns true if auto mining is enabled, and false.
     ///
     /// Handler for ETH RPC call: `anvil_getAutomine`
@@ -1736,6 +1790,24 @@ impl EthApi {
         self.backend.load_state_bytes(buf).await
     }
 
+    /// Evicts the oldest states persisted to disk until at most `keep` states are left.
+    ///
+    /// Returns the number of bytes freed.
+    ///
+    /// Handler for RPC call: `anvil_flushStateCache`
+    pub fn anvil_flush_state_cache(&self, keep: u64) -> Result<u64> {
+        node_info!("anvil_flushStateCache");
+        Ok(self.backend.flush_state_cache(keep.try_into().unwrap_or(usize::MAX)))
+    }
+
+    /// Returns the states persisted to disk, oldest first, and their sizes.
+    ///
+    /// Handler for RPC call: `anvil_getStateCacheInfo`
+    pub fn anvil_get_state_cache_info(&self) -> Result<StateCacheInfo> {
+        node_info!("anvil_getStateCacheInfo");
+        Ok(self.backend.state_cache_info())
+    }
+
     /// Retrieves the Anvil node configuration params.
     ///
     /// Handler for RPC call: `anvil_nodeInfo`
@@ -2588,8 +2660,13 @@ impl EthApi {
     ) -> Result<TxHash> {
         let from = *pending_transaction.sender();
         let priority = self.transaction_priority(&pending_transaction.transaction);
-        let pool_transaction =
-            PoolTransaction { requires, provides, pending_transaction, priority };
+        let pool_transaction = PoolTransaction {
+            requires,
+            provides,
+            pending_transaction,
+            priority,
+            conditional: None,
+        };
         let tx = self.pool.add_transaction(pool_transaction)?;
         trace!(target: "node", "Added transaction: [{:?}] sender={:?}", tx.hash(), from);
         Ok(*tx.hash())
@@ -0,0 +1,170 @@
+//! Decoding of reverts with the artifacts of a foundry project
+
+use crate::eth::backend::mem::inspector::RevertOrigin;
+use alloy_json_abi::JsonAbi;
+use foundry_common::contracts::diff_score;
+use foundry_compilers::{sourcemap::SourceMap, ConfigurableContractArtifact};
+use foundry_config::Config;
+use foundry_evm::{
+    decode::decode_revert,
+    revm::{interpreter::InstructionResult, primitives::SpecId},
+    utils::PcIcMap,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The artifacts of a foundry project, used to decode custom errors and to locate reverts in the
+/// sources.
+///
+/// Artifacts that can't be read, or that don't match the deployed code, are ignored, in which
+/// case reverts are decoded as without artifacts.
+#[derive(Debug, Default)]
+pub struct ProjectArtifacts {
+    /// The root the paths of the sources are relative to
+    root: PathBuf,
+    /// All custom errors of the artifacts
+    errors: JsonAbi,
+    /// The path of every source file id
+    sources: HashMap<u32, PathBuf>,
+    /// The deployed code and its source map of every contract
+    contracts: Vec<(Vec<u8>, SourceMap)>,
+}
+
+impl ProjectArtifacts {
+    /// Reads all artifacts in the `artifacts` directory, whose sources are relative to the root of
+    /// the project: the closest ancestor with a foundry config, or else the parent directory.
+    ///
+    /// Returns `None` if there are no artifacts, or if the root can't be determined.
+    pub fn load(artifacts: &Path) -> Option<Self> {
+        let artifacts = match fs::canonicalize(artifacts) {
+            Ok(artifacts) => artifacts,
+            Err(err) => {
+                trace!(target: "backend", ?artifacts, %err, "no artifacts");
+                return None
+            }
+        };
+        let Some(root) = artifacts
+            .ancestors()
+            .skip(1)
+            .find(|dir| dir.join(Config::FILE_NAME).exists())
+            .or_else(|| artifacts.parent())
+        else {
+            trace!(target: "backend", ?artifacts, "no project root of the artifacts");
+            return None
+        };
+        let mut this = Self { root: root.to_path_buf(), ..Default::default() };
+        let mut dirs = vec![artifacts.clone()];
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else { continue };
+            for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+                if path.is_dir() {
+                    // build infos aren't artifacts of contracts
+                    if path.file_name().map_or(true, |name| name != "build-info") {
+                        dirs.push(path);
+                    }
+                } else if path.extension().map_or(false, |ext| ext == "json") {
+                    this.add_artifact(&path);
+                }
+            }
+        }
+        trace!(target: "backend", contracts = this.contracts.len(), "loaded artifacts");
+        (!this.errors.errors.is_empty() || !this.contracts.is_empty()).then_some(this)
+    }
+
+    fn add_artifact(&mut self, path: &Path) {
+        let Some(artifact) = fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<ConfigurableContractArtifact>(&s).ok())
+        else {
+            return
+        };
+        if let Some(source) = artifact.source_file() {
+            if let Some(ast) = source.ast {
+                self.sources.insert(source.id, ast.absolute_path.into());
+            }
+        }
+
+        let contract = artifact.into_contract_bytecode();
+        for error in contract.abi.iter().flat_map(|abi| abi.errors()) {
+            let errors = self.errors.errors.entry(error.name.clone()).or_default();
+            if !errors.iter().any(|known| known.selector() == error.selector()) {
+                errors.push(error.clone());
+            }
+        }
+
+        // only linked code can be matched against the deployed code
+        let Some(bytecode) = contract.deployed_bytecode.and_then(|code| code.bytecode) else {
+            return
+        };
+        if let (Some(code), Some(Ok(source_map))) =
+            (bytecode.object.as_bytes(), bytecode.source_map())
+        {
+            if !code.is_empty() {
+                self.contracts.push((code.to_vec(), source_map));
+            }
+        }
+    }
+
+    /// Describes the revert with `data`, decoding custom errors of the artifacts, followed by the
+    /// source location of the revert if its origin is known, e.g. `VaultPaused() at
+    /// src/Vault.sol:142`.
+    pub fn describe_revert(
+        &self,
+        data: &[u8],
+        status: Option<InstructionResult>,
+        origin: Option<&RevertOrigin>,
+    ) -> String {
+        let reason = decode_revert(data, Some(&self.errors), status);
+        match origin.and_then(|origin| self.source_location(origin)) {
+            Some(location) => format!("{reason} at {location}"),
+            None => reason,
+        }
+    }
+
+    /// Returns the location `path:line` of the source of the revert at `origin`.
+    pub fn source_location(&self, origin: &RevertOrigin) -> Option<String> {
+        let (_, source_map) = self.contracts.iter().find(|(code, _)| {
+            // the code at runtime may be padded
+            origin.code.len() >= code.len() &&
+                origin.code.len() - code.len() <= 33 &&
+                diff_score(code, &origin.code) < 0.1
+        })?;
+        let ic = PcIcMap::new(SpecId::LATEST, &origin.code).get(origin.pc)?;
+        let element = source_map.get(ic)?;
+        let path = self.sources.get(&element.index?)?;
+        // the sources may have changed since the artifacts were built
+        let source = fs::read_to_string(self.root.join(path)).ok()?;
+        let line = source.get(..element.offset)?.matches('\n').count() + 1;
+        Some(format!("{}:{line}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_root_of_nested_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifacts = dir.path().join("build/out");
+        fs::create_dir_all(artifacts.join("Vault.sol")).unwrap();
+        fs::write(
+            artifacts.join("Vault.sol/Vault.json"),
+            r#"{"abi":[{"type":"error","name":"Paused","inputs":[]}]}"#,
+        )
+        .unwrap();
+
+        let loaded = ProjectArtifacts::load(&artifacts).unwrap();
+        assert_eq!(loaded.root, fs::canonicalize(dir.path().join("build")).unwrap());
+        assert!(loaded.errors.errors.contains_key("Paused"));
+
+        fs::write(dir.path().join(Config::FILE_NAME), "").unwrap();
+        let loaded = ProjectArtifacts::load(&artifacts).unwrap();
+        assert_eq!(loaded.root, fs::canonicalize(dir.path()).unwrap());
+
+        assert!(ProjectArtifacts::load(&dir.path().join("missing")).is_none());
+    }
+}
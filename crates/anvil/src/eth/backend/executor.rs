@@ -2,15 +2,22 @@ use crate::{
     eth::{
         backend::{db::Db, validate::TransactionValidator},
         error::InvalidTransactionError,
+        macros::node_info,
         pool::transactions::PoolTransaction,
     },
-    mem::inspector::Inspector,
+    mem::inspector::{Inspector, RevertOrigin},
 };
-use anvil_core::eth::{
-    block::{Block, BlockInfo, Header, PartialHeader},
-    receipt::{DepositReceipt, EIP1559Receipt, EIP2930Receipt, EIP658Receipt, Log, TypedReceipt},
-    transaction::{PendingTransaction, TransactionInfo, TypedTransaction},
-    trie,
+use alloy_primitives::{B256, U256 as rU256};
+use anvil_core::{
+    eth::{
+        block::{Block, BlockInfo, Header, PartialHeader},
+        receipt::{
+            DepositReceipt, EIP1559Receipt, EIP2930Receipt, EIP658Receipt, Log, TypedReceipt,
+        },
+        transaction::{PendingTransaction, TransactionInfo, TypedTransaction},
+        trie,
+    },
+    types::{KnownAccount, TransactionConditional},
 };
 use ethers::{
     abi::ethereum_types::BloomInput,
@@ -29,7 +36,7 @@ use foundry_evm::{
     traces::CallTraceNode,
     utils::{eval_to_instruction_result, halt_to_instruction_result},
 };
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 /// Represents an executed transaction (transacted on the DB)
 pub struct ExecutedTransaction {
@@ -40,6 +47,7 @@ pub struct ExecutedTransaction {
     logs: Vec<Log>,
     traces: Vec<CallTraceNode>,
     nonce: u64,
+    revert_origin: Option<RevertOrigin>,
 }
 
 // == impl ExecutedTransaction ==
@@ -93,6 +101,8 @@ pub struct ExecutedTransactions {
     /// All transactions that were invalid at the point of their execution and were not included in
     /// the block
     pub invalid: Vec<Arc<PoolTransaction>>,
+    /// The origins of the reverts of the included transactions by their hash, if recorded
+    pub revert_origins: HashMap<H256, RevertOrigin>,
 }
 
 /// An executor for a series of transactions
@@ -109,6 +119,8 @@ pub struct TransactionExecutor<'a, Db: ?Sized, Validator: TransactionValidator>
     /// Cumulative gas used by all executed transactions
     pub gas_used: U256,
     pub enable_steps_tracing: bool,
+    /// Whether to record where transactions revert
+    pub record_revert_origins: bool,
 }
 
 impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'a, DB, Validator> {
@@ -121,6 +133,7 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
         let mut cumulative_gas_used = U256::zero();
         let mut invalid = Vec::new();
         let mut included = Vec::new();
+        let mut revert_origins = HashMap::new();
         let gas_limit = self.block_env.gas_limit;
         let parent_hash = self.parent_hash;
         let block_number = self.block_env.number;
@@ -140,7 +153,8 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
                     tx
                 }
                 TransactionExecutionOutcome::Exhausted(_) => continue,
-                TransactionExecutionOutcome::Invalid(tx, _) => {
+                TransactionExecutionOutcome::Invalid(tx, err) => {
+                    node_info!("    Dropped transaction: {:?} ({err})", tx.hash());
                     invalid.push(tx);
                     continue
                 }
@@ -153,7 +167,18 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
             };
             let receipt = tx.create_receipt();
             cumulative_gas_used = cumulative_gas_used.saturating_add(receipt.gas_used());
-            let ExecutedTransaction { transaction, logs, out, traces, exit_reason: exit, .. } = tx;
+            let ExecutedTransaction {
+                transaction,
+                logs,
+                out,
+                traces,
+                exit_reason: exit,
+                revert_origin,
+                ..
+            } = tx;
+            if let Some(origin) = revert_origin {
+                revert_origins.insert(transaction.hash().to_ethers(), origin);
+            }
             logs_bloom(logs.clone(), &mut bloom);
 
             let contract_address = if let Some(Output::Create(_, contract_address)) = out {
@@ -209,7 +234,7 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
 
         let block = Block::new(partial_header, transactions.clone(), ommers);
         let block = BlockInfo { block, transactions: transaction_infos, receipts };
-        ExecutedTransactions { block, included, invalid }
+        ExecutedTransactions { block, included, invalid, revert_origins }
     }
 
     fn env_for(&self, tx: &PendingTransaction) -> Env {
@@ -217,6 +242,55 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
     }
 }
 
+/// Checks the preconditions of a conditional transaction against the state of the `db` and the
+/// block the transaction would be included in
+pub fn validate_conditional<DB: Db + ?Sized>(
+    db: &DB,
+    conditional: &TransactionConditional,
+    block: &BlockEnv,
+) -> Result<(), InvalidTransactionError> {
+    let not_met = |msg: String| Err(InvalidTransactionError::ConditionalNotMet(msg));
+    let number = block.number.saturating_to::<u64>();
+    if conditional.block_number_min.is_some_and(|min| number < min.to::<u64>()) {
+        return not_met(format!("block number {number} below the minimum"))
+    }
+    if conditional.block_number_max.is_some_and(|max| number > max.to::<u64>()) {
+        return not_met(format!("block number {number} above the maximum"))
+    }
+    let timestamp = block.timestamp.saturating_to::<u64>();
+    if conditional.timestamp_min.is_some_and(|min| timestamp < min.to::<u64>()) {
+        return not_met(format!("timestamp {timestamp} below the minimum"))
+    }
+    if conditional.timestamp_max.is_some_and(|max| timestamp > max.to::<u64>()) {
+        return not_met(format!("timestamp {timestamp} above the maximum"))
+    }
+
+    for (address, known) in &conditional.known_accounts {
+        match known {
+            KnownAccount::StorageRoot(expected) => {
+                let Some((_, root)) = db.maybe_account_db(*address) else {
+                    return not_met(format!("storage root of {address} unavailable"))
+                };
+                if root != *expected {
+                    return not_met(format!("storage root of {address} changed"))
+                }
+            }
+            KnownAccount::Slots(slots) => {
+                for (slot, expected) in slots {
+                    let value =
+                        db.storage_ref(*address, rU256::from_be_bytes(slot.0)).map_err(|err| {
+                            InvalidTransactionError::ConditionalNotMet(err.to_string())
+                        })?;
+                    if B256::from(value) != *expected {
+                        return not_met(format!("storage slot {slot} of {address} changed"))
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Represents the result of a single transaction execution attempt
 pub enum TransactionExecutionOutcome {
     /// Transaction successfully executed
@@ -248,6 +322,14 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
             return Some(TransactionExecutionOutcome::Exhausted(transaction))
         }
 
+        // the preconditions of a conditional transaction must hold at inclusion
+        if let Some(conditional) = &transaction.conditional {
+            if let Err(err) = validate_conditional(&*self.db, conditional, &env.block) {
+                warn!(target: "backend", "Dropping conditional tx [{:?}] {err}", transaction.hash());
+                return Some(TransactionExecutionOutcome::Invalid(transaction, err))
+            }
+        }
+
         // validate before executing
         if let Err(err) = self.validator.validate_pool_transaction_for(
             &transaction.pending_transaction,
@@ -269,6 +351,9 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
         if self.enable_steps_tracing {
             inspector = inspector.with_steps_tracing();
         }
+        if self.record_revert_origins {
+            inspector = inspector.with_revert_origins();
+        }
 
         trace!(target: "backend", "[{:?}] executing", transaction.hash());
         // transact and commit the transaction
@@ -316,6 +401,12 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
 
         trace!(target: "backend::executor", "transacted [{:?}], result: {:?} gas {}", transaction.hash(), exit_reason, gas_used);
 
+        let revert_origin = match (&exit_reason, &out) {
+            (InstructionResult::Revert, Some(Output::Call(output))) => {
+                inspector.revert_origin(output).cloned()
+            }
+            _ => None,
+        };
         let tx = ExecutedTransaction {
             transaction,
             exit_reason,
@@ -329,6 +420,7 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
                 .clone()
                 .into_nodes(),
             nonce,
+            revert_origin,
         };
 
         Some(TransactionExecutionOutcome::Executed(tx))
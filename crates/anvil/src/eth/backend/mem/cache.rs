@@ -1,11 +1,15 @@
 use crate::config::anvil_tmp_dir;
 use alloy_primitives::B256;
 use foundry_evm::backend::StateSnapshot;
+use parking_lot::Mutex;
 use std::{
+    collections::HashMap,
     io,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 use tempfile::TempDir;
+use tokio::task::JoinHandle;
 
 /// On disk state cache
 ///
@@ -15,6 +19,10 @@ pub struct DiskStateCache {
     pub(crate) temp_path: Option<PathBuf>,
     /// Holds the temp dir object.
     pub(crate) temp_dir: Option<TempDir>,
+    /// The sizes of the cache files in bytes, `None` while a file is being written
+    files: Arc<Mutex<HashMap<B256, Option<u64>>>>,
+    /// The tasks writing cache files which may still be running
+    writes: Vec<JoinHandle<()>>,
 }
 
 impl DiskStateCache {
@@ -53,22 +61,45 @@ impl DiskStateCache {
 
     /// Stores the snapshot for the given hash
     ///
-    /// Note: this writes the state on a new spawned task
+    /// Note: this writes the state on a new spawned task, the size of the file is known once it's
+    /// written
     ///
     /// Caution: this requires a running tokio Runtime.
     pub fn write(&mut self, hash: B256, state: StateSnapshot) {
-        self.with_cache_file(hash, |file| {
+        let files = Arc::clone(&self.files);
+        self.writes.retain(|write| !write.is_finished());
+        let write = self.with_cache_file(hash, |file| {
+            files.lock().insert(hash, None);
             tokio::task::spawn(async move {
-                match foundry_common::fs::write_json_file(&file, &state) {
-                    Ok(_) => {
-                        trace!(target: "backend", ?hash, "wrote state json file");
+                if let Err(err) = foundry_common::fs::write_json_file(&file, &state) {
+                    error!(target: "backend", %err, ?hash, "Failed to write state snapshot");
+                    files.lock().remove(&hash);
+                    return
+                }
+                let size = std::fs::metadata(&file).map(|metadata| metadata.len()).unwrap_or(0);
+                let mut files = files.lock();
+                match files.get_mut(&hash) {
+                    Some(entry) => {
+                        *entry = Some(size);
+                        trace!(target: "backend", ?hash, size, "wrote state json file");
                     }
-                    Err(err) => {
-                        error!(target: "backend", %err, ?hash, "Failed to load state snapshot");
+                    // the state was removed while it was being written
+                    None => {
+                        let _ = std::fs::remove_file(&file);
                     }
-                };
-            });
+                }
+            })
         });
+        self.writes.extend(write);
+    }
+
+    /// Waits until all the cache files that are being written are written
+    pub async fn wait_for_writes(&mut self) {
+        for write in std::mem::take(&mut self.writes) {
+            if let Err(err) = write.await {
+                error!(target: "backend", %err, "Failed to join state snapshot write");
+            }
+        }
     }
 
     /// Loads the snapshot file for the given hash
@@ -91,18 +122,40 @@ impl DiskStateCache {
     }
 
     /// Removes the cache file for the given hash, if it exists
-    pub fn remove(&mut self, hash: B256) {
+    ///
+    /// Returns the size of the removed file in bytes, a file that's still being written is removed
+    /// once it's written and not counted
+    pub fn remove(&mut self, hash: B256) -> u64 {
+        let Some(size) = self.files.lock().remove(&hash) else { return 0 };
+        let Some(size) = size else { return 0 };
         self.with_cache_file(hash, |file| {
             foundry_common::fs::remove_file(file).map_err(|err| {
                 error!(target: "backend", %err, %hash, "Failed to remove state snapshot");
             })
         });
+        size
+    }
+
+    /// Returns the size of the cache file for the given hash in bytes, `None` if it's still being
+    /// written
+    pub fn size(&self, hash: &B256) -> Option<u64> {
+        self.files.lock().get(hash).copied().flatten()
+    }
+
+    /// Returns the total size of the written cache files in bytes
+    pub fn total_size(&self) -> u64 {
+        self.files.lock().values().flatten().sum()
     }
 }
 
 impl Default for DiskStateCache {
     fn default() -> Self {
-        DiskStateCache { temp_path: anvil_tmp_dir(), temp_dir: None }
+        DiskStateCache {
+            temp_path: anvil_tmp_dir(),
+            temp_dir: None,
+            files: Default::default(),
+            writes: Default::default(),
+        }
     }
 }
 
@@ -8,7 +8,7 @@ use foundry_evm::{
     inspectors::{LogCollector, TracingInspector},
     revm,
     revm::{
-        interpreter::{CallInputs, CreateInputs, Gas, InstructionResult, Interpreter},
+        interpreter::{opcode, CallInputs, CreateInputs, Gas, InstructionResult, Interpreter},
         primitives::{Address, Bytes, B256},
         EVMData,
     },
@@ -21,6 +21,19 @@ pub struct Inspector {
     pub tracer: Option<TracingInspector>,
     /// collects all `console.sol` logs
     pub log_collector: LogCollector,
+    /// records all `REVERT`s, if enabled
+    pub reverts: Option<Vec<RevertOrigin>>,
+}
+
+/// Where a `REVERT` was executed
+#[derive(Clone, Debug)]
+pub struct RevertOrigin {
+    /// The code that reverted
+    pub code: Bytes,
+    /// The program counter of the `REVERT`
+    pub pc: usize,
+    /// The revert data
+    pub data: Bytes,
 }
 
 // === impl Inspector ===
@@ -39,6 +52,18 @@ impl Inspector {
         self
     }
 
+    /// Enables recording the origins of reverts, see [Self::revert_origin].
+    pub fn with_revert_origins(mut self) -> Self {
+        self.reverts = Some(Vec::new());
+        self
+    }
+
+    /// Returns where the revert with the `output` of the transaction originated: the first
+    /// `REVERT` with the same data, before it was bubbled up by the callers.
+    pub fn revert_origin(&self, output: &[u8]) -> Option<&RevertOrigin> {
+        self.reverts.as_ref()?.iter().find(|revert| revert.data[..] == *output)
+    }
+
     /// Enables steps recording for `Tracer`.
     pub fn with_steps_tracing(mut self) -> Self {
         self.tracer = Some(TracingInspector::new(TracingInspectorConfig::all()));
@@ -59,6 +84,25 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
         call_inspectors!([&mut self.tracer], |inspector| {
             inspector.step(interp, data);
         });
+
+        if let Some(reverts) = &mut self.reverts {
+            if interp.current_opcode() == opcode::REVERT {
+                let (Ok(offset), Ok(len)) = (interp.stack().peek(0), interp.stack().peek(1)) else {
+                    return
+                };
+                let memory = interp.shared_memory.context_memory();
+                let (offset, len) = (offset.saturating_to::<usize>(), len.saturating_to::<usize>());
+                let data = offset
+                    .checked_add(len)
+                    .and_then(|end| memory.get(offset..end))
+                    .unwrap_or_default();
+                reverts.push(RevertOrigin {
+                    code: interp.contract.bytecode.bytecode().clone(),
+                    pc: interp.program_counter(),
+                    data: Bytes::copy_from_slice(data),
+                });
+            }
+        }
     }
 
     #[inline]
@@ -3,9 +3,10 @@ use crate::{
     config::PruneStateHistoryConfig,
     eth::{
         backend::{
+            artifacts::ProjectArtifacts,
             cheats::CheatsManager,
             db::{AsHashDB, Db, MaybeHashDatabase, SerializableState},
-            executor::{ExecutedTransactions, TransactionExecutor},
+            executor::{validate_conditional, ExecutedTransactions, TransactionExecutor},
             fork::ClientFork,
             genesis::GenesisConfig,
             mem::storage::MinedTransactionReceipt,
@@ -50,7 +51,7 @@ use anvil_core::{
         trie::RefTrieDB,
         utils::alloy_to_revm_access_list,
     },
-    types::{Forking, Index},
+    types::{Forking, Index, StateCacheEntry, StateCacheInfo, TransactionConditional},
 };
 use anvil_rpc::error::RpcError;
 use ethers::{
@@ -81,7 +82,7 @@ use hash_db::HashDB;
 use itertools::Itertools;
 use parking_lot::{Mutex, RwLock};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     io::{Read, Write},
     ops::Deref,
     sync::Arc,
@@ -164,10 +165,17 @@ pub struct Backend {
     /// keeps track of active snapshots at a specific block
     active_snapshots: Arc<Mutex<HashMap<U256, (u64, B256)>>>,
     enable_steps_tracing: bool,
+    /// The artifacts of the project, to decode reverts with
+    artifacts: Option<Arc<ProjectArtifacts>>,
+    /// Whether reverts are located in the sources and the errors of reverted calls include the
+    /// reverts decoded with the artifacts
+    verbose_errors: bool,
     /// How to keep history state
     prune_state_history_config: PruneStateHistoryConfig,
     /// max number of blocks with transactions in memory
     transaction_block_keeper: Option<usize>,
+    /// the addresses whose transactions are posted to the webhook
+    watched_addresses: Arc<RwLock<HashSet<Address>>>,
     node_config: Arc<AsyncRwLock<NodeConfig>>,
 }
 
@@ -181,6 +189,8 @@ impl Backend {
         fees: FeeManager,
         fork: Arc<RwLock<Option<ClientFork>>>,
         enable_steps_tracing: bool,
+        artifacts: Option<Arc<ProjectArtifacts>>,
+        verbose_errors: bool,
         prune_state_history_config: PruneStateHistoryConfig,
         transaction_block_keeper: Option<usize>,
         automine_block_time: Option<Duration>,
@@ -204,6 +214,12 @@ impl Backend {
             genesis.timestamp
         };
 
+        let (watched_addresses, max_persisted_states, state_cache_max_bytes) = {
+            let config = node_config.read().await;
+            let watched = config.hooks.watch.iter().copied().collect::<HashSet<_>>();
+            (watched, config.max_persisted_states, config.state_cache_max_bytes)
+        };
+
         let states = if prune_state_history_config.is_config_enabled() {
             // if prune state history is enabled, configure the state cache only for memory
             prune_state_history_config
@@ -212,7 +228,12 @@ impl Backend {
                 .unwrap_or_default()
                 .memory_only()
         } else {
-            Default::default()
+            let states =
+                InMemoryBlockStates::default().with_max_on_disk_bytes(state_cache_max_bytes);
+            match max_persisted_states {
+                Some(limit) => states.with_max_on_disk_limit(limit),
+                None => states,
+            }
         };
 
         let backend = Self {
@@ -228,8 +249,11 @@ impl Backend {
             genesis,
             active_snapshots: Arc::new(Mutex::new(Default::default())),
             enable_steps_tracing,
+            artifacts,
+            verbose_errors,
             prune_state_history_config,
             transaction_block_keeper,
+            watched_addresses: Arc::new(RwLock::new(watched_addresses)),
             node_config,
         };
 
@@ -332,6 +356,44 @@ impl Backend {
         self.cheats.set_auto_impersonate_account(enabled);
     }
 
+    /// Adds the address to the addresses whose transactions are posted to the webhook
+    ///
+    /// Returns `false` if the address is already watched
+    pub fn watch_address(&self, address: Address) -> bool {
+        self.watched_addresses.write().insert(address)
+    }
+
+    /// Removes the address from the addresses whose transactions are posted to the webhook
+    ///
+    /// Returns `false` if the address isn't watched
+    pub fn unwatch_address(&self, address: &Address) -> bool {
+        self.watched_addresses.write().remove(address)
+    }
+
+    /// Returns the addresses whose transactions are posted to the webhook
+    pub fn watched_addresses(&self) -> HashSet<Address> {
+        self.watched_addresses.read().clone()
+    }
+
+    /// Evicts the oldest states persisted to disk until at most `keep` states are left
+    ///
+    /// Returns the number of bytes freed
+    pub fn flush_state_cache(&self, keep: usize) -> u64 {
+        self.states.write().flush_disk_cache(keep)
+    }
+
+    /// Returns the states persisted to disk, oldest first, and the limits of the cache
+    pub fn state_cache_info(&self) -> StateCacheInfo {
+        let states = self.states.read();
+        let (max_entries, max_bytes) = states.disk_cache_limits();
+        let entries = states
+            .disk_cache_entries()
+            .into_iter()
+            .map(|(block_hash, size)| StateCacheEntry { block_hash, size })
+            .collect();
+        StateCacheInfo { entries, total_bytes: states.disk_cache_size(), max_entries, max_bytes }
+    }
+
     /// Returns the configured fork, if any
     pub fn get_fork(&self) -> Option<ClientFork> {
         self.fork.read().clone()
@@ -524,6 +586,17 @@ impl Backend {
         Ok(U256::from(self.get_account(address).await?.nonce))
     }
 
+    /// Checks the preconditions of a conditional transaction against the current state and the
+    /// next block
+    pub async fn validate_conditional(
+        &self,
+        conditional: &TransactionConditional,
+    ) -> Result<(), BlockchainError> {
+        let env = self.next_env();
+        let db = self.db.read().await;
+        Ok(validate_conditional(&**db, conditional, &env.block)?)
+    }
+
     /// Sets the coinbase address
     pub fn set_coinbase(&self, address: Address) {
         self.env.write().block.coinbase = address;
@@ -852,6 +925,7 @@ impl Backend {
             parent_hash: storage.best_hash.to_ethers(),
             gas_used: U256::ZERO.to_ethers(),
             enable_steps_tracing: self.enable_steps_tracing,
+            record_revert_origins: false,
         };
 
         // create a new pending block
@@ -911,6 +985,7 @@ impl Backend {
                     parent_hash: best_hash.to_ethers(),
                     gas_used: U256::ZERO.to_ethers(),
                     enable_steps_tracing: self.enable_steps_tracing,
+                    record_revert_origins: self.verbose_errors && self.artifacts.is_some(),
                 };
                 let executed_tx = executor.execute();
 
@@ -925,7 +1000,7 @@ impl Backend {
             };
 
             // create the new block with the current timestamp
-            let ExecutedTransactions { block, included, invalid } = executed_tx;
+            let ExecutedTransactions { block, included, invalid, revert_origins } = executed_tx;
             let BlockInfo { block, transactions, receipts } = block;
 
             let header = block.header.clone();
@@ -963,11 +1038,15 @@ impl Backend {
                 }
                 node_info!("    Gas used: {}", receipt.gas_used());
                 if !info.exit.is_ok() {
-                    let r = decode_revert(
-                        info.out.as_deref().unwrap_or_default(),
-                        None,
-                        Some(info.exit),
-                    );
+                    let out = info.out.as_deref().unwrap_or_default();
+                    let r = match &self.artifacts {
+                        Some(artifacts) => artifacts.describe_revert(
+                            out,
+                            Some(info.exit),
+                            revert_origins.get(&info.transaction_hash),
+                        ),
+                        None => decode_revert(out, None, Some(info.exit)),
+                    };
                     node_info!("    Error: reverted with: {r}");
                 }
                 node_info!("");
@@ -1036,14 +1115,23 @@ impl Backend {
     ) -> Result<(InstructionResult, Option<Output>, u64, State), BlockchainError> {
         self.with_database_at(block_request, |state, block| {
             let block_number = (block.number.to_ethers()).as_u64();
+            let mut inspector = Inspector::default();
+            if self.verbose_errors && self.artifacts.is_some() {
+                inspector = inspector.with_revert_origins();
+            }
             let (exit, out, gas, state) = match overrides {
-                None => self.call_with_state(state, request, fee_details, block),
+                None => {
+                    self.call_with_inspector(state, request, fee_details, block, &mut inspector)
+                }
                 Some(overrides) => {
                     let state = state::apply_state_override(overrides.into_iter().collect(), state)?;
-                    self.call_with_state(state, request, fee_details, block)
+                    self.call_with_inspector(state, request, fee_details, block, &mut inspector)
                 },
             }?;
             trace!(target: "backend", "call return {:?} out: {:?} gas {} on block {}", exit, out, gas, block_number);
+            if let Some(err) = self.decoded_revert_error(exit, &out, &inspector) {
+                return Err(err)
+            }
             Ok((exit, out, gas, state))
         }).await?
     }
@@ -1108,11 +1196,24 @@ impl Backend {
     where
         D: DatabaseRef<Error = DatabaseError>,
     {
-        let mut inspector = Inspector::default();
+        self.call_with_inspector(state, request, fee_details, block_env, &mut Inspector::default())
+    }
+
+    fn call_with_inspector<D>(
+        &self,
+        state: D,
+        request: CallRequest,
+        fee_details: FeeDetails,
+        block_env: BlockEnv,
+        inspector: &mut Inspector,
+    ) -> Result<(InstructionResult, Option<Output>, u64, State), BlockchainError>
+    where
+        D: DatabaseRef<Error = DatabaseError>,
+    {
         let mut evm = revm::EVM::new();
         evm.env = self.build_call_env(request, fee_details, block_env);
         evm.database(state);
-        let result_and_state = match evm.inspect_ref(&mut inspector) {
+        let result_and_state = match evm.inspect_ref(&mut *inspector) {
             Ok(result_and_state) => result_and_state,
             Err(e) => match e {
                 EVMError::Transaction(invalid_tx) => {
@@ -1145,6 +1246,25 @@ impl Backend {
         Ok((exit_reason, out, gas_used, state))
     }
 
+    /// Returns the error of a reverted call with the revert decoded with the artifacts of the
+    /// project, if `--verbose-errors` is enabled.
+    fn decoded_revert_error(
+        &self,
+        exit: InstructionResult,
+        out: &Option<Output>,
+        inspector: &Inspector,
+    ) -> Option<BlockchainError> {
+        let artifacts = self.artifacts.as_ref().filter(|_| self.verbose_errors)?;
+        let Some(Output::Call(data)) = out.as_ref().filter(|_| exit == InstructionResult::Revert)
+        else {
+            return None
+        };
+        let reason = artifacts.describe_revert(data, Some(exit), inspector.revert_origin(data));
+        Some(
+            InvalidTransactionError::RevertWithReason { reason, data: data.to_vec().into() }.into(),
+        )
+    }
+
     pub async fn call_with_tracing(
         &self,
         request: CallRequest,
@@ -55,6 +55,8 @@ pub struct InMemoryBlockStates {
     ///
     /// Limiting the states will prevent disk blow up, especially in interval mining mode
     max_on_disk_limit: usize,
+    /// maximum total size of the states we keep on disk, in bytes
+    max_on_disk_bytes: Option<u64>,
     /// the oldest states written to disk
     oldest_on_disk: VecDeque<B256>,
     /// all states present, used to enforce `in_memory_limit`
@@ -74,6 +76,7 @@ impl InMemoryBlockStates {
             in_memory_limit: limit,
             min_in_memory_limit: limit.min(MIN_HISTORY_LIMIT),
             max_on_disk_limit: MAX_ON_DISK_HISTORY_LIMIT,
+            max_on_disk_bytes: None,
             oldest_on_disk: Default::default(),
             present: Default::default(),
             disk_cache: Default::default(),
@@ -86,6 +89,18 @@ impl InMemoryBlockStates {
         self
     }
 
+    /// Configures the maximum number of states we keep on disk, `0` disables disk caching
+    pub fn with_max_on_disk_limit(mut self, limit: usize) -> Self {
+        self.max_on_disk_limit = limit;
+        self
+    }
+
+    /// Configures the maximum total size of the states we keep on disk, in bytes
+    pub fn with_max_on_disk_bytes(mut self, max_bytes: Option<u64>) -> Self {
+        self.max_on_disk_bytes = max_bytes;
+        self
+    }
+
     /// This modifies the `limit` what to keep stored in memory.
     ///
     /// This will ensure the new limit adjusts based on the block time.
@@ -151,14 +166,62 @@ impl InMemoryBlockStates {
 
         // enforce on disk limit and purge the oldest state cached on disk
         while !self.is_memory_only() && self.oldest_on_disk.len() >= self.max_on_disk_limit {
-            // evict the oldest block
-            if let Some(hash) = self.oldest_on_disk.pop_front() {
-                self.on_disk_states.remove(&hash);
-                self.disk_cache.remove(hash);
+            if self.evict_oldest_on_disk().is_none() {
+                break
+            }
+        }
+
+        // enforce the on disk size limit, the size of a state is known once it's written
+        if let Some(max_bytes) = self.max_on_disk_bytes {
+            while self.disk_cache.total_size() > max_bytes {
+                if self.evict_oldest_on_disk().is_none() {
+                    break
+                }
             }
         }
     }
 
+    /// Removes the oldest state cached on disk
+    ///
+    /// Returns the size of its removed file in bytes, or `None` if no state is cached on disk
+    fn evict_oldest_on_disk(&mut self) -> Option<u64> {
+        let hash = self.oldest_on_disk.pop_front()?;
+        self.on_disk_states.remove(&hash);
+        Some(self.disk_cache.remove(hash))
+    }
+
+    /// Evicts the oldest states cached on disk until at most `keep` are left
+    ///
+    /// Returns the number of bytes freed
+    pub fn flush_disk_cache(&mut self, keep: usize) -> u64 {
+        let mut freed = 0;
+        while self.oldest_on_disk.len() > keep {
+            freed += self.evict_oldest_on_disk().unwrap_or_default();
+        }
+        freed
+    }
+
+    /// Waits until the states that are being written to disk are written
+    pub async fn wait_for_disk_writes(&mut self) {
+        self.disk_cache.wait_for_writes().await
+    }
+
+    /// Returns the hashes of the states cached on disk, oldest first, with the sizes of their files
+    /// in bytes, `None` while a file is being written
+    pub fn disk_cache_entries(&self) -> Vec<(B256, Option<u64>)> {
+        self.oldest_on_disk.iter().map(|hash| (*hash, self.disk_cache.size(hash))).collect()
+    }
+
+    /// Returns the total size of the states cached on disk, in bytes
+    pub fn disk_cache_size(&self) -> u64 {
+        self.disk_cache.total_size()
+    }
+
+    /// Returns the maximum number of states we keep on disk, and their maximum total size in bytes
+    pub fn disk_cache_limits(&self) -> (usize, Option<u64>) {
+        (self.max_on_disk_limit, self.max_on_disk_bytes)
+    }
+
     /// Returns the state for the given `hash` if present
     pub fn get(&mut self, hash: &B256) -> Option<&StateDb> {
         self.states.get(hash).or_else(|| {
@@ -183,7 +246,7 @@ impl InMemoryBlockStates {
         self.on_disk_states.clear();
         self.present.clear();
         for on_disk in std::mem::take(&mut self.oldest_on_disk) {
-            self.disk_cache.remove(on_disk)
+            self.disk_cache.remove(on_disk);
         }
     }
 }
@@ -194,6 +257,7 @@ impl fmt::Debug for InMemoryBlockStates {
             .field("in_memory_limit", &self.in_memory_limit)
             .field("min_in_memory_limit", &self.min_in_memory_limit)
             .field("max_on_disk_limit", &self.max_on_disk_limit)
+            .field("max_on_disk_bytes", &self.max_on_disk_bytes)
             .field("oldest_on_disk", &self.oldest_on_disk)
             .field("present", &self.present)
             .finish_non_exhaustive()
@@ -468,8 +532,7 @@ mod tests {
         storage.insert(one, StateDb::new(state));
         storage.insert(two, StateDb::new(MemDb::default()));
 
-        // wait for files to be flushed
-        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        storage.wait_for_disk_writes().await;
 
         assert_eq!(storage.on_disk_states.len(), 1);
         assert!(storage.on_disk_states.get(&one).is_some());
@@ -496,8 +559,7 @@ mod tests {
             storage.insert(hash, StateDb::new(state));
         }
 
-        // wait for files to be flushed
-        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        storage.wait_for_disk_writes().await;
 
         assert_eq!(storage.on_disk_states.len(), num_states - storage.min_in_memory_limit);
         assert_eq!(storage.present.len(), storage.min_in_memory_limit);
@@ -511,4 +573,65 @@ mod tests {
             assert_eq!(acc.balance, rU256::from(balance));
         }
     }
+
+    fn account_state(idx: u64) -> (B256, StateDb) {
+        let mut state = MemDb::default();
+        let hash = B256::from(U256::from(idx));
+        let info = AccountInfo::from_balance(rU256::from(idx));
+        state.insert_account(Address::from_word(hash), info);
+        (hash, StateDb::new(state))
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_account_disk_cache_size() {
+        let mut storage = InMemoryBlockStates::new(1);
+        for idx in 0..4 {
+            let (hash, state) = account_state(idx);
+            storage.insert(hash, state);
+        }
+
+        storage.wait_for_disk_writes().await;
+
+        let entries = storage.disk_cache_entries();
+        assert_eq!(entries.len(), 3);
+        let total = entries.iter().map(|(_, size)| size.unwrap()).sum::<u64>();
+        assert_eq!(total, storage.disk_cache_size());
+        let dir = storage.disk_cache.temp_dir.as_ref().unwrap().path();
+        let on_disk = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().metadata().unwrap().len())
+            .sum::<u64>();
+        assert_eq!(total, on_disk);
+
+        let freed = storage.flush_disk_cache(1);
+        assert_eq!(freed, total - entries[2].1.unwrap());
+        assert_eq!(storage.disk_cache_entries(), vec![entries[2]]);
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 1);
+        assert!(storage.get(&entries[2].0).is_some());
+        assert!(storage.get(&entries[0].0).is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_limit_disk_cache_size() {
+        let mut storage = InMemoryBlockStates::new(1);
+        for idx in 0..2 {
+            let (hash, state) = account_state(idx);
+            storage.insert(hash, state);
+        }
+        storage.wait_for_disk_writes().await;
+        let state_size = storage.disk_cache_size();
+        assert!(state_size > 0);
+
+        let mut storage = InMemoryBlockStates::new(1).with_max_on_disk_bytes(Some(state_size * 2));
+        for idx in 0..10 {
+            let (hash, state) = account_state(idx);
+            storage.insert(hash, state);
+            // the cap is enforced on insert, the state that's being written isn't accounted yet
+            assert!(storage.disk_cache_entries().len() <= 3);
+            storage.wait_for_disk_writes().await;
+        }
+        // all states have the same size
+        assert_eq!(storage.disk_cache_entries().len(), 3);
+        assert_eq!(storage.disk_cache_size(), state_size * 3);
+    }
 }
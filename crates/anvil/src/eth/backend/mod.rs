@@ -5,6 +5,7 @@ pub mod db;
 /// In-memory Backend
 pub mod mem;
 
+pub mod artifacts;
 pub mod cheats;
 pub mod time;
 
@@ -185,6 +185,10 @@ pub enum InvalidTransactionError {
     /// Thrown if executing a transaction failed during estimate/call
     #[error("execution reverted: {0:?}")]
     Revert(Option<Bytes>),
+    /// Thrown if executing a call failed with `--verbose-errors`, with the revert decoded using
+    /// the artifacts of the project
+    #[error("execution reverted: {reason}")]
+    RevertWithReason { reason: String, data: Bytes },
     /// Thrown if the sender of a transaction is a contract.
     #[error("sender not an eoa")]
     SenderNoEOA,
@@ -208,6 +212,10 @@ pub enum InvalidTransactionError {
     /// Thrown when `max_fee_per_blob_gas` is not supported for blocks before the Cancun hardfork.
     #[error("`max_fee_per_blob_gas` is not supported for blocks before the Cancun hardfork.")]
     MaxFeePerBlobGasNotSupported,
+    /// Thrown when the preconditions of a conditional transaction are not met, see
+    /// `eth_sendRawTransactionConditional`.
+    #[error("conditional not met: {0}")]
+    ConditionalNotMet(String),
 }
 
 impl From<revm::primitives::InvalidTransaction> for InvalidTransactionError {
@@ -329,6 +337,11 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                             data: serde_json::to_value(data).ok(),
                         }
                     }
+                    InvalidTransactionError::RevertWithReason { reason, data } => RpcError {
+                        code: ErrorCode::ExecutionError,
+                        message: format!("execution reverted: {reason}").into(),
+                        data: serde_json::to_value(data).ok(),
+                    },
                     InvalidTransactionError::GasTooLow => {
                         // <https://eips.ethereum.org/EIPS/eip-1898>
                         RpcError {
@@ -1,6 +1,9 @@
 use crate::eth::{error::PoolError, util::hex_fmt_many};
 use alloy_primitives::{Address, TxHash, U256};
-use anvil_core::eth::transaction::{PendingTransaction, TypedTransaction};
+use anvil_core::{
+    eth::transaction::{PendingTransaction, TypedTransaction},
+    types::TransactionConditional,
+};
 use foundry_common::types::ToAlloy;
 use parking_lot::RwLock;
 use std::{
@@ -82,6 +85,9 @@ pub struct PoolTransaction {
     pub provides: Vec<TxMarker>,
     /// priority of the transaction
     pub priority: TransactionPriority,
+    /// preconditions the state must satisfy for the transaction to be included, see
+    /// `eth_sendRawTransactionConditional`
+    pub conditional: Option<TransactionConditional>,
 }
 
 // == impl PoolTransaction ==
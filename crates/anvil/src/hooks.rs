@@ -0,0 +1,251 @@
+//! Webhooks the on-chain events of the node are posted to
+
+use crate::eth::{
+    backend::{mem::Backend, notifications::NewBlockNotifications},
+    macros::node_info,
+};
+use alloy_primitives::{Address, B256};
+use alloy_rpc_types::BlockId;
+use anvil_core::eth::block::Header;
+use foundry_config::{HookEvent, HooksConfig};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::mpsc;
+
+/// The version of the schema of the payloads, bumped on breaking changes.
+pub const HOOK_PAYLOAD_VERSION: u64 = 1;
+
+/// The maximum number of payloads that wait to be delivered, newer payloads are dropped if the
+/// webhook can't keep up.
+const MAX_PENDING_PAYLOADS: usize = 1024;
+
+/// The backoff before the first retry of a failed delivery, doubled with every retry.
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// The JSON payload of an event that's posted to the webhook.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookPayload {
+    /// The version of the schema of the payload, [HOOK_PAYLOAD_VERSION].
+    pub version: u64,
+    pub chain_id: u64,
+    #[serde(flatten)]
+    pub event: HookPayloadEvent,
+}
+
+impl HookPayload {
+    /// Returns the kind of the event.
+    pub fn kind(&self) -> HookEvent {
+        match self.event {
+            HookPayloadEvent::Block { .. } => HookEvent::Block,
+            HookPayloadEvent::Transaction { .. } => HookEvent::Transaction,
+            HookPayloadEvent::Deployment { .. } => HookEvent::Deployment,
+        }
+    }
+}
+
+/// The events, as `{"event": "block", "data": {..}}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "lowercase")]
+pub enum HookPayloadEvent {
+    /// A new block was mined.
+    #[serde(rename_all = "camelCase")]
+    Block { number: u64, hash: B256, timestamp: u64, transaction_count: usize },
+    /// A transaction from or to the `watched` addresses was mined.
+    #[serde(rename_all = "camelCase")]
+    Transaction {
+        block_number: u64,
+        block_hash: B256,
+        hash: B256,
+        from: Address,
+        to: Option<Address>,
+        success: bool,
+        watched: Vec<Address>,
+    },
+    /// A contract was deployed.
+    #[serde(rename_all = "camelCase")]
+    Deployment {
+        block_number: u64,
+        block_hash: B256,
+        hash: B256,
+        from: Address,
+        contract_address: Address,
+    },
+}
+
+/// Posts the events of the mined blocks to the webhook.
+///
+/// The payloads are queued and delivered by a separate task, so neither a slow nor an unreachable
+/// webhook ever blocks mining.
+pub struct HookService {
+    backend: Arc<Backend>,
+    config: HooksConfig,
+    new_blocks: NewBlockNotifications,
+}
+
+impl HookService {
+    pub fn new(backend: Arc<Backend>, config: HooksConfig) -> Self {
+        let new_blocks = backend.new_block_notifications();
+        Self { backend, config, new_blocks }
+    }
+
+    /// Queues the payloads of every new block until the node shuts down.
+    pub async fn run(mut self) {
+        let Some(url) = self.config.url.clone() else { return };
+        let client = match reqwest::Client::builder()
+            .timeout(Duration::from_millis(self.config.timeout))
+            .build()
+        {
+            Ok(client) => client,
+            Err(err) => {
+                error!(target: "hooks", ?err, "failed to create the webhook client");
+                return
+            }
+        };
+
+        let (tx, rx) = mpsc::channel(MAX_PENDING_PAYLOADS);
+        tokio::task::spawn(deliver_all(client, url, self.config.retries, rx));
+
+        while let Some(notification) = self.new_blocks.next().await {
+            for payload in self.payloads(notification.hash, &notification.header) {
+                if tx.try_send(payload).is_err() {
+                    node_info!("Dropped the webhook payload of block {}", notification.hash);
+                }
+            }
+        }
+    }
+
+    /// Returns the payloads of the enabled events of the block.
+    fn payloads(&self, hash: B256, header: &Header) -> Vec<HookPayload> {
+        let receipts =
+            self.backend.mined_block_receipts(BlockId::Hash(hash.into())).unwrap_or_default();
+        let number = header.number.as_u64();
+        let mut events = Vec::new();
+
+        if self.config.is_enabled(HookEvent::Block) {
+            events.push(HookPayloadEvent::Block {
+                number,
+                hash,
+                timestamp: header.timestamp,
+                transaction_count: receipts.len(),
+            });
+        }
+
+        let watched = if self.config.is_enabled(HookEvent::Transaction) {
+            self.backend.watched_addresses()
+        } else {
+            Default::default()
+        };
+        for receipt in receipts {
+            let Some(tx_hash) = receipt.transaction_hash else { continue };
+            let matched = [Some(receipt.from), receipt.to]
+                .into_iter()
+                .flatten()
+                .filter(|address| watched.contains(address))
+                .collect::<Vec<_>>();
+            if !matched.is_empty() {
+                events.push(HookPayloadEvent::Transaction {
+                    block_number: number,
+                    block_hash: hash,
+                    hash: tx_hash,
+                    from: receipt.from,
+                    to: receipt.to,
+                    success: receipt.status_code.map_or(false, |status| status.to::<u64>() == 1),
+                    watched: matched,
+                });
+            }
+            if let Some(contract_address) = receipt.contract_address {
+                if self.config.is_enabled(HookEvent::Deployment) {
+                    events.push(HookPayloadEvent::Deployment {
+                        block_number: number,
+                        block_hash: hash,
+                        hash: tx_hash,
+                        from: receipt.from,
+                        contract_address,
+                    });
+                }
+            }
+        }
+
+        let chain_id = self.backend.chain_id().to::<u64>();
+        events
+            .into_iter()
+            .map(|event| HookPayload { version: HOOK_PAYLOAD_VERSION, chain_id, event })
+            .collect()
+    }
+}
+
+/// Delivers the queued payloads in order.
+async fn deliver_all(
+    client: reqwest::Client,
+    url: String,
+    retries: u32,
+    mut payloads: mpsc::Receiver<HookPayload>,
+) {
+    while let Some(payload) = payloads.recv().await {
+        deliver(&client, &url, &payload, retries).await;
+    }
+}
+
+/// Posts the payload, retrying failed deliveries with an exponential backoff.
+///
+/// The payload is dropped once all retries failed.
+async fn deliver(client: &reqwest::Client, url: &str, payload: &HookPayload, retries: u32) {
+    let mut backoff = RETRY_BACKOFF;
+    for attempt in 0..=retries {
+        if attempt > 0 {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+        match client.post(url).json(payload).send().await.and_then(|res| res.error_for_status()) {
+            Ok(_) => {
+                trace!(target: "hooks", event=%payload.kind(), "posted webhook");
+                return
+            }
+            Err(err) => {
+                if attempt == retries {
+                    node_info!("Failed to post the {} event to the webhook: {err}", payload.kind());
+                } else {
+                    let event = payload.kind();
+                    debug!(target: "hooks", %event, attempt, ?err, "failed to post webhook");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_versioned_payloads() {
+        let payload = HookPayload {
+            version: HOOK_PAYLOAD_VERSION,
+            chain_id: 31337,
+            event: HookPayloadEvent::Block {
+                number: 1,
+                hash: B256::ZERO,
+                timestamp: 100,
+                transaction_count: 2,
+            },
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "version": 1,
+                "chainId": 31337,
+                "event": "block",
+                "data": {
+                    "number": 1,
+                    "hash": B256::ZERO,
+                    "timestamp": 100,
+                    "transactionCount": 2,
+                },
+            })
+        );
+        assert_eq!(serde_json::from_value::<HookPayload>(json).unwrap(), payload);
+    }
+}
@@ -11,6 +11,7 @@ use crate::{
         EthApi,
     },
     filter::Filters,
+    hooks::HookService,
     logging::{LoggingManager, NodeLogLayer},
     service::NodeService,
     shutdown::Signal,
@@ -64,6 +65,7 @@ pub mod eth;
 pub mod filter;
 /// support for handling `genesis.json` files
 pub mod genesis;
+pub mod hooks;
 /// commandline output
 pub mod logging;
 /// types for subscriptions
@@ -157,6 +159,10 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
 
     let filters = Filters::default();
 
+    if config.hooks.url.is_some() {
+        tokio::task::spawn(HookService::new(Arc::clone(&backend), config.hooks.clone()).run());
+    }
+
     // create the cloneable api wrapper
     let api = EthApi::new(
         Arc::clone(&pool),
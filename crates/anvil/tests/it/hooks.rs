@@ -0,0 +1,160 @@
+//! tests for the webhooks of on-chain events
+
+use alloy_primitives::Address;
+use anvil::{
+    hooks::{HookPayload, HookPayloadEvent, HOOK_PAYLOAD_VERSION},
+    spawn, NodeConfig,
+};
+use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use ethers::{
+    prelude::{Middleware, Signer},
+    types::{Bytes, TransactionRequest},
+};
+use foundry_common::types::{ToAlloy, ToEthers};
+use foundry_config::{HookEvent, HooksConfig};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::mpsc;
+
+/// The state of the webhook server, which fails the first `failures` deliveries.
+#[derive(Clone)]
+struct Webhook {
+    payloads: mpsc::UnboundedSender<HookPayload>,
+    failures: Arc<AtomicUsize>,
+}
+
+async fn receive(State(hook): State<Webhook>, Json(payload): Json<HookPayload>) -> StatusCode {
+    if hook.failures.load(Ordering::SeqCst) > 0 {
+        hook.failures.fetch_sub(1, Ordering::SeqCst);
+        return StatusCode::INTERNAL_SERVER_ERROR
+    }
+    let _ = hook.payloads.send(payload);
+    StatusCode::OK
+}
+
+/// Serves the webhook, returns its URL and the received payloads.
+fn serve_webhook(failures: usize) -> (String, mpsc::UnboundedReceiver<HookPayload>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let hook = Webhook { payloads: tx, failures: Arc::new(AtomicUsize::new(failures)) };
+    let app = Router::new().route("/hooks", post(receive)).with_state(hook);
+    let server =
+        axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(app.into_make_service());
+    let url = format!("http://{}/hooks", server.local_addr());
+    tokio::spawn(server);
+    (url, rx)
+}
+
+async fn next_payload(payloads: &mut mpsc::UnboundedReceiver<HookPayload>) -> HookPayload {
+    let payload = tokio::time::timeout(Duration::from_secs(10), payloads.recv()).await;
+    let payload = payload.expect("timed out waiting for a webhook").unwrap();
+    assert_eq!(payload.version, HOOK_PAYLOAD_VERSION);
+    payload
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_post_hooks_of_mined_blocks() {
+    let (url, mut payloads) = serve_webhook(0);
+    let config = NodeConfig::test();
+    let accounts = config.signer_accounts.iter().map(|wallet| wallet.address()).collect::<Vec<_>>();
+    let (alice, bob, carol) = (accounts[0], accounts[1], accounts[2]);
+
+    let hooks = HooksConfig { url: Some(url), watch: vec![bob.to_alloy()], ..Default::default() };
+    let (api, handle) = spawn(config.with_hooks(hooks)).await;
+    let provider = handle.ethers_http_provider();
+    api.anvil_add_address_watch(carol.to_alloy()).unwrap();
+
+    let tx = TransactionRequest::new().from(alice).to(bob).value(1);
+    let transfer = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    // returns 42
+    let init_code: Bytes = "0x600a600c600039600a6000f3602a60005260206000f3".parse().unwrap();
+    let tx = TransactionRequest::new().from(carol).data(init_code);
+    let deploy = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    api.mine_one().await;
+
+    let payload = next_payload(&mut payloads).await;
+    assert_eq!(payload.chain_id, 31337);
+    assert_eq!(
+        payload.event,
+        HookPayloadEvent::Block {
+            number: 1,
+            hash: transfer.block_hash.unwrap().to_alloy(),
+            timestamp: provider.get_block(1).await.unwrap().unwrap().timestamp.as_u64(),
+            transaction_count: 1,
+        }
+    );
+    let payload = next_payload(&mut payloads).await;
+    assert_eq!(
+        payload.event,
+        HookPayloadEvent::Transaction {
+            block_number: 1,
+            block_hash: transfer.block_hash.unwrap().to_alloy(),
+            hash: transfer.transaction_hash.to_alloy(),
+            from: alice.to_alloy(),
+            to: Some(bob.to_alloy()),
+            success: true,
+            watched: vec![bob.to_alloy()],
+        }
+    );
+
+    let payload = next_payload(&mut payloads).await;
+    assert_eq!(payload.kind(), HookEvent::Block);
+    let payload = next_payload(&mut payloads).await;
+    assert_eq!(payload.kind(), HookEvent::Transaction);
+    let payload = next_payload(&mut payloads).await;
+    let HookPayloadEvent::Deployment { block_number, from, contract_address, .. } = payload.event
+    else {
+        panic!("expected a deployment, got {payload:?}")
+    };
+    assert_eq!(block_number, 2);
+    assert_eq!(from, carol.to_alloy());
+    assert_eq!(contract_address, deploy.contract_address.unwrap().to_alloy());
+    assert_eq!(provider.get_code(contract_address.to_ethers(), None).await.unwrap().len(), 10);
+
+    // the empty block
+    let payload = next_payload(&mut payloads).await;
+    assert!(
+        matches!(payload.event, HookPayloadEvent::Block { number: 3, transaction_count: 0, .. }),
+        "{payload:?}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retries_failed_hooks() {
+    let (url, mut payloads) = serve_webhook(2);
+    let hooks =
+        HooksConfig { url: Some(url), events: vec![HookEvent::Block], ..Default::default() };
+    let (api, _handle) = spawn(NodeConfig::test().with_hooks(hooks)).await;
+
+    api.mine_one().await;
+    api.mine_one().await;
+
+    // the first block is delivered after two failed attempts, in order
+    let payload = next_payload(&mut payloads).await;
+    assert!(matches!(payload.event, HookPayloadEvent::Block { number: 1, .. }), "{payload:?}");
+    let payload = next_payload(&mut payloads).await;
+    assert!(matches!(payload.event, HookPayloadEvent::Block { number: 2, .. }), "{payload:?}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unreachable_hooks_dont_block_mining() {
+    let hooks = HooksConfig {
+        url: Some("http://127.0.0.1:1/hooks".to_string()),
+        retries: 10,
+        ..Default::default()
+    };
+    let (api, handle) = spawn(NodeConfig::test().with_hooks(hooks)).await;
+    let provider = handle.ethers_http_provider();
+
+    for _ in 0..5 {
+        api.mine_one().await;
+    }
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 5);
+    // an address is watched regardless
+    api.anvil_add_address_watch(Address::random()).unwrap();
+}
@@ -7,6 +7,7 @@ mod ganache;
 mod gas;
 mod genesis;
 mod geth;
+mod hooks;
 mod ipc;
 mod logs;
 mod optimism;
@@ -223,3 +223,42 @@ contract Contract {
 
     let _ = resp.unwrap_err();
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_verbose_errors_decode_reverts_with_artifacts() {
+    let prj = TempProject::dapptools().unwrap();
+    prj.add_source(
+        "Contract",
+        r#"
+pragma solidity 0.8.13;
+contract Contract {
+    error Paused();
+    function pause() public pure {
+        revert Paused();
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let mut compiled = prj.compile().unwrap();
+    assert!(!compiled.has_compiler_errors());
+    let contract = compiled.remove_first("Contract").unwrap();
+    let (abi, bytecode, _) = contract.into_contract_bytecode().into_parts();
+
+    let config = NodeConfig::test()
+        .with_artifacts(Some(prj.paths().artifacts.clone()))
+        .with_verbose_errors(true);
+    let (_api, handle) = spawn(config).await;
+    let provider = handle.ws_provider();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let factory = ContractFactory::new(abi.unwrap(), bytecode.unwrap(), client);
+    let contract = factory.deploy(()).unwrap().send().await.unwrap();
+
+    let err = contract.method::<_, ()>("pause", ()).unwrap().call().await.unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("execution reverted: Paused() at "), "{msg}");
+    assert!(msg.contains("Contract.sol:6"), "{msg}");
+}
@@ -1,5 +1,7 @@
 use crate::abi::*;
+use alloy_primitives::{B256, U256 as rU256};
 use anvil::{spawn, Hardfork, NodeConfig};
+use anvil_core::types::{KnownAccount, TransactionConditional};
 use ethers::{
     abi::ethereum_types::BigEndianHash,
     prelude::{
@@ -7,7 +9,10 @@ use ethers::{
         TransactionRequest,
     },
     types::{
-        transaction::eip2930::{AccessList, AccessListItem},
+        transaction::{
+            eip2718::TypedTransaction,
+            eip2930::{AccessList, AccessListItem},
+        },
         Address, BlockNumber, Transaction, TransactionReceipt, H256, U256,
     },
 };
@@ -1019,3 +1024,47 @@ async fn test_reject_eip1559_pre_london() {
     let greeting = greeter_contract.greet().call().await.unwrap();
     assert_eq!("Hello World!", greeting);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_drop_conditional_tx_with_changed_slot() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.ethers_http_provider();
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let tx: TypedTransaction = TransactionRequest::new()
+        .from(wallet.address())
+        .to(Address::random())
+        .value(1u64)
+        .nonce(0u64)
+        .gas(21_000u64)
+        .gas_price(api.gas_price().unwrap().to_ethers())
+        .chain_id(api.chain_id())
+        .into();
+    let signature = wallet.sign_transaction_sync(&tx).unwrap();
+    let raw = tx.rlp_signed(&signature).to_alloy();
+
+    // the transaction requires the slot of the watched account to be unset
+    let watched = Address::random().to_alloy();
+    let conditional = TransactionConditional {
+        known_accounts: [(watched, KnownAccount::Slots([(B256::ZERO, B256::ZERO)].into()))].into(),
+        ..Default::default()
+    };
+    let hash =
+        api.send_raw_transaction_conditional(raw.clone(), conditional.clone()).await.unwrap();
+    let pending = provider.get_transaction(hash.to_ethers()).await.unwrap().unwrap();
+    assert!(pending.block_number.is_none());
+
+    // change the slot before the transaction is mined
+    api.anvil_set_storage_at(watched, rU256::ZERO, B256::with_last_byte(1)).await.unwrap();
+    api.evm_mine(None).await.unwrap();
+
+    assert!(provider.get_transaction(hash.to_ethers()).await.unwrap().is_none());
+    assert!(provider.get_transaction_receipt(hash.to_ethers()).await.unwrap().is_none());
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 1);
+    assert!(provider.get_block(1).await.unwrap().unwrap().transactions.is_empty());
+
+    // the preconditions are also checked on submission
+    let err = api.send_raw_transaction_conditional(raw, conditional).await.unwrap_err();
+    assert!(err.to_string().contains("conditional not met"), "{err}");
+}
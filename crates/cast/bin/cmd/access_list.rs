@@ -1,15 +1,21 @@
-use cast::{Cast, TxBuilder};
+use super::call::{override_executor, StateOverrideArgs};
+use alloy_primitives::U256;
+use cast::{
+    format_access_list, format_access_list_comparison, overrides::is_method_not_found_error,
+    AccessListComparison, TxBuilder,
+};
 use clap::Parser;
-use ethers_core::types::{BlockId, NameOrAddress};
-use ethers_providers::Middleware;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, BlockId, NameOrAddress};
+use ethers_providers::{Middleware, MiddlewareError};
 use eyre::{Result, WrapErr};
 use foundry_cli::{
     opts::{EthereumOpts, TransactionOpts},
     utils,
 };
-use foundry_common::types::ToEthers;
-use foundry_config::{Chain, Config};
+use foundry_common::types::{ToAlloy, ToEthers};
+use foundry_config::Config;
 use std::str::FromStr;
+use yansi::Paint;
 
 /// CLI arguments for `cast access-list`.
 #[derive(Debug, Parser)]
@@ -43,10 +49,22 @@ pub struct AccessListArgs {
     #[clap(long, short = 'B')]
     block: Option<BlockId>,
 
-    /// Print the access list as JSON.
+    /// Print the access list as JSON, which `cast send --access-list` accepts.
     #[clap(long, short, help_heading = "Display options")]
     json: bool,
 
+    /// Also estimate the gas of the transaction without and with the access list, to show the
+    /// expected savings.
+    #[clap(long)]
+    compare: bool,
+
+    /// State overrides of the transaction.
+    ///
+    /// `eth_createAccessList` doesn't support state overrides, the access list is generated by
+    /// executing the transaction locally against a fork of the endpoint instead.
+    #[clap(flatten)]
+    overrides: StateOverrideArgs,
+
     #[clap(flatten)]
     tx: TransactionOpts,
 
@@ -56,36 +74,94 @@ pub struct AccessListArgs {
 
 impl AccessListArgs {
     pub async fn run(self) -> Result<()> {
-        let AccessListArgs { to, sig, args, data, tx, eth, block, json: to_json } = self;
+        let AccessListArgs {
+            to,
+            sig,
+            args,
+            data,
+            tx,
+            eth,
+            block,
+            json: to_json,
+            compare,
+            overrides,
+        } = self;
 
         let config = Config::from(&eth);
         let provider = utils::get_provider(&config)?;
         let chain = utils::get_chain(config.chain, &provider).await?;
         let sender = eth.wallet.sender().await;
 
-        access_list(&provider, sender.to_ethers(), to, sig, args, data, tx, chain, block, to_json)
-            .await?;
+        let mut builder =
+            TxBuilder::new(&provider, sender.to_ethers(), to, chain, tx.legacy).await?;
+        fill_tx(&mut builder, tx, sig, args, data).await?;
+        let (tx, _) = builder.build();
+
+        // the transaction is executed locally if the endpoint can't generate the access list
+        let overrides = overrides.overrides();
+        let mut executor = None;
+        let access_list = if overrides.is_empty() {
+            match provider.create_access_list(&tx, block).await {
+                Ok(access_list) => access_list,
+                Err(err) if err.as_error_response().is_some_and(is_method_not_found_error) => {
+                    eprintln!(
+                        "{}",
+                        Paint::yellow(
+                            "Warning: the endpoint doesn't support eth_createAccessList, \
+                             executing locally"
+                        )
+                    );
+                    let executor = executor.insert(
+                        override_executor(&config, eth.rpc, &provider, block, None, &overrides)
+                            .await?,
+                    );
+                    executor.access_list(&tx)?
+                }
+                Err(err) => return Err(err.into()),
+            }
+        } else {
+            let executor = executor.insert(
+                override_executor(&config, eth.rpc, &provider, block, None, &overrides).await?,
+            );
+            executor.access_list(&tx)?
+        };
+
+        if !compare {
+            println!("{}", format_access_list(&access_list, to_json)?);
+            return Ok(())
+        }
+
+        if matches!(tx, TypedTransaction::Legacy(_)) {
+            eyre::bail!("legacy transactions don't have access lists, remove `--legacy`")
+        }
+        let mut with_access_list = tx.clone();
+        with_access_list.set_access_list(access_list.access_list.clone());
+        let comparison = match &mut executor {
+            Some(executor) => AccessListComparison {
+                gas_without: U256::from(executor.estimate_gas(&tx)?),
+                gas_with: U256::from(executor.estimate_gas(&with_access_list)?),
+            },
+            None => AccessListComparison {
+                gas_without: provider.estimate_gas(&tx, block).await?.to_alloy(),
+                gas_with: provider.estimate_gas(&with_access_list, block).await?.to_alloy(),
+            },
+        };
+        println!("{}", format_access_list_comparison(&access_list, &comparison, to_json)?);
         Ok(())
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-async fn access_list<M: Middleware, F: Into<NameOrAddress>, T: Into<NameOrAddress>>(
-    provider: M,
-    from: F,
-    to: Option<T>,
+/// fills the builder from args
+async fn fill_tx<M: Middleware>(
+    builder: &mut TxBuilder<'_, M>,
+    tx: TransactionOpts,
     sig: Option<String>,
     args: Vec<String>,
     data: Option<String>,
-    tx: TransactionOpts,
-    chain: Chain,
-    block: Option<BlockId>,
-    to_json: bool,
 ) -> Result<()>
 where
     M::Error: 'static,
 {
-    let mut builder = TxBuilder::new(&provider, from, to, chain, tx.legacy).await?;
     builder
         .gas(tx.gas_limit)
         .gas_price(tx.gas_price)
@@ -102,13 +178,5 @@ where
         builder.set_data(hex::decode(data).wrap_err("Expected hex encoded function data")?);
     }
 
-    let builder_output = builder.peek();
-
-    let cast = Cast::new(&provider);
-
-    let access_list: String = cast.access_list(builder_output, block, to_json).await?;
-
-    println!("{}", access_list);
-
     Ok(())
 }
@@ -0,0 +1,98 @@
+use cast::{block::BlockSelection, Cast};
+use clap::Parser;
+use ethers_core::types::{BlockId, BlockNumber};
+use eyre::Result;
+use foundry_cli::{opts::RpcOpts, utils};
+use foundry_config::Config;
+use futures::{stream, StreamExt};
+
+/// CLI arguments for `cast block`.
+#[derive(Clone, Debug, Parser)]
+pub struct BlockArgs {
+    /// The block height to query at, or an inclusive range of block numbers, e.g.
+    /// `18000000..18000100`.
+    ///
+    /// Can also be the tags earliest, finalized, safe, latest, or pending.
+    block: Option<BlockSelection>,
+
+    /// If specified, only get the given field of the block.
+    #[clap(long, short, conflicts_with = "fields")]
+    field: Option<String>,
+
+    /// Only get the given comma-separated fields of the block, tab-separated or as a JSON object
+    /// with `--json`.
+    ///
+    /// Nested fields are separated by dots, e.g. `transactions.from` with `--full`.
+    #[clap(long, value_delimiter = ',', value_name = "FIELDS")]
+    fields: Vec<String>,
+
+    #[clap(long, env = "CAST_FULL_BLOCK")]
+    full: bool,
+
+    /// Print the block as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+
+    /// The maximum number of blocks of a range fetched concurrently.
+    #[clap(long, default_value = "8", value_name = "N")]
+    concurrency: usize,
+
+    /// Abort on the first block of a range that can't be fetched, instead of reporting it and
+    /// continuing.
+    #[clap(long)]
+    fail_fast: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl BlockArgs {
+    pub async fn run(self) -> Result<()> {
+        let BlockArgs { block, field, fields, full, json, concurrency, fail_fast, rpc } = self;
+        let config = Config::from(&rpc);
+        let provider = utils::get_provider(&config)?;
+        let cast = Cast::new(provider);
+
+        let fetch = |block: BlockId| {
+            let (cast, field, fields) = (&cast, field.clone(), &fields);
+            async move {
+                if fields.is_empty() {
+                    return cast.block(block, full, field, json).await
+                }
+                cast.block_fields(block, full, fields, json).await
+            }
+        };
+
+        let range = match block.unwrap_or(BlockSelection::Block(BlockNumber::Latest.into())) {
+            BlockSelection::Block(block) => {
+                println!("{}", fetch(block).await?);
+                return Ok(())
+            }
+            BlockSelection::Range(range) => range,
+        };
+
+        // the blocks are fetched concurrently but printed in order
+        let total = range.clone().count();
+        let mut blocks = stream::iter(range)
+            .map(|number| {
+                let block = fetch(BlockId::from(number));
+                async move { (number, block.await) }
+            })
+            .buffered(concurrency.max(1));
+        let mut failed = 0;
+        while let Some((number, block)) = blocks.next().await {
+            match block {
+                Ok(block) => println!("{block}"),
+                Err(err) if fail_fast => return Err(err.wrap_err(format!("block {number}"))),
+                Err(err) => {
+                    eprintln!("Error: block {number}: {err:#}");
+                    failed += 1;
+                }
+            }
+        }
+        if failed > 0 {
+            eyre::bail!("{failed} of {total} blocks couldn't be fetched")
+        }
+        Ok(())
+    }
+}
@@ -1,20 +1,29 @@
-use alloy_primitives::U256;
-use cast::{Cast, TxBuilder};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use cast::{
+    format_call_output,
+    overrides::{
+        archive_error, is_unsupported_error, revert_error, OverrideExecutor, StateOverrides,
+    },
+    Cast, TxBuilder,
+};
 use clap::Parser;
-use ethers_core::types::{BlockId, NameOrAddress};
-use eyre::{Result, WrapErr};
+use ethers_core::types::{BlockId, BlockNumber, NameOrAddress};
+use ethers_providers::Middleware;
+use eyre::{eyre, Result, WrapErr};
 use foundry_cli::{
-    opts::{EthereumOpts, TransactionOpts},
+    opts::{EthereumOpts, RpcOpts, TransactionOpts},
     utils::{self, handle_traces, parse_ether_value, TraceResult},
 };
 use foundry_common::{
+    fs,
     runtime_client::RuntimeClient,
     types::{ToAlloy, ToEthers},
 };
 use foundry_compilers::EvmVersion;
-use foundry_config::{find_project_root_path, Config};
+use foundry_config::{find_project_root_path, Chain, Config};
 use foundry_evm::{executors::TracingExecutor, opts::EvmOpts};
 use std::str::FromStr;
+use yansi::Paint;
 
 type Provider = ethers_providers::Provider<RuntimeClient>;
 
@@ -70,12 +79,17 @@ pub struct CallArgs {
     /// The block height to query at.
     ///
     /// Can also be the tags earliest, finalized, safe, latest, or pending.
+    ///
+    /// With `--trace`, the call is executed on a local fork of the endpoint at the block.
     #[clap(long, short)]
     block: Option<BlockId>,
 
     #[clap(subcommand)]
     command: Option<CallSubcommands>,
 
+    #[clap(flatten)]
+    overrides: StateOverrideArgs,
+
     #[clap(flatten)]
     tx: TransactionOpts,
 
@@ -123,8 +137,10 @@ impl CallArgs {
             debug,
             verbose,
             labels,
+            overrides,
         } = self;
 
+        let overrides = overrides.overrides();
         let config = Config::from(&eth);
         let provider = utils::get_provider(&config)?;
         let chain = utils::get_chain(config.chain, &provider).await?;
@@ -143,17 +159,16 @@ impl CallArgs {
         match command {
             Some(CallSubcommands::Create { code, sig, args, value }) => {
                 if trace {
-                    let figment = Config::figment_with_root(find_project_root_path(None).unwrap())
-                        .merge(eth.rpc);
-
-                    let evm_opts = figment.extract::<EvmOpts>()?;
-
-                    let (env, fork, chain) =
-                        TracingExecutor::get_fork_material(&config, evm_opts).await?;
-
-                    let mut executor =
-                        foundry_evm::executors::TracingExecutor::new(env, fork, evm_version, debug)
-                            .await;
+                    let (mut executor, chain) = trace_executor(
+                        &config,
+                        eth.rpc,
+                        &provider,
+                        block,
+                        evm_version,
+                        debug,
+                        &overrides,
+                    )
+                    .await?;
 
                     let trace = match executor.deploy(
                         sender,
@@ -162,10 +177,20 @@ impl CallArgs {
                         None,
                     ) {
                         Ok(deploy_result) => TraceResult::from(deploy_result),
-                        Err(evm_err) => TraceResult::try_from(evm_err)?,
+                        Err(evm_err) => TraceResult::try_from(evm_err)
+                            .map_err(|err| archive_error(err.into(), block))?,
                     };
 
-                    handle_traces(trace, &config, chain, labels, verbose).await?;
+                    handle_traces(
+                        trace,
+                        &config,
+                        chain,
+                        labels,
+                        verbose,
+                        None,
+                        &Default::default(),
+                    )
+                    .await?;
 
                     return Ok(());
                 }
@@ -178,28 +203,39 @@ impl CallArgs {
                 fill_tx(&mut builder, tx.value, sig, args, data).await?;
 
                 if trace {
-                    let figment = Config::figment_with_root(find_project_root_path(None).unwrap())
-                        .merge(eth.rpc);
-
-                    let evm_opts = figment.extract::<EvmOpts>()?;
-
-                    let (env, fork, chain) =
-                        TracingExecutor::get_fork_material(&config, evm_opts).await?;
-
-                    let mut executor =
-                        foundry_evm::executors::TracingExecutor::new(env, fork, evm_version, debug)
-                            .await;
+                    let (mut executor, chain) = trace_executor(
+                        &config,
+                        eth.rpc,
+                        &provider,
+                        block,
+                        evm_version,
+                        debug,
+                        &overrides,
+                    )
+                    .await?;
 
                     let (tx, _) = builder.build();
 
-                    let trace = TraceResult::from(executor.call_raw_committing(
-                        sender,
-                        tx.to_addr().copied().expect("an address to be here").to_alloy(),
-                        tx.data().cloned().unwrap_or_default().to_vec().into(),
-                        tx.value().copied().unwrap_or_default().to_alloy(),
-                    )?);
-
-                    handle_traces(trace, &config, chain, labels, verbose).await?;
+                    let result = executor
+                        .call_raw_committing(
+                            sender,
+                            tx.to_addr().copied().expect("an address to be here").to_alloy(),
+                            tx.data().cloned().unwrap_or_default().to_vec().into(),
+                            tx.value().copied().unwrap_or_default().to_alloy(),
+                        )
+                        .map_err(|err| archive_error(err, block))?;
+                    let trace = TraceResult::from(result);
+
+                    handle_traces(
+                        trace,
+                        &config,
+                        chain,
+                        labels,
+                        verbose,
+                        None,
+                        &Default::default(),
+                    )
+                    .await?;
 
                     return Ok(());
                 }
@@ -207,12 +243,209 @@ impl CallArgs {
         };
 
         let builder_output = builder.build();
-        println!("{}", Cast::new(provider).call(builder_output, block).await?);
+        if overrides.is_empty() {
+            let output = Cast::new(&provider)
+                .call(builder_output, block)
+                .await
+                .map_err(|err| archive_error(err, block))?;
+            println!("{output}");
+            return Ok(())
+        }
+
+        match Cast::new(&provider)
+            .call_with_overrides(builder_output.clone(), block, &overrides)
+            .await
+        {
+            Ok(output) => println!("{output}"),
+            Err(err) if is_unsupported_error(&format!("{err:#}")) => {
+                warn_local_execution();
+                let (tx, func) = builder_output;
+                let mut executor =
+                    override_executor(&config, eth.rpc, &provider, block, evm_version, &overrides)
+                        .await?;
+                let result = executor.call(&tx).map_err(|err| archive_error(err, block))?;
+                if result.reverted {
+                    return Err(revert_error(&result))
+                }
+                println!("{}", format_call_output(func.as_ref(), &result.result)?);
+            }
+            Err(err) => return Err(archive_error(err, block)),
+        }
 
         Ok(())
     }
 }
 
+/// State overrides of the transaction, like geth's `stateOverride` parameter.
+///
+/// The overrides are sent to the endpoint, if it doesn't support them the transaction is executed
+/// locally against a fork of the endpoint instead.
+#[derive(Clone, Debug, Default, Parser)]
+#[clap(next_help_heading = "State overrides")]
+pub struct StateOverrideArgs {
+    /// Override the balance of an account.
+    ///
+    /// The balance is either specified in wei, or as a string with a unit type, e.g. 1ether.
+    #[clap(
+        long = "override-balance",
+        value_name = "ADDRESS:BALANCE",
+        value_parser = parse_balance_override
+    )]
+    pub balances: Vec<(Address, U256)>,
+
+    /// Override the nonce of an account.
+    #[clap(
+        long = "override-nonce",
+        value_name = "ADDRESS:NONCE",
+        value_parser = parse_nonce_override
+    )]
+    pub nonces: Vec<(Address, u64)>,
+
+    /// Override the runtime bytecode of an account.
+    ///
+    /// The code is either hex encoded, or the path of a file with the bytecode.
+    #[clap(
+        long = "override-code",
+        value_name = "ADDRESS:CODE",
+        value_parser = parse_code_override
+    )]
+    pub codes: Vec<(Address, Bytes)>,
+
+    /// Override the value of a storage slot of an account, the other slots keep their value.
+    #[clap(
+        long = "override-state",
+        value_name = "ADDRESS:SLOT:VALUE",
+        value_parser = parse_storage_override
+    )]
+    pub storage: Vec<(Address, B256, B256)>,
+}
+
+impl StateOverrideArgs {
+    pub fn overrides(&self) -> StateOverrides {
+        let mut overrides = StateOverrides::default();
+        for (address, balance) in &self.balances {
+            overrides.set_balance(*address, *balance);
+        }
+        for (address, nonce) in &self.nonces {
+            overrides.set_nonce(*address, *nonce);
+        }
+        for (address, code) in &self.codes {
+            overrides.set_code(*address, code.clone());
+        }
+        for (address, slot, value) in &self.storage {
+            overrides.set_storage(*address, *slot, *value);
+        }
+        overrides
+    }
+}
+
+fn parse_override<T>(s: &str, parse_value: impl FnOnce(&str) -> Result<T>) -> Result<(Address, T)> {
+    let (address, value) =
+        s.split_once(':').ok_or_else(|| eyre!("expected `<address>:<value>`, got {s:?}"))?;
+    Ok((address.parse()?, parse_value(value)?))
+}
+
+fn parse_balance_override(s: &str) -> Result<(Address, U256)> {
+    parse_override(s, parse_ether_value)
+}
+
+fn parse_nonce_override(s: &str) -> Result<(Address, u64)> {
+    parse_override(s, |nonce| Ok(nonce.parse()?))
+}
+
+fn parse_code_override(s: &str) -> Result<(Address, Bytes)> {
+    parse_override(s, |code| {
+        if code.starts_with("0x") {
+            return Ok(hex::decode(code)?.into())
+        }
+        // the file may contain the hex encoded bytecode, e.g. the output of `solc --bin-runtime`
+        let content = fs::read(code)?;
+        Ok(match std::str::from_utf8(&content).ok().and_then(|hex| hex::decode(hex.trim()).ok()) {
+            Some(code) => code.into(),
+            None => content.into(),
+        })
+    })
+}
+
+fn parse_storage_override(s: &str) -> Result<(Address, B256, B256)> {
+    let mut parts = s.splitn(3, ':');
+    let (Some(address), Some(slot), Some(value)) = (parts.next(), parts.next(), parts.next())
+    else {
+        eyre::bail!("expected `<address>:<slot>:<value>`, got {s:?}")
+    };
+    let parse_word = |word: &str| -> Result<B256> { Ok(B256::from(word.parse::<U256>()?)) };
+    Ok((address.parse()?, parse_word(slot)?, parse_word(value)?))
+}
+
+pub(crate) fn warn_local_execution() {
+    eprintln!(
+        "{}",
+        Paint::yellow("Warning: the endpoint doesn't support state overrides, executing locally")
+    );
+}
+
+/// Returns an executor of a local fork of the endpoint at the block, with the overrides applied.
+pub(crate) async fn override_executor(
+    config: &Config,
+    rpc: RpcOpts,
+    provider: &Provider,
+    block: Option<BlockId>,
+    evm_version: Option<EvmVersion>,
+    overrides: &StateOverrides,
+) -> Result<OverrideExecutor> {
+    let figment = Config::figment_with_root(find_project_root_path(None).unwrap()).merge(rpc);
+    let evm_opts = figment.extract::<EvmOpts>()?;
+    let fork_block_number = fork_block_number(provider, block).await?;
+    OverrideExecutor::new(config, evm_opts, fork_block_number, evm_version, overrides)
+        .await
+        .map_err(|err| archive_error(err, block))
+}
+
+/// Returns a tracing executor of a local fork of the endpoint at the block, with the overrides
+/// applied, and the chain of the endpoint.
+///
+/// The state is fetched with the standard methods, so this works for endpoints without the
+/// `debug` namespace.
+async fn trace_executor(
+    config: &Config,
+    rpc: RpcOpts,
+    provider: &Provider,
+    block: Option<BlockId>,
+    evm_version: Option<EvmVersion>,
+    debug: bool,
+    overrides: &StateOverrides,
+) -> Result<(TracingExecutor, Option<Chain>)> {
+    let figment = Config::figment_with_root(find_project_root_path(None).unwrap()).merge(rpc);
+    let evm_opts = figment.extract::<EvmOpts>()?;
+    let mut config = config.clone();
+    if let Some(number) = fork_block_number(provider, block).await? {
+        config.fork_block_number = Some(number);
+    }
+    let (env, fork, chain) = TracingExecutor::get_fork_material(&config, evm_opts)
+        .await
+        .map_err(|err| archive_error(err, block))?;
+    let mut executor = TracingExecutor::new(env, fork, evm_version, debug).await;
+    overrides.apply(&mut executor)?;
+    Ok((executor, chain))
+}
+
+/// Returns the number of the block to fork at, unset for the latest block.
+async fn fork_block_number(provider: &Provider, block: Option<BlockId>) -> Result<Option<u64>> {
+    Ok(match block {
+        None | Some(BlockId::Number(BlockNumber::Latest)) => None,
+        Some(BlockId::Number(BlockNumber::Number(number))) => Some(number.as_u64()),
+        Some(block) => Some(
+            provider
+                .get_block(block)
+                .await
+                .map_err(|err| archive_error(err.into(), Some(block)))?
+                .and_then(|block| block.number)
+                .ok_or_else(|| eyre!("block {block:?} not found"))?
+                .as_u64(),
+        ),
+    })
+}
+
 /// fills the builder from create arg
 async fn fill_create(
     builder: &mut TxBuilder<'_, Provider>,
@@ -260,7 +493,6 @@ async fn fill_tx(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy_primitives::Address;
 
     #[test]
     fn can_parse_call_data() {
@@ -287,4 +519,27 @@ mod tests {
 
         assert!(args.is_err());
     }
+
+    #[test]
+    fn can_parse_code_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let hex_file = dir.path().join("code.hex");
+        std::fs::write(&hex_file, "6000\n").unwrap();
+        let bin_file = dir.path().join("code.bin");
+        std::fs::write(&bin_file, [0x60, 0x01]).unwrap();
+
+        let (address, code) = parse_code_override(&format!("{}:0x6002", Address::ZERO)).unwrap();
+        assert_eq!(address, Address::ZERO);
+        assert_eq!(code, Bytes::from_static(&[0x60, 0x02]));
+
+        let (_, code) =
+            parse_code_override(&format!("{}:{}", Address::ZERO, hex_file.display())).unwrap();
+        assert_eq!(code, Bytes::from_static(&[0x60, 0x00]));
+
+        let (_, code) =
+            parse_code_override(&format!("{}:{}", Address::ZERO, bin_file.display())).unwrap();
+        assert_eq!(code, Bytes::from_static(&[0x60, 0x01]));
+
+        assert!(parse_code_override(&Address::ZERO.to_string()).is_err());
+    }
 }
@@ -0,0 +1,77 @@
+use super::constructor_args::read_abi;
+use alloy_primitives::hex;
+use cast::calldata::{
+    decode_calldata, decode_calldata_with_abi, decoded_to_json, pretty_decoded, ParamScale, Scaling,
+};
+use clap::Parser;
+use eyre::Result;
+use foundry_common::{abi::get_func, fmt::format_tokens};
+use std::path::PathBuf;
+
+/// CLI arguments for `cast calldata-decode`.
+#[derive(Debug, Parser)]
+pub struct CalldataDecodeArgs {
+    /// The function signature in the format `<name>(<in-types>)(<out-types>)`, optionally with
+    /// the names of the parameters, e.g. `transfer(address to, uint256 amount)`.
+    ///
+    /// Omitted with `--abi`.
+    #[clap(value_name = "SIG")]
+    sig: Option<String>,
+
+    /// The ABI-encoded calldata.
+    #[clap(value_name = "CALLDATA")]
+    calldata: Option<String>,
+
+    /// The ABI or artifact of the contract, whose function is looked up by the selector of the
+    /// calldata instead of passing its signature.
+    #[clap(long, value_name = "PATH")]
+    abi: Option<PathBuf>,
+
+    /// Print the arguments with their names, with nested tuples and arrays indented.
+    #[clap(long, short)]
+    pretty: bool,
+
+    /// Show the uint arguments whose name looks like an amount, e.g. `amount` or `maxPrice`,
+    /// scaled by these decimals. Implies `--pretty`.
+    #[clap(long, value_name = "DECIMALS", conflicts_with = "json")]
+    decimals: Option<u8>,
+
+    /// Show the uint argument with the name scaled by the decimals, can be repeated. Implies
+    /// `--pretty`.
+    #[clap(long, value_name = "NAME:DECIMALS", conflicts_with = "json")]
+    scale: Vec<ParamScale>,
+
+    /// Print the arguments as a JSON array, tuples and arrays as nested arrays.
+    #[clap(long, short, conflicts_with = "pretty", help_heading = "Display options")]
+    json: bool,
+}
+
+impl CalldataDecodeArgs {
+    pub fn run(self) -> Result<()> {
+        let Self { sig, calldata, abi, pretty, decimals, scale, json } = self;
+
+        let (func, values) = match (abi, sig, calldata) {
+            (Some(abi), Some(calldata), None) => {
+                let (abi, _) = read_abi(&abi)?;
+                decode_calldata_with_abi(&abi, &hex::decode(calldata)?)?
+            }
+            (None, Some(sig), Some(calldata)) => {
+                let func = get_func(&sig)?;
+                let values = decode_calldata(&func, &hex::decode(calldata)?)?;
+                (func, values)
+            }
+            (Some(_), ..) => eyre::bail!("Pass either the function signature or `--abi`, not both"),
+            (None, ..) => eyre::bail!("Pass the function signature of the calldata, or `--abi`"),
+        };
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&decoded_to_json(&values))?);
+        } else if pretty || decimals.is_some() || !scale.is_empty() {
+            let params = scale.into_iter().map(|scale| (scale.name, scale.decimals)).collect();
+            println!("{}", pretty_decoded(&func, &values, &Scaling { decimals, params }));
+        } else {
+            format_tokens(&values).for_each(|t| println!("{t}"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use cast::Cast;
+use clap::Parser;
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use eyre::Result;
+use foundry_cli::{opts::RpcOpts, utils};
+use foundry_config::{
+    chains::{chain_registry, ChainInfo},
+    Config,
+};
+
+/// CLI arguments for `cast chain`.
+#[derive(Debug, Parser)]
+pub struct ChainArgs {
+    /// List the known chains instead, without an RPC.
+    #[clap(long, conflicts_with = "search")]
+    list: bool,
+
+    /// Search the known chains by name instead, without an RPC.
+    #[clap(long, value_name = "NAME")]
+    search: Option<String>,
+
+    /// Print the chains as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl ChainArgs {
+    pub async fn run(self) -> Result<()> {
+        let ChainArgs { list, search, json, rpc } = self;
+        let registry = chain_registry();
+        let chains = match search {
+            Some(query) => registry.search(&query).collect::<Vec<_>>(),
+            None if list => registry.iter().collect(),
+            None => {
+                let config = Config::from(&rpc);
+                let provider = utils::get_provider(&config)?;
+                println!("{}", Cast::new(provider).chain().await?);
+                return Ok(())
+            }
+        };
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&chains)?);
+        } else if chains.is_empty() {
+            println!(
+                "No known chain matches, custom chains can be added in ~/.foundry/chains.toml"
+            );
+        } else {
+            println!("{}", pretty_chains(&chains));
+        }
+        Ok(())
+    }
+}
+
+fn pretty_chains(chains: &[&ChainInfo]) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN);
+    table.set_header(["Id", "Name", "Title", "Currency", "Explorer"]);
+    for chain in chains {
+        let mut name = chain.name.clone();
+        if chain.custom {
+            name.push_str(" (custom)");
+        }
+        table.add_row([
+            chain.id.to_string(),
+            name,
+            chain.title.clone(),
+            chain.currency.clone(),
+            chain.explorer.clone().unwrap_or_default(),
+        ]);
+    }
+    table.to_string()
+}
@@ -0,0 +1,105 @@
+use super::creation_code::ContractCreationOpts;
+use alloy_json_abi::{ContractObject, JsonAbi};
+use alloy_primitives::Bytes;
+use cast::creation::{decode_constructor_args, split_constructor_args};
+use clap::Parser;
+use eyre::{Context, Result};
+use foundry_common::{fmt::format_token_raw, fs};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+/// CLI arguments for `cast constructor-args`.
+#[derive(Clone, Debug, Parser)]
+pub struct ConstructorArgsArgs {
+    #[clap(flatten)]
+    contract: ContractCreationOpts,
+
+    /// The ABI or artifact of the contract, instead of its verified ABI.
+    ///
+    /// The constructor arguments are split off with the length of the bytecode of artifacts.
+    #[clap(long, value_name = "PATH")]
+    abi: Option<PathBuf>,
+
+    /// Print the constructor arguments as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+}
+
+impl ConstructorArgsArgs {
+    pub async fn run(self) -> Result<()> {
+        let Self { contract, abi, json } = self;
+        let address = contract.address;
+        let (creation, client) = contract.locate().await?;
+        let creation_code = &creation.creation_code[..];
+
+        // the ABI, and the length of the creation code without the arguments if known
+        let (abi, code_len) = if let Some(path) = abi {
+            read_abi(&path)?
+        } else if let Some(client) = client {
+            let source = client
+                .contract_source_code(address)
+                .await
+                .wrap_err_with(|| format!("failed to get the verified ABI of {address}"))?;
+            let abi = source.abis()?.into_iter().next().ok_or_else(|| {
+                eyre::eyre!("{address} isn't verified, pass its ABI with `--abi <PATH>`")
+            })?;
+            let args = &source.items[0].constructor_arguments;
+            let code_len = creation_code
+                .ends_with(args)
+                .then(|| creation_code.len() - args.len())
+                .filter(|_| !args.is_empty());
+            (abi, code_len)
+        } else {
+            eyre::bail!(
+                "No ABI to decode the constructor arguments of {address}, pass it with \
+                 `--abi <PATH>` or set an Etherscan API key"
+            )
+        };
+
+        let constructor = abi.constructor.as_ref();
+        let (_, args) = split_constructor_args(creation_code, constructor, code_len)?;
+        let values = match constructor {
+            Some(constructor) if !args.is_empty() => decode_constructor_args(constructor, args)?,
+            _ => vec![],
+        };
+
+        if json {
+            let params = constructor.map(|c| c.inputs.as_slice()).unwrap_or_default();
+            let decoded = params
+                .iter()
+                .zip(&values)
+                .map(|(param, value)| {
+                    json!({
+                        "name": param.name,
+                        "type": param.selector_type(),
+                        "value": format_token_raw(value),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let output = json!({
+                "constructorArgs": Bytes::copy_from_slice(args),
+                "decoded": decoded,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            println!("{}", Bytes::copy_from_slice(args));
+            for value in &values {
+                println!("{}", format_token_raw(value));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a raw ABI or an artifact, with the length of its bytecode.
+pub fn read_abi(path: &Path) -> Result<(JsonAbi, Option<usize>)> {
+    let file = fs::read_to_string(path)?;
+    if let Ok(abi) = serde_json::from_str::<JsonAbi>(&file) {
+        return Ok((abi, None))
+    }
+    let obj: ContractObject = serde_json::from_str(&file)
+        .wrap_err_with(|| format!("{} is neither an ABI nor an artifact", path.display()))?;
+    let abi =
+        obj.abi.ok_or_else(|| eyre::eyre!("could not find ABI in file {}", path.display()))?;
+    Ok((abi, obj.bytecode.map(|code| code.len()).filter(|len| *len > 0)))
+}
@@ -0,0 +1,86 @@
+use alloy_primitives::Address;
+use cast::{creation::ContractCreation, Cast};
+use clap::Parser;
+use eyre::Result;
+use foundry_block_explorers::Client;
+use foundry_cli::{
+    opts::{EtherscanOpts, RpcOpts},
+    utils,
+};
+use foundry_config::{
+    figment::{self, value::Dict, Metadata, Profile},
+    impl_figment_convert_cast, Config,
+};
+
+/// The contract whose deployment is located by `cast creation-code` and `cast constructor-args`.
+#[derive(Clone, Debug, Parser)]
+pub struct ContractCreationOpts {
+    /// The address of the contract.
+    pub address: Address,
+
+    #[clap(flatten)]
+    pub etherscan: EtherscanOpts,
+
+    #[clap(flatten)]
+    pub rpc: RpcOpts,
+}
+
+impl_figment_convert_cast!(ContractCreationOpts);
+
+impl figment::Provider for ContractCreationOpts {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("ContractCreationOpts")
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        let mut dict = self.rpc.dict();
+        dict.extend(self.etherscan.dict());
+        Ok(figment::value::Map::from([(Config::selected_profile(), dict)]))
+    }
+}
+
+impl ContractCreationOpts {
+    /// Locates the deployment of the contract, through the explorer if an API key is configured
+    /// for the chain, else by binary-searching the block in which its code appeared.
+    ///
+    /// Returns the explorer client too, if any.
+    pub async fn locate(&self) -> Result<(ContractCreation, Option<Client>)> {
+        let config = Config::from(self);
+        let provider = utils::get_provider(&config)?;
+        let chain = utils::get_chain(config.chain, &provider).await?;
+        let client = match config.get_etherscan_api_key(Some(chain)) {
+            Some(api_key) => Some(Client::new(chain, api_key)?),
+            None => None,
+        };
+
+        let cast = Cast::new(provider);
+        let creation =
+            cast.contract_creation(self.address, client.as_ref()).await.map_err(|err| {
+                eyre::eyre!("Could not locate the deployment of {}: {err:#}", self.address)
+            })?;
+        Ok((creation, client))
+    }
+}
+
+/// CLI arguments for `cast creation-code`.
+#[derive(Clone, Debug, Parser)]
+pub struct CreationCodeArgs {
+    #[clap(flatten)]
+    contract: ContractCreationOpts,
+
+    /// Print the deployment of the contract as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+}
+
+impl CreationCodeArgs {
+    pub async fn run(self) -> Result<()> {
+        let (creation, _) = self.contract.locate().await?;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&creation)?);
+        } else {
+            println!("{}", creation.creation_code);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use cast::Cast;
+use clap::{Parser, Subcommand};
+use eyre::Result;
+use foundry_cli::{opts::RpcOpts, utils};
+use foundry_config::Config;
+
+/// The subcommands of `cast ens`.
+#[derive(Debug, Subcommand)]
+pub enum EnsSubcommands {
+    /// Get a text record of an ENS name, e.g. `url`, `com.twitter` or `avatar`.
+    #[clap(visible_alias = "t")]
+    Text {
+        /// The ENS name.
+        name: String,
+
+        /// The key of the record.
+        key: String,
+
+        #[clap(flatten)]
+        opts: EnsOpts,
+    },
+
+    /// Get the URL of the avatar image of an ENS name, including NFT avatars.
+    #[clap(visible_alias = "a")]
+    Avatar {
+        /// The ENS name.
+        name: String,
+
+        #[clap(flatten)]
+        opts: EnsOpts,
+    },
+
+    /// Get the resolver of an ENS name, following the wildcard resolution of subnames.
+    #[clap(visible_alias = "r")]
+    Resolver {
+        /// The ENS name.
+        name: String,
+
+        #[clap(flatten)]
+        opts: EnsOpts,
+    },
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct EnsOpts {
+    /// Don't make the offchain lookups of CCIP-Read resolvers.
+    #[clap(long)]
+    no_ccip: bool,
+
+    /// Fail if the name has no such record or resolver, instead of printing nothing.
+    #[clap(long)]
+    strict: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl EnsSubcommands {
+    pub async fn run(self) -> Result<()> {
+        let (name, opts) = match &self {
+            Self::Text { name, opts, .. } |
+            Self::Avatar { name, opts } |
+            Self::Resolver { name, opts } => (name, opts),
+        };
+        let config = Config::from(&opts.rpc);
+        let provider = utils::get_provider(&config)?;
+        let cast = Cast::new(provider);
+        let ccip = !opts.no_ccip;
+
+        let (value, missing) = match &self {
+            Self::Text { key, .. } => {
+                (cast.ens_text(name, key, ccip).await?, format!("`{key}` text record"))
+            }
+            Self::Avatar { .. } => (cast.ens_avatar(name, ccip).await?, "avatar".to_string()),
+            Self::Resolver { .. } => {
+                let resolver = cast.ens_resolver(name).await?;
+                if let Some(resolver) = resolver.as_ref().filter(|resolver| resolver.wildcard) {
+                    eprintln!("{name} is resolved by the wildcard resolver of {}", resolver.name);
+                }
+                (
+                    resolver.map(|resolver| resolver.address.to_checksum(None)),
+                    "resolver".to_string(),
+                )
+            }
+        };
+        match value {
+            Some(value) => println!("{value}"),
+            None if opts.strict => eyre::bail!("{name} has no {missing}"),
+            None => {}
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
+use super::call::{override_executor, warn_local_execution, StateOverrideArgs};
 use alloy_primitives::U256;
-use cast::{Cast, TxBuilder};
+use cast::{
+    overrides::{archive_error, is_unsupported_error},
+    Cast, TxBuilder,
+};
 use clap::Parser;
-use ethers_core::types::NameOrAddress;
+use ethers_core::types::{BlockId, NameOrAddress};
 use eyre::Result;
 use foundry_cli::{
     opts::{EtherscanOpts, RpcOpts},
@@ -41,6 +45,15 @@ pub struct EstimateArgs {
     #[clap(long, value_parser = parse_ether_value)]
     value: Option<U256>,
 
+    /// The block height to estimate at.
+    ///
+    /// Can also be the tags earliest, finalized, safe, latest, or pending.
+    #[clap(long, short)]
+    block: Option<BlockId>,
+
+    #[clap(flatten)]
+    overrides: StateOverrideArgs,
+
     #[clap(flatten)]
     rpc: RpcOpts,
 
@@ -77,9 +90,10 @@ pub enum EstimateSubcommands {
 
 impl EstimateArgs {
     pub async fn run(self) -> Result<()> {
-        let EstimateArgs { from, to, sig, args, value, rpc, etherscan, command } = self;
+        let EstimateArgs { from, to, sig, args, value, block, overrides, rpc, etherscan, command } =
+            self;
 
-        let figment = Figment::from(Config::figment()).merge(etherscan).merge(rpc);
+        let figment = Figment::from(Config::figment()).merge(etherscan).merge(rpc.clone());
         let config = Config::try_from(figment)?;
 
         let provider = utils::get_provider(&config)?;
@@ -109,7 +123,24 @@ impl EstimateArgs {
         };
 
         let builder_output = builder.peek();
-        let gas = Cast::new(&provider).estimate(builder_output).await?;
+        let overrides = overrides.overrides();
+        let gas = if overrides.is_empty() {
+            Cast::new(&provider).estimate(builder_output, block).await
+        } else {
+            match Cast::new(&provider)
+                .estimate_with_overrides(builder_output, block, &overrides)
+                .await
+            {
+                Err(err) if is_unsupported_error(&format!("{err:#}")) => {
+                    warn_local_execution();
+                    let mut executor =
+                        override_executor(&config, rpc, &provider, block, None, &overrides).await?;
+                    executor.estimate_gas(builder_output.0).map(U256::from)
+                }
+                res => res,
+            }
+        }
+        .map_err(|err| archive_error(err, block))?;
         println!("{gas}");
         Ok(())
     }
@@ -118,10 +149,43 @@ impl EstimateArgs {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy_primitives::{Address, B256, U64};
 
     #[test]
     fn parse_estimate_value() {
         let args: EstimateArgs = EstimateArgs::parse_from(["foundry-cli", "--value", "100"]);
         assert!(args.value.is_some());
     }
+
+    #[test]
+    fn parse_estimate_block() {
+        let args: EstimateArgs = EstimateArgs::parse_from(["foundry-cli", "--block", "100"]);
+        assert_eq!(args.block, Some(BlockId::from(100u64)));
+        let args: EstimateArgs = EstimateArgs::parse_from(["foundry-cli", "-b", "safe"]);
+        assert_eq!(args.block, Some(BlockId::from(ethers_core::types::BlockNumber::Safe)));
+    }
+
+    #[test]
+    fn parse_estimate_overrides() {
+        let args: EstimateArgs = EstimateArgs::parse_from([
+            "foundry-cli",
+            "--override-balance",
+            "0x000000000000000000000000000000000000dEaD:1ether",
+            "--override-nonce",
+            "0x000000000000000000000000000000000000dEaD:5",
+            "--override-state",
+            "0x000000000000000000000000000000000000dEaD:0x1:42",
+        ]);
+        let overrides = args.overrides.overrides();
+        let account =
+            &overrides.0[&"0x000000000000000000000000000000000000dEaD".parse::<Address>().unwrap()];
+        assert_eq!(account.balance, Some(U256::from(10).pow(U256::from(18))));
+        assert_eq!(account.nonce, Some(U64::from(5)));
+        assert_eq!(
+            account.state_diff.iter().next(),
+            Some((&B256::with_last_byte(1), &B256::with_last_byte(42)))
+        );
+
+        assert!(EstimateArgs::try_parse_from(["foundry-cli", "--override-nonce", "5"]).is_err());
+    }
 }
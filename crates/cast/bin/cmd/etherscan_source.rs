@@ -0,0 +1,665 @@
+use alloy_primitives::Address;
+use clap::{Parser, ValueEnum, ValueHint};
+use eyre::{Context, Result};
+use foundry_block_explorers::contract::Metadata;
+use foundry_cli::opts::EtherscanOpts;
+use foundry_common::{compile::ProjectCompiler, etherscan::EtherscanPool, fs};
+use foundry_compilers::remappings::Remapping;
+use foundry_config::{chains::chain_registry, Chain, Config, ResolvedEtherscanConfig};
+use itertools::Itertools;
+use regex::Regex;
+use semver::Version;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Component, Path, PathBuf},
+};
+
+/// The URL of the Sourcify server.
+const SOURCIFY_URL: &str = "https://sourcify.dev/server";
+
+/// The directory the verified sources are written to.
+const SRC_DIR: &str = "src";
+
+/// The explorer to fetch a verified source from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Explorer {
+    /// Etherscan, or the Etherscan compatible explorer of the chain.
+    #[default]
+    Etherscan,
+    /// A Blockscout instance.
+    Blockscout,
+    /// Sourcify.
+    Sourcify,
+}
+
+/// CLI arguments for `cast etherscan-source`.
+#[derive(Clone, Debug, Parser)]
+pub struct EtherscanSourceArgs {
+    /// The contract's address.
+    address: Address,
+
+    /// The output directory to write a project with the verified source into.
+    ///
+    /// The project has a `foundry.toml` with the compiler settings and remappings of the
+    /// verification.
+    #[clap(short, value_hint = ValueHint::DirPath)]
+    directory: Option<PathBuf>,
+
+    /// The explorer to fetch the verified source from.
+    #[clap(long, value_enum, default_value = "etherscan")]
+    explorer: Explorer,
+
+    /// The URL of the API of the explorer.
+    ///
+    /// Required for Blockscout, unless the explorer of the chain is a Blockscout instance.
+    #[clap(long, value_name = "URL")]
+    explorer_url: Option<String>,
+
+    /// Flatten the verified source into a single file.
+    #[clap(long)]
+    flatten: bool,
+
+    /// Build the written project to check that it compiles.
+    #[clap(long, requires = "directory")]
+    verify_build: bool,
+
+    #[clap(flatten)]
+    etherscan: EtherscanOpts,
+}
+
+impl EtherscanSourceArgs {
+    pub async fn run(self) -> Result<()> {
+        let EtherscanSourceArgs {
+            address,
+            directory,
+            explorer,
+            explorer_url,
+            flatten,
+            verify_build,
+            etherscan,
+        } = self;
+
+        let config = Config::from(&etherscan);
+        let chain = config.chain.unwrap_or_default();
+        let source = match explorer {
+            Explorer::Etherscan => {
+                // all the keys of the chain, a key which hits the rate limit is backed off
+                let etherscan_config = config.get_etherscan_config_with_chain(Some(chain))?;
+                let explorer = match (explorer_url, etherscan_config) {
+                    (Some(url), Some(config)) => ResolvedEtherscanConfig {
+                        key: config.key,
+                        keys: config.keys,
+                        ..custom_explorer(url)
+                    },
+                    (Some(url), None) => custom_explorer(url),
+                    (None, Some(config)) => config,
+                    (None, None) => ResolvedEtherscanConfig::create("", chain)
+                        .ok_or_else(|| eyre::eyre!("{chain} has no Etherscan explorer"))?,
+                };
+                fetch_etherscan_source(explorer, address).await?
+            }
+            Explorer::Blockscout => {
+                let url = explorer_url.or_else(|| blockscout_api_url(chain)).ok_or_else(|| {
+                    eyre::eyre!("{chain} has no Blockscout explorer, pass its API URL with `--explorer-url`")
+                })?;
+                // the Etherscan key of the chain isn't sent to another explorer
+                let explorer = custom_explorer(url)
+                    .with_api_keys(etherscan.key.as_deref().unwrap_or_default());
+                fetch_etherscan_source(explorer, address).await?
+            }
+            Explorer::Sourcify => {
+                let url = explorer_url.as_deref().unwrap_or(SOURCIFY_URL);
+                fetch_sourcify_source(url, chain, address).await?
+            }
+        };
+
+        let source = if flatten {
+            let tmp = tempfile::tempdir()?;
+            write_project(&source, tmp.path())?;
+            source.flattened(tmp.path())?
+        } else {
+            source
+        };
+
+        let Some(dir) = directory else {
+            println!("{}", source.concatenated());
+            return Ok(())
+        };
+        write_project(&source, &dir)?;
+
+        if verify_build {
+            let config = Config::load_with_root(&dir).sanitized();
+            ProjectCompiler::new().compile(&config.project()?)?;
+        }
+        Ok(())
+    }
+}
+
+/// The verified source of a contract, with the compiler settings of its verification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct VerifiedSource {
+    /// The name of the contract.
+    name: String,
+    /// The contents of the sources by their path, which can't escape the project.
+    sources: BTreeMap<PathBuf, String>,
+    /// The path of the source that declares the contract, if the explorer has it.
+    target: Option<PathBuf>,
+    compiler_version: Version,
+    optimizer: bool,
+    optimizer_runs: usize,
+    evm_version: Option<String>,
+    via_ir: bool,
+    remappings: Vec<Remapping>,
+}
+
+impl VerifiedSource {
+    /// Normalizes the metadata of a contract verified on an Etherscan compatible explorer.
+    fn from_metadata(meta: &Metadata) -> Result<Self> {
+        if meta.is_vyper() {
+            eyre::bail!("`{}` is a Vyper contract, only Solidity is supported", meta.contract_name)
+        }
+        let settings = meta.settings().wrap_err("Failed to read the compiler settings")?;
+        let sources = meta
+            .source_tree()
+            .entries
+            .into_iter()
+            .map(|entry| {
+                // the paths of the source tree are prefixed with the name of the contract
+                let path = entry.path.strip_prefix(&meta.contract_name).unwrap_or(&entry.path);
+                Ok((sanitize_path(path)?, entry.contents))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            name: meta.contract_name.clone(),
+            sources,
+            target: None,
+            compiler_version: meta.compiler_version()?,
+            optimizer: settings.optimizer.enabled.unwrap_or_default(),
+            optimizer_runs: settings.optimizer.runs.unwrap_or(200),
+            evm_version: settings.evm_version.map(|version| version.to_string()),
+            via_ir: settings.via_ir.unwrap_or_default(),
+            remappings: settings.remappings,
+        })
+    }
+
+    /// Normalizes the files of a contract verified on Sourcify.
+    fn from_sourcify(files: SourcifyFiles) -> Result<Self> {
+        let (metadata, files): (Vec<_>, Vec<_>) =
+            files.files.into_iter().partition(|file| file.name == "metadata.json");
+        let metadata = metadata.first().ok_or_else(|| eyre::eyre!("No metadata.json found"))?;
+        let metadata: SolcMetadata = serde_json::from_str(&metadata.content)
+            .wrap_err("Failed to parse the metadata of the contract")?;
+        if metadata.language != "Solidity" {
+            eyre::bail!(
+                "The contract is written in {}, only Solidity is supported",
+                metadata.language
+            )
+        }
+
+        // the sources are stored under `sources/` by their path at compilation
+        let sources = files
+            .into_iter()
+            .filter_map(|file| {
+                let path = file.path.split_once("/sources/")?.1.to_string();
+                Some((path, file.content))
+            })
+            .map(|(path, content)| Ok((sanitize_path(Path::new(&path))?, content)))
+            .collect::<Result<_>>()?;
+        let settings = metadata.settings;
+        let (target, name) = settings
+            .compilation_target
+            .iter()
+            .next()
+            .ok_or_else(|| eyre::eyre!("The metadata has no compilation target"))?;
+        Ok(Self {
+            name: name.clone(),
+            sources,
+            target: Some(sanitize_path(Path::new(target))?),
+            compiler_version: Version::parse(metadata.compiler.version.trim_start_matches('v'))?,
+            optimizer: settings.optimizer.enabled,
+            optimizer_runs: settings.optimizer.runs.unwrap_or(200),
+            evm_version: settings.evm_version,
+            via_ir: settings.via_ir,
+            remappings: settings
+                .remappings
+                .iter()
+                .map(|remapping| remapping.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|err| eyre::eyre!("Invalid remapping: {err}"))?,
+        })
+    }
+
+    /// Returns the path of the source that declares the contract.
+    fn target(&self) -> Option<PathBuf> {
+        if self.target.is_some() {
+            return self.target.clone()
+        }
+        let re = Regex::new(
+            r"(?m)^[ \t]*(?:abstract[ \t]+)?(?:contract|library|interface)[ \t]+([A-Za-z0-9_$]+)",
+        )
+        .expect("valid regex");
+        self.sources.iter().find_map(|(path, contents)| {
+            let declares_target = re.captures_iter(contents).any(|cap| cap[1] == *self.name);
+            declares_target.then(|| path.clone())
+        })
+    }
+
+    /// Flattens the sources, which are written to the project at `root`, into a single source.
+    fn flattened(self, root: &Path) -> Result<Self> {
+        let target = self
+            .target()
+            .ok_or_else(|| eyre::eyre!("No source declares the contract `{}`", self.name))?;
+        let paths = Config::load_with_root(root).project_paths();
+        let flattened = paths
+            .flatten(&root.join(SRC_DIR).join(target))
+            .map_err(|err| eyre::eyre!("Failed to flatten: {err}"))?;
+        let target = PathBuf::from(format!("{}.sol", self.name));
+        Ok(Self {
+            sources: BTreeMap::from([(target.clone(), flattened)]),
+            target: Some(target),
+            remappings: Vec::new(),
+            ..self
+        })
+    }
+
+    /// Returns all sources in a single string, each preceded by its path if there are several.
+    fn concatenated(&self) -> String {
+        if self.sources.len() == 1 {
+            return self.sources.values().next().cloned().unwrap_or_default()
+        }
+        self.sources
+            .iter()
+            .map(|(path, contents)| format!("// {}\n{contents}", path.display()))
+            .join("\n")
+    }
+}
+
+/// The files of a contract verified on Sourcify.
+#[derive(Debug, Deserialize)]
+struct SourcifyFiles {
+    files: Vec<SourcifyFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourcifyFile {
+    name: String,
+    path: String,
+    content: String,
+}
+
+/// The parts of the metadata of solc that configure the compiler.
+#[derive(Debug, Deserialize)]
+struct SolcMetadata {
+    compiler: SolcMetadataCompiler,
+    language: String,
+    settings: SolcMetadataSettings,
+}
+
+#[derive(Debug, Deserialize)]
+struct SolcMetadataCompiler {
+    version: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SolcMetadataSettings {
+    #[serde(default)]
+    compilation_target: BTreeMap<String, String>,
+    evm_version: Option<String>,
+    #[serde(default)]
+    optimizer: SolcMetadataOptimizer,
+    #[serde(default)]
+    remappings: Vec<String>,
+    #[serde(default, rename = "viaIR")]
+    via_ir: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SolcMetadataOptimizer {
+    #[serde(default)]
+    enabled: bool,
+    runs: Option<usize>,
+}
+
+/// Returns the config of the Etherscan compatible API at `url`.
+fn custom_explorer(url: String) -> ResolvedEtherscanConfig {
+    // without a chain the responses aren't cached with the ones of the chain's explorer
+    ResolvedEtherscanConfig {
+        api_url: url,
+        browser_url: None,
+        key: String::new(),
+        keys: Vec::new(),
+        chain: None,
+    }
+}
+
+/// Returns the Etherscan compatible API of the explorer of the chain if it's a Blockscout instance.
+fn blockscout_api_url(chain: Chain) -> Option<String> {
+    let explorer = chain_registry().get(chain.id())?.explorer.as_ref()?;
+    explorer.contains("blockscout").then(|| format!("{}/api", explorer.trim_end_matches('/')))
+}
+
+async fn fetch_etherscan_source(
+    explorer: ResolvedEtherscanConfig,
+    address: Address,
+) -> Result<VerifiedSource> {
+    let meta = EtherscanPool::new(explorer)?.contract_source_code(address).await?;
+    let meta =
+        meta.items.first().ok_or_else(|| eyre::eyre!("No source code found for {address}"))?;
+    VerifiedSource::from_metadata(meta)
+}
+
+async fn fetch_sourcify_source(
+    url: &str,
+    chain: Chain,
+    address: Address,
+) -> Result<VerifiedSource> {
+    let url = format!("{}/files/any/{}/{address}", url.trim_end_matches('/'), chain.id());
+    let response = reqwest::get(&url).await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        eyre::bail!("Contract {address} isn't verified on Sourcify for {chain}")
+    }
+    let files = response.error_for_status()?.json::<SourcifyFiles>().await?;
+    VerifiedSource::from_sourcify(files)
+}
+
+/// Writes the sources to the `src` dir of `root`, with a `foundry.toml` that compiles them with
+/// the settings of the verification.
+fn write_project(source: &VerifiedSource, root: &Path) -> Result<()> {
+    for (path, contents) in &source.sources {
+        let file = root.join(SRC_DIR).join(path);
+        fs::create_dir_all(file.parent().unwrap())?;
+        fs::write(&file, contents)?;
+    }
+
+    let config = Config::load_with_root(root);
+    fs::write(root.join(Config::FILE_NAME), config.into_basic().to_string_pretty()?)?;
+
+    let remappings = project_remappings(&source.remappings, source.sources.keys())
+        .into_iter()
+        .map(|remapping| remapping.to_string())
+        .collect::<toml_edit::Array>();
+    let version = &source.compiler_version;
+    Config::update_at(root, |_, doc| {
+        let profile = &mut doc[Config::PROFILE_SECTION][Config::DEFAULT_PROFILE.as_str().as_str()];
+        profile["solc"] =
+            toml_edit::value(format!("{}.{}.{}", version.major, version.minor, version.patch));
+        profile["auto_detect_remappings"] = toml_edit::value(false);
+        profile["remappings"] = toml_edit::value(remappings);
+        profile["optimizer"] = toml_edit::value(source.optimizer);
+        profile["optimizer_runs"] = toml_edit::value(source.optimizer_runs as i64);
+        profile["via_ir"] = toml_edit::value(source.via_ir);
+        if let Some(evm_version) = &source.evm_version {
+            profile["evm_version"] = toml_edit::value(evm_version.to_lowercase());
+        }
+        true
+    })
+}
+
+/// Returns the remappings for the sources written to the `src` dir.
+///
+/// The verified remappings are rebased onto `src`, and every top level directory of the source
+/// paths is remapped, so that direct imports like `@openzeppelin/contracts/...` still resolve.
+fn project_remappings<'a>(
+    remappings: &[Remapping],
+    paths: impl IntoIterator<Item = &'a PathBuf>,
+) -> Vec<Remapping> {
+    let mut rebased = remappings
+        .iter()
+        .map(|remapping| Remapping {
+            context: remapping.context.clone(),
+            name: remapping.name.clone(),
+            path: format!("{SRC_DIR}/{}", remapping.path),
+        })
+        .collect::<Vec<_>>();
+
+    let top_level_dirs = paths
+        .into_iter()
+        .filter_map(|path| {
+            let mut components = path.iter();
+            let first = components.next()?;
+            // files at the top level are imported relatively
+            components.next()?;
+            Some(first.to_string_lossy().to_string())
+        })
+        .collect::<BTreeSet<_>>();
+    for dir in top_level_dirs {
+        let name = format!("{dir}/");
+        if !rebased.iter().any(|remapping| remapping.name == name) {
+            rebased.push(Remapping { context: None, name, path: format!("{SRC_DIR}/{dir}/") });
+        }
+    }
+
+    rebased
+}
+
+/// Returns the path without root or parent components, so that it can't escape the project.
+fn sanitize_path(path: &Path) -> Result<PathBuf> {
+    let mut sanitized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => sanitized.push(part),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+            Component::ParentDir => {
+                eyre::bail!("Invalid source path `{}`", path.display())
+            }
+        }
+    }
+    Ok(sanitized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+    };
+
+    const ADDRESS: Address = Address::repeat_byte(0x11);
+
+    const TOKEN: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.19;
+
+import {ERC20} from "@openzeppelin/contracts/token/ERC20/ERC20.sol";
+
+contract Token is ERC20 {
+    constructor() ERC20("Token", "TKN") {}
+}
+"#;
+
+    const ERC20: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+abstract contract ERC20 {
+    constructor(string memory, string memory) {}
+}
+"#;
+
+    /// Spawns an explorer which answers every request with `response`, and returns its URL.
+    fn spawn_explorer(response: serde_json::Value) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let response = response.to_string();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0; 4096];
+                let _ = stream.read(&mut buf);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+                     Connection: close\r\n\r\n{response}",
+                    response.len()
+                );
+            }
+        });
+        url
+    }
+
+    /// The response of an Etherscan compatible API for the token, verified with a standard json.
+    fn etherscan_response() -> serde_json::Value {
+        let input = json!({
+            "language": "Solidity",
+            "sources": {
+                "contracts/Token.sol": { "content": TOKEN },
+                "@openzeppelin/contracts/token/ERC20/ERC20.sol": { "content": ERC20 },
+            },
+            "settings": {
+                "optimizer": { "enabled": true, "runs": 10000 },
+                "evmVersion": "paris",
+                "viaIR": true,
+                "remappings": [],
+            },
+        });
+        json!({
+            "status": "1",
+            "message": "OK",
+            "result": [{
+                "SourceCode": format!("{{{input}}}"),
+                "ABI": "[]",
+                "ContractName": "Token",
+                "CompilerVersion": "v0.8.19+commit.7dd6d404",
+                "OptimizationUsed": "1",
+                "Runs": "10000",
+                "ConstructorArguments": "",
+                "EVMVersion": "paris",
+                "Library": "",
+                "LicenseType": "MIT",
+                "Proxy": "0",
+                "Implementation": "",
+                "SwarmSource": "",
+            }],
+        })
+    }
+
+    fn expected_source() -> VerifiedSource {
+        VerifiedSource {
+            name: "Token".to_string(),
+            sources: BTreeMap::from([
+                (PathBuf::from("@openzeppelin/contracts/token/ERC20/ERC20.sol"), ERC20.to_string()),
+                (PathBuf::from("contracts/Token.sol"), TOKEN.to_string()),
+            ]),
+            target: None,
+            compiler_version: Version::parse("0.8.19+commit.7dd6d404").unwrap(),
+            optimizer: true,
+            optimizer_runs: 10000,
+            evm_version: Some("paris".to_string()),
+            via_ir: true,
+            remappings: Vec::new(),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_fetch_etherscan_source() {
+        let url = spawn_explorer(etherscan_response());
+        let explorer = custom_explorer(format!("{url}/api")).with_api_keys("KEY1,KEY2");
+        let source = fetch_etherscan_source(explorer, ADDRESS).await.unwrap();
+        assert_eq!(source, expected_source());
+        assert_eq!(source.target(), Some(PathBuf::from("contracts/Token.sol")));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_fetch_blockscout_source() {
+        // Blockscout serves an Etherscan compatible API without a key
+        let url = spawn_explorer(etherscan_response());
+        let explorer = custom_explorer(format!("{url}/api"));
+        let source = fetch_etherscan_source(explorer, ADDRESS).await.unwrap();
+        assert_eq!(source, expected_source());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_fetch_sourcify_source() {
+        let metadata = json!({
+            "compiler": { "version": "0.8.19+commit.7dd6d404" },
+            "language": "Solidity",
+            "settings": {
+                "compilationTarget": { "contracts/Token.sol": "Token" },
+                "evmVersion": "paris",
+                "optimizer": { "enabled": true, "runs": 10000 },
+                "remappings": [],
+                "viaIR": true,
+            },
+        });
+        let repo = format!("/data/repository/contracts/full_match/1/{ADDRESS}");
+        let url = spawn_explorer(json!({
+            "status": "full",
+            "files": [
+                {
+                    "name": "metadata.json",
+                    "path": format!("{repo}/metadata.json"),
+                    "content": metadata.to_string(),
+                },
+                {
+                    "name": "Token.sol",
+                    "path": format!("{repo}/sources/contracts/Token.sol"),
+                    "content": TOKEN,
+                },
+                {
+                    "name": "ERC20.sol",
+                    "path": format!("{repo}/sources/@openzeppelin/contracts/token/ERC20/ERC20.sol"),
+                    "content": ERC20,
+                },
+            ],
+        }));
+        let source = fetch_sourcify_source(&url, Chain::mainnet(), ADDRESS).await.unwrap();
+        let expected = VerifiedSource {
+            target: Some(PathBuf::from("contracts/Token.sol")),
+            ..expected_source()
+        };
+        assert_eq!(source, expected);
+    }
+
+    #[test]
+    fn can_write_project() {
+        let root = tempfile::tempdir().unwrap();
+        let source = VerifiedSource {
+            remappings: vec!["forge-std/=lib/forge-std/src/".parse().unwrap()],
+            ..expected_source()
+        };
+        write_project(&source, root.path()).unwrap();
+        assert_eq!(fs::read_to_string(root.path().join("src/contracts/Token.sol")).unwrap(), TOKEN);
+
+        let config: toml::Table =
+            fs::read_to_string(root.path().join(Config::FILE_NAME)).unwrap().parse().unwrap();
+        let profile = &config["profile"]["default"];
+        assert_eq!(profile["solc"].as_str(), Some("0.8.19"));
+        assert_eq!(profile["optimizer"].as_bool(), Some(true));
+        assert_eq!(profile["optimizer_runs"].as_integer(), Some(10000));
+        assert_eq!(profile["evm_version"].as_str(), Some("paris"));
+        assert_eq!(profile["via_ir"].as_bool(), Some(true));
+        assert_eq!(profile["auto_detect_remappings"].as_bool(), Some(false));
+        assert_eq!(
+            profile["remappings"],
+            toml::Value::from(vec![
+                "forge-std/=src/lib/forge-std/src/",
+                "@openzeppelin/=src/@openzeppelin/",
+                "contracts/=src/contracts/",
+            ])
+        );
+    }
+
+    #[test]
+    fn can_flatten_source() {
+        let root = tempfile::tempdir().unwrap();
+        let source = expected_source();
+        write_project(&source, root.path()).unwrap();
+        let flattened = source.flattened(root.path()).unwrap();
+        assert_eq!(flattened.target, Some(PathBuf::from("Token.sol")));
+        assert!(flattened.remappings.is_empty());
+        let contents = flattened.concatenated();
+        assert!(contents.contains("abstract contract ERC20"));
+        assert!(contents.contains("contract Token is ERC20"));
+        assert!(!contents.contains("import"));
+    }
+
+    #[test]
+    fn can_sanitize_paths() {
+        assert_eq!(
+            sanitize_path(Path::new("/contracts/./Token.sol")).unwrap(),
+            PathBuf::from("contracts/Token.sol")
+        );
+        assert!(sanitize_path(Path::new("../Token.sol")).is_err());
+    }
+}
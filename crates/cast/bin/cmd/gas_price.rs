@@ -0,0 +1,65 @@
+use cast::Cast;
+use clap::Parser;
+use ethers_core::types::BlockNumber;
+use eyre::Result;
+use foundry_cli::{opts::RpcOpts, utils};
+use foundry_config::Config;
+
+/// CLI arguments for `cast gas-price`.
+#[derive(Debug, Parser)]
+pub struct GasPriceArgs {
+    /// Print the base fees and the priority fee percentiles of this many blocks, and the fees
+    /// `cast send` would use by default.
+    ///
+    /// Before EIP-1559, the gas prices of the transactions of the blocks are sampled instead.
+    #[clap(long, value_name = "BLOCKS")]
+    history: Option<u64>,
+
+    /// The last block of the history.
+    ///
+    /// Can also be the tags earliest, finalized, safe, latest, or pending.
+    #[clap(long, value_name = "BLOCK", requires = "history")]
+    at: Option<BlockNumber>,
+
+    /// Print the history as JSON.
+    #[clap(long, short, requires = "history", help_heading = "Display options")]
+    json: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl GasPriceArgs {
+    pub async fn run(self) -> Result<()> {
+        let GasPriceArgs { history, at, json, rpc } = self;
+        let config = Config::from(&rpc);
+        let provider = utils::get_provider(&config)?;
+        let cast = Cast::new(provider);
+
+        let Some(blocks) = history else {
+            println!("{}", cast.gas_price().await?);
+            return Ok(())
+        };
+        let summary = cast.fee_history(blocks, at).await?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        } else {
+            println!("{}", summary.pretty());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_options_require_history() {
+        let args = GasPriceArgs::parse_from(["foundry-cli", "--history", "20", "--at", "100"]);
+        assert_eq!(args.history, Some(20));
+        assert_eq!(args.at, Some(BlockNumber::Number(100.into())));
+        assert!(GasPriceArgs::try_parse_from(["foundry-cli", "--at", "100"]).is_err());
+        assert!(GasPriceArgs::try_parse_from(["foundry-cli", "--json"]).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::opts::parse_slot;
+use alloy_dyn_abi::DynSolType;
+use alloy_primitives::{hex, B256, U256};
+use cast::{proxy, slots};
+use clap::{Parser, ValueEnum};
+use ethers_core::types::{BlockId, NameOrAddress};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_cli::{opts::RpcOpts, utils};
+use foundry_common::{
+    fmt::format_token_raw,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_config::Config;
+use std::str::FromStr;
+
+/// CLI arguments for `cast index`.
+#[derive(Debug, Parser)]
+pub struct IndexArgs {
+    /// The mapping key type, the mapping key and the storage slot of the mapping.
+    ///
+    /// With `--nested`, the key types and keys of the nested mappings from the outermost one,
+    /// followed by the slot, e.g. `address <OWNER> address <SPENDER> <SLOT>`.
+    #[clap(value_names = ["KEY_TYPE", "KEY", "SLOT"], num_args = 3.., required = true)]
+    args: Vec<String>,
+
+    /// Compute the slot of an entry in mappings of mappings.
+    #[clap(long)]
+    nested: bool,
+
+    #[clap(flatten)]
+    read: ReadSlotOpts,
+}
+
+impl IndexArgs {
+    pub async fn run(self) -> Result<()> {
+        let Self { args, nested, read } = self;
+        let Some((slot, keys)) = args.split_last() else { unreachable!("required by clap") };
+        if !nested && keys.len() != 2 {
+            eyre::bail!("Pass the key type, the key and the slot of the mapping, or `--nested`")
+        }
+        if keys.len() % 2 != 0 {
+            eyre::bail!("Pass the key types and the keys of the mappings, followed by the slot")
+        }
+        let keys = keys.chunks(2).map(|pair| (pair[0].as_str(), pair[1].as_str()));
+        let slot = slots::nested_mapping_slot(keys, parse_slot(slot)?)?;
+        read.print(slot, 0, 32, None).await
+    }
+}
+
+/// CLI arguments for `cast index-erc7201`.
+#[derive(Debug, Parser)]
+pub struct IndexErc7201Args {
+    /// The namespace of the `@custom:storage-location erc7201:<NAMESPACE>` annotation, e.g.
+    /// `openzeppelin.storage.ERC20`.
+    namespace: String,
+
+    #[clap(flatten)]
+    read: ReadSlotOpts,
+}
+
+impl IndexErc7201Args {
+    pub async fn run(self) -> Result<()> {
+        self.read.print(slots::erc7201_slot(&self.namespace), 0, 32, None).await
+    }
+}
+
+/// CLI arguments for `cast index-array`.
+#[derive(Debug, Parser)]
+pub struct IndexArrayArgs {
+    /// The storage slot of the dynamic array, which holds its length.
+    #[clap(value_parser = parse_slot)]
+    slot: B256,
+
+    /// The index of the element.
+    index: U256,
+
+    /// The size of the elements in bytes, e.g. 32 for `uint256`, 8 for `uint64` or 64 for
+    /// structs of two slots.
+    ///
+    /// Elements of up to 16 bytes are packed: the slot and the byte offset of the element in the
+    /// slot are printed.
+    #[clap(default_value_t = 32)]
+    element_size: u64,
+
+    #[clap(flatten)]
+    read: ReadSlotOpts,
+}
+
+impl IndexArrayArgs {
+    pub async fn run(self) -> Result<()> {
+        let Self { slot, index, element_size, read } = self;
+        if element_size == 0 {
+            eyre::bail!("The size of the elements must be at least 1 byte")
+        }
+        let (element_slot, offset) =
+            slots::dynamic_array_element_location(slot, element_size, index);
+        if let Some(address) = &read.address {
+            let length = U256::from_be_bytes(read.read(address, slot).await?.0);
+            if index >= length {
+                eyre::bail!("Index {index} is out of bounds of the array of length {length}")
+            }
+        } else if element_size <= 16 {
+            println!("{element_slot} {offset}");
+            return Ok(())
+        }
+        read.print(element_slot, offset, element_size.min(32), None).await
+    }
+}
+
+/// CLI arguments for `cast index-1967`.
+#[derive(Debug, Parser)]
+pub struct Index1967Args {
+    /// The EIP-1967 slot.
+    #[clap(value_enum)]
+    kind: Eip1967Slot,
+
+    #[clap(flatten)]
+    read: ReadSlotOpts,
+}
+
+/// The standard slots of EIP-1967 proxies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Eip1967Slot {
+    /// The implementation of transparent and UUPS proxies.
+    Implementation,
+    /// The admin of transparent proxies.
+    Admin,
+    /// The beacon of beacon proxies.
+    Beacon,
+}
+
+impl Index1967Args {
+    pub async fn run(self) -> Result<()> {
+        let slot = match self.kind {
+            Eip1967Slot::Implementation => proxy::IMPLEMENTATION_SLOT,
+            Eip1967Slot::Admin => proxy::ADMIN_SLOT,
+            Eip1967Slot::Beacon => proxy::BEACON_SLOT,
+        };
+        self.read.print(slot, 0, 20, Some("address")).await
+    }
+}
+
+/// Options to read the computed slot of a contract.
+#[derive(Debug, Parser)]
+pub struct ReadSlotOpts {
+    /// Read the slot of this contract and print its value instead of the slot.
+    #[clap(long, value_parser = NameOrAddress::from_str)]
+    address: Option<NameOrAddress>,
+
+    /// Decode the value as this value type, e.g. `uint64`, `address` or `bool`.
+    ///
+    /// The value is printed as hex by default.
+    #[clap(long = "type", value_name = "TYPE", requires = "address")]
+    value_type: Option<String>,
+
+    /// The block height to read at.
+    ///
+    /// Can also be the tags earliest, finalized, safe, latest, or pending.
+    #[clap(long, short = 'B', requires = "address")]
+    block: Option<BlockId>,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl ReadSlotOpts {
+    /// Prints the slot, or the value of `size` bytes at the offset of the slot if `--address` is
+    /// set.
+    async fn print(
+        &self,
+        slot: B256,
+        offset: u64,
+        size: u64,
+        default_type: Option<&str>,
+    ) -> Result<()> {
+        let Some(address) = &self.address else {
+            println!("{slot}");
+            return Ok(())
+        };
+        let word = self.read(address, slot).await?;
+        let value = slots::packed_value(&word, offset, size);
+        match self.value_type.as_deref().or(default_type) {
+            Some(ty) => {
+                let value = slots::decode_storage_value(&DynSolType::parse(ty)?, value)?;
+                println!("{}", format_token_raw(&value));
+            }
+            None => println!("{}", hex::encode_prefixed(value)),
+        }
+        Ok(())
+    }
+
+    async fn read(&self, address: &NameOrAddress, slot: B256) -> Result<B256> {
+        let provider = utils::get_provider(&Config::from(&self.rpc))?;
+        let value = provider.get_storage_at(address.clone(), slot.to_ethers(), self.block).await?;
+        Ok(value.to_alloy())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_index_args() {
+        let args =
+            IndexArgs::parse_from(["foundry-cli", "--nested", "address", "0x1", "uint8", "2", "3"]);
+        assert!(args.nested);
+        assert_eq!(args.args.len(), 5);
+        assert!(IndexArgs::try_parse_from(["foundry-cli", "address", "0x1"]).is_err());
+        let args = ["foundry-cli", "--type", "bool", "uint256", "1", "2"];
+        assert!(IndexArgs::try_parse_from(args).is_err());
+
+        let args = Index1967Args::parse_from(["foundry-cli", "admin", "--address", "0x1"]);
+        assert_eq!(args.kind, Eip1967Slot::Admin);
+        assert!(Index1967Args::try_parse_from(["foundry-cli", "rollback"]).is_err());
+    }
+}
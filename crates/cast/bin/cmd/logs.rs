@@ -1,5 +1,6 @@
-use cast::Cast;
-use clap::Parser;
+use alloy_json_abi::ContractObject;
+use cast::{logs::LogDecoder, Cast};
+use clap::{Parser, ValueHint};
 use ethers_core::{
     abi::{
         token::{LenientTokenizer, StrictTokenizer, Tokenizer},
@@ -12,9 +13,10 @@ use ethers_core::{
 use ethers_providers::Middleware;
 use eyre::{Result, WrapErr};
 use foundry_cli::{opts::EthereumOpts, utils};
+use foundry_common::{abi::get_event, fs};
 use foundry_config::Config;
 use itertools::Itertools;
-use std::{io, str::FromStr};
+use std::{io, path::PathBuf, str::FromStr, time::Duration};
 
 /// CLI arguments for `cast logs`.
 #[derive(Debug, Parser)]
@@ -53,6 +55,31 @@ pub struct LogsArgs {
     #[clap(long)]
     subscribe: bool,
 
+    /// Print the logs since FROM_BLOCK, then follow the new logs until interrupted.
+    ///
+    /// The logs are streamed with `eth_subscribe` if the RPC endpoint supports it, otherwise the
+    /// endpoint is polled every POLL_INTERVAL. With `--json`, each log is printed as a JSON line.
+    #[clap(long, conflicts_with_all = ["subscribe", "to_block"])]
+    follow: bool,
+
+    /// The interval between two polls of the RPC endpoint when following logs, in seconds or
+    /// with a `ms` suffix.
+    #[clap(
+        long,
+        requires = "follow",
+        value_parser = utils::parse_delay,
+        default_value = "2",
+        value_name = "DELAY"
+    )]
+    poll_interval: Duration,
+
+    /// The path of an ABI or artifact file to decode the followed logs with, the events are
+    /// selected by the first topic of the logs.
+    ///
+    /// The logs are also decoded with the event of SIG_OR_TOPIC if it's a signature.
+    #[clap(long, requires = "follow", value_hint = ValueHint::FilePath, value_name = "PATH")]
+    abi: Option<PathBuf>,
+
     /// Print the logs as JSON.s
     #[clap(long, short, help_heading = "Display options")]
     json: bool,
@@ -70,6 +97,9 @@ impl LogsArgs {
             sig_or_topic,
             topics_or_args,
             subscribe,
+            follow,
+            poll_interval,
+            abi,
             json,
             eth,
         } = self;
@@ -93,8 +123,29 @@ impl LogsArgs {
         let from_block = cast.convert_block_number(from_block).await?;
         let to_block = cast.convert_block_number(to_block).await?;
 
+        let mut events = vec![];
+        if follow {
+            if let Some(event) = sig_or_topic.as_deref().and_then(|sig| get_event(sig).ok()) {
+                events.push(event);
+            }
+            if let Some(path) = &abi {
+                let obj: ContractObject = serde_json::from_str(&fs::read_to_string(path)?)?;
+                let abi = obj
+                    .abi
+                    .ok_or_else(|| eyre::eyre!("could not find ABI in file {}", path.display()))?;
+                events.extend(abi.events().cloned());
+            }
+        }
+
         let filter = build_filter(from_block, to_block, address, sig_or_topic, topics_or_args)?;
 
+        if follow {
+            let decoder = LogDecoder::new(events);
+            let mut stdout = io::stdout();
+            cast.follow_logs(filter, &decoder, &mut stdout, json, poll_interval).await?;
+            return Ok(())
+        }
+
         if !subscribe {
             let logs = cast.filter_logs(filter, json).await?;
 
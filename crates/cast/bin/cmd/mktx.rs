@@ -0,0 +1,276 @@
+use cast::{
+    blob::{self, BlobSidecar},
+    read_access_list, Cast, TxBuilder,
+};
+use clap::Parser;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, AccessList, Address, NameOrAddress,
+};
+use ethers_providers::Middleware;
+use ethers_signers::Signer;
+use eyre::Result;
+use foundry_cli::{
+    opts::{EthereumOpts, HardwareDerivation, TransactionOpts, WalletSigner},
+    utils::{self, parse_ether_value},
+};
+use foundry_common::types::{ToAlloy, ToEthers};
+use foundry_config::{Chain, Config};
+use std::{path::PathBuf, str::FromStr};
+
+/// CLI arguments for `cast mktx`.
+#[derive(Debug, Parser)]
+pub struct MakeTxArgs {
+    /// The destination of the transaction.
+    ///
+    /// If not provided, you must use `cast mktx --create`.
+    #[clap(value_parser = NameOrAddress::from_str)]
+    to: Option<NameOrAddress>,
+
+    /// The signature of the function to call.
+    sig: Option<String>,
+
+    /// The arguments of the function to call.
+    args: Vec<String>,
+
+    #[clap(subcommand)]
+    command: Option<MakeTxSubcommands>,
+
+    #[clap(flatten)]
+    tx: TransactionOpts,
+
+    /// The access list of the transaction, e.g. the output of `cast access-list --json`.
+    ///
+    /// Either the JSON itself, the path of a JSON file, or `-` to read it from stdin. Access lists
+    /// are sent in EIP-1559 transactions, or in EIP-2930 transactions on chains without EIP-1559.
+    #[clap(
+        long,
+        value_parser = read_access_list,
+        value_name = "JSON|PATH",
+        conflicts_with_all = ["blob", "legacy"]
+    )]
+    access_list: Option<AccessList>,
+
+    #[clap(flatten)]
+    blob: BlobOpts,
+
+    #[clap(flatten)]
+    eth: EthereumOpts,
+}
+
+#[derive(Debug, Parser)]
+pub enum MakeTxSubcommands {
+    /// Use to deploy raw contract bytecode.
+    #[clap(name = "--create")]
+    Create {
+        /// The bytecode of the contract to deploy.
+        code: String,
+
+        /// The signature of the function to call.
+        sig: Option<String>,
+
+        /// The arguments of the function to call.
+        args: Vec<String>,
+    },
+}
+
+/// The options of EIP-4844 blob transactions.
+#[derive(Clone, Debug, Default, Parser)]
+#[clap(next_help_heading = "Blob options")]
+pub struct BlobOpts {
+    /// Send an EIP-4844 blob transaction carrying the data of the blob files.
+    #[clap(long, requires = "blob_files")]
+    pub blob: bool,
+
+    /// A file whose raw bytes are chunked into blobs, can be repeated.
+    ///
+    /// A blob holds 126976 bytes of data and a transaction at most 6 blobs.
+    #[clap(long = "blob-file", value_name = "PATH", requires = "blob")]
+    pub blob_files: Vec<PathBuf>,
+
+    /// The max fee per blob gas, either specified in wei, or as a string with a unit type.
+    ///
+    /// Defaults to twice the blob base fee of the node.
+    #[clap(long, requires = "blob", value_parser = parse_ether_value, value_name = "PRICE")]
+    pub max_fee_per_blob_gas: Option<alloy_primitives::U256>,
+
+    /// The KZG trusted setup file used to compute the commitments and proofs of the blobs.
+    ///
+    /// Defaults to the setup of the Ethereum KZG ceremony.
+    #[clap(long, requires = "blob", value_name = "PATH")]
+    pub kzg_setup: Option<PathBuf>,
+}
+
+impl BlobOpts {
+    /// Reads the blob files and computes the commitments and proofs of their blobs.
+    pub fn sidecar(&self) -> Result<BlobSidecar> {
+        let blobs = blob::blobs_from_files(&self.blob_files)?;
+        let settings = blob::load_kzg_settings(self.kzg_setup.as_deref())?;
+        BlobSidecar::new(blobs, settings.get())
+    }
+}
+
+impl MakeTxArgs {
+    pub async fn run(self) -> Result<()> {
+        let MakeTxArgs { to, mut sig, mut args, command, tx, access_list, blob, eth } = self;
+
+        let code = if let Some(MakeTxSubcommands::Create {
+            code,
+            sig: constructor_sig,
+            args: constructor_args,
+        }) = command
+        {
+            sig = constructor_sig;
+            args = constructor_args;
+            Some(code)
+        } else {
+            None
+        };
+
+        if code.is_none() && to.is_none() {
+            eyre::bail!("Must specify a recipient address or contract code to deploy");
+        }
+        if blob.blob && code.is_some() {
+            eyre::bail!("Blob transactions can't deploy contracts");
+        }
+
+        let config = Config::from(&eth);
+        let provider = utils::get_provider(&config)?;
+        let chain = utils::get_chain(config.chain, &provider).await?;
+        let api_key = config.get_etherscan_api_key(Some(chain));
+
+        let signer = eth.wallet.signer(chain.id()).await?;
+        let from = signer.address();
+        ensure_access_list_signable(&signer, chain, access_list.as_ref())?;
+
+        let mut tx = build_tx(
+            &provider,
+            from,
+            to,
+            code,
+            (sig.unwrap_or_default(), args),
+            tx,
+            access_list,
+            chain,
+            api_key,
+        )
+        .await?;
+
+        if blob.blob {
+            let (raw_tx, sidecar) = sign_blob_tx(provider, &signer, tx, &blob).await?;
+            print_versioned_hashes(&sidecar);
+            println!("{raw_tx}");
+            return Ok(())
+        }
+
+        if tx.nonce().is_none() {
+            tx.set_nonce(provider.get_transaction_count(from, None).await?);
+        }
+        provider.fill_transaction(&mut tx, None).await?;
+        let signature = signer.sign_transaction(&tx).await?;
+        println!("{}", tx.rlp_signed(&signature));
+
+        Ok(())
+    }
+}
+
+/// Builds the transaction of `cast mktx` and `cast send` from their arguments.
+#[allow(clippy::too_many_arguments)]
+pub async fn build_tx<M: Middleware, T: Into<NameOrAddress>>(
+    provider: &M,
+    from: Address,
+    to: Option<T>,
+    code: Option<String>,
+    args: (String, Vec<String>),
+    tx: TransactionOpts,
+    access_list: Option<AccessList>,
+    chain: Chain,
+    etherscan_api_key: Option<String>,
+) -> Result<TypedTransaction>
+where
+    M::Error: 'static,
+{
+    let (sig, params) = args;
+    let params = if !sig.is_empty() { Some((&sig[..], params)) } else { None };
+    let mut builder = TxBuilder::new(provider, from, to, chain, tx.legacy).await?;
+    builder
+        .etherscan_api_key(etherscan_api_key)
+        .gas(tx.gas_limit)
+        .gas_price(tx.gas_price)
+        .priority_gas_price(tx.priority_gas_price)
+        .value(tx.value)
+        .nonce(tx.nonce)
+        .access_list(access_list);
+
+    if let Some(code) = code {
+        let mut data = hex::decode(code)?;
+
+        if let Some((sig, args)) = params {
+            let (mut sigdata, _) = builder.create_args(sig, args).await?;
+            data.append(&mut sigdata);
+        }
+
+        builder.set_data(data);
+    } else {
+        builder.args(params).await?;
+    };
+    let (tx, _) = builder.build();
+    Ok(tx)
+}
+
+/// Signs the transaction as a blob transaction carrying the blob files, returns its network
+/// encoding and its sidecar.
+///
+/// Blob transactions can't be signed by hardware wallets, which only sign the transaction types
+/// they know of.
+pub async fn sign_blob_tx<M: Middleware>(
+    provider: M,
+    signer: &WalletSigner,
+    tx: TypedTransaction,
+    blob: &BlobOpts,
+) -> Result<(alloy_primitives::Bytes, BlobSidecar)>
+where
+    M::Error: 'static,
+{
+    let WalletSigner::Local(wallet) = signer else {
+        eyre::bail!("Blob transactions can only be signed with a private key, mnemonic or keystore")
+    };
+    let sidecar = blob.sidecar()?;
+    let cast = Cast::new(provider);
+    let tx = cast
+        .blob_transaction(tx, wallet.address().to_alloy(), blob.max_fee_per_blob_gas, &sidecar)
+        .await?;
+    let signature = wallet.sign_hash(tx.signature_hash().to_ethers())?;
+    Ok((tx.encode_network(&signature, &sidecar), sidecar))
+}
+
+/// Checks that the signer can sign the transaction carrying the access list.
+///
+/// Trezor devices sign EIP-2930 transactions as legacy transactions, which drops their access
+/// list, so access lists can only be sent with them on chains with EIP-1559.
+pub fn ensure_access_list_signable(
+    signer: &WalletSigner,
+    chain: Chain,
+    access_list: Option<&AccessList>,
+) -> Result<()> {
+    let trezor = match signer {
+        WalletSigner::Trezor(_) => true,
+        WalletSigner::Hardware(account) => {
+            matches!(account.derivation(), HardwareDerivation::Trezor(_))
+        }
+        _ => false,
+    };
+    if trezor && access_list.is_some() && chain.is_legacy() {
+        eyre::bail!(
+            "Trezor devices can't sign EIP-2930 transactions, access lists can only be sent with \
+             them on chains with EIP-1559"
+        )
+    }
+    Ok(())
+}
+
+/// Prints the versioned hashes of the blobs to stderr, so stdout stays parseable.
+pub fn print_versioned_hashes(sidecar: &BlobSidecar) {
+    for hash in sidecar.versioned_hashes() {
+        eprintln!("Blob versioned hash: {hash}");
+    }
+}
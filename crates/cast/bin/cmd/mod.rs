@@ -7,14 +7,30 @@
 
 pub mod access_list;
 pub mod bind;
+pub mod block;
 pub mod call;
+pub mod calldata_decode;
+pub mod chain;
+pub mod constructor_args;
 pub mod create2;
+pub mod creation_code;
+pub mod ens;
 pub mod estimate;
+pub mod etherscan_source;
 pub mod find_block;
+pub mod gas_price;
+pub mod index;
 pub mod interface;
 pub mod logs;
+pub mod mktx;
+pub mod multicall;
+pub mod pending;
+pub mod receipt;
 pub mod rpc;
 pub mod run;
+pub mod selectors;
 pub mod send;
+pub mod simulate;
 pub mod storage;
+pub mod storage_layout;
 pub mod wallet;
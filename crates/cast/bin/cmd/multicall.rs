@@ -0,0 +1,133 @@
+use alloy_primitives::Address;
+use cast::{
+    multicall::{
+        batch_eth_calls, format_multicall_results, read_calls, CallResult, MulticallCall,
+        MULTICALL3_ADDRESS,
+    },
+    Cast,
+};
+use clap::Parser;
+use ethers_core::types::{BlockId, BlockNumber};
+use eyre::{Result, WrapErr};
+use foundry_cli::{opts::RpcOpts, utils};
+use foundry_config::Config;
+use std::path::PathBuf;
+use yansi::Paint;
+
+/// CLI arguments for `cast multicall`.
+#[derive(Debug, Parser)]
+pub struct MulticallArgs {
+    /// A call of the batch as `<TO> <SIG> [ARGS]...`, can be repeated.
+    ///
+    /// The signature can include the return types to decode the result, e.g.
+    /// `balanceOf(address)(uint256)`.
+    #[clap(
+        long = "call",
+        num_args = 2..,
+        value_names = ["TO", "SIG", "ARGS"],
+        required_unless_present = "calls"
+    )]
+    call: Vec<Vec<String>>,
+
+    /// A JSON array or a TOML file of `[[call]]` tables of the calls of the batch, made after the
+    /// calls of `--call`.
+    ///
+    /// A call is an object with `to`, `sig`, and optionally `args` and `allowFailure`.
+    #[clap(long, value_name = "PATH")]
+    calls: Option<PathBuf>,
+
+    /// Report the failed calls instead of aborting the batch.
+    #[clap(long)]
+    allow_failure: bool,
+
+    /// The address of the Multicall3 contract, for chains without the canonical deployment.
+    ///
+    /// If the chain has no Multicall3 contract, the calls are sent as a JSON-RPC batch.
+    #[clap(long, value_name = "ADDRESS", default_value_t = MULTICALL3_ADDRESS)]
+    multicall_address: Address,
+
+    /// The block height to query at.
+    ///
+    /// Can also be the tags earliest, finalized, safe, latest, or pending.
+    #[clap(long, short = 'B')]
+    block: Option<BlockId>,
+
+    /// Print the results as a JSON array.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl MulticallArgs {
+    pub async fn run(self) -> Result<()> {
+        let MulticallArgs {
+            call,
+            calls: calls_file,
+            allow_failure,
+            multicall_address,
+            block,
+            json,
+            rpc,
+        } = self;
+
+        let mut calls =
+            call.iter().map(|call| MulticallCall::from_args(call)).collect::<Result<Vec<_>>>()?;
+        if let Some(path) = calls_file {
+            calls.extend(read_calls(&path)?);
+        }
+        if calls.is_empty() {
+            eyre::bail!("No calls to make, pass them with `--call` or `--calls`")
+        }
+        let data = calls
+            .iter()
+            .enumerate()
+            .map(|(i, call)| Ok((call.to, call.calldata().wrap_err_with(|| format!("call {i}"))?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let config = Config::from(&rpc);
+        let provider = utils::get_provider(&config)?;
+        let cast = Cast::new(&provider);
+        let outcomes = if cast.has_multicall(multicall_address, block).await? {
+            cast.multicall(multicall_address, &data, block).await?
+        } else {
+            eprintln!(
+                "{}",
+                Paint::yellow(format!(
+                    "Warning: there is no Multicall3 contract at {multicall_address}, sending the \
+                     calls as a JSON-RPC batch"
+                ))
+            );
+            // batches are only sent to endpoints without authentication, other transports make
+            // the calls concurrently
+            let url = config.get_rpc_url_or_localhost_http()?;
+            if url.starts_with("http") && config.eth_rpc_jwt.is_none() {
+                let block = block.unwrap_or_else(|| BlockNumber::Latest.into());
+                batch_eth_calls(&url, &data, block).await?
+            } else {
+                cast.eth_calls(&data, block).await
+            }
+        };
+
+        let results = calls
+            .iter()
+            .zip(outcomes)
+            .map(|(call, outcome)| CallResult::new(call, outcome))
+            .collect::<Vec<_>>();
+        for (i, (call, result)) in calls.iter().zip(&results).enumerate() {
+            if let Err(err) = &result.decoded {
+                if !call.allow_failure.unwrap_or(allow_failure) {
+                    eyre::bail!(
+                        "Call {i} to {} failed: {err}\nPass `--allow-failure` to report failed \
+                         calls instead",
+                        call.to
+                    )
+                }
+            }
+        }
+
+        println!("{}", format_multicall_results(&calls, &results, json)?);
+        Ok(())
+    }
+}
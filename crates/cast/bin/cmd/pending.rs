@@ -0,0 +1,44 @@
+use cast::Cast;
+use clap::Parser;
+use ethers_core::types::NameOrAddress;
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_cli::{opts::RpcOpts, utils};
+use foundry_common::types::ToAlloy;
+use foundry_config::Config;
+use std::str::FromStr;
+
+/// CLI arguments for `cast pending`.
+#[derive(Debug, Parser)]
+pub struct PendingArgs {
+    /// The account to list the pending transactions of.
+    #[clap(value_parser = NameOrAddress::from_str)]
+    who: NameOrAddress,
+
+    /// Print the nonces and the transactions as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl PendingArgs {
+    pub async fn run(self) -> Result<()> {
+        let PendingArgs { who, json, rpc } = self;
+        let config = Config::from(&rpc);
+        let provider = utils::get_provider(&config)?;
+        let address = match who {
+            NameOrAddress::Name(name) => provider.resolve_name(&name).await?,
+            NameOrAddress::Address(address) => address,
+        };
+
+        let pool = Cast::new(&provider).account_pool(address.to_alloy()).await?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&pool)?);
+        } else {
+            println!("{}", pool.pretty());
+        }
+        Ok(())
+    }
+}
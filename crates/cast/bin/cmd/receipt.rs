@@ -0,0 +1,129 @@
+use super::constructor_args::read_abi;
+use alloy_primitives::Address;
+use cast::{logs::LogDecoder, receipt::DecodedReceipt, Cast};
+use clap::Parser;
+use eyre::{Result, WrapErr};
+use foundry_cli::{
+    opts::{EtherscanOpts, RpcOpts},
+    utils,
+};
+use foundry_common::fmt::UIfmt;
+use foundry_config::{
+    chains::etherscan_client,
+    figment::{self, value::Dict, Metadata, Profile},
+    impl_figment_convert_cast, Config,
+};
+use std::path::Path;
+
+/// CLI arguments for `cast receipt`.
+#[derive(Clone, Debug, Parser)]
+pub struct ReceiptArgs {
+    /// The transaction hash.
+    tx_hash: String,
+
+    /// If specified, only get the given field of the receipt, e.g. status, gasUsed or logs.
+    field: Option<String>,
+
+    /// Only get the given field of the receipt, e.g. status, gasUsed or logs.
+    #[clap(long = "field", value_name = "FIELD", conflicts_with = "field")]
+    field_name: Option<String>,
+
+    /// The number of confirmations to wait for before the receipt is fetched.
+    #[clap(long, default_value = "1")]
+    confirmations: usize,
+
+    /// Exit immediately if the transaction was not found.
+    #[clap(long = "async", env = "CAST_ASYNC", name = "async", alias = "cast-async")]
+    cast_async: bool,
+
+    /// The ABI or artifact to decode the logs with, or the address of a verified contract to
+    /// fetch the ABI of.
+    ///
+    /// The logs which can't be decoded are printed with their raw topics and data.
+    #[clap(long, value_name = "PATH|ADDRESS", conflicts_with = "raw")]
+    abi: Option<String>,
+
+    /// Print the consensus encoding of the receipt, prefixed by the type of the transaction.
+    #[clap(long, conflicts_with_all = ["field", "field_name", "json"])]
+    raw: bool,
+
+    /// Print as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+
+    #[clap(flatten)]
+    etherscan: EtherscanOpts,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl_figment_convert_cast!(ReceiptArgs);
+
+impl figment::Provider for ReceiptArgs {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("ReceiptArgs")
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        let mut dict = self.rpc.dict();
+        dict.extend(self.etherscan.dict());
+        Ok(figment::value::Map::from([(Config::selected_profile(), dict)]))
+    }
+}
+
+impl ReceiptArgs {
+    pub async fn run(self) -> Result<()> {
+        let config = Config::from(&self);
+        let ReceiptArgs {
+            tx_hash,
+            field,
+            field_name,
+            confirmations,
+            cast_async,
+            abi,
+            raw,
+            json,
+            ..
+        } = self;
+        let field = field.or(field_name);
+        let provider = utils::get_provider(&config)?;
+        let cast = Cast::new(&provider);
+
+        if raw {
+            let tx_hash = tx_hash.parse().wrap_err("invalid tx hash")?;
+            println!("{}", cast.raw_receipt(tx_hash, confirmations, cast_async).await?);
+            return Ok(())
+        }
+        let Some(abi) = abi else {
+            println!("{}", cast.receipt(tx_hash, field, confirmations, cast_async, json).await?);
+            return Ok(())
+        };
+
+        let abi = if Path::new(&abi).exists() {
+            read_abi(Path::new(&abi))?.0
+        } else {
+            let address: Address =
+                abi.parse().wrap_err("the ABI is neither a file nor an address")?;
+            let chain = utils::get_chain(config.chain, &provider).await?;
+            let api_key = config.get_etherscan_api_key(Some(chain)).ok_or_else(|| {
+                eyre::eyre!("an Etherscan API key is required to fetch the ABI of {address}")
+            })?;
+            etherscan_client(chain, api_key)?
+                .contract_abi(address)
+                .await
+                .wrap_err_with(|| format!("could not fetch the verified ABI of {address}"))?
+        };
+        let decoder = LogDecoder::new(abi.events().cloned());
+
+        let tx_hash = tx_hash.parse().wrap_err("invalid tx hash")?;
+        let receipt = cast.transaction_receipt(tx_hash, confirmations, cast_async).await?;
+        let receipt = DecodedReceipt::new(receipt, &decoder);
+        match field {
+            Some(field) => println!("{}", receipt.field(&field, json)?),
+            None if json => println!("{}", receipt.to_json()?),
+            None => println!("{}", receipt.pretty()),
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use alloy_primitives::U256;
-use clap::Parser;
+use clap::{Parser, ValueHint};
 use ethers_providers::Middleware;
 use eyre::{Result, WrapErr};
 use foundry_cli::{
@@ -8,14 +8,20 @@ use foundry_cli::{
     update_progress, utils,
     utils::{handle_traces, TraceResult},
 };
-use foundry_common::{is_known_system_sender, types::ToAlloy, SYSTEM_TRANSACTION_TYPE};
+use foundry_common::{
+    compile::ProjectCompiler, is_known_system_sender, types::ToAlloy, ContractsByArtifact,
+    SYSTEM_TRANSACTION_TYPE,
+};
 use foundry_compilers::EvmVersion;
 use foundry_config::{find_project_root_path, Config};
 use foundry_evm::{
     executors::{EvmError, TracingExecutor},
     opts::EvmOpts,
+    traces::TraceRenderOptions,
     utils::configure_tx_env,
 };
+use std::path::PathBuf;
+use yansi::Paint;
 
 /// CLI arguments for `cast run`.
 #[derive(Clone, Debug, Parser)]
@@ -43,10 +49,30 @@ pub struct RunArgs {
 
     /// Label addresses in the trace.
     ///
-    /// Example: 0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045:vitalik.eth
+    /// Labels from the `[labels]` section of the config are applied as well.
+    ///
+    /// Example: 0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045=vitalik.eth
     #[clap(long, short)]
     label: Vec<String>,
 
+    /// Collapse consecutive calls to the same contract with the same calldata and result in the
+    /// trace into one, annotated with the number of calls.
+    #[clap(long)]
+    collapse_repeated_calls: bool,
+
+    /// Hide the calls nested deeper than this in the trace, summarizing their number and gas.
+    ///
+    /// The top-level call is at depth 0.
+    #[clap(long, value_name = "DEPTH")]
+    max_trace_depth: Option<usize>,
+
+    /// The project whose artifacts are used to identify contracts and decode their calls, events
+    /// and errors.
+    ///
+    /// Defaults to the current project if it has a `foundry.toml`.
+    #[clap(long, value_hint = ValueHint::DirPath, value_name = "PATH")]
+    project: Option<PathBuf>,
+
     #[clap(flatten)]
     rpc: RpcOpts,
 
@@ -79,11 +105,29 @@ impl RunArgs {
     ///
     /// Note: This executes the transaction(s) as is: Cheatcodes are disabled
     pub async fn run(self) -> Result<()> {
-        let figment =
-            Config::figment_with_root(find_project_root_path(None).unwrap()).merge(self.rpc);
+        let root = match &self.project {
+            Some(project) => dunce::canonicalize(project)
+                .wrap_err_with(|| format!("invalid project path: {}", project.display()))?,
+            None => find_project_root_path(None).unwrap(),
+        };
+        let figment = Config::figment_with_root(&root).merge(self.rpc);
         let evm_opts = figment.extract::<EvmOpts>()?;
         let mut config = Config::try_from(figment)?.sanitized();
 
+        let known_contracts = if self.project.is_some() || root.join(Config::FILE_NAME).exists() {
+            match project_contracts(&config) {
+                Ok(contracts) => Some(contracts),
+                Err(err) if self.project.is_none() => {
+                    let msg = format!("Warning: failed to compile the local project: {err}");
+                    eprintln!("{}", Paint::yellow(msg));
+                    None
+                }
+                Err(err) => return Err(err),
+            }
+        } else {
+            None
+        };
+
         let compute_units_per_second =
             if self.no_rate_limit { Some(u64::MAX) } else { self.compute_units_per_second };
 
@@ -205,8 +249,45 @@ impl RunArgs {
             }
         };
 
-        handle_traces(result, &config, chain, self.label, self.debug).await?;
+        let render_options = TraceRenderOptions {
+            collapse_repeated: self.collapse_repeated_calls,
+            max_depth: self.max_trace_depth,
+            ..Default::default()
+        };
+        handle_traces(
+            result,
+            &config,
+            chain,
+            self.label,
+            self.debug,
+            known_contracts.as_ref(),
+            &render_options,
+        )
+        .await?;
 
         Ok(())
     }
 }
+
+/// Compiles the project and returns its contracts, with their deployed bytecode if it's linked.
+fn project_contracts(config: &Config) -> Result<ContractsByArtifact> {
+    let project = config.project()?;
+    if !project.paths.has_input_files() {
+        return Ok(Default::default())
+    }
+    let output = ProjectCompiler::new().quiet(true).compile(&project)?;
+    let contracts = output
+        .with_stripped_file_prefixes(project.root())
+        .into_artifacts()
+        .filter_map(|(id, artifact)| {
+            let contract = artifact.into_contract_bytecode();
+            let code = contract
+                .deployed_bytecode
+                .and_then(|deployed| deployed.bytecode)
+                .and_then(|bytecode| bytecode.object.into_bytes())
+                .unwrap_or_default();
+            Some((id, (contract.abi?, code.to_vec())))
+        })
+        .collect();
+    Ok(ContractsByArtifact(contracts))
+}
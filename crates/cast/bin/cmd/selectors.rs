@@ -0,0 +1,112 @@
+use alloy_primitives::hex;
+use cast::dispatcher::{recover_dispatcher, Dispatcher};
+use clap::Parser;
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use ethers_core::types::{BlockId, NameOrAddress};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_cli::{opts::RpcOpts, utils};
+use foundry_config::Config;
+use foundry_evm::traces::identifier::SignaturesIdentifier;
+use std::str::FromStr;
+
+/// CLI arguments for `cast selectors`.
+#[derive(Debug, Parser)]
+pub struct SelectorsArgs {
+    /// The address or the ENS name of the contract, or its hex encoded runtime bytecode.
+    #[clap(value_name = "ADDRESS|BYTECODE")]
+    target: String,
+
+    /// Resolve the function signatures of the selectors using the local project, the signature
+    /// cache and https://openchain.xyz.
+    #[clap(long, short)]
+    resolve: bool,
+
+    /// The block height to read the code of the contract at.
+    ///
+    /// Can also be the tags earliest, finalized, safe, latest, or pending.
+    #[clap(long, short = 'B')]
+    block: Option<BlockId>,
+
+    /// Print the functions as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl SelectorsArgs {
+    pub async fn run(self) -> Result<()> {
+        let SelectorsArgs { target, resolve, block, json, rpc } = self;
+        let config = Config::from(&rpc);
+
+        // a contract of 20 bytes of code can't have a dispatcher, so it's an address
+        let code = match hex::decode(&target) {
+            Ok(code) if code.len() != 20 => code,
+            _ => {
+                let provider = utils::get_provider(&config)?;
+                let code = provider.get_code(NameOrAddress::from_str(&target)?, block).await?;
+                if code.is_empty() {
+                    eyre::bail!("{target} has no code")
+                }
+                code.to_vec()
+            }
+        };
+
+        let mut dispatcher = recover_dispatcher(&code);
+        if resolve {
+            let identifier =
+                SignaturesIdentifier::new(Config::foundry_cache_dir(), config.offline)?;
+            let mut identifier = identifier.write().await;
+            identifier.add_local_artifacts(&Config::load().project_paths().artifacts);
+            for function in &mut dispatcher.functions {
+                function.signatures = identifier.function_signatures(&function.selector[..]).await;
+            }
+        }
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&dispatcher)?);
+        } else {
+            println!("{}", pretty_dispatcher(&dispatcher, resolve));
+        }
+        Ok(())
+    }
+}
+
+fn pretty_dispatcher(dispatcher: &Dispatcher, resolve: bool) -> String {
+    if let Some(implementation) = dispatcher.minimal_proxy_implementation {
+        return format!(
+            "EIP-1167 minimal proxy of {implementation}, whose functions are the ones of the \
+             implementation"
+        )
+    }
+
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN);
+    let mut header = vec!["Selector", "Arguments", "Jump destination"];
+    if resolve {
+        header.push("Signatures");
+    }
+    table.set_header(header);
+    for function in &dispatcher.functions {
+        let mut row = vec![
+            function.selector.to_string(),
+            function.arguments.clone(),
+            function.jump_destination.map(|pc| format!("{pc:#06x}")).unwrap_or_default(),
+        ];
+        if resolve {
+            row.push(function.signatures.join("|"));
+        }
+        table.add_row(row);
+    }
+
+    let mut out = table.to_string();
+    if dispatcher.diamond {
+        out.push_str(
+            "\n\nThis looks like an EIP-2535 diamond: the selectors of its facets are stored in its \
+             storage, call `facets()` to list them",
+        );
+    }
+    out
+}
@@ -1,6 +1,12 @@
-use cast::{Cast, TxBuilder};
+use crate::cmd::mktx::{
+    build_tx, ensure_access_list_signable, print_versioned_hashes, sign_blob_tx, BlobOpts,
+};
+use cast::{
+    pending::{ConfirmOpts, SendOutcome},
+    read_access_list, Cast,
+};
 use clap::Parser;
-use ethers_core::types::NameOrAddress;
+use ethers_core::types::{AccessList, Address, NameOrAddress};
 use ethers_middleware::MiddlewareBuilder;
 use ethers_providers::Middleware;
 use ethers_signers::Signer;
@@ -14,7 +20,11 @@ use foundry_common::{
     types::{ToAlloy, ToEthers},
 };
 use foundry_config::{Chain, Config};
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
+
+/// The exit code of `cast send` when the transaction isn't confirmed before the timeout, like
+/// `timeout(1)`.
+const TIMEOUT_EXIT_CODE: i32 = 124;
 
 /// CLI arguments for `cast send`.
 #[derive(Debug, Parser)]
@@ -35,10 +45,24 @@ pub struct SendTxArgs {
     #[clap(name = "async", long = "async", alias = "cast-async", env = "CAST_ASYNC")]
     cast_async: bool,
 
-    /// The number of confirmations until the receipt is fetched.
+    /// The number of confirmations to wait for before printing the receipt.
     #[clap(long, default_value = "1")]
     confirmations: usize,
 
+    /// Stop waiting for the transaction after this many seconds, print its hash and exit with
+    /// code 124.
+    #[clap(long, conflicts_with_all = ["async", "blob"], value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Resend the transaction with its fees bumped by this percentage if it isn't mined within
+    /// the bump interval.
+    #[clap(long, conflicts_with_all = ["async", "blob"], value_name = "PERCENT")]
+    auto_bump: Option<u64>,
+
+    /// The number of seconds to wait for the transaction to be mined before bumping its fees.
+    #[clap(long, requires = "auto_bump", default_value = "30", value_name = "SECONDS")]
+    bump_interval: u64,
+
     /// Print the transaction receipt as JSON.
     #[clap(long, short, help_heading = "Display options")]
     json: bool,
@@ -51,12 +75,27 @@ pub struct SendTxArgs {
     command: Option<SendTxSubcommands>,
 
     /// Send via `eth_sendTransaction using the `--from` argument or $ETH_FROM as sender
-    #[clap(long, requires = "from")]
+    #[clap(long, requires = "from", conflicts_with = "blob")]
     unlocked: bool,
 
     #[clap(flatten)]
     tx: TransactionOpts,
 
+    /// The access list of the transaction, e.g. the output of `cast access-list --json`.
+    ///
+    /// Either the JSON itself, the path of a JSON file, or `-` to read it from stdin. Access lists
+    /// are sent in EIP-1559 transactions, or in EIP-2930 transactions on chains without EIP-1559.
+    #[clap(
+        long,
+        value_parser = read_access_list,
+        value_name = "JSON|PATH",
+        conflicts_with_all = ["blob", "legacy"]
+    )]
+    access_list: Option<AccessList>,
+
+    #[clap(flatten)]
+    blob: BlobOpts,
+
     #[clap(flatten)]
     eth: EthereumOpts,
 }
@@ -87,10 +126,15 @@ impl SendTxArgs {
             mut args,
             mut tx,
             confirmations,
+            timeout,
+            auto_bump,
+            bump_interval,
             json: to_json,
             resend,
             command,
             unlocked,
+            access_list,
+            blob,
         } = self;
 
         let mut sig = sig.unwrap_or_default();
@@ -111,11 +155,20 @@ impl SendTxArgs {
         if code.is_none() && to.is_none() {
             eyre::bail!("Must specify a recipient address or contract code to deploy");
         }
+        if blob.blob && code.is_some() {
+            eyre::bail!("Blob transactions can't deploy contracts");
+        }
 
         let config = Config::from(&eth);
         let provider = utils::get_provider(&config)?;
         let chain = utils::get_chain(config.chain, &provider).await?;
         let api_key = config.get_etherscan_api_key(Some(chain));
+        let confirm_opts = ConfirmOpts {
+            confirmations,
+            timeout: timeout.map(Duration::from_secs),
+            auto_bump,
+            bump_interval: Duration::from_secs(bump_interval),
+        };
 
         // Case 1:
         // Default to sending via eth_sendTransaction if the --unlocked flag is passed.
@@ -157,10 +210,11 @@ impl SendTxArgs {
                 code,
                 (sig, args),
                 tx,
+                access_list,
                 chain,
                 api_key,
                 cast_async,
-                confirmations,
+                &confirm_opts,
                 to_json,
             )
             .await
@@ -191,6 +245,27 @@ corresponds to the sender, or let foundry automatically detect it by not specify
                 tx.nonce = Some(provider.get_transaction_count(from, None).await?.to_alloy());
             }
 
+            // blob transactions are signed and published raw, signer middlewares can't sign them
+            if blob.blob {
+                let tx = build_tx(&provider, from, to, None, (sig, args), tx, None, chain, api_key)
+                    .await?;
+                let (raw_tx, sidecar) = sign_blob_tx(provider.clone(), &signer, tx, &blob).await?;
+                print_versioned_hashes(&sidecar);
+
+                let cast = Cast::new(provider);
+                let tx_hash = *cast.publish(raw_tx.to_string()).await?;
+                if cast_async {
+                    println!("{tx_hash:#x}");
+                } else {
+                    let receipt = cast
+                        .receipt(format!("{tx_hash:#x}"), None, confirmations, false, to_json)
+                        .await?;
+                    println!("{receipt}");
+                }
+                return Ok(())
+            }
+
+            ensure_access_list_signable(&signer, chain, access_list.as_ref())?;
             let provider = provider.with_signer(signer);
 
             cast_send(
@@ -200,10 +275,11 @@ corresponds to the sender, or let foundry automatically detect it by not specify
                 code,
                 (sig, args),
                 tx,
+                access_list,
                 chain,
                 api_key,
                 cast_async,
-                confirmations,
+                &confirm_opts,
                 to_json,
             )
             .await
@@ -212,57 +288,49 @@ corresponds to the sender, or let foundry automatically detect it by not specify
 }
 
 #[allow(clippy::too_many_arguments)]
-async fn cast_send<M: Middleware, F: Into<NameOrAddress>, T: Into<NameOrAddress>>(
+async fn cast_send<M: Middleware, T: Into<NameOrAddress>>(
     provider: M,
-    from: F,
+    from: Address,
     to: Option<T>,
     code: Option<String>,
     args: (String, Vec<String>),
     tx: TransactionOpts,
+    access_list: Option<AccessList>,
     chain: Chain,
     etherscan_api_key: Option<String>,
     cast_async: bool,
-    confs: usize,
+    confirm_opts: &ConfirmOpts,
     to_json: bool,
 ) -> Result<()>
 where
     M::Error: 'static,
 {
-    let (sig, params) = args;
-    let params = if !sig.is_empty() { Some((&sig[..], params)) } else { None };
-    let mut builder = TxBuilder::new(&provider, from, to, chain, tx.legacy).await?;
-    builder
-        .etherscan_api_key(etherscan_api_key)
-        .gas(tx.gas_limit)
-        .gas_price(tx.gas_price)
-        .priority_gas_price(tx.priority_gas_price)
-        .value(tx.value)
-        .nonce(tx.nonce);
-
-    if let Some(code) = code {
-        let mut data = hex::decode(code)?;
-
-        if let Some((sig, args)) = params {
-            let (mut sigdata, _) = builder.create_args(sig, args).await?;
-            data.append(&mut sigdata);
-        }
-
-        builder.set_data(data);
-    } else {
-        builder.args(params).await?;
-    };
-    let builder_output = builder.build();
+    let tx = build_tx(&provider, from, to, code, args, tx, access_list, chain, etherscan_api_key)
+        .await?;
+    let builder_output = (tx, None);
 
     let cast = Cast::new(provider);
 
-    let pending_tx = cast.send(builder_output).await?;
-    let tx_hash = *pending_tx;
-
     if cast_async {
+        let pending_tx = cast.send(builder_output).await?;
+        let tx_hash = *pending_tx;
         println!("{tx_hash:#x}");
-    } else {
-        let receipt = cast.receipt(format!("{tx_hash:#x}"), None, confs, false, to_json).await?;
-        println!("{receipt}");
+        return Ok(())
+    }
+
+    let (tx, _) = builder_output;
+    match cast.send_and_confirm(tx, from, confirm_opts).await? {
+        SendOutcome::Confirmed(tx_hash) => {
+            let receipt = cast
+                .receipt(format!("{tx_hash:#x}"), None, confirm_opts.confirmations, false, to_json)
+                .await?;
+            println!("{receipt}");
+        }
+        SendOutcome::TimedOut(tx_hash) => {
+            eprintln!("Transaction {tx_hash:#x} was not confirmed before the timeout");
+            println!("{tx_hash:#x}");
+            std::process::exit(TIMEOUT_EXIT_CODE);
+        }
     }
 
     Ok(())
@@ -0,0 +1,437 @@
+use alloy_dyn_abi::JsonAbiExt;
+use alloy_primitives::{hex, Address, Bytes, Log, B256, U256, U64};
+use cast::raw_tx::{DecodedTransaction, TxType};
+use clap::{Parser, ValueHint};
+use eyre::{Result, WrapErr};
+use foundry_cli::opts::RpcOpts;
+use foundry_common::fs;
+use foundry_compilers::EvmVersion;
+use foundry_config::{find_project_root_path, Config};
+use foundry_evm::{
+    decode::decode_revert,
+    executors::TracingExecutor,
+    opts::EvmOpts,
+    revm::{
+        interpreter::InstructionResult,
+        primitives::{Env, TransactTo},
+        DatabaseCommit, DatabaseRef,
+    },
+    traces::{
+        identifier::{SignaturesIdentifier, SingleSignaturesIdentifier},
+        CallTraceDecoder, CallTraceDecoderBuilder, DecodedCallLog,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, path::PathBuf};
+use yansi::Paint;
+
+/// CLI arguments for `cast simulate`.
+#[derive(Clone, Debug, Parser)]
+pub struct SimulateArgs {
+    /// The bundle of transactions, executed in order.
+    ///
+    /// Either a JSON array of transactions with `from`, `to`, `data`, `value` and optionally
+    /// `gas` and `gasPrice`, or of raw signed EIP-2718 transactions, which can also be listed one
+    /// per line. Transactions without `to` deploy their `data`.
+    ///
+    /// Transactions without a gas price are executed with a zero gas price, like `eth_call`.
+    #[clap(long, short, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    file: PathBuf,
+
+    /// The URL of the node to fork.
+    #[clap(long, visible_alias = "rpc-url", env = "ETH_RPC_URL", value_name = "URL")]
+    fork_url: Option<String>,
+
+    /// The block to fork at, the latest block by default.
+    ///
+    /// The state fetched at a given block is cached like in forge, so simulating again is fast.
+    #[clap(long, value_name = "BLOCK")]
+    fork_block: Option<u64>,
+
+    /// Keep executing the transactions after one fails, instead of stopping.
+    #[clap(long)]
+    continue_on_failure: bool,
+
+    /// The evm version to use.
+    ///
+    /// Overrides the version specified in the config.
+    #[clap(long, short)]
+    evm_version: Option<EvmVersion>,
+
+    /// Print the results as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+}
+
+/// A transaction of a bundle.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum BundleEntry {
+    /// A raw signed transaction
+    Raw(Bytes),
+    Transaction(BundleTransaction),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleTransaction {
+    from: Address,
+    to: Option<Address>,
+    #[serde(default, alias = "input")]
+    data: Bytes,
+    #[serde(default)]
+    value: U256,
+    gas: Option<U64>,
+    gas_price: Option<U256>,
+}
+
+/// The outcome of a transaction of a bundle.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulatedTransaction {
+    index: usize,
+    /// The hash of raw transactions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<B256>,
+    from: Address,
+    to: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    contract_address: Option<Address>,
+    success: bool,
+    gas_used: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revert_reason: Option<String>,
+    logs: Vec<SimulatedLog>,
+    balance_changes: Vec<BalanceChange>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulatedLog {
+    address: Address,
+    topics: Vec<B256>,
+    data: Bytes,
+    /// The event with its decoded parameters, if its signature is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decoded: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BalanceChange {
+    address: Address,
+    before: U256,
+    after: U256,
+}
+
+impl SimulateArgs {
+    pub async fn run(self) -> Result<()> {
+        let Self { file, fork_url, fork_block, continue_on_failure, evm_version, json } = self;
+        let bundle = read_bundle(&fs::read_to_string(&file)?)
+            .wrap_err_with(|| format!("invalid bundle {}", file.display()))?;
+
+        let rpc = RpcOpts { url: fork_url, ..Default::default() };
+        let figment = Config::figment_with_root(find_project_root_path(None).unwrap()).merge(rpc);
+        let evm_opts = figment.extract::<EvmOpts>()?;
+        let mut config = Config::try_from(figment)?.sanitized();
+        config.fork_block_number = fork_block;
+        let (env, fork, _) = TracingExecutor::get_fork_material(&config, evm_opts).await?;
+        let mut executor = TracingExecutor::new(env.clone(), fork, evm_version, false).await;
+
+        let signatures = SignaturesIdentifier::new(Config::foundry_cache_dir(), config.offline)?;
+        let decoder = CallTraceDecoderBuilder::new().with_signature_identifier(signatures).build();
+
+        if !json {
+            println!(
+                "Simulating {} transactions on top of block {}\n",
+                bundle.len(),
+                env.block.number
+            );
+        }
+        let mut results = Vec::with_capacity(bundle.len());
+        for (index, entry) in bundle.into_iter().enumerate() {
+            let result = simulate(&mut executor, &env, &decoder, index, entry).await?;
+            if !json {
+                println!("{}", result.pretty());
+            }
+            let success = result.success;
+            results.push(result);
+            if !success && !continue_on_failure {
+                break
+            }
+        }
+        if json {
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        }
+
+        let failed = results.iter().filter(|result| !result.success).count();
+        if failed > 0 {
+            if continue_on_failure {
+                eyre::bail!("{failed} of the {} transactions failed", results.len())
+            }
+            eyre::bail!(
+                "transaction [{}] failed, pass `--continue-on-failure` to execute the following \
+                 ones anyway",
+                results.len() - 1
+            )
+        }
+        Ok(())
+    }
+}
+
+/// Parses a bundle: a JSON array of transactions, or raw transactions one per line.
+fn read_bundle(content: &str) -> Result<Vec<BundleEntry>> {
+    if content.trim_start().starts_with('[') {
+        return Ok(serde_json::from_str(content)?)
+    }
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| Ok(BundleEntry::Raw(hex::decode(line)?.into())))
+        .collect()
+}
+
+/// Executes the transaction and commits its changes, unless it's invalid.
+async fn simulate(
+    executor: &mut TracingExecutor,
+    env: &Env,
+    decoder: &CallTraceDecoder,
+    index: usize,
+    entry: BundleEntry,
+) -> Result<SimulatedTransaction> {
+    let mut env = env.clone();
+    let (hash, from, to) = match entry {
+        BundleEntry::Raw(raw) => {
+            let tx = DecodedTransaction::decode(&raw)
+                .wrap_err_with(|| format!("invalid raw transaction [{index}]"))?;
+            if matches!(tx.tx_type, TxType::Eip4844 | TxType::Eip7702) {
+                eyre::bail!("{} transactions can't be simulated, see [{index}]", tx.tx_type)
+            }
+            env.tx.caller = tx.from;
+            env.tx.gas_limit = tx.gas.to();
+            env.tx.gas_price = tx.gas_price.or(tx.max_fee_per_gas).unwrap_or_default();
+            env.tx.gas_priority_fee = tx.max_priority_fee_per_gas;
+            env.tx.nonce = Some(tx.nonce.to());
+            env.tx.chain_id = tx.chain_id.map(|chain_id| chain_id.to());
+            env.tx.access_list = tx
+                .access_list
+                .unwrap_or_default()
+                .into_iter()
+                .map(|item| {
+                    let keys = item.storage_keys.into_iter().map(|key| U256::from_be_bytes(key.0));
+                    (item.address, keys.collect())
+                })
+                .collect();
+            env.tx.value = tx.value;
+            env.tx.data = tx.input;
+            (Some(tx.hash), tx.from, tx.to)
+        }
+        BundleEntry::Transaction(tx) => {
+            env.tx.caller = tx.from;
+            env.tx.gas_limit = tx.gas.map_or(env.block.gas_limit.to(), |gas| gas.to());
+            env.tx.gas_price = tx.gas_price.unwrap_or_default();
+            env.tx.gas_priority_fee = None;
+            env.tx.nonce = None;
+            env.tx.value = tx.value;
+            env.tx.data = tx.data;
+            (None, tx.from, tx.to)
+        }
+    };
+    env.tx.transact_to = to.map(TransactTo::Call).unwrap_or_else(TransactTo::create);
+    env.cfg.disable_base_fee = env.tx.gas_price.is_zero();
+
+    let mut simulated = SimulatedTransaction {
+        index,
+        hash,
+        from,
+        to,
+        contract_address: None,
+        success: false,
+        gas_used: 0,
+        revert_reason: None,
+        logs: Vec::new(),
+        balance_changes: Vec::new(),
+    };
+    if to.is_none() {
+        let nonce = executor.backend.basic_ref(from)?.map(|info| info.nonce).unwrap_or_default();
+        simulated.contract_address = Some(from.create(nonce));
+    }
+
+    // invalid transactions, e.g. with a wrong nonce or without the funds, don't change the state
+    let result = match executor.call_raw_with_env(env) {
+        Ok(result) => result,
+        Err(err) => {
+            simulated.revert_reason = Some(err.to_string());
+            return Ok(simulated)
+        }
+    };
+    simulated.success = !result.reverted;
+    simulated.gas_used = result.gas_used;
+    if result.reverted {
+        simulated.revert_reason =
+            Some(decode_revert_reason(&result.result, result.exit_reason, decoder).await);
+        simulated.contract_address = None;
+    }
+    for log in &result.logs {
+        simulated.logs.push(simulated_log(log, decoder).await);
+    }
+
+    if let Some(changes) = result.state_changeset {
+        for (address, account) in &changes {
+            let before =
+                executor.backend.basic_ref(*address)?.map(|info| info.balance).unwrap_or_default();
+            if before != account.info.balance {
+                let after = account.info.balance;
+                simulated.balance_changes.push(BalanceChange { address: *address, before, after });
+            }
+        }
+        simulated.balance_changes.sort_by_key(|change| change.address);
+        executor.backend.commit(changes);
+    }
+    Ok(simulated)
+}
+
+/// Decodes the revert data, custom errors by their selector.
+async fn decode_revert_reason(
+    data: &[u8],
+    status: InstructionResult,
+    decoder: &CallTraceDecoder,
+) -> String {
+    let reason = decode_revert(data, None, Some(status));
+    if !reason.starts_with("custom error") {
+        return reason
+    }
+    if let Some(identifier) = &decoder.signature_identifier {
+        if let Some(error) = identify_error(identifier, &data[..4], &data[4..]).await {
+            return error
+        }
+    }
+    reason
+}
+
+/// Looks up the signature of the custom error, which shares the selector database with functions.
+async fn identify_error(
+    identifier: &SingleSignaturesIdentifier,
+    selector: &[u8],
+    args: &[u8],
+) -> Option<String> {
+    let error = identifier.write().await.identify_function(selector).await?;
+    let args = error.abi_decode_input(args, false).ok()?;
+    let args = args.iter().map(foundry_common::fmt::format_token).collect::<Vec<_>>();
+    Some(format!("{}({})", error.name, args.join(", ")))
+}
+
+async fn simulated_log(log: &Log, decoder: &CallTraceDecoder) -> SimulatedLog {
+    let decoded = match decoder.decode_event(&log.data).await {
+        DecodedCallLog::Decoded(name, params) => {
+            let params = params.iter().map(|(name, value)| format!("{name}: {value}"));
+            Some(format!("{name}({})", params.collect::<Vec<_>>().join(", ")))
+        }
+        DecodedCallLog::Raw(_) => None,
+    };
+    SimulatedLog {
+        address: log.address,
+        topics: log.data.topics().to_vec(),
+        data: log.data.data.clone(),
+        decoded,
+    }
+}
+
+impl SimulatedTransaction {
+    fn pretty(&self) -> String {
+        let mut out = format!("[{}] ", self.index);
+        if let Some(hash) = self.hash {
+            let _ = write!(out, "{hash} ");
+        }
+        let _ = write!(out, "{} -> ", self.from);
+        match (self.to, self.contract_address) {
+            (Some(to), _) => {
+                let _ = write!(out, "{to}");
+            }
+            (None, Some(contract)) => {
+                let _ = write!(out, "new contract {contract}");
+            }
+            (None, None) => out.push_str("new contract"),
+        }
+        if self.success {
+            let _ = writeln!(out, "\n    {}, gas used: {}", Paint::green("Success"), self.gas_used);
+        } else {
+            let _ = writeln!(
+                out,
+                "\n    {}: {}, gas used: {}",
+                Paint::red("Failed"),
+                self.revert_reason.as_deref().unwrap_or_default(),
+                self.gas_used
+            );
+        }
+        if !self.logs.is_empty() {
+            out.push_str("    Logs:\n");
+        }
+        for log in &self.logs {
+            match &log.decoded {
+                Some(decoded) => {
+                    let _ = writeln!(out, "      {}: {decoded}", log.address);
+                }
+                None => {
+                    let topics = log.topics.iter().map(|topic| topic.to_string());
+                    let _ = writeln!(
+                        out,
+                        "      {}: topics [{}], data {}",
+                        log.address,
+                        topics.collect::<Vec<_>>().join(", "),
+                        log.data
+                    );
+                }
+            }
+        }
+        if !self.balance_changes.is_empty() {
+            out.push_str("    Balance changes:\n");
+        }
+        for change in &self.balance_changes {
+            let diff = if change.after >= change.before {
+                format!("+{}", change.after - change.before)
+            } else {
+                format!("-{}", change.before - change.after)
+            };
+            let _ = writeln!(
+                out,
+                "      {}: {} -> {} ({diff})",
+                change.address, change.before, change.after
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_read_bundles() {
+        let bundle = read_bundle(
+            r#"[
+                {
+                    "from": "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266",
+                    "to": "0x70997970C51812dc3A010C7d01b50e0d17dc79C8",
+                    "value": "0xde0b6b3a7640000"
+                },
+                { "from": "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266", "input": "0x6000" },
+                "0x02f86c"
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(bundle.len(), 3);
+        let BundleEntry::Transaction(transfer) = &bundle[0] else { panic!("expected a call") };
+        assert_eq!(transfer.value, U256::from(10).pow(U256::from(18)));
+        assert!(transfer.data.is_empty());
+        let BundleEntry::Transaction(create) = &bundle[1] else { panic!("expected a create") };
+        assert_eq!((create.to, create.data.to_vec()), (None, vec![0x60, 0x00]));
+        assert_eq!(bundle[2], BundleEntry::Raw(vec![0x02, 0xf8, 0x6c].into()));
+
+        let bundle = read_bundle("0x02f86c\n\n0xf86c\n").unwrap();
+        assert_eq!(bundle.len(), 2);
+        assert!(read_bundle("not a bundle").is_err());
+    }
+}
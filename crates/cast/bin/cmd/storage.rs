@@ -1,6 +1,6 @@
 use crate::opts::parse_slot;
-use alloy_primitives::{B256, U256};
-use cast::Cast;
+use alloy_primitives::{keccak256, Address, B256, U256};
+use cast::{proxy::ProxyImplementation, slots, Cast};
 use clap::Parser;
 use comfy_table::{presets::ASCII_MARKDOWN, Table};
 use ethers_core::types::{BlockId, NameOrAddress};
@@ -18,7 +18,8 @@ use foundry_common::{
     types::{ToAlloy, ToEthers},
 };
 use foundry_compilers::{
-    artifacts::StorageLayout, Artifact, ConfigurableContractArtifact, Project, Solc,
+    artifacts::{Storage, StorageLayout, StorageType},
+    Artifact, Project, Solc,
 };
 use foundry_config::{
     figment::{self, value::Dict, Metadata, Profile},
@@ -30,7 +31,7 @@ use std::str::FromStr;
 /// The minimum Solc version for outputting storage layouts.
 ///
 /// https://github.com/ethereum/solidity/blob/develop/Changelog.md#065-2020-04-06
-const MIN_SOLC: Version = Version::new(0, 6, 5);
+pub(crate) const MIN_SOLC: Version = Version::new(0, 6, 5);
 
 /// CLI arguments for `cast storage`.
 #[derive(Clone, Debug, Parser)]
@@ -49,6 +50,28 @@ pub struct StorageArgs {
     #[clap(long, short)]
     block: Option<BlockId>,
 
+    /// Decode the storage of proxies with the storage layout of their implementation.
+    ///
+    /// EIP-1967 transparent, UUPS and beacon proxies, and EIP-1822 proxies are detected.
+    #[clap(
+        long,
+        default_value_t = true,
+        default_missing_value = "true",
+        num_args(0..=1),
+        value_name = "BOOL"
+    )]
+    follow_proxy: bool,
+
+    /// The address of the contract whose storage layout is used, instead of the implementation
+    /// of the proxy.
+    #[clap(long = "impl", value_name = "ADDRESS")]
+    implementation: Option<Address>,
+
+    /// Read a single variable of the storage layout, following mappings, arrays and struct
+    /// members, e.g. `balances[0xabc...]`, `owners[1]` or `config.fee`.
+    #[clap(long, value_name = "EXPR", conflicts_with = "slot")]
+    slot_of: Option<String>,
+
     #[clap(flatten)]
     rpc: RpcOpts,
 
@@ -79,7 +102,7 @@ impl StorageArgs {
     pub async fn run(self) -> Result<()> {
         let config = Config::from(&self);
 
-        let Self { address, slot, block, build, .. } = self;
+        let Self { address, slot, block, follow_proxy, implementation, slot_of, build, .. } = self;
 
         let provider = utils::get_provider(&config)?;
 
@@ -97,6 +120,20 @@ impl StorageArgs {
             eyre::bail!("Provided address has no deployed code and thus no storage");
         }
 
+        // The storage of a proxy is decoded with the layout of its implementation
+        let layout_address =
+            layout_address(&provider, &address, block, implementation, follow_proxy).await?;
+        let layout_code = match layout_address {
+            Some(layout_address) => {
+                let code = provider.get_code(layout_address.to_ethers(), block).await?.to_alloy();
+                if code.is_empty() {
+                    eyre::bail!("The implementation {layout_address} has no deployed code");
+                }
+                code
+            }
+            None => address_code,
+        };
+
         // Check if we're in a forge project and if we can find the address' code
         let mut project = build.project()?;
         if project.paths.has_input_files() {
@@ -104,10 +141,12 @@ impl StorageArgs {
             add_storage_layout_output(&mut project);
             let out = ProjectCompiler::new().compile(&project)?;
             let artifact = out.artifacts().find(|(_, artifact)| {
-                artifact.get_deployed_bytecode_bytes().is_some_and(|b| *b == address_code)
+                artifact.get_deployed_bytecode_bytes().is_some_and(|b| *b == layout_code)
             });
             if let Some((_, artifact)) = artifact {
-                return fetch_and_print_storage(provider, address.clone(), artifact, true).await;
+                let layout = artifact.storage_layout.as_ref();
+                return fetch_and_print_storage(provider, address, block, layout, slot_of, true)
+                    .await;
             }
         }
 
@@ -122,59 +161,104 @@ impl StorageArgs {
         let chain = utils::get_chain(config.chain, &provider).await?;
         let api_key = config.get_etherscan_api_key(Some(chain)).unwrap_or_default();
         let client = Client::new(chain, api_key)?;
-        let addr = address
-            .as_address()
-            .ok_or_else(|| eyre::eyre!("Could not resolve address"))?
-            .to_alloy();
-        let source = find_source(client, addr).await?;
-        let metadata = source.items.first().unwrap();
-        if metadata.is_vyper() {
-            eyre::bail!("Contract at provided address is not a valid Solidity contract")
+        let addr = match layout_address {
+            Some(layout_address) => layout_address,
+            None => address
+                .as_address()
+                .ok_or_else(|| eyre::eyre!("Could not resolve address"))?
+                .to_alloy(),
+        };
+        let layout = fetch_verified_storage_layout(client, addr).await?;
+        fetch_and_print_storage(provider, address, block, layout.as_ref(), slot_of, true).await
+    }
+}
+
+/// Returns the address of the contract whose storage layout is used for the storage of
+/// `address`: the `implementation` if set, or the implementation of the proxy if `follow_proxy`.
+///
+/// Returns `None` if the layout of the contract itself is used.
+pub(crate) async fn layout_address(
+    provider: &RetryProvider,
+    address: &NameOrAddress,
+    block: Option<BlockId>,
+    implementation: Option<Address>,
+    follow_proxy: bool,
+) -> Result<Option<Address>> {
+    Ok(match implementation {
+        Some(implementation) => {
+            eprintln!("Using the storage layout of {implementation}");
+            Some(implementation)
         }
+        None if follow_proxy => {
+            let cast = Cast::new(provider.clone());
+            let proxy = cast.proxy_implementation(address.clone(), block).await?;
+            proxy.map(|ProxyImplementation { kind, implementation }| {
+                eprintln!(
+                    "Detected an {kind}, using the storage layout of the implementation \
+                     {implementation}"
+                );
+                implementation
+            })
+        }
+        None => None,
+    })
+}
 
-        let version = metadata.compiler_version()?;
-        let auto_detect = version < MIN_SOLC;
-
-        // Create a new temp project
-        // TODO: Cache instead of using a temp directory: metadata from Etherscan won't change
-        let root = tempfile::tempdir()?;
-        let root_path = root.path();
-        let mut project = etherscan_project(metadata, root_path)?;
-        add_storage_layout_output(&mut project);
-        project.auto_detect = auto_detect;
-
-        // Compile
-        let mut out = ProjectCompiler::new().quiet(true).compile(&project)?;
-        let artifact = {
-            let (_, mut artifact) = out
-                .artifacts()
-                .find(|(name, _)| name == &metadata.contract_name)
-                .ok_or_else(|| eyre::eyre!("Could not find artifact"))?;
-
-            if is_storage_layout_empty(&artifact.storage_layout) && auto_detect {
-                // try recompiling with the minimum version
-                eprintln!("The requested contract was compiled with {version} while the minimum version for storage layouts is {MIN_SOLC} and as a result the output may be empty.");
-                let solc = Solc::find_or_install_svm_version(MIN_SOLC.to_string())?;
-                project.solc = solc;
-                project.auto_detect = false;
-                if let Ok(output) = ProjectCompiler::new().quiet(true).compile(&project) {
-                    out = output;
-                    let (_, new_artifact) = out
-                        .artifacts()
-                        .find(|(name, _)| name == &metadata.contract_name)
-                        .ok_or_else(|| eyre::eyre!("Could not find artifact"))?;
-                    artifact = new_artifact;
-                }
+/// Compiles the verified source of the contract at the address, fetched from Etherscan, and
+/// returns its storage layout.
+pub(crate) async fn fetch_verified_storage_layout(
+    client: Client,
+    address: Address,
+) -> Result<Option<StorageLayout>> {
+    let source = find_source(client, address).await?;
+    let metadata =
+        source.items.first().ok_or_else(|| eyre::eyre!("No source code found for {address}"))?;
+    if metadata.is_vyper() {
+        eyre::bail!("Contract at provided address is not a valid Solidity contract")
+    }
+
+    let version = metadata.compiler_version()?;
+    let auto_detect = version < MIN_SOLC;
+
+    // Create a new temp project
+    // TODO: Cache instead of using a temp directory: metadata from Etherscan won't change
+    let root = tempfile::tempdir()?;
+    let root_path = root.path();
+    let mut project = etherscan_project(metadata, root_path)?;
+    add_storage_layout_output(&mut project);
+    project.auto_detect = auto_detect;
+
+    // Compile
+    let mut out = ProjectCompiler::new().quiet(true).compile(&project)?;
+    let artifact = {
+        let (_, mut artifact) = out
+            .artifacts()
+            .find(|(name, _)| name == &metadata.contract_name)
+            .ok_or_else(|| eyre::eyre!("Could not find artifact"))?;
+
+        if is_storage_layout_empty(artifact.storage_layout.as_ref()) && auto_detect {
+            // try recompiling with the minimum version
+            eprintln!("The requested contract was compiled with {version} while the minimum version for storage layouts is {MIN_SOLC} and as a result the output may be empty.");
+            let solc = Solc::find_or_install_svm_version(MIN_SOLC.to_string())?;
+            project.solc = solc;
+            project.auto_detect = false;
+            if let Ok(output) = ProjectCompiler::new().quiet(true).compile(&project) {
+                out = output;
+                let (_, new_artifact) = out
+                    .artifacts()
+                    .find(|(name, _)| name == &metadata.contract_name)
+                    .ok_or_else(|| eyre::eyre!("Could not find artifact"))?;
+                artifact = new_artifact;
             }
+        }
 
-            artifact
-        };
+        artifact
+    };
 
-        // Clear temp directory
-        root.close()?;
+    // Clear temp directory
+    root.close()?;
 
-        fetch_and_print_storage(provider, address, artifact, true).await
-    }
+    Ok(artifact.storage_layout.clone())
 }
 
 /// Represents the value of a storage slot `eth_getStorageAt` call.
@@ -211,15 +295,21 @@ impl StorageValue {
 async fn fetch_and_print_storage(
     provider: RetryProvider,
     address: NameOrAddress,
-    artifact: &ConfigurableContractArtifact,
+    block: Option<BlockId>,
+    storage_layout: Option<&StorageLayout>,
+    slot_of: Option<String>,
     pretty: bool,
 ) -> Result<()> {
-    if is_storage_layout_empty(&artifact.storage_layout) {
+    if is_storage_layout_empty(storage_layout) {
         eprintln!("Storage layout is empty.");
         Ok(())
     } else {
-        let layout = artifact.storage_layout.as_ref().unwrap().clone();
-        let values = fetch_storage_slots(provider, address, &layout).await?;
+        let mut layout = storage_layout.unwrap().clone();
+        if let Some(expr) = slot_of {
+            let variable = resolve_slot(&layout, &expr)?;
+            layout.storage = vec![variable];
+        }
+        let values = fetch_storage_slots(provider, address, block, &layout).await?;
         print_storage(layout, values, pretty)
     }
 }
@@ -227,12 +317,13 @@ async fn fetch_and_print_storage(
 async fn fetch_storage_slots(
     provider: RetryProvider,
     address: NameOrAddress,
+    block: Option<BlockId>,
     layout: &StorageLayout,
 ) -> Result<Vec<StorageValue>> {
     let requests = layout.storage.iter().map(|storage_slot| async {
         let slot = B256::from(U256::from_str(&storage_slot.slot)?);
         let raw_slot_value =
-            provider.get_storage_at(address.clone(), slot.to_ethers(), None).await?.to_alloy();
+            provider.get_storage_at(address.clone(), slot.to_ethers(), block).await?.to_alloy();
 
         let value = StorageValue { slot, raw_slot_value };
 
@@ -275,16 +366,271 @@ fn print_storage(layout: StorageLayout, values: Vec<StorageValue>, pretty: bool)
     Ok(())
 }
 
-fn add_storage_layout_output(project: &mut Project) {
+/// Resolves the storage location of a variable of the layout, following the mapping keys, array
+/// indices and struct members of the expression, e.g. `balances[0xabc...]` or `orders[1].amount`.
+///
+/// The returned variable is labeled with the expression.
+pub(crate) fn resolve_slot(layout: &StorageLayout, expr: &str) -> Result<Storage> {
+    let (name, mut rest) = split_accessor(expr);
+    let variable = layout
+        .storage
+        .iter()
+        .find(|storage| storage.label == name)
+        .ok_or_else(|| eyre::eyre!("No variable named `{name}` in the storage layout"))?;
+
+    let mut slot = U256::from_str(&variable.slot)?;
+    let mut offset = variable.offset;
+    let mut storage_type = variable.storage_type.clone();
+    while !rest.is_empty() {
+        let ty = storage_type_of(layout, &storage_type)?;
+        if let Some(accessor) = rest.strip_prefix('[') {
+            let end = accessor.find(']').ok_or_else(|| eyre::eyre!("Unclosed `[` in `{expr}`"))?;
+            let key = accessor[..end].trim();
+            rest = &accessor[end + 1..];
+
+            match ty.encoding.as_str() {
+                "mapping" => {
+                    let key_type = ty.key.as_deref().map(|key| storage_type_of(layout, key));
+                    let key_label = key_type.transpose()?.map_or("", |t| t.label.as_str());
+                    let value_slot = slots::mapping_slot(key_label, key, slot.into())?;
+                    slot = U256::from_be_bytes(value_slot.0);
+                    offset = 0;
+                    storage_type = ty
+                        .value
+                        .clone()
+                        .ok_or_else(|| eyre::eyre!("Mapping without value type"))?;
+                }
+                "dynamic_array" | "inplace" if ty.other.contains_key("base") => {
+                    let base = ty.other["base"]
+                        .as_str()
+                        .ok_or_else(|| eyre::eyre!("Invalid array base type"))?
+                        .to_string();
+                    let index = U256::from_str(key)
+                        .map_err(|_| eyre::eyre!("Invalid array index `{key}`"))?;
+                    // the elements of dynamic arrays are stored from the hash of the slot
+                    let start = if ty.encoding == "dynamic_array" {
+                        U256::from_be_bytes(keccak256(slot.to_be_bytes::<32>()).0)
+                    } else {
+                        slot
+                    };
+                    let size = storage_type_of(layout, &base)?.number_of_bytes.parse::<u64>()?;
+                    let (element_slot, element_offset) =
+                        slots::array_element_location(start, size, index);
+                    (slot, offset) = (element_slot, element_offset as i64);
+                    storage_type = base;
+                }
+                _ => eyre::bail!("`{}` is not a mapping or an array", ty.label),
+            }
+        } else if let Some(accessor) = rest.strip_prefix('.') {
+            let (member, remaining) = split_accessor(accessor);
+            rest = remaining;
+            let members: Vec<Storage> = match ty.other.get("members") {
+                Some(members) => serde_json::from_value(members.clone())?,
+                None => eyre::bail!("`{}` is not a struct", ty.label),
+            };
+            let member = members
+                .into_iter()
+                .find(|storage| storage.label == member)
+                .ok_or_else(|| eyre::eyre!("`{}` has no member `{member}`", ty.label))?;
+            slot += U256::from_str(&member.slot)?;
+            offset = member.offset;
+            storage_type = member.storage_type;
+        } else {
+            eyre::bail!("Invalid accessor `{rest}` in `{expr}`");
+        }
+    }
+
+    Ok(Storage {
+        label: expr.to_string(),
+        slot: slot.to_string(),
+        offset,
+        storage_type,
+        ..variable.clone()
+    })
+}
+
+/// Splits the leading identifier of the expression from its accessors.
+fn split_accessor(expr: &str) -> (&str, &str) {
+    let end = expr.find(|c| c == '[' || c == '.').unwrap_or(expr.len());
+    (expr[..end].trim(), &expr[end..])
+}
+
+pub(crate) fn storage_type_of<'a>(layout: &'a StorageLayout, id: &str) -> Result<&'a StorageType> {
+    layout.types.get(id).ok_or_else(|| eyre::eyre!("Unknown storage type `{id}`"))
+}
+
+pub(crate) fn add_storage_layout_output(project: &mut Project) {
     project.artifacts.additional_values.storage_layout = true;
     let output_selection = project.artifacts.output_selection();
     project.solc_config.settings.push_all(output_selection);
 }
 
-fn is_storage_layout_empty(storage_layout: &Option<StorageLayout>) -> bool {
-    if let Some(ref s) = storage_layout {
+pub(crate) fn is_storage_layout_empty(storage_layout: Option<&StorageLayout>) -> bool {
+    if let Some(s) = storage_layout {
         s.storage.is_empty()
     } else {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // contract C {
+    //     struct S { uint128 a; uint256 b; }
+    //     uint256 x;
+    //     mapping(address => uint256) balances;
+    //     uint64[] small;
+    //     S s;
+    //     mapping(string => S) named;
+    // }
+    const LAYOUT: &str = r#"{
+        "storage": [
+            {
+                "astId": 1,
+                "contract": "C",
+                "label": "x",
+                "offset": 0,
+                "slot": "0",
+                "type": "t_uint256"
+            },
+            {
+                "astId": 2,
+                "contract": "C",
+                "label": "balances",
+                "offset": 0,
+                "slot": "1",
+                "type": "t_mapping(t_address,t_uint256)"
+            },
+            {
+                "astId": 3,
+                "contract": "C",
+                "label": "small",
+                "offset": 0,
+                "slot": "2",
+                "type": "t_array(t_uint64)dyn_storage"
+            },
+            {
+                "astId": 4,
+                "contract": "C",
+                "label": "s",
+                "offset": 0,
+                "slot": "3",
+                "type": "t_struct(S)"
+            },
+            {
+                "astId": 5,
+                "contract": "C",
+                "label": "named",
+                "offset": 0,
+                "slot": "5",
+                "type": "t_mapping(t_string_memory_ptr,t_struct(S))"
+            }
+        ],
+        "types": {
+            "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+            "t_uint64": { "encoding": "inplace", "label": "uint64", "numberOfBytes": "8" },
+            "t_uint128": { "encoding": "inplace", "label": "uint128", "numberOfBytes": "16" },
+            "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+            "t_string_memory_ptr": {
+                "encoding": "bytes",
+                "label": "string",
+                "numberOfBytes": "32"
+            },
+            "t_array(t_uint64)dyn_storage": {
+                "encoding": "dynamic_array",
+                "base": "t_uint64",
+                "label": "uint64[]",
+                "numberOfBytes": "32"
+            },
+            "t_mapping(t_address,t_uint256)": {
+                "encoding": "mapping",
+                "key": "t_address",
+                "value": "t_uint256",
+                "label": "mapping(address => uint256)",
+                "numberOfBytes": "32"
+            },
+            "t_mapping(t_string_memory_ptr,t_struct(S))": {
+                "encoding": "mapping",
+                "key": "t_string_memory_ptr",
+                "value": "t_struct(S)",
+                "label": "mapping(string => struct C.S)",
+                "numberOfBytes": "32"
+            },
+            "t_struct(S)": {
+                "encoding": "inplace",
+                "label": "struct C.S",
+                "numberOfBytes": "64",
+                "members": [
+                    {
+                        "astId": 6,
+                        "contract": "C",
+                        "label": "a",
+                        "offset": 0,
+                        "slot": "0",
+                        "type": "t_uint128"
+                    },
+                    {
+                        "astId": 7,
+                        "contract": "C",
+                        "label": "b",
+                        "offset": 0,
+                        "slot": "1",
+                        "type": "t_uint256"
+                    }
+                ]
+            }
+        }
+    }"#;
+
+    fn resolve(expr: &str) -> (String, i64, String) {
+        let layout: StorageLayout = serde_json::from_str(LAYOUT).unwrap();
+        let storage = resolve_slot(&layout, expr).unwrap();
+        assert_eq!(storage.label, expr);
+        (storage.slot, storage.offset, storage.storage_type)
+    }
+
+    #[test]
+    fn can_resolve_slots() {
+        assert_eq!(resolve("x"), ("0".to_string(), 0, "t_uint256".to_string()));
+        assert_eq!(
+            resolve("balances[0x0000000000000000000000000000000000000001]"),
+            (
+                "92458281274488595289803937127152923398167637295201432141969818930235769911599"
+                    .to_string(),
+                0,
+                "t_uint256".to_string()
+            )
+        );
+        // 4 elements per slot
+        assert_eq!(
+            resolve("small[5]"),
+            (
+                "29102676481673041902632991033461445430619272659676223336789171408008386403023"
+                    .to_string(),
+                8,
+                "t_uint64".to_string()
+            )
+        );
+        assert_eq!(resolve("s.b"), ("4".to_string(), 0, "t_uint256".to_string()));
+        assert_eq!(
+            resolve("named[\"foo\"].b"),
+            (
+                "52359036226035077591250740062413829368590588997124281490982605754798430417502"
+                    .to_string(),
+                0,
+                "t_uint256".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_accessors() {
+        let layout: StorageLayout = serde_json::from_str(LAYOUT).unwrap();
+        assert!(resolve_slot(&layout, "missing").is_err());
+        assert!(resolve_slot(&layout, "x[1]").is_err());
+        assert!(resolve_slot(&layout, "s.c").is_err());
+        assert!(resolve_slot(&layout, "balances[0x01").is_err());
+    }
+}
@@ -0,0 +1,609 @@
+use super::storage::{
+    add_storage_layout_output, fetch_verified_storage_layout, is_storage_layout_empty,
+    layout_address, resolve_slot, storage_type_of,
+};
+use alloy_primitives::{keccak256, Address, B256, I256, U256};
+use cast::slots;
+use clap::Parser;
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use ethers_core::types::{BlockId, NameOrAddress};
+use ethers_providers::Middleware;
+use eyre::{Context, Result};
+use foundry_block_explorers::Client;
+use foundry_cli::{
+    opts::{CoreBuildArgs, EtherscanOpts, RpcOpts},
+    utils,
+};
+use foundry_common::{
+    compile::ProjectCompiler,
+    fs,
+    provider::ethers::RetryProvider,
+    storage_diff::{print_storage_layout_diff, StorageLayoutDiff},
+    types::{ToAlloy, ToEthers},
+};
+use foundry_compilers::{
+    artifacts::{Storage, StorageLayout},
+    info::ContractInfo,
+    utils::canonicalize,
+    Artifact, ProjectCompileOutput,
+};
+use foundry_config::{
+    figment::{self, value::Dict, Metadata, Profile},
+    impl_figment_convert_cast, Config,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, str::FromStr};
+
+/// The maximum number of elements of an array whose values are read.
+const MAX_ARRAY_ELEMENTS: u64 = 32;
+
+/// The maximum length of the `bytes` and `string` values that are read.
+const MAX_BYTES_LENGTH: usize = 4096;
+
+/// CLI arguments for `cast storage-layout`.
+#[derive(Clone, Debug, Parser)]
+pub struct StorageLayoutArgs {
+    /// The contract address.
+    #[clap(value_parser = NameOrAddress::from_str)]
+    address: NameOrAddress,
+
+    /// The block height to query at.
+    ///
+    /// Can also be the tags earliest, finalized, safe, latest, or pending.
+    #[clap(long, short)]
+    block: Option<BlockId>,
+
+    /// Read the storage and decode the values of the variables.
+    ///
+    /// Structs and arrays are expanded into their members and elements, only the first 32
+    /// elements of an array are read. The values of mappings are read with `--slot-of`.
+    #[clap(long)]
+    values: bool,
+
+    /// Read the value of a variable of the storage layout, following mappings, arrays and struct
+    /// members, e.g. `balances[0xabc...]`, `owners[1]` or `config.fee`.
+    ///
+    /// Can be repeated, implies `--values`.
+    #[clap(long, value_name = "EXPR")]
+    slot_of: Vec<String>,
+
+    /// Use the storage layout of the contract of the local project that was deployed at the
+    /// address, according to the deployments registry of the chain, see `save_deployments`.
+    ///
+    /// Contracts missing in the registry are matched by their deployed bytecode.
+    #[clap(long)]
+    project: bool,
+
+    /// Compare the storage layout with the one of another contract, the verified contract at the
+    /// address or the contract of the local project, in the form `(<path>:)?<contractname>`.
+    ///
+    /// The contract at the address is the deployed implementation, the other one its new
+    /// implementation. Exits with an error if variables of the deployed contract were moved,
+    /// resized, retyped or deleted.
+    #[clap(long, value_name = "ADDRESS|CONTRACT", conflicts_with_all = ["values", "slot_of"])]
+    diff: Option<String>,
+
+    /// Use the storage layout of the implementation of proxies.
+    ///
+    /// EIP-1967 transparent, UUPS and beacon proxies, and EIP-1822 proxies are detected.
+    #[clap(
+        long,
+        default_value_t = true,
+        default_missing_value = "true",
+        num_args(0..=1),
+        value_name = "BOOL"
+    )]
+    follow_proxy: bool,
+
+    /// The address of the contract whose storage layout is used, instead of the implementation
+    /// of the proxy.
+    #[clap(long = "impl", value_name = "ADDRESS")]
+    implementation: Option<Address>,
+
+    /// Print the storage layout as JSON.
+    #[clap(long, short)]
+    json: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+
+    #[clap(flatten)]
+    etherscan: EtherscanOpts,
+
+    #[clap(flatten)]
+    build: CoreBuildArgs,
+}
+
+impl_figment_convert_cast!(StorageLayoutArgs);
+
+impl figment::Provider for StorageLayoutArgs {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("StorageLayoutArgs")
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        let mut map = self.build.data()?;
+        let dict = map.get_mut(&Config::selected_profile()).unwrap();
+        dict.extend(self.rpc.dict());
+        dict.extend(self.etherscan.dict());
+        Ok(map)
+    }
+}
+
+impl StorageLayoutArgs {
+    pub async fn run(self) -> Result<()> {
+        let config = Config::from(&self);
+        let provider = utils::get_provider(&config)?;
+
+        let code = provider.get_code(self.address.clone(), self.block).await?.to_alloy();
+        if code.is_empty() {
+            eyre::bail!("Provided address has no deployed code and thus no storage");
+        }
+        let address = match &self.address {
+            NameOrAddress::Address(address) => address.to_alloy(),
+            NameOrAddress::Name(name) => provider.resolve_name(name).await?.to_alloy(),
+        };
+
+        // The storage of a proxy is decoded with the layout of its implementation
+        let layout_address = layout_address(
+            &provider,
+            &self.address,
+            self.block,
+            self.implementation,
+            self.follow_proxy,
+        )
+        .await?
+        .unwrap_or(address);
+
+        let mut output = None;
+        let layout = self.fetch_layout(&config, &provider, layout_address, &mut output).await?;
+
+        if let Some(other) = &self.diff {
+            let new = match Address::from_str(other) {
+                Ok(other) => self.fetch_layout(&config, &provider, other, &mut output).await?,
+                Err(_) => {
+                    let mut contract = ContractInfo::from_str(other)?;
+                    if let Some(path) = &mut contract.path {
+                        *path = canonicalize(&*path)?.to_string_lossy().to_string();
+                    }
+                    let output = self.compile(&mut output)?;
+                    let artifact = output.find_contract(&contract).ok_or_else(|| {
+                        eyre::eyre!(
+                            "Could not find artifact `{contract}` in the compiled artifacts"
+                        )
+                    })?;
+                    artifact.storage_layout.clone().ok_or_else(|| {
+                        eyre::eyre!("Could not get storage layout of `{contract}`")
+                    })?
+                }
+            };
+            let diff = StorageLayoutDiff::new(&layout, &new);
+            print_storage_layout_diff(&diff, !self.json)?;
+            if !diff.is_compatible() {
+                std::process::exit(1);
+            }
+            return Ok(())
+        }
+
+        let values = self.values || !self.slot_of.is_empty();
+        let rows = if values {
+            let variables = if self.slot_of.is_empty() {
+                layout.storage.clone()
+            } else {
+                self.slot_of
+                    .iter()
+                    .map(|expr| resolve_slot(&layout, expr))
+                    .collect::<Result<_>>()?
+            };
+            let mut decoder = StorageDecoder::new(&provider, &self.address, self.block, &layout);
+            let mut rows = Vec::new();
+            for variable in &variables {
+                rows.extend(decoder.decode(variable).await?);
+            }
+            rows
+        } else {
+            layout
+                .storage
+                .iter()
+                .map(|variable| -> Result<_> {
+                    let ty = storage_type_of(&layout, &variable.storage_type)?;
+                    Ok(StorageLayoutRow::new(
+                        &variable.label,
+                        &ty.label,
+                        U256::from_str(&variable.slot)?,
+                        variable.offset,
+                        &ty.number_of_bytes,
+                        &variable.contract,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        } else {
+            print_rows(&rows, values);
+        }
+        Ok(())
+    }
+
+    /// Returns the storage layout of the contract at the address, from the local project if
+    /// `--project` is set, or else the verified contract on Etherscan.
+    async fn fetch_layout(
+        &self,
+        config: &Config,
+        provider: &RetryProvider,
+        address: Address,
+        output: &mut Option<ProjectCompileOutput>,
+    ) -> Result<StorageLayout> {
+        let layout = if self.project {
+            let chain_id = provider.get_chainid().await?.as_u64();
+            let deployment = find_deployment(config, chain_id, address)?;
+            let output = self.compile(output)?;
+            let artifact = match &deployment {
+                Some(id) => {
+                    let mut contract = ContractInfo::from_str(id)?;
+                    if let Some(path) = &mut contract.path {
+                        *path = config.__root.0.join(&*path).to_string_lossy().to_string();
+                    }
+                    output.find_contract(&contract).ok_or_else(|| {
+                        eyre::eyre!("Could not find artifact `{id}` of {address} in the project")
+                    })?
+                }
+                None => {
+                    let code = provider.get_code(address.to_ethers(), self.block).await?.to_alloy();
+                    output
+                        .artifacts()
+                        .find(|(_, artifact)| {
+                            artifact.get_deployed_bytecode_bytes().is_some_and(|b| *b == code)
+                        })
+                        .map(|(_, artifact)| artifact)
+                        .ok_or_else(|| {
+                            eyre::eyre!("No contract of the project is deployed at {address}")
+                        })?
+                }
+            };
+            artifact.storage_layout.clone()
+        } else {
+            if self.etherscan.key.is_none() {
+                eyre::bail!("You must provide an Etherscan API key if you're fetching a remote contract's storage layout, or use `--project` for a local deployment.");
+            }
+            let chain = utils::get_chain(config.chain, provider).await?;
+            let api_key = config.get_etherscan_api_key(Some(chain)).unwrap_or_default();
+            let client = Client::new(chain, api_key)?;
+            fetch_verified_storage_layout(client, address).await?
+        };
+
+        match layout {
+            Some(layout) if !is_storage_layout_empty(Some(&layout)) => Ok(layout),
+            _ => eyre::bail!("The storage layout of {address} is empty"),
+        }
+    }
+
+    /// Compiles the local project with the storage layout output, once.
+    fn compile<'a>(
+        &self,
+        output: &'a mut Option<ProjectCompileOutput>,
+    ) -> Result<&'a ProjectCompileOutput> {
+        if output.is_none() {
+            let mut project = self.build.project()?;
+            add_storage_layout_output(&mut project);
+            *output = Some(ProjectCompiler::new().quiet(self.json).compile(&project)?);
+        }
+        Ok(output.as_ref().unwrap())
+    }
+}
+
+/// A deployment of the registry file of a contract, see `forge script`.
+#[derive(Deserialize)]
+struct RegistryDeployment {
+    address: Address,
+    artifact: Option<String>,
+}
+
+/// The registry file of a contract on a chain, `<deployments>/<chain id>/<contract name>.json`.
+#[derive(Deserialize)]
+struct RegistryRecord {
+    #[serde(flatten)]
+    latest: RegistryDeployment,
+    #[serde(default)]
+    history: Vec<RegistryDeployment>,
+}
+
+/// Returns the identifier of the artifact, `<path>:<name>`, of the contract that was deployed at
+/// the address by a script, from the deployments registry of the chain.
+fn find_deployment(config: &Config, chain_id: u64, address: Address) -> Result<Option<String>> {
+    let dir = config.deployments.join(chain_id.to_string());
+    if !dir.is_dir() {
+        return Ok(None)
+    }
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().map_or(true, |ext| ext != "json") {
+            continue
+        }
+        let record: RegistryRecord = fs::read_json_file(&path)
+            .wrap_err_with(|| format!("Failed to read deployment {}", path.display()))?;
+        let deployment = std::iter::once(&record.latest)
+            .chain(&record.history)
+            .find(|deployment| deployment.address == address);
+        if let Some(deployment) = deployment {
+            return Ok(deployment.artifact.clone())
+        }
+    }
+    Ok(None)
+}
+
+/// A variable of the storage layout, a member of a struct or an element of an array, with its
+/// decoded value if the storage was read.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+struct StorageLayoutRow {
+    name: String,
+    #[serde(rename = "type")]
+    ty: String,
+    slot: String,
+    offset: i64,
+    bytes: String,
+    contract: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+impl StorageLayoutRow {
+    fn new(name: &str, ty: &str, slot: U256, offset: i64, bytes: &str, contract: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ty: ty.to_string(),
+            slot: slot.to_string(),
+            offset,
+            bytes: bytes.to_string(),
+            contract: contract.to_string(),
+            value: None,
+        }
+    }
+}
+
+fn print_rows(rows: &[StorageLayoutRow], values: bool) {
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN);
+    let mut header = vec!["Name", "Type", "Slot", "Offset", "Bytes", "Contract"];
+    if values {
+        header.push("Value");
+    }
+    table.set_header(header);
+
+    for row in rows {
+        let mut cells = vec![
+            row.name.clone(),
+            row.ty.clone(),
+            row.slot.clone(),
+            row.offset.to_string(),
+            row.bytes.clone(),
+            row.contract.clone(),
+        ];
+        if values {
+            cells.push(row.value.clone().unwrap_or_else(|| "-".to_string()));
+        }
+        table.add_row(cells);
+    }
+
+    println!("{table}");
+}
+
+/// A variable whose value is yet to be decoded.
+struct PendingVariable {
+    name: String,
+    slot: U256,
+    offset: i64,
+    ty: String,
+}
+
+/// Reads and decodes the values of the variables of a storage layout.
+struct StorageDecoder<'a> {
+    provider: &'a RetryProvider,
+    address: &'a NameOrAddress,
+    block: Option<BlockId>,
+    layout: &'a StorageLayout,
+    contract: String,
+    /// The slots that were read, which are shared by packed variables
+    slots: HashMap<U256, B256>,
+}
+
+impl<'a> StorageDecoder<'a> {
+    fn new(
+        provider: &'a RetryProvider,
+        address: &'a NameOrAddress,
+        block: Option<BlockId>,
+        layout: &'a StorageLayout,
+    ) -> Self {
+        Self { provider, address, block, layout, contract: String::new(), slots: HashMap::new() }
+    }
+
+    async fn read(&mut self, slot: U256) -> Result<B256> {
+        if let Some(value) = self.slots.get(&slot) {
+            return Ok(*value)
+        }
+        let value = self
+            .provider
+            .get_storage_at(self.address.clone(), B256::from(slot).to_ethers(), self.block)
+            .await?
+            .to_alloy();
+        self.slots.insert(slot, value);
+        Ok(value)
+    }
+
+    /// Decodes the variable, followed by its members or elements.
+    async fn decode(&mut self, variable: &Storage) -> Result<Vec<StorageLayoutRow>> {
+        self.contract = variable.contract.clone();
+        let mut rows = Vec::new();
+        let mut pending = vec![PendingVariable {
+            name: variable.label.clone(),
+            slot: U256::from_str(&variable.slot)?,
+            offset: variable.offset,
+            ty: variable.storage_type.clone(),
+        }];
+        while let Some(variable) = pending.pop() {
+            let (row, children) = self.decode_one(variable).await?;
+            rows.push(row);
+            // the members and elements are decoded next, in order
+            pending.extend(children.into_iter().rev());
+        }
+        Ok(rows)
+    }
+
+    /// Decodes the value of the variable, and returns its members or elements.
+    async fn decode_one(
+        &mut self,
+        variable: PendingVariable,
+    ) -> Result<(StorageLayoutRow, Vec<PendingVariable>)> {
+        let layout = self.layout;
+        let ty = storage_type_of(layout, &variable.ty)?;
+        let mut row = StorageLayoutRow::new(
+            &variable.name,
+            &ty.label,
+            variable.slot,
+            variable.offset,
+            &ty.number_of_bytes,
+            &self.contract,
+        );
+        let mut children = Vec::new();
+
+        if let Some(members) = ty.other.get("members") {
+            let members: Vec<Storage> = serde_json::from_value(members.clone())?;
+            for member in members {
+                children.push(PendingVariable {
+                    name: format!("{}.{}", variable.name, member.label),
+                    slot: variable.slot + U256::from_str(&member.slot)?,
+                    offset: member.offset,
+                    ty: member.storage_type,
+                });
+            }
+        } else if let Some(base) = ty.other.get("base") {
+            let base = base.as_str().ok_or_else(|| eyre::eyre!("Invalid array base type"))?;
+            let size = storage_type_of(layout, base)?.number_of_bytes.parse::<u64>()?;
+            let (start, length) = if ty.encoding == "dynamic_array" {
+                let length = U256::from_be_bytes(self.read(variable.slot).await?.0);
+                (U256::from_be_bytes(keccak256(variable.slot.to_be_bytes::<32>()).0), length)
+            } else {
+                (variable.slot, static_array_length(&ty.label)?)
+            };
+            row.value = Some(format!("length: {length}"));
+            for index in 0..length.min(U256::from(MAX_ARRAY_ELEMENTS)).to::<u64>() {
+                let (slot, offset) = slots::array_element_location(start, size, U256::from(index));
+                children.push(PendingVariable {
+                    name: format!("{}[{index}]", variable.name),
+                    slot,
+                    offset: offset as i64,
+                    ty: base.to_string(),
+                });
+            }
+        } else {
+            match ty.encoding.as_str() {
+                // the keys of mappings are unknown, values are read with `--slot-of`
+                "mapping" => {}
+                "bytes" => row.value = Some(self.decode_bytes(variable.slot, &ty.label).await?),
+                _ => {
+                    let size = ty.number_of_bytes.parse::<usize>()?;
+                    let word = self.read(variable.slot).await?;
+                    row.value = Some(decode_value(&ty.label, word, variable.offset, size));
+                }
+            }
+        }
+
+        Ok((row, children))
+    }
+
+    /// Decodes a `bytes` or `string` value, which is stored in the slot if it's shorter than 32
+    /// bytes, or else from the hash of the slot.
+    async fn decode_bytes(&mut self, slot: U256, label: &str) -> Result<String> {
+        let word = self.read(slot).await?;
+        let data = if word[31] & 1 == 0 {
+            let length = (word[31] / 2) as usize;
+            word[..length.min(31)].to_vec()
+        } else {
+            let length = U256::from_be_bytes(word.0) >> 1;
+            if length > U256::from(MAX_BYTES_LENGTH) {
+                return Ok(format!("<{length} bytes>"))
+            }
+            let length = length.to::<usize>();
+            let start = U256::from_be_bytes(keccak256(slot.to_be_bytes::<32>()).0);
+            let mut data = Vec::with_capacity(length);
+            for i in 0..length.div_ceil(32) {
+                data.extend_from_slice(self.read(start + U256::from(i)).await?.as_slice());
+            }
+            data.truncate(length);
+            data
+        };
+        Ok(if label == "string" {
+            format!("{:?}", String::from_utf8_lossy(&data))
+        } else {
+            hex::encode_prefixed(data)
+        })
+    }
+}
+
+/// Returns the length of a static array from its label, e.g. `uint8[2][3]` has 3 elements.
+fn static_array_length(label: &str) -> Result<U256> {
+    label
+        .strip_suffix(']')
+        .and_then(|label| label.rsplit_once('['))
+        .and_then(|(_, length)| U256::from_str(length).ok())
+        .ok_or_else(|| eyre::eyre!("Invalid static array type `{label}`"))
+}
+
+/// Decodes the `size` bytes of the value at `offset` of the storage word, counted from the right,
+/// as a value of the type.
+fn decode_value(label: &str, word: B256, offset: i64, size: usize) -> String {
+    let end = 32usize.saturating_sub(offset.max(0) as usize);
+    let bytes = &word[end.saturating_sub(size)..end];
+    let value = U256::from_be_slice(bytes);
+    if label == "bool" {
+        (value != U256::ZERO).to_string()
+    } else if label.starts_with("address") || label.starts_with("contract ") {
+        Address::from_word(value.into()).to_string()
+    } else if label.starts_with("uint") || label.starts_with("enum ") {
+        value.to_string()
+    } else if label.starts_with("int") {
+        // sign extend to 256 bits
+        let bits = bytes.len() * 8;
+        let negative = bits > 0 && bits < 256 && value.bit(bits - 1);
+        let value = if negative { value | (U256::MAX << bits) } else { value };
+        I256::from_raw(value).to_string()
+    } else {
+        hex::encode_prefixed(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_decode_packed_values() {
+        // int8 -2, bool true, address 0x..01, uint16 513 and bytes2 0xabcd, from right to left
+        let mut word = [0u8; 32];
+        word[31] = 0xfe;
+        word[30] = 1;
+        word[29] = 1;
+        word[8..10].copy_from_slice(&[2, 1]);
+        word[6..8].copy_from_slice(&[0xab, 0xcd]);
+        let word = B256::from(word);
+        assert_eq!(decode_value("int8", word, 0, 1), "-2");
+        assert_eq!(decode_value("bool", word, 1, 1), "true");
+        assert_eq!(
+            decode_value("address", word, 2, 20),
+            "0x0000000000000000000000000000000000000001"
+        );
+        assert_eq!(decode_value("uint16", word, 22, 2), "513");
+        assert_eq!(decode_value("bytes2", word, 24, 2), "0xabcd");
+        assert_eq!(decode_value("enum C.E", word, 22, 1), "1");
+        assert_eq!(decode_value("int256", B256::repeat_byte(0xff), 0, 32), "-1");
+    }
+
+    #[test]
+    fn can_parse_static_array_lengths() {
+        assert_eq!(static_array_length("uint8[3]").unwrap(), U256::from(3));
+        assert_eq!(static_array_length("uint8[2][3]").unwrap(), U256::from(3));
+        assert!(static_array_length("uint8[]").is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! Helpers for the encrypted keystores of `cast wallet import`, `export` and `list`.
+
+use alloy_primitives::Address;
+use ethers_core::rand::thread_rng;
+use eyre::{Context, Result};
+use foundry_common::fs;
+use foundry_config::Config;
+use std::path::{Path, PathBuf};
+
+/// Returns the keystore directory, `~/.foundry/keystores` by default.
+pub fn keystore_dir(dir: Option<String>) -> Result<PathBuf> {
+    match dir {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Config::foundry_keystores_dir()
+            .ok_or_else(|| eyre::eyre!("Could not find the default keystore directory.")),
+    }
+}
+
+/// Reads the password of a keystore from the file, or with a hidden prompt.
+pub fn read_password(password_file: Option<&Path>, prompt: &str) -> Result<String> {
+    match password_file {
+        Some(path) => password_from_file(path),
+        None => Ok(rpassword::prompt_password(prompt)?),
+    }
+}
+
+/// Reads the password of a new keystore from the file, or with a hidden prompt asking for it
+/// twice.
+pub fn read_new_password(password_file: Option<&Path>) -> Result<String> {
+    if let Some(path) = password_file {
+        return password_from_file(path)
+    }
+    let password = rpassword::prompt_password("Enter password: ")?;
+    let confirmation = rpassword::prompt_password("Confirm password: ")?;
+    if password != confirmation {
+        eyre::bail!("Passwords don't match")
+    }
+    Ok(password)
+}
+
+fn password_from_file(path: &Path) -> Result<String> {
+    if !path.is_file() {
+        eyre::bail!("Keystore password file `{}` does not exist", path.display())
+    }
+    Ok(fs::read_to_string(path)?.trim_end().to_string())
+}
+
+/// Encrypts the private key into a new keystore file at `path`.
+///
+/// The address of the key is stored unencrypted in the keystore, like geth does, so it can be
+/// listed without the password. The keystore is written to a temporary file first so `path` never
+/// holds a partial keystore.
+pub fn create_keystore(
+    path: &Path,
+    private_key: &[u8],
+    address: Address,
+    password: &str,
+) -> Result<()> {
+    if path.exists() {
+        eyre::bail!("Keystore file already exists at {}", path.display())
+    }
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| eyre::eyre!("invalid keystore path: {}", path.display()))?;
+    fs::create_dir_all(dir)?;
+
+    let tmp_name = format!(".{file_name}.tmp");
+    let tmp_path = dir.join(&tmp_name);
+    let written = (|| -> Result<()> {
+        eth_keystore::encrypt_key(dir, &mut thread_rng(), private_key, password, Some(&tmp_name))?;
+        let mut keystore: serde_json::Value = fs::read_json_file(&tmp_path)?;
+        keystore["address"] = hex::encode(address).into();
+        fs::write(&tmp_path, serde_json::to_string(&keystore)?)?;
+        std::fs::rename(&tmp_path, path)
+            .wrap_err_with(|| format!("failed to write keystore {}", path.display()))
+    })();
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    written
+}
+
+/// Decrypts the private key of the keystore.
+///
+/// The keystore file is only read, a wrong password leaves it untouched.
+pub fn decrypt_keystore(path: &Path, password: &str) -> Result<Vec<u8>> {
+    if !path.is_file() {
+        eyre::bail!("Keystore file `{}` does not exist", path.display())
+    }
+    eth_keystore::decrypt_key(path, password)
+        .wrap_err_with(|| format!("Failed to decrypt keystore {}", path.display()))
+}
+
+/// Returns the address stored unencrypted in the keystore, if any.
+pub fn keystore_address(path: &Path) -> Option<Address> {
+    let keystore: serde_json::Value = fs::read_json_file(path).ok()?;
+    keystore.get("address")?.as_str()?.parse().ok()
+}
+
+/// Returns the paths of the keystores in the directory, sorted by name.
+pub fn list_keystores(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut keystores = vec![];
+    for entry in std::fs::read_dir(dir).wrap_err("Failed to read the directory")? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(true, |name| name.starts_with('.'));
+        if path.is_file() && path.extension().is_none() && !hidden {
+            keystores.push(path);
+        }
+    }
+    keystores.sort();
+    Ok(keystores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_create_and_decrypt_keystore() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("account");
+        let private_key = [0x42; 32];
+        let address = Address::repeat_byte(1);
+
+        create_keystore(&path, &private_key, address, "secret").unwrap();
+        assert_eq!(keystore_address(&path), Some(address));
+        assert_eq!(decrypt_keystore(&path, "secret").unwrap(), private_key);
+        assert_eq!(list_keystores(dir.path()).unwrap(), vec![path.clone()]);
+
+        // a wrong password fails without touching the keystore
+        assert!(decrypt_keystore(&path, "wrong").is_err());
+        assert_eq!(decrypt_keystore(&path, "secret").unwrap(), private_key);
+
+        // existing keystores are never overwritten
+        assert!(create_keystore(&path, &[0x43; 32], address, "other").is_err());
+        assert_eq!(decrypt_keystore(&path, "secret").unwrap(), private_key);
+    }
+}
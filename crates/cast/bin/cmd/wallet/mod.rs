@@ -2,26 +2,36 @@ use alloy_primitives::Address;
 use clap::Parser;
 use ethers_core::{
     rand::thread_rng,
-    types::{transaction::eip712::TypedData, Signature},
+    types::{BlockNumber, Signature},
 };
+use ethers_providers::Middleware;
 use ethers_signers::{
     coins_bip39::{English, Mnemonic},
     LocalWallet, MnemonicBuilder, Signer,
 };
 use eyre::{Context, Result};
-use foundry_cli::opts::{RawWallet, Wallet};
+use foundry_cli::{
+    opts::{RawWallet, RpcOpts, Wallet},
+    utils,
+};
 use foundry_common::{
+    authorization::Authorization,
     fs,
     types::{ToAlloy, ToEthers},
 };
 use foundry_config::Config;
 use serde_json::json;
-use std::path::Path;
+use std::path::PathBuf;
 use yansi::Paint;
 
 pub mod vanity;
 use vanity::VanityArgs;
 
+mod keystore;
+
+mod typed_data;
+use typed_data::DomainOverrides;
+
 /// CLI arguments for `cast wallet`.
 #[derive(Debug, Parser)]
 pub enum WalletSubcommands {
@@ -93,8 +103,9 @@ pub enum WalletSubcommands {
         /// Use --data flag to denote the message is a string of typed data.
         /// Use --data --from-file to denote the message is a file name containing typed data.
         /// The data will be combined and hashed using the EIP712 specification before signing.
-        /// The data should be formatted as JSON.
-        message: String,
+        /// The data should be formatted as JSON. Omitted with --data-file.
+        #[clap(required_unless_present = "data_file")]
+        message: Option<String>,
 
         /// If provided, the message will be treated as typed data.
         #[clap(long)]
@@ -105,6 +116,46 @@ pub enum WalletSubcommands {
         #[clap(long, requires = "data")]
         from_file: bool,
 
+        /// Sign the typed data of the `eth_signTypedData_v4` JSON document, with its `domain`,
+        /// `types`, `primaryType` and `message`.
+        #[clap(long, value_name = "PATH", conflicts_with_all = ["message", "data"])]
+        data_file: Option<PathBuf>,
+
+        #[clap(flatten)]
+        domain: DomainOverrides,
+
+        /// Print the signature split into its `v`, `r` and `s` values.
+        #[clap(long)]
+        split: bool,
+
+        #[clap(flatten)]
+        wallet: Wallet,
+    },
+
+    /// Sign an EIP-7702 authorization, which delegates the code of the signer to a contract.
+    ///
+    /// The signed authorization is printed as the hex encoded RLP list
+    /// `[chain_id, address, nonce, y_parity, r, s]`.
+    #[clap(visible_alias = "sa")]
+    SignAuth {
+        /// The address of the contract to delegate the code to.
+        address: Address,
+
+        /// The nonce of the signer the authorization is valid for.
+        ///
+        /// Defaults to the pending nonce of the signer on the RPC.
+        #[clap(long)]
+        nonce: Option<u64>,
+
+        /// The chain the authorization is valid on, 0 for all chains.
+        ///
+        /// Defaults to the chain of the RPC.
+        #[clap(long)]
+        chain_id: Option<u64>,
+
+        #[clap(flatten)]
+        rpc: RpcOpts,
+
         #[clap(flatten)]
         wallet: Wallet,
     },
@@ -122,7 +173,25 @@ pub enum WalletSubcommands {
         #[clap(long, short)]
         address: Address,
     },
-    /// Import a private key into an encrypted keystore.
+
+    /// Verify the signature of typed data.
+    #[clap(visible_alias = "vt")]
+    VerifyTypedData {
+        /// The `eth_signTypedData_v4` JSON document of the typed data.
+        #[clap(value_name = "PATH")]
+        data_file: PathBuf,
+
+        /// The signature to verify.
+        signature: Signature,
+
+        /// The address of the signer of the typed data.
+        #[clap(long, short)]
+        address: Address,
+
+        #[clap(flatten)]
+        domain: DomainOverrides,
+    },
+    /// Import a private key, or a key derived from a mnemonic, into an encrypted keystore.
     #[clap(visible_alias = "i")]
     Import {
         /// The name for the account in the keystore.
@@ -132,12 +201,47 @@ pub enum WalletSubcommands {
         /// (~/.foundry/keystores)
         #[clap(long, short)]
         keystore_dir: Option<String>,
+        /// The file containing the password of the new keystore, otherwise it is prompted for.
+        #[clap(long, value_name = "PASSWORD_FILE")]
+        password_file: Option<PathBuf>,
         #[clap(flatten)]
         raw_wallet_options: RawWallet,
     },
+    /// Export the private key of a keystore, or re-encrypt it with a new password.
+    Export {
+        /// The name of the account in the keystore.
+        #[clap(value_name = "ACCOUNT_NAME")]
+        account_name: String,
+        /// If provided, the keystore will be read from here instead of the default keystores
+        /// directory (~/.foundry/keystores)
+        #[clap(long, short)]
+        keystore_dir: Option<String>,
+        /// The file containing the password of the keystore, otherwise it is prompted for.
+        #[clap(long, value_name = "PASSWORD_FILE")]
+        password_file: Option<PathBuf>,
+        /// Print the decrypted private key.
+        ///
+        /// This is UNSAFE, the private key is printed in cleartext.
+        #[clap(long = "unsafe", conflicts_with = "out")]
+        unsafe_print: bool,
+        /// The path of the new keystore, encrypted with a new password.
+        #[clap(long, required_unless_present = "unsafe_print", value_name = "PATH")]
+        out: Option<PathBuf>,
+        /// The file containing the password of the new keystore, otherwise it is prompted for.
+        #[clap(long, requires = "out", value_name = "PASSWORD_FILE")]
+        new_password_file: Option<PathBuf>,
+    },
     /// List all the accounts in the keystore default directory
     #[clap(visible_alias = "ls")]
-    List,
+    List {
+        /// If provided, the keystores will be listed from here instead of the default keystores
+        /// directory (~/.foundry/keystores)
+        #[clap(long, short)]
+        keystore_dir: Option<String>,
+        /// Also print the addresses of the accounts, without decrypting the keystores.
+        #[clap(long)]
+        detailed: bool,
+    },
 
     /// Derives private key from mnemonic
     #[clap(name = "derive-private-key", visible_aliases = &["--derive-private-key"])]
@@ -247,21 +351,72 @@ impl WalletSubcommands {
                 let addr = wallet.address();
                 println!("{}", addr.to_alloy().to_checksum(None));
             }
-            WalletSubcommands::Sign { message, data, from_file, wallet } => {
+            WalletSubcommands::Sign {
+                message,
+                data,
+                from_file,
+                data_file,
+                domain,
+                split,
+                wallet,
+            } => {
+                if !data && data_file.is_none() && !domain.is_empty() {
+                    eyre::bail!("The domain can only be overridden when signing typed data")
+                }
                 let wallet = wallet.signer(0).await?;
-                let sig = if data {
-                    let typed_data: TypedData = if from_file {
+                // the message is required unless signing a typed data file
+                let message = message.unwrap_or_default();
+                let sig = if let Some(path) = data_file {
+                    wallet.sign_typed_data(&typed_data::read_typed_data(&path, &domain)?).await?
+                } else if data {
+                    let typed_data = if from_file {
                         // data is a file name, read json from file
-                        foundry_common::fs::read_json_file(message.as_ref())?
+                        typed_data::read_typed_data(message.as_ref(), &domain)?
                     } else {
                         // data is a json string
-                        serde_json::from_str(&message)?
+                        typed_data::parse_typed_data(&message, &domain)?
                     };
                     wallet.sign_typed_data(&typed_data).await?
                 } else {
                     wallet.sign_message(Self::hex_str_to_bytes(&message)?).await?
                 };
-                println!("0x{sig}");
+                if split {
+                    println!("{}", typed_data::split_signature(&sig));
+                } else {
+                    println!("0x{sig}");
+                }
+            }
+            WalletSubcommands::SignAuth { address, nonce, chain_id, rpc, wallet } => {
+                // remote and hardware signers can't sign raw hashes
+                let wallet = wallet.try_resolve_local_wallet()?.ok_or_else(|| {
+                    eyre::eyre!(
+                        "Signing an authorization requires a private key, mnemonic or keystore"
+                    )
+                })?;
+                let (nonce, chain_id) = match (nonce, chain_id) {
+                    (Some(nonce), Some(chain_id)) => (nonce, chain_id),
+                    (nonce, chain_id) => {
+                        let provider = utils::get_provider(&Config::from(&rpc))?;
+                        let nonce = match nonce {
+                            Some(nonce) => nonce,
+                            None => provider
+                                .get_transaction_count(
+                                    wallet.address(),
+                                    Some(BlockNumber::Pending.into()),
+                                )
+                                .await?
+                                .as_u64(),
+                        };
+                        let chain_id = match chain_id {
+                            Some(chain_id) => chain_id,
+                            None => provider.get_chainid().await?.as_u64(),
+                        };
+                        (nonce, chain_id)
+                    }
+                };
+                let auth = Authorization { chain_id, address, nonce };
+                let signature = wallet.sign_hash(auth.signature_hash().to_ethers())?;
+                println!("{}", auth.into_signed(&signature).to_rlp());
             }
             WalletSubcommands::Verify { message, signature, address } => {
                 match signature.verify(Self::hex_str_to_bytes(&message)?, address.to_ethers()) {
@@ -273,20 +428,28 @@ impl WalletSubcommands {
                     }
                 }
             }
-            WalletSubcommands::Import { account_name, keystore_dir, raw_wallet_options } => {
-                // Set up keystore directory
-                let dir = if let Some(path) = keystore_dir {
-                    Path::new(&path).to_path_buf()
+            WalletSubcommands::VerifyTypedData { data_file, signature, address, domain } => {
+                let typed_data = typed_data::read_typed_data(&data_file, &domain)?;
+                let signer = typed_data::recover_typed_data_signer(&typed_data, &signature)?;
+                if signer == address {
+                    println!("Validation succeeded. Address {address} signed this typed data.")
                 } else {
-                    Config::foundry_keystores_dir().ok_or_else(|| {
-                        eyre::eyre!("Could not find the default keystore directory.")
-                    })?
-                };
-
-                fs::create_dir_all(&dir)?;
+                    println!(
+                        "Validation failed. Address {address} did not sign this typed data, it \
+                         was signed by {signer}."
+                    )
+                }
+            }
+            WalletSubcommands::Import {
+                account_name,
+                keystore_dir,
+                password_file,
+                raw_wallet_options,
+            } => {
+                let dir = keystore::keystore_dir(keystore_dir)?;
 
                 // check if account exists already
-                let keystore_path = Path::new(&dir).join(&account_name);
+                let keystore_path = dir.join(&account_name);
                 if keystore_path.exists() {
                     eyre::bail!("Keystore file already exists at {}", keystore_path.display());
                 }
@@ -299,62 +462,70 @@ impl WalletSubcommands {
 Did you set a private key or mnemonic?
 Run `cast wallet import --help` and use the corresponding CLI
 flag to set your key via:
---private-key, --mnemonic-path or --interactive."
+--private-key, --mnemonic or --interactive."
                     )
                 })?;
 
-                let private_key = wallet.signer().to_bytes();
-                let password = rpassword::prompt_password("Enter password: ")?;
-
-                let mut rng = thread_rng();
-                eth_keystore::encrypt_key(
-                    &dir,
-                    &mut rng,
-                    private_key,
+                let password = keystore::read_new_password(password_file.as_deref())?;
+                let address = wallet.address().to_alloy();
+                keystore::create_keystore(
+                    &keystore_path,
+                    &wallet.signer().to_bytes(),
+                    address,
                     &password,
-                    Some(&account_name),
                 )?;
-                let address = wallet.address();
                 let success_message = format!(
-                    "`{}` keystore was saved successfully. Address: {:?}",
+                    "`{}` keystore was saved successfully. Address: {}",
                     &account_name, address,
                 );
                 println!("{}", Paint::green(success_message));
             }
-            WalletSubcommands::List => {
-                let default_keystore_dir = Config::foundry_keystores_dir()
-                    .ok_or_else(|| eyre::eyre!("Could not find the default keystore directory."))?;
+            WalletSubcommands::Export {
+                account_name,
+                keystore_dir,
+                password_file,
+                unsafe_print,
+                out,
+                new_password_file,
+            } => {
+                let path = keystore::keystore_dir(keystore_dir)?.join(&account_name);
+                let password =
+                    keystore::read_password(password_file.as_deref(), "Enter keystore password: ")?;
+                let private_key = keystore::decrypt_keystore(&path, &password)?;
+                let wallet = LocalWallet::from_bytes(&private_key)?;
+
+                if unsafe_print {
+                    println!("0x{}", hex::encode(&private_key));
+                } else if let Some(out) = out {
+                    let password = keystore::read_new_password(new_password_file.as_deref())?;
+                    let address = wallet.address().to_alloy();
+                    keystore::create_keystore(&out, &private_key, address, &password)?;
+                    let success_message = format!(
+                        "`{}` keystore was exported to {}. Address: {}",
+                        &account_name,
+                        out.display(),
+                        address,
+                    );
+                    println!("{}", Paint::green(success_message));
+                }
+            }
+            WalletSubcommands::List { keystore_dir, detailed } => {
+                let dir = keystore::keystore_dir(keystore_dir)?;
                 // Create the keystore directory if it doesn't exist
-                fs::create_dir_all(&default_keystore_dir)?;
-                // List all files in keystore directory
-                let keystore_files: Result<Vec<_>, eyre::Report> =
-                    std::fs::read_dir(&default_keystore_dir)
-                        .wrap_err("Failed to read the directory")?
-                        .filter_map(|entry| match entry {
-                            Ok(entry) => {
-                                let path = entry.path();
-                                if path.is_file() && path.extension().is_none() {
-                                    Some(Ok(path))
-                                } else {
-                                    None
-                                }
-                            }
-                            Err(e) => Some(Err(e.into())),
-                        })
-                        .collect::<Result<Vec<_>, eyre::Report>>();
-                // Print the names of the keystore files
-                match keystore_files {
-                    Ok(files) => {
-                        // Print the names of the keystore files
-                        for file in files {
-                            if let Some(file_name) = file.file_name() {
-                                if let Some(name) = file_name.to_str() {
-                                    println!("{}", name);
-                                }
-                            }
-                        }
+                fs::create_dir_all(&dir)?;
+                for path in keystore::list_keystores(&dir)? {
+                    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                        continue
+                    };
+                    if !detailed {
+                        println!("{name}");
+                        continue
+                    }
+                    // keystores created by older versions have no address
+                    match keystore::keystore_address(&path) {
+                        Some(address) => println!("{name} ({address})"),
+                        None => println!("{name} (unknown address)"),
                     }
-                    Err(e) => return Err(e),
                 }
             }
             WalletSubcommands::DerivePrivateKey { mnemonic, mnemonic_index } => {
@@ -392,7 +563,7 @@ mod tests {
         let args = WalletSubcommands::parse_from(["foundry-cli", "sign", "deadbeef"]);
         match args {
             WalletSubcommands::Sign { message, data, from_file, .. } => {
-                assert_eq!(message, "deadbeef".to_string());
+                assert_eq!(message, Some("deadbeef".to_string()));
                 assert!(!data);
                 assert!(!from_file);
             }
@@ -405,7 +576,7 @@ mod tests {
         let args = WalletSubcommands::parse_from(["foundry-cli", "sign", "0xdeadbeef"]);
         match args {
             WalletSubcommands::Sign { message, data, from_file, .. } => {
-                assert_eq!(message, "0xdeadbeef".to_string());
+                assert_eq!(message, Some("0xdeadbeef".to_string()));
                 assert!(!data);
                 assert!(!from_file);
             }
@@ -418,7 +589,7 @@ mod tests {
         let args = WalletSubcommands::parse_from(["foundry-cli", "sign", "--data", "{ ... }"]);
         match args {
             WalletSubcommands::Sign { message, data, from_file, .. } => {
-                assert_eq!(message, "{ ... }".to_string());
+                assert_eq!(message, Some("{ ... }".to_string()));
                 assert!(data);
                 assert!(!from_file);
             }
@@ -437,11 +608,36 @@ mod tests {
         ]);
         match args {
             WalletSubcommands::Sign { message, data, from_file, .. } => {
-                assert_eq!(message, "tests/data/typed_data.json".to_string());
+                assert_eq!(message, Some("tests/data/typed_data.json".to_string()));
                 assert!(data);
                 assert!(from_file);
             }
             _ => panic!("expected WalletSubcommands::Sign"),
         }
     }
+
+    #[test]
+    fn can_parse_wallet_sign_typed_data_file() {
+        let args = WalletSubcommands::parse_from([
+            "foundry-cli",
+            "sign",
+            "--data-file",
+            "typed_data.json",
+            "--domain-chain-id",
+            "5",
+            "--split",
+        ]);
+        match args {
+            WalletSubcommands::Sign { message, data_file, domain, split, .. } => {
+                assert_eq!(message, None);
+                assert_eq!(data_file, Some(PathBuf::from("typed_data.json")));
+                assert_eq!(domain.domain_chain_id, Some(5));
+                assert!(split);
+            }
+            _ => panic!("expected WalletSubcommands::Sign"),
+        }
+        let args = ["foundry-cli", "sign", "--data", "--data-file", "typed_data.json"];
+        assert!(WalletSubcommands::try_parse_from(args).is_err());
+        assert!(WalletSubcommands::try_parse_from(["foundry-cli", "sign"]).is_err());
+    }
 }
@@ -0,0 +1,177 @@
+use alloy_primitives::{Address, B256};
+use clap::Parser;
+use ethers_core::types::{
+    transaction::eip712::{Eip712, Eip712DomainType, TypedData},
+    Signature, H256,
+};
+use eyre::{Context, Result};
+use foundry_common::types::{ToAlloy, ToEthers};
+use std::path::Path;
+
+/// Overrides of the domain of typed data, to retarget a document to another chain or contract.
+#[derive(Clone, Debug, Default, Parser)]
+pub struct DomainOverrides {
+    /// Override the `chainId` of the domain of the typed data.
+    #[clap(long, value_name = "CHAIN_ID")]
+    pub domain_chain_id: Option<u64>,
+
+    /// Override the `verifyingContract` of the domain of the typed data.
+    #[clap(long, value_name = "ADDRESS")]
+    pub domain_verifying_contract: Option<Address>,
+}
+
+impl DomainOverrides {
+    /// Returns whether no field of the domain is overridden.
+    pub fn is_empty(&self) -> bool {
+        self.domain_chain_id.is_none() && self.domain_verifying_contract.is_none()
+    }
+
+    /// Applies the overrides to the domain, and declares the overridden fields in the
+    /// `EIP712Domain` type of the document if it's missing them.
+    pub fn apply(&self, typed_data: &mut TypedData) {
+        if self.is_empty() {
+            return
+        }
+        let mut fields = Vec::new();
+        if let Some(chain_id) = self.domain_chain_id {
+            typed_data.domain.chain_id = Some(chain_id.into());
+            fields.push(("chainId", "uint256"));
+        }
+        if let Some(verifying_contract) = self.domain_verifying_contract {
+            typed_data.domain.verifying_contract = Some(verifying_contract.to_ethers());
+            fields.push(("verifyingContract", "address"));
+        }
+        let Some(domain_type) = typed_data.types.get_mut("EIP712Domain") else { return };
+        for (name, ty) in fields {
+            if !domain_type.iter().any(|field| field.name == name) {
+                domain_type.push(Eip712DomainType { name: name.to_string(), r#type: ty.into() });
+            }
+        }
+        // the fields of the domain type are in the order of the spec
+        let order = ["name", "version", "chainId", "verifyingContract", "salt"];
+        domain_type.sort_by_key(|field| order.iter().position(|name| *name == field.name));
+    }
+}
+
+/// Reads an `eth_signTypedData_v4` document, with the overrides of its domain applied.
+pub fn read_typed_data(path: &Path, overrides: &DomainOverrides) -> Result<TypedData> {
+    let mut typed_data: TypedData = foundry_common::fs::read_json_file(path)
+        .wrap_err_with(|| format!("could not read the typed data of {}", path.display()))?;
+    overrides.apply(&mut typed_data);
+    Ok(typed_data)
+}
+
+/// Parses typed data, with the overrides of its domain applied.
+pub fn parse_typed_data(json: &str, overrides: &DomainOverrides) -> Result<TypedData> {
+    let mut typed_data: TypedData = serde_json::from_str(json)?;
+    overrides.apply(&mut typed_data);
+    Ok(typed_data)
+}
+
+/// Recovers the signer of the typed data.
+pub fn recover_typed_data_signer(typed_data: &TypedData, signature: &Signature) -> Result<Address> {
+    let hash = typed_data.encode_eip712()?;
+    Ok(signature.recover(H256::from(hash))?.to_alloy())
+}
+
+/// Formats the signature split into its `v`, `r` and `s` values.
+pub fn split_signature(signature: &Signature) -> String {
+    let (r, s) = (B256::from(signature.r.to_alloy()), B256::from(signature.s.to_alloy()));
+    format!("v: {}\nr: {r}\ns: {s}", signature.v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_signers::{LocalWallet, Signer};
+
+    // <https://github.com/ethereum/EIPs/blob/master/assets/eip-712/Example.js>
+    const MAIL: &str = r#"{
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "Person": [
+                { "name": "name", "type": "string" },
+                { "name": "wallet", "type": "address" }
+            ],
+            "Mail": [
+                { "name": "from", "type": "Person" },
+                { "name": "to", "type": "Person" },
+                { "name": "contents", "type": "string" }
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        },
+        "message": {
+            "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+            "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+            "contents": "Hello, Bob!"
+        }
+    }"#;
+
+    // `keccak256("cow")`
+    const COW: &str = "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4";
+
+    #[test]
+    fn matches_spec_vectors() {
+        let typed_data = parse_typed_data(MAIL, &DomainOverrides::default()).unwrap();
+        assert_eq!(
+            hex::encode(typed_data.domain.separator()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            hex::encode(typed_data.struct_hash().unwrap()),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        let hash = typed_data.encode_eip712().unwrap();
+        assert_eq!(
+            hex::encode(hash),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+
+        let wallet: LocalWallet = COW.parse().unwrap();
+        let signature = wallet.sign_hash(H256::from(hash)).unwrap();
+        assert_eq!(
+            split_signature(&signature),
+            "\
+v: 28
+r: 0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d
+s: 0x07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562"
+        );
+        assert_eq!(
+            recover_typed_data_signer(&typed_data, &signature).unwrap(),
+            wallet.address().to_alloy()
+        );
+    }
+
+    #[test]
+    fn can_override_domain() {
+        let overrides = DomainOverrides { domain_chain_id: Some(5), ..Default::default() };
+        let typed_data = parse_typed_data(MAIL, &overrides).unwrap();
+        assert_eq!(
+            hex::encode(typed_data.encode_eip712().unwrap()),
+            "37abd8589f35b81d0ed965127e85b3de86f17c06f3736cfbb5f8e67767a8dd45"
+        );
+
+        // the overridden fields are declared if the document is missing them
+        let mut json: serde_json::Value = serde_json::from_str(MAIL).unwrap();
+        json["types"]["EIP712Domain"].as_array_mut().unwrap().remove(2);
+        json["domain"].as_object_mut().unwrap().remove("chainId");
+        let typed_data = parse_typed_data(&json.to_string(), &overrides).unwrap();
+        let fields = typed_data.types["EIP712Domain"].iter().map(|field| field.name.as_str());
+        assert_eq!(fields.collect::<Vec<_>>(), ["name", "version", "chainId", "verifyingContract"]);
+        assert_eq!(
+            hex::encode(typed_data.encode_eip712().unwrap()),
+            "37abd8589f35b81d0ed965127e85b3de86f17c06f3736cfbb5f8e67767a8dd45"
+        );
+    }
+}
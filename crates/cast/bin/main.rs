@@ -1,26 +1,24 @@
 #[macro_use]
 extern crate tracing;
 
+use alloy_json_abi::ContractObject;
 use alloy_primitives::{keccak256, Address, B256};
-use cast::{Cast, SimpleCast};
+use cast::{proof::ProofVerification, raw_tx::DecodedTransaction, Cast, SimpleCast};
 use clap::{CommandFactory, Parser};
 use clap_complete::generate;
 use ethers_core::types::{BlockId, BlockNumber::Latest};
 use ethers_providers::Middleware;
-use eyre::Result;
-use foundry_cli::{handler, prompt, stdin, utils};
+use eyre::{Result, WrapErr};
+use foundry_cli::{handler, stdin, utils};
 use foundry_common::{
-    abi::get_event,
+    abi::{get_event, get_func, rank_calldata_signatures},
     fmt::format_tokens,
     fs,
-    selectors::{
-        decode_calldata, decode_event_topic, decode_function_selector, decode_selectors,
-        import_selectors, parse_signatures, pretty_calldata, ParsedSignatures, SelectorImportData,
-        SelectorType,
-    },
+    selectors::{import_selectors, parse_signatures, ParsedSignatures, SelectorImportData},
     types::{ToAlloy, ToEthers},
 };
-use foundry_config::Config;
+use foundry_config::{chains::parse_chain, Config};
+use foundry_evm::traces::identifier::{SignaturesIdentifier, SingleSignaturesIdentifier};
 use std::time::Instant;
 
 pub mod cmd;
@@ -36,6 +34,10 @@ async fn main() -> Result<()> {
     utils::enable_paint();
 
     let opts = Opts::parse();
+    // the config of every command is offline
+    if opts.offline {
+        std::env::set_var("FOUNDRY_OFFLINE", "true");
+    }
     match opts.sub {
         // Constants
         Subcommands::MaxInt { r#type } => {
@@ -154,27 +156,37 @@ async fn main() -> Result<()> {
         }
 
         // ABI encoding & decoding
-        Subcommands::AbiDecode { sig, calldata, input } => {
-            let tokens = SimpleCast::abi_decode(&sig, &calldata, input)?;
+        Subcommands::AbiDecode { sig, calldata, input, packed, types } => {
+            let tokens = match (types, sig, calldata) {
+                // with `--types`, the packed data is the only positional argument
+                (Some(types), Some(data), None) => SimpleCast::abi_decode_packed(&types, &data)?,
+                (None, Some(sig), Some(data)) if packed => {
+                    SimpleCast::abi_decode_packed(&sig, &data)?
+                }
+                (None, Some(sig), Some(data)) => SimpleCast::abi_decode(&sig, &data, input)?,
+                (Some(_), ..) => eyre::bail!("pass only the packed data with `--types`"),
+                _ => eyre::bail!("pass the function signature and the data to decode"),
+            };
             let tokens = format_tokens(&tokens);
             tokens.for_each(|t| println!("{t}"));
         }
-        Subcommands::AbiEncode { sig, args } => {
-            println!("{}", SimpleCast::abi_encode(&sig, &args)?);
-        }
-        Subcommands::CalldataDecode { sig, calldata } => {
-            let tokens = SimpleCast::calldata_decode(&sig, &calldata, true)?;
-            let tokens = format_tokens(&tokens);
-            tokens.for_each(|t| println!("{t}"));
+        Subcommands::AbiEncode { sig, args, packed } => {
+            if packed {
+                println!("{}", SimpleCast::abi_encode_packed(&sig, &args)?);
+            } else {
+                println!("{}", SimpleCast::abi_encode(&sig, &args)?);
+            }
         }
+        Subcommands::CalldataDecode(cmd) => cmd.run()?,
         Subcommands::CalldataEncode { sig, args } => {
             println!("{}", SimpleCast::calldata_encode(sig, &args)?);
         }
         Subcommands::Interface(cmd) => cmd.run().await?,
         Subcommands::Bind(cmd) => cmd.run().await?,
-        Subcommands::PrettyCalldata { calldata, offline } => {
+        Subcommands::PrettyCalldata { calldata } => {
             let calldata = stdin::unwrap_line(calldata)?;
-            println!("{}", pretty_calldata(&calldata, offline).await?);
+            let identifier = signatures_identifier().await?;
+            println!("{}", identifier.write().await.pretty_calldata(&calldata).await?);
         }
         Subcommands::Sig { sig, optimize } => {
             let sig = stdin::unwrap_line(sig)?;
@@ -219,27 +231,20 @@ async fn main() -> Result<()> {
                 Cast::new(provider).base_fee(block.unwrap_or(BlockId::Number(Latest))).await?
             );
         }
-        Subcommands::Block { block, full, field, json, rpc } => {
-            let config = Config::from(&rpc);
-            let provider = utils::get_provider(&config)?;
-            println!(
-                "{}",
-                Cast::new(provider)
-                    .block(block.unwrap_or(BlockId::Number(Latest)), full, field, json)
-                    .await?
-            );
-        }
+        Subcommands::Block(cmd) => cmd.run().await?,
         Subcommands::BlockNumber { rpc } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
             println!("{}", Cast::new(provider).block_number().await?);
         }
-        Subcommands::Chain { rpc } => {
-            let config = Config::from(&rpc);
-            let provider = utils::get_provider(&config)?;
-            println!("{}", Cast::new(provider).chain().await?);
+        Subcommands::Chain(cmd) => cmd.run().await?,
+        Subcommands::ChainId { name: Some(name), .. } => {
+            let chain = parse_chain(&name).ok_or_else(|| {
+                eyre::eyre!("unknown chain `{name}`, see `cast chain --list` for the known chains")
+            })?;
+            println!("{}", chain.id());
         }
-        Subcommands::ChainId { rpc } => {
+        Subcommands::ChainId { name: None, rpc } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
             println!("{}", Cast::new(provider).chain_id().await?);
@@ -249,6 +254,8 @@ async fn main() -> Result<()> {
             let provider = utils::get_provider(&config)?;
             println!("{}", provider.client_version().await?);
         }
+        Subcommands::CreationCode(cmd) => cmd.run().await?,
+        Subcommands::ConstructorArgs(cmd) => cmd.run().await?,
         Subcommands::Code { block, who, disassemble, rpc } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
@@ -270,38 +277,13 @@ async fn main() -> Result<()> {
         Subcommands::Disassemble { bytecode } => {
             println!("{}", SimpleCast::disassemble(&bytecode)?);
         }
-        Subcommands::Selectors { bytecode, resolve } => {
-            let selectors_and_args = SimpleCast::extract_selectors(&bytecode)?;
-            if resolve {
-                let selectors_it = selectors_and_args.iter().map(|r| &r.0);
-                let resolve_results =
-                    decode_selectors(SelectorType::Function, selectors_it).await?;
-
-                let max_args_len = selectors_and_args.iter().map(|r| r.1.len()).max().unwrap_or(0);
-                for ((selector, arguments), func_names) in
-                    selectors_and_args.into_iter().zip(resolve_results.into_iter())
-                {
-                    let resolved = match func_names {
-                        Some(v) => v.join("|"),
-                        None => "".to_string(),
-                    };
-                    println!("{selector}\t{arguments:max_args_len$}\t{resolved}");
-                }
-            } else {
-                for (selector, arguments) in selectors_and_args {
-                    println!("{selector}\t{arguments}");
-                }
-            }
-        }
+        Subcommands::Selectors(cmd) => cmd.run().await?,
         Subcommands::FindBlock(cmd) => cmd.run().await?,
-        Subcommands::GasPrice { rpc } => {
-            let config = Config::from(&rpc);
-            let provider = utils::get_provider(&config)?;
-            println!("{}", Cast::new(provider).gas_price().await?);
-        }
-        Subcommands::Index { key_type, key, slot_number } => {
-            println!("{}", SimpleCast::index(&key_type, &key, &slot_number)?);
-        }
+        Subcommands::GasPrice(cmd) => cmd.run().await?,
+        Subcommands::Index(cmd) => cmd.run().await?,
+        Subcommands::IndexErc7201(cmd) => cmd.run().await?,
+        Subcommands::IndexArray(cmd) => cmd.run().await?,
+        Subcommands::Index1967(cmd) => cmd.run().await?,
         Subcommands::Implementation { block, who, rpc } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
@@ -312,24 +294,56 @@ async fn main() -> Result<()> {
             let provider = utils::get_provider(&config)?;
             println!("{}", Cast::new(provider).admin(who, block).await?);
         }
-        Subcommands::Nonce { block, who, rpc } => {
+        Subcommands::Nonce { block, latest, who, rpc } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
-            println!("{}", Cast::new(provider).nonce(who, block).await?);
+            let cast = Cast::new(provider);
+            let nonce = if block.is_some() || latest {
+                cast.nonce(who, block).await?
+            } else {
+                cast.pending_nonce(who).await?
+            };
+            println!("{nonce}");
         }
-        Subcommands::Proof { address, slots, rpc, block } => {
+        Subcommands::Pending(cmd) => cmd.run().await?,
+        Subcommands::Proof { address, slots, rpc, block, verify, json } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
-            let value = provider
-                .get_proof(address, slots.into_iter().map(|s| s.to_ethers()).collect(), block)
-                .await?;
-            println!("{}", serde_json::to_string(&value)?);
+            let slots = slots.into_iter().map(|s| s.to_ethers()).collect();
+            if !verify {
+                let value = provider.get_proof(address, slots, block).await?;
+                println!("{}", serde_json::to_string(&value)?);
+                return Ok(())
+            }
+
+            // the proof must be of the block whose header is verified against
+            let header = provider
+                .get_block(block.unwrap_or_else(|| Latest.into()))
+                .await?
+                .ok_or_else(|| eyre::eyre!("block not found"))?;
+            let number = header.number.ok_or_else(|| eyre::eyre!("block is pending"))?;
+            let proof = provider.get_proof(address, slots, Some(number.into())).await?;
+            let verification =
+                ProofVerification::new(number.as_u64(), header.state_root.to_alloy(), &proof);
+
+            if json {
+                let mut value = serde_json::to_value(&proof)?;
+                value["verification"] = serde_json::to_value(&verification)?;
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                print!("{verification}");
+            }
+            if !verification.is_valid() {
+                eyre::bail!("the proof is invalid");
+            }
         }
         Subcommands::Rpc(cmd) => cmd.run().await?,
         Subcommands::Storage(cmd) => cmd.run().await?,
+        Subcommands::StorageLayout(cmd) => cmd.run().await?,
 
         // Calls & transactions
         Subcommands::Call(cmd) => cmd.run().await?,
+        Subcommands::Multicall(cmd) => cmd.run().await?,
         Subcommands::Estimate(cmd) => cmd.run().await?,
         Subcommands::PublishTx { raw_tx, cast_async, rpc } => {
             let config = Config::from(&rpc);
@@ -346,17 +360,10 @@ async fn main() -> Result<()> {
                 println!("{}", serde_json::json!(receipt));
             }
         }
-        Subcommands::Receipt { tx_hash, field, json, cast_async, confirmations, rpc } => {
-            let config = Config::from(&rpc);
-            let provider = utils::get_provider(&config)?;
-            println!(
-                "{}",
-                Cast::new(provider)
-                    .receipt(tx_hash, field, confirmations, cast_async, json)
-                    .await?
-            );
-        }
+        Subcommands::Receipt(cmd) => cmd.run().await?,
         Subcommands::Run(cmd) => cmd.run().await?,
+        Subcommands::Simulate(cmd) => cmd.run().await?,
+        Subcommands::MakeTx(cmd) => cmd.run().await?,
         Subcommands::SendTx(cmd) => cmd.run().await?,
         Subcommands::Tx { tx_hash, field, raw, json, rpc } => {
             let config = Config::from(&rpc);
@@ -371,7 +378,12 @@ async fn main() -> Result<()> {
         // 4Byte
         Subcommands::FourByte { selector } => {
             let selector = stdin::unwrap_line(selector)?;
-            let sigs = decode_function_selector(&selector).await?;
+            let bytes = hex::decode(&selector)?;
+            if bytes.len() != 4 {
+                eyre::bail!("Invalid selector `{selector}`: expected 4 bytes");
+            }
+            let sigs =
+                signatures_identifier().await?.write().await.function_signatures(&bytes).await;
             if sigs.is_empty() {
                 eyre::bail!("No matching function signatures found for selector `{selector}`");
             }
@@ -381,26 +393,31 @@ async fn main() -> Result<()> {
         }
         Subcommands::FourByteDecode { calldata } => {
             let calldata = stdin::unwrap_line(calldata)?;
-            let sigs = decode_calldata(&calldata).await?;
-            sigs.iter().enumerate().for_each(|(i, sig)| println!("{}) \"{sig}\"", i + 1));
+            let calldata = calldata.strip_prefix("0x").unwrap_or(&calldata);
+            let selector = hex::decode(calldata.get(..8).unwrap_or_default())?;
+            if selector.len() < 4 {
+                eyre::bail!("Calldata too short: expected at least a 4 byte selector");
+            }
 
-            let sig = match sigs.len() {
-                0 => eyre::bail!("No signatures found"),
-                1 => sigs.first().unwrap(),
-                _ => {
-                    let i: usize = prompt!("Select a function signature by number: ")?;
-                    sigs.get(i - 1).ok_or_else(|| eyre::eyre!("Invalid signature index"))?
-                }
-            };
+            let identifier = signatures_identifier().await?;
+            let sigs = identifier.write().await.function_signatures(&selector).await;
 
-            let tokens = SimpleCast::calldata_decode(sig, &calldata, true)?;
-            for token in format_tokens(&tokens) {
+            let decoded = rank_calldata_signatures(&sigs, calldata)?;
+            for (i, (sig, _)) in decoded.iter().enumerate() {
+                println!("{}) \"{sig}\"", i + 1);
+            }
+            let Some((_, tokens)) = decoded.first() else { eyre::bail!("No signatures found") };
+            for token in format_tokens(tokens) {
                 println!("{token}");
             }
         }
         Subcommands::FourByteEvent { topic } => {
             let topic = stdin::unwrap_line(topic)?;
-            let sigs = decode_event_topic(&topic).await?;
+            let bytes = hex::decode(&topic)?;
+            if bytes.len() != 32 {
+                eyre::bail!("Invalid topic `{topic}`: expected 32 bytes");
+            }
+            let sigs = signatures_identifier().await?.write().await.event_signatures(&bytes).await;
             if sigs.is_empty() {
                 eyre::bail!("No matching event signatures found for topic `{topic}`");
             }
@@ -454,9 +471,15 @@ async fn main() -> Result<()> {
             }
             println!("{}", address.to_alloy().to_checksum(None));
         }
+        Subcommands::Ens { command } => command.run().await?,
 
         // Misc
-        Subcommands::Keccak { data } => {
+        Subcommands::Keccak { data, args, packed } => {
+            if packed {
+                let types = data.expect("required by clap");
+                println!("{}", SimpleCast::keccak(&SimpleCast::abi_encode_packed(&types, &args)?)?);
+                return Ok(())
+            }
             let bytes = match data {
                 Some(data) => data.into_bytes(),
                 None => stdin::read_bytes(false)?,
@@ -484,20 +507,7 @@ async fn main() -> Result<()> {
         Subcommands::RightShift { value, bits, base_in, base_out } => {
             println!("{}", SimpleCast::right_shift(&value, &bits, base_in.as_deref(), &base_out)?);
         }
-        Subcommands::EtherscanSource { address, directory, etherscan } => {
-            let config = Config::from(&etherscan);
-            let chain = config.chain.unwrap_or_default();
-            let api_key = config.get_etherscan_api_key(Some(chain)).unwrap_or_default();
-            match directory {
-                Some(dir) => {
-                    SimpleCast::expand_etherscan_source_to_directory(chain, address, api_key, dir)
-                        .await?
-                }
-                None => {
-                    println!("{}", SimpleCast::etherscan_source(chain, address, api_key).await?);
-                }
-            }
-        }
+        Subcommands::EtherscanSource(cmd) => cmd.run().await?,
         Subcommands::Create2(cmd) => {
             cmd.run()?;
         }
@@ -512,19 +522,35 @@ async fn main() -> Result<()> {
             &mut std::io::stdout(),
         ),
         Subcommands::Logs(cmd) => cmd.run().await?,
-        Subcommands::DecodeTransaction { tx } => {
+        Subcommands::DecodeTransaction { tx, sig, abi, json } => {
             let tx = stdin::unwrap_line(tx)?;
-            let (tx, sig) = SimpleCast::decode_raw_transaction(&tx)?;
-
-            // Serialize tx, sig and constructed a merged json string
-            let mut tx = serde_json::to_value(&tx)?;
-            let tx_map = tx.as_object_mut().unwrap();
-            serde_json::to_value(sig)?.as_object().unwrap().iter().for_each(|(k, v)| {
-                tx_map.entry(k).or_insert(v.clone());
-            });
+            let raw = hex::decode(tx.trim()).wrap_err("expected a hex encoded transaction")?;
+            let mut tx = DecodedTransaction::decode(&raw)?;
+            if let Some(sig) = sig {
+                tx.decode_input(&get_func(&sig)?)?;
+            } else if let Some(path) = abi {
+                let obj: ContractObject = serde_json::from_str(&fs::read_to_string(&path)?)?;
+                let abi = obj
+                    .abi
+                    .ok_or_else(|| eyre::eyre!("could not find ABI in file {}", path.display()))?;
+                tx.decode_input_with_abi(&abi)?;
+            }
 
-            println!("{}", serde_json::to_string_pretty(&tx)?);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tx)?);
+            } else {
+                println!("{}", tx.pretty());
+            }
         }
     };
     Ok(())
 }
+
+/// Returns the identifier of the selectors, with the cache and the offline mode of the config,
+/// which knows the signatures of the artifacts of the local project.
+async fn signatures_identifier() -> Result<SingleSignaturesIdentifier> {
+    let config = Config::load();
+    let identifier = SignaturesIdentifier::new(Config::foundry_cache_dir(), config.offline)?;
+    identifier.write().await.add_local_artifacts(&config.project_paths().artifacts);
+    Ok(identifier)
+}
@@ -1,13 +1,39 @@
 use crate::cmd::{
-    access_list::AccessListArgs, bind::BindArgs, call::CallArgs, create2::Create2Args,
-    estimate::EstimateArgs, find_block::FindBlockArgs, interface::InterfaceArgs, logs::LogsArgs,
-    rpc::RpcArgs, run::RunArgs, send::SendTxArgs, storage::StorageArgs, wallet::WalletSubcommands,
+    access_list::AccessListArgs,
+    bind::BindArgs,
+    block::BlockArgs,
+    call::CallArgs,
+    calldata_decode::CalldataDecodeArgs,
+    chain::ChainArgs,
+    constructor_args::ConstructorArgsArgs,
+    create2::Create2Args,
+    creation_code::CreationCodeArgs,
+    ens::EnsSubcommands,
+    estimate::EstimateArgs,
+    etherscan_source::EtherscanSourceArgs,
+    find_block::FindBlockArgs,
+    gas_price::GasPriceArgs,
+    index::{Index1967Args, IndexArgs, IndexArrayArgs, IndexErc7201Args},
+    interface::InterfaceArgs,
+    logs::LogsArgs,
+    mktx::MakeTxArgs,
+    multicall::MulticallArgs,
+    pending::PendingArgs,
+    receipt::ReceiptArgs,
+    rpc::RpcArgs,
+    run::RunArgs,
+    selectors::SelectorsArgs,
+    send::SendTxArgs,
+    simulate::SimulateArgs,
+    storage::StorageArgs,
+    storage_layout::StorageLayoutArgs,
+    wallet::WalletSubcommands,
 };
 use alloy_primitives::{Address, B256, U256};
 use clap::{Parser, Subcommand, ValueHint};
 use ethers_core::types::{BlockId, NameOrAddress};
 use eyre::Result;
-use foundry_cli::opts::{EtherscanOpts, RpcOpts};
+use foundry_cli::opts::RpcOpts;
 use std::{path::PathBuf, str::FromStr};
 
 const VERSION_MESSAGE: &str = concat!(
@@ -30,6 +56,13 @@ const VERSION_MESSAGE: &str = concat!(
 pub struct Opts {
     #[clap(subcommand)]
     pub sub: Subcommands,
+
+    /// Don't look up selectors at https://openchain.xyz, only the cached and the local
+    /// signatures are used.
+    ///
+    /// Same as `offline = true` in the config or `FOUNDRY_OFFLINE=true`.
+    #[clap(long, global = true)]
+    pub offline: bool,
 }
 
 #[derive(Subcommand)]
@@ -295,28 +328,9 @@ pub enum Subcommands {
     /// Get logs by signature or topic.
     #[clap(visible_alias = "l")]
     Logs(LogsArgs),
-    /// Get information about a block.
+    /// Get information about a block, or about a range of blocks.
     #[clap(visible_alias = "bl")]
-    Block {
-        /// The block height to query at.
-        ///
-        /// Can also be the tags earliest, finalized, safe, latest, or pending.
-        block: Option<BlockId>,
-
-        /// If specified, only get the given field of the block.
-        #[clap(long, short)]
-        field: Option<String>,
-
-        #[clap(long, env = "CAST_FULL_BLOCK")]
-        full: bool,
-
-        /// Print the block as JSON.
-        #[clap(long, short, help_heading = "Display options")]
-        json: bool,
-
-        #[clap(flatten)]
-        rpc: RpcOpts,
-    },
+    Block(BlockArgs),
 
     /// Get the latest block number.
     #[clap(visible_alias = "bn")]
@@ -329,6 +343,10 @@ pub enum Subcommands {
     #[clap(visible_alias = "c")]
     Call(CallArgs),
 
+    /// Batch read-only calls into a single call to Multicall3.
+    #[clap(visible_alias = "mc")]
+    Multicall(MulticallArgs),
+
     /// ABI-encode a function with arguments.
     #[clap(name = "calldata", visible_alias = "cd")]
     CalldataEncode {
@@ -340,15 +358,19 @@ pub enum Subcommands {
         args: Vec<String>,
     },
 
-    /// Get the symbolic name of the current chain.
-    Chain {
-        #[clap(flatten)]
-        rpc: RpcOpts,
-    },
+    /// Get the symbolic name of the current chain, or list and search the known chains.
+    ///
+    /// The known chains are the ones of the built-in registry and the custom ones of
+    /// `~/.foundry/chains.toml`.
+    Chain(ChainArgs),
 
-    /// Get the Ethereum chain ID.
+    /// Get the Ethereum chain ID, of the current chain or of a known chain.
     #[clap(visible_aliases = &["ci", "cid"])]
     ChainId {
+        /// The name of a known chain to get the ID of, without an RPC.
+        #[clap(value_name = "CHAIN")]
+        name: Option<String>,
+
         #[clap(flatten)]
         rpc: RpcOpts,
     },
@@ -395,7 +417,10 @@ pub enum Subcommands {
         /// transaction will be printed.
         field: Option<String>,
 
-        /// Print the raw RLP encoded transaction.
+        /// Print the signed network encoding of the transaction.
+        ///
+        /// Uses `eth_getRawTransactionByHash` if the node supports it, otherwise the encoding is
+        /// reconstructed from the fields of the transaction and checked against its hash.
         #[clap(long, conflicts_with = "field")]
         raw: bool,
 
@@ -407,30 +432,13 @@ pub enum Subcommands {
         rpc: RpcOpts,
     },
 
-    /// Get the transaction receipt for a transaction.
+    /// Get the transaction receipt for a transaction, optionally with its logs decoded.
     #[clap(visible_alias = "re")]
-    Receipt {
-        /// The transaction hash.
-        tx_hash: String,
-
-        /// If specified, only get the given field of the transaction.
-        field: Option<String>,
+    Receipt(ReceiptArgs),
 
-        /// The number of confirmations until the receipt is fetched
-        #[clap(long, default_value = "1")]
-        confirmations: usize,
-
-        /// Exit immediately if the transaction was not found.
-        #[clap(long = "async", env = "CAST_ASYNC", name = "async", alias = "cast-async")]
-        cast_async: bool,
-
-        /// Print as JSON.
-        #[clap(long, short, help_heading = "Display options")]
-        json: bool,
-
-        #[clap(flatten)]
-        rpc: RpcOpts,
-    },
+    /// Build and sign a transaction, and print its raw encoding.
+    #[clap(name = "mktx", visible_alias = "m")]
+    MakeTx(MakeTxArgs),
 
     /// Sign and publish a transaction.
     #[clap(name = "send", visible_alias = "s")]
@@ -459,55 +467,77 @@ pub enum Subcommands {
     /// Similar to `abi-decode --input`, but function selector MUST be prefixed in `calldata`
     /// string
     #[clap(visible_aliases = &["--calldata-decode","cdd"])]
-    CalldataDecode {
-        /// The function signature in the format `<name>(<in-types>)(<out-types>)`.
-        sig: String,
-
-        /// The ABI-encoded calldata.
-        calldata: String,
-    },
+    CalldataDecode(CalldataDecodeArgs),
 
     /// Decode ABI-encoded input or output data.
     ///
     /// Defaults to decoding output data. To decode input data pass --input.
     ///
     /// When passing `--input`, function selector must NOT be prefixed in `calldata` string
+    ///
+    /// With `--packed`, data packed like `abi.encodePacked` is split into the values of the input
+    /// types of the signature, or of `--types`. At most one of the types can be dynamic.
     #[clap(name = "abi-decode", visible_aliases = &["ad", "--abi-decode"])]
     AbiDecode {
         /// The function signature in the format `<name>(<in-types>)(<out-types>)`.
-        sig: String,
+        ///
+        /// With `--types`, the packed data is the only argument.
+        #[clap(required_unless_present = "types")]
+        sig: Option<String>,
 
         /// The ABI-encoded calldata.
-        calldata: String,
+        calldata: Option<String>,
 
         /// Whether to decode the input or output data.
-        #[clap(long, short, help_heading = "Decode input data instead of output data")]
+        #[clap(
+            long,
+            short,
+            conflicts_with = "packed",
+            help_heading = "Decode input data instead of output data"
+        )]
         input: bool,
+
+        /// Split data packed like `abi.encodePacked`.
+        #[clap(long)]
+        packed: bool,
+
+        /// The types of the packed values, e.g. `address,uint96,bytes`.
+        #[clap(long, value_name = "TYPES", requires = "packed")]
+        types: Option<String>,
     },
 
     /// ABI encode the given function argument, excluding the selector.
     #[clap(visible_alias = "ae")]
     AbiEncode {
         /// The function signature.
+        ///
+        /// With `--packed`, a list of types such as `address,uint256` can be passed instead.
         sig: String,
 
         /// The arguments of the function.
         #[clap(allow_hyphen_values = true)]
         args: Vec<String>,
+
+        /// Pack the arguments like `abi.encodePacked`: value types aren't padded, and strings and
+        /// bytes are concatenated in place.
+        #[clap(long)]
+        packed: bool,
     },
 
     /// Compute the storage slot for an entry in a mapping.
     #[clap(visible_alias = "in")]
-    Index {
-        /// The mapping key type.
-        key_type: String,
+    Index(IndexArgs),
 
-        /// The mapping key.
-        key: String,
+    /// Compute the root storage slot of an ERC-7201 namespace.
+    #[clap(name = "index-erc7201")]
+    IndexErc7201(IndexErc7201Args),
 
-        /// The storage slot of the mapping.
-        slot_number: String,
-    },
+    /// Compute the storage slot of an element of a dynamic array.
+    IndexArray(IndexArrayArgs),
+
+    /// Print the standard storage slots of EIP-1967 proxies.
+    #[clap(name = "index-1967")]
+    Index1967(Index1967Args),
 
     /// Fetch the EIP-1967 implementation account
     #[clap(visible_alias = "impl")]
@@ -543,21 +573,27 @@ pub enum Subcommands {
         rpc: RpcOpts,
     },
 
-    /// Get the function signatures for the given selector from https://openchain.xyz.
+    /// Get the function signatures for the given selector from the signatures of the local
+    /// project, the signature cache and https://openchain.xyz.
     #[clap(name = "4byte", visible_aliases = &["4", "4b"])]
     FourByte {
         /// The function selector.
         selector: Option<String>,
     },
 
-    /// Decode ABI-encoded calldata using https://openchain.xyz.
+    /// Decode ABI-encoded calldata using the signatures of the local project, the signature cache
+    /// and https://openchain.xyz.
+    ///
+    /// Inside a project, the compiled artifacts are looked up first, then the signatures cached by
+    /// `forge selectors cache` and previous sessions, and only then https://openchain.xyz.
     #[clap(name = "4byte-decode", visible_aliases = &["4d", "4bd"])]
     FourByteDecode {
         /// The ABI-encoded calldata.
         calldata: Option<String>,
     },
 
-    /// Get the event signature for a given topic 0 from https://openchain.xyz.
+    /// Get the event signature for a given topic 0 from the signatures of the local project, the
+    /// signature cache and https://openchain.xyz.
     #[clap(name = "4byte-event", visible_aliases = &["4e", "4be", "topic0-event", "t0e"])]
     FourByteEvent {
         /// Topic 0
@@ -583,15 +619,12 @@ pub enum Subcommands {
 
     /// Pretty print calldata.
     ///
-    /// Tries to decode the calldata using https://openchain.xyz unless --offline is passed.
+    /// Tries to decode the calldata using the cached and the local signatures, then
+    /// https://openchain.xyz unless --offline is passed.
     #[clap(visible_alias = "pc")]
     PrettyCalldata {
         /// The calldata.
         calldata: Option<String>,
-
-        /// Skip the https://openchain.xyz lookup.
-        #[clap(long, short)]
-        offline: bool,
     },
 
     /// Get the timestamp of a block.
@@ -639,6 +672,20 @@ pub enum Subcommands {
         rpc: RpcOpts,
     },
 
+    /// Get the creation bytecode of a contract, from its deployment transaction.
+    ///
+    /// The deployment is located with the explorer if an API key is set, else by binary-searching
+    /// the block in which the code appeared, which needs an archive node. Contracts created by
+    /// factories are found in the trace of the transaction.
+    #[clap(visible_alias = "cc")]
+    CreationCode(CreationCodeArgs),
+
+    /// Get and decode the constructor arguments of a contract, from its deployment transaction.
+    ///
+    /// The arguments are decoded with the verified ABI of the contract, or with `--abi`.
+    #[clap(visible_alias = "cag")]
+    ConstructorArgs(ConstructorArgsArgs),
+
     /// Get the runtime bytecode of a contract.
     #[clap(visible_alias = "co")]
     Code {
@@ -677,12 +724,9 @@ pub enum Subcommands {
         rpc: RpcOpts,
     },
 
-    /// Get the current gas price.
+    /// Get the current gas price, or analyse the fees of recent blocks.
     #[clap(visible_alias = "g")]
-    GasPrice {
-        #[clap(flatten)]
-        rpc: RpcOpts,
-    },
+    GasPrice(GasPriceArgs),
 
     /// Generate event signatures from event string.
     #[clap(visible_alias = "se")]
@@ -695,7 +739,18 @@ pub enum Subcommands {
     #[clap(visible_alias = "k")]
     Keccak {
         /// The data to hash.
+        ///
+        /// With `--packed`, the types of the values to pack, e.g. `address,uint256`.
         data: Option<String>,
+
+        /// The values to pack.
+        #[clap(allow_hyphen_values = true, requires = "packed")]
+        args: Vec<String>,
+
+        /// Hash the values packed like `abi.encodePacked`, i.e.
+        /// `keccak256(abi.encodePacked(...))`.
+        #[clap(long, requires = "data")]
+        packed: bool,
     },
 
     /// Perform an ENS lookup.
@@ -726,10 +781,20 @@ pub enum Subcommands {
         rpc: RpcOpts,
     },
 
+    /// ENS text records, avatars and resolvers.
+    Ens {
+        #[clap(subcommand)]
+        command: EnsSubcommands,
+    },
+
     /// Get the raw value of a contract's storage slot.
     #[clap(visible_alias = "st")]
     Storage(StorageArgs),
 
+    /// Get the storage layout of a contract, and decode the values of its variables.
+    #[clap(visible_alias = "sl")]
+    StorageLayout(StorageLayoutArgs),
+
     /// Generate a storage proof for a given storage slot.
     #[clap(visible_alias = "pr")]
     Proof {
@@ -747,19 +812,35 @@ pub enum Subcommands {
         #[clap(long, short = 'B')]
         block: Option<BlockId>,
 
+        /// Verify the account proof against the state root of the block, and the storage proofs
+        /// against the storage root of the account.
+        #[clap(long)]
+        verify: bool,
+
+        /// Print the proof and its verification as JSON.
+        #[clap(long, short, requires = "verify")]
+        json: bool,
+
         #[clap(flatten)]
         rpc: RpcOpts,
     },
 
     /// Get the nonce for an account.
+    ///
+    /// This is the nonce of the next transaction of the account, after its pending transactions.
     #[clap(visible_alias = "n")]
     Nonce {
         /// The block height to query at.
         ///
-        /// Can also be the tags earliest, finalized, safe, latest, or pending.
+        /// Can also be the tags earliest, finalized, safe, latest, or pending. Defaults to
+        /// pending, or latest if the RPC doesn't support it.
         #[clap(long, short = 'B')]
         block: Option<BlockId>,
 
+        /// Get the nonce at the latest block, ignoring the pending transactions of the account.
+        #[clap(long, conflicts_with = "block")]
+        latest: bool,
+
         /// The address to get the nonce for.
         #[clap(value_parser = NameOrAddress::from_str)]
         who: NameOrAddress,
@@ -768,19 +849,13 @@ pub enum Subcommands {
         rpc: RpcOpts,
     },
 
-    /// Get the source code of a contract from Etherscan.
-    #[clap(visible_aliases = &["et", "src"])]
-    EtherscanSource {
-        /// The contract's address.
-        address: String,
-
-        /// The output directory to expand source tree into.
-        #[clap(short, value_hint = ValueHint::DirPath)]
-        directory: Option<PathBuf>,
+    /// List the transactions of an account in the pool of the node, with its confirmed nonce
+    /// and the nonce of its next transaction.
+    Pending(PendingArgs),
 
-        #[clap(flatten)]
-        etherscan: EtherscanOpts,
-    },
+    /// Get the source code of a contract from Etherscan, Blockscout or Sourcify.
+    #[clap(visible_aliases = &["et", "src"])]
+    EtherscanSource(EtherscanSourceArgs),
 
     /// Wallet management utilities.
     #[clap(visible_alias = "w")]
@@ -832,6 +907,11 @@ pub enum Subcommands {
     #[clap(visible_alias = "r")]
     Run(RunArgs),
 
+    /// Executes a bundle of transactions from a file in order against a local fork, and prints
+    /// their outcome, logs and balance changes.
+    #[clap(visible_alias = "sim")]
+    Simulate(SimulateArgs),
+
     /// Perform a raw JSON-RPC request.
     #[clap(visible_alias = "rp")]
     Rpc(RpcArgs),
@@ -856,20 +936,32 @@ pub enum Subcommands {
         bytes: Option<String>,
     },
 
-    /// Decodes a raw signed EIP 2718 typed transaction
+    /// Decodes a raw signed transaction offline, legacy or EIP-2718 typed.
+    ///
+    /// Prints the fields of the transaction, the recovered sender and the transaction hash.
     #[clap(visible_alias = "dt")]
-    DecodeTransaction { tx: Option<String> },
+    DecodeTransaction {
+        /// The hex encoded raw transaction.
+        tx: Option<String>,
 
-    /// Extracts function selectors and arguments from bytecode
-    #[clap(visible_alias = "sel")]
-    Selectors {
-        /// The hex encoded bytecode.
-        bytecode: String,
+        /// The signature of the called function, to decode the calldata.
+        #[clap(long, conflicts_with = "abi")]
+        sig: Option<String>,
 
-        /// Resolve the function signatures for the extracted selectors using https://openchain.xyz
-        #[clap(long, short)]
-        resolve: bool,
+        /// The path of an ABI or artifact file to decode the calldata with, the function is
+        /// selected by the selector of the calldata.
+        #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+        abi: Option<PathBuf>,
+
+        /// Print the transaction as JSON.
+        #[clap(long, short, help_heading = "Display options")]
+        json: bool,
     },
+
+    /// Extracts the function selectors, arguments and jump destinations from the dispatcher of
+    /// runtime bytecode.
+    #[clap(visible_alias = "sel")]
+    Selectors(SelectorsArgs),
 }
 
 /// CLI arguments for `cast --to-base`.
@@ -0,0 +1,357 @@
+//! EIP-4844 blob transactions, see `cast mktx --blob` and `cast send --blob`.
+
+use crate::Cast;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_rlp::{Encodable, Header};
+use c_kzg::{Blob, KzgCommitment, KzgProof, KzgSettings};
+use ethers_core::types::{transaction::eip2718::TypedTransaction, BlockNumber, Signature};
+use ethers_providers::Middleware;
+use eyre::{Context, Result};
+use foundry_common::{
+    fs,
+    types::{ToAlloy, ToEthers},
+};
+use revm_primitives::kzg::EnvKzgSettings;
+use sha2::{Digest, Sha256};
+use std::{path::Path, sync::Arc};
+
+/// The EIP-2718 type of blob transactions.
+pub const BLOB_TX_TYPE: u8 = 3;
+
+/// The number of field elements of a blob.
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+
+/// The size of a blob.
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * 32;
+
+/// The bytes of data stored in a field element, its first byte is zero so it's always below the
+/// BLS modulus.
+const DATA_BYTES_PER_FIELD_ELEMENT: usize = 31;
+
+/// The bytes of data stored in a blob.
+pub const DATA_BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * DATA_BYTES_PER_FIELD_ELEMENT;
+
+/// The maximum number of blobs of a transaction.
+pub const MAX_BLOBS_PER_TRANSACTION: usize = 6;
+
+/// The version byte of the versioned hashes of KZG commitments.
+const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// Encodes the data into blobs, 31 bytes per field element, the last blob is zero padded.
+pub fn encode_blobs(data: &[u8]) -> Vec<Vec<u8>> {
+    data.chunks(DATA_BYTES_PER_BLOB)
+        .map(|chunk| {
+            let mut blob = vec![0; BYTES_PER_BLOB];
+            let elements = chunk.chunks(DATA_BYTES_PER_FIELD_ELEMENT);
+            for (element, bytes) in blob.chunks_mut(32).zip(elements) {
+                element[1..=bytes.len()].copy_from_slice(bytes);
+            }
+            blob
+        })
+        .collect()
+}
+
+/// Reads the files and encodes each of them into its own blobs.
+pub fn blobs_from_files(files: &[impl AsRef<Path>]) -> Result<Vec<Vec<u8>>> {
+    let mut blobs = vec![];
+    for file in files {
+        let file = file.as_ref();
+        let data = fs::read(file)?;
+        if data.is_empty() {
+            eyre::bail!("blob file {} is empty", file.display());
+        }
+        blobs.extend(encode_blobs(&data));
+    }
+    if blobs.len() > MAX_BLOBS_PER_TRANSACTION {
+        eyre::bail!(
+            "the blob files need {} blobs but a transaction has at most \
+             {MAX_BLOBS_PER_TRANSACTION}, a blob holds {DATA_BYTES_PER_BLOB} bytes",
+            blobs.len()
+        );
+    }
+    Ok(blobs)
+}
+
+/// Returns the KZG trusted setup of the file, or the one of the Ethereum KZG ceremony bundled with
+/// revm.
+pub fn load_kzg_settings(path: Option<&Path>) -> Result<EnvKzgSettings> {
+    let Some(path) = path else { return Ok(EnvKzgSettings::Default) };
+    let settings = KzgSettings::load_trusted_setup_file(path)
+        .map_err(|err| eyre::eyre!("{err:?}"))
+        .wrap_err_with(|| format!("failed to load the KZG trusted setup {}", path.display()))?;
+    Ok(EnvKzgSettings::Custom(Arc::new(settings)))
+}
+
+/// Returns the versioned hash of the KZG commitment.
+pub fn kzg_to_versioned_hash(commitment: &[u8]) -> B256 {
+    let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    B256::from(hash)
+}
+
+/// The blobs of a transaction with their KZG commitments and proofs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlobSidecar {
+    pub blobs: Vec<Bytes>,
+    pub commitments: Vec<Bytes>,
+    pub proofs: Vec<Bytes>,
+}
+
+impl BlobSidecar {
+    /// Computes the KZG commitments and proofs of the blobs.
+    pub fn new(blobs: Vec<Vec<u8>>, settings: &KzgSettings) -> Result<Self> {
+        let kzg_err = |err: c_kzg::Error| eyre::eyre!("KZG error: {err:?}");
+        let mut sidecar = Self { blobs: vec![], commitments: vec![], proofs: vec![] };
+        for blob in blobs {
+            let kzg_blob = Blob::from_bytes(&blob).map_err(kzg_err)?;
+            let commitment =
+                KzgCommitment::blob_to_kzg_commitment(&kzg_blob, settings).map_err(kzg_err)?;
+            let commitment = commitment.to_bytes();
+            let proof = KzgProof::compute_blob_kzg_proof(&kzg_blob, &commitment, settings)
+                .map_err(kzg_err)?;
+            sidecar.blobs.push(blob.into());
+            sidecar.commitments.push(Bytes::copy_from_slice(&*commitment));
+            sidecar.proofs.push(Bytes::copy_from_slice(&*proof.to_bytes()));
+        }
+        Ok(sidecar)
+    }
+
+    /// Returns the versioned hashes of the commitments.
+    pub fn versioned_hashes(&self) -> Vec<B256> {
+        self.commitments.iter().map(|commitment| kzg_to_versioned_hash(commitment)).collect()
+    }
+}
+
+/// An unsigned EIP-4844 blob transaction, without an access list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlobTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+    /// Blob transactions can't create contracts
+    pub to: Address,
+    pub value: U256,
+    pub input: Bytes,
+    pub max_fee_per_blob_gas: U256,
+    pub blob_versioned_hashes: Vec<B256>,
+}
+
+impl BlobTransaction {
+    fn fields_length(&self) -> usize {
+        self.chain_id.length() +
+            self.nonce.length() +
+            self.max_priority_fee_per_gas.length() +
+            self.max_fee_per_gas.length() +
+            self.gas_limit.length() +
+            self.to.length() +
+            self.value.length() +
+            self.input.length() +
+            // empty access list
+            1 +
+            self.max_fee_per_blob_gas.length() +
+            self.blob_versioned_hashes.length()
+    }
+
+    fn encode_fields(&self, out: &mut Vec<u8>) {
+        self.chain_id.encode(out);
+        self.nonce.encode(out);
+        self.max_priority_fee_per_gas.encode(out);
+        self.max_fee_per_gas.encode(out);
+        self.gas_limit.encode(out);
+        self.to.encode(out);
+        self.value.encode(out);
+        self.input.encode(out);
+        Header { list: true, payload_length: 0 }.encode(out);
+        self.max_fee_per_blob_gas.encode(out);
+        self.blob_versioned_hashes.encode(out);
+    }
+
+    /// Returns the hash to sign, `keccak256(0x03 || rlp(fields))`.
+    pub fn signature_hash(&self) -> B256 {
+        let mut out = vec![BLOB_TX_TYPE];
+        Header { list: true, payload_length: self.fields_length() }.encode(&mut out);
+        self.encode_fields(&mut out);
+        keccak256(out)
+    }
+
+    /// Returns the RLP list of the signed fields.
+    fn encode_signed_fields(&self, signature: &Signature) -> Vec<u8> {
+        // signatures of typed transactions are prefixed with their y parity
+        let y_parity = if signature.v >= 27 { signature.v - 27 } else { signature.v };
+        let (r, s) = (signature.r.to_alloy(), signature.s.to_alloy());
+        let payload_length = self.fields_length() + y_parity.length() + r.length() + s.length();
+        let mut out = vec![];
+        Header { list: true, payload_length }.encode(&mut out);
+        self.encode_fields(&mut out);
+        y_parity.encode(&mut out);
+        r.encode(&mut out);
+        s.encode(&mut out);
+        out
+    }
+
+    /// Returns the canonical encoding of the signed transaction, whose hash is the transaction
+    /// hash.
+    pub fn encode_signed(&self, signature: &Signature) -> Bytes {
+        let mut out = vec![BLOB_TX_TYPE];
+        out.extend(self.encode_signed_fields(signature));
+        out.into()
+    }
+
+    /// Returns the network encoding of the signed transaction with its sidecar,
+    /// `0x03 || rlp([tx, blobs, commitments, proofs])`, as sent with `eth_sendRawTransaction`.
+    pub fn encode_network(&self, signature: &Signature, sidecar: &BlobSidecar) -> Bytes {
+        let tx = self.encode_signed_fields(signature);
+        let payload_length = tx.len() +
+            sidecar.blobs.length() +
+            sidecar.commitments.length() +
+            sidecar.proofs.length();
+        let mut out = vec![BLOB_TX_TYPE];
+        Header { list: true, payload_length }.encode(&mut out);
+        out.extend(tx);
+        sidecar.blobs.encode(&mut out);
+        sidecar.commitments.encode(&mut out);
+        sidecar.proofs.encode(&mut out);
+        out.into()
+    }
+}
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Builds a blob transaction carrying the sidecar from the EIP-1559 transaction, filling its
+    /// missing nonce, fees and gas limit from the provider.
+    ///
+    /// The max fee per blob gas defaults to twice the blob base fee of the node.
+    pub async fn blob_transaction(
+        &self,
+        tx: TypedTransaction,
+        from: Address,
+        max_fee_per_blob_gas: Option<U256>,
+        sidecar: &BlobSidecar,
+    ) -> Result<BlobTransaction> {
+        let TypedTransaction::Eip1559(mut tx) = tx else {
+            eyre::bail!("blob transactions can't be legacy transactions")
+        };
+        let to = tx
+            .to
+            .as_ref()
+            .and_then(|to| to.as_address())
+            .ok_or_else(|| eyre::eyre!("blob transactions must have a recipient"))?
+            .to_alloy();
+        tx.from = Some(from.to_ethers());
+
+        let nonce = match tx.nonce {
+            Some(nonce) => nonce,
+            None => {
+                let pending = Some(BlockNumber::Pending.into());
+                self.provider.get_transaction_count(from.to_ethers(), pending).await?
+            }
+        };
+        let (max_fee_per_gas, max_priority_fee_per_gas) =
+            match (tx.max_fee_per_gas, tx.max_priority_fee_per_gas) {
+                (Some(max_fee), Some(priority_fee)) => (max_fee, priority_fee),
+                (max_fee, priority_fee) => {
+                    let (estimated_max_fee, estimated_priority_fee) =
+                        self.provider.estimate_eip1559_fees(None).await?;
+                    let priority_fee = priority_fee.unwrap_or(estimated_priority_fee);
+                    (max_fee.unwrap_or(estimated_max_fee.max(priority_fee)), priority_fee)
+                }
+            };
+        let gas_limit = match tx.gas {
+            Some(gas) => gas,
+            None => self.provider.estimate_gas(&tx.clone().into(), None).await?,
+        };
+        let max_fee_per_blob_gas = match max_fee_per_blob_gas {
+            Some(fee) => fee,
+            None => {
+                let blob_base_fee: U256 =
+                    self.provider.provider().request("eth_blobBaseFee", ()).await.wrap_err(
+                        "failed to get the blob base fee, set the max fee per blob gas",
+                    )?;
+                blob_base_fee.saturating_mul(U256::from(2)).max(U256::from(1))
+            }
+        };
+
+        Ok(BlobTransaction {
+            chain_id: tx.chain_id.map(|id| id.as_u64()).unwrap_or_default(),
+            nonce: nonce.as_u64(),
+            max_priority_fee_per_gas: max_priority_fee_per_gas.to_alloy(),
+            max_fee_per_gas: max_fee_per_gas.to_alloy(),
+            gas_limit: gas_limit.to_alloy(),
+            to,
+            value: tx.value.unwrap_or_default().to_alloy(),
+            input: tx.data.unwrap_or_default().to_alloy(),
+            max_fee_per_blob_gas,
+            blob_versioned_hashes: sidecar.versioned_hashes(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw_tx::{DecodedTransaction, TxType};
+    use ethers_signers::LocalWallet;
+
+    #[test]
+    fn can_encode_blobs() {
+        let data = vec![0xff; DATA_BYTES_PER_BLOB + 32];
+        let blobs = encode_blobs(&data);
+        assert_eq!(blobs.len(), 2);
+        assert!(blobs.iter().all(|blob| blob.len() == BYTES_PER_BLOB));
+        // the first byte of each field element is zero
+        assert!(blobs[0].chunks(32).all(|element| element[0] == 0 && element[1..] == [0xff; 31]));
+        assert_eq!(blobs[1][..64], [[0].as_slice(), &[0xff; 31], &[0], &[0xff], &[0; 30]].concat());
+        assert!(blobs[1][64..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn rejects_too_many_blobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("data");
+        std::fs::write(&file, vec![1; DATA_BYTES_PER_BLOB * MAX_BLOBS_PER_TRANSACTION]).unwrap();
+        assert_eq!(blobs_from_files(&[&file]).unwrap().len(), MAX_BLOBS_PER_TRANSACTION);
+
+        std::fs::write(&file, vec![1; DATA_BYTES_PER_BLOB * MAX_BLOBS_PER_TRANSACTION + 1])
+            .unwrap();
+        let err = blobs_from_files(&[&file]).unwrap_err().to_string();
+        assert!(err.contains("need 7 blobs"), "{err}");
+
+        std::fs::write(&file, b"").unwrap();
+        assert!(blobs_from_files(&[&file]).is_err());
+    }
+
+    #[test]
+    fn can_sign_and_decode_blob_transactions() {
+        let settings = load_kzg_settings(None).unwrap();
+        let sidecar = BlobSidecar::new(encode_blobs(b"blob"), settings.get()).unwrap();
+        assert_eq!(sidecar.commitments[0].len(), 48);
+        assert_eq!(sidecar.versioned_hashes()[0][0], VERSIONED_HASH_VERSION_KZG);
+
+        let wallet: LocalWallet =
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let tx = BlobTransaction {
+            chain_id: 1,
+            nonce: 2,
+            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+            max_fee_per_gas: U256::from(10_000_000_000u64),
+            gas_limit: U256::from(21_000),
+            to: Address::repeat_byte(0x11),
+            value: U256::ZERO,
+            input: Bytes::new(),
+            max_fee_per_blob_gas: U256::from(3),
+            blob_versioned_hashes: sidecar.versioned_hashes(),
+        };
+        let signature = wallet.sign_hash(tx.signature_hash().to_ethers()).unwrap();
+
+        let decoded = DecodedTransaction::decode(&tx.encode_network(&signature, &sidecar)).unwrap();
+        assert_eq!(decoded.tx_type, TxType::Eip4844);
+        assert_eq!(decoded.from, wallet.address().to_alloy());
+        assert_eq!(decoded.hash, keccak256(tx.encode_signed(&signature)));
+        assert_eq!(decoded.max_fee_per_blob_gas, Some(U256::from(3)));
+        assert_eq!(decoded.blob_versioned_hashes, Some(sidecar.versioned_hashes()));
+    }
+}
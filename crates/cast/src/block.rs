@@ -0,0 +1,249 @@
+//! Block field selection and block ranges, see `cast block --fields`.
+
+use crate::Cast;
+use ethers_core::types::{Block, BlockId, Transaction, H256};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::fmt::{get_pretty_block_attr, get_pretty_tx_attr};
+use serde::Serialize;
+use serde_json::Value;
+use std::{ops::RangeInclusive, str::FromStr};
+
+/// A block, or an inclusive range of block numbers, e.g. `18000000..18000100`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockSelection {
+    Block(BlockId),
+    Range(RangeInclusive<u64>),
+}
+
+impl FromStr for BlockSelection {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((start, end)) = s.split_once("..") else {
+            return BlockId::from_str(s).map(Self::Block).map_err(|err| eyre::eyre!("{err}"))
+        };
+        let parse = |n: &str| {
+            n.trim().parse::<u64>().map_err(|_| eyre::eyre!("invalid block number `{n}` in `{s}`"))
+        };
+        let (start, end) = (parse(start)?, parse(end)?);
+        if start > end {
+            eyre::bail!("invalid block range `{s}`, the start is after the end")
+        }
+        Ok(Self::Range(start..=end))
+    }
+}
+
+/// The transactions of a block, their hashes or the full transactions.
+pub trait BlockTransaction: Serialize {
+    /// Returns the formatted field of the transaction, `None` if it isn't a known field.
+    fn pretty_field(&self, field: &str) -> Option<String>;
+}
+
+impl BlockTransaction for H256 {
+    fn pretty_field(&self, _field: &str) -> Option<String> {
+        None
+    }
+}
+
+impl BlockTransaction for Transaction {
+    fn pretty_field(&self, field: &str) -> Option<String> {
+        get_pretty_tx_attr(self, field)
+    }
+}
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Returns the fields of the block, see [`format_block_fields`].
+    pub async fn block_fields<T: Into<BlockId>>(
+        &self,
+        block: T,
+        full: bool,
+        fields: &[String],
+        to_json: bool,
+    ) -> Result<String> {
+        let block = block.into();
+        let not_found = || eyre::eyre!("block {block:?} not found");
+        if full {
+            let block = self.provider.get_block_with_txs(block).await?.ok_or_else(not_found)?;
+            format_block_fields(&block, fields, full, to_json)
+        } else {
+            let block = self.provider.get_block(block).await?.ok_or_else(not_found)?;
+            format_block_fields(&block, fields, full, to_json)
+        }
+    }
+}
+
+/// Formats the fields of the block, tab-separated or as a JSON object with the fields in order.
+///
+/// Fields are the camelCase names of the JSON-RPC block, nested fields are separated by dots and
+/// fields of arrays select the field of each element, e.g. `transactions.from` with the full
+/// transactions. Values of array fields are comma-separated.
+pub fn format_block_fields<TX: BlockTransaction>(
+    block: &Block<TX>,
+    fields: &[String],
+    full: bool,
+    to_json: bool,
+) -> Result<String> {
+    if let Some(field) = fields.iter().find(|field| field.starts_with("transactions.")) {
+        if !full {
+            eyre::bail!("use --full to select the fields of transactions, like {field}")
+        }
+    }
+
+    let json = serde_json::to_value(block)?;
+    if to_json {
+        // the object is written by hand to keep the order of the fields
+        let entries = fields
+            .iter()
+            .map(|field| {
+                let value = json_field(&json, field)?.clone_value();
+                Ok(format!("{}:{value}", Value::String(field.clone())))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        return Ok(format!("{{{}}}", entries.join(",")))
+    }
+
+    let values = fields
+        .iter()
+        .map(|field| {
+            let pretty = match field.strip_prefix("transactions.") {
+                Some(tx_field) => block
+                    .transactions
+                    .iter()
+                    .map(|tx| tx.pretty_field(tx_field))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|values| values.join(",")),
+                None => get_pretty_block_attr(block, field),
+            };
+            match pretty {
+                Some(pretty) => Ok(pretty),
+                None => Ok(format_value(&json_field(&json, field)?.clone_value())),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(values.join("\t"))
+}
+
+/// The value of a field, or the values of the field of the elements of arrays.
+enum FieldValue<'a> {
+    Value(&'a Value),
+    Array(Vec<FieldValue<'a>>),
+}
+
+impl FieldValue<'_> {
+    fn clone_value(&self) -> Value {
+        match self {
+            FieldValue::Value(value) => (*value).clone(),
+            FieldValue::Array(values) => values.iter().map(|value| value.clone_value()).collect(),
+        }
+    }
+}
+
+fn json_field<'a>(block: &'a Value, field: &str) -> Result<FieldValue<'a>> {
+    fn select<'a>(value: &'a Value, path: &[&str]) -> Option<FieldValue<'a>> {
+        let Some((key, rest)) = path.split_first() else { return Some(FieldValue::Value(value)) };
+        match value {
+            Value::Array(values) => values
+                .iter()
+                .map(|value| select(value, path))
+                .collect::<Option<Vec<_>>>()
+                .map(FieldValue::Array),
+            Value::Object(object) => select(object.get(*key)?, rest),
+            _ => None,
+        }
+    }
+    select(block, &field.split('.').collect::<Vec<_>>())
+        .ok_or_else(|| eyre::eyre!("{field} is not a valid block field"))
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(values) => values.iter().map(format_value).collect::<Vec<_>>().join(","),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Address, U256, U64};
+
+    fn block() -> Block<Transaction> {
+        let tx = |nonce: u64| Transaction {
+            from: Address::repeat_byte(nonce as u8),
+            nonce: nonce.into(),
+            max_fee_per_gas: Some(U256::from(100)),
+            ..Default::default()
+        };
+        Block {
+            number: Some(U64::from(18_000_000)),
+            gas_used: U256::from(21_000),
+            base_fee_per_gas: Some(U256::from(7)),
+            transactions: vec![tx(1), tx(2)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn can_parse_block_selection() {
+        assert_eq!(
+            "18000000..18000100".parse::<BlockSelection>().unwrap(),
+            BlockSelection::Range(18_000_000..=18_000_100)
+        );
+        assert_eq!(
+            "latest".parse::<BlockSelection>().unwrap(),
+            BlockSelection::Block(BlockId::Number(ethers_core::types::BlockNumber::Latest))
+        );
+        assert!("10..1".parse::<BlockSelection>().is_err());
+        assert!("1..latest".parse::<BlockSelection>().is_err());
+    }
+
+    #[test]
+    fn can_format_block_fields() {
+        let block = block();
+        let fields = ["baseFeePerGas", "gasUsed", "number"].map(String::from);
+        assert_eq!(
+            format_block_fields(&block, &fields, true, false).unwrap(),
+            "7\t21000\t18000000"
+        );
+        assert_eq!(
+            format_block_fields(&block, &fields, true, true).unwrap(),
+            r#"{"baseFeePerGas":"0x7","gasUsed":"0x5208","number":"0x112a880"}"#
+        );
+
+        // nested fields of the transactions, known or not
+        let fields = ["transactions.nonce", "transactions.maxFeePerGas"].map(String::from);
+        assert_eq!(format_block_fields(&block, &fields, true, false).unwrap(), "1,2\t0x64,0x64");
+        assert_eq!(
+            format_block_fields(&block, &fields, true, true).unwrap(),
+            r#"{"transactions.nonce":["0x1","0x2"],"transactions.maxFeePerGas":["0x64","0x64"]}"#
+        );
+
+        let err = format_block_fields(&block, &["foo".to_string()], true, false).unwrap_err();
+        assert_eq!(err.to_string(), "foo is not a valid block field");
+    }
+
+    #[test]
+    fn transaction_fields_need_full_blocks() {
+        let block = Block::<H256> {
+            number: Some(U64::from(18_000_000)),
+            transactions: block().transactions.iter().map(|tx| tx.hash).collect(),
+            ..Default::default()
+        };
+        let err = format_block_fields(&block, &["transactions.from".to_string()], false, false)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "use --full to select the fields of transactions, like transactions.from"
+        );
+        assert_eq!(
+            format_block_fields(&block, &["transactions".to_string()], false, false).unwrap(),
+            format!("{:?},{:?}", H256::zero(), H256::zero())
+        );
+    }
+}
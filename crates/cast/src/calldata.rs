@@ -0,0 +1,272 @@
+//! Decoding and pretty-printing calldata, see `cast calldata-decode`.
+
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy_json_abi::{Function, JsonAbi, Param};
+use alloy_primitives::{hex, U256};
+use eyre::{Context, Result};
+use foundry_common::fmt::format_token_raw;
+use serde_json::Value;
+use std::{collections::HashMap, fmt::Write, str::FromStr};
+
+/// Names of uint parameters which usually hold token amounts, the ones scaled by the default
+/// decimals of [`Scaling`].
+const AMOUNT_NAMES: &[&str] =
+    &["amount", "value", "balance", "wad", "price", "supply", "fee", "shares", "assets"];
+
+/// Decodes the calldata of the function, its selector is skipped without being checked.
+pub fn decode_calldata(func: &Function, calldata: &[u8]) -> Result<Vec<DynSolValue>> {
+    if calldata.len() < 4 {
+        eyre::bail!("calldata too short: expected at least 4 bytes, got {}", calldata.len())
+    }
+    func.abi_decode_input(&calldata[4..], false)
+        .wrap_err_with(|| format!("could not decode the calldata as {}", func.signature()))
+}
+
+/// Looks up the function of the calldata by its selector in the ABI and decodes the calldata.
+pub fn decode_calldata_with_abi(
+    abi: &JsonAbi,
+    calldata: &[u8],
+) -> Result<(Function, Vec<DynSolValue>)> {
+    let Some(selector) = calldata.get(..4) else {
+        eyre::bail!("calldata too short: expected at least 4 bytes, got {}", calldata.len())
+    };
+    let func = abi.functions().find(|func| func.selector()[..] == *selector).ok_or_else(|| {
+        eyre::eyre!("no function of the ABI has the selector {}", hex::encode_prefixed(selector))
+    })?;
+    let values = decode_calldata(func, calldata)?;
+    Ok((func.clone(), values))
+}
+
+/// The decimals uint parameters are scaled by in the pretty output, as a hint next to their
+/// value.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Scaling {
+    /// The decimals of the parameters whose name looks like an amount, e.g. `amount` or
+    /// `maxPrice`.
+    pub decimals: Option<u8>,
+    /// The decimals of parameters by name, which take precedence over the default.
+    pub params: HashMap<String, u8>,
+}
+
+impl Scaling {
+    /// Returns the decimals of the uint parameter with the name, if it is scaled.
+    pub fn decimals_of(&self, name: &str) -> Option<u8> {
+        if let Some(decimals) = self.params.get(name) {
+            return Some(*decimals)
+        }
+        let name = name.to_lowercase();
+        self.decimals.filter(|_| AMOUNT_NAMES.iter().any(|amount| name.contains(amount)))
+    }
+}
+
+/// The decimals of a parameter, given as `<NAME>:<DECIMALS>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamScale {
+    pub name: String,
+    pub decimals: u8,
+}
+
+impl FromStr for ParamScale {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, decimals) = s
+            .rsplit_once(':')
+            .ok_or_else(|| eyre::eyre!("expected `<NAME>:<DECIMALS>`, got `{s}`"))?;
+        let decimals = decimals.parse().wrap_err_with(|| format!("invalid decimals in `{s}`"))?;
+        Ok(Self { name: name.to_string(), decimals })
+    }
+}
+
+/// Pretty-prints the decoded arguments of the function, one per line with their names, nested
+/// tuples and arrays indented.
+pub fn pretty_decoded(func: &Function, values: &[DynSolValue], scaling: &Scaling) -> String {
+    let mut out = func.signature();
+    for (i, (param, value)) in func.inputs.iter().zip(values).enumerate() {
+        out.push('\n');
+        write_value(&mut out, &param_name(param, i), Some(param), value, scaling, 0);
+    }
+    out
+}
+
+fn param_name(param: &Param, index: usize) -> String {
+    if param.name.is_empty() {
+        format!("[{index}]")
+    } else {
+        param.name.clone()
+    }
+}
+
+fn write_value(
+    out: &mut String,
+    name: &str,
+    param: Option<&Param>,
+    value: &DynSolValue,
+    scaling: &Scaling,
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+    let _ = write!(out, "{indent}{name}: ");
+    match value {
+        DynSolValue::Tuple(values) => {
+            out.push('(');
+            let components = param.map(|param| param.components.as_slice()).unwrap_or_default();
+            for (i, value) in values.iter().enumerate() {
+                let component = components.get(i);
+                let name = component.map_or_else(|| format!("[{i}]"), |c| param_name(c, i));
+                out.push('\n');
+                write_value(out, &name, component, value, scaling, depth + 1);
+            }
+            let _ = write!(out, "\n{indent})");
+        }
+        DynSolValue::Array(values) | DynSolValue::FixedArray(values) => {
+            if values.is_empty() {
+                out.push_str("[]");
+                return
+            }
+            out.push('[');
+            // the elements of arrays of tuples have the components of the array
+            for (i, value) in values.iter().enumerate() {
+                out.push('\n');
+                write_value(out, &format!("[{i}]"), param, value, scaling, depth + 1);
+            }
+            let _ = write!(out, "\n{indent}]");
+        }
+        DynSolValue::Uint(value, _) => {
+            out.push_str(&value.to_string());
+            if let Some(decimals) = param.and_then(|param| scaling.decimals_of(&param.name)) {
+                let _ =
+                    write!(out, " ({} with {decimals} decimals)", format_units(*value, decimals));
+            }
+        }
+        value => out.push_str(&format_leaf(value)),
+    }
+}
+
+/// Formats a value which isn't a tuple nor an array.
+fn format_leaf(value: &DynSolValue) -> String {
+    match value {
+        DynSolValue::Address(address) => address.to_checksum(None),
+        DynSolValue::Bool(b) => b.to_string(),
+        DynSolValue::Int(value, _) => value.to_string(),
+        DynSolValue::Uint(value, _) => value.to_string(),
+        DynSolValue::FixedBytes(word, size) => hex::encode_prefixed(&word[..*size]),
+        DynSolValue::Bytes(bytes) => hex::encode_prefixed(bytes),
+        DynSolValue::String(s) => format!("{s:?}"),
+        value => format_token_raw(value),
+    }
+}
+
+/// Formats the amount with the decimals, without trailing zeros.
+pub(crate) fn format_units(amount: U256, decimals: u8) -> String {
+    let unit = U256::from(10).pow(U256::from(decimals));
+    let (integer, fraction) = (amount / unit, amount % unit);
+    if fraction.is_zero() {
+        return integer.to_string()
+    }
+    let fraction = format!("{:0>width$}", fraction.to_string(), width = decimals as usize);
+    format!("{integer}.{}", fraction.trim_end_matches('0'))
+}
+
+/// Converts the decoded arguments to JSON, like `JSON.stringify` of an ethers `Result`.
+///
+/// Tuples and arrays are arrays, integers are decimal strings, addresses are checksummed and
+/// bytes are hex strings.
+pub fn decoded_to_json(values: &[DynSolValue]) -> Value {
+    values.iter().map(value_to_json).collect()
+}
+
+fn value_to_json(value: &DynSolValue) -> Value {
+    match value {
+        DynSolValue::Tuple(values) |
+        DynSolValue::Array(values) |
+        DynSolValue::FixedArray(values) => decoded_to_json(values),
+        DynSolValue::Bool(b) => Value::Bool(*b),
+        DynSolValue::String(s) => Value::String(s.clone()),
+        value => Value::String(format_leaf(value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleCast;
+    use foundry_common::abi::get_func;
+
+    const SIG: &str = "fill((address maker,uint256 amount)[] orders,bytes32 salt,uint256 deadline)";
+
+    fn encoded() -> String {
+        let args = [
+            "[(0xd8da6bf26964af9d7eed9e03e53415d37aa96045,1500000000000000000),(0x0000000000000000000000000000000000000001,2)]",
+            "0x0000000000000000000000000000000000000000000000000000000000000007",
+            "1700000000",
+        ];
+        SimpleCast::calldata_encode(SIG, &args).unwrap()
+    }
+
+    #[test]
+    fn can_pretty_print_round_trip() {
+        let func = get_func(SIG).unwrap();
+        let values = decode_calldata(&func, &hex::decode(encoded()).unwrap()).unwrap();
+        let scaling = Scaling { decimals: Some(18), ..Default::default() };
+        assert_eq!(
+            pretty_decoded(&func, &values, &scaling),
+            "\
+fill((address,uint256)[],bytes32,uint256)
+orders: [
+  [0]: (
+    maker: 0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045
+    amount: 1500000000000000000 (1.5 with 18 decimals)
+  )
+  [1]: (
+    maker: 0x0000000000000000000000000000000000000001
+    amount: 2 (0.000000000000000002 with 18 decimals)
+  )
+]
+salt: 0x0000000000000000000000000000000000000000000000000000000000000007
+deadline: 1700000000"
+        );
+
+        let scaling =
+            Scaling { params: HashMap::from([("deadline".to_string(), 3)]), ..Default::default() };
+        let pretty = pretty_decoded(&func, &values, &scaling);
+        assert!(pretty.ends_with("deadline: 1700000000 (1700000 with 3 decimals)"), "{pretty}");
+        assert!(pretty.contains("amount: 1500000000000000000\n"), "{pretty}");
+    }
+
+    #[test]
+    fn can_decode_with_abi() {
+        let abi: JsonAbi = serde_json::from_str(
+            r#"[{"type":"function","name":"fill","stateMutability":"nonpayable","outputs":[],"inputs":[{"name":"orders","type":"tuple[]","components":[{"name":"maker","type":"address"},{"name":"amount","type":"uint256"}]},{"name":"salt","type":"bytes32"},{"name":"deadline","type":"uint256"}]}]"#,
+        )
+        .unwrap();
+        let calldata = hex::decode(encoded()).unwrap();
+        let (func, values) = decode_calldata_with_abi(&abi, &calldata).unwrap();
+        assert_eq!(func.name, "fill");
+        assert_eq!(
+            decoded_to_json(&values),
+            serde_json::json!([
+                [
+                    ["0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045", "1500000000000000000"],
+                    ["0x0000000000000000000000000000000000000001", "2"],
+                ],
+                "0x0000000000000000000000000000000000000000000000000000000000000007",
+                "1700000000",
+            ])
+        );
+
+        let err = decode_calldata_with_abi(&abi, &hex::decode("0x12345678").unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "no function of the ABI has the selector 0x12345678");
+    }
+
+    #[test]
+    fn can_parse_param_scale() {
+        assert_eq!(
+            "amountIn:6".parse::<ParamScale>().unwrap(),
+            ParamScale { name: "amountIn".to_string(), decimals: 6 }
+        );
+        assert!("amountIn".parse::<ParamScale>().is_err());
+        assert_eq!(format_units(U256::from(1_230_000), 6), "1.23");
+        assert_eq!(format_units(U256::from(5), 0), "5");
+    }
+}
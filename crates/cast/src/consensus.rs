@@ -0,0 +1,307 @@
+//! Consensus encoding of transactions and receipts from their JSON-RPC fields, see
+//! `cast tx --raw` and `cast receipt --raw`.
+
+use crate::{
+    overrides::is_method_not_found_error,
+    raw_tx::{prefixed_list, TxType},
+    Cast,
+};
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256, U64};
+use alloy_rlp::Encodable;
+use ethers_core::types::H256;
+use ethers_providers::{Middleware, MiddlewareError, PendingTransaction};
+use eyre::{eyre, Result};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{Map, Value};
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Returns the signed network encoding of the transaction.
+    ///
+    /// The encoding is fetched with `eth_getRawTransactionByHash` if the node supports it, and
+    /// otherwise reconstructed from the fields of the transaction, see [`encode_transaction`].
+    pub async fn raw_transaction(&self, tx_hash: H256) -> Result<Bytes> {
+        match self
+            .provider
+            .request::<_, Option<Bytes>>("eth_getRawTransactionByHash", [tx_hash])
+            .await
+        {
+            Ok(Some(raw)) if !raw.is_empty() => return Ok(raw),
+            Ok(_) => {}
+            Err(err) if err.as_error_response().is_some_and(is_method_not_found_error) => {}
+            Err(err) => return Err(err.into()),
+        }
+        let tx: Option<Value> =
+            self.provider.request("eth_getTransactionByHash", [tx_hash]).await?;
+        let tx = tx.ok_or_else(|| eyre!("tx not found: {tx_hash:?}"))?;
+        encode_transaction(&tx)
+    }
+
+    /// Returns the consensus encoding of the receipt of the transaction, see [`encode_receipt`].
+    ///
+    /// Unless `cast_async` is set, waits for the confirmations if the transaction is pending.
+    pub async fn raw_receipt(
+        &self,
+        tx_hash: H256,
+        confs: usize,
+        cast_async: bool,
+    ) -> Result<Bytes> {
+        let mut receipt: Option<Value> =
+            self.provider.request("eth_getTransactionReceipt", [tx_hash]).await?;
+        if receipt.is_none() {
+            if cast_async {
+                eyre::bail!("tx not found: {tx_hash:?}")
+            }
+            PendingTransaction::new(tx_hash, self.provider.provider())
+                .confirmations(confs)
+                .await?
+                .ok_or_else(|| {
+                    eyre!("tx not found, might have been dropped from mempool: {tx_hash:?}")
+                })?;
+            receipt = self.provider.request("eth_getTransactionReceipt", [tx_hash]).await?;
+        }
+        let receipt = receipt.ok_or_else(|| eyre!("tx not found: {tx_hash:?}"))?;
+        encode_receipt(&receipt)
+    }
+}
+
+/// Reconstructs the signed network encoding of a transaction from its JSON-RPC fields.
+///
+/// Fails if the encoding doesn't reproduce the hash of the transaction, e.g. for transaction
+/// types of other chains with fields unknown to cast, rather than returning wrong bytes.
+pub fn encode_transaction(tx: &Value) -> Result<Bytes> {
+    let tx = JsonFields::new(tx, "transaction")?;
+    let hash = tx.get::<B256>("hash")?;
+    let tx_type = tx
+        .tx_type()
+        .map_err(|err| eyre!("can't reconstruct the raw encoding of transaction {hash}: {err}"))?;
+
+    let mut fields = Vec::new();
+    if tx_type == TxType::Legacy {
+        tx.get::<U64>("nonce")?.encode(&mut fields);
+        tx.get::<U256>("gasPrice")?.encode(&mut fields);
+        tx.get::<U256>("gas")?.encode(&mut fields);
+        encode_to(tx.opt("to")?, &mut fields);
+        tx.get::<U256>("value")?.encode(&mut fields);
+        tx.get::<Bytes>("input")?.encode(&mut fields);
+        tx.get::<U256>("v")?.encode(&mut fields);
+    } else {
+        tx.get::<U64>("chainId")?.encode(&mut fields);
+        tx.get::<U64>("nonce")?.encode(&mut fields);
+        if tx_type == TxType::Eip2930 {
+            tx.get::<U256>("gasPrice")?.encode(&mut fields);
+        } else {
+            tx.get::<U256>("maxPriorityFeePerGas")?.encode(&mut fields);
+            tx.get::<U256>("maxFeePerGas")?.encode(&mut fields);
+        }
+        tx.get::<U256>("gas")?.encode(&mut fields);
+        encode_to(tx.opt("to")?, &mut fields);
+        tx.get::<U256>("value")?.encode(&mut fields);
+        tx.get::<Bytes>("input")?.encode(&mut fields);
+        let access_list = tx.opt::<Vec<AccessListItem>>("accessList")?.unwrap_or_default();
+        encode_list(&access_list, &mut fields, |item, out| {
+            let mut item_fields = Vec::new();
+            item.address.encode(&mut item_fields);
+            item.storage_keys.encode(&mut item_fields);
+            out.extend(prefixed_list(None, &item_fields));
+        });
+        if tx_type == TxType::Eip4844 {
+            tx.get::<U256>("maxFeePerBlobGas")?.encode(&mut fields);
+            tx.get::<Vec<B256>>("blobVersionedHashes")?.encode(&mut fields);
+        }
+        if tx_type == TxType::Eip7702 {
+            let authorizations = tx.get::<Vec<Authorization>>("authorizationList")?;
+            encode_list(&authorizations, &mut fields, |auth, out| {
+                let mut auth_fields = Vec::new();
+                auth.chain_id.encode(&mut auth_fields);
+                auth.address.encode(&mut auth_fields);
+                auth.nonce.encode(&mut auth_fields);
+                auth.y_parity.encode(&mut auth_fields);
+                auth.r.encode(&mut auth_fields);
+                auth.s.encode(&mut auth_fields);
+                out.extend(prefixed_list(None, &auth_fields));
+            });
+        }
+        // older nodes only return `v`, which is the parity for typed transactions
+        let y_parity = match tx.opt::<U64>("yParity")? {
+            Some(y_parity) => y_parity,
+            None => tx.get::<U64>("v")?,
+        };
+        y_parity.encode(&mut fields);
+    }
+    tx.get::<U256>("r")?.encode(&mut fields);
+    tx.get::<U256>("s")?.encode(&mut fields);
+
+    let prefix = (tx_type != TxType::Legacy).then_some(tx_type as u8);
+    let raw = prefixed_list(prefix, &fields);
+    let reconstructed = keccak256(&raw);
+    if reconstructed != hash {
+        eyre::bail!(
+            "can't reconstruct the raw encoding of {} transaction {hash}: the encoding of its \
+             fields hashes to {reconstructed}, the node may have returned fields unknown to cast",
+            tx_type.name()
+        )
+    }
+    Ok(raw.into())
+}
+
+/// Builds the consensus encoding of a receipt from its JSON-RPC fields, prefixed by the type
+/// of its transaction unless legacy.
+///
+/// Receipts from before the Byzantium fork are encoded with their state `root` instead of the
+/// `status`.
+pub fn encode_receipt(receipt: &Value) -> Result<Bytes> {
+    let receipt = JsonFields::new(receipt, "receipt")?;
+    let tx_type = receipt.tx_type()?;
+
+    let mut fields = Vec::new();
+    match receipt.opt::<U64>("status")? {
+        Some(status) => status.encode(&mut fields),
+        None => receipt.get::<B256>("root")?.encode(&mut fields),
+    }
+    receipt.get::<U256>("cumulativeGasUsed")?.encode(&mut fields);
+    let bloom = receipt.get::<Bytes>("logsBloom")?;
+    if bloom.len() != 256 {
+        eyre::bail!(
+            "invalid `logsBloom` field in the receipt: expected 256 bytes, got {}",
+            bloom.len()
+        )
+    }
+    bloom.encode(&mut fields);
+    encode_list(&receipt.get::<Vec<Log>>("logs")?, &mut fields, |log, out| {
+        let mut log_fields = Vec::new();
+        log.address.encode(&mut log_fields);
+        log.topics.encode(&mut log_fields);
+        log.data.encode(&mut log_fields);
+        out.extend(prefixed_list(None, &log_fields));
+    });
+
+    let prefix = (tx_type != TxType::Legacy).then_some(tx_type as u8);
+    Ok(prefixed_list(prefix, &fields).into())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessListItem {
+    address: Address,
+    storage_keys: Vec<B256>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Authorization {
+    chain_id: U256,
+    address: Address,
+    nonce: U64,
+    y_parity: U64,
+    r: U256,
+    s: U256,
+}
+
+#[derive(Deserialize)]
+struct Log {
+    address: Address,
+    topics: Vec<B256>,
+    data: Bytes,
+}
+
+/// The recipient, the empty string for contract creations.
+fn encode_to(to: Option<Address>, out: &mut Vec<u8>) {
+    match to {
+        Some(to) => to.encode(out),
+        None => Bytes::new().encode(out),
+    }
+}
+
+/// Encodes the items with `encode` as an RLP list.
+fn encode_list<T>(items: &[T], out: &mut Vec<u8>, encode: impl Fn(&T, &mut Vec<u8>)) {
+    let mut payload = Vec::new();
+    for item in items {
+        encode(item, &mut payload);
+    }
+    out.extend(prefixed_list(None, &payload));
+}
+
+/// The fields of a JSON-RPC object.
+struct JsonFields<'a> {
+    object: &'a Map<String, Value>,
+    name: &'static str,
+}
+
+impl<'a> JsonFields<'a> {
+    fn new(value: &'a Value, name: &'static str) -> Result<Self> {
+        let object =
+            value.as_object().ok_or_else(|| eyre!("invalid {name}: expected an object"))?;
+        Ok(Self { object, name })
+    }
+
+    /// Returns the field, which must be set.
+    fn get<T: DeserializeOwned>(&self, key: &str) -> Result<T> {
+        self.opt(key)?.ok_or_else(|| eyre!("missing `{key}` field in the {}", self.name))
+    }
+
+    /// Returns the field, if set and not null.
+    fn opt<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        match self.object.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => T::deserialize(value)
+                .map(Some)
+                .map_err(|err| eyre!("invalid `{key}` field in the {}: {err}", self.name)),
+        }
+    }
+
+    /// Returns the EIP-2718 type, legacy if unset.
+    fn tx_type(&self) -> Result<TxType> {
+        let tx_type = self.opt::<U64>("type")?.unwrap_or_default();
+        u8::try_from(tx_type)
+            .ok()
+            .and_then(|tx_type| TxType::try_from(tx_type).ok())
+            .ok_or_else(|| eyre!("unsupported transaction type {tx_type:#x}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn can_encode_receipts() {
+        let log = json!({
+            "address": "0x000000000000000000000000000000000000dead",
+            "topics": ["0x0000000000000000000000000000000000000000000000000000000000000001"],
+            "data": "0x02",
+            "logIndex": "0x0",
+        });
+        let bloom = format!("0x{}", "00".repeat(256));
+        let receipt = json!({
+            "type": "0x2",
+            "status": "0x1",
+            "cumulativeGasUsed": "0x5208",
+            "logsBloom": bloom,
+            "logs": [log],
+        });
+        let raw = encode_receipt(&receipt).unwrap();
+        // type, list header, status, cumulative gas, bloom header
+        assert_eq!(raw[..11], [0x02, 0xf9, 0x01, 0x43, 0x01, 0x82, 0x52, 0x08, 0xb9, 0x01, 0x00]);
+        assert_eq!(raw.len(), 4 + 0x143);
+
+        let mut legacy = receipt.clone();
+        legacy["type"] = json!("0x0");
+        assert_eq!(encode_receipt(&legacy).unwrap()[..], raw[1..]);
+
+        let mut pre_byzantium = legacy;
+        pre_byzantium.as_object_mut().unwrap().remove("status");
+        pre_byzantium["root"] = json!(B256::with_last_byte(1));
+        let raw = encode_receipt(&pre_byzantium).unwrap();
+        assert_eq!(raw[..4], [0xf9, 0x01, 0x63, 0xa0]);
+        assert_eq!(raw[4..36], B256::with_last_byte(1)[..]);
+
+        let mut deposit = receipt;
+        deposit["type"] = json!("0x7e");
+        let err = encode_receipt(&deposit).unwrap_err();
+        assert_eq!(err.to_string(), "unsupported transaction type 0x7e");
+    }
+}
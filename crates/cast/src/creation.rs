@@ -0,0 +1,314 @@
+//! Locating the deployment of contracts, see `cast creation-code` and `cast constructor-args`.
+
+use crate::Cast;
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy_json_abi::Constructor;
+use alloy_primitives::{Address, Bytes, B256};
+use ethers_core::{
+    types::{
+        Action, BlockId, CallFrame, GethDebugBuiltInTracerType, GethDebugTracerType,
+        GethDebugTracingOptions, GethTrace, GethTraceFrame, Res, Transaction,
+    },
+    utils::get_contract_address,
+};
+use ethers_providers::Middleware;
+use eyre::{Context, Result};
+use foundry_block_explorers::Client;
+use foundry_common::types::{ToAlloy, ToEthers};
+use serde::Serialize;
+
+/// The deployment of a contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractCreation {
+    pub address: Address,
+    pub transaction_hash: B256,
+    pub block_number: Option<u64>,
+    /// The account that created the contract, the factory if it was created by a contract
+    pub creator: Address,
+    /// Whether the contract was created by a factory rather than by the transaction itself
+    pub factory: bool,
+    /// The init code, including the constructor arguments
+    pub creation_code: Bytes,
+}
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Locates the deployment of the contract, through the explorer if a client is given, or by
+    /// binary-searching the block in which its code appeared.
+    pub async fn contract_creation(
+        &self,
+        address: Address,
+        explorer: Option<&Client>,
+    ) -> Result<ContractCreation> {
+        let tx = match explorer {
+            Some(client) => {
+                let data = client.contract_creation_data(address).await.wrap_err_with(|| {
+                    format!("failed to get the creation transaction of {address} from the explorer")
+                })?;
+                let hash = data.transaction_hash;
+                self.provider
+                    .get_transaction(hash.to_ethers())
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("creation transaction {hash} not found"))?
+            }
+            None => {
+                let block = self.creation_block(address).await?;
+                self.creation_transaction(address, block).await?
+            }
+        };
+        self.creation_in_transaction(address, &tx).await?.ok_or_else(|| {
+            eyre::eyre!(
+                "{address} isn't created by transaction {:#x}, nor by a CREATE of its trace",
+                tx.hash
+            )
+        })
+    }
+
+    /// Returns the first block at which the address has code, by binary-searching `eth_getCode`,
+    /// which needs an archive node.
+    pub async fn creation_block(&self, address: Address) -> Result<u64> {
+        let latest = self.provider.get_block_number().await?.as_u64();
+        if !self.has_code(address, latest).await? {
+            eyre::bail!("{address} has no code at the latest block {latest}, it isn't a contract")
+        }
+        let (mut low, mut high) = (0, latest);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.has_code(address, mid).await? {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        Ok(low)
+    }
+
+    async fn has_code(&self, address: Address, block: u64) -> Result<bool> {
+        let code = self
+            .provider
+            .get_code(address.to_ethers(), Some(BlockId::from(block)))
+            .await
+            .wrap_err_with(|| {
+                format!(
+                    "failed to get the code at block {block}, the RPC may not be an archive node"
+                )
+            })?;
+        Ok(!code.is_empty())
+    }
+
+    /// Returns the transaction of the block that created the contract.
+    pub async fn creation_transaction(&self, address: Address, block: u64) -> Result<Transaction> {
+        let txs = self
+            .provider
+            .get_block_with_txs(block)
+            .await?
+            .ok_or_else(|| eyre::eyre!("block {block} not found"))?
+            .transactions;
+
+        // direct deployments can be identified without tracing
+        if let Some(tx) = txs.iter().find(|tx| {
+            tx.to.is_none() && get_contract_address(tx.from, tx.nonce).to_alloy() == address
+        }) {
+            return Ok(tx.clone())
+        }
+        for tx in txs.iter().filter(|tx| tx.to.is_some()) {
+            if self.traced_creation(address, tx.hash.to_alloy()).await?.is_some() {
+                return Ok(tx.clone())
+            }
+        }
+        eyre::bail!("could not find the transaction that created {address} in block {block}")
+    }
+
+    /// Returns the creation of the contract by the transaction, directly or by a `CREATE` of its
+    /// trace.
+    async fn creation_in_transaction(
+        &self,
+        address: Address,
+        tx: &Transaction,
+    ) -> Result<Option<ContractCreation>> {
+        let creation = |creator, factory, creation_code| ContractCreation {
+            address,
+            transaction_hash: tx.hash.to_alloy(),
+            block_number: tx.block_number.map(|n| n.as_u64()),
+            creator,
+            factory,
+            creation_code,
+        };
+        if tx.to.is_none() && get_contract_address(tx.from, tx.nonce).to_alloy() == address {
+            return Ok(Some(creation(tx.from.to_alloy(), false, tx.input.clone().to_alloy())))
+        }
+        let traced = self.traced_creation(address, tx.hash.to_alloy()).await?;
+        Ok(traced.map(|(creator, code)| creation(creator, true, code)))
+    }
+
+    /// Returns the creator and the init code of the `CREATE` of the address in the trace of the
+    /// transaction.
+    ///
+    /// Uses the call tracer of `debug_traceTransaction`, falling back to `trace_transaction` if the
+    /// node doesn't support it.
+    async fn traced_creation(
+        &self,
+        address: Address,
+        tx_hash: B256,
+    ) -> Result<Option<(Address, Bytes)>> {
+        let opts = GethDebugTracingOptions {
+            tracer: Some(GethDebugTracerType::BuiltInTracer(
+                GethDebugBuiltInTracerType::CallTracer,
+            )),
+            ..Default::default()
+        };
+        if let Ok(GethTrace::Known(GethTraceFrame::CallTracer(frame))) =
+            self.provider.debug_trace_transaction(tx_hash.to_ethers(), opts).await
+        {
+            return Ok(find_create_frame(&frame, address))
+        }
+
+        let traces = self.provider.trace_transaction(tx_hash.to_ethers()).await.wrap_err(
+            "failed to trace the transaction, the RPC supports neither `debug_traceTransaction` \
+             nor `trace_transaction`",
+        )?;
+        Ok(traces.into_iter().find_map(|trace| match (trace.action, trace.result) {
+            (Action::Create(create), Some(Res::Create(result)))
+                if result.address.to_alloy() == address =>
+            {
+                Some((create.from.to_alloy(), create.init.to_alloy()))
+            }
+            _ => None,
+        }))
+    }
+}
+
+/// Returns the creator and the init code of the `CREATE` or `CREATE2` frame of the address.
+fn find_create_frame(frame: &CallFrame, address: Address) -> Option<(Address, Bytes)> {
+    let to = frame.to.as_ref().and_then(|to| to.as_address()).map(|to| to.to_alloy());
+    if frame.typ.starts_with("CREATE") && to == Some(address) {
+        return Some((frame.from.to_alloy(), frame.input.clone().to_alloy()))
+    }
+    frame.calls.iter().flatten().find_map(|call| find_create_frame(call, address))
+}
+
+/// Splits the ABI-encoded constructor arguments off the end of the creation code.
+///
+/// The arguments are the shortest suffix that decodes as the constructor parameters and
+/// re-encodes to the same bytes, if the length of the init code isn't known.
+pub fn split_constructor_args<'a>(
+    creation_code: &'a [u8],
+    constructor: Option<&Constructor>,
+    code_len: Option<usize>,
+) -> Result<(&'a [u8], &'a [u8])> {
+    let Some(constructor) = constructor.filter(|c| !c.inputs.is_empty()) else {
+        return Ok((creation_code, &[]))
+    };
+    if let Some(len) = code_len {
+        if len > creation_code.len() {
+            eyre::bail!("the creation code is shorter than the bytecode of the artifact")
+        }
+        return Ok(creation_code.split_at(len))
+    }
+
+    let ty = constructor_type(constructor)?;
+    let mut len = 32;
+    while len <= creation_code.len() {
+        let (code, args) = creation_code.split_at(creation_code.len() - len);
+        if let Ok(value) = ty.abi_decode_params(args) {
+            if value.abi_encode_params() == args {
+                return Ok((code, args))
+            }
+        }
+        len += 32;
+    }
+    eyre::bail!(
+        "could not find constructor arguments matching `{}` at the end of the creation code",
+        constructor_signature(constructor)
+    )
+}
+
+/// Decodes the constructor arguments.
+pub fn decode_constructor_args(constructor: &Constructor, args: &[u8]) -> Result<Vec<DynSolValue>> {
+    let value = constructor_type(constructor)?
+        .abi_decode_params(args)
+        .wrap_err("failed to decode the constructor arguments")?;
+    match value {
+        DynSolValue::Tuple(values) => Ok(values),
+        value => Ok(vec![value]),
+    }
+}
+
+fn constructor_type(constructor: &Constructor) -> Result<DynSolType> {
+    let types = constructor
+        .inputs
+        .iter()
+        .map(|param| param.selector_type().parse())
+        .collect::<Result<Vec<DynSolType>, _>>()?;
+    Ok(DynSolType::Tuple(types))
+}
+
+fn constructor_signature(constructor: &Constructor) -> String {
+    let types = constructor.inputs.iter().map(|param| param.selector_type()).collect::<Vec<_>>();
+    format!("constructor({})", types.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_json_abi::JsonAbi;
+    use alloy_primitives::U256;
+
+    fn constructor(inputs: &str) -> Constructor {
+        let abi: JsonAbi = serde_json::from_str(&format!(
+            r#"[{{"type":"constructor","stateMutability":"nonpayable","inputs":{inputs}}}]"#
+        ))
+        .unwrap();
+        abi.constructor.unwrap()
+    }
+
+    #[test]
+    fn can_split_static_constructor_args() {
+        let constructor =
+            constructor(r#"[{"name":"a","type":"uint256"},{"name":"b","type":"address"}]"#);
+        let args = DynSolValue::Tuple(vec![
+            DynSolValue::Uint(U256::from(42), 256),
+            DynSolValue::Address(Address::repeat_byte(0x11)),
+        ])
+        .abi_encode_params();
+        let creation_code = [hex::decode("6080604052").unwrap(), args.clone()].concat();
+
+        let (code, split) =
+            split_constructor_args(&creation_code, Some(&constructor), None).unwrap();
+        assert_eq!(code, hex::decode("6080604052").unwrap());
+        assert_eq!(split, args);
+        let values = decode_constructor_args(&constructor, split).unwrap();
+        assert_eq!(values[0], DynSolValue::Uint(U256::from(42), 256));
+
+        // the length of the artifact's bytecode takes precedence
+        let (code, _) =
+            split_constructor_args(&creation_code, Some(&constructor), Some(2)).unwrap();
+        assert_eq!(code.len(), 2);
+    }
+
+    #[test]
+    fn can_split_dynamic_constructor_args() {
+        let constructor = constructor(r#"[{"name":"name","type":"string"}]"#);
+        let args =
+            DynSolValue::Tuple(vec![DynSolValue::String("token".to_string())]).abi_encode_params();
+        let creation_code = [vec![0xfe; 100], args.clone()].concat();
+
+        let (code, split) =
+            split_constructor_args(&creation_code, Some(&constructor), None).unwrap();
+        assert_eq!(code.len(), 100);
+        assert_eq!(split, args);
+        let values = decode_constructor_args(&constructor, split).unwrap();
+        assert_eq!(values, vec![DynSolValue::String("token".to_string())]);
+
+        assert!(split_constructor_args(&[0xfe; 100], Some(&constructor), None).is_err());
+    }
+
+    #[test]
+    fn no_constructor_args() {
+        let (code, args) = split_constructor_args(&[1, 2, 3], None, None).unwrap();
+        assert_eq!((code, args), (&[1, 2, 3][..], &[][..]));
+    }
+}
@@ -0,0 +1,214 @@
+//! Recovery of the functions of deployed bytecode from its dispatcher, see `cast selectors`.
+
+use crate::proxy::minimal_proxy_implementation;
+use alloy_primitives::{b256, hex, Address, Selector, B256};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The storage slot of the diamond storage of the EIP-2535 reference implementation,
+/// `keccak256("diamond.standard.diamond.storage")`.
+const DIAMOND_STORAGE_SLOT: B256 =
+    b256!("c8fcad8db84d3cc18b4c41d551ea0ee66dd599cde068d998e57d5e09332c131c");
+
+/// The selectors of `diamondCut(...)`, `facets()` and `facetAddress(bytes4)` of EIP-2535.
+const DIAMOND_SELECTORS: [[u8; 4]; 3] = [hex!("1f931c1c"), hex!("7a0ed627"), hex!("cdffacc6")];
+
+const EQ: u8 = 0x14;
+const ISZERO: u8 = 0x15;
+const XOR: u8 = 0x18;
+const SUB: u8 = 0x03;
+const JUMPI: u8 = 0x57;
+const JUMPDEST: u8 = 0x5b;
+const PUSH1: u8 = 0x60;
+const PUSH4: u8 = 0x63;
+const PUSH32: u8 = 0x7f;
+const DUP1: u8 = 0x80;
+const SWAP16: u8 = 0x9f;
+
+/// A function of the dispatcher.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DispatchedFunction {
+    pub selector: Selector,
+    /// The argument types guessed from how the function reads its calldata
+    pub arguments: String,
+    /// The offset of the code of the function the dispatcher jumps to, if it was found
+    pub jump_destination: Option<usize>,
+    /// The known signatures of the selector, if they were looked up
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub signatures: Vec<String>,
+}
+
+/// The functions of bytecode recovered from its dispatcher.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dispatcher {
+    /// The implementation if the code is the one of an EIP-1167 minimal proxy, which has no
+    /// functions of its own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimal_proxy_implementation: Option<Address>,
+    /// Whether the code looks like the one of an EIP-2535 diamond, whose selectors are stored in
+    /// its storage with the facets implementing them
+    pub diamond: bool,
+    pub functions: Vec<DispatchedFunction>,
+}
+
+/// Recovers the functions of the runtime code from its dispatcher.
+///
+/// The selectors and the argument types are found by symbolically executing the dispatcher,
+/// which works for the linear and binary search dispatchers of solc and vyper. The jump
+/// destinations are found by matching the comparisons of the calldata selector with each one.
+pub fn recover_dispatcher(code: &[u8]) -> Dispatcher {
+    if let Some(implementation) = minimal_proxy_implementation(code) {
+        return Dispatcher {
+            minimal_proxy_implementation: Some(implementation),
+            ..Default::default()
+        }
+    }
+
+    let selectors = evmole::function_selectors(code, 0);
+    let destinations = jump_destinations(code);
+    let functions = selectors
+        .iter()
+        .map(|selector| DispatchedFunction {
+            selector: Selector::from(*selector),
+            arguments: evmole::function_arguments(code, selector, 0),
+            jump_destination: destinations.get(selector).copied(),
+            signatures: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+    let diamond = functions.iter().any(|f| DIAMOND_SELECTORS.contains(&f.selector.0)) ||
+        instructions(code)
+            .any(|(_, op, data)| op == PUSH32 && data == DIAMOND_STORAGE_SLOT.as_slice());
+    Dispatcher { minimal_proxy_implementation: None, diamond, functions }
+}
+
+/// Iterates over the offsets, opcodes and immediate data of the instructions of the code.
+fn instructions(code: &[u8]) -> impl Iterator<Item = (usize, u8, &[u8])> {
+    let mut pc = 0;
+    std::iter::from_fn(move || {
+        let op = *code.get(pc)?;
+        let size = if (PUSH1..=PUSH32).contains(&op) { (op - PUSH1 + 1) as usize } else { 0 };
+        let data = &code[(pc + 1).min(code.len())..(pc + 1 + size).min(code.len())];
+        let instruction = (pc, op, data);
+        pc += 1 + size;
+        Some(instruction)
+    })
+}
+
+/// Returns the destinations of the dispatcher branches per selector.
+///
+/// The branches compare a pushed selector with the one of the calldata, e.g.
+/// `PUSH4 <selector> EQ PUSH2 <destination> JUMPI` for solc, or
+/// `PUSH4 <selector> DUP2 XOR PUSH2 <next branch> JUMPI` for vyper which then falls through to
+/// the function. Selectors with leading zero bytes are pushed with less than 4 bytes.
+fn jump_destinations(code: &[u8]) -> HashMap<[u8; 4], usize> {
+    let instructions = instructions(code).collect::<Vec<_>>();
+    let op_at = |i: usize| instructions.get(i).map(|(_, op, _)| *op);
+    let mut destinations = HashMap::new();
+    for (i, &(_, op, data)) in instructions.iter().enumerate() {
+        if !(PUSH1..=PUSH4).contains(&op) || data.len() != (op - PUSH1 + 1) as usize {
+            continue
+        }
+        let mut selector = [0u8; 4];
+        selector[4 - data.len()..].copy_from_slice(data);
+
+        // the calldata selector is duplicated or swapped next to the pushed one
+        let mut j = i + 1;
+        while j < i + 3 && op_at(j).is_some_and(|op| (DUP1..=SWAP16).contains(&op)) {
+            j += 1;
+        }
+        let Some(comparison @ (EQ | XOR | SUB)) = op_at(j) else { continue };
+        let negated = op_at(j + 1) == Some(ISZERO);
+        if negated {
+            j += 1;
+        }
+        let Some(&(_, push, target)) = instructions.get(j + 1) else { continue };
+        if !(PUSH1..=PUSH4).contains(&push) || op_at(j + 2) != Some(JUMPI) {
+            continue
+        }
+        // `EQ` jumps to the function, `XOR` and `SUB` skip it unless negated
+        let destination = if (comparison == EQ) != negated {
+            target.iter().fold(0usize, |acc, byte| acc << 8 | *byte as usize)
+        } else {
+            instructions[j + 2].0 + 1
+        };
+        // solc functions start with a `JUMPDEST`, vyper ones right after the branch
+        if comparison == EQ && code.get(destination) != Some(&JUMPDEST) {
+            continue
+        }
+        destinations.entry(selector).or_insert(destination);
+    }
+    destinations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn can_recover_solc_dispatcher() {
+        let artifact: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/fixtures/ERC20Artifact.json")).unwrap();
+        let code = hex::decode(artifact["deployedBytecode"]["object"].as_str().unwrap()).unwrap();
+        let dispatcher = recover_dispatcher(&code);
+        assert!(!dispatcher.diamond);
+        assert_eq!(dispatcher.minimal_proxy_implementation, None);
+
+        // the binary search dispatcher branches on 13 selectors
+        let identifiers: BTreeMap<String, String> =
+            serde_json::from_value(artifact["methodIdentifiers"].clone()).unwrap();
+        let mut expected = identifiers.values().cloned().collect::<Vec<_>>();
+        expected.sort();
+        let mut selectors =
+            dispatcher.functions.iter().map(|f| hex::encode(f.selector)).collect::<Vec<_>>();
+        selectors.sort();
+        assert_eq!(selectors, expected);
+
+        let function = |selector: &str| {
+            dispatcher.functions.iter().find(|f| hex::encode(f.selector) == selector).unwrap()
+        };
+        // `name()` and `transfer(address,uint256)`
+        assert_eq!(function("06fdde03").jump_destination, Some(212));
+        let transfer = function("a9059cbb");
+        assert_eq!(transfer.jump_destination, Some(477));
+        assert_eq!(transfer.arguments, "address,uint256");
+        assert!(dispatcher.functions.iter().all(|f| f.jump_destination.is_some()));
+    }
+
+    #[test]
+    fn can_find_vyper_branches() {
+        // PUSH1 0 CALLDATALOAD PUSH1 0xe0 SHR
+        // PUSH4 0xa9059cbb DUP2 XOR PUSH1 0x11 JUMPI <transfer> JUMPDEST
+        // PUSH3 0xfdd58e DUP2 XOR PUSH1 0x1c JUMPI <balanceOf> JUMPDEST STOP
+        let code = hex!(
+            "60003560e01c"
+            "63a9059cbb8118601157005b"
+            "62fdd58e8118601c57005b00"
+        );
+        let destinations = jump_destinations(&code);
+        assert_eq!(destinations[&hex!("a9059cbb")], 16);
+        assert_eq!(destinations[&hex!("00fdd58e")], 27);
+    }
+
+    #[test]
+    fn can_detect_minimal_proxies_and_diamonds() {
+        let code = hex!(
+            "363d3d373d3d3d363d73"
+            "bebebebebebebebebebebebebebebebebebebebe"
+            "5af43d82803e903d91602b57fd5bf3"
+        );
+        let dispatcher = recover_dispatcher(&code);
+        let implementation = address!("bebebebebebebebebebebebebebebebebebebebe");
+        assert_eq!(dispatcher.minimal_proxy_implementation, Some(implementation));
+        assert!(dispatcher.functions.is_empty());
+
+        // loads of the diamond storage slot
+        let mut code = vec![PUSH32];
+        code.extend_from_slice(DIAMOND_STORAGE_SLOT.as_slice());
+        code.extend_from_slice(&hex!("5400"));
+        assert!(recover_dispatcher(&code).diamond);
+    }
+}
@@ -0,0 +1,447 @@
+//! ENS text records, avatars and resolvers, see `cast ens`.
+//!
+//! The resolver of a name is found with the wildcard resolution of ENSIP-10, and the offchain
+//! lookups of CCIP-Read (EIP-3668) resolvers are made through their gateways unless disabled.
+
+use crate::Cast;
+use alloy_dyn_abi::{DynSolValue, FunctionExt, JsonAbiExt};
+use alloy_json_abi::Function;
+use alloy_primitives::{address, hex, keccak256, Address, B256, U256};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ethers_core::types::TransactionRequest;
+use ethers_providers::{Middleware, MiddlewareError};
+use eyre::{eyre, Result, WrapErr};
+use foundry_common::{abi::get_func, types::ToEthers};
+use serde_json::{json, Value};
+
+/// The address of the ENS registry, the same on mainnet and its testnets.
+pub const ENS_REGISTRY: Address = address!("00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+/// The ERC-165 interface of the `resolve(bytes,bytes)` function of ENSIP-10 resolvers.
+const EXTENDED_RESOLVER_INTERFACE: [u8; 4] = hex!("9061b923");
+
+/// The error CCIP-Read contracts revert with to request an offchain lookup.
+const OFFCHAIN_LOOKUP: &str = "OffchainLookup(address,string[],bytes,bytes4,bytes)";
+
+/// The most offchain lookups of a call, as recommended by EIP-3668.
+const MAX_CCIP_REDIRECTS: usize = 4;
+
+const IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+const ARWEAVE_GATEWAY: &str = "https://arweave.net/";
+
+/// Returns the ENS node of the name.
+///
+/// The name is lowercased, but not otherwise normalized.
+pub fn namehash(name: &str) -> B256 {
+    name.to_lowercase()
+        .rsplit('.')
+        .filter(|label| !label.is_empty())
+        .fold(B256::ZERO, |node, label| {
+            keccak256([node.as_slice(), keccak256(label.as_bytes()).as_slice()].concat())
+        })
+}
+
+/// Returns the DNS wire format of the name, which ENSIP-10 resolvers are called with.
+pub fn dns_encode(name: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    for label in name.to_lowercase().split('.').filter(|label| !label.is_empty()) {
+        let len = u8::try_from(label.len())
+            .map_err(|_| eyre!("the label `{label}` is longer than 255 bytes"))?;
+        out.push(len);
+        out.extend_from_slice(label.as_bytes());
+    }
+    out.push(0);
+    Ok(out)
+}
+
+/// The resolver of a name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnsResolver {
+    pub address: Address,
+    /// The name the resolver is set for, a parent of the resolved name for wildcard resolution
+    pub name: String,
+    /// Whether the resolver was found for a parent of the resolved name
+    pub wildcard: bool,
+    /// Whether the resolver implements `resolve(bytes,bytes)` of ENSIP-10
+    pub extended: bool,
+}
+
+/// The token standard of an NFT avatar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NftStandard {
+    Erc721,
+    Erc1155,
+}
+
+/// An `avatar` text record, see ENSIP-12.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AvatarUri {
+    /// An NFT, as `eip155:<chain id>/<erc721|erc1155>:<contract>/<token id>`
+    Nft { chain_id: u64, standard: NftStandard, contract: Address, token_id: U256 },
+    /// The URI of the image, e.g. `https`, `ipfs` or `data`
+    Uri(String),
+}
+
+impl AvatarUri {
+    pub fn parse(avatar: &str) -> Result<Self> {
+        let Some(nft) = avatar.strip_prefix("eip155:") else {
+            return Ok(Self::Uri(avatar.to_string()))
+        };
+        let invalid = || {
+            eyre!(
+                "invalid NFT avatar `{avatar}`, expected \
+                 `eip155:<chain id>/<erc721|erc1155>:<contract>/<token id>`"
+            )
+        };
+        let mut parts = nft.split('/');
+        let (Some(chain_id), Some(asset), Some(token_id), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid())
+        };
+        let (standard, contract) = asset.split_once(':').ok_or_else(invalid)?;
+        let standard = match standard.to_lowercase().as_str() {
+            "erc721" => NftStandard::Erc721,
+            "erc1155" => NftStandard::Erc1155,
+            _ => eyre::bail!("unsupported NFT standard `{standard}` of the avatar `{avatar}`"),
+        };
+        Ok(Self::Nft {
+            chain_id: chain_id.parse().map_err(|_| invalid())?,
+            standard,
+            contract: contract.parse().map_err(|_| invalid())?,
+            token_id: token_id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Returns the resolver of the name, following the wildcard resolution of ENSIP-10.
+    ///
+    /// A resolver set for a parent of the name only resolves the name if it is an extended
+    /// resolver.
+    pub async fn ens_resolver(&self, name: &str) -> Result<Option<EnsResolver>> {
+        let name = name.to_lowercase();
+        let registry = get_func("resolver(bytes32)(address)")?;
+        let mut parent = name.as_str();
+        loop {
+            let node = DynSolValue::FixedBytes(namehash(parent), 32);
+            let output = self.eth_call(ENS_REGISTRY, registry.abi_encode_input(&[node])?).await?;
+            let resolver = registry
+                .abi_decode_output(&output, false)
+                .wrap_err("invalid output of the ENS registry")?
+                .first()
+                .and_then(DynSolValue::as_address)
+                .unwrap_or_default();
+            if !resolver.is_zero() {
+                let extended = self.supports_interface(resolver, EXTENDED_RESOLVER_INTERFACE).await;
+                let wildcard = parent != name;
+                if wildcard && !extended {
+                    return Ok(None)
+                }
+                let name = parent.to_string();
+                return Ok(Some(EnsResolver { address: resolver, name, wildcard, extended }))
+            }
+            match parent.split_once('.') {
+                Some((_, rest)) if !rest.is_empty() => parent = rest,
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    /// Returns the text record of the name with the key, e.g. `url` or `com.twitter`.
+    ///
+    /// Unset records and names without resolver are `None`.
+    pub async fn ens_text(&self, name: &str, key: &str, ccip: bool) -> Result<Option<String>> {
+        let func = get_func("text(bytes32,string)(string)")?;
+        let output = self
+            .resolve_record(name, &func, vec![DynSolValue::String(key.to_string())], ccip)
+            .await?;
+        Ok(output
+            .and_then(|values| values.into_iter().next())
+            .and_then(|value| value.as_str().map(str::to_string))
+            .filter(|text| !text.is_empty()))
+    }
+
+    /// Returns the URL of the image of the `avatar` of the name.
+    ///
+    /// NFT avatars are resolved to the image of the metadata of the token. `ipfs` and `ar` URIs
+    /// are converted to the URLs of public gateways.
+    pub async fn ens_avatar(&self, name: &str, ccip: bool) -> Result<Option<String>> {
+        let Some(avatar) = self.ens_text(name, "avatar", ccip).await? else { return Ok(None) };
+        let image = match AvatarUri::parse(&avatar)? {
+            AvatarUri::Uri(uri) => uri,
+            AvatarUri::Nft { chain_id, standard, contract, token_id } => {
+                let chain = self.provider.get_chainid().await?.as_u64();
+                if chain_id != chain {
+                    eyre::bail!(
+                        "the avatar `{avatar}` is an NFT of chain {chain_id}, but the endpoint is \
+                         on chain {chain}"
+                    )
+                }
+                let func = match standard {
+                    NftStandard::Erc721 => get_func("tokenURI(uint256)(string)")?,
+                    NftStandard::Erc1155 => get_func("uri(uint256)(string)")?,
+                };
+                let data = func.abi_encode_input(&[DynSolValue::Uint(token_id, 256)])?;
+                let output =
+                    func.abi_decode_output(&self.eth_call(contract, data).await?, false)?;
+                let mut uri = output
+                    .first()
+                    .and_then(DynSolValue::as_str)
+                    .ok_or_else(|| eyre!("invalid metadata URI of the avatar `{avatar}`"))?
+                    .to_string();
+                if standard == NftStandard::Erc1155 {
+                    uri = uri.replace("{id}", &hex::encode(token_id.to_be_bytes::<32>()));
+                }
+                let metadata: Value = serde_json::from_slice(&read_uri(&uri).await?)
+                    .wrap_err_with(|| format!("invalid metadata of the avatar `{avatar}`"))?;
+                ["image", "image_url"]
+                    .iter()
+                    .find_map(|key| metadata[key].as_str())
+                    .ok_or_else(|| eyre!("the metadata of the avatar `{avatar}` has no image"))?
+                    .to_string()
+            }
+        };
+        Ok(Some(gateway_url(&image)))
+    }
+
+    /// Calls the record function of the resolver of the name, through `resolve(bytes,bytes)` for
+    /// extended resolvers, with the node of the name prepended to the arguments.
+    async fn resolve_record(
+        &self,
+        name: &str,
+        func: &Function,
+        args: Vec<DynSolValue>,
+        ccip: bool,
+    ) -> Result<Option<Vec<DynSolValue>>> {
+        let Some(resolver) = self.ens_resolver(name).await? else { return Ok(None) };
+        let mut values = vec![DynSolValue::FixedBytes(namehash(name), 32)];
+        values.extend(args);
+        let mut data = func.abi_encode_input(&values)?;
+        if resolver.extended {
+            let resolve = get_func("resolve(bytes,bytes)(bytes)")?;
+            data = resolve.abi_encode_input(&[
+                DynSolValue::Bytes(dns_encode(name)?),
+                DynSolValue::Bytes(data),
+            ])?;
+            let output = self.ccip_call(resolver.address, data, ccip).await?;
+            let output = resolve.abi_decode_output(&output, false)?;
+            data = output.first().and_then(DynSolValue::as_bytes).unwrap_or_default().to_vec();
+        } else {
+            data = self.ccip_call(resolver.address, data, ccip).await?;
+        }
+        // resolvers without the record have no output
+        if data.is_empty() {
+            return Ok(None)
+        }
+        let decoded = func
+            .abi_decode_output(&data, false)
+            .wrap_err_with(|| format!("invalid output of the resolver {}", resolver.address))?;
+        Ok(Some(decoded))
+    }
+
+    /// Makes the call, following the offchain lookups the contract reverts with, see EIP-3668.
+    async fn ccip_call(&self, mut to: Address, mut data: Vec<u8>, ccip: bool) -> Result<Vec<u8>> {
+        let lookup = get_func(OFFCHAIN_LOOKUP)?;
+        for _ in 0..=MAX_CCIP_REDIRECTS {
+            let tx = TransactionRequest::new().to(to.to_ethers()).data(data.clone());
+            let err = match self.provider.call(&tx.into(), None).await {
+                Ok(output) => return Ok(output.to_vec()),
+                Err(err) => err,
+            };
+            let revert = err
+                .as_error_response()
+                .and_then(|err| err.data.as_ref())
+                .and_then(Value::as_str)
+                .and_then(|data| hex::decode(data).ok())
+                .filter(|revert| revert.starts_with(&lookup.selector()[..]));
+            let Some(revert) = revert else { return Err(err.into()) };
+            if !ccip {
+                eyre::bail!(
+                    "the resolver {to} requires an offchain lookup (CCIP-Read), which `--no-ccip` \
+                     disables"
+                )
+            }
+
+            let params = lookup
+                .abi_decode_input(&revert[4..], false)
+                .wrap_err("invalid OffchainLookup revert")?;
+            let param = |i: usize| params.get(i);
+            let (Some(sender), Some(urls), Some(request), Some((callback, _)), Some(extra)) = (
+                param(0).and_then(DynSolValue::as_address),
+                param(1).and_then(DynSolValue::as_array),
+                param(2).and_then(DynSolValue::as_bytes),
+                param(3).and_then(DynSolValue::as_fixed_bytes),
+                param(4).and_then(DynSolValue::as_bytes),
+            ) else {
+                eyre::bail!("invalid OffchainLookup revert")
+            };
+            if sender != to {
+                eyre::bail!("the OffchainLookup sender {sender} isn't the called contract {to}")
+            }
+            let urls = urls.iter().filter_map(DynSolValue::as_str).collect::<Vec<_>>();
+            let response = ccip_fetch(sender, &urls, request).await?;
+
+            data = callback[..4].to_vec();
+            data.extend(
+                DynSolValue::Tuple(vec![
+                    DynSolValue::Bytes(response),
+                    DynSolValue::Bytes(extra.to_vec()),
+                ])
+                .abi_encode_params(),
+            );
+            to = sender;
+        }
+        eyre::bail!("too many offchain lookups, the limit is {MAX_CCIP_REDIRECTS}")
+    }
+
+    async fn eth_call(&self, to: Address, data: Vec<u8>) -> Result<Vec<u8>> {
+        let tx = TransactionRequest::new().to(to.to_ethers()).data(data);
+        Ok(self.provider.call(&tx.into(), None).await?.to_vec())
+    }
+
+    /// Returns whether the contract implements the ERC-165 interface, false if it fails.
+    async fn supports_interface(&self, contract: Address, interface: [u8; 4]) -> bool {
+        let Ok(func) = get_func("supportsInterface(bytes4)(bool)") else { return false };
+        let Ok(data) = func
+            .abi_encode_input(&[DynSolValue::FixedBytes(B256::right_padding_from(&interface), 4)])
+        else {
+            return false
+        };
+        match self.eth_call(contract, data).await {
+            Ok(output) => matches!(
+                func.abi_decode_output(&output, false).as_deref(),
+                Ok([DynSolValue::Bool(true)])
+            ),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Fetches the response of an offchain lookup from the first gateway which answers.
+///
+/// URLs with `{data}` are requested with `GET`, the others with `POST`.
+async fn ccip_fetch(sender: Address, urls: &[&str], data: &[u8]) -> Result<Vec<u8>> {
+    let sender = hex::encode_prefixed(sender);
+    let data = hex::encode_prefixed(data);
+    let client = reqwest::Client::new();
+    let mut errors = Vec::new();
+    for url in urls {
+        let href = url.replace("{sender}", &sender).replace("{data}", &data);
+        let request = if url.contains("{data}") {
+            client.get(&href)
+        } else {
+            client.post(&href).json(&json!({ "data": data, "sender": sender }))
+        };
+        let response = match request.send().await.and_then(|res| res.error_for_status()) {
+            Ok(response) => response,
+            Err(err) => {
+                errors.push(format!("{href}: {err}"));
+                continue
+            }
+        };
+        let body: Value =
+            response.json().await.wrap_err_with(|| format!("{href}: invalid response"))?;
+        let output =
+            body["data"].as_str().ok_or_else(|| eyre!("{href}: the response has no `data`"))?;
+        return Ok(hex::decode(output).wrap_err_with(|| format!("{href}: invalid `data`"))?)
+    }
+    eyre::bail!("the gateways of the offchain lookup failed:\n{}", errors.join("\n"))
+}
+
+/// Returns the HTTP URL of the resource, through a public gateway for `ipfs` and `ar` URIs.
+pub fn gateway_url(uri: &str) -> String {
+    if let Some(path) = uri.strip_prefix("ipfs://") {
+        let path = path.strip_prefix("ipfs/").unwrap_or(path);
+        return format!("{IPFS_GATEWAY}{path}")
+    }
+    if let Some(path) = uri.strip_prefix("ar://") {
+        return format!("{ARWEAVE_GATEWAY}{path}")
+    }
+    uri.to_string()
+}
+
+/// Returns the content of a `data` URI, or fetches the resource of the URI.
+async fn read_uri(uri: &str) -> Result<Vec<u8>> {
+    if let Some(data) = decode_data_uri(uri) {
+        return data
+    }
+    let url = gateway_url(uri);
+    let response = reqwest::get(&url).await?.error_for_status()?;
+    Ok(response.bytes().await.wrap_err_with(|| format!("failed to fetch {url}"))?.to_vec())
+}
+
+/// Decodes the content of a `data` URI, `None` if the URI isn't one.
+fn decode_data_uri(uri: &str) -> Option<Result<Vec<u8>>> {
+    let (header, content) = uri.strip_prefix("data:")?.split_once(',')?;
+    Some(if header.ends_with(";base64") {
+        BASE64.decode(content).wrap_err("invalid base64 data URI")
+    } else {
+        Ok(content.as_bytes().to_vec())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_hash_and_encode_names() {
+        assert_eq!(namehash(""), B256::ZERO);
+        assert_eq!(
+            namehash("eth").to_string(),
+            "0x93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae"
+        );
+        assert_eq!(
+            namehash("Foo.eth").to_string(),
+            "0xde9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f"
+        );
+        assert_eq!(dns_encode("sub.foo.eth").unwrap(), b"\x03sub\x03foo\x03eth\x00");
+        assert!(dns_encode(&format!("{}.eth", "a".repeat(256))).is_err());
+    }
+
+    #[test]
+    fn can_parse_avatars() {
+        assert_eq!(
+            AvatarUri::parse("eip155:1/erc721:0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB/2430")
+                .unwrap(),
+            AvatarUri::Nft {
+                chain_id: 1,
+                standard: NftStandard::Erc721,
+                contract: address!("b47e3cd837dDF8e4c57F05d70Ab865de6e193BBB"),
+                token_id: U256::from(2430),
+            }
+        );
+        assert!(matches!(
+            AvatarUri::parse("eip155:1/ERC1155:0x495f947276749ce646f68ac8c248420045cb7b5e/1"),
+            Ok(AvatarUri::Nft { standard: NftStandard::Erc1155, .. })
+        ));
+        assert_eq!(
+            AvatarUri::parse("ipfs://QmHash").unwrap(),
+            AvatarUri::Uri("ipfs://QmHash".to_string())
+        );
+        assert!(AvatarUri::parse("eip155:1/erc721:0xb47e").is_err());
+        assert!(AvatarUri::parse("eip155:1/erc20:0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB/1")
+            .unwrap_err()
+            .to_string()
+            .contains("unsupported NFT standard `erc20`"));
+    }
+
+    #[test]
+    fn can_read_uris() {
+        assert_eq!(gateway_url("ipfs://ipfs/QmHash/1.png"), "https://ipfs.io/ipfs/QmHash/1.png");
+        assert_eq!(gateway_url("ipfs://QmHash"), "https://ipfs.io/ipfs/QmHash");
+        assert_eq!(gateway_url("ar://tx"), "https://arweave.net/tx");
+        assert_eq!(gateway_url("https://example.com/a.png"), "https://example.com/a.png");
+
+        let json = r#"{"image":"ipfs://QmHash"}"#;
+        let base64 = format!("data:application/json;base64,{}", BASE64.encode(json));
+        assert_eq!(decode_data_uri(&base64).unwrap().unwrap(), json.as_bytes());
+        let plain = format!("data:application/json,{json}");
+        assert_eq!(decode_data_uri(&plain).unwrap().unwrap(), json.as_bytes());
+        assert!(decode_data_uri("https://example.com").is_none());
+    }
+}
@@ -0,0 +1,304 @@
+//! Analysis of the fees of recent blocks, see `cast gas-price --history`.
+
+use crate::{calldata::format_units, overrides::is_method_not_found_error, Cast};
+use alloy_primitives::U256;
+use ethers_core::{
+    types::{BlockNumber, FeeHistory},
+    utils::{
+        eip1559_default_estimator, EIP1559_FEE_ESTIMATION_PAST_BLOCKS,
+        EIP1559_FEE_ESTIMATION_REWARD_PERCENTILE,
+    },
+};
+use ethers_providers::{Middleware, MiddlewareError};
+use eyre::{eyre, Result};
+use foundry_common::types::ToAlloy;
+use futures::{StreamExt, TryStreamExt};
+use serde::Serialize;
+use std::fmt::Write;
+
+/// The percentiles of the priority fees of each block, or of the gas prices before EIP-1559.
+pub const PERCENTILES: [f64; 5] = [10.0, 25.0, 50.0, 75.0, 90.0];
+
+/// The most blocks nodes return from a single `eth_feeHistory` request.
+pub const MAX_HISTORY_BLOCKS: u64 = 1024;
+
+/// The fees paid in a block.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockFees {
+    pub number: u64,
+    /// The base fee, unset before EIP-1559
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<U256>,
+    pub gas_used_ratio: f64,
+    /// The priority fees at [`PERCENTILES`], or the gas prices before EIP-1559
+    pub percentiles: Vec<U256>,
+}
+
+/// The fees of a transaction filled like `cast send` without fee options does.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum FeeSuggestion {
+    #[serde(rename_all = "camelCase")]
+    Eip1559 { max_fee_per_gas: U256, max_priority_fee_per_gas: U256 },
+    #[serde(rename_all = "camelCase")]
+    Legacy { gas_price: U256 },
+}
+
+/// The fees of a window of blocks, and the fees suggested for a transaction after them.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FeeHistorySummary {
+    pub percentiles: Vec<f64>,
+    pub blocks: Vec<BlockFees>,
+    pub suggestion: FeeSuggestion,
+}
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Returns the fees of the `blocks` blocks up to `at`, the latest block by default.
+    ///
+    /// The fees come from `eth_feeHistory`. Before EIP-1559, or if the endpoint doesn't support
+    /// `eth_feeHistory`, the gas prices of the transactions of the blocks are sampled instead.
+    pub async fn fee_history(
+        &self,
+        blocks: u64,
+        at: Option<BlockNumber>,
+    ) -> Result<FeeHistorySummary> {
+        if blocks == 0 || blocks > MAX_HISTORY_BLOCKS {
+            eyre::bail!("the history must have between 1 and {MAX_HISTORY_BLOCKS} blocks")
+        }
+        let at = at.unwrap_or(BlockNumber::Latest);
+        let block =
+            self.provider.get_block(at).await?.ok_or_else(|| eyre!("block {at:?} not found"))?;
+        let number = block.number.ok_or_else(|| eyre!("block {at:?} has no number"))?.as_u64();
+
+        if let Some(base_fee) = block.base_fee_per_gas {
+            match self.provider.fee_history(blocks, number.into(), &PERCENTILES).await {
+                Ok(history) => {
+                    // the same estimation as the one filling the fees of `cast send`
+                    let rewards = self
+                        .provider
+                        .fee_history(
+                            EIP1559_FEE_ESTIMATION_PAST_BLOCKS,
+                            number.into(),
+                            &[EIP1559_FEE_ESTIMATION_REWARD_PERCENTILE],
+                        )
+                        .await?
+                        .reward;
+                    let (max_fee, max_priority_fee) = eip1559_default_estimator(base_fee, rewards);
+                    return Ok(FeeHistorySummary {
+                        percentiles: PERCENTILES.to_vec(),
+                        blocks: blocks_fees(&history),
+                        suggestion: FeeSuggestion::Eip1559 {
+                            max_fee_per_gas: max_fee.to_alloy(),
+                            max_priority_fee_per_gas: max_priority_fee.to_alloy(),
+                        },
+                    })
+                }
+                Err(err) if err.as_error_response().is_some_and(is_method_not_found_error) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let first = number.saturating_sub(blocks - 1);
+        let fetched = futures::stream::iter(first..=number)
+            .map(|number| self.provider.get_block_with_txs(number))
+            .buffered(16)
+            .try_collect::<Vec<_>>()
+            .await?;
+        let mut all_prices = Vec::new();
+        let blocks = fetched
+            .into_iter()
+            .zip(first..)
+            .map(|(block, number)| {
+                let block = block.ok_or_else(|| eyre!("block {number} not found"))?;
+                let mut prices = block
+                    .transactions
+                    .iter()
+                    .filter_map(|tx| tx.gas_price)
+                    .map(|price| price.to_alloy())
+                    .collect::<Vec<_>>();
+                prices.sort();
+                all_prices.extend_from_slice(&prices);
+                Ok(BlockFees {
+                    number,
+                    base_fee_per_gas: None,
+                    gas_used_ratio: block.gas_used.low_u64() as f64 /
+                        block.gas_limit.low_u64().max(1) as f64,
+                    percentiles: PERCENTILES.iter().map(|p| percentile(&prices, *p)).collect(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // `cast send` uses the `eth_gasPrice` of the endpoint, which is only known for the latest
+        // block, the median gas price of the window stands in for older blocks
+        let gas_price = if at == BlockNumber::Latest {
+            self.provider.get_gas_price().await?.to_alloy()
+        } else {
+            all_prices.sort();
+            percentile(&all_prices, 50.0)
+        };
+        Ok(FeeHistorySummary {
+            percentiles: PERCENTILES.to_vec(),
+            blocks,
+            suggestion: FeeSuggestion::Legacy { gas_price },
+        })
+    }
+}
+
+fn blocks_fees(history: &FeeHistory) -> Vec<BlockFees> {
+    let oldest = history.oldest_block.as_u64();
+    history
+        .gas_used_ratio
+        .iter()
+        .enumerate()
+        .map(|(i, ratio)| BlockFees {
+            number: oldest + i as u64,
+            base_fee_per_gas: history.base_fee_per_gas.get(i).map(|fee| fee.to_alloy()),
+            gas_used_ratio: *ratio,
+            percentiles: history
+                .reward
+                .get(i)
+                .map(|rewards| rewards.iter().map(|reward| reward.to_alloy()).collect())
+                .unwrap_or_else(|| vec![U256::ZERO; PERCENTILES.len()]),
+        })
+        .collect()
+}
+
+/// Returns the nearest-rank percentile of the sorted values, zero if there are none.
+fn percentile(sorted: &[U256], percentile: f64) -> U256 {
+    if sorted.is_empty() {
+        return U256::ZERO
+    }
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl FeeHistorySummary {
+    /// Returns the table of the fees of the blocks in gwei, the trend of the base fee and the
+    /// suggested fees.
+    pub fn pretty(&self) -> String {
+        let eip1559 = matches!(self.suggestion, FeeSuggestion::Eip1559 { .. });
+        let mut table = comfy_table::Table::new();
+        table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        let mut header = vec!["Block".to_string()];
+        if eip1559 {
+            header.push("Base fee".to_string());
+        }
+        header.push("Gas used".to_string());
+        header.extend(self.percentiles.iter().map(|p| format!("p{p}")));
+        table.set_header(header);
+        for block in &self.blocks {
+            let mut row = vec![block.number.to_string()];
+            if eip1559 {
+                row.push(block.base_fee_per_gas.map(gwei).unwrap_or_default());
+            }
+            row.push(format!("{:.1}%", block.gas_used_ratio * 100.0));
+            row.extend(block.percentiles.iter().map(|fee| gwei(*fee)));
+            table.add_row(row);
+        }
+
+        let mut out = if eip1559 {
+            "Priority fees of the blocks by percentile, in gwei:\n"
+        } else {
+            "Gas prices of the transactions of the blocks by percentile, in gwei:\n"
+        }
+        .to_string();
+        let _ = write!(out, "{table}\n\n");
+        let base_fees = self.blocks.iter().filter_map(|block| block.base_fee_per_gas);
+        if let (Some(first), Some(last)) = (base_fees.clone().next(), base_fees.last()) {
+            let first_fee = first.saturating_to::<u128>() as f64;
+            let change = (last.saturating_to::<u128>() as f64 - first_fee) / first_fee.max(1.0);
+            let _ = writeln!(
+                out,
+                "Base fee: {} gwei -> {} gwei ({:+.1}%)",
+                gwei(first),
+                gwei(last),
+                change * 100.0
+            );
+        }
+        match self.suggestion {
+            FeeSuggestion::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } => {
+                let _ = writeln!(
+                    out,
+                    "Suggested maxFeePerGas: {} gwei ({max_fee_per_gas})",
+                    gwei(max_fee_per_gas)
+                );
+                let _ = write!(
+                    out,
+                    "Suggested maxPriorityFeePerGas: {} gwei ({max_priority_fee_per_gas})",
+                    gwei(max_priority_fee_per_gas)
+                );
+            }
+            FeeSuggestion::Legacy { gas_price } => {
+                let _ = write!(out, "Suggested gasPrice: {} gwei ({gas_price})", gwei(gas_price));
+            }
+        }
+        out
+    }
+}
+
+fn gwei(wei: U256) -> String {
+    format_units(wei, 9)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_compute_percentiles() {
+        let values = (1..=10u64).map(U256::from).collect::<Vec<_>>();
+        let percentiles = PERCENTILES.map(|p| percentile(&values, p).to::<u64>());
+        assert_eq!(percentiles, [1, 3, 5, 8, 9]);
+        assert_eq!(percentile(&values[..1], 90.0), U256::from(1));
+        assert_eq!(percentile(&[], 50.0), U256::ZERO);
+    }
+
+    #[test]
+    fn can_summarize_fee_history() {
+        let rewards = PERCENTILES.iter().map(|p| (*p as u64 * 100_000_000).into()).collect();
+        let history = FeeHistory {
+            base_fee_per_gas: vec![
+                10_000_000_000u64.into(),
+                12_500_000_000u64.into(),
+                11_000_000_000u64.into(),
+            ],
+            gas_used_ratio: vec![0.75, 0.25],
+            oldest_block: 100.into(),
+            reward: vec![rewards, vec![0.into(); PERCENTILES.len()]],
+        };
+        let (max_fee, max_priority_fee) =
+            eip1559_default_estimator(12_500_000_000u64.into(), history.reward.clone());
+        let summary = FeeHistorySummary {
+            percentiles: PERCENTILES.to_vec(),
+            blocks: blocks_fees(&history),
+            suggestion: FeeSuggestion::Eip1559 {
+                max_fee_per_gas: max_fee.to_alloy(),
+                max_priority_fee_per_gas: max_priority_fee.to_alloy(),
+            },
+        };
+        assert_eq!(
+            summary.pretty(),
+            "\
+Priority fees of the blocks by percentile, in gwei:
+| Block | Base fee | Gas used | p10 | p25 | p50 | p75 | p90 |
+|-------|----------|----------|-----|-----|-----|-----|-----|
+| 100   | 10       | 75.0%    | 1   | 2.5 | 5   | 7.5 | 9   |
+| 101   | 12.5     | 25.0%    | 0   | 0   | 0   | 0   | 0   |
+
+Base fee: 10 gwei -> 12.5 gwei (+25.0%)
+Suggested maxFeePerGas: 25 gwei (25000000000)
+Suggested maxPriorityFeePerGas: 3 gwei (3000000000)"
+        );
+        assert_eq!(
+            serde_json::to_value(&summary.suggestion).unwrap(),
+            serde_json::json!({
+                "maxFeePerGas": "0x5d21dba00",
+                "maxPriorityFeePerGas": "0xb2d05e00",
+            })
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! Solidity interfaces generated from ABIs, see `cast interface`.
+
+use alloy_json_abi::{InternalType, JsonAbi, Param, StateMutability};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+/// Generates the Solidity source of an interface with the functions, events and errors of the ABI.
+///
+/// The structs used by the items are reconstructed from the components of their tuple parameters,
+/// named after their `internalType`. Structs of the same name with different fields, e.g. declared
+/// in different contracts, are namespaced with their contract as `Contract_Struct`, and tuples
+/// without an `internalType` are named `Struct0`, `Struct1`, ... in order of appearance.
+///
+/// Enums and contracts can't be reconstructed from an ABI, they are replaced with their ABI types,
+/// `uint8` and `address`.
+pub fn abi_to_interface(abi: &JsonAbi, name: &str) -> String {
+    let mut structs = Structs::default();
+    for function in abi.functions() {
+        function.inputs.iter().chain(&function.outputs).for_each(|param| structs.collect(param));
+    }
+    for event in abi.events() {
+        for param in &event.inputs {
+            structs.collect_tuple(&param.ty, param.internal_type.as_ref(), &param.components);
+        }
+    }
+    for error in abi.errors() {
+        error.inputs.iter().for_each(|param| structs.collect(param));
+    }
+    structs.resolve_names();
+
+    let mut items = vec![];
+    for def in &structs.defs {
+        let mut item = format!("struct {} {{\n", def.sol_name);
+        for (i, component) in def.components.iter().enumerate() {
+            let name =
+                if component.name.is_empty() { format!("_{i}") } else { component.name.clone() };
+            writeln!(item, "    {} {name};", structs.sol_type(component)).unwrap();
+        }
+        item.push('}');
+        items.push(item);
+    }
+    for event in abi.events() {
+        let params = event
+            .inputs
+            .iter()
+            .map(|param| {
+                let mut decl =
+                    structs.sol_type_of(&param.ty, param.internal_type.as_ref(), &param.components);
+                if param.indexed {
+                    decl.push_str(" indexed");
+                }
+                push_name(&mut decl, &param.name);
+                decl
+            })
+            .collect::<Vec<_>>();
+        let anonymous = if event.anonymous { " anonymous" } else { "" };
+        items.push(format!("event {}({}){anonymous};", event.name, params.join(", ")));
+    }
+    for error in abi.errors() {
+        let params = structs.params(&error.inputs, None);
+        items.push(format!("error {}({params});", error.name));
+    }
+    if let Some(fallback) = &abi.fallback {
+        items.push(format!("fallback() external{};", mutability(fallback.state_mutability)));
+    }
+    if abi.receive.is_some() {
+        items.push("receive() external payable;".to_string());
+    }
+    for function in abi.functions() {
+        let mut item = format!(
+            "function {}({}) external{}",
+            function.name,
+            structs.params(&function.inputs, Some("calldata")),
+            mutability(function.state_mutability),
+        );
+        if !function.outputs.is_empty() {
+            write!(item, " returns ({})", structs.params(&function.outputs, Some("memory")))
+                .unwrap();
+        }
+        item.push(';');
+        items.push(item);
+    }
+
+    let mut source = format!("interface {name} {{\n");
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            source.push('\n');
+        }
+        for line in item.lines() {
+            writeln!(source, "    {line}").unwrap();
+        }
+    }
+    source.push_str("}\n");
+    source
+}
+
+/// A struct reconstructed from the components of tuple parameters.
+#[derive(Debug)]
+struct StructDef {
+    /// The contract and the name of the struct from the `internalType`
+    contract: Option<String>,
+    name: Option<String>,
+    components: Vec<Param>,
+    /// The name of the struct in the interface
+    sol_name: String,
+}
+
+#[derive(Debug, Default)]
+struct Structs {
+    /// The structs in order of appearance, nested structs first
+    defs: Vec<StructDef>,
+}
+
+impl Structs {
+    fn collect(&mut self, param: &Param) {
+        self.collect_tuple(&param.ty, param.internal_type.as_ref(), &param.components)
+    }
+
+    fn collect_tuple(
+        &mut self,
+        ty: &str,
+        internal_type: Option<&InternalType>,
+        components: &[Param],
+    ) {
+        if !ty.starts_with("tuple") {
+            return
+        }
+        components.iter().for_each(|component| self.collect(component));
+        let (contract, name) = struct_name(internal_type);
+        if self.find(name.as_deref(), components).is_none() {
+            self.defs.push(StructDef {
+                contract,
+                name,
+                components: components.to_vec(),
+                sol_name: String::new(),
+            });
+        }
+    }
+
+    /// Returns the struct with the name and components, structs of the same name and fields are
+    /// the same even if they're declared in different contracts.
+    fn find(&self, name: Option<&str>, components: &[Param]) -> Option<&StructDef> {
+        self.defs.iter().find(|def| def.name.as_deref() == name && def.components == components)
+    }
+
+    fn resolve_names(&mut self) {
+        let mut counts = BTreeMap::<&str, usize>::new();
+        for name in self.defs.iter().filter_map(|def| def.name.as_deref()) {
+            *counts.entry(name).or_default() += 1;
+        }
+
+        let mut used = BTreeSet::new();
+        let mut anonymous = 0..;
+        let names = self
+            .defs
+            .iter()
+            .map(|def| {
+                let sol_name = match (&def.contract, &def.name) {
+                    (Some(contract), Some(name)) if counts[name.as_str()] > 1 => {
+                        format!("{contract}_{name}")
+                    }
+                    (_, Some(name)) => name.clone(),
+                    (_, None) => format!("Struct{}", anonymous.next().unwrap()),
+                };
+                let mut unique = sol_name.clone();
+                for n in 1.. {
+                    if used.insert(unique.clone()) {
+                        break
+                    }
+                    unique = format!("{sol_name}_{n}");
+                }
+                unique
+            })
+            .collect::<Vec<_>>();
+        for (def, name) in self.defs.iter_mut().zip(names) {
+            def.sol_name = name;
+        }
+    }
+
+    fn sol_type(&self, param: &Param) -> String {
+        self.sol_type_of(&param.ty, param.internal_type.as_ref(), &param.components)
+    }
+
+    fn sol_type_of(
+        &self,
+        ty: &str,
+        internal_type: Option<&InternalType>,
+        components: &[Param],
+    ) -> String {
+        let Some(dims) = ty.strip_prefix("tuple") else { return ty.to_string() };
+        let (_, name) = struct_name(internal_type);
+        let def = self.find(name.as_deref(), components).expect("all structs are collected");
+        format!("{}{dims}", def.sol_name)
+    }
+
+    /// Formats the parameters, with the data location of the reference types if any.
+    fn params(&self, params: &[Param], location: Option<&str>) -> String {
+        params
+            .iter()
+            .map(|param| {
+                let mut decl = self.sol_type(param);
+                if let Some(location) = location.filter(|_| is_reference_type(&param.ty)) {
+                    write!(decl, " {location}").unwrap();
+                }
+                push_name(&mut decl, &param.name);
+                decl
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Returns the contract and the name of the struct of the `internalType`, without the array
+/// dimensions.
+fn struct_name(internal_type: Option<&InternalType>) -> (Option<String>, Option<String>) {
+    match internal_type {
+        Some(InternalType::Struct { contract, ty }) => {
+            let name = ty.split('[').next().unwrap_or(ty);
+            (contract.clone(), Some(name.to_string()))
+        }
+        _ => (None, None),
+    }
+}
+
+fn is_reference_type(ty: &str) -> bool {
+    ty.ends_with(']') || ty.starts_with("tuple") || ty == "bytes" || ty == "string"
+}
+
+fn push_name(decl: &mut String, name: &str) {
+    if !name.is_empty() {
+        decl.push(' ');
+        decl.push_str(name);
+    }
+}
+
+fn mutability(state_mutability: StateMutability) -> &'static str {
+    match state_mutability {
+        StateMutability::Pure => " pure",
+        StateMutability::View => " view",
+        StateMutability::NonPayable => "",
+        StateMutability::Payable => " payable",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interface(abi: &str) -> String {
+        abi_to_interface(&serde_json::from_str(abi).unwrap(), "Interface")
+    }
+
+    #[test]
+    fn can_reconstruct_nested_structs() {
+        let abi = r#"[
+            {
+                "type": "function",
+                "name": "submit",
+                "stateMutability": "payable",
+                "inputs": [
+                    {
+                        "name": "orders",
+                        "type": "tuple[]",
+                        "internalType": "struct Exchange.Order[]",
+                        "components": [
+                            { "name": "maker", "type": "address", "internalType": "address" },
+                            {
+                                "name": "asset",
+                                "type": "tuple",
+                                "internalType": "struct Exchange.Asset",
+                                "components": [
+                                    {
+                                        "name": "token",
+                                        "type": "address",
+                                        "internalType": "contract IERC20"
+                                    },
+                                    {
+                                        "name": "amount",
+                                        "type": "uint256",
+                                        "internalType": "uint256"
+                                    }
+                                ]
+                            },
+                            {
+                                "name": "side",
+                                "type": "uint8",
+                                "internalType": "enum Exchange.Side"
+                            }
+                        ]
+                    }
+                ],
+                "outputs": [
+                    {
+                        "name": "",
+                        "type": "tuple",
+                        "internalType": "struct Exchange.Asset",
+                        "components": [
+                            {
+                                "name": "token",
+                                "type": "address",
+                                "internalType": "contract IERC20"
+                            },
+                            { "name": "amount", "type": "uint256", "internalType": "uint256" }
+                        ]
+                    }
+                ]
+            },
+            {
+                "type": "event",
+                "name": "Submitted",
+                "anonymous": false,
+                "inputs": [
+                    {
+                        "name": "maker",
+                        "type": "address",
+                        "indexed": true,
+                        "internalType": "address"
+                    },
+                    {
+                        "name": "asset",
+                        "type": "tuple",
+                        "indexed": false,
+                        "internalType": "struct Exchange.Asset",
+                        "components": [
+                            {
+                                "name": "token",
+                                "type": "address",
+                                "internalType": "contract IERC20"
+                            },
+                            { "name": "amount", "type": "uint256", "internalType": "uint256" }
+                        ]
+                    }
+                ]
+            }
+        ]"#;
+        assert_eq!(
+            interface(abi),
+            "\
+interface Interface {
+    struct Asset {
+        address token;
+        uint256 amount;
+    }
+
+    struct Order {
+        address maker;
+        Asset asset;
+        uint8 side;
+    }
+
+    event Submitted(address indexed maker, Asset asset);
+
+    function submit(Order[] calldata orders) external payable returns (Asset memory);
+}
+"
+        );
+    }
+
+    #[test]
+    fn namespaces_duplicated_struct_names() {
+        let abi = r#"[
+            {
+                "type": "function",
+                "name": "a",
+                "stateMutability": "view",
+                "inputs": [
+                    {
+                        "name": "config",
+                        "type": "tuple",
+                        "internalType": "struct A.Config",
+                        "components": [
+                            {
+                                "name": "x",
+                                "type": "uint256",
+                                "internalType": "uint256"
+                            }
+                        ]
+                    }
+                ],
+                "outputs": []
+            },
+            {
+                "type": "function",
+                "name": "b",
+                "stateMutability": "view",
+                "inputs": [
+                    {
+                        "name": "config",
+                        "type": "tuple",
+                        "internalType": "struct B.Config",
+                        "components": [{ "name": "y", "type": "bytes", "internalType": "bytes" }]
+                    },
+                    {
+                        "name": "same",
+                        "type": "tuple",
+                        "internalType": "struct C.Config",
+                        "components": [
+                            {
+                                "name": "x",
+                                "type": "uint256",
+                                "internalType": "uint256"
+                            }
+                        ]
+                    }
+                ],
+                "outputs": []
+            },
+            {
+                "type": "error",
+                "name": "Invalid",
+                "inputs": [
+                    {
+                        "name": "",
+                        "type": "tuple",
+                        "components": [{ "name": "", "type": "string" }]
+                    }
+                ]
+            }
+        ]"#;
+        assert_eq!(
+            interface(abi),
+            "\
+interface Interface {
+    struct A_Config {
+        uint256 x;
+    }
+
+    struct B_Config {
+        bytes y;
+    }
+
+    struct Struct0 {
+        string _0;
+    }
+
+    error Invalid(Struct0);
+
+    function a(A_Config calldata config) external view;
+
+    function b(B_Config calldata config, A_Config calldata same) external view;
+}
+"
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use alloy_dyn_abi::{DynSolType, DynSolValue, FunctionExt};
-use alloy_json_abi::ContractObject;
+use alloy_json_abi::{ContractObject, Function, JsonAbi};
 use alloy_primitives::{
     utils::{keccak256, ParseUnits, Unit},
     Address, I256, U256,
@@ -8,20 +8,24 @@ use alloy_rlp::Decodable;
 use base::{Base, NumberWithBase, ToBase};
 use chrono::NaiveDateTime;
 use ethers_core::{
-    types::{transaction::eip2718::TypedTransaction, *},
+    types::{
+        transaction::{eip2718::TypedTransaction, eip2930::AccessListWithGasUsed},
+        *,
+    },
     utils::rlp,
 };
 use ethers_providers::{Middleware, PendingTransaction, PubsubClient};
 use evm_disassembler::{disassemble_bytes, disassemble_str, format_operations};
 use eyre::{Context, ContextCompat, Result};
-use foundry_block_explorers::Client;
 use foundry_common::{
     abi::{encode_function_args, get_func},
     fmt::*,
     types::{ToAlloy, ToEthers},
-    TransactionReceiptWithRevertReason,
 };
-use foundry_config::Chain;
+use foundry_config::{
+    chains::{chain_registry, etherscan_client},
+    Chain,
+};
 use futures::{future::Either, FutureExt, StreamExt};
 use rayon::prelude::*;
 use std::{
@@ -42,10 +46,31 @@ pub use rusoto_kms::KmsClient;
 pub use tx::TxBuilder;
 
 pub mod base;
+pub mod blob;
+pub mod block;
+pub mod calldata;
+pub mod consensus;
+pub mod creation;
+pub mod dispatcher;
+pub mod ens;
 pub mod errors;
+pub mod gas;
+pub mod interface;
+pub mod logs;
+pub mod multicall;
+pub mod overrides;
+pub mod packed;
+pub mod pending;
+pub mod proof;
+pub mod proxy;
+pub mod raw_tx;
+pub mod receipt;
 mod rlp_converter;
+pub mod slots;
 mod tx;
+pub mod txpool;
 
+use overrides::StateOverrides;
 use rlp_converter::Item;
 
 // TODO: CastContract with common contract initializers? Same for CastProviders?
@@ -109,38 +134,40 @@ where
         let (tx, func) = builder_output;
         let res = self.provider.call(&tx, block).await?;
 
-        let mut decoded = vec![];
-
-        if let Some(func) = func {
-            // decode args into tokens
-            decoded = match func.abi_decode_output(res.as_ref(), false) {
-                Ok(decoded) => decoded,
-                Err(err) => {
-                    // ensure the address is a contract
-                    if res.is_empty() {
-                        // check that the recipient is a contract that can be called
-                        if let Some(NameOrAddress::Address(addr)) = tx.to() {
-                            if let Ok(code) = self.provider.get_code(*addr, block).await {
-                                if code.is_empty() {
-                                    eyre::bail!("contract {addr:?} does not have any code")
-                                }
+        match format_call_output(func.as_ref(), &res) {
+            Ok(output) => Ok(output),
+            Err(err) => {
+                // ensure the address is a contract
+                if res.is_empty() {
+                    // check that the recipient is a contract that can be called
+                    if let Some(NameOrAddress::Address(addr)) = tx.to() {
+                        if let Ok(code) = self.provider.get_code(*addr, block).await {
+                            if code.is_empty() {
+                                eyre::bail!("contract {addr:?} does not have any code")
                             }
                         }
                     }
-                    return Err(err).wrap_err(
-                        "could not decode output; did you specify the wrong function return data type?"
-                    );
                 }
-            };
+                Err(err)
+            }
         }
+    }
 
-        // handle case when return type is not specified
-        Ok(if decoded.is_empty() {
-            format!("{res}\n")
-        } else {
-            // seth compatible user-friendly return type conversions
-            decoded.iter().map(format_token).collect::<Vec<_>>().join("\n")
-        })
+    /// Makes a read-only call with the state overrides, the `stateOverride` parameter of geth's
+    /// `eth_call`.
+    ///
+    /// Fails if the endpoint doesn't support state overrides, see
+    /// [`overrides::is_unsupported_error`].
+    pub async fn call_with_overrides(
+        &self,
+        builder_output: TxBuilderOutput,
+        block: Option<BlockId>,
+        overrides: &StateOverrides,
+    ) -> Result<String> {
+        let (tx, func) = builder_output;
+        let block = block.unwrap_or_else(|| BlockNumber::Latest.into());
+        let res: Bytes = self.provider.request("eth_call", (&tx, block, overrides)).await?;
+        format_call_output(func.as_ref(), &res)
     }
 
     /// Generates an access list for the specified transaction
@@ -175,25 +202,17 @@ where
         to_json: bool,
     ) -> Result<String> {
         let (tx, _) = builder_output;
-        let access_list = self.provider.create_access_list(tx, block).await?;
-        let res = if to_json {
-            serde_json::to_string(&access_list)?
-        } else {
-            let mut s =
-                vec![format!("gas used: {}", access_list.gas_used), "access list:".to_string()];
-            for al in access_list.access_list.0 {
-                s.push(format!("- address: {}", &al.address.to_alloy().to_checksum(None)));
-                if !al.storage_keys.is_empty() {
-                    s.push("  keys:".to_string());
-                    for key in al.storage_keys {
-                        s.push(format!("    {key:?}"));
-                    }
-                }
-            }
-            s.join("\n")
-        };
+        let access_list = self.create_access_list(tx, block).await?;
+        format_access_list(&access_list, to_json)
+    }
 
-        Ok(res)
+    /// Generates an access list for the transaction with `eth_createAccessList`.
+    pub async fn create_access_list(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<AccessListWithGasUsed> {
+        Ok(self.provider.create_access_list(tx, block).await?)
     }
 
     pub async fn balance<T: Into<NameOrAddress> + Send + Sync>(
@@ -291,19 +310,41 @@ where
     /// builder.set_value(value).set_args(sig, args).await?;
     /// let builder_output = builder.peek();
     /// let cast = Cast::new(&provider);
-    /// let data = cast.estimate(builder_output).await?;
+    /// let data = cast.estimate(builder_output, None).await?;
     /// println!("{}", data);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn estimate(&self, builder_output: TxBuilderPeekOutput<'_>) -> Result<U256> {
+    pub async fn estimate(
+        &self,
+        builder_output: TxBuilderPeekOutput<'_>,
+        block: Option<BlockId>,
+    ) -> Result<U256> {
         let (tx, _) = builder_output;
 
-        let res = self.provider.estimate_gas(tx, None).await?;
+        let res = self.provider.estimate_gas(tx, block).await?;
 
         Ok::<_, eyre::Error>(res.to_alloy())
     }
 
+    /// Estimates the gas of the transaction with the state overrides, the `stateOverride`
+    /// parameter of geth's `eth_estimateGas`.
+    ///
+    /// Fails if the endpoint doesn't support state overrides, see
+    /// [`overrides::is_unsupported_error`].
+    pub async fn estimate_with_overrides(
+        &self,
+        builder_output: TxBuilderPeekOutput<'_>,
+        block: Option<BlockId>,
+        overrides: &StateOverrides,
+    ) -> Result<U256> {
+        let (tx, _) = builder_output;
+        let block = block.unwrap_or_else(|| BlockNumber::Latest.into());
+        let res: ethers_core::types::U256 =
+            self.provider.request("eth_estimateGas", (tx, block, overrides)).await?;
+        Ok(res.to_alloy())
+    }
+
     /// # Example
     ///
     /// ```ignore
@@ -455,7 +496,10 @@ where
                     _ => "avalanche",
                 }
             }
-            _ => "unknown",
+            // otherwise the chain of the chain id in the registry, which can't tell forks apart
+            _ => chain_registry()
+                .get(self.chain_id().await?.to())
+                .map_or("unknown", |chain| chain.name.as_str()),
         })
     }
 
@@ -657,15 +701,16 @@ where
         to_json: bool,
     ) -> Result<String> {
         let tx_hash = H256::from_str(&tx_hash).wrap_err("invalid tx hash")?;
+        if raw {
+            return Ok(self.raw_transaction(tx_hash).await?.to_string())
+        }
         let tx = self
             .provider
             .get_transaction(tx_hash)
             .await?
             .ok_or_else(|| eyre::eyre!("tx not found: {:?}", tx_hash))?;
 
-        Ok(if raw {
-            format!("0x{}", hex::encode(tx.rlp()))
-        } else if let Some(field) = field {
+        Ok(if let Some(field) = field {
             get_pretty_tx_attr(&tx, field.as_str())
                 .ok_or_else(|| eyre::eyre!("invalid tx field: {}", field.to_string()))?
         } else if to_json {
@@ -700,30 +745,7 @@ where
         to_json: bool,
     ) -> Result<String> {
         let tx_hash = H256::from_str(&tx_hash).wrap_err("invalid tx hash")?;
-
-        let mut receipt: TransactionReceiptWithRevertReason =
-            match self.provider.get_transaction_receipt(tx_hash).await? {
-                Some(r) => r,
-                None => {
-                    // if the async flag is provided, immediately exit if no tx is found, otherwise
-                    // try to poll for it
-                    if cast_async {
-                        eyre::bail!("tx not found: {:?}", tx_hash)
-                    } else {
-                        let tx = PendingTransaction::new(tx_hash, self.provider.provider());
-                        tx.confirmations(confs).await?.ok_or_else(|| {
-                            eyre::eyre!(
-                                "tx not found, might have been dropped from mempool: {:?}",
-                                tx_hash
-                            )
-                        })?
-                    }
-                }
-            }
-            .into();
-
-        // Allow to fail silently
-        let _ = receipt.update_revert_reason(&self.provider).await;
+        let receipt = self.transaction_receipt(tx_hash, confs, cast_async).await?;
 
         Ok(if let Some(ref field) = field {
             get_pretty_tx_receipt_attr(&receipt, field)
@@ -953,6 +975,111 @@ where
     }
 }
 
+/// Formats the output of a call to `func`, or as hex if the function isn't known.
+pub fn format_call_output(func: Option<&Function>, res: &[u8]) -> Result<String> {
+    let mut decoded = vec![];
+    if let Some(func) = func {
+        // decode args into tokens
+        decoded = func.abi_decode_output(res, false).wrap_err(
+            "could not decode output; did you specify the wrong function return data type?",
+        )?;
+    }
+
+    // handle case when return type is not specified
+    Ok(if decoded.is_empty() {
+        format!("{}\n", Bytes::from(res.to_vec()))
+    } else {
+        // seth compatible user-friendly return type conversions
+        decoded.iter().map(format_token).collect::<Vec<_>>().join("\n")
+    })
+}
+
+/// Formats an access list as JSON, or as a list of the accessed storage keys per address.
+pub fn format_access_list(access_list: &AccessListWithGasUsed, to_json: bool) -> Result<String> {
+    if to_json {
+        return Ok(serde_json::to_string(access_list)?)
+    }
+    let mut s = vec![format!("gas used: {}", access_list.gas_used), "access list:".to_string()];
+    for al in &access_list.access_list.0 {
+        s.push(format!("- address: {}", &al.address.to_alloy().to_checksum(None)));
+        if !al.storage_keys.is_empty() {
+            s.push("  keys:".to_string());
+            for key in &al.storage_keys {
+                s.push(format!("    {key:?}"));
+            }
+        }
+    }
+    Ok(s.join("\n"))
+}
+
+/// Parses an access list from JSON, either the list itself or the output of
+/// `cast access-list --json`.
+pub fn parse_access_list(s: &str) -> Result<AccessList> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Output {
+        access_list: AccessList,
+    }
+
+    serde_json::from_str::<AccessList>(s)
+        .or_else(|_| serde_json::from_str::<Output>(s).map(|output| output.access_list))
+        .wrap_err(
+            "expected an access list as JSON, e.g. `[{\"address\":\"0x...\",\"storageKeys\":[]}]`",
+        )
+}
+
+/// Reads an access list given as JSON, as the path of a JSON file, or as `-` to read it from
+/// stdin, see [`parse_access_list`].
+pub fn read_access_list(s: &str) -> Result<AccessList> {
+    if s == "-" {
+        let mut json = String::new();
+        io::Read::read_to_string(&mut io::stdin(), &mut json)
+            .wrap_err("failed to read the access list from stdin")?;
+        return parse_access_list(&json)
+    }
+    if s.trim_start().starts_with(['[', '{']) {
+        return parse_access_list(s)
+    }
+    let json = foundry_common::fs::read_to_string(s)?;
+    parse_access_list(&json).wrap_err_with(|| format!("invalid access list in {s}"))
+}
+
+/// The gas estimates of a transaction without and with its access list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccessListComparison {
+    pub gas_without: U256,
+    pub gas_with: U256,
+}
+
+impl AccessListComparison {
+    /// Returns the gas saved by the access list, negative if it costs more than it saves.
+    pub fn savings(&self) -> I256 {
+        I256::from_raw(self.gas_without) - I256::from_raw(self.gas_with)
+    }
+}
+
+/// Formats the access list with the gas estimates of the transaction without and with it.
+pub fn format_access_list_comparison(
+    access_list: &AccessListWithGasUsed,
+    comparison: &AccessListComparison,
+    to_json: bool,
+) -> Result<String> {
+    if to_json {
+        let mut value = serde_json::to_value(access_list)?;
+        value["gasWithoutAccessList"] = serde_json::to_value(comparison.gas_without)?;
+        value["gasWithAccessList"] = serde_json::to_value(comparison.gas_with)?;
+        value["savings"] = comparison.savings().to_string().into();
+        return Ok(value.to_string())
+    }
+    Ok(format!(
+        "{}\ngas without access list: {}\ngas with access list: {}\nsavings: {}",
+        format_access_list(access_list, false)?,
+        comparison.gas_without,
+        comparison.gas_with,
+        comparison.savings(),
+    ))
+}
+
 pub struct InterfaceSource {
     pub name: String,
     pub json_abi: String,
@@ -1549,6 +1676,47 @@ impl SimpleCast {
         Ok(format!("0x{encoded}"))
     }
 
+    /// Packs the arguments like `abi.encodePacked`, see [`packed::encode_packed`].
+    ///
+    /// The types are the ones of a function signature or a list of types.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// assert_eq!(
+    ///     "0xffff42000348656c6c6f2c20776f726c6421",
+    ///     Cast::abi_encode_packed(
+    ///         "int16,bytes1,uint16,string",
+    ///         &["-1", "0x42", "3", "Hello, world!"]
+    ///     )?
+    /// );
+    /// # Ok::<_, eyre::Report>(())
+    /// ```
+    pub fn abi_encode_packed(types: &str, args: &[impl AsRef<str>]) -> Result<String> {
+        let types = packed::parse_packed_types(types)?;
+        Ok(hex::encode_prefixed(packed::encode_packed(&types, args)?))
+    }
+
+    /// Splits data packed like `abi.encodePacked` into the values of the types, see
+    /// [`packed::decode_packed`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// let values = Cast::abi_decode_packed("bool,uint16,string", "0x01002a666f6f")?;
+    /// assert_eq!(values[1].as_uint().unwrap().0.to_string(), "42");
+    /// assert_eq!(values[2].as_str(), Some("foo"));
+    /// # Ok::<_, eyre::Report>(())
+    /// ```
+    pub fn abi_decode_packed(types: &str, data: &str) -> Result<Vec<DynSolValue>> {
+        let types = packed::parse_packed_types(types)?;
+        packed::decode_packed(&types, &hex::decode(data)?)
+    }
+
     /// Performs ABI encoding to produce the hexadecimal calldata with the given arguments.
     ///
     /// # Example
@@ -1585,13 +1753,18 @@ impl SimpleCast {
         let (contract_abis, contract_names) = match address_or_path {
             AbiPath::Local { path, name } => {
                 let file = std::fs::read_to_string(&path).wrap_err("unable to read abi file")?;
-                let obj: ContractObject = serde_json::from_str(&file)?;
-                let abi =
-                    obj.abi.ok_or_else(|| eyre::eyre!("could not find ABI in file {path}"))?;
+                // either a raw ABI or an artifact containing one
+                let abi = match serde_json::from_str::<JsonAbi>(&file) {
+                    Ok(abi) => abi,
+                    Err(_) => {
+                        let obj: ContractObject = serde_json::from_str(&file)?;
+                        obj.abi.ok_or_else(|| eyre::eyre!("could not find ABI in file {path}"))?
+                    }
+                };
                 (vec![abi], vec![name.unwrap_or_else(|| "Interface".to_owned())])
             }
             AbiPath::Etherscan { address, chain, api_key } => {
-                let client = Client::new(chain, api_key)?;
+                let client = etherscan_client(chain, api_key)?;
                 let source = client.contract_source_code(address).await?;
                 let names = source
                     .items
@@ -1608,7 +1781,8 @@ impl SimpleCast {
             .iter()
             .zip(contract_names)
             .map(|(contract_abi, name)| {
-                let source = foundry_cli::utils::abi_to_solidity(contract_abi, &name)?;
+                let source = interface::abi_to_interface(contract_abi, &name);
+                let source = foundry_cli::utils::format_solidity(&source)?;
                 Ok(InterfaceSource {
                     name,
                     json_abi: serde_json::to_string_pretty(contract_abi)?,
@@ -1621,7 +1795,8 @@ impl SimpleCast {
     /// Prints the slot number for the specified mapping type and input data
     /// Uses abi_encode to pad the data to 32 bytes.
     /// For value types v, slot number of v is keccak256(concat(h(v) , p)) where h is the padding
-    /// function and p is slot number of the mapping.
+    /// function and p is slot number of the mapping. Strings and bytes are hashed without
+    /// padding, see [`slots::mapping_slot`].
     ///
     /// # Example
     ///
@@ -1639,10 +1814,9 @@ impl SimpleCast {
     /// # Ok::<_, eyre::Report>(())
     /// ```
     pub fn index(from_type: &str, from_value: &str, slot_number: &str) -> Result<String> {
-        let sig = format!("x({from_type},uint256)");
-        let encoded = Self::abi_encode(&sig, &[from_value, slot_number])?;
-        let location: String = Self::keccak(&encoded)?;
-        Ok(location)
+        let slot = U256::from_str(slot_number)
+            .map_err(|err| eyre::eyre!("Could not parse slot number: {err}"))?;
+        Ok(slots::mapping_slot(from_type, from_value, slot.into())?.to_string())
     }
 
     /// Converts ENS names to their namehash representation
@@ -1807,7 +1981,7 @@ impl SimpleCast {
         contract_address: String,
         etherscan_api_key: String,
     ) -> Result<String> {
-        let client = Client::new(chain, etherscan_api_key)?;
+        let client = etherscan_client(chain, etherscan_api_key)?;
         let metadata = client.contract_source_code(contract_address.parse()?).await?;
         Ok(metadata.source_code())
     }
@@ -1838,7 +2012,7 @@ impl SimpleCast {
         etherscan_api_key: String,
         output_directory: PathBuf,
     ) -> eyre::Result<()> {
-        let client = Client::new(chain, etherscan_api_key)?;
+        let client = etherscan_client(chain, etherscan_api_key)?;
         let meta = client.contract_source_code(contract_address.parse()?).await?;
         let source_tree = meta.source_tree();
         source_tree.write_to(&output_directory)?;
@@ -2094,4 +2268,21 @@ mod tests {
             r#"["0x2b5df5f0757397573e8ff34a8b987b21680357de1f6c8d10273aa528a851eaca","0x","0x","0x2838ac1d2d2721ba883169179b48480b2ba4f43d70fcf806956746bd9e83f903","0x","0xe46fff283b0ab96a32a7cc375cecc3ed7b6303a43d64e0a12eceb0bc6bd87549","0x","0x1d818c1c414c665a9c9a0e0c0ef1ef87cacb380b8c1f6223cb2a68a4b2d023f5","0x","0x","0x","0x236e8f61ecde6abfebc6c529441f782f62469d8a2cc47b7aace2c136bd3b1ff0","0x","0x","0x","0x","0x"]"#
         )
     }
+
+    #[test]
+    fn read_access_list_from_json_or_file() {
+        let json = r#"[{"address":"0x0000000000000000000000000000000000000001","storageKeys":["0x0000000000000000000000000000000000000000000000000000000000000002"]}]"#;
+        let access_list = read_access_list(json).unwrap();
+        assert_eq!(access_list.0.len(), 1);
+        assert_eq!(access_list.0[0].storage_keys.len(), 1);
+
+        let output = format!(r#"{{"accessList":{json},"gasUsed":"0x5208"}}"#);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("access-list.json");
+        std::fs::write(&path, output).unwrap();
+        assert_eq!(read_access_list(path.to_str().unwrap()).unwrap(), access_list);
+
+        std::fs::write(&path, r#"[{"address":"0x01"}]"#).unwrap();
+        assert!(read_access_list(path.to_str().unwrap()).is_err());
+    }
 }
@@ -0,0 +1,430 @@
+//! Following the logs of a filter, see `cast logs --follow`.
+//!
+//! The logs of the past blocks are back-filled first, then the new logs are streamed with a
+//! subscription if the endpoint supports it, or by polling the endpoint otherwise.
+
+use crate::Cast;
+use alloy_dyn_abi::{DynSolValue, EventExt};
+use alloy_json_abi::Event;
+use alloy_primitives::LogData;
+use ethers_core::types::{BlockNumber, Filter, FilterBlockOption, Log};
+use ethers_providers::{Middleware, PubsubClient};
+use eyre::Result;
+use foundry_common::{
+    abi::get_indexed_event,
+    fmt::{format_token, UIfmt},
+    types::ToAlloy,
+};
+use futures::StreamExt;
+use serde::Serialize;
+use std::{io, time::Duration};
+use tokio::signal::ctrl_c;
+
+/// The maximum number of blocks queried at once when back-filling logs.
+const BACKFILL_BLOCK_RANGE: u64 = 2_000;
+
+/// A log decoded with the event matching its first topic.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DecodedLog {
+    /// The signature of the event
+    pub event: String,
+    pub args: Vec<String>,
+    /// The names of the parameters of the event, empty for unnamed ones
+    pub names: Vec<String>,
+}
+
+impl DecodedLog {
+    /// Returns the arguments prefixed with the names of their parameters, if any.
+    pub fn pretty_args(&self) -> String {
+        let args = self.args.iter().zip(&self.names).map(|(arg, name)| {
+            if name.is_empty() {
+                arg.clone()
+            } else {
+                format!("{name}: {arg}")
+            }
+        });
+        args.collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Decodes logs with a set of events.
+#[derive(Clone, Debug, Default)]
+pub struct LogDecoder {
+    events: Vec<Event>,
+}
+
+impl LogDecoder {
+    pub fn new(events: impl IntoIterator<Item = Event>) -> Self {
+        Self { events: events.into_iter().collect() }
+    }
+
+    /// Returns true if the decoder has no events.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Decodes the log with the first event matching it, if any.
+    pub fn decode(&self, log: &Log) -> Option<DecodedLog> {
+        let topics = log.topics.iter().map(|topic| topic.to_alloy()).collect::<Vec<_>>();
+        let log = LogData::new_unchecked(topics, log.data.clone().to_alloy());
+        self.events
+            .iter()
+            .filter(|event| event.anonymous || log.topics().first() == Some(&event.selector()))
+            .find_map(|event| {
+                // events parsed from signatures without `indexed` keywords are matched with the
+                // number of topics of the log
+                let event = if event.inputs.iter().any(|input| input.indexed) {
+                    event.clone()
+                } else {
+                    get_indexed_event(event.clone(), &log)
+                };
+                let decoded = event.decode_log(&log, false).ok()?;
+                let (mut indexed, mut body) =
+                    (decoded.indexed.into_iter(), decoded.body.into_iter());
+                let args = event
+                    .inputs
+                    .iter()
+                    .map(|input| if input.indexed { indexed.next() } else { body.next() })
+                    .collect::<Option<Vec<DynSolValue>>>()?;
+                Some(DecodedLog {
+                    event: event.signature(),
+                    args: args.iter().map(format_token).collect(),
+                    names: event.inputs.iter().map(|input| input.name.clone()).collect(),
+                })
+            })
+    }
+}
+
+/// Tracks the logs which were already printed, to back-fill the logs of the blocks missed while
+/// following without printing a log twice.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogCursor {
+    /// The block number and log index of the first log which wasn't printed yet
+    next_log: (u64, u64),
+    /// The first block whose logs weren't all fetched yet
+    next_block: u64,
+}
+
+impl LogCursor {
+    /// Creates a cursor which starts at `block`.
+    pub fn new(block: u64) -> Self {
+        Self { next_log: (block, 0), next_block: block }
+    }
+
+    /// Returns true if the log should be printed, i.e. it comes after the last printed log.
+    ///
+    /// Logs removed by a reorg rewind the cursor, so the logs of the new chain are printed.
+    pub fn accept(&mut self, log: &Log) -> bool {
+        let (Some(block), Some(index)) = (log.block_number, log.log_index) else {
+            // pending logs have no position
+            return true
+        };
+        let position = (block.as_u64(), index.as_u64());
+        if log.removed == Some(true) {
+            self.next_log = self.next_log.min(position);
+            self.next_block = self.next_block.min(position.0);
+            return true
+        }
+        if position < self.next_log {
+            return false
+        }
+        self.next_log = (position.0, position.1 + 1);
+        true
+    }
+
+    /// Marks the logs of all the blocks up to `block` as fetched.
+    pub fn synced(&mut self, block: u64) {
+        self.next_block = self.next_block.max(block + 1);
+    }
+
+    /// Returns the first block to fetch the logs of, to not miss any log.
+    pub fn resume_block(&self) -> u64 {
+        // the logs of the block of the last printed log may not have been all received
+        self.next_block.max(self.next_log.0)
+    }
+}
+
+/// Prints the logs which weren't printed yet.
+struct LogPrinter<'a> {
+    cursor: LogCursor,
+    decoder: &'a LogDecoder,
+    output: &'a mut dyn io::Write,
+    to_json: bool,
+}
+
+impl LogPrinter<'_> {
+    fn print(&mut self, log: &Log) -> Result<()> {
+        if !self.cursor.accept(log) {
+            return Ok(())
+        }
+        let decoded = self.decoder.decode(log);
+        if self.to_json {
+            let mut value = serde_json::to_value(log)?;
+            if let Some(decoded) = decoded {
+                value["decoded"] = serde_json::to_value(decoded)?;
+            }
+            // one log per line
+            writeln!(self.output, "{value}")?;
+        } else {
+            let mut pretty = log.pretty();
+            if let Some(decoded) = decoded {
+                pretty.push_str(&format!(
+                    "\nevent: {}\nargs: [{}]",
+                    decoded.event,
+                    decoded.pretty_args()
+                ));
+            }
+            let pretty = pretty
+                .replacen('\n', "- ", 1) // Remove empty first line
+                .replace('\n', "\n  "); // Indent
+            writeln!(self.output, "{pretty}")?;
+        }
+        self.output.flush()?;
+        Ok(())
+    }
+}
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Prints the logs matching the filter as they are included, until interrupted.
+    ///
+    /// The logs of the blocks since the `from_block` of the filter are printed first. The new logs
+    /// are then streamed with `eth_subscribe` if the endpoint supports it, otherwise the endpoint
+    /// is polled every `poll_interval`. Subscriptions which end, e.g. because the connection was
+    /// lost, are renewed and the logs of the blocks missed in the meantime are back-filled.
+    ///
+    /// With `to_json`, the logs are printed as JSON lines.
+    pub async fn follow_logs(
+        &self,
+        filter: Filter,
+        decoder: &LogDecoder,
+        output: &mut dyn io::Write,
+        to_json: bool,
+        poll_interval: Duration,
+    ) -> Result<()>
+    where
+        <M as Middleware>::Provider: PubsubClient,
+    {
+        let head = self.provider.get_block_number().await?.as_u64();
+        let start = match filter.block_option {
+            FilterBlockOption::Range { from_block: Some(BlockNumber::Number(block)), .. } => {
+                block.as_u64()
+            }
+            FilterBlockOption::Range { from_block: Some(BlockNumber::Earliest), .. } => 0,
+            // only the new logs
+            _ => head + 1,
+        };
+        let mut printer = LogPrinter { cursor: LogCursor::new(start), decoder, output, to_json };
+
+        // the subscription only filters on the addresses and topics
+        let mut live_filter = filter.clone();
+        live_filter.block_option = FilterBlockOption::Range { from_block: None, to_block: None };
+
+        self.backfill_logs(&filter, &mut printer, head).await?;
+
+        let mut subscribed = false;
+        loop {
+            let mut stream = match self.provider.subscribe_logs(&live_filter).await {
+                Ok(stream) => stream,
+                Err(err) if !subscribed => {
+                    tracing::debug!(%err, "failed to subscribe to logs, polling instead");
+                    return self.poll_logs(&filter, &mut printer, poll_interval).await
+                }
+                Err(err) => {
+                    eprintln!("Warning: failed to resubscribe to logs: {err}");
+                    tokio::select! {
+                        _ = tokio::time::sleep(poll_interval) => continue,
+                        _ = ctrl_c() => return Ok(()),
+                    }
+                }
+            };
+
+            // the blocks mined before the subscription started
+            let resume_block = printer.cursor.resume_block();
+            let caught_up = match self.provider.get_block_number().await {
+                Ok(head) => self.backfill_logs(&filter, &mut printer, head.as_u64()).await,
+                Err(err) => Err(err.into()),
+            };
+            if let Err(err) = caught_up {
+                eprintln!(
+                    "Warning: failed to back-fill the logs since block {resume_block}, \
+                     some logs may be missing: {err}"
+                );
+            }
+            subscribed = true;
+
+            loop {
+                tokio::select! {
+                    log = stream.next() => match log {
+                        Some(log) => printer.print(&log)?,
+                        None => break,
+                    },
+                    _ = ctrl_c() => return Ok(()),
+                }
+            }
+            eprintln!("Warning: the log subscription ended, resubscribing");
+        }
+    }
+
+    /// Polls the logs of the new blocks every `poll_interval`, until interrupted.
+    async fn poll_logs(
+        &self,
+        filter: &Filter,
+        printer: &mut LogPrinter<'_>,
+        poll_interval: Duration,
+    ) -> Result<()> {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(poll_interval) => {}
+                _ = ctrl_c() => return Ok(()),
+            }
+            // failed requests are retried from the same block on the next poll
+            let polled = match self.provider.get_block_number().await {
+                Ok(head) => self.backfill_logs(filter, printer, head.as_u64()).await,
+                Err(err) => Err(err.into()),
+            };
+            if let Err(err) = polled {
+                eprintln!("Warning: failed to poll logs: {err}, retrying in {poll_interval:?}");
+            }
+        }
+    }
+
+    /// Prints the logs of the blocks from the cursor up to `to_block`.
+    async fn backfill_logs(
+        &self,
+        filter: &Filter,
+        printer: &mut LogPrinter<'_>,
+        to_block: u64,
+    ) -> Result<()> {
+        let mut from_block = printer.cursor.resume_block();
+        while from_block <= to_block {
+            let range_end = to_block.min(from_block + BACKFILL_BLOCK_RANGE - 1);
+            let range = filter.clone().from_block(from_block).to_block(range_end);
+            for log in self.provider.get_logs(&range).await? {
+                printer.print(&log)?;
+            }
+            printer.cursor.synced(range_end);
+            from_block = range_end + 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256, U256};
+    use ethers_core::types::{TransactionRequest, H160, U64};
+    use foundry_common::{
+        provider::ethers::{try_get_http_provider, RetryProvider},
+        types::ToEthers,
+    };
+
+    fn log(block: u64, index: u64) -> Log {
+        Log {
+            block_number: Some(U64::from(block)),
+            log_index: Some(index.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Deploys a contract whose init code emits a `LOG0` with the word 42.
+    async fn emit_log(provider: &RetryProvider, from: H160) {
+        let init_code = vec![0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xa0, 0x00];
+        let tx = TransactionRequest::new().from(from).data(init_code);
+        provider.send_transaction(tx, None).await.unwrap().await.unwrap();
+    }
+
+    #[test]
+    fn cursor_skips_printed_logs() {
+        let mut cursor = LogCursor::new(5);
+        assert_eq!(cursor.resume_block(), 5);
+
+        assert!(cursor.accept(&log(5, 0)));
+        assert!(cursor.accept(&log(5, 1)));
+        cursor.synced(6);
+        assert!(cursor.accept(&log(7, 0)));
+        assert_eq!(cursor.resume_block(), 7);
+
+        // the same logs received from a back-fill
+        assert!(!cursor.accept(&log(5, 1)));
+        assert!(!cursor.accept(&log(7, 0)));
+        assert!(cursor.accept(&log(7, 1)));
+        assert!(cursor.accept(&Log::default()));
+    }
+
+    #[test]
+    fn cursor_rewinds_on_removed_logs() {
+        let mut cursor = LogCursor::new(0);
+        assert!(cursor.accept(&log(3, 0)));
+        assert!(cursor.accept(&log(4, 0)));
+        cursor.synced(4);
+
+        let removed = Log { removed: Some(true), ..log(4, 0) };
+        assert!(cursor.accept(&removed));
+        assert_eq!(cursor.resume_block(), 4);
+
+        // the log of the new block 4
+        assert!(cursor.accept(&log(4, 0)));
+        assert!(!cursor.accept(&log(3, 0)));
+    }
+
+    #[test]
+    fn can_decode_logs() {
+        let decoder =
+            LogDecoder::new([Event::parse("event Transfer(address,address,uint256)").unwrap()]);
+        let from = Address::repeat_byte(1);
+        let to = Address::repeat_byte(2);
+        let transfer = Log {
+            topics: vec![
+                decoder.events[0].selector().to_ethers(),
+                from.into_word().to_ethers(),
+                to.into_word().to_ethers(),
+            ],
+            data: B256::from(U256::from(100)).0.to_vec().into(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            decoder.decode(&transfer),
+            Some(DecodedLog {
+                event: "Transfer(address,address,uint256)".to_string(),
+                args: vec![from.to_checksum(None), to.to_checksum(None), "100".to_string()],
+                names: vec![String::new(); 3],
+            })
+        );
+        assert_eq!(decoder.decode(&Log { topics: vec![], ..transfer }), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn follows_logs_without_duplicates() {
+        let (_api, handle) = anvil::spawn(anvil::NodeConfig::test()).await;
+        let provider = try_get_http_provider(handle.http_endpoint()).unwrap();
+        let from = handle.dev_accounts().next().unwrap();
+
+        emit_log(&provider, from).await;
+        let cast = Cast::new(&provider);
+        let filter = Filter::new().from_block(0);
+        let mut output = vec![];
+        let follow = cast.follow_logs(
+            filter,
+            &LogDecoder::default(),
+            &mut output,
+            true,
+            Duration::from_millis(100),
+        );
+        let _ = tokio::join!(tokio::time::timeout(Duration::from_secs(2), follow), async {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            emit_log(&provider, from).await;
+        });
+
+        let logs = String::from_utf8(output).unwrap();
+        let logs =
+            logs.lines().map(|line| serde_json::from_str(line).unwrap()).collect::<Vec<Log>>();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].block_number, Some(U64::from(1)));
+        assert_eq!(logs[1].block_number, Some(U64::from(2)));
+    }
+}
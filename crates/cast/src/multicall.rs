@@ -0,0 +1,450 @@
+//! Batching read-only calls into a single round trip, see `cast multicall`.
+
+use crate::Cast;
+use alloy_dyn_abi::{DynSolValue, FunctionExt};
+use alloy_json_abi::Function;
+use alloy_primitives::{address, hex, Address, Bytes};
+use ethers_core::types::{BlockId, TransactionRequest};
+use ethers_providers::Middleware;
+use eyre::{Context, Result};
+use foundry_common::{
+    abi::{encode_function_args, get_func},
+    fmt::{format_token, format_token_raw},
+    fs,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_evm::decode::decode_revert;
+use reqwest::{
+    header::{HeaderMap, CONTENT_TYPE, RETRY_AFTER},
+    StatusCode,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{path::Path, time::Duration};
+
+/// The address of the canonical Multicall3 deployment, the same on most chains.
+pub const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+/// The `aggregate3` function of Multicall3.
+const AGGREGATE3: &str = "aggregate3((address,bool,bytes)[])((bool,bytes)[])";
+
+/// How many times a JSON-RPC batch is sent again when the endpoint is rate limited.
+const BATCH_RETRIES: u32 = 5;
+
+/// The wait before the first retry of a rate limited JSON-RPC batch, if the endpoint doesn't tell
+/// with a `Retry-After` header. It's doubled for each retry.
+const BATCH_BACKOFF: Duration = Duration::from_millis(500);
+
+/// A read-only call of a batch.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MulticallCall {
+    /// The contract to call.
+    pub to: Address,
+    /// The signature of the function, with its return types to decode the result, or the raw
+    /// calldata.
+    pub sig: String,
+    /// The arguments of the function.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Whether the batch may continue if the call fails, the default of the batch if not set.
+    #[serde(default)]
+    pub allow_failure: Option<bool>,
+}
+
+impl MulticallCall {
+    /// Parses a call given as `<TO> <SIG> [ARGS]...`.
+    pub fn from_args(values: &[String]) -> Result<Self> {
+        let [to, sig, args @ ..] = values else {
+            eyre::bail!("expected a call as `<TO> <SIG> [ARGS]...`, got `{}`", values.join(" "))
+        };
+        let to = to.parse().wrap_err_with(|| format!("invalid address `{to}` of a call"))?;
+        Ok(Self { to, sig: sig.clone(), args: args.to_vec(), allow_failure: None })
+    }
+
+    /// Returns the function of the call, `None` if the call is raw calldata.
+    pub fn function(&self) -> Result<Option<Function>> {
+        if self.sig.starts_with("0x") {
+            return Ok(None)
+        }
+        get_func(&self.sig).map(Some)
+    }
+
+    /// Returns the calldata of the call.
+    pub fn calldata(&self) -> Result<Bytes> {
+        match self.function()? {
+            Some(func) => Ok(encode_function_args(&func, &self.args)?.into()),
+            None => Ok(hex::decode(&self.sig)?.into()),
+        }
+    }
+}
+
+/// Reads the calls of a batch from a JSON array of calls, or a TOML file of `[[call]]` tables.
+///
+/// A call is an object like `{ "to": "0x...", "sig": "balanceOf(address)(uint256)", "args":
+/// ["0x..."] }`, optionally with `allowFailure`.
+pub fn read_calls(path: &Path) -> Result<Vec<MulticallCall>> {
+    #[derive(Deserialize)]
+    struct CallsFile {
+        call: Vec<MulticallCall>,
+    }
+
+    let file = fs::read_to_string(path)?;
+    let calls = if path.extension().is_some_and(|ext| ext == "toml") {
+        toml::from_str::<CallsFile>(&file).map(|file| file.call).map_err(eyre::Report::from)
+    } else {
+        serde_json::from_str(&file).map_err(eyre::Report::from)
+    };
+    calls.wrap_err_with(|| format!("invalid calls in {}", path.display()))
+}
+
+/// The outcome of a call of a batch, its return data or its revert data.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CallOutcome {
+    pub success: bool,
+    pub return_data: Bytes,
+    /// The error of the endpoint, if the call failed without revert data
+    pub error: Option<String>,
+}
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Returns whether the Multicall3 contract at `multicall` is deployed at the block.
+    pub async fn has_multicall(&self, multicall: Address, block: Option<BlockId>) -> Result<bool> {
+        Ok(!self.provider.get_code(multicall.to_ethers(), block).await?.is_empty())
+    }
+
+    /// Makes the calls with a single `eth_call` to `aggregate3` of the Multicall3 contract.
+    ///
+    /// All calls are allowed to fail, so that the outcome of each call is returned.
+    pub async fn multicall(
+        &self,
+        multicall: Address,
+        calls: &[(Address, Bytes)],
+        block: Option<BlockId>,
+    ) -> Result<Vec<CallOutcome>> {
+        let func = get_func(AGGREGATE3)?;
+        let calls = calls
+            .iter()
+            .map(|(to, data)| {
+                DynSolValue::Tuple(vec![
+                    DynSolValue::Address(*to),
+                    DynSolValue::Bool(true),
+                    DynSolValue::Bytes(data.to_vec()),
+                ])
+            })
+            .collect();
+        let data = func.abi_encode_input(&[DynSolValue::Array(calls)])?;
+        let tx = TransactionRequest::new().to(multicall.to_ethers()).data(data);
+        let res = self.provider.call(&tx.into(), block).await?;
+
+        let decoded = func.abi_decode_output(&res, false).wrap_err("invalid aggregate3 output")?;
+        let Some(DynSolValue::Array(results)) = decoded.into_iter().next() else {
+            eyre::bail!("invalid aggregate3 output")
+        };
+        results
+            .into_iter()
+            .map(|result| match result.as_tuple() {
+                Some([DynSolValue::Bool(success), DynSolValue::Bytes(data)]) => Ok(CallOutcome {
+                    success: *success,
+                    return_data: data.clone().into(),
+                    error: None,
+                }),
+                _ => eyre::bail!("invalid aggregate3 output"),
+            })
+            .collect()
+    }
+
+    /// Makes the calls with concurrent `eth_call`s, for transports without batches.
+    pub async fn eth_calls(
+        &self,
+        calls: &[(Address, Bytes)],
+        block: Option<BlockId>,
+    ) -> Vec<CallOutcome> {
+        let requests = calls.iter().map(|(to, data)| async move {
+            let tx = TransactionRequest::new().to(to.to_ethers()).data(data.to_ethers());
+            match self.provider.call(&tx.into(), block).await {
+                Ok(res) => CallOutcome { success: true, return_data: res.to_alloy(), error: None },
+                Err(err) => failed_call(err.to_string(), None),
+            }
+        });
+        futures::future::join_all(requests).await
+    }
+}
+
+/// Makes the calls with a JSON-RPC batch of `eth_call`s to the HTTP endpoint.
+pub async fn batch_eth_calls(
+    url: &str,
+    calls: &[(Address, Bytes)],
+    block: BlockId,
+) -> Result<Vec<CallOutcome>> {
+    let block = serde_json::to_value(block)?;
+    let batch = calls
+        .iter()
+        .enumerate()
+        .map(|(id, (to, data))| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "eth_call",
+                "params": [{ "to": to, "data": data }, block],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let client = reqwest::Client::new();
+    let mut backoff = BATCH_BACKOFF;
+    let mut retries = 0;
+    let response = loop {
+        let response = client.post(url).json(&batch).send().await?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS || retries == BATCH_RETRIES {
+            break response
+        }
+        let wait = retry_after(response.headers()).unwrap_or(backoff);
+        tracing::debug!(?wait, "JSON-RPC batch is rate limited");
+        tokio::time::sleep(wait).await;
+        backoff *= 2;
+        retries += 1;
+    };
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        eyre::bail!("the JSON-RPC batch is rate limited, retried {BATCH_RETRIES} times")
+    }
+    let response = response.error_for_status()?;
+    // e.g. the HTML error page of a proxy
+    let content_type = response.headers().get(CONTENT_TYPE).and_then(|value| value.to_str().ok());
+    if let Some(content_type) = content_type.filter(|ty| !ty.starts_with("application/json")) {
+        eyre::bail!("the endpoint responded to the JSON-RPC batch with `{content_type}`")
+    }
+    let responses = batch_responses(response.json().await?)?;
+
+    // the responses of a batch can be in any order
+    let mut outcomes = vec![None; calls.len()];
+    for response in responses {
+        let id = response["id"].as_u64().and_then(|id| usize::try_from(id).ok());
+        let Some(outcome) = id.and_then(|id| outcomes.get_mut(id)) else {
+            eyre::bail!("unexpected response in the JSON-RPC batch: {response}")
+        };
+        *outcome = Some(match (&response["result"], &response["error"]) {
+            (Value::String(result), _) => {
+                CallOutcome { success: true, return_data: hex::decode(result)?.into(), error: None }
+            }
+            (_, error) => {
+                let data = error["data"].as_str().and_then(|data| hex::decode(data).ok());
+                let message = error["message"].as_str().unwrap_or("unknown error").to_string();
+                failed_call(message, data)
+            }
+        });
+    }
+    outcomes
+        .into_iter()
+        .enumerate()
+        .map(|(id, outcome)| {
+            outcome.ok_or_else(|| eyre::eyre!("no response for call {id} in the JSON-RPC batch"))
+        })
+        .collect()
+}
+
+/// Returns the wait of the `Retry-After` header in seconds, if any.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+/// Returns the responses of a JSON-RPC batch.
+///
+/// Endpoints without batches respond with a single error instead.
+fn batch_responses(body: Value) -> Result<Vec<Value>> {
+    match body {
+        Value::Array(responses) => Ok(responses),
+        body => {
+            let message = body["error"]["message"].as_str().map(str::to_owned);
+            eyre::bail!(
+                "the endpoint doesn't support JSON-RPC batches: {}",
+                message.unwrap_or_else(|| body.to_string())
+            )
+        }
+    }
+}
+
+/// The outcome of a failed `eth_call`, its revert data if known or else its error message.
+fn failed_call(message: String, data: Option<Vec<u8>>) -> CallOutcome {
+    match data.filter(|data| !data.is_empty()) {
+        Some(data) => CallOutcome { success: false, return_data: data.into(), error: None },
+        None => CallOutcome { success: false, return_data: Bytes::new(), error: Some(message) },
+    }
+}
+
+/// The result of a call of a batch, its decoded output or why it failed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallResult {
+    pub outcome: CallOutcome,
+    pub decoded: Result<Vec<DynSolValue>, String>,
+}
+
+impl CallResult {
+    /// Decodes the outcome of the call, with the return types of its function if any.
+    pub fn new(call: &MulticallCall, outcome: CallOutcome) -> Self {
+        let decoded = if !outcome.success {
+            Err(match &outcome.error {
+                Some(error) => error.clone(),
+                None => format!("reverted: {}", decode_revert(&outcome.return_data, None, None)),
+            })
+        } else {
+            match call.function() {
+                Ok(Some(func)) => func
+                    .abi_decode_output(&outcome.return_data, false)
+                    .map_err(|err| format!("could not decode output: {err}")),
+                _ => Ok(vec![]),
+            }
+        };
+        Self { outcome, decoded }
+    }
+}
+
+/// Formats the results of the calls in order, as a table or as a JSON array.
+pub fn format_multicall_results(
+    calls: &[MulticallCall],
+    results: &[CallResult],
+    to_json: bool,
+) -> Result<String> {
+    if to_json {
+        let results = calls
+            .iter()
+            .zip(results)
+            .map(|(call, result)| {
+                let mut value = json!({
+                    "to": call.to,
+                    "sig": call.sig,
+                    "success": result.outcome.success,
+                    "returnData": result.outcome.return_data,
+                });
+                match &result.decoded {
+                    Ok(decoded) => {
+                        value["decoded"] = decoded.iter().map(format_token_raw).collect()
+                    }
+                    Err(err) => value["error"] = err.clone().into(),
+                }
+                value
+            })
+            .collect::<Vec<_>>();
+        return Ok(serde_json::to_string_pretty(&results)?)
+    }
+
+    let mut table = comfy_table::Table::new();
+    table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+    table.set_header(["#", "To", "Function", "Result"]);
+    for (i, (call, result)) in calls.iter().zip(results).enumerate() {
+        let output = match &result.decoded {
+            Ok(decoded) if decoded.is_empty() => result.outcome.return_data.to_string(),
+            Ok(decoded) => decoded.iter().map(format_token).collect::<Vec<_>>().join(", "),
+            Err(err) => format!("failed: {err}"),
+        };
+        table.add_row([i.to_string(), call.to.to_checksum(None), call.sig.clone(), output]);
+    }
+    Ok(table.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+
+    fn call(sig: &str, args: &[&str]) -> MulticallCall {
+        MulticallCall {
+            to: Address::repeat_byte(1),
+            sig: sig.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            allow_failure: None,
+        }
+    }
+
+    #[test]
+    fn can_parse_calls() {
+        let args =
+            ["0x0101010101010101010101010101010101010101", "balanceOf(address)(uint256)", "0x02"]
+                .map(String::from);
+        assert_eq!(
+            MulticallCall::from_args(&args).unwrap(),
+            call("balanceOf(address)(uint256)", &["0x02"])
+        );
+        assert!(MulticallCall::from_args(&args[..1]).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("calls.toml");
+        std::fs::write(
+            &path,
+            r#"
+[[call]]
+to = "0x0101010101010101010101010101010101010101"
+sig = "totalSupply()(uint256)"
+
+[[call]]
+to = "0x0101010101010101010101010101010101010101"
+sig = "balanceOf(address)(uint256)"
+args = ["0x02"]
+allowFailure = true
+"#,
+        )
+        .unwrap();
+        let calls = read_calls(&path).unwrap();
+        assert_eq!(calls[0], call("totalSupply()(uint256)", &[]));
+        assert_eq!(calls[1].allow_failure, Some(true));
+
+        let path = dir.path().join("calls.json");
+        std::fs::write(
+            &path,
+            r#"[{"to":"0x0101010101010101010101010101010101010101","sig":"totalSupply()(uint256)"}]"#,
+        )
+        .unwrap();
+        assert_eq!(read_calls(&path).unwrap(), [call("totalSupply()(uint256)", &[])]);
+    }
+
+    #[test]
+    fn can_read_batch_headers_and_responses() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, "3".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+        headers.insert(RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+
+        let responses = json!([{ "jsonrpc": "2.0", "id": 0, "result": "0x" }]);
+        assert_eq!(batch_responses(responses.clone()).unwrap(), responses.as_array().unwrap()[..]);
+        let err = batch_responses(json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": { "code": -32600, "message": "batch requests are not supported" }
+        }))
+        .unwrap_err();
+        assert!(err.to_string().contains("batch requests are not supported"), "{err}");
+    }
+
+    #[test]
+    fn can_format_results() {
+        let calls = [call("totalSupply()(uint256)", &[]), call("decimals()(uint8)", &[])];
+        let supply = DynSolValue::Uint(U256::from(1000), 256);
+        let results = [
+            CallResult::new(
+                &calls[0],
+                CallOutcome { success: true, return_data: supply.abi_encode().into(), error: None },
+            ),
+            CallResult::new(&calls[1], CallOutcome::default()),
+        ];
+        assert_eq!(results[0].decoded, Ok(vec![supply]));
+        assert_eq!(results[1].decoded, Err("reverted: <empty revert data>".to_string()));
+
+        let table = format_multicall_results(&calls, &results, false).unwrap();
+        let rows = table.lines().skip(2).collect::<Vec<_>>();
+        assert!(rows[0].contains("| totalSupply()(uint256) | 1000"), "{table}");
+        assert!(rows[1].contains("failed: reverted: <empty revert data>"), "{table}");
+
+        let json: Value =
+            serde_json::from_str(&format_multicall_results(&calls, &results, true).unwrap())
+                .unwrap();
+        assert_eq!(json[0]["decoded"], json!(["1000"]));
+        assert_eq!(json[1]["success"], false);
+        assert!(json[1]["error"].is_string());
+    }
+}
@@ -0,0 +1,390 @@
+//! State overrides of calls, the geth `stateOverride` parameter of `eth_call` and
+//! `eth_estimateGas`.
+
+use alloy_primitives::{Address, Bytes, B256, U256, U64};
+use ethers_core::types::{
+    transaction::{eip2718::TypedTransaction, eip2930::AccessListWithGasUsed},
+    AccessList, AccessListItem, BlockId, BlockNumber,
+};
+use ethers_providers::JsonRpcError;
+use eyre::Result;
+use foundry_common::types::{ToAlloy, ToEthers};
+use foundry_compilers::EvmVersion;
+use foundry_config::Config;
+use foundry_evm::{
+    decode::decode_revert,
+    executors::{Executor, RawCallResult, TracingExecutor},
+    inspectors::AccessListTracer,
+    opts::EvmOpts,
+    revm::{
+        precompile::{Precompiles, SpecId as PrecompileSpecId},
+        primitives::{Bytecode, CreateScheme, Env, ExecutionResult, TransactTo},
+        DatabaseRef,
+    },
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The overrides of an account.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// The overridden storage slots, the other slots keep their value
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub state_diff: BTreeMap<B256, B256>,
+}
+
+/// The state overrides of a call, `address -> overrides`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct StateOverrides(pub BTreeMap<Address, AccountOverride>);
+
+impl StateOverrides {
+    /// Returns true if no account is overridden.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn set_balance(&mut self, address: Address, balance: U256) -> &mut Self {
+        self.0.entry(address).or_default().balance = Some(balance);
+        self
+    }
+
+    pub fn set_nonce(&mut self, address: Address, nonce: u64) -> &mut Self {
+        self.0.entry(address).or_default().nonce = Some(U64::from(nonce));
+        self
+    }
+
+    pub fn set_code(&mut self, address: Address, code: Bytes) -> &mut Self {
+        self.0.entry(address).or_default().code = Some(code);
+        self
+    }
+
+    pub fn set_storage(&mut self, address: Address, slot: B256, value: B256) -> &mut Self {
+        self.0.entry(address).or_default().state_diff.insert(slot, value);
+        self
+    }
+
+    /// Applies the overrides to the state of the executor.
+    pub fn apply(&self, executor: &mut Executor) -> Result<()> {
+        for (address, account) in &self.0 {
+            let mut info = executor.backend.basic_ref(*address)?.unwrap_or_default();
+            if let Some(balance) = account.balance {
+                info.balance = balance;
+            }
+            if let Some(nonce) = account.nonce {
+                info.nonce = nonce.to();
+            }
+            if let Some(code) = &account.code {
+                let code = Bytecode::new_raw(code.clone()).to_checked();
+                info.code_hash = code.hash_slow();
+                info.code = Some(code);
+            }
+            executor.backend.insert_account_info(*address, info);
+
+            for (slot, value) in &account.state_diff {
+                executor.backend.insert_account_storage(
+                    *address,
+                    U256::from_be_bytes(slot.0),
+                    U256::from_be_bytes(value.0),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if the error of a request with state overrides means that the endpoint doesn't
+/// support them, e.g. because it doesn't accept the extra parameter.
+///
+/// Only errors which clearly reject the parameter or the method are matched, any other error, e.g.
+/// an invalid argument or a revert, is reported as is.
+pub fn is_unsupported_error(err: &str) -> bool {
+    let err = err.to_lowercase();
+    if err.contains("execution reverted") {
+        return false
+    }
+    // the endpoint doesn't accept the third parameter
+    err.contains("too many arguments") ||
+        (err.contains("invalid length 3") && err.contains("expected 2")) ||
+        // the endpoint rejects state overrides explicitly
+        (err.contains("override") && (err.contains("not supported") || err.contains("unsupported"))) ||
+        // the endpoint doesn't implement the method
+        err.contains("method not found") ||
+        err.contains("-32601")
+}
+
+/// Returns true if the error response of a request means that the endpoint doesn't implement the
+/// method, the `Method not found` error of JSON-RPC.
+///
+/// Errors are classified on their code only, messages like "not available" are also returned for
+/// missing state or unsupported parameters of implemented methods.
+pub fn is_method_not_found_error(err: &JsonRpcError) -> bool {
+    err.code == -32601
+}
+
+/// Returns true if the error of a request at an old block means that the endpoint no longer has
+/// the state of the block, e.g. because it isn't an archive node.
+///
+/// Only the errors clients return for pruned state are matched, reverts of the execution never
+/// are, even if their reason contains one of the messages.
+pub fn is_archive_required_error(err: &str) -> bool {
+    let err = err.to_lowercase();
+    if err.contains("execution reverted") {
+        return false
+    }
+    // geth (hash scheme), reth and erigon
+    err.contains("missing trie node") ||
+        // geth (path scheme): "historical state 0x.. is not available"
+        (err.contains("historical state") && err.contains("is not available")) ||
+        // geth, while the state history is being indexed
+        err.contains("state histories haven't been fully indexed") ||
+        // nethermind
+        err.contains("no state available for block") ||
+        // besu
+        err.contains("world state unavailable")
+}
+
+/// Reports the error of a request at the block as requiring an archive node if it is one, see
+/// [`is_archive_required_error`], so that it isn't mistaken for a revert of the execution.
+pub fn archive_error(err: eyre::Report, block: Option<BlockId>) -> eyre::Report {
+    if !is_archive_required_error(&format!("{err:#}")) {
+        return err
+    }
+    let block = match block {
+        Some(BlockId::Number(BlockNumber::Number(number))) => number.to_string(),
+        Some(BlockId::Number(tag)) => format!("{tag:?}").to_lowercase(),
+        Some(BlockId::Hash(hash)) => format!("{hash:?}"),
+        None => "latest".to_string(),
+    };
+    err.wrap_err(format!(
+        "The endpoint doesn't have the state at block {block}, querying historical state requires \
+         an archive node"
+    ))
+}
+
+/// Executes transactions locally against the state of a fork, with the state overrides applied.
+///
+/// This is the fallback for endpoints that don't support state overrides.
+pub struct OverrideExecutor {
+    executor: TracingExecutor,
+}
+
+impl OverrideExecutor {
+    /// Forks the endpoint of the config at `fork_block_number`, or at the latest block, and
+    /// applies the overrides.
+    pub async fn new(
+        config: &Config,
+        evm_opts: EvmOpts,
+        fork_block_number: Option<u64>,
+        evm_version: Option<EvmVersion>,
+        overrides: &StateOverrides,
+    ) -> Result<Self> {
+        let mut config = config.clone();
+        config.fork_block_number = fork_block_number;
+        let (env, fork, _) = TracingExecutor::get_fork_material(&config, evm_opts).await?;
+        let mut executor = TracingExecutor::new(env, fork, evm_version, false).await;
+        overrides.apply(&mut executor)?;
+        Ok(Self { executor })
+    }
+
+    /// Executes the transaction without committing its changes.
+    pub fn call(&mut self, tx: &TypedTransaction) -> Result<RawCallResult> {
+        let env = self.env(tx);
+        self.executor.call_raw_with_env(env)
+    }
+
+    /// Returns the lowest gas limit the transaction succeeds with.
+    pub fn estimate_gas(&mut self, tx: &TypedTransaction) -> Result<u64> {
+        let mut env = self.env(tx);
+        let result = self.executor.call_raw_with_env(env.clone())?;
+        if result.reverted {
+            return Err(revert_error(&result))
+        }
+
+        // the gas used is only a lower bound, refunds and the 63/64 rule can require more
+        let mut lowest_failing = result.gas_used.saturating_sub(1);
+        let mut highest_passing = env.tx.gas_limit;
+        while highest_passing - lowest_failing > 1 {
+            let mid = lowest_failing + (highest_passing - lowest_failing) / 2;
+            env.tx.gas_limit = mid;
+            match self.executor.call_raw_with_env(env.clone()) {
+                Ok(result) if !result.reverted => highest_passing = mid,
+                _ => lowest_failing = mid,
+            }
+        }
+        Ok(highest_passing)
+    }
+
+    /// Generates the access list of the transaction.
+    pub fn access_list(&mut self, tx: &TypedTransaction) -> Result<AccessListWithGasUsed> {
+        let mut env = self.env(tx);
+        let from = env.tx.caller;
+        let to = match env.tx.transact_to {
+            TransactTo::Call(to) => to,
+            TransactTo::Create(_) => {
+                let nonce = self.executor.backend.basic_ref(from)?.unwrap_or_default().nonce;
+                from.create(nonce)
+            }
+        };
+        let precompiles =
+            Precompiles::new(PrecompileSpecId::BERLIN).addresses().into_iter().copied().collect();
+        let mut tracer = AccessListTracer::new(Default::default(), from, to, precompiles);
+        let result = self.executor.backend.inspect_ref(&mut env, &mut tracer)?;
+        match &result.result {
+            ExecutionResult::Success { .. } => {}
+            ExecutionResult::Revert { output, .. } => {
+                eyre::bail!("execution reverted: {}", decode_revert(output, None, None))
+            }
+            ExecutionResult::Halt { reason, .. } => eyre::bail!("execution halted: {reason:?}"),
+        }
+
+        let access_list = tracer
+            .access_list()
+            .0
+            .into_iter()
+            .map(|item| AccessListItem {
+                address: item.address.to_ethers(),
+                storage_keys: item.storage_keys.into_iter().map(|key| key.to_ethers()).collect(),
+            })
+            .collect();
+        Ok(AccessListWithGasUsed {
+            access_list: AccessList(access_list),
+            gas_used: U256::from(result.result.gas_used()).to_ethers(),
+        })
+    }
+
+    /// Returns the environment of the transaction, free of charge like `eth_call`.
+    fn env(&self, tx: &TypedTransaction) -> Env {
+        let mut env = self.executor.env.clone();
+        env.block.basefee = U256::ZERO;
+        env.tx.caller = tx.from().copied().unwrap_or_default().to_alloy();
+        env.tx.transact_to = match tx.to_addr() {
+            Some(to) => TransactTo::Call(to.to_alloy()),
+            None => TransactTo::Create(CreateScheme::Create),
+        };
+        env.tx.data = tx.data().cloned().unwrap_or_default().to_alloy();
+        env.tx.value = tx.value().copied().unwrap_or_default().to_alloy();
+        env.tx.gas_price = U256::ZERO;
+        env.tx.gas_priority_fee = None;
+        env.tx.gas_limit = tx
+            .gas()
+            .map_or(env.block.gas_limit.saturating_to(), |gas| gas.to_alloy().saturating_to());
+        env.tx.nonce = None;
+        env.tx.access_list = tx
+            .access_list()
+            .map(|list| {
+                list.0
+                    .iter()
+                    .map(|item| {
+                        let keys = item.storage_keys.iter().map(|key| U256::from_be_bytes(key.0));
+                        (item.address.to_alloy(), keys.collect())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        env
+    }
+}
+
+/// Returns the error of a reverted call, with the decoded revert reason.
+pub fn revert_error(result: &RawCallResult) -> eyre::Report {
+    eyre::eyre!(
+        "execution reverted: {}",
+        decode_revert(&result.result, None, Some(result.exit_reason))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use serde_json::json;
+
+    #[test]
+    fn can_serialize_state_overrides() {
+        let account = address!("deadbeefdeadbeefdeadbeefdeadbeefdeadbeef");
+        let mut overrides = StateOverrides::default();
+        overrides
+            .set_balance(account, U256::from(100))
+            .set_nonce(account, 3)
+            .set_code(Address::ZERO, Bytes::from_static(&[0x60, 0x00]))
+            .set_storage(account, B256::with_last_byte(1), B256::with_last_byte(2));
+
+        assert_eq!(
+            serde_json::to_value(&overrides).unwrap(),
+            json!({
+                "0x0000000000000000000000000000000000000000": { "code": "0x6000" },
+                "0xdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef": {
+                    "balance": "0x64",
+                    "nonce": "0x3",
+                    "stateDiff": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":
+                            "0x0000000000000000000000000000000000000000000000000000000000000002"
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn can_detect_unsupported_overrides() {
+        assert!(is_unsupported_error(
+            "(code: -32602, message: too many arguments, want at most 2, data: None)"
+        ));
+        assert!(is_unsupported_error("Invalid params: invalid length 3, expected 2 elements"));
+        assert!(is_unsupported_error("state overrides are not supported"));
+        assert!(is_unsupported_error("(code: -32601, message: Method not found, data: None)"));
+        assert!(!is_unsupported_error("execution reverted: insufficient balance"));
+        assert!(!is_unsupported_error("execution reverted: overrides not supported"));
+        assert!(!is_unsupported_error(
+            "(code: -32602, message: invalid argument 0: hex string has length 39, want 40 for common.Address, data: None)"
+        ));
+        assert!(!is_unsupported_error("Invalid params: unknown field `gasPrise`"));
+        assert!(!is_unsupported_error("transaction type not supported"));
+    }
+
+    #[test]
+    fn can_detect_method_not_found_errors() {
+        let error =
+            |code, message: &str| JsonRpcError { code, message: message.into(), data: None };
+        assert!(is_method_not_found_error(&error(-32601, "Method not found")));
+        assert!(is_method_not_found_error(&error(
+            -32601,
+            "the method eth_feeHistory does not exist/is not available"
+        )));
+        assert!(!is_method_not_found_error(&error(-32000, "historical state is not available")));
+        assert!(!is_method_not_found_error(&error(-32000, "transaction type not supported")));
+        assert!(!is_method_not_found_error(&error(-32602, "block 0x1 does not exist")));
+    }
+
+    #[test]
+    fn can_detect_archive_required_errors() {
+        assert!(is_archive_required_error(
+            "(code: -32000, message: missing trie node 1b3c (path ) state 0x1b3c is not available, data: None)"
+        ));
+        assert!(is_archive_required_error(
+            "historical state 0x9c2cfd2a22ba2b8b5b0a1ad0e7b7bb7f4c1cc3234bfc9a0ec3bde4d3a6f0e1f3 is not available"
+        ));
+        assert!(is_archive_required_error("World state unavailable for block 100"));
+        assert!(!is_archive_required_error("header not found"));
+        assert!(!is_archive_required_error("execution reverted: insufficient balance"));
+        assert!(!is_archive_required_error("execution reverted: archive is pruned"));
+        assert!(!is_archive_required_error("execution reverted: missing trie node"));
+        assert!(!is_archive_required_error("method not available"));
+
+        let err = archive_error(
+            eyre::eyre!("missing trie node"),
+            Some(BlockId::Number(BlockNumber::Number(100.into()))),
+        );
+        assert!(err.to_string().contains("state at block 100, querying historical state"));
+        let err = archive_error(eyre::eyre!("execution reverted"), None);
+        assert_eq!(err.to_string(), "execution reverted");
+    }
+}
@@ -0,0 +1,218 @@
+//! Non-standard packed ABI encoding, i.e. `abi.encodePacked`, see `cast abi-encode --packed`.
+
+use crate::slots::decode_storage_value;
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use eyre::{Context, Result};
+use foundry_common::abi::get_func;
+
+/// Parses the types of a function signature, e.g. `f(address,uint96)`, or a list of types, e.g.
+/// `address,uint96`.
+pub fn parse_packed_types(types: &str) -> Result<Vec<DynSolType>> {
+    let types = types.trim();
+    let sig = if !types.contains('(') {
+        format!("f({types})")
+    } else if types.starts_with('(') {
+        format!("f{types}")
+    } else {
+        types.to_string()
+    };
+    get_func(&sig)?
+        .inputs
+        .iter()
+        .map(|input| {
+            let ty = DynSolType::parse(&input.selector_type())?;
+            check_packable(&ty)?;
+            Ok(ty)
+        })
+        .collect()
+}
+
+/// Returns the number of bytes of a value type in packed mode, `None` for other types.
+pub fn packed_size(ty: &DynSolType) -> Option<usize> {
+    match ty {
+        DynSolType::Bool => Some(1),
+        DynSolType::Address => Some(20),
+        DynSolType::Function => Some(24),
+        DynSolType::Uint(bits) | DynSolType::Int(bits) => Some(bits / 8),
+        DynSolType::FixedBytes(size) => Some(*size),
+        _ => None,
+    }
+}
+
+/// Checks that values of the type can be packed: arrays of value types are, but nested arrays,
+/// arrays of dynamic types and tuples aren't, as for solc.
+fn check_packable(ty: &DynSolType) -> Result<()> {
+    match ty {
+        DynSolType::String | DynSolType::Bytes => Ok(()),
+        DynSolType::Array(inner) | DynSolType::FixedArray(inner, _) => {
+            if packed_size(inner).is_none() {
+                eyre::bail!(
+                    "`{ty}` can't be packed: only arrays of value types are supported, nested \
+                     arrays and arrays of dynamic types aren't"
+                )
+            }
+            Ok(())
+        }
+        DynSolType::Tuple(_) => eyre::bail!("`{ty}` can't be packed: structs aren't supported"),
+        ty if packed_size(ty).is_some() => Ok(()),
+        ty => eyre::bail!("`{ty}` can't be packed"),
+    }
+}
+
+/// Packs the values like `abi.encodePacked`: value types aren't padded, strings and bytes are
+/// concatenated in place, and the elements of arrays are padded to 32 bytes without their length.
+pub fn encode_packed(types: &[DynSolType], args: &[impl AsRef<str>]) -> Result<Vec<u8>> {
+    if types.len() != args.len() {
+        eyre::bail!("expected {} arguments, got {}", types.len(), args.len())
+    }
+    let mut packed = Vec::new();
+    for (i, (ty, arg)) in types.iter().zip(args).enumerate() {
+        check_packable(ty)?;
+        let value = ty
+            .coerce_str(arg.as_ref())
+            .wrap_err_with(|| format!("could not parse argument {i} as `{ty}`"))?;
+        pack_value(&value, &mut packed);
+    }
+    Ok(packed)
+}
+
+fn pack_value(value: &DynSolValue, packed: &mut Vec<u8>) {
+    match value {
+        DynSolValue::String(s) => packed.extend_from_slice(s.as_bytes()),
+        DynSolValue::Bytes(bytes) => packed.extend_from_slice(bytes),
+        // the elements are padded, as in the standard encoding
+        DynSolValue::Array(values) | DynSolValue::FixedArray(values) => {
+            values.iter().for_each(|value| packed.extend_from_slice(&value.abi_encode()))
+        }
+        value => {
+            let word = value.abi_encode();
+            // fixed bytes and functions are left-aligned in their word, numbers right-aligned
+            match *value {
+                DynSolValue::FixedBytes(_, size) => packed.extend_from_slice(&word[..size]),
+                DynSolValue::Function(_) => packed.extend_from_slice(&word[..24]),
+                DynSolValue::Uint(_, bits) | DynSolValue::Int(_, bits) => {
+                    packed.extend_from_slice(&word[32 - bits / 8..])
+                }
+                DynSolValue::Address(_) => packed.extend_from_slice(&word[12..]),
+                _ => packed.extend_from_slice(&word[31..]),
+            }
+        }
+    }
+}
+
+/// Splits packed data into the values of the types.
+///
+/// The sizes of the values must be known, so at most one of the types can be dynamic: a string,
+/// bytes or a dynamic array, whose size is the remainder of the data.
+pub fn decode_packed(types: &[DynSolType], data: &[u8]) -> Result<Vec<DynSolValue>> {
+    let sizes = types
+        .iter()
+        .map(|ty| {
+            check_packable(ty)?;
+            Ok(match ty {
+                DynSolType::FixedArray(_, len) => Some(32 * len),
+                ty => packed_size(ty),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let static_size = sizes.iter().flatten().sum::<usize>();
+    let dynamic = sizes.iter().filter(|size| size.is_none()).count();
+    if dynamic > 1 {
+        eyre::bail!("packed data can only be split with at most one string, bytes or dynamic array")
+    }
+    if data.len() < static_size || (dynamic == 0 && data.len() != static_size) {
+        eyre::bail!("the packed data has {} bytes, the types take {static_size}", data.len())
+    }
+
+    let mut values = Vec::with_capacity(types.len());
+    let mut rest = data;
+    for (ty, size) in types.iter().zip(sizes) {
+        let (chunk, tail) = rest.split_at(size.unwrap_or(data.len() - static_size));
+        rest = tail;
+        let value = match ty {
+            DynSolType::String => DynSolValue::String(
+                String::from_utf8(chunk.to_vec()).wrap_err("the packed string isn't UTF-8")?,
+            ),
+            DynSolType::Bytes => DynSolValue::Bytes(chunk.to_vec()),
+            DynSolType::Array(inner) | DynSolType::FixedArray(inner, _) => {
+                if chunk.len() % 32 != 0 {
+                    eyre::bail!("the {} bytes of the packed `{ty}` aren't words", chunk.len())
+                }
+                let elements = chunk
+                    .chunks(32)
+                    .map(|word| Ok(inner.abi_decode(word)?))
+                    .collect::<Result<Vec<_>>>()?;
+                if matches!(ty, DynSolType::Array(_)) {
+                    DynSolValue::Array(elements)
+                } else {
+                    DynSolValue::FixedArray(elements)
+                }
+            }
+            ty => decode_storage_value(ty, chunk)?,
+        };
+        values.push(value);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::hex;
+
+    fn packed(types: &str, args: &[&str]) -> String {
+        hex::encode(encode_packed(&parse_packed_types(types).unwrap(), args).unwrap())
+    }
+
+    #[test]
+    fn can_encode_packed() {
+        // <https://docs.soliditylang.org/en/latest/abi-spec.html#non-standard-packed-mode>
+        assert_eq!(
+            packed("int16,bytes1,uint16,string", &["-1", "0x42", "3", "Hello, world!"]),
+            "ffff42000348656c6c6f2c20776f726c6421"
+        );
+        assert_eq!(packed("f(bool a, bool b)", &["true", "false"]), "0100");
+        assert_eq!(
+            packed("(address,uint96)", &["0x000000000000000000000000000000000000dEaD", "7"]),
+            "000000000000000000000000000000000000dead000000000000000000000007"
+        );
+        // strings are concatenated
+        assert_eq!(packed("string,string", &["a", "bc"]), packed("string,string", &["ab", "c"]));
+        // the elements of arrays are padded
+        assert_eq!(
+            packed("uint8[],bytes2[1]", &["[1,2]", "[0x0102]"]),
+            "0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000002\
+             0102000000000000000000000000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn rejects_unpackable_types() {
+        for types in ["uint256[][]", "string[]", "bytes[2]", "f((uint256,bool))"] {
+            assert!(parse_packed_types(types).is_err(), "{types}");
+        }
+        let types = parse_packed_types("uint8").unwrap();
+        assert!(encode_packed(&types, &["1", "2"]).is_err());
+    }
+
+    #[test]
+    fn can_decode_packed() {
+        let types = parse_packed_types("address,uint96,bool,int16,bytes").unwrap();
+        let data = hex::decode(
+            "000000000000000000000000000000000000dead000000000000000000000007\
+             01ff9c0102",
+        )
+        .unwrap();
+        let values = decode_packed(&types, &data).unwrap();
+        assert_eq!(values[1], DynSolValue::Uint(alloy_primitives::U256::from(7), 96));
+        assert_eq!(values[2], DynSolValue::Bool(true));
+        assert_eq!(values[3].as_int().unwrap().0.to_string(), "-100");
+        assert_eq!(values[4], DynSolValue::Bytes(vec![1, 2]));
+
+        let types = parse_packed_types("address,uint96").unwrap();
+        assert!(decode_packed(&types, &data).is_err());
+        let types = parse_packed_types("string,bytes").unwrap();
+        assert!(decode_packed(&types, &data).is_err());
+    }
+}
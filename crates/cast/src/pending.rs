@@ -0,0 +1,137 @@
+//! Sending transactions and waiting for their confirmations, see `cast send`.
+
+use crate::Cast;
+use alloy_primitives::B256;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, Address, BlockNumber, H256};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{
+    fee_bump::{
+        fee_of, is_already_known_error, wait_with_fee_bumps, BumpOutcome, FeeBump, SendAttempt,
+    },
+    types::{ToAlloy, ToEthers},
+};
+use std::time::{Duration, Instant};
+
+/// How to wait for a sent transaction to be confirmed.
+#[derive(Clone, Debug)]
+pub struct ConfirmOpts {
+    /// The number of blocks the transaction must be included in for, 1 if it only has to be mined
+    pub confirmations: usize,
+    /// How long to wait before giving up, forever if `None`
+    pub timeout: Option<Duration>,
+    /// The percentage the fees are bumped by when the transaction isn't mined within
+    /// `bump_interval`
+    pub auto_bump: Option<u64>,
+    pub bump_interval: Duration,
+}
+
+impl Default for ConfirmOpts {
+    fn default() -> Self {
+        Self {
+            confirmations: 1,
+            timeout: None,
+            auto_bump: None,
+            bump_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// The outcome of [`Cast::send_and_confirm`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SendOutcome {
+    /// The transaction with this hash was confirmed
+    Confirmed(H256),
+    /// The transaction wasn't confirmed before the timeout, the hash is the one of the last
+    /// submitted transaction
+    TimedOut(H256),
+}
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Sends the transaction and waits for it to be confirmed.
+    ///
+    /// If the node already knows the transaction, e.g. because a request was retried, its hash is
+    /// waited for. With `auto_bump`, the transaction is resent with bumped fees every
+    /// `bump_interval` until it's mined, the receipts of all the sent transactions are checked
+    /// since any of them can be mined.
+    pub async fn send_and_confirm(
+        &self,
+        mut tx: TypedTransaction,
+        from: Address,
+        opts: &ConfirmOpts,
+    ) -> Result<SendOutcome> {
+        // the fees and the nonce must be known to be bumped
+        self.provider.fill_transaction(&mut tx, None).await?;
+        if opts.auto_bump.is_some() && tx.nonce().is_none() {
+            let nonce = self
+                .provider
+                .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+                .await?;
+            tx.set_nonce(nonce);
+        }
+
+        let start = Instant::now();
+        let hash = self.submit(tx.clone(), from).await?;
+        let mut attempts = vec![SendAttempt { hash, fee: fee_of(&tx) }];
+        let bump = opts.auto_bump.map(|percent| FeeBump {
+            interval: opts.bump_interval,
+            percent,
+            max_fee: None,
+        });
+        let outcome = wait_with_fee_bumps(
+            &self.provider,
+            tx,
+            &mut attempts,
+            bump,
+            opts.timeout,
+            move |tx| self.submit(tx, from),
+            |_| Ok(()),
+        )
+        .await?;
+        let hash = match outcome {
+            BumpOutcome::Mined(hash) => hash.to_ethers(),
+            BumpOutcome::TimedOut(hash) => return Ok(SendOutcome::TimedOut(hash.to_ethers())),
+        };
+
+        // wait for the confirmations of the mined transaction
+        let poll_interval = self.provider.provider().get_interval();
+        loop {
+            if let Some(block) = self
+                .provider
+                .get_transaction_receipt(hash)
+                .await?
+                .and_then(|receipt| receipt.block_number)
+            {
+                let head = self.provider.get_block_number().await?;
+                if head.as_u64() + 1 >= block.as_u64() + opts.confirmations.max(1) as u64 {
+                    return Ok(SendOutcome::Confirmed(hash))
+                }
+            }
+
+            let elapsed = start.elapsed();
+            let sleep = match opts.timeout {
+                Some(timeout) if elapsed >= timeout => return Ok(SendOutcome::TimedOut(hash)),
+                Some(timeout) => poll_interval.min(timeout - elapsed),
+                None => poll_interval,
+            };
+            tokio::time::sleep(sleep).await;
+        }
+    }
+
+    /// Sends the transaction and returns its hash.
+    async fn submit(&self, tx: TypedTransaction, from: Address) -> Result<B256> {
+        let hash = match self.provider.send_transaction(tx.clone(), None).await {
+            Ok(pending) => *pending,
+            Err(err) if is_already_known_error(&err.to_string()) => {
+                // the hash of the known transaction is only available if it's signed locally
+                let signature = self.provider.sign_transaction(&tx, from).await.map_err(|_| err)?;
+                tx.hash(&signature)
+            }
+            Err(err) => return Err(err.into()),
+        };
+        Ok(hash.to_alloy())
+    }
+}
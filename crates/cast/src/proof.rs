@@ -0,0 +1,111 @@
+//! Client-side verification of `eth_getProof` responses, see `cast proof --verify`.
+
+use alloy_primitives::{Bytes, B256};
+use ethers_core::types::EIP1186ProofResponse;
+use foundry_common::{
+    proof::{verify_account_proof, verify_storage_proof, ProofError, TrieAccount},
+    types::ToAlloy,
+};
+use serde::Serialize;
+use std::fmt;
+
+/// The verification result of a proof.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemVerification {
+    /// The storage slot, `None` for the account proof
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<B256>,
+    pub valid: bool,
+    /// The index of the first invalid node of the proof
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ItemVerification {
+    fn new(slot: Option<B256>, result: Result<(), ProofError>) -> Self {
+        match result {
+            Ok(()) => Self { slot, valid: true, node_index: None, error: None },
+            Err(err) => Self {
+                slot,
+                valid: false,
+                node_index: Some(err.index()),
+                error: Some(err.to_string()),
+            },
+        }
+    }
+}
+
+/// The verification of the account and storage proofs of an `eth_getProof` response against the
+/// state root of a block.
+///
+/// The storage proofs are verified against the storage root of the response, which is itself
+/// proven by the account proof.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofVerification {
+    pub block_number: u64,
+    pub state_root: B256,
+    pub account: ItemVerification,
+    pub storage: Vec<ItemVerification>,
+}
+
+impl ProofVerification {
+    /// Verifies the proofs of the response.
+    pub fn new(block_number: u64, state_root: B256, proof: &EIP1186ProofResponse) -> Self {
+        let account = TrieAccount {
+            nonce: proof.nonce.as_u64(),
+            balance: proof.balance.to_alloy(),
+            storage_root: proof.storage_hash.to_alloy(),
+            code_hash: proof.code_hash.to_alloy(),
+        };
+        let account_proof = to_alloy_nodes(&proof.account_proof);
+        let account = ItemVerification::new(
+            None,
+            verify_account_proof(state_root, proof.address.to_alloy(), &account, &account_proof),
+        );
+
+        let storage = proof
+            .storage_proof
+            .iter()
+            .map(|storage| {
+                let slot = storage.key.to_alloy();
+                let result = verify_storage_proof(
+                    proof.storage_hash.to_alloy(),
+                    slot,
+                    storage.value.to_alloy(),
+                    &to_alloy_nodes(&storage.proof),
+                );
+                ItemVerification::new(Some(slot), result)
+            })
+            .collect();
+
+        Self { block_number, state_root, account, storage }
+    }
+
+    /// Returns true if all the proofs are valid.
+    pub fn is_valid(&self) -> bool {
+        self.account.valid && self.storage.iter().all(|storage| storage.valid)
+    }
+}
+
+impl fmt::Display for ProofVerification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "block {} (state root {})", self.block_number, self.state_root)?;
+        let mut line = |name: String, item: &ItemVerification| match &item.error {
+            None => writeln!(f, "{name}: PASS"),
+            Some(err) => writeln!(f, "{name}: FAIL, {err}"),
+        };
+        line("account".to_string(), &self.account)?;
+        for storage in &self.storage {
+            line(format!("storage {}", storage.slot.unwrap_or_default()), storage)?;
+        }
+        Ok(())
+    }
+}
+
+fn to_alloy_nodes(nodes: &[ethers_core::types::Bytes]) -> Vec<Bytes> {
+    nodes.iter().map(|node| node.clone().to_alloy()).collect()
+}
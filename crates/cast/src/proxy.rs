@@ -0,0 +1,116 @@
+//! Detection of the implementation of proxies, see `cast storage`.
+
+use crate::Cast;
+use alloy_primitives::{b256, hex, Address, B256};
+use ethers_core::types::{BlockId, NameOrAddress, TransactionRequest};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::types::{ToAlloy, ToEthers};
+use std::fmt;
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`, used by transparent and UUPS
+/// proxies.
+pub const IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.admin")) - 1)`, the admin of transparent proxies.
+pub const ADMIN_SLOT: B256 =
+    b256!("b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103");
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.beacon")) - 1)`.
+pub const BEACON_SLOT: B256 =
+    b256!("a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50");
+
+/// `keccak256("PROXIABLE")`, used by EIP-1822 UUPS proxies.
+pub const PROXIABLE_SLOT: B256 =
+    b256!("c5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7");
+
+/// The selector of the `implementation()` function of beacons.
+const IMPLEMENTATION_SELECTOR: [u8; 4] = [0x5c, 0x60, 0xda, 0x1b];
+
+/// The code of EIP-1167 minimal proxies before the address of their implementation.
+const MINIMAL_PROXY_PREFIX: [u8; 10] = hex!("363d3d373d3d3d363d73");
+
+/// The code of EIP-1167 minimal proxies after the address of their implementation.
+const MINIMAL_PROXY_SUFFIX: [u8; 15] = hex!("5af43d82803e903d91602b57fd5bf3");
+
+/// Returns the implementation of the EIP-1167 minimal proxy with this runtime code, or `None` if
+/// the code isn't the one of a minimal proxy.
+pub fn minimal_proxy_implementation(code: &[u8]) -> Option<Address> {
+    let implementation =
+        code.strip_prefix(&MINIMAL_PROXY_PREFIX)?.strip_suffix(&MINIMAL_PROXY_SUFFIX)?;
+    (implementation.len() == 20).then(|| Address::from_slice(implementation))
+}
+
+/// The kind of a proxy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProxyKind {
+    /// An EIP-1967 transparent or UUPS proxy
+    Eip1967,
+    /// An EIP-1967 beacon proxy
+    Beacon(Address),
+    /// An EIP-1822 UUPS proxy
+    Eip1822,
+}
+
+impl fmt::Display for ProxyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProxyKind::Eip1967 => f.write_str("EIP-1967 proxy"),
+            ProxyKind::Beacon(beacon) => write!(f, "EIP-1967 beacon proxy, beacon {beacon}"),
+            ProxyKind::Eip1822 => f.write_str("EIP-1822 proxy"),
+        }
+    }
+}
+
+/// The implementation of a proxy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProxyImplementation {
+    pub kind: ProxyKind,
+    pub implementation: Address,
+}
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Returns the implementation of the proxy, by reading the implementation slots of EIP-1967
+    /// and EIP-1822, or `None` if the contract isn't a proxy.
+    pub async fn proxy_implementation<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        proxy: T,
+        block: Option<BlockId>,
+    ) -> Result<Option<ProxyImplementation>> {
+        let proxy = proxy.into();
+        if let Some(implementation) = self.slot_address(&proxy, IMPLEMENTATION_SLOT, block).await? {
+            return Ok(Some(ProxyImplementation { kind: ProxyKind::Eip1967, implementation }))
+        }
+        if let Some(beacon) = self.slot_address(&proxy, BEACON_SLOT, block).await? {
+            let tx = TransactionRequest::new()
+                .to(beacon.to_ethers())
+                .data(IMPLEMENTATION_SELECTOR.to_vec());
+            let output = self.provider.call(&tx.into(), block).await?;
+            if output.len() != 32 {
+                eyre::bail!("beacon {beacon} returned an invalid implementation: {output}");
+            }
+            let implementation = Address::from_word(B256::from_slice(&output));
+            return Ok(Some(ProxyImplementation { kind: ProxyKind::Beacon(beacon), implementation }))
+        }
+        if let Some(implementation) = self.slot_address(&proxy, PROXIABLE_SLOT, block).await? {
+            return Ok(Some(ProxyImplementation { kind: ProxyKind::Eip1822, implementation }))
+        }
+        Ok(None)
+    }
+
+    /// Reads the address stored in the slot, `None` if it's zero.
+    async fn slot_address(
+        &self,
+        who: &NameOrAddress,
+        slot: B256,
+        block: Option<BlockId>,
+    ) -> Result<Option<Address>> {
+        let value = self.provider.get_storage_at(who.clone(), slot.to_ethers(), block).await?;
+        let address = Address::from_word(value.to_alloy());
+        Ok((!address.is_zero()).then_some(address))
+    }
+}
@@ -0,0 +1,701 @@
+//! Offline decoding of raw signed transactions, see `cast decode-transaction`.
+
+use alloy_dyn_abi::FunctionExt;
+use alloy_json_abi::{Function, JsonAbi};
+use alloy_primitives::{hex, keccak256, Address, Bytes, B256, U256, U64};
+use alloy_rlp::{Decodable, Encodable, Header};
+use ethers_core::types::{RecoveryMessage, Signature};
+use eyre::{eyre, Result};
+use foundry_common::{
+    fmt::format_token,
+    types::{ToAlloy, ToEthers},
+};
+use serde::{Serialize, Serializer};
+use std::fmt::{self, Write};
+
+/// The EIP-2718 envelope type of a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TxType {
+    Legacy = 0,
+    Eip2930 = 1,
+    Eip1559 = 2,
+    Eip4844 = 3,
+    Eip7702 = 4,
+}
+
+impl TryFrom<u8> for TxType {
+    type Error = eyre::Report;
+
+    fn try_from(value: u8) -> Result<Self> {
+        Ok(match value {
+            0 => Self::Legacy,
+            1 => Self::Eip2930,
+            2 => Self::Eip1559,
+            3 => Self::Eip4844,
+            4 => Self::Eip7702,
+            _ => eyre::bail!("unsupported transaction type {value:#x}"),
+        })
+    }
+}
+
+impl TxType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Legacy => "legacy",
+            Self::Eip2930 => "EIP-2930",
+            Self::Eip1559 => "EIP-1559",
+            Self::Eip4844 => "EIP-4844",
+            Self::Eip7702 => "EIP-7702",
+        }
+    }
+}
+
+impl fmt::Display for TxType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", *self as u8, self.name())
+    }
+}
+
+impl Serialize for TxType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        U64::from(*self as u8).serialize(serializer)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<B256>,
+}
+
+/// An EIP-7702 authorization to delegate the code of the authority to `address`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authorization {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: U64,
+    pub y_parity: U64,
+    pub r: U256,
+    pub s: U256,
+    /// The signer of the authorization, if the signature is valid
+    pub authority: Option<Address>,
+}
+
+/// The calldata of a transaction decoded with the ABI of the called function.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DecodedInput {
+    pub function: String,
+    pub args: Vec<String>,
+}
+
+/// The fields of a raw signed transaction, with the recovered sender and the hash.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedTransaction {
+    #[serde(rename = "type")]
+    pub tx_type: TxType,
+    pub hash: B256,
+    pub from: Address,
+    /// The chain ID, unset for legacy transactions without EIP-155 replay protection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<U64>,
+    pub nonce: U64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_blob_gas: Option<U256>,
+    pub gas: U64,
+    /// The recipient, unset for contract creations
+    pub to: Option<Address>,
+    pub value: U256,
+    pub input: Bytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<Vec<AccessListItem>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_versioned_hashes: Option<Vec<B256>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorization_list: Option<Vec<Authorization>>,
+    /// The `v` value of legacy transactions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub v: Option<U64>,
+    /// The signature parity of typed transactions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y_parity: Option<U64>,
+    pub r: U256,
+    pub s: U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoded_input: Option<DecodedInput>,
+}
+
+impl DecodedTransaction {
+    /// Decodes a raw signed transaction, either a legacy RLP encoded transaction or an EIP-2718
+    /// typed transaction.
+    ///
+    /// Blob transactions can also be in their network form, with the blobs sidecar.
+    pub fn decode(raw: &[u8]) -> Result<Self> {
+        let first = *raw.first().ok_or_else(|| eyre!("empty transaction"))?;
+        if first >= 0xc0 {
+            Self::decode_legacy(raw)
+        } else {
+            match TxType::try_from(first)? {
+                TxType::Legacy => eyre::bail!("legacy transactions don't have a type prefix"),
+                tx_type => Self::decode_typed(tx_type, raw),
+            }
+        }
+    }
+
+    fn decode_legacy(raw: &[u8]) -> Result<Self> {
+        let mut buf = raw;
+        let mut fields = Fields::new(&mut buf, "transaction")?;
+        if !buf.is_empty() {
+            eyre::bail!("unexpected bytes after the transaction")
+        }
+
+        let unsigned_start = fields.payload;
+        let nonce = fields.next("nonce")?;
+        let gas_price = fields.next("gasPrice")?;
+        let gas = fields.next("gas")?;
+        let to = fields.next_to()?;
+        let value = fields.next("value")?;
+        let input = fields.next("input")?;
+        let unsigned = fields.consumed_since(unsigned_start);
+        let v: u64 = fields.next("v")?;
+        let r = fields.next("r")?;
+        let s = fields.next("s")?;
+        fields.finish("transaction")?;
+
+        let mut message = unsigned.to_vec();
+        let chain_id = match v {
+            27 | 28 => None,
+            v if v >= 35 => {
+                // EIP-155 transactions also sign `chain_id, 0, 0`
+                let chain_id = (v - 35) / 2;
+                chain_id.encode(&mut message);
+                message.extend_from_slice(&[alloy_rlp::EMPTY_STRING_CODE; 2]);
+                Some(U64::from(chain_id))
+            }
+            v => eyre::bail!("invalid `v` field: {v}"),
+        };
+        let from = recover(&prefixed_list(None, &message), v, r, s)?;
+
+        Ok(Self {
+            tx_type: TxType::Legacy,
+            hash: keccak256(raw),
+            from,
+            chain_id,
+            nonce,
+            gas_price: Some(gas_price),
+            max_priority_fee_per_gas: None,
+            max_fee_per_gas: None,
+            max_fee_per_blob_gas: None,
+            gas,
+            to,
+            value,
+            input,
+            access_list: None,
+            blob_versioned_hashes: None,
+            authorization_list: None,
+            v: Some(U64::from(v)),
+            y_parity: None,
+            r,
+            s,
+            decoded_input: None,
+        })
+    }
+
+    fn decode_typed(tx_type: TxType, raw: &[u8]) -> Result<Self> {
+        let mut buf = &raw[1..];
+        let mut fields = Fields::new(&mut buf, "transaction")?;
+        if !buf.is_empty() {
+            eyre::bail!("unexpected bytes after the transaction")
+        }
+
+        // the network form of blob transactions is `[tx, blobs, commitments, proofs]`, the hash
+        // only covers the transaction
+        let mut hash = keccak256(raw);
+        if tx_type == TxType::Eip4844 && fields.is_next_list() {
+            let start = fields.payload;
+            let tx = Fields::new(&mut fields.payload, "transaction")?;
+            hash = keccak256(prefixed(tx_type, fields.consumed_since(start)));
+            fields = tx;
+        }
+
+        let unsigned_start = fields.payload;
+        let chain_id = fields.next("chainId")?;
+        let nonce = fields.next("nonce")?;
+        let (gas_price, max_priority_fee_per_gas, max_fee_per_gas) = if tx_type == TxType::Eip2930 {
+            (Some(fields.next("gasPrice")?), None, None)
+        } else {
+            (None, Some(fields.next("maxPriorityFeePerGas")?), Some(fields.next("maxFeePerGas")?))
+        };
+        let gas = fields.next("gas")?;
+        let to = fields.next_to()?;
+        let value = fields.next("value")?;
+        let input = fields.next("input")?;
+        let access_list = fields.access_list()?;
+        let (max_fee_per_blob_gas, blob_versioned_hashes) = if tx_type == TxType::Eip4844 {
+            (Some(fields.next("maxFeePerBlobGas")?), Some(fields.next("blobVersionedHashes")?))
+        } else {
+            (None, None)
+        };
+        let authorization_list =
+            if tx_type == TxType::Eip7702 { Some(fields.authorization_list()?) } else { None };
+        let unsigned = fields.consumed_since(unsigned_start);
+        let y_parity: u64 = fields.next("yParity")?;
+        let r = fields.next("r")?;
+        let s = fields.next("s")?;
+        fields.finish("transaction")?;
+
+        if to.is_none() && matches!(tx_type, TxType::Eip4844 | TxType::Eip7702) {
+            eyre::bail!(
+                "invalid `to` field: {} transactions can't create contracts",
+                tx_type.name()
+            )
+        }
+        if y_parity > 1 {
+            eyre::bail!("invalid `yParity` field: {y_parity}")
+        }
+        let from = recover(&prefixed_list(Some(tx_type as u8), unsigned), y_parity, r, s)?;
+
+        Ok(Self {
+            tx_type,
+            hash,
+            from,
+            chain_id: Some(chain_id),
+            nonce,
+            gas_price,
+            max_priority_fee_per_gas,
+            max_fee_per_gas,
+            max_fee_per_blob_gas,
+            gas,
+            to,
+            value,
+            input,
+            access_list: Some(access_list),
+            blob_versioned_hashes,
+            authorization_list,
+            v: None,
+            y_parity: Some(U64::from(y_parity)),
+            r,
+            s,
+            decoded_input: None,
+        })
+    }
+
+    /// Decodes the calldata with the ABI of the called function.
+    pub fn decode_input(&mut self, func: &Function) -> Result<()> {
+        let data = match self.input.get(..4) {
+            Some(selector) if selector == func.selector().as_slice() => &self.input[4..],
+            _ => eyre::bail!("the calldata doesn't match the selector of {}", func.signature()),
+        };
+        let args = func
+            .abi_decode_input(data, false)
+            .map_err(|err| eyre!("failed to decode the calldata of {}: {err}", func.signature()))?;
+        self.decoded_input = Some(DecodedInput {
+            function: func.signature(),
+            args: args.iter().map(format_token).collect(),
+        });
+        Ok(())
+    }
+
+    /// Decodes the calldata with the function of the ABI matching its selector.
+    pub fn decode_input_with_abi(&mut self, abi: &JsonAbi) -> Result<()> {
+        let selector =
+            self.input.get(..4).ok_or_else(|| eyre!("the calldata doesn't have a selector"))?;
+        let func = abi.functions().find(|func| func.selector().as_slice() == selector).ok_or_else(
+            || eyre!("no function with the selector {} in the ABI", hex::encode_prefixed(selector)),
+        )?;
+        self.decode_input(func)
+    }
+
+    /// Returns the fields as aligned `key value` lines.
+    pub fn pretty(&self) -> String {
+        let mut out = String::new();
+        let mut line = |key: &str, value: &dyn fmt::Display| {
+            let _ = writeln!(out, "{key:<21}{value}");
+        };
+        line("type", &self.tx_type);
+        line("hash", &self.hash);
+        line("from", &self.from.to_checksum(None));
+        if let Some(chain_id) = self.chain_id {
+            line("chainId", &chain_id);
+        }
+        line("nonce", &self.nonce);
+        if let Some(gas_price) = self.gas_price {
+            line("gasPrice", &gas_price);
+        }
+        if let Some(fee) = self.max_priority_fee_per_gas {
+            line("maxPriorityFeePerGas", &fee);
+        }
+        if let Some(fee) = self.max_fee_per_gas {
+            line("maxFeePerGas", &fee);
+        }
+        if let Some(fee) = self.max_fee_per_blob_gas {
+            line("maxFeePerBlobGas", &fee);
+        }
+        line("gas", &self.gas);
+        match self.to {
+            Some(to) => line("to", &to.to_checksum(None)),
+            None => line("to", &"(contract creation)"),
+        }
+        line("value", &self.value);
+        line("input", &self.input);
+        if let Some(access_list) = &self.access_list {
+            line(
+                "accessList",
+                &list(access_list.iter().map(|item| {
+                    let keys =
+                        item.storage_keys.iter().map(ToString::to_string).collect::<Vec<_>>();
+                    format!("{} [{}]", item.address.to_checksum(None), keys.join(", "))
+                })),
+            );
+        }
+        if let Some(hashes) = &self.blob_versioned_hashes {
+            line("blobVersionedHashes", &list(hashes.iter()));
+        }
+        if let Some(authorizations) = &self.authorization_list {
+            line(
+                "authorizationList",
+                &list(authorizations.iter().map(|auth| {
+                    let authority =
+                        auth.authority.map_or("invalid signature".to_string(), |authority| {
+                            authority.to_checksum(None)
+                        });
+                    format!(
+                        "{} -> {} (chainId {}, nonce {})",
+                        authority,
+                        auth.address.to_checksum(None),
+                        auth.chain_id,
+                        auth.nonce
+                    )
+                })),
+            );
+        }
+        if let Some(v) = self.v {
+            line("v", &v);
+        }
+        if let Some(y_parity) = self.y_parity {
+            line("yParity", &y_parity);
+        }
+        line("r", &B256::from(self.r));
+        line("s", &B256::from(self.s));
+        if let Some(decoded) = &self.decoded_input {
+            line("function", &decoded.function);
+            line("args", &list(decoded.args.iter()));
+        }
+        out.truncate(out.trim_end().len());
+        out
+    }
+}
+
+/// Formats the items of a list on separate lines, aligned with the values of
+/// [`DecodedTransaction::pretty`].
+fn list<T: fmt::Display>(items: impl Iterator<Item = T>) -> String {
+    let items = items.map(|item| item.to_string()).collect::<Vec<_>>();
+    if items.is_empty() {
+        return "[]".to_string()
+    }
+    items.join(&format!("\n{:21}", ""))
+}
+
+fn prefixed(tx_type: TxType, payload: &[u8]) -> Vec<u8> {
+    let mut out = vec![tx_type as u8];
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Returns the list of the RLP encoded fields, prefixed by the type if any.
+pub(crate) fn prefixed_list(prefix: Option<u8>, fields: &[u8]) -> Vec<u8> {
+    let mut out = prefix.into_iter().collect::<Vec<_>>();
+    Header { list: true, payload_length: fields.len() }.encode(&mut out);
+    out.extend_from_slice(fields);
+    out
+}
+
+/// Recovers the signer of the message.
+fn recover(message: &[u8], v: u64, r: U256, s: U256) -> Result<Address> {
+    let signature = Signature { r: r.to_ethers(), s: s.to_ethers(), v };
+    let hash = keccak256(message).to_ethers();
+    signature
+        .recover(RecoveryMessage::Hash(hash))
+        .map(|address| address.to_alloy())
+        .map_err(|err| eyre!("failed to recover the signer: {err}"))
+}
+
+/// The items of an RLP list, decoded in order.
+struct Fields<'a> {
+    payload: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    /// Decodes the header of the list named `name`, and advances the buffer past the list.
+    fn new(buf: &mut &'a [u8], name: &str) -> Result<Self> {
+        let header = Header::decode(buf).map_err(|err| eyre!("invalid {name}: {err}"))?;
+        if !header.list {
+            eyre::bail!("invalid {name}: expected a list")
+        }
+        if buf.len() < header.payload_length {
+            eyre::bail!("invalid {name}: input too short")
+        }
+        let (payload, rest) = buf.split_at(header.payload_length);
+        *buf = rest;
+        Ok(Self { payload })
+    }
+
+    fn next<T: Decodable>(&mut self, name: &str) -> Result<T> {
+        T::decode(&mut self.payload).map_err(|err| eyre!("invalid `{name}` field: {err}"))
+    }
+
+    /// Decodes the recipient, the empty string for contract creations.
+    fn next_to(&mut self) -> Result<Option<Address>> {
+        let to: Bytes = self.next("to")?;
+        match to.len() {
+            0 => Ok(None),
+            20 => Ok(Some(Address::from_slice(&to))),
+            len => eyre::bail!("invalid `to` field: expected 20 bytes, got {len}"),
+        }
+    }
+
+    fn is_next_list(&self) -> bool {
+        self.payload.first().map_or(false, |first| *first >= alloy_rlp::EMPTY_LIST_CODE)
+    }
+
+    fn access_list(&mut self) -> Result<Vec<AccessListItem>> {
+        let mut list = Fields::new(&mut self.payload, "`accessList` field")?;
+        let mut items = vec![];
+        while !list.payload.is_empty() {
+            let mut item = Fields::new(&mut list.payload, "`accessList` item")?;
+            let address = item.next("accessList.address")?;
+            let storage_keys = item.next("accessList.storageKeys")?;
+            item.finish("`accessList` item")?;
+            items.push(AccessListItem { address, storage_keys });
+        }
+        Ok(items)
+    }
+
+    fn authorization_list(&mut self) -> Result<Vec<Authorization>> {
+        let mut list = Fields::new(&mut self.payload, "`authorizationList` field")?;
+        let mut authorizations = vec![];
+        while !list.payload.is_empty() {
+            let mut item = Fields::new(&mut list.payload, "`authorizationList` item")?;
+            let unsigned_start = item.payload;
+            let chain_id = item.next("authorizationList.chainId")?;
+            let address = item.next("authorizationList.address")?;
+            let nonce = item.next("authorizationList.nonce")?;
+            let unsigned = item.consumed_since(unsigned_start);
+            let y_parity: u64 = item.next("authorizationList.yParity")?;
+            let r = item.next("authorizationList.r")?;
+            let s = item.next("authorizationList.s")?;
+            item.finish("`authorizationList` item")?;
+
+            // the authority signs `0x05 || rlp([chain_id, address, nonce])`
+            let authority = if y_parity <= 1 {
+                recover(&prefixed_list(Some(0x05), unsigned), y_parity, r, s).ok()
+            } else {
+                None
+            };
+            authorizations.push(Authorization {
+                chain_id,
+                address,
+                nonce,
+                y_parity: U64::from(y_parity),
+                r,
+                s,
+                authority,
+            });
+        }
+        Ok(authorizations)
+    }
+
+    /// Returns the raw encoding of the fields decoded since `start`.
+    fn consumed_since(&self, start: &'a [u8]) -> &'a [u8] {
+        &start[..start.len() - self.payload.len()]
+    }
+
+    fn finish(self, name: &str) -> Result<()> {
+        if !self.payload.is_empty() {
+            eyre::bail!("invalid {name}: unexpected trailing fields")
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use foundry_common::abi::get_func;
+
+    const FROM: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+    const DEAD: Address = address!("000000000000000000000000000000000000dEaD");
+
+    // signed by the first anvil dev account
+    const LEGACY: &str = "f86c808504a817c80082520894000000000000000000000000000000000000dead880de0b6b3a7640000801ca0fd19f3a66b5201414a8e1bffba023736b419621fc492551d8b7d3f324b989e24a00e943a1d95c46ea5022cc9b78dcb705e235030143be45885f0d5256a4788e286";
+    const EIP155: &str = "f8a9018504a817c80082ea6094000000000000000000000000000000000000dead80b844a9059cbb00000000000000000000000070997970c51812dc3a010c7d01b50e0d17dc79c8000000000000000000000000000000000000000000000000000000000000006425a0c9373c21a6fb476bdaea65c72719e8b5800e87549f34b049ef2cad962aba9d7da04ff617331b13fbaa752d6d97293ea2169bce950beda1e1fb42e2374d10ac243a";
+    const EIP2930: &str = "01f8e401028504a817c80082ea6094000000000000000000000000000000000000dead80b844a9059cbb00000000000000000000000070997970c51812dc3a010c7d01b50e0d17dc79c80000000000000000000000000000000000000000000000000000000000000064f838f794000000000000000000000000000000000000deade1a0000000000000000000000000000000000000000000000000000000000000000180a03697140d3ed415ee3e34ed6086dadd86d346862a909fd430c8493815207896e1a07d7d75efb52a0871f429832273f638804d04e5c477c65dcc9d5de1d810c23347";
+    const EIP1559: &str = "02f85d0103843b9aca008506fc23ac00830186a08080856080604052c001a0f87afac7e117fa34e3768fba8f7b18f45c78b0d036e5a174e4c4f6af222207d8a02185599ea65a4d575f34d3b13672fbe8cb0a3fc6064e78b772cdbca6362204e1";
+    const EIP4844: &str = "03f8920104843b9aca008506fc23ac0082520894000000000000000000000000000000000000dead8080c0843b9aca00e1a001ababababababababababababababababababababababababababababababab80a0ac128cdf7484844e15611e694afd7b070b02bd54b7a082ae7fb3c4c71d435e61a006d667beef62b3ac6cf2cf761b6d55fa7fe0214a87c5ae6b798f4d42dc4b09bf";
+    const EIP7702: &str = "04f8ca0105843b9aca008506fc23ac00830186a094000000000000000000000000000000000000dead8080c0f85cf85a0194000000000000000000000000000000000000beef8080a076ab58ff22e35d998f389863d5e08c2a9f5792314a2c33855956320868b1298fa05277f038fc8ebe4a69c330afab9cc2149d0c11006cfaf89ded43742db83ffe3701a00550e6dfc32b9d69a34e7ba99991fc36b8d71b4d76860cdcf5db760cc9d485e7a01e39dc6c3db4f021b9ecff0ec5235e73b3691e6e438ba1608b2fce84c414e00f";
+
+    fn decode(raw: &str) -> Result<DecodedTransaction> {
+        DecodedTransaction::decode(&hex::decode(raw).unwrap())
+    }
+
+    #[test]
+    fn can_decode_legacy_transactions() {
+        let tx = decode(LEGACY).unwrap();
+        assert_eq!(tx.tx_type, TxType::Legacy);
+        assert_eq!(tx.from, FROM);
+        assert_eq!(
+            tx.hash.to_string(),
+            "0x6a414b668284b158706ab0d06095fc5e006acebf254cfbf42ff91094daea3052"
+        );
+        assert_eq!(tx.chain_id, None);
+        assert_eq!(tx.gas_price, Some(U256::from(20_000_000_000u64)));
+        assert_eq!(tx.to, Some(DEAD));
+        assert_eq!(tx.value, U256::from(10).pow(U256::from(18)));
+
+        let tx = decode(EIP155).unwrap();
+        assert_eq!(tx.from, FROM);
+        assert_eq!(
+            tx.hash.to_string(),
+            "0x87a373f56d5bd39b83658b07c8aebc83be98c742804fff10d846028c61cdc945"
+        );
+        assert_eq!(tx.chain_id, Some(U64::from(1)));
+        assert!(matches!(tx.v.unwrap().to::<u64>(), 37 | 38));
+    }
+
+    #[test]
+    fn can_decode_typed_transactions() {
+        let tx = decode(EIP2930).unwrap();
+        assert_eq!(tx.tx_type, TxType::Eip2930);
+        assert_eq!(tx.from, FROM);
+        assert_eq!(
+            tx.hash.to_string(),
+            "0xf191ff57e647c681a1f53d61de75451fd1bfaf21a30e399e326dc1d7490f947e"
+        );
+        assert_eq!(
+            tx.access_list,
+            Some(vec![AccessListItem {
+                address: DEAD,
+                storage_keys: vec![B256::with_last_byte(1)]
+            }])
+        );
+
+        let tx = decode(EIP1559).unwrap();
+        assert_eq!(tx.tx_type, TxType::Eip1559);
+        assert_eq!(tx.from, FROM);
+        assert_eq!(
+            tx.hash.to_string(),
+            "0x55450fe79e534f7e992a0e7e2d066211cb62d490abf10c3c575839500f4f9bb6"
+        );
+        assert_eq!(tx.to, None);
+        assert_eq!(tx.max_priority_fee_per_gas, Some(U256::from(1_000_000_000u64)));
+        assert_eq!(tx.max_fee_per_gas, Some(U256::from(30_000_000_000u64)));
+        assert_eq!(tx.input, Bytes::from_static(&[0x60, 0x80, 0x60, 0x40, 0x52]));
+
+        let tx = decode(EIP4844).unwrap();
+        assert_eq!(tx.tx_type, TxType::Eip4844);
+        assert_eq!(tx.from, FROM);
+        assert_eq!(
+            tx.hash.to_string(),
+            "0x51f828a8d7fe4485e15f4200399733a637b36211e6da0531bc6093fa562966e7"
+        );
+        assert_eq!(tx.max_fee_per_blob_gas, Some(U256::from(1_000_000_000u64)));
+        assert_eq!(tx.blob_versioned_hashes.as_ref().map(Vec::len), Some(1));
+
+        let tx = decode(EIP7702).unwrap();
+        assert_eq!(tx.tx_type, TxType::Eip7702);
+        assert_eq!(tx.from, FROM);
+        assert_eq!(
+            tx.hash.to_string(),
+            "0xa06770e3d483ffa8b910db27c0c92826e4a1e59555cce9607a0e226426ca8b05"
+        );
+        let authorization = &tx.authorization_list.as_ref().unwrap()[0];
+        assert_eq!(authorization.address, address!("000000000000000000000000000000000000bEEF"));
+        assert_eq!(
+            authorization.authority,
+            Some(address!("70997970C51812dc3A010C7d01b50e0d17dc79C8"))
+        );
+    }
+
+    #[test]
+    fn can_decode_blob_transactions_with_sidecar() {
+        let tx = hex::decode(EIP4844).unwrap();
+        let sidecar = vec![Bytes::from_static(&[0; 8])];
+        let mut network = vec![0x03];
+        Header { list: true, payload_length: tx.len() - 1 + 3 * sidecar.length() }
+            .encode(&mut network);
+        network.extend_from_slice(&tx[1..]);
+        for _ in 0..3 {
+            sidecar.encode(&mut network);
+        }
+        assert_eq!(DecodedTransaction::decode(&network).unwrap(), decode(EIP4844).unwrap());
+    }
+
+    #[test]
+    fn can_decode_calldata() {
+        let mut tx = decode(EIP155).unwrap();
+        tx.decode_input(&get_func("transfer(address,uint256)").unwrap()).unwrap();
+        let decoded = tx.decoded_input.as_ref().unwrap();
+        assert_eq!(decoded.function, "transfer(address,uint256)");
+        assert_eq!(decoded.args[0].to_lowercase(), "0x70997970c51812dc3a010c7d01b50e0d17dc79c8");
+        assert_eq!(decoded.args[1], "100");
+        assert!(tx.pretty().contains("function             transfer(address,uint256)"));
+
+        let err = tx.decode_input(&get_func("approve(address,uint256)").unwrap()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the calldata doesn't match the selector of approve(address,uint256)"
+        );
+    }
+
+    #[test]
+    fn malformed_transactions_name_the_field() {
+        let err = |raw: &str| decode(raw).unwrap_err().to_string();
+
+        assert_eq!(err(""), "empty transaction");
+        assert_eq!(err("7ec0"), "unsupported transaction type 0x7e");
+        assert_eq!(err(&format!("{LEGACY}00")), "unexpected bytes after the transaction");
+        assert!(err("01c20102").starts_with("invalid `gasPrice` field"), "{}", err("01c20102"));
+        assert_eq!(
+            err("cd800182520882dead80801b0101"),
+            "invalid `to` field: expected 20 bytes, got 2"
+        );
+        assert_eq!(
+            err("02dd0103843b9aca008506fc23ac00830186a08080856080604052c0020101"),
+            "invalid `yParity` field: 2"
+        );
+        assert_eq!(
+            err("03f83e0104843b9aca008506fc23ac00825208808080c0843b9aca00e1a001ababababababababababababababababababababababababababababababab800101"),
+            "invalid `to` field: EIP-4844 transactions can't create contracts"
+        );
+    }
+
+    #[test]
+    fn can_reencode_transactions_from_json() {
+        for raw in [LEGACY, EIP155, EIP2930, EIP1559, EIP4844, EIP7702] {
+            let json = serde_json::to_value(decode(raw).unwrap()).unwrap();
+            let encoded = crate::consensus::encode_transaction(&json).unwrap();
+            assert_eq!(hex::encode(encoded), raw);
+        }
+
+        let mut json = serde_json::to_value(decode(EIP1559).unwrap()).unwrap();
+        json["gas"] = serde_json::json!("0x1");
+        let err = crate::consensus::encode_transaction(&json).unwrap_err().to_string();
+        assert!(
+            err.starts_with("can't reconstruct the raw encoding of EIP-1559 transaction"),
+            "{err}"
+        );
+
+        json["type"] = serde_json::json!("0x7e");
+        let err = crate::consensus::encode_transaction(&json).unwrap_err().to_string();
+        assert!(err.ends_with("unsupported transaction type 0x7e"), "{err}");
+    }
+}
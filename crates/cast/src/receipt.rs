@@ -0,0 +1,170 @@
+//! Waiting for receipts and decoding their logs with an ABI, see `cast receipt --abi`.
+
+use crate::{
+    logs::{DecodedLog, LogDecoder},
+    Cast,
+};
+use ethers_core::types::{Log, H256};
+use ethers_providers::{Middleware, PendingTransaction};
+use eyre::Result;
+use foundry_common::{
+    fmt::{get_pretty_tx_receipt_attr, UIfmt},
+    TransactionReceiptWithRevertReason,
+};
+use serde_json::Value;
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Returns the receipt of the transaction once it has `confs` confirmations, or as soon as it
+    /// is found with `cast_async`.
+    ///
+    /// A receipt which is already available is only returned once it has enough confirmations.
+    pub async fn transaction_receipt(
+        &self,
+        tx_hash: H256,
+        confs: usize,
+        cast_async: bool,
+    ) -> Result<TransactionReceiptWithRevertReason> {
+        let receipt = match self.provider.get_transaction_receipt(tx_hash).await? {
+            Some(receipt) if cast_async || confs <= 1 => receipt,
+            // if the async flag is provided, immediately exit if no tx is found
+            None if cast_async => eyre::bail!("tx not found: {:?}", tx_hash),
+            // otherwise poll for it until it has enough confirmations
+            _ => PendingTransaction::new(tx_hash, self.provider.provider())
+                .confirmations(confs)
+                .await?
+                .ok_or_else(|| {
+                    eyre::eyre!("tx not found, might have been dropped from mempool: {:?}", tx_hash)
+                })?,
+        };
+        let mut receipt = TransactionReceiptWithRevertReason::from(receipt);
+
+        // Allow to fail silently
+        let _ = receipt.update_revert_reason(&self.provider).await;
+        Ok(receipt)
+    }
+}
+
+/// A receipt whose logs were decoded with the events of an ABI.
+#[derive(Clone, Debug)]
+pub struct DecodedReceipt {
+    pub receipt: TransactionReceiptWithRevertReason,
+    /// The decoded logs of the receipt, `None` for the ones no event could decode
+    pub logs: Vec<Option<DecodedLog>>,
+}
+
+impl DecodedReceipt {
+    /// Decodes the logs of the receipt, the logs which can't be decoded are kept raw.
+    pub fn new(receipt: TransactionReceiptWithRevertReason, decoder: &LogDecoder) -> Self {
+        let logs = receipt.receipt.logs.iter().map(|log| decoder.decode(log)).collect();
+        Self { receipt, logs }
+    }
+
+    /// Returns the JSON of the receipt, with a `decoded` field in the logs which were decoded.
+    pub fn to_json(&self) -> Result<Value> {
+        // to_value first to sort json object keys
+        let mut receipt = serde_json::to_value(&self.receipt)?;
+        receipt["logs"] = self.logs_json()?;
+        Ok(receipt)
+    }
+
+    /// Returns the JSON of the logs, with a `decoded` field in the ones which were decoded.
+    pub fn logs_json(&self) -> Result<Value> {
+        let logs = self
+            .raw_logs()
+            .zip(&self.logs)
+            .map(|(log, decoded)| {
+                let mut log = serde_json::to_value(log)?;
+                if let Some(decoded) = decoded {
+                    log["decoded"] = serde_json::to_value(decoded)?;
+                }
+                Ok(log)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Value::Array(logs))
+    }
+
+    /// Returns the logs one per item, with the event and the named arguments of the decoded ones
+    /// and the topics and data of the other ones.
+    pub fn pretty_logs(&self) -> String {
+        let logs = self.raw_logs().zip(&self.logs).map(|(log, decoded)| match decoded {
+            Some(decoded) => format!(
+                "- address: {}\n  event: {}\n  args: [{}]",
+                log.address.pretty(),
+                decoded.event,
+                decoded.pretty_args()
+            ),
+            None => format!(
+                "- address: {}\n  topics: [{}]\n  data: {}",
+                log.address.pretty(),
+                log.topics.iter().map(|topic| topic.pretty()).collect::<Vec<_>>().join(", "),
+                log.data.pretty()
+            ),
+        });
+        logs.collect::<Vec<_>>().join("\n")
+    }
+
+    /// Returns the field of the receipt, the logs being decoded.
+    pub fn field(&self, field: &str, to_json: bool) -> Result<String> {
+        match field {
+            "logs" if to_json => Ok(self.logs_json()?.to_string()),
+            "logs" => Ok(self.pretty_logs()),
+            field => get_pretty_tx_receipt_attr(&self.receipt, field)
+                .ok_or_else(|| eyre::eyre!("invalid receipt field: {}", field)),
+        }
+    }
+
+    fn raw_logs(&self) -> impl Iterator<Item = &Log> {
+        self.receipt.receipt.logs.iter()
+    }
+}
+
+impl UIfmt for DecodedReceipt {
+    fn pretty(&self) -> String {
+        format!("{}\ndecodedLogs\n{}", self.receipt.pretty(), self.pretty_logs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_json_abi::Event;
+    use alloy_primitives::{Address, B256, U256};
+    use ethers_core::types::TransactionReceipt;
+    use foundry_common::types::ToEthers;
+
+    #[test]
+    fn decodes_the_logs_it_can() {
+        let event =
+            Event::parse("event Transfer(address indexed from, address indexed to, uint256 value)")
+                .unwrap();
+        let from = Address::repeat_byte(1);
+        let transfer = Log {
+            topics: vec![
+                event.selector().to_ethers(),
+                from.into_word().to_ethers(),
+                Address::repeat_byte(2).into_word().to_ethers(),
+            ],
+            data: B256::from(U256::from(100)).0.to_vec().into(),
+            ..Default::default()
+        };
+        // the same event without the indexed `to`
+        let unknown = Log { topics: transfer.topics[..2].to_vec(), ..transfer.clone() };
+        let receipt = TransactionReceipt { logs: vec![transfer, unknown], ..Default::default() };
+        let receipt = DecodedReceipt::new(receipt.into(), &LogDecoder::new([event]));
+
+        let json = receipt.to_json().unwrap();
+        assert_eq!(json["logs"][0]["decoded"]["event"], "Transfer(address,address,uint256)");
+        assert_eq!(json["logs"][0]["decoded"]["names"][2], "value");
+        assert_eq!(json["logs"][1]["decoded"], Value::Null);
+        assert_eq!(json["logs"][1]["topics"].as_array().unwrap().len(), 2);
+
+        let logs = receipt.field("logs", false).unwrap();
+        assert!(logs.contains(&format!("args: [from: {}", from.to_checksum(None))), "{logs}");
+        assert!(logs.contains("value: 100]"), "{logs}");
+        assert!(logs.contains("topics: ["), "{logs}");
+        assert!(receipt.field("nope", false).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! Storage locations of variables, see `cast index` and `cast storage --slot-of`.
+
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy_primitives::{hex, keccak256, B256, U256};
+use eyre::Result;
+
+/// Encodes a mapping key as it's hashed with the slot of the mapping: value types are padded to
+/// 32 bytes, strings and bytes are hashed as is.
+///
+/// The key type can also be the label of the type in a storage layout, e.g. `contract IERC20`.
+pub fn encode_mapping_key(key_type: &str, key: &str) -> Result<Vec<u8>> {
+    let ty = match key_type {
+        "string" => return Ok(key.trim_matches('"').as_bytes().to_vec()),
+        "bytes" => return Ok(hex::decode(key)?),
+        label if label.starts_with("contract ") || label == "address payable" => "address",
+        label if label.starts_with("enum ") => "uint8",
+        label => label,
+    };
+    let ty = DynSolType::parse(ty)?;
+    Ok(ty.coerce_str(key)?.abi_encode())
+}
+
+/// Returns the slot of the value of a mapping at `slot` for the key.
+pub fn mapping_slot(key_type: &str, key: &str, slot: B256) -> Result<B256> {
+    let mut preimage = encode_mapping_key(key_type, key)?;
+    preimage.extend_from_slice(slot.as_slice());
+    Ok(keccak256(preimage))
+}
+
+/// Returns the slot of the value of nested mappings at `slot`, for the `(key type, key)` pairs
+/// from the outermost mapping, e.g. `allowances[owner][spender]`.
+pub fn nested_mapping_slot<'a>(
+    keys: impl IntoIterator<Item = (&'a str, &'a str)>,
+    slot: B256,
+) -> Result<B256> {
+    keys.into_iter().try_fold(slot, |slot, (key_type, key)| mapping_slot(key_type, key, slot))
+}
+
+/// Returns the slot and the offset of an element of an array starting at `start`, elements of
+/// up to 16 bytes are packed.
+pub fn array_element_location(start: U256, size: u64, index: U256) -> (U256, u64) {
+    if size <= 16 {
+        let per_slot = U256::from(32 / size);
+        let offset = (index % per_slot).to::<u64>() * size;
+        (start + index / per_slot, offset)
+    } else {
+        (start + index * U256::from(size.div_ceil(32)), 0)
+    }
+}
+
+/// Returns the slot and the offset of an element of `size` bytes of the dynamic array at `slot`,
+/// whose elements are stored from the hash of the slot.
+pub fn dynamic_array_element_location(slot: B256, size: u64, index: U256) -> (B256, u64) {
+    let start = U256::from_be_bytes(keccak256(slot).0);
+    let (slot, offset) = array_element_location(start, size, index);
+    (slot.into(), offset)
+}
+
+/// Returns the root slot of the ERC-7201 namespace, the id of the `@custom:storage-location
+/// erc7201:<namespace>` annotation:
+/// `keccak256(abi.encode(uint256(keccak256(namespace)) - 1)) & ~bytes32(uint256(0xff))`.
+pub fn erc7201_slot(namespace: &str) -> B256 {
+    let id = U256::from_be_bytes(keccak256(namespace).0) - U256::from(1);
+    let mut slot = keccak256(id.to_be_bytes::<32>());
+    slot[31] = 0;
+    slot
+}
+
+/// Returns the `size` bytes at the offset of the slot value, from the low-order end as values
+/// are packed.
+pub fn packed_value(word: &B256, offset: u64, size: u64) -> &[u8] {
+    let end = 32 - (offset as usize).min(32);
+    let start = end.saturating_sub(size as usize);
+    &word[start..end]
+}
+
+/// Decodes the value type from its bytes in a storage slot, see [`packed_value`].
+pub fn decode_storage_value(ty: &DynSolType, bytes: &[u8]) -> Result<DynSolValue> {
+    if bytes.len() > 32 {
+        eyre::bail!("a value of `{ty}` can't span {} bytes", bytes.len())
+    }
+    let mut word = [0u8; 32];
+    match ty {
+        // fixed bytes are left-aligned in their ABI encoding
+        DynSolType::FixedBytes(_) => word[..bytes.len()].copy_from_slice(bytes),
+        DynSolType::Address | DynSolType::Bool | DynSolType::Uint(_) | DynSolType::Int(_) => {
+            // signed integers are sign extended
+            if matches!(ty, DynSolType::Int(_)) && bytes.first().is_some_and(|b| b & 0x80 != 0) {
+                word = [0xff; 32];
+            }
+            word[32 - bytes.len()..].copy_from_slice(bytes);
+        }
+        _ => eyre::bail!("only value types can be decoded from a storage slot, got `{ty}`"),
+    }
+    Ok(ty.abi_decode(&word)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::{ADMIN_SLOT, BEACON_SLOT, IMPLEMENTATION_SLOT};
+    use alloy_primitives::b256;
+
+    // `ERC20Upgradeable` of OpenZeppelin 5: `_balances` and `_allowances` are the first members
+    // of the `ERC20Storage` struct of the namespace
+    const ERC20_STORAGE: B256 =
+        b256!("52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace00");
+    const OWNER: &str = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045";
+
+    #[test]
+    fn can_compute_erc7201_slots() {
+        assert_eq!(erc7201_slot("openzeppelin.storage.ERC20"), ERC20_STORAGE);
+        assert_eq!(
+            erc7201_slot("openzeppelin.storage.Ownable"),
+            b256!("9016d09d72d40fdae2fd8ceac6b6234c7706214fd39c1cd1e609a0528c199300")
+        );
+        assert_eq!(
+            erc7201_slot("openzeppelin.storage.Initializable"),
+            b256!("f0c57e16840df040f15088dc2f81fe391c3923bec73e23a9662efc9c229c6a00")
+        );
+    }
+
+    #[test]
+    fn can_compute_mapping_slots() {
+        assert_eq!(
+            mapping_slot("address", OWNER, ERC20_STORAGE).unwrap(),
+            b256!("d1c18dff8cded65a0c764166337132c784ca3fdb8a600b60b812205278d53038")
+        );
+        let allowances = B256::from(U256::from_be_bytes(ERC20_STORAGE.0) + U256::from(1));
+        let spender = "0x0000000000000000000000000000000000000001";
+        assert_eq!(
+            nested_mapping_slot([("address", OWNER), ("address", spender)], allowances).unwrap(),
+            b256!("af8441c3ce57ef6adc4285fef762056b94463aa3ee1a6acd1d37424a3cedd7b9")
+        );
+        assert_eq!(
+            mapping_slot("string", "foo", B256::ZERO).unwrap(),
+            b256!("58710dca1e2a45afe5496e8d02017d2788e01040086e5de4c6d09b2c1a60473d")
+        );
+    }
+
+    #[test]
+    fn can_compute_array_slots() {
+        let slot = B256::with_last_byte(3);
+        // four `uint64` per slot
+        assert_eq!(
+            dynamic_array_element_location(slot, 8, U256::from(5)),
+            (b256!("c2575a0e9e593c00f959f8c92f12db2869c3395a3b0502d05e2516446f71f85c"), 8)
+        );
+        // structs of two slots
+        assert_eq!(
+            dynamic_array_element_location(slot, 64, U256::from(5)),
+            (b256!("c2575a0e9e593c00f959f8c92f12db2869c3395a3b0502d05e2516446f71f865"), 0)
+        );
+    }
+
+    #[test]
+    fn can_compute_eip1967_slots() {
+        for (slot, name) in [
+            (IMPLEMENTATION_SLOT, "implementation"),
+            (ADMIN_SLOT, "admin"),
+            (BEACON_SLOT, "beacon"),
+        ] {
+            let hash = U256::from_be_bytes(keccak256(format!("eip1967.proxy.{name}")).0);
+            assert_eq!(slot, B256::from(hash - U256::from(1)));
+        }
+    }
+
+    #[test]
+    fn can_decode_packed_values() {
+        let word = b256!("000000000000000000000000000000000000000000ff9c00000000000000ab01");
+        assert_eq!(packed_value(&word, 0, 1), [0x01]);
+        let value = decode_storage_value(&DynSolType::Bool, packed_value(&word, 0, 1)).unwrap();
+        assert_eq!(value, DynSolValue::Bool(true));
+        let value = packed_value(&word, 1, 8);
+        assert_eq!(
+            decode_storage_value(&DynSolType::Uint(64), value).unwrap(),
+            DynSolValue::Uint(U256::from(0xab), 64)
+        );
+        assert_eq!(
+            decode_storage_value(&DynSolType::FixedBytes(8), value).unwrap(),
+            DynSolValue::FixedBytes(B256::right_padding_from(&[0, 0, 0, 0, 0, 0, 0, 0xab]), 8)
+        );
+        let value = decode_storage_value(&DynSolType::Int(16), packed_value(&word, 9, 2)).unwrap();
+        assert_eq!(value.as_int().unwrap().0.to_string(), "-100");
+        assert!(decode_storage_value(&DynSolType::String, &[]).is_err());
+    }
+}
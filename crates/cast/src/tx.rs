@@ -2,8 +2,8 @@ use crate::errors::FunctionSignatureError;
 use alloy_json_abi::Function;
 use alloy_primitives::{Address, U256};
 use ethers_core::types::{
-    transaction::eip2718::TypedTransaction, Eip1559TransactionRequest, NameOrAddress,
-    TransactionRequest,
+    transaction::{eip2718::TypedTransaction, eip2930::Eip2930TransactionRequest},
+    AccessList, Eip1559TransactionRequest, NameOrAddress, TransactionRequest,
 };
 use ethers_providers::Middleware;
 use eyre::{eyre, Result};
@@ -148,6 +148,24 @@ impl<'a, M: Middleware> TxBuilder<'a, M> {
         self
     }
 
+    /// Set access list, turns legacy transactions into EIP-2930 transactions
+    pub fn set_access_list(&mut self, v: AccessList) -> &mut Self {
+        if let TypedTransaction::Legacy(tx) = &self.tx {
+            self.tx = Eip2930TransactionRequest::new(tx.clone(), v).into();
+        } else {
+            self.tx.set_access_list(v);
+        }
+        self
+    }
+
+    /// Set access list, if `v` is not None
+    pub fn access_list(&mut self, v: Option<AccessList>) -> &mut Self {
+        if let Some(value) = v {
+            self.set_access_list(value);
+        }
+        self
+    }
+
     /// Set etherscan API key. Used to look up function signature buy name
     pub fn set_etherscan_api_key(&mut self, v: String) -> &mut Self {
         self.etherscan_api_key = Some(v);
@@ -276,7 +294,10 @@ mod tests {
     use crate::TxBuilder;
     use alloy_primitives::{Address, U256};
     use async_trait::async_trait;
-    use ethers_core::types::{transaction::eip2718::TypedTransaction, NameOrAddress, H160};
+    use ethers_core::types::{
+        transaction::eip2718::TypedTransaction, AccessList, AccessListItem, NameOrAddress, H160,
+        H256,
+    };
     use ethers_providers::{JsonRpcClient, Middleware, ProviderError};
     use foundry_common::types::ToEthers;
     use foundry_config::NamedChain;
@@ -383,6 +404,32 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn builder_access_list() -> eyre::Result<()> {
+        let provider = MyProvider {};
+        let access_list = AccessList(vec![AccessListItem {
+            address: H160::from_str(ADDR_1).unwrap(),
+            storage_keys: vec![H256::from_low_u64_be(2)],
+        }]);
+
+        let mut builder =
+            TxBuilder::new(&provider, "a.eth", Some("b.eth"), NamedChain::Mainnet, false).await?;
+        builder.gas_price(Some(U256::from(34u32))).access_list(Some(access_list.clone()));
+        let (tx, _) = builder.build();
+        assert!(matches!(tx, TypedTransaction::Eip1559(_)));
+        assert_eq!(tx.access_list(), Some(&access_list));
+
+        // legacy transactions become EIP-2930 transactions, with their gas price
+        let mut builder =
+            TxBuilder::new(&provider, "a.eth", Some("b.eth"), NamedChain::Mainnet, true).await?;
+        builder.gas_price(Some(U256::from(34u32))).access_list(Some(access_list.clone()));
+        let (tx, _) = builder.build();
+        assert!(matches!(tx, TypedTransaction::Eip2930(_)));
+        assert_eq!(tx.access_list(), Some(&access_list));
+        assert_eq!(tx.gas_price().unwrap().as_u32(), 34);
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn builder_args() -> eyre::Result<()> {
         let provider = MyProvider {};
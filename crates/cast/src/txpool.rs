@@ -0,0 +1,219 @@
+//! Nonces and pooled transactions of accounts, see `cast nonce` and `cast pending`.
+
+use crate::{calldata::format_units, Cast};
+use alloy_primitives::{Address, B256, U256};
+use ethers_core::types::{BlockNumber, NameOrAddress, Transaction};
+use ethers_providers::{JsonRpcError, Middleware, MiddlewareError};
+use eyre::Result;
+use foundry_common::types::{ToAlloy, ToEthers};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write};
+
+/// A transaction of an account in the pool of the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolTransaction {
+    pub nonce: u64,
+    pub hash: B256,
+    pub to: Option<Address>,
+    pub value: U256,
+    /// The gas price of legacy transactions, the max fee per gas of EIP-1559 ones
+    pub gas_price: Option<U256>,
+    /// Whether the transaction can't be mined yet, because of a gap in the nonces
+    pub queued: bool,
+}
+
+/// The nonces and the pooled transactions of an account.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountPool {
+    pub address: Address,
+    /// The nonce at the latest block, i.e. the number of mined transactions of the account
+    pub confirmed_nonce: u64,
+    /// The nonce of the next transaction of the account, after its pending transactions
+    pub next_nonce: u64,
+    /// The transactions in the pool sorted by nonce, `None` if the node doesn't expose its pool
+    pub transactions: Option<Vec<PoolTransaction>>,
+}
+
+/// The response of `txpool_contentFrom`.
+#[derive(Debug, Default, Deserialize)]
+struct AccountContent {
+    #[serde(default)]
+    pending: BTreeMap<String, Transaction>,
+    #[serde(default)]
+    queued: BTreeMap<String, Transaction>,
+}
+
+/// Returns true if the error response of a request at the `pending` block means that the endpoint
+/// doesn't support the tag.
+///
+/// Only invalid params errors, and errors saying that the state isn't available, which are about
+/// the `pending` tag are matched.
+pub fn is_pending_unsupported_error(err: &JsonRpcError) -> bool {
+    let message = err.message.to_lowercase();
+    message.contains("pending") &&
+        (err.code == -32602 ||
+            ["not available", "not supported", "unsupported", "invalid block"]
+                .iter()
+                .any(|pattern| message.contains(pattern)))
+}
+
+/// Returns true if the error response of a request means that the endpoint doesn't implement the
+/// method, the `Method not found` and `Method not supported` errors of EIP-1474.
+pub fn is_method_unsupported_error(err: &JsonRpcError) -> bool {
+    matches!(err.code, -32601 | -32604)
+}
+
+/// Returns the nonce following the consecutive nonces of the pending transactions from `nonce`.
+pub fn next_usable_nonce(nonce: u64, pending: impl IntoIterator<Item = u64>) -> u64 {
+    let mut pending = pending.into_iter().collect::<Vec<_>>();
+    pending.sort_unstable();
+    pending.into_iter().fold(nonce, |next, nonce| if nonce == next { next + 1 } else { next })
+}
+
+impl<M: Middleware> Cast<M>
+where
+    M::Error: 'static,
+{
+    /// Returns the nonce of the account at the `pending` block, i.e. the nonce of its next
+    /// transaction, or at the latest block if the endpoint doesn't support the `pending` tag.
+    pub async fn pending_nonce<T: Into<NameOrAddress> + Send + Sync>(&self, who: T) -> Result<u64> {
+        let who = who.into();
+        let pending = Some(BlockNumber::Pending.into());
+        match self.provider.get_transaction_count(who.clone(), pending).await {
+            Ok(nonce) => Ok(nonce.to_alloy().to()),
+            Err(err) if err.as_error_response().is_some_and(is_pending_unsupported_error) => {
+                tracing::debug!(%err, "pending nonce unavailable, using the latest one");
+                self.nonce(who, None).await
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Returns the confirmed and the next nonce of the account, and its transactions in the pool
+    /// of the node.
+    ///
+    /// The transactions of the account come from `txpool_contentFrom`, or `txpool_content` if the
+    /// endpoint only implements the latter.
+    pub async fn account_pool(&self, address: Address) -> Result<AccountPool> {
+        let confirmed_nonce = self.nonce(address.to_ethers(), None).await?;
+        let pending_nonce = self.pending_nonce(address.to_ethers()).await?;
+        let transactions = self.pool_transactions(address).await?;
+        let pending = transactions.iter().flatten().filter(|tx| !tx.queued).map(|tx| tx.nonce);
+        Ok(AccountPool {
+            address,
+            confirmed_nonce,
+            next_nonce: next_usable_nonce(pending_nonce.max(confirmed_nonce), pending),
+            transactions,
+        })
+    }
+
+    async fn pool_transactions(&self, address: Address) -> Result<Option<Vec<PoolTransaction>>> {
+        let content = match self.provider.request("txpool_contentFrom", [address]).await {
+            Ok(content) => content,
+            Err(err) if err.as_error_response().is_some_and(is_method_unsupported_error) => {
+                match self.provider.txpool_content().await {
+                    Ok(mut content) => AccountContent {
+                        pending: content.pending.remove(&address.to_ethers()).unwrap_or_default(),
+                        queued: content.queued.remove(&address.to_ethers()).unwrap_or_default(),
+                    },
+                    Err(err)
+                        if err.as_error_response().is_some_and(is_method_unsupported_error) =>
+                    {
+                        return Ok(None)
+                    }
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let AccountContent { pending, queued } = content;
+        let pending = pending.into_values().map(|tx| (tx, false));
+        let mut transactions = pending
+            .chain(queued.into_values().map(|tx| (tx, true)))
+            .map(|(tx, queued)| PoolTransaction {
+                nonce: tx.nonce.to_alloy().to(),
+                hash: tx.hash.to_alloy(),
+                to: tx.to.map(|to| to.to_alloy()),
+                value: tx.value.to_alloy(),
+                gas_price: tx.max_fee_per_gas.or(tx.gas_price).map(|price| price.to_alloy()),
+                queued,
+            })
+            .collect::<Vec<_>>();
+        transactions.sort_by_key(|tx| tx.nonce);
+        Ok(Some(transactions))
+    }
+}
+
+impl AccountPool {
+    /// Returns the nonces and the transactions in the pool, one per line.
+    pub fn pretty(&self) -> String {
+        let mut out =
+            format!("Confirmed nonce: {}\nNext nonce: {}\n", self.confirmed_nonce, self.next_nonce);
+        let Some(transactions) = &self.transactions else {
+            out.push_str("Pooled transactions: not supported by this RPC, it doesn't implement `txpool_content`");
+            return out
+        };
+        if transactions.is_empty() {
+            out.push_str("No pooled transactions");
+            return out
+        }
+        for (queued, title) in [(false, "Pending"), (true, "Queued")] {
+            let mut transactions = transactions.iter().filter(|tx| tx.queued == queued).peekable();
+            if transactions.peek().is_none() {
+                continue
+            }
+            let _ = write!(out, "\n{title} transactions:");
+            for tx in transactions {
+                let to = tx.to.map_or_else(|| "contract creation".to_string(), |to| to.to_string());
+                let _ = write!(out, "\n  {} {} -> {to} value {}", tx.nonce, tx.hash, tx.value);
+                if let Some(gas_price) = tx.gas_price {
+                    let _ = write!(out, " gas price {} gwei", format_units(gas_price, 9));
+                }
+            }
+            out.push('\n');
+        }
+        out.pop();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_compute_next_usable_nonce() {
+        assert_eq!(next_usable_nonce(3, []), 3);
+        assert_eq!(next_usable_nonce(3, [4, 3, 5]), 6);
+        // the transactions after the gap are stuck
+        assert_eq!(next_usable_nonce(3, [3, 5, 6]), 4);
+        // the node already counted some of the pending transactions
+        assert_eq!(next_usable_nonce(5, [3, 4, 5]), 6);
+    }
+
+    fn error(code: i64, message: &str) -> JsonRpcError {
+        JsonRpcError { code, message: message.to_string(), data: None }
+    }
+
+    #[test]
+    fn can_classify_pending_errors() {
+        assert!(is_pending_unsupported_error(&error(-32000, "pending block is not available")));
+        assert!(is_pending_unsupported_error(&error(-32602, "invalid block tag: pending")));
+        assert!(!is_pending_unsupported_error(&error(-32602, "invalid address")));
+        assert!(!is_pending_unsupported_error(&error(-32000, "header not found")));
+        assert!(!is_pending_unsupported_error(&error(-32005, "too many pending requests")));
+    }
+
+    #[test]
+    fn can_classify_unsupported_methods() {
+        assert!(is_method_unsupported_error(&error(
+            -32601,
+            "the method txpool_contentFrom does not exist/is not available"
+        )));
+        assert!(is_method_unsupported_error(&error(-32604, "Method not supported")));
+        assert!(!is_method_unsupported_error(&error(-32000, "txpool is not available")));
+        assert!(!is_method_unsupported_error(&error(-32005, "request rate exceeded")));
+    }
+}
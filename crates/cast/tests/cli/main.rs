@@ -1,8 +1,15 @@
 //! Contains various tests for checking cast commands
 
-use foundry_common::rpc::{next_http_rpc_endpoint, next_ws_rpc_endpoint};
-use foundry_test_utils::{casttest, util::OutputExt};
-use std::{io::Write, path::Path};
+use anvil::{spawn, Hardfork, NodeConfig};
+use ethers_providers::Middleware;
+use foundry_common::rpc::{
+    next_http_archive_rpc_endpoint, next_http_rpc_endpoint, next_ws_rpc_endpoint,
+};
+use foundry_test_utils::{
+    casttest,
+    util::{OutputExt, TestCommand},
+};
+use std::{fs, io::Write, path::Path};
 
 // tests `--help` is printed to std out
 casttest!(print_help, |_prj, cmd| {
@@ -71,6 +78,91 @@ casttest!(wallet_address_keystore_with_password_file, |_prj, cmd| {
     assert!(out.contains("0xeC554aeAFE75601AaAb43Bd4621A22284dB566C2"));
 });
 
+// tests that a key derived from a mnemonic can be imported, listed and exported
+casttest!(wallet_import_mnemonic_and_export, |prj, cmd| {
+    let keystore_dir = prj.root().join("keystores");
+    let keystore_dir = keystore_dir.to_str().unwrap();
+    let password_file = prj.root().join("password");
+    fs::write(&password_file, "secret\n").unwrap();
+    let password_file = password_file.to_str().unwrap();
+
+    cmd.args([
+        "wallet",
+        "import",
+        "deployer",
+        "--keystore-dir",
+        keystore_dir,
+        "--password-file",
+        password_file,
+        "--mnemonic",
+        "test test test test test test test test test test test junk",
+        "--mnemonic-index",
+        "1",
+    ]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("0x70997970C51812dc3A010C7d01b50e0d17dc79C8"), "{out}");
+
+    cmd.cast_fuse().args(["wallet", "list", "--keystore-dir", keystore_dir, "--detailed"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "deployer (0x70997970C51812dc3A010C7d01b50e0d17dc79C8)");
+
+    cmd.cast_fuse().args([
+        "wallet",
+        "export",
+        "deployer",
+        "--keystore-dir",
+        keystore_dir,
+        "--password-file",
+        password_file,
+        "--unsafe",
+    ]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d"
+    );
+
+    // a wrong password fails and leaves the keystore usable
+    let wrong_password_file = prj.root().join("wrong-password");
+    fs::write(&wrong_password_file, "wrong").unwrap();
+    cmd.cast_fuse().args([
+        "wallet",
+        "export",
+        "deployer",
+        "--keystore-dir",
+        keystore_dir,
+        "--password-file",
+        wrong_password_file.to_str().unwrap(),
+        "--unsafe",
+    ]);
+    cmd.assert_err();
+
+    // re-encrypt the key with a new password
+    let exported = prj.root().join("exported");
+    cmd.cast_fuse().args([
+        "wallet",
+        "export",
+        "deployer",
+        "--keystore-dir",
+        keystore_dir,
+        "--password-file",
+        password_file,
+        "--out",
+        exported.to_str().unwrap(),
+        "--new-password-file",
+        wrong_password_file.to_str().unwrap(),
+    ]);
+    cmd.assert_non_empty_stdout();
+
+    cmd.cast_fuse().args([
+        "wallet",
+        "address",
+        "--keystore",
+        exported.to_str().unwrap(),
+        "--password-file",
+        wrong_password_file.to_str().unwrap(),
+    ]);
+    assert!(cmd.stdout_lossy().contains("0x70997970C51812dc3A010C7d01b50e0d17dc79C8"));
+});
+
 // tests that `cast wallet sign message` outputs the expected signature
 casttest!(wallet_sign_message_utf8_data, |_prj, cmd| {
     cmd.args([
@@ -97,6 +189,23 @@ casttest!(wallet_sign_message_hex_data, |_prj, cmd| {
     assert_eq!(output.trim(), "0x23a42ca5616ee730ff3735890c32fc7b9491a9f633faca9434797f2c845f5abf4d9ba23bd7edb8577acebaa3644dc5a4995296db420522bb40060f1693c33c9b1c");
 });
 
+// tests that `cast wallet sign-auth` outputs the expected RLP encoded authorization
+casttest!(wallet_sign_auth, |_prj, cmd| {
+    cmd.args([
+        "wallet",
+        "sign-auth",
+        "--private-key",
+        "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "--nonce",
+        "0",
+        "--chain-id",
+        "1",
+        "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+    ]);
+    let output = cmd.stdout_lossy();
+    assert_eq!(output.trim(), "0xf85a01945fbdb2315678afecb367f032d93f642f64180aa38001a045b3739197963d9da878d8908608160a246a7cf6f779a5f40dc034154d3dd218a030a0340752af1b2ec8c8eb957cea1c39c606cae1e6e119dda359d58b55b10c1f");
+});
+
 // tests that `cast wallet sign typed-data` outputs the expected signature, given a JSON string
 casttest!(wallet_sign_typed_data_string, |_prj, cmd| {
     cmd.args([
@@ -131,6 +240,41 @@ casttest!(wallet_sign_typed_data_file, |_prj, cmd| {
     assert_eq!(output.trim(), "0x06c18bdc8163219fddc9afaf5a0550e381326474bb757c86dc32317040cf384e07a2c72ce66c1a0626b6750ca9b6c035bf6f03e7ed67ae2d1134171e9085c0b51b");
 });
 
+// tests that `cast wallet sign --data-file` signs the EIP-712 example like the spec, and that
+// `cast wallet verify-typed-data` checks the signer
+casttest!(wallet_sign_and_verify_typed_data_file, |_prj, cmd| {
+    let mail = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/eip712_mail.json");
+    let mail = mail.to_str().unwrap();
+    // `keccak256("cow")`
+    let cow = "0xc85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4";
+    cmd.args(["wallet", "sign", "--private-key", cow, "--data-file", mail, "--split"]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "\
+v: 28
+r: 0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d
+s: 0x07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562"
+    );
+
+    cmd.cast_fuse().args(["wallet", "sign", "--private-key", cow, "--data-file", mail]);
+    let signature = cmd.stdout_lossy().trim().to_string();
+    let signer = "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826";
+    cmd.cast_fuse().args(["wallet", "verify-typed-data", mail, &signature, "--address", signer]);
+    assert!(cmd.stdout_lossy().contains("Validation succeeded"));
+
+    // the signature doesn't hold for the document retargeted to another chain
+    cmd.cast_fuse().args(["wallet", "verify-typed-data", mail, &signature, "--address", signer]);
+    cmd.args(["--domain-chain-id", "5"]);
+    assert!(cmd.stdout_lossy().contains("Validation failed"));
+    cmd.cast_fuse().args(["wallet", "sign", "--private-key", cow, "--data-file", mail]);
+    cmd.args(["--domain-chain-id", "5"]);
+    let retargeted = cmd.stdout_lossy().trim().to_string();
+    assert_ne!(retargeted, signature);
+    cmd.cast_fuse().args(["wallet", "verify-typed-data", mail, &retargeted, "--address", signer]);
+    cmd.args(["--domain-chain-id", "5"]);
+    assert!(cmd.stdout_lossy().contains("Validation succeeded"));
+});
+
 // tests that `cast estimate` is working correctly.
 casttest!(estimate_function_gas, |_prj, cmd| {
     let eth_rpc_url = next_http_rpc_endpoint();
@@ -331,6 +475,18 @@ casttest!(run_succeeds, |_prj, cmd| {
     assert!(!output.contains("Revert"));
 });
 
+casttest!(run_requires_existing_project, |prj, cmd| {
+    let project = prj.root().join("missing");
+    cmd.args([
+        "run",
+        "0x2d951c5c95d374263ca99ad9c20c9797fc714330a8037429a3aa4c83d456f845",
+        "--project",
+        project.to_str().unwrap(),
+    ]);
+    let output = cmd.stderr_lossy();
+    assert!(output.contains("invalid project path"), "{output}");
+});
+
 // tests that `cast --to-base` commands are working correctly.
 casttest!(to_base, |_prj, cmd| {
     let values = [
@@ -556,3 +712,1609 @@ casttest!(storage, |_prj, cmd| {
     cmd.cast_fuse().args(["storage", usdt, decimals_slot, "--rpc-url", &rpc]);
     assert_eq!(cmd.stdout_lossy().trim(), six);
 });
+
+// tests that the state overrides are sent to endpoints which support them
+casttest!(async call_with_state_overrides, |_prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let rpc = handle.http_endpoint();
+    let account = "0x000000000000000000000000000000000000dEaD";
+
+    // PUSH1 42 PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let code = format!("{account}:0x602a60005260206000f3");
+    cmd.args(["call", account, "f()(uint256)", "--override-code", &code, "--rpc-url", &rpc]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert_eq!(stdout.trim(), "42");
+    assert!(!stderr.contains("executing locally"), "{stderr}");
+});
+
+// tests that the transaction is executed locally if the endpoint doesn't support state overrides
+casttest!(async estimate_with_state_overrides_falls_back_to_local_execution, |_prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let rpc = handle.http_endpoint();
+    let account = "0x000000000000000000000000000000000000dEaD";
+
+    // reverts unless storage slot 0 is 1:
+    // PUSH1 0 SLOAD PUSH1 1 EQ PUSH1 13 JUMPI PUSH1 0 DUP1 REVERT JUMPDEST STOP
+    let code = format!("{account}:0x600054600114600d57600080fd5b00");
+    let state = format!("{account}:0:1");
+    cmd.args([
+        "estimate",
+        account,
+        "f()",
+        "--override-code",
+        &code,
+        "--override-state",
+        &state,
+        "--rpc-url",
+        &rpc,
+    ]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("executing locally"), "{stderr}");
+    let gas: u64 = stdout.trim().parse().unwrap();
+    assert!(gas > 21_000, "{gas}");
+
+    cmd.cast_fuse().args(["estimate", account, "f()", "--override-code", &code, "--rpc-url", &rpc]);
+    cmd.assert_err();
+});
+
+// tests that `cast decode-transaction` decodes raw transactions offline
+casttest!(decode_raw_transaction, |prj, cmd| {
+    let tx = "0xf8a9018504a817c80082ea6094000000000000000000000000000000000000dead80b844a9059cbb00000000000000000000000070997970c51812dc3a010c7d01b50e0d17dc79c8000000000000000000000000000000000000000000000000000000000000006425a0c9373c21a6fb476bdaea65c72719e8b5800e87549f34b049ef2cad962aba9d7da04ff617331b13fbaa752d6d97293ea2169bce950beda1e1fb42e2374d10ac243a";
+
+    cmd.args(["decode-transaction", tx]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("type                 0 (legacy)"), "{output}");
+    assert!(output.contains("from                 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"));
+    assert!(output.contains(
+        "hash                 0x87a373f56d5bd39b83658b07c8aebc83be98c742804fff10d846028c61cdc945"
+    ));
+
+    cmd.cast_fuse().args([
+        "decode-transaction",
+        tx,
+        "--sig",
+        "transfer(address,uint256)",
+        "--json",
+    ]);
+    let json: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(json["chainId"], "0x1");
+    assert_eq!(json["decodedInput"]["function"], "transfer(address,uint256)");
+    assert_eq!(json["decodedInput"]["args"][1], "100");
+
+    let abi = prj.root().join("abi.json");
+    fs::write(
+        &abi,
+        r#"[{"type":"function","name":"transfer","inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],"outputs":[{"type":"bool"}],"stateMutability":"nonpayable"}]"#,
+    )
+    .unwrap();
+    cmd.cast_fuse().args(["decode-transaction", tx, "--abi", abi.to_str().unwrap()]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("function             transfer(address,uint256)"), "{output}");
+
+    cmd.cast_fuse().args(["decode-transaction", "0x01c20102"]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("invalid `gasPrice` field"), "{err}");
+});
+
+// tests that `cast send --timeout` prints the hash and exits with a distinct code if the
+// transaction isn't mined in time
+casttest!(async send_times_out_when_not_mined, |_prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test().with_no_mining(true)).await;
+    let rpc = handle.http_endpoint();
+
+    cmd.args([
+        "send",
+        "0x000000000000000000000000000000000000dEaD",
+        "--private-key",
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        "--timeout",
+        "2",
+        "--rpc-url",
+        &rpc,
+    ]);
+    let output = cmd.unchecked_output();
+    assert_eq!(output.status.code(), Some(124));
+    let hash = String::from_utf8_lossy(&output.stdout);
+    assert!(hash.trim().starts_with("0x") && hash.trim().len() == 66, "{hash}");
+});
+
+// tests that `cast send --confirmations` waits for the blocks after the transaction's block
+casttest!(async send_waits_for_confirmations, |_prj, cmd| {
+    let (_api, handle) = spawn(
+        NodeConfig::test().with_blocktime(Some(std::time::Duration::from_secs(1))),
+    )
+    .await;
+    let rpc = handle.http_endpoint();
+
+    cmd.args([
+        "send",
+        "0x000000000000000000000000000000000000dEaD",
+        "--private-key",
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        "--confirmations",
+        "3",
+        "--json",
+        "--rpc-url",
+        &rpc,
+    ]);
+    let receipt: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let block = u64::from_str_radix(
+        receipt["blockNumber"].as_str().unwrap().trim_start_matches("0x"),
+        16,
+    )
+    .unwrap();
+    let head = handle.http_provider().get_block_number().await.unwrap().as_u64();
+    assert!(head >= block + 2, "receipt block {block}, head {head}");
+});
+
+// tests that `cast send --auto-bump` replaces the transaction with higher fees until it's mined
+casttest!(async send_bumps_fees_until_mined, |_prj, cmd| {
+    let (api, handle) = spawn(NodeConfig::test().with_no_mining(true)).await;
+    let rpc = handle.http_endpoint();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(3500)).await;
+        api.mine_one().await;
+    });
+
+    cmd.args([
+        "send",
+        "0x000000000000000000000000000000000000dEaD",
+        "--private-key",
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        "--legacy",
+        "--gas-price",
+        "2000000000",
+        "--auto-bump",
+        "50",
+        "--bump-interval",
+        "1",
+        "--timeout",
+        "20",
+        "--json",
+        "--rpc-url",
+        &rpc,
+    ]);
+    let receipt: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let gas_price = u64::from_str_radix(
+        receipt["effectiveGasPrice"].as_str().unwrap().trim_start_matches("0x"),
+        16,
+    )
+    .unwrap();
+    assert!(gas_price >= 3_000_000_000, "{gas_price}");
+});
+
+// tests that `cast send` keeps waiting if the node already knows the transaction
+casttest!(async send_waits_for_already_known_transaction, |_prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test().with_no_mining(true)).await;
+    let rpc = handle.http_endpoint();
+    let args = [
+        "send",
+        "0x000000000000000000000000000000000000dEaD",
+        "--private-key",
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        "--gas-limit",
+        "21000",
+        "--gas-price",
+        "3000000000",
+        "--priority-gas-price",
+        "1000000000",
+        "--nonce",
+        "0",
+        "--rpc-url",
+        &rpc,
+    ];
+
+    cmd.args(args).arg("--async");
+    let hash = cmd.stdout_lossy();
+
+    // the same transaction again
+    cmd.cast_fuse().args(args).args(["--timeout", "2"]);
+    let output = cmd.unchecked_output();
+    assert_eq!(output.status.code(), Some(124), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), hash.trim());
+});
+
+// tests that `cast interface` writes interfaces with the structs of an artifact
+casttest!(interface_from_artifact_with_structs, |prj, cmd| {
+    let artifact = prj.root().join("Vault.json");
+    fs::write(
+        &artifact,
+        r#"{
+            "abi": [
+                {
+                    "type": "function",
+                    "name": "deposit",
+                    "stateMutability": "nonpayable",
+                    "inputs": [
+                        {
+                            "name": "params",
+                            "type": "tuple",
+                            "internalType": "struct Vault.Deposit",
+                            "components": [
+                                { "name": "amount", "type": "uint256", "internalType": "uint256" },
+                                { "name": "data", "type": "bytes", "internalType": "bytes" }
+                            ]
+                        }
+                    ],
+                    "outputs": []
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let output = prj.root().join("src/IVault.sol");
+
+    cmd.args(["interface", artifact.to_str().unwrap(), "--name", "IVault", "--pragma", "^0.8.20"])
+        .args(["--output", output.to_str().unwrap()]);
+    assert!(cmd.stdout_lossy().contains("Saved interface at"));
+
+    let source = fs::read_to_string(output).unwrap();
+    assert!(source.contains("pragma solidity ^0.8.20;"), "{source}");
+    assert!(source.contains("interface IVault {"), "{source}");
+    assert!(source.contains("struct Deposit {"), "{source}");
+    assert!(source.contains("function deposit(Deposit calldata params) external;"), "{source}");
+});
+
+// tests that `cast storage` decodes the storage of a transparent proxy, deployed on a forked
+// chain, with the layout of its implementation
+casttest!(async storage_layout_of_proxy_implementation, |prj, cmd| {
+    prj.add_source(
+        "Proxy",
+        r#"
+contract Implementation {
+    uint256 public value;
+    mapping(address => uint256) public balances;
+
+    function set(uint256 v) external {
+        value = v;
+        balances[msg.sender] = v;
+    }
+}
+
+contract Proxy {
+    bytes32 constant IMPLEMENTATION_SLOT =
+        0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc;
+    bytes32 constant ADMIN_SLOT =
+        0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103;
+
+    constructor(address implementation, address admin) {
+        assembly {
+            sstore(IMPLEMENTATION_SLOT, implementation)
+            sstore(ADMIN_SLOT, admin)
+        }
+    }
+
+    fallback() external payable {
+        // calls of the admin are not forwarded to the implementation
+        address admin;
+        assembly {
+            admin := sload(ADMIN_SLOT)
+        }
+        require(msg.sender != admin, "admin cannot fallback to the implementation");
+        assembly {
+            calldatacopy(0, 0, calldatasize())
+            let ok := delegatecall(gas(), sload(IMPLEMENTATION_SLOT), 0, calldatasize(), 0, 0)
+            returndatacopy(0, 0, returndatasize())
+            switch ok
+            case 0 { revert(0, returndatasize()) }
+            default { return(0, returndatasize()) }
+        }
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let (_api, handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(next_http_archive_rpc_endpoint()))).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let sender = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    let admin = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+    let mut deploy = |contract: &str, args: &[&str]| {
+        cmd.forge_fuse()
+            .args(["create", contract, "--rpc-url", &endpoint, "--private-key", private_key])
+            .args(args);
+        let out = cmd.stdout_lossy();
+        let address = out.lines().find_map(|line| line.strip_prefix("Deployed to: "));
+        address.unwrap_or_else(|| panic!("{out}")).trim().to_string()
+    };
+    let implementation = deploy("src/Proxy.sol:Implementation", &[]);
+    let proxy = deploy("src/Proxy.sol:Proxy", &["--constructor-args", &implementation, admin]);
+
+    cmd.cast_fuse().args(["send", &proxy, "set(uint256)", "42"]).args([
+        "--rpc-url",
+        &endpoint,
+        "--private-key",
+        private_key,
+    ]);
+    cmd.assert_non_empty_stdout();
+
+    cmd.cast_fuse().args(["storage", &proxy, "--rpc-url", &endpoint]);
+    let output = cmd.unchecked_output();
+    let (stdout, stderr) =
+        (String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    assert!(stderr.contains("Detected an EIP-1967 proxy"), "{stderr}");
+    assert!(stderr.to_lowercase().contains(&implementation.to_lowercase()), "{stderr}");
+    let value =
+        stdout.lines().find(|line| line.contains("| value")).unwrap_or_else(|| panic!("{stdout}"));
+    assert!(value.contains("| 42 "), "{value}");
+
+    let balance = format!("balances[{sender}]");
+    cmd.cast_fuse().args(["storage", &proxy, "--rpc-url", &endpoint, "--slot-of", &balance]);
+    let out = cmd.stdout_lossy();
+    let row = out.lines().find(|line| line.contains(&balance)).unwrap_or_else(|| panic!("{out}"));
+    assert!(row.contains("| 42 "), "{row}");
+
+    // the proxy itself has no storage variables
+    cmd.cast_fuse().args(["storage", &proxy, "--rpc-url", &endpoint, "--follow-proxy=false"]);
+    let output = cmd.unchecked_output();
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Storage layout is empty."));
+
+    // the layout can be forced
+    cmd.cast_fuse().args(["storage", &proxy, "--rpc-url", &endpoint, "--impl", &implementation]);
+    let output = cmd.unchecked_output();
+    let (stdout, stderr) =
+        (String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    assert!(stderr.contains("Using the storage layout of"), "{stderr}");
+    let value =
+        stdout.lines().find(|line| line.contains("| value")).unwrap_or_else(|| panic!("{stdout}"));
+    assert!(value.contains("| 42 "), "{value}");
+});
+
+// tests that `cast storage-layout` decodes the storage of a local deployment of the registry
+casttest!(async storage_layout_of_local_deployment, |prj, cmd| {
+    prj.add_source(
+        "Vault",
+        r#"
+contract Vault {
+    enum Status { Closed, Open }
+    struct Config {
+        uint128 fee;
+        address admin;
+    }
+
+    int8 delta;
+    bool paused;
+    address owner;
+    Status status;
+    Config config;
+    uint64[] amounts;
+    string name;
+    mapping(address => uint256) balances;
+    uint256 public immutable created = block.number;
+
+    constructor() {
+        delta = -2;
+        paused = true;
+        owner = msg.sender;
+        status = Status.Open;
+        config = Config(3, msg.sender);
+        amounts.push(7);
+        amounts.push(8);
+        name = "vault";
+        balances[msg.sender] = 42;
+    }
+}
+
+contract VaultV2 is Vault {
+    uint256 cap;
+}
+
+contract VaultBroken {
+    address owner;
+    int8 delta;
+}
+"#,
+    )
+    .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let sender = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    cmd.forge_fuse()
+        .args(["create", "src/Vault.sol:Vault", "--rpc-url", &endpoint])
+        .args(["--private-key", private_key]);
+    let out = cmd.stdout_lossy();
+    let vault = out.lines().find_map(|line| line.strip_prefix("Deployed to: "));
+    let vault = vault.unwrap_or_else(|| panic!("{out}")).trim().to_string();
+
+    // the immutable differs from the artifact, the deployment is only found in the registry
+    cmd.cast_fuse().args(["storage-layout", &vault, "--project", "--rpc-url", &endpoint]);
+    let output = cmd.unchecked_output();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No contract of the project is deployed at"), "{stderr}");
+
+    let registry = prj.root().join("deployments/31337/Vault.json");
+    fs::create_dir_all(registry.parent().unwrap()).unwrap();
+    fs::write(
+        &registry,
+        format!(
+            r#"{{
+                "contractName": "Vault",
+                "artifact": "src/Vault.sol:Vault",
+                "address": "{vault}",
+                "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "blockNumber": 1,
+                "timestamp": 0,
+                "history": []
+            }}"#
+        ),
+    )
+    .unwrap();
+
+    cmd.cast_fuse().args(["storage-layout", &vault, "--project", "--rpc-url", &endpoint]);
+    let out = cmd.stdout_lossy();
+    // the cells of the row of the variable
+    let row = |out: &str, name: &str| {
+        let row = out.lines().map(|line| line.split('|').map(str::trim).collect::<Vec<_>>()).find(
+            |cells| cells.len() > 2 && cells[1] == name,
+        );
+        let cells = row.unwrap_or_else(|| panic!("{out}"));
+        cells[1..cells.len() - 1].iter().map(|cell| cell.to_string()).collect::<Vec<_>>()
+    };
+    assert_eq!(row(&out, "owner"), ["owner", "address", "0", "2", "20", "src/Vault.sol:Vault"]);
+    assert_eq!(row(&out, "balances")[2..4], ["5", "0"]);
+    assert!(!out.contains("config.fee"), "{out}");
+
+    cmd.cast_fuse()
+        .args(["storage-layout", &vault, "--project", "--values", "--rpc-url", &endpoint]);
+    let out = cmd.stdout_lossy();
+    for (name, value) in [
+        ("delta", "-2"),
+        ("paused", "true"),
+        ("owner", sender),
+        ("status", "1"),
+        ("config.fee", "3"),
+        ("config.admin", sender),
+        ("amounts", "length: 2"),
+        ("amounts[0]", "7"),
+        ("amounts[1]", "8"),
+        ("name", "\"vault\""),
+        ("balances", "-"),
+    ] {
+        assert_eq!(row(&out, name).last().unwrap(), value, "{out}");
+    }
+
+    let balance = format!("balances[{sender}]");
+    cmd.cast_fuse()
+        .args(["storage-layout", &vault, "--project", "--slot-of", &balance, "--json"])
+        .args(["--rpc-url", &endpoint]);
+    let rows: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(rows[0]["name"], balance);
+    assert_eq!(rows[0]["type"], "uint256");
+    assert_eq!(rows[0]["value"], "42");
+
+    cmd.cast_fuse()
+        .args(["storage-layout", &vault, "--project", "--diff", "VaultV2"])
+        .args(["--rpc-url", &endpoint]);
+    assert!(cmd.stdout_lossy().contains("The storage layouts are compatible."));
+
+    cmd.cast_fuse()
+        .args(["storage-layout", &vault, "--project", "--diff", "src/Vault.sol:VaultBroken"])
+        .args(["--rpc-url", &endpoint]);
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("error"));
+});
+
+// tests that `cast 4byte-decode` and the other selector lookups find selectors in the artifacts
+// of the project
+casttest!(four_byte_decode_from_local_artifacts, |prj, cmd| {
+    let artifact = prj.root().join("out/Secret.sol/Secret.json");
+    fs::create_dir_all(artifact.parent().unwrap()).unwrap();
+    fs::write(
+        &artifact,
+        r#"{
+            "abi": [
+                {
+                    "type": "function",
+                    "name": "madeUpSecretFunction",
+                    "stateMutability": "nonpayable",
+                    "inputs": [
+                        { "name": "amount", "type": "uint256" },
+                        { "name": "to", "type": "address" }
+                    ],
+                    "outputs": []
+                },
+                {
+                    "type": "event",
+                    "name": "MadeUpSecretEvent",
+                    "inputs": [{ "name": "amount", "type": "uint256", "indexed": false }],
+                    "anonymous": false
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let calldata = "0x2529a3ab\
+        000000000000000000000000000000000000000000000000000000000000002a\
+        000000000000000000000000f39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+    cmd.args(["4byte-decode", calldata, "--offline"]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "1) \"madeUpSecretFunction(uint256,address)\"\n\
+         42\n\
+         0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+    );
+
+    // unknown selectors aren't found offline
+    cmd.cast_fuse().args(["4byte-decode", "0xdeadbeef", "--offline"]);
+    cmd.assert_err();
+
+    cmd.cast_fuse().args(["--offline", "4byte", "0x2529a3ab"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "madeUpSecretFunction(uint256,address)");
+    cmd.cast_fuse().args(["4byte", "0xdeadbeef", "--offline"]);
+    cmd.assert_err();
+
+    let topic = "0x441c9fcd37031c6433233c10f6a0c9a8f1b1d396fa4788a89c9e36bb09784c3f";
+    cmd.cast_fuse().args(["4byte-event", topic, "--offline"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "MadeUpSecretEvent(uint256)");
+
+    cmd.cast_fuse().args(["pretty-calldata", calldata, "--offline"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("- madeUpSecretFunction(uint256,address)"), "{out}");
+    cmd.cast_fuse().args(["pretty-calldata", "0xdeadbeef", "--offline"]);
+    assert!(cmd.stdout_lossy().contains("Method: deadbeef"));
+});
+
+// tests that `cast mktx --blob` builds a blob transaction offline that `cast decode-transaction`
+// decodes
+casttest!(mktx_blob_transaction, |prj, cmd| {
+    let data = prj.root().join("data.txt");
+    fs::write(&data, "blob data").unwrap();
+
+    cmd.args([
+        "mktx",
+        "0x000000000000000000000000000000000000dEaD",
+        "--blob",
+        "--blob-file",
+        data.to_str().unwrap(),
+        "--max-fee-per-blob-gas",
+        "1gwei",
+        "--nonce",
+        "0",
+        "--gas-limit",
+        "21000",
+        "--gas-price",
+        "10gwei",
+        "--priority-gas-price",
+        "1gwei",
+        "--chain",
+        "1",
+        "--private-key",
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+    ]);
+    let output = cmd.unchecked_output();
+    assert!(output.status.success());
+    let raw_tx = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let versioned_hash = stderr
+        .lines()
+        .find_map(|line| line.strip_prefix("Blob versioned hash: "))
+        .expect("versioned hash is printed")
+        .to_string();
+    assert!(versioned_hash.starts_with("0x01"), "{versioned_hash}");
+
+    cmd.cast_fuse().args(["decode-transaction", &raw_tx, "--json"]);
+    let json: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(json["type"], "0x3");
+    assert!(json["from"]
+        .as_str()
+        .unwrap()
+        .eq_ignore_ascii_case("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"));
+    assert_eq!(json["blobVersionedHashes"][0], versioned_hash.as_str());
+
+    // a transaction has at most 6 blobs
+    fs::write(&data, vec![1; 126976 * 6 + 1]).unwrap();
+    cmd.cast_fuse().args([
+        "mktx",
+        "0x000000000000000000000000000000000000dEaD",
+        "--blob",
+        "--blob-file",
+        data.to_str().unwrap(),
+        "--chain",
+        "1",
+        "--private-key",
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+    ]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("need 7 blobs"), "{err}");
+});
+
+// tests that `cast creation-code` and `cast constructor-args` locate direct and factory
+// deployments without an explorer
+casttest!(async creation_code_and_constructor_args, |prj, cmd| {
+    prj.add_source(
+        "Creation",
+        r#"
+contract Token {
+    uint256 public supply;
+    string public name;
+
+    constructor(uint256 _supply, string memory _name) {
+        supply = _supply;
+        name = _name;
+    }
+}
+
+contract Factory {
+    Token public token;
+
+    function deploy() external {
+        token = new Token(7, "child");
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    let mut deploy = |contract: &str, args: &[&str]| {
+        cmd.forge_fuse()
+            .args(["create", contract, "--rpc-url", &endpoint, "--private-key", private_key])
+            .args(args);
+        let out = cmd.stdout_lossy();
+        let address = out.lines().find_map(|line| line.strip_prefix("Deployed to: "));
+        address.unwrap_or_else(|| panic!("{out}")).trim().to_string()
+    };
+    let token = deploy("src/Creation.sol:Token", &["--constructor-args", "1000", "token"]);
+    let factory = deploy("src/Creation.sol:Factory", &[]);
+    let artifact = prj.root().join("out/Creation.sol/Token.json");
+    let artifact = artifact.to_str().unwrap();
+
+    cmd.cast_fuse().args(["creation-code", &token, "--rpc-url", &endpoint, "--json"]);
+    let json: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(json["factory"], false);
+    assert_eq!(json["blockNumber"], 1);
+
+    cmd.cast_fuse().args(["constructor-args", &token, "--rpc-url", &endpoint, "--abi", artifact]);
+    let output = cmd.stdout_lossy();
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines[1..], ["1000", "\"token\""], "{output}");
+
+    cmd.cast_fuse().args(["send", &factory, "deploy()", "--rpc-url", &endpoint]).args([
+        "--private-key",
+        private_key,
+    ]);
+    cmd.assert_non_empty_stdout();
+    cmd.cast_fuse().args(["call", &factory, "token()(address)", "--rpc-url", &endpoint]);
+    let child = cmd.stdout_lossy().trim().to_string();
+
+    cmd.cast_fuse().args(["creation-code", &child, "--rpc-url", &endpoint, "--json"]);
+    let json: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(json["factory"], true);
+    assert!(json["creator"].as_str().unwrap().eq_ignore_ascii_case(&factory));
+
+    // without an artifact, the arguments are found by decoding the end of the creation code
+    let abi = prj.root().join("Token.abi.json");
+    let artifact: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(artifact).unwrap()).unwrap();
+    fs::write(&abi, artifact["abi"].to_string()).unwrap();
+    cmd.cast_fuse().args(["constructor-args", &child, "--rpc-url", &endpoint, "--json"]).args([
+        "--abi",
+        abi.to_str().unwrap(),
+    ]);
+    let json: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(json["decoded"][0]["value"], "7");
+    assert_eq!(json["decoded"][1]["name"], "_name");
+
+    // without an ABI or an explorer there is nothing to decode with
+    cmd.cast_fuse().args(["constructor-args", &child, "--rpc-url", &endpoint]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("No ABI to decode the constructor arguments"), "{err}");
+
+    cmd.cast_fuse().args([
+        "creation-code",
+        "0x000000000000000000000000000000000000dEaD",
+        "--rpc-url",
+        &endpoint,
+    ]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("has no code at the latest block"), "{err}");
+});
+
+// tests that `cast block --fields` selects fields of a block or of a range of blocks
+casttest!(async block_fields_and_ranges, |_prj, cmd| {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    api.mine_one().await;
+    api.mine_one().await;
+    api.mine_one().await;
+
+    cmd.args(["block", "2", "--fields", "number,gasUsed", "--rpc-url", &endpoint]);
+    assert_eq!(cmd.stdout_lossy().trim(), "2\t0");
+
+    cmd.cast_fuse().args(["block", "2", "--fields", "number,hash", "--json", "--rpc-url"]).arg(
+        &endpoint,
+    );
+    let json: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(json["number"], "0x2");
+    assert!(json["hash"].is_string());
+
+    cmd.cast_fuse().args(["block", "0..3", "--fields", "number", "--rpc-url", &endpoint]);
+    assert_eq!(cmd.stdout_lossy().lines().collect::<Vec<_>>(), ["0", "1", "2", "3"]);
+
+    // missing blocks are reported without aborting the range, unless --fail-fast
+    cmd.cast_fuse().args(["block", "2..5", "--fields", "number", "--rpc-url", &endpoint]);
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().collect::<Vec<_>>(), ["2", "3"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("block 4") && stderr.contains("2 of 4 blocks"), "{stderr}");
+
+    cmd.cast_fuse().args(["block", "3..5", "--fields", "number", "--fail-fast", "--rpc-url"]).arg(
+        &endpoint,
+    );
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("blocks couldn't be fetched"));
+
+    cmd.cast_fuse().args(["block", "1", "--fields", "transactions.from", "--rpc-url", &endpoint]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("use --full"), "{err}");
+});
+
+// tests that the access list can be compared and sent with `cast send --access-list`
+casttest!(async access_list_compare_and_send, |prj, cmd| {
+    prj.add_source(
+        "Counter",
+        r#"
+contract Counter {
+    uint256 public number;
+
+    function increment() external {
+        number++;
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let from = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    cmd.forge_fuse()
+        .args(["create", "src/Counter.sol:Counter", "--rpc-url", &endpoint, "--private-key"])
+        .arg(private_key);
+    let out = cmd.stdout_lossy();
+    let counter = out.lines().find_map(|line| line.strip_prefix("Deployed to: "));
+    let counter = counter.unwrap_or_else(|| panic!("{out}")).trim().to_string();
+
+    cmd.cast_fuse()
+        .args(["access-list", &counter, "increment()", "--json", "--from", from])
+        .args(["--rpc-url", &endpoint]);
+    let access_list = cmd.stdout_lossy();
+    let json: serde_json::Value = serde_json::from_str(&access_list).unwrap();
+    let address = json["accessList"][0]["address"].as_str().unwrap();
+    assert!(address.eq_ignore_ascii_case(&counter), "{access_list}");
+
+    cmd.cast_fuse()
+        .args(["access-list", &counter, "increment()", "--compare", "--from", from])
+        .args(["--rpc-url", &endpoint]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("gas without access list:") && out.contains("savings:"), "{out}");
+
+    cmd.cast_fuse()
+        .args(["send", &counter, "increment()", "--access-list", access_list.trim()])
+        .args(["--private-key", private_key, "--rpc-url", &endpoint]);
+    assert!(cmd.stdout_lossy().contains("status               1"));
+
+    cmd.cast_fuse().args(["call", &counter, "number()(uint256)", "--rpc-url", &endpoint]);
+    assert_eq!(cmd.stdout_lossy().trim(), "1");
+});
+
+// tests that the access list of `cast send` and `cast mktx` round-trips through the mined tx
+casttest!(async send_and_mktx_access_list, |prj, cmd| {
+    prj.add_source(
+        "Counter",
+        r#"
+contract Counter {
+    uint256 public number;
+
+    function increment() external {
+        number++;
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    cmd.forge_fuse()
+        .args(["create", "src/Counter.sol:Counter", "--rpc-url", &endpoint, "--private-key"])
+        .arg(private_key);
+    let out = cmd.stdout_lossy();
+    let counter = out.lines().find_map(|line| line.strip_prefix("Deployed to: "));
+    let counter = counter.unwrap_or_else(|| panic!("{out}")).trim().to_lowercase();
+
+    let access_list = serde_json::json!([{
+        "address": counter,
+        "storageKeys": ["0x0000000000000000000000000000000000000000000000000000000000000000"],
+    }]);
+    let path = prj.root().join("access-list.json");
+    std::fs::write(&path, access_list.to_string()).unwrap();
+
+    fn mined_access_list(cmd: &mut TestCommand, endpoint: &str, tx: &str) -> serde_json::Value {
+        cmd.cast_fuse().args(["tx", tx, "--json", "--rpc-url", endpoint]);
+        let tx: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+        assert_eq!(tx["type"], "0x2");
+        tx["accessList"].clone()
+    }
+
+    // from a file
+    cmd.cast_fuse()
+        .args(["send", &counter, "increment()", "--access-list", path.to_str().unwrap()])
+        .args(["--async", "--private-key", private_key, "--rpc-url", &endpoint]);
+    let tx_hash = cmd.stdout_lossy().trim().to_string();
+    assert_eq!(mined_access_list(&mut cmd, &endpoint, &tx_hash), access_list);
+
+    // from stdin, signed by `cast mktx` and published
+    let json = access_list.to_string();
+    cmd.cast_fuse()
+        .args(["mktx", &counter, "increment()", "--access-list", "-"])
+        .args(["--private-key", private_key, "--rpc-url", &endpoint])
+        .stdin(move |mut stdin| stdin.write_all(json.as_bytes()).unwrap());
+    let raw_tx = cmd.stdout_lossy().trim().to_string();
+    cmd.cast_fuse().args(["publish", &raw_tx, "--async", "--rpc-url", &endpoint]);
+    let tx_hash = cmd.stdout_lossy().trim().to_string();
+    assert_eq!(mined_access_list(&mut cmd, &endpoint, &tx_hash), access_list);
+
+    cmd.cast_fuse()
+        .args(["send", &counter, "increment()", "--access-list", path.to_str().unwrap()])
+        .args(["--legacy", "--private-key", private_key, "--rpc-url", &endpoint]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("cannot be used with"), "{err}");
+
+    cmd.cast_fuse()
+        .args(["send", &counter, "increment()", "--access-list", r#"[{"address":"0x01"}]"#])
+        .args(["--private-key", private_key, "--rpc-url", &endpoint]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("expected an access list as JSON"), "{err}");
+});
+
+// tests that `cast multicall` batches calls through Multicall3, or through a JSON-RPC batch
+casttest!(async multicall, |prj, cmd| {
+    prj.add_source(
+        "Multicall",
+        r#"
+contract Multicall3 {
+    struct Call3 {
+        address target;
+        bool allowFailure;
+        bytes callData;
+    }
+
+    struct Result {
+        bool success;
+        bytes returnData;
+    }
+
+    function aggregate3(Call3[] calldata calls) external payable returns (Result[] memory results) {
+        results = new Result[](calls.length);
+        for (uint256 i = 0; i < calls.length; i++) {
+            (bool success, bytes memory data) = calls[i].target.call(calls[i].callData);
+            require(success || calls[i].allowFailure, "Multicall3: call failed");
+            results[i] = Result(success, data);
+        }
+    }
+}
+
+contract Token {
+    function balanceOf(address account) external pure returns (uint256) {
+        return uint160(account) % 1000;
+    }
+
+    function paused() external pure returns (bool) {
+        revert("not supported");
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    let mut deploy = |contract: &str| {
+        cmd.forge_fuse()
+            .args(["create", contract, "--rpc-url", &endpoint, "--private-key", private_key]);
+        let out = cmd.stdout_lossy();
+        let address = out.lines().find_map(|line| line.strip_prefix("Deployed to: "));
+        address.unwrap_or_else(|| panic!("{out}")).trim().to_string()
+    };
+    let multicall = deploy("src/Multicall.sol:Multicall3");
+    let token = deploy("src/Multicall.sol:Token");
+
+    let account = "0x00000000000000000000000000000000000003e9";
+    let calls = serde_json::json!([
+        { "to": token, "sig": "balanceOf(address)(uint256)", "args": [account] },
+        { "to": token, "sig": "paused()(bool)", "allowFailure": true },
+    ]);
+    let path = prj.root().join("calls.json");
+    fs::write(&path, calls.to_string()).unwrap();
+
+    cmd.cast_fuse()
+        .args(["multicall", "--call", &token, "balanceOf(address)(uint256)", "0x07"])
+        .args(["--calls", path.to_str().unwrap(), "--multicall-address", &multicall, "--json"])
+        .args(["--rpc-url", &endpoint]);
+    let json: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(json[0]["decoded"], serde_json::json!(["7"]));
+    assert_eq!(json[1]["decoded"], serde_json::json!(["1"]));
+    assert_eq!(json[2]["success"], false);
+    assert!(json[2]["error"].as_str().unwrap().contains("not supported"), "{json}");
+
+    // without Multicall3 the calls are sent as a JSON-RPC batch, with the same results
+    cmd.cast_fuse()
+        .args(["multicall", "--call", &token, "balanceOf(address)(uint256)", "0x07"])
+        .args(["--calls", path.to_str().unwrap(), "--json", "--rpc-url", &endpoint]);
+    let output = cmd.unchecked_output();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("JSON-RPC batch"), "{stderr}");
+    let batched: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!((&batched[0], &batched[1]), (&json[0], &json[1]));
+    assert!(batched[2]["error"].as_str().unwrap().contains("not supported"), "{batched}");
+
+    // failed calls abort the batch unless they're allowed to fail
+    cmd.cast_fuse()
+        .args(["multicall", "--call", &token, "paused()(bool)", "--multicall-address", &multicall])
+        .args(["--rpc-url", &endpoint]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("Call 0 to") && err.contains("not supported"), "{err}");
+
+    cmd.cast_fuse()
+        .args(["multicall", "--call", &token, "paused()(bool)", "--multicall-address", &multicall])
+        .args(["--allow-failure", "--rpc-url", &endpoint]);
+    assert!(cmd.stdout_lossy().contains("failed: reverted: not supported"));
+});
+
+// tests that `cast calldata-decode` pretty-prints what `cast calldata` encodes
+casttest!(calldata_decode_pretty, |prj, cmd| {
+    let sig = "fill((address maker,uint256 amount)[] orders,bytes32 salt,uint256 deadline)";
+    let orders = "[(0xd8da6bf26964af9d7eed9e03e53415d37aa96045,1500000000000000000)]";
+    let salt = "0x0000000000000000000000000000000000000000000000000000000000000007";
+    cmd.args(["calldata", sig, orders, salt, "1700000000"]);
+    let calldata = cmd.stdout_lossy().trim().to_string();
+
+    cmd.cast_fuse().args(["calldata-decode", sig, &calldata, "--decimals", "18"]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        format!(
+            "\
+fill((address,uint256)[],bytes32,uint256)
+orders: [
+  [0]: (
+    maker: 0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045
+    amount: 1500000000000000000 (1.5 with 18 decimals)
+  )
+]
+salt: {salt}
+deadline: 1700000000"
+        )
+    );
+
+    // the function is looked up by its selector in the ABI
+    let abi = prj.root().join("abi.json");
+    fs::write(
+        &abi,
+        r#"[{"type":"function","name":"fill","stateMutability":"nonpayable","outputs":[],"inputs":[{"name":"orders","type":"tuple[]","components":[{"name":"maker","type":"address"},{"name":"amount","type":"uint256"}]},{"name":"salt","type":"bytes32"},{"name":"deadline","type":"uint256"}]}]"#,
+    )
+    .unwrap();
+    cmd.cast_fuse().args(["calldata-decode", "--abi", abi.to_str().unwrap(), &calldata, "--json"]);
+    let json: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([
+            [["0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045", "1500000000000000000"]],
+            salt,
+            "1700000000"
+        ])
+    );
+
+    cmd.cast_fuse().args(["calldata-decode", "--abi", abi.to_str().unwrap(), &calldata]);
+    cmd.args(["--scale", "deadline:3"]);
+    assert!(cmd.stdout_lossy().trim().ends_with("deadline: 1700000000 (1700000 with 3 decimals)"));
+});
+
+// tests that `cast tx --raw` and `cast receipt --raw` encode every transaction type
+casttest!(async tx_and_receipt_raw, |_prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let from = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    let dead = "0x000000000000000000000000000000000000dEaD";
+
+    let mut txs = Vec::new();
+    for (tx_type, args) in [("legacy", &["--legacy"][..]), ("0x02", &[][..])] {
+        cmd.cast_fuse()
+            .args(["send", dead, "--value", "1", "--async", "--private-key", private_key])
+            .args(args)
+            .args(["--rpc-url", &endpoint]);
+        txs.push((tx_type, cmd.stdout_lossy().trim().to_string()));
+    }
+    let request = serde_json::json!({
+        "from": from,
+        "to": dead,
+        "type": "0x1",
+        "gasPrice": "0x77359400",
+        "accessList": [{
+            "address": dead,
+            "storageKeys": ["0x0000000000000000000000000000000000000000000000000000000000000001"],
+        }],
+    });
+    cmd.cast_fuse().args(["rpc", "eth_sendTransaction", &request.to_string(), "--rpc-url"]);
+    cmd.arg(&endpoint);
+    let tx_hash: String = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    txs.push(("0x01", tx_hash));
+
+    for (tx_type, tx_hash) in txs {
+        cmd.cast_fuse().args(["tx", &tx_hash, "--raw", "--rpc-url", &endpoint]);
+        let raw_tx = cmd.stdout_lossy().trim().to_string();
+        cmd.cast_fuse().args(["keccak", &raw_tx]);
+        assert_eq!(cmd.stdout_lossy().trim(), tx_hash, "{tx_type}: {raw_tx}");
+
+        cmd.cast_fuse().args(["tx", &tx_hash, "raw", "--rpc-url", &endpoint]);
+        assert_eq!(cmd.stdout_lossy().trim(), raw_tx);
+
+        cmd.cast_fuse().args(["receipt", &tx_hash, "--raw", "--rpc-url", &endpoint]);
+        let raw_receipt = cmd.stdout_lossy().trim().to_string();
+        match tx_type {
+            // a list of 4 items with the bloom doesn't fit in a short list header
+            "legacy" => assert!(raw_receipt.starts_with("0xf9"), "{raw_receipt}"),
+            prefix => assert!(raw_receipt.starts_with(&format!("{prefix}f9")), "{raw_receipt}"),
+        }
+        // status, cumulative gas used and the bloom, with no logs
+        let rlp = &raw_receipt[if tx_type == "legacy" { 2 } else { 4 }..];
+        assert!(rlp.ends_with("c0"), "{raw_receipt}");
+        assert_eq!(&rlp[6..8], "01", "{raw_receipt}");
+    }
+
+    let missing = format!("0x{}", "0".repeat(64));
+    cmd.cast_fuse().args(["receipt", &missing, "--raw", "--async", "--rpc-url", &endpoint]);
+    assert!(cmd.stderr_lossy().contains("tx not found"));
+});
+
+// tests that `cast estimate` and `cast call --trace` run at the state of an older block
+casttest!(async estimate_and_trace_at_block, |prj, cmd| {
+    prj.add_source(
+        "Counter",
+        r#"
+contract Counter {
+    uint256 public number;
+
+    function increment() external {
+        require(number < 2, "too many increments");
+        number++;
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    cmd.forge_fuse()
+        .args(["create", "src/Counter.sol:Counter", "--rpc-url", &endpoint, "--private-key"])
+        .arg(private_key);
+    let out = cmd.stdout_lossy();
+    let counter = out.lines().find_map(|line| line.strip_prefix("Deployed to: "));
+    let counter = counter.unwrap_or_else(|| panic!("{out}")).trim().to_string();
+
+    let increment = |cmd: &mut TestCommand| {
+        cmd.cast_fuse()
+            .args(["send", &counter, "increment()", "--private-key", private_key])
+            .args(["--rpc-url", &endpoint]);
+        cmd.assert_non_empty_stdout();
+        cmd.cast_fuse().args(["block-number", "--rpc-url", &endpoint]);
+        cmd.stdout_lossy().trim().to_string()
+    };
+    let block = increment(&mut cmd);
+    increment(&mut cmd);
+
+    // the counter can't be incremented anymore at the latest block
+    cmd.cast_fuse().args(["estimate", &counter, "increment()", "--rpc-url", &endpoint]);
+    assert!(cmd.stderr_lossy().contains("too many increments"));
+    cmd.cast_fuse().args(["estimate", &counter, "increment()", "--block", &block]);
+    cmd.args(["--rpc-url", &endpoint]);
+    assert!(cmd.stdout_lossy().trim().parse::<u64>().unwrap() > 21_000);
+
+    let one = format!("0x{:0>64}", 1);
+    cmd.cast_fuse().args(["call", &counter, "number()", "--block", &block, "--rpc-url", &endpoint]);
+    assert_eq!(cmd.stdout_lossy().trim(), one);
+    cmd.cast_fuse().args(["call", &counter, "number()", "--trace", "--block", &block]);
+    cmd.args(["--rpc-url", &endpoint]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("Traces:") && out.contains(&one), "{out}");
+    assert!(out.contains("Transaction successfully executed."), "{out}");
+
+    cmd.cast_fuse().args(["call", &counter, "increment()", "--trace", "--block", &block]);
+    cmd.args(["--rpc-url", &endpoint]);
+    assert!(cmd.stdout_lossy().contains("Transaction successfully executed."));
+});
+
+// tests that `cast gas-price --history` suggests the fees `cast send` uses by default
+casttest!(async gas_price_history, |_prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let dead = "0x000000000000000000000000000000000000dEaD";
+
+    for _ in 0..3 {
+        cmd.cast_fuse()
+            .args(["send", dead, "--value", "1", "--private-key", private_key])
+            .args(["--rpc-url", &endpoint]);
+        cmd.assert_non_empty_stdout();
+    }
+
+    cmd.cast_fuse().args(["gas-price", "--history", "3", "--json", "--rpc-url", &endpoint]);
+    let summary: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let blocks = summary["blocks"].as_array().unwrap();
+    assert_eq!(blocks.len(), 3);
+    assert_eq!(blocks[2]["number"], 3);
+    assert_eq!(blocks[0]["percentiles"].as_array().unwrap().len(), 5);
+
+    cmd.cast_fuse()
+        .args(["mktx", dead, "--value", "1", "--private-key", private_key])
+        .args(["--rpc-url", &endpoint]);
+    let raw_tx = cmd.stdout_lossy().trim().to_string();
+    cmd.cast_fuse().args(["decode-transaction", &raw_tx, "--json"]);
+    let tx: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(summary["suggestion"]["maxFeePerGas"], tx["maxFeePerGas"]);
+    assert_eq!(summary["suggestion"]["maxPriorityFeePerGas"], tx["maxPriorityFeePerGas"]);
+
+    cmd.cast_fuse().args(["gas-price", "--history", "2", "--at", "1", "--rpc-url", &endpoint]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("| 0     |") && out.contains("| 1     |"), "{out}");
+    assert!(!out.contains("| 2     |"), "{out}");
+    assert!(out.contains("Suggested maxPriorityFeePerGas:"), "{out}");
+});
+
+// tests that `cast gas-price --history` samples the gas prices of the transactions before
+// EIP-1559
+casttest!(async gas_price_history_legacy, |_prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test().with_hardfork(Some(Hardfork::Berlin))).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    cmd.cast_fuse()
+        .args(["send", "0x000000000000000000000000000000000000dEaD", "--value", "1", "--legacy"])
+        .args(["--gas-price", "7gwei", "--private-key", private_key, "--rpc-url", &endpoint]);
+    cmd.assert_non_empty_stdout();
+
+    cmd.cast_fuse().args(["gas-price", "--history", "1", "--json", "--rpc-url", &endpoint]);
+    let summary: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let block = &summary["blocks"][0];
+    assert!(block.get("baseFeePerGas").is_none(), "{summary}");
+    assert_eq!(block["percentiles"][2], "0x1a13b8600");
+    cmd.cast_fuse().args(["gas-price", "--rpc-url", &endpoint]);
+    let gas_price: u64 = cmd.stdout_lossy().trim().parse().unwrap();
+    assert_eq!(summary["suggestion"]["gasPrice"], format!("{gas_price:#x}"));
+});
+
+// tests `cast ens` text records, avatars and resolvers, with a registry at the ENS address
+casttest!(async ens_records, |prj, cmd| {
+    prj.add_source(
+        "Ens",
+        r#"
+contract Registry {
+    mapping(bytes32 => address) public resolver;
+
+    function setResolver(bytes32 node, address addr) external {
+        resolver[node] = addr;
+    }
+}
+
+contract Resolver {
+    mapping(bytes32 => mapping(string => string)) internal texts;
+
+    function text(bytes32 node, string calldata key) external view returns (string memory) {
+        return texts[node][key];
+    }
+
+    function setText(bytes32 node, string calldata key, string calldata value) external {
+        texts[node][key] = value;
+    }
+
+    function supportsInterface(bytes4 id) external pure returns (bool) {
+        return id == 0x01ffc9a7 || id == 0x59d1d43c;
+    }
+}
+
+contract WildcardResolver {
+    function resolve(bytes calldata, bytes calldata) external pure returns (bytes memory) {
+        return abi.encode("wildcard");
+    }
+
+    function supportsInterface(bytes4 id) external pure returns (bool) {
+        return id == 0x9061b923;
+    }
+}
+
+contract OffchainResolver {
+    error OffchainLookup(address, string[], bytes, bytes4, bytes);
+
+    function resolve(bytes calldata, bytes calldata data) external view returns (bytes memory) {
+        string[] memory urls = new string[](1);
+        urls[0] = "http://127.0.0.1:1/{sender}/{data}.json";
+        revert OffchainLookup(address(this), urls, data, this.resolveWithProof.selector, data);
+    }
+
+    function resolveWithProof(bytes calldata res, bytes calldata) external pure returns (bytes memory) {
+        return res;
+    }
+
+    function supportsInterface(bytes4 id) external pure returns (bool) {
+        return id == 0x9061b923;
+    }
+}
+
+contract Nft {
+    function tokenURI(uint256) external pure returns (string memory) {
+        return 'data:application/json,{"image":"ipfs://QmAvatar"}';
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let registry = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+
+    let deploy = |cmd: &mut TestCommand, contract: &str| {
+        cmd.forge_fuse()
+            .args(["create", &format!("src/Ens.sol:{contract}"), "--rpc-url", &endpoint])
+            .args(["--private-key", private_key]);
+        let out = cmd.stdout_lossy();
+        let address = out.lines().find_map(|line| line.strip_prefix("Deployed to: "));
+        address.unwrap_or_else(|| panic!("{out}")).trim().to_string()
+    };
+    let send = |cmd: &mut TestCommand, to: &str, args: &[&str]| {
+        cmd.cast_fuse().args(["send", to]).args(args);
+        cmd.args(["--private-key", private_key, "--rpc-url", &endpoint]);
+        cmd.assert_non_empty_stdout();
+    };
+    let namehash = |cmd: &mut TestCommand, name: &str| {
+        cmd.cast_fuse().args(["namehash", name]);
+        cmd.stdout_lossy().trim().to_string()
+    };
+
+    let code = {
+        let deployed = deploy(&mut cmd, "Registry");
+        cmd.cast_fuse().args(["code", &deployed, "--rpc-url", &endpoint]);
+        cmd.stdout_lossy().trim().to_string()
+    };
+    cmd.cast_fuse().args(["rpc", "anvil_setCode", registry, &code, "--rpc-url", &endpoint]);
+    cmd.assert_non_empty_stdout();
+
+    let resolver = deploy(&mut cmd, "Resolver");
+    let wildcard = deploy(&mut cmd, "WildcardResolver");
+    let offchain = deploy(&mut cmd, "OffchainResolver");
+    let nft = deploy(&mut cmd, "Nft");
+    for (name, addr) in [("foo.eth", &resolver), ("wild.eth", &wildcard), ("off.eth", &offchain)] {
+        let node = namehash(&mut cmd, name);
+        send(&mut cmd, registry, &["setResolver(bytes32,address)", &node, addr]);
+    }
+    let node = namehash(&mut cmd, "foo.eth");
+    let sig = "setText(bytes32,string,string)";
+    send(&mut cmd, &resolver, &[sig, &node, "url", "https://foo.example"]);
+    send(&mut cmd, &resolver, &[sig, &node, "avatar", &format!("eip155:31337/erc721:{nft}/1")]);
+
+    let ens = |cmd: &mut TestCommand, args: &[&str]| {
+        cmd.cast_fuse().arg("ens").args(args).args(["--rpc-url", &endpoint]);
+        cmd.stdout_lossy().trim().to_string()
+    };
+    assert_eq!(ens(&mut cmd, &["text", "foo.eth", "url"]), "https://foo.example");
+    assert_eq!(ens(&mut cmd, &["text", "foo.eth", "com.twitter"]), "");
+    assert_eq!(ens(&mut cmd, &["avatar", "foo.eth"]), "https://ipfs.io/ipfs/QmAvatar");
+    assert_eq!(ens(&mut cmd, &["resolver", "foo.eth"]).to_lowercase(), resolver.to_lowercase());
+
+    // subnames are resolved by the extended resolver of their parent
+    let sub_resolver = ens(&mut cmd, &["resolver", "sub.wild.eth"]);
+    assert_eq!(sub_resolver.to_lowercase(), wildcard.to_lowercase());
+    assert!(cmd.stderr_lossy().contains("wildcard resolver of wild.eth"));
+    assert_eq!(ens(&mut cmd, &["text", "sub.wild.eth", "url"]), "wildcard");
+    // but not by plain resolvers
+    assert_eq!(ens(&mut cmd, &["resolver", "sub.foo.eth"]), "");
+
+    cmd.cast_fuse().args(["ens", "text", "foo.eth", "com.twitter", "--strict", "--rpc-url"]);
+    cmd.arg(&endpoint);
+    assert!(cmd.stderr_lossy().contains("foo.eth has no `com.twitter` text record"));
+
+    cmd.cast_fuse().args(["ens", "text", "off.eth", "url", "--no-ccip", "--rpc-url", &endpoint]);
+    assert!(cmd.stderr_lossy().contains("requires an offchain lookup (CCIP-Read)"));
+    cmd.cast_fuse().args(["ens", "text", "off.eth", "url", "--rpc-url", &endpoint]);
+    assert!(cmd.stderr_lossy().contains("the gateways of the offchain lookup failed"));
+});
+
+// tests the storage slots of `cast index` and its variants, and reading them
+casttest!(async index_slots, |_prj, cmd| {
+    cmd.args(["index-erc7201", "openzeppelin.storage.ERC20"]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "0x52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace00"
+    );
+    let owner = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045";
+    let spender = "0x0000000000000000000000000000000000000001";
+    let allowances = "0x52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace01";
+    cmd.cast_fuse().args(["index", "--nested", "address", owner, "address", spender, allowances]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "0xaf8441c3ce57ef6adc4285fef762056b94463aa3ee1a6acd1d37424a3cedd7b9"
+    );
+    cmd.cast_fuse().args(["index", "address", owner, "address", spender, allowances]);
+    assert!(cmd.stderr_lossy().contains("or `--nested`"));
+    cmd.cast_fuse().args(["index-1967", "admin"]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103"
+    );
+    cmd.cast_fuse().args(["index-array", "3", "5", "8"]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "0xc2575a0e9e593c00f959f8c92f12db2869c3395a3b0502d05e2516446f71f85c 8"
+    );
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let contract = "0x000000000000000000000000000000000000c0de";
+    let set_storage = |cmd: &mut TestCommand, slot: &str, value: &str| {
+        cmd.cast_fuse().args(["rpc", "anvil_setStorageAt", contract, slot, value]);
+        cmd.args(["--rpc-url", &endpoint]).assert_non_empty_stdout();
+    };
+    // a `uint64[]` of length 6 at slot 3, `[5]` is the second of its second slot
+    set_storage(
+        &mut cmd,
+        "0x0000000000000000000000000000000000000000000000000000000000000003",
+        "0x0000000000000000000000000000000000000000000000000000000000000006",
+    );
+    set_storage(
+        &mut cmd,
+        "0xc2575a0e9e593c00f959f8c92f12db2869c3395a3b0502d05e2516446f71f85c",
+        "0x00000000000000000000000000000000000000000000002a0000000000000007",
+    );
+    let read = ["--address", contract, "--rpc-url", &endpoint];
+    cmd.cast_fuse().args(["index-array", "3", "5", "8", "--type", "uint64"]).args(read);
+    assert_eq!(cmd.stdout_lossy().trim(), "42");
+    cmd.cast_fuse().args(["index-array", "3", "4", "8"]).args(read);
+    assert_eq!(cmd.stdout_lossy().trim(), "0x0000000000000007");
+    cmd.cast_fuse().args(["index-array", "3", "6", "8"]).args(read);
+    assert!(cmd.stderr_lossy().contains("out of bounds of the array of length 6"));
+    cmd.cast_fuse().args(["index-1967", "implementation"]).args(read);
+    assert_eq!(cmd.stdout_lossy().trim(), "0x0000000000000000000000000000000000000000");
+});
+
+// tests that `cast simulate` executes a bundle against a fork, stopping at the first failure
+// unless `--continue-on-failure`
+casttest!(async simulate_bundle, |prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let sender = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    let key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    cmd.args(["mktx", "0x000000000000000000000000000000000000bEEF", "--value", "2ether"]);
+    cmd.args(["--private-key", key, "--rpc-url", &endpoint]);
+    let raw = cmd.stdout_lossy().trim().to_string();
+
+    let transfer = |to: &str, from: &str| {
+        serde_json::json!({ "from": from, "to": to, "value": "0xde0b6b3a7640000" })
+    };
+    // an account without funds
+    let unfunded = "0x0000000000000000000000000000000000000001";
+    let bundle = serde_json::json!([
+        raw,
+        transfer("0x000000000000000000000000000000000000dEaD", sender),
+        transfer("0x000000000000000000000000000000000000dEaD", unfunded),
+        transfer("0x000000000000000000000000000000000000c0DE", sender),
+    ]);
+    let file = prj.root().join("bundle.json");
+    fs::write(&file, bundle.to_string()).unwrap();
+
+    cmd.cast_fuse().args(["simulate", "--file", file.to_str().unwrap(), "--fork-url", &endpoint]);
+    let output = cmd.unchecked_output();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[0] 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266 -> 0x000"), "{stdout}");
+    assert!(stdout.contains("0x000000000000000000000000000000000000dEaD: 0 -> "), "{stdout}");
+    assert!(stdout.contains("(+1000000000000000000)"), "{stdout}");
+    assert!(stdout.contains("(+2000000000000000000)"), "{stdout}");
+    assert!(stdout.contains("[2]") && stdout.contains("Failed"), "{stdout}");
+    assert!(!stdout.contains("[3]"), "{stdout}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("transaction [2] failed"), "{stderr}");
+
+    cmd.cast_fuse().args(["simulate", "--file", file.to_str().unwrap(), "--fork-url", &endpoint]);
+    cmd.args(["--continue-on-failure", "--json"]);
+    let output = cmd.unchecked_output();
+    let results: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let success = results.as_array().unwrap().iter().map(|result| result["success"].clone());
+    assert_eq!(success.collect::<Vec<_>>(), [true, true, false, true]);
+    assert!(results[0]["hash"].is_string() && results[1]["hash"].is_null());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 of the 4 transactions failed"));
+
+    // nothing was sent to the node
+    cmd.cast_fuse().args(["balance", "0x000000000000000000000000000000000000dEaD"]);
+    assert_eq!(cmd.args(["--rpc-url", &endpoint]).stdout_lossy().trim(), "0");
+});
+
+// tests that `cast nonce` counts the pending transactions and `cast pending` lists them
+casttest!(async nonce_and_pending, |_prj, cmd| {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let from = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    let dead = "0x000000000000000000000000000000000000dEaD";
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    cmd.args(["send", dead, "--value", "1", "--async", "--private-key", private_key]);
+    let tx_hash = cmd.args(["--rpc-url", &endpoint]).stdout_lossy().trim().to_string();
+
+    cmd.cast_fuse().args(["nonce", from, "--rpc-url", &endpoint]);
+    assert_eq!(cmd.stdout_lossy().trim(), "1");
+    cmd.cast_fuse().args(["nonce", from, "--latest", "--rpc-url", &endpoint]);
+    assert_eq!(cmd.stdout_lossy().trim(), "0");
+    cmd.cast_fuse().args(["nonce", from, "--latest", "--block", "1", "--rpc-url", &endpoint]);
+    cmd.assert_err();
+
+    cmd.cast_fuse().args(["pending", from, "--rpc-url", &endpoint]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("Confirmed nonce: 0\nNext nonce: 1\n"), "{output}");
+    let pending = format!("Pending transactions:\n  0 {tx_hash} -> {dead}");
+    assert!(output.contains(&pending), "{output}");
+
+    cmd.cast_fuse().args(["pending", from, "--json", "--rpc-url", &endpoint]);
+    let pool: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(pool["nextNonce"], 1);
+    assert_eq!(pool["transactions"][0]["hash"], tx_hash.as_str());
+    assert_eq!(pool["transactions"][0]["queued"], false);
+});
+
+// tests `abi.encodePacked` encoding, hashing and splitting
+// <https://docs.soliditylang.org/en/latest/abi-spec.html#non-standard-packed-mode>
+casttest!(abi_encode_packed, |_prj, cmd| {
+    let args = ["int16,bytes1,uint16,string", "-1", "0x42", "3", "Hello, world!"];
+    cmd.args(["abi-encode", "--packed"]).args(args);
+    assert_eq!(cmd.stdout_lossy().trim(), "0xffff42000348656c6c6f2c20776f726c6421");
+
+    cmd.cast_fuse().args(["keccak", "--packed"]).args(args);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "0xa61ecacd5de1490dcd3f7dad8f517cb383f00d6839207a7d8587ded6965e7889"
+    );
+
+    cmd.cast_fuse().args(["abi-encode", "--packed", "uint256[][]", "[[1]]"]);
+    assert!(cmd.stderr_lossy().contains("nested arrays and arrays of dynamic types"));
+
+    let packed = "0x000000000000000000000000000000000000dead0000000000000000000000070102";
+    cmd.cast_fuse().args(["abi-decode", "--packed", "--types", "address,uint96,bytes", packed]);
+    assert_eq!(cmd.stdout_lossy(), "0x000000000000000000000000000000000000dEaD\n7\n0x0102\n");
+    cmd.cast_fuse().args(["abi-decode", "--packed", "f(address,uint96,bytes)", packed]);
+    assert!(cmd.stdout_lossy().starts_with("0x000000000000000000000000000000000000dEaD\n"));
+    cmd.cast_fuse().args(["abi-decode", "--packed", "--types", "string,bytes", packed]);
+    assert!(cmd.stderr_lossy().contains("at most one string, bytes or dynamic array"));
+});
+
+// tests that `cast selectors` recovers the functions of bytecode and of deployed contracts
+casttest!(async selectors_from_dispatcher, |_prj, cmd| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ERC20Artifact.json");
+    let artifact: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    let code = artifact["deployedBytecode"]["object"].as_str().unwrap();
+    cmd.args(["selectors", code]);
+    let output = cmd.stdout_lossy();
+    for selector in artifact["methodIdentifiers"].as_object().unwrap().values() {
+        assert!(output.contains(&format!("0x{}", selector.as_str().unwrap())), "{output}");
+    }
+    assert!(output.contains("address,uint256"), "{output}");
+    assert!(output.contains("0x01dd"), "{output}");
+
+    let implementation = "bebebebebebebebebebebebebebebebebebebebe";
+    let proxy = format!("0x363d3d373d3d3d363d73{implementation}5af43d82803e903d91602b57fd5bf3");
+    cmd.cast_fuse().args(["selectors", &proxy]);
+    let output = cmd.stdout_lossy();
+    let checksummed = "0xBEbeBeBEbeBebeBeBEBEbebEBeBeBebeBeBebebe";
+    assert!(output.contains(&format!("minimal proxy of {checksummed}")), "{output}");
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let token = "0x000000000000000000000000000000000000c0DE";
+    cmd.cast_fuse().args(["rpc", "anvil_setCode", token, code, "--rpc-url", &endpoint]);
+    cmd.assert_success();
+    cmd.cast_fuse().args(["selectors", token, "--json", "--rpc-url", &endpoint]);
+    let dispatcher: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(dispatcher["diamond"], false);
+    let functions = dispatcher["functions"].as_array().unwrap();
+    assert_eq!(functions.len(), 13);
+    let transfer = functions.iter().find(|f| f["selector"] == "0xa9059cbb").unwrap();
+    assert_eq!(transfer["jumpDestination"], 477);
+
+    cmd.cast_fuse().args(["selectors", "0x000000000000000000000000000000000000dEaD"]);
+    assert!(cmd.args(["--rpc-url", &endpoint]).stderr_lossy().contains("has no code"));
+});
+
+// tests that the custom chains of `~/.foundry/chains.toml` take precedence over the built-in ones
+casttest!(chain_registry_custom_chains, |prj, cmd| {
+    let foundry_dir = prj.root().join(".foundry");
+    fs::create_dir_all(&foundry_dir).unwrap();
+    fs::write(foundry_dir.join("chains.toml"), "[base]\nid = 4242\ntitle = \"Not Base\"\n")
+        .unwrap();
+
+    cmd.cast_fuse().args(["chain-id", "base"]).set_env("HOME", prj.root().display());
+    assert_eq!(cmd.stdout_lossy().trim(), "4242");
+
+    cmd.cast_fuse().args(["chain-id", "sepolia"]).set_env("HOME", prj.root().display());
+    assert_eq!(cmd.stdout_lossy().trim(), "11155111");
+
+    cmd.cast_fuse().args(["chain", "--search", "base"]).set_env("HOME", prj.root().display());
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("base (custom)"), "{out}");
+    assert!(out.contains("Base Sepolia Testnet"), "{out}");
+
+    cmd.cast_fuse().args(["chain-id", "not-a-chain"]).set_env("HOME", prj.root().display());
+    cmd.assert_err();
+});
+
+// tests that `cast receipt --abi` decodes the logs of the receipt with the events of the ABI
+casttest!(async receipt_decoded_logs, |prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let emitter = "0x000000000000000000000000000000000000c0DE";
+
+    // emits `Transfer(msg.sender, msg.sender, 100)`
+    let code = "0x60646000523333\
+        7fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef60206000a300";
+    cmd.args(["rpc", "anvil_setCode", emitter, code, "--rpc-url", &endpoint]);
+    cmd.assert_non_empty_stdout();
+    cmd.cast_fuse().args(["send", emitter, "--async", "--private-key", private_key]);
+    let tx_hash = cmd.args(["--rpc-url", &endpoint]).stdout_lossy().trim().to_string();
+
+    let abi = prj.root().join("abi.json");
+    fs::write(
+        &abi,
+        r#"[{"type":"event","name":"Transfer","anonymous":false,"inputs":[
+            {"name":"from","type":"address","indexed":true},
+            {"name":"to","type":"address","indexed":true},
+            {"name":"value","type":"uint256","indexed":false}]}]"#,
+    )
+    .unwrap();
+    let abi = abi.to_str().unwrap();
+
+    cmd.cast_fuse().args(["receipt", &tx_hash, "--abi", abi, "--json", "--rpc-url", &endpoint]);
+    let json: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let log = &json["logs"][0];
+    assert_eq!(log["topics"].as_array().unwrap().len(), 3);
+    assert_eq!(log["decoded"]["event"], "Transfer(address,address,uint256)");
+    assert_eq!(log["decoded"]["names"], serde_json::json!(["from", "to", "value"]));
+    assert_eq!(log["decoded"]["args"][2], "100");
+
+    let from = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    cmd.cast_fuse().args(["receipt", &tx_hash, "--abi", abi, "--field", "logs"]);
+    let logs = cmd.args(["--rpc-url", &endpoint]).stdout_lossy();
+    assert!(logs.contains(&format!("args: [from: {from}, to: {from}, value: 100]")), "{logs}");
+
+    cmd.cast_fuse().args(["receipt", &tx_hash, "--field", "status", "--rpc-url", &endpoint]);
+    assert_eq!(cmd.stdout_lossy().trim(), "1");
+
+    // the logs of other contracts are kept raw
+    fs::write(prj.root().join("empty.json"), "[]").unwrap();
+    let empty = prj.root().join("empty.json");
+    cmd.cast_fuse().args(["receipt", &tx_hash, "--abi", empty.to_str().unwrap(), "--field"]);
+    let logs = cmd.args(["logs", "--rpc-url", &endpoint]).stdout_lossy();
+    assert!(logs.contains("topics: [0xddf252ad"), "{logs}");
+});
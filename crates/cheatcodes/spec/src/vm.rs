@@ -221,6 +221,34 @@ interface Vm {
         bool reverted;
     }
 
+    /// Gas used. Returned by `lastCallGas`.
+    struct Gas {
+        /// The gas limit of the call.
+        uint64 gasLimit;
+        /// The total gas used.
+        uint64 gasTotalUsed;
+        /// The amount of gas used for memory expansion.
+        uint64 gasMemoryUsed;
+        /// The amount of gas refunded.
+        int64 gasRefunded;
+        /// The amount of gas remaining.
+        uint64 gasRemaining;
+    }
+
+    /// A signed EIP-7702 authorization, which delegates the code of its signer to `implementation`.
+    struct SignedDelegation {
+        /// The y-parity of the signature.
+        uint8 v;
+        /// The `r` value of the signature.
+        bytes32 r;
+        /// The `s` value of the signature.
+        bytes32 s;
+        /// The nonce of the signer the delegation is valid for.
+        uint64 nonce;
+        /// The address of the contract the code is delegated to.
+        address implementation;
+    }
+
     // ======== EVM ========
 
     /// Gets the address for a given private key.
@@ -251,6 +279,18 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Safe)]
     function signP256(uint256 privateKey, bytes32 digest) external pure returns (bytes32 r, bytes32 s);
 
+    /// Signs an EIP-7702 authorization delegating the code of the account of `privateKey` to `implementation`, for the current chain and nonce of the account.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function signDelegation(address implementation, uint256 privateKey) external returns (SignedDelegation memory signedDelegation);
+
+    /// Applies a signed EIP-7702 authorization, so that calls to its signer run the code of the implementation on the storage of the signer.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function attachDelegation(SignedDelegation calldata signedDelegation) external;
+
+    /// Signs and applies an EIP-7702 authorization delegating the code of the account of `privateKey` to `implementation`.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function signAndAttachDelegation(address implementation, uint256 privateKey) external returns (SignedDelegation memory signedDelegation);
+
     // -------- Record Storage --------
 
     /// Records all storage reads and writes.
@@ -270,6 +310,25 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Safe)]
     function stopAndReturnStateDiff() external returns (AccountAccess[] memory accountAccesses);
 
+    /// Returns the same account accesses as `vm.stopAndReturnStateDiff`, with the transient storage
+    /// accesses instead of the persistent ones in `storageAccesses`.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function stopAndReturnTransientStateDiff() external returns (AccountAccess[] memory accountAccesses);
+
+    /// Returns a human-readable summary of the state changes since `vm.startStateDiffRecording`,
+    /// without stopping the recording.
+    ///
+    /// Lists the balance, nonce, code and storage changes of every changed account. Storage slots
+    /// of project contracts are labeled with their storage layout entries, e.g. `Vault._totalSupply`,
+    /// which requires `extra_output = ["storageLayout"]` and read access to the artifacts directory.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function getStateDiff() external view returns (string memory diff);
+
+    /// Returns the state changes since `vm.startStateDiffRecording` as a JSON object keyed by
+    /// account, without stopping the recording. See `getStateDiff`.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function getStateDiffJson() external view returns (string memory diff);
+
     // -------- Recording Map Writes --------
 
     /// Starts recording all map SSTOREs for later retrieval.
@@ -325,6 +384,12 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Unsafe)]
     function roll(uint256 newHeight) external;
 
+    /// Sets the hash returned by `blockhash` for the given block number.
+    /// The block number must be within the last 256 blocks before the current `block.number`.
+    /// Previously set hashes are preserved when using `roll`, and shadow the hashes of a forked chain.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function setBlockhash(uint256 blockNumber, bytes32 blockHash) external;
+
     /// Gets the current `block.number`.
     /// You should use this instead of `block.number` if you use `vm.roll`, as `block.number` is assumed to be constant across a transaction,
     /// and as a result will get optimized out by the compiler.
@@ -373,6 +438,17 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Unsafe)]
     function store(address target, bytes32 slot, bytes32 value) external;
 
+    /// Loads a transient storage slot from an address.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function tload(address target, bytes32 slot) external view returns (bytes32 data);
+
+    /// Stores a value to an address' transient storage slot.
+    ///
+    /// Transient storage is cleared at the end of the transaction, so the value is only visible to
+    /// the current test, and not to calls that are executed in isolation mode.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function tstore(address target, bytes32 slot, bytes32 value) external;
+
     /// Marks the slots of an account and the account address as cold.
     #[cheatcode(group = Evm, safety = Unsafe, status = Experimental)]
     function cool(address target) external;
@@ -587,6 +663,10 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Safe)]
     function resumeGasMetering() external;
 
+    /// Gets the gas used in the last call.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function lastCallGas() external view returns (Gas memory gas);
+
     // ======== Test Assertions and Utilities ========
 
     /// If the condition is false, discard this run's fuzz inputs and generate new ones.
@@ -676,7 +756,7 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectRevert() external;
 
-    /// Expects an error on next call that starts with the revert data.
+    /// Expects an error on next call that exactly matches the revert data.
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectRevert(bytes4 revertData) external;
 
@@ -684,6 +764,16 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectRevert(bytes calldata revertData) external;
 
+    /// Expects an error on next call that starts with the custom error selector and whose ABI-encoded
+    /// argument at `argIndex` equals `argValue`, regardless of the other arguments.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(bytes4 revertData, uint256 argIndex, bytes32 argValue) external;
+
+    /// Expects an error on next call that starts with the revert data, e.g. a custom error selector
+    /// with any arguments.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectPartialRevert(bytes4 revertData) external;
+
     /// Expects an error on next cheatcode call with any revert data.
     #[cheatcode(group = Testing, safety = Unsafe, status = Internal)]
     function _expectCheatcodeRevert() external;
@@ -701,9 +791,9 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectSafeMemory(uint64 min, uint64 max) external;
 
-    /// Only allows memory writes to offsets [0x00, 0x60) ∪ [min, max) in the next created subcontext.
-    /// If any other memory is written to, the test will fail. Can be called multiple times to add more ranges
-    /// to the set.
+    /// Only allows memory writes to offsets [0x00, 0x60) ∪ [min, max) in the next call or create, but not in the
+    /// ones after it. If any other memory is written to, the test will fail. Can be called multiple times to add
+    /// more ranges to the set.
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectSafeMemoryCall(uint64 min, uint64 max) external;
 
@@ -833,10 +923,21 @@ interface Vm {
     #[cheatcode(group = Filesystem)]
     function getCode(string calldata artifactPath) external view returns (bytes memory creationBytecode);
 
-    /// Gets the deployed bytecode from an artifact file. Takes in the relative path to the json file.
+    /// Gets the deployed bytecode from an artifact file, with zeros in place of the immutables. Takes in the relative path
+    /// to the json file.
     #[cheatcode(group = Filesystem)]
     function getDeployedCode(string calldata artifactPath) external view returns (bytes memory runtimeBytecode);
 
+    /// Gets the id of the artifact whose deployed bytecode matches `deployedCode`, e.g. `Counter.sol:Counter`, ignoring the
+    /// compiler metadata and the immutables. Reverts if no artifact or multiple artifacts match.
+    #[cheatcode(group = Filesystem)]
+    function getArtifactByDeployedCode(bytes calldata deployedCode) external view returns (string memory artifactId);
+
+    /// Gets the address of the latest deployment of a contract on a chain from the deployments registry, see
+    /// `save_deployments`. Reads `<deployments>/<chainId>/<contractName>.json`.
+    #[cheatcode(group = Filesystem)]
+    function getDeployment(string calldata contractName, uint256 chainId) external view returns (address deployedAddress);
+
     // -------- Foreign Function Interface --------
 
     /// Performs a foreign function call via the terminal.
@@ -847,6 +948,25 @@ interface Vm {
     #[cheatcode(group = Filesystem)]
     function tryFfi(string[] calldata commandInput) external returns (FfiResult memory result);
 
+    // -------- User Interaction --------
+
+    /// Prompts the user for a string value in the terminal.
+    #[cheatcode(group = Filesystem)]
+    function prompt(string calldata promptText) external returns (string memory input);
+
+    /// Prompts the user for a hidden string value in the terminal. The value is masked in the output and
+    /// broadcast files.
+    #[cheatcode(group = Filesystem)]
+    function promptSecret(string calldata promptText) external returns (string memory input);
+
+    /// Prompts the user for an address in the terminal, prompting again until a valid address is entered.
+    #[cheatcode(group = Filesystem)]
+    function promptAddress(string calldata promptText) external returns (address);
+
+    /// Prompts the user for a uint256 in the terminal, prompting again until a valid number is entered.
+    #[cheatcode(group = Filesystem)]
+    function promptUint(string calldata promptText) external returns (uint256);
+
     // ======== Environment Variables ========
 
     /// Sets environment variables.
@@ -997,6 +1117,23 @@ interface Vm {
         external view
         returns (bytes[] memory value);
 
+    /// Gets the environment variable `name` and parses it as `uint256`, like `envUint`.
+    /// The value is masked in the console output, traces and broadcast files of scripts, whatever the name.
+    #[cheatcode(group = Environment)]
+    function envSensitiveUint(string calldata name) external view returns (uint256 value);
+    /// Gets the environment variable `name` and parses it as `bytes32`, like `envBytes32`.
+    /// The value is masked in the console output, traces and broadcast files of scripts, whatever the name.
+    #[cheatcode(group = Environment)]
+    function envSensitiveBytes32(string calldata name) external view returns (bytes32 value);
+    /// Gets the environment variable `name` and parses it as `string`, like `envString`.
+    /// The value is masked in the console output, traces and broadcast files of scripts, whatever the name.
+    #[cheatcode(group = Environment)]
+    function envSensitiveString(string calldata name) external view returns (string memory value);
+    /// Gets the environment variable `name` and parses it as `bytes`, like `envBytes`.
+    /// The value is masked in the console output, traces and broadcast files of scripts, whatever the name.
+    #[cheatcode(group = Environment)]
+    function envSensitiveBytes(string calldata name) external view returns (bytes memory value);
+
     // ======== Scripts ========
 
     // -------- Broadcasting Transactions --------
@@ -1,16 +1,23 @@
 use super::Result;
-use crate::Vm::Rpc;
+use crate::{evm::state_diff::LayoutContract, Vm::Rpc};
+use alloy_json_abi::JsonAbi;
 use alloy_primitives::Address;
-use foundry_common::fs::normalize_path;
+use foundry_common::{
+    fs::normalize_path,
+    redact::{is_sensitive_env, Redactor},
+};
 use foundry_compilers::{utils::canonicalize, ProjectPathsConfig};
 use foundry_config::{
-    cache::StorageCachingConfig, fs_permissions::FsAccessKind, Config, FsPermissions,
-    ResolvedRpcEndpoints,
+    cache::StorageCachingConfig,
+    fs_permissions::{FsAccessKind, PathPermission},
+    Config, FsPermissions, ResolvedRpcEndpoints,
 };
 use foundry_evm_core::opts::EvmOpts;
 use std::{
-    collections::HashMap,
-    path::{Path, PathBuf},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
 };
 
 /// Additional, configurable context the `Cheatcodes` inspector has access to
@@ -20,8 +27,13 @@ use std::{
 pub struct CheatsConfig {
     /// Whether the FFI cheatcode is enabled.
     pub ffi: bool,
+    /// Prefixes of the commands the FFI cheatcode is allowed to execute, see
+    /// [`CheatsConfig::ensure_ffi_allowed`]
+    pub allowed_ffi: Vec<String>,
     /// RPC storage caching settings determines what chains and endpoints to cache
     pub rpc_storage_caching: StorageCachingConfig,
+    /// Disables the RPC storage caching of forks entirely
+    pub no_storage_caching: bool,
     /// All known endpoints and their aliases
     pub rpc_endpoints: ResolvedRpcEndpoints,
     /// Project's paths as configured
@@ -30,12 +42,27 @@ pub struct CheatsConfig {
     pub fs_permissions: FsPermissions,
     /// Project root
     pub root: PathBuf,
+    /// Where the registry of deployed contracts is stored, see `vm.getDeployment`
+    pub deployments: PathBuf,
     /// Paths (directories) where file reading/writing is allowed
     pub allowed_paths: Vec<PathBuf>,
     /// How the evm was configured by the user
     pub evm_opts: EvmOpts,
     /// Address labels from config
     pub labels: HashMap<Address, String>,
+    /// All known errors, used for decoding reverts
+    pub errors: Option<JsonAbi>,
+    /// The permissions exercised so far, shared by all inspectors using this config
+    pub exercised_permissions: Arc<Mutex<PermissionsReport>>,
+    /// Patterns of the names of sensitive environment variables, see `Config::sensitive_env`
+    pub sensitive_env: Vec<String>,
+    /// The environment variables read so far, shared by all inspectors using this config
+    pub env_report: Arc<Mutex<EnvReport>>,
+    /// Whether the `vm.prompt*` cheatcodes fail instead of reading from the terminal
+    pub non_interactive: bool,
+    /// The project contracts with a storage layout, read once for all inspectors using this
+    /// config, see `vm.getStateDiff`
+    pub(crate) layout_contracts: Arc<OnceLock<Vec<LayoutContract>>>,
 }
 
 impl CheatsConfig {
@@ -50,17 +77,31 @@ impl CheatsConfig {
 
         Self {
             ffi: evm_opts.ffi,
+            allowed_ffi: config.allowed_ffi.clone(),
             rpc_storage_caching: config.rpc_storage_caching.clone(),
+            no_storage_caching: config.no_storage_caching,
             rpc_endpoints,
             paths: config.project_paths(),
             fs_permissions: config.fs_permissions.clone().joined(&config.__root),
             root: config.__root.0.clone(),
+            deployments: config.deployments.clone(),
             allowed_paths,
             evm_opts,
             labels: config.labels.clone(),
+            errors: None,
+            exercised_permissions: Default::default(),
+            sensitive_env: config.sensitive_env.clone(),
+            env_report: Default::default(),
+            non_interactive: false,
+            layout_contracts: Default::default(),
         }
     }
 
+    /// Whether the RPC storage of forks of the endpoint should be cached
+    pub fn enable_caching(&self, endpoint: &str) -> bool {
+        !self.no_storage_caching && self.rpc_storage_caching.enable_for_endpoint(endpoint)
+    }
+
     /// Attempts to canonicalize (see [std::fs::canonicalize]) the path.
     ///
     /// Canonicalization fails for non-existing paths, in which case we just normalize the path.
@@ -80,7 +121,29 @@ impl CheatsConfig {
     }
 
     fn is_normalized_path_allowed(&self, path: &Path, kind: FsAccessKind) -> bool {
-        self.fs_permissions.is_path_allowed(path, kind)
+        self.granting_permission(path, kind).is_some()
+    }
+
+    /// Returns the description of the permission that grants `kind` access to the normalized
+    /// `path`, if any.
+    fn granting_permission(&self, path: &Path, kind: FsAccessKind) -> Option<String> {
+        match self.fs_permissions.find_path_permission(path) {
+            Some(permission) => {
+                permission.is_granted(kind).then(|| self.describe_path_permission(permission))
+            }
+            // the default mode only applies to the project
+            None => (path.starts_with(&self.root) && self.fs_permissions.default.is_granted(kind))
+                .then(|| self.describe_default_permission()),
+        }
+    }
+
+    fn describe_path_permission(&self, permission: &PathPermission) -> String {
+        let path = permission.path.strip_prefix(&self.root).unwrap_or(&permission.path);
+        format!("{} ./{}", permission.access, path.display())
+    }
+
+    fn describe_default_permission(&self) -> String {
+        format!("{} (default)", self.fs_permissions.default)
     }
 
     /// Returns an error if no access is granted to access `path`, See also [Self::is_path_allowed]
@@ -93,12 +156,83 @@ impl CheatsConfig {
     ) -> Result<PathBuf> {
         let path = path.as_ref();
         let normalized = self.normalized_path(path);
+        let relative = normalized.strip_prefix(&self.root).unwrap_or(path);
+        let Some(permission) = self.granting_permission(&normalized, kind) else {
+            bail!(
+                "the path {} is not allowed to be accessed for {kind} operations",
+                relative.display()
+            )
+        };
+        let access = format!("{kind} {}", relative.display());
+        self.exercised_permissions.lock().unwrap().fs.entry(permission).or_default().insert(access);
+        Ok(normalized)
+    }
+
+    /// Returns an error if the FFI cheatcode is not allowed to execute the command `input`.
+    ///
+    /// FFI is allowed if it's enabled or if [`Self::allowed_ffi`] is not empty. In the latter case
+    /// the command has to start with one of the allowed prefixes.
+    pub fn ensure_ffi_allowed(&self, input: &[String]) -> Result<()> {
         ensure!(
-            self.is_normalized_path_allowed(&normalized, kind),
-            "the path {} is not allowed to be accessed for {kind} operations",
-            normalized.strip_prefix(&self.root).unwrap_or(path).display()
+            self.ffi || !self.allowed_ffi.is_empty(),
+            "FFI is disabled; add the `--ffi` flag to allow tests to call external commands"
         );
-        Ok(normalized)
+        let command = input.join(" ");
+        let permission = if self.allowed_ffi.is_empty() {
+            "all commands".to_string()
+        } else {
+            let Some(prefix) =
+                self.allowed_ffi.iter().find(|prefix| ffi_prefix_matches(prefix, input))
+            else {
+                bail!("the command `{command}` is not allowed by `allowed_ffi`")
+            };
+            format!("`{prefix}`")
+        };
+        self.exercised_permissions
+            .lock()
+            .unwrap()
+            .ffi
+            .entry(permission)
+            .or_default()
+            .insert(command);
+        Ok(())
+    }
+
+    /// Returns all configured permissions, together with the commands and paths they granted
+    /// access to so far.
+    pub fn permissions_report(&self) -> PermissionsReport {
+        let mut report = self.exercised_permissions.lock().unwrap().clone();
+        for prefix in &self.allowed_ffi {
+            report.ffi.entry(format!("`{prefix}`")).or_default();
+        }
+        for permission in &self.fs_permissions.permissions {
+            report.fs.entry(self.describe_path_permission(permission)).or_default();
+        }
+        if self.fs_permissions.default != Default::default() {
+            report.fs.entry(self.describe_default_permission()).or_default();
+        }
+        report
+    }
+
+    /// Records that the environment variable was read, masks its value if it's sensitive.
+    ///
+    /// A variable is sensitive if its name matches [`Self::sensitive_env`] or if `sensitive` is
+    /// set, see `vm.envSensitiveString`. Values of arrays are masked as a whole and per element.
+    pub fn record_env_read(&self, name: &str, value: &str, delim: Option<&str>, sensitive: bool) {
+        let sensitive = sensitive || is_sensitive_env(name, &self.sensitive_env);
+        let mut report = self.env_report.lock().unwrap();
+        *report.vars.entry(name.to_string()).or_default() |= sensitive;
+        if sensitive {
+            report.redactor.add(value);
+            if let Some(delim) = delim {
+                value.split(delim).for_each(|value| report.redactor.add(value));
+            }
+        }
+    }
+
+    /// Records a value entered with `vm.promptSecret`, so it's masked like sensitive variables.
+    pub fn record_secret(&self, value: &str) {
+        self.env_report.lock().unwrap().redactor.add(value);
     }
 
     /// Returns true if the given `path` is the project's foundry.toml file
@@ -160,18 +294,116 @@ impl CheatsConfig {
     }
 }
 
+/// Returns true if the command `input` starts with the allowed command `prefix`.
+///
+/// The words of the prefix have to match the arguments of the command. The last word only has to be
+/// the start of its argument, unless the prefix ends with a whitespace: `"python3 scripts/"`
+/// matches `python3 scripts/gen.py --out x`, `"node "` doesn't match `nodejs x.js`.
+///
+/// A partially matched argument must not traverse to a parent directory, so `"python3 scripts/"`
+/// doesn't match `python3 scripts/../evil.py`.
+fn ffi_prefix_matches(prefix: &str, input: &[String]) -> bool {
+    let words = prefix.split_whitespace().collect::<Vec<_>>();
+    let Some((last, full)) = words.split_last() else { return false };
+    if input.len() < words.len() || full.iter().zip(input).any(|(word, arg)| arg != word) {
+        return false
+    }
+
+    let arg = &input[full.len()];
+    if prefix.ends_with(char::is_whitespace) {
+        return arg == last
+    }
+    arg.starts_with(last) && !Path::new(arg).components().any(|c| c == Component::ParentDir)
+}
+
+/// The permissions exercised by cheatcodes, see [`CheatsConfig::permissions_report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PermissionsReport {
+    /// The commands executed by the FFI cheatcode, by the allowed prefix they matched
+    pub ffi: BTreeMap<String, BTreeSet<String>>,
+    /// The paths accessed by file system cheatcodes, by the permission that granted the access
+    pub fs: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl PermissionsReport {
+    /// Returns true if no permissions are configured or exercised
+    pub fn is_empty(&self) -> bool {
+        self.ffi.is_empty() && self.fs.is_empty()
+    }
+}
+
+impl fmt::Display for PermissionsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, permissions) in [("ffi", &self.ffi), ("fs_permissions", &self.fs)] {
+            if permissions.is_empty() {
+                continue
+            }
+            writeln!(f, "{name}:")?;
+            for (permission, accesses) in permissions {
+                if accesses.is_empty() {
+                    writeln!(f, "  {permission}: unused")?;
+                    continue
+                }
+                writeln!(f, "  {permission}:")?;
+                for access in accesses {
+                    writeln!(f, "    {access}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The environment variables read by cheatcodes, see [`CheatsConfig::record_env_read`].
+#[derive(Clone, Debug, Default)]
+pub struct EnvReport {
+    /// `name -> sensitive` of the variables read
+    pub vars: BTreeMap<String, bool>,
+    /// Masks the values of the sensitive variables and of the prompted secrets
+    pub redactor: Redactor,
+}
+
+impl EnvReport {
+    /// Returns true if no variables were read
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+}
+
+impl fmt::Display for EnvReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, sensitive) in &self.vars {
+            if *sensitive {
+                writeln!(f, "  {name} (sensitive)")?;
+            } else {
+                writeln!(f, "  {name}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Default for CheatsConfig {
     fn default() -> Self {
         Self {
             ffi: false,
+            allowed_ffi: vec![],
             rpc_storage_caching: Default::default(),
+            no_storage_caching: false,
             rpc_endpoints: Default::default(),
             paths: ProjectPathsConfig::builder().build_with_root("./"),
             fs_permissions: Default::default(),
             root: Default::default(),
+            deployments: "deployments".into(),
             allowed_paths: vec![],
             evm_opts: Default::default(),
             labels: Default::default(),
+            errors: None,
+            exercised_permissions: Default::default(),
+            sensitive_env: vec![],
+            env_report: Default::default(),
+            non_interactive: false,
+            layout_contracts: Default::default(),
         }
     }
 }
@@ -179,7 +411,8 @@ impl Default for CheatsConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use foundry_config::fs_permissions::PathPermission;
+    use foundry_common::redact::mask;
+    use foundry_config::fs_permissions::FsAccessPermission;
 
     fn config(root: &str, fs_permissions: FsPermissions) -> CheatsConfig {
         CheatsConfig::new(
@@ -201,6 +434,84 @@ mod tests {
         assert!(config.ensure_path_allowed("../../root/t.txt", FsAccessKind::Write).is_err());
     }
 
+    #[test]
+    fn test_default_fs_permission() {
+        let root = "/my/project/root/";
+        let permissions = FsPermissions::new(vec![PathPermission::none("./secrets")])
+            .with_default(FsAccessPermission::Read);
+        let config = config(root, permissions);
+
+        assert!(config.ensure_path_allowed("./t.txt", FsAccessKind::Read).is_ok());
+        assert!(config.ensure_path_allowed("./t.txt", FsAccessKind::Write).is_err());
+        assert!(config.ensure_path_allowed("./secrets/key", FsAccessKind::Read).is_err());
+        assert!(config.ensure_path_allowed("../t.txt", FsAccessKind::Read).is_err());
+        assert!(config.ensure_path_allowed("./a/../../t.txt", FsAccessKind::Read).is_err());
+
+        let report = config.permissions_report();
+        assert_eq!(report.fs["read (default)"], BTreeSet::from(["read t.txt".to_string()]));
+        assert!(report.fs["none ./secrets"].is_empty());
+    }
+
+    #[test]
+    fn test_allowed_ffi() {
+        let config = CheatsConfig {
+            allowed_ffi: vec!["python3 scripts/".to_string(), "node ".to_string()],
+            ..Default::default()
+        };
+        let allowed = |input: &[&str]| {
+            let input = input.iter().map(ToString::to_string).collect::<Vec<_>>();
+            config.ensure_ffi_allowed(&input).is_ok()
+        };
+
+        assert!(allowed(&["python3", "scripts/gen.py"]));
+        assert!(allowed(&["python3", "scripts/gen.py", "--out", "../x"]));
+        assert!(allowed(&["node"]));
+        assert!(allowed(&["node", "index.js"]));
+        assert!(!allowed(&["python3"]));
+        assert!(!allowed(&["python3", "-c", "print(1)"]));
+        assert!(!allowed(&["python3", "scripts_evil/gen.py"]));
+        assert!(!allowed(&["python3", "scripts/../evil.py"]));
+        assert!(!allowed(&["python3", "scripts/a/../../evil.py"]));
+        assert!(!allowed(&["nodejs", "index.js"]));
+        assert!(!allowed(&["bash", "-c", "python3 scripts/gen.py"]));
+
+        let err = config.ensure_ffi_allowed(&["rm".to_string(), "-rf".to_string()]).unwrap_err();
+        assert_eq!(err.to_string(), "the command `rm -rf` is not allowed by `allowed_ffi`");
+
+        let report = config.permissions_report();
+        assert_eq!(report.ffi["`node `"].len(), 2);
+        assert_eq!(report.ffi["`python3 scripts/`"].len(), 2);
+    }
+
+    #[test]
+    fn test_ffi_disabled() {
+        let config = CheatsConfig::default();
+        assert!(config.ensure_ffi_allowed(&["echo".to_string()]).is_err());
+
+        let config = CheatsConfig { ffi: true, ..Default::default() };
+        assert!(config.ensure_ffi_allowed(&["echo".to_string(), "gm".to_string()]).is_ok());
+        assert_eq!(
+            config.permissions_report().ffi["all commands"],
+            BTreeSet::from(["echo gm".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_record_env_read() {
+        let config =
+            CheatsConfig { sensitive_env: vec!["*_KEY".to_string()], ..Default::default() };
+        config.record_env_read("RPC_URL", "http://localhost", None, false);
+        config.record_env_read("API_KEY", "secret-key", None, false);
+        config.record_env_read("NAMES", "alice,bobby", Some(","), true);
+
+        let report = config.env_report.lock().unwrap();
+        assert_eq!(report.to_string(), "  API_KEY (sensitive)\n  NAMES (sensitive)\n  RPC_URL\n");
+        assert_eq!(
+            report.redactor.redact("secret-key http://localhost alice"),
+            format!("{} http://localhost {}", mask("secret-key"), mask("alice"))
+        );
+    }
+
     #[test]
     fn test_is_foundry_toml() {
         let root = "/my/project/root/";
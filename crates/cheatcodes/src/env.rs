@@ -25,232 +25,276 @@ impl Cheatcode for setEnvCall {
 }
 
 impl Cheatcode for envBool_0Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name } = self;
-        env(name, &DynSolType::Bool)
+        env(state, name, &DynSolType::Bool)
     }
 }
 
 impl Cheatcode for envUint_0Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name } = self;
-        env(name, &DynSolType::Uint(256))
+        env(state, name, &DynSolType::Uint(256))
     }
 }
 
 impl Cheatcode for envInt_0Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name } = self;
-        env(name, &DynSolType::Int(256))
+        env(state, name, &DynSolType::Int(256))
     }
 }
 
 impl Cheatcode for envAddress_0Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name } = self;
-        env(name, &DynSolType::Address)
+        env(state, name, &DynSolType::Address)
     }
 }
 
 impl Cheatcode for envBytes32_0Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name } = self;
-        env(name, &DynSolType::FixedBytes(32))
+        env(state, name, &DynSolType::FixedBytes(32))
     }
 }
 
 impl Cheatcode for envString_0Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name } = self;
-        env(name, &DynSolType::String)
+        env(state, name, &DynSolType::String)
     }
 }
 
 impl Cheatcode for envBytes_0Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name } = self;
-        env(name, &DynSolType::Bytes)
+        env(state, name, &DynSolType::Bytes)
     }
 }
 
 impl Cheatcode for envBool_1Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim } = self;
-        env_array(name, delim, &DynSolType::Bool)
+        env_array(state, name, delim, &DynSolType::Bool)
     }
 }
 
 impl Cheatcode for envUint_1Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim } = self;
-        env_array(name, delim, &DynSolType::Uint(256))
+        env_array(state, name, delim, &DynSolType::Uint(256))
     }
 }
 
 impl Cheatcode for envInt_1Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim } = self;
-        env_array(name, delim, &DynSolType::Int(256))
+        env_array(state, name, delim, &DynSolType::Int(256))
     }
 }
 
 impl Cheatcode for envAddress_1Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim } = self;
-        env_array(name, delim, &DynSolType::Address)
+        env_array(state, name, delim, &DynSolType::Address)
     }
 }
 
 impl Cheatcode for envBytes32_1Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim } = self;
-        env_array(name, delim, &DynSolType::FixedBytes(32))
+        env_array(state, name, delim, &DynSolType::FixedBytes(32))
     }
 }
 
 impl Cheatcode for envString_1Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim } = self;
-        env_array(name, delim, &DynSolType::String)
+        env_array(state, name, delim, &DynSolType::String)
     }
 }
 
 impl Cheatcode for envBytes_1Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim } = self;
-        env_array(name, delim, &DynSolType::Bytes)
+        env_array(state, name, delim, &DynSolType::Bytes)
     }
 }
 
 // bool
 impl Cheatcode for envOr_0Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, defaultValue } = self;
-        env_default(name, defaultValue, &DynSolType::Bool)
+        env_default(state, name, defaultValue, &DynSolType::Bool)
     }
 }
 
 // uint256
 impl Cheatcode for envOr_1Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, defaultValue } = self;
-        env_default(name, defaultValue, &DynSolType::Uint(256))
+        env_default(state, name, defaultValue, &DynSolType::Uint(256))
     }
 }
 
 // int256
 impl Cheatcode for envOr_2Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, defaultValue } = self;
-        env_default(name, defaultValue, &DynSolType::Int(256))
+        env_default(state, name, defaultValue, &DynSolType::Int(256))
     }
 }
 
 // address
 impl Cheatcode for envOr_3Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, defaultValue } = self;
-        env_default(name, defaultValue, &DynSolType::Address)
+        env_default(state, name, defaultValue, &DynSolType::Address)
     }
 }
 
 // bytes32
 impl Cheatcode for envOr_4Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, defaultValue } = self;
-        env_default(name, defaultValue, &DynSolType::FixedBytes(32))
+        env_default(state, name, defaultValue, &DynSolType::FixedBytes(32))
     }
 }
 
 // string
 impl Cheatcode for envOr_5Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, defaultValue } = self;
-        env_default(name, defaultValue, &DynSolType::String)
+        env_default(state, name, defaultValue, &DynSolType::String)
     }
 }
 
 // bytes
 impl Cheatcode for envOr_6Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, defaultValue } = self;
-        env_default(name, defaultValue, &DynSolType::Bytes)
+        env_default(state, name, defaultValue, &DynSolType::Bytes)
     }
 }
 
 // bool[]
 impl Cheatcode for envOr_7Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim, defaultValue } = self;
-        env_array_default(name, delim, defaultValue, &DynSolType::Bool)
+        env_array_default(state, name, delim, defaultValue, &DynSolType::Bool)
     }
 }
 
 // uint256[]
 impl Cheatcode for envOr_8Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim, defaultValue } = self;
-        env_array_default(name, delim, defaultValue, &DynSolType::Uint(256))
+        env_array_default(state, name, delim, defaultValue, &DynSolType::Uint(256))
     }
 }
 
 // int256[]
 impl Cheatcode for envOr_9Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim, defaultValue } = self;
-        env_array_default(name, delim, defaultValue, &DynSolType::Int(256))
+        env_array_default(state, name, delim, defaultValue, &DynSolType::Int(256))
     }
 }
 
 // address[]
 impl Cheatcode for envOr_10Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim, defaultValue } = self;
-        env_array_default(name, delim, defaultValue, &DynSolType::Address)
+        env_array_default(state, name, delim, defaultValue, &DynSolType::Address)
     }
 }
 
 // bytes32[]
 impl Cheatcode for envOr_11Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim, defaultValue } = self;
-        env_array_default(name, delim, defaultValue, &DynSolType::FixedBytes(32))
+        env_array_default(state, name, delim, defaultValue, &DynSolType::FixedBytes(32))
     }
 }
 
 // string[]
 impl Cheatcode for envOr_12Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim, defaultValue } = self;
-        env_array_default(name, delim, defaultValue, &DynSolType::String)
+        env_array_default(state, name, delim, defaultValue, &DynSolType::String)
     }
 }
 
 // bytes[]
 impl Cheatcode for envOr_13Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { name, delim, defaultValue } = self;
         let default = defaultValue.iter().map(|vec| vec.clone().into()).collect::<Vec<Bytes>>();
-        env_array_default(name, delim, &default, &DynSolType::Bytes)
+        env_array_default(state, name, delim, &default, &DynSolType::Bytes)
+    }
+}
+
+impl Cheatcode for envSensitiveUintCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        env_sensitive(state, name, &DynSolType::Uint(256))
+    }
+}
+
+impl Cheatcode for envSensitiveBytes32Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        env_sensitive(state, name, &DynSolType::FixedBytes(32))
     }
 }
 
-fn env(key: &str, ty: &DynSolType) -> Result {
-    get_env(key).and_then(|val| string::parse(&val, ty).map_err(map_env_err(key, &val)))
+impl Cheatcode for envSensitiveStringCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        env_sensitive(state, name, &DynSolType::String)
+    }
+}
+
+impl Cheatcode for envSensitiveBytesCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        env_sensitive(state, name, &DynSolType::Bytes)
+    }
+}
+
+fn env(state: &Cheatcodes, key: &str, ty: &DynSolType) -> Result {
+    read_env(state, key, ty, false)
+}
+
+fn env_sensitive(state: &Cheatcodes, key: &str, ty: &DynSolType) -> Result {
+    read_env(state, key, ty, true)
+}
+
+fn read_env(state: &Cheatcodes, key: &str, ty: &DynSolType, sensitive: bool) -> Result {
+    let val = get_env(key)?;
+    state.config.record_env_read(key, &val, None, sensitive);
+    string::parse(&val, ty).map_err(map_env_err(key, &val))
 }
 
-fn env_default<T: SolValue>(key: &str, default: &T, ty: &DynSolType) -> Result {
-    Ok(env(key, ty).unwrap_or_else(|_| default.abi_encode()))
+fn env_default<T: SolValue>(state: &Cheatcodes, key: &str, default: &T, ty: &DynSolType) -> Result {
+    Ok(env(state, key, ty).unwrap_or_else(|_| default.abi_encode()))
 }
 
-fn env_array(key: &str, delim: &str, ty: &DynSolType) -> Result {
-    get_env(key).and_then(|val| {
-        string::parse_array(val.split(delim).map(str::trim), ty).map_err(map_env_err(key, &val))
-    })
+fn env_array(state: &Cheatcodes, key: &str, delim: &str, ty: &DynSolType) -> Result {
+    let val = get_env(key)?;
+    state.config.record_env_read(key, &val, Some(delim), false);
+    string::parse_array(val.split(delim).map(str::trim), ty).map_err(map_env_err(key, &val))
 }
 
-fn env_array_default<T: SolValue>(key: &str, delim: &str, default: &T, ty: &DynSolType) -> Result {
-    Ok(env_array(key, delim, ty).unwrap_or_else(|_| default.abi_encode()))
+fn env_array_default<T: SolValue>(
+    state: &Cheatcodes,
+    key: &str,
+    delim: &str,
+    default: &T,
+    ty: &DynSolType,
+) -> Result {
+    Ok(env_array(state, key, delim, ty).unwrap_or_else(|_| default.abi_encode()))
 }
 
 fn get_env(key: &str) -> Result<String> {
@@ -288,7 +332,7 @@ mod tests {
         let value = "t";
         env::set_var(key, value);
 
-        let err = env(key, &DynSolType::Uint(256)).unwrap_err().to_string();
+        let err = env(&Cheatcodes::default(), key, &DynSolType::Uint(256)).unwrap_err().to_string();
         assert_eq!(err.matches("$parse_env_uint").count(), 2, "{err:?}");
         env::remove_var(key);
     }
@@ -9,6 +9,7 @@ use ethers_core::{
 };
 use ethers_signers::Signer;
 use foundry_common::{
+    authorization::{Authorization, SignedAuthorization},
     fs::{read_json_file, write_json_file},
     types::{ToAlloy, ToEthers},
 };
@@ -22,10 +23,14 @@ use revm::{
 };
 use std::{collections::HashMap, path::Path};
 
+/// The number of most recent blocks the `BLOCKHASH` opcode returns the hash of.
+const BLOCKHASH_WINDOW: u64 = 256;
+
 mod fork;
 pub(crate) mod mapping;
 pub(crate) mod mock;
 pub(crate) mod prank;
+pub(crate) mod state_diff;
 
 /// Records storage slots reads and writes.
 #[derive(Clone, Debug, Default)]
@@ -159,6 +164,29 @@ impl Cheatcode for signP256Call {
     }
 }
 
+impl Cheatcode for signDelegationCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { implementation, privateKey } = self;
+        Ok(sign_delegation(ccx, privateKey, *implementation)?.abi_encode())
+    }
+}
+
+impl Cheatcode for attachDelegationCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { signedDelegation } = self;
+        attach_delegation(ccx, signedDelegation)
+    }
+}
+
+impl Cheatcode for signAndAttachDelegationCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { implementation, privateKey } = self;
+        let delegation = sign_delegation(ccx, privateKey, *implementation)?;
+        attach_delegation(ccx, &delegation)?;
+        Ok(delegation.abi_encode())
+    }
+}
+
 impl Cheatcode for recordCall {
     fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self {} = self;
@@ -217,6 +245,16 @@ impl Cheatcode for resumeGasMeteringCall {
     }
 }
 
+impl Cheatcode for lastCallGasCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self {} = self;
+        let Some(last_call_gas) = &state.last_call_gas else {
+            bail!("`lastCallGas` is only available after a call");
+        };
+        Ok(last_call_gas.abi_encode())
+    }
+}
+
 impl Cheatcode for chainIdCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { newChainId } = self;
@@ -276,6 +314,20 @@ impl Cheatcode for rollCall {
     }
 }
 
+impl Cheatcode for setBlockhashCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { blockNumber, blockHash } = *self;
+        let current = ccx.data.env.block.number;
+        ensure!(
+            blockNumber < current && current - blockNumber <= U256::from(BLOCKHASH_WINDOW),
+            "block number {blockNumber} must be within the last {BLOCKHASH_WINDOW} blocks before \
+             the current block number {current}"
+        );
+        ccx.data.db.set_blockhash(blockNumber, blockHash);
+        Ok(Default::default())
+    }
+}
+
 impl Cheatcode for getBlockNumberCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self {} = self;
@@ -379,6 +431,24 @@ impl Cheatcode for storeCall {
     }
 }
 
+impl Cheatcode for tloadCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { target, slot } = *self;
+        ensure_not_precompile!(&target, ccx);
+        let val = ccx.data.journaled_state.tload(target, slot.into());
+        Ok(val.abi_encode())
+    }
+}
+
+impl Cheatcode for tstoreCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { target, slot, value } = *self;
+        ensure_not_precompile!(&target, ccx);
+        ccx.data.journaled_state.tstore(target, slot.into(), value.into());
+        Ok(Default::default())
+    }
+}
+
 impl Cheatcode for coolCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { target } = self;
@@ -458,9 +528,16 @@ impl Cheatcode for deleteSnapshotsCall {
 }
 
 impl Cheatcode for startStateDiffRecordingCall {
-    fn apply(&self, state: &mut Cheatcodes) -> Result {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self {} = self;
-        state.recorded_account_diffs_stack = Some(Default::default());
+        ccx.state.recorded_account_diffs_stack = Some(Default::default());
+        ccx.state.state_diff_baseline = ccx
+            .data
+            .journaled_state
+            .state
+            .iter()
+            .map(|(address, account)| (*address, account.info.clone()))
+            .collect();
         Ok(Default::default())
     }
 }
@@ -468,7 +545,14 @@ impl Cheatcode for startStateDiffRecordingCall {
 impl Cheatcode for stopAndReturnStateDiffCall {
     fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self {} = self;
-        get_state_diff(state)
+        get_state_diff(state, false)
+    }
+}
+
+impl Cheatcode for stopAndReturnTransientStateDiffCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self {} = self;
+        get_state_diff(state, true)
     }
 }
 
@@ -478,6 +562,100 @@ pub(super) fn get_nonce<DB: DatabaseExt>(ccx: &mut CheatsCtxt<DB>, address: &Add
     Ok(account.info.nonce.abi_encode())
 }
 
+/// Signs an authorization of the account of `private_key` for the current chain and its nonce.
+fn sign_delegation<DB: DatabaseExt>(
+    ccx: &mut CheatsCtxt<DB>,
+    private_key: &U256,
+    implementation: Address,
+) -> Result<SignedDelegation> {
+    let wallet = super::utils::parse_wallet(private_key)?;
+    let authority = wallet.address().to_alloy();
+    super::script::correct_sender_nonce(ccx)?;
+    let (account, _) = ccx.data.journaled_state.load_account(authority, ccx.data.db)?;
+    let auth = Authorization {
+        chain_id: ccx.data.env.cfg.chain_id,
+        address: implementation,
+        nonce: account.info.nonce,
+    };
+    let signed = auth.into_signed(&wallet.sign_hash(auth.signature_hash().to_ethers())?);
+    Ok(SignedDelegation {
+        v: signed.y_parity,
+        r: signed.r.into(),
+        s: signed.s.into(),
+        nonce: signed.inner.nonce,
+        implementation,
+    })
+}
+
+/// The prefix of the code of an account which delegates its code with EIP-7702, followed by the
+/// address of the implementation.
+const DELEGATION_DESIGNATOR: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// Returns the implementation `code` delegates to, if it's a delegation designator.
+pub(crate) fn delegated_implementation(code: &[u8]) -> Option<Address> {
+    code.strip_prefix(&DELEGATION_DESIGNATOR[..])
+        .filter(|address| address.len() == 20)
+        .map(Address::from_slice)
+}
+
+/// Applies the authorization to its signer, as a set code transaction of the current chain would.
+///
+/// The signer's code is set to the delegation designator and its nonce is bumped, both journaled so
+/// that a revert undoes the delegation. The EVM can't resolve the designator, so the
+/// [Cheatcodes](crate::Cheatcodes) inspector runs the code of the implementation for the calls to
+/// the signer.
+fn attach_delegation<DB: DatabaseExt>(
+    ccx: &mut CheatsCtxt<DB>,
+    delegation: &SignedDelegation,
+) -> Result {
+    let SignedDelegation { v, r, s, nonce, implementation } = *delegation;
+    let signed = SignedAuthorization {
+        inner: Authorization {
+            chain_id: ccx.data.env.cfg.chain_id,
+            address: implementation,
+            nonce,
+        },
+        y_parity: v,
+        r: U256::from_be_bytes(r.0),
+        s: U256::from_be_bytes(s.0),
+    };
+    let authority = signed
+        .recover_authority()
+        .map_err(|err| fmt_err!("invalid delegation signature: {err}"))?;
+    ensure!(!ccx.is_precompile(&authority), "cannot delegate the code of precompile {authority}");
+
+    let (account, _) = ccx.data.journaled_state.load_code(implementation, ccx.data.db)?;
+    let code = account.info.code.clone().unwrap_or_default();
+    ensure!(
+        !code.original_bytes().is_empty(),
+        "cannot delegate to {implementation}, which has no code"
+    );
+
+    let (account, _) = ccx.data.journaled_state.load_code(authority, ccx.data.db)?;
+    let delegated = account
+        .info
+        .code
+        .as_ref()
+        .is_some_and(|code| delegated_implementation(code.original_bytes().as_ref()).is_some());
+    ensure!(
+        delegated || account.info.code_hash == KECCAK_EMPTY,
+        "cannot delegate the code of {authority}, which is a contract"
+    );
+    ensure!(
+        account.info.nonce == nonce,
+        "invalid delegation nonce for {authority}: expected {}, got {nonce}",
+        account.info.nonce
+    );
+
+    let designator = [&DELEGATION_DESIGNATOR[..], implementation.as_slice()].concat();
+    let account = journaled_account(ccx.data, authority)?;
+    account.info.nonce += 1;
+    ccx.data.journaled_state.set_code(authority, Bytecode::new_raw(designator.into()).to_checked());
+    ccx.state.delegations.insert(authority, implementation);
+    debug!(target: "cheatcodes", %authority, %implementation, "attached delegation");
+    Ok(Default::default())
+}
+
 /// Reads the current caller information and returns the current [CallerMode], `msg.sender` and
 /// `tx.origin`.
 ///
@@ -543,14 +721,21 @@ pub(super) fn journaled_account<'a, DB: DatabaseExt>(
 /// In the case where `stopAndReturnStateDiff` is called at a lower
 /// depth than `startStateDiffRecording`, multiple `Vec<RecordedAccountAccesses>`
 /// will be flattened, preserving the order of the accesses.
-fn get_state_diff(state: &mut Cheatcodes) -> Result {
+///
+/// If `transient` is set, the storage accesses of the records are the transient ones.
+fn get_state_diff(state: &mut Cheatcodes, transient: bool) -> Result {
     let res = state
         .recorded_account_diffs_stack
         .replace(Default::default())
         .unwrap_or_default()
         .into_iter()
         .flatten()
-        .map(|record| record.access)
+        .map(|mut record| {
+            if transient {
+                record.access.storageAccesses = record.transient_storage_accesses;
+            }
+            record.access
+        })
         .collect::<Vec<_>>();
     Ok(res.abi_encode())
 }
@@ -0,0 +1,406 @@
+//! Summaries of the state changes of a `vm.startStateDiffRecording` session.
+
+use crate::{Cheatcode, CheatsConfig, CheatsCtxt, DatabaseExt, Result, Vm::*};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_sol_types::SolValue;
+use foundry_common::{contracts::diff_score, fs};
+use foundry_compilers::artifacts::StorageLayout;
+use foundry_config::fs_permissions::FsAccessKind;
+use revm::primitives::{AccountInfo, KECCAK_EMPTY};
+use serde_json::{json, Map, Value};
+use std::{collections::BTreeMap, fmt::Write, sync::Arc};
+
+impl Cheatcode for getStateDiffCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self {} = self;
+        Ok(format_state_diffs(&state_diffs(ccx)?).abi_encode())
+    }
+}
+
+impl Cheatcode for getStateDiffJsonCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self {} = self;
+        Ok(state_diffs_json(&state_diffs(ccx)?).to_string().abi_encode())
+    }
+}
+
+/// The changes of an account since the state diff recording started.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct AccountStateDiff {
+    /// The label of the account, see `vm.label`, or the name of its project contract
+    pub label: Option<String>,
+    /// The previous and new balance, if changed
+    pub balance: Option<(U256, U256)>,
+    /// The previous and new nonce, if changed
+    pub nonce: Option<(u64, u64)>,
+    /// The previous and new code, if changed
+    pub code: Option<(Bytes, Bytes)>,
+    /// The changed storage slots
+    pub storage: BTreeMap<B256, SlotStateDiff>,
+}
+
+impl AccountStateDiff {
+    /// Returns true if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.balance.is_none() &&
+            self.nonce.is_none() &&
+            self.code.is_none() &&
+            self.storage.is_empty()
+    }
+}
+
+/// The change of a storage slot since the state diff recording started.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SlotStateDiff {
+    /// The storage layout entries of the slot, e.g. `Vault._totalSupply`
+    pub label: Option<String>,
+    /// The value before the first access
+    pub previous_value: B256,
+    /// The current value
+    pub new_value: B256,
+}
+
+/// A project contract compiled with the `storageLayout` extra output.
+#[derive(Debug)]
+pub(crate) struct LayoutContract {
+    /// The name of the contract
+    name: String,
+    /// The deployed bytecode
+    code: Vec<u8>,
+    /// The storage layout
+    layout: StorageLayout,
+}
+
+impl LayoutContract {
+    /// Returns the labels of the variables stored in the slot, joined if packed.
+    fn slot_label(&self, slot: B256) -> Option<String> {
+        let slot = U256::from_be_bytes(slot.0);
+        let labels: Vec<_> = self
+            .layout
+            .storage
+            .iter()
+            .filter(|entry| entry.slot.parse::<U256>().ok() == Some(slot))
+            .map(|entry| {
+                let contract = entry.contract.rsplit(':').next().unwrap_or(&entry.contract);
+                format!("{contract}.{}", entry.label)
+            })
+            .collect();
+        (!labels.is_empty()).then(|| labels.join(", "))
+    }
+}
+
+/// Returns the project contracts with a storage layout, which are read from the artifacts
+/// directory on first use and then shared by all inspectors using the config.
+fn layout_contracts(config: &CheatsConfig) -> &[LayoutContract] {
+    config.layout_contracts.get_or_init(|| read_layout_contracts(config))
+}
+
+/// Reads the project contracts with a storage layout from the artifacts directory.
+///
+/// Returns no contracts if the artifacts directory isn't readable per `fs_permissions`.
+fn read_layout_contracts(config: &CheatsConfig) -> Vec<LayoutContract> {
+    let artifacts = &config.paths.artifacts;
+    let Ok(artifacts) = config.ensure_path_allowed(artifacts, FsAccessKind::Read) else {
+        return Vec::new()
+    };
+    fs::json_files(&artifacts)
+        .into_iter()
+        .filter(|path| !path.components().any(|component| component.as_os_str() == "build-info"))
+        .filter_map(|path| {
+            let mut artifact: Value = fs::read_json_file(&path).ok()?;
+            let layout: StorageLayout =
+                serde_json::from_value(artifact.get_mut("storageLayout")?.take()).ok()?;
+            if layout.storage.is_empty() {
+                return None
+            }
+            let code = hex::decode(artifact.pointer("/deployedBytecode/object")?.as_str()?).ok()?;
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            Some(LayoutContract { name, code, layout })
+        })
+        .collect()
+}
+
+/// Computes the changes of the accounts accessed since `vm.startStateDiffRecording`, without
+/// stopping the recording.
+///
+/// The previous state of an account is its state when the recording started, the previous value
+/// of a slot is its value before the first recorded access.
+pub(crate) fn state_diffs<DB: DatabaseExt>(
+    ccx: &mut CheatsCtxt<DB>,
+) -> Result<BTreeMap<Address, AccountStateDiff>> {
+    let Some(recorded) = &ccx.state.recorded_account_diffs_stack else {
+        bail!("no state diff recording in progress; call `vm.startStateDiffRecording` first")
+    };
+    let mut accessed: BTreeMap<Address, BTreeMap<B256, B256>> = BTreeMap::new();
+    for access in recorded.iter().flatten().map(|record| &record.access) {
+        accessed.entry(access.accessor).or_default();
+        accessed.entry(access.account).or_default();
+        for storage in &access.storageAccesses {
+            accessed
+                .entry(storage.account)
+                .or_default()
+                .entry(storage.slot)
+                .or_insert(storage.previousValue);
+        }
+    }
+
+    let config = Arc::clone(&ccx.state.config);
+    let contracts = layout_contracts(&config);
+    let mut diffs = BTreeMap::new();
+    for (address, slots) in accessed {
+        let previous = match ccx.state.state_diff_baseline.get(&address) {
+            Some(info) => info.clone(),
+            None => ccx.data.db.basic(address)?.unwrap_or_default(),
+        };
+        let previous_code = match previous.code {
+            Some(code) => code.original_bytes(),
+            None if previous.code_hash == KECCAK_EMPTY => Bytes::new(),
+            None => ccx.data.db.code_by_hash(previous.code_hash)?.original_bytes(),
+        };
+        let (account, _) = ccx.data.journaled_state.load_code(address, ccx.data.db)?;
+        let AccountInfo { balance, nonce, code, .. } = account.info.clone();
+        let code = code.map(|code| code.original_bytes()).unwrap_or_default();
+
+        let contract = (!code.is_empty())
+            .then(|| {
+                contracts.iter().find(|contract| {
+                    contract.code.len() == code.len() && diff_score(&contract.code, &code) < 0.1
+                })
+            })
+            .flatten();
+
+        let mut diff = AccountStateDiff {
+            label: ccx
+                .state
+                .labels
+                .get(&address)
+                .cloned()
+                .or_else(|| contract.map(|contract| contract.name.clone())),
+            balance: (previous.balance != balance).then_some((previous.balance, balance)),
+            nonce: (previous.nonce != nonce).then_some((previous.nonce, nonce)),
+            code: None,
+            storage: BTreeMap::new(),
+        };
+        if previous_code != code {
+            diff.code = Some((previous_code, code));
+        }
+        for (slot, previous_value) in slots {
+            let (value, _) = ccx.data.journaled_state.sload(address, slot.into(), ccx.data.db)?;
+            let new_value = B256::from(value.to_be_bytes());
+            if new_value != previous_value {
+                let label = contract.and_then(|contract| contract.slot_label(slot));
+                diff.storage.insert(slot, SlotStateDiff { label, previous_value, new_value });
+            }
+        }
+        if !diff.is_empty() {
+            diffs.insert(address, diff);
+        }
+    }
+    Ok(diffs)
+}
+
+/// Formats the state diffs as returned by `vm.getStateDiff`:
+///
+/// ```text
+/// 0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f (Vault):
+///   balance: 0 -> 1000
+///   nonce: 1 -> 2
+///   code: 0 bytes -> 1024 bytes
+///   0x00…02 (Vault._totalSupply): 0x00…00 -> 0x00…64
+/// ```
+pub(crate) fn format_state_diffs(diffs: &BTreeMap<Address, AccountStateDiff>) -> String {
+    let mut out = String::new();
+    for (address, diff) in diffs {
+        out.push_str(&address.to_checksum(None));
+        if let Some(label) = &diff.label {
+            let _ = write!(out, " ({label})");
+        }
+        out.push_str(":\n");
+        if let Some((previous, new)) = diff.balance {
+            let _ = writeln!(out, "  balance: {previous} -> {new}");
+        }
+        if let Some((previous, new)) = diff.nonce {
+            let _ = writeln!(out, "  nonce: {previous} -> {new}");
+        }
+        if let Some((previous, new)) = &diff.code {
+            let _ = writeln!(out, "  code: {} bytes -> {} bytes", previous.len(), new.len());
+        }
+        for (slot, slot_diff) in &diff.storage {
+            let _ = write!(out, "  {slot}");
+            if let Some(label) = &slot_diff.label {
+                let _ = write!(out, " ({label})");
+            }
+            let _ = writeln!(out, ": {} -> {}", slot_diff.previous_value, slot_diff.new_value);
+        }
+    }
+    out
+}
+
+/// Returns the state diffs as returned by `vm.getStateDiffJson`, an object keyed by the
+/// checksummed address of the account:
+///
+/// ```json
+/// {
+///   "0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f": {
+///     "label": "Vault",
+///     "balanceDiff": { "previousValue": "0x0", "newValue": "0x3e8" },
+///     "nonceDiff": { "previousValue": 1, "newValue": 2 },
+///     "codeDiff": { "previousValue": "0x", "newValue": "0x6080…" },
+///     "stateDiff": {
+///       "0x00…02": {
+///         "label": "Vault._totalSupply",
+///         "previousValue": "0x00…00",
+///         "newValue": "0x00…64"
+///       }
+///     }
+///   }
+/// }
+/// ```
+///
+/// `label`, `balanceDiff`, `nonceDiff` and `codeDiff` are only present if set or changed.
+pub(crate) fn state_diffs_json(diffs: &BTreeMap<Address, AccountStateDiff>) -> Value {
+    fn change(previous: impl Into<Value>, new: impl Into<Value>) -> Value {
+        json!({ "previousValue": previous.into(), "newValue": new.into() })
+    }
+
+    let mut accounts = Map::new();
+    for (address, diff) in diffs {
+        let mut account = Map::new();
+        if let Some(label) = &diff.label {
+            account.insert("label".to_string(), label.clone().into());
+        }
+        if let Some((previous, new)) = diff.balance {
+            account.insert("balanceDiff".to_string(), change(json!(previous), json!(new)));
+        }
+        if let Some((previous, new)) = diff.nonce {
+            account.insert("nonceDiff".to_string(), change(previous, new));
+        }
+        if let Some((previous, new)) = &diff.code {
+            account.insert("codeDiff".to_string(), change(json!(previous), json!(new)));
+        }
+        let storage = diff
+            .storage
+            .iter()
+            .map(|(slot, slot_diff)| {
+                let mut value =
+                    change(slot_diff.previous_value.to_string(), slot_diff.new_value.to_string());
+                if let Some(label) = &slot_diff.label {
+                    value["label"] = label.clone().into();
+                }
+                (slot.to_string(), value)
+            })
+            .collect::<Map<_, _>>();
+        account.insert("stateDiff".to_string(), storage.into());
+        accounts.insert(address.to_checksum(None), account.into());
+    }
+    accounts.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use foundry_config::{fs_permissions::PathPermission, Config, FsPermissions};
+
+    fn diffs() -> BTreeMap<Address, AccountStateDiff> {
+        let vault = AccountStateDiff {
+            label: Some("Vault".to_string()),
+            nonce: Some((1, 2)),
+            storage: [(
+                B256::with_last_byte(2),
+                SlotStateDiff {
+                    label: Some("Vault._totalSupply".to_string()),
+                    previous_value: B256::ZERO,
+                    new_value: B256::with_last_byte(100),
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let user = AccountStateDiff {
+            balance: Some((U256::from(1000), U256::from(900))),
+            code: Some((Bytes::new(), Bytes::from_static(&[0x60, 0x00]))),
+            ..Default::default()
+        };
+        [
+            (address!("5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"), vault),
+            (address!("0000000000000000000000000000000000000001"), user),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn can_format_state_diffs() {
+        let expected = "0x0000000000000000000000000000000000000001:
+  balance: 1000 -> 900
+  code: 0 bytes -> 2 bytes
+0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f (Vault):
+  nonce: 1 -> 2
+  0x0000000000000000000000000000000000000000000000000000000000000002 (Vault._totalSupply): \
+0x0000000000000000000000000000000000000000000000000000000000000000 -> \
+0x0000000000000000000000000000000000000000000000000000000000000064
+";
+        assert_eq!(format_state_diffs(&diffs()), expected);
+    }
+
+    #[test]
+    fn can_serialize_state_diffs() {
+        let json = state_diffs_json(&diffs());
+        let user = &json["0x0000000000000000000000000000000000000001"];
+        assert_eq!(user["balanceDiff"], json!({ "previousValue": "0x3e8", "newValue": "0x384" }));
+        assert_eq!(user["codeDiff"], json!({ "previousValue": "0x", "newValue": "0x6000" }));
+        assert_eq!(user["stateDiff"], json!({}));
+        assert!(user.get("label").is_none());
+        assert!(user.get("nonceDiff").is_none());
+
+        let vault = &json["0x5615dEB798BB3E4dFa0139dFa1b3D433Cc23b72f"];
+        assert_eq!(vault["label"], "Vault");
+        assert_eq!(vault["nonceDiff"], json!({ "previousValue": 1, "newValue": 2 }));
+        let slot = B256::with_last_byte(2).to_string();
+        assert_eq!(
+            vault["stateDiff"][slot],
+            json!({
+                "label": "Vault._totalSupply",
+                "previousValue": B256::ZERO.to_string(),
+                "newValue": B256::with_last_byte(100).to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn reads_layout_contracts_once() {
+        let root = std::env::temp_dir().join(format!("foundry-state-diff-{}", std::process::id()));
+        let artifact = json!({
+            "deployedBytecode": { "object": "0x6000" },
+            "storageLayout": {
+                "storage": [{
+                    "astId": 1,
+                    "contract": "src/Vault.sol:Vault",
+                    "label": "_totalSupply",
+                    "offset": 0,
+                    "slot": "2",
+                    "type": "t_uint256"
+                }],
+                "types": {}
+            }
+        });
+        std::fs::create_dir_all(root.join("out/Vault.sol")).unwrap();
+        std::fs::write(root.join("out/Vault.sol/Vault.json"), artifact.to_string()).unwrap();
+
+        let config = CheatsConfig::new(
+            &Config {
+                __root: root.clone().into(),
+                fs_permissions: FsPermissions::new(vec![PathPermission::read("./out")]),
+                ..Default::default()
+            },
+            Default::default(),
+        );
+        let contracts = layout_contracts(&config);
+        assert_eq!(contracts.len(), 1);
+        assert_eq!(contracts[0].slot_label(B256::with_last_byte(2)).unwrap(), "Vault._totalSupply");
+
+        // the artifacts are not read again
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(layout_contracts(&config).len(), 1);
+    }
+}
@@ -1,15 +1,19 @@
 //! Implementations of [`Filesystem`](crate::Group::Filesystem) cheatcodes.
 
-use crate::{Cheatcode, Cheatcodes, Result, Vm::*};
+use crate::{Cheatcode, Cheatcodes, CheatsConfig, Result, Vm::*};
 use alloy_json_abi::ContractObject;
-use alloy_primitives::U256;
+use alloy_primitives::{Address, U256};
 use alloy_sol_types::SolValue;
 use foundry_common::{fs, get_artifact_path};
+use foundry_compilers::sourcemap;
 use foundry_config::fs_permissions::FsAccessKind;
+use foundry_evm_core::utils::PcIcMap;
+use revm::primitives::SpecId;
 use std::{
-    collections::hash_map::Entry,
-    io::{BufRead, BufReader, Write},
-    path::Path,
+    collections::{hash_map::Entry, HashMap},
+    io::{self, BufRead, BufReader, IsTerminal, Write},
+    ops::Range,
+    path::{Path, PathBuf},
     process::Command,
     time::{SystemTime, UNIX_EPOCH},
 };
@@ -266,6 +270,156 @@ impl Cheatcode for getDeployedCodeCall {
     }
 }
 
+impl Cheatcode for getDeploymentCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { contractName: name, chainId: chain_id } = self;
+        let path = state.config.deployments.join(chain_id.to_string()).join(format!("{name}.json"));
+        let path = state.config.ensure_path_allowed(path, FsAccessKind::Read)?;
+        if !path.exists() {
+            bail!("no deployment of `{name}` on chain {chain_id} found at {}", path.display());
+        }
+        let registry: serde_json::Value = fs::read_json_file(&path)?;
+        let address = registry
+            .get("address")
+            .and_then(|address| address.as_str())
+            .and_then(|address| address.parse::<Address>().ok())
+            .ok_or_else(|| fmt_err!("invalid deployment registry file {}", path.display()))?;
+        Ok(address.abi_encode())
+    }
+}
+
+impl Cheatcode for getArtifactByDeployedCodeCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { deployedCode: code } = self;
+        let artifacts =
+            state.config.ensure_path_allowed(&state.config.paths.artifacts, FsAccessKind::Read)?;
+        match artifacts_by_deployed_code(&artifacts, code).as_slice() {
+            [] => bail!("no artifact in {} matches the deployed code", artifacts.display()),
+            [id] => Ok(id.abi_encode()),
+            ids => bail!("the deployed code matches multiple artifacts: {}", ids.join(", ")),
+        }
+    }
+}
+
+/// Returns the ids of the artifacts in `dir` whose deployed bytecode matches `code`, e.g.
+/// `Counter.sol:Counter`.
+///
+/// The compiler metadata and the immutables are ignored, which differ between builds and
+/// deployments of the same contract.
+fn artifacts_by_deployed_code(dir: &Path, code: &[u8]) -> Vec<String> {
+    let code = strip_metadata(code);
+    artifacts(dir)
+        .filter(|(_, artifact)| artifact_matches_deployed_code(artifact, code))
+        .filter_map(|(path, _)| {
+            let file = path.parent()?.strip_prefix(dir).ok()?;
+            let name = path.file_stem()?.to_str()?;
+            Some(format!("{}:{name}", file.display()))
+        })
+        .collect()
+}
+
+/// Returns the location `path:line` of the source of the instruction at `pc` of the deployed
+/// `code`, if an artifact in the artifacts directory matches it.
+///
+/// The paths of the sources are relative to the root of the project.
+pub(crate) fn source_location(config: &CheatsConfig, code: &[u8], pc: usize) -> Option<String> {
+    let stripped = strip_metadata(code);
+    // the source map may refer to any source of the compilation, e.g. inlined libraries
+    let mut sources = HashMap::new();
+    let mut source_map = None;
+    for (_, artifact) in artifacts(&config.paths.artifacts) {
+        let id = artifact.get("id").and_then(|id| id.as_u64());
+        let path = artifact.pointer("/ast/absolutePath").and_then(|path| path.as_str());
+        if let (Some(id), Some(path)) = (id, path) {
+            sources.insert(id as u32, PathBuf::from(path));
+        }
+        if source_map.is_none() && artifact_matches_deployed_code(&artifact, stripped) {
+            source_map = artifact
+                .pointer("/deployedBytecode/sourceMap")
+                .and_then(|source_map| source_map.as_str())
+                .map(str::to_string);
+        }
+    }
+
+    let source_map = sourcemap::parse(&source_map?).ok()?;
+    let ic = PcIcMap::new(SpecId::LATEST, code).get(pc)?;
+    let element = source_map.get(ic)?;
+    let path = sources.get(&element.index?)?;
+    // the sources may have changed since the artifacts were built
+    let source = std::fs::read_to_string(config.root.join(path)).ok()?;
+    let line = source.get(..element.offset)?.matches('\n').count() + 1;
+    Some(format!("{}:{line}", path.display()))
+}
+
+/// Returns the paths and the JSON of all artifacts in `dir`.
+fn artifacts(dir: &Path) -> impl Iterator<Item = (PathBuf, serde_json::Value)> {
+    WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != "build-info")
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "json"))
+        .filter_map(|entry| {
+            let artifact = fs::read_json_file(entry.path()).ok()?;
+            Some((entry.into_path(), artifact))
+        })
+}
+
+/// Returns whether the deployed bytecode of the artifact matches `code`, without its metadata.
+fn artifact_matches_deployed_code(artifact: &serde_json::Value, code: &[u8]) -> bool {
+    let Some(deployed) = artifact.get("deployedBytecode") else { return false };
+    // unlinked bytecode isn't valid hex
+    let Some(expected) = deployed
+        .get("object")
+        .and_then(|object| object.as_str())
+        .and_then(|object| hex::decode(object).ok())
+    else {
+        return false
+    };
+    let immutables = deployed
+        .get("immutableReferences")
+        .and_then(|references| references.as_object())
+        .into_iter()
+        .flat_map(|references| references.values())
+        .filter_map(|offsets| offsets.as_array())
+        .flatten()
+        .filter_map(|offset| {
+            let start = offset.get("start")?.as_u64()? as usize;
+            Some(start..start + offset.get("length")?.as_u64()? as usize)
+        })
+        .collect::<Vec<_>>();
+    matches_deployed_code(strip_metadata(&expected), code, &immutables)
+}
+
+/// Returns whether the code matches the expected code, ignoring the immutables the expected code
+/// has zeros in place of.
+fn matches_deployed_code(expected: &[u8], code: &[u8], immutables: &[Range<usize>]) -> bool {
+    if expected.is_empty() || expected.len() != code.len() {
+        return false
+    }
+    let mut code = code.to_vec();
+    for range in immutables {
+        match code.get_mut(range.clone()) {
+            Some(immutable) => immutable.fill(0),
+            None => return false,
+        }
+    }
+    code == expected
+}
+
+/// Strips the CBOR encoded compiler metadata from the end of the bytecode, if any.
+///
+/// The metadata is followed by its length as two big-endian bytes.
+fn strip_metadata(code: &[u8]) -> &[u8] {
+    let Some(len_start) = code.len().checked_sub(2) else { return code };
+    let len = u16::from_be_bytes([code[len_start], code[len_start + 1]]) as usize;
+    match len_start.checked_sub(len) {
+        // the metadata is a CBOR map
+        Some(start) if len > 0 && (0xa1..=0xb7).contains(&code[start]) => &code[..start],
+        _ => code,
+    }
+}
+
 /// Reads the bytecode object(s) from the matching artifact
 fn read_bytecode(state: &Cheatcodes, path: &str) -> Result<ContractObject> {
     let path = get_artifact_path(&state.config.paths, path);
@@ -296,6 +450,78 @@ impl Cheatcode for tryFfiCall {
     }
 }
 
+impl Cheatcode for promptCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { promptText: text } = self;
+        prompt(state, text, false, |input| Ok(input.to_string())).map(|input| input.abi_encode())
+    }
+}
+
+impl Cheatcode for promptSecretCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { promptText: text } = self;
+        let input = prompt(state, text, true, |input| Ok(input.to_string()))?;
+        state.config.record_secret(&input);
+        Ok(input.abi_encode())
+    }
+}
+
+impl Cheatcode for promptAddressCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { promptText: text } = self;
+        prompt(state, text, false, |input| input.parse::<Address>().map_err(|e| e.to_string()))
+            .map(|address| address.abi_encode())
+    }
+}
+
+impl Cheatcode for promptUintCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { promptText: text } = self;
+        prompt(state, text, false, |input| input.parse::<U256>().map_err(|e| e.to_string()))
+            .map(|number| number.abi_encode())
+    }
+}
+
+/// Prompts the user until `parse` accepts the input.
+///
+/// Reads from the terminal, or a line of the standard input if it isn't a terminal, so scripted
+/// input can be piped in.
+fn prompt<T>(
+    state: &Cheatcodes,
+    text: &str,
+    secret: bool,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<T> {
+    ensure!(
+        !state.config.non_interactive,
+        "cannot prompt for `{text}` in non-interactive mode (`--non-interactive`)"
+    );
+    let terminal = io::stdin().is_terminal();
+    loop {
+        let input = if !terminal {
+            eprint!("{text}: ");
+            let mut line = String::new();
+            if io::stdin().lock().read_line(&mut line)? == 0 {
+                bail!("cannot prompt for `{text}`: the standard input is closed");
+            }
+            eprintln!();
+            line
+        } else if secret {
+            rpassword::prompt_password(format!("{text}: "))?
+        } else {
+            dialoguer::Input::<String>::new()
+                .with_prompt(text)
+                .allow_empty(true)
+                .interact_text()
+                .map_err(|err| fmt_err!("failed to prompt for `{text}`: {err}"))?
+        };
+        match parse(input.trim()) {
+            Ok(value) => return Ok(value),
+            Err(err) => eprintln!("Invalid input: {err}. Please try again."),
+        }
+    }
+}
+
 pub(super) fn write_file(state: &Cheatcodes, path: &Path, contents: &[u8]) -> Result {
     let path = state.config.ensure_path_allowed(path, FsAccessKind::Write)?;
     // write access to foundry.toml is not allowed
@@ -339,11 +565,8 @@ fn read_dir(state: &Cheatcodes, path: &Path, max_depth: u64, follow_links: bool)
 }
 
 fn ffi(state: &Cheatcodes, input: &[String]) -> Result<FfiResult> {
-    ensure!(
-        state.config.ffi,
-        "FFI is disabled; add the `--ffi` flag to allow tests to call external commands"
-    );
     ensure!(!input.is_empty() && !input[0].is_empty(), "can't execute empty command");
+    state.config.ensure_ffi_allowed(input)?;
     let mut cmd = Command::new(&input[0]);
     cmd.args(&input[1..]);
 
@@ -403,6 +626,28 @@ mod tests {
         assert_eq!(output.stdout, msg.as_bytes());
     }
 
+    #[test]
+    fn test_strip_metadata() {
+        let code = hex::decode("6080604052fe").unwrap();
+        assert_eq!(strip_metadata(&code), &code[..]);
+
+        // `{"ipfs": <hash>, "solc": 0.8.19}`
+        let metadata = format!("a2646970667358221220{}64736f6c63430008130033", "00".repeat(32));
+        let with_metadata = hex::decode(format!("6080604052fe{metadata}")).unwrap();
+        assert_eq!(strip_metadata(&with_metadata), &code[..]);
+    }
+
+    #[test]
+    fn test_matches_deployed_code_with_immutables() {
+        let expected = [0x7f, 0, 0, 0x60, 0x01];
+        let immutables = [1..3];
+        assert!(matches_deployed_code(&expected, &[0x7f, 0xab, 0xcd, 0x60, 0x01], &immutables));
+        assert!(!matches_deployed_code(&expected, &[0x7f, 0xab, 0xcd, 0x60, 0x02], &immutables));
+        assert!(!matches_deployed_code(&expected, &[0x7f, 0xab, 0xcd, 0x60, 0x01], &[]));
+        assert!(!matches_deployed_code(&expected, &[0x7f, 0xab, 0xcd, 0x60], &immutables));
+        assert!(!matches_deployed_code(&expected, &expected, &[4..6]));
+    }
+
     #[test]
     fn test_artifact_parsing() {
         let s = include_str!("../../evm/test-data/solc-obj.json");
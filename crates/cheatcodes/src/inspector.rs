@@ -28,12 +28,12 @@ use revm::{
     interpreter::{
         opcode, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult, Interpreter,
     },
-    primitives::{BlockEnv, CreateScheme, TransactTo},
+    primitives::{AccountInfo, BlockEnv, CreateScheme, TransactTo},
     EVMData, Inspector,
 };
 use serde_json::Value;
 use std::{
-    collections::{BTreeMap, HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     fs::File,
     io::BufReader,
     ops::Range,
@@ -90,6 +90,8 @@ pub struct AccountAccess {
     pub access: crate::Vm::AccountAccess,
     /// The call depth the account was accessed.
     pub depth: u64,
+    /// The transient storage accesses, which aren't part of `access.storageAccesses`.
+    pub transient_storage_accesses: Vec<crate::Vm::StorageAccess>,
 }
 
 /// An EVM inspector that handles calls to various cheatcodes, each with their own behavior.
@@ -126,6 +128,12 @@ pub struct Cheatcodes {
     /// Address labels
     pub labels: HashMap<Address, String>,
 
+    /// The implementations the code of accounts is delegated to with `vm.attachDelegation`
+    ///
+    /// The delegation designator in the journaled code of the account is what's applied, this
+    /// only avoids loading the code of every called account.
+    pub delegations: HashMap<Address, Address>,
+
     /// Remembered private keys
     pub script_wallets: Vec<LocalWallet>,
 
@@ -148,6 +156,10 @@ pub struct Cheatcodes {
     /// merged into the previous vector.
     pub recorded_account_diffs_stack: Option<Vec<Vec<AccountAccess>>>,
 
+    /// The accounts loaded when the state diff recording started, the previous state of
+    /// `vm.getStateDiff`
+    pub state_diff_baseline: HashMap<Address, AccountInfo>,
+
     /// Recorded logs
     pub recorded_logs: Option<Vec<crate::Vm::Log>>,
 
@@ -163,6 +175,10 @@ pub struct Cheatcodes {
     /// Map of context depths to memory offset ranges that may be written to within the call depth.
     pub allowed_mem_writes: HashMap<u64, Vec<Range<u64>>>,
 
+    /// Context depths whose allowed memory writes only apply to the next call or create at that
+    /// depth, set by `expectSafeMemoryCall`.
+    pub single_call_mem_writes: HashSet<u64>,
+
     /// Current broadcasting information
     pub broadcast: Option<Broadcast>,
 
@@ -203,6 +219,9 @@ pub struct Cheatcodes {
     /// paused and creating new contracts.
     pub gas_metering_create: Option<Option<Gas>>,
 
+    /// The gas of the last call that ended, returned by `vm.lastCallGas`
+    pub last_call_gas: Option<Vm::Gas>,
+
     /// Mapping slots.
     pub mapping_slots: Option<HashMap<Address, MappingSlots>>,
 
@@ -292,6 +311,35 @@ impl Cheatcodes {
             }
         }
     }
+
+    /// Clears the memory writes allowed by `expectSafeMemoryCall` once the call they apply to
+    /// returned to `depth`.
+    fn clear_single_call_mem_writes(&mut self, depth: u64) {
+        if self.single_call_mem_writes.remove(&(depth + 1)) {
+            self.allowed_mem_writes.remove(&(depth + 1));
+        }
+    }
+
+    /// Runs the code of the implementation `call.contract` delegates to, if its code is a
+    /// delegation designator.
+    ///
+    /// Like a `DELEGATECALL`, the call keeps its context and only its code address changes. No
+    /// state is modified, so reverting the designator also reverts the delegation.
+    fn resolve_delegation<DB: DatabaseExt>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &mut CallInputs,
+    ) -> Result<()> {
+        let (account, _) = data.journaled_state.load_code(call.contract, data.db)?;
+        let Some(code) = &account.info.code else { return Ok(()) };
+        if let Some(implementation) =
+            crate::evm::delegated_implementation(code.original_bytes().as_ref())
+        {
+            call.contract = implementation;
+            call.context.code_address = implementation;
+        }
+        Ok(())
+    }
 }
 
 impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
@@ -437,7 +485,11 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                 };
                 // Ensure that we're not selfdestructing a context recording was initiated on
                 if let Some(last) = account_accesses.last_mut() {
-                    last.push(AccountAccess { access, depth: data.journaled_state.depth() });
+                    last.push(AccountAccess {
+                        access,
+                        depth: data.journaled_state.depth(),
+                        transient_storage_accesses: vec![],
+                    });
                 }
             }
         }
@@ -470,6 +522,7 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                     append_storage_access(
                         recorded_account_diffs_stack,
                         access,
+                        false,
                         data.journaled_state.depth(),
                     );
                 }
@@ -498,6 +551,32 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                     append_storage_access(
                         recorded_account_diffs_stack,
                         access,
+                        false,
+                        data.journaled_state.depth(),
+                    );
+                }
+                opcode::TLOAD | opcode::TSTORE => {
+                    let key = try_or_continue!(interpreter.stack().peek(0));
+                    let address = interpreter.contract().address;
+                    let previous_value = data.journaled_state.tload(address, key);
+                    let (is_write, new_value) = if interpreter.current_opcode() == opcode::TSTORE {
+                        (true, try_or_continue!(interpreter.stack().peek(1)))
+                    } else {
+                        (false, previous_value)
+                    };
+
+                    let access = crate::Vm::StorageAccess {
+                        account: address,
+                        slot: key.into(),
+                        isWrite: is_write,
+                        previousValue: previous_value.into(),
+                        newValue: new_value.into(),
+                        reverted: false,
+                    };
+                    append_storage_access(
+                        recorded_account_diffs_stack,
+                        access,
+                        true,
                         data.journaled_state.depth(),
                     );
                 }
@@ -546,6 +625,7 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                         access: account_access,
                         // use current depth; EXT* opcodes are not creating new contexts
                         depth: data.journaled_state.depth(),
+                        transient_storage_accesses: vec![],
                     };
                     // Record the EXT* call as an account access at the current depth
                     // (future storage accesses will be recorded in a new "Resume" context)
@@ -586,9 +666,9 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                             // If none of the allowed ranges contain [offset, offset + 32), memory has been
                             // unexpectedly mutated.
                             if !ranges.iter().any(|range| {
-                                range.contains(&offset) && range.contains(&(offset + 31))
+                                range.contains(&offset) && range.contains(&offset.saturating_add(31))
                             }) {
-                                disallowed_mem_write(offset, 32, interpreter, ranges);
+                                disallowed_mem_write(&self.config, offset, 32, interpreter, ranges);
                                 interpreter.instruction_result = InstructionResult::Revert;
                                 return
                             }
@@ -600,7 +680,34 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                             // If none of the allowed ranges contain the offset, memory has been
                             // unexpectedly mutated.
                             if !ranges.iter().any(|range| range.contains(&offset)) {
-                                disallowed_mem_write(offset, 1, interpreter, ranges);
+                                disallowed_mem_write(&self.config, offset, 1, interpreter, ranges);
+                                interpreter.instruction_result = InstructionResult::Revert;
+                                return
+                            }
+                        }
+
+                        opcode::MCOPY => {
+                            // The destination offset, source offset and size of the copy are at the
+                            // top of the stack.
+                            let dest_offset = try_or_continue!(interpreter.stack().peek(0)).saturating_to::<u64>();
+                            let offset = try_or_continue!(interpreter.stack().peek(1)).saturating_to::<u64>();
+                            let size = try_or_continue!(interpreter.stack().peek(2)).saturating_to::<u64>();
+
+                            // If none of the allowed ranges contain [dest_offset, dest_offset + size),
+                            // memory has been unexpectedly mutated. Reading the source can expand
+                            // memory as well.
+                            let contains = |start: u64| ranges.iter().any(|range| {
+                                range.contains(&start) &&
+                                    range.contains(&start.saturating_add(size - 1))
+                            });
+                            let expands = offset.saturating_add(size) > interpreter.shared_memory.len() as u64;
+                            if size > 0 && !contains(dest_offset) {
+                                disallowed_mem_write(&self.config, dest_offset, size, interpreter, ranges);
+                                interpreter.instruction_result = InstructionResult::Revert;
+                                return
+                            }
+                            if size > 0 && expands && !contains(offset) {
+                                disallowed_mem_write(&self.config, offset, size, interpreter, ranges);
                                 interpreter.instruction_result = InstructionResult::Revert;
                                 return
                             }
@@ -618,9 +725,9 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                             // memory is being expanded. If none of the allowed ranges contain
                             // [offset, offset + 32), memory has been unexpectedly mutated.
                             if offset >= interpreter.shared_memory.len() as u64 && !ranges.iter().any(|range| {
-                                range.contains(&offset) && range.contains(&(offset + 31))
+                                range.contains(&offset) && range.contains(&offset.saturating_add(31))
                             }) {
-                                disallowed_mem_write(offset, 32, interpreter, ranges);
+                                disallowed_mem_write(&self.config, offset, 32, interpreter, ranges);
                                 interpreter.instruction_result = InstructionResult::Revert;
                                 return
                             }
@@ -640,11 +747,12 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                             // If none of the allowed ranges contain [dest_offset, dest_offset + size),
                             // memory outside of the expected ranges has been touched. If the opcode
                             // only reads from memory, this is okay as long as the memory is not expanded.
-                            let fail_cond = !ranges.iter().any(|range| {
+                            // Operations of size zero don't touch memory at all.
+                            let fail_cond = size > 0 && !ranges.iter().any(|range| {
                                     range.contains(&dest_offset) &&
-                                        range.contains(&(dest_offset + size.saturating_sub(1)))
+                                        range.contains(&dest_offset.saturating_add(size - 1))
                                 }) && ($writes ||
-                                    [dest_offset, (dest_offset + size).saturating_sub(1)].into_iter().any(|offset| {
+                                    [dest_offset, dest_offset.saturating_add(size - 1)].into_iter().any(|offset| {
                                         offset >= interpreter.shared_memory.len() as u64
                                     })
                                 );
@@ -652,7 +760,7 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                             // If the failure condition is met, set the output buffer to a revert string
                             // that gives information about the allowed ranges and revert.
                             if fail_cond {
-                                disallowed_mem_write(dest_offset, size, interpreter, ranges);
+                                disallowed_mem_write(&self.config, dest_offset, size, interpreter, ranges);
                                 interpreter.instruction_result = InstructionResult::Revert;
                                 return
                             }
@@ -901,9 +1009,17 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                     storageAccesses: vec![], // updated on step
                 },
                 depth: data.journaled_state.depth(),
+                transient_storage_accesses: vec![],
             }]);
         }
 
+        // Run the code of the implementation if the target delegates its code
+        if self.delegations.contains_key(&call.contract) {
+            if let Err(err) = self.resolve_delegation(data, call) {
+                return (InstructionResult::Revert, gas, Error::encode(err));
+            }
+        }
+
         (InstructionResult::Continue, gas, Bytes::new())
     }
 
@@ -922,6 +1038,8 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
         // it for cheatcode calls because they are not appplied for cheatcodes in the `call` hook.
         // This should be placed before the revert handling, because we might exit early there
         if !cheatcode_call {
+            self.clear_single_call_mem_writes(data.journaled_state.depth());
+
             // Clean up pranks
             if let Some(prank) = &self.prank {
                 if data.journaled_state.depth() == prank.depth {
@@ -945,6 +1063,15 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                     }
                 }
             }
+
+            // In isolation mode the gas of a top-level call is the gas of its transaction
+            self.last_call_gas = Some(Vm::Gas {
+                gasLimit: remaining_gas.limit(),
+                gasTotalUsed: remaining_gas.limit().saturating_sub(remaining_gas.remaining()),
+                gasMemoryUsed: remaining_gas.memory(),
+                gasRefunded: remaining_gas.refunded(),
+                gasRemaining: remaining_gas.remaining(),
+            });
         }
 
         // Handle expected reverts
@@ -963,7 +1090,8 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                     let expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
                     return match expect::handle_expect_revert(
                         false,
-                        expected_revert.reason.as_deref(),
+                        &expected_revert,
+                        self.config.errors.as_ref(),
                         status,
                         retdata,
                     ) {
@@ -1009,6 +1137,7 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                             .access
                             .storageAccesses
                             .iter_mut()
+                            .chain(&mut element.transient_storage_accesses)
                             .for_each(|storage_access| storage_access.reverted = true);
                     })
                 }
@@ -1017,8 +1146,10 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                 // Depending on the depth the cheat was called at, there may not be any pending
                 // calls to update if execution has percolated up to a higher depth.
                 if call_access.depth == data.journaled_state.depth() {
-                    if let Ok((acc, _)) = data.journaled_state.load_account(call.contract, data.db)
-                    {
+                    // the contract of the call is the implementation if the account delegates its
+                    // code
+                    let account = call_access.access.account;
+                    if let Ok((acc, _)) = data.journaled_state.load_account(account, data.db) {
                         debug_assert!(access_is_call(call_access.access.kind));
                         call_access.access.newBalance = acc.info.balance;
                     }
@@ -1268,6 +1399,7 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                     storageAccesses: vec![], // updated on create_end
                 },
                 depth: data.journaled_state.depth(),
+                transient_storage_accesses: vec![],
             }]);
         }
 
@@ -1283,6 +1415,8 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
         remaining_gas: Gas,
         retdata: Bytes,
     ) -> (InstructionResult, Option<Address>, Gas, Bytes) {
+        self.clear_single_call_mem_writes(data.journaled_state.depth());
+
         // Clean up pranks
         if let Some(prank) = &self.prank {
             if data.journaled_state.depth() == prank.depth {
@@ -1315,7 +1449,8 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                 let expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
                 return match expect::handle_expect_revert(
                     true,
-                    expected_revert.reason.as_deref(),
+                    &expected_revert,
+                    self.config.errors.as_ref(),
                     status,
                     retdata,
                 ) {
@@ -1345,6 +1480,7 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                             .access
                             .storageAccesses
                             .iter_mut()
+                            .chain(&mut element.transient_storage_accesses)
                             .for_each(|storage_access| storage_access.reverted = true);
                     })
                 }
@@ -1390,14 +1526,29 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
 
 /// Helper that expands memory, stores a revert string pertaining to a disallowed memory write,
 /// and sets the return range to the revert string's location in memory.
+///
+/// The revert string names the violating opcode, its program counter, and its location in the
+/// sources if the code matches an artifact of the project, e.g. `MSTORE at pc 0x2F
+/// (src/Vault.sol:42)`.
 fn disallowed_mem_write(
+    config: &CheatsConfig,
     dest_offset: u64,
     size: u64,
     interpreter: &mut Interpreter<'_>,
     ranges: &[Range<u64>],
 ) {
+    let opcode = opcode::OPCODE_JUMPMAP[interpreter.current_opcode() as usize].unwrap_or("UNKNOWN");
+    let pc = interpreter.program_counter();
+    let location = crate::fs::source_location(
+        config,
+        interpreter.contract.bytecode.original_bytecode_slice(),
+        pc,
+    )
+    .map(|location| format!(" ({location})"))
+    .unwrap_or_default();
     let revert_string = format!(
-        "memory write at offset 0x{:02X} of size 0x{:02X} not allowed; safe range: {}",
+        "memory write at offset 0x{:02X} of size 0x{:02X} not allowed; safe range: {}; \
+         violated by {opcode} at pc 0x{pc:02X}{location}",
         dest_offset,
         size,
         ranges.iter().map(|r| format!("(0x{:02X}, 0x{:02X}]", r.start, r.end)).join(" ∪ ")
@@ -1496,8 +1647,16 @@ fn access_is_call(kind: crate::Vm::AccountAccessKind) -> bool {
 fn append_storage_access(
     accesses: &mut [Vec<AccountAccess>],
     storage_access: crate::Vm::StorageAccess,
+    transient: bool,
     storage_depth: u64,
 ) {
+    let push = |record: &mut AccountAccess| {
+        if transient {
+            record.transient_storage_accesses.push(storage_access);
+        } else {
+            record.access.storageAccesses.push(storage_access);
+        }
+    };
     if let Some(last) = accesses.last_mut() {
         // Assert that there's an existing record for the current context.
         if !last.is_empty() && last.first().unwrap().depth < storage_depth {
@@ -1507,11 +1666,11 @@ fn append_storage_access(
             // 2. If there's an existing Resume record, then add the storage access to it.
             // 3. Otherwise, create a new Resume record based on the current context.
             if last.len() == 1 {
-                last.first_mut().unwrap().access.storageAccesses.push(storage_access);
+                push(last.first_mut().unwrap());
             } else {
                 let last_record = last.last_mut().unwrap();
                 if last_record.access.kind as u8 == crate::Vm::AccountAccessKind::Resume as u8 {
-                    last_record.access.storageAccesses.push(storage_access);
+                    push(last_record);
                 } else {
                     let entry = last.first().unwrap();
                     let resume_record = crate::Vm::AccountAccess {
@@ -1523,7 +1682,7 @@ fn append_storage_access(
                         account: entry.access.account,
                         kind: crate::Vm::AccountAccessKind::Resume,
                         initialized: entry.access.initialized,
-                        storageAccesses: vec![storage_access],
+                        storageAccesses: vec![],
                         reverted: entry.access.reverted,
                         // The remaining fields are defaults
                         oldBalance: U256::ZERO,
@@ -1532,7 +1691,13 @@ fn append_storage_access(
                         data: vec![],
                         deployedCode: vec![],
                     };
-                    last.push(AccountAccess { access: resume_record, depth: entry.depth });
+                    let mut record = AccountAccess {
+                        access: resume_record,
+                        depth: entry.depth,
+                        transient_storage_accesses: vec![],
+                    };
+                    push(&mut record);
+                    last.push(record);
                 }
             }
         }
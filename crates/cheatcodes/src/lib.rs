@@ -21,7 +21,7 @@ mod error;
 pub use error::{Error, ErrorKind, Result};
 
 mod config;
-pub use config::CheatsConfig;
+pub use config::{CheatsConfig, EnvReport, PermissionsReport};
 
 mod inspector;
 pub use inspector::{BroadcastableTransaction, BroadcastableTransactions, Cheatcodes, Context};
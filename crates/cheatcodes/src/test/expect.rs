@@ -1,6 +1,10 @@
 use crate::{Cheatcode, Cheatcodes, CheatsCtxt, DatabaseExt, Result, Vm::*};
+use alloy_dyn_abi::JsonAbiExt;
+use alloy_json_abi::JsonAbi;
 use alloy_primitives::{address, Address, Bytes, LogData as RawLog, B256, U256};
 use alloy_sol_types::{SolError, SolValue};
+use foundry_common::fmt::format_tokens;
+use itertools::Itertools;
 use revm::interpreter::{return_ok, InstructionResult};
 use spec::Vm;
 use std::collections::{hash_map::Entry, HashMap};
@@ -70,6 +74,11 @@ pub enum ExpectedRevertKind {
 pub struct ExpectedRevert {
     /// The expected data returned by the revert, None being any
     pub reason: Option<Vec<u8>>,
+    /// Whether the revert data only has to start with the expected data
+    pub partial_match: bool,
+    /// The ABI-encoded argument of the custom error at the given index that has to match, the
+    /// other arguments may be anything
+    pub arg: Option<(usize, B256)>,
     /// The depth at which the revert is expected
     pub depth: u64,
     /// The type of expected revert.
@@ -255,6 +264,39 @@ impl Cheatcode for expectRevert_2Call {
     }
 }
 
+impl Cheatcode for expectRevert_3Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData, argIndex, argValue } = self;
+        let index = argIndex.saturating_to();
+        set_expected_revert(
+            ccx.state,
+            ExpectedRevert {
+                reason: Some(revertData.to_vec()),
+                partial_match: false,
+                arg: Some((index, *argValue)),
+                depth: ccx.data.journaled_state.depth(),
+                kind: ExpectedRevertKind::Default,
+            },
+        )
+    }
+}
+
+impl Cheatcode for expectPartialRevertCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData } = self;
+        set_expected_revert(
+            ccx.state,
+            ExpectedRevert {
+                reason: Some(revertData.to_vec()),
+                partial_match: true,
+                arg: None,
+                depth: ccx.data.journaled_state.depth(),
+                kind: ExpectedRevertKind::Default,
+            },
+        )
+    }
+}
+
 impl Cheatcode for _expectCheatcodeRevert_0Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         expect_revert(ccx.state, None, ccx.data.journaled_state.depth(), true)
@@ -285,7 +327,9 @@ impl Cheatcode for expectSafeMemoryCall {
 impl Cheatcode for expectSafeMemoryCallCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { min, max } = *self;
-        expect_safe_memory(ccx.state, min, max, ccx.data.journaled_state.depth() + 1)
+        let depth = ccx.data.journaled_state.depth() + 1;
+        ccx.state.single_call_mem_writes.insert(depth);
+        expect_safe_memory(ccx.state, min, max, depth)
     }
 }
 
@@ -471,25 +515,34 @@ fn expect_revert(
     depth: u64,
     cheatcode: bool,
 ) -> Result {
+    let kind = if cheatcode {
+        ExpectedRevertKind::Cheatcode { pending_processing: true }
+    } else {
+        ExpectedRevertKind::Default
+    };
+    let expected_revert = ExpectedRevert {
+        reason: reason.map(<[_]>::to_vec),
+        partial_match: false,
+        arg: None,
+        depth,
+        kind,
+    };
+    set_expected_revert(state, expected_revert)
+}
+
+fn set_expected_revert(state: &mut Cheatcodes, expected_revert: ExpectedRevert) -> Result {
     ensure!(
         state.expected_revert.is_none(),
         "you must call another function prior to expecting a second revert"
     );
-    state.expected_revert = Some(ExpectedRevert {
-        reason: reason.map(<[_]>::to_vec),
-        depth,
-        kind: if cheatcode {
-            ExpectedRevertKind::Cheatcode { pending_processing: true }
-        } else {
-            ExpectedRevertKind::Default
-        },
-    });
+    state.expected_revert = Some(expected_revert);
     Ok(Default::default())
 }
 
 pub(crate) fn handle_expect_revert(
     is_create: bool,
-    expected_revert: Option<&[u8]>,
+    expected: &ExpectedRevert,
+    errors: Option<&JsonAbi>,
     status: InstructionResult,
     retdata: Bytes,
 ) -> Result<(Option<Address>, Bytes)> {
@@ -504,7 +557,7 @@ pub(crate) fn handle_expect_revert(
     ensure!(!matches!(status, return_ok!()), "call did not revert as expected");
 
     // If None, accept any revert
-    let Some(expected_revert) = expected_revert else {
+    let Some(expected_revert) = expected.reason.as_deref() else {
         return Ok(success_return());
     };
 
@@ -524,23 +577,46 @@ pub(crate) fn handle_expect_revert(
         }
     }
 
-    if actual_revert == expected_revert {
+    // The argument words of the custom error follow its selector
+    let arg_word = |index: usize| actual_revert.get(4 + index * 32..4 + (index + 1) * 32);
+    let matches = if let Some((index, value)) = expected.arg {
+        actual_revert.starts_with(expected_revert) && arg_word(index) == Some(&value[..])
+    } else if expected.partial_match {
+        actual_revert.starts_with(expected_revert)
+    } else {
+        actual_revert == expected_revert
+    };
+
+    if matches {
         Ok(success_return())
     } else {
         let stringify = |data: &[u8]| {
-            String::abi_decode(data, false)
-                .ok()
+            decode_custom_error(data, errors)
+                .or_else(|| String::abi_decode(data, false).ok())
                 .or_else(|| std::str::from_utf8(data).ok().map(ToOwned::to_owned))
                 .unwrap_or_else(|| hex::encode_prefixed(data))
         };
-        Err(fmt_err!(
-            "Error != expected error: {} != {}",
-            stringify(&actual_revert),
-            stringify(expected_revert),
-        ))
+        let mut expected_revert = stringify(expected_revert);
+        if let Some((index, value)) = expected.arg {
+            expected_revert = format!("{expected_revert} with argument {index} == {value}");
+        }
+        Err(fmt_err!("Error != expected error: {} != {expected_revert}", stringify(&actual_revert)))
     }
 }
 
+/// Decodes the revert data as one of the given custom errors.
+///
+/// If the arguments can't be decoded, e.g. because only the selector is given, only the name of the
+/// error is returned.
+fn decode_custom_error(data: &[u8], errors: Option<&JsonAbi>) -> Option<String> {
+    let selector = data.get(..4)?;
+    let error = errors?.errors().find(|error| error.selector().as_slice() == selector)?;
+    Some(match error.abi_decode_input(&data[4..], false) {
+        Ok(args) => format!("{}({})", error.name, format_tokens(&args).format(", ")),
+        Err(_) => format!("{}(..)", error.name),
+    })
+}
+
 fn expect_safe_memory(state: &mut Cheatcodes, start: u64, end: u64, depth: u64) -> Result {
     ensure!(start < end, "memory range start ({start}) is greater than end ({end})");
     #[allow(clippy::single_range_in_vec_init)] // Wanted behaviour
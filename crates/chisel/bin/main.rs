@@ -11,7 +11,7 @@ use clap::Parser;
 use eyre::Context;
 use foundry_cli::{
     handler,
-    opts::CoreBuildArgs,
+    opts::{CoreBuildArgs, RawWallet},
     utils::{self, LoadConfig},
 };
 use foundry_common::{evm::EvmArgs, fs};
@@ -22,8 +22,8 @@ use foundry_config::{
     },
     Config,
 };
-use rustyline::{config::Configurer, error::ReadlineError, Editor};
-use std::path::PathBuf;
+use rustyline::{error::ReadlineError, Config as EditorConfig, Editor};
+use std::{io::Read, path::PathBuf};
 use tracing::debug;
 use yansi::Paint;
 
@@ -61,11 +61,25 @@ pub struct ChiselParser {
     ))]
     pub no_vm: bool,
 
+    /// Evaluate the statements read from stdin instead of starting the REPL.
+    ///
+    /// The process exits with a non-zero code if any of the statements failed.
+    #[clap(long, help_heading = "Script options")]
+    pub stdin: bool,
+
+    /// Print the value of the final expression and the console logs of a script as JSON.
+    #[clap(long, global = true, help_heading = "Script options")]
+    pub json: bool,
+
     #[clap(flatten)]
     pub opts: CoreBuildArgs,
 
     #[clap(flatten)]
     pub evm_opts: EvmArgs,
+
+    /// The wallet which signs the transactions of `!broadcast`.
+    #[clap(flatten)]
+    pub wallet: RawWallet,
 }
 
 /// Chisel binary subcommands
@@ -86,8 +100,26 @@ pub enum ChiselParserSub {
         id: String,
     },
 
+    /// Delete a cached session
+    Delete {
+        /// The ID of the session to delete.
+        id: String,
+    },
+
     /// Clear all cached chisel sessions from the cache directory
     ClearCache,
+
+    /// Evaluate Solidity statements and print the value of the final expression
+    Eval {
+        /// The statements to evaluate, separated by `;`.
+        code: String,
+    },
+
+    /// Evaluate a script of REPL lines non-interactively
+    Script {
+        /// The path to the script, one statement or builtin command per line.
+        path: PathBuf,
+    },
 }
 
 #[tokio::main]
@@ -104,6 +136,11 @@ async fn main() -> eyre::Result<()> {
     // Parse command args
     let args = ChiselParser::parse();
 
+    // Keep the JSON output free of colors
+    if args.json {
+        Paint::disable();
+    }
+
     // Keeps track of whether or not an interrupt was the last input
     let mut interrupt = false;
 
@@ -119,7 +156,14 @@ async fn main() -> eyre::Result<()> {
         evm_opts,
         backend: None,
         calldata: None,
+        forks: Vec::new(),
     })?;
+    dispatcher.wallet = args.wallet.clone();
+
+    // Pin the fork of `--fork-url` to its current block, which is shown in the prompt
+    if dispatcher.source().config.evm_opts.fork_url.is_some() {
+        dispatcher.resolve_fork().await?;
+    }
 
     // Execute prelude Solidity source files
     evaluate_prelude(&mut dispatcher, args.prelude).await?;
@@ -159,6 +203,14 @@ async fn main() -> eyre::Result<()> {
                 return Ok(())
             }
         }
+        Some(ChiselParserSub::Delete { id }) => {
+            match dispatcher.dispatch_command(ChiselCommand::Delete, &[id]).await {
+                DispatchResult::CommandSuccess(Some(msg)) => println!("{}", Paint::green(msg)),
+                DispatchResult::CommandFailed(e) => eprintln!("{e}"),
+                _ => panic!("Unexpected result! Please report this bug."),
+            }
+            return Ok(())
+        }
         Some(ChiselParserSub::ClearCache) => {
             match dispatcher.dispatch_command(ChiselCommand::ClearCache, &[]).await {
                 DispatchResult::CommandSuccess(Some(msg)) => println!("{}", Paint::green(msg)),
@@ -167,15 +219,28 @@ async fn main() -> eyre::Result<()> {
             }
             return Ok(())
         }
+        Some(ChiselParserSub::Eval { code }) => {
+            return run_script(&mut dispatcher, code, args.json).await
+        }
+        Some(ChiselParserSub::Script { path }) => {
+            let script = fs::read_to_string(path)?;
+            return run_script(&mut dispatcher, &script, args.json).await
+        }
+        None if args.stdin => {
+            let mut script = String::new();
+            std::io::stdin().read_to_string(&mut script).wrap_err("Could not read stdin")?;
+            return run_script(&mut dispatcher, &script, args.json).await
+        }
         None => { /* No chisel subcommand present; Continue */ }
     }
 
-    // Create a new rustyline Editor
-    let mut rl = Editor::<SolidityHelper, _>::new()?;
-    rl.set_helper(Some(SolidityHelper::default()));
-
-    // automatically add lines to history
-    rl.set_auto_add_history(true);
+    // Create a new rustyline Editor, which keeps the pasted blocks in one piece. The history is
+    // added to once a whole unit of input is read.
+    let editor_config = EditorConfig::builder().bracketed_paste(true).build();
+    let mut rl = Editor::<SolidityHelper, _>::with_config(editor_config)?;
+    let mut helper = SolidityHelper::default();
+    helper.set_completions(dispatcher.source().completions());
+    rl.set_helper(Some(helper));
 
     // load history
     if let Some(chisel_history) = chisel_history_file() {
@@ -201,9 +266,23 @@ async fn main() -> eyre::Result<()> {
                 // Clear interrupt flag
                 interrupt = false;
 
+                // Wait for the rest of the unit if its brackets are not closed yet
+                let unit = match dispatcher.buffer_line(&line) {
+                    Ok(Some(unit)) => unit,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        eprintln!("{}", Paint::red(e));
+                        continue
+                    }
+                };
+                let _ = rl.add_history_entry(unit.as_str());
+
                 // Dispatch and match results
-                let errored = dispatch_repl_line(&mut dispatcher, &line).await;
-                rl.helper_mut().unwrap().set_errored(errored);
+                let errored = dispatch_repl_unit(&mut dispatcher, &unit).await;
+                rl.helper_mut()
+                    .unwrap()
+                    .set_errored(errored)
+                    .set_completions(dispatcher.source().completions());
             }
             Err(ReadlineError::Interrupted) => {
                 if interrupt {
@@ -239,10 +318,18 @@ impl Provider for ChiselParser {
     }
 }
 
-/// Evaluate a single Solidity line.
-async fn dispatch_repl_line(dispatcher: &mut ChiselDispatcher, line: &str) -> bool {
-    let r = dispatcher.dispatch(line).await;
-    match &r {
+/// Evaluates a unit of input, which may span lines, and prints the result of each statement.
+async fn dispatch_repl_unit(dispatcher: &mut ChiselDispatcher, unit: &str) -> bool {
+    let mut errored = false;
+    for r in dispatcher.dispatch_unit(unit).await {
+        errored |= print_dispatch_result(unit, &r);
+    }
+    errored
+}
+
+/// Prints the result of a dispatch, and returns whether it is an error.
+fn print_dispatch_result(line: &str, r: &DispatchResult) -> bool {
+    match r {
         DispatchResult::Success(msg) | DispatchResult::CommandSuccess(msg) => {
             debug!(%line, ?msg, "dispatch success");
             if let Some(msg) = msg {
@@ -261,6 +348,39 @@ async fn dispatch_repl_line(dispatcher: &mut ChiselDispatcher, line: &str) -> bo
     r.is_error()
 }
 
+/// Evaluates a script non-interactively and prints the value of its final expression.
+///
+/// Exits with a non-zero code if any of its statements failed.
+async fn run_script(
+    dispatcher: &mut ChiselDispatcher,
+    script: &str,
+    json: bool,
+) -> eyre::Result<()> {
+    let output = match dispatcher.run_script(script).await {
+        Ok(output) => output,
+        Err(err) => {
+            eprintln!("{}", Paint::red(err));
+            std::process::exit(1)
+        }
+    };
+
+    if json {
+        println!("{}", output.to_json());
+        return Ok(())
+    }
+    if !output.logs.is_empty() {
+        println!("{}", Paint::green("Logs:"));
+        for log in &output.logs {
+            println!("  {log}");
+        }
+    }
+    if let Some(value) = output.value {
+        let source = dispatcher.source();
+        println!("{}", source.display(&value, source.config.foundry_config.chisel.annotate).await);
+    }
+    Ok(())
+}
+
 /// Evaluate multiple Solidity source files contained within a
 /// Chisel prelude directory.
 async fn evaluate_prelude(
@@ -291,6 +411,7 @@ async fn evaluate_prelude(
 async fn load_prelude_file(dispatcher: &mut ChiselDispatcher, file: PathBuf) -> eyre::Result<()> {
     let prelude = fs::read_to_string(file)
         .wrap_err("Could not load source file. Are you sure this path is correct?")?;
-    dispatch_repl_line(dispatcher, &prelude).await;
+    let r = dispatcher.dispatch(&prelude).await;
+    print_dispatch_result(&prelude, &r);
     Ok(())
 }
@@ -0,0 +1,117 @@
+//! Annotations
+//!
+//! This module contains the annotations of the values chisel displays with their readable forms,
+//! which `!fmt` enables. The raw value is always displayed, the annotations come after it.
+
+use crate::{
+    executor::Inspected,
+    session::{systemtime_strftime, TIME_FORMAT},
+    session_source::SessionSource,
+};
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::{utils::format_ether, Address, U256};
+use ethers_providers::Middleware;
+use foundry_common::{provider::ethers::ProviderBuilder, types::ToEthers};
+use foundry_config::{ChiselConfig, Config};
+use foundry_evm::traces::identifier::SignaturesIdentifier;
+use time::OffsetDateTime;
+use yansi::Paint;
+
+impl SessionSource {
+    /// Formats an inspected element, followed by the annotations of its value if `annotate`.
+    pub async fn display(&self, inspected: &Inspected, annotate: bool) -> String {
+        let mut formatted = inspected.to_string();
+        if annotate {
+            for annotation in self.annotations(inspected).await {
+                formatted.push_str(&format!("\n  {} {annotation}", Paint::yellow("↳")));
+            }
+        }
+        formatted
+    }
+
+    /// Returns the readable forms of an inspected value:
+    /// - the amount in ether and the UTC datetime of the uints within the ranges of the `[chisel]`
+    ///   config, see [uint_annotations]
+    /// - the function signature of the `bytes4` in the local signatures cache
+    /// - the configured label of the addresses, or their ENS name if the session is forking
+    pub async fn annotations(&self, inspected: &Inspected) -> Vec<String> {
+        let Inspected::Value { value, .. } = inspected else { return vec![] };
+        match value {
+            DynSolValue::Uint(value, _) => {
+                uint_annotations(*value, &self.config.foundry_config.chisel)
+            }
+            DynSolValue::FixedBytes(word, 4) => {
+                self.function_signature(&word[..4]).await.into_iter().collect()
+            }
+            DynSolValue::Address(address) => {
+                self.address_name(*address).await.into_iter().collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Returns the signature of the function with the selector, if it is in the local cache.
+    async fn function_signature(&self, selector: &[u8]) -> Option<String> {
+        let identifier = SignaturesIdentifier::new(Config::foundry_cache_dir(), true).ok()?;
+        let function = identifier.write().await.identify_function(selector).await?;
+        Some(function.signature())
+    }
+
+    /// Returns the configured label of the address, or its ENS name on the forked chain.
+    async fn address_name(&self, address: Address) -> Option<String> {
+        if let Some(label) = self.config.foundry_config.labels.get(&address) {
+            return Some(label.clone())
+        }
+        let url = self.config.evm_opts.fork_url.as_ref()?;
+        let provider = ProviderBuilder::new(url).build().ok()?;
+        provider.lookup_address(address.to_ethers()).await.ok()
+    }
+}
+
+/// Returns the amount in ether of the uint if it is within `10 ** ether_min_exponent` and
+/// `10 ** ether_max_exponent`, and its UTC datetime if it is within `timestamp_min` and
+/// `timestamp_max`.
+pub fn uint_annotations(value: U256, config: &ChiselConfig) -> Vec<String> {
+    let mut annotations = Vec::new();
+    let ten = U256::from(10);
+    if value >= ten.pow(U256::from(config.ether_min_exponent)) &&
+        value < ten.pow(U256::from(config.ether_max_exponent))
+    {
+        let ether = format_ether(value);
+        let ether = ether.trim_end_matches('0').trim_end_matches('.');
+        annotations.push(format!("{ether} ether"));
+    }
+    if value >= U256::from(config.timestamp_min) && value <= U256::from(config.timestamp_max) {
+        let datetime = i64::try_from(value.to::<u64>())
+            .ok()
+            .and_then(|timestamp| OffsetDateTime::from_unix_timestamp(timestamp).ok())
+            .and_then(|datetime| systemtime_strftime(datetime, TIME_FORMAT).ok());
+        if let Some(datetime) = datetime {
+            annotations.push(format!("{datetime} UTC"));
+        }
+    }
+    annotations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uint_annotations() {
+        let config = ChiselConfig::default();
+        let annotations = |value: u128| uint_annotations(U256::from(value), &config);
+
+        assert_eq!(annotations(1_500_000_000_000_000_000), ["1.5 ether"]);
+        assert_eq!(annotations(1_000_000_000_000_000_000_000), ["1000 ether"]);
+        assert_eq!(annotations(1_704_067_200), ["2024-01-01 00:00:00 UTC"]);
+        assert!(annotations(42).is_empty());
+        assert!(uint_annotations(U256::MAX, &config).is_empty());
+
+        let config = ChiselConfig { ether_min_exponent: 6, timestamp_max: 0, ..config };
+        assert_eq!(
+            uint_annotations(U256::from(1_704_067_200), &config),
+            ["0.0000000017040672 ether"]
+        );
+    }
+}
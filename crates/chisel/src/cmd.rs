@@ -19,34 +19,64 @@ pub enum ChiselCommand {
     /// Print the generated source contract
     Source,
     /// Save the current session to the cache
-    /// Takes: [session-id]
+    /// Takes: [session-name] [description]
     Save,
-    /// Load a previous session from cache
-    /// Takes: <session-id>
+    /// Load a previous session from cache, or a contract of the foundry project
+    /// Takes: <session-id|contract-name>
     ///
-    /// WARNING: This will overwrite the current session (though the current session will be
-    /// optimistically cached)
+    /// WARNING: Loading a session will overwrite the current session (though the current session
+    /// will be optimistically cached)
     Load,
+    /// Bind a variable to a loaded project contract deployed at an address
+    /// Takes: <contract-name> <addr> [var-name]
+    Attach,
+    /// Recompile the foundry project and refresh the loaded project contracts
+    Reload,
     /// List all cached sessions
     ListSessions,
+    /// Delete a cached session
+    /// Takes: <session-id>
+    Delete,
     /// Clear the cache of all stored sessions
     ClearCache,
-    /// Fork an RPC in the current session
-    /// Takes <fork-url|env-var|rpc_endpoints-alias>
+    /// Fork an RPC in the current session, or inspect the forks of the session
+    /// Takes [fork-url|env-var|rpc_endpoints-alias|fork-index] [block], or `list`, `block` or
+    /// `roll <block>`
     Fork,
     /// Enable / disable traces for the current session
     Traces,
+    /// Enable / disable the annotations of the displayed values with their readable forms, or
+    /// display an expression with its annotations
+    /// Takes: [on|off|expression]
+    Fmt,
     /// Set calldata (`msg.data`) for the current session (appended after function selector)
     Calldata,
-    /// Dump the raw memory
+    /// Dump the raw memory, or a range of it as a hex and ASCII dump
+    /// Takes: [offset length]
     MemDump,
     /// Dump the raw stack
     StackDump,
-    /// Export the current REPL session source to a Script file
+    /// Display the storage layout of the REPL contract and the values of its variables
+    StorageLayout,
+    /// Read a raw storage slot of the REPL contract
+    /// Takes: <slot>
+    Storage,
+    /// Enable / disable the gas report of the executed statements, or measure the gas of an
+    /// expression
+    /// Takes: [on|off|expression]
+    Gas,
+    /// Export the current REPL session source to a Script file, or to a test file
+    /// Takes: [path]
     Export,
     /// Fetch an interface of a verified contract on Etherscan
     /// Takes: <addr> <interface-name>
     Fetch,
+    /// Dump the state of the session in the format of `anvil --load-state`
+    /// Takes: <path>
+    DumpState,
+    /// Replay the calls and contract creations of the session as transactions against an RPC
+    /// Takes: <url|alias> [--skip-confirm]
+    Broadcast,
     /// Executes a shell command
     Exec,
     /// Display the raw value of a variable's stack allocation.
@@ -67,15 +97,24 @@ impl FromStr for ChiselCommand {
             "source" | "so" => Ok(ChiselCommand::Source),
             "save" | "s" => Ok(ChiselCommand::Save),
             "list" | "ls" => Ok(ChiselCommand::ListSessions),
+            "delete" | "del" => Ok(ChiselCommand::Delete),
             "load" | "l" => Ok(ChiselCommand::Load),
+            "attach" | "at" => Ok(ChiselCommand::Attach),
+            "reload" | "rl" => Ok(ChiselCommand::Reload),
             "clearcache" | "cc" => Ok(ChiselCommand::ClearCache),
             "fork" | "f" => Ok(ChiselCommand::Fork),
             "traces" | "t" => Ok(ChiselCommand::Traces),
+            "fmt" => Ok(ChiselCommand::Fmt),
             "calldata" | "cd" => Ok(ChiselCommand::Calldata),
             "memdump" | "md" => Ok(ChiselCommand::MemDump),
             "stackdump" | "sd" => Ok(ChiselCommand::StackDump),
+            "storagelayout" | "sl" => Ok(ChiselCommand::StorageLayout),
+            "storage" | "st" => Ok(ChiselCommand::Storage),
+            "gas" | "g" => Ok(ChiselCommand::Gas),
             "export" | "ex" => Ok(ChiselCommand::Export),
             "fetch" | "fe" => Ok(ChiselCommand::Fetch),
+            "dumpstate" | "ds" => Ok(ChiselCommand::DumpState),
+            "broadcast" | "bc" => Ok(ChiselCommand::Broadcast),
             "exec" | "e" => Ok(ChiselCommand::Exec),
             "rawstack" | "rs" => Ok(ChiselCommand::RawStack),
             "edit" => Ok(ChiselCommand::Edit),
@@ -126,19 +165,28 @@ impl From<ChiselCommand> for CmdDescriptor {
             // Session
             ChiselCommand::Clear => (&["clear", "c"], "Clear current session source", CmdCategory::Session),
             ChiselCommand::Source => (&["source", "so"], "Display the source code of the current session", CmdCategory::Session),
-            ChiselCommand::Save => (&["save [id]", "s [id]"], "Save the current session to cache", CmdCategory::Session),
-            ChiselCommand::Load => (&["load <id>", "l <id>"], "Load a previous session ID from cache", CmdCategory::Session),
-            ChiselCommand::ListSessions => (&["list", "ls"], "List all cached sessions", CmdCategory::Session),
+            ChiselCommand::Save => (&["save [name] [description]", "s [name] [description]"], "Save the current session to cache, optionally under a name and with a description", CmdCategory::Session),
+            ChiselCommand::Load => (&["load <id|contract>", "l <id|contract>"], "Load a previous session ID from cache, or import a contract of the foundry project", CmdCategory::Session),
+            ChiselCommand::Attach => (&["attach <contract> <addr> [var]", "at <contract> <addr> [var]"], "Bind a variable to a loaded project contract deployed at an address", CmdCategory::Session),
+            ChiselCommand::Reload => (&["reload", "rl"], "Recompile the foundry project and refresh the loaded project contracts", CmdCategory::Session),
+            ChiselCommand::ListSessions => (&["list", "ls"], "List all cached sessions with their description, creation time and fork", CmdCategory::Session),
+            ChiselCommand::Delete => (&["delete <id>", "del <id>"], "Delete a cached session", CmdCategory::Session),
             ChiselCommand::ClearCache => (&["clearcache", "cc"], "Clear the chisel cache of all stored sessions", CmdCategory::Session),
-            ChiselCommand::Export => (&["export", "ex"], "Export the current session source to a script file", CmdCategory::Session),
+            ChiselCommand::Export => (&["export [path]", "ex [path]"], "Export the current session source to a script file, or to a forge test file at the path", CmdCategory::Session),
             ChiselCommand::Fetch => (&["fetch <addr> <name>", "fe <addr> <name>"], "Fetch the interface of a verified contract on Etherscan", CmdCategory::Session),
+            ChiselCommand::DumpState => (&["dumpstate <path>", "ds <path>"], "Dump the accounts, code and storage of the session to a file which `anvil --load-state` loads", CmdCategory::Session),
+            ChiselCommand::Broadcast => (&["broadcast <url|alias> [--skip-confirm]", "bc <url|alias> [--skip-confirm]"], "Replay the calls and contract creations of the session as transactions signed by the wallet of chisel, confirming each of them unless --skip-confirm", CmdCategory::Session),
             // Environment
-            ChiselCommand::Fork => (&["fork <url>", "f <url>"], "Fork an RPC for the current session. Supply 0 arguments to return to a local network", CmdCategory::Env),
+            ChiselCommand::Fork => (&["fork <url|alias|index> [block]", "f <url|alias|index> [block]", "fork list", "fork block", "fork roll <block>"], "Fork an RPC for the current session at its latest block by default, list the forks of the session, show the block of the active fork or move it to another block. Supply 0 arguments to return to a local network", CmdCategory::Env),
             ChiselCommand::Traces => (&["traces", "t"], "Enable / disable traces for the current session", CmdCategory::Env),
+            ChiselCommand::Fmt => (&["fmt [on|off]", "fmt <expr>"], "Enable / disable the annotations of the displayed values with their amount in ether, datetime, function signature or address name, or display an expression with its annotations", CmdCategory::Env),
             ChiselCommand::Calldata => (&["calldata [data]", "cd [data]"], "Set calldata (`msg.data`) for the current session (appended after function selector). Clears it if no argument provided.", CmdCategory::Env),
             // Debug
-            ChiselCommand::MemDump => (&["memdump", "md"], "Dump the raw memory of the current state", CmdCategory::Debug),
+            ChiselCommand::MemDump => (&["memdump [offset len]", "md [offset len]"], "Dump the raw memory of the current state, or the range of it as a hex and ASCII dump", CmdCategory::Debug),
             ChiselCommand::StackDump => (&["stackdump", "sd"], "Dump the raw stack of the current state", CmdCategory::Debug),
+            ChiselCommand::StorageLayout => (&["storagelayout", "sl"], "Display the storage layout of the session contract with the values of its variables", CmdCategory::Debug),
+            ChiselCommand::Storage => (&["storage <slot>", "st <slot>"], "Read a raw storage slot of the session contract", CmdCategory::Debug),
+            ChiselCommand::Gas => (&["gas [on|off]", "g [on|off]", "gas <expr>", "g <expr>"], "Enable / disable the report of the gas and the memory expansion cost of each executed statement, or measure the gas of an expression without adding it to the session", CmdCategory::Debug),
             ChiselCommand::Edit => (&["edit"], "Open the current session in an editor", CmdCategory::Session),
             ChiselCommand::RawStack => (&["rawstack <var>", "rs <var>"], "Display the raw value of a variable's stack allocation. For variables that are > 32 bytes in length, this will display their memory pointer.", CmdCategory::Debug),
         }
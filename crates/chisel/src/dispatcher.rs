@@ -4,15 +4,21 @@
 //! of both builtin commands and Solidity snippets.
 
 use crate::{
+    executor::{format_storage_layout, GasUsage},
     prelude::{
-        ChiselCommand, ChiselResult, ChiselSession, CmdCategory, CmdDescriptor,
+        ChiselCommand, ChiselResult, ChiselSession, CmdCategory, CmdDescriptor, ForkInfo,
         SessionSourceConfig, SolidityHelper,
     },
+    script::split_statements,
     session_source::SessionSource,
 };
 use alloy_json_abi::JsonAbi;
-use alloy_primitives::{hex, Address};
+use alloy_primitives::{hex, Address, B256, U256};
+use alloy_providers::provider::TempProvider;
+use alloy_rpc_types::BlockNumberOrTag;
 use forge_fmt::FormatterConfig;
+use foundry_cli::opts::RawWallet;
+use foundry_common::{compile::ProjectCompiler, provider::alloy::ProviderBuilder};
 use foundry_config::{Config, RpcEndpoint};
 use foundry_evm::{
     decode::decode_console_logs,
@@ -32,6 +38,7 @@ use std::{
     io::Write,
     path::{Path, PathBuf},
     process::Command,
+    str::FromStr,
 };
 use strum::IntoEnumIterator;
 use tracing::debug;
@@ -43,11 +50,13 @@ static DEFAULT_PROMPT: &str = "➜ ";
 
 /// Command leader character
 pub static COMMAND_LEADER: char = '!';
+/// The line which opens the unclosed input in the editor
+pub static EDIT_COMMAND: &str = "!edit";
 /// Chisel character
 pub static CHISEL_CHAR: &str = "⚒️";
 
 /// Matches Solidity comments
-static COMMENT_RE: Lazy<Regex> =
+pub(crate) static COMMENT_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^\s*(?://.*\s*$)|(/*[\s\S]*?\*/\s*$)").unwrap());
 
 /// Matches Ethereum addresses
@@ -58,6 +67,23 @@ static ADDRESS_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"0x[a-fA-F0-9]{40}").u
 pub struct ChiselDispatcher {
     /// A Chisel Session
     pub session: ChiselSession,
+    /// The gas report of the executed statements, enabled with `!gas on`
+    pub gas_report: Option<GasReport>,
+    /// The wallet the transactions of `!broadcast` are signed with
+    pub wallet: RawWallet,
+    /// The lines of the input whose brackets are not closed yet, see [Self::buffer_line]
+    pending_input: String,
+}
+
+/// The state of the gas report of `!gas on`
+#[derive(Debug)]
+pub struct GasReport {
+    /// The gas usage of the empty session, i.e. of the REPL contract scaffolding
+    baseline: GasUsage,
+    /// The gas usage of the session after the last executed statement
+    last: GasUsage,
+    /// The `run()` code the last gas usage was measured for
+    run_code: String,
 }
 
 /// Chisel dispatch result variants
@@ -134,7 +160,12 @@ pub fn format_source(source: &str, config: FormatterConfig) -> eyre::Result<Stri
 impl ChiselDispatcher {
     /// Associated public function to create a new Dispatcher instance
     pub fn new(config: SessionSourceConfig) -> eyre::Result<Self> {
-        ChiselSession::new(config).map(|session| Self { session })
+        ChiselSession::new(config).map(|session| Self {
+            session,
+            gas_report: None,
+            wallet: Default::default(),
+            pending_input: String::new(),
+        })
     }
 
     /// Returns the optional ID of the current session.
@@ -161,19 +192,25 @@ impl ChiselDispatcher {
 
     /// Returns the prompt based on the current status of the Dispatcher
     pub fn get_prompt(&self) -> Cow<'static, str> {
-        match self.session.id.as_deref() {
-            // `(ID: {id}) ➜ `
-            Some(id) => {
-                let mut prompt = String::with_capacity(DEFAULT_PROMPT.len() + id.len() + 7);
-                prompt.push_str("(ID: ");
-                prompt.push_str(id);
-                prompt.push_str(") ");
-                prompt.push_str(DEFAULT_PROMPT);
-                Cow::Owned(prompt)
-            }
+        let fork = self.source().config.active_fork();
+        if self.session.id.is_none() && fork.is_none() {
             // `➜ `
-            None => Cow::Borrowed(DEFAULT_PROMPT),
+            return Cow::Borrowed(DEFAULT_PROMPT)
+        }
+
+        let mut prompt = String::with_capacity(DEFAULT_PROMPT.len() + 48);
+        // `(ID: {id}) `
+        if let Some(id) = self.session.id.as_deref() {
+            prompt.push_str("(ID: ");
+            prompt.push_str(id);
+            prompt.push_str(") ");
+        }
+        // `[{chain_id}@{block}] `
+        if let Some(fork) = fork {
+            prompt.push_str(&format!("[{}@{}] ", fork.chain_id, fork.block_number));
         }
+        prompt.push_str(DEFAULT_PROMPT);
+        Cow::Owned(prompt)
     }
 
     /// Dispatches a [ChiselCommand]
@@ -234,38 +271,65 @@ impl ChiselDispatcher {
                 self.source_mut().drain_run();
                 self.source_mut().drain_global_code();
                 self.source_mut().drain_top_level_code();
+                self.source_mut().drain_project_contracts();
                 DispatchResult::CommandSuccess(Some(String::from("Cleared session!")))
             }
             ChiselCommand::Save => {
-                if args.len() <= 1 {
-                    // If a new name was supplied, overwrite the ID of the current session.
-                    if args.len() == 1 {
-                        // TODO: Should we delete the old cache file if the id of the session
-                        // changes?
-                        self.session.id = Some(args[0].to_owned());
+                // If a new name was supplied, overwrite the ID of the current session.
+                if let Some(name) = args.first() {
+                    if !ChiselSession::is_valid_id(name) {
+                        return DispatchResult::CommandFailed(Self::make_error(
+                            "Session names may only contain alphanumeric characters, `-` and \
+                             `_`.",
+                        ))
                     }
+                    // TODO: Should we delete the old cache file if the id of the session
+                    // changes?
+                    self.session.id = Some(name.to_string());
+                }
+                // The rest of the arguments is the description
+                if args.len() > 1 {
+                    self.session.description = Some(args[1..].join(" "));
+                }
 
-                    if let Err(e) = self.session.write() {
-                        return DispatchResult::FileIoError(e.into())
-                    }
-                    DispatchResult::CommandSuccess(Some(format!(
-                        "Saved session to cache with ID = {}",
-                        self.session.id.as_ref().unwrap()
-                    )))
-                } else {
-                    DispatchResult::CommandFailed(Self::make_error("Too many arguments supplied!"))
+                if let Err(e) = self.session.write() {
+                    return DispatchResult::FileIoError(e.into())
                 }
+                DispatchResult::CommandSuccess(Some(format!(
+                    "Saved session to cache with ID = {}",
+                    self.session.id.as_ref().unwrap()
+                )))
             }
             ChiselCommand::Load => {
                 if args.len() != 1 {
-                    // Must supply a session ID as the argument.
+                    // Must supply a session ID or a contract name as the argument.
                     return DispatchResult::CommandFailed(Self::make_error(
-                        "Must supply a session ID as the argument.",
+                        "Must supply a session ID or a project contract name as the argument.",
                     ))
                 }
 
                 // Use args as the name
                 let name = args[0];
+
+                // Parse the arguments
+                let new_session = match name {
+                    "latest" => ChiselSession::latest(),
+                    _ => ChiselSession::load(name),
+                };
+
+                // If there is no such session, load the contract of the project
+                let Ok(mut new_session) = new_session else {
+                    return match self.source_mut().load_project_contract(name) {
+                        Ok(name) => DispatchResult::CommandSuccess(Some(format!(
+                            "Loaded project contract `{name}`, deploy it with `new {name}(...)` or \
+                             attach to a deployment with `!attach {name} <address>`"
+                        ))),
+                        Err(e) => DispatchResult::CommandFailed(Self::make_error(format!(
+                            "Failed to load session or project contract `{name}`: {e}"
+                        ))),
+                    }
+                };
+
                 // Try to save the current session before loading another
                 // Don't save an empty session
                 if !self.source().run_code.is_empty() {
@@ -275,38 +339,114 @@ impl ChiselDispatcher {
                     println!("{}", Paint::green("Saved current session!"));
                 }
 
-                // Parse the arguments
-                let new_session = match name {
-                    "latest" => ChiselSession::latest(),
-                    _ => ChiselSession::load(name),
+                // WARNING: Overwrites the current session
+                // Regenerate [IntermediateOutput]; It cannot be serialized.
+                //
+                // SAFETY
+                // Should never panic due to the checks performed when the session was created
+                // in the first place.
+                new_session.session_source.build().unwrap();
+
+                self.session = new_session;
+                DispatchResult::CommandSuccess(Some(format!(
+                    "Loaded Chisel session! (ID = {})",
+                    self.session.id.as_ref().unwrap()
+                )))
+            }
+            ChiselCommand::Attach => {
+                if !(2..=3).contains(&args.len()) {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "Incorrect number of arguments supplied. Expected: <contract> <address> \
+                         [var]",
+                    ))
+                }
+
+                let name = args[0];
+                let address = match args[1].parse::<Address>() {
+                    Ok(address) => address,
+                    Err(e) => {
+                        return DispatchResult::CommandFailed(Self::make_error(format!(
+                            "Invalid address \"{}\": {e}",
+                            args[1]
+                        )))
+                    }
                 };
+                if !self.source().project_contracts.contains_key(name) {
+                    if let Err(e) = self.source_mut().load_project_contract(name) {
+                        return DispatchResult::CommandFailed(Self::make_error(e))
+                    }
+                }
 
-                // WARNING: Overwrites the current session
-                if let Ok(mut new_session) = new_session {
-                    // Regenerate [IntermediateOutput]; It cannot be serialized.
-                    //
-                    // SAFETY
-                    // Should never panic due to the checks performed when the session was created
-                    // in the first place.
-                    new_session.session_source.build().unwrap();
-
-                    self.session = new_session;
-                    DispatchResult::CommandSuccess(Some(format!(
-                        "Loaded Chisel session! (ID = {})",
-                        self.session.id.as_ref().unwrap()
-                    )))
-                } else {
-                    DispatchResult::CommandFailed(Self::make_error("Failed to load session!"))
+                // Name the variable after the contract by default, e.g. `myToken` for `MyToken`
+                let var = match args.get(2) {
+                    Some(var) => var.to_string(),
+                    None => {
+                        let mut chars = name.chars();
+                        let first = chars.next().unwrap_or_default().to_ascii_lowercase();
+                        let var = format!("{first}{}", chars.as_str());
+                        if var == name {
+                            format!("{var}_")
+                        } else {
+                            var
+                        }
+                    }
+                };
+
+                // Bind the variable in the run function, without executing the session
+                let line = format!("{name} {var} = {name}({address});");
+                let mut new_source = match self.source().clone_with_new_line(line) {
+                    Ok((new_source, _)) => new_source,
+                    Err(e) => {
+                        return DispatchResult::CommandFailed(Self::make_error(format!(
+                            "Failed to parse the binding! {e}"
+                        )))
+                    }
+                };
+                match new_source.build() {
+                    Ok(_) => {
+                        *self.source_mut() = new_source;
+                        DispatchResult::CommandSuccess(Some(format!(
+                            "Attached `{var}` to the {name} at {address}"
+                        )))
+                    }
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
                 }
             }
-            ChiselCommand::ListSessions => match ChiselSession::list_sessions() {
+            ChiselCommand::Reload => match self.source_mut().reload_project_contracts() {
+                Ok(0) => DispatchResult::CommandFailed(Self::make_error(
+                    "No project contracts loaded. Use `!load <contract>` to load one.",
+                )),
+                Ok(count) => DispatchResult::CommandSuccess(Some(format!(
+                    "Reloaded {count} project contract{}!",
+                    if count == 1 { "" } else { "s" }
+                ))),
+                Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+            },
+            ChiselCommand::ListSessions => match ChiselSession::session_infos() {
                 Ok(sessions) => DispatchResult::CommandSuccess(Some(format!(
                     "{}\n{}",
                     Paint::cyan(format!("{CHISEL_CHAR} Chisel Sessions")),
                     sessions
                         .iter()
-                        .map(|(time, name)| {
-                            format!("{} - {}", Paint::blue(format!("{time:?}")), name)
+                        .map(|session| {
+                            let mut line = format!(
+                                "{} - {}",
+                                Paint::blue(&session.created_at),
+                                Paint::green(&session.id)
+                            );
+                            if let Some(description) = &session.description {
+                                line.push_str(&format!(" - {description}"));
+                            }
+                            if let Some(fork) = &session.fork {
+                                line.push_str(&format!(
+                                    " {}",
+                                    Paint::yellow(format!(
+                                        "(fork of chain {} at block {})",
+                                        fork.chain_id, fork.block_number
+                                    ))
+                                ));
+                            }
+                            line
                         })
                         .collect::<Vec<String>>()
                         .join("\n")
@@ -315,6 +455,23 @@ impl ChiselDispatcher {
                     "No sessions found. Use the `!save` command to save a session.",
                 )),
             },
+            ChiselCommand::Delete => {
+                let [id] = args else {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "Must supply a session ID as the argument.",
+                    ))
+                };
+                match ChiselSession::delete(id) {
+                    Ok(()) => {
+                        // The current session is not cached anymore
+                        if self.session.id.as_deref() == Some(*id) {
+                            self.session.id = None;
+                        }
+                        DispatchResult::CommandSuccess(Some(format!("Deleted session {id}!")))
+                    }
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+                }
+            }
             ChiselCommand::Source => match self.format_source() {
                 Ok(formatted_source) => DispatchResult::CommandSuccess(Some(
                     SolidityHelper::highlight(&formatted_source).into_owned(),
@@ -330,58 +487,116 @@ impl ChiselDispatcher {
                 }
                 Err(_) => DispatchResult::CommandFailed(Self::make_error("Failed to clear cache!")),
             },
-            ChiselCommand::Fork => {
-                if args.is_empty() || args[0].trim().is_empty() {
+            ChiselCommand::Fork => match args {
+                [] | [""] => {
                     self.source_mut().config.evm_opts.fork_url = None;
-                    return DispatchResult::CommandSuccess(Some(
-                        "Now using local environment.".to_string(),
-                    ))
+                    self.source_mut().config.evm_opts.fork_block_number = None;
+                    self.source_mut().config.backend = None;
+                    DispatchResult::CommandSuccess(Some("Now using local environment.".to_string()))
                 }
-                if args.len() != 1 {
-                    return DispatchResult::CommandFailed(Self::make_error(
-                        "Must supply a session ID as the argument.",
+                ["list" | "ls"] => {
+                    let config = &self.source().config;
+                    if config.forks.is_empty() {
+                        return DispatchResult::CommandSuccess(Some(
+                            "No forks in this session. Use `!fork <url>` to create one."
+                                .to_string(),
+                        ))
+                    }
+                    let active = config.active_fork();
+                    DispatchResult::CommandSuccess(Some(
+                        config
+                            .forks
+                            .iter()
+                            .enumerate()
+                            .map(|(i, fork)| {
+                                format!(
+                                    "{} {} - chain {} at block {} - {}",
+                                    if Some(fork) == active { "*" } else { " " },
+                                    Paint::yellow(i),
+                                    fork.chain_id,
+                                    fork.block_number,
+                                    fork.url
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n"),
                     ))
                 }
-                let arg = *args.first().unwrap();
-
-                // If the argument is an RPC alias designated in the
-                // `[rpc_endpoints]` section of the `foundry.toml` within
-                // the pwd, use the URL matched to the key.
-                let endpoint = if let Some(endpoint) =
-                    self.source_mut().config.foundry_config.rpc_endpoints.get(arg)
-                {
-                    endpoint.clone()
-                } else {
-                    RpcEndpoint::Env(arg.to_string()).into()
-                };
-                let fork_url = match endpoint.resolve() {
-                    Ok(fork_url) => fork_url,
-                    Err(e) => {
-                        return DispatchResult::CommandFailed(Self::make_error(format!(
-                            "\"{}\" ENV Variable not set!",
-                            e.var
-                        )))
+                ["block"] => match self.source().config.active_fork() {
+                    Some(fork) => DispatchResult::CommandSuccess(Some(format!(
+                        "Forking chain {} at block {}",
+                        fork.chain_id,
+                        Paint::yellow(fork.block_number)
+                    ))),
+                    None => DispatchResult::CommandFailed(Self::make_error(
+                        "Not forking. Use `!fork <url> [block]` to create a fork.",
+                    )),
+                },
+                ["roll", block] => {
+                    let Some(url) = self.source().config.evm_opts.fork_url.clone() else {
+                        return DispatchResult::CommandFailed(Self::make_error(
+                            "Not forking. Use `!fork <url> [block]` to create a fork.",
+                        ))
+                    };
+                    match block.parse::<u64>() {
+                        Ok(block) => self.switch_fork(url, Some(block)).await,
+                        Err(_) => DispatchResult::CommandFailed(Self::make_error(format!(
+                            "Invalid block number \"{block}\"!"
+                        ))),
                     }
-                };
-
-                // Check validity of URL
-                if Url::parse(&fork_url).is_err() {
-                    return DispatchResult::CommandFailed(Self::make_error("Invalid fork URL!"))
                 }
+                [arg] | [arg, _] => {
+                    let block = match args.get(1).map(|block| block.parse::<u64>()) {
+                        Some(Ok(block)) => Some(block),
+                        Some(Err(_)) => {
+                            return DispatchResult::CommandFailed(Self::make_error(format!(
+                                "Invalid block number \"{}\"!",
+                                args[1]
+                            )))
+                        }
+                        None => None,
+                    };
 
-                // Create success message before moving the fork_url
-                let success_msg = format!("Set fork URL to {}", Paint::yellow(&fork_url));
+                    // A fork of `!fork list`
+                    if let Some(fork) =
+                        arg.parse::<usize>().ok().and_then(|i| self.source().config.forks.get(i))
+                    {
+                        let url = fork.url.clone();
+                        let block = block.or(Some(fork.block_number));
+                        return self.switch_fork(url, block).await
+                    }
 
-                // Update the fork_url inside of the [SessionSourceConfig]'s [EvmOpts]
-                // field
-                self.source_mut().config.evm_opts.fork_url = Some(fork_url);
+                    // If the argument is an RPC alias designated in the
+                    // `[rpc_endpoints]` section of the `foundry.toml` within
+                    // the pwd, use the URL matched to the key.
+                    let endpoint = if let Some(endpoint) =
+                        self.source_mut().config.foundry_config.rpc_endpoints.get(*arg)
+                    {
+                        endpoint.clone()
+                    } else {
+                        RpcEndpoint::Env(arg.to_string()).into()
+                    };
+                    let fork_url = match endpoint.resolve() {
+                        Ok(fork_url) => fork_url,
+                        Err(e) => {
+                            return DispatchResult::CommandFailed(Self::make_error(format!(
+                                "\"{}\" ENV Variable not set!",
+                                e.var
+                            )))
+                        }
+                    };
 
-                // Clear the backend so that it is re-instantiated with the new fork
-                // upon the next execution of the session source.
-                self.source_mut().config.backend = None;
+                    // Check validity of URL
+                    if Url::parse(&fork_url).is_err() {
+                        return DispatchResult::CommandFailed(Self::make_error("Invalid fork URL!"))
+                    }
 
-                DispatchResult::CommandSuccess(Some(success_msg))
-            }
+                    self.switch_fork(fork_url, block).await
+                }
+                _ => DispatchResult::CommandFailed(Self::make_error(
+                    "Too many arguments supplied! Expected: <url|alias> [block]",
+                )),
+            },
             ChiselCommand::Traces => {
                 self.source_mut().config.traces = !self.source_mut().config.traces;
                 DispatchResult::CommandSuccess(Some(format!(
@@ -389,6 +604,32 @@ impl ChiselDispatcher {
                     if self.source_mut().config.traces { "Enabled" } else { "Disabled" }
                 )))
             }
+            ChiselCommand::Fmt => match args {
+                [] | ["on"] | ["off"] => {
+                    let annotate = &mut self.source_mut().config.foundry_config.chisel.annotate;
+                    *annotate = match args {
+                        ["on"] => true,
+                        ["off"] => false,
+                        _ => !*annotate,
+                    };
+                    DispatchResult::CommandSuccess(Some(format!(
+                        "{} annotations!",
+                        if *annotate { "Enabled" } else { "Disabled" }
+                    )))
+                }
+                expression => {
+                    let source = self.source();
+                    match source.inspect_value(&checksum_addresses(&expression.join(" "))).await {
+                        Ok((_, Some(inspected))) => DispatchResult::CommandSuccess(Some(
+                            source.display(&inspected, true).await,
+                        )),
+                        Ok((_, None)) => DispatchResult::CommandFailed(Self::make_error(
+                            "Could not evaluate the expression!",
+                        )),
+                        Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+                    }
+                }
+            },
             ChiselCommand::Calldata => {
                 // remove empty space, double quotes, and 0x prefix
                 let arg = args
@@ -417,6 +658,74 @@ impl ChiselDispatcher {
                     ))),
                 }
             }
+            ChiselCommand::MemDump if !args.is_empty() => {
+                let [offset, len] = args else {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "!memdump takes an offset and a length, or no arguments.",
+                    ))
+                };
+                let (Some(offset), Some(len)) = (parse_usize(offset), parse_usize(len)) else {
+                    return DispatchResult::CommandFailed(Self::make_error(format!(
+                        "Invalid memory range: {offset} {len}"
+                    )))
+                };
+                match self.source_mut().execute().await {
+                    Ok((_, res)) => match res.state.as_ref() {
+                        Some((_, mem, _)) if offset < mem.len() => {
+                            let end = mem.len().min(offset.saturating_add(len));
+                            println!("{}", hex_dump(&mem[offset..end], offset));
+                            DispatchResult::CommandSuccess(None)
+                        }
+                        Some((_, mem, _)) => DispatchResult::CommandFailed(Self::make_error(
+                            format!("The offset is out of the {} bytes of memory.", mem.len()),
+                        )),
+                        None => DispatchResult::CommandFailed(Self::make_error(
+                            "Run function is empty.",
+                        )),
+                    },
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(e.to_string())),
+                }
+            }
+            ChiselCommand::StorageLayout => {
+                let layout = match self.source_mut().storage_layout() {
+                    Ok(layout) => layout,
+                    Err(e) => return DispatchResult::CommandFailed(Self::make_error(e)),
+                };
+                if layout.storage.is_empty() {
+                    return DispatchResult::CommandSuccess(Some(
+                        "The session contract has no storage variables.".to_string(),
+                    ))
+                }
+                match self.source().storage().await {
+                    Ok(storage) => DispatchResult::CommandSuccess(Some(format_storage_layout(
+                        &layout, &storage,
+                    ))),
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+                }
+            }
+            ChiselCommand::Storage => {
+                let [slot] = args else {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "!storage takes a storage slot.",
+                    ))
+                };
+                let Ok(slot) = U256::from_str(slot) else {
+                    return DispatchResult::CommandFailed(Self::make_error(format!(
+                        "Invalid storage slot: {slot}"
+                    )))
+                };
+                match self.source().storage().await {
+                    Ok(storage) => {
+                        let value = storage.get(&slot).copied().unwrap_or_default();
+                        DispatchResult::CommandSuccess(Some(format!(
+                            "{}: {}",
+                            Paint::yellow(format!("[{slot:#x}]")),
+                            Paint::cyan(B256::from(value))
+                        )))
+                    }
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+                }
+            }
             ChiselCommand::MemDump | ChiselCommand::StackDump => {
                 match self.source_mut().execute().await {
                     Ok((_, res)) => {
@@ -450,6 +759,89 @@ impl ChiselDispatcher {
                     Err(e) => DispatchResult::CommandFailed(Self::make_error(e.to_string())),
                 }
             }
+            ChiselCommand::Gas => match args {
+                [] if self.gas_report.is_some() => {
+                    self.gas_report = None;
+                    DispatchResult::CommandSuccess(Some("Disabled gas report!".to_string()))
+                }
+                ["off"] => {
+                    self.gas_report = None;
+                    DispatchResult::CommandSuccess(Some("Disabled gas report!".to_string()))
+                }
+                [] | ["on"] => {
+                    let source = self.source();
+                    let mut empty = source.clone();
+                    empty.drain_run();
+                    let usages = match (empty.gas_usage().await, source.gas_usage().await) {
+                        (Ok(baseline), Ok(last)) => (baseline, last),
+                        (Err(e), _) | (_, Err(e)) => {
+                            return DispatchResult::CommandFailed(Self::make_error(format!(
+                                "Could not measure the gas of the session: {e}"
+                            )))
+                        }
+                    };
+                    self.gas_report = Some(GasReport {
+                        baseline: usages.0,
+                        last: usages.1,
+                        run_code: self.source().run_code.clone(),
+                    });
+                    DispatchResult::CommandSuccess(Some("Enabled gas report!".to_string()))
+                }
+                expression => match self.source().measure_gas(&expression.join(" ")).await {
+                    Ok((gas, memory)) => DispatchResult::CommandSuccess(Some(format!(
+                        "Gas: {} | Memory expansion: {}",
+                        Paint::yellow(gas),
+                        Paint::yellow(memory)
+                    ))),
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(format!(
+                        "Could not measure the gas of the expression: {e}"
+                    ))),
+                },
+            },
+            ChiselCommand::DumpState => {
+                let [path] = args else {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "!dumpstate takes the path of the file to write the state to",
+                    ))
+                };
+                let state = match self.source().dump_state().await {
+                    Ok(state) => state,
+                    Err(e) => {
+                        return DispatchResult::CommandFailed(Self::make_error(format!(
+                            "Could not dump the state of the session: {e}"
+                        )))
+                    }
+                };
+                match foundry_common::fs::write_json_file(Path::new(path), &state) {
+                    Ok(()) => DispatchResult::CommandSuccess(Some(format!(
+                        "Dumped the state of the session to {path}, load it with `anvil \
+                         --load-state {path}`"
+                    ))),
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(e)),
+                }
+            }
+            ChiselCommand::Broadcast => {
+                let skip_confirm = args.contains(&"--skip-confirm");
+                let rpc: Vec<&str> =
+                    args.iter().copied().filter(|a| *a != "--skip-confirm").collect();
+                let [rpc] = rpc[..] else {
+                    return DispatchResult::CommandFailed(Self::make_error(
+                        "!broadcast takes an RPC URL or alias, and optionally --skip-confirm",
+                    ))
+                };
+                match self.broadcast(rpc, skip_confirm).await {
+                    Ok(0) => DispatchResult::CommandSuccess(Some(
+                        "The session makes no calls nor contract creations to broadcast"
+                            .to_string(),
+                    )),
+                    Ok(sent) => DispatchResult::CommandSuccess(Some(format!(
+                        "Broadcast {sent} transactions!"
+                    ))),
+                    Err(e) => DispatchResult::CommandFailed(Self::make_error(format!(
+                        "Could not broadcast the session: {e:#}"
+                    ))),
+                }
+            }
             ChiselCommand::Export => {
                 // Check if the current session inherits `Script.sol` before exporting
 
@@ -460,6 +852,10 @@ impl ChiselDispatcher {
                     ));
                 }
 
+                if let [path] = args {
+                    return self.export_test(Path::new(path))
+                }
+
                 // Create "script" dir if it does not already exist.
                 if !Path::new("script").exists() {
                     if let Err(e) = std::fs::create_dir_all("script") {
@@ -634,52 +1030,13 @@ impl ChiselDispatcher {
                 }
             }
             ChiselCommand::Edit => {
-                // create a temp file with the content of the run code
-                let mut temp_file_path = std::env::temp_dir();
-                temp_file_path.push("chisel-tmp.sol");
-                let result = std::fs::File::create(&temp_file_path)
-                    .map(|mut file| file.write_all(self.source().run_code.as_bytes()));
-                if let Err(e) = result {
-                    return DispatchResult::CommandFailed(format!(
-                        "Could not write to a temporary file: {e}"
-                    ))
-                }
-
-                // open the temp file with the editor
-                let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
-                let mut cmd = Command::new(editor);
-                cmd.arg(&temp_file_path);
-
-                match cmd.status() {
-                    Ok(status) => {
-                        if !status.success() {
-                            if let Some(status_code) = status.code() {
-                                return DispatchResult::CommandFailed(format!(
-                                    "Editor exited with status {status_code}"
-                                ))
-                            } else {
-                                return DispatchResult::CommandFailed(
-                                    "Editor exited without a status code".to_string(),
-                                )
-                            }
-                        }
-                    }
-                    Err(_) => {
-                        return DispatchResult::CommandFailed(
-                            "Editor exited without a status code".to_string(),
-                        )
-                    }
-                }
-
+                let edited_code = match edit_in_editor(&self.source().run_code) {
+                    Ok(edited_code) => edited_code,
+                    Err(e) => return DispatchResult::CommandFailed(e),
+                };
                 let mut new_session_source = self.source().clone();
-                if let Ok(edited_code) = std::fs::read_to_string(temp_file_path) {
-                    new_session_source.drain_run();
-                    new_session_source.with_run_code(&edited_code);
-                } else {
-                    return DispatchResult::CommandFailed(
-                        "Could not read the edited file".to_string(),
-                    )
-                }
+                new_session_source.drain_run();
+                new_session_source.with_run_code(&edited_code);
 
                 // if the editor exited successfully, try to compile the new code
                 match new_session_source.execute().await {
@@ -755,6 +1112,265 @@ impl ChiselDispatcher {
         }
     }
 
+    /// Prints the gas consumed by the statement executed in `new_source`, the result of its
+    /// execution, and the total gas of the session.
+    async fn report_gas(&mut self, new_source: &SessionSource, res: &ChiselResult) {
+        let usage = GasUsage::new(res);
+        let Some(report) = &self.gas_report else { return };
+        // The session may have changed since the last statement, e.g. with `!edit`
+        let previous = if report.run_code == self.source().run_code {
+            Ok(report.last)
+        } else {
+            self.source().gas_usage().await
+        };
+        let Some(report) = &mut self.gas_report else { return };
+        match previous {
+            Ok(previous) => {
+                let (gas, memory) = usage.since(&previous);
+                let (total, _) = usage.since(&report.baseline);
+                println!(
+                    "Gas: {} | Memory expansion: {} | Session total: {}",
+                    Paint::yellow(gas),
+                    Paint::yellow(memory),
+                    Paint::yellow(total)
+                );
+            }
+            Err(e) => debug!(%e, "could not measure the gas of the previous session"),
+        }
+        report.last = usage;
+        report.run_code = new_source.run_code.clone();
+    }
+
+    /// Exports the session to a forge test file and checks that it compiles in the project.
+    fn export_test(&self, path: &Path) -> DispatchResult {
+        let source = self.source();
+        let config = &source.config.foundry_config;
+        if !config.get_all_remappings().into_iter().any(|r| r.name.starts_with("forge-std")) {
+            return DispatchResult::CommandFailed(Self::make_error(
+                "Exporting to a test file requires forge-std, install it with `forge install \
+                 foundry-rs/forge-std`.",
+            ))
+        }
+
+        // `test/Counter.t.sol` contains `CounterTest`
+        let stem = path.file_name().unwrap_or_default().to_string_lossy();
+        let stem = stem.split('.').next().unwrap_or_default();
+        let mut contract_name =
+            stem.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '_').collect::<String>();
+        if contract_name.is_empty() || contract_name.starts_with(|c: char| c.is_ascii_digit()) {
+            contract_name.insert_str(0, "Repl");
+        }
+        if !contract_name.ends_with("Test") {
+            contract_name.push_str("Test");
+        }
+
+        let test_source = source.to_test_source(&contract_name);
+        let test_source = format_source(&test_source, config.fmt.clone()).unwrap_or(test_source);
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            if let Err(e) = std::fs::create_dir_all(parent) {
+                return DispatchResult::CommandFailed(Self::make_error(e))
+            }
+        }
+        if let Err(e) = std::fs::write(path, test_source) {
+            return DispatchResult::CommandFailed(Self::make_error(e))
+        }
+
+        // The exported file must compile in the project
+        let compiled = config.project().map_err(Into::into).and_then(|project| {
+            let file = project.root().join(path);
+            ProjectCompiler::new().quiet(true).files([file]).compile(&project)
+        });
+        match compiled {
+            Ok(_) => DispatchResult::CommandSuccess(Some(format!(
+                "Exported session to {} as `{contract_name}`, run it with `forge test --match-path \
+                 {}`",
+                path.display(),
+                path.display()
+            ))),
+            Err(e) => DispatchResult::CommandFailed(Self::make_error(format!(
+                "Exported session to {}, but it does not compile in the project: {e}",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Resolves the chain id and the block of the fork of the session, pinning the fork to its
+    /// latest block if no block was set, and adds it to the forks of the session.
+    pub async fn resolve_fork(&mut self) -> eyre::Result<ForkInfo> {
+        let evm_opts = &self.source().config.evm_opts;
+        let url =
+            evm_opts.fork_url.clone().ok_or_else(|| eyre::eyre!("The session is not forking"))?;
+        let env = evm_opts.evm_env().await?;
+        let fork = ForkInfo {
+            url,
+            chain_id: env.cfg.chain_id,
+            block_number: env.block.number.saturating_to(),
+        };
+
+        let config = &mut self.source_mut().config;
+        config.evm_opts.fork_block_number = Some(fork.block_number);
+        if !config.forks.contains(&fork) {
+            config.forks.push(fork.clone());
+        }
+        Ok(fork)
+    }
+
+    /// Switches the session to the fork of the URL at the block, or at its latest block.
+    ///
+    /// The session is executed on the new fork, warning about the addresses it uses which have
+    /// no code there and about failures to execute it, since its state-dependent values may have
+    /// been computed on a previous fork.
+    async fn switch_fork(&mut self, url: String, block: Option<u64>) -> DispatchResult {
+        let previous = self.source().config.clone();
+
+        let config = &mut self.source_mut().config;
+        config.evm_opts.fork_url = Some(url);
+        config.evm_opts.fork_block_number = block;
+        // Clear the backend so that it is re-instantiated with the new fork
+        // upon the next execution of the session source.
+        config.backend = None;
+
+        let fork = match self.resolve_fork().await {
+            Ok(fork) => fork,
+            Err(e) => {
+                // Keep the previous fork
+                self.source_mut().config = previous;
+                return DispatchResult::CommandFailed(Self::make_error(format!(
+                    "Could not fork: {e}"
+                )))
+            }
+        };
+
+        if !self.source().run_code.is_empty() {
+            for warning in self.fork_state_warnings(&fork).await {
+                println!("{}", Paint::yellow(warning));
+            }
+        }
+
+        DispatchResult::CommandSuccess(Some(format!(
+            "Forking chain {} at block {} of {}",
+            fork.chain_id,
+            Paint::yellow(fork.block_number),
+            Paint::yellow(&fork.url)
+        )))
+    }
+
+    /// Returns the warnings about the session state which is invalid on the fork: the addresses
+    /// without code there, and the failure to execute the session on it.
+    async fn fork_state_warnings(&mut self, fork: &ForkInfo) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let source = self.source();
+
+        // The contracts the session uses may only have been deployed on a previous fork
+        if !source.config.foundry_config.offline {
+            let code = format!("{}\n{}", source.top_level_code, source.run_code);
+            let mut addresses = ADDRESS_RE
+                .find_iter(&code)
+                .filter_map(|m| m.as_str().parse::<Address>().ok())
+                .collect::<Vec<_>>();
+            addresses.sort();
+            addresses.dedup();
+            if !addresses.is_empty() {
+                match ProviderBuilder::new(&fork.url).build() {
+                    Ok(provider) => {
+                        for address in addresses {
+                            let block = BlockNumberOrTag::Number(fork.block_number).into();
+                            match provider.get_code_at(address, block).await {
+                                Ok(code) if code.is_empty() => warnings.push(format!(
+                                    "{address} has no code on this fork, it may have been \
+                                     deployed on a previous one: redeploy it in the session, \
+                                     e.g. with `new <Contract>(...)`, or switch back with \
+                                     `!fork <index>` (see `!fork list`)"
+                                )),
+                                Ok(_) => {}
+                                Err(e) => debug!(%address, %e, "could not fetch code"),
+                            }
+                        }
+                    }
+                    Err(e) => debug!(%e, "could not connect to fork"),
+                }
+            }
+        }
+
+        // The session variables are recomputed on the new fork
+        let mut source = source.clone();
+        let executed = source.execute().await;
+        // Reuse the backend of the new fork
+        self.source_mut().config.backend = source.config.backend;
+        match executed {
+            Ok((_, res)) if res.success => {}
+            _ => warnings.push(
+                "The session fails to execute on this fork, its state-dependent values are only \
+                 valid on a previous one. Update it with `!edit`, `!clear` it, or switch back with \
+                 `!fork <index>` (see `!fork list`)"
+                    .to_string(),
+            ),
+        }
+        warnings
+    }
+
+    /// Returns whether the input of the previous lines is not closed yet.
+    pub fn is_buffering(&self) -> bool {
+        !self.pending_input.is_empty()
+    }
+
+    /// Buffers a line of input until all of its brackets are closed.
+    ///
+    /// A builtin command is returned as is, unless it follows unclosed input. An [EDIT_COMMAND]
+    /// line after unclosed input opens the input in the editor and buffers it once edited.
+    ///
+    /// ### Returns
+    ///
+    /// The whole unit of input once it is closed, to be dispatched with [Self::dispatch_unit]
+    pub fn buffer_line(&mut self, line: &str) -> eyre::Result<Option<String>> {
+        if self.pending_input.is_empty() && line.trim_start().starts_with(COMMAND_LEADER) {
+            return Ok(Some(line.to_string()))
+        }
+
+        let mut input = std::mem::take(&mut self.pending_input);
+        input.push_str(line);
+        let (head, last) = input.rsplit_once('\n').unwrap_or(("", &input));
+        if last.trim() == EDIT_COMMAND && !head.trim().is_empty() {
+            let head = head.to_string();
+            match edit_in_editor(&head) {
+                Ok(edited) => input = edited.trim_end().to_string(),
+                Err(e) => {
+                    self.pending_input = head + "\n";
+                    eyre::bail!(e)
+                }
+            }
+        }
+
+        if SolidityHelper::is_closed(&input) {
+            Ok(Some(input))
+        } else {
+            input.push('\n');
+            self.pending_input = input;
+            Ok(None)
+        }
+    }
+
+    /// Dispatches a unit of input, which may span lines, e.g. a pasted block.
+    ///
+    /// The statements of an input of several lines are dispatched one by one, see
+    /// [split_statements], up to the first one which fails.
+    pub async fn dispatch_unit(&mut self, input: &str) -> Vec<DispatchResult> {
+        if !input.trim().contains('\n') {
+            return vec![self.dispatch(input).await]
+        }
+
+        let mut results = Vec::new();
+        for statement in split_statements(input) {
+            let res = self.dispatch(&statement.code).await;
+            let failed = res.is_error();
+            results.push(res);
+            if failed {
+                break
+            }
+        }
+        results
+    }
+
     /// Dispatches an input as a command via [Self::dispatch_command] or as a Solidity snippet.
     pub async fn dispatch(&mut self, mut input: &str) -> DispatchResult {
         // Check if the input is a builtin command.
@@ -785,15 +1401,7 @@ impl ChiselDispatcher {
 
         // If there is an address (or multiple addresses) in the input, ensure that they are
         // encoded with a valid checksum per EIP-55.
-        let mut heap_input = input.to_string();
-        ADDRESS_RE.find_iter(input).for_each(|m| {
-            // Convert the match to a string slice
-            let match_str = m.as_str();
-            // We can always safely unwrap here due to the regex matching.
-            let addr: Address = match_str.parse().expect("Valid address regex");
-            // Replace all occurrences of the address with a checksummed version
-            heap_input = heap_input.replace(match_str, &addr.to_string());
-        });
+        let heap_input = checksum_addresses(input);
         // Replace the old input with the formatted input.
         input = &heap_input;
 
@@ -809,13 +1417,18 @@ impl ChiselDispatcher {
 
         // TODO: Cloning / parsing the session source twice on non-inspected inputs kinda sucks.
         // Should change up how this works.
-        match source.inspect(input).await {
+        let annotate = source.config.foundry_config.chisel.annotate;
+        match source.inspect_value(input).await {
             // Continue and print
-            Ok((true, Some(res))) => println!("{res}"),
+            Ok((true, Some(res))) => println!("{}", source.display(&res, annotate).await),
             Ok((true, None)) => {}
             // Return successfully
             Ok((false, res)) => {
                 debug!(%input, ?res, "inspect success");
+                let res = match res {
+                    Some(res) => Some(source.display(&res, annotate).await),
+                    None => None,
+                };
                 return DispatchResult::Success(res)
             }
 
@@ -855,6 +1468,10 @@ impl ChiselDispatcher {
                         }
                     }
 
+                    if self.gas_report.is_some() {
+                        self.report_gas(&new_source, &res).await;
+                    }
+
                     // Replace the old session source with the new version
                     *self.source_mut() = new_source;
 
@@ -953,6 +1570,74 @@ impl ChiselDispatcher {
     }
 }
 
+/// Parses a decimal or a `0x` prefixed hex number.
+fn parse_usize(s: &str) -> Option<usize> {
+    match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// Formats bytes as a hex and ASCII dump of 16 bytes per line, the first one being at the offset.
+fn hex_dump(bytes: &[u8], offset: usize) -> String {
+    let lines = bytes.chunks(16).enumerate().map(|(i, chunk)| {
+        let hex = chunk.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(" ");
+        let ascii = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect::<String>();
+        format!("{:#06x}: {hex:<47}  |{ascii}|", offset + i * 16)
+    });
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+/// Returns the input with all of its addresses encoded with a valid checksum per EIP-55.
+pub(crate) fn checksum_addresses(input: &str) -> String {
+    let mut heap_input = input.to_string();
+    ADDRESS_RE.find_iter(input).for_each(|m| {
+        // Convert the match to a string slice
+        let match_str = m.as_str();
+        // We can always safely unwrap here due to the regex matching.
+        let addr: Address = match_str.parse().expect("Valid address regex");
+        // Replace all occurrences of the address with a checksummed version
+        heap_input = heap_input.replace(match_str, &addr.to_string());
+    });
+    heap_input
+}
+
+/// Opens `content` in `$EDITOR`, `vim` by default, and returns it once the editor exits.
+fn edit_in_editor(content: &str) -> Result<String, String> {
+    // create a temp file with the content
+    let mut temp_file_path = std::env::temp_dir();
+    temp_file_path.push("chisel-tmp.sol");
+    let result =
+        std::fs::File::create(&temp_file_path).map(|mut file| file.write_all(content.as_bytes()));
+    if let Err(e) = result {
+        return Err(format!("Could not write to a temporary file: {e}"))
+    }
+
+    // open the temp file with the editor
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
+    let mut cmd = Command::new(editor);
+    cmd.arg(&temp_file_path);
+
+    match cmd.status() {
+        Ok(status) => {
+            if !status.success() {
+                if let Some(status_code) = status.code() {
+                    return Err(format!("Editor exited with status {status_code}"))
+                } else {
+                    return Err("Editor exited without a status code".to_string())
+                }
+            }
+        }
+        Err(_) => return Err("Editor exited without a status code".to_string()),
+    }
+
+    std::fs::read_to_string(temp_file_path)
+        .map_err(|_| "Could not read the edited file".to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -967,4 +1652,18 @@ mod tests {
         assert!(COMMENT_RE.is_match(" \t\n  /* block \n \t comment */\n"));
         assert!(!COMMENT_RE.is_match("/* block \n \t comment */\nwith \tother"));
     }
+
+    #[test]
+    fn test_hex_dump() {
+        let mut bytes = b"hello, chisel!\n".to_vec();
+        bytes.extend([0xff; 4]);
+        assert_eq!(
+            hex_dump(&bytes, 0x40),
+            "0x0040: 68 65 6c 6c 6f 2c 20 63 68 69 73 65 6c 21 0a ff  |hello, chisel!..|\n\
+             0x0050: ff ff ff                                         |...|"
+        );
+        assert_eq!(parse_usize("0x20"), Some(32));
+        assert_eq!(parse_usize("64"), Some(64));
+        assert_eq!(parse_usize("0xzz"), None);
+    }
 }
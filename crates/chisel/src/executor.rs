@@ -8,20 +8,53 @@ use crate::prelude::{
 use alloy_dyn_abi::{DynSolType, DynSolValue};
 use alloy_json_abi::EventParam;
 use alloy_primitives::{hex, Address, U256};
-use core::fmt::Debug;
+use core::fmt::{self, Debug};
 use eyre::{Result, WrapErr};
-use foundry_compilers::Artifact;
+use foundry_common::fmt::format_token_raw;
+use foundry_compilers::{artifacts::StorageLayout, Artifact};
 use foundry_evm::{
     backend::Backend, decode::decode_console_logs, executors::ExecutorBuilder,
     inspectors::CheatsConfig,
 };
 use solang_parser::pt::{self, CodeLocation};
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 use tracing::debug;
 use yansi::Paint;
 
 const USIZE_MAX_AS_U256: U256 = U256::from_limbs([usize::MAX as u64, 0, 0, 0]);
 
+/// The gas consumed by an execution of the session, see `!gas`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GasUsage {
+    /// The gas consumed by the call to `run()`
+    pub gas: u64,
+    /// The size of the memory at the last statement of `run()`, in bytes
+    pub memory: usize,
+}
+
+impl GasUsage {
+    /// Returns the gas usage of an execution of the session
+    pub fn new(result: &ChiselResult) -> Self {
+        let memory = result.state.as_ref().map_or(0, |(_, memory, _)| memory.len());
+        Self { gas: result.call_gas_used, memory }
+    }
+
+    /// Returns the cost of the expansion of the memory to its size
+    pub fn memory_cost(&self) -> u64 {
+        let words = (self.memory as u64).div_ceil(32);
+        3 * words + words * words / 512
+    }
+
+    /// Returns the gas consumed and the memory expansion cost of the code executed in addition to
+    /// the one of `previous`.
+    pub fn since(&self, previous: &Self) -> (u64, u64) {
+        (
+            self.gas.saturating_sub(previous.gas),
+            self.memory_cost().saturating_sub(previous.memory_cost()),
+        )
+    }
+}
+
 /// Executor implementation for [SessionSource]
 impl SessionSource {
     /// Runs the source with the [ChiselRunner]
@@ -33,6 +66,16 @@ impl SessionSource {
     ///
     /// Returns an error if compilation fails.
     pub async fn execute(&mut self) -> Result<(Address, ChiselResult)> {
+        let (address, res, _) = self.execute_with_runner().await?;
+        Ok((address, res))
+    }
+
+    /// Runs the source like [SessionSource::execute], also returning the [ChiselRunner] it was run
+    /// with, so that the state of its executor can be read. There is no runner if the `run()`
+    /// function is empty.
+    pub(crate) async fn execute_with_runner(
+        &mut self,
+    ) -> Result<(Address, ChiselResult, Option<ChiselRunner>)> {
         // Recompile the project and ensure no errors occurred.
         let compiled = self.build()?;
         if let Some((_, contract)) =
@@ -109,16 +152,69 @@ impl SessionSource {
                 let mut runner = self.prepare_runner(final_pc).await;
 
                 // Return [ChiselResult] or bubble up error
-                runner.run(bytecode.into_owned())
+                let (address, res) = runner.run(bytecode.into_owned())?;
+                Ok((address, res, Some(runner)))
             } else {
                 // Return a default result if no statements are present.
-                Ok((Address::ZERO, ChiselResult::default()))
+                Ok((Address::ZERO, ChiselResult::default(), None))
             }
         } else {
             eyre::bail!("Failed to find REPL contract!")
         }
     }
 
+    /// Executes the session and returns the gas it consumes.
+    ///
+    /// An empty session consumes the gas of the REPL contract scaffolding, which is the baseline
+    /// the gas of the statements is measured against.
+    pub async fn gas_usage(&self) -> Result<GasUsage> {
+        let mut source = self.clone();
+        if source.run_code.trim().is_empty() {
+            // an empty block so that the REPL contract is still called
+            source.with_run_code("{}");
+        }
+        let (_, res) = source.execute().await?;
+        if !res.success {
+            eyre::bail!("The session reverted")
+        }
+        Ok(GasUsage::new(&res))
+    }
+
+    /// Measures the gas consumed by a statement or an expression, without adding it to the
+    /// session.
+    ///
+    /// ### Returns
+    ///
+    /// The gas consumed and the memory expansion cost of the input.
+    pub async fn measure_gas(&self, input: &str) -> Result<(u64, u64)> {
+        let (source, _) = self.clone_with_new_line(input.to_string())?;
+        let previous = self.gas_usage().await?;
+        let usage = source.gas_usage().await?;
+        Ok(usage.since(&previous))
+    }
+
+    /// Executes the session and returns the non-zero storage slots of the REPL contract.
+    pub async fn storage(&self) -> Result<BTreeMap<U256, U256>> {
+        let mut source = self.clone();
+        if source.run_code.trim().is_empty() {
+            // an empty block so that the REPL contract is still deployed
+            source.with_run_code("{}");
+        }
+        let (_, res) = source.execute().await?;
+        Ok(res.storage)
+    }
+
+    /// Returns the storage layout of the REPL contract, as output by the compiler.
+    pub fn storage_layout(&mut self) -> Result<StorageLayout> {
+        let compiled = self.build()?;
+        compiled
+            .compiler_output
+            .contracts_into_iter()
+            .find(|(name, _)| name == "REPL")
+            .map(|(_, contract)| contract.storage_layout)
+            .ok_or_else(|| eyre::eyre!("Failed to find REPL contract!"))
+    }
+
     /// Inspect a contract element inside of the current session
     ///
     /// ### Takes
@@ -131,6 +227,18 @@ impl SessionSource {
     /// - `continue` is true if the input should be appended to the source
     /// - `formatted_output` is the formatted value, if any
     pub async fn inspect(&self, input: &str) -> Result<(bool, Option<String>)> {
+        let (cont, inspected) = self.inspect_value(input).await?;
+        Ok((cont, inspected.map(|inspected| inspected.to_string())))
+    }
+
+    /// Inspect a contract element inside of the current session, like [SessionSource::inspect],
+    /// without formatting it.
+    ///
+    /// ### Returns
+    ///
+    /// If the input is valid `Ok((continue, inspected))` where `inspected` is the decoded value
+    /// or the event definition, if any
+    pub async fn inspect_value(&self, input: &str) -> Result<(bool, Option<Inspected>)> {
         let line = format!("bytes memory inspectoor = abi.encode({input});");
         let mut source = match self.clone_with_new_line(line.clone()) {
             Ok((source, _)) => source,
@@ -175,7 +283,7 @@ impl SessionSource {
 
             if let Some(event_definition) = intermediate_contract.event_definitions.get(input) {
                 let formatted = format_event_definition(event_definition)?;
-                return Ok((false, Some(formatted)))
+                return Ok((false, Some(Inspected::Event(formatted))))
             }
 
             // we were unable to check the event
@@ -244,7 +352,7 @@ impl SessionSource {
         // `tokens` is guaranteed to have the same length as the provided types
         let token =
             DynSolType::abi_decode(&ty, data).wrap_err("Could not decode inspected values")?;
-        Ok((should_continue(contract_expr), Some(format_token(token))))
+        Ok((should_continue(contract_expr), Some(Inspected::Value { ty, value: token })))
     }
 
     /// Gracefully attempts to extract the type of the expression within the `abi.encode(...)`
@@ -319,6 +427,85 @@ impl SessionSource {
     }
 }
 
+/// Formats the variables of a storage layout, one per line, with their values in the storage.
+///
+/// The values packed in a slot are extracted from it, the ones spanning several slots are listed
+/// by slot. Dynamic arrays are shown with their length, and mappings without a value.
+pub fn format_storage_layout(layout: &StorageLayout, storage: &BTreeMap<U256, U256>) -> String {
+    let lines = layout.storage.iter().map(|var| {
+        let (label, bytes, encoding) = match layout.types.get(&var.storage_type) {
+            Some(ty) => (
+                ty.label.as_str(),
+                ty.number_of_bytes.parse::<usize>().unwrap_or(32),
+                ty.encoding.as_str(),
+            ),
+            None => ("?", 32, "inplace"),
+        };
+        let slot = U256::from_str(&var.slot).unwrap_or_default();
+        let word = |i: usize| storage.get(&(slot + U256::from(i))).copied().unwrap_or_default();
+        let value = match encoding {
+            "mapping" => "-".to_string(),
+            "dynamic_array" => format!("length {}", word(0)),
+            "inplace" if bytes > 32 => {
+                let words = (0..bytes.div_ceil(32)).map(|i| format!("{:#x}", word(i)));
+                format!("[{}]", words.collect::<Vec<_>>().join(", "))
+            }
+            "inplace" => {
+                let mask = if bytes >= 32 {
+                    U256::MAX
+                } else {
+                    (U256::from(1) << (bytes * 8)) - U256::from(1)
+                };
+                format!("{:#x}", (word(0) >> (var.offset as usize * 8)) & mask)
+            }
+            // the raw slot of `bytes` and `string`
+            _ => format!("{:#x}", word(0)),
+        };
+        format!(
+            "slot {}, offset {}: {} ({label}, {bytes} bytes) = {value}",
+            var.slot, var.offset, var.label
+        )
+    });
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+/// An inspected element of the session, see [SessionSource::inspect_value]
+#[derive(Clone, Debug)]
+pub enum Inspected {
+    /// The decoded value of an expression
+    Value {
+        /// The type of the expression
+        ty: DynSolType,
+        /// The value of the expression
+        value: DynSolValue,
+    },
+    /// The formatted definition of an event
+    Event(String),
+}
+
+impl Inspected {
+    /// Returns the JSON of the inspected element, with its type and its raw value
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Inspected::Value { ty, value } => {
+                serde_json::json!({ "type": ty.sol_type_name(), "value": format_token_raw(value) })
+            }
+            Inspected::Event(definition) => {
+                serde_json::json!({ "type": "event", "value": definition })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Inspected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Inspected::Value { value, .. } => f.write_str(&format_token(value.clone())),
+            Inspected::Event(definition) => f.write_str(definition),
+        }
+    }
+}
+
 /// Formats a [Token] into an inspection message
 ///
 /// ### Takes
@@ -1394,6 +1581,7 @@ impl<'a> Iterator for InstructionIter<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy_primitives::keccak256;
     use foundry_compilers::{error::SolcError, Solc};
     use std::sync::Mutex;
 
@@ -1661,6 +1849,66 @@ mod tests {
         generic_type_test(&mut source(), global_variables);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_measure_gas() {
+        let mut source = source();
+        source.with_top_level_code("uint256[] internal slots;");
+        let sstores = "for (uint256 i; i < 10; i++) slots.push(i);";
+
+        let (gas, _) = source.measure_gas(sstores).await.unwrap();
+        // at least the 10 SSTOREs of new slots and the one of the length
+        assert!(gas > 11 * 20_000, "{gas}");
+        assert!(gas < 20 * 20_000, "{gas}");
+        // the measures are stable across re-runs
+        assert_eq!(source.measure_gas(sstores).await.unwrap().0, gas);
+
+        // the measure excludes the statements already in the session
+        source.with_run_code("uint256 a = 1;");
+        assert_eq!(source.measure_gas(sstores).await.unwrap().0, gas);
+
+        // the baseline is the empty session
+        let empty = source.clone().drain_run().gas_usage().await.unwrap();
+        let session = source.gas_usage().await.unwrap();
+        assert!(session.since(&empty).0 > 0);
+
+        let (_, memory) = source.measure_gas("bytes memory b = new bytes(1024);").await.unwrap();
+        assert!(memory > 0, "{memory}");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_storage_layout() {
+        let mut source = source();
+        source
+            .with_top_level_code("struct Order { uint128 amount; address owner; }")
+            .with_top_level_code("Order internal order;")
+            .with_top_level_code("mapping(address => uint256) internal balances;")
+            .with_top_level_code("uint256[] internal ids;")
+            .with_top_level_code("uint128 internal low;")
+            .with_top_level_code("uint64 internal high;")
+            .with_run_code("order = Order(5, address(uint160(0x1234)));")
+            .with_run_code("balances[address(uint160(0x1234))] = 10;")
+            .with_run_code("ids.push(7); ids.push(8);")
+            .with_run_code("low = 1; high = 2;");
+
+        let layout = source.storage_layout().unwrap();
+        let storage = source.storage().await.unwrap();
+        assert_eq!(
+            format_storage_layout(&layout, &storage),
+            "\
+slot 0, offset 0: order (struct REPL.Order, 64 bytes) = [0x5, 0x1234]
+slot 2, offset 0: balances (mapping(address => uint256), 32 bytes) = -
+slot 3, offset 0: ids (uint256[], 32 bytes) = length 2
+slot 4, offset 0: low (uint128, 16 bytes) = 0x1
+slot 4, offset 16: high (uint64, 8 bytes) = 0x2"
+        );
+
+        // the elements of the dynamic array and the mapping values are in the raw storage
+        let ids = U256::from_be_bytes(keccak256(U256::from(3).to_be_bytes::<32>()).0);
+        assert_eq!(storage.get(&ids), Some(&U256::from(7)));
+        assert_eq!(storage.get(&(ids + U256::from(1))), Some(&U256::from(8)));
+        assert_eq!(storage.values().filter(|value| **value == U256::from(10)).count(), 1);
+    }
+
     #[track_caller]
     fn source() -> SessionSource {
         // synchronize solc install
@@ -27,9 +27,19 @@ pub mod executor;
 /// A Solidity Helper module for rustyline
 pub mod solidity_helper;
 
+/// Non-interactive evaluation of chisel scripts
+pub mod script;
+
+/// Annotations of the displayed values with their readable forms
+pub mod annotations;
+
+/// Dumping the state of a session and replaying its transactions
+pub mod replay;
+
 /// Prelude of all chisel modules
 pub mod prelude {
     pub use crate::{
-        cmd::*, dispatcher::*, runner::*, session::*, session_source::*, solidity_helper::*,
+        annotations::*, cmd::*, dispatcher::*, replay::*, runner::*, script::*, session::*,
+        session_source::*, solidity_helper::*,
     };
 }
@@ -0,0 +1,209 @@
+//! Replay
+//!
+//! This module contains the dump of the state of a session in the format of `anvil --load-state`,
+//! see `!dumpstate`, and the replay of the transactions of a session against an RPC, see
+//! `!broadcast`.
+
+use crate::prelude::{ChiselDispatcher, ChiselRunner, SessionSource};
+use alloy_primitives::{hex, Address, Bytes, U256};
+use alloy_rpc_types::request::TransactionRequest;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, TransactionRequest as EthersTransactionRequest,
+};
+use ethers_middleware::MiddlewareBuilder;
+use ethers_providers::Middleware;
+use ethers_signers::Signer;
+use eyre::{Result, WrapErr};
+use foundry_cli::{opts::Wallet, prompt};
+use foundry_common::{
+    provider::ethers::ProviderBuilder,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_evm::inspectors::cheatcodes::BroadcastableTransactions;
+use revm::{
+    db::{AccountState, CacheDB},
+    primitives::BlockEnv,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use yansi::Paint;
+
+/// The state of the executor of a session, in the format `anvil --load-state` loads
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DumpedState {
+    /// The block environment of the session
+    pub block: Option<BlockEnv>,
+    /// The accounts of the session, with their code and storage
+    pub accounts: BTreeMap<Address, DumpedAccount>,
+}
+
+/// An account of a [DumpedState]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpedAccount {
+    /// The nonce of the account
+    pub nonce: u64,
+    /// The balance of the account
+    pub balance: U256,
+    /// The runtime code of the account, empty if it is not a contract
+    pub code: Bytes,
+    /// The storage slots of the account
+    pub storage: BTreeMap<U256, U256>,
+}
+
+impl ChiselRunner {
+    /// Returns the state of the database of the executor, the one of the active fork if any.
+    pub fn dump_state(&self) -> DumpedState {
+        let backend = &self.executor.backend;
+        let accounts = match backend.active_fork_db() {
+            Some(db) => dump_accounts(db),
+            None => dump_accounts(backend.mem_db()),
+        };
+        DumpedState { block: Some(self.executor.env.block.clone()), accounts }
+    }
+}
+
+/// Returns the existing accounts of a database, with their code and their storage.
+fn dump_accounts<ExtDB>(db: &CacheDB<ExtDB>) -> BTreeMap<Address, DumpedAccount> {
+    db.accounts
+        .iter()
+        .filter(|(_, account)| !matches!(account.account_state, AccountState::NotExisting))
+        .map(|(address, account)| {
+            let code =
+                account.info.code.as_ref().or_else(|| db.contracts.get(&account.info.code_hash));
+            let account = DumpedAccount {
+                nonce: account.info.nonce,
+                balance: account.info.balance,
+                code: code.map(|code| code.original_bytes()).unwrap_or_default(),
+                storage: account.storage.iter().map(|(slot, value)| (*slot, *value)).collect(),
+            };
+            (*address, account)
+        })
+        .collect()
+}
+
+impl SessionSource {
+    /// Executes the session and returns the state it results in.
+    pub async fn dump_state(&self) -> Result<DumpedState> {
+        let mut source = self.clone();
+        if source.run_code.trim().is_empty() {
+            // an empty block so that the REPL contract is still deployed
+            source.with_run_code("{}");
+        }
+        let (_, res, runner) = source.execute_with_runner().await?;
+        if !res.success {
+            eyre::bail!("The session reverted")
+        }
+        runner.map(|runner| runner.dump_state()).ok_or_else(|| eyre::eyre!("The session is empty"))
+    }
+
+    /// Executes the session with its calls and contract creations broadcast by `sender`, from
+    /// `nonce` on, and returns the transactions they are recorded as.
+    ///
+    /// The statements which only change the variables of the session are not recorded, as they
+    /// are executed by the REPL contract itself.
+    pub async fn broadcast_transactions(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> Result<BroadcastableTransactions> {
+        if self.config.no_vm {
+            eyre::bail!("Broadcasting needs the `Vm` cheatcodes, which the session does not import")
+        }
+        let mut source = self.clone();
+        let run_code = std::mem::take(&mut source.run_code);
+        source
+            .with_run_code(&format!("vm.setNonceUnsafe({sender}, {nonce});"))
+            .with_run_code(&format!("vm.startBroadcast({sender});"))
+            .with_run_code(&run_code);
+        let (_, res) = source.execute().await?;
+        if !res.success {
+            eyre::bail!("The session reverted")
+        }
+        Ok(res.transactions.unwrap_or_default())
+    }
+}
+
+impl ChiselDispatcher {
+    /// Replays the transactions of the session against an RPC URL or an alias of the
+    /// `[rpc_endpoints]`, signed by the wallet chisel was started with.
+    ///
+    /// Each transaction is sent once it is confirmed with a prompt, unless `skip_confirm`, and
+    /// once the previous one is mined.
+    ///
+    /// ### Returns
+    ///
+    /// The number of transactions which were sent
+    pub async fn broadcast(&self, rpc: &str, skip_confirm: bool) -> Result<usize> {
+        let url = match self.source().config.foundry_config.rpc_endpoints.get(rpc) {
+            Some(endpoint) => endpoint
+                .clone()
+                .resolve()
+                .map_err(|e| eyre::eyre!("\"{}\" ENV Variable not set!", e.var))?,
+            None => rpc.to_string(),
+        };
+        let provider = ProviderBuilder::new(&url).build()?;
+        let chain_id = provider.get_chainid().await.wrap_err("Could not fetch the chain id")?;
+        let signer = Wallet::from(self.wallet.clone())
+            .signer(chain_id.as_u64())
+            .await
+            .wrap_err("No signer, start chisel with `--private-key`, `--mnemonic` or `-i`")?;
+        let sender = signer.address();
+        let nonce = provider.get_transaction_count(sender, None).await?.as_u64();
+
+        let transactions = self.source().broadcast_transactions(sender.to_alloy(), nonce).await?;
+        let total = transactions.len();
+        let client = provider.with_signer(signer);
+        for (i, tx) in transactions.into_iter().enumerate() {
+            println!("Transaction {}/{total}:\n{}", i + 1, format_transaction(&tx.transaction));
+            if !skip_confirm {
+                let answer: String = prompt!("Send it? [y/N] ")?;
+                if !matches!(answer.as_str(), "y" | "Y") {
+                    eyre::bail!("Cancelled, {i} of the {total} transactions were sent")
+                }
+            }
+
+            let receipt = client
+                .send_transaction(into_typed(tx.transaction), None)
+                .await?
+                .await?
+                .ok_or_else(|| eyre::eyre!("The transaction was dropped"))?;
+            let hash = receipt.transaction_hash.to_alloy();
+            if receipt.status.map_or(true, |status| status.is_zero()) {
+                eyre::bail!(
+                    "Transaction {hash} reverted, {i} of the {total} transactions succeeded"
+                )
+            }
+            println!(
+                "{} {hash} in block {}",
+                Paint::green("Mined"),
+                receipt.block_number.unwrap_or_default()
+            );
+        }
+        Ok(total)
+    }
+}
+
+/// Formats the recipient, the value and the calldata of a transaction for its confirmation.
+fn format_transaction(tx: &TransactionRequest) -> String {
+    let data = tx.data.clone().unwrap_or_default();
+    format!(
+        "├ To: {}\n├ Value: {}\n└ Data: {} ({} bytes)",
+        tx.to.map_or_else(|| "contract creation".to_string(), |to| to.to_string()),
+        tx.value.unwrap_or_default(),
+        hex::encode_prefixed(&data[..data.len().min(4)]),
+        data.len()
+    )
+}
+
+/// Converts a recorded transaction to a legacy transaction, filled by the provider when sent.
+fn into_typed(tx: TransactionRequest) -> TypedTransaction {
+    TypedTransaction::Legacy(EthersTransactionRequest {
+        from: tx.from.map(ToEthers::to_ethers),
+        to: tx.to.map(ToEthers::to_ethers).map(Into::into),
+        value: tx.value.map(ToEthers::to_ethers),
+        data: tx.data.map(ToEthers::to_ethers),
+        nonce: tx.nonce.map(|n| n.to::<u64>().into()),
+        gas: tx.gas.map(ToEthers::to_ethers),
+        ..Default::default()
+    })
+}
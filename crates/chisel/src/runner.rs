@@ -7,10 +7,11 @@ use alloy_primitives::{Address, Bytes, Log, U256};
 use eyre::Result;
 use foundry_evm::{
     executors::{DeployResult, Executor, RawCallResult},
+    inspectors::cheatcodes::BroadcastableTransactions,
     traces::{CallTraceArena, TraceKind},
 };
 use revm::interpreter::{return_ok, InstructionResult};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// The function selector of the REPL contract's entrypoint, the `run()` function.
 static RUN_SELECTOR: [u8; 4] = [0xc0, 0x40, 0x62, 0x26];
@@ -42,6 +43,9 @@ pub struct ChiselResult {
     pub traces: Vec<(TraceKind, CallTraceArena)>,
     /// Amount of gas used in the transaction
     pub gas_used: u64,
+    /// Exact amount of gas consumed by the call to `run()`, `gas_used` being an estimate of the
+    /// gas limit it needs
+    pub call_gas_used: u64,
     /// Map of addresses to their labels
     pub labeled_addresses: HashMap<Address, String>,
     /// Return data
@@ -50,6 +54,10 @@ pub struct ChiselResult {
     pub address: Option<Address>,
     /// EVM State at the final instruction of the `run()` function
     pub state: Option<(revm::interpreter::Stack, Vec<u8>, InstructionResult)>,
+    /// The non-zero storage slots of the REPL contract after the call to `run()`
+    pub storage: BTreeMap<U256, U256>,
+    /// The transactions recorded with `vm.startBroadcast()` during the call to `run()`
+    pub transactions: Option<BroadcastableTransactions>,
 }
 
 /// ChiselRunner implementation
@@ -104,9 +112,30 @@ impl ChiselRunner {
         }
 
         // Call the "run()" function of the REPL contract
-        let call_res = self.call(self.sender, address, Bytes::from(calldata), U256::from(0), true);
+        let mut res =
+            self.call(self.sender, address, Bytes::from(calldata), U256::from(0), true)?;
+        res.storage = self.storage_of(address);
 
-        call_res.map(|res| (address, res))
+        Ok((address, res))
+    }
+
+    /// Returns the non-zero storage slots of an account, as committed in the executor's database.
+    fn storage_of(&self, address: Address) -> BTreeMap<U256, U256> {
+        let backend = &self.executor.backend;
+        let account = match backend.active_fork_db() {
+            Some(db) => db.accounts.get(&address),
+            None => backend.mem_db().accounts.get(&address),
+        };
+        account
+            .map(|account| {
+                account
+                    .storage
+                    .iter()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(|(slot, value)| (*slot, *value))
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
     /// Executes the call.
@@ -192,12 +221,23 @@ impl ChiselRunner {
             res = self.executor.call_raw_committing(from, to, calldata, value)?;
         }
 
-        let RawCallResult { result, reverted, logs, traces, labels, chisel_state, .. } = res;
+        let RawCallResult {
+            result,
+            reverted,
+            gas_used: call_gas_used,
+            logs,
+            traces,
+            labels,
+            chisel_state,
+            transactions,
+            ..
+        } = res;
 
         Ok(ChiselResult {
             returned: result,
             success: !reverted,
             gas_used,
+            call_gas_used,
             logs,
             traces: traces
                 .map(|traces| {
@@ -209,6 +249,8 @@ impl ChiselRunner {
             labeled_addresses: labels,
             address: None,
             state: chisel_state,
+            storage: BTreeMap::new(),
+            transactions,
         })
     }
 }
@@ -0,0 +1,261 @@
+//! Script
+//!
+//! This module contains the non-interactive evaluation of Solidity snippets and chisel scripts,
+//! used by `chisel eval`, `chisel script` and `chisel --stdin`.
+
+use crate::{
+    dispatcher::{checksum_addresses, COMMAND_LEADER, COMMENT_RE},
+    executor::Inspected,
+    prelude::{ChiselCommand, ChiselDispatcher, DispatchResult},
+};
+use eyre::{Result, WrapErr};
+use foundry_evm::decode::{decode_console_logs, decode_revert};
+use serde_json::json;
+use yansi::Paint;
+
+/// A statement of a script
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statement {
+    /// The line of the script the statement starts at, starting from 1
+    pub line: usize,
+    /// The Solidity code of the statement, or a builtin command
+    pub code: String,
+}
+
+/// The outcome of a script, see [ChiselDispatcher::run_script]
+#[derive(Clone, Debug, Default)]
+pub struct ScriptOutput {
+    /// The value of the final statement of the script, if it is an expression
+    pub value: Option<Inspected>,
+    /// The console logs of the last execution of the session
+    pub logs: Vec<String>,
+}
+
+impl ScriptOutput {
+    /// Returns the JSON of the final value and of the logs
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({ "value": self.value.as_ref().map(Inspected::to_json), "logs": self.logs })
+    }
+}
+
+/// Splits a script into its statements.
+///
+/// A builtin command takes a whole line. A Solidity statement ends with a `;` or with the end of
+/// its line, unless it is within brackets, so that definitions and blocks may span lines.
+pub fn split_statements(script: &str) -> Vec<Statement> {
+    let mut statements = Vec::new();
+    let mut code = String::new();
+    let mut start = None;
+    let mut depth = 0usize;
+    let mut block_comment = false;
+
+    for (i, line) in script.lines().enumerate() {
+        let line_number = i + 1;
+        if start.is_none() && !block_comment && line.trim_start().starts_with(COMMAND_LEADER) {
+            statements.push(Statement { line: line_number, code: line.trim().to_string() });
+            continue
+        }
+
+        let mut quote = None;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if start.is_none() && !c.is_whitespace() {
+                start = Some(line_number);
+            }
+            code.push(c);
+            if block_comment {
+                if c == '*' && chars.next_if_eq(&'/').is_some() {
+                    code.push('/');
+                    block_comment = false;
+                }
+                continue
+            }
+            if let Some(q) = quote {
+                if c == '\\' {
+                    code.extend(chars.next());
+                } else if c == q {
+                    quote = None;
+                }
+                continue
+            }
+            match c {
+                '"' | '\'' => quote = Some(c),
+                '/' if chars.peek() == Some(&'/') => code.extend(chars.by_ref()),
+                '/' if chars.peek() == Some(&'*') => {
+                    code.extend(chars.next());
+                    block_comment = true;
+                }
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                ';' if depth == 0 => push_statement(&mut statements, &mut start, &mut code),
+                _ => {}
+            }
+        }
+
+        if depth == 0 && !block_comment {
+            push_statement(&mut statements, &mut start, &mut code);
+        } else {
+            code.push('\n');
+        }
+    }
+    push_statement(&mut statements, &mut start, &mut code);
+    statements
+}
+
+/// Pushes the pending code as a statement, if it is not blank. An `else` or a `catch` clause is
+/// appended to the statement before it instead.
+fn push_statement(statements: &mut Vec<Statement>, start: &mut Option<usize>, code: &mut String) {
+    if let Some(line) = start.take() {
+        let trimmed = code.trim();
+        match statements.last_mut() {
+            Some(last)
+                if continues_statement(trimmed) && !last.code.starts_with(COMMAND_LEADER) =>
+            {
+                last.code.push('\n');
+                last.code.push_str(trimmed);
+            }
+            _ => statements.push(Statement { line, code: trimmed.to_string() }),
+        }
+    }
+    code.clear();
+}
+
+/// Returns whether the code is a clause of the statement before it, i.e. starts with the `else` or
+/// `catch` keyword.
+fn continues_statement(code: &str) -> bool {
+    ["else", "catch"].into_iter().any(|keyword| {
+        code.strip_prefix(keyword)
+            .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+    })
+}
+
+impl ChiselDispatcher {
+    /// Evaluates the statements of a script one after the other, like the REPL does with the
+    /// lines it reads.
+    ///
+    /// ### Returns
+    ///
+    /// The value of the final statement of the script and the console logs, or the error of the
+    /// first statement which failed, along with its line in the script.
+    pub async fn run_script(&mut self, script: &str) -> Result<ScriptOutput> {
+        let mut output = ScriptOutput::default();
+        for statement in split_statements(script) {
+            if COMMENT_RE.is_match(&statement.code) {
+                continue
+            }
+            output.value =
+                self.evaluate(&statement.code, &mut output.logs).await.map_err(|err| {
+                    eyre::eyre!("Error at line {}: `{}`\n{err:#}", statement.line, statement.code)
+                })?;
+        }
+        Ok(output)
+    }
+
+    /// Evaluates a single statement or builtin command, keeping the console logs of its execution.
+    ///
+    /// ### Returns
+    ///
+    /// The value of the statement, if it is an expression
+    async fn evaluate(&mut self, input: &str, logs: &mut Vec<String>) -> Result<Option<Inspected>> {
+        if input.starts_with(COMMAND_LEADER) {
+            let split: Vec<&str> = input.split_whitespace().collect();
+            let cmd = split[0][1..].parse::<ChiselCommand>().map_err(|err| eyre::eyre!("{err}"))?;
+            return match self.dispatch_command(cmd, &split[1..]).await {
+                DispatchResult::Success(msg) | DispatchResult::CommandSuccess(msg) => {
+                    // stdout is kept for the final value
+                    if let Some(msg) = msg {
+                        eprintln!("{}", Paint::green(msg));
+                    }
+                    Ok(None)
+                }
+                DispatchResult::CommandFailed(msg) | DispatchResult::Failure(Some(msg)) => {
+                    Err(eyre::eyre!(msg))
+                }
+                DispatchResult::UnrecognizedCommand(err) | DispatchResult::FileIoError(err) => {
+                    Err(eyre::eyre!("{err}"))
+                }
+                DispatchResult::SolangParserFailed(diagnostics) => {
+                    Err(eyre::eyre!("Compilation error: {diagnostics:?}"))
+                }
+                DispatchResult::Failure(None) => Err(eyre::eyre!("Unknown chisel error")),
+            }
+        }
+
+        let input = checksum_addresses(input);
+        let (mut new_source, do_execute) =
+            self.source().clone_with_new_line(input.clone()).wrap_err("Failed to parse input")?;
+
+        let (cont, value) = self.source().inspect_value(&input).await?;
+        if !cont {
+            return Ok(value)
+        }
+
+        if do_execute {
+            let (_, res) = new_source.execute().await?;
+            *logs = decode_console_logs(&res.logs);
+            if !res.success {
+                eyre::bail!("Execution reverted: {}", decode_revert(&res.returned, None, None))
+            }
+        } else {
+            new_source.build()?;
+        }
+        *self.source_mut() = new_source;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(script: &str) -> Vec<(usize, String)> {
+        split_statements(script).into_iter().map(|s| (s.line, s.code)).collect()
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            codes("uint256 a = 1; a + 1"),
+            [(1, "uint256 a = 1;".to_string()), (1, "a + 1".to_string())]
+        );
+
+        let script = r#"
+!fork mainnet
+string memory s = "a;b"; // a comment; with a semicolon
+function f(uint256 x) public pure returns (uint256) {
+    for (uint256 i; i < 2; i++) { x += i; }
+    return x;
+}
+/* a block
+   comment */
+f(1)
+"#;
+        assert_eq!(
+            codes(script),
+            [
+                (2, "!fork mainnet".to_string()),
+                (3, r#"string memory s = "a;b";"#.to_string()),
+                (3, "// a comment; with a semicolon".to_string()),
+                (
+                    4,
+                    "function f(uint256 x) public pure returns (uint256) {\n    for (uint256 i; i < \
+                     2; i++) { x += i; }\n    return x;\n}"
+                        .to_string()
+                ),
+                (8, "/* a block\n   comment */".to_string()),
+                (10, "f(1)".to_string()),
+            ]
+        );
+
+        let script =
+            "if (a > 1) {\n    a = 1;\n}\nelse a = 2;\ntry this.f(1) {} catch {}\nelsewhere = 3;";
+        assert_eq!(
+            codes(script),
+            [
+                (1, "if (a > 1) {\n    a = 1;\n}\nelse a = 2;".to_string()),
+                (5, "try this.f(1) {} catch {}".to_string()),
+                (6, "elsewhere = 3;".to_string()),
+            ]
+        );
+    }
+}
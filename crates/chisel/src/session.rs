@@ -3,19 +3,42 @@
 //! This module contains the `ChiselSession` struct, which is the top-level
 //! wrapper for a serializable REPL session.
 
-use crate::prelude::{SessionSource, SessionSourceConfig};
+use crate::prelude::{ForkInfo, SessionSource, SessionSourceConfig};
 use eyre::Result;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use time::{format_description, OffsetDateTime};
 
+/// The format of the times of the sessions
+pub(crate) const TIME_FORMAT: &str = "[year]-[month]-[day] [hour]:[minute]:[second]";
+
 /// A Chisel REPL Session
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ChiselSession {
     /// The `SessionSource` object that houses the REPL session.
     pub session_source: SessionSource,
-    /// The current session's identifier
+    /// The current session's identifier, either a name or a number
     pub id: Option<String>,
+    /// The description of the session, set with `!save <name> <description>`
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The unix timestamp of the first time the session was saved
+    #[serde(default)]
+    pub created_at: Option<i64>,
+}
+
+/// The metadata of a cached session, see `!list`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionInfo {
+    /// The identifier of the session
+    pub id: String,
+    /// The description of the session
+    pub description: Option<String>,
+    /// The creation time of the session, or its last modification time for sessions saved before
+    /// the creation time was recorded
+    pub created_at: String,
+    /// The active fork of the session
+    pub fork: Option<ForkInfo>,
 }
 
 // ChiselSession Common Associated Functions
@@ -32,7 +55,12 @@ impl ChiselSession {
     pub fn new(config: SessionSourceConfig) -> Result<Self> {
         let solc = config.solc()?;
         // Return initialized ChiselSession with set solc version
-        Ok(Self { session_source: SessionSource::new(solc, config), id: None })
+        Ok(Self {
+            session_source: SessionSource::new(solc, config),
+            id: None,
+            description: None,
+            created_at: None,
+        })
     }
 
     /// Render the full source code for the current session.
@@ -94,6 +122,10 @@ impl ChiselSession {
             }
         };
 
+        if self.created_at.is_none() {
+            self.created_at = Some(OffsetDateTime::now_utc().unix_timestamp());
+        }
+
         // Write the current ChiselSession to that file
         let serialized_contents = serde_json::to_string_pretty(self)?;
         std::fs::write(&cache_file_name, serialized_contents)?;
@@ -110,28 +142,30 @@ impl ChiselSession {
     /// Optionally, returns a tuple containing the next cached session's id and file name.
     pub fn next_cached_session() -> Result<(String, String)> {
         let cache_dir = Self::cache_dir()?;
-        let mut entries = std::fs::read_dir(&cache_dir)?;
 
-        // If there are no existing cached sessions, just create the first one: "chisel-0.json"
-        let mut latest = if let Some(e) = entries.next() {
-            e?
-        } else {
-            return Ok((String::from("0"), format!("{cache_dir}chisel-0.json")))
-        };
-
-        let mut session_num = 1;
-        // Get the latest cached session
-        for entry in entries {
-            let entry = entry?;
-            if entry.metadata()?.modified()? >= latest.metadata()?.modified()? {
-                latest = entry;
+        // Use the number after the highest numbered session, named sessions are skipped.
+        let mut next = 0u64;
+        for entry in std::fs::read_dir(&cache_dir)? {
+            let file_name = entry?.file_name();
+            let id =
+                Self::id_of(&file_name.to_string_lossy()).and_then(|id| id.parse::<u64>().ok());
+            if let Some(id) = id {
+                next = next.max(id + 1);
             }
-
-            // Increase session_num counter rather than cloning the iterator and using `.count`
-            session_num += 1;
         }
 
-        Ok((format!("{session_num}"), format!("{cache_dir}chisel-{session_num}.json")))
+        Ok((format!("{next}"), format!("{cache_dir}chisel-{next}.json")))
+    }
+
+    /// Returns the id of the session of a cache file name, `chisel-<id>.json`
+    fn id_of(file_name: &str) -> Option<&str> {
+        file_name.strip_prefix("chisel-")?.strip_suffix(".json")
+    }
+
+    /// Returns whether the name can identify a session: it must be non empty and only contain
+    /// alphanumeric characters, `-` and `_`.
+    pub fn is_valid_id(id: &str) -> bool {
+        !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
     }
 
     /// The Chisel Cache Directory
@@ -180,11 +214,7 @@ impl ChiselSession {
             let file_name = file_name
                 .into_string()
                 .map_err(|e| eyre::eyre!(format!("{}", e.to_string_lossy())))?;
-            sessions.push((
-                systemtime_strftime(modified_time, "[year]-[month]-[day] [hour]:[minute]:[second]")
-                    .unwrap(),
-                file_name,
-            ));
+            sessions.push((systemtime_strftime(modified_time, TIME_FORMAT).unwrap(), file_name));
         }
 
         if sessions.is_empty() {
@@ -195,6 +225,64 @@ impl ChiselSession {
         }
     }
 
+    /// Lists the metadata of all cached sessions, sorted by creation time
+    ///
+    /// ### Returns
+    ///
+    /// Optionally, a vector containing the [SessionInfo] of the sessions.
+    pub fn session_infos() -> Result<Vec<SessionInfo>> {
+        let cache_dir = Self::cache_dir()?;
+        let mut sessions = Vec::new();
+        for entry in std::fs::read_dir(cache_dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let Some(id) = Self::id_of(&file_name.to_string_lossy()).map(str::to_string) else {
+                continue
+            };
+            let modified = OffsetDateTime::from(entry.metadata()?.modified()?);
+            let session = std::fs::read_to_string(entry.path())
+                .ok()
+                .and_then(|contents| serde_json::from_str::<ChiselSession>(&contents).ok());
+            let created_at = session
+                .as_ref()
+                .and_then(|session| session.created_at)
+                .and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok())
+                .unwrap_or(modified);
+            sessions.push((
+                created_at,
+                SessionInfo {
+                    id,
+                    description: session.as_ref().and_then(|session| session.description.clone()),
+                    created_at: systemtime_strftime(created_at, TIME_FORMAT)?,
+                    fork: session
+                        .as_ref()
+                        .and_then(|session| session.session_source.config.active_fork().cloned()),
+                },
+            ));
+        }
+
+        if sessions.is_empty() {
+            eyre::bail!("No sessions found!")
+        }
+        sessions.sort_by_key(|(created_at, _)| *created_at);
+        Ok(sessions.into_iter().map(|(_, info)| info).collect())
+    }
+
+    /// Deletes a cached session
+    ///
+    /// ### Takes
+    ///
+    /// The ID of the chisel session that you wish to delete.
+    pub fn delete(id: &str) -> Result<()> {
+        let cache_dir = ChiselSession::cache_dir()?;
+        let path = format!("{cache_dir}chisel-{id}.json");
+        if !Path::new(&path).exists() {
+            eyre::bail!("No session with ID \"{id}\"")
+        }
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
     /// Loads a specific ChiselSession from the specified cache file
     ///
     /// ### Takes
@@ -248,7 +336,7 @@ impl ChiselSession {
 
 /// Generic helper function that attempts to convert a type that has
 /// an [Into<OffsetDateTime>] implementation into a formatted date string.
-fn systemtime_strftime<T>(dt: T, format: &str) -> Result<String>
+pub(crate) fn systemtime_strftime<T>(dt: T, format: &str) -> Result<String>
 where
     T: Into<OffsetDateTime>,
 {
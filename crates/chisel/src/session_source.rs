@@ -4,18 +4,27 @@
 //! the REPL contract's source code. It provides simple compilation, parsing, and
 //! execution helpers.
 
+use crate::solidity_helper::{Completion, Completions};
+use alloy_json_abi::JsonAbi;
 use eyre::Result;
 use forge_fmt::solang_ext::SafeUnwrap;
+use foundry_cli::utils::did_you_mean;
+use foundry_common::compile::ProjectCompiler;
 use foundry_compilers::{
-    artifacts::{Source, Sources},
-    CompilerInput, CompilerOutput, EvmVersion, Solc,
+    artifacts::{output_selection::ContractOutputSelection, Source, Sources},
+    Artifact, CompilerInput, CompilerOutput, EvmVersion, ProjectCompileOutput, Solc,
 };
 use foundry_config::{Config, SolcReq};
 use foundry_evm::{backend::Backend, opts::EvmOpts};
+use once_cell::sync::Lazy;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use solang_parser::pt;
-use std::{collections::HashMap, fs, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
 use yansi::Paint;
 
 /// The minimum Solidity version of the `Vm` interface.
@@ -24,6 +33,46 @@ pub const MIN_VM_VERSION: Version = Version::new(0, 6, 2);
 /// Solidity source for the `Vm` interface in [forge-std](https://github.com/foundry-rs/forge-std)
 static VM_SOURCE: &str = include_str!("../../../testdata/cheats/Vm.sol");
 
+/// The cheatcodes of the `Vm` interface with their signatures, for completion
+static VM_CHEATCODES: Lazy<Vec<Completion>> = Lazy::new(|| {
+    let Ok((pt::SourceUnit(parts), _)) = solang_parser::parse(VM_SOURCE, 0) else {
+        return Vec::new()
+    };
+    let mut cheatcodes = parts
+        .iter()
+        .filter_map(|part| match part {
+            pt::SourceUnitPart::ContractDefinition(contract) => Some(contract),
+            _ => None,
+        })
+        .flat_map(|contract| &contract.parts)
+        .filter_map(|part| match part {
+            pt::ContractPart::FunctionDefinition(function) => {
+                let name = &function.name.as_ref()?.name;
+                let params = function
+                    .params
+                    .iter()
+                    .filter_map(|(_, param)| param.as_ref())
+                    .map(|param| {
+                        let storage = param.storage.as_ref().map(|s| format!(" {s}"));
+                        let name = param.name.as_ref().map(|n| format!(" {}", n.name));
+                        format!(
+                            "{}{}{}",
+                            param.ty,
+                            storage.unwrap_or_default(),
+                            name.unwrap_or_default()
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                Some(Completion::new(name, format!("{name}({})", params.join(", "))))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    cheatcodes.sort();
+    cheatcodes.dedup();
+    cheatcodes
+});
+
 /// Intermediate output for the compiled [SessionSource]
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IntermediateOutput {
@@ -81,9 +130,32 @@ pub struct SessionSourceConfig {
     pub traces: bool,
     /// Optionally set calldata for the REPL contract execution
     pub calldata: Option<Vec<u8>>,
+    /// The forks used in the session, see `!fork list`
+    #[serde(default)]
+    pub forks: Vec<ForkInfo>,
+}
+
+/// A fork used in a session
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkInfo {
+    /// The RPC URL of the fork
+    pub url: String,
+    /// The chain id of the fork
+    pub chain_id: u64,
+    /// The block number the fork is pinned to
+    pub block_number: u64,
 }
 
 impl SessionSourceConfig {
+    /// Returns the active fork, if the session is forking and the fork was resolved already.
+    pub fn active_fork(&self) -> Option<&ForkInfo> {
+        let url = self.evm_opts.fork_url.as_ref()?;
+        self.forks.iter().rev().find(|fork| {
+            &fork.url == url &&
+                self.evm_opts.fork_block_number.map_or(true, |block| block == fork.block_number)
+        })
+    }
+
     /// Returns the solc version to use
     ///
     /// Solc version precedence
@@ -145,6 +217,22 @@ You can also set the solc version in your foundry.toml.")
     }
 }
 
+/// A contract of the surrounding foundry project, loaded with `!load <ContractName>`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProjectContract {
+    /// The path of the source file of the contract, relative to the project root
+    pub path: PathBuf,
+    /// The ABI of the contract
+    pub abi: JsonAbi,
+}
+
+impl ProjectContract {
+    /// Returns the import directive of the contract
+    pub fn import(&self, name: &str) -> String {
+        format!("import {{{name}}} from \"{}\";", self.path.display())
+    }
+}
+
 /// REPL Session Source wrapper
 ///
 /// Heavily based on soli's [`ConstructedSource`](https://github.com/jpopesculian/soli/blob/master/src/main.rs#L166)
@@ -167,6 +255,9 @@ pub struct SessionSource {
     pub top_level_code: String,
     /// Code existing within the "run()" function's scope
     pub run_code: String,
+    /// The contracts of the foundry project imported into the session
+    #[serde(default)]
+    pub project_contracts: BTreeMap<String, ProjectContract>,
     /// The generated output
     pub generated_output: Option<GeneratedOutput>,
     /// Session Source configuration
@@ -205,6 +296,7 @@ impl SessionSource {
             global_code: Default::default(),
             top_level_code: Default::default(),
             run_code: Default::default(),
+            project_contracts: Default::default(),
             generated_output: None,
         }
     }
@@ -223,6 +315,7 @@ impl SessionSource {
             global_code: self.global_code.clone(),
             top_level_code: self.top_level_code.clone(),
             run_code: self.run_code.clone(),
+            project_contracts: self.project_contracts.clone(),
             generated_output: None,
             config: self.config.clone(),
         }
@@ -313,6 +406,162 @@ impl SessionSource {
         self
     }
 
+    /// Forgets the loaded project contracts, whose imports are part of the global code
+    pub fn drain_project_contracts(&mut self) -> &mut Self {
+        self.project_contracts.clear();
+        self.generated_output = None;
+        self
+    }
+
+    // Project contracts
+
+    /// Returns the root of the foundry project of the session
+    pub fn project_root(&self) -> &Path {
+        &self.config.foundry_config.__root.0
+    }
+
+    /// Compiles the foundry project with forge's compile pipeline, reusing its cache.
+    ///
+    /// The compiler errors are the ones of the project sources.
+    fn compile_project(&self) -> Result<ProjectCompileOutput> {
+        let project = self.config.foundry_config.project()?;
+        if !project.paths.has_input_files() {
+            eyre::bail!("The project at {} has no sources to load", self.project_root().display())
+        }
+        ProjectCompiler::new().quiet(true).compile(&project)
+    }
+
+    /// Compiles the project and imports the contract into the session.
+    ///
+    /// The contract is either a name or `<path>:<name>` if several sources define the name.
+    ///
+    /// ### Returns
+    ///
+    /// The name of the imported contract
+    pub fn load_project_contract(&mut self, contract: &str) -> Result<String> {
+        let output = self.compile_project()?;
+        let (name, loaded) = self.find_project_contract(&output, contract)?;
+        if !self.project_contracts.contains_key(&name) {
+            self.with_global_code(&loaded.import(&name));
+        }
+        self.project_contracts.insert(name.clone(), loaded);
+        self.generated_output = None;
+        Ok(name)
+    }
+
+    /// Recompiles the project and refreshes the ABIs and the imports of the loaded contracts,
+    /// so that the session picks up the changes made to the project sources.
+    ///
+    /// ### Returns
+    ///
+    /// The number of reloaded contracts
+    pub fn reload_project_contracts(&mut self) -> Result<usize> {
+        let output = self.compile_project()?;
+        let mut reloaded = BTreeMap::new();
+        for (name, old) in &self.project_contracts {
+            let contract = format!("{}:{name}", old.path.display());
+            let new = match self.find_project_contract(&output, &contract) {
+                Ok((_, new)) => new,
+                // the contract may have been moved to another file
+                Err(_) => self.find_project_contract(&output, name)?.1,
+            };
+            reloaded.insert(name.clone(), new);
+        }
+        for (name, new) in &reloaded {
+            let old = self.project_contracts[name].import(name);
+            self.global_code = self.global_code.replace(&old, &new.import(name));
+        }
+        self.project_contracts = reloaded;
+        self.generated_output = None;
+        Ok(self.project_contracts.len())
+    }
+
+    /// Finds a contract in the compiled project, by name or by `<path>:<name>`.
+    fn find_project_contract(
+        &self,
+        output: &ProjectCompileOutput,
+        contract: &str,
+    ) -> Result<(String, ProjectContract)> {
+        let (path, name) = match contract.rsplit_once(':') {
+            Some((path, name)) => (Some(Path::new(path)), name),
+            None => (None, contract),
+        };
+        let root = self.project_root();
+        let mut found = output
+            .artifact_ids()
+            .filter(|(id, _)| id.name == name)
+            .map(|(id, artifact)| {
+                let source = id.source.strip_prefix(root).unwrap_or(&id.source).to_path_buf();
+                (source, artifact)
+            })
+            .filter(|(source, _)| path.map_or(true, |path| source == path))
+            .collect::<Vec<_>>();
+        found.sort_by(|(a, _), (b, _)| a.cmp(b));
+        found.dedup_by(|(a, _), (b, _)| a == b);
+
+        match found.as_slice() {
+            [] => {
+                let mut err = format!("Could not find contract `{contract}` in the project");
+                let names = output.artifact_ids().map(|(id, _)| id.name);
+                if let Some(suggestion) = did_you_mean(name, names).pop() {
+                    err.push_str(&format!(", did you mean `{suggestion}`?"));
+                }
+                eyre::bail!(err)
+            }
+            [(source, artifact)] => {
+                let abi = artifact
+                    .get_abi()
+                    .ok_or_else(|| eyre::eyre!("Contract `{contract}` has no ABI"))?
+                    .into_owned();
+                Ok((name.to_string(), ProjectContract { path: source.clone(), abi }))
+            }
+            found => eyre::bail!(
+                "Several sources define `{name}`, load one of {}",
+                found
+                    .iter()
+                    .map(|(source, _)| format!("`{}:{name}`", source.display()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    /// Returns the identifiers of the session and their members for completion: the variables
+    /// with their types, the loaded contracts, the functions of the contracts and of the variables
+    /// bound to one, and the cheatcodes of `vm`.
+    pub fn completions(&self) -> Completions {
+        let functions = |name: &str| {
+            self.project_contracts.get(name).map(|contract| {
+                let functions = contract.abi.functions();
+                functions.map(|function| Completion::new(&function.name, function.signature()))
+            })
+        };
+        let mut completions = Completions::default();
+        if !self.config.no_vm {
+            completions.identifiers.push(Completion::new("vm", "vm: Vm"));
+            completions.members.insert("vm".to_string(), VM_CHEATCODES.clone());
+        }
+        for name in self.project_contracts.keys() {
+            completions.identifiers.push(Completion::new(name, format!("{name}: contract")));
+            completions
+                .members
+                .insert(name.clone(), functions(name).into_iter().flatten().collect());
+        }
+        if let Some(output) = &self.generated_output {
+            for (var, ty) in &output.intermediate.repl_contract_expressions {
+                completions.identifiers.push(Completion::new(var, format!("{var}: {ty}")));
+                if let pt::Expression::Variable(ty) = ty {
+                    if let Some(functions) = functions(&ty.name) {
+                        completions.members.insert(var.clone(), functions.collect());
+                    }
+                }
+            }
+        }
+        completions.identifiers.sort();
+        completions.identifiers.dedup_by(|a, b| a.name == b.name);
+        completions
+    }
+
     /// Generates and foundry_compilers::CompilerInput from the source
     ///
     /// ### Returns
@@ -347,6 +596,18 @@ impl SessionSource {
         // We also need to enforce the EVM version that the user has specified.
         compiler_input.settings.evm_version = Some(self.config.foundry_config.evm_version);
 
+        // The storage layout of the REPL contract is shown by `!storagelayout`
+        compiler_input.settings.push_all([ContractOutputSelection::StorageLayout]);
+
+        // Link the project libraries which are deployed already, like forge does.
+        match self.config.foundry_config.parsed_libraries() {
+            Ok(libraries) => {
+                compiler_input.settings.libraries =
+                    libraries.with_applied_remappings(&self.config.foundry_config.project_paths())
+            }
+            Err(e) => tracing::warn!(%e, "could not parse the project libraries"),
+        }
+
         compiler_input
     }
 
@@ -371,6 +632,13 @@ impl SessionSource {
         for source in parsed_map.values() {
             Self::get_intermediate_contract(&source.content, &mut res_map);
         }
+        // The imports are read relative to the working directory, which might not be the root of
+        // the project.
+        for contract in self.project_contracts.values() {
+            if let Ok(source) = fs::read_to_string(self.project_root().join(&contract.path)) {
+                Self::get_intermediate_contract(&source, &mut res_map);
+            }
+        }
         Ok(res_map)
     }
 
@@ -413,15 +681,31 @@ impl SessionSource {
     ///
     /// Optionally, a [CompilerOutput] object that contains compilation artifacts.
     pub fn compile(&self) -> Result<CompilerOutput> {
-        // Compile the contract
-        let compiled = self.solc.compile_exact(&self.compiler_input())?;
+        // Compile the contract, resolving the imports of the project contracts from the project
+        // root, like forge does.
+        let compiled = if self.project_contracts.is_empty() {
+            self.solc.compile_exact(&self.compiler_input())?
+        } else {
+            let solc = self.solc.clone().with_base_path(self.project_root());
+            solc.compile_exact(&self.compiler_input())?
+        };
 
         // Extract compiler errors
         let errors =
             compiled.errors.iter().filter(|error| error.severity.is_error()).collect::<Vec<_>>();
         if !errors.is_empty() {
+            // The errors in the project sources are reported against the project files.
+            let repl_file = self.file_name.to_string_lossy();
+            let in_project = errors.iter().any(|error| {
+                error.source_location.as_ref().map_or(false, |loc| loc.file != repl_file)
+            });
             eyre::bail!(
-                "Compiler errors:\n{}",
+                "{}:\n{}",
+                if in_project {
+                    "Compiler errors in the project sources, fix them and run `!reload`"
+                } else {
+                    "Compiler errors"
+                },
                 errors.into_iter().map(|err| err.to_string()).collect::<String>()
             );
         }
@@ -480,6 +764,63 @@ contract {contract_name} is Script {{
         )
     }
 
+    /// Convert the [SessionSource] to a forge test contract, whose `setUp` reproduces the
+    /// environment of the session and whose `test_repl` runs the session.
+    ///
+    /// ### Returns
+    ///
+    /// The [SessionSource] represented as a forge test contract named `contract_name`.
+    pub fn to_test_source(&self, contract_name: &str) -> String {
+        let Version { major, minor, patch, .. } = self.solc.version().unwrap();
+        let Self { global_code, top_level_code, run_code, config, .. } = self;
+
+        // The REPL contract is funded with the maximum balance
+        let mut set_up = vec!["vm.deal(address(this), type(uint256).max);".to_string()];
+        if let Some(url) = &config.evm_opts.fork_url {
+            // Prefer the `[rpc_endpoints]` alias of the fork URL, which doesn't leak API keys
+            let alias = config.foundry_config.rpc_endpoints.clone().resolved().iter().find_map(
+                |(alias, endpoint)| {
+                    endpoint
+                        .as_ref()
+                        .ok()
+                        .filter(|endpoint| *endpoint == url)
+                        .map(|_| alias.clone())
+                },
+            );
+            let url = alias.unwrap_or_else(|| url.clone());
+            match config.evm_opts.fork_block_number {
+                Some(block) => set_up.push(format!("vm.createSelectFork(\"{url}\", {block});")),
+                None => set_up.push(format!("vm.createSelectFork(\"{url}\");")),
+            }
+        }
+        if config.calldata.is_some() {
+            set_up.push("// `msg.data` was set with `!calldata` in the session".to_string());
+        }
+        let set_up = set_up.join("\n        ");
+
+        format!(
+            r#"// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^{major}.{minor}.{patch};
+
+import {{Test}} from "forge-std/Test.sol";
+{global_code}
+
+contract {contract_name} is Test {{
+    {top_level_code}
+
+    function setUp() public {{
+        {set_up}
+    }}
+
+    /// @notice The body of the REPL session
+    function test_repl() public {{
+        {run_code}
+    }}
+}}
+"#,
+        )
+    }
+
     /// Convert the [SessionSource] to a valid REPL contract
     ///
     /// ### Returns
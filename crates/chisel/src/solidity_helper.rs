@@ -4,21 +4,22 @@
 //! usage in Chisel. It is ported from [soli](https://github.com/jpopesculian/soli/blob/master/src/main.rs).
 
 use crate::{
-    dispatcher::PROMPT_ARROW,
-    prelude::{ChiselCommand, COMMAND_LEADER},
+    dispatcher::{EDIT_COMMAND, PROMPT_ARROW},
+    prelude::{ChiselCommand, CmdDescriptor, COMMAND_LEADER},
 };
 use rustyline::{
-    completion::Completer,
+    completion::{Completer, Pair},
     highlight::Highlighter,
     hint::Hinter,
     validate::{ValidationContext, ValidationResult, Validator},
-    Helper,
+    Context, Helper,
 };
 use solang_parser::{
     lexer::{Lexer, Token},
     pt,
 };
-use std::{borrow::Cow, str::FromStr};
+use std::{borrow::Cow, collections::BTreeMap, str::FromStr};
+use strum::IntoEnumIterator;
 use yansi::{Color, Paint, Style};
 
 /// The default pre-allocation for solang parsed comments
@@ -36,11 +37,85 @@ const MAX_ANSI_LEN: usize = 9;
 /// `(start, style, end)`
 pub type SpannedStyle = (usize, Style, usize);
 
+/// The members of the global symbols of Solidity, with their types or signatures.
+static GLOBAL_MEMBERS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "abi",
+        &[
+            ("decode", "decode(bytes memory, (...))"),
+            ("encode", "encode(...)"),
+            ("encodeCall", "encodeCall(function, (...))"),
+            ("encodePacked", "encodePacked(...)"),
+            ("encodeWithSelector", "encodeWithSelector(bytes4, ...)"),
+            ("encodeWithSignature", "encodeWithSignature(string memory, ...)"),
+        ],
+    ),
+    (
+        "block",
+        &[
+            ("basefee", "basefee: uint256"),
+            ("blobbasefee", "blobbasefee: uint256"),
+            ("chainid", "chainid: uint256"),
+            ("coinbase", "coinbase: address payable"),
+            ("difficulty", "difficulty: uint256"),
+            ("gaslimit", "gaslimit: uint256"),
+            ("number", "number: uint256"),
+            ("prevrandao", "prevrandao: uint256"),
+            ("timestamp", "timestamp: uint256"),
+        ],
+    ),
+    (
+        "msg",
+        &[
+            ("data", "data: bytes calldata"),
+            ("sender", "sender: address"),
+            ("sig", "sig: bytes4"),
+            ("value", "value: uint256"),
+        ],
+    ),
+    ("tx", &[("gasprice", "gasprice: uint256"), ("origin", "origin: address")]),
+];
+
+/// A completion candidate
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Completion {
+    /// The identifier inserted by the completion
+    pub name: String,
+    /// The text shown for the candidate, e.g. the identifier with its type or its signature
+    pub display: String,
+}
+
+impl Completion {
+    /// Creates a new completion candidate.
+    pub fn new(name: impl Into<String>, display: impl Into<String>) -> Self {
+        Self { name: name.into(), display: display.into() }
+    }
+}
+
+impl From<&Completion> for Pair {
+    fn from(completion: &Completion) -> Self {
+        Pair { display: completion.display.clone(), replacement: completion.name.clone() }
+    }
+}
+
+/// The identifiers of a session and their members, which are completed along with the global
+/// symbols of Solidity.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Completions {
+    /// The identifiers, e.g. the variables of the session and the loaded contracts
+    pub identifiers: Vec<Completion>,
+    /// The members of the identifiers, e.g. the functions of a contract or the cheatcodes of
+    /// `vm`
+    pub members: BTreeMap<String, Vec<Completion>>,
+}
+
 /// A rustyline helper for Solidity code
 #[derive(Clone, Debug, Default)]
 pub struct SolidityHelper {
     /// Whether the dispatcher has errored.
     pub errored: bool,
+    /// The identifiers of the session and their members.
+    pub completions: Completions,
 }
 
 impl SolidityHelper {
@@ -55,6 +130,111 @@ impl SolidityHelper {
         self
     }
 
+    /// Set the completions field.
+    pub fn set_completions(&mut self, completions: Completions) -> &mut Self {
+        self.completions = completions;
+        self
+    }
+
+    /// Returns the completions of the word before the cursor, and the position it starts at.
+    ///
+    /// Builtin commands are completed after the command leader, identifiers and their members
+    /// everywhere else but in string literals and comments.
+    pub fn completions_at(&self, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+        let line = &line[..pos];
+        if let Some(cmd) = line.strip_prefix(COMMAND_LEADER) {
+            if cmd.contains(char::is_whitespace) {
+                return (pos, Vec::new())
+            }
+            let mut candidates = ChiselCommand::iter()
+                .flat_map(|cmd| CmdDescriptor::from(cmd).0.iter())
+                .filter_map(|usage| {
+                    let name = usage.split_whitespace().next()?;
+                    name.starts_with(cmd).then(|| Completion::new(name, *usage))
+                })
+                .collect::<Vec<_>>();
+            candidates.sort();
+            candidates.dedup_by(|a, b| a.name == b.name);
+            return (pos - cmd.len(), candidates.iter().map(Pair::from).collect())
+        }
+        if Self::in_literal_or_comment(line) {
+            return (pos, Vec::new())
+        }
+
+        // Complete the identifier, or the member of the identifier, before the cursor
+        let start = line
+            .char_indices()
+            .rfind(|&(_, c)| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let word = &line[start..];
+        match word.rsplit_once('.') {
+            Some((ident, member)) => {
+                let globals = GLOBAL_MEMBERS
+                    .iter()
+                    .filter(|(global, _)| *global == ident)
+                    .flat_map(|(_, members)| members.iter())
+                    .map(|(name, display)| Completion::new(*name, *display));
+                let candidates = self
+                    .completions
+                    .members
+                    .get(ident)
+                    .into_iter()
+                    .flatten()
+                    .cloned()
+                    .chain(globals)
+                    .filter(|completion| completion.name.starts_with(member))
+                    .map(|completion| Pair::from(&completion))
+                    .collect();
+                (pos - member.len(), candidates)
+            }
+            None if word.is_empty() => (pos, Vec::new()),
+            None => {
+                let globals = GLOBAL_MEMBERS
+                    .iter()
+                    .map(|(global, _)| Completion::new(*global, format!("{global}.")));
+                let candidates = self
+                    .completions
+                    .identifiers
+                    .iter()
+                    .cloned()
+                    .chain(globals)
+                    .filter(|completion| completion.name.starts_with(word))
+                    .map(|completion| Pair::from(&completion))
+                    .collect();
+                (start, candidates)
+            }
+        }
+    }
+
+    /// Returns whether the end of the line is within a string literal or a comment.
+    fn in_literal_or_comment(line: &str) -> bool {
+        let mut chars = line.chars().peekable();
+        let mut quote = None;
+        let mut block_comment = false;
+        while let Some(c) = chars.next() {
+            if block_comment {
+                if c == '*' && chars.next_if_eq(&'/').is_some() {
+                    block_comment = false;
+                }
+                continue
+            }
+            match quote {
+                Some(_) if c == '\\' => {
+                    chars.next();
+                }
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None => match c {
+                    '"' | '\'' => quote = Some(c),
+                    '/' if chars.next_if_eq(&'/').is_some() => return true,
+                    '/' if chars.next_if_eq(&'*').is_some() => block_comment = true,
+                    _ => {}
+                },
+            }
+        }
+        quote.is_some() || block_comment
+    }
+
     /// Get styles for a solidity source string
     pub fn get_styles(input: &str) -> Vec<SpannedStyle> {
         let mut comments = Vec::with_capacity(DEFAULT_COMMENTS);
@@ -147,6 +327,11 @@ impl SolidityHelper {
         }
     }
 
+    /// Returns whether a source snippet is closed (i.e., all braces and parenthesis are matched).
+    pub fn is_closed(input: &str) -> bool {
+        matches!(Self::validate_closed(input), ValidationResult::Valid(_))
+    }
+
     /// Validate that a source snippet is closed (i.e., all braces and parenthesis are matched).
     fn validate_closed(input: &str) -> ValidationResult {
         let mut bracket_depth = 0usize;
@@ -253,12 +438,27 @@ impl Highlighter for SolidityHelper {
 
 impl Validator for SolidityHelper {
     fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
-        Ok(Self::validate_closed(ctx.input()))
+        let input = ctx.input();
+        // `!edit` on its own line opens the unclosed input in the editor, see
+        // `ChiselDispatcher::buffer_line`
+        if input.lines().last().is_some_and(|line| line.trim() == EDIT_COMMAND) {
+            return Ok(ValidationResult::Valid(None))
+        }
+        Ok(Self::validate_closed(input))
     }
 }
 
 impl Completer for SolidityHelper {
-    type Candidate = String;
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Self::Candidate>)> {
+        Ok(self.completions_at(line, pos))
+    }
 }
 
 impl Hinter for SolidityHelper {
@@ -308,3 +508,99 @@ impl<'a> TokenStyle for Token<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn helper() -> SolidityHelper {
+        let mut helper = SolidityHelper::new();
+        helper.set_completions(Completions {
+            identifiers: vec![
+                Completion::new("counter", "counter: Counter"),
+                Completion::new("count", "count: uint256"),
+                Completion::new("vm", "vm: Vm"),
+            ],
+            members: BTreeMap::from([
+                (
+                    "counter".to_string(),
+                    vec![
+                        Completion::new("increment", "increment()"),
+                        Completion::new("number", "number()"),
+                    ],
+                ),
+                (
+                    "vm".to_string(),
+                    vec![
+                        Completion::new("warp", "warp(uint256 newTimestamp)"),
+                        Completion::new("roll", "roll(uint256 newHeight)"),
+                    ],
+                ),
+            ]),
+        });
+        helper
+    }
+
+    fn complete(line: &str) -> (usize, Vec<(String, String)>) {
+        let (start, candidates) = helper().completions_at(line, line.len());
+        (start, candidates.into_iter().map(|pair| (pair.replacement, pair.display)).collect())
+    }
+
+    fn names(line: &str) -> Vec<String> {
+        complete(line).1.into_iter().map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn test_complete_identifiers() {
+        assert_eq!(
+            complete("uint256 x = cou"),
+            (
+                12,
+                vec![
+                    ("counter".to_string(), "counter: Counter".to_string()),
+                    ("count".to_string(), "count: uint256".to_string())
+                ]
+            )
+        );
+        assert_eq!(names("b"), ["block"]);
+        assert!(names("uint256 x = ").is_empty());
+        // the identifier follows a multi-byte character
+        assert_eq!(complete("x = \u{2192}cou").0, 7);
+        assert_eq!(names("€vm"), ["vm"]);
+    }
+
+    #[test]
+    fn test_complete_members() {
+        assert_eq!(
+            complete("vm.w"),
+            (3, vec![("warp".to_string(), "warp(uint256 newTimestamp)".to_string())])
+        );
+        assert_eq!(names("counter."), ["increment", "number"]);
+        assert_eq!(names("block.time"), ["timestamp"]);
+        assert_eq!(names("abi.encodeW"), ["encodeWithSelector", "encodeWithSignature"]);
+        assert_eq!(names("msg.s"), ["sender", "sig"]);
+        assert!(names("count.").is_empty());
+    }
+
+    #[test]
+    fn test_complete_commands() {
+        let (start, candidates) = complete("!fo");
+        assert_eq!(start, 1);
+        assert_eq!(
+            candidates,
+            [("fork".to_string(), "fork <url|alias|index> [block]".to_string())]
+        );
+        assert_eq!(names("!st"), ["stackdump", "storage", "storagelayout"]);
+        assert!(names("!fork ma").is_empty());
+    }
+
+    #[test]
+    fn test_no_completion_in_literals_and_comments() {
+        assert!(names(r#"string memory s = "vm.w"#).is_empty());
+        assert!(names("bytes1 b = 'cou").is_empty());
+        assert!(names("uint256 x; // cou").is_empty());
+        assert!(names("/* vm.").is_empty());
+        assert_eq!(names(r#"string memory s = "a\"b"; cou"#), ["counter", "count"]);
+        assert_eq!(names("/* a */ vm.r"), ["roll"]);
+    }
+}
@@ -220,3 +220,46 @@ fn test_load_latest_cache() {
     assert_eq!(new_env.id.unwrap(), "1");
     assert_eq!(new_env.session_source.to_repl_source(), env.session_source.to_repl_source());
 }
+
+#[test]
+#[serial]
+fn test_named_sessions() {
+    // Create and clear the cache directory
+    ChiselSession::create_cache_dir().unwrap();
+    ChiselSession::clear_cache().unwrap();
+
+    // Force the solc version to be 0.8.19
+    let foundry_config = Config { evm_version: EvmVersion::London, ..Default::default() };
+
+    // Create a named session and a numbered one
+    let mut env = ChiselSession::new(chisel::session_source::SessionSourceConfig {
+        foundry_config: foundry_config.clone(),
+        ..Default::default()
+    })
+    .unwrap_or_else(|e| panic!("Failed to create ChiselSession! {}", e));
+    env.id = Some(String::from("weth"));
+    env.description = Some(String::from("Wrapping WETH"));
+    env.write().unwrap();
+
+    let mut env2 = ChiselSession::new(chisel::session_source::SessionSourceConfig {
+        foundry_config,
+        ..Default::default()
+    })
+    .unwrap_or_else(|e| panic!("Failed to create ChiselSession! {}", e));
+    env2.write().unwrap();
+    // Named sessions don't take a number
+    assert_eq!(env2.id.as_deref(), Some("0"));
+
+    // List the sessions
+    let sessions = ChiselSession::session_infos().unwrap();
+    assert_eq!(sessions.len(), 2);
+    let named = sessions.iter().find(|session| session.id == "weth").unwrap();
+    assert_eq!(named.description.as_deref(), Some("Wrapping WETH"));
+    assert_eq!(named.fork, None);
+
+    // Delete the named session
+    ChiselSession::delete("weth").unwrap();
+    assert!(ChiselSession::load("weth").is_err());
+    assert!(ChiselSession::delete("weth").is_err());
+    assert_eq!(ChiselSession::session_infos().unwrap().len(), 1);
+}
@@ -0,0 +1,75 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn chisel() -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_chisel"));
+    cmd.env("NO_COLOR", "1");
+    cmd
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_eval_exit_code() {
+    let output = chisel().args(["eval", "--json", "uint256 a = 40; a + 2"]).output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["value"], serde_json::json!({ "type": "uint256", "value": "42" }));
+
+    let output = chisel().args(["eval", "uint256 a = 1; require(a > 1, \"empty\"); a"]).output();
+    let output = output.unwrap();
+    assert_eq!(output.status.code(), Some(1), "{}", stdout(&output));
+    let err = stderr(&output);
+    assert!(err.contains("Error at line 1: `require(a > 1, \"empty\");`"), "{err}");
+}
+
+#[test]
+fn test_script_exit_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("check.sol.repl");
+    std::fs::write(
+        &path,
+        "uint256 balance = 0;\nbalance += 1;\nrequire(balance > 1, \"empty\");\n",
+    )
+    .unwrap();
+    let output = chisel().arg("script").arg(&path).output().unwrap();
+    assert_eq!(output.status.code(), Some(1), "{}", stdout(&output));
+    let err = stderr(&output);
+    assert!(err.contains("Error at line 3: `require(balance > 1, \"empty\");`"), "{err}");
+    assert!(err.contains("empty"), "{err}");
+
+    std::fs::write(&path, "uint256 balance = 0;\nbalance += 1;\nbalance\n").unwrap();
+    let output = chisel().args(["script", "--json"]).arg(&path).output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains(r#""value":"1""#), "{}", stdout(&output));
+}
+
+#[test]
+fn test_stdin_exit_code() {
+    let run = |script: &str| {
+        let mut child = chisel()
+            .arg("--stdin")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+        child.wait_with_output().unwrap()
+    };
+
+    let output = run("uint256 a = 2;\nassert(a == 2);\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = run("uint256 a = 2;\nassert(a == 3);\n");
+    assert_eq!(output.status.code(), Some(1), "{}", stdout(&output));
+    assert!(stderr(&output).contains("Error at line 2"), "{}", stderr(&output));
+}
@@ -0,0 +1,99 @@
+use chisel::prelude::{ChiselDispatcher, DispatchResult, SessionSourceConfig};
+use foundry_compilers::EvmVersion;
+use foundry_config::{Config, RpcEndpoint, RpcEndpoints};
+use foundry_evm::opts::EvmOpts;
+use serial_test::serial;
+use std::fs;
+
+/// The subset of forge-std used by the exported tests.
+const FORGE_STD_TEST: &str = r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=0.8.0;
+
+interface Vm {
+    function deal(address account, uint256 newBalance) external;
+    function createSelectFork(string calldata urlOrAlias, uint256 blockNumber)
+        external
+        returns (uint256 forkId);
+}
+
+abstract contract Test {
+    Vm internal constant vm = Vm(address(uint160(uint256(keccak256("hevm cheat code")))));
+}
+"#;
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn test_export_session_to_test() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("foundry.toml"), "[profile.default]\n").unwrap();
+    fs::create_dir_all(dir.path().join("lib/forge-std/src")).unwrap();
+    fs::write(dir.path().join("lib/forge-std/src/Test.sol"), FORGE_STD_TEST).unwrap();
+    // `!export` writes relative to the project it is run in
+    let cwd = std::env::current_dir().unwrap();
+    std::env::set_current_dir(dir.path()).unwrap();
+
+    let foundry_config = Config {
+        evm_version: EvmVersion::London,
+        ..Config::load_with_root(dir.path()).sanitized()
+    };
+    let mut dispatcher =
+        ChiselDispatcher::new(SessionSourceConfig { foundry_config, ..Default::default() })
+            .unwrap();
+    for line in ["uint256 public stored;", "stored = 21 * 2;"] {
+        let res = dispatcher.dispatch(line).await;
+        assert!(matches!(res, DispatchResult::Success(_)), "{line}: {res:?}");
+    }
+
+    let res = dispatcher.dispatch("!export test/Repl.t.sol").await;
+    std::env::set_current_dir(cwd).unwrap();
+    match res {
+        DispatchResult::CommandSuccess(Some(out)) => {
+            assert!(out.contains("as `ReplTest`"), "{out}")
+        }
+        res => panic!("{res:?}"),
+    }
+
+    let exported = fs::read_to_string(dir.path().join("test/Repl.t.sol")).unwrap();
+    for expected in [
+        "import {Test} from \"forge-std/Test.sol\";",
+        "contract ReplTest is Test {",
+        "uint256 public stored;",
+        "vm.deal(address(this), type(uint256).max);",
+        "function test_repl() public {",
+        "stored = 21 * 2;",
+    ] {
+        assert!(exported.contains(expected), "{expected}:\n{exported}");
+    }
+    // the session is only run by the test
+    let set_up = &exported[exported.find("function setUp()").unwrap()..];
+    let set_up = &set_up[..set_up.find('}').unwrap()];
+    assert!(!set_up.contains("stored"), "{exported}");
+}
+
+#[test]
+fn test_export_fork_by_alias() {
+    let url = "https://eth-mainnet.example.com/v2/secret-key";
+    let foundry_config = Config {
+        evm_version: EvmVersion::London,
+        rpc_endpoints: RpcEndpoints::new([("mainnet", RpcEndpoint::Url(url.to_string()))]),
+        ..Default::default()
+    };
+    let evm_opts = EvmOpts {
+        fork_url: Some(url.to_string()),
+        fork_block_number: Some(100),
+        ..Default::default()
+    };
+    let dispatcher = ChiselDispatcher::new(SessionSourceConfig {
+        foundry_config,
+        evm_opts,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let exported = dispatcher.source().to_test_source("ForkTest");
+    assert!(exported.contains("contract ForkTest is Test {"), "{exported}");
+    // the alias doesn't leak the key of the URL
+    assert!(exported.contains("vm.createSelectFork(\"mainnet\", 100);"), "{exported}");
+    assert!(!exported.contains("secret-key"), "{exported}");
+}
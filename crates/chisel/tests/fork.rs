@@ -0,0 +1,96 @@
+use anvil::{spawn, NodeConfig};
+use chisel::prelude::{ChiselDispatcher, DispatchResult, SessionSourceConfig};
+use foundry_compilers::EvmVersion;
+use foundry_config::Config;
+
+async fn dispatch_ok(dispatcher: &mut ChiselDispatcher, line: &str) -> Option<String> {
+    match dispatcher.dispatch(line).await {
+        DispatchResult::Success(out) | DispatchResult::CommandSuccess(out) => out,
+        res => panic!("{line}: {res:?}"),
+    }
+}
+
+async fn dispatch_err(dispatcher: &mut ChiselDispatcher, line: &str) -> String {
+    match dispatcher.dispatch(line).await {
+        DispatchResult::CommandFailed(e) => e,
+        res => panic!("{line}: {res:?}"),
+    }
+}
+
+/// Checks the block number the session is executed at, without keeping the check in the session.
+async fn assert_block(dispatcher: &mut ChiselDispatcher, number: u64) {
+    dispatch_ok(dispatcher, &format!("require(block.number == {number});")).await;
+    dispatch_ok(dispatcher, "!clear").await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_switch_list_and_roll_forks() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    for _ in 0..3 {
+        api.mine_one().await;
+    }
+    let endpoint = handle.http_endpoint();
+
+    let foundry_config = Config { evm_version: EvmVersion::London, ..Default::default() };
+    let mut dispatcher =
+        ChiselDispatcher::new(SessionSourceConfig { foundry_config, ..Default::default() })
+            .unwrap();
+    let out = dispatch_ok(&mut dispatcher, "!fork list").await;
+    assert!(out.unwrap().contains("No forks in this session"));
+    assert!(dispatch_err(&mut dispatcher, "!fork block").await.contains("Not forking"));
+    assert!(dispatch_err(&mut dispatcher, "!fork roll 1").await.contains("Not forking"));
+
+    // the fork is pinned to the latest block
+    dispatch_ok(&mut dispatcher, &format!("!fork {endpoint}")).await;
+    assert_eq!(dispatcher.get_prompt(), "[31337@3] ➜ ");
+    assert_block(&mut dispatcher, 3).await;
+
+    dispatch_ok(&mut dispatcher, &format!("!fork {endpoint} 1")).await;
+    assert_eq!(dispatcher.get_prompt(), "[31337@1] ➜ ");
+    // the session is executed on the new fork
+    let res = dispatcher.dispatch("require(block.number == 3);").await;
+    assert!(matches!(res, DispatchResult::Failure(_)), "{res:?}");
+    assert_block(&mut dispatcher, 1).await;
+
+    let list = dispatch_ok(&mut dispatcher, "!fork list").await.unwrap();
+    let lines = list.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2, "{list}");
+    assert!(lines[0].starts_with("  ") && lines[0].contains("chain 31337 at block 3"), "{list}");
+    assert!(lines[1].starts_with("* ") && lines[1].contains("chain 31337 at block 1"), "{list}");
+
+    assert!(dispatch_err(&mut dispatcher, "!fork roll latest").await.contains("Invalid block"));
+    dispatch_ok(&mut dispatcher, "!fork roll 2").await;
+    assert_eq!(dispatcher.get_prompt(), "[31337@2] ➜ ");
+    assert_block(&mut dispatcher, 2).await;
+    assert_eq!(dispatcher.source().config.forks.len(), 3);
+
+    // the forks of the list are switched to by index
+    dispatch_ok(&mut dispatcher, "!fork 0").await;
+    assert_eq!(dispatcher.get_prompt(), "[31337@3] ➜ ");
+    assert_eq!(dispatcher.source().config.forks.len(), 3);
+    assert_block(&mut dispatcher, 3).await;
+
+    // back to the local environment
+    let out = dispatch_ok(&mut dispatcher, "!fork").await;
+    assert_eq!(out.unwrap(), "Now using local environment.");
+    assert_eq!(dispatcher.get_prompt(), "➜ ");
+    assert!(dispatch_err(&mut dispatcher, "!fork block").await.contains("Not forking"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_keep_fork_when_switching_fails() {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let endpoint = handle.http_endpoint();
+
+    let foundry_config = Config { evm_version: EvmVersion::London, ..Default::default() };
+    let mut dispatcher =
+        ChiselDispatcher::new(SessionSourceConfig { foundry_config, ..Default::default() })
+            .unwrap();
+    dispatch_ok(&mut dispatcher, &format!("!fork {endpoint}")).await;
+    assert_eq!(dispatcher.get_prompt(), "[31337@0] ➜ ");
+
+    let e = dispatch_err(&mut dispatcher, "!fork http://127.0.0.1:1").await;
+    assert!(e.contains("Could not fork"), "{e}");
+    assert_eq!(dispatcher.get_prompt(), "[31337@0] ➜ ");
+    assert_eq!(dispatcher.source().config.forks.len(), 1);
+}
@@ -0,0 +1,64 @@
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::U256;
+use chisel::{
+    executor::Inspected,
+    prelude::{ChiselDispatcher, SessionSourceConfig},
+};
+use foundry_compilers::EvmVersion;
+use foundry_config::Config;
+
+fn dispatcher() -> ChiselDispatcher {
+    let foundry_config = Config { evm_version: EvmVersion::London, ..Default::default() };
+    ChiselDispatcher::new(SessionSourceConfig { foundry_config, ..Default::default() }).unwrap()
+}
+
+async fn uint_value(dispatcher: &ChiselDispatcher, expr: &str) -> U256 {
+    match dispatcher.source().inspect_value(expr).await.unwrap() {
+        (_, Some(Inspected::Value { value: DynSolValue::Uint(value, _), .. })) => value,
+        res => panic!("{expr} is not a uint: {res:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_buffers_lines_until_closed() {
+    let mut dispatcher = dispatcher();
+    let lines = ["function add(uint256 a, uint256 b)", "    internal pure returns (uint256) {"];
+    for line in lines {
+        assert_eq!(dispatcher.buffer_line(line).unwrap(), None);
+        assert!(dispatcher.is_buffering());
+    }
+    assert_eq!(dispatcher.buffer_line("    return a + b;").unwrap(), None);
+
+    let unit = dispatcher.buffer_line("}").unwrap().expect("the function is closed");
+    assert!(!dispatcher.is_buffering());
+    assert_eq!(unit.lines().count(), 4);
+    for res in dispatcher.dispatch_unit(&unit).await {
+        assert!(!res.is_error(), "{res:?}");
+    }
+
+    let unit = dispatcher.buffer_line("uint256 c = add(1, 2);").unwrap().unwrap();
+    for res in dispatcher.dispatch_unit(&unit).await {
+        assert!(!res.is_error(), "{res:?}");
+    }
+    assert_eq!(uint_value(&dispatcher, "c").await, U256::from(3));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dispatches_pasted_block() {
+    let mut dispatcher = dispatcher();
+    let block = "uint256 a = 1;\nuint256 b = 2;\nif (a < b) {\n    a = b;\n}\nelse b = a;";
+    let unit = dispatcher.buffer_line(block).unwrap().expect("the block is closed");
+
+    let results = dispatcher.dispatch_unit(&unit).await;
+    assert_eq!(results.len(), 3);
+    for res in &results {
+        assert!(!res.is_error(), "{res:?}");
+    }
+    assert_eq!(uint_value(&dispatcher, "a").await, U256::from(2));
+
+    // the statements after a failing one are not dispatched
+    let results = dispatcher.dispatch_unit("a = 3;\nuint256 a = 4;\na = 5;").await;
+    assert_eq!(results.len(), 2);
+    assert!(results[1].is_error());
+    assert_eq!(uint_value(&dispatcher, "a").await, U256::from(3));
+}
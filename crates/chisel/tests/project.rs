@@ -0,0 +1,89 @@
+use chisel::prelude::{ChiselDispatcher, DispatchResult, SessionSourceConfig};
+use foundry_compilers::EvmVersion;
+use foundry_config::Config;
+use std::{fs, path::Path};
+
+const COUNTER: &str = r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.19;
+
+contract Counter {
+    uint256 public number;
+
+    function setNumber(uint256 newNumber) public {
+        number = newNumber;
+    }
+}
+"#;
+
+fn dispatcher(root: &Path) -> ChiselDispatcher {
+    let foundry_config =
+        Config { evm_version: EvmVersion::London, ..Config::load_with_root(root).sanitized() };
+    ChiselDispatcher::new(SessionSourceConfig { foundry_config, ..Default::default() }).unwrap()
+}
+
+async fn dispatch_ok(dispatcher: &mut ChiselDispatcher, line: &str) -> Option<String> {
+    match dispatcher.dispatch(line).await {
+        DispatchResult::Success(out) | DispatchResult::CommandSuccess(out) => out,
+        res => panic!("{line}: {res:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_load_attach_and_reload_project_contracts() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/Counter.sol"), COUNTER).unwrap();
+    let mut dispatcher = dispatcher(dir.path());
+
+    let res = dispatcher.dispatch("!load Countr").await;
+    assert!(
+        matches!(&res, DispatchResult::CommandFailed(e) if e.contains("did you mean `Counter`")),
+        "{res:?}"
+    );
+    let out = dispatch_ok(&mut dispatcher, "!load Counter").await.unwrap();
+    assert!(out.contains("Loaded project contract `Counter`"), "{out}");
+
+    // the contract can be deployed and called
+    dispatch_ok(&mut dispatcher, "Counter counter = new Counter();").await;
+    dispatch_ok(&mut dispatcher, "counter.setNumber(7);").await;
+
+    // the first contract created by the session contract
+    let (session, _) = dispatcher.source().clone().execute().await.unwrap();
+    let counter = session.create(1);
+    let res = dispatcher.dispatch("!attach Counter 0xdead").await;
+    assert!(matches!(&res, DispatchResult::CommandFailed(e) if e.contains("Invalid address")));
+    let out = dispatch_ok(&mut dispatcher, &format!("!attach Counter {counter} attached")).await;
+    assert_eq!(out.unwrap(), format!("Attached `attached` to the Counter at {counter}"));
+    dispatch_ok(&mut dispatcher, "require(attached.number() == 7);").await;
+    let res = dispatcher.dispatch("require(attached.number() == 8);").await;
+    assert!(matches!(res, DispatchResult::Failure(_)), "{res:?}");
+
+    // the changes of the project are picked up by a reload
+    let members = |dispatcher: &ChiselDispatcher| {
+        let completions = dispatcher.source().completions();
+        completions.members["Counter"].iter().map(|member| member.name.clone()).collect::<Vec<_>>()
+    };
+    assert!(!members(&dispatcher).contains(&"increment".to_string()));
+    let number = "    uint256 public number;\n";
+    let increment = "\n    function increment() public {\n        number++;\n    }\n";
+    let counter = COUNTER.replace(number, &format!("{number}{increment}"));
+    fs::write(dir.path().join("src/Counter.sol"), counter).unwrap();
+    let out = dispatch_ok(&mut dispatcher, "!reload").await;
+    assert_eq!(out.unwrap(), "Reloaded 1 project contract!");
+    assert!(members(&dispatcher).contains(&"increment".to_string()));
+    dispatch_ok(&mut dispatcher, "attached.increment();").await;
+    dispatch_ok(&mut dispatcher, "require(counter.number() == 8);").await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reload_without_project_contracts() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/Counter.sol"), COUNTER).unwrap();
+    let mut dispatcher = dispatcher(dir.path());
+
+    let res = dispatcher.dispatch("!reload").await;
+    let loaded = "No project contracts loaded";
+    assert!(matches!(&res, DispatchResult::CommandFailed(e) if e.contains(loaded)), "{res:?}");
+}
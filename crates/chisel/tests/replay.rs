@@ -0,0 +1,36 @@
+use alloy_json_abi::Function;
+use anvil::{spawn, NodeConfig};
+use chisel::prelude::{ChiselDispatcher, DispatchResult, SessionSourceConfig};
+use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionRequest, U256};
+use ethers_providers::Middleware;
+use foundry_common::types::ToEthers;
+use foundry_compilers::EvmVersion;
+use foundry_config::Config;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dump_state_loads_into_anvil() {
+    let foundry_config = Config { evm_version: EvmVersion::London, ..Default::default() };
+    let mut dispatcher =
+        ChiselDispatcher::new(SessionSourceConfig { foundry_config, ..Default::default() })
+            .unwrap();
+    for line in ["uint256 public stored;", "stored = 42;"] {
+        let res = dispatcher.dispatch(line).await;
+        assert!(matches!(res, DispatchResult::Success(_)), "{line}: {res:?}");
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+    let res = dispatcher.dispatch(&format!("!dumpstate {}", path.display())).await;
+    assert!(matches!(res, DispatchResult::CommandSuccess(_)), "{res:?}");
+
+    // the session contract is deployed at the same address by every execution
+    let (address, _) = dispatcher.source().clone().execute().await.unwrap();
+    let (_api, handle) = spawn(NodeConfig::test().with_init_state_path(&path)).await;
+    let provider = handle.http_provider();
+
+    let stored = Function::parse("stored() returns (uint256)").unwrap();
+    let tx: TypedTransaction =
+        TransactionRequest::new().to(address.to_ethers()).data(stored.selector().to_vec()).into();
+    let returned = provider.call(&tx, None).await.unwrap();
+    assert_eq!(U256::from_big_endian(&returned), U256::from(42));
+}
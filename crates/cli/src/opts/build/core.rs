@@ -60,7 +60,7 @@ pub struct CoreBuildArgs {
 
     /// Do not access the network.
     ///
-    /// Missing solc versions will not be installed.
+    /// Missing solc versions will not be installed, and forks are only served from the RPC cache.
     #[clap(help_heading = "Compiler options", long)]
     #[serde(skip)]
     pub offline: bool,
@@ -1,12 +1,13 @@
 use clap::builder::{PossibleValuesParser, TypedValueParser};
 use eyre::Result;
-use foundry_config::{Chain, NamedChain};
+use foundry_config::{chains::parse_chain, Chain, NamedChain};
 use std::ffi::OsStr;
 use strum::VariantNames;
 
 /// Custom Clap value parser for [`Chain`]s.
 ///
-/// Displays all possible chains when an invalid chain is provided.
+/// Accepts the custom chains of `~/.foundry/chains.toml`, and displays all possible chains when an
+/// invalid chain is provided.
 #[derive(Clone, Debug)]
 pub struct ChainValueParser {
     pub inner: PossibleValuesParser,
@@ -29,17 +30,9 @@ impl TypedValueParser for ChainValueParser {
     ) -> Result<Self::Value, clap::Error> {
         let s =
             value.to_str().ok_or_else(|| clap::Error::new(clap::error::ErrorKind::InvalidUtf8))?;
-        if let Ok(id) = s.parse() {
-            Ok(Chain::from_id(id))
-        } else {
-            // NamedChain::VARIANTS is a subset of all possible variants, since there are aliases:
-            // mumbai instead of polygon-mumbai etc
-            //
-            // Parse first as NamedChain, if it fails parse with NamedChain::VARIANTS for displaying
-            // the error to the user
-            s.parse()
-                .map_err(|_| self.inner.parse_ref(cmd, arg, value).unwrap_err())
-                .map(Chain::from_named)
-        }
+        // ids, the chains of the registry and of `~/.foundry/chains.toml`, and all the NamedChain
+        // names, since NamedChain::VARIANTS is a subset of them: mumbai instead of polygon-mumbai
+        // etc. NamedChain::VARIANTS is only used for displaying the error to the user
+        parse_chain(s).ok_or_else(|| self.inner.parse_ref(cmd, arg, value).unwrap_err())
     }
 }
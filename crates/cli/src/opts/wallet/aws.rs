@@ -0,0 +1,171 @@
+//! Helpers to create signers of AWS KMS keys.
+
+use ethers_signers::{AwsSigner, AwsSignerError};
+use eyre::Result;
+use foundry_config::WalletsConfig;
+use rusoto_core::{
+    credential::ChainProvider as AwsChainProvider, region::Region as AwsRegion,
+    request::HttpClient as AwsHttpClient, Client as AwsClient,
+};
+use rusoto_kms::KmsClient;
+use std::str::FromStr;
+
+/// Returns a KMS client of the region of `AWS_DEFAULT_REGION` or `AWS_REGION`, or else the
+/// `aws_region` of the config.
+pub fn aws_kms_client(config: &WalletsConfig) -> Result<KmsClient> {
+    let has_env_region =
+        std::env::var("AWS_DEFAULT_REGION").is_ok() || std::env::var("AWS_REGION").is_ok();
+    let region = match &config.aws_region {
+        Some(region) if !has_env_region => AwsRegion::from_str(region)
+            .map_err(|_| eyre::eyre!("Invalid AWS region `{region}` in the wallets config"))?,
+        _ => AwsRegion::default(),
+    };
+    let client = AwsClient::new_with(AwsChainProvider::default(), AwsHttpClient::new()?);
+    Ok(KmsClient::new_with_client(client, region))
+}
+
+/// Returns the ids of the AWS KMS keys to use: the comma separated `AWS_KMS_KEY_IDS`, the
+/// `AWS_KMS_KEY_ID`, or else the `aws_kms` keys of the config.
+pub fn aws_kms_key_ids(config: &WalletsConfig) -> Result<Vec<String>> {
+    let key_ids: Vec<String> = std::env::var("AWS_KMS_KEY_IDS")
+        .or_else(|_| std::env::var("AWS_KMS_KEY_ID"))
+        .map(|ids| ids.split(',').map(|id| id.trim().to_string()).collect())
+        .unwrap_or_else(|_| config.aws_kms_keys())
+        .into_iter()
+        .filter(|id| !id.is_empty())
+        .collect();
+    if key_ids.is_empty() {
+        eyre::bail!(NO_KEY_CONFIGURED)
+    }
+    Ok(key_ids)
+}
+
+/// Returns the id of the AWS KMS key of a single signer, the first of the [aws_kms_key_ids].
+pub fn aws_kms_key_id(config: &WalletsConfig) -> Result<String> {
+    aws_kms_key_ids(config)?.into_iter().next().ok_or_else(|| eyre::eyre!(NO_KEY_CONFIGURED))
+}
+
+const NO_KEY_CONFIGURED: &str =
+    "No AWS KMS key configured. Set `AWS_KMS_KEY_ID` or add the key to `[wallets.aws_kms]`.";
+
+/// Creates the signer of an AWS KMS key, explaining failures caused by missing permissions.
+pub async fn aws_signer(kms: KmsClient, key_id: &str, chain_id: u64) -> Result<AwsSigner> {
+    AwsSigner::new(kms, key_id, chain_id).await.map_err(|err| aws_error(key_id, err))
+}
+
+/// Wraps an error of AWS KMS, with a hint if the credentials lack a permission.
+pub fn aws_error(key_id: &str, err: AwsSignerError) -> eyre::Report {
+    let msg = err.to_string();
+    if msg.contains("AccessDenied") || msg.contains("not authorized") {
+        eyre::eyre!(
+            "Permission denied for AWS KMS key `{key_id}`: {msg}\n\
+The credentials need the `kms:GetPublicKey` and `kms:Sign` permissions on the key."
+        )
+    } else if msg.contains("NotFoundException") {
+        eyre::eyre!("AWS KMS key `{key_id}` not found in the region: {msg}")
+    } else {
+        eyre::eyre!("Failed to use AWS KMS key `{key_id}`: {msg}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        http::{HeaderMap, StatusCode},
+        response::IntoResponse,
+        Json, Router,
+    };
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use ethers_signers::{LocalWallet, Signer};
+    use k256::{
+        ecdsa::{Signature, SigningKey},
+        pkcs8::EncodePublicKey,
+    };
+    use rusoto_core::credential::StaticProvider;
+    use serde_json::{json, Value};
+
+    const PRIVATE_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_slice(&hex::decode(PRIVATE_KEY).unwrap()).unwrap()
+    }
+
+    /// Spawns the JSON API of a KMS holding the `allowed` key, any other key is denied, and
+    /// returns a client of it.
+    fn spawn_kms() -> KmsClient {
+        // the requests are `application/x-amz-json-1.1`, which the `Json` extractor rejects
+        let app = Router::new().fallback(|headers: HeaderMap, body: String| async move {
+            let body: Value = serde_json::from_str(&body).unwrap();
+            let target = headers["x-amz-target"].to_str().unwrap().to_string();
+            if body["KeyId"] != "allowed" {
+                let denied = json!({
+                    "__type": "AccessDeniedException",
+                    "message": "User is not authorized to perform: kms:GetPublicKey",
+                });
+                return (StatusCode::BAD_REQUEST, Json(denied)).into_response()
+            }
+            let key = signing_key();
+            let public_key = key.verifying_key().to_public_key_der().unwrap();
+            let response = match target.as_str() {
+                "TrentService.GetPublicKey" => json!({
+                    "KeyId": "allowed",
+                    "KeySpec": "ECC_SECG_P256K1",
+                    "KeyUsage": "SIGN_VERIFY",
+                    "PublicKey": STANDARD.encode(public_key.as_bytes()),
+                    "SigningAlgorithms": ["ECDSA_SHA_256"],
+                }),
+                "TrentService.Sign" => {
+                    let digest = STANDARD.decode(body["Message"].as_str().unwrap()).unwrap();
+                    let signature: Signature = key.sign_prehash_recoverable(&digest).unwrap().0;
+                    json!({
+                        "KeyId": "allowed",
+                        "Signature": STANDARD.encode(signature.to_der().as_bytes()),
+                        "SigningAlgorithm": "ECDSA_SHA_256",
+                    })
+                }
+                target => panic!("unexpected KMS request {target}"),
+            };
+            Json(response).into_response()
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+        let region = AwsRegion::Custom { name: "us-east-1".to_string(), endpoint };
+        let credentials = StaticProvider::new_minimal("id".to_string(), "secret".to_string());
+        KmsClient::new_with(AwsHttpClient::new().unwrap(), credentials, region)
+    }
+
+    #[tokio::test]
+    async fn signs_with_a_kms_key() {
+        let signer = aws_signer(spawn_kms(), "allowed", 1).await.unwrap();
+        let wallet = PRIVATE_KEY.parse::<LocalWallet>().unwrap();
+        assert_eq!(signer.address(), wallet.address());
+
+        let signature = signer.sign_message("foundry").await.unwrap();
+        assert_eq!(signature.recover("foundry").unwrap(), wallet.address());
+    }
+
+    #[tokio::test]
+    async fn explains_denied_kms_keys() {
+        let err = aws_signer(spawn_kms(), "denied", 1).await.unwrap_err().to_string();
+        assert!(err.contains("Permission denied for AWS KMS key `denied`"), "{err}");
+        assert!(err.contains("kms:GetPublicKey"), "{err}");
+    }
+
+    #[test]
+    fn requires_a_kms_key() {
+        if std::env::var("AWS_KMS_KEY_IDS").is_ok() || std::env::var("AWS_KMS_KEY_ID").is_ok() {
+            return
+        }
+        let err = aws_kms_key_id(&WalletsConfig::default()).unwrap_err();
+        assert!(err.to_string().contains("No AWS KMS key configured"), "{err}");
+
+        let config = WalletsConfig {
+            aws_kms: [("deployer".to_string(), "key".to_string())].into(),
+            ..Default::default()
+        };
+        assert_eq!(aws_kms_key_id(&config).unwrap(), "key");
+    }
+}
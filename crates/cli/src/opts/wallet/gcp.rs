@@ -0,0 +1,381 @@
+//! Signer backed by a key of Google Cloud KMS.
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ethers_core::{
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature, H256, U256,
+    },
+    utils::{hash_message, keccak256},
+};
+use ethers_signers::Signer;
+use k256::{
+    ecdsa::{RecoveryId, Signature as KSignature, VerifyingKey},
+    pkcs8::DecodePublicKey,
+};
+use serde::Deserialize;
+use std::{fmt, sync::Arc};
+
+/// The algorithm of the keys which can sign Ethereum transactions.
+pub const GCP_KMS_ALGORITHM: &str = "EC_SIGN_SECP256K1_SHA256";
+
+/// The default endpoint of the KMS API, `GCP_KMS_ENDPOINT` overrides it.
+const DEFAULT_ENDPOINT: &str = "https://cloudkms.googleapis.com";
+
+/// Errors of the [GcpKmsSigner].
+#[derive(Debug, thiserror::Error)]
+pub enum GcpKmsError {
+    #[error(
+        "no access token for GCP KMS, set `GCP_ACCESS_TOKEN` or log in with `gcloud auth login`"
+    )]
+    MissingToken,
+    #[error("GCP KMS rejected the access token, it may have expired: {0}")]
+    Unauthenticated(String),
+    #[error(
+        "permission denied for GCP KMS key `{key}`, needs `roles/cloudkms.signerVerifier`: {message}"
+    )]
+    PermissionDenied { key: String, message: String },
+    #[error("GCP KMS key version `{0}` not found")]
+    NotFound(String),
+    #[error("GCP KMS key `{key}` has algorithm `{algorithm}`, expected `{GCP_KMS_ALGORITHM}`")]
+    UnsupportedAlgorithm { key: String, algorithm: String },
+    #[error("GCP KMS request failed with status {status}: {message}")]
+    Api { status: u16, message: String },
+    #[error("invalid public key of GCP KMS key `{0}`")]
+    InvalidPublicKey(String),
+    #[error("invalid signature of GCP KMS key `{0}`")]
+    InvalidSignature(String),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("failed to encode the typed data: {0}")]
+    Eip712(String),
+}
+
+/// The subset of the KMS API the [GcpKmsSigner] uses.
+#[async_trait]
+pub trait GcpKms: fmt::Debug + Send + Sync {
+    /// Returns the PEM encoded public key of the key version `name`, checking its algorithm.
+    async fn public_key(&self, name: &str) -> Result<String, GcpKmsError>;
+
+    /// Signs the SHA-256 `digest` with the key version `name`, returning the DER encoded
+    /// signature.
+    ///
+    /// The digest of a secp256k1 key isn't checked, so it can be any other 32 byte hash.
+    async fn asymmetric_sign(&self, name: &str, digest: &[u8; 32]) -> Result<Vec<u8>, GcpKmsError>;
+}
+
+/// Client of the REST API of Google Cloud KMS.
+#[derive(Clone)]
+pub struct GcpKmsClient {
+    http: reqwest::Client,
+    endpoint: String,
+    token: String,
+}
+
+impl fmt::Debug for GcpKmsClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GcpKmsClient").field("endpoint", &self.endpoint).finish_non_exhaustive()
+    }
+}
+
+impl GcpKmsClient {
+    /// Creates a client authenticated with `GCP_ACCESS_TOKEN`, or the token of
+    /// `gcloud auth print-access-token` if it's not set.
+    pub fn from_env() -> Result<Self, GcpKmsError> {
+        let token = match std::env::var("GCP_ACCESS_TOKEN") {
+            Ok(token) => token,
+            Err(_) => std::process::Command::new("gcloud")
+                .args(["auth", "print-access-token"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .ok_or(GcpKmsError::MissingToken)?,
+        };
+        let token = token.trim();
+        if token.is_empty() {
+            return Err(GcpKmsError::MissingToken)
+        }
+        let endpoint =
+            std::env::var("GCP_KMS_ENDPOINT").unwrap_or_else(|_| DEFAULT_ENDPOINT.to_string());
+        Ok(Self::new(endpoint, token.to_string()))
+    }
+
+    /// Creates a client of the API at `endpoint`.
+    pub fn new(endpoint: impl Into<String>, token: impl Into<String>) -> Self {
+        let endpoint = endpoint.into().trim_end_matches('/').to_string();
+        Self { http: reqwest::Client::new(), endpoint, token: token.into() }
+    }
+
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        name: &str,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, GcpKmsError> {
+        let response = request.bearer_auth(&self.token).send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response.json().await?)
+        }
+
+        #[derive(Deserialize)]
+        struct ErrorResponse {
+            error: ErrorBody,
+        }
+        #[derive(Deserialize)]
+        struct ErrorBody {
+            message: String,
+        }
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<ErrorResponse>(&body)
+            .map(|response| response.error.message)
+            .unwrap_or(body);
+        Err(match status.as_u16() {
+            401 => GcpKmsError::Unauthenticated(message),
+            403 => GcpKmsError::PermissionDenied { key: name.to_string(), message },
+            404 => GcpKmsError::NotFound(name.to_string()),
+            status => GcpKmsError::Api { status, message },
+        })
+    }
+}
+
+#[async_trait]
+impl GcpKms for GcpKmsClient {
+    async fn public_key(&self, name: &str) -> Result<String, GcpKmsError> {
+        #[derive(Deserialize)]
+        struct PublicKey {
+            pem: String,
+            algorithm: String,
+        }
+        let url = format!("{}/v1/{name}/publicKey", self.endpoint);
+        let key: PublicKey = self.send(name, self.http.get(url)).await?;
+        if key.algorithm != GCP_KMS_ALGORITHM {
+            return Err(GcpKmsError::UnsupportedAlgorithm {
+                key: name.to_string(),
+                algorithm: key.algorithm,
+            })
+        }
+        Ok(key.pem)
+    }
+
+    async fn asymmetric_sign(&self, name: &str, digest: &[u8; 32]) -> Result<Vec<u8>, GcpKmsError> {
+        #[derive(Deserialize)]
+        struct Signed {
+            signature: String,
+        }
+        let url = format!("{}/v1/{name}:asymmetricSign", self.endpoint);
+        let body = serde_json::json!({ "digest": { "sha256": BASE64.encode(digest) } });
+        let signed: Signed = self.send(name, self.http.post(url).json(&body)).await?;
+        BASE64.decode(signed.signature).map_err(|_| GcpKmsError::InvalidSignature(name.to_string()))
+    }
+}
+
+/// A signer which signs with a secp256k1 key version of Google Cloud KMS.
+///
+/// KMS returns signatures without a recovery id, which is found by recovering the public key
+/// from the signature normalized to the lower `s`.
+#[derive(Clone, Debug)]
+pub struct GcpKmsSigner {
+    kms: Arc<dyn GcpKms>,
+    key_name: String,
+    verifying_key: VerifyingKey,
+    address: Address,
+    chain_id: u64,
+}
+
+impl GcpKmsSigner {
+    /// Creates a signer of the key version `key_name`, fetching its public key.
+    pub async fn new(
+        kms: Arc<dyn GcpKms>,
+        key_name: impl Into<String>,
+        chain_id: u64,
+    ) -> Result<Self, GcpKmsError> {
+        let key_name = key_name.into();
+        let pem = kms.public_key(&key_name).await?;
+        let verifying_key = VerifyingKey::from_public_key_pem(&pem)
+            .map_err(|_| GcpKmsError::InvalidPublicKey(key_name.clone()))?;
+        let address = verifying_key_to_address(&verifying_key);
+        Ok(Self { kms, key_name, verifying_key, address, chain_id })
+    }
+
+    /// Returns the resource name of the key version.
+    pub fn key_name(&self) -> &str {
+        &self.key_name
+    }
+
+    /// Signs the digest, returning a signature with `v` set to the recovery id.
+    async fn sign_digest(&self, digest: H256) -> Result<Signature, GcpKmsError> {
+        let der = self.kms.asymmetric_sign(&self.key_name, digest.as_fixed_bytes()).await?;
+        let invalid = || GcpKmsError::InvalidSignature(self.key_name.clone());
+        let signature = KSignature::from_der(&der).map_err(|_| invalid())?;
+        let signature = signature.normalize_s().unwrap_or(signature);
+        let recovery_id = [0, 1]
+            .into_iter()
+            .filter_map(RecoveryId::from_byte)
+            .find(|id| {
+                VerifyingKey::recover_from_prehash(digest.as_bytes(), &signature, *id)
+                    .is_ok_and(|key| key == self.verifying_key)
+            })
+            .ok_or_else(invalid)?;
+        Ok(Signature {
+            r: U256::from_big_endian(signature.r().to_bytes().as_slice()),
+            s: U256::from_big_endian(signature.s().to_bytes().as_slice()),
+            v: u64::from(recovery_id.to_byte()),
+        })
+    }
+}
+
+/// Returns the address of a public key.
+fn verifying_key_to_address(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    Address::from_slice(&keccak256(&point.as_bytes()[1..])[12..])
+}
+
+#[async_trait]
+impl Signer for GcpKmsSigner {
+    type Error = GcpKmsError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        let mut signature = self.sign_digest(hash_message(message)).await?;
+        signature.v += 27;
+        Ok(signature)
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        let mut tx = tx.clone();
+        tx.set_chain_id(chain_id);
+        let mut signature = self.sign_digest(tx.sighash()).await?;
+        // EIP-155, the `v` of typed transactions is normalized to the parity when encoded
+        signature.v += 35 + chain_id * 2;
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let digest = payload.encode_eip712().map_err(|err| GcpKmsError::Eip712(err.to_string()))?;
+        let mut signature = self.sign_digest(digest.into()).await?;
+        signature.v += 27;
+        Ok(signature)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{
+        transaction::eip2930::AccessList, Eip1559TransactionRequest, Eip2930TransactionRequest,
+        TransactionRequest,
+    };
+    use ethers_signers::LocalWallet;
+    use k256::{ecdsa::SigningKey, pkcs8::EncodePublicKey};
+
+    /// A KMS holding a single key, which optionally returns signatures with the higher `s`.
+    #[derive(Debug)]
+    struct MockKms {
+        key: SigningKey,
+        high_s: bool,
+    }
+
+    #[async_trait]
+    impl GcpKms for MockKms {
+        async fn public_key(&self, _name: &str) -> Result<String, GcpKmsError> {
+            Ok(self.key.verifying_key().to_public_key_pem(Default::default()).unwrap())
+        }
+
+        async fn asymmetric_sign(
+            &self,
+            _name: &str,
+            digest: &[u8; 32],
+        ) -> Result<Vec<u8>, GcpKmsError> {
+            let (signature, _) = self.key.sign_prehash_recoverable(digest).unwrap();
+            let signature = if self.high_s {
+                KSignature::from_scalars(signature.r(), -signature.s()).unwrap()
+            } else {
+                signature
+            };
+            Ok(signature.to_der().as_bytes().to_vec())
+        }
+    }
+
+    const PRIVATE_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    async fn signers(high_s: bool) -> (GcpKmsSigner, LocalWallet) {
+        let key = SigningKey::from_slice(&hex::decode(PRIVATE_KEY).unwrap()).unwrap();
+        let kms = Arc::new(MockKms { key, high_s });
+        let signer = GcpKmsSigner::new(kms, "projects/p/cryptoKeyVersions/1", 10).await.unwrap();
+        (signer, PRIVATE_KEY.parse::<LocalWallet>().unwrap().with_chain_id(10u64))
+    }
+
+    fn transactions() -> Vec<TypedTransaction> {
+        let to: Address = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse().unwrap();
+        let legacy = TransactionRequest::new().to(to).value(1).nonce(0).gas(21000).gas_price(1);
+        let eip2930 = Eip2930TransactionRequest::new(legacy.clone(), AccessList::default());
+        let eip1559 = Eip1559TransactionRequest::new()
+            .to(to)
+            .value(1)
+            .nonce(1)
+            .gas(21000)
+            .max_fee_per_gas(2)
+            .max_priority_fee_per_gas(1);
+        vec![
+            legacy.clone().into(),
+            legacy.chain_id(1u64).into(),
+            TypedTransaction::Eip2930(eip2930),
+            eip1559.into(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn derives_the_address_of_the_key() {
+        let (signer, wallet) = signers(false).await;
+        assert_eq!(signer.address(), wallet.address());
+        assert_eq!(signer.key_name(), "projects/p/cryptoKeyVersions/1");
+    }
+
+    #[tokio::test]
+    async fn signs_like_a_local_wallet() {
+        for high_s in [false, true] {
+            let (signer, wallet) = signers(high_s).await;
+
+            let signature = signer.sign_message("foundry").await.unwrap();
+            assert_eq!(signature, wallet.sign_message("foundry").await.unwrap());
+            assert_eq!(signature.recover("foundry").unwrap(), wallet.address());
+
+            for tx in transactions() {
+                let signature = signer.sign_transaction(&tx).await.unwrap();
+                assert_eq!(signature, wallet.sign_transaction(&tx).await.unwrap());
+
+                let mut tx = tx.clone();
+                if tx.chain_id().is_none() {
+                    tx.set_chain_id(10);
+                }
+                assert_eq!(signature.recover(tx.sighash()).unwrap(), wallet.address());
+                let decoded = TypedTransaction::decode_signed(&ethers_core::utils::rlp::Rlp::new(
+                    &tx.rlp_signed(&signature),
+                ))
+                .unwrap();
+                assert_eq!(decoded.1.recover(decoded.0.sighash()).unwrap(), wallet.address());
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use super::WalletSignerError;
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::{eip2718::TypedTransaction, eip712::Eip712},
+    Address, Signature,
+};
+use ethers_signers::{HDPath as LedgerHDPath, Ledger, Signer, Trezor, TrezorHDPath};
+use std::fmt;
+
+/// The derivation path of an account on a hardware wallet.
+#[derive(Clone, Debug)]
+pub enum HardwareDerivation {
+    Ledger(LedgerHDPath),
+    Trezor(TrezorHDPath),
+}
+
+impl HardwareDerivation {
+    /// Returns the name of the device.
+    pub fn device(&self) -> &'static str {
+        match self {
+            Self::Ledger(_) => "Ledger",
+            Self::Trezor(_) => "Trezor",
+        }
+    }
+}
+
+impl fmt::Display for HardwareDerivation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ledger(path) => write!(f, "{path}"),
+            Self::Trezor(path) => write!(f, "{path}"),
+        }
+    }
+}
+
+/// An account on a hardware wallet which only connects to the device when it has to sign.
+///
+/// A device can't be opened more than once at the same time, so this is used when multiple
+/// derivation paths of the same device are used within one run.
+#[derive(Clone, Debug)]
+pub struct HardwareAccount {
+    derivation: HardwareDerivation,
+    address: Address,
+    chain_id: u64,
+}
+
+/// Connects to the device of the account and evaluates `$e` with it.
+macro_rules! with_device {
+    ($account:ident, $device:ident => $e:expr) => {
+        match &$account.derivation {
+            HardwareDerivation::Ledger(path) => {
+                let $device = Ledger::new(path.clone(), $account.chain_id).await?;
+                $e.map_err(WalletSignerError::from)
+            }
+            HardwareDerivation::Trezor(path) => {
+                let $device = Trezor::new(path.clone(), $account.chain_id, None).await?;
+                $e.map_err(WalletSignerError::from)
+            }
+        }
+    };
+}
+
+impl HardwareAccount {
+    /// Connects to the device once to resolve the address of the account at `derivation`.
+    ///
+    /// The device is released again before returning.
+    pub async fn new(
+        derivation: HardwareDerivation,
+        chain_id: u64,
+    ) -> Result<Self, WalletSignerError> {
+        let address = match &derivation {
+            HardwareDerivation::Ledger(path) => {
+                Ledger::new(path.clone(), chain_id).await?.address()
+            }
+            HardwareDerivation::Trezor(path) => {
+                Trezor::new(path.clone(), chain_id, None).await?.address()
+            }
+        };
+        Ok(Self { derivation, address, chain_id })
+    }
+
+    /// Returns the derivation path of the account.
+    pub fn derivation(&self) -> &HardwareDerivation {
+        &self.derivation
+    }
+}
+
+#[async_trait]
+impl Signer for HardwareAccount {
+    type Error = WalletSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        with_device!(self, device => device.sign_message(message).await)
+    }
+
+    async fn sign_transaction(&self, message: &TypedTransaction) -> Result<Signature, Self::Error> {
+        with_device!(self, device => device.sign_transaction(message).await)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        with_device!(self, device => device.sign_typed_data(payload).await)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
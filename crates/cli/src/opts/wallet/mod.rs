@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use clap::Parser;
 use ethers_core::types::{
     transaction::{eip2718::TypedTransaction, eip712::Eip712},
-    Signature,
+    Bytes, Signature,
 };
 use ethers_signers::{
     coins_bip39::English, AwsSigner, AwsSignerError, HDPath as LedgerHDPath, Ledger, LedgerError,
@@ -13,20 +13,24 @@ use ethers_signers::{
 use eyre::{bail, Result, WrapErr};
 use foundry_common::{fs, types::ToAlloy};
 use foundry_config::Config;
-use rusoto_core::{
-    credential::ChainProvider as AwsChainProvider, region::Region as AwsRegion,
-    request::HttpClient as AwsHttpClient, Client as AwsClient,
-};
-use rusoto_kms::KmsClient;
 use serde::{Deserialize, Serialize};
 use std::{
     path::{Path, PathBuf},
     str::FromStr,
+    sync::Arc,
 };
 
 pub mod multi_wallet;
 pub use multi_wallet::*;
 
+pub mod hardware;
+pub use hardware::*;
+
+pub mod aws;
+
+pub mod gcp;
+pub use gcp::{GcpKms, GcpKmsClient, GcpKmsError, GcpKmsSigner};
+
 pub mod error;
 
 /// A wrapper for the raw data options for `Wallet`, extracted to also be used standalone.
@@ -72,6 +76,7 @@ pub struct RawWallet {
 /// 3. Trezor
 /// 4. Keystore (via file path)
 /// 5. AWS KMS
+/// 6. GCP KMS
 #[derive(Clone, Debug, Default, Serialize, Parser)]
 #[clap(next_help_heading = "Wallet options", about = None, long_about = None)]
 pub struct Wallet {
@@ -139,8 +144,20 @@ pub struct Wallet {
     pub trezor: bool,
 
     /// Use AWS Key Management Service.
-    #[clap(long, help_heading = "Wallet options - AWS KMS")]
+    ///
+    /// The key is read from `AWS_KMS_KEY_ID`, or the `[wallets.aws_kms]` config.
+    #[clap(long, help_heading = "Wallet options - remote")]
     pub aws: bool,
+
+    /// Use the key version of Google Cloud KMS with the given resource name, or alias of the
+    /// `[wallets.gcp_kms]` config.
+    #[clap(
+        long,
+        help_heading = "Wallet options - remote",
+        value_name = "KEY_NAME",
+        env = "GCP_KMS_KEY_NAME"
+    )]
+    pub gcp_kms: Option<String>,
 }
 
 impl From<RawWallet> for Wallet {
@@ -243,16 +260,18 @@ Make sure it's connected and unlocked, with no other conflicting desktop wallet
 
             Ok(WalletSigner::Trezor(trezor))
         } else if self.aws {
-            let client =
-                AwsClient::new_with(AwsChainProvider::default(), AwsHttpClient::new().unwrap());
-
-            let kms = KmsClient::new_with_client(client, AwsRegion::default());
-
-            let key_id = std::env::var("AWS_KMS_KEY_ID")?;
-
-            let aws_signer = AwsSigner::new(kms, key_id, chain_id).await?;
+            let config = Config::load().wallets;
+            let kms = aws::aws_kms_client(&config)?;
+            let key_id = aws::aws_kms_key_id(&config)?;
+            let aws_signer = aws::aws_signer(kms, &key_id, chain_id).await?;
 
             Ok(WalletSigner::Aws(aws_signer))
+        } else if let Some(key_name) = &self.gcp_kms {
+            let key_name = Config::load().wallets.gcp_kms_key(key_name);
+            let kms = Arc::new(GcpKmsClient::from_env()?);
+            let gcp_signer = GcpKmsSigner::new(kms, key_name, chain_id).await?;
+
+            Ok(WalletSigner::Gcp(gcp_signer))
         } else {
             trace!("finding local key");
 
@@ -264,7 +283,7 @@ Make sure it's connected and unlocked, with no other conflicting desktop wallet
 Error accessing local wallet. Did you set a private key, mnemonic or keystore?
 Run `cast send --help` or `forge create --help` and use the corresponding CLI
 flag to set your key via:
---private-key, --mnemonic-path, --aws, --interactive, --trezor or --ledger.
+--private-key, --mnemonic-path, --aws, --gcp-kms, --interactive, --trezor or --ledger.
 Alternatively, if you're using a local node with unlocked accounts,
 use the --unlocked flag and either set the `ETH_FROM` environment variable to the address
 of the unlocked account you want to use, or provide the --from flag with the address directly."
@@ -423,6 +442,8 @@ pub enum WalletSignerError {
     Trezor(#[from] TrezorError),
     #[error(transparent)]
     Aws(#[from] AwsSignerError),
+    #[error(transparent)]
+    Gcp(#[from] GcpKmsError),
 }
 
 #[derive(Debug)]
@@ -431,6 +452,33 @@ pub enum WalletSigner {
     Ledger(Ledger),
     Trezor(Trezor),
     Aws(AwsSigner),
+    Gcp(GcpKmsSigner),
+    Hardware(HardwareAccount),
+}
+
+impl WalletSigner {
+    /// Returns a description of the device this signer has to be confirmed on, if any.
+    pub fn device_description(&self) -> Option<String> {
+        match self {
+            Self::Ledger(_) => Some("Ledger".to_string()),
+            Self::Trezor(_) => Some("Trezor".to_string()),
+            Self::Hardware(account) => Some(format!(
+                "{} (derivation path {})",
+                account.derivation().device(),
+                account.derivation()
+            )),
+            Self::Local(_) | Self::Aws(_) | Self::Gcp(_) => None,
+        }
+    }
+
+    /// Signs the transaction and returns it RLP encoded, as sent with `eth_sendRawTransaction`.
+    ///
+    /// Signs directly instead of going through `fill_transaction`, so that no
+    /// `eth_createAccessList` request is made.
+    pub async fn sign_raw_transaction(&self, tx: &TypedTransaction) -> Result<Bytes> {
+        let signature = self.sign_transaction(tx).await.wrap_err("Failed to sign transaction")?;
+        Ok(tx.rlp_signed(&signature))
+    }
 }
 
 impl From<LocalWallet> for WalletSigner {
@@ -457,6 +505,18 @@ impl From<AwsSigner> for WalletSigner {
     }
 }
 
+impl From<GcpKmsSigner> for WalletSigner {
+    fn from(wallet: GcpKmsSigner) -> Self {
+        Self::Gcp(wallet)
+    }
+}
+
+impl From<HardwareAccount> for WalletSigner {
+    fn from(account: HardwareAccount) -> Self {
+        Self::Hardware(account)
+    }
+}
+
 macro_rules! delegate {
     ($s:ident, $inner:ident => $e:expr) => {
         match $s {
@@ -464,6 +524,8 @@ macro_rules! delegate {
             Self::Ledger($inner) => $e,
             Self::Trezor($inner) => $e,
             Self::Aws($inner) => $e,
+            Self::Gcp($inner) => $e,
+            Self::Hardware($inner) => $e,
         }
     };
 }
@@ -504,6 +566,8 @@ impl Signer for WalletSigner {
             Self::Ledger(inner) => Self::Ledger(inner.with_chain_id(chain_id)),
             Self::Trezor(inner) => Self::Trezor(inner.with_chain_id(chain_id)),
             Self::Aws(inner) => Self::Aws(inner.with_chain_id(chain_id)),
+            Self::Gcp(inner) => Self::Gcp(inner.with_chain_id(chain_id)),
+            Self::Hardware(inner) => Self::Hardware(inner.with_chain_id(chain_id)),
         }
     }
 }
@@ -553,6 +617,7 @@ pub struct KeystoreFile {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ethers_core::types::{Eip1559TransactionRequest, TransactionRequest};
 
     #[test]
     fn find_keystore() {
@@ -588,6 +653,7 @@ mod tests {
             ledger: false,
             trezor: false,
             aws: false,
+            gcp_kms: None,
         };
         match wallet.private_key() {
             Ok(_) => {
@@ -602,6 +668,59 @@ mod tests {
         }
     }
 
+    async fn sign_and_decode(tx: TypedTransaction) -> (TypedTransaction, Signature, Bytes) {
+        let wallet: LocalWallet =
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let signer = WalletSigner::from(wallet).with_chain_id(31337u64);
+        let raw = signer.sign_raw_transaction(&tx).await.unwrap();
+        let (decoded, signature) =
+            TypedTransaction::decode_signed(&ethers_core::utils::rlp::Rlp::new(&raw)).unwrap();
+        assert_eq!(signature.recover(decoded.sighash()).unwrap(), signer.address());
+        (decoded, signature, raw)
+    }
+
+    #[tokio::test]
+    async fn signs_legacy_transaction() {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(ethers_core::types::Address::repeat_byte(1))
+            .value(1)
+            .nonce(3)
+            .gas(21000)
+            .gas_price(1_000_000_000)
+            .chain_id(31337u64)
+            .into();
+        let (decoded, signature, _) = sign_and_decode(tx.clone()).await;
+
+        assert!(matches!(decoded, TypedTransaction::Legacy(_)));
+        assert_eq!(decoded.nonce(), tx.nonce());
+        assert_eq!(decoded.gas_price(), tx.gas_price());
+        // EIP-155 replay protection
+        assert!(signature.v == 31337 * 2 + 35 || signature.v == 31337 * 2 + 36);
+    }
+
+    #[tokio::test]
+    async fn signs_eip1559_transaction() {
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(ethers_core::types::Address::repeat_byte(1))
+            .value(1)
+            .nonce(3)
+            .gas(21000)
+            .max_fee_per_gas(2_000_000_000)
+            .max_priority_fee_per_gas(1_000_000_000)
+            .chain_id(31337u64)
+            .into();
+        let (decoded, signature, raw) = sign_and_decode(tx.clone()).await;
+
+        assert_eq!(raw[0], 2);
+        let TypedTransaction::Eip1559(inner) = decoded else {
+            panic!("not an EIP-1559 transaction")
+        };
+        assert_eq!(inner.chain_id, Some(31337u64.into()));
+        assert_eq!(inner.max_fee_per_gas, Some(2_000_000_000u64.into()));
+        assert_eq!(inner.max_priority_fee_per_gas, Some(1_000_000_000u64.into()));
+        assert!(signature.v <= 1);
+    }
+
     #[test]
     fn gets_password_from_file() {
         let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../cast/tests/fixtures/keystore/password");
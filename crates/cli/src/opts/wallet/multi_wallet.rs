@@ -1,4 +1,6 @@
-use super::{WalletSigner, WalletTrait};
+use super::{
+    aws, GcpKmsClient, GcpKmsSigner, HardwareAccount, HardwareDerivation, WalletSigner, WalletTrait,
+};
 use alloy_primitives::Address;
 use clap::Parser;
 use ethers_providers::Middleware;
@@ -9,11 +11,6 @@ use eyre::{Context, ContextCompat, Result};
 use foundry_common::{provider::ethers::RetryProvider, types::ToAlloy};
 use foundry_config::Config;
 use itertools::izip;
-use rusoto_core::{
-    credential::ChainProvider as AwsChainProvider, region::Region as AwsRegion,
-    request::HttpClient as AwsHttpClient, Client as AwsClient,
-};
-use rusoto_kms::KmsClient;
 use serde::Serialize;
 use std::{
     collections::{HashMap, HashSet},
@@ -119,7 +116,8 @@ pub struct MultiWallet {
 
     /// The wallet derivation path.
     ///
-    /// Works with both --mnemonic-path and hardware wallets.
+    /// Works with both --mnemonic-path and hardware wallets. Multiple paths can be passed with
+    /// --ledger or --trezor to use several accounts of the same device.
     #[clap(
         long = "mnemonic-derivation-paths",
         alias = "hd-paths",
@@ -193,8 +191,23 @@ pub struct MultiWallet {
     pub trezor: bool,
 
     /// Use AWS Key Management Service.
+    ///
+    /// The keys are read from `AWS_KMS_KEY_IDS`, `AWS_KMS_KEY_ID`, or the `[wallets.aws_kms]`
+    /// config.
     #[clap(long, help_heading = "Wallet options - remote")]
     pub aws: bool,
+
+    /// Use the key versions of Google Cloud KMS with the given resource names, or aliases of the
+    /// `[wallets.gcp_kms]` config.
+    #[clap(
+        long,
+        help_heading = "Wallet options - remote",
+        num_args(1..),
+        value_name = "KEY_NAMES",
+        env = "GCP_KMS_KEY_NAMES",
+        value_delimiter = ','
+    )]
+    pub gcp_kms: Option<Vec<String>>,
 }
 
 impl WalletTrait for MultiWallet {
@@ -228,6 +241,7 @@ impl MultiWallet {
                 self.mnemonics()?,
                 self.keystores()?,
                 self.aws_signers(chain).await?,
+                self.gcp_signers(chain).await?,
                 (!script_wallets.is_empty()).then(|| script_wallets.to_vec())
             ],
             for wallet in wallets.into_iter() {
@@ -264,6 +278,51 @@ impl MultiWallet {
         )
     }
 
+    /// Checks that every address in `senders` has a matching signer, so that a missing one is
+    /// reported before anything is broadcasted.
+    ///
+    /// Only signers which can be resolved without user interaction are considered (hardware
+    /// wallets, raw private keys and script wallets). If any other kind of signer is configured,
+    /// senders which are left unmatched are checked later in [`MultiWallet::find_all`] instead.
+    pub async fn ensure_signers(
+        &self,
+        mut senders: HashSet<Address>,
+        script_wallets: &[LocalWallet],
+    ) -> Result<()> {
+        // The chain id doesn't affect the derived addresses.
+        let chain = 1;
+
+        get_wallets!(
+            wallets,
+            [
+                self.trezors(chain).await?,
+                self.ledgers(chain).await?,
+                self.private_keys()?,
+                (!script_wallets.is_empty()).then(|| script_wallets.to_vec())
+            ],
+            for wallet in wallets.into_iter() {
+                senders.remove(&wallet.address().to_alloy());
+            }
+        );
+
+        let has_interactive_signers = self.interactives != 0 ||
+            self.mnemonics.is_some() ||
+            self.keystore_paths.is_some() ||
+            self.keystore_account_names.is_some() ||
+            self.aws ||
+            self.gcp_kms.is_some();
+        if senders.is_empty() || has_interactive_signers {
+            return Ok(());
+        }
+
+        let mut senders = senders.into_iter().collect::<Vec<_>>();
+        senders.sort();
+        eyre::bail!(
+            "No signer configured for broadcast sender(s): {}. Add the private key, hardware wallet derivation path (--hd-paths) or keystore for each of them.",
+            senders.iter().map(|sender| sender.to_string()).collect::<Vec<_>>().join(", ")
+        )
+    }
+
     pub fn interactives(&self) -> Result<Option<Vec<LocalWallet>>> {
         if self.interactives != 0 {
             let mut wallets = vec![];
@@ -357,46 +416,111 @@ impl MultiWallet {
         Ok(None)
     }
 
-    pub async fn ledgers(&self, chain_id: u64) -> Result<Option<Vec<Ledger>>> {
+    /// Returns the Ledger signers.
+    ///
+    /// If more than one derivation path or index is passed, each account only connects to the
+    /// device when signing, since the device can't be opened more than once at the same time.
+    pub async fn ledgers(&self, chain_id: u64) -> Result<Option<Vec<WalletSigner>>> {
         if self.ledger {
-            let mut args = self.clone();
+            if let Some(derivations) = self.hw_derivations(
+                |path| HardwareDerivation::Ledger(LedgerHDPath::Other(path.to_string())),
+                |index| HardwareDerivation::Ledger(LedgerHDPath::LedgerLive(index)),
+            ) {
+                return self.hardware_accounts(derivations, chain_id).await.map(Some);
+            }
 
-            if let Some(paths) = &args.hd_paths {
-                if paths.len() > 1 {
-                    eyre::bail!("Ledger only supports one signer.");
-                }
+            let mut args = self.clone();
+            if args.hd_paths.is_some() {
                 args.mnemonic_indexes = None;
             }
 
             create_hw_wallets!(args, chain_id, get_from_ledger, wallets);
-            return Ok(Some(wallets));
+            return Ok(Some(wallets.into_iter().map(Into::into).collect()));
         }
         Ok(None)
     }
 
-    pub async fn trezors(&self, chain_id: u64) -> Result<Option<Vec<Trezor>>> {
+    /// Returns the Trezor signers.
+    ///
+    /// See [`MultiWallet::ledgers`] for how multiple derivation paths are handled.
+    pub async fn trezors(&self, chain_id: u64) -> Result<Option<Vec<WalletSigner>>> {
         if self.trezor {
+            if let Some(derivations) = self.hw_derivations(
+                |path| HardwareDerivation::Trezor(TrezorHDPath::Other(path.to_string())),
+                |index| HardwareDerivation::Trezor(TrezorHDPath::TrezorLive(index)),
+            ) {
+                return self.hardware_accounts(derivations, chain_id).await.map(Some);
+            }
+
             create_hw_wallets!(self, chain_id, get_from_trezor, wallets);
-            return Ok(Some(wallets));
+            return Ok(Some(wallets.into_iter().map(Into::into).collect()));
         }
         Ok(None)
     }
 
+    /// Returns the hardware wallet derivations, if more than one account of the device is
+    /// requested through `--hd-paths` or `--mnemonic-indexes`.
+    fn hw_derivations(
+        &self,
+        from_path: impl Fn(&str) -> HardwareDerivation,
+        from_index: impl Fn(usize) -> HardwareDerivation,
+    ) -> Option<Vec<HardwareDerivation>> {
+        let derivations: Vec<_> = if let Some(hd_paths) = &self.hd_paths {
+            hd_paths.iter().map(|path| from_path(path)).collect()
+        } else {
+            self.mnemonic_indexes
+                .iter()
+                .flatten()
+                .map(|index| from_index(*index as usize))
+                .collect()
+        };
+        (derivations.len() > 1).then_some(derivations)
+    }
+
+    /// Resolves the accounts at the given derivations, connecting to the device once per account.
+    async fn hardware_accounts(
+        &self,
+        derivations: Vec<HardwareDerivation>,
+        chain_id: u64,
+    ) -> Result<Vec<WalletSigner>> {
+        let mut accounts = Vec::with_capacity(derivations.len());
+        for derivation in derivations {
+            let device = derivation.device();
+            let path = derivation.to_string();
+            let account = HardwareAccount::new(derivation, chain_id).await.wrap_err_with(|| {
+                format!("Could not connect to {device} device to derive account at `{path}`.")
+            })?;
+            accounts.push(account.into());
+        }
+        Ok(accounts)
+    }
+
     pub async fn aws_signers(&self, chain_id: u64) -> Result<Option<Vec<AwsSigner>>> {
         if self.aws {
             let mut wallets = vec![];
-            let client =
-                AwsClient::new_with(AwsChainProvider::default(), AwsHttpClient::new().unwrap());
+            let config = Config::load().wallets;
+            let kms = aws::aws_kms_client(&config)?;
 
-            let kms = KmsClient::new_with_client(client, AwsRegion::default());
+            for key in aws::aws_kms_key_ids(&config)? {
+                let aws_signer = aws::aws_signer(kms.clone(), &key, chain_id).await?;
+                wallets.push(aws_signer)
+            }
 
-            let env_key_ids = std::env::var("AWS_KMS_KEY_IDS");
-            let key_ids =
-                if env_key_ids.is_ok() { env_key_ids? } else { std::env::var("AWS_KMS_KEY_ID")? };
+            return Ok(Some(wallets));
+        }
+        Ok(None)
+    }
 
-            for key in key_ids.split(',') {
-                let aws_signer = AwsSigner::new(kms.clone(), key, chain_id).await?;
-                wallets.push(aws_signer)
+    pub async fn gcp_signers(&self, chain_id: u64) -> Result<Option<Vec<GcpKmsSigner>>> {
+        if let Some(key_names) = &self.gcp_kms {
+            let mut wallets = vec![];
+            let config = Config::load().wallets;
+            let kms = Arc::new(GcpKmsClient::from_env()?);
+
+            for key_name in key_names {
+                let key_name = config.gcp_kms_key(key_name);
+                let gcp_signer = GcpKmsSigner::new(kms.clone(), key_name, chain_id).await?;
+                wallets.push(gcp_signer)
             }
 
             return Ok(Some(wallets));
@@ -481,6 +605,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_multiple_hardware_wallet_paths() {
+        let args: MultiWallet = MultiWallet::parse_from([
+            "foundry-cli",
+            "--ledger",
+            "--hd-paths",
+            "m/44'/60'/0'/0/0",
+            "--hd-paths",
+            "m/44'/60'/0'/0/1",
+        ]);
+        let derivations = args
+            .hw_derivations(
+                |path| HardwareDerivation::Ledger(LedgerHDPath::Other(path.to_string())),
+                |index| HardwareDerivation::Ledger(LedgerHDPath::LedgerLive(index)),
+            )
+            .expect("multiple derivations");
+        let paths = derivations.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["m/44'/60'/0'/0/0", "m/44'/60'/0'/0/1"]);
+
+        // A single account keeps connecting to the device directly.
+        let args: MultiWallet = MultiWallet::parse_from(["foundry-cli", "--trezor"]);
+        assert!(args
+            .hw_derivations(
+                |path| HardwareDerivation::Trezor(TrezorHDPath::Other(path.to_string())),
+                |index| HardwareDerivation::Trezor(TrezorHDPath::TrezorLive(index)),
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn parses_gcp_kms_key_names() {
+        let args: MultiWallet =
+            MultiWallet::parse_from(["foundry-cli", "--gcp-kms", "deployer,projects/p/keys/1"]);
+        assert_eq!(
+            args.gcp_kms,
+            Some(vec!["deployer".to_string(), "projects/p/keys/1".to_string()])
+        );
+    }
+
     // https://github.com/foundry-rs/foundry/issues/5179
     #[test]
     fn should_not_require_the_mnemonics_flag_with_mnemonic_indexes() {
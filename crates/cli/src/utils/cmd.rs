@@ -1,7 +1,8 @@
-use alloy_json_abi::JsonAbi;
+use alloy_dyn_abi::{DynSolValue, ResolveSolType};
+use alloy_json_abi::{Constructor, JsonAbi};
 use alloy_primitives::Address;
 use eyre::{Result, WrapErr};
-use foundry_common::{cli_warn, fs, TestFunctionExt};
+use foundry_common::{abi::coerce_json_args, cli_warn, fs, ContractsByArtifact, TestFunctionExt};
 use foundry_compilers::{
     artifacts::{CompactBytecode, CompactDeployedBytecode},
     cache::{CacheEntry, SolFilesCache},
@@ -16,11 +17,16 @@ use foundry_evm::{
     executors::{DeployResult, EvmError, ExecutionErr, RawCallResult},
     opts::EvmOpts,
     traces::{
-        identifier::{EtherscanIdentifier, SignaturesIdentifier},
-        render_trace_arena, CallTraceDecoder, CallTraceDecoderBuilder, TraceKind, Traces,
+        identifier::{EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier},
+        render_trace_arena_with_options, CallTraceDecoder, CallTraceDecoderBuilder, TraceKind,
+        TraceRenderOptions, Traces,
     },
 };
-use std::{fmt::Write, path::PathBuf, str::FromStr};
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 use yansi::Paint;
 
 /// Given a `Project`'s output, removes the matching ABI, Bytecode and
@@ -315,6 +321,35 @@ pub fn read_constructor_args_file(constructor_args_path: PathBuf) -> Result<Vec<
     Ok(args)
 }
 
+/// Reads the constructor arguments from the given file and coerces them to the input types of the
+/// `constructor`.
+///
+/// JSON files contain either an array of values or an object keyed by parameter name, see
+/// [`coerce_json_args`]. All other files contain the whitespace separated arguments.
+pub fn read_constructor_args(path: &Path, constructor: &Constructor) -> Result<Vec<DynSolValue>> {
+    if path.extension() != Some(std::ffi::OsStr::new("json")) {
+        return read_constructor_args_file(path.to_path_buf())?
+            .iter()
+            .zip(&constructor.inputs)
+            .map(|(arg, input)| {
+                let ty = input.resolve().wrap_err_with(|| {
+                    format!("Could not resolve constructor arg: input={input}")
+                })?;
+                ty.coerce_str(arg).wrap_err_with(|| format!("Could not parse `{arg}` as {input}"))
+            })
+            .collect()
+    }
+
+    if !path.exists() {
+        eyre::bail!("Constructor args file \"{}\" not found", path.display());
+    }
+    let args: serde_json::Value = read_json_file(path).wrap_err_with(|| {
+        format!("Constructor args file \"{}\" must contain valid json", path.display())
+    })?;
+    coerce_json_args(&constructor.inputs, &args)
+        .wrap_err_with(|| format!("Invalid constructor args in \"{}\"", path.display()))
+}
+
 /// A slimmed down return from the executor used for returning minimal trace + gas metering info
 pub struct TraceResult {
     pub success: bool,
@@ -369,39 +404,51 @@ impl TryFrom<EvmError> for TraceResult {
 }
 
 /// labels the traces, conditionally prints them or opens the debugger
+///
+/// The `known_contracts`, e.g. the artifacts of the local project, are used to identify the
+/// contracts and decode their calls, events and errors before falling back to Etherscan.
 pub async fn handle_traces(
     mut result: TraceResult,
     config: &Config,
     chain: Option<Chain>,
     labels: Vec<String>,
     debug: bool,
+    known_contracts: Option<&ContractsByArtifact>,
+    render_options: &TraceRenderOptions,
 ) -> Result<()> {
     let mut etherscan_identifier = EtherscanIdentifier::new(config, chain)?;
+    let mut local_identifier = known_contracts.map(LocalTraceIdentifier::new);
 
+    // labels are either `<address>:<label>` or `<address>=<label>`
     let labeled_addresses = labels.iter().filter_map(|label_str| {
-        let mut iter = label_str.split(':');
-
-        if let Some(addr) = iter.next() {
-            if let (Ok(address), Some(label)) = (Address::from_str(addr), iter.next()) {
-                return Some((address, label.to_string()));
-            }
-        }
-        None
+        let (addr, label) = label_str.split_once(|c| c == ':' || c == '=')?;
+        Some((Address::from_str(addr).ok()?, label.to_string()))
     });
 
     let labeled_addresses_in_config = config.labels.clone().into_iter();
 
     let concatenated_addresses = labeled_addresses.chain(labeled_addresses_in_config);
 
-    let mut decoder = CallTraceDecoderBuilder::new()
+    // the selectors of the known contracts take precedence over the cached and remote ones, like
+    // in `cast 4byte-decode`
+    let signatures = SignaturesIdentifier::new(Config::foundry_cache_dir(), config.offline)?;
+    if let Some(known_contracts) = known_contracts {
+        let mut signatures = signatures.write().await;
+        known_contracts.values().for_each(|(abi, _)| signatures.add_local_abi(abi));
+    }
+
+    let mut builder = CallTraceDecoderBuilder::new()
         .with_labels(concatenated_addresses)
-        .with_signature_identifier(SignaturesIdentifier::new(
-            Config::foundry_cache_dir(),
-            config.offline,
-        )?)
-        .build();
+        .with_signature_identifier(signatures);
+    if let Some(local_identifier) = &local_identifier {
+        builder = builder.with_local_identifier_abis(local_identifier);
+    }
+    let mut decoder = builder.build();
 
     for (_, trace) in &mut result.traces {
+        if let Some(local_identifier) = &mut local_identifier {
+            decoder.identify(trace, local_identifier);
+        }
         decoder.identify(trace, &mut etherscan_identifier);
     }
 
@@ -414,20 +461,24 @@ pub async fn handle_traces(
             .build();
         debugger.try_run()?;
     } else {
-        print_traces(&mut result, &decoder).await?;
+        print_traces(&mut result, &decoder, render_options).await?;
     }
 
     Ok(())
 }
 
-pub async fn print_traces(result: &mut TraceResult, decoder: &CallTraceDecoder) -> Result<()> {
+pub async fn print_traces(
+    result: &mut TraceResult,
+    decoder: &CallTraceDecoder,
+    render_options: &TraceRenderOptions,
+) -> Result<()> {
     if result.traces.is_empty() {
         panic!("No traces found")
     }
 
     println!("Traces:");
     for (_, arena) in &result.traces {
-        println!("{}", render_trace_arena(arena, decoder).await?);
+        println!("{}", render_trace_arena_with_options(arena, decoder, render_options).await?);
     }
     println!();
 
@@ -76,9 +76,12 @@ pub fn subscriber() {
 }
 
 pub fn abi_to_solidity(abi: &JsonAbi, name: &str) -> Result<String> {
-    let s = abi.to_sol(name);
-    let s = forge_fmt::format(&s)?;
-    Ok(s)
+    format_solidity(&abi.to_sol(name))
+}
+
+/// Formats the Solidity source with the default formatter config.
+pub fn format_solidity(source: &str) -> Result<String> {
+    Ok(forge_fmt::format(source)?)
 }
 
 /// Returns a [RetryProvider](foundry_common::RetryProvider) instantiated using [Config]'s RPC URL
@@ -139,13 +142,13 @@ pub fn parse_ether_value(value: &str) -> Result<U256> {
     })
 }
 
-/// Parses a `Duration` from a &str
+/// Parses a `Duration` from a &str, in seconds if it has no `ms` suffix
 pub fn parse_delay(delay: &str) -> Result<Duration> {
     let delay = if delay.ends_with("ms") {
         let d: u64 = delay.trim_end_matches("ms").parse()?;
         Duration::from_millis(d)
     } else {
-        let d: f64 = delay.parse()?;
+        let d: f64 = delay.strip_suffix('s').unwrap_or(delay).parse()?;
         let delay = (d * 1000.0).round();
         if delay.is_infinite() || delay.is_nan() || delay.is_sign_negative() {
             eyre::bail!("delay must be finite and non-negative");
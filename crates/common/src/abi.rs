@@ -1,11 +1,12 @@
 //! ABI related helper functions.
 
-use alloy_dyn_abi::{DynSolType, DynSolValue, FunctionExt, JsonAbiExt};
-use alloy_json_abi::{Event, Function};
+use alloy_dyn_abi::{DynSolType, DynSolValue, FunctionExt, JsonAbiExt, ResolveSolType};
+use alloy_json_abi::{Event, Function, Param};
 use alloy_primitives::{hex, Address, LogData};
 use eyre::{Context, ContextCompat, Result};
 use foundry_block_explorers::{contract::ContractMetadata, errors::EtherscanError, Client};
 use foundry_config::Chain;
+use serde_json::Value;
 use std::{future::Future, pin::Pin};
 
 /// Given a function and a vector of string arguments, it proceeds to convert the args to alloy
@@ -51,6 +52,33 @@ pub fn abi_decode_calldata(
     Ok(res)
 }
 
+/// Decodes the calldata with each of the function signatures and returns the signatures that
+/// decode it with the decoded arguments, best matches first.
+///
+/// Signatures whose encoding of the decoded arguments is exactly the calldata rank before the ones
+/// only decoding it partially, e.g. ignoring trailing bytes.
+pub fn rank_calldata_signatures(
+    sigs: &[String],
+    calldata: &str,
+) -> Result<Vec<(String, Vec<DynSolValue>)>> {
+    let calldata = hex::decode(calldata)?;
+    if calldata.len() < 4 {
+        eyre::bail!("calldata too short: expected at least 4 bytes, got {}", calldata.len())
+    }
+    let mut decoded = sigs
+        .iter()
+        .filter_map(|sig| {
+            let func = get_func(sig).ok()?;
+            let args = func.abi_decode_input(&calldata[4..], false).ok()?;
+            let exact = func.abi_encode_input(&args).is_ok_and(|encoded| encoded == calldata);
+            Some((!exact, sig.clone(), args))
+        })
+        .collect::<Vec<_>>();
+    // stable, so equally good matches keep their order
+    decoded.sort_by_key(|(inexact, ..)| *inexact);
+    Ok(decoded.into_iter().map(|(_, sig, args)| (sig, args)).collect())
+}
+
 /// Helper trait for converting types to Functions. Helpful for allowing the `call`
 /// function on the EVM to be generic over `String`, `&str` and `Function`.
 pub trait IntoFunction {
@@ -182,6 +210,144 @@ fn coerce_value(ty: &str, arg: &str) -> Result<DynSolValue> {
     Ok(DynSolType::coerce_str(&ty, arg)?)
 }
 
+/// Coerces the JSON `value` to [DynSolValue]s of the types of the given `params`.
+///
+/// The value is either an array with an entry for every param, or an object keyed by param name.
+/// The same applies to nested tuples. Numbers can be passed as JSON numbers or as strings, all
+/// other values as the strings accepted by [DynSolType::coerce_str]. Mixed case addresses must be
+/// checksummed.
+///
+/// Errors point at the offending value, e.g. `$.owners[1]`.
+pub fn coerce_json_args(params: &[Param], value: &Value) -> Result<Vec<DynSolValue>> {
+    coerce_json_params(params, value, "$")
+}
+
+fn coerce_json_params(params: &[Param], value: &Value, path: &str) -> Result<Vec<DynSolValue>> {
+    let values = match value {
+        Value::Array(values) => {
+            eyre::ensure!(
+                values.len() == params.len(),
+                "{path}: expected {} values, found {}",
+                params.len(),
+                values.len()
+            );
+            values.iter().enumerate().map(|(i, value)| (format!("{path}[{i}]"), value)).collect()
+        }
+        Value::Object(values) => {
+            if let Some(key) = values.keys().find(|key| !params.iter().any(|p| &p.name == *key)) {
+                eyre::bail!("{path}: unknown parameter `{key}`")
+            }
+            params
+                .iter()
+                .enumerate()
+                .map(|(i, param)| {
+                    eyre::ensure!(
+                        !param.name.is_empty(),
+                        "{path}: parameter {i} is unnamed, pass the values as an array instead"
+                    );
+                    let value = values
+                        .get(&param.name)
+                        .wrap_err_with(|| format!("{path}: missing parameter `{}`", param.name))?;
+                    Ok((format!("{path}.{}", param.name), value))
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+        _ => eyre::bail!("{path}: expected an array or an object, found {}", json_kind(value)),
+    };
+
+    std::iter::zip(params, values)
+        .map(|(param, (path, value))| {
+            let ty = param
+                .resolve()
+                .wrap_err_with(|| format!("{path}: could not resolve type `{}`", param.ty))?;
+            coerce_json_value(&ty, &param.components, value, &path)
+        })
+        .collect()
+}
+
+/// Coerces a single JSON value, `components` are the fields of the (array's) tuple type.
+fn coerce_json_value(
+    ty: &DynSolType,
+    components: &[Param],
+    value: &Value,
+    path: &str,
+) -> Result<DynSolValue> {
+    match (ty, value) {
+        (DynSolType::Array(ty), Value::Array(values)) => {
+            Ok(DynSolValue::Array(coerce_json_array(ty, components, values, path)?))
+        }
+        (DynSolType::FixedArray(ty, len), Value::Array(values)) => {
+            eyre::ensure!(
+                values.len() == *len,
+                "{path}: expected {len} values, found {}",
+                values.len()
+            );
+            Ok(DynSolValue::FixedArray(coerce_json_array(ty, components, values, path)?))
+        }
+        (DynSolType::Tuple(_), Value::Array(_) | Value::Object(_)) => {
+            Ok(DynSolValue::Tuple(coerce_json_params(components, value, path)?))
+        }
+        (DynSolType::CustomStruct { name, prop_names, .. }, Value::Array(_) | Value::Object(_)) => {
+            Ok(DynSolValue::CustomStruct {
+                name: name.clone(),
+                prop_names: prop_names.clone(),
+                tuple: coerce_json_params(components, value, path)?,
+            })
+        }
+        (DynSolType::Bool, Value::Bool(value)) => Ok(DynSolValue::Bool(*value)),
+        (DynSolType::Int(_) | DynSolType::Uint(_), Value::Number(number)) => {
+            eyre::ensure!(
+                number.is_i64() || number.is_u64(),
+                "{path}: {number} is not an integer or too large, pass it as a string instead"
+            );
+            coerce_json_str(ty, &number.to_string(), path)
+        }
+        (_, Value::String(value)) => coerce_json_str(ty, value, path),
+        _ => eyre::bail!("{path}: expected {}, found {}", ty.sol_type_name(), json_kind(value)),
+    }
+}
+
+fn coerce_json_array(
+    ty: &DynSolType,
+    components: &[Param],
+    values: &[Value],
+    path: &str,
+) -> Result<Vec<DynSolValue>> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| coerce_json_value(ty, components, value, &format!("{path}[{i}]")))
+        .collect()
+}
+
+fn coerce_json_str(ty: &DynSolType, value: &str, path: &str) -> Result<DynSolValue> {
+    let coerced = ty
+        .coerce_str(value)
+        .wrap_err_with(|| format!("{path}: could not parse `{value}` as {}", ty.sol_type_name()))?;
+    if let DynSolValue::Address(address) = coerced {
+        let hex = value.trim_start_matches("0x");
+        let is_mixed_case = hex.chars().any(|c| c.is_ascii_lowercase()) &&
+            hex.chars().any(|c| c.is_ascii_uppercase());
+        let checksummed = address.to_checksum(None);
+        eyre::ensure!(
+            !is_mixed_case || checksummed[2..] == *hex,
+            "{path}: invalid address checksum `{value}`, expected `{checksummed}`"
+        );
+    }
+    Ok(coerced)
+}
+
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -209,6 +375,24 @@ mod tests {
         assert_eq!(func.outputs[0].ty, "bytes4");
     }
 
+    #[test]
+    fn test_rank_calldata_signatures() {
+        let sigs =
+            vec!["collate_propagate_storage(bytes16)".to_string(), "burn(uint256)".to_string()];
+        let calldata = "42966c680000000000000000000000000000000000000000000000000000000000000001";
+        let ranked = rank_calldata_signatures(&sigs, calldata).unwrap();
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(
+            ranked[0],
+            ("burn(uint256)".to_string(), vec![DynSolValue::Uint(U256::from(1), 256)])
+        );
+        assert_eq!(ranked[1].0, "collate_propagate_storage(bytes16)");
+
+        // too short for a uint256
+        let ranked = rank_calldata_signatures(&sigs[1..], "42966c6801").unwrap();
+        assert!(ranked.is_empty());
+    }
+
     #[test]
     fn test_indexed_only_address() {
         let event = get_event("event Ev(address,uint256,address)").unwrap();
@@ -254,4 +438,91 @@ mod tests {
         assert_eq!(parsed.indexed[1], DynSolValue::Uint(U256::from_be_bytes([3; 32]), 256));
         assert_eq!(parsed.indexed[2], DynSolValue::Address(Address::from_word(param2)));
     }
+
+    fn constructor_params() -> Vec<Param> {
+        serde_json::from_str(
+            r#"[
+                { "name": "owners", "type": "address[]", "internalType": "address[]" },
+                {
+                    "name": "config",
+                    "type": "tuple",
+                    "internalType": "struct Config",
+                    "components": [
+                        { "name": "threshold", "type": "uint256", "internalType": "uint256" },
+                        { "name": "label", "type": "string", "internalType": "string" }
+                    ]
+                },
+                { "name": "flags", "type": "bool[2]", "internalType": "bool[2]" }
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    fn encode_json_args(params: &[Param], json: &str) -> Result<Vec<u8>> {
+        let args = coerce_json_args(params, &serde_json::from_str(json).unwrap())?;
+        Ok(DynSolValue::Tuple(args).abi_encode_params())
+    }
+
+    #[test]
+    fn test_coerce_json_args() {
+        let params = constructor_params();
+        let owner = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+        let from_strings =
+            encode_json_args(&params, &format!(r#"["[{owner}]", "(2, gm)", "[true, false]"]"#))
+                .unwrap();
+        let from_array =
+            encode_json_args(&params, &format!(r#"[["{owner}"], [2, "gm"], [true, false]]"#))
+                .unwrap();
+        let from_object = encode_json_args(
+            &params,
+            &format!(
+                r#"{{
+                    "flags": [true, "false"],
+                    "owners": ["{}"],
+                    "config": {{ "label": "gm", "threshold": "0x02" }}
+                }}"#,
+                owner.to_lowercase()
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(from_strings, from_array);
+        assert_eq!(from_strings, from_object);
+    }
+
+    #[test]
+    fn test_coerce_json_args_errors() {
+        let params = constructor_params();
+        let err = |json: &str| encode_json_args(&params, json).unwrap_err().to_string();
+
+        assert_eq!(err(r#"[[], [2, "gm"]]"#), "$: expected 3 values, found 2");
+        assert_eq!(
+            err(r#"[["0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"], [2, "gm"], [true, true]]"#),
+            "$[0][0]: invalid address checksum `0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD`, \
+             expected `0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed`"
+        );
+        assert_eq!(
+            err(
+                r#"{"owners": [], "config": {"threshold": 1.5, "label": ""}, "flags": [true, true]}"#
+            ),
+            "$.config.threshold: 1.5 is not an integer or too large, pass it as a string instead"
+        );
+        assert_eq!(
+            err(r#"{"owners": [], "config": [1, 2], "flags": [true, true]}"#),
+            "$.config[1]: expected string, found a number"
+        );
+        assert_eq!(
+            err(r#"{"owners": [], "config": [1, ""], "flags": [true]}"#),
+            "$.flags: expected 2 values, found 1"
+        );
+        assert_eq!(
+            err(r#"{"owners": [], "config": [1, ""], "flags": [true, true], "x": 1}"#),
+            "$: unknown parameter `x`"
+        );
+        assert_eq!(
+            err(r#"{"owners": [], "flags": [true, true]}"#),
+            "$: missing parameter `config`"
+        );
+    }
 }
@@ -0,0 +1,176 @@
+//! Authorizations of [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702), which delegate the code
+//! of an account to a contract.
+
+use crate::types::{ToAlloy, ToEthers};
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_rlp::{Decodable, Encodable, Header};
+use ethers_core::types::{Signature, SignatureError};
+
+/// The byte prepended to the RLP encoding of an authorization to compute its signature hash.
+pub const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+/// An unsigned authorization to delegate the code of the signer to `address`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Authorization {
+    /// The chain the authorization is valid on, or zero for all chains.
+    pub chain_id: u64,
+    /// The address of the contract the code is delegated to.
+    pub address: Address,
+    /// The nonce of the signer the authorization is valid for.
+    pub nonce: u64,
+}
+
+impl Authorization {
+    /// Returns the hash the signer signs, `keccak256(MAGIC || rlp([chain_id, address, nonce]))`.
+    pub fn signature_hash(&self) -> B256 {
+        let mut out = vec![AUTHORIZATION_MAGIC];
+        self.encode(&mut out);
+        keccak256(out)
+    }
+
+    /// Attaches the signature of the signature hash.
+    pub fn into_signed(self, signature: &Signature) -> SignedAuthorization {
+        // signatures of hashes have a `v` of 27 or 28
+        let y_parity = signature.v.checked_sub(27).unwrap_or(signature.v) as u8;
+        SignedAuthorization {
+            inner: self,
+            y_parity,
+            r: signature.r.to_alloy(),
+            s: signature.s.to_alloy(),
+        }
+    }
+
+    fn fields_len(&self) -> usize {
+        self.chain_id.length() + self.address.length() + self.nonce.length()
+    }
+
+    fn encode_fields(&self, out: &mut dyn alloy_rlp::BufMut) {
+        self.chain_id.encode(out);
+        self.address.encode(out);
+        self.nonce.encode(out);
+    }
+}
+
+impl Encodable for Authorization {
+    fn encode(&self, out: &mut dyn alloy_rlp::BufMut) {
+        Header { list: true, payload_length: self.fields_len() }.encode(out);
+        self.encode_fields(out);
+    }
+}
+
+/// A signed authorization, as included in the authorization list of a set code transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignedAuthorization {
+    /// The authorization.
+    pub inner: Authorization,
+    /// The parity of the `y` coordinate of the signature.
+    pub y_parity: u8,
+    /// The `r` value of the signature.
+    pub r: U256,
+    /// The `s` value of the signature.
+    pub s: U256,
+}
+
+impl SignedAuthorization {
+    /// Recovers the account which signed the authorization, whose code is delegated.
+    pub fn recover_authority(&self) -> Result<Address, SignatureError> {
+        let signature = Signature {
+            r: self.r.to_ethers(),
+            s: self.s.to_ethers(),
+            v: self.y_parity as u64 + 27,
+        };
+        Ok(signature.recover(self.inner.signature_hash().to_ethers())?.to_alloy())
+    }
+
+    /// Returns the RLP encoding `rlp([chain_id, address, nonce, y_parity, r, s])`.
+    pub fn to_rlp(&self) -> Bytes {
+        let mut out = Vec::new();
+        self.encode(&mut out);
+        out.into()
+    }
+
+    /// Decodes the RLP encoding of [SignedAuthorization::to_rlp].
+    pub fn from_rlp(mut data: &[u8]) -> alloy_rlp::Result<Self> {
+        let auth = Self::decode(&mut data)?;
+        if !data.is_empty() {
+            return Err(alloy_rlp::Error::UnexpectedLength)
+        }
+        Ok(auth)
+    }
+
+    fn payload_len(&self) -> usize {
+        self.inner.fields_len() + self.y_parity.length() + self.r.length() + self.s.length()
+    }
+}
+
+impl Encodable for SignedAuthorization {
+    fn encode(&self, out: &mut dyn alloy_rlp::BufMut) {
+        Header { list: true, payload_length: self.payload_len() }.encode(out);
+        self.inner.encode_fields(out);
+        self.y_parity.encode(out);
+        self.r.encode(out);
+        self.s.encode(out);
+    }
+}
+
+impl Decodable for SignedAuthorization {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(alloy_rlp::Error::UnexpectedString)
+        }
+        let started_len = buf.len();
+        let auth = Self {
+            inner: Authorization {
+                chain_id: Decodable::decode(buf)?,
+                address: Decodable::decode(buf)?,
+                nonce: Decodable::decode(buf)?,
+            },
+            y_parity: Decodable::decode(buf)?,
+            r: Decodable::decode(buf)?,
+            s: Decodable::decode(buf)?,
+        };
+        if started_len - buf.len() != header.payload_length {
+            return Err(alloy_rlp::Error::ListLengthMismatch {
+                expected: header.payload_length,
+                got: started_len - buf.len(),
+            })
+        }
+        Ok(auth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use ethers_core::{k256::ecdsa::SigningKey, utils::secret_key_to_address};
+
+    #[test]
+    fn signs_and_recovers_authorizations() {
+        let key = SigningKey::from_slice(&[1; 32]).unwrap();
+        let signer = secret_key_to_address(&key).to_alloy();
+
+        let auth = Authorization {
+            chain_id: 1,
+            address: address!("5FbDB2315678afecb367f032d93F642f64180aa3"),
+            nonce: 7,
+        };
+        let (sig, recid) = key.sign_prehash_recoverable(auth.signature_hash().as_slice()).unwrap();
+        let signature = Signature {
+            r: U256::from_be_slice(&sig.r().to_bytes()).to_ethers(),
+            s: U256::from_be_slice(&sig.s().to_bytes()).to_ethers(),
+            v: recid.to_byte() as u64 + 27,
+        };
+        let signed = auth.into_signed(&signature);
+        assert_eq!(signed.y_parity, recid.to_byte());
+        assert_eq!(signed.recover_authority().unwrap(), signer);
+
+        let decoded = SignedAuthorization::from_rlp(&signed.to_rlp()).unwrap();
+        assert_eq!(decoded, signed);
+
+        let mut tampered = signed;
+        tampered.inner.nonce += 1;
+        assert_ne!(tampered.recover_authority().unwrap(), signer);
+    }
+}
@@ -5,20 +5,23 @@ use comfy_table::{presets::ASCII_MARKDOWN, Attribute, Cell, Color, Table};
 use eyre::Result;
 use foundry_block_explorers::contract::Metadata;
 use foundry_compilers::{
-    artifacts::{BytecodeObject, ContractBytecodeSome},
+    artifacts::{BytecodeObject, ContractBytecodeSome, Source},
+    cache::SolFilesCache,
     remappings::Remapping,
     report::{BasicStdoutReporter, NoReporter, Report},
     Artifact, ArtifactId, FileFilter, Graph, Project, ProjectCompileOutput, ProjectPathsConfig,
     Solc, SolcConfig,
 };
+use foundry_config::Config;
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     convert::Infallible,
     fmt::Display,
     io::IsTerminal,
     path::{Path, PathBuf},
     result,
     str::FromStr,
+    sync::Arc,
 };
 
 /// Builder type to configure how to compile a project.
@@ -36,6 +39,15 @@ pub struct ProjectCompiler {
     /// Whether to also print contract sizes.
     print_sizes: Option<bool>,
 
+    /// Whether to print contract sizes as JSON.
+    sizes_json: Option<bool>,
+
+    /// The minimum number of bytes contracts must stay below the code size limits.
+    size_limit_margin: Option<usize>,
+
+    /// Files to exclude from the size report.
+    size_filter: Option<Box<dyn FileFilter>>,
+
     /// Whether to print anything at all. Overrides other `print` options.
     quiet: Option<bool>,
 
@@ -47,6 +59,11 @@ pub struct ProjectCompiler {
 
     /// Extra files to include, that are not necessarily in the project's source dir.
     files: Vec<PathBuf>,
+
+    /// Projects compiling the sources of compilation overrides, with the filter of their sources.
+    ///
+    /// `None` if the compilation overrides aren't handled by the caller.
+    overrides: Option<Vec<(Project, Box<dyn FileFilter>)>>,
 }
 
 impl Default for ProjectCompiler {
@@ -64,10 +81,14 @@ impl ProjectCompiler {
             verify: None,
             print_names: None,
             print_sizes: None,
+            sizes_json: None,
+            size_limit_margin: None,
+            size_filter: None,
             quiet: Some(crate::shell::verbosity().is_silent()),
             bail: None,
             filter: None,
             files: Vec::new(),
+            overrides: None,
         }
     }
 
@@ -92,6 +113,29 @@ impl ProjectCompiler {
         self
     }
 
+    /// Sets whether to print contract sizes as JSON.
+    ///
+    /// This prints the size report even if [`quiet`](Self::quiet) is set.
+    #[inline]
+    pub fn sizes_json(mut self, yes: bool) -> Self {
+        self.sizes_json = Some(yes);
+        self
+    }
+
+    /// Sets the minimum number of bytes contracts must stay below the code size limits.
+    #[inline]
+    pub fn size_limit_margin(mut self, margin: Option<usize>) -> Self {
+        self.size_limit_margin = margin;
+        self
+    }
+
+    /// Sets the filter for the files to include in the size report.
+    #[inline]
+    pub fn size_filter(mut self, filter: Box<dyn FileFilter>) -> Self {
+        self.size_filter = Some(filter);
+        self
+    }
+
     /// Sets whether to print anything at all. Overrides other `print` options.
     #[inline]
     #[doc(alias = "silent")]
@@ -130,20 +174,48 @@ impl ProjectCompiler {
         self
     }
 
+    /// Sets the projects that compile the sources of compilation overrides in separate solc
+    /// jobs, see [compilation_override_projects].
+    ///
+    /// The sources of the overrides must be excluded from the project by the filter. An empty list
+    /// marks the compilation overrides as handled, e.g. if the project already has the settings of
+    /// the override of the sources it compiles.
+    #[inline]
+    pub fn overrides(mut self, overrides: Vec<(Project, Box<dyn FileFilter>)>) -> Self {
+        self.overrides = Some(overrides);
+        self
+    }
+
     /// Compiles the project.
+    ///
+    /// If there are [overrides](Self::overrides), names and sizes are printed for the contracts of
+    /// all projects, but only the output of this project is returned. If they aren't set, this
+    /// fails if the project has compilation overrides, since all of its sources would be compiled
+    /// with the settings of the profile.
     pub fn compile(mut self, project: &Project) -> Result<ProjectCompileOutput> {
         // Taking is fine since we don't need these in `compile_with`.
         let filter = std::mem::take(&mut self.filter);
         let files = std::mem::take(&mut self.files);
+        let overrides = match self.overrides.take() {
+            Some(overrides) => overrides,
+            None => {
+                ensure_no_compilation_overrides(project)?;
+                Vec::new()
+            }
+        };
         self.compile_with(project, || {
-            if !files.is_empty() {
+            let output = if !files.is_empty() {
                 project.compile_files(files)
             } else if let Some(filter) = filter {
                 project.compile_sparse(filter)
             } else {
                 project.compile()
+            }?;
+            let mut outputs = vec![output];
+            for (project, filter) in overrides {
+                outputs.push(project.compile_sparse(filter)?);
             }
-            .map_err(Into::into)
+            Ok(outputs)
         })
     }
 
@@ -155,12 +227,12 @@ impl ProjectCompiler {
     /// use foundry_common::compile::ProjectCompiler;
     /// let config = foundry_config::Config::load();
     /// let prj = config.project().unwrap();
-    /// ProjectCompiler::new().compile_with(&prj, || Ok(prj.compile()?)).unwrap();
+    /// ProjectCompiler::new().compile_with(&prj, || Ok(vec![prj.compile()?])).unwrap();
     /// ```
     #[instrument(target = "forge::compile", skip_all)]
     fn compile_with<F>(self, project: &Project, f: F) -> Result<ProjectCompileOutput>
     where
-        F: FnOnce() -> Result<ProjectCompileOutput>,
+        F: FnOnce() -> Result<Vec<ProjectCompileOutput>>,
     {
         // TODO: Avoid process::exit
         if !project.paths.has_input_files() {
@@ -182,7 +254,7 @@ impl ProjectCompiler {
             }
         };
 
-        let output = foundry_compilers::report::with_scoped(&reporter, || {
+        let outputs = foundry_compilers::report::with_scoped(&reporter, || {
             tracing::debug!("compiling project");
 
             let timer = std::time::Instant::now();
@@ -196,33 +268,44 @@ impl ProjectCompiler {
         // need to drop the reporter here, so that the spinner terminates
         drop(reporter);
 
-        if bail && output.has_compiler_errors() {
-            eyre::bail!("{output}")
+        if bail {
+            if let Some(output) = outputs.iter().find(|output| output.has_compiler_errors()) {
+                eyre::bail!("{output}")
+            }
         }
 
         if !quiet {
-            if output.is_unchanged() {
+            if outputs.iter().all(|output| output.is_unchanged()) {
                 println!("No files changed, compilation skipped");
             } else {
                 // print the compiler output / warnings
-                println!("{output}");
+                for output in outputs.iter().filter(|output| !output.is_unchanged()) {
+                    println!("{output}");
+                }
             }
-
-            self.handle_output(&output);
         }
 
-        Ok(output)
+        self.handle_output(&outputs, quiet);
+
+        Ok(outputs.into_iter().next().expect("the output of the project"))
     }
 
     /// If configured, this will print sizes or names
-    fn handle_output(&self, output: &ProjectCompileOutput) {
-        let print_names = self.print_names.unwrap_or(false);
-        let print_sizes = self.print_sizes.unwrap_or(false);
+    ///
+    /// The sizes are checked against the limits whenever they are printed or a size limit margin
+    /// is configured.
+    fn handle_output(&self, outputs: &[ProjectCompileOutput], quiet: bool) {
+        let sizes_json = self.sizes_json.unwrap_or(false);
+        let print_names = !quiet && self.print_names.unwrap_or(false);
+        let print_sizes = self.print_sizes.unwrap_or(false) && (!quiet || sizes_json);
+        let check_sizes = print_sizes || (!quiet && self.size_limit_margin.is_some());
 
         // print any sizes or names
         if print_names {
             let mut artifacts: BTreeMap<_, Vec<_>> = BTreeMap::new();
-            for (name, (_, version)) in output.versioned_artifacts() {
+            for (name, (_, version)) in
+                outputs.iter().flat_map(|output| output.versioned_artifacts())
+            {
                 artifacts.entry(version).or_default().push(name);
             }
             for (version, names) in artifacts {
@@ -236,16 +319,24 @@ impl ProjectCompiler {
             }
         }
 
-        if print_sizes {
+        if check_sizes {
             // add extra newline if names were already printed
-            if print_names {
+            if print_names && print_sizes {
                 println!();
             }
 
             let mut size_report = SizeReport { contracts: BTreeMap::new() };
-            let artifacts: BTreeMap<_, _> = output.artifacts().collect();
+            let artifacts: BTreeMap<_, _> = outputs
+                .iter()
+                .flat_map(|output| output.artifact_ids())
+                .filter(|(id, _)| {
+                    self.size_filter.as_ref().map_or(true, |filter| filter.is_match(&id.source))
+                })
+                .map(|(id, artifact)| (id.name, artifact))
+                .collect();
             for (name, artifact) in artifacts {
                 let size = deployed_contract_size(artifact).unwrap_or_default();
+                let init_size = init_code_size(artifact).unwrap_or_default();
 
                 let dev_functions =
                     artifact.abi.as_ref().map(|abi| abi.functions()).into_iter().flatten().filter(
@@ -257,27 +348,62 @@ impl ProjectCompiler {
                     );
 
                 let is_dev_contract = dev_functions.count() > 0;
-                size_report.contracts.insert(name, ContractInfo { size, is_dev_contract });
+                size_report
+                    .contracts
+                    .insert(name, ContractInfo { size, init_size, is_dev_contract });
             }
 
-            println!("{size_report}");
+            if sizes_json {
+                println!("{}", size_report.to_json(self.size_limit_margin));
+            } else if print_sizes {
+                println!("{size_report}");
+            }
 
             // TODO: avoid process::exit
-            // exit with error if any contract exceeds the size limit, excluding test contracts.
-            if size_report.exceeds_size_limit() {
+            // exit with error if any contract exceeds the size limits or violates the configured
+            // margin, excluding test contracts.
+            let offenders = size_report.offenders(self.size_limit_margin.unwrap_or_default());
+            if !offenders.is_empty() {
+                let margin = self.size_limit_margin.unwrap_or_default();
+                if margin > 0 {
+                    eprintln!("Contracts within {margin} bytes of the code size limits:");
+                } else {
+                    eprintln!("Contracts exceeding the code size limits:");
+                }
+                for (name, contract) in offenders {
+                    if contract.exceeds_runtime_limit(margin) {
+                        eprintln!(
+                            "  {name}: runtime size of {} bytes exceeds the limit of {} bytes",
+                            contract.size,
+                            CONTRACT_SIZE_LIMIT.saturating_sub(margin)
+                        );
+                    }
+                    if contract.exceeds_init_limit(margin) {
+                        eprintln!(
+                            "  {name}: initcode size of {} bytes exceeds the limit of {} bytes",
+                            contract.init_size,
+                            CONTRACT_INITCODE_SIZE_LIMIT.saturating_sub(margin)
+                        );
+                    }
+                }
                 std::process::exit(1);
             }
         }
     }
 }
 
-/// Map over artifacts contract sources name -> file_id -> (source, contract)
+/// Map over artifacts contract sources name -> file_id -> (path, source, contract)
 #[derive(Clone, Debug, Default)]
-pub struct ContractSources(pub HashMap<String, HashMap<u32, (String, ContractBytecodeSome)>>);
+pub struct ContractSources(
+    pub HashMap<String, HashMap<u32, (PathBuf, String, ContractBytecodeSome)>>,
+);
 
 // https://eips.ethereum.org/EIPS/eip-170
 const CONTRACT_SIZE_LIMIT: usize = 24576;
 
+// https://eips.ethereum.org/EIPS/eip-3860
+const CONTRACT_INITCODE_SIZE_LIMIT: usize = 49152;
+
 /// Contracts with info about their size
 pub struct SizeReport {
     /// `contract name -> info`
@@ -296,9 +422,60 @@ impl SizeReport {
         max_size
     }
 
-    /// Returns true if any contract exceeds the size limit, excluding test contracts.
+    /// Returns the initcode size of the largest contract, excluding test contracts.
+    pub fn max_init_size(&self) -> usize {
+        self.contracts
+            .values()
+            .filter(|contract| !contract.is_dev_contract)
+            .map(|contract| contract.init_size)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns true if any contract exceeds the size limits, excluding test contracts.
     pub fn exceeds_size_limit(&self) -> bool {
-        self.max_size() > CONTRACT_SIZE_LIMIT
+        self.max_size() > CONTRACT_SIZE_LIMIT || self.max_init_size() > CONTRACT_INITCODE_SIZE_LIMIT
+    }
+
+    /// Returns all contracts, excluding test contracts, whose runtime or initcode size is within
+    /// `margin` bytes of the respective size limit.
+    pub fn offenders(&self, margin: usize) -> Vec<(&String, &ContractInfo)> {
+        self.contracts
+            .iter()
+            .filter(|(_, contract)| {
+                !contract.is_dev_contract &&
+                    (contract.exceeds_runtime_limit(margin) ||
+                        contract.exceeds_init_limit(margin))
+            })
+            .collect()
+    }
+
+    /// Returns the report as JSON object, mapping each contract, excluding test contracts, to its
+    /// sizes and margins.
+    ///
+    /// If a `margin` is configured, every contract also reports whether it violates it.
+    pub fn to_json(&self, margin: Option<usize>) -> serde_json::Value {
+        let contracts = self
+            .contracts
+            .iter()
+            .filter(|(_, contract)| !contract.is_dev_contract && contract.size > 0)
+            .map(|(name, contract)| {
+                let mut value = serde_json::json!({
+                    "runtime_size": contract.size,
+                    "init_size": contract.init_size,
+                    "runtime_margin": CONTRACT_SIZE_LIMIT as isize - contract.size as isize,
+                    "init_margin": CONTRACT_INITCODE_SIZE_LIMIT as isize -
+                        contract.init_size as isize,
+                });
+                if let Some(margin) = margin {
+                    value["exceeds_margin"] = (contract.exceeds_runtime_limit(margin) ||
+                        contract.exceeds_init_limit(margin))
+                    .into();
+                }
+                (name.clone(), value)
+            })
+            .collect::<serde_json::Map<_, _>>();
+        contracts.into()
     }
 }
 
@@ -310,21 +487,31 @@ impl Display for SizeReport {
             Cell::new("Contract").add_attribute(Attribute::Bold).fg(Color::Blue),
             Cell::new("Size (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
             Cell::new("Margin (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Initcode Size (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Initcode Margin (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
         ]);
 
         let contracts = self.contracts.iter().filter(|(_, c)| !c.is_dev_contract && c.size > 0);
         for (name, contract) in contracts {
             let margin = CONTRACT_SIZE_LIMIT as isize - contract.size as isize;
+            let init_margin = CONTRACT_INITCODE_SIZE_LIMIT as isize - contract.init_size as isize;
             let color = match contract.size {
                 0..=17999 => Color::Reset,
                 18000..=CONTRACT_SIZE_LIMIT => Color::Yellow,
                 _ => Color::Red,
             };
+            let init_color = match contract.init_size {
+                0..=35999 => Color::Reset,
+                36000..=CONTRACT_INITCODE_SIZE_LIMIT => Color::Yellow,
+                _ => Color::Red,
+            };
 
             table.add_row([
                 Cell::new(name).fg(color),
                 Cell::new(contract.size as f64 / 1000.0).fg(color),
                 Cell::new(margin as f64 / 1000.0).fg(color),
+                Cell::new(contract.init_size as f64 / 1000.0).fg(init_color),
+                Cell::new(init_margin as f64 / 1000.0).fg(init_color),
             ]);
         }
 
@@ -333,10 +520,176 @@ impl Display for SizeReport {
     }
 }
 
+/// Why a source file can or can't be served from the cache
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// The file and its compiler settings are unchanged, its artifacts are reused
+    Hit,
+    /// The file is not in the cache
+    New,
+    /// The content of the file changed since it was compiled
+    ContentChanged,
+    /// The compiler version or settings changed since the file was compiled
+    SettingsChanged,
+    /// An artifact of the file is missing
+    MissingArtifact,
+    /// The file is unchanged, but one of the files it imports, directly or not, is recompiled
+    DependencyChanged,
+}
+
+impl CacheStatus {
+    /// Returns true if the artifacts of the file are reused
+    pub fn is_hit(&self) -> bool {
+        matches!(self, Self::Hit)
+    }
+}
+
+impl Display for CacheStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.write_str(match self {
+            Self::Hit => "hit",
+            Self::New => "miss: new file",
+            Self::ContentChanged => "miss: content changed",
+            Self::SettingsChanged => "miss: settings changed",
+            Self::MissingArtifact => "miss: missing artifact",
+            Self::DependencyChanged => "miss: dependency changed",
+        })
+    }
+}
+
+/// The cache status of the sources of a project, printed by `forge build --cache-report`.
+///
+/// Sources are identified by their content hash and the compiler settings, so the cache stays
+/// valid when the project is moved or checked out again, which only changes paths and timestamps.
+#[derive(Clone, Debug, Default)]
+pub struct CacheReport {
+    /// The status of each source, by its path relative to the project root
+    pub files: BTreeMap<PathBuf, CacheStatus>,
+}
+
+impl CacheReport {
+    /// Returns the status of the Solidity sources of the project in its cache.
+    ///
+    /// This has to be called before the project is compiled, which updates the cache.
+    pub fn solc(project: &Project) -> Result<Self> {
+        let cache = if project.cache_path().exists() {
+            SolFilesCache::read_joined(&project.paths)?
+        } else {
+            SolFilesCache::default()
+        };
+
+        // the status of the file itself, regardless of the files it imports
+        let own_status = |file: &Path| match cache.files.get(file) {
+            None => CacheStatus::New,
+            Some(entry)
+                if Source::read(file)
+                    .map_or(true, |source| source.content_hash() != entry.content_hash) =>
+            {
+                CacheStatus::ContentChanged
+            }
+            Some(entry) if entry.solc_config != project.solc_config => CacheStatus::SettingsChanged,
+            Some(entry)
+                if entry
+                    .artifacts_versions()
+                    .any(|(_, path)| !project.paths.artifacts.join(path).exists()) =>
+            {
+                CacheStatus::MissingArtifact
+            }
+            Some(_) => CacheStatus::Hit,
+        };
+
+        // like the cache of the compiler, a file is dirty if any of the files it imports, directly
+        // or through other imports, is dirty, which includes the files of the libraries
+        let mut own_statuses = HashMap::new();
+        let mut has_dirty_import = |file: &Path| {
+            let mut visited = HashSet::new();
+            let mut queue = cache
+                .files
+                .get(file)
+                .map(|entry| entry.imports.iter().cloned().collect::<Vec<_>>())
+                .unwrap_or_default();
+            while let Some(import) = queue.pop() {
+                if !visited.insert(import.clone()) {
+                    continue
+                }
+                let status =
+                    *own_statuses.entry(import.clone()).or_insert_with(|| own_status(&import));
+                if !status.is_hit() {
+                    return true
+                }
+                if let Some(entry) = cache.files.get(&import) {
+                    queue.extend(entry.imports.iter().cloned());
+                }
+            }
+            false
+        };
+
+        let mut report = Self::default();
+        for file in project.paths.input_files() {
+            let status = match own_status(&file) {
+                CacheStatus::Hit if has_dirty_import(&file) => CacheStatus::DependencyChanged,
+                status => status,
+            };
+            let file =
+                file.strip_prefix(&project.paths.root).map(Path::to_path_buf).unwrap_or(file);
+            report.files.insert(file, status);
+        }
+        Ok(report)
+    }
+
+    /// Adds the statuses of another report.
+    pub fn extend(&mut self, other: Self) {
+        self.files.extend(other.files);
+    }
+
+    /// Returns the number of files whose artifacts are reused
+    pub fn hits(&self) -> usize {
+        self.files.values().filter(|status| status.is_hit()).count()
+    }
+
+    /// Returns the number of files which are recompiled
+    pub fn misses(&self) -> usize {
+        self.files.len() - self.hits()
+    }
+}
+
+impl Display for CacheReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header([
+            Cell::new("File").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Cache").add_attribute(Attribute::Bold).fg(Color::Blue),
+        ]);
+        for (file, status) in &self.files {
+            let color = if status.is_hit() { Color::Green } else { Color::Yellow };
+            table.add_row([
+                Cell::new(file.display()).fg(color),
+                Cell::new(status.to_string()).fg(color),
+            ]);
+        }
+
+        writeln!(f, "{table}")?;
+        writeln!(f, "{} hits, {} misses", self.hits(), self.misses())?;
+        Ok(())
+    }
+}
+
 /// Returns the size of the deployed contract
 pub fn deployed_contract_size<T: Artifact>(artifact: &T) -> Option<usize> {
     let bytecode = artifact.get_deployed_bytecode_object()?;
-    let size = match bytecode.as_ref() {
+    Some(bytecode_size(bytecode.as_ref()))
+}
+
+/// Returns the size of the contract's initcode
+pub fn init_code_size<T: Artifact>(artifact: &T) -> Option<usize> {
+    let bytecode = artifact.get_bytecode_object()?;
+    Some(bytecode_size(bytecode.as_ref()))
+}
+
+/// Returns the size of the given bytecode in bytes
+fn bytecode_size(bytecode: &BytecodeObject) -> usize {
+    match bytecode {
         BytecodeObject::Bytecode(bytes) => bytes.len(),
         BytecodeObject::Unlinked(unlinked) => {
             // we don't need to account for placeholders here, because library placeholders take up
@@ -348,8 +701,7 @@ pub fn deployed_contract_size<T: Artifact>(artifact: &T) -> Option<usize> {
             // hex -> bytes
             size / 2
         }
-    };
-    Some(size)
+    }
 }
 
 /// How big the contract is and whether it is a dev contract where size limits can be neglected
@@ -357,10 +709,24 @@ pub fn deployed_contract_size<T: Artifact>(artifact: &T) -> Option<usize> {
 pub struct ContractInfo {
     /// size of the contract in bytes
     pub size: usize,
+    /// size of the contract's initcode in bytes
+    pub init_size: usize,
     /// A development contract is either a Script or a Test contract.
     pub is_dev_contract: bool,
 }
 
+impl ContractInfo {
+    /// Returns whether the runtime size is within `margin` bytes of the runtime size limit.
+    pub fn exceeds_runtime_limit(&self, margin: usize) -> bool {
+        self.size.saturating_add(margin) > CONTRACT_SIZE_LIMIT
+    }
+
+    /// Returns whether the initcode size is within `margin` bytes of the initcode size limit.
+    pub fn exceeds_init_limit(&self, margin: usize) -> bool {
+        self.init_size.saturating_add(margin) > CONTRACT_INITCODE_SIZE_LIMIT
+    }
+}
+
 /// Compiles target file path.
 ///
 /// If `quiet` no solc related output will be emitted to stdout.
@@ -468,6 +834,72 @@ pub fn etherscan_project(metadata: &Metadata, target_path: impl AsRef<Path>) ->
         .build()?)
 }
 
+/// Returns the projects that compile the sources of the compilation overrides of the `config`, one
+/// per distinct settings, each with the filter of its sources among the sources matched by
+/// `filter`.
+///
+/// The sources of the project of the `config` itself are matched by
+/// `CompilationOverrideFilter { settings_hash: None, .. }`.
+pub fn compilation_override_projects<F: FileFilter + Clone + 'static>(
+    config: &Arc<Config>,
+    filter: &F,
+) -> Result<Vec<(Project, Box<dyn FileFilter>)>> {
+    let mut projects = Vec::new();
+    let mut settings_hashes = HashSet::new();
+    for overrides in config.compilation_overrides.values() {
+        let settings_hash = overrides.settings_hash();
+        if !settings_hashes.insert(settings_hash.clone()) {
+            continue
+        }
+        let project = config.with_compilation_override(overrides).project()?;
+        let filter = CompilationOverrideFilter {
+            inner: filter.clone(),
+            config: config.clone(),
+            settings_hash: Some(settings_hash),
+        };
+        projects.push((project, Box::new(filter) as Box<dyn FileFilter>));
+    }
+    Ok(projects)
+}
+
+/// Fails if the project has compilation overrides, which the command can't apply because it
+/// compiles all sources with the settings of the profile, so that the bytecode it tests or deploys
+/// never differs from the artifacts of `forge build`.
+fn ensure_no_compilation_overrides(project: &Project) -> Result<()> {
+    let Ok(config) = Config::try_from(Config::figment_with_root(&project.paths.root)) else {
+        return Ok(())
+    };
+    if config.compilation_overrides.is_empty() {
+        return Ok(())
+    }
+    let globs = config.compilation_overrides.keys().map(String::as_str).collect::<Vec<_>>();
+    eyre::bail!(
+        "`compilation_overrides` are only supported by `forge build` and `forge verify-contract`, \
+         this command would compile the sources matching {} with the settings of the profile",
+        globs.join(", ")
+    )
+}
+
+/// Restricts a filter to the sources compiled with the same settings, either those of one of the
+/// compilation overrides or those of the profile, see [Config::compilation_override].
+#[derive(Clone, Debug)]
+pub struct CompilationOverrideFilter<F> {
+    /// The filter of the sources to compile
+    pub inner: F,
+    /// The config with the compilation overrides
+    pub config: Arc<Config>,
+    /// The hash of the settings of the overrides, `None` for the settings of the profile
+    pub settings_hash: Option<String>,
+}
+
+impl<F: FileFilter + Clone> FileFilter for CompilationOverrideFilter<F> {
+    fn is_match(&self, file: &Path) -> bool {
+        let settings_hash =
+            self.config.compilation_override(file).map(|(_, overrides)| overrides.settings_hash());
+        settings_hash == self.settings_hash && self.inner.is_match(file)
+    }
+}
+
 /// Bundles multiple `SkipBuildFilter` into a single `FileFilter`
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SkipBuildFilters(pub Vec<SkipBuildFilter>);
@@ -555,4 +987,36 @@ mod tests {
         let file = Path::new("/home/script/Contract.sol");
         assert!(!SkipBuildFilter::Custom("*/script/**".to_string()).is_match(file));
     }
+
+    #[test]
+    fn test_size_report_margin() {
+        let contract =
+            |size, init_size, is_dev_contract| ContractInfo { size, init_size, is_dev_contract };
+        let report = SizeReport {
+            contracts: BTreeMap::from([
+                ("Small".to_string(), contract(1000, 1500, false)),
+                ("Large".to_string(), contract(24000, 25000, false)),
+                ("LargeInit".to_string(), contract(10000, 48500, false)),
+                ("Test".to_string(), contract(30000, 60000, true)),
+            ]),
+        };
+        assert!(!report.exceeds_size_limit());
+        assert!(report.offenders(0).is_empty());
+
+        let offenders = report.offenders(1024);
+        let names = offenders.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["Large", "LargeInit"]);
+
+        let json = report.to_json(Some(1024));
+        assert_eq!(json.as_object().unwrap().len(), 3);
+        assert_eq!(json["Small"]["runtime_margin"], 23576);
+        assert_eq!(json["Small"]["init_margin"], 47652);
+        assert_eq!(json["Small"]["exceeds_margin"], false);
+        assert_eq!(json["LargeInit"]["exceeds_margin"], true);
+        assert!(report.to_json(None)["Large"].get("exceeds_margin").is_none());
+
+        // a margin above the limits doesn't overflow
+        assert_eq!(report.offenders(usize::MAX).len(), 3);
+        assert_eq!(report.to_json(Some(usize::MAX))["Small"]["exceeds_margin"], true);
+    }
 }
@@ -175,8 +175,15 @@ pub fn get_artifact_path(paths: &ProjectPathsConfig, path: &str) -> PathBuf {
     } else {
         let parts: Vec<&str> = path.split(':').collect();
         let file = parts[0];
-        let contract_name =
-            if parts.len() == 1 { parts[0].replace(".sol", "") } else { parts[1].to_string() };
+        let contract_name = if parts.len() == 1 {
+            // Vyper contracts are named after their file
+            match file.strip_suffix(".vy") {
+                Some(name) => name.to_string(),
+                None => file.replace(".sol", ""),
+            }
+        } else {
+            parts[1].to_string()
+        };
         paths.artifacts.join(format!("{file}/{contract_name}.json"))
     }
 }
@@ -0,0 +1,614 @@
+//! A pool of Etherscan clients, one per API key of a chain.
+//!
+//! The requests are spread across the keys in a round-robin, a key which hits the rate limit is
+//! backed off for a while and the request is retried with the next key. The rate limit is read
+//! from the response headers if the API reports it there, so that a key is backed off before it
+//! hits the limit. Identical requests which are in flight at the same time are only sent once.
+
+use alloy_json_abi::JsonAbi;
+use alloy_primitives::Address;
+use foundry_block_explorers::{
+    contract::{ContractMetadata, Metadata},
+    errors::EtherscanError,
+    Client,
+};
+use foundry_config::{Config, ResolvedEtherscanConfig};
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
+use reqwest::{header::HeaderMap, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt::Write,
+    future::Future,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// The time a key is backed off for after it first hits the rate limit, doubled every time it
+/// hits it again
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The maximum time a key is backed off for
+const MAX_BACKOFF: Duration = Duration::from_secs(16);
+
+/// The maximum time a key is backed off for when the response headers tell when it can be used
+/// again
+const MAX_HEADER_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The number of times a request is retried per key when it hits the rate limit
+const RATE_LIMITED_RETRIES_PER_KEY: usize = 3;
+
+/// The time the fetched sources are cached for, the same as the cache of [Client]
+const SOURCES_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The header with the number of seconds to wait for before sending another request
+const RETRY_AFTER: &str = "retry-after";
+
+/// The header with the number of requests the key can send before it hits the rate limit
+const RATE_LIMIT_REMAINING: &str = "x-ratelimit-remaining";
+
+/// The header with the number of seconds until the rate limit of the key is reset
+const RATE_LIMIT_RESET: &str = "x-ratelimit-reset";
+
+/// A request shared by all the callers which are waiting for it
+type SharedRequest<T> = Shared<BoxFuture<'static, Result<T, Arc<EtherscanError>>>>;
+
+/// The usage of an API key of an [EtherscanPool]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyStats {
+    /// The redacted API key
+    pub key: String,
+    /// The number of requests sent with the key
+    pub requests: usize,
+    /// The number of requests which hit the rate limit
+    pub rate_limited: usize,
+}
+
+/// A pool of Etherscan clients, one per API key, see the [module docs](self).
+///
+/// Cloning the pool is cheap, the clones share the state of the keys.
+#[derive(Clone)]
+pub struct EtherscanPool {
+    inner: Arc<PoolInner>,
+}
+
+impl EtherscanPool {
+    /// Creates a pool with a client for each of the [keys](ResolvedEtherscanConfig::api_keys) of
+    /// the config.
+    pub fn new(config: ResolvedEtherscanConfig) -> Result<Self, EtherscanError> {
+        let http = reqwest::Client::new();
+        let pooled_key = |key: &str| {
+            let client =
+                ResolvedEtherscanConfig { key: key.to_string(), keys: vec![], ..config.clone() }
+                    .into_client()?;
+            Ok(Arc::new(PooledKey {
+                label: redact_key(key),
+                key: key.to_string(),
+                api_url: config.api_url.clone(),
+                http: http.clone(),
+                client: Arc::new(client),
+                state: Default::default(),
+                requests: AtomicUsize::new(0),
+                rate_limited: AtomicUsize::new(0),
+            }))
+        };
+        let mut keys = config
+            .api_keys()
+            .into_iter()
+            .map(pooled_key)
+            .collect::<Result<Vec<_>, EtherscanError>>()?;
+        if keys.is_empty() {
+            keys.push(pooled_key("")?);
+        }
+        Ok(Self {
+            inner: Arc::new(PoolInner {
+                keys,
+                next: AtomicUsize::new(0),
+                in_flight: Mutex::new(HashMap::new()),
+                sources_cache: config
+                    .chain
+                    .and_then(Config::foundry_etherscan_chain_cache_dir)
+                    .map(|dir| dir.join("sources")),
+            }),
+        })
+    }
+
+    /// Returns the number of keys of the pool.
+    pub fn len(&self) -> usize {
+        self.inner.keys.len()
+    }
+
+    /// Returns `true` if the pool has no keys, which never happens as a pool always has a client.
+    pub fn is_empty(&self) -> bool {
+        self.inner.keys.is_empty()
+    }
+
+    /// Fetches the source code of the verified contract at the address.
+    ///
+    /// The sources are cached for the chain of the pool, like the ones fetched by a [Client].
+    pub async fn contract_source_code(
+        &self,
+        address: Address,
+    ) -> Result<ContractMetadata, Arc<EtherscanError>> {
+        if let Some(meta) = self.inner.cached_source(address) {
+            return Ok(meta)
+        }
+        let meta = self
+            .request_with_key(format!("contract_source_code/{address}"), move |key| async move {
+                let items = key.get::<Vec<Metadata>>("getsourcecode", address).await?;
+                Ok(ContractMetadata { items })
+            })
+            .await?;
+        self.inner.cache_source(address, &meta);
+        Ok(meta)
+    }
+
+    /// Fetches the ABI of the verified contract at the address.
+    pub async fn contract_abi(&self, address: Address) -> Result<JsonAbi, Arc<EtherscanError>> {
+        self.request_with_key(format!("contract_abi/{address}"), move |key| async move {
+            let abi = key.get::<String>("getabi", address).await?;
+            Ok(serde_json::from_str(&abi)?)
+        })
+        .await
+    }
+
+    /// Sends a request with the next available key.
+    ///
+    /// The request is retried with the next key if it hits the rate limit or if the key is
+    /// invalid. If a request with the same `id` is already in flight, its result is awaited
+    /// instead of sending the request again.
+    ///
+    /// The [Client] doesn't expose the response headers, so the key is only backed off once a
+    /// response of the request reports that it hit the rate limit.
+    pub async fn request<T, F, Fut>(
+        &self,
+        id: impl Into<String>,
+        request: F,
+    ) -> Result<T, Arc<EtherscanError>>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: Fn(Arc<Client>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, EtherscanError>> + Send + 'static,
+    {
+        self.request_with_key(id, move |key| request(key.client.clone())).await
+    }
+
+    /// Sends a request with the next available key, see [Self::request].
+    async fn request_with_key<T, F, Fut>(
+        &self,
+        id: impl Into<String>,
+        request: F,
+    ) -> Result<T, Arc<EtherscanError>>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: Fn(Arc<PooledKey>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, EtherscanError>> + Send + 'static,
+    {
+        let id = id.into();
+        let shared = {
+            let mut in_flight = self.inner.in_flight.lock().unwrap();
+            match in_flight.get(&id).and_then(|req| req.downcast_ref::<SharedRequest<T>>()) {
+                Some(req) => {
+                    trace!(target: "etherscan", %id, "awaiting in flight request");
+                    req.clone()
+                }
+                None => {
+                    let inner = self.inner.clone();
+                    let req =
+                        async move { inner.send(request).await.map_err(Arc::new) }.boxed().shared();
+                    in_flight.insert(id.clone(), Box::new(req.clone()));
+                    req
+                }
+            }
+        };
+
+        let res = shared.clone().await;
+
+        let mut in_flight = self.inner.in_flight.lock().unwrap();
+        let is_done = in_flight
+            .get(&id)
+            .and_then(|req| req.downcast_ref::<SharedRequest<T>>())
+            .map_or(false, |req| req.ptr_eq(&shared));
+        if is_done {
+            in_flight.remove(&id);
+        }
+        res
+    }
+
+    /// Returns the usage of each key of the pool.
+    pub fn stats(&self) -> Vec<KeyStats> {
+        self.inner
+            .keys
+            .iter()
+            .map(|key| KeyStats {
+                key: key.label.clone(),
+                requests: key.requests.load(Ordering::Relaxed),
+                rate_limited: key.rate_limited.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Returns the usage of each key of the pool, formatted for `-vvv`, or `None` if no request
+    /// was sent.
+    pub fn usage_report(&self) -> Option<String> {
+        let stats = self.stats();
+        if stats.iter().all(|stats| stats.requests == 0) {
+            return None
+        }
+        let mut report = String::from("Etherscan API key usage:");
+        for KeyStats { key, requests, rate_limited } in stats {
+            let _ = write!(report, "\n  {key}: {requests} requests, {rate_limited} rate limited");
+        }
+        Some(report)
+    }
+}
+
+impl std::fmt::Debug for EtherscanPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EtherscanPool").field("keys", &self.stats()).finish()
+    }
+}
+
+struct PoolInner {
+    keys: Vec<Arc<PooledKey>>,
+    /// The index of the key the next request starts looking for an available key at
+    next: AtomicUsize,
+    /// The [SharedRequest]s in flight, by id
+    in_flight: Mutex<HashMap<String, Box<dyn Any + Send + Sync>>>,
+    /// The dir the sources of the chain are cached in
+    sources_cache: Option<PathBuf>,
+}
+
+impl PoolInner {
+    async fn send<T, F, Fut>(&self, request: F) -> Result<T, EtherscanError>
+    where
+        F: Fn(Arc<PooledKey>) -> Fut,
+        Fut: Future<Output = Result<T, EtherscanError>>,
+    {
+        let mut rate_limited = 0;
+        loop {
+            let key = self.next_key().await?;
+            key.requests.fetch_add(1, Ordering::Relaxed);
+            match request(key.clone()).await {
+                Err(EtherscanError::RateLimitExceeded) => {
+                    warn!(target: "etherscan", key = %key.label, "rate limit exceeded");
+                    key.rate_limited.fetch_add(1, Ordering::Relaxed);
+                    key.state.lock().unwrap().back_off();
+                    rate_limited += 1;
+                    if rate_limited >= RATE_LIMITED_RETRIES_PER_KEY * self.keys.len() {
+                        return Err(EtherscanError::RateLimitExceeded)
+                    }
+                }
+                Err(EtherscanError::InvalidApiKey) => {
+                    warn!(target: "etherscan", key = %key.label, "invalid api key");
+                    key.state.lock().unwrap().invalid = true;
+                }
+                res => {
+                    key.state.lock().unwrap().backoff = None;
+                    return res
+                }
+            }
+        }
+    }
+
+    /// Returns the next key in the round-robin which is not backed off, waiting for one if all
+    /// the keys are backed off.
+    async fn next_key(&self) -> Result<Arc<PooledKey>, EtherscanError> {
+        loop {
+            let now = Instant::now();
+            let start = self.next.fetch_add(1, Ordering::Relaxed);
+            let mut available_at: Option<Instant> = None;
+            for i in 0..self.keys.len() {
+                let key = &self.keys[(start + i) % self.keys.len()];
+                let state = key.state.lock().unwrap();
+                if state.invalid {
+                    continue
+                }
+                match state.available_at {
+                    Some(at) if at > now => {
+                        available_at = Some(available_at.map_or(at, |other| other.min(at)));
+                    }
+                    _ => return Ok(key.clone()),
+                }
+            }
+            match available_at {
+                Some(at) => tokio::time::sleep_until(at.into()).await,
+                None => return Err(EtherscanError::InvalidApiKey),
+            }
+        }
+    }
+
+    fn source_cache_path(&self, address: Address) -> Option<PathBuf> {
+        Some(self.sources_cache.as_ref()?.join(format!("{address:?}.json")))
+    }
+
+    /// Returns the cached source of the contract, if it didn't expire.
+    fn cached_source(&self, address: Address) -> Option<ContractMetadata> {
+        let file = std::fs::File::open(self.source_cache_path(address)?).ok()?;
+        let cached: CachedSource = serde_json::from_reader(std::io::BufReader::new(file)).ok()?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        if cached.expiry < now {
+            return None
+        }
+        cached.data
+    }
+
+    fn cache_source(&self, address: Address, meta: &ContractMetadata) {
+        let Some(path) = self.source_cache_path(address) else { return };
+        let expiry = (SystemTime::now() + SOURCES_TTL)
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let cached = CachedSource { expiry, data: Some(meta.clone()) };
+        let _ = std::fs::create_dir_all(path.parent().unwrap());
+        if let Err(err) = std::fs::write(&path, serde_json::to_vec(&cached).unwrap_or_default()) {
+            warn!(target: "etherscan", ?path, %err, "could not cache the source");
+        }
+    }
+}
+
+/// A source in the cache, in the format of the cache of [Client] so that they share it
+#[derive(Serialize, Deserialize)]
+struct CachedSource {
+    /// The unix timestamp the source expires at
+    expiry: u64,
+    data: Option<ContractMetadata>,
+}
+
+/// The response of the Etherscan API
+#[derive(Deserialize)]
+struct ApiResponse {
+    status: String,
+    message: String,
+    result: serde_json::Value,
+}
+
+struct PooledKey {
+    /// The redacted key, see [redact_key]
+    label: String,
+    key: String,
+    api_url: String,
+    http: reqwest::Client,
+    client: Arc<Client>,
+    state: Mutex<KeyState>,
+    requests: AtomicUsize,
+    rate_limited: AtomicUsize,
+}
+
+impl PooledKey {
+    /// Sends a request of the `contract` module for the address with the key.
+    ///
+    /// The rate limit headers of the response are tracked in the [KeyState] of the key.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        action: &str,
+        address: Address,
+    ) -> Result<T, EtherscanError> {
+        let address_param = address.to_string();
+        let mut request = self.http.get(&self.api_url).query(&[
+            ("module", "contract"),
+            ("action", action),
+            ("address", &address_param),
+        ]);
+        if !self.key.is_empty() {
+            request = request.query(&[("apikey", &self.key)]);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        self.state.lock().unwrap().observe(response.headers());
+        let body = response.text().await?;
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(EtherscanError::RateLimitExceeded)
+        }
+        if body.contains("Contract source code not verified") {
+            return Err(EtherscanError::ContractCodeNotVerified(address))
+        }
+
+        let response: ApiResponse = serde_json::from_str(&body)?;
+        if response.status == "1" {
+            return Ok(serde_json::from_value(response.result)?)
+        }
+        let result = response.result.as_str().map(str::to_string);
+        match result.as_deref() {
+            Some(result) if result.contains("rate limit") => Err(EtherscanError::RateLimitExceeded),
+            Some(result) if result.contains("Invalid API Key") => {
+                Err(EtherscanError::InvalidApiKey)
+            }
+            _ => Err(EtherscanError::ErrorResponse {
+                status: response.status,
+                message: response.message,
+                result,
+            }),
+        }
+    }
+}
+
+#[derive(Default)]
+struct KeyState {
+    /// Whether Etherscan rejected the key
+    invalid: bool,
+    /// The time the key is backed off for, since it last hit the rate limit
+    backoff: Option<Duration>,
+    /// The time the key can be used again at, if it hit the rate limit
+    available_at: Option<Instant>,
+}
+
+impl KeyState {
+    fn back_off(&mut self) {
+        let backoff =
+            self.backoff.map_or(INITIAL_BACKOFF, |backoff| (backoff * 2).min(MAX_BACKOFF));
+        self.backoff = Some(backoff);
+        // the headers of the response may already have backed off the key for longer
+        let at = Instant::now() + backoff;
+        self.available_at = Some(self.available_at.map_or(at, |other| other.max(at)));
+    }
+
+    /// Backs off the key until the time the rate limit headers of a response tell it can be used
+    /// again, if it has no requests left.
+    fn observe(&mut self, headers: &HeaderMap) {
+        let number = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<u64>().ok();
+        let secs = |name: &str| Some(Duration::from_secs(number(name)?).min(MAX_HEADER_BACKOFF));
+        let wait = secs(RETRY_AFTER).or_else(|| {
+            let no_requests_left = number(RATE_LIMIT_REMAINING)? == 0;
+            no_requests_left.then(|| secs(RATE_LIMIT_RESET).unwrap_or(INITIAL_BACKOFF))
+        });
+        if let Some(wait) = wait {
+            trace!(target: "etherscan", ?wait, "backing off key from the rate limit headers");
+            self.available_at = Some(Instant::now() + wait);
+        }
+    }
+}
+
+/// Returns the first and the last 4 characters of the key, so that it can be told apart from the
+/// other keys of the pool without being leaked.
+fn redact_key(key: &str) -> String {
+    if key.len() <= 8 || !key.is_ascii() {
+        return "*".repeat(key.len().min(8))
+    }
+    format!("{}…{}", &key[..4], &key[key.len() - 4..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        http::{StatusCode, Uri},
+        response::IntoResponse,
+        Router,
+    };
+
+    /// Spawns an Etherscan API which answers after `delay`, and returns its URL and the number
+    /// of requests it received.
+    ///
+    /// The `LIMITED` key always hits the rate limit, the `RETRY` key is answered with a `429` and
+    /// a `Retry-After` header, and the responses of the `EXHAUSTED` key report that it has no
+    /// requests left.
+    fn spawn_etherscan_api(delay: Duration) -> (String, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let app = Router::new().fallback(move |uri: Uri| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(delay).await;
+                let query = uri.query().unwrap_or_default().to_string();
+                let ok = r#"{"status":"1","message":"OK","result":"[]"}"#;
+                if query.contains("apikey=LIMITED") {
+                    r#"{"status":"0","message":"NOTOK","result":"Max rate limit reached"}"#
+                        .into_response()
+                } else if query.contains("apikey=RETRY") {
+                    (StatusCode::TOO_MANY_REQUESTS, [(RETRY_AFTER, "30")], "Too Many Requests")
+                        .into_response()
+                } else if query.contains("apikey=EXHAUSTED") {
+                    ([(RATE_LIMIT_REMAINING, "0"), (RATE_LIMIT_RESET, "30")], ok).into_response()
+                } else {
+                    ok.into_response()
+                }
+            }
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api", listener.local_addr().unwrap());
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+        (url, requests)
+    }
+
+    fn pool(url: String, keys: &[&str]) -> EtherscanPool {
+        EtherscanPool::new(ResolvedEtherscanConfig {
+            api_url: url,
+            browser_url: None,
+            key: keys[0].to_string(),
+            keys: keys[1..].iter().map(|key| key.to_string()).collect(),
+            chain: None,
+        })
+        .unwrap()
+    }
+
+    fn requests_per_key(pool: &EtherscanPool) -> Vec<(usize, usize)> {
+        pool.stats().into_iter().map(|stats| (stats.requests, stats.rate_limited)).collect()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn spreads_requests_across_keys() {
+        let (url, requests) = spawn_etherscan_api(Duration::ZERO);
+        let pool = pool(url, &["KEY1", "KEY2", "KEY3"]);
+        assert_eq!(pool.len(), 3);
+        for i in 0..6u8 {
+            pool.contract_abi(Address::with_last_byte(i)).await.unwrap();
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 6);
+        assert_eq!(requests_per_key(&pool), [(2, 0), (2, 0), (2, 0)]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn backs_off_rate_limited_keys() {
+        let (url, _) = spawn_etherscan_api(Duration::ZERO);
+        let pool = pool(url, &["LIMITED", "KEY2"]);
+        for i in 0..4u8 {
+            pool.contract_abi(Address::with_last_byte(i)).await.unwrap();
+        }
+        // the limited key is not used again while it is backed off
+        assert_eq!(requests_per_key(&pool), [(1, 1), (4, 0)]);
+
+        let report = pool.usage_report().unwrap();
+        assert!(report.contains("1 requests, 1 rate limited"), "{report}");
+        assert!(!report.contains("LIMITED"), "{report}");
+
+        let (url, requests) = spawn_etherscan_api(Duration::ZERO);
+        let pool = pool(url, &["LIMITED"]);
+        let err = pool.contract_abi(Address::ZERO).await.unwrap_err();
+        assert!(matches!(*err, EtherscanError::RateLimitExceeded), "{err:?}");
+        assert_eq!(requests.load(Ordering::SeqCst), RATE_LIMITED_RETRIES_PER_KEY);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn backs_off_keys_from_rate_limit_headers() {
+        let (url, _) = spawn_etherscan_api(Duration::ZERO);
+        let pool = pool(url, &["RETRY", "KEY2"]);
+        for i in 0..4u8 {
+            pool.contract_abi(Address::with_last_byte(i)).await.unwrap();
+        }
+        assert_eq!(requests_per_key(&pool), [(1, 1), (4, 0)]);
+        let state = pool.inner.keys[0].state.lock().unwrap();
+        // backed off for the `Retry-After` of the response, not the initial backoff
+        assert!(state.available_at.unwrap() > Instant::now() + MAX_BACKOFF);
+        drop(state);
+
+        // the key isn't used once it has no requests left, before it hits the rate limit
+        let (url, _) = spawn_etherscan_api(Duration::ZERO);
+        let pool = pool(url, &["EXHAUSTED", "KEY2"]);
+        for i in 0..4u8 {
+            pool.contract_abi(Address::with_last_byte(i)).await.unwrap();
+        }
+        assert_eq!(requests_per_key(&pool), [(1, 0), (3, 0)]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn coalesces_identical_requests() {
+        let (url, requests) = spawn_etherscan_api(Duration::from_millis(200));
+        let pool = pool(url, &["KEY1", "KEY2"]);
+        let results = futures::future::join_all(
+            (0..10).map(|_| pool.contract_abi(Address::ZERO)).collect::<Vec<_>>(),
+        )
+        .await;
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // finished requests are sent again
+        pool.contract_abi(Address::ZERO).await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn redacts_keys() {
+        assert_eq!(redact_key("ABCDEFGHIJKLMNOP"), "ABCD…MNOP");
+        assert_eq!(redact_key("KEY1"), "****");
+    }
+}
@@ -135,6 +135,13 @@ pub struct EvmArgs {
     #[serde(skip)]
     pub no_rpc_rate_limit: bool,
 
+    /// Whether to enable isolation of calls.
+    /// In isolation mode all top-level calls are executed as a separate transaction in a separate
+    /// EVM context, enabling more precise gas accounting and transaction state changes.
+    #[clap(long)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub isolate: bool,
+
     /// All ethereum environment related arguments
     #[clap(flatten)]
     #[serde(flatten)]
@@ -0,0 +1,261 @@
+//! Replacing transactions that aren't mined in time with ones with higher fees, see
+//! `forge script --gas-price-bump-interval` and `cast send --auto-bump`.
+
+use crate::types::{ToAlloy, ToEthers};
+use alloy_primitives::{B256, U256};
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_providers::Middleware;
+use eyre::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+/// Replaces transactions that aren't mined within an interval with ones with higher fees
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeBump {
+    /// How long to wait for a transaction to be mined before replacing it
+    pub interval: Duration,
+    /// The percentage the fees are increased by on every replacement
+    pub percent: u64,
+    /// The maximum fee per gas, or gas price, of a replacement
+    pub max_fee: Option<U256>,
+}
+
+impl FeeBump {
+    /// Returns the fee increased by the percentage, and at least by 1 wei, capped at the
+    /// maximum fee.
+    fn bump_fee(&self, fee: U256) -> U256 {
+        let bumped = fee * U256::from(100 + self.percent) / U256::from(100);
+        let bumped = bumped.max(fee + U256::from(1));
+        self.max_fee.map_or(bumped, |max_fee| bumped.min(max_fee))
+    }
+
+    /// Returns the transaction with bumped fees, or `None` if the fees can't be increased any
+    /// further.
+    pub fn bump(&self, tx: &TypedTransaction) -> Option<TypedTransaction> {
+        let fee = fee_of(tx);
+        let bumped = self.bump_fee(fee);
+        if bumped <= fee {
+            return None
+        }
+
+        let mut tx = tx.clone();
+        match tx {
+            TypedTransaction::Eip1559(ref mut inner) => {
+                let priority_fee = inner.max_priority_fee_per_gas.unwrap_or_default().to_alloy();
+                inner.max_fee_per_gas = Some(bumped.to_ethers());
+                inner.max_priority_fee_per_gas =
+                    Some(self.bump_fee(priority_fee).min(bumped).to_ethers());
+            }
+            _ => tx.set_gas_price(bumped.to_ethers()),
+        }
+        Some(tx)
+    }
+
+    /// Returns the fees of the attempts of sending the transaction, at most `limit`.
+    pub fn schedule(&self, tx: &TypedTransaction, limit: usize) -> Vec<U256> {
+        let mut fees = vec![fee_of(tx)];
+        let mut tx = tx.clone();
+        while fees.len() < limit {
+            let Some(replacement) = self.bump(&tx) else { break };
+            fees.push(fee_of(&replacement));
+            tx = replacement;
+        }
+        fees
+    }
+}
+
+/// An attempt of sending a transaction, see [FeeBump]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendAttempt {
+    pub hash: B256,
+    /// The max fee per gas, or the gas price of legacy transactions
+    pub fee: U256,
+}
+
+/// The outcome of [wait_with_fee_bumps].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BumpOutcome {
+    /// The attempt with this hash was mined
+    Mined(B256),
+    /// None of the attempts was mined before the timeout, the hash is the one of the last attempt
+    TimedOut(B256),
+}
+
+/// Waits for one of the attempts of sending the transaction to be mined, and replaces the last
+/// one with `send` whenever none of them got mined within the interval of the [FeeBump].
+///
+/// `tx` is the transaction of the last attempt. `on_attempt` is called with all the attempts as
+/// soon as a replacement is sent. Once an attempt is mined it's moved last.
+///
+/// Fails if a replacement is rejected because its nonce is used and none of the attempts gets
+/// mined within the next interval, since another transaction with the same nonce was.
+pub async fn wait_with_fee_bumps<M, S, F>(
+    provider: &M,
+    mut tx: TypedTransaction,
+    attempts: &mut Vec<SendAttempt>,
+    bump: Option<FeeBump>,
+    timeout: Option<Duration>,
+    mut send: S,
+    mut on_attempt: impl FnMut(&[SendAttempt]) -> Result<()>,
+) -> Result<BumpOutcome>
+where
+    M: Middleware,
+    M::Error: 'static,
+    S: FnMut(TypedTransaction) -> F,
+    F: Future<Output = Result<B256>>,
+{
+    let start = Instant::now();
+    let mut last_attempt = Instant::now();
+    let mut poll_interval = provider.provider().get_interval();
+    if let Some(bump) = &bump {
+        poll_interval = poll_interval.min(bump.interval);
+    }
+    // whether a replacement was rejected because the nonce is already used
+    let mut nonce_used = false;
+
+    loop {
+        for (i, attempt) in attempts.iter().enumerate() {
+            if provider.get_transaction_receipt(attempt.hash.to_ethers()).await?.is_some() {
+                // the mined attempt goes last
+                let mined = attempts.remove(i);
+                let hash = mined.hash;
+                attempts.push(mined);
+                return Ok(BumpOutcome::Mined(hash))
+            }
+        }
+
+        let previous = attempts.last().expect("at least one attempt").hash;
+        let elapsed = start.elapsed();
+        if let Some(timeout) = timeout {
+            if elapsed >= timeout {
+                return Ok(BumpOutcome::TimedOut(previous))
+            }
+        }
+
+        if let Some(bump) = &bump {
+            if last_attempt.elapsed() >= bump.interval {
+                if nonce_used {
+                    bail!("The nonce of transaction {previous:?} was used by another transaction.")
+                }
+                last_attempt = Instant::now();
+
+                // keep waiting for the pending attempts once the fees are at the cap
+                if let Some(replacement) = bump.bump(&tx) {
+                    tx = replacement;
+                    let fee = fee_of(&tx);
+                    match send(tx.clone()).await {
+                        Ok(hash) => {
+                            if !attempts.iter().any(|attempt| attempt.hash == hash) {
+                                attempts.push(SendAttempt { hash, fee });
+                                on_attempt(attempts)?;
+                            }
+                        }
+                        // the fees are bumped further on the next attempt
+                        Err(err) if is_underpriced_error(&format!("{err:#}")) => {
+                            tracing::warn!(?previous, %fee, "replacement transaction underpriced");
+                        }
+                        // an earlier attempt is still known to the node, keep waiting for it
+                        Err(err) if is_already_known_error(&format!("{err:#}")) => {
+                            tracing::debug!(?previous, %err, "transaction was not replaced");
+                        }
+                        // an earlier attempt got mined, its receipt is checked for one more
+                        // interval
+                        Err(err) if is_nonce_too_low_error(&format!("{err:#}")) => {
+                            tracing::debug!(?previous, %err, "transaction nonce already used");
+                            nonce_used = true;
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+            }
+        }
+
+        let sleep = match timeout {
+            Some(timeout) => poll_interval.min(timeout.saturating_sub(elapsed)),
+            None => poll_interval,
+        };
+        tokio::time::sleep(sleep).await;
+    }
+}
+
+/// Returns the max fee per gas of EIP1559 transactions, or the gas price of other transactions.
+pub fn fee_of(tx: &TypedTransaction) -> U256 {
+    let fee = match tx {
+        TypedTransaction::Eip1559(inner) => inner.max_fee_per_gas,
+        _ => tx.gas_price(),
+    };
+    fee.unwrap_or_default().to_alloy()
+}
+
+/// Returns true if the node rejected the transaction because it already has it in its pool.
+pub fn is_already_known_error(err: &str) -> bool {
+    let err = err.to_lowercase();
+    ["already known", "already imported", "known transaction"]
+        .iter()
+        .any(|pattern| err.contains(pattern))
+}
+
+/// Returns true if the node rejected a replacement transaction because its fees aren't high
+/// enough.
+pub fn is_underpriced_error(err: &str) -> bool {
+    err.to_lowercase().contains("underpriced")
+}
+
+/// Returns true if the nonce of the transaction was already used, i.e. one of the previous
+/// submissions was mined.
+pub fn is_nonce_too_low_error(err: &str) -> bool {
+    err.to_lowercase().contains("nonce too low")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Eip1559TransactionRequest, TransactionRequest};
+
+    #[test]
+    fn can_bump_fees() {
+        let bump = FeeBump {
+            interval: Duration::from_secs(30),
+            percent: 15,
+            max_fee: Some(U256::from(130)),
+        };
+
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .max_fee_per_gas(100)
+            .max_priority_fee_per_gas(10)
+            .into();
+        let bumped = bump.bump(&tx).unwrap();
+        let TypedTransaction::Eip1559(ref inner) = bumped else { unreachable!() };
+        assert_eq!(inner.max_fee_per_gas, Some(115.into()));
+        assert_eq!(inner.max_priority_fee_per_gas, Some(11.into()));
+
+        // capped
+        let bumped = bump.bump(&bumped).unwrap();
+        assert_eq!(fee_of(&bumped), U256::from(130));
+        assert!(bump.bump(&bumped).is_none());
+
+        let tx: TypedTransaction = TransactionRequest::new().gas_price(100).into();
+        assert_eq!(
+            bump.schedule(&tx, usize::MAX),
+            vec![U256::from(100), U256::from(115), U256::from(130)]
+        );
+
+        // bumped by at least 1 wei
+        let bump = FeeBump { max_fee: None, ..bump };
+        let tx: TypedTransaction = TransactionRequest::new().gas_price(1).into();
+        assert_eq!(fee_of(&bump.bump(&tx).unwrap()), U256::from(2));
+    }
+
+    #[test]
+    fn can_classify_send_errors() {
+        assert!(is_already_known_error("(code: -32000, message: already known, data: None)"));
+        assert!(is_already_known_error("transaction already imported"));
+        assert!(is_underpriced_error("replacement transaction underpriced"));
+        assert!(is_nonce_too_low_error("nonce too low: next nonce 5, tx nonce 4"));
+        assert!(!is_already_known_error("insufficient funds for gas * price + value"));
+    }
+}
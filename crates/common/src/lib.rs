@@ -8,26 +8,34 @@ extern crate self as foundry_common;
 extern crate tracing;
 
 pub mod abi;
+pub mod authorization;
 pub mod calc;
 pub mod compile;
 pub mod constants;
 pub mod contracts;
 pub mod errors;
+pub mod etherscan;
 pub mod evm;
+pub mod fee_bump;
 pub mod fmt;
 pub mod fs;
 pub mod glob;
+pub mod proof;
 pub mod provider;
+pub mod redact;
 pub mod retry;
 pub mod rpc;
 pub mod runtime_client;
 pub mod selectors;
 pub mod serde_helpers;
 pub mod shell;
+pub mod signatures;
+pub mod storage_diff;
 pub mod term;
 pub mod traits;
 pub mod transactions;
 pub mod types;
+pub mod vyper;
 
 pub use constants::*;
 pub use contracts::*;
@@ -0,0 +1,411 @@
+//! Verification of the Merkle-Patricia trie proofs returned by `eth_getProof`, see
+//! [EIP-1186](https://eips.ethereum.org/EIPS/eip-1186).
+
+use alloy_primitives::{b256, keccak256, Address, Bytes, B256, U256};
+use alloy_rlp::{Decodable, Header};
+
+/// The root of an empty trie, `keccak256(rlp(""))`.
+pub const EMPTY_ROOT: B256 =
+    b256!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// The code hash of accounts without code, `keccak256("")`.
+pub const KECCAK_EMPTY: B256 =
+    b256!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+
+/// The reasons a proof can be invalid, with the index of the offending node in the proof.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum ProofError {
+    /// The hash of the node doesn't match the reference of its parent, or the root.
+    #[error("node {index} doesn't match the hash referenced by its parent")]
+    HashMismatch { index: usize },
+    /// The node can't be decoded.
+    #[error("node {index} is not a valid trie node: {reason}")]
+    InvalidNode { index: usize, reason: String },
+    /// The proof ends before the path of the key is resolved.
+    #[error("the proof is missing node {index}")]
+    MissingNode { index: usize },
+    /// The proof has more nodes than the path of the key.
+    #[error("node {index} is past the end of the path")]
+    UnexpectedNode { index: usize },
+    /// The value proven by the node doesn't match the expected one.
+    #[error("the value proven by node {index} doesn't match")]
+    ValueMismatch { index: usize },
+}
+
+impl ProofError {
+    /// Returns the index of the offending node in the proof.
+    pub fn index(&self) -> usize {
+        match self {
+            Self::HashMismatch { index } |
+            Self::InvalidNode { index, .. } |
+            Self::MissingNode { index } |
+            Self::UnexpectedNode { index } |
+            Self::ValueMismatch { index } => *index,
+        }
+    }
+}
+
+/// An account, as stored in the state trie.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct TrieAccount {
+    pub nonce: u64,
+    pub balance: U256,
+    pub storage_root: B256,
+    pub code_hash: B256,
+}
+
+impl TrieAccount {
+    /// Returns true if the account is empty, i.e. it isn't in the state trie.
+    ///
+    /// Nodes report the storage root and the code hash of empty accounts either as zero or as the
+    /// hashes of empty values.
+    pub fn is_empty(&self) -> bool {
+        self.nonce == 0 &&
+            self.balance.is_zero() &&
+            (self.storage_root.is_zero() || self.storage_root == EMPTY_ROOT) &&
+            (self.code_hash.is_zero() || self.code_hash == KECCAK_EMPTY)
+    }
+}
+
+impl Decodable for TrieAccount {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(alloy_rlp::Error::UnexpectedString)
+        }
+        let remaining = buf.len();
+        let account = Self {
+            nonce: Decodable::decode(buf)?,
+            balance: Decodable::decode(buf)?,
+            storage_root: Decodable::decode(buf)?,
+            code_hash: Decodable::decode(buf)?,
+        };
+        let consumed = remaining - buf.len();
+        if consumed != header.payload_length {
+            return Err(alloy_rlp::Error::ListLengthMismatch {
+                expected: header.payload_length,
+                got: consumed,
+            })
+        }
+        Ok(account)
+    }
+}
+
+/// Verifies the proof of the account against the state root of a block.
+pub fn verify_account_proof(
+    state_root: B256,
+    address: Address,
+    account: &TrieAccount,
+    proof: &[Bytes],
+) -> Result<(), ProofError> {
+    let last = proof.len().saturating_sub(1);
+    let matches = match verify_proof(state_root, keccak256(address).as_slice(), proof)? {
+        Some(value) => {
+            let proven = TrieAccount::decode(&mut value.as_slice())
+                .map_err(|err| ProofError::InvalidNode { index: last, reason: err.to_string() })?;
+            proven == *account
+        }
+        // the account doesn't exist
+        None => account.is_empty(),
+    };
+    if !matches {
+        return Err(ProofError::ValueMismatch { index: last })
+    }
+    Ok(())
+}
+
+/// Verifies the proof of the storage slot against the storage root of its account.
+///
+/// Slots holding zero aren't in the storage trie.
+pub fn verify_storage_proof(
+    storage_root: B256,
+    slot: B256,
+    value: U256,
+    proof: &[Bytes],
+) -> Result<(), ProofError> {
+    let last = proof.len().saturating_sub(1);
+    let proven = match verify_proof(storage_root, keccak256(slot).as_slice(), proof)? {
+        Some(value) => U256::decode(&mut value.as_slice())
+            .map_err(|err| ProofError::InvalidNode { index: last, reason: err.to_string() })?,
+        None => U256::ZERO,
+    };
+    if proven != value {
+        return Err(ProofError::ValueMismatch { index: last })
+    }
+    Ok(())
+}
+
+/// Verifies the proof of the key against the root of a trie.
+///
+/// Returns the value of the key, or `None` if the proof shows that the trie doesn't contain it.
+/// The key is the path in the trie, i.e. the hash of the address or of the storage slot.
+pub fn verify_proof(
+    root: B256,
+    key: &[u8],
+    proof: &[Bytes],
+) -> Result<Option<Vec<u8>>, ProofError> {
+    if root == EMPTY_ROOT && proof.is_empty() {
+        return Ok(None)
+    }
+
+    let path = key.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect::<Vec<_>>();
+    let mut path = path.as_slice();
+    let mut reference = Reference::Hash(root);
+    let mut next = 0;
+    let value = loop {
+        let (node, index) = match reference {
+            Reference::Hash(hash) => {
+                let node = proof.get(next).ok_or(ProofError::MissingNode { index: next })?;
+                if keccak256(node) != hash {
+                    return Err(ProofError::HashMismatch { index: next })
+                }
+                next += 1;
+                (&node[..], next - 1)
+            }
+            // embedded in the previous node
+            Reference::Inline(node) => (node, next - 1),
+        };
+        let invalid = |reason: String| ProofError::InvalidNode { index, reason };
+
+        let items = decode_node(node).map_err(invalid)?;
+        let child = match items.as_slice() {
+            // the empty trie
+            [] => break None,
+            [Item::String(encoded_path), item] => {
+                let (node_path, is_leaf) = decode_path(encoded_path).map_err(invalid)?;
+                if is_leaf {
+                    break match item {
+                        Item::String(value) if path == node_path => Some(value.to_vec()),
+                        Item::List(_) if path == node_path => {
+                            return Err(invalid("leaf value is a list".to_string()))
+                        }
+                        _ => None,
+                    }
+                }
+                match path.strip_prefix(node_path.as_slice()) {
+                    Some(rest) => path = rest,
+                    None => break None,
+                }
+                *item
+            }
+            [children @ .., value] if children.len() == 16 => match path.split_first() {
+                Some((nibble, rest)) => {
+                    path = rest;
+                    children[*nibble as usize]
+                }
+                None => match value {
+                    Item::String([]) => break None,
+                    Item::String(value) => break Some(value.to_vec()),
+                    Item::List(_) => return Err(invalid("branch value is a list".to_string())),
+                },
+            },
+            items => return Err(invalid(format!("unexpected number of items: {}", items.len()))),
+        };
+
+        reference = match child {
+            Item::String([]) => break None,
+            Item::String(hash) if hash.len() == 32 => Reference::Hash(B256::from_slice(hash)),
+            Item::String(_) => return Err(invalid("invalid child reference".to_string())),
+            Item::List(node) => Reference::Inline(node),
+        };
+    };
+
+    if next < proof.len() {
+        return Err(ProofError::UnexpectedNode { index: next })
+    }
+    Ok(value)
+}
+
+/// The reference of a node to a child node.
+enum Reference<'a> {
+    Hash(B256),
+    /// Nodes shorter than 32 bytes are embedded in their parent
+    Inline(&'a [u8]),
+}
+
+/// An item of a trie node.
+#[derive(Clone, Copy)]
+enum Item<'a> {
+    /// The payload of a string
+    String(&'a [u8]),
+    /// The encoding of an embedded node
+    List(&'a [u8]),
+}
+
+/// Decodes the items of a branch, extension or leaf node, the empty trie has no items.
+fn decode_node(node: &[u8]) -> Result<Vec<Item<'_>>, String> {
+    let mut buf = node;
+    let header = Header::decode(&mut buf).map_err(|err| err.to_string())?;
+    if buf.len() != header.payload_length {
+        return Err("trailing bytes".to_string())
+    }
+    if !header.list {
+        return if header.payload_length == 0 {
+            Ok(vec![])
+        } else {
+            Err("expected a list".to_string())
+        }
+    }
+
+    let mut items = vec![];
+    while !buf.is_empty() {
+        let start = buf;
+        let header = Header::decode(&mut buf).map_err(|err| err.to_string())?;
+        let payload = buf.get(..header.payload_length).ok_or("item overflows the node")?;
+        buf = &buf[header.payload_length..];
+        items.push(if header.list {
+            Item::List(&start[..start.len() - buf.len()])
+        } else {
+            Item::String(payload)
+        });
+    }
+    Ok(items)
+}
+
+/// Decodes the hex-prefix encoded path of an extension or leaf node, returns the nibbles and
+/// whether the node is a leaf.
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool), String> {
+    let (first, rest) = encoded.split_first().ok_or("empty path")?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(format!("invalid path flag {flag}"))
+    }
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    } else if first & 0x0f != 0 {
+        return Err("invalid path padding".to_string())
+    }
+    nibbles.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]));
+    Ok((nibbles, flag & 2 == 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, hex};
+
+    // a state trie of 4 accounts and the storage trie of `0x..aa` with slots 0, 1 and 3 set
+    const STATE_ROOT: B256 =
+        b256!("161f76dd745765b7dee8f2b32fe29a05f321b979dd95d533c67667884f8f0b36");
+    const STORAGE_ROOT: B256 =
+        b256!("748626743938d210b2e16eeddb72b1ff1e48fe8facf3f09d73ef677a5fdce3f3");
+    const STATE_BRANCH: &str = "f8918080808080a07256a5da2affc82e7a71ff123378a9e21536d6f27aff4baadb71247543197219808080a0106c01b2ea85fd3b0ab5e2f731b4af928e0ed54a4b1689ecc4617c3fe226fae4a0c06c9b1aa9cd49b06e6dfd584d5fe4d0d89723e0c50b3d0b12384a7283a7bd7c808080a00d47719e907c46dcf166cdf810eac4df4c7063d43091447bee02f42f9dd620588080";
+    const ACCOUNT_LEAF: &str = "f871a0328b55564e8518548e42b534da3a526179b820f264ee7c6929d00b0b6a31cfc2b84ef84c01880de0b6b3a7640000a0748626743938d210b2e16eeddb72b1ff1e48fe8facf3f09d73ef677a5fdce3f3a007ad118d6cc8642c86c03827f276d8b791a65e5c99a3845faf186be720a1455d";
+    const STORAGE_BRANCH: &str = "f8718080a0f73cea67884580eec8c3f6d0746360906cf897bf812183520e51b89a12166cfe8080808080808080a0a321cf1d8b029196f6a2d23e58f149a779f8a5be08587c4ce2c531bb8aba7e31a05ec1ebe3db57e54f3a6efa6800fd3565cf7d6c70f93a9c2a78a271580446c07080808080";
+    const SLOT_0_LEAF: &str =
+        "e2a0390decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e5632a";
+    const SLOT_1_LEAF: &str =
+        "e8a0310e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf686851234567890";
+
+    fn nodes(nodes: &[&str]) -> Vec<Bytes> {
+        nodes.iter().map(|node| hex::decode(node).unwrap().into()).collect()
+    }
+
+    fn account() -> TrieAccount {
+        TrieAccount {
+            nonce: 1,
+            balance: U256::from(10).pow(U256::from(18)),
+            storage_root: STORAGE_ROOT,
+            code_hash: b256!("07ad118d6cc8642c86c03827f276d8b791a65e5c99a3845faf186be720a1455d"),
+        }
+    }
+
+    #[test]
+    fn can_verify_account_proof() {
+        let address = address!("00000000000000000000000000000000000000aa");
+        let proof = nodes(&[STATE_BRANCH, ACCOUNT_LEAF]);
+        verify_account_proof(STATE_ROOT, address, &account(), &proof).unwrap();
+
+        let mut lying = account();
+        lying.balance += U256::from(1);
+        assert_eq!(
+            verify_account_proof(STATE_ROOT, address, &lying, &proof),
+            Err(ProofError::ValueMismatch { index: 1 })
+        );
+
+        let mut tampered = proof.clone();
+        let mut leaf = tampered[1].to_vec();
+        *leaf.last_mut().unwrap() ^= 1;
+        tampered[1] = leaf.into();
+        assert_eq!(
+            verify_account_proof(STATE_ROOT, address, &account(), &tampered),
+            Err(ProofError::HashMismatch { index: 1 })
+        );
+
+        assert_eq!(
+            verify_account_proof(STATE_ROOT, address, &account(), &proof[..1]),
+            Err(ProofError::MissingNode { index: 1 })
+        );
+        assert_eq!(
+            verify_account_proof(B256::ZERO, address, &account(), &proof),
+            Err(ProofError::HashMismatch { index: 0 })
+        );
+    }
+
+    #[test]
+    fn can_verify_absent_account_proof() {
+        let address = address!("00000000000000000000000000000000000000dd");
+        let proof = nodes(&[STATE_BRANCH]);
+        let empty = TrieAccount { code_hash: KECCAK_EMPTY, ..Default::default() };
+        verify_account_proof(STATE_ROOT, address, &empty, &proof).unwrap();
+        assert_eq!(
+            verify_account_proof(STATE_ROOT, address, &account(), &proof),
+            Err(ProofError::ValueMismatch { index: 0 })
+        );
+
+        // the branch doesn't lead to the account
+        assert_eq!(
+            verify_account_proof(
+                STATE_ROOT,
+                address,
+                &empty,
+                &nodes(&[STATE_BRANCH, ACCOUNT_LEAF])
+            ),
+            Err(ProofError::UnexpectedNode { index: 1 })
+        );
+    }
+
+    #[test]
+    fn can_verify_storage_proofs() {
+        let slot = |slot: u64| B256::from(U256::from(slot));
+        verify_storage_proof(
+            STORAGE_ROOT,
+            slot(0),
+            U256::from(42),
+            &nodes(&[STORAGE_BRANCH, SLOT_0_LEAF]),
+        )
+        .unwrap();
+        verify_storage_proof(
+            STORAGE_ROOT,
+            slot(1),
+            U256::from(0x1234567890u64),
+            &nodes(&[STORAGE_BRANCH, SLOT_1_LEAF]),
+        )
+        .unwrap();
+
+        // unset slots hold zero
+        verify_storage_proof(STORAGE_ROOT, slot(9), U256::ZERO, &nodes(&[STORAGE_BRANCH])).unwrap();
+        assert_eq!(
+            verify_storage_proof(STORAGE_ROOT, slot(9), U256::from(1), &nodes(&[STORAGE_BRANCH])),
+            Err(ProofError::ValueMismatch { index: 0 })
+        );
+
+        // the leaf of another slot
+        assert_eq!(
+            verify_storage_proof(
+                STORAGE_ROOT,
+                slot(0),
+                U256::from(42),
+                &nodes(&[STORAGE_BRANCH, SLOT_1_LEAF])
+            ),
+            Err(ProofError::HashMismatch { index: 1 })
+        );
+
+        // empty storage tries
+        verify_storage_proof(EMPTY_ROOT, slot(0), U256::ZERO, &[]).unwrap();
+        verify_storage_proof(EMPTY_ROOT, slot(0), U256::ZERO, &nodes(&["80"])).unwrap();
+    }
+}
@@ -10,7 +10,7 @@ use alloy_transport::BoxTransport;
 use ethers_middleware::gas_oracle::{GasCategory, GasOracle, Polygon};
 use eyre::{Result, WrapErr};
 use foundry_common::types::ToAlloy;
-use foundry_config::NamedChain;
+use foundry_config::{NamedChain, RpcConfig};
 use reqwest::Url;
 use std::{
     path::{Path, PathBuf},
@@ -70,6 +70,8 @@ pub struct ProviderBuilder {
     /// JWT Secret
     jwt: Option<String>,
     headers: Vec<String>,
+    /// The retry settings of the `[rpc]` section
+    rpc_config: RpcConfig,
 }
 
 // === impl ProviderBuilder ===
@@ -114,6 +116,7 @@ impl ProviderBuilder {
             compute_units_per_second: ALCHEMY_FREE_TIER_CUPS,
             jwt: None,
             headers: vec![],
+            rpc_config: Default::default(),
         }
     }
 
@@ -183,6 +186,13 @@ impl ProviderBuilder {
         self
     }
 
+    /// Sets the maximum backoff, the jitter and the retry budgets of methods of the `[rpc]`
+    /// section
+    pub fn rpc_config(mut self, rpc_config: &RpcConfig) -> Self {
+        self.rpc_config = rpc_config.clone();
+        self
+    }
+
     /// Sets aggressive `max_retry` and `initial_backoff` values
     ///
     /// This is only recommend for local dev nodes
@@ -227,6 +237,7 @@ impl ProviderBuilder {
             compute_units_per_second,
             jwt,
             headers,
+            rpc_config,
         } = self;
         let url = url?;
 
@@ -235,7 +246,10 @@ impl ProviderBuilder {
             timeout_retry,
             initial_backoff,
             compute_units_per_second,
-        );
+        )
+        .max_backoff(rpc_config.max_backoff)
+        .jitter(rpc_config.jitter)
+        .method_retries(rpc_config.method_retries);
         let transport = RuntimeTransportBuilder::new(url.clone())
             .with_timeout(timeout)
             .with_headers(headers)
@@ -1,7 +1,11 @@
 //! An utility trait for retrying requests based on the error type. See [TransportError].
+use super::runtime_transport::HttpError;
 use alloy_json_rpc::ErrorPayload;
-use alloy_transport::TransportError;
+use alloy_transport::{TransportError, TransportErrorKind};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::Deserialize;
+use std::time::Duration;
 
 /// [RetryPolicy] defines logic for which [JsonRpcClient::Error] instances should
 /// the client retry the request and try to recover from.
@@ -9,6 +13,15 @@ pub trait RetryPolicy: Send + Sync + std::fmt::Debug {
     /// Whether to retry the request based on the given `error`
     fn should_retry(&self, error: &TransportError) -> bool;
 
+    /// Whether the provider rejected the request because of a rate limit, i.e. it's certain the
+    /// request wasn't executed and can be retried even if it changes the state of the node.
+    fn is_rate_limited(&self, error: &TransportError) -> bool;
+
+    /// Whether the provider is temporarily unavailable, e.g. it responded with `503 Service
+    /// Unavailable`. The request is retried with its own backoff, but it's not a rate limit as it's
+    /// unknown whether the request was received.
+    fn is_unavailable(&self, error: &TransportError) -> bool;
+
     /// Providers may include the `backoff` in the error response directly
     fn backoff_hint(&self, error: &TransportError) -> Option<std::time::Duration>;
 }
@@ -29,45 +42,127 @@ impl RetryPolicy for RateLimitRetryPolicy {
             // the start.
             TransportError::SerError(_) => false,
             TransportError::DeserError { text, .. } => {
-                // some providers send invalid JSON RPC in the error case (no `id:u64`), but the
-                // text should be a `JsonRpcError`
-                #[derive(Deserialize)]
-                struct Resp {
-                    error: ErrorPayload,
-                }
-
-                if let Ok(resp) = serde_json::from_str::<Resp>(text) {
-                    return should_retry_json_rpc_error(&resp.error)
-                }
-                false
+                json_rpc_error(text).map_or(false, |error| should_retry_json_rpc_error(&error))
             }
             TransportError::ErrorResp(err) => should_retry_json_rpc_error(err),
         }
     }
 
-    fn backoff_hint(&self, error: &TransportError) -> Option<std::time::Duration> {
-        if let TransportError::ErrorResp(resp) = error {
-            let data = resp.try_data_as::<serde_json::Value>();
-            if let Some(Ok(data)) = data {
-                // if daily rate limit exceeded, infura returns the requested backoff in the error
-                // response
-                let backoff_seconds = &data["rate"]["backoff_seconds"];
-                // infura rate limit error
-                if let Some(seconds) = backoff_seconds.as_u64() {
-                    return Some(std::time::Duration::from_secs(seconds))
-                }
-                if let Some(seconds) = backoff_seconds.as_f64() {
-                    return Some(std::time::Duration::from_secs(seconds as u64 + 1))
-                }
+    fn is_rate_limited(&self, error: &TransportError) -> bool {
+        match error {
+            TransportError::Transport(kind) => http_error(kind).map_or(false, |error| {
+                error.status == 429 ||
+                    json_rpc_error(&error.body)
+                        .map_or(false, |error| is_rate_limit_json_rpc_error(&error))
+            }),
+            TransportError::SerError(_) => false,
+            TransportError::DeserError { text, .. } => {
+                json_rpc_error(text).map_or(false, |error| is_rate_limit_json_rpc_error(&error))
+            }
+            TransportError::ErrorResp(err) => is_rate_limit_json_rpc_error(err),
+        }
+    }
+
+    fn is_unavailable(&self, error: &TransportError) -> bool {
+        match error {
+            TransportError::Transport(kind) => {
+                http_error(kind).map_or(false, |error| error.status == 503)
+            }
+            _ => false,
+        }
+    }
+
+    fn backoff_hint(&self, error: &TransportError) -> Option<Duration> {
+        match error {
+            TransportError::Transport(kind) => {
+                let error = http_error(kind)?;
+                error.retry_after.or_else(|| json_rpc_error(&error.body)?.backoff_hint())
+            }
+            TransportError::DeserError { text, .. } => json_rpc_error(text)?.backoff_hint(),
+            TransportError::ErrorResp(resp) => resp.backoff_hint(),
+            TransportError::SerError(_) => None,
+        }
+    }
+}
+
+/// Returns the HTTP error the request failed with, if it did.
+fn http_error(kind: &TransportErrorKind) -> Option<&HttpError> {
+    match kind {
+        TransportErrorKind::Custom(err) => err.downcast_ref::<HttpError>(),
+        _ => None,
+    }
+}
+
+/// Parses the JSON-RPC error of a response body.
+///
+/// Some providers send invalid JSON RPC in the error case (no `id:u64`), but the text should be a
+/// `JsonRpcError`.
+fn json_rpc_error(text: &str) -> Option<ErrorPayload> {
+    #[derive(Deserialize)]
+    struct Resp {
+        error: ErrorPayload,
+    }
+
+    serde_json::from_str::<Resp>(text).ok().map(|resp| resp.error)
+}
+
+/// Extracts the backoff providers request in their rate limit errors.
+trait BackoffHint {
+    fn backoff_hint(&self) -> Option<Duration>;
+}
+
+impl BackoffHint for ErrorPayload {
+    fn backoff_hint(&self) -> Option<Duration> {
+        static RE_RETRY_IN: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(
+                r"(?i)(?:retry|try again)\s+(?:after|in)\s+(\d+(?:\.\d+)?)\s*(ms|s|sec|seconds?)\b",
+            )
+            .unwrap()
+        });
+
+        if let Some(Ok(data)) = self.try_data_as::<serde_json::Value>() {
+            // if daily rate limit exceeded, infura returns the requested backoff in the error
+            // response
+            let backoff_seconds = &data["rate"]["backoff_seconds"];
+            // infura rate limit error
+            if let Some(seconds) = backoff_seconds.as_u64() {
+                return Some(Duration::from_secs(seconds))
+            }
+            if let Some(seconds) = backoff_seconds.as_f64() {
+                return Some(Duration::from_secs(seconds as u64 + 1))
             }
         }
-        None
+
+        // e.g. `Too many requests, please try again in 2s`
+        let cap = RE_RETRY_IN.captures(&self.message)?;
+        let value: f64 = cap[1].parse().ok()?;
+        let millis = if &cap[2] == "ms" { value } else { value * 1000.0 };
+        Some(Duration::from_millis(millis.ceil() as u64))
     }
 }
 
+/// Returns true if the JSON-RPC method changes the state of the node, so that a request that may
+/// have been received already must not be sent again.
+pub fn is_state_changing_method(method: &str) -> bool {
+    matches!(
+        method,
+        "eth_sendRawTransaction" |
+            "eth_sendTransaction" |
+            "eth_sendBundle" |
+            "eth_sendPrivateTransaction" |
+            "personal_sendTransaction"
+    )
+}
+
 /// Analyzes the [ErrorPayload] and decides if the request should be retried based on the
 /// error code or the message.
 fn should_retry_json_rpc_error(error: &ErrorPayload) -> bool {
+    // this is commonly thrown by infura and is apparently a load balancer issue, see also <https://github.com/MetaMask/metamask-extension/issues/7234>
+    error.message == "header not found" || is_rate_limit_json_rpc_error(error)
+}
+
+/// Returns true if the [ErrorPayload] is a rate limit error of one of the major providers.
+fn is_rate_limit_json_rpc_error(error: &ErrorPayload) -> bool {
     let ErrorPayload { code, message, .. } = error;
     // alchemy throws it this way
     if *code == 429 {
@@ -91,15 +186,81 @@ fn should_retry_json_rpc_error(error: &ErrorPayload) -> bool {
     }
 
     match message.as_str() {
-        // this is commonly thrown by infura and is apparently a load balancer issue, see also <https://github.com/MetaMask/metamask-extension/issues/7234>
-        "header not found" => true,
         // also thrown by infura if out of budget for the day and ratelimited
         "daily request count exceeded, request rate limited" => true,
         msg => {
+            let msg = msg.to_lowercase();
             msg.contains("rate limit") ||
                 msg.contains("rate exceeded") ||
                 msg.contains("too many requests") ||
-                msg.contains("request limit")
+                msg.contains("request limit") ||
+                msg.contains("compute units per second")
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(code: i64, message: &str) -> ErrorPayload {
+        ErrorPayload { code, message: message.to_string(), data: None }
+    }
+
+    #[test]
+    fn parses_backoff_hints() {
+        let hint = |message| error(429, message).backoff_hint();
+        assert_eq!(hint("Too many requests, please try again in 2s"), Some(Duration::from_secs(2)));
+        assert_eq!(hint("rate limited, retry after 250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(hint("Retry in 1.5 seconds"), Some(Duration::from_millis(1500)));
+        assert_eq!(hint("Your app has exceeded its compute units per second capacity"), None);
+
+        let infura: ErrorPayload = serde_json::from_str(
+            r#"{"code":-32005,"message":"daily request count exceeded, request rate limited","data":{"rate":{"backoff_seconds":30}}}"#,
+        )
+        .unwrap();
+        assert_eq!(infura.backoff_hint(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn distinguishes_rate_limits_from_ambiguous_errors() {
+        let policy = RateLimitRetryPolicy;
+
+        let rate_limited = TransportError::ErrorResp(error(-32005, "project rate limit exceeded"));
+        assert!(policy.should_retry(&rate_limited));
+        assert!(policy.is_rate_limited(&rate_limited));
+
+        let header_not_found = TransportError::ErrorResp(error(-32000, "header not found"));
+        assert!(policy.should_retry(&header_not_found));
+        assert!(!policy.is_rate_limited(&header_not_found));
+
+        let reverted = TransportError::ErrorResp(error(3, "execution reverted"));
+        assert!(!policy.should_retry(&reverted));
+
+        let too_many = TransportErrorKind::custom(HttpError {
+            status: 429,
+            retry_after: Some(Duration::from_secs(3)),
+            body: "Too Many Requests".to_string(),
+        });
+        assert!(policy.is_rate_limited(&too_many));
+        assert_eq!(policy.backoff_hint(&too_many), Some(Duration::from_secs(3)));
+
+        let bad_gateway = TransportErrorKind::custom(HttpError {
+            status: 502,
+            retry_after: None,
+            body: String::new(),
+        });
+        assert!(policy.should_retry(&bad_gateway));
+        assert!(!policy.is_rate_limited(&bad_gateway));
+        assert!(!policy.is_unavailable(&bad_gateway));
+
+        let unavailable = TransportErrorKind::custom(HttpError {
+            status: 503,
+            retry_after: Some(Duration::from_secs(10)),
+            body: "Service Unavailable".to_string(),
+        });
+        assert!(policy.is_unavailable(&unavailable));
+        assert!(!policy.is_rate_limited(&unavailable));
+        assert_eq!(policy.backoff_hint(&unavailable), Some(Duration::from_secs(10)));
+    }
+}
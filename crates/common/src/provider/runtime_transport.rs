@@ -6,12 +6,19 @@ use alloy_pubsub::{PubSubConnect, PubSubFrontend};
 use alloy_transport::{
     Authorization, BoxTransport, TransportError, TransportErrorKind, TransportFut,
 };
-use alloy_transport_http::Http;
 use alloy_transport_ipc::IpcConnect;
 use alloy_transport_ws::WsConnect;
 use ethers_providers::{JwtAuth, JwtKey};
-use reqwest::header::{HeaderName, HeaderValue};
-use std::{path::PathBuf, str::FromStr, sync::Arc};
+use reqwest::{
+    header::{HeaderName, HeaderValue, CONTENT_TYPE, RETRY_AFTER},
+    StatusCode,
+};
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 use thiserror::Error;
 use tokio::sync::RwLock;
 use tower::Service;
@@ -22,7 +29,7 @@ use url::Url;
 #[derive(Clone, Debug)]
 pub enum InnerTransport {
     /// HTTP transport
-    Http(Http<reqwest::Client>),
+    Http(reqwest::Client),
     /// WebSocket transport
     Ws(PubSubFrontend),
     // TODO: IPC
@@ -62,6 +69,18 @@ pub enum RuntimeTransportError {
     InvalidJwt(String),
 }
 
+/// A response of an HTTP endpoint with an error status, e.g. `429 Too Many Requests`
+#[derive(Error, Debug)]
+#[error("HTTP error {status} with body: {body}")]
+pub struct HttpError {
+    /// The status code of the response
+    pub status: u16,
+    /// The backoff requested by the `Retry-After` header of the response
+    pub retry_after: Option<Duration>,
+    /// The body of the response
+    pub body: String,
+}
+
 /// A runtime transport is a custom [alloy_transport::Transport] that only connects when the *first*
 /// request is made. When the first request is made, it will connect to the runtime using either an
 /// HTTP WebSocket, or IPC transport depending on the URL used.
@@ -143,7 +162,7 @@ impl RuntimeTransport {
         }
     }
 
-    /// Connects to an HTTP [alloy_transport_http::Http] transport.
+    /// Connects to an HTTP transport.
     async fn connect_http(&self) -> Result<InnerTransport, RuntimeTransportError> {
         let mut client_builder = reqwest::Client::builder().timeout(self.timeout);
         let mut headers = reqwest::header::HeaderMap::new();
@@ -177,8 +196,7 @@ impl RuntimeTransport {
         let client =
             client_builder.build().map_err(RuntimeTransportError::HttpConstructionError)?;
 
-        // todo: retry tower layer
-        Ok(InnerTransport::Http(Http::with_client(client, self.url.clone())))
+        Ok(InnerTransport::Http(client))
     }
 
     /// Connects to a WS transport.
@@ -220,12 +238,14 @@ impl RuntimeTransport {
             // SAFETY: We just checked that the inner transport exists.
             let inner_mut = inner.as_mut().expect("We should have an inner transport.");
 
-            match inner_mut {
-                InnerTransport::Http(http) => http.call(req),
+            let fut: TransportFut<'static> = match inner_mut {
+                InnerTransport::Http(client) => {
+                    Box::pin(request_http(client.clone(), this.url.clone(), req))
+                }
                 InnerTransport::Ws(ws) => ws.call(req),
                 InnerTransport::Ipc(ipc) => ipc.call(req),
-            }
-            .await
+            };
+            fut.await
         })
     }
 
@@ -276,6 +296,53 @@ impl Service<RequestPacket> for &RuntimeTransport {
     }
 }
 
+/// Sends a request to an HTTP endpoint.
+///
+/// Unlike the HTTP transport of alloy, responses with a rate limit or server error status are
+/// returned as an [HttpError], which keeps the status and the `Retry-After` header for the retries.
+async fn request_http(
+    client: reqwest::Client,
+    url: Url,
+    req: RequestPacket,
+) -> Result<ResponsePacket, TransportError> {
+    let body = serde_json::to_vec(&req).map_err(TransportError::ser_err)?;
+    let resp = client
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(TransportErrorKind::custom)?;
+    let status = resp.status();
+    let retry_after = resp
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after);
+    let body = resp.text().await.map_err(TransportErrorKind::custom)?;
+
+    // JSON-RPC errors can come with any other status
+    if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        return Err(TransportErrorKind::custom(HttpError {
+            status: status.as_u16(),
+            retry_after,
+            body,
+        }))
+    }
+    serde_json::from_str(&body).map_err(|err| TransportError::deser_err(err, &body))
+}
+
+/// Parses the value of a `Retry-After` header, either the number of seconds to wait or the date
+/// to retry at.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds))
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
 fn build_auth(jwt: String) -> eyre::Result<Authorization> {
     // Decode jwt from hex, then generate claims (iat with current timestamp)
     let jwt = hex::decode(jwt)?;
@@ -308,3 +375,17 @@ fn url_to_file_path(url: &Url) -> Result<PathBuf, ()> {
 fn url_to_file_path(url: &Url) -> Result<PathBuf, ()> {
     url.to_file_path()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        let later = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(60));
+        assert!(parse_retry_after(&later).unwrap() > Duration::from_secs(50));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
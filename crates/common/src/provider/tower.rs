@@ -1,21 +1,79 @@
 //! Alloy-related tower middleware for retrying rate-limited requests
 //! and applying backoff.
 use std::{
+    collections::BTreeMap,
+    fmt,
     sync::{
-        atomic::{AtomicU32, Ordering},
+        atomic::{AtomicU32, AtomicU64, Ordering},
         Arc,
     },
     task::{Context, Poll},
+    time::Duration,
 };
 
 use alloy_json_rpc::{RequestPacket, ResponsePacket};
 use alloy_transport::{TransportError, TransportErrorKind, TransportFut};
+use once_cell::sync::Lazy;
+use rand::Rng;
 use tower::Service;
 
-use super::{
-    retry::{RateLimitRetryPolicy, RetryPolicy},
-    runtime_transport::RuntimeTransport,
-};
+use super::retry::{is_state_changing_method, RateLimitRetryPolicy, RetryPolicy};
+
+/// The retry counters of all the providers that don't have their own
+static RETRY_STATS: Lazy<Arc<RetryStats>> = Lazy::new(Default::default);
+
+/// Counters of the retries of a [RetryBackoffService]
+#[derive(Debug, Default)]
+pub struct RetryStats {
+    /// The number of retried requests
+    retries: AtomicU64,
+    /// The number of retries because of a rate limit
+    rate_limited: AtomicU64,
+    /// The total time spent backing off, in milliseconds
+    backoff_millis: AtomicU64,
+}
+
+impl RetryStats {
+    /// Returns the counters shared by all providers, see [RetryBackoffLayer::new].
+    pub fn global() -> Arc<Self> {
+        RETRY_STATS.clone()
+    }
+
+    /// Returns the number of retried requests.
+    pub fn retries(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of retries because the provider rate limited a request.
+    pub fn rate_limited(&self) -> u64 {
+        self.rate_limited.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total time spent backing off before the retries.
+    pub fn backoff(&self) -> Duration {
+        Duration::from_millis(self.backoff_millis.load(Ordering::Relaxed))
+    }
+
+    fn record(&self, rate_limited: bool, backoff: Duration) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+        if rate_limited {
+            self.rate_limited.fetch_add(1, Ordering::Relaxed);
+        }
+        self.backoff_millis.fetch_add(backoff.as_millis() as u64, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Display for RetryStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} RPC retries ({} rate limited), {:.1?} spent backing off",
+            self.retries(),
+            self.rate_limited(),
+            self.backoff()
+        )
+    }
+}
 
 /// An Alloy Tower Layer that is responsible for retrying requests based on the
 /// error type. See [TransportError].
@@ -29,10 +87,21 @@ pub struct RetryBackoffLayer {
     initial_backoff: u64,
     /// The number of compute units per second for this provider
     compute_units_per_second: u64,
+    /// The maximum backoff in milliseconds
+    max_backoff: u64,
+    /// Whether to add a random jitter to the backoff
+    jitter: bool,
+    /// The maximum number of retries by method, overriding the other maximums
+    method_retries: Arc<BTreeMap<String, u32>>,
+    /// The counters of the retries
+    stats: Arc<RetryStats>,
 }
 
 impl RetryBackoffLayer {
     /// Creates a new [RetryWithPolicyLayer] with the given parameters
+    ///
+    /// The backoff is capped at 30 seconds and jittered, the retries are counted in
+    /// [RetryStats::global].
     pub fn new(
         max_rate_limit_retries: u32,
         max_timeout_retries: u32,
@@ -44,8 +113,36 @@ impl RetryBackoffLayer {
             max_timeout_retries,
             initial_backoff,
             compute_units_per_second,
+            max_backoff: 30_000,
+            jitter: true,
+            method_retries: Default::default(),
+            stats: RetryStats::global(),
         }
     }
+
+    /// Sets the maximum backoff in milliseconds
+    pub fn max_backoff(mut self, max_backoff: u64) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets whether to add a random jitter of up to half the backoff
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the maximum number of retries of some methods
+    pub fn method_retries(mut self, method_retries: BTreeMap<String, u32>) -> Self {
+        self.method_retries = Arc::new(method_retries);
+        self
+    }
+
+    /// Sets the counters the retries are recorded in
+    pub fn stats(mut self, stats: Arc<RetryStats>) -> Self {
+        self.stats = stats;
+        self
+    }
 }
 
 impl<S> tower::layer::Layer<S> for RetryBackoffLayer {
@@ -59,6 +156,10 @@ impl<S> tower::layer::Layer<S> for RetryBackoffLayer {
             max_timeout_retries: self.max_timeout_retries,
             initial_backoff: self.initial_backoff,
             compute_units_per_second: self.compute_units_per_second,
+            max_backoff: self.max_backoff,
+            jitter: self.jitter,
+            method_retries: self.method_retries.clone(),
+            stats: self.stats.clone(),
             requests_enqueued: Arc::new(AtomicU32::new(0)),
         }
     }
@@ -80,12 +181,62 @@ pub struct RetryBackoffService<S> {
     initial_backoff: u64,
     /// The number of compute units per second for this service
     compute_units_per_second: u64,
+    /// The maximum backoff in milliseconds
+    max_backoff: u64,
+    /// Whether to add a random jitter to the backoff
+    jitter: bool,
+    /// The maximum number of retries by method, overriding the other maximums
+    method_retries: Arc<BTreeMap<String, u32>>,
+    /// The counters of the retries
+    stats: Arc<RetryStats>,
     /// The number of requests currently enqueued
     requests_enqueued: Arc<AtomicU32>,
 }
 
+impl<S> RetryBackoffService<S> {
+    /// Returns the maximum number of retries of the request for the two kinds of errors, the
+    /// lowest of its methods if it's a batch.
+    fn max_retries(&self, request: &RequestPacket) -> (u32, u32) {
+        let methods = match request {
+            RequestPacket::Single(req) => vec![req.method()],
+            RequestPacket::Batch(reqs) => reqs.iter().map(|req| req.method()).collect(),
+        };
+        let mut max_retries = (self.max_rate_limit_retries, self.max_timeout_retries);
+        for method in methods {
+            if let Some(retries) = self.method_retries.get(method) {
+                max_retries = (max_retries.0.min(*retries), max_retries.1.min(*retries));
+            }
+            // a transaction that may have been received must not be sent twice
+            if is_state_changing_method(method) {
+                max_retries.1 = 0;
+            }
+        }
+        max_retries
+    }
+
+    /// Returns the exponential backoff before the given retry, from `initial_backoff` up to
+    /// `max_backoff`, with a random jitter of up to half of it if enabled.
+    fn exponential_backoff(&self, retry: u32) -> Duration {
+        let factor = 1u64.checked_shl(retry.saturating_sub(1)).unwrap_or(u64::MAX);
+        let backoff = self.initial_backoff.saturating_mul(factor).min(self.max_backoff);
+        let jitter = if self.jitter && backoff > 1 {
+            rand::thread_rng().gen_range(0..=backoff / 2)
+        } else {
+            0
+        };
+        Duration::from_millis(backoff + jitter)
+    }
+}
+
 // impl tower service
-impl Service<RequestPacket> for RetryBackoffService<RuntimeTransport> {
+impl<S> Service<RequestPacket> for RetryBackoffService<S>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+{
     type Response = ResponsePacket;
     type Error = TransportError;
     type Future = TransportFut<'static>;
@@ -100,8 +251,10 @@ impl Service<RequestPacket> for RetryBackoffService<RuntimeTransport> {
         let mut this = self.clone();
         Box::pin(async move {
             let ahead_in_queue = this.requests_enqueued.fetch_add(1, Ordering::SeqCst) as u64;
-            let mut rate_limit_retry_number: u32 = 0;
+            let (max_rate_limit_retries, max_timeout_retries) = this.max_retries(&request);
+            let mut retries: u32 = 0;
             let mut timeout_retries: u32 = 0;
+            let mut unavailable_retries: u32 = 0;
             loop {
                 let err;
                 let fut = this.inner.call(request.clone()).await;
@@ -118,54 +271,74 @@ impl Service<RequestPacket> for RetryBackoffService<RuntimeTransport> {
                     Err(e) => err = e,
                 }
 
-                let should_retry = this.policy.should_retry(&err);
-                if should_retry {
-                    rate_limit_retry_number += 1;
-                    if rate_limit_retry_number > this.max_rate_limit_retries {
+                // requests that were rejected by the provider are always safe to retry, other
+                // errors like timeouts leave it unknown whether the request was received
+                let rate_limited = this.policy.is_rate_limited(&err);
+                // an unavailable provider may have received the request, so it shares the budget
+                // of the timeouts, with its own backoff
+                let unavailable = !rate_limited && this.policy.is_unavailable(&err);
+                let retry = if rate_limited {
+                    retries < max_rate_limit_retries
+                } else if unavailable {
+                    unavailable_retries < max_timeout_retries
+                } else {
+                    this.policy.should_retry(&err) && timeout_retries < max_timeout_retries
+                };
+                if !retry {
+                    this.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
+                    if rate_limited {
                         return Err(TransportErrorKind::custom_str("Max retries exceeded"))
                     }
-                    trace!("retrying request due to {:?}", err);
-
-                    let current_queued_reqs = this.requests_enqueued.load(Ordering::SeqCst) as u64;
-
-                    // try to extract the requested backoff from the error or compute the next
-                    // backoff based on retry count
-                    let backoff_hint = this.policy.backoff_hint(&err);
-                    let next_backoff = backoff_hint
-                        .unwrap_or_else(|| std::time::Duration::from_millis(this.initial_backoff));
-
-                    // requests are usually weighted and can vary from 10 CU to several 100 CU,
-                    // cheaper requests are more common some example alchemy
-                    // weights:
-                    // - `eth_getStorageAt`: 17
-                    // - `eth_getBlockByNumber`: 16
-                    // - `eth_newFilter`: 20
-                    //
-                    // (coming from forking mode) assuming here that storage request will be the
-                    // driver for Rate limits we choose `17` as the average cost
-                    // of any request
-                    const AVG_COST: u64 = 17u64;
-                    let seconds_to_wait_for_compute_budget = compute_unit_offset_in_secs(
+                    return Err(err)
+                }
+                if rate_limited {
+                    retries += 1;
+                } else if unavailable {
+                    unavailable_retries += 1;
+                } else {
+                    timeout_retries += 1;
+                }
+                trace!("retrying request due to {:?}", err);
+
+                let current_queued_reqs = this.requests_enqueued.load(Ordering::SeqCst) as u64;
+
+                // try to extract the requested backoff from the error or compute the next
+                // backoff based on retry count
+                let backoff_hint = this.policy.backoff_hint(&err);
+                let next_backoff = backoff_hint.unwrap_or_else(|| {
+                    let retry =
+                        if unavailable { unavailable_retries } else { retries + timeout_retries };
+                    this.exponential_backoff(retry)
+                });
+
+                // requests are usually weighted and can vary from 10 CU to several 100 CU,
+                // cheaper requests are more common some example alchemy
+                // weights:
+                // - `eth_getStorageAt`: 17
+                // - `eth_getBlockByNumber`: 16
+                // - `eth_newFilter`: 20
+                //
+                // (coming from forking mode) assuming here that storage request will be the
+                // driver for Rate limits we choose `17` as the average cost
+                // of any request
+                const AVG_COST: u64 = 17u64;
+                let seconds_to_wait_for_compute_budget = if rate_limited {
+                    compute_unit_offset_in_secs(
                         AVG_COST,
                         this.compute_units_per_second,
                         current_queued_reqs,
                         ahead_in_queue,
-                    );
-                    let total_backoff = next_backoff +
-                        std::time::Duration::from_secs(seconds_to_wait_for_compute_budget);
-
-                    trace!(?total_backoff, budget_backoff = ?seconds_to_wait_for_compute_budget, default_backoff = ?next_backoff, ?backoff_hint, "backing off due to rate limit");
-
-                    tokio::time::sleep(total_backoff).await;
+                    )
                 } else {
-                    if timeout_retries < this.max_timeout_retries {
-                        timeout_retries += 1;
-                        continue;
-                    }
+                    0
+                };
+                let total_backoff =
+                    next_backoff + Duration::from_secs(seconds_to_wait_for_compute_budget);
 
-                    this.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
-                    return Err(err)
-                }
+                trace!(?total_backoff, budget_backoff = ?seconds_to_wait_for_compute_budget, default_backoff = ?next_backoff, ?backoff_hint, "backing off due to rate limit");
+
+                this.stats.record(rate_limited, total_backoff);
+                tokio::time::sleep(total_backoff).await;
             }
         })
     }
@@ -195,3 +368,180 @@ fn compute_unit_offset_in_secs(
         0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::runtime_transport::HttpError;
+    use alloy_json_rpc::{Id, Request};
+    use std::{collections::VecDeque, sync::Mutex};
+    use tower::Layer;
+
+    const OK: &str = r#"{"jsonrpc":"2.0","id":1,"result":"0x1"}"#;
+
+    /// A transport that returns the queued responses and counts the requests
+    #[derive(Clone, Default)]
+    struct MockTransport {
+        responses: Arc<Mutex<VecDeque<Result<ResponsePacket, TransportError>>>>,
+        requests: Arc<AtomicU32>,
+    }
+
+    impl MockTransport {
+        fn push_json(&self, json: &str) -> &Self {
+            self.responses.lock().unwrap().push_back(Ok(serde_json::from_str(json).unwrap()));
+            self
+        }
+
+        fn push_status(&self, status: u16, retry_after: Option<u64>) -> &Self {
+            let body = if status == 503 { "Service Unavailable" } else { "Too Many Requests" };
+            let err = TransportErrorKind::custom(HttpError {
+                status,
+                retry_after: retry_after.map(Duration::from_secs),
+                body: body.to_string(),
+            });
+            self.responses.lock().unwrap().push_back(Err(err));
+            self
+        }
+
+        fn requests(&self) -> u32 {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+
+    impl Service<RequestPacket> for MockTransport {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: RequestPacket) -> Self::Future {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            let res = self.responses.lock().unwrap().pop_front().expect("no response queued");
+            Box::pin(async move { res })
+        }
+    }
+
+    fn request(method: &'static str) -> RequestPacket {
+        Request::new(method, Id::Number(1), ()).serialize().unwrap().into()
+    }
+
+    fn layer(stats: &Arc<RetryStats>) -> RetryBackoffLayer {
+        RetryBackoffLayer::new(3, 3, 1000, 330).jitter(false).stats(stats.clone())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn respects_retry_after() {
+        let transport = MockTransport::default();
+        transport.push_status(429, Some(5)).push_json(OK);
+        let stats = Arc::default();
+        let mut service = layer(&stats).layer(transport.clone());
+
+        assert!(service.call(request("eth_getStorageAt")).await.is_ok());
+        assert_eq!(transport.requests(), 2);
+        assert_eq!(stats.retries(), 1);
+        assert_eq!(stats.rate_limited(), 1);
+        assert_eq!(stats.backoff(), Duration::from_secs(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_unavailable_service_without_rate_limit() {
+        let transport = MockTransport::default();
+        transport.push_status(503, Some(2)).push_status(503, None).push_json(OK);
+        let stats = Arc::default();
+        let mut service = layer(&stats).layer(transport.clone());
+
+        assert!(service.call(request("eth_getStorageAt")).await.is_ok());
+        assert_eq!(stats.retries(), 2);
+        assert_eq!(stats.rate_limited(), 0);
+        // the `Retry-After` of the response, then the first exponential backoff
+        assert_eq!(stats.backoff(), Duration::from_secs(3));
+
+        // the error of the service is returned once the retries are exhausted
+        for _ in 0..4 {
+            transport.push_status(503, None);
+        }
+        let err = service.call(request("eth_getStorageAt")).await.unwrap_err();
+        assert!(err.to_string().contains("HTTP error 503"), "{err}");
+        assert_eq!(transport.requests(), 7);
+
+        // a transaction the service may have received is not sent again
+        transport.push_status(503, None);
+        assert!(service.call(request("eth_sendRawTransaction")).await.is_err());
+        assert_eq!(transport.requests(), 8);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn backs_off_exponentially() {
+        let transport = MockTransport::default();
+        transport
+            .push_status(429, None)
+            .push_status(429, None)
+            .push_status(429, None)
+            .push_json(OK);
+        let stats = Arc::default();
+        let mut service = layer(&stats).max_backoff(3000).layer(transport.clone());
+
+        assert!(service.call(request("eth_getBalance")).await.is_ok());
+        assert_eq!(stats.retries(), 3);
+        // 1s, 2s, then capped at 3s
+        assert_eq!(stats.backoff(), Duration::from_secs(6));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn uses_backoff_of_rate_limit_error() {
+        let transport = MockTransport::default();
+        transport
+            .push_json(
+                r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32005,"message":"project rate limit exceeded","data":{"rate":{"backoff_seconds":2}}}}"#,
+            )
+            .push_json(OK);
+        let stats = Arc::default();
+        let mut service = layer(&stats).layer(transport.clone());
+
+        assert!(service.call(request("eth_getCode")).await.is_ok());
+        assert_eq!(stats.backoff(), Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_resend_transactions_after_ambiguous_errors() {
+        let transport = MockTransport::default();
+        transport.push_status(502, None);
+        let stats = Arc::default();
+        let mut service = layer(&stats).layer(transport.clone());
+
+        assert!(service.call(request("eth_sendRawTransaction")).await.is_err());
+        assert_eq!(transport.requests(), 1);
+        assert_eq!(stats.retries(), 0);
+
+        // rejected transactions are retried
+        transport.push_status(429, None).push_json(OK);
+        assert!(service.call(request("eth_sendRawTransaction")).await.is_ok());
+        assert_eq!(transport.requests(), 3);
+
+        // reads are retried after ambiguous errors
+        transport.push_status(502, None).push_json(OK);
+        assert!(service.call(request("eth_call")).await.is_ok());
+        assert_eq!(transport.requests(), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn applies_method_retry_budgets() {
+        let transport = MockTransport::default();
+        transport.push_status(429, None).push_status(429, None);
+        let stats = Arc::default();
+        let budgets = BTreeMap::from([("eth_getProof".to_string(), 1)]);
+        let mut service = layer(&stats).method_retries(budgets).layer(transport.clone());
+
+        let err = service.call(request("eth_getProof")).await.unwrap_err();
+        assert!(err.to_string().contains("Max retries exceeded"), "{err}");
+        assert_eq!(transport.requests(), 2);
+
+        // other methods keep the default budget
+        transport.push_status(429, None).push_status(429, None).push_json(OK);
+        assert!(service.call(request("eth_getStorageAt")).await.is_ok());
+        assert_eq!(transport.requests(), 5);
+    }
+}
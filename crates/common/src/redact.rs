@@ -0,0 +1,236 @@
+//! Masking of sensitive values, e.g. private keys and API keys read from the environment.
+
+use alloy_primitives::{keccak256, U256};
+use serde_json::Value;
+use std::{cmp::Reverse, collections::BTreeMap};
+
+/// Sensitive values shorter than this are not masked, they would match too much unrelated output.
+pub const MIN_SENSITIVE_LEN: usize = 4;
+
+/// Returns true if the name of the environment variable matches one of the patterns, see
+/// `Config::sensitive_env`.
+pub fn is_sensitive_env(name: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| {
+        match globset::GlobBuilder::new(pattern).case_insensitive(true).build() {
+            Ok(glob) => glob.compile_matcher().is_match(name),
+            Err(err) => {
+                warn!(%pattern, %err, "invalid sensitive environment variable pattern");
+                false
+            }
+        }
+    })
+}
+
+/// Returns the mask of a sensitive value: `***` followed by a short hash of the value, so
+/// distinct values can be told apart.
+pub fn mask(value: &str) -> String {
+    format!("***{}", hex::encode(&keccak256(value)[..3]))
+}
+
+/// Replaces sensitive values with their [mask].
+#[derive(Clone, Debug, Default)]
+pub struct Redactor {
+    /// `sensitive value -> mask`
+    masks: BTreeMap<String, String>,
+}
+
+impl Redactor {
+    /// Returns a redactor of the values of the environment variables that match the patterns.
+    pub fn from_env(patterns: &[String]) -> Self {
+        let mut redactor = Self::default();
+        for (name, value) in std::env::vars() {
+            if is_sensitive_env(&name, patterns) {
+                redactor.add(&value);
+            }
+        }
+        redactor
+    }
+
+    /// Adds a sensitive value.
+    ///
+    /// Numbers are also masked in their other representation, the decimal representation of a
+    /// hex private key for example, and hex strings also without their `0x` prefix. The hex
+    /// encoding of the value is masked as well, it's how strings appear in calldata.
+    pub fn add(&mut self, value: &str) {
+        let value = value.trim();
+        if value.len() < MIN_SENSITIVE_LEN {
+            return
+        }
+        let mask = mask(value);
+        let mut representations = vec![value.to_string(), hex::encode(value)];
+        if let Some(hex) = value.strip_prefix("0x") {
+            representations.push(hex.to_string());
+            representations.push(hex.to_lowercase());
+        }
+        if let Ok(number) = value.parse::<U256>() {
+            representations.push(number.to_string());
+            representations.push(format!("{number:#x}"));
+        }
+        for representation in representations {
+            if representation.len() >= MIN_SENSITIVE_LEN {
+                self.masks.entry(representation).or_insert_with(|| mask.clone());
+            }
+        }
+    }
+
+    /// Adds the sensitive values of another redactor.
+    pub fn extend(&mut self, other: &Self) {
+        for (value, mask) in &other.masks {
+            self.masks.entry(value.clone()).or_insert_with(|| mask.clone());
+        }
+    }
+
+    /// Returns true if there are no sensitive values.
+    pub fn is_empty(&self) -> bool {
+        self.masks.is_empty()
+    }
+
+    /// Returns the string with all sensitive values masked.
+    pub fn redact(&self, s: &str) -> String {
+        // mask the longest values first, so values that contain others are masked as a whole
+        let mut masks: Vec<_> = self.masks.iter().collect();
+        masks.sort_by_key(|(value, _)| Reverse(value.len()));
+        let mut redacted = s.to_string();
+        for (value, mask) in masks {
+            if redacted.contains(value.as_str()) {
+                redacted = redacted.replace(value.as_str(), mask);
+            }
+        }
+        redacted
+    }
+
+    /// Masks the sensitive values of all strings and object keys of the JSON value, except for the
+    /// values of the `skip` keys.
+    pub fn redact_json(&self, value: &mut Value, skip: &[&str]) {
+        self.redact_json_reversibly(value, skip);
+    }
+
+    /// Like [`Self::redact_json`], but returns the original values of the masked strings, by the
+    /// JSON pointer of their location in the redacted value, see [`restore_json`].
+    pub fn redact_json_reversibly(
+        &self,
+        value: &mut Value,
+        skip: &[&str],
+    ) -> BTreeMap<String, String> {
+        let mut originals = BTreeMap::new();
+        if !self.is_empty() {
+            self.redact_json_at(value, skip, "", &mut originals);
+        }
+        originals
+    }
+
+    fn redact_json_at(
+        &self,
+        value: &mut Value,
+        skip: &[&str],
+        pointer: &str,
+        originals: &mut BTreeMap<String, String>,
+    ) {
+        match value {
+            Value::String(s) => {
+                let redacted = self.redact(s);
+                if redacted != *s {
+                    originals.insert(pointer.to_string(), std::mem::replace(s, redacted));
+                }
+            }
+            Value::Array(values) => values.iter_mut().enumerate().for_each(|(i, value)| {
+                self.redact_json_at(value, skip, &format!("{pointer}/{i}"), originals)
+            }),
+            Value::Object(object) => {
+                *object = std::mem::take(object)
+                    .into_iter()
+                    .map(|(key, mut value)| {
+                        if skip.contains(&key.as_str()) {
+                            return (key, value)
+                        }
+                        let key = self.redact(&key);
+                        let escaped = key.replace('~', "~0").replace('/', "~1");
+                        self.redact_json_at(
+                            &mut value,
+                            skip,
+                            &format!("{pointer}/{escaped}"),
+                            originals,
+                        );
+                        (key, value)
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Puts the original values returned by [`Redactor::redact_json_reversibly`] back into the
+/// redacted JSON value.
+pub fn restore_json(value: &mut Value, originals: &BTreeMap<String, String>) {
+    for (pointer, original) in originals {
+        if let Some(value) = value.pointer_mut(pointer) {
+            *value = Value::String(original.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn can_match_sensitive_env() {
+        let patterns = ["*_KEY".to_string(), "PRIVATE_*".to_string()];
+        assert!(is_sensitive_env("ETHERSCAN_API_KEY", &patterns));
+        assert!(is_sensitive_env("private_key", &patterns));
+        assert!(!is_sensitive_env("ETH_RPC_URL", &patterns));
+        assert!(!is_sensitive_env("KEYS", &patterns));
+    }
+
+    #[test]
+    fn can_redact() {
+        let mut redactor = Redactor::default();
+        redactor.add("supersecret");
+        redactor.add("0xAB12cd34");
+        redactor.add("abc");
+
+        let secret = mask("supersecret");
+        let key = mask("0xAB12cd34");
+        assert_ne!(secret, key);
+        assert_eq!(
+            redactor.redact("https://rpc.example.com/v2/supersecret"),
+            format!("https://rpc.example.com/v2/{secret}")
+        );
+        assert_eq!(redactor.redact("key: 0xAB12cd34, abc"), format!("key: {key}, abc"));
+        assert_eq!(redactor.redact("calldata ab12cd34"), format!("calldata {key}"));
+        assert_eq!(redactor.redact("as uint 2870136116"), format!("as uint {key}"));
+
+        // the value as string in calldata
+        assert_eq!(
+            redactor.redact(&format!("0x{}", hex::encode("supersecret"))),
+            format!("0x{secret}")
+        );
+
+        let mut value = json!({ "rpc": "supersecret", "transaction": { "data": "0xab12cd34" } });
+        redactor.redact_json(&mut value, &["transaction"]);
+        assert_eq!(value, json!({ "rpc": secret, "transaction": { "data": "0xab12cd34" } }));
+    }
+
+    #[test]
+    fn can_restore_redacted_json() {
+        let mut redactor = Redactor::default();
+        redactor.add("supersecret");
+
+        let original = json!({
+            "transactions": [{ "arguments": ["supersecret"], "data": "0x00supersecret" }],
+            "a/b": { "key": "supersecret" },
+            "receipts": ["supersecret"]
+        });
+        let mut value = original.clone();
+        let originals = redactor.redact_json_reversibly(&mut value, &["receipts"]);
+        assert_eq!(originals.len(), 3);
+        assert!(!value["transactions"].to_string().contains("supersecret"));
+        assert!(!value["a/b"].to_string().contains("supersecret"));
+        assert_eq!(value["receipts"], original["receipts"]);
+
+        restore_json(&mut value, &originals);
+        assert_eq!(value, original);
+    }
+}
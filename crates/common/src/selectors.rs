@@ -35,6 +35,8 @@ pub struct SignEthClient {
     timedout_requests: Arc<AtomicUsize>,
     /// Max allowed request that can time out
     max_timedout_requests: usize,
+    /// The URL signatures are looked up at
+    lookup_url: String,
 }
 
 impl SignEthClient {
@@ -52,9 +54,16 @@ impl SignEthClient {
             spurious_connection: Arc::new(Default::default()),
             timedout_requests: Arc::new(Default::default()),
             max_timedout_requests: MAX_TIMEDOUT_REQ,
+            lookup_url: SELECTOR_LOOKUP_URL.to_string(),
         })
     }
 
+    /// Sets the URL signatures are looked up at, `https://api.openchain.xyz` by default
+    pub fn with_lookup_url(mut self, lookup_url: impl Into<String>) -> Self {
+        self.lookup_url = lookup_url.into();
+        self
+    }
+
     async fn get_text(&self, url: &str) -> reqwest::Result<String> {
         self.inner
             .get(url)
@@ -199,7 +208,8 @@ impl SignEthClient {
         // using openchain.xyz signature database over 4byte
         // see https://github.com/foundry-rs/foundry/issues/1672
         let url = format!(
-            "{SELECTOR_LOOKUP_URL}?{ltype}={selectors_str}",
+            "{lookup_url}?{ltype}={selectors_str}",
+            lookup_url = self.lookup_url,
             ltype = match selector_type {
                 SelectorType::Function => "function",
                 SelectorType::Event => "event",
@@ -285,9 +295,7 @@ impl SignEthClient {
         calldata: impl AsRef<str>,
         offline: bool,
     ) -> eyre::Result<PossibleSigs> {
-        let mut possible_info = PossibleSigs::new();
         let calldata = calldata.as_ref().trim_start_matches("0x");
-
         let selector =
             calldata.get(..8).ok_or_else(|| eyre::eyre!("calldata cannot be less that 4 bytes"))?;
 
@@ -296,23 +304,7 @@ impl SignEthClient {
         } else {
             self.decode_function_selector(selector).await.unwrap_or_default().into_iter().collect()
         };
-        let (_, data) = calldata.split_at(8);
-
-        if data.len() % 64 != 0 {
-            eyre::bail!("\nInvalid calldata size")
-        }
-
-        let row_length = data.len() / 64;
-
-        for row in 0..row_length {
-            possible_info.data.push(data[64 * row..64 * (row + 1)].to_string());
-        }
-        if sigs.is_empty() {
-            possible_info.method = SelectorOrSig::Selector(selector.to_string());
-        } else {
-            possible_info.method = SelectorOrSig::Sig(sigs);
-        }
-        Ok(possible_info)
+        PossibleSigs::from_calldata(calldata, sigs)
     }
 
     /// uploads selectors to https://api.openchain.xyz using the given data
@@ -363,8 +355,25 @@ pub struct PossibleSigs {
 }
 
 impl PossibleSigs {
-    fn new() -> Self {
-        PossibleSigs { method: SelectorOrSig::Selector("0x00000000".to_string()), data: vec![] }
+    /// Splits the calldata into its selector and its words, the method is the selector if there
+    /// are no possible signatures.
+    pub(crate) fn from_calldata(calldata: &str, sigs: Vec<String>) -> eyre::Result<Self> {
+        let calldata = calldata.trim_start_matches("0x");
+        let selector =
+            calldata.get(..8).ok_or_else(|| eyre::eyre!("calldata cannot be less that 4 bytes"))?;
+        let (_, data) = calldata.split_at(8);
+
+        if data.len() % 64 != 0 {
+            eyre::bail!("\nInvalid calldata size")
+        }
+
+        let method = if sigs.is_empty() {
+            SelectorOrSig::Selector(selector.to_string())
+        } else {
+            SelectorOrSig::Sig(sigs)
+        };
+        let data = data.as_bytes().chunks(64).map(|row| String::from_utf8_lossy(row).into_owned());
+        Ok(PossibleSigs { method, data: data.collect() })
     }
 }
 
@@ -0,0 +1,615 @@
+//! Identification of function, error and event selectors from their signatures, shared by all the
+//! tools which decode calls and logs.
+//!
+//! The signatures fetched from `https://api.openchain.xyz` are cached on disk, in
+//! `~/.foundry/cache/signatures`, so that they are only ever fetched once. No lookup is made in
+//! offline mode, e.g. with `offline = true` in the config or `FOUNDRY_OFFLINE=true`, only the
+//! cached signatures are used.
+
+use crate::{
+    abi::{get_event, get_func},
+    fs,
+    selectors::{PossibleSigs, SelectorType, SignEthClient},
+};
+use alloy_json_abi::{Event, Function, JsonAbi};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::RwLock;
+
+/// The maximum number of selectors looked up at `https://api.openchain.xyz` in one request
+const SELECTORS_PER_REQUEST: usize = 100;
+
+/// A [SignaturesIdentifier] shared by the tasks of a session
+pub type SingleSignaturesIdentifier = Arc<RwLock<SignaturesIdentifier>>;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct CachedSignatures {
+    events: BTreeMap<String, String>,
+    functions: BTreeMap<String, String>,
+}
+
+/// A dump of signatures to import into the cache, see [SignaturesIdentifier::import].
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SignaturesDump {
+    /// The selectors and signatures of functions and events, as they are cached
+    Cached(CachedSignatures),
+    /// Function, error and event signatures, the latter prefixed with `event`
+    Signatures(Vec<String>),
+}
+
+/// The ABI of an artifact.
+#[derive(Deserialize)]
+struct AbiArtifact {
+    abi: Option<JsonAbi>,
+}
+
+/// The signatures of the ABIs of the local project, all the signatures of colliding selectors are
+/// kept.
+#[derive(Debug, Default)]
+struct LocalSignatures {
+    events: BTreeMap<String, Vec<String>>,
+    functions: BTreeMap<String, Vec<String>>,
+}
+
+/// An identifier that tries to identify functions and events using signatures found at
+/// `https://openchain.xyz`.
+///
+/// Signatures are looked up in the local ABIs first, then in the cache written by
+/// `forge selectors cache` and previous sessions, and only then at `https://openchain.xyz`.
+#[derive(Debug)]
+pub struct SignaturesIdentifier {
+    /// Signatures of the local ABIs, these are not saved to the cache
+    local: LocalSignatures,
+    /// Cached selectors for functions and events
+    cached: CachedSignatures,
+    /// Location where to save `CachedSignatures`
+    cached_path: Option<PathBuf>,
+    /// Selectors that were unavailable during the session.
+    unavailable: HashSet<String>,
+    /// The API client to fetch signatures from
+    sign_eth_api: SignEthClient,
+    /// whether traces should be decoded via `sign_eth_api`
+    offline: bool,
+}
+
+impl SignaturesIdentifier {
+    /// Creates an identifier which reads and writes the cache in `cache_path`, usually
+    /// [`Config::foundry_cache_dir`](foundry_config::Config::foundry_cache_dir), and which looks
+    /// up the selectors missing from it at `https://api.openchain.xyz` unless `offline`.
+    pub fn new(
+        cache_path: Option<PathBuf>,
+        offline: bool,
+    ) -> eyre::Result<SingleSignaturesIdentifier> {
+        Self::with_client(cache_path, offline, SignEthClient::new()?)
+    }
+
+    /// Same as [Self::new] but the selectors are looked up with the given client.
+    #[instrument(target = "forge::signatures", skip(sign_eth_api))]
+    pub fn with_client(
+        cache_path: Option<PathBuf>,
+        offline: bool,
+        sign_eth_api: SignEthClient,
+    ) -> eyre::Result<SingleSignaturesIdentifier> {
+        let identifier = if let Some(cache_path) = cache_path {
+            let path = cache_path.join("signatures");
+            trace!(?path, "reading signature cache");
+            let cached = if path.is_file() {
+                read_cache(&path)
+            } else {
+                if let Err(err) = std::fs::create_dir_all(cache_path) {
+                    warn!("could not create signatures cache dir: {:?}", err);
+                }
+                CachedSignatures::default()
+            };
+            Self {
+                local: Default::default(),
+                cached,
+                cached_path: Some(path),
+                unavailable: HashSet::new(),
+                sign_eth_api,
+                offline,
+            }
+        } else {
+            Self {
+                local: Default::default(),
+                cached: Default::default(),
+                cached_path: None,
+                unavailable: HashSet::new(),
+                sign_eth_api,
+                offline,
+            }
+        };
+
+        Ok(Arc::new(RwLock::new(identifier)))
+    }
+
+    /// Writes the cache, merged with the signatures other sessions cached in the meantime.
+    #[instrument(target = "forge::signatures", skip(self))]
+    pub fn save(&self) {
+        if let Some(cached_path) = &self.cached_path {
+            if let Some(parent) = cached_path.parent() {
+                if let Err(err) = std::fs::create_dir_all(parent) {
+                    warn!(?parent, ?err, "failed to create cache");
+                }
+            }
+            let mut cached =
+                if cached_path.is_file() { read_cache(cached_path) } else { Default::default() };
+            cached.functions.extend(self.cached.functions.clone());
+            cached.events.extend(self.cached.events.clone());
+            if let Err(err) = fs::write_json_file(cached_path, &cached) {
+                warn!(?cached_path, ?err, "failed to flush signature cache");
+            } else {
+                trace!(?cached_path, "flushed signature cache")
+            }
+        }
+    }
+
+    /// Imports the signatures of a JSON dump into the cache, either in the format of the cache or
+    /// as a list of function, error and event signatures, the latter prefixed with `event`.
+    ///
+    /// Returns the number of new signatures.
+    pub fn import(&mut self, path: &Path) -> eyre::Result<usize> {
+        let mut added = 0;
+        match fs::read_json_file::<SignaturesDump>(path)? {
+            SignaturesDump::Cached(dump) => {
+                for (selectors, cached) in [
+                    (dump.functions, &mut self.cached.functions),
+                    (dump.events, &mut self.cached.events),
+                ] {
+                    for (selector, signature) in selectors {
+                        if let Entry::Vacant(entry) = cached.entry(selector.to_lowercase()) {
+                            entry.insert(signature);
+                            added += 1;
+                        }
+                    }
+                }
+            }
+            SignaturesDump::Signatures(signatures) => {
+                for signature in signatures {
+                    let signature = signature.trim();
+                    let (selector, signature, cached) = match signature.strip_prefix("event ") {
+                        Some(event) => {
+                            let event = get_event(event)?;
+                            (event.selector().to_vec(), event.signature(), &mut self.cached.events)
+                        }
+                        None => {
+                            let func = signature.strip_prefix("error ").unwrap_or(signature);
+                            let func = get_func(func.strip_prefix("function ").unwrap_or(func))?;
+                            (func.selector().to_vec(), func.signature(), &mut self.cached.functions)
+                        }
+                    };
+                    if let Entry::Vacant(entry) = cached.entry(hex::encode_prefixed(selector)) {
+                        entry.insert(signature);
+                        added += 1;
+                    }
+                }
+            }
+        }
+        Ok(added)
+    }
+}
+
+/// Reads the signatures cache, which is empty if it can't be read.
+fn read_cache(path: &Path) -> CachedSignatures {
+    fs::read_json_file(path)
+        .map_err(|err| warn!(?path, ?err, "failed to read cache file"))
+        .unwrap_or_default()
+}
+
+impl SignaturesIdentifier {
+    async fn identify<T>(
+        &mut self,
+        selector_type: SelectorType,
+        identifiers: impl IntoIterator<Item = impl AsRef<[u8]>>,
+        get_type: impl Fn(&str) -> eyre::Result<T>,
+    ) -> Vec<Option<T>> {
+        let (local, cache) = match selector_type {
+            SelectorType::Function => (&self.local.functions, &mut self.cached.functions),
+            SelectorType::Event => (&self.local.events, &mut self.cached.events),
+        };
+
+        let hex_identifiers: Vec<String> =
+            identifiers.into_iter().map(hex::encode_prefixed).collect();
+
+        if !self.offline {
+            // each missing selector is looked up once, in batches
+            let query: Vec<_> = hex_identifiers
+                .iter()
+                .filter(|v| !local.contains_key(v.as_str()))
+                .filter(|v| !cache.contains_key(v.as_str()))
+                .filter(|v| !self.unavailable.contains(v.as_str()))
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+
+            for query in query.chunks(SELECTORS_PER_REQUEST) {
+                let Ok(res) =
+                    self.sign_eth_api.decode_selectors(selector_type, query.iter().copied()).await
+                else {
+                    break
+                };
+                for (hex_id, selector_result) in query.iter().zip(res.into_iter()) {
+                    let mut found = false;
+                    if let Some(decoded_results) = selector_result {
+                        if let Some(decoded_result) = decoded_results.into_iter().next() {
+                            cache.insert((*hex_id).clone(), decoded_result);
+                            found = true;
+                        }
+                    }
+                    if !found {
+                        self.unavailable.insert((*hex_id).clone());
+                    }
+                }
+            }
+        }
+
+        hex_identifiers
+            .iter()
+            .map(|v| {
+                let local = local.get(v).and_then(|sigs| sigs.first());
+                local.or_else(|| cache.get(v)).and_then(|v| get_type(v).ok())
+            })
+            .collect()
+    }
+
+    /// Returns all the signatures of the function or error selector, from the first source that
+    /// knows it: the local ABIs, the cache, or `https://api.openchain.xyz` unless offline.
+    ///
+    /// The first signature found at `https://api.openchain.xyz` is cached.
+    pub async fn function_signatures(&mut self, selector: &[u8]) -> Vec<String> {
+        self.signatures(SelectorType::Function, selector).await
+    }
+
+    /// Returns all the signatures of the event topic, like [Self::function_signatures].
+    pub async fn event_signatures(&mut self, topic: &[u8]) -> Vec<String> {
+        self.signatures(SelectorType::Event, topic).await
+    }
+
+    /// Returns the words of the calldata along with the possible signatures of its selector, see
+    /// [Self::function_signatures].
+    pub async fn pretty_calldata(&mut self, calldata: &str) -> eyre::Result<PossibleSigs> {
+        let calldata = calldata.trim_start_matches("0x");
+        let selector = hex::decode(calldata.get(..8).unwrap_or_default())?;
+        if selector.len() < 4 {
+            eyre::bail!("calldata cannot be less that 4 bytes")
+        }
+        let sigs = self.function_signatures(&selector).await;
+        PossibleSigs::from_calldata(calldata, sigs)
+    }
+
+    async fn signatures(&mut self, selector_type: SelectorType, selector: &[u8]) -> Vec<String> {
+        let (local, cache) = match selector_type {
+            SelectorType::Function => (&self.local.functions, &mut self.cached.functions),
+            SelectorType::Event => (&self.local.events, &mut self.cached.events),
+        };
+        let hex_id = hex::encode_prefixed(selector);
+        if let Some(sigs) = local.get(&hex_id) {
+            return sigs.clone()
+        }
+        if let Some(sig) = cache.get(&hex_id) {
+            return vec![sig.clone()]
+        }
+        if self.offline || self.unavailable.contains(&hex_id) {
+            return vec![]
+        }
+
+        match self.sign_eth_api.decode_selectors(selector_type, [hex_id.clone()]).await {
+            Ok(mut res) => {
+                let sigs = res.pop().flatten().unwrap_or_default();
+                if let Some(sig) = sigs.first() {
+                    cache.insert(hex_id, sig.clone());
+                } else {
+                    self.unavailable.insert(hex_id);
+                }
+                sigs
+            }
+            Err(err) => {
+                warn!(selector = %hex_id, ?err, "failed to query signatures");
+                vec![]
+            }
+        }
+    }
+
+    /// Adds the signatures of the functions, events and errors of the ABI to the local
+    /// signatures, which are looked up before the cache and aren't saved to it.
+    ///
+    /// Errors share the selectors with functions.
+    pub fn add_local_abi(&mut self, abi: &JsonAbi) {
+        let add = |signatures: &mut BTreeMap<String, Vec<String>>, selector: Vec<u8>, signature| {
+            let sigs = signatures.entry(hex::encode_prefixed(selector)).or_default();
+            if !sigs.contains(&signature) {
+                sigs.push(signature);
+            }
+        };
+        for func in abi.functions() {
+            add(&mut self.local.functions, func.selector().to_vec(), func.signature());
+        }
+        for error in abi.errors() {
+            add(&mut self.local.functions, error.selector().to_vec(), error.signature());
+        }
+        for event in abi.events() {
+            add(&mut self.local.events, event.selector().to_vec(), event.signature());
+        }
+    }
+
+    /// Adds the ABIs of the artifacts in the directory, e.g. the `out` directory of a project, to
+    /// the local signatures.
+    ///
+    /// Returns the number of artifacts with an ABI.
+    pub fn add_local_artifacts(&mut self, artifacts: &Path) -> usize {
+        let mut added = 0;
+        for path in fs::json_files(artifacts) {
+            if path.components().any(|component| component.as_os_str() == "build-info") {
+                continue
+            }
+            match fs::read_json_file::<AbiArtifact>(&path) {
+                Ok(AbiArtifact { abi: Some(abi) }) => {
+                    self.add_local_abi(&abi);
+                    added += 1;
+                }
+                Ok(_) => {}
+                Err(err) => trace!(?path, ?err, "skipping invalid artifact"),
+            }
+        }
+        added
+    }
+
+    /// Adds the signatures of the functions, events and errors of the ABI to the cache, so they
+    /// can be identified without querying `https://api.openchain.xyz`.
+    ///
+    /// Errors share the cache with functions. Returns the number of new signatures.
+    pub fn extend_from_abi(&mut self, abi: &JsonAbi) -> usize {
+        let mut added = 0;
+        for func in abi.functions() {
+            let selector = hex::encode_prefixed(func.selector());
+            if self.cached.functions.insert(selector, func.signature()).is_none() {
+                added += 1;
+            }
+        }
+        for event in abi.events() {
+            let selector = hex::encode_prefixed(event.selector());
+            if self.cached.events.insert(selector, event.signature()).is_none() {
+                added += 1;
+            }
+        }
+        for error in abi.errors() {
+            let selector = hex::encode_prefixed(error.selector());
+            if let Entry::Vacant(entry) = self.cached.functions.entry(selector) {
+                entry.insert(error.signature());
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Identifies `Function`s from its cache or `https://api.openchain.xyz`
+    pub async fn identify_functions(
+        &mut self,
+        identifiers: impl IntoIterator<Item = impl AsRef<[u8]>>,
+    ) -> Vec<Option<Function>> {
+        self.identify(SelectorType::Function, identifiers, get_func).await
+    }
+
+    /// Identifies `Function` from its cache or `https://api.openchain.xyz`
+    pub async fn identify_function(&mut self, identifier: &[u8]) -> Option<Function> {
+        self.identify_functions(&[identifier]).await.pop().unwrap()
+    }
+
+    /// Identifies `Event`s from its cache or `https://api.openchain.xyz`
+    pub async fn identify_events(
+        &mut self,
+        identifiers: impl IntoIterator<Item = impl AsRef<[u8]>>,
+    ) -> Vec<Option<Event>> {
+        self.identify(SelectorType::Event, identifiers, get_event).await
+    }
+
+    /// Identifies `Event` from its cache or `https://api.openchain.xyz`
+    pub async fn identify_event(&mut self, identifier: &[u8]) -> Option<Event> {
+        self.identify_events(&[identifier]).await.pop().unwrap()
+    }
+}
+
+impl Drop for SignaturesIdentifier {
+    fn drop(&mut self) {
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::header::CONTENT_TYPE, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Spawns a lookup API which answers every request with `response`, and returns its URL with
+    /// the number of requests it received.
+    fn spawn_lookup_api(response: &'static str) -> (String, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let app = Router::new().fallback(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async move { ([(CONTENT_TYPE, "application/json")], response) }
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service());
+        tokio::spawn(server);
+        (format!("http://{addr}/lookup"), requests)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_query_signatures() {
+        let tmp = tempfile::tempdir().unwrap();
+        {
+            let sigs = SignaturesIdentifier::new(Some(tmp.path().into()), false).unwrap();
+
+            assert!(sigs.read().await.cached.events.is_empty());
+            assert!(sigs.read().await.cached.functions.is_empty());
+
+            let func = sigs.write().await.identify_function(&[35, 184, 114, 221]).await.unwrap();
+            let event = sigs
+                .write()
+                .await
+                .identify_event(&[
+                    39, 119, 42, 220, 99, 219, 7, 170, 231, 101, 183, 30, 178, 181, 51, 6, 79, 167,
+                    129, 189, 87, 69, 126, 27, 19, 133, 146, 216, 25, 141, 9, 89,
+                ])
+                .await
+                .unwrap();
+
+            assert_eq!(func, get_func("transferFrom(address,address,uint256)").unwrap());
+            assert_eq!(event, get_event("Transfer(address,address,uint128)").unwrap());
+
+            // dropping saves the cache
+        }
+
+        let sigs = SignaturesIdentifier::new(Some(tmp.path().into()), false).unwrap();
+        assert_eq!(sigs.read().await.cached.events.len(), 1);
+        assert_eq!(sigs.read().await.cached.functions.len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_cache_abi_signatures() {
+        let tmp = tempfile::tempdir().unwrap();
+        let func = get_func("transfer(address,uint256)").unwrap();
+        let event = get_event("Transfer(address,address,uint256)").unwrap();
+        let error = alloy_json_abi::Error { name: "Unauthorized".to_string(), inputs: vec![] };
+
+        let mut abi = JsonAbi::default();
+        abi.functions.insert(func.name.clone(), vec![func.clone()]);
+        abi.events.insert(event.name.clone(), vec![event.clone()]);
+        abi.errors.insert(error.name.clone(), vec![error.clone()]);
+        {
+            let sigs = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+            assert_eq!(sigs.write().await.extend_from_abi(&abi), 3);
+            assert_eq!(sigs.write().await.extend_from_abi(&abi), 0);
+        }
+
+        let sigs = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+        let mut sigs = sigs.write().await;
+        assert_eq!(sigs.identify_function(&func.selector()[..]).await, Some(func));
+        assert_eq!(sigs.identify_event(&event.selector()[..]).await, Some(event));
+        assert_eq!(
+            sigs.identify_function(&error.selector()[..]).await,
+            Some(get_func("Unauthorized()").unwrap())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prefers_local_signatures() {
+        let tmp = tempfile::tempdir().unwrap();
+        // colliding selectors
+        let func = get_func("collate_propagate_storage(bytes16)").unwrap();
+        let other = get_func("burn(uint256)").unwrap();
+        assert_eq!(func.selector(), other.selector());
+
+        let mut abi = JsonAbi::default();
+        abi.functions.insert(func.name.clone(), vec![func.clone()]);
+        abi.functions.insert(other.name.clone(), vec![other.clone()]);
+        {
+            let sigs = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+            let mut sigs = sigs.write().await;
+            assert!(sigs.function_signatures(&func.selector()[..]).await.is_empty());
+
+            sigs.add_local_abi(&abi);
+            assert_eq!(
+                sigs.function_signatures(&func.selector()[..]).await,
+                vec![other.signature(), func.signature()]
+            );
+            assert_eq!(sigs.identify_function(&func.selector()[..]).await, Some(other));
+        }
+
+        // local signatures aren't cached
+        let sigs = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+        assert!(sigs.read().await.cached.functions.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn offline_only_uses_cached_signatures() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dump = tmp.path().join("dump.json");
+        fs::write_json_file(
+            &dump,
+            &["transfer(address,uint256)", "event Transfer(address,address,uint256)"],
+        )
+        .unwrap();
+
+        let (url, requests) =
+            spawn_lookup_api(r#"{"ok":true,"result":{"event":{},"function":{}}}"#);
+        let client = SignEthClient::new().unwrap().with_lookup_url(url);
+        let sigs =
+            SignaturesIdentifier::with_client(Some(tmp.path().into()), true, client).unwrap();
+        let mut sigs = sigs.write().await;
+        assert_eq!(sigs.import(&dump).unwrap(), 2);
+        assert_eq!(sigs.import(&dump).unwrap(), 0);
+
+        let func = get_func("transfer(address,uint256)").unwrap();
+        let event = get_event("Transfer(address,address,uint256)").unwrap();
+        assert_eq!(sigs.identify_function(&func.selector()[..]).await, Some(func.clone()));
+        assert_eq!(sigs.identify_event(&event.selector()[..]).await, Some(event.clone()));
+        assert_eq!(sigs.identify_function(&[0xde, 0xad, 0xbe, 0xef]).await, None);
+        assert!(sigs.function_signatures(&[0xde, 0xad, 0xbe, 0xef]).await.is_empty());
+        assert_eq!(sigs.event_signatures(&event.selector()[..]).await, [event.signature()]);
+        assert!(sigs.event_signatures(&[0xde; 32]).await.is_empty());
+        let calldata = format!("{}{:0>64}", hex::encode(func.selector()), 1);
+        let pretty = sigs.pretty_calldata(&calldata).await.unwrap().to_string();
+        assert!(pretty.contains("- transfer(address,uint256)"), "{pretty}");
+        let pretty = sigs.pretty_calldata("0xdeadbeef").await.unwrap().to_string();
+        assert!(pretty.contains("Method: deadbeef"), "{pretty}");
+
+        // not a single lookup was made
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn looks_up_missing_selectors_once() {
+        let (url, requests) = spawn_lookup_api(concat!(
+            r#"{"ok":true,"result":{"event":{},"function":{"#,
+            r#""0xa9059cbb":[{"name":"transfer(address,uint256)"}],"0xdeadbeef":null}}}"#
+        ));
+        let tmp = tempfile::tempdir().unwrap();
+        let client = SignEthClient::new().unwrap().with_lookup_url(url);
+        let sigs =
+            SignaturesIdentifier::with_client(Some(tmp.path().into()), false, client).unwrap();
+        let func = get_func("transfer(address,uint256)").unwrap();
+
+        let selectors =
+            [func.selector().to_vec(), vec![0xde, 0xad, 0xbe, 0xef], func.selector().to_vec()];
+        let identified = sigs.write().await.identify_functions(&selectors).await;
+        assert_eq!(identified, [Some(func.clone()), None, Some(func.clone())]);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // both the found and the unavailable selectors aren't looked up again
+        let identified = sigs.write().await.identify_functions(&selectors).await;
+        assert_eq!(identified, [Some(func.clone()), None, Some(func)]);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn save_merges_with_other_sessions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let one = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+        let other = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+
+        let mut abi = JsonAbi::default();
+        let func = get_func("transfer(address,uint256)").unwrap();
+        abi.functions.insert(func.name.clone(), vec![func]);
+        one.blocking_write().extend_from_abi(&abi);
+        drop(one);
+
+        let mut abi = JsonAbi::default();
+        let func = get_func("approve(address,uint256)").unwrap();
+        abi.functions.insert(func.name.clone(), vec![func]);
+        other.blocking_write().extend_from_abi(&abi);
+        drop(other);
+
+        let sigs = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+        assert_eq!(sigs.blocking_read().cached.functions.len(), 2);
+    }
+}
@@ -0,0 +1,749 @@
+//! Comparing storage layouts for upgrade safety, see `forge inspect --compare` and
+//! `cast storage-layout --diff`.
+
+use alloy_primitives::U256;
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use foundry_compilers::artifacts::{Storage, StorageLayout};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt,
+};
+use yansi::Paint;
+
+/// Matches the contract qualifier of user defined types, e.g. `Vault.` in `struct Vault.Position`.
+static QUALIFIER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(struct|enum) [A-Za-z0-9_$]+\.").unwrap());
+
+/// A variable of a storage layout, struct members are flattened into their own entries.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageEntry {
+    /// The path of the variable, e.g. `positions[].amount` for a member of a mapping value
+    pub label: String,
+    /// The contract that declares the variable
+    pub contract: String,
+    /// The type of the variable
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// The slot of the variable, relative to the mapping or array for mapping values and array
+    /// elements
+    pub slot: U256,
+    /// The offset of the variable in the slot
+    pub offset: u64,
+    /// The number of bytes the variable occupies
+    pub number_of_bytes: u64,
+    /// The mapping or dynamic array the variable is part of, empty for the contract storage
+    #[serde(skip)]
+    scope: String,
+}
+
+impl StorageEntry {
+    /// Returns the range of storage bytes the variable occupies.
+    fn bytes(&self) -> (U256, U256) {
+        let start = self.slot * U256::from(32) + U256::from(self.offset);
+        (start, start + U256::from(self.number_of_bytes))
+    }
+
+    /// Returns true if the ranges of storage bytes of the variables intersect.
+    fn overlaps(&self, other: &Self) -> bool {
+        let (start, end) = self.bytes();
+        let (other_start, other_end) = other.bytes();
+        start < other_end && other_start < end
+    }
+
+    /// Returns true if the variable is a storage gap, e.g. `uint256[50] private __gap`.
+    fn is_gap(&self) -> bool {
+        self.label
+            .rsplit(|c| c == '.' || c == ']')
+            .next()
+            .map_or(false, |name| name.starts_with("__gap"))
+    }
+
+    fn same_position(&self, other: &Self) -> bool {
+        self.slot == other.slot && self.offset == other.offset
+    }
+}
+
+impl fmt::Display for StorageEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at slot {} offset {}", self.ty, self.slot, self.offset)
+    }
+}
+
+/// Returns the variables of the storage layout.
+///
+/// Struct members are listed instead of the struct, and the values of mappings and the elements
+/// of dynamic arrays are listed in their own scope, e.g. `positions[].amount`.
+pub fn storage_entries(layout: &StorageLayout) -> Vec<StorageEntry> {
+    let mut entries = Vec::new();
+    for storage in &layout.storage {
+        let slot = storage.slot.parse().unwrap_or_default();
+        push_entries(
+            layout,
+            &mut entries,
+            "",
+            storage.label.clone(),
+            &storage.contract,
+            slot,
+            storage.offset as u64,
+            &storage.storage_type,
+        );
+    }
+    entries
+}
+
+#[allow(clippy::too_many_arguments)]
+fn push_entries(
+    layout: &StorageLayout,
+    entries: &mut Vec<StorageEntry>,
+    scope: &str,
+    label: String,
+    contract: &str,
+    slot: U256,
+    offset: u64,
+    type_id: &str,
+) {
+    let Some(ty) = layout.types.get(type_id) else {
+        warn!(%label, %type_id, "missing storage layout type");
+        return
+    };
+
+    let members = ty
+        .other
+        .get("members")
+        .and_then(|members| serde_json::from_value::<Vec<Storage>>(members.clone()).ok());
+    if let (Some(members), "inplace") = (members, ty.encoding.as_str()) {
+        for member in members {
+            let member_slot = slot + member.slot.parse::<U256>().unwrap_or_default();
+            push_entries(
+                layout,
+                entries,
+                scope,
+                format!("{label}.{}", member.label),
+                contract,
+                member_slot,
+                member.offset as u64,
+                &member.storage_type,
+            );
+        }
+        return
+    }
+
+    entries.push(StorageEntry {
+        label: label.clone(),
+        contract: contract.to_string(),
+        ty: QUALIFIER.replace_all(&ty.label, "$1 ").into_owned(),
+        slot,
+        offset,
+        number_of_bytes: ty.number_of_bytes.parse().unwrap_or_default(),
+        scope: scope.to_string(),
+    });
+
+    let inner = match ty.encoding.as_str() {
+        "mapping" => ty.value.clone(),
+        "dynamic_array" => ty.other.get("base").and_then(|base| base.as_str()).map(Into::into),
+        _ => None,
+    };
+    if let Some(inner) = inner {
+        let scope = format!("{label}[]");
+        push_entries(layout, entries, &scope, scope.clone(), contract, U256::ZERO, 0, &inner);
+    }
+}
+
+/// The kind of a change of a storage variable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageChangeKind {
+    /// The variable is stored at another slot or offset
+    Moved,
+    /// The variable occupies another number of bytes
+    Resized,
+    /// The variable has another type of the same size
+    Retyped,
+    /// The variable was removed
+    Deleted,
+    /// A new variable overlaps the storage of an existing variable
+    Overlapping,
+    /// The variable was renamed, its position and type are unchanged
+    Renamed,
+    /// A new variable is stored after the existing ones
+    Appended,
+    /// A new variable is stored in the space of a storage gap
+    AddedInGap,
+    /// A storage gap was shrunk, it still ends at the same slot
+    GapShrunk,
+    /// A storage gap was removed
+    GapRemoved,
+}
+
+impl StorageChangeKind {
+    /// Returns true if the change corrupts the storage of an upgraded contract.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Self::Moved | Self::Resized | Self::Retyped | Self::Deleted | Self::Overlapping
+        )
+    }
+}
+
+impl fmt::Display for StorageChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Moved => "moved",
+            Self::Resized => "resized",
+            Self::Retyped => "retyped",
+            Self::Deleted => "deleted",
+            Self::Overlapping => "overlapping",
+            Self::Renamed => "renamed",
+            Self::Appended => "appended",
+            Self::AddedInGap => "added in gap",
+            Self::GapShrunk => "gap shrunk",
+            Self::GapRemoved => "gap removed",
+        };
+        f.write_str(s)
+    }
+}
+
+/// A change of a storage variable between two storage layouts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct StorageChange {
+    pub kind: StorageChangeKind,
+    /// The variable in the old layout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<StorageEntry>,
+    /// The variable in the new layout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<StorageEntry>,
+    /// The variable of the old layout a new variable overlaps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overlaps: Option<String>,
+}
+
+impl StorageChange {
+    /// Returns the label of the variable, the new one for renamed variables.
+    pub fn label(&self) -> &str {
+        self.new.as_ref().or(self.old.as_ref()).map_or("", |entry| &entry.label)
+    }
+}
+
+/// The changes of a new storage layout compared to an old one, e.g. of a new implementation of
+/// an upgradeable contract compared to the deployed one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageLayoutDiff {
+    pub changes: Vec<StorageChange>,
+}
+
+impl StorageLayoutDiff {
+    /// Compares the new storage layout to the old one.
+    ///
+    /// Variables are matched by their path, a variable without a match is renamed if a new one
+    /// with the same type replaces it. Mapping values and array elements are only compared if
+    /// their mapping or array didn't change.
+    pub fn new(old: &StorageLayout, new: &StorageLayout) -> Self {
+        let mut old_scopes: BTreeMap<String, Vec<StorageEntry>> = BTreeMap::new();
+        for entry in storage_entries(old) {
+            old_scopes.entry(entry.scope.clone()).or_default().push(entry);
+        }
+        let mut new_scopes: BTreeMap<String, Vec<StorageEntry>> = BTreeMap::new();
+        for entry in storage_entries(new) {
+            new_scopes.entry(entry.scope.clone()).or_default().push(entry);
+        }
+
+        let mut diff = Self::default();
+        // the labels of the changed variables, the values of changed mappings are not compared
+        let mut changed = BTreeSet::new();
+        for (scope, old_entries) in &old_scopes {
+            if let Some(owner) = scope.strip_suffix("[]") {
+                if changed.contains(owner) {
+                    changed.insert(scope.clone());
+                    continue
+                }
+            }
+            let new_entries = new_scopes.get(scope).map_or(&[][..], Vec::as_slice);
+            let changes = diff_scope(old_entries, new_entries);
+            for change in &changes {
+                if let Some(old) = &change.old {
+                    if change.kind.is_error() {
+                        changed.insert(old.label.clone());
+                    }
+                }
+            }
+            diff.changes.extend(changes);
+        }
+        diff
+    }
+
+    /// Returns true if the new layout doesn't corrupt the storage of the old one.
+    pub fn is_compatible(&self) -> bool {
+        !self.changes.iter().any(|change| change.kind.is_error())
+    }
+
+    /// Returns the number of changes that corrupt the storage.
+    pub fn errors(&self) -> usize {
+        self.changes.iter().filter(|change| change.kind.is_error()).count()
+    }
+}
+
+impl Serialize for StorageLayoutDiff {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        #[derive(Serialize)]
+        struct Change<'a> {
+            #[serde(flatten)]
+            change: &'a StorageChange,
+            severity: &'static str,
+        }
+
+        let changes: Vec<_> = self
+            .changes
+            .iter()
+            .map(|change| Change {
+                change,
+                severity: if change.kind.is_error() { "error" } else { "info" },
+            })
+            .collect();
+        let mut s = serializer.serialize_struct("StorageLayoutDiff", 2)?;
+        s.serialize_field("compatible", &self.is_compatible())?;
+        s.serialize_field("changes", &changes)?;
+        s.end()
+    }
+}
+
+/// Compares the variables of a scope.
+fn diff_scope(old: &[StorageEntry], new: &[StorageEntry]) -> Vec<StorageChange> {
+    let change = |kind, old: Option<&StorageEntry>, new: Option<&StorageEntry>| StorageChange {
+        kind,
+        old: old.cloned(),
+        new: new.cloned(),
+        overlaps: None,
+    };
+
+    let old_labels: HashSet<_> = old.iter().map(|entry| entry.label.as_str()).collect();
+    let mut matched = HashSet::new();
+    let mut changes = Vec::new();
+    for old_entry in old {
+        if let Some((i, new_entry)) =
+            new.iter().enumerate().find(|(_, entry)| entry.label == old_entry.label)
+        {
+            matched.insert(i);
+            let (old_start, old_end) = old_entry.bytes();
+            let (new_start, new_end) = new_entry.bytes();
+            if old_entry.is_gap() && new_end == old_end && new_start > old_start {
+                changes.push(change(
+                    StorageChangeKind::GapShrunk,
+                    Some(old_entry),
+                    Some(new_entry),
+                ));
+            } else if !old_entry.same_position(new_entry) {
+                changes.push(change(StorageChangeKind::Moved, Some(old_entry), Some(new_entry)));
+            } else if old_entry.number_of_bytes != new_entry.number_of_bytes {
+                changes.push(change(StorageChangeKind::Resized, Some(old_entry), Some(new_entry)));
+            } else if old_entry.ty != new_entry.ty {
+                changes.push(change(StorageChangeKind::Retyped, Some(old_entry), Some(new_entry)));
+            }
+            continue
+        }
+
+        let renamed = new.iter().enumerate().find(|(i, entry)| {
+            !matched.contains(i) &&
+                !old_labels.contains(entry.label.as_str()) &&
+                entry.same_position(old_entry) &&
+                entry.number_of_bytes == old_entry.number_of_bytes &&
+                entry.ty == old_entry.ty
+        });
+        if let Some((i, new_entry)) = renamed {
+            matched.insert(i);
+            changes.push(change(StorageChangeKind::Renamed, Some(old_entry), Some(new_entry)));
+        } else if old_entry.is_gap() {
+            changes.push(change(StorageChangeKind::GapRemoved, Some(old_entry), None));
+        } else {
+            changes.push(change(StorageChangeKind::Deleted, Some(old_entry), None));
+        }
+    }
+
+    for (_, new_entry) in new.iter().enumerate().filter(|(i, _)| !matched.contains(i)) {
+        let overlapping = old.iter().filter(|old_entry| old_entry.overlaps(new_entry));
+        let (gaps, vars): (Vec<_>, Vec<_>) = overlapping.partition(|old_entry| old_entry.is_gap());
+        if let Some(var) = vars.first() {
+            changes.push(StorageChange {
+                overlaps: Some(var.label.clone()),
+                ..change(StorageChangeKind::Overlapping, None, Some(new_entry))
+            });
+        } else if !gaps.is_empty() {
+            changes.push(change(StorageChangeKind::AddedInGap, None, Some(new_entry)));
+        } else {
+            changes.push(change(StorageChangeKind::Appended, None, Some(new_entry)));
+        }
+    }
+    changes
+}
+
+/// Prints the changes of the storage layout, as JSON unless `pretty`.
+pub fn print_storage_layout_diff(diff: &StorageLayoutDiff, pretty: bool) -> eyre::Result<()> {
+    if !pretty {
+        println!("{}", serde_json::to_string_pretty(diff)?);
+        return Ok(())
+    }
+
+    if !diff.changes.is_empty() {
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header(["Severity", "Change", "Variable", "Old", "New"]);
+        for change in &diff.changes {
+            let variable = match (&change.kind, &change.old, &change.new) {
+                (StorageChangeKind::Renamed, Some(old), Some(new)) => {
+                    format!("{} -> {}", old.label, new.label)
+                }
+                _ => change.label().to_string(),
+            };
+            let old = match (&change.old, &change.overlaps) {
+                (Some(old), _) => old.to_string(),
+                (None, Some(overlaps)) => format!("overlaps `{overlaps}`"),
+                (None, None) => String::new(),
+            };
+            let severity = if change.kind.is_error() { "error" } else { "info" };
+            table.add_row([
+                severity.to_string(),
+                change.kind.to_string(),
+                variable,
+                old,
+                change.new.as_ref().map(ToString::to_string).unwrap_or_default(),
+            ]);
+        }
+        println!("{table}");
+    }
+
+    if diff.is_compatible() {
+        println!("{}", Paint::green("The storage layouts are compatible."));
+    } else {
+        let errors = diff.errors();
+        println!(
+            "{}",
+            Paint::red(format!(
+                "The storage layouts are incompatible: {errors} error{}.",
+                if errors == 1 { "" } else { "s" }
+            ))
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Returns a storage layout of `(label, contract, slot, offset, type)` variables.
+    fn layout(storage: &[(&str, &str, u64, u64, &str)]) -> StorageLayout {
+        let storage: Vec<_> = storage
+            .iter()
+            .enumerate()
+            .map(|(i, (label, contract, slot, offset, ty))| {
+                json!({
+                    "astId": i,
+                    "contract": format!("src/Vault.sol:{contract}"),
+                    "label": label,
+                    "offset": offset,
+                    "slot": slot.to_string(),
+                    "type": ty,
+                })
+            })
+            .collect();
+        let member = |label: &str, slot: u64, offset: u64, ty: &str| {
+            json!({
+                "astId": 0,
+                "contract": "src/Vault.sol:Vault",
+                "label": label,
+                "offset": offset,
+                "slot": slot.to_string(),
+                "type": ty,
+            })
+        };
+        serde_json::from_value(json!({
+            "storage": storage,
+            "types": {
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+                "t_bool": { "encoding": "inplace", "label": "bool", "numberOfBytes": "1" },
+                "t_uint64": { "encoding": "inplace", "label": "uint64", "numberOfBytes": "8" },
+                "t_uint128": { "encoding": "inplace", "label": "uint128", "numberOfBytes": "16" },
+                "t_int128": { "encoding": "inplace", "label": "int128", "numberOfBytes": "16" },
+                "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+                "t_array(t_uint256)50_storage": {
+                    "encoding": "inplace",
+                    "label": "uint256[50]",
+                    "numberOfBytes": "1600",
+                    "base": "t_uint256",
+                },
+                "t_array(t_uint256)49_storage": {
+                    "encoding": "inplace",
+                    "label": "uint256[49]",
+                    "numberOfBytes": "1568",
+                    "base": "t_uint256",
+                },
+                "t_struct(Position)1_storage": {
+                    "encoding": "inplace",
+                    "label": "struct Vault.Position",
+                    "numberOfBytes": "64",
+                    "members": [
+                        member("owner", 0, 0, "t_address"),
+                        member("active", 0, 20, "t_bool"),
+                        member("amount", 1, 0, "t_uint256"),
+                    ],
+                },
+                "t_struct(Position)2_storage": {
+                    "encoding": "inplace",
+                    "label": "struct VaultV2.Position",
+                    "numberOfBytes": "64",
+                    "members": [
+                        member("owner", 0, 0, "t_address"),
+                        member("active", 0, 20, "t_bool"),
+                        member("expiry", 0, 21, "t_uint64"),
+                        member("amount", 1, 0, "t_uint256"),
+                    ],
+                },
+                "t_struct(Position)3_storage": {
+                    "encoding": "inplace",
+                    "label": "struct VaultV2.Position",
+                    "numberOfBytes": "64",
+                    "members": [
+                        member("active", 0, 0, "t_bool"),
+                        member("owner", 0, 1, "t_address"),
+                        member("amount", 1, 0, "t_uint256"),
+                    ],
+                },
+                "t_mapping(t_address,t_struct(Position)1_storage)": {
+                    "encoding": "mapping",
+                    "key": "t_address",
+                    "label": "mapping(address => struct Vault.Position)",
+                    "numberOfBytes": "32",
+                    "value": "t_struct(Position)1_storage",
+                },
+                "t_mapping(t_address,t_struct(Position)2_storage)": {
+                    "encoding": "mapping",
+                    "key": "t_address",
+                    "label": "mapping(address => struct VaultV2.Position)",
+                    "numberOfBytes": "32",
+                    "value": "t_struct(Position)2_storage",
+                },
+                "t_mapping(t_address,t_struct(Position)3_storage)": {
+                    "encoding": "mapping",
+                    "key": "t_address",
+                    "label": "mapping(address => struct VaultV2.Position)",
+                    "numberOfBytes": "32",
+                    "value": "t_struct(Position)3_storage",
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    fn kinds(diff: &StorageLayoutDiff) -> Vec<(StorageChangeKind, &str)> {
+        diff.changes.iter().map(|change| (change.kind, change.label())).collect()
+    }
+
+    #[test]
+    fn flattens_structs_and_mapping_values() {
+        let old = layout(&[
+            ("owner", "Vault", 0, 0, "t_address"),
+            ("position", "Vault", 1, 0, "t_struct(Position)1_storage"),
+            ("positions", "Vault", 3, 0, "t_mapping(t_address,t_struct(Position)1_storage)"),
+        ]);
+        let entries = storage_entries(&old);
+        let labels: Vec<_> =
+            entries.iter().map(|entry| (entry.label.as_str(), entry.slot.to::<u64>())).collect();
+        assert_eq!(
+            labels,
+            [
+                ("owner", 0),
+                ("position.owner", 1),
+                ("position.active", 1),
+                ("position.amount", 2),
+                ("positions", 3),
+                ("positions[].owner", 0),
+                ("positions[].active", 0),
+                ("positions[].amount", 1),
+            ]
+        );
+        assert_eq!(entries[4].ty, "mapping(address => struct Position)");
+    }
+
+    #[test]
+    fn identical_layouts_are_compatible() {
+        let old = layout(&[
+            ("owner", "Vault", 0, 0, "t_address"),
+            ("positions", "Vault", 1, 0, "t_mapping(t_address,t_struct(Position)1_storage)"),
+        ]);
+        let diff = StorageLayoutDiff::new(&old, &old);
+        assert!(diff.changes.is_empty());
+        assert!(diff.is_compatible());
+    }
+
+    #[test]
+    fn appended_variables_are_compatible() {
+        let old = layout(&[("owner", "Vault", 0, 0, "t_address")]);
+        let new = layout(&[
+            ("owner", "Vault", 0, 0, "t_address"),
+            ("paused", "Vault", 0, 20, "t_bool"),
+            ("total", "Vault", 1, 0, "t_uint256"),
+        ]);
+        let diff = StorageLayoutDiff::new(&old, &new);
+        assert_eq!(
+            kinds(&diff),
+            [(StorageChangeKind::Appended, "paused"), (StorageChangeKind::Appended, "total")]
+        );
+        assert!(diff.is_compatible());
+    }
+
+    #[test]
+    fn detects_struct_packing_changes() {
+        let old = layout(&[("position", "Vault", 0, 0, "t_struct(Position)1_storage")]);
+
+        // a member packed into the free bytes of the first slot
+        let packed = layout(&[("position", "Vault", 0, 0, "t_struct(Position)2_storage")]);
+        let diff = StorageLayoutDiff::new(&old, &packed);
+        assert_eq!(kinds(&diff), [(StorageChangeKind::Appended, "position.expiry")]);
+        assert!(diff.is_compatible());
+
+        // reordered members change their offsets
+        let reordered = layout(&[("position", "Vault", 0, 0, "t_struct(Position)3_storage")]);
+        let diff = StorageLayoutDiff::new(&old, &reordered);
+        assert_eq!(
+            kinds(&diff),
+            [
+                (StorageChangeKind::Moved, "position.owner"),
+                (StorageChangeKind::Moved, "position.active")
+            ]
+        );
+        assert_eq!(diff.errors(), 2);
+    }
+
+    #[test]
+    fn compares_mapping_values() {
+        let old = layout(&[(
+            "positions",
+            "Vault",
+            0,
+            0,
+            "t_mapping(t_address,t_struct(Position)1_storage)",
+        )]);
+        let new = layout(&[(
+            "positions",
+            "VaultV2",
+            0,
+            0,
+            "t_mapping(t_address,t_struct(Position)3_storage)",
+        )]);
+        let diff = StorageLayoutDiff::new(&old, &new);
+        assert_eq!(
+            kinds(&diff),
+            [
+                (StorageChangeKind::Moved, "positions[].owner"),
+                (StorageChangeKind::Moved, "positions[].active"),
+            ]
+        );
+
+        // the values of a moved mapping are not compared
+        let new = layout(&[
+            ("owner", "VaultV2", 0, 0, "t_address"),
+            ("positions", "VaultV2", 1, 0, "t_mapping(t_address,t_struct(Position)3_storage)"),
+        ]);
+        let diff = StorageLayoutDiff::new(&old, &new);
+        assert_eq!(
+            kinds(&diff),
+            [(StorageChangeKind::Moved, "positions"), (StorageChangeKind::Overlapping, "owner")]
+        );
+        assert_eq!(diff.changes[1].overlaps.as_deref(), Some("positions"));
+    }
+
+    #[test]
+    fn detects_inheritance_reordering() {
+        // contract Vault is Ownable, Pausable -> contract Vault is Pausable, Ownable
+        let old = layout(&[
+            ("owner", "Ownable", 0, 0, "t_address"),
+            ("paused", "Pausable", 0, 20, "t_bool"),
+            ("total", "Vault", 1, 0, "t_uint256"),
+        ]);
+        let new = layout(&[
+            ("paused", "Pausable", 0, 0, "t_bool"),
+            ("owner", "Ownable", 0, 1, "t_address"),
+            ("total", "Vault", 1, 0, "t_uint256"),
+        ]);
+        let diff = StorageLayoutDiff::new(&old, &new);
+        assert_eq!(
+            kinds(&diff),
+            [(StorageChangeKind::Moved, "owner"), (StorageChangeKind::Moved, "paused")]
+        );
+        assert!(!diff.is_compatible());
+    }
+
+    #[test]
+    fn detects_resized_retyped_and_deleted_variables() {
+        let old = layout(&[
+            ("a", "Vault", 0, 0, "t_uint128"),
+            ("b", "Vault", 0, 16, "t_uint128"),
+            ("c", "Vault", 1, 0, "t_uint256"),
+            ("d", "Vault", 2, 0, "t_uint256"),
+        ]);
+        let new = layout(&[
+            ("a", "Vault", 0, 0, "t_uint64"),
+            ("b", "Vault", 0, 16, "t_int128"),
+            ("renamed", "Vault", 1, 0, "t_uint256"),
+        ]);
+        let diff = StorageLayoutDiff::new(&old, &new);
+        assert_eq!(
+            kinds(&diff),
+            [
+                (StorageChangeKind::Resized, "a"),
+                (StorageChangeKind::Retyped, "b"),
+                (StorageChangeKind::Renamed, "renamed"),
+                (StorageChangeKind::Deleted, "d"),
+            ]
+        );
+        assert_eq!(diff.errors(), 3);
+    }
+
+    #[test]
+    fn understands_storage_gaps() {
+        let old = layout(&[
+            ("owner", "Vault", 0, 0, "t_address"),
+            ("__gap", "Vault", 1, 0, "t_array(t_uint256)50_storage"),
+            ("total", "VaultChild", 51, 0, "t_uint256"),
+        ]);
+
+        // a variable added in the space of the shrunk gap
+        let new = layout(&[
+            ("owner", "Vault", 0, 0, "t_address"),
+            ("fee", "Vault", 1, 0, "t_uint256"),
+            ("__gap", "Vault", 2, 0, "t_array(t_uint256)49_storage"),
+            ("total", "VaultChild", 51, 0, "t_uint256"),
+        ]);
+        let diff = StorageLayoutDiff::new(&old, &new);
+        assert_eq!(
+            kinds(&diff),
+            [(StorageChangeKind::GapShrunk, "__gap"), (StorageChangeKind::AddedInGap, "fee")]
+        );
+        assert!(diff.is_compatible());
+
+        // the gap wasn't shrunk, the following variables moved
+        let new = layout(&[
+            ("owner", "Vault", 0, 0, "t_address"),
+            ("fee", "Vault", 1, 0, "t_uint256"),
+            ("__gap", "Vault", 2, 0, "t_array(t_uint256)50_storage"),
+            ("total", "VaultChild", 52, 0, "t_uint256"),
+        ]);
+        let diff = StorageLayoutDiff::new(&old, &new);
+        assert_eq!(
+            kinds(&diff),
+            [
+                (StorageChangeKind::Moved, "__gap"),
+                (StorageChangeKind::Moved, "total"),
+                (StorageChangeKind::AddedInGap, "fee"),
+            ]
+        );
+        assert!(!diff.is_compatible());
+    }
+}
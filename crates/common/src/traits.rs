@@ -33,6 +33,9 @@ pub trait TestFunctionExt {
 
     /// Returns whether this function is a `setUp` function.
     fn is_setup(&self) -> bool;
+
+    /// Returns whether this function is an `afterInvariant` function.
+    fn is_after_invariant(&self) -> bool;
 }
 
 impl TestFunctionExt for Function {
@@ -56,6 +59,10 @@ impl TestFunctionExt for Function {
     fn is_setup(&self) -> bool {
         self.name.is_setup()
     }
+
+    fn is_after_invariant(&self) -> bool {
+        self.name.is_after_invariant()
+    }
 }
 
 impl TestFunctionExt for String {
@@ -78,6 +85,10 @@ impl TestFunctionExt for String {
     fn is_setup(&self) -> bool {
         self.as_str().is_setup()
     }
+
+    fn is_after_invariant(&self) -> bool {
+        self.as_str().is_after_invariant()
+    }
 }
 
 impl TestFunctionExt for str {
@@ -100,6 +111,10 @@ impl TestFunctionExt for str {
     fn is_setup(&self) -> bool {
         self.eq_ignore_ascii_case("setup")
     }
+
+    fn is_after_invariant(&self) -> bool {
+        self == "afterInvariant"
+    }
 }
 
 /// An extension trait for `std::error::Error` for ABI encoding.
@@ -0,0 +1,506 @@
+//! Support for compiling the Vyper sources of a project with `vyper`
+
+use crate::{
+    compile::{CacheReport, CacheStatus},
+    fs,
+};
+use alloy_json_abi::JsonAbi;
+use alloy_primitives::{hex, keccak256, Bytes};
+use eyre::{Result, WrapErr};
+use foundry_compilers::{utils::RuntimeOrHandle, FileFilter};
+use foundry_config::{Config, VyperConfig};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The extension of Vyper source files
+pub const VYPER_EXTENSION: &str = "vy";
+
+/// The name of the file that caches the state of the compiled Vyper sources
+pub const VYPER_FILES_CACHE_FILENAME: &str = "vyper-files-cache.json";
+
+/// Returns true if the file is a Vyper source file
+pub fn is_vyper_file(path: impl AsRef<Path>) -> bool {
+    path.as_ref().extension().is_some_and(|ext| ext == VYPER_EXTENSION)
+}
+
+/// A `vyper` binary
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vyper {
+    /// Path to the binary
+    pub path: PathBuf,
+    /// The version reported by the binary
+    pub version: Version,
+}
+
+impl Vyper {
+    /// Returns the `vyper` binary configured in the `[vyper]` section.
+    ///
+    /// The pinned `version` is installed if it's missing, if neither a `path` nor a `version` is
+    /// configured the `vyper` on `PATH` is used.
+    pub fn find_or_install(config: &VyperConfig) -> Result<Self> {
+        if let Some(path) = &config.path {
+            let vyper = Self::new(path)?;
+            if let Some(version) = &config.version {
+                eyre::ensure!(
+                    is_same_release(&vyper.version, version),
+                    "`{}` is vyper {}, but version {version} is configured",
+                    path.display(),
+                    vyper.version
+                );
+            }
+            return Ok(vyper)
+        }
+
+        if let Some(version) = &config.version {
+            let path = installed_path(version)?;
+            if !path.exists() {
+                RuntimeOrHandle::new().block_on(install(version, &path))?;
+            }
+            return Self::new(path)
+        }
+
+        Self::new("vyper").wrap_err(
+            "failed to find vyper, set `vyper.version` to install it or `vyper.path` to use an \
+             existing binary",
+        )
+    }
+
+    /// Creates a new instance for the given binary, querying its version.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let output = Command::new(&path)
+            .arg("--version")
+            .output()
+            .wrap_err_with(|| format!("failed to execute `{}`", path.display()))?;
+        let version = String::from_utf8_lossy(&output.stdout);
+        let version = Version::parse(version.trim()).wrap_err_with(|| {
+            format!("failed to parse the version of `{}`: {version}", path.display())
+        })?;
+        Ok(Self { path, version })
+    }
+
+    /// Compiles the given sources, returning their artifacts in the same order.
+    pub fn compile(
+        &self,
+        root: &Path,
+        sources: &[PathBuf],
+        args: &[String],
+    ) -> Result<Vec<VyperArtifact>> {
+        let output = Command::new(&self.path)
+            .current_dir(root)
+            .args(["-f", "combined_json", "-p"])
+            .arg(root)
+            .args(args)
+            .args(sources)
+            .output()
+            .wrap_err_with(|| format!("failed to execute `{}`", self.path.display()))?;
+        if !output.status.success() {
+            eyre::bail!(
+                "vyper {} failed to compile:\n{}",
+                self.version,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+        }
+
+        let mut contracts: BTreeMap<String, serde_json::Value> =
+            serde_json::from_slice(&output.stdout)
+                .wrap_err("failed to parse the output of vyper")?;
+        sources
+            .iter()
+            .map(|source| {
+                let relative = source.strip_prefix(root).unwrap_or(source);
+                let contract = contracts
+                    .remove(&*source.to_string_lossy())
+                    .or_else(|| contracts.remove(&*relative.to_string_lossy()))
+                    .ok_or_else(|| {
+                        eyre::eyre!("vyper didn't output a contract for {}", relative.display())
+                    })?;
+                let contract: CombinedJsonContract = serde_json::from_value(contract)
+                    .wrap_err_with(|| format!("invalid vyper output for {}", relative.display()))?;
+                Ok(contract.into())
+            })
+            .collect()
+    }
+}
+
+/// The compiled Vyper sources of a project
+#[derive(Clone, Debug, Default)]
+pub struct VyperOutput {
+    /// The artifacts by the path of their source file
+    pub artifacts: BTreeMap<PathBuf, VyperArtifact>,
+    /// Whether the artifacts were all read from the cache
+    pub cached: bool,
+    /// The cache status of the sources before they were compiled
+    pub cache: CacheReport,
+}
+
+/// The artifact of a Vyper contract, in the format of the artifacts of Solidity contracts
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VyperArtifact {
+    /// The ABI of the contract
+    pub abi: JsonAbi,
+    /// The creation bytecode
+    pub bytecode: VyperBytecode,
+    /// The runtime bytecode
+    pub deployed_bytecode: VyperBytecode,
+    /// The selectors of the functions by their signature
+    #[serde(default)]
+    pub method_identifiers: BTreeMap<String, String>,
+}
+
+/// A bytecode object of a [VyperArtifact]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VyperBytecode {
+    /// The bytecode
+    pub object: Bytes,
+    /// The compressed source map, if `vyper` outputs one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_map: Option<String>,
+    /// Always empty, Vyper contracts have no libraries to link
+    #[serde(default)]
+    pub link_references: BTreeMap<String, serde_json::Value>,
+}
+
+/// A contract of the `combined_json` output of `vyper`
+#[derive(Deserialize)]
+struct CombinedJsonContract {
+    abi: JsonAbi,
+    bytecode: Bytes,
+    bytecode_runtime: Bytes,
+    #[serde(default)]
+    source_map: Option<serde_json::Value>,
+    #[serde(default)]
+    method_identifiers: BTreeMap<String, String>,
+}
+
+impl From<CombinedJsonContract> for VyperArtifact {
+    fn from(contract: CombinedJsonContract) -> Self {
+        let source_map = contract
+            .source_map
+            .as_ref()
+            .and_then(|map| map.get("pc_pos_map_compressed"))
+            .and_then(|map| map.as_str())
+            .map(str::to_string);
+        let method_identifiers = contract
+            .method_identifiers
+            .into_iter()
+            .map(|(signature, selector)| (signature, selector.trim_start_matches("0x").to_string()))
+            .collect();
+        Self {
+            abi: contract.abi,
+            bytecode: VyperBytecode {
+                object: contract.bytecode,
+                source_map: None,
+                link_references: Default::default(),
+            },
+            deployed_bytecode: VyperBytecode {
+                object: contract.bytecode_runtime,
+                source_map,
+                link_references: Default::default(),
+            },
+            method_identifiers,
+        }
+    }
+}
+
+/// The state of the Vyper sources at the time they were last compiled
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VyperCache {
+    /// Identifies the compiler version and settings that were used
+    settings: String,
+    /// The content hashes of the compiled sources, by their path relative to the project root
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl VyperCache {
+    /// Returns the cache status of a source, by its path relative to the project root.
+    fn status(&self, settings: &str, source: &Path, hash: &str, artifact: &Path) -> CacheStatus {
+        match self.files.get(source) {
+            None => CacheStatus::New,
+            Some(cached) if cached != hash => CacheStatus::ContentChanged,
+            Some(_) if self.settings != settings => CacheStatus::SettingsChanged,
+            Some(_) if !artifact.exists() => CacheStatus::MissingArtifact,
+            Some(_) => CacheStatus::Hit,
+        }
+    }
+}
+
+/// Compiles the `.vy` files in the sources of the project that match the filter and writes their
+/// artifacts to the project's artifacts directory.
+///
+/// Sources are only recompiled if any of them changed, or if the `vyper` version or settings
+/// changed. Returns `None` if the project has no Vyper sources.
+pub fn compile_project(
+    config: &Config,
+    filter: Option<&dyn FileFilter>,
+    quiet: bool,
+) -> Result<Option<VyperOutput>> {
+    let paths = config.project_paths();
+    let sources: Vec<_> = fs::files_with_ext(&paths.sources, VYPER_EXTENSION)
+        .into_iter()
+        .filter(|source| filter.map_or(true, |filter| filter.is_match(source)))
+        .collect();
+    if sources.is_empty() {
+        return Ok(None)
+    }
+
+    let vyper = Vyper::find_or_install(&config.vyper)?;
+    let args = vyper_args(config);
+    let settings = format!("{} {}", vyper.version, args.join(" "));
+
+    let cache_path = paths.cache.with_file_name(VYPER_FILES_CACHE_FILENAME);
+    let mut cache: VyperCache = fs::read_json_file(&cache_path).unwrap_or_default();
+    let hashes = sources
+        .iter()
+        .map(|source| Ok(hex::encode(keccak256(fs::read(source)?))))
+        .collect::<Result<Vec<_>>>()?;
+
+    let relative: Vec<_> = sources
+        .iter()
+        .map(|source| source.strip_prefix(&paths.root).unwrap_or(source).to_path_buf())
+        .collect();
+    let mut report = CacheReport::default();
+    for ((source, relative), hash) in sources.iter().zip(&relative).zip(&hashes) {
+        let artifact = artifact_path(&paths.artifacts, source);
+        report.files.insert(relative.clone(), cache.status(&settings, relative, hash, &artifact));
+    }
+
+    let is_cached = report.misses() == 0;
+    if is_cached {
+        let artifacts = sources
+            .into_iter()
+            .map(|source| {
+                let artifact = fs::read_json_file(&artifact_path(&paths.artifacts, &source))?;
+                Ok((source, artifact))
+            })
+            .collect::<Result<_>>()?;
+        if !quiet {
+            println!("No Vyper files changed, compilation skipped");
+        }
+        return Ok(Some(VyperOutput { artifacts, cached: true, cache: report }))
+    }
+
+    if !quiet {
+        println!("Compiling {} Vyper files with vyper {}", sources.len(), vyper.version);
+    }
+    let artifacts = vyper.compile(&paths.root, &sources, &args)?;
+
+    let mut output = VyperOutput { cache: report, ..Default::default() };
+    if cache.settings != settings {
+        cache = VyperCache { settings, files: BTreeMap::new() };
+    }
+    let sources = sources.into_iter().zip(relative);
+    for (((source, relative), hash), artifact) in sources.zip(hashes).zip(artifacts) {
+        let path = artifact_path(&paths.artifacts, &source);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write_json_file(&path, &artifact)?;
+        cache.files.insert(relative, hash);
+        output.artifacts.insert(source, artifact);
+    }
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write_json_file(&cache_path, &cache)?;
+
+    Ok(Some(output))
+}
+
+/// Returns the path of the artifact of a Vyper source, e.g. `out/Token.vy/Token.json`.
+pub fn artifact_path(artifacts: &Path, source: &Path) -> PathBuf {
+    let file_name = source.file_name().unwrap_or_default();
+    let name = source.file_stem().unwrap_or_default().to_string_lossy();
+    artifacts.join(file_name).join(format!("{name}.json"))
+}
+
+/// Returns the arguments passed to `vyper` for the settings of the config.
+fn vyper_args(config: &Config) -> Vec<String> {
+    let evm_version =
+        if config.cancun { "cancun".to_string() } else { config.evm_version.to_string() };
+    let mut args = vec!["--evm-version".to_string(), evm_version];
+    if let Some(optimize) = &config.vyper.optimize {
+        args.extend(["--optimize".to_string(), optimize.clone()]);
+    }
+    args
+}
+
+/// Returns true if both versions are the same release, ignoring build metadata like the commit.
+fn is_same_release(a: &Version, b: &Version) -> bool {
+    (a.major, a.minor, a.patch, &a.pre) == (b.major, b.minor, b.patch, &b.pre)
+}
+
+/// Returns the path the given version is installed at.
+fn installed_path(version: &Version) -> Result<PathBuf> {
+    let dir = Config::foundry_dir().ok_or_else(|| eyre::eyre!("failed to find home directory"))?;
+    let name = if cfg!(windows) { "vyper.exe" } else { "vyper" };
+    Ok(dir.join("vyper").join(version.to_string()).join(name))
+}
+
+/// A release of `vyper` on GitHub
+#[derive(Deserialize)]
+struct Release {
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// The sha256 checksums of the `vyper` release binaries that can be installed, by version and the
+/// suffix of the release asset of the platform.
+///
+/// Installing a version that isn't pinned here fails, `vyper.path` can point to a binary that was
+/// installed manually instead.
+const RELEASE_CHECKSUMS: &[(&str, &str, &str)] = &[];
+
+/// Returns the pinned sha256 checksum of the release binary of the given version and platform.
+fn release_checksum(version: &Version, suffix: &str) -> Option<&'static str> {
+    RELEASE_CHECKSUMS.iter().find_map(|(release, platform, checksum)| {
+        (release.parse().is_ok_and(|release| is_same_release(&release, version)) &&
+            *platform == suffix)
+            .then_some(*checksum)
+    })
+}
+
+/// Fails if the sha256 checksum of the downloaded binary isn't the expected one.
+fn verify_checksum(binary: &[u8], expected: &str) -> Result<()> {
+    let checksum = hex::encode(Sha256::digest(binary));
+    eyre::ensure!(
+        checksum.eq_ignore_ascii_case(expected.trim_start_matches("0x")),
+        "checksum mismatch of the downloaded vyper binary: expected {expected}, got {checksum}"
+    );
+    Ok(())
+}
+
+/// Downloads the release binary of the given version for the current platform to `path`.
+///
+/// The binary is verified against its pinned checksum in [`RELEASE_CHECKSUMS`] before it's made
+/// executable.
+async fn install(version: &Version, path: &Path) -> Result<()> {
+    let suffix = match std::env::consts::OS {
+        "linux" => ".linux",
+        "macos" => ".darwin",
+        "windows" => ".windows.exe",
+        os => eyre::bail!("vyper releases are not available for {os}"),
+    };
+    let checksum = release_checksum(version, suffix).ok_or_else(|| {
+        eyre::eyre!(
+            "vyper {version} has no pinned checksum for {suffix} and can't be installed, set \
+             `vyper.path` to an existing binary instead"
+        )
+    })?;
+
+    let client = reqwest::Client::builder().user_agent("foundry").build()?;
+    let url = format!("https://api.github.com/repos/vyperlang/vyper/releases/tags/v{version}");
+    let release = client.get(url).send().await?.error_for_status()?.text().await?;
+    let release: Release = serde_json::from_str(&release)?;
+    let asset = release
+        .assets
+        .into_iter()
+        .find(|asset| asset.name.ends_with(suffix))
+        .ok_or_else(|| eyre::eyre!("vyper {version} has no release binary for {suffix}"))?;
+
+    let binary = client
+        .get(asset.browser_download_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await
+        .wrap_err_with(|| format!("failed to download vyper {version}"))?;
+    verify_checksum(&binary, checksum)
+        .wrap_err_with(|| format!("failed to install {}", asset.name))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_combined_json_output() {
+        let contract = serde_json::json!({
+            "abi": [{
+                "type": "function",
+                "name": "totalSupply",
+                "inputs": [],
+                "outputs": [{ "name": "", "type": "uint256" }],
+                "stateMutability": "view"
+            }],
+            "bytecode": "0x6003361161000c",
+            "bytecode_runtime": "0x6003361161",
+            "source_map": { "pc_pos_map_compressed": "-1:-1:0:-;;;" },
+            "method_identifiers": { "totalSupply()": "0x18160ddd" }
+        });
+        let contract: CombinedJsonContract = serde_json::from_value(contract).unwrap();
+        let artifact = VyperArtifact::from(contract);
+        assert_eq!(artifact.deployed_bytecode.source_map.as_deref(), Some("-1:-1:0:-;;;"));
+        assert_eq!(artifact.method_identifiers["totalSupply()"], "18160ddd");
+
+        // can be read as an artifact
+        let json = serde_json::to_string(&artifact).unwrap();
+        let object: alloy_json_abi::ContractObject = serde_json::from_str(&json).unwrap();
+        assert_eq!(object.bytecode.unwrap(), artifact.bytecode.object);
+        assert_eq!(object.abi.unwrap().functions().count(), 1);
+    }
+
+    #[test]
+    fn can_get_cache_status() {
+        let artifacts = tempfile::tempdir().unwrap();
+        let source = Path::new("src/Token.vy");
+        let artifact = artifact_path(artifacts.path(), source);
+        let cache = VyperCache {
+            settings: "0.3.10 --evm-version paris".to_string(),
+            files: BTreeMap::from([(source.to_path_buf(), "aa".to_string())]),
+        };
+
+        let status = |settings, hash| cache.status(settings, source, hash, &artifact);
+        assert_eq!(status("0.3.10 --evm-version paris", "aa"), CacheStatus::MissingArtifact);
+        fs::create_dir_all(artifact.parent().unwrap()).unwrap();
+        fs::write(&artifact, "{}").unwrap();
+        assert_eq!(status("0.3.10 --evm-version paris", "aa"), CacheStatus::Hit);
+        assert_eq!(status("0.3.10 --evm-version paris", "bb"), CacheStatus::ContentChanged);
+        assert_eq!(status("0.3.10 --evm-version cancun", "aa"), CacheStatus::SettingsChanged);
+        assert_eq!(cache.status("", Path::new("src/Other.vy"), "aa", &artifact), CacheStatus::New);
+    }
+
+    #[test]
+    fn can_verify_checksum() {
+        let binary = b"vyper";
+        let checksum = hex::encode(Sha256::digest(binary));
+        verify_checksum(binary, &checksum).unwrap();
+        verify_checksum(binary, &format!("0x{}", checksum.to_uppercase())).unwrap();
+
+        let err = verify_checksum(b"not vyper", &checksum).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+    }
+
+    #[test]
+    fn can_get_artifact_path() {
+        assert_eq!(
+            artifact_path(Path::new("out"), Path::new("src/tokens/Token.vy")),
+            PathBuf::from("out/Token.vy/Token.json")
+        );
+    }
+}
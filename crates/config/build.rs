@@ -0,0 +1,17 @@
+use std::{env, fs, path::PathBuf};
+
+/// Embeds the chain registry, `assets/chains.json` or the file in the format of
+/// <https://chainid.network/chains.json> set in `FOUNDRY_CHAIN_REGISTRY`.
+fn main() {
+    println!("cargo:rerun-if-env-changed=FOUNDRY_CHAIN_REGISTRY");
+    let registry = match env::var_os("FOUNDRY_CHAIN_REGISTRY") {
+        Some(path) => PathBuf::from(path),
+        None => {
+            PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap()).join("assets/chains.json")
+        }
+    };
+    println!("cargo:rerun-if-changed={}", registry.display());
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("chains.json");
+    fs::copy(&registry, out)
+        .unwrap_or_else(|err| panic!("could not copy {}: {err}", registry.display()));
+}
@@ -0,0 +1,15 @@
+//! Configuration specific to the `forge build` command
+
+use serde::{Deserialize, Serialize};
+
+/// Contains the config for `forge build`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildConfig {
+    /// The minimum number of bytes every contract must stay below the code size limits.
+    ///
+    /// If set, `forge build` fails if the runtime size of a contract is within this margin
+    /// of the [EIP-170](https://eips.ethereum.org/EIPS/eip-170) limit, or its initcode size is
+    /// within this margin of the [EIP-3860](https://eips.ethereum.org/EIPS/eip-3860) limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_limit_margin: Option<usize>,
+}
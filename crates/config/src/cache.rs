@@ -172,7 +172,7 @@ impl Serialize for CachedEndpoints {
 }
 
 /// Content of the foundry cache folder
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct Cache {
     /// The list of chains in the cache
     pub chains: Vec<ChainCache>,
@@ -215,18 +215,34 @@ impl fmt::Display for Cache {
 }
 
 /// A representation of data for a given chain in the foundry cache
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ChainCache {
     /// The name of the chain
     pub name: String,
 
     /// A tuple containing block number and the block directory size in bytes
+    #[serde(serialize_with = "serialize_blocks")]
     pub blocks: Vec<(String, u64)>,
 
     /// The size of the block explorer directory in bytes
     pub block_explorer: u64,
 }
 
+/// Serializes the blocks of a [ChainCache] as `[{ "block": .., "size": .. }]`.
+fn serialize_blocks<S>(blocks: &[(String, u64)], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    #[derive(Serialize)]
+    struct Block<'a> {
+        block: &'a str,
+        size: u64,
+    }
+
+    serializer.collect_seq(blocks.iter().map(|(block, size)| Block { block, size: *size }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -309,4 +325,25 @@ mod tests {
                 - Block 2 (2.0 B)\n";
         assert_str_eq!(format!("{cache}"), expected);
     }
+
+    #[test]
+    fn cache_to_json() {
+        let cache = Cache {
+            chains: vec![ChainCache {
+                name: "mainnet".to_string(),
+                blocks: vec![("19000000".to_string(), 1024)],
+                block_explorer: 500,
+            }],
+        };
+        assert_eq!(
+            serde_json::to_value(&cache).unwrap(),
+            serde_json::json!({
+                "chains": [{
+                    "name": "mainnet",
+                    "blocks": [{ "block": "19000000", "size": 1024 }],
+                    "blockExplorer": 500
+                }]
+            })
+        );
+    }
 }
@@ -0,0 +1,308 @@
+//! An offline registry of chains with their names, currencies and block explorers.
+//!
+//! The built-in chains are embedded at build time from `assets/chains.json`, which follows the
+//! format of <https://chainid.network/chains.json>. Setting `FOUNDRY_CHAIN_REGISTRY` to the path
+//! of a newer dump of that file embeds it instead.
+//!
+//! Custom chains can be added in `~/.foundry/chains.toml`, one table per chain name:
+//!
+//! ```toml
+//! [my-rollup]
+//! id = 4242
+//! title = "My Rollup"
+//! currency = "ETH"
+//! explorer = "https://explorer.my-rollup.xyz"
+//! etherscan_api = "https://explorer.my-rollup.xyz/api"
+//! ```
+//!
+//! A custom chain replaces the built-in chain with the same id, and takes precedence over the
+//! built-in one with the same name.
+
+use crate::{etherscan::ResolvedEtherscanConfig, Config};
+use alloy_chains::{Chain, NamedChain};
+use eyre::WrapErr;
+use foundry_block_explorers::{errors::EtherscanError, Client};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// The name of the file of the custom chains in the foundry dir.
+pub const CHAINS_FILE_NAME: &str = "chains.toml";
+
+/// The embedded chain registry.
+const BUILTIN_CHAINS: &str = include_str!(concat!(env!("OUT_DIR"), "/chains.json"));
+
+/// The registry of the built-in chains and the custom ones of `~/.foundry/chains.toml`.
+static CHAIN_REGISTRY: Lazy<ChainRegistry> = Lazy::new(|| {
+    ChainRegistry::load().unwrap_or_else(|err| {
+        warn!("could not load the custom chains: {err:?}");
+        ChainRegistry::builtin()
+    })
+});
+
+/// Returns the registry of the built-in chains and the custom ones of `~/.foundry/chains.toml`.
+pub fn chain_registry() -> &'static ChainRegistry {
+    &CHAIN_REGISTRY
+}
+
+/// Parses a chain from its id or its name, custom chains included.
+pub fn parse_chain(chain: &str) -> Option<Chain> {
+    if let Ok(id) = chain.parse::<u64>() {
+        return Some(Chain::from_id(id))
+    }
+    if let Some(info) = chain_registry().by_name(chain) {
+        return Some(Chain::from_id(info.id))
+    }
+    chain.parse::<NamedChain>().ok().map(Chain::from_named)
+}
+
+/// Returns the Etherscan API and browser URLs of the chain, the ones of `~/.foundry/chains.toml`
+/// first.
+pub fn etherscan_urls(chain: Chain) -> Option<(String, String)> {
+    let custom = chain_registry().get(chain.id()).filter(|info| info.custom);
+    if let Some(api_url) = custom.and_then(|info| info.etherscan_api.as_ref()) {
+        let browser_url = custom.and_then(|info| info.explorer.clone());
+        return Some((api_url.clone(), browser_url.unwrap_or_else(|| api_url.clone())))
+    }
+    chain
+        .etherscan_urls()
+        .map(|(api_url, browser_url)| (api_url.to_string(), browser_url.to_string()))
+}
+
+/// Returns an Etherscan client for the chain, using the endpoints of `~/.foundry/chains.toml` for
+/// custom chains.
+pub fn etherscan_client(
+    chain: Chain,
+    api_key: impl Into<String>,
+) -> Result<Client, EtherscanError> {
+    let api_key = api_key.into();
+    match ResolvedEtherscanConfig::create(api_key.clone(), chain) {
+        Some(config) => config.into_client(),
+        None => Client::new(chain, api_key),
+    }
+}
+
+/// A chain of the registry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainInfo {
+    /// The EIP-155 chain id.
+    pub id: u64,
+    /// The name of the chain in foundry, e.g. `mainnet` or `arbitrum`.
+    pub name: String,
+    /// The full name of the chain, e.g. `Ethereum Mainnet`.
+    pub title: String,
+    /// The symbol of the native currency.
+    pub currency: String,
+    /// The URL of the block explorer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explorer: Option<String>,
+    /// The URL of the Etherscan compatible API of the block explorer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etherscan_api: Option<String>,
+    /// Whether the chain comes from `~/.foundry/chains.toml`.
+    pub custom: bool,
+}
+
+/// A chain of the embedded registry, in the format of <https://chainid.network/chains.json>.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegistryChain {
+    name: String,
+    chain_id: u64,
+    short_name: String,
+    native_currency: RegistryCurrency,
+    #[serde(default)]
+    explorers: Vec<RegistryExplorer>,
+}
+
+#[derive(Deserialize)]
+struct RegistryCurrency {
+    symbol: String,
+}
+
+#[derive(Deserialize)]
+struct RegistryExplorer {
+    url: String,
+}
+
+/// A chain of `~/.foundry/chains.toml`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CustomChain {
+    id: u64,
+    title: Option<String>,
+    currency: Option<String>,
+    explorer: Option<String>,
+    etherscan_api: Option<String>,
+}
+
+/// The known chains, sorted by id.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChainRegistry {
+    chains: Vec<ChainInfo>,
+}
+
+impl ChainRegistry {
+    /// Returns the registry of the embedded chains.
+    ///
+    /// The chains known to foundry are named like in the rest of foundry, the other ones by their
+    /// short name in the registry.
+    pub fn builtin() -> Self {
+        let chains: Vec<RegistryChain> =
+            serde_json::from_str(BUILTIN_CHAINS).expect("the embedded chain registry is valid");
+        let mut chains = chains
+            .into_iter()
+            .map(|entry| {
+                let chain = Chain::from_id(entry.chain_id);
+                ChainInfo {
+                    id: entry.chain_id,
+                    name: chain
+                        .named()
+                        .map(|named| named.to_string())
+                        .unwrap_or_else(|| entry.short_name.to_lowercase()),
+                    title: entry.name,
+                    currency: entry.native_currency.symbol,
+                    explorer: entry
+                        .explorers
+                        .into_iter()
+                        .next()
+                        .map(|explorer| explorer.url.trim_end_matches('/').to_string()),
+                    etherscan_api: chain.etherscan_urls().map(|(api_url, _)| api_url.to_string()),
+                    custom: false,
+                }
+            })
+            .collect::<Vec<_>>();
+        chains.sort_by_key(|chain| chain.id);
+        Self { chains }
+    }
+
+    /// Returns the registry of the embedded chains and the custom ones of
+    /// `~/.foundry/chains.toml`, if it exists.
+    pub fn load() -> eyre::Result<Self> {
+        let mut registry = Self::builtin();
+        if let Some(path) = Config::foundry_dir().map(|dir| dir.join(CHAINS_FILE_NAME)) {
+            if path.exists() {
+                registry.merge_file(&path)?;
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Merges the custom chains of the TOML file.
+    pub fn merge_file(&mut self, path: &Path) -> eyre::Result<()> {
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read {}", path.display()))?;
+        self.merge_toml(&content).wrap_err_with(|| format!("invalid chains in {}", path.display()))
+    }
+
+    /// Merges custom chains, one table per chain name.
+    pub fn merge_toml(&mut self, content: &str) -> eyre::Result<()> {
+        let custom: BTreeMap<String, CustomChain> = toml::from_str(content)?;
+        for (name, chain) in custom {
+            let builtin = self.get(chain.id).filter(|info| !info.custom).cloned();
+            let info = ChainInfo {
+                id: chain.id,
+                title: chain
+                    .title
+                    .or_else(|| builtin.as_ref().map(|info| info.title.clone()))
+                    .unwrap_or_else(|| name.clone()),
+                name,
+                currency: chain
+                    .currency
+                    .or_else(|| builtin.as_ref().map(|info| info.currency.clone()))
+                    .unwrap_or_else(|| "ETH".to_string()),
+                explorer: chain.explorer.or_else(|| builtin.as_ref()?.explorer.clone()),
+                etherscan_api: chain
+                    .etherscan_api
+                    .or_else(|| builtin.as_ref()?.etherscan_api.clone()),
+                custom: true,
+            };
+            match self.chains.binary_search_by_key(&info.id, |chain| chain.id) {
+                Ok(i) => self.chains[i] = info,
+                Err(i) => self.chains.insert(i, info),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the chain with the id.
+    pub fn get(&self, id: u64) -> Option<&ChainInfo> {
+        let i = self.chains.binary_search_by_key(&id, |chain| chain.id).ok()?;
+        self.chains.get(i)
+    }
+
+    /// Returns the chain with the name, ignoring case. Custom chains take precedence over
+    /// built-in ones with the same name.
+    pub fn by_name(&self, name: &str) -> Option<&ChainInfo> {
+        let mut chains = self.chains.iter().filter(|chain| chain.name.eq_ignore_ascii_case(name));
+        let first = chains.next()?;
+        Some(chains.find(|chain| chain.custom).unwrap_or(first))
+    }
+
+    /// Returns the chains whose name or title contains the query, ignoring case.
+    pub fn search<'a>(&'a self, query: &str) -> impl Iterator<Item = &'a ChainInfo> + 'a {
+        let query = query.to_lowercase();
+        self.chains.iter().filter(move |chain| {
+            chain.name.to_lowercase().contains(&query) ||
+                chain.title.to_lowercase().contains(&query)
+        })
+    }
+
+    /// Returns all the chains, sorted by id.
+    pub fn iter(&self) -> impl Iterator<Item = &ChainInfo> {
+        self.chains.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_load_builtin_chains() {
+        let registry = ChainRegistry::builtin();
+        let mainnet = registry.get(1).unwrap();
+        assert_eq!(mainnet.name, "mainnet");
+        assert_eq!(mainnet.currency, "ETH");
+        assert_eq!(mainnet.explorer.as_deref(), Some("https://etherscan.io"));
+        assert_eq!(registry.by_name("Base").unwrap().id, 8453);
+        assert!(registry.search("sepolia").any(|chain| chain.id == 11155111));
+        assert!(registry.iter().zip(registry.iter().skip(1)).all(|(a, b)| a.id < b.id));
+    }
+
+    #[test]
+    fn custom_chains_override_builtin_ones() {
+        let mut registry = ChainRegistry::builtin();
+        registry
+            .merge_toml(
+                r#"
+                [base]
+                id = 4242
+                title = "Not Base"
+                explorer = "https://explorer.example.com"
+                etherscan_api = "https://explorer.example.com/api"
+
+                [mainnet-fork]
+                id = 1
+                "#,
+            )
+            .unwrap();
+
+        // the custom chain has the name of a built-in one
+        let custom = registry.by_name("base").unwrap();
+        assert_eq!(custom.id, 4242);
+        assert_eq!(custom.currency, "ETH");
+        assert!(custom.custom);
+        assert!(!registry.get(8453).unwrap().custom);
+
+        // the custom chain has the id of a built-in one, whose details are kept
+        let fork = registry.get(1).unwrap();
+        assert_eq!(fork.name, "mainnet-fork");
+        assert_eq!(fork.title, "Ethereum Mainnet");
+        assert_eq!(fork.explorer.as_deref(), Some("https://etherscan.io"));
+        assert!(registry.by_name("mainnet").is_none());
+
+        assert!(registry.merge_toml("[broken]\nid = 1\nrpc = \"http://localhost\"").is_err());
+    }
+}
@@ -0,0 +1,32 @@
+//! Configuration specific to `chisel`
+
+use serde::{Deserialize, Serialize};
+
+/// Contains the config for the chisel REPL
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChiselConfig {
+    /// Whether the values chisel displays are annotated with their readable forms, which `!fmt`
+    /// toggles.
+    pub annotate: bool,
+    /// uints from `10 ** ether_min_exponent` are annotated with their value in ether.
+    pub ether_min_exponent: u8,
+    /// uints from `10 ** ether_max_exponent` are not annotated with their value in ether.
+    pub ether_max_exponent: u8,
+    /// The earliest unix timestamp which uints are annotated with the UTC datetime of.
+    pub timestamp_min: u64,
+    /// The latest unix timestamp which uints are annotated with the UTC datetime of.
+    pub timestamp_max: u64,
+}
+
+impl Default for ChiselConfig {
+    fn default() -> Self {
+        Self {
+            annotate: false,
+            ether_min_exponent: 15,
+            ether_max_exponent: 30,
+            // 2000-01-01 to 2100-01-01
+            timestamp_min: 946_684_800,
+            timestamp_max: 4_102_444_800,
+        }
+    }
+}
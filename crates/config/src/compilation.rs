@@ -0,0 +1,144 @@
+//! Overrides of the solc settings for the sources matching a glob
+
+use crate::Config;
+use alloy_primitives::{hex, keccak256};
+use foundry_compilers::{artifacts::output_selection::ContractOutputSelection, EvmVersion};
+use globset::GlobBuilder;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// The solc settings that override the settings of the profile for the sources matching a glob,
+/// configured in `compilation_overrides`:
+///
+/// ```toml
+/// [profile.default.compilation_overrides]
+/// "src/core/**" = { via_ir = true, optimizer_runs = 1000000 }
+/// "test/helpers/**" = { optimizer = false }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettingsOverrides {
+    /// Whether to enable the optimizer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub optimizer: Option<bool>,
+    /// The number of optimizer runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub optimizer_runs: Option<usize>,
+    /// Whether to compile through the Yul intermediate representation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub via_ir: Option<bool>,
+    /// The EVM version to compile for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evm_version: Option<EvmVersion>,
+    /// The extra output to include in the artifacts, replacing the `extra_output` of the profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_output: Option<Vec<ContractOutputSelection>>,
+}
+
+impl SettingsOverrides {
+    /// Applies the overrides to the settings of the `config`.
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(optimizer) = self.optimizer {
+            config.optimizer = optimizer;
+        }
+        if let Some(runs) = self.optimizer_runs {
+            config.optimizer_runs = runs;
+        }
+        if let Some(via_ir) = self.via_ir {
+            config.via_ir = via_ir;
+        }
+        if let Some(evm_version) = self.evm_version {
+            config.evm_version = evm_version;
+        }
+        if let Some(extra_output) = &self.extra_output {
+            config.extra_output = extra_output.clone();
+        }
+    }
+
+    /// Returns a short hash of the overrides, which names the directories of the artifacts and the
+    /// cache of the sources compiled with them.
+    pub fn settings_hash(&self) -> String {
+        let json = serde_json::to_vec(self).expect("overrides serialize");
+        hex::encode(&keccak256(json)[..8])
+    }
+}
+
+/// Returns the glob of `overrides`, and its overrides, that applies to the source at `path`,
+/// which is relative to the root of the project.
+///
+/// If several globs match, the most specific one applies: the glob with the most literal
+/// characters, then the longest glob, then the first glob in lexicographic order.
+pub fn matching_override<'a>(
+    overrides: &'a BTreeMap<String, SettingsOverrides>,
+    path: &Path,
+) -> Option<(&'a str, &'a SettingsOverrides)> {
+    let mut matching: Option<(&str, &SettingsOverrides)> = None;
+    for (glob, settings) in overrides {
+        let matcher = match GlobBuilder::new(glob).literal_separator(true).build() {
+            Ok(glob) => glob.compile_matcher(),
+            Err(err) => {
+                warn!(%glob, %err, "invalid glob of a compilation override");
+                continue
+            }
+        };
+        if !matcher.is_match(path) {
+            continue
+        }
+        if matching.map_or(true, |(best, _)| specificity(glob) > specificity(best)) {
+            matching = Some((glob, settings));
+        }
+    }
+    matching
+}
+
+/// The number of literal characters and the length of a glob.
+fn specificity(glob: &str) -> (usize, usize) {
+    let literals = glob.chars().filter(|c| !matches!(c, '*' | '?' | '[' | ']' | '{' | '}')).count();
+    (literals, glob.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(globs: &[&str]) -> BTreeMap<String, SettingsOverrides> {
+        globs
+            .iter()
+            .enumerate()
+            .map(|(runs, glob)| {
+                let settings =
+                    SettingsOverrides { optimizer_runs: Some(runs), ..Default::default() };
+                (glob.to_string(), settings)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn uses_most_specific_glob() {
+        let overrides = overrides(&["src/**", "src/core/*.sol", "src/core/Vault.sol"]);
+        let glob = |path: &str| matching_override(&overrides, Path::new(path)).map(|(g, _)| g);
+
+        assert_eq!(glob("src/core/Vault.sol"), Some("src/core/Vault.sol"));
+        assert_eq!(glob("src/core/Pool.sol"), Some("src/core/*.sol"));
+        assert_eq!(glob("src/core/libraries/Math.sol"), Some("src/**"));
+        assert_eq!(glob("src/Token.sol"), Some("src/**"));
+        assert_eq!(glob("test/Vault.t.sol"), None);
+    }
+
+    #[test]
+    fn breaks_ties_deterministically() {
+        // both globs have the same number of literal characters and the same length
+        let globs = ["src/?ore/Vault.sol", "src/core/?ault.sol"];
+        for order in [globs, [globs[1], globs[0]]] {
+            let overrides = overrides(&order);
+            let (glob, _) = matching_override(&overrides, Path::new("src/core/Vault.sol")).unwrap();
+            assert_eq!(glob, "src/?ore/Vault.sol");
+        }
+    }
+
+    #[test]
+    fn hashes_settings() {
+        let via_ir = SettingsOverrides { via_ir: Some(true), ..Default::default() };
+        assert_eq!(via_ir.settings_hash(), via_ir.clone().settings_hash());
+        assert_ne!(via_ir.settings_hash(), SettingsOverrides::default().settings_hash());
+    }
+}
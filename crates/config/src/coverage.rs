@@ -0,0 +1,20 @@
+//! Configuration for `forge coverage`
+
+use serde::{Deserialize, Serialize};
+
+/// Contains the config of the sources and contracts `forge coverage` reports on, the `[coverage]`
+/// section
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoverageConfig {
+    /// Globs of the source paths, relative to the project root, to exclude from the report, e.g.
+    /// `test/**`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    /// Globs of the source paths to report on even if they match a glob of `exclude`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Globs of the names of the contracts to exclude from the report, e.g. `*Mock*`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_contracts: Vec<String>,
+}
@@ -1,6 +1,7 @@
 //! Support for multiple Etherscan keys.
 
 use crate::{
+    chains::{etherscan_urls, parse_chain},
     resolve::{interpolate, UnresolvedEnvVarError, RE_PLACEHOLDER},
     Chain, Config, NamedChain,
 };
@@ -147,7 +148,11 @@ pub struct EtherscanConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
     /// The etherscan API KEY that's required to make requests
+    #[serde(default)]
     pub key: EtherscanApiKey,
+    /// Additional API keys of the chain, requests are spread across all the keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<EtherscanApiKey>,
 }
 
 // === impl EtherscanConfig ===
@@ -155,6 +160,9 @@ pub struct EtherscanConfig {
 impl EtherscanConfig {
     /// Returns the etherscan config required to create a client.
     ///
+    /// The first of the `key` and the `keys` is the resolved `key`, the others are the resolved
+    /// `keys`, see [ResolvedEtherscanConfig::api_keys].
+    ///
     /// # Errors
     ///
     /// Returns an error if the type holds a reference to an env var and the env var is not set or
@@ -163,7 +171,7 @@ impl EtherscanConfig {
         self,
         alias: Option<&str>,
     ) -> Result<ResolvedEtherscanConfig, EtherscanConfigError> {
-        let EtherscanConfig { chain, mut url, key } = self;
+        let EtherscanConfig { chain, mut url, key, keys } = self;
 
         if let Some(url) = &mut url {
             *url = interpolate(url)?;
@@ -172,26 +180,37 @@ impl EtherscanConfig {
         let (chain, alias) = match (chain, alias) {
             // fill one with the other
             (Some(chain), None) => (Some(chain), Some(chain.to_string())),
-            (None, Some(alias)) => (alias.parse().ok(), Some(alias.into())),
+            (None, Some(alias)) => (parse_chain(alias), Some(alias.into())),
             // leave as is
             (Some(chain), Some(alias)) => (Some(chain), Some(alias.into())),
             (None, None) => (None, None),
         };
-        let key = key.resolve()?;
+        let mut keys = std::iter::once(key)
+            .chain(keys)
+            .filter(|key| !key.is_empty())
+            .map(EtherscanApiKey::resolve)
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .flat_map(|key| split_api_keys(key))
+            .collect::<Vec<_>>();
+        let key = if keys.is_empty() { String::new() } else { keys.remove(0) };
 
         match (chain, url) {
             (Some(chain), Some(api_url)) => Ok(ResolvedEtherscanConfig {
                 api_url,
-                browser_url: chain.etherscan_urls().map(|(_, url)| url.to_string()),
+                browser_url: etherscan_urls(chain).map(|(_, url)| url),
                 key,
+                keys,
                 chain: Some(chain),
             }),
-            (Some(chain), None) => ResolvedEtherscanConfig::create(key, chain).ok_or_else(|| {
-                let msg = alias.map(|a| format!(" `{a}`")).unwrap_or_default();
-                EtherscanConfigError::UnknownChain(msg, chain)
-            }),
+            (Some(chain), None) => ResolvedEtherscanConfig::create(key, chain)
+                .map(|config| ResolvedEtherscanConfig { keys, ..config })
+                .ok_or_else(|| {
+                    let msg = alias.map(|a| format!(" `{a}`")).unwrap_or_default();
+                    EtherscanConfigError::UnknownChain(msg, chain)
+                }),
             (None, Some(api_url)) => {
-                Ok(ResolvedEtherscanConfig { api_url, browser_url: None, key, chain: None })
+                Ok(ResolvedEtherscanConfig { api_url, browser_url: None, key, keys, chain: None })
             }
             (None, None) => {
                 let msg = alias
@@ -212,8 +231,11 @@ pub struct ResolvedEtherscanConfig {
     /// Optional browser URL.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub browser_url: Option<String>,
-    /// The resolved API key.
+    /// The resolved API key, the first one if the chain has several keys
     pub key: String,
+    /// The additional resolved API keys of the chain, requests are spread across all the keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<String>,
     /// The chain name or EIP-155 chain ID.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub chain: Option<Chain>,
@@ -223,15 +245,36 @@ pub struct ResolvedEtherscanConfig {
 
 impl ResolvedEtherscanConfig {
     /// Creates a new instance using the api key and chain
+    ///
+    /// The api key can hold several keys separated by commas, see [Self::set_api_keys].
     pub fn create(api_key: impl Into<String>, chain: impl Into<Chain>) -> Option<Self> {
         let chain = chain.into();
-        let (api_url, browser_url) = chain.etherscan_urls()?;
-        Some(Self {
-            api_url: api_url.to_string(),
-            browser_url: Some(browser_url.to_string()),
-            key: api_key.into(),
+        let (api_url, browser_url) = etherscan_urls(chain)?;
+        let config = Self {
+            api_url,
+            browser_url: Some(browser_url),
+            key: String::new(),
+            keys: Vec::new(),
             chain: Some(chain),
-        })
+        };
+        Some(config.with_api_keys(&api_key.into()))
+    }
+
+    /// Sets the API keys and consumes the type, see [Self::set_api_keys]
+    pub fn with_api_keys(mut self, api_key: &str) -> Self {
+        self.set_api_keys(api_key);
+        self
+    }
+
+    /// Sets the API keys
+    ///
+    /// The api key holds several keys separated by commas if it is set from an env var such as
+    /// `ETHERSCAN_API_KEY=KEY1,KEY2`.
+    pub fn set_api_keys(&mut self, api_key: &str) -> &mut Self {
+        let mut keys = split_api_keys(api_key);
+        self.key = keys.next().unwrap_or_default();
+        self.keys = keys.collect();
+        self
     }
 
     /// Sets the chain value and consumes the type
@@ -245,21 +288,30 @@ impl ResolvedEtherscanConfig {
     /// Sets the chain value
     pub fn set_chain(&mut self, chain: impl Into<Chain>) -> &mut Self {
         let chain = chain.into();
-        if let Some((api, browser)) = chain.etherscan_urls() {
-            self.api_url = api.to_string();
-            self.browser_url = Some(browser.to_string());
+        if let Some((api, browser)) = etherscan_urls(chain) {
+            self.api_url = api;
+            self.browser_url = Some(browser);
         }
         self.chain = Some(chain);
         self
     }
 
+    /// Returns all the API keys of the config, the `key` followed by the `keys`.
+    pub fn api_keys(&self) -> Vec<&str> {
+        std::iter::once(&self.key)
+            .chain(&self.keys)
+            .map(String::as_str)
+            .filter(|key| !key.is_empty())
+            .collect()
+    }
+
     /// Returns the corresponding `foundry_block_explorers::Client`, configured with the `api_url`,
-    /// `api_key` and cache
+    /// the first API key and cache
     pub fn into_client(
         self,
     ) -> Result<foundry_block_explorers::Client, foundry_block_explorers::errors::EtherscanError>
     {
-        let ResolvedEtherscanConfig { api_url, browser_url, key: api_key, chain } = self;
+        let ResolvedEtherscanConfig { api_url, browser_url, key: api_key, chain, .. } = self;
         let (mainnet_api, mainnet_url) = NamedChain::Mainnet.etherscan_urls().expect("exist; qed");
 
         let cache = chain
@@ -294,6 +346,11 @@ impl ResolvedEtherscanConfig {
     }
 }
 
+/// Returns the keys of an API key which holds several keys separated by commas.
+fn split_api_keys(api_key: &str) -> impl Iterator<Item = String> + '_ {
+    api_key.split(',').map(str::trim).filter(|key| !key.is_empty()).map(str::to_string)
+}
+
 /// Represents a single etherscan API key
 ///
 /// This type preserves the value as it's stored in the config. If the value is a reference to an
@@ -313,6 +370,11 @@ pub enum EtherscanApiKey {
 // === impl EtherscanApiKey ===
 
 impl EtherscanApiKey {
+    /// Returns true if this is an empty raw key
+    pub fn is_empty(&self) -> bool {
+        matches!(self, EtherscanApiKey::Key(key) if key.is_empty())
+    }
+
     /// Returns the key variant
     pub fn as_key(&self) -> Option<&str> {
         match self {
@@ -342,6 +404,12 @@ impl EtherscanApiKey {
     }
 }
 
+impl Default for EtherscanApiKey {
+    fn default() -> Self {
+        EtherscanApiKey::Key(String::new())
+    }
+}
+
 impl Serialize for EtherscanApiKey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -390,6 +458,7 @@ mod tests {
                 chain: Some(Mainnet.into()),
                 url: None,
                 key: EtherscanApiKey::Key("ABCDEFG".to_string()),
+                keys: vec![],
             },
         );
 
@@ -407,6 +476,7 @@ mod tests {
                 chain: Some(Mainnet.into()),
                 url: Some("https://api.etherscan.io/api".to_string()),
                 key: EtherscanApiKey::Key("ABCDEFG".to_string()),
+                keys: vec![],
             },
         );
 
@@ -425,6 +495,7 @@ mod tests {
                 chain: Some(Mainnet.into()),
                 url: Some("https://api.etherscan.io/api".to_string()),
                 key: EtherscanApiKey::Env(format!("${{{env}}}")),
+                keys: vec![],
             },
         );
 
@@ -441,4 +512,36 @@ mod tests {
 
         std::env::remove_var(env);
     }
+
+    #[test]
+    fn can_resolve_multiple_keys() {
+        let env = "_CONFIG_ETHERSCAN_POOL_KEYS";
+        std::env::set_var(env, "KEY3, KEY4");
+        let config = EtherscanConfig {
+            chain: Some(Mainnet.into()),
+            url: None,
+            key: EtherscanApiKey::default(),
+            keys: vec![
+                EtherscanApiKey::Key("KEY1".to_string()),
+                EtherscanApiKey::Key("KEY2".to_string()),
+                EtherscanApiKey::Env(format!("${{{env}}}")),
+            ],
+        };
+
+        let config = config.resolve(None).unwrap();
+        assert_eq!(config.key, "KEY1");
+        assert_eq!(config.keys, ["KEY2", "KEY3", "KEY4"]);
+        assert_eq!(config.api_keys(), ["KEY1", "KEY2", "KEY3", "KEY4"]);
+        let _ = config.into_client().unwrap();
+
+        let config = ResolvedEtherscanConfig::create("ABCDEFG", Mainnet).unwrap();
+        assert_eq!(config.api_keys(), ["ABCDEFG"]);
+        assert!(config.keys.is_empty());
+
+        let config = ResolvedEtherscanConfig::create("KEY1, KEY2", Mainnet).unwrap();
+        assert_eq!(config.key, "KEY1");
+        assert_eq!(config.keys, ["KEY2"]);
+
+        std::env::remove_var(env);
+    }
 }
@@ -15,6 +15,8 @@ pub struct FormatterConfig {
     pub int_types: IntTypes,
     /// Style of multiline function header in case it doesn't fit
     pub multiline_func_header: MultilineFuncHeaderStyle,
+    /// Style of multiline call arguments in case the call doesn't fit
+    pub call_arg_wrapping: CallArgWrapping,
     /// Style of quotation marks
     pub quote_style: QuoteStyle,
     /// Style of underscores in number literals
@@ -32,7 +34,17 @@ pub struct FormatterConfig {
     /// Add new line at start and end of contract declarations
     pub contract_new_lines: bool,
     /// Sort import statements alphabetically in groups (a group is separated by a newline).
+    ///
+    /// Within a group, imports are split into external dependencies, project absolute (`src/`,
+    /// `test/`, `script/`) and relative imports, or into the configured
+    /// [`import_groups`](Self::import_groups).
     pub sort_imports: bool,
+    /// Custom order of the import groups used by `sort_imports`, by path prefix.
+    ///
+    /// Every prefix defines a group, e.g. `["forge-std/", "@openzeppelin/", "src/"]`. Imports that
+    /// don't match any prefix are grouped after them, followed by relative imports.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub import_groups: Vec<String>,
 }
 
 /// Style of uint/int256 types
@@ -157,11 +169,26 @@ pub enum MultilineFuncHeaderStyle {
     ParamsFirst,
     /// Write function attributes multiline first
     AttributesFirst,
+    /// Write function attributes multiline first and the function parameters on a single
+    /// indented line if they don't fit after the function name
+    ParamsSingleLine,
     /// If function params or attrs are multiline
     /// split the rest
     All,
 }
 
+/// Style of call arguments in case the call doesn't fit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallArgWrapping {
+    /// Write the arguments on a single indented line if they fit, one per line otherwise
+    Compact,
+    /// Write every argument on a separate line
+    All,
+    /// Write as many arguments per line as fit
+    Fill,
+}
+
 impl Default for FormatterConfig {
     fn default() -> Self {
         FormatterConfig {
@@ -170,6 +197,7 @@ impl Default for FormatterConfig {
             bracket_spacing: false,
             int_types: IntTypes::Long,
             multiline_func_header: MultilineFuncHeaderStyle::AttributesFirst,
+            call_arg_wrapping: CallArgWrapping::Compact,
             quote_style: QuoteStyle::Double,
             number_underscore: NumberUnderscore::Preserve,
             hex_underscore: HexUnderscore::Remove,
@@ -179,6 +207,7 @@ impl Default for FormatterConfig {
             ignore: vec![],
             contract_new_lines: false,
             sort_imports: false,
+            import_groups: vec![],
         }
     }
 }
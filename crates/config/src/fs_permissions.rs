@@ -10,11 +10,20 @@ use std::{
 /// Configures file system access
 ///
 /// E.g. for cheat codes (`vm.writeFile`)
-#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(transparent)]
+///
+/// This is either configured as a list of permissions, or as a table with a `default` mode and the
+/// list of `permissions`:
+///
+/// ```toml
+/// fs_permissions = { default = "read", permissions = [{ access = "read-write", path = "./out"}] }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct FsPermissions {
     /// what kind of access is allowed
     pub permissions: Vec<PathPermission>,
+    /// what kind of access is allowed to paths in the project root that don't match any of the
+    /// `permissions`
+    pub default: FsAccessPermission,
 }
 
 // === impl FsPermissions ===
@@ -22,7 +31,13 @@ pub struct FsPermissions {
 impl FsPermissions {
     /// Creates anew instance with the given `permissions`
     pub fn new(permissions: impl IntoIterator<Item = PathPermission>) -> Self {
-        Self { permissions: permissions.into_iter().collect() }
+        Self { permissions: permissions.into_iter().collect(), default: FsAccessPermission::None }
+    }
+
+    /// Sets the access that's granted to paths in the project root that don't match any permission
+    pub fn with_default(mut self, default: FsAccessPermission) -> Self {
+        self.default = default;
+        self
     }
 
     /// Adds a new permission
@@ -51,6 +66,11 @@ impl FsPermissions {
     ///
     /// And we check for `./out/contracts/MyContract.sol` we will get `read-write` as permission.
     pub fn find_permission(&self, path: &Path) -> Option<FsAccessPermission> {
+        self.find_path_permission(path).map(|perm| perm.access)
+    }
+
+    /// Same as [`Self::find_permission`] but returns the matching [`PathPermission`]
+    pub fn find_path_permission(&self, path: &Path) -> Option<&PathPermission> {
         let mut permission: Option<&PathPermission> = None;
         for perm in &self.permissions {
             if path.starts_with(&perm.path) {
@@ -63,7 +83,7 @@ impl FsPermissions {
                 permission = Some(perm);
             }
         }
-        permission.map(|perm| perm.access)
+        permission
     }
 
     /// Updates all `allowed_paths` and joins ([`Path::join`]) the `root` with all entries
@@ -97,6 +117,51 @@ impl FsPermissions {
     }
 }
 
+impl Serialize for FsPermissions {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Table<'a> {
+            default: FsAccessPermission,
+            permissions: &'a [PathPermission],
+        }
+
+        // keep the plain list format unless a default mode is configured
+        if self.default == FsAccessPermission::None {
+            self.permissions.serialize(serializer)
+        } else {
+            Table { default: self.default, permissions: &self.permissions }.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FsPermissions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Permissions {
+            List(Vec<PathPermission>),
+            Table {
+                #[serde(default)]
+                default: FsAccessPermission,
+                #[serde(default)]
+                permissions: Vec<PathPermission>,
+            },
+        }
+        match Permissions::deserialize(deserializer)? {
+            Permissions::List(permissions) => Ok(Self::new(permissions)),
+            Permissions::Table { default, permissions } => {
+                Ok(Self::new(permissions).with_default(default))
+            }
+        }
+    }
+}
+
 /// Represents an access permission to a single path
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PathPermission {
@@ -277,4 +342,27 @@ mod tests {
         let permission = permissions.find_permission(Path::new("./out/MyContract.sol")).unwrap();
         assert_eq!(FsAccessPermission::Write, permission);
     }
+
+    #[test]
+    fn can_deserialize_default_mode() {
+        let permissions: FsPermissions =
+            serde_json::from_str(r#"[{ "access": "read", "path": "./out" }]"#).unwrap();
+        assert_eq!(permissions, FsPermissions::new([PathPermission::read("./out")]));
+        assert_eq!(
+            serde_json::to_string(&permissions).unwrap(),
+            r#"[{"access":"read","path":"./out"}]"#
+        );
+
+        let permissions: FsPermissions = serde_json::from_str(
+            r#"{ "default": "read", "permissions": [{ "access": true, "path": "./out" }] }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            permissions,
+            FsPermissions::new([PathPermission::read_write("./out")])
+                .with_default(FsAccessPermission::Read)
+        );
+        let roundtrip = serde_json::to_string(&permissions).unwrap();
+        assert_eq!(serde_json::from_str::<FsPermissions>(&roundtrip).unwrap(), permissions);
+    }
 }
@@ -86,6 +86,8 @@ pub struct FuzzDictionaryConfig {
     /// Once the fuzzer exceeds this limit, it will start evicting random entries
     #[serde(deserialize_with = "crate::deserialize_usize_or_max")]
     pub max_fuzz_dictionary_values: usize,
+    /// The flag indicating whether to print the dictionary collected before each campaign
+    pub dump_dictionary: bool,
 }
 
 impl Default for FuzzDictionaryConfig {
@@ -98,6 +100,7 @@ impl Default for FuzzDictionaryConfig {
             max_fuzz_dictionary_addresses: (300 * 1024 * 1024) / 20,
             // limit this to 200MB
             max_fuzz_dictionary_values: (200 * 1024 * 1024) / 32,
+            dump_dictionary: false,
         }
     }
 }
@@ -0,0 +1,18 @@
+//! Configuration specific to `forge test --gas-report`
+
+use serde::{Deserialize, Serialize};
+
+/// Contains the config for gas reports
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasReportConfig {
+    /// Whether to ignore the calls made in `setUp()`.
+    ///
+    /// Contracts deployed in `setUp()` are still reported.
+    pub ignore_setup: bool,
+    /// Globs of the contracts or functions to report, e.g. `Vault` or `Vault.deposit*`.
+    ///
+    /// Everything that passes `gas_reports` is reported if empty.
+    pub include: Vec<String>,
+    /// Globs of the contracts or functions to leave out of the report, e.g. `Vault.withdraw*`.
+    pub exclude: Vec<String>,
+}
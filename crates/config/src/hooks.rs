@@ -0,0 +1,74 @@
+//! Configuration of the webhooks of `anvil`
+
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Contains the config of the webhooks `anvil` posts on-chain events to, the `[hooks]` section
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// The URL the JSON payloads of the events are posted to, `--hook-url`.
+    ///
+    /// No hooks are posted if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The events that are posted, all of them by default.
+    pub events: Vec<HookEvent>,
+    /// The addresses whose transactions are posted as `transaction` events.
+    ///
+    /// More addresses can be watched with `anvil_addAddressWatch`.
+    pub watch: Vec<Address>,
+    /// How many times a failed delivery is retried, with an exponential backoff.
+    pub retries: u32,
+    /// The timeout of a delivery, in milliseconds.
+    pub timeout: u64,
+}
+
+impl HooksConfig {
+    /// Returns whether the event is posted.
+    pub fn is_enabled(&self, event: HookEvent) -> bool {
+        self.url.is_some() && self.events.contains(&event)
+    }
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            events: HookEvent::ALL.to_vec(),
+            watch: Vec::new(),
+            retries: 3,
+            timeout: 5_000,
+        }
+    }
+}
+
+/// The on-chain events `anvil` posts to the webhook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookEvent {
+    /// A new block was mined.
+    Block,
+    /// A transaction from or to a watched address was mined.
+    Transaction,
+    /// A contract was deployed.
+    Deployment,
+}
+
+impl HookEvent {
+    /// All events.
+    pub const ALL: [HookEvent; 3] =
+        [HookEvent::Block, HookEvent::Transaction, HookEvent::Deployment];
+}
+
+impl fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            HookEvent::Block => "block",
+            HookEvent::Transaction => "transaction",
+            HookEvent::Deployment => "deployment",
+        };
+        f.write_str(s)
+    }
+}
@@ -14,6 +14,9 @@ pub enum InlineConfigParserError {
     /// An error occurred while trying to parse a boolean configuration value
     #[error("Invalid config value for key '{0}'. Unable to parse '{1}' into a boolean value")]
     ParseBool(String, String),
+    /// An error occurred while trying to parse an EVM version configuration value
+    #[error("Invalid config value for key '{0}'. '{1}' is not a valid EVM version")]
+    ParseEvmVersion(String, String),
 }
 
 /// Wrapper error struct that catches config parsing
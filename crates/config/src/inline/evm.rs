@@ -0,0 +1,210 @@
+use super::{remove_whitespaces, InlineConfigParserError, INLINE_CONFIG_PREFIX};
+use crate::utils::evm_spec_id;
+use foundry_compilers::EvmVersion;
+use revm_primitives::SpecId;
+
+const INLINE_CONFIG_EVM_VERSION_KEY: &str = "evm_version";
+const INLINE_CONFIG_FORK_URL_KEY: &str = "fork_url";
+const INLINE_CONFIG_FORK_BLOCK_NUMBER_KEY: &str = "fork_block_number";
+const INLINE_CONFIG_FORK_NO_STORAGE_CACHING_KEY: &str = "fork.no_storage_caching";
+
+/// EVM settings of a test contract or of a single test function, declared inline.
+///
+/// ```solidity
+/// /// forge-config: default.evm_version = "cancun"
+/// /// forge-config: default.fork_url = "mainnet"
+/// /// forge-config: default.fork_block_number = 19000000
+/// contract MyTest is Test {
+///     /// forge-config: default.evm_version = "london"
+///     function test_London() public {...}
+///
+///     /// forge-config: default.fork.no_storage_caching = true
+///     function test_Uncached() public {...}
+/// }
+/// ```
+///
+/// Settings of a test function take precedence over the settings of its contract, which take
+/// precedence over the settings of the profile. `fork_url` is either a URL or an alias of the
+/// `rpc_endpoints` table, a `fork_block_number` without `fork_url` forks the `eth_rpc_url` of the
+/// profile. `fork.no_storage_caching` disables the RPC storage caching of the fork and of the forks
+/// created by the tests.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InlineEvmConfig {
+    /// The EVM spec derived from the `evm_version`
+    pub spec_id: Option<SpecId>,
+    /// The URL or rpc alias to fork
+    pub fork_url: Option<String>,
+    /// The block to fork at
+    pub fork_block_number: Option<u64>,
+    /// Whether to disable the RPC storage caching of forks
+    pub no_storage_caching: Option<bool>,
+}
+
+impl InlineEvmConfig {
+    /// Tries to parse the EVM settings from the given config lines.
+    ///
+    /// Returns
+    /// - `Some(Self)` in case some EVM settings are configured.
+    /// - `None` in case none of the lines configure an EVM setting.
+    /// - `Err(InlineConfigParserError)` in case of a wrong configuration value.
+    pub fn try_parse(configs: &[String]) -> Result<Option<Self>, InlineConfigParserError> {
+        let mut config = Self::default();
+        let mut found = false;
+
+        for line in configs {
+            let line = remove_whitespaces(line);
+            // i.e. "forge-config:default.evm_version=cancun"
+            let Some(line) = line.strip_prefix(&format!("{INLINE_CONFIG_PREFIX}:")) else {
+                continue
+            };
+            let Some((_profile, key_value)) = line.split_once('.') else { continue };
+            let Some((key, value)) = key_value.split_once('=') else { continue };
+            let value = value.trim_matches('"');
+            match key {
+                INLINE_CONFIG_EVM_VERSION_KEY => config.spec_id = Some(parse_spec_id(key, value)?),
+                INLINE_CONFIG_FORK_URL_KEY => config.fork_url = Some(value.to_string()),
+                INLINE_CONFIG_FORK_BLOCK_NUMBER_KEY => {
+                    config.fork_block_number = Some(value.parse().map_err(|_| {
+                        InlineConfigParserError::ParseInt(key.to_string(), value.to_string())
+                    })?)
+                }
+                INLINE_CONFIG_FORK_NO_STORAGE_CACHING_KEY => {
+                    config.no_storage_caching = Some(value.parse().map_err(|_| {
+                        InlineConfigParserError::ParseBool(key.to_string(), value.to_string())
+                    })?)
+                }
+                _ => continue,
+            }
+            found = true;
+        }
+
+        Ok(found.then_some(config))
+    }
+
+    /// Returns the settings of `self`, falling back to `other` for the ones that are not set.
+    pub fn or(&self, other: &Self) -> Self {
+        Self {
+            spec_id: self.spec_id.or(other.spec_id),
+            fork_url: self.fork_url.clone().or_else(|| other.fork_url.clone()),
+            fork_block_number: self.fork_block_number.or(other.fork_block_number),
+            no_storage_caching: self.no_storage_caching.or(other.no_storage_caching),
+        }
+    }
+
+    /// Returns true if `self` and `other` configure the same setting with different values.
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        fn conflicts<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+            matches!((a, b), (Some(a), Some(b)) if a != b)
+        }
+        conflicts(&self.spec_id, &other.spec_id) ||
+            conflicts(&self.fork_url, &other.fork_url) ||
+            conflicts(&self.fork_block_number, &other.fork_block_number) ||
+            conflicts(&self.no_storage_caching, &other.no_storage_caching)
+    }
+
+    /// Returns true if a fork is configured
+    pub fn is_fork(&self) -> bool {
+        self.fork_url.is_some() || self.fork_block_number.is_some()
+    }
+
+    /// Returns true if the RPC storage caching of forks is disabled
+    pub fn is_storage_caching_disabled(&self) -> bool {
+        self.no_storage_caching.unwrap_or_default()
+    }
+}
+
+/// Parses the EVM spec of an `evm_version`.
+///
+/// Also accepts `cancun`, which isn't an [EvmVersion] yet, see [`Config::cancun`](crate::Config).
+fn parse_spec_id(key: &str, value: &str) -> Result<SpecId, InlineConfigParserError> {
+    if value.eq_ignore_ascii_case("cancun") {
+        return Ok(SpecId::CANCUN)
+    }
+    match value.parse::<EvmVersion>() {
+        Ok(evm_version) => Ok(evm_spec_id(&evm_version)),
+        Err(_) => Err(InlineConfigParserError::ParseEvmVersion(key.to_string(), value.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_evm_config() {
+        let configs = [
+            "forge-config: default.evm_version = \"cancun\"".to_string(),
+            "forge-config: default.fork_url = mainnet".to_string(),
+            "forge-config: default.fork_block_number = 19000000".to_string(),
+            "forge-config: default.fuzz.runs = 10".to_string(),
+        ];
+        let config = InlineEvmConfig::try_parse(&configs).unwrap().unwrap();
+        assert_eq!(
+            config,
+            InlineEvmConfig {
+                spec_id: Some(SpecId::CANCUN),
+                fork_url: Some("mainnet".to_string()),
+                fork_block_number: Some(19000000),
+                no_storage_caching: None,
+            }
+        );
+
+        let configs = ["forge-config: default.evm_version = london".to_string()];
+        let config = InlineEvmConfig::try_parse(&configs).unwrap().unwrap();
+        assert_eq!(config.spec_id, Some(SpecId::LONDON));
+        assert!(!config.is_fork());
+    }
+
+    #[test]
+    fn ignores_other_configs() {
+        let configs = ["forge-config: default.invariant.runs = 1".to_string()];
+        assert_eq!(InlineEvmConfig::try_parse(&configs).unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let configs = ["forge-config: default.evm_version = prague1".to_string()];
+        assert!(InlineEvmConfig::try_parse(&configs).is_err());
+
+        let configs = ["forge-config: default.fork_block_number = latest".to_string()];
+        assert!(InlineEvmConfig::try_parse(&configs).is_err());
+    }
+
+    #[test]
+    fn function_config_takes_precedence() {
+        let contract = InlineEvmConfig {
+            spec_id: Some(SpecId::CANCUN),
+            fork_url: Some("mainnet".to_string()),
+            fork_block_number: None,
+            no_storage_caching: None,
+        };
+        let function = InlineEvmConfig { spec_id: Some(SpecId::LONDON), ..Default::default() };
+        assert!(function.conflicts_with(&contract));
+        assert!(!InlineEvmConfig::default().conflicts_with(&contract));
+        assert_eq!(
+            function.or(&contract),
+            InlineEvmConfig {
+                spec_id: Some(SpecId::LONDON),
+                fork_url: Some("mainnet".to_string()),
+                fork_block_number: None,
+                no_storage_caching: None,
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_no_storage_caching() {
+        let configs = ["forge-config: default.fork.no_storage_caching = true".to_string()];
+        let config = InlineEvmConfig::try_parse(&configs).unwrap().unwrap();
+        assert_eq!(config.no_storage_caching, Some(true));
+        assert!(config.is_storage_caching_disabled());
+        assert!(!config.is_fork());
+
+        let contract = InlineEvmConfig { no_storage_caching: Some(false), ..Default::default() };
+        assert!(config.conflicts_with(&contract));
+        assert!(!contract.or(&config).is_storage_caching_disabled());
+
+        let configs = ["forge-config: default.fork.no_storage_caching = 1".to_string()];
+        assert!(InlineEvmConfig::try_parse(&configs).is_err());
+    }
+}
@@ -1,12 +1,14 @@
 use crate::Config;
 pub use conf_parser::{parse_config_bool, parse_config_u32, validate_profiles, InlineConfigParser};
 pub use error::{InlineConfigError, InlineConfigParserError};
+pub use evm::InlineEvmConfig;
 pub use natspec::NatSpec;
 use once_cell::sync::Lazy;
 use std::{borrow::Cow, collections::HashMap};
 
 mod conf_parser;
 mod error;
+mod evm;
 mod natspec;
 
 pub const INLINE_CONFIG_FUZZ_KEY: &str = "fuzz";
@@ -11,7 +11,7 @@ use std::{collections::BTreeMap, path::Path};
 pub struct NatSpec {
     /// The parent contract of the natspec
     pub contract: String,
-    /// The function annotated with the natspec
+    /// The function annotated with the natspec, empty if the natspec annotates the contract
     pub function: String,
     /// The line the natspec appears, in the form
     /// `row:col:length` i.e. `10:21:122`
@@ -35,6 +35,14 @@ impl NatSpec {
             // id.identifier
             let contract = format!("{}:{}", path.display(), id.name);
             let Some(node) = contract_root_node(&ast.nodes, &contract) else { continue };
+            if let Some((docs, line)) = get_fn_docs(&node.other) {
+                natspecs.push(NatSpec {
+                    contract: contract.clone(),
+                    function: String::new(),
+                    line,
+                    docs,
+                })
+            }
             apply(&mut natspecs, &contract, node)
         }
 
@@ -45,6 +53,9 @@ impl NatSpec {
     /// context, for debugging purposes 🐞
     /// i.e. `test/Counter.t.sol:CounterTest:testFuzz_SetNumber`
     pub fn debug_context(&self) -> String {
+        if self.function.is_empty() {
+            return self.contract.clone()
+        }
         format!("{}:{}", self.contract, self.function)
     }
 
@@ -120,7 +131,7 @@ fn get_fn_name(fn_data: &BTreeMap<String, Value>) -> Option<String> {
     }
 }
 
-/// Inspects Solc compiler output for documentation comments. Returns:
+/// Inspects Solc compiler output for documentation comments of a function or contract. Returns:
 /// - `Some((String, String))` in case the function has natspec comments. First item is a textual
 ///   natspec representation, the second item is the natspec src line, in the form "raw:col:length".
 /// - `None` in case the function has not natspec comments.
@@ -32,7 +32,7 @@ use semver::Version;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
     path::{Path, PathBuf},
     str::FromStr,
@@ -49,10 +49,14 @@ mod endpoints;
 pub use endpoints::{ResolvedRpcEndpoints, RpcEndpoint, RpcEndpoints};
 
 mod etherscan;
+pub use etherscan::ResolvedEtherscanConfig;
+
 mod resolve;
 pub use resolve::UnresolvedEnvVarError;
 
 pub mod cache;
+
+pub mod chains;
 use cache::{Cache, ChainCache};
 
 pub mod fmt;
@@ -67,6 +71,39 @@ pub use error::SolidityErrorCode;
 pub mod doc;
 pub use doc::DocConfig;
 
+pub mod gas_report;
+pub use gas_report::GasReportConfig;
+
+pub mod vyper;
+pub use vyper::VyperConfig;
+
+pub mod lint;
+pub use lint::{LintConfig, LintSeverity};
+
+pub mod hooks;
+pub use hooks::{HookEvent, HooksConfig};
+
+pub mod rpc;
+pub use rpc::RpcConfig;
+
+pub mod wallets;
+pub use wallets::WalletsConfig;
+
+pub mod coverage;
+pub use coverage::CoverageConfig;
+
+pub mod build;
+pub use build::BuildConfig;
+
+pub mod compilation;
+pub use compilation::SettingsOverrides;
+
+pub mod selectors;
+pub use selectors::SelectorsConfig;
+
+pub mod chisel;
+pub use chisel::ChiselConfig;
+
 mod warning;
 pub use warning::*;
 
@@ -82,7 +119,7 @@ pub mod providers;
 
 use crate::{
     error::ExtractConfigError,
-    etherscan::{EtherscanConfigError, EtherscanConfigs, ResolvedEtherscanConfig},
+    etherscan::{EtherscanConfigError, EtherscanConfigs},
 };
 use providers::*;
 
@@ -94,8 +131,14 @@ use crate::fs_permissions::PathPermission;
 pub use invariant::InvariantConfig;
 use providers::remappings::RemappingsProvider;
 
+mod verifier;
+pub use verifier::{VerifierApi, VerifierConfig, VerifierConfigs, VerifierRequestEncoding};
+
 mod inline;
-pub use inline::{validate_profiles, InlineConfig, InlineConfigError, InlineConfigParser, NatSpec};
+pub use inline::{
+    validate_profiles, InlineConfig, InlineConfigError, InlineConfigParser, InlineEvmConfig,
+    NatSpec,
+};
 
 /// Foundry configuration
 ///
@@ -160,6 +203,11 @@ pub struct Config {
     pub cache_path: PathBuf,
     /// where the broadcast logs are stored
     pub broadcast: PathBuf,
+    /// where the registry of the contracts deployed by scripts is stored, see `save_deployments`
+    pub deployments: PathBuf,
+    /// whether broadcasted scripts record the contracts they deploy in the registry,
+    /// `<deployments>/<chain id>/<contract name>.json`
+    pub save_deployments: bool,
     /// additional solc allow paths for `--allow-paths`
     pub allow_paths: Vec<PathBuf>,
     /// additional solc include paths for `--include-path`
@@ -173,6 +221,8 @@ pub struct Config {
     pub gas_reports: Vec<String>,
     /// list of contracts to ignore for gas reports
     pub gas_reports_ignore: Vec<String>,
+    /// Additional filters and settings of gas reports
+    pub gas_report: GasReportConfig,
     /// The Solc instance to use if any.
     ///
     /// This takes precedence over `auto_detect_solc`, if a version is set then this overrides
@@ -189,7 +239,15 @@ pub struct Config {
     ///    - if `auto_detect_solc = true` and `offline = true`, the required solc version(s) will
     ///      be auto detected but if the solc version is not installed, it will _not_ try to
     ///      install it
+    ///
+    /// Forks are created from the RPC cache only, requests for data that is not cached fail.
+    ///
+    /// Selectors are only identified from the signatures cache, they are not looked up at
+    /// `https://api.openchain.xyz`.
     pub offline: bool,
+    /// The number of threads to use for compiling and running tests, all available parallelism
+    /// if not set
+    pub threads: Option<usize>,
     /// Whether to activate optimizer
     pub optimizer: bool,
     /// Sets the optimizer runs
@@ -211,6 +269,9 @@ pub struct Config {
     /// Multiple etherscan api configs and their aliases
     #[serde(default, skip_serializing_if = "EtherscanConfigs::is_empty")]
     pub etherscan: EtherscanConfigs,
+    /// Custom verification providers and their names
+    #[serde(default, skip_serializing_if = "VerifierConfigs::is_empty")]
+    pub verifiers: VerifierConfigs,
     /// list of solidity error codes to always silence in the compiler output
     pub ignored_error_codes: Vec<SolidityErrorCode>,
     /// When true, compiler warnings are treated as errors
@@ -239,6 +300,15 @@ pub struct Config {
     pub invariant: InvariantConfig,
     /// Whether to allow ffi cheatcodes in test
     pub ffi: bool,
+    /// Prefixes of the commands the ffi cheatcodes are allowed to execute, e.g. `"python3
+    /// scripts/"`.
+    ///
+    /// If not empty, ffi is allowed for the matching commands only, even if `ffi` is disabled.
+    pub allowed_ffi: Vec<String>,
+    /// Names of the environment variables whose values are masked in the console output, traces
+    /// and broadcast files of scripts, e.g. `"*_KEY"`. `*` matches any characters, the names are
+    /// matched case-insensitively.
+    pub sensitive_env: Vec<String>,
     /// The address which will be executing all tests
     pub sender: Address,
     /// The tx.origin value during EVM execution
@@ -278,6 +348,10 @@ pub struct Config {
     ///
     /// The default is 128MiB.
     pub memory_limit: u64,
+    /// Whether to enable call isolation.
+    ///
+    /// Useful for more correct gas accounting and EVM behavior in general.
+    pub isolate: bool,
     /// Additional output selection for all contracts, such as "ir", "devdoc", "storageLayout",
     /// etc.
     ///
@@ -315,6 +389,13 @@ pub struct Config {
     /// If set to true, changes compilation pipeline to go through the Yul intermediate
     /// representation.
     pub via_ir: bool,
+    /// Overrides of the solc settings for the sources matching a glob relative to the root, e.g.
+    /// `"src/core/**" = { via_ir = true, optimizer_runs = 1000000 }`.
+    ///
+    /// The sources matching a glob are compiled in a separate solc job by `forge build`, see
+    /// [`Config::compilation_override`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub compilation_overrides: BTreeMap<String, SettingsOverrides>,
     /// RPC storage caching settings determines what chains and endpoints to cache
     pub rpc_storage_caching: StorageCachingConfig,
     /// Disables storage caching entirely. This overrides any settings made in
@@ -354,10 +435,28 @@ pub struct Config {
     pub build_info: bool,
     /// The path to the `build-info` directory that contains the build info json files.
     pub build_info_path: Option<PathBuf>,
+    /// Configuration for `forge build`
+    pub build: BuildConfig,
+    /// Configuration for `forge selectors`
+    pub selectors: SelectorsConfig,
+    /// Configuration for `chisel`
+    pub chisel: ChiselConfig,
     /// Configuration for `forge fmt`
     pub fmt: FormatterConfig,
     /// Configuration for `forge doc`
     pub doc: DocConfig,
+    /// Configuration for `forge lint`
+    pub lint: LintConfig,
+    /// Configuration of the webhooks of `anvil`
+    pub hooks: HooksConfig,
+    /// Configuration for compiling Vyper sources
+    pub vyper: VyperConfig,
+    /// Configuration for retrying failed RPC requests
+    pub rpc: RpcConfig,
+    /// Configuration of the keys of remote signers
+    pub wallets: WalletsConfig,
+    /// Configuration for `forge coverage`
+    pub coverage: CoverageConfig,
     /// Configures the permissions of cheat codes that touch the file system.
     ///
     /// This includes what operations can be executed (read, write)
@@ -412,9 +511,39 @@ impl Config {
     /// TOML section for profiles
     pub const PROFILE_SECTION: &'static str = "profile";
 
+    /// TOML key of the list of config files a foundry.toml includes, relative to it
+    ///
+    /// The included files are merged before the file itself, in order, so a value of the file
+    /// overrides the same value of the files it includes, and a value of an included file
+    /// overrides the same value of the files included before it.
+    pub const INCLUDE_KEY: &'static str = "include";
+
+    /// Sections which resolve their `${ENV_VAR}` placeholders only when they are used, the
+    /// placeholders of all the other values are resolved when the config is loaded
+    pub const LAZILY_RESOLVED_SECTIONS: &'static [&'static str] =
+        &["rpc_endpoints", "etherscan", "verifiers"];
+
     /// Standalone sections in the config which get integrated into the selected profile
-    pub const STANDALONE_SECTIONS: &'static [&'static str] =
-        &["rpc_endpoints", "etherscan", "fmt", "doc", "fuzz", "invariant", "labels"];
+    pub const STANDALONE_SECTIONS: &'static [&'static str] = &[
+        "rpc_endpoints",
+        "etherscan",
+        "verifiers",
+        "build",
+        "selectors",
+        "chisel",
+        "fmt",
+        "doc",
+        "lint",
+        "hooks",
+        "gas_report",
+        "vyper",
+        "rpc",
+        "wallets",
+        "coverage",
+        "fuzz",
+        "invariant",
+        "labels",
+    ];
 
     /// File name of config toml file
     pub const FILE_NAME: &'static str = "foundry.toml";
@@ -427,6 +556,10 @@ impl Config {
     /// `0x1804c8AB1F12E6bbf3894d4083f33e07309d1f38`
     pub const DEFAULT_SENDER: Address = address!("1804c8AB1F12E6bbf3894d4083f33e07309d1f38");
 
+    /// Default names of the environment variables masked in script output, see
+    /// [`Config::sensitive_env`]
+    pub const DEFAULT_SENSITIVE_ENV: &'static [&'static str] = &["*_KEY", "*_SECRET", "PRIVATE_*"];
+
     /// Returns the current `Config`
     ///
     /// See `Config::figment`
@@ -529,6 +662,7 @@ impl Config {
         self.script = p(&root, &self.script);
         self.out = p(&root, &self.out);
         self.broadcast = p(&root, &self.broadcast);
+        self.deployments = p(&root, &self.deployments);
         self.cache_path = p(&root, &self.cache_path);
 
         if let Some(build_info_path) = self.build_info_path {
@@ -644,6 +778,10 @@ impl Config {
             .set_no_artifacts(no_artifacts)
             .build()?;
 
+        if let Some(threads) = self.threads {
+            project.solc_jobs = threads.max(1);
+        }
+
         if self.force {
             project.cleanup()?;
         }
@@ -932,7 +1070,7 @@ impl Config {
                 (Ok(mut config), Some(key)) => {
                     // we update the key, because if an etherscan_api_key is set, it should take
                     // precedence over the entry, since this is usually set via env var or CLI args.
-                    config.key = key.clone();
+                    config.set_api_keys(key);
                     return Ok(Some(config))
                 }
                 (Ok(config), None) => return Ok(Some(config)),
@@ -952,7 +1090,7 @@ impl Config {
         Ok(None)
     }
 
-    /// Helper function to just get the API key
+    /// Helper function to just get the API key, the first one if the chain has several keys
     pub fn get_etherscan_api_key(&self, chain: Option<Chain>) -> Option<String> {
         self.get_etherscan_config_with_chain(chain).ok().flatten().map(|c| c.key)
     }
@@ -1015,6 +1153,37 @@ impl Config {
         Libraries::parse(&self.libraries)
     }
 
+    /// Returns the glob of the `compilation_overrides`, and its overrides, that applies to the
+    /// source at `path`, see [compilation::matching_override].
+    pub fn compilation_override(&self, path: &Path) -> Option<(&str, &SettingsOverrides)> {
+        let path = path.strip_prefix(&self.__root.0).unwrap_or(path);
+        compilation::matching_override(&self.compilation_overrides, path)
+    }
+
+    /// Returns the config to compile the sources of a compilation override with.
+    ///
+    /// Its artifacts and its cache are kept in the `overrides/<settings hash>` sub directories of
+    /// `out` and `cache_path`, apart from the sources compiled with the settings of the profile.
+    pub fn with_compilation_override(&self, overrides: &SettingsOverrides) -> Self {
+        let mut config = self.clone();
+        overrides.apply(&mut config);
+        let dir = Path::new("overrides").join(overrides.settings_hash());
+        config.out = self.out.join(&dir);
+        config.cache_path = self.cache_path.join(&dir);
+        config.build_info_path = self.build_info_path.as_ref().map(|path| path.join(&dir));
+        config.compilation_overrides.clear();
+        config
+    }
+
+    /// Returns the config the source at `path` is compiled with, which is this config unless a
+    /// compilation override applies to the source.
+    pub fn for_source(&self, path: &Path) -> Self {
+        match self.compilation_override(path) {
+            Some((_, overrides)) => self.with_compilation_override(overrides),
+            None => self.clone(),
+        }
+    }
+
     /// Returns the configured `solc` `Settings` that includes:
     ///   - all libraries
     ///   - the optimizer (including details, if configured)
@@ -1259,6 +1428,40 @@ impl Config {
         toml::to_string_pretty(&toml::Value::Table(wrapping_table))
     }
 
+    /// Returns where each value of the config, by its dotted key such as `fuzz.runs`, is set: the
+    /// path of the config file, or the name of the provider, e.g. an env var or the CLI args, of
+    /// the `figment` the config is extracted from.
+    ///
+    /// The defaults are set by the `Foundry Config` provider.
+    pub fn origins(&self, figment: &Figment) -> Result<BTreeMap<String, String>, Error> {
+        fn collect_keys(prefix: &str, value: &Value, keys: &mut Vec<String>) {
+            match value {
+                Value::Dict(_, dict) if !dict.is_empty() => {
+                    for (key, value) in dict {
+                        let key =
+                            if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                        collect_keys(&key, value, keys);
+                    }
+                }
+                _ => keys.push(prefix.to_string()),
+            }
+        }
+
+        let mut keys = Vec::new();
+        collect_keys("", &Value::serialize(self)?, &mut keys);
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| {
+                let metadata = figment.find_metadata(&key)?;
+                let origin = match &metadata.source {
+                    Some(figment::Source::File(path)) => path.display().to_string(),
+                    _ => metadata.name.to_string(),
+                };
+                Some((key, origin))
+            })
+            .collect())
+    }
+
     /// Returns the path to the `foundry.toml`  of this `Config`
     pub fn get_config_path(&self) -> PathBuf {
         self.__root.0.join(Config::FILE_NAME)
@@ -1505,6 +1708,32 @@ impl Config {
         dir_size_recursive(fs::read_dir(chain_path)?)
     }
 
+    /// Merges a toml file after the files it includes, see [Config::INCLUDE_KEY]
+    ///
+    /// The env vars of the files are only interpolated in the selected profile, see
+    /// [InterpolatedTomlProvider].
+    fn merge_toml_file(mut figment: Figment, file: TomlFileProvider, profile: Profile) -> Figment {
+        let interpolated = |file: TomlFileProvider| {
+            let mut profiles = vec![Config::DEFAULT_PROFILE];
+            if profile != Config::DEFAULT_PROFILE {
+                profiles.push(profile.clone());
+            }
+            InterpolatedTomlProvider { provider: file, profiles }
+        };
+        let includes = match file.includes() {
+            Ok(includes) => includes,
+            Err(err) => return Self::merge_toml_provider(figment, file.with_error(err), profile),
+        };
+        for include in includes {
+            figment = Self::merge_toml_provider(
+                figment,
+                interpolated(TomlFileProvider::new(None, include).cached()),
+                profile.clone(),
+            );
+        }
+        Self::merge_toml_provider(figment, interpolated(file.cached()), profile)
+    }
+
     fn merge_toml_provider(
         mut figment: Figment,
         toml_provider: impl Provider,
@@ -1557,17 +1786,16 @@ impl From<Config> for Figment {
 
         // merge global foundry.toml file
         if let Some(global_toml) = Config::foundry_dir_toml().filter(|p| p.exists()) {
-            figment = Config::merge_toml_provider(
+            figment = Config::merge_toml_file(
                 figment,
-                TomlFileProvider::new(None, global_toml).cached(),
+                TomlFileProvider::new(None, global_toml),
                 profile.clone(),
             );
         }
         // merge local foundry.toml file
-        figment = Config::merge_toml_provider(
+        figment = Config::merge_toml_file(
             figment,
-            TomlFileProvider::new(Some("FOUNDRY_CONFIG"), c.__root.0.join(Config::FILE_NAME))
-                .cached(),
+            TomlFileProvider::new(Some("FOUNDRY_CONFIG"), c.__root.0.join(Config::FILE_NAME)),
             profile.clone(),
         );
 
@@ -1764,15 +1992,19 @@ impl Default for Config {
             cache: true,
             cache_path: "cache".into(),
             broadcast: "broadcast".into(),
+            deployments: "deployments".into(),
+            save_deployments: false,
             allow_paths: vec![],
             include_paths: vec![],
             force: false,
             evm_version: EvmVersion::Paris,
             gas_reports: vec!["*".to_string()],
             gas_reports_ignore: vec![],
+            gas_report: Default::default(),
             solc: None,
             auto_detect_solc: true,
             offline: false,
+            threads: None,
             optimizer: true,
             optimizer_runs: 200,
             optimizer_details: None,
@@ -1790,6 +2022,8 @@ impl Default for Config {
             fuzz: Default::default(),
             invariant: Default::default(),
             ffi: false,
+            allowed_ffi: vec![],
+            sensitive_env: Config::DEFAULT_SENSITIVE_ENV.iter().map(|s| s.to_string()).collect(),
             sender: Config::DEFAULT_SENDER,
             tx_origin: Config::DEFAULT_SENDER,
             initial_balance: U256::from(0xffffffffffffffffffffffffu128),
@@ -1806,6 +2040,7 @@ impl Default for Config {
             block_prevrandao: Default::default(),
             block_gas_limit: None,
             memory_limit: 1 << 27, // 2**27 = 128MiB = 134_217_728 bytes
+            isolate: false,
             eth_rpc_url: None,
             eth_rpc_jwt: None,
             etherscan_api_key: None,
@@ -1823,6 +2058,7 @@ impl Default for Config {
             rpc_storage_caching: Default::default(),
             rpc_endpoints: Default::default(),
             etherscan: Default::default(),
+            verifiers: Default::default(),
             no_storage_caching: false,
             no_rpc_rate_limit: false,
             use_literal_content: false,
@@ -1832,8 +2068,18 @@ impl Default for Config {
             sparse_mode: false,
             build_info: false,
             build_info_path: None,
+            build: Default::default(),
+            compilation_overrides: Default::default(),
+            selectors: Default::default(),
+            chisel: Default::default(),
             fmt: Default::default(),
             doc: Default::default(),
+            lint: Default::default(),
+            hooks: Default::default(),
+            vyper: Default::default(),
+            rpc: Default::default(),
+            wallets: Default::default(),
+            coverage: Default::default(),
             labels: Default::default(),
             __non_exhaustive: (),
             __warnings: vec![],
@@ -1972,24 +2218,160 @@ impl TomlFileProvider {
         self
     }
 
+    fn with_error(mut self, err: Error) -> Self {
+        self.cache = Some(Err(err));
+        self
+    }
+
     fn read(&self) -> Result<Map<Profile, Dict>, Error> {
         use serde::de::Error as _;
-        if let Some(file) = self.env_val() {
-            let path = Path::new(&file);
-            if !path.exists() {
-                return Err(Error::custom(format!(
-                    "Config file `{}` set in env var `{}` does not exist",
-                    file,
-                    self.env_var.unwrap()
-                )))
+        let file = self.file();
+        if let Some(var) = self.env_var.filter(|_| self.is_missing()) {
+            return Err(Error::custom(format!(
+                "Config file `{}` set in env var `{var}` does not exist",
+                file.display()
+            )))
+        }
+
+        // the profiles are nested once the `include` key is removed, as it is not a profile, and
+        // like nested toml data the keys of the top level which aren't tables are ignored
+        let mut dict = Toml::file(&file).data()?.into_values().next().unwrap_or_default();
+        dict.remove(Config::INCLUDE_KEY);
+        Ok(dict
+            .into_iter()
+            .filter_map(|(profile, value)| Some((Profile::new(&profile), value.into_dict()?)))
+            .collect())
+    }
+
+    /// Returns the files the file includes, and the files they include themselves, in the order
+    /// they are merged, see [Config::INCLUDE_KEY].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an included file does not exist or if files include each other
+    fn includes(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut includes = Vec::new();
+        collect_includes(&self.file(), &mut Vec::new(), &mut includes)?;
+        Ok(includes)
+    }
+}
+
+/// Collects the files `file` includes after the files they include, `stack` is the chain of files
+/// which include `file`.
+fn collect_includes(
+    file: &Path,
+    stack: &mut Vec<PathBuf>,
+    includes: &mut Vec<PathBuf>,
+) -> Result<(), Error> {
+    use serde::de::Error as _;
+    // a missing or invalid file is reported by its provider
+    let Ok(content) = fs::read_to_string(file) else { return Ok(()) };
+    let Ok(table) = toml::from_str::<toml::Table>(&content) else { return Ok(()) };
+    let Some(paths) = table.get(Config::INCLUDE_KEY) else { return Ok(()) };
+    let paths: Vec<String> = paths.clone().try_into().map_err(|_| {
+        Error::custom(format!(
+            "`{}` of `{}` must be a list of paths",
+            Config::INCLUDE_KEY,
+            file.display()
+        ))
+    })?;
+
+    let file = foundry_compilers::utils::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+    let dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+    stack.push(file.clone());
+    for path in paths {
+        let path = resolve::interpolate(&path).map_err(|err| {
+            Error::custom(format!("{err} for `{}` of `{}`", Config::INCLUDE_KEY, file.display()))
+        })?;
+        let include = foundry_compilers::utils::canonicalize(dir.join(&path)).map_err(|_| {
+            Error::custom(format!(
+                "Config file `{path}` included by `{}` does not exist",
+                file.display()
+            ))
+        })?;
+        if let Some(pos) = stack.iter().position(|file| *file == include) {
+            let cycle = stack[pos..]
+                .iter()
+                .chain([&include])
+                .map(|file| format!("`{}`", file.display()))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(Error::custom(format!("Config files include each other: {cycle}")))
+        }
+        collect_includes(&include, stack, includes)?;
+        if !includes.contains(&include) {
+            includes.push(include);
+        }
+    }
+    stack.pop();
+    Ok(())
+}
+
+/// A Provider that replaces the `${ENV_VAR}` placeholders of a toml file when its data is used,
+/// only in the selected `profiles` and in the sections which are not profiles, except the
+/// [Config::LAZILY_RESOLVED_SECTIONS].
+///
+/// A placeholder of a profile which is not selected is not resolved, so a missing env var is only
+/// an error if the profile is used.
+struct InterpolatedTomlProvider<P> {
+    provider: P,
+    profiles: Vec<Profile>,
+}
+
+impl<P: Provider> Provider for InterpolatedTomlProvider<P> {
+    fn metadata(&self) -> Metadata {
+        self.provider.metadata()
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let file = match self.provider.metadata().source {
+            Some(figment::Source::File(path)) => path,
+            _ => PathBuf::new(),
+        };
+        let mut data = self.provider.data()?;
+        for (section, dict) in data.iter_mut() {
+            if section.as_str() == Config::PROFILE_SECTION {
+                for profile in &self.profiles {
+                    if let Some(value) = dict.get_mut(profile.as_str()) {
+                        interpolate_value(value, &format!("{section}.{profile}"), &file)?;
+                    }
+                }
+            } else if !Config::LAZILY_RESOLVED_SECTIONS.contains(&section.as_str()) {
+                for (key, value) in dict.iter_mut() {
+                    interpolate_value(value, &format!("{section}.{key}"), &file)?;
+                }
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// Replaces the `${ENV_VAR}` placeholders of the strings of a toml value with the values of the
+/// env vars, except in the [Config::LAZILY_RESOLVED_SECTIONS], `key` is the path of the value in
+/// `file`.
+fn interpolate_value(value: &mut Value, key: &str, file: &Path) -> Result<(), Error> {
+    use serde::de::Error as _;
+    match value {
+        Value::String(_, s) => {
+            *s = resolve::interpolate(s).map_err(|err| {
+                Error::custom(format!("{err} for `{key}` in `{}`", file.display()))
+            })?;
+        }
+        Value::Dict(_, dict) => {
+            for (k, value) in dict.iter_mut() {
+                if !Config::LAZILY_RESOLVED_SECTIONS.contains(&k.as_str()) {
+                    interpolate_value(value, &format!("{key}.{k}"), file)?;
+                }
             }
-            Toml::file(file)
-        } else {
-            Toml::file(&self.default)
         }
-        .nested()
-        .data()
+        Value::Array(_, values) => {
+            for value in values {
+                interpolate_value(value, key, file)?;
+            }
+        }
+        _ => {}
     }
+    Ok(())
 }
 
 impl Provider for TomlFileProvider {
@@ -2521,7 +2903,7 @@ mod tests {
         cache::{CachedChains, CachedEndpoints},
         endpoints::{RpcEndpoint, RpcEndpointConfig, RpcEndpointType},
         etherscan::ResolvedEtherscanConfigs,
-        fs_permissions::PathPermission,
+        fs_permissions::{FsAccessPermission, PathPermission},
     };
     use alloy_primitives::Address;
     use figment::{error::Kind::InvalidType, value::Value, Figment};
@@ -2997,6 +3379,7 @@ mod tests {
                             chain: Some(NamedChain::Mainnet.into()),
                             browser_url: Some(mainnet_urls.1.to_string()),
                             key: "FX42Z3BBJJEWXWGYV2X1CIPRSCN".to_string(),
+                            keys: vec![],
                         }
                     ),
                     (
@@ -3006,6 +3389,7 @@ mod tests {
                             chain: Some(Moonbeam.into()),
                             browser_url: Some(mb_urls.1.to_string()),
                             key: "123456789".to_string(),
+                            keys: vec![],
                         }
                     ),
                 ])
@@ -3015,6 +3399,159 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_resolve_etherscan_keys() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+
+                [etherscan]
+                mainnet = { keys = ["KEY1", "KEY2"] }
+                optimism = { key = "KEY3", keys = ["KEY4"] }
+            "#,
+            )?;
+            jail.set_env("ETHERSCAN_API_KEY", "KEY5,KEY6");
+
+            let config = Config::load();
+            let mainnet = config.get_etherscan_config_with_chain(Some(Chain::mainnet())).unwrap();
+            assert_eq!(mainnet.unwrap().api_keys(), ["KEY5", "KEY6"]);
+
+            let configs = config.etherscan.clone().resolved();
+            let optimism = configs["optimism"].as_ref().unwrap();
+            assert_eq!(optimism.api_keys(), ["KEY3", "KEY4"]);
+            let mainnet = configs["mainnet"].as_ref().unwrap();
+            assert_eq!(mainnet.key, "KEY1");
+            assert_eq!(mainnet.keys, ["KEY2"]);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_include_config_files() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.shared.toml",
+                r#"
+                include = ["foundry.base.toml"]
+
+                [profile.default]
+                optimizer_runs = 500
+                eth_rpc_url = "${_CONFIG_SHARED_RPC:-http://localhost:8545}"
+
+                [rpc_endpoints]
+                mainnet = "${_CONFIG_SHARED_MAINNET}"
+            "#,
+            )?;
+            jail.create_file(
+                "foundry.base.toml",
+                r#"
+                [profile.default]
+                optimizer_runs = 100
+                libs = ["modules"]
+
+                [fuzz]
+                runs = 1000
+            "#,
+            )?;
+            fs::create_dir(jail.directory().join("pkg")).unwrap();
+            jail.create_file(
+                "pkg/foundry.toml",
+                r#"
+                include = ["../foundry.shared.toml"]
+
+                [profile.default]
+                src = "${_CONFIG_PKG_SRC}"
+
+                [fuzz]
+                seed = "0x1"
+            "#,
+            )?;
+            jail.set_env("_CONFIG_PKG_SRC", "contracts");
+
+            let root = jail.directory().join("pkg");
+            let figment = Config::figment_with_root(&root);
+            let config = Config::from_provider(figment.clone());
+            assert_eq!(config.src, PathBuf::from("contracts"));
+            assert_eq!(config.optimizer_runs, 500);
+            assert_eq!(config.libs, vec![PathBuf::from("modules")]);
+            assert_eq!(config.eth_rpc_url, Some("http://localhost:8545".to_string()));
+            assert_eq!(config.fuzz.runs, 1000);
+            assert_eq!(config.fuzz.seed, Some(U256::from(1)));
+            // the endpoints are resolved when they're used
+            assert_eq!(
+                config.rpc_endpoints,
+                RpcEndpoints::new([(
+                    "mainnet",
+                    RpcEndpoint::Env("${_CONFIG_SHARED_MAINNET}".into())
+                )])
+            );
+
+            let origins = config.origins(&figment).unwrap();
+            let origin = |key: &str| PathBuf::from(&origins[key]);
+            assert_eq!(origin("src"), root.join("foundry.toml"));
+            assert_eq!(origin("optimizer_runs"), jail.directory().join("foundry.shared.toml"));
+            assert_eq!(origin("fuzz.runs"), jail.directory().join("foundry.base.toml"));
+            assert_eq!(origin("fuzz.seed"), root.join("foundry.toml"));
+            assert_eq!(origins["via_ir"], "Foundry Config");
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_include_config_errors() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file("foundry.toml", r#"include = ["foundry.a.toml"]"#)?;
+            jail.create_file("foundry.a.toml", r#"include = ["foundry.b.toml"]"#)?;
+            jail.create_file("foundry.b.toml", r#"include = ["foundry.a.toml"]"#)?;
+            let err = Config::try_from(Config::figment()).unwrap_err().to_string();
+            assert!(err.contains("Config files include each other"), "{err}");
+            assert!(err.contains("foundry.a.toml`"), "{err}");
+
+            jail.create_file("foundry.toml", r#"include = ["foundry.missing.toml"]"#)?;
+            let err = Config::try_from(Config::figment()).unwrap_err().to_string();
+            assert!(err.contains("`foundry.missing.toml` included by"), "{err}");
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                eth_rpc_url = "${_CONFIG_MISSING_RPC}"
+            "#,
+            )?;
+            let err = Config::try_from(Config::figment()).unwrap_err().to_string();
+            assert!(err.contains("_CONFIG_MISSING_RPC"), "{err}");
+            assert!(err.contains("profile.default.eth_rpc_url"), "{err}");
+
+            // only the selected profile is interpolated, the keys which aren't tables are ignored
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                solc = "0.8.10"
+
+                [profile.default]
+                src = "${_CONFIG_SELECTED_SRC}"
+
+                [profile.ci]
+                eth_rpc_url = "${_CONFIG_MISSING_RPC}"
+            "#,
+            )?;
+            jail.set_env("_CONFIG_SELECTED_SRC", "contracts");
+            let config = Config::load();
+            assert_eq!(config.src, PathBuf::from("contracts"));
+            assert_eq!(config.solc, None);
+
+            jail.set_env("FOUNDRY_PROFILE", "ci");
+            let err = Config::try_from(Config::figment()).unwrap_err().to_string();
+            assert!(err.contains("profile.ci.eth_rpc_url"), "{err}");
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_resolve_rpc_url() {
         figment::Jail::expect_with(|jail| {
@@ -3307,6 +3844,33 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_extract_verifiers() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+
+                [verifiers.mycorp]
+                url = "https://explorer.mycorp.io/api"
+                encoding = "json"
+                fields = { guid = "id" }
+            "#,
+            )?;
+
+            let config = Config::load();
+
+            let mycorp = config.verifiers.get("mycorp").unwrap();
+            assert_eq!(mycorp.api, VerifierApi::Etherscan);
+            assert_eq!(mycorp.url, "https://explorer.mycorp.io/api");
+            assert_eq!(mycorp.encoding, VerifierRequestEncoding::Json);
+            assert_eq!(mycorp.field_name("guid"), Some("id"));
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_extract_etherscan_config_by_chain_and_alias() {
         figment::Jail::expect_with(|jail| {
@@ -4053,6 +4617,20 @@ mod tests {
             let loaded = Config::load();
             assert_eq!(loaded.fs_permissions, FsPermissions::new(vec![PathPermission::none("./")]));
 
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                fs_permissions = { default = "read", permissions = [{ access = "write", path = "./out"}] }
+            "#,
+            )?;
+            let loaded = Config::load();
+            assert_eq!(
+                loaded.fs_permissions,
+                FsPermissions::new(vec![PathPermission::write("./out")])
+                    .with_default(FsAccessPermission::Read)
+            );
+
             Ok(())
         });
     }
@@ -4203,6 +4781,305 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_build_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r"
+                [build]
+                size_limit_margin = 1024
+            ",
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(loaded.build, BuildConfig { size_limit_margin: Some(1024) });
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_compilation_overrides() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                optimizer = true
+
+                [profile.default.compilation_overrides]
+                "src/**" = { optimizer = false }
+                "src/core/*.sol" = { via_ir = true, optimizer_runs = 1000000 }
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            let root = loaded.__root.0.clone();
+
+            // the more specific glob applies, whatever the order of the globs
+            let (glob, _) = loaded.compilation_override(&root.join("src/core/Vault.sol")).unwrap();
+            assert_eq!(glob, "src/core/*.sol");
+            let (glob, _) = loaded.compilation_override(&root.join("src/Token.sol")).unwrap();
+            assert_eq!(glob, "src/**");
+            assert!(loaded.compilation_override(&root.join("test/Vault.t.sol")).is_none());
+
+            let vault = loaded.for_source(&root.join("src/core/Vault.sol"));
+            assert!(vault.via_ir && vault.optimizer);
+            assert_eq!(vault.optimizer_runs, 1000000);
+            assert!(vault.out.starts_with(loaded.out.join("overrides")));
+            assert!(vault.cache_path.starts_with(loaded.cache_path.join("overrides")));
+            assert_ne!(vault.out, loaded.for_source(&root.join("src/Token.sol")).out);
+            assert_eq!(loaded.for_source(&root.join("test/Vault.t.sol")), loaded);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_gas_report_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [gas_report]
+                ignore_setup = true
+                include = ["Vault", "Router.swap*"]
+                exclude = ["Vault.withdraw*"]
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(
+                loaded.gas_report,
+                GasReportConfig {
+                    ignore_setup: true,
+                    include: vec!["Vault".to_string(), "Router.swap*".to_string()],
+                    exclude: vec!["Vault.withdraw*".to_string()],
+                }
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_vyper_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [vyper]
+                version = "0.3.10"
+                optimize = "codesize"
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(
+                loaded.vyper,
+                VyperConfig {
+                    version: Some(Version::new(0, 3, 10)),
+                    path: None,
+                    optimize: Some("codesize".to_string()),
+                }
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_lint_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [lint]
+                fail_on = "warning"
+                ignore = ["src/legacy/**"]
+
+                [lint.severity]
+                unused-import = "error"
+                tx-origin = "off"
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(loaded.lint.fail_on, Some(LintSeverity::Warning));
+            assert_eq!(loaded.lint.ignore, vec!["src/legacy/**".to_string()]);
+            assert_eq!(
+                loaded.lint.severity_of("unused-import", LintSeverity::Warning),
+                LintSeverity::Error
+            );
+            assert_eq!(
+                loaded.lint.severity_of("tx-origin", LintSeverity::Error),
+                LintSeverity::Off
+            );
+            assert_eq!(
+                loaded.lint.severity_of("unchecked-call", LintSeverity::Info),
+                LintSeverity::Info
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_hooks_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [hooks]
+                url = "http://localhost:3000/hooks"
+                events = ["block", "deployment"]
+                watch = ["0x70997970C51812dc3A010C7d01b50e0d17dc79C8"]
+                retries = 5
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(
+                loaded.hooks,
+                HooksConfig {
+                    url: Some("http://localhost:3000/hooks".to_string()),
+                    events: vec![HookEvent::Block, HookEvent::Deployment],
+                    watch: vec![address!("70997970C51812dc3A010C7d01b50e0d17dc79C8")],
+                    retries: 5,
+                    timeout: 5_000,
+                }
+            );
+            assert!(loaded.hooks.is_enabled(HookEvent::Block));
+            assert!(!loaded.hooks.is_enabled(HookEvent::Transaction));
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_rpc_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [rpc]
+                max_backoff = 10000
+
+                [rpc.method_retries]
+                eth_getStorageAt = 16
+                eth_sendRawTransaction = 0
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(
+                loaded.rpc,
+                RpcConfig {
+                    max_backoff: 10_000,
+                    jitter: true,
+                    method_retries: BTreeMap::from([
+                        ("eth_getStorageAt".to_string(), 16),
+                        ("eth_sendRawTransaction".to_string(), 0),
+                    ]),
+                }
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_wallets_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [wallets]
+                aws_region = "eu-west-1"
+
+                [wallets.aws_kms]
+                deployer = "alias/deployer"
+
+                [wallets.gcp_kms]
+                deployer = "projects/p/locations/global/keyRings/r/cryptoKeys/k/cryptoKeyVersions/1"
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(loaded.wallets.aws_region.as_deref(), Some("eu-west-1"));
+            assert_eq!(loaded.wallets.aws_kms_keys(), vec!["alias/deployer".to_string()]);
+            assert_eq!(
+                loaded.wallets.gcp_kms_key("deployer"),
+                "projects/p/locations/global/keyRings/r/cryptoKeys/k/cryptoKeyVersions/1"
+            );
+            assert_eq!(loaded.wallets.gcp_kms_key("projects/other"), "projects/other");
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_coverage_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [coverage]
+                exclude = ["test/**", "src/vendor/**"]
+                include = ["src/vendor/Kept.sol"]
+                exclude_contracts = ["*Mock*"]
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(
+                loaded.coverage,
+                CoverageConfig {
+                    exclude: vec!["test/**".to_string(), "src/vendor/**".to_string()],
+                    include: vec!["src/vendor/Kept.sol".to_string()],
+                    exclude_contracts: vec!["*Mock*".to_string()],
+                }
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_selectors_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [selectors]
+                facets = [["DiamondCutFacet", "TokenFacet"]]
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(
+                loaded.selectors,
+                SelectorsConfig {
+                    facets: vec![vec!["DiamondCutFacet".to_string(), "TokenFacet".to_string()]]
+                }
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_chisel_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [chisel]
+                annotate = true
+                ether_min_exponent = 12
+            "#,
+            )?;
+            let loaded = Config::load().sanitized();
+            assert_eq!(
+                loaded.chisel,
+                ChiselConfig { annotate: true, ether_min_exponent: 12, ..Default::default() }
+            );
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_fmt_config() {
         figment::Jail::expect_with(|jail| {
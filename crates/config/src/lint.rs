@@ -0,0 +1,51 @@
+//! Configuration specific to the `forge lint` command
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// Contains the config of the rules of `forge lint`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintConfig {
+    /// The lowest severity of the findings that fail `forge lint`, `error` if unset.
+    ///
+    /// If set, `forge build` lints the sources as well, and fails on the same findings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_on: Option<LintSeverity>,
+    /// The severity of the rules by their id, e.g. `unused-import = "error"`, overriding the
+    /// default severity of the rules. Rules with the severity `off` are disabled.
+    #[serde(default)]
+    pub severity: BTreeMap<String, LintSeverity>,
+    /// Globs of the sources that aren't linted.
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+impl LintConfig {
+    /// Returns the severity of the rule, or its `default` severity if it isn't configured.
+    pub fn severity_of(&self, rule: &str, default: LintSeverity) -> LintSeverity {
+        self.severity.get(rule).copied().unwrap_or(default)
+    }
+}
+
+/// The severity of the findings of a rule of `forge lint`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    /// The rule is disabled.
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            LintSeverity::Off => "off",
+            LintSeverity::Info => "info",
+            LintSeverity::Warning => "warning",
+            LintSeverity::Error => "error",
+        };
+        f.write_str(s)
+    }
+}
@@ -45,17 +45,30 @@ impl std::error::Error for UnresolvedEnvVarError {
 }
 
 /// Replaces all Env var placeholders in the input string with the values they hold
+///
+/// A placeholder can have a default value, `${ENV_VAR:-default}` is replaced with `default` if
+/// `ENV_VAR` is not set or empty.
 pub fn interpolate(input: &str) -> Result<String, UnresolvedEnvVarError> {
     let mut res = input.to_string();
 
     // loop over all placeholders in the input and replace them one by one
     for caps in RE_PLACEHOLDER.captures_iter(input) {
-        let var = &caps["inner"];
-        let value = env::var(var).map_err(|source| UnresolvedEnvVarError {
-            unresolved: input.to_string(),
-            var: var.to_string(),
-            source,
-        })?;
+        let (var, default) = match caps["inner"].split_once(":-") {
+            Some((var, default)) => (var.trim(), Some(default)),
+            None => (&caps["inner"], None),
+        };
+        let value = match (env::var(var), default) {
+            (Ok(value), Some(default)) if value.is_empty() => default.to_string(),
+            (Ok(value), _) => value,
+            (Err(_), Some(default)) => default.to_string(),
+            (Err(source), None) => {
+                return Err(UnresolvedEnvVarError {
+                    unresolved: input.to_string(),
+                    var: var.to_string(),
+                    source,
+                })
+            }
+        };
 
         res = res.replacen(&caps["outer"], &value, 1);
     }
@@ -81,4 +94,20 @@ mod tests {
         assert_eq!(cap.name("outer").unwrap().as_str(), "${API_KEY}");
         assert_eq!(cap.name("inner").unwrap().as_str(), "API_KEY");
     }
+
+    #[test]
+    fn can_interpolate_default_values() {
+        let var = "_RESOLVE_DEFAULT_VALUE";
+        assert_eq!(interpolate(&format!("${{{var}:-fallback}}/v2")).unwrap(), "fallback/v2");
+        assert_eq!(interpolate(&format!("${{ {var} :-}}")).unwrap(), "");
+
+        let err = interpolate(&format!("${{{var}}}")).unwrap_err();
+        assert_eq!(err.var, var);
+
+        std::env::set_var(var, "value");
+        assert_eq!(interpolate(&format!("${{{var}:-fallback}}/v2")).unwrap(), "value/v2");
+        std::env::set_var(var, "");
+        assert_eq!(interpolate(&format!("${{{var}:-fallback}}")).unwrap(), "fallback");
+        std::env::remove_var(var);
+    }
 }
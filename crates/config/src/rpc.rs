@@ -0,0 +1,28 @@
+//! Configuration for the retries of RPC requests
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Contains the config for retrying failed RPC requests, the `[rpc]` section
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpcConfig {
+    /// The upper bound of the exponential backoff between two retries, in milliseconds.
+    ///
+    /// A `Retry-After` the provider responds with takes precedence.
+    pub max_backoff: u64,
+    /// Whether to add a random jitter of up to half the backoff, so that concurrent requests
+    /// don't retry in lockstep.
+    pub jitter: bool,
+    /// The maximum number of retries by method, e.g. `eth_getStorageAt = 16`.
+    ///
+    /// Methods that aren't listed are retried up to `--fork-retries` times.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub method_retries: BTreeMap<String, u32>,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self { max_backoff: 30_000, jitter: true, method_retries: Default::default() }
+    }
+}
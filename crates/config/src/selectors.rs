@@ -0,0 +1,15 @@
+//! Configuration specific to the `forge selectors` command
+
+use serde::{Deserialize, Serialize};
+
+/// Contains the config for `forge selectors`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectorsConfig {
+    /// Sets of contracts that are deployed behind the same proxy, e.g. the facets of an
+    /// [EIP-2535](https://eips.ethereum.org/EIPS/eip-2535) diamond.
+    ///
+    /// `forge selectors check` reports every selector that is defined by more than one contract
+    /// of a set, e.g. `facets = [["DiamondCutFacet", "OwnershipFacet", "TokenFacet"]]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub facets: Vec<Vec<String>>,
+}
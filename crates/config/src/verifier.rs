@@ -0,0 +1,191 @@
+//! Support for custom contract verification providers.
+
+use crate::resolve::{interpolate, UnresolvedEnvVarError};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    ops::{Deref, DerefMut},
+};
+
+/// Container type for named verifier profiles, configured in the `[verifiers]` table.
+///
+/// ```toml
+/// [verifiers.mycorp]
+/// api = "etherscan"
+/// url = "https://explorer.mycorp.io/api"
+/// key = "${MYCORP_API_KEY}"
+/// auth_header = "X-Api-Key"
+/// encoding = "multipart"
+/// fields = { contractaddress = "address", guid = "id" }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VerifierConfigs {
+    configs: BTreeMap<String, VerifierConfig>,
+}
+
+// === impl VerifierConfigs ===
+
+impl VerifierConfigs {
+    /// Creates a new list of verifier profiles
+    pub fn new(configs: impl IntoIterator<Item = (impl Into<String>, VerifierConfig)>) -> Self {
+        Self { configs: configs.into_iter().map(|(name, config)| (name.into(), config)).collect() }
+    }
+
+    /// Returns `true` if this type doesn't contain any profiles
+    pub fn is_empty(&self) -> bool {
+        self.configs.is_empty()
+    }
+}
+
+impl Deref for VerifierConfigs {
+    type Target = BTreeMap<String, VerifierConfig>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.configs
+    }
+}
+
+impl DerefMut for VerifierConfigs {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.configs
+    }
+}
+
+/// The API flavour a verifier speaks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerifierApi {
+    /// An Etherscan compatible `module=contract&action=verifysourcecode` API
+    #[default]
+    Etherscan,
+    /// The Etherscan compatible API of Blockscout instances
+    Blockscout,
+    /// The Sourcify server API
+    Sourcify,
+}
+
+/// How the verification request is encoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerifierRequestEncoding {
+    /// `application/x-www-form-urlencoded`
+    #[default]
+    Form,
+    /// `multipart/form-data`
+    Multipart,
+    /// `application/json`
+    Json,
+}
+
+/// A single verifier profile.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierConfig {
+    /// The API flavour of the verifier
+    #[serde(default)]
+    pub api: VerifierApi,
+    /// The API endpoint, may contain `${ENV_VAR}` placeholders
+    pub url: String,
+    /// The API key, may contain `${ENV_VAR}` placeholders
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// The name of the header the API key is sent in.
+    ///
+    /// If not set, the key is sent as `apikey` field of the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_header: Option<String>,
+    /// How the verification request is encoded
+    #[serde(default)]
+    pub encoding: VerifierRequestEncoding,
+    /// Renames request fields from their Etherscan name to the name the verifier expects.
+    ///
+    /// An empty name drops the field from the request.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    /// Seconds to wait between two verification status checks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval: Option<u64>,
+    /// How many times the verification status is checked before giving up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_retries: Option<u32>,
+}
+
+// === impl VerifierConfig ===
+
+impl VerifierConfig {
+    /// Creates a new profile for the given API and url with default settings
+    pub fn new(api: VerifierApi, url: impl Into<String>) -> Self {
+        Self {
+            api,
+            url: url.into(),
+            key: None,
+            auth_header: None,
+            encoding: Default::default(),
+            fields: Default::default(),
+            poll_interval: None,
+            poll_retries: None,
+        }
+    }
+
+    /// Returns the name of the field `name` is sent as, or `None` if the field is dropped.
+    pub fn field_name<'a>(&'a self, name: &'a str) -> Option<&'a str> {
+        match self.fields.get(name) {
+            Some(renamed) if renamed.is_empty() => None,
+            Some(renamed) => Some(renamed),
+            None => Some(name),
+        }
+    }
+
+    /// Returns the profile with all `${ENV_VAR}` placeholders in `url` and `key` resolved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a referenced env var is not set
+    pub fn resolve(mut self) -> Result<Self, UnresolvedEnvVarError> {
+        self.url = interpolate(&self.url)?;
+        if let Some(key) = &mut self.key {
+            *key = interpolate(key)?;
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_verifier_profile() {
+        let s = r#"
+        api = "blockscout"
+        url = "https://explorer.mycorp.io/api"
+        key = "${_VERIFIER_TEST_KEY}"
+        auth_header = "X-Api-Key"
+        encoding = "multipart"
+        fields = { contractaddress = "address", constructorArguements = "" }
+        poll_interval = 3
+        "#;
+
+        let config: VerifierConfig = toml::from_str(s).unwrap();
+        assert_eq!(config.api, VerifierApi::Blockscout);
+        assert_eq!(config.encoding, VerifierRequestEncoding::Multipart);
+        assert_eq!(config.field_name("contractaddress"), Some("address"));
+        assert_eq!(config.field_name("constructorArguements"), None);
+        assert_eq!(config.field_name("sourceCode"), Some("sourceCode"));
+        assert_eq!(config.poll_interval, Some(3));
+        assert_eq!(config.poll_retries, None);
+
+        assert!(config.clone().resolve().is_err());
+
+        std::env::set_var("_VERIFIER_TEST_KEY", "secret");
+        let resolved = config.resolve().unwrap();
+        assert_eq!(resolved.key.as_deref(), Some("secret"));
+        std::env::remove_var("_VERIFIER_TEST_KEY");
+    }
+
+    #[test]
+    fn defaults_to_etherscan_form() {
+        let config: VerifierConfig = toml::from_str(r#"url = "http://localhost:8080""#).unwrap();
+        assert_eq!(config, VerifierConfig::new(VerifierApi::Etherscan, "http://localhost:8080"));
+    }
+}
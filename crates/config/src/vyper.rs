@@ -0,0 +1,22 @@
+//! Configuration for compiling the Vyper sources of a project
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Contains the config for compiling `.vy` files with `vyper`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VyperConfig {
+    /// The `vyper` version to use.
+    ///
+    /// The release is downloaded to `~/.foundry/vyper` if it's not installed yet, and verified
+    /// against the pinned checksum of its binary.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+    /// Path to the `vyper` binary to use, takes precedence over `version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// The optimization mode, passed as `--optimize`, e.g. `gas`, `codesize` or `none`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub optimize: Option<String>,
+}
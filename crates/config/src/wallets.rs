@@ -0,0 +1,35 @@
+//! Configuration for the remote signers
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Contains the config of the keys of remote signers, the `[wallets]` section
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WalletsConfig {
+    /// The ids of the AWS KMS keys by alias.
+    ///
+    /// All of them are used with `--aws`, unless `AWS_KMS_KEY_IDS` or `AWS_KMS_KEY_ID` is set.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub aws_kms: BTreeMap<String, String>,
+    /// The region of the AWS KMS keys, `AWS_DEFAULT_REGION` and `AWS_REGION` take precedence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aws_region: Option<String>,
+    /// The resource names of the GCP KMS key versions by alias, which can be passed to
+    /// `--gcp-kms` instead of the resource name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub gcp_kms: BTreeMap<String, String>,
+}
+
+impl WalletsConfig {
+    /// Returns the AWS KMS key ids of the config.
+    pub fn aws_kms_keys(&self) -> Vec<String> {
+        self.aws_kms.values().cloned().collect()
+    }
+
+    /// Resolves the resource name of a GCP KMS key version from its alias, the name is returned
+    /// as is if it isn't an alias.
+    pub fn gcp_kms_key(&self, name_or_alias: &str) -> String {
+        self.gcp_kms.get(name_or_alias).cloned().unwrap_or_else(|| name_or_alias.to_string())
+    }
+}
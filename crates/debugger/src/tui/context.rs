@@ -1,11 +1,21 @@
 //! Debugger context and event handler implementation.
 
 use crate::{Debugger, ExitReason};
-use alloy_primitives::Address;
+use alloy_primitives::{Address, U256};
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
-use foundry_evm_core::debug::{DebugNodeFlat, DebugStep};
+use foundry_evm_core::debug::{DebugNodeFlat, DebugStep, Instruction};
+use revm::interpreter::opcode;
 use revm_inspectors::tracing::types::CallKind;
-use std::{cell::RefCell, ops::ControlFlow};
+use std::{
+    cell::RefCell,
+    fmt,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// A position in the debug arena: `(call index, step index)`.
+pub(crate) type Position = (usize, usize);
 
 /// This is currently used to remember last scroll position so screen doesn't wiggle as much.
 #[derive(Default)]
@@ -17,6 +27,61 @@ pub(crate) struct DrawMemory {
     pub(crate) current_stack_startline: usize,
 }
 
+/// A breakpoint on a line of a source file, e.g. `src/Vault.sol:87`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct LineBreakpoint {
+    /// The path of the source file, matched against the end of the source paths.
+    pub(crate) path: PathBuf,
+    /// The 1-based line number.
+    pub(crate) line: usize,
+}
+
+impl LineBreakpoint {
+    /// Returns whether the breakpoint is set on the given line of the file.
+    fn matches(&self, path: &Path, line: usize) -> bool {
+        self.line == line && path.ends_with(&self.path)
+    }
+}
+
+impl FromStr for LineBreakpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, line) =
+            s.rsplit_once(':').ok_or_else(|| format!("expected `<file>:<line>`, got `{s}`"))?;
+        let line = line.parse().ok().filter(|&line| line > 0);
+        let line = line.ok_or_else(|| format!("invalid line number in `{s}`"))?;
+        Ok(Self { path: path.into(), line })
+    }
+}
+
+impl fmt::Display for LineBreakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line)
+    }
+}
+
+/// A value that is displayed in the watch pane.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Watch {
+    /// The stack item at the given depth, `0` is the top of the stack.
+    Stack(usize),
+    /// A storage slot of the current call's address.
+    ///
+    /// The value is known once the slot has been read or written by a call to the same address
+    /// in the debug arena.
+    Slot(U256),
+}
+
+impl fmt::Display for Watch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stack(depth) => write!(f, "stack[{depth}]"),
+            Self::Slot(slot) => write!(f, "slot {slot:#x}"),
+        }
+    }
+}
+
 pub(crate) struct DebuggerContext<'a> {
     pub(crate) debugger: &'a mut Debugger,
 
@@ -31,6 +96,16 @@ pub(crate) struct DebuggerContext<'a> {
     pub(crate) stack_labels: bool,
     pub(crate) mem_utf: bool,
     pub(crate) show_shortcuts: bool,
+    pub(crate) show_help: bool,
+
+    /// The command being entered after `:`, if any.
+    pub(crate) command: Option<String>,
+    /// The result of the last command or movement, shown in the status bar.
+    pub(crate) message: Option<Result<String, String>>,
+    /// Breakpoints on source lines, set with `:b <file>:<line>`.
+    pub(crate) line_breakpoints: Vec<LineBreakpoint>,
+    /// Values displayed in the watch pane, set with `:w stack <n>` or `:w slot <slot>`.
+    pub(crate) watches: Vec<Watch>,
 }
 
 impl<'a> DebuggerContext<'a> {
@@ -47,6 +122,12 @@ impl<'a> DebuggerContext<'a> {
             stack_labels: false,
             mem_utf: false,
             show_shortcuts: true,
+            show_help: false,
+
+            command: None,
+            message: None,
+            line_breakpoints: Vec::new(),
+            watches: Vec::new(),
         }
     }
 
@@ -82,6 +163,101 @@ impl<'a> DebuggerContext<'a> {
         &self.debug_steps()[self.current_step]
     }
 
+    /// Returns the current position in the debug arena.
+    pub(crate) fn position(&self) -> Position {
+        (self.draw_memory.inner_call_index, self.current_step)
+    }
+
+    /// Moves to the given position in the debug arena.
+    fn set_position(&mut self, (call, step): Position) {
+        self.draw_memory.inner_call_index = call;
+        self.current_step = step;
+        if self.last_index != call {
+            self.gen_opcode_list();
+            self.last_index = call;
+        }
+    }
+
+    /// Returns the position of the step after the given one.
+    fn next_position(&self, (call, step): Position) -> Option<Position> {
+        if step + 1 < self.debug_arena()[call].steps.len() {
+            Some((call, step + 1))
+        } else {
+            (call + 1 < self.debug_arena().len()).then_some((call + 1, 0))
+        }
+    }
+
+    /// Returns the position of the step before the given one.
+    pub(crate) fn prev_position(&self, (call, step): Position) -> Option<Position> {
+        if step > 0 {
+            Some((call, step - 1))
+        } else {
+            let call = call.checked_sub(1)?;
+            Some((call, self.debug_arena()[call].steps.len() - 1))
+        }
+    }
+
+    /// Returns the source file and line the step at the given position maps to.
+    fn source_line(&self, (call, step): Position) -> Option<(&Path, usize)> {
+        let call = &self.debug_arena()[call];
+        let location = self.debugger.source_location(call, call.steps[step].pc).ok()?;
+        Some((location.path, location.line))
+    }
+
+    /// Returns the index of the line breakpoint the step at the given position hits, if any.
+    fn breakpoint_at(&self, position: Position) -> Option<usize> {
+        if self.line_breakpoints.is_empty() {
+            return None;
+        }
+        let (path, line) = self.source_line(position)?;
+        self.line_breakpoints.iter().position(|breakpoint| breakpoint.matches(path, line))
+    }
+
+    /// Returns all positions in the debug arena, in order.
+    fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        self.debug_arena()
+            .iter()
+            .enumerate()
+            .flat_map(|(call, node)| (0..node.steps.len()).map(move |step| (call, step)))
+    }
+
+    /// Returns the value of the watch before the step at the given position executes, if known.
+    pub(crate) fn watch_value(&self, watch: Watch, (call, step): Position) -> Option<U256> {
+        match watch {
+            Watch::Stack(depth) => {
+                let stack = &self.debug_arena()[call].steps[step].stack;
+                stack.len().checked_sub(depth + 1).map(|i| stack[i])
+            }
+            Watch::Slot(slot) => {
+                let address = self.debug_arena()[call].address;
+                let mut value = None;
+                for (i, node) in self.debug_arena()[..=call].iter().enumerate() {
+                    if node.address != address {
+                        continue;
+                    }
+                    let end = if i == call { step } else { node.steps.len() };
+                    for (j, debug_step) in node.steps[..end].iter().enumerate() {
+                        match (debug_step.instruction, &debug_step.stack[..]) {
+                            (Instruction::OpCode(opcode::SSTORE), [.., new, key])
+                                if *key == slot =>
+                            {
+                                value = Some(*new);
+                            }
+                            (Instruction::OpCode(opcode::SLOAD), [.., key]) if *key == slot => {
+                                // the loaded value is on top of the stack of the next step
+                                if let Some(next) = node.steps.get(j + 1) {
+                                    value = next.stack.last().copied();
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                value
+            }
+        }
+    }
+
     fn gen_opcode_list(&mut self) {
         self.opcode_list = self.opcode_list();
     }
@@ -106,6 +282,18 @@ impl DebuggerContext<'_> {
     }
 
     fn handle_key_event(&mut self, event: KeyEvent) -> ControlFlow<ExitReason> {
+        if self.command.is_some() {
+            self.handle_command_key(event);
+            return ControlFlow::Continue(());
+        }
+
+        // Any key closes the help overlay
+        if self.show_help && event.code != KeyCode::Char('q') {
+            self.show_help = false;
+            return ControlFlow::Continue(());
+        }
+
+        self.message = None;
         if let KeyCode::Char(c) = event.code {
             if c.is_alphabetic() && self.key_buffer.starts_with('\'') {
                 self.handle_breakpoint(c);
@@ -245,6 +433,37 @@ impl DebuggerContext<'_> {
                 }
                 self.key_buffer.clear();
             }
+            // Step forward one source line
+            KeyCode::Char('n') => {
+                for _ in 0..buffer_as_number(&self.key_buffer, 1) {
+                    self.step_line_forward();
+                }
+                self.key_buffer.clear();
+            }
+            // Step back one source line
+            KeyCode::Char('p') => {
+                for _ in 0..buffer_as_number(&self.key_buffer, 1) {
+                    self.step_line_back();
+                }
+                self.key_buffer.clear();
+            }
+            // Run to the next breakpoint
+            KeyCode::Char('r') => {
+                self.run_to_breakpoint(true);
+                self.key_buffer.clear();
+            }
+            // Run back to the previous breakpoint
+            KeyCode::Char('R') => {
+                self.run_to_breakpoint(false);
+                self.key_buffer.clear();
+            }
+            // Enter a command
+            KeyCode::Char(':') => {
+                self.command = Some(String::new());
+                self.key_buffer.clear();
+            }
+            // toggle help overlay
+            KeyCode::Char('?') => self.show_help = true,
             // toggle stack labels
             KeyCode::Char('t') => self.stack_labels = !self.stack_labels,
             // toggle memory utf8 decoding
@@ -260,6 +479,162 @@ impl DebuggerContext<'_> {
         ControlFlow::Continue(())
     }
 
+    fn handle_command_key(&mut self, event: KeyEvent) {
+        let Some(command) = &mut self.command else { return };
+        match event.code {
+            KeyCode::Enter => {
+                let command = std::mem::take(command);
+                self.command = None;
+                self.message = Some(self.execute_command(&command));
+            }
+            KeyCode::Esc => self.command = None,
+            KeyCode::Backspace => {
+                if command.pop().is_none() {
+                    self.command = None;
+                }
+            }
+            KeyCode::Char(c) => command.push(c),
+            _ => {}
+        }
+    }
+
+    /// Executes a command entered after `:`.
+    fn execute_command(&mut self, command: &str) -> Result<String, String> {
+        let words = command.split_whitespace().collect::<Vec<_>>();
+        match words[..] {
+            ["b" | "break", location] => {
+                let breakpoint = location.parse::<LineBreakpoint>()?;
+                let hits = self
+                    .positions()
+                    .filter(|&position| {
+                        self.source_line(position)
+                            .is_some_and(|(path, line)| breakpoint.matches(path, line))
+                    })
+                    .count();
+                if hits == 0 {
+                    return Err(format!("no instructions map to {breakpoint}"));
+                }
+                let message = format!("breakpoint set at {breakpoint} ({hits} instructions)");
+                if !self.line_breakpoints.contains(&breakpoint) {
+                    self.line_breakpoints.push(breakpoint);
+                }
+                Ok(message)
+            }
+            ["d" | "delete"] => {
+                self.line_breakpoints.clear();
+                Ok("deleted all breakpoints".to_string())
+            }
+            ["d" | "delete", location] => {
+                let breakpoint = location.parse::<LineBreakpoint>()?;
+                let len = self.line_breakpoints.len();
+                self.line_breakpoints.retain(|b| *b != breakpoint);
+                if self.line_breakpoints.len() == len {
+                    return Err(format!("no breakpoint set at {breakpoint}"));
+                }
+                Ok(format!("deleted breakpoint at {breakpoint}"))
+            }
+            ["w" | "watch", "stack", depth] => {
+                let depth = depth.parse().map_err(|_| format!("invalid stack depth `{depth}`"))?;
+                self.watches.push(Watch::Stack(depth));
+                Ok(format!("watching stack[{depth}]"))
+            }
+            ["w" | "watch", "slot", slot] => {
+                let slot = slot.parse().map_err(|_| format!("invalid storage slot `{slot}`"))?;
+                let watch = Watch::Slot(slot);
+                self.watches.push(watch);
+                Ok(format!("watching {watch}"))
+            }
+            ["w" | "watch", "clear"] => {
+                self.watches.clear();
+                Ok("cleared all watches".to_string())
+            }
+            [] => Ok(String::new()),
+            _ => Err(format!("unknown command `{command}`, press `?` for help")),
+        }
+    }
+
+    /// Moves to the first step that maps to a different source line than the current one.
+    fn step_line_forward(&mut self) {
+        let line = self.source_line(self.position());
+        let mut position = self.position();
+        while let Some(next) = self.next_position(position) {
+            position = next;
+            if self.source_line(next).is_some_and(|next| Some(next) != line) {
+                self.set_position(next);
+                return;
+            }
+        }
+    }
+
+    /// Moves to the first step of the previous source line.
+    fn step_line_back(&mut self) {
+        let line = self.source_line(self.position());
+        let mut position = self.position();
+        let mut target = None;
+        while let Some(prev) = self.prev_position(position) {
+            let prev_line = self.source_line(prev);
+            match target {
+                None if prev_line.is_some() && prev_line != line => target = prev_line,
+                Some(_) if prev_line != target => break,
+                _ => {}
+            }
+            position = prev;
+        }
+        if target.is_some() {
+            self.set_position(position);
+        }
+    }
+
+    /// Moves to the next (or previous) step that hits a line breakpoint.
+    ///
+    /// The steps directly following (or preceding) the current one that hit the same breakpoint
+    /// are skipped, and running back stops at the first step of a hit.
+    fn run_to_breakpoint(&mut self, forward: bool) {
+        if self.line_breakpoints.is_empty() {
+            self.message = Some(Err("no breakpoints set, use `:b <file>:<line>`".to_string()));
+            return;
+        }
+
+        // The breakpoint hit by the run of steps that is being skipped
+        let mut run = self.breakpoint_at(self.position());
+        let mut position = self.position();
+        let mut target = None;
+        if forward {
+            while let Some(next) = self.next_position(position) {
+                let hit = self.breakpoint_at(next);
+                if hit.is_some() && hit != run {
+                    target = Some(next);
+                    break;
+                }
+                run = hit;
+                position = next;
+            }
+        } else {
+            while let Some(prev) = self.prev_position(position) {
+                let hit = self.breakpoint_at(prev);
+                if target.is_some() {
+                    if hit != run {
+                        break;
+                    }
+                    target = Some(prev);
+                } else if hit.is_some() && hit != run {
+                    target = Some(prev);
+                }
+                run = hit;
+                position = prev;
+            }
+        }
+
+        match target {
+            Some(target) => {
+                let hit = self.breakpoint_at(target).unwrap();
+                self.set_position(target);
+                self.message = Some(Ok(format!("hit breakpoint {}", self.line_breakpoints[hit])));
+            }
+            None => self.message = Some(Err("no more breakpoint hits".to_string())),
+        }
+    }
+
     fn handle_breakpoint(&mut self, c: char) {
         // Find the location of the called breakpoint in the whole debug arena (at this address with
         // this pc)
@@ -6,11 +6,12 @@ use alloy_primitives::U256;
 use foundry_compilers::sourcemap::SourceElement;
 use foundry_evm_core::debug::Instruction;
 use ratatui::{
+    backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    terminal::Frame,
+    terminal::{Frame, Terminal},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
 use revm::interpreter::opcode;
 use revm_inspectors::tracing::types::CallKind;
@@ -18,7 +19,7 @@ use std::{cmp, collections::VecDeque, fmt::Write, io};
 
 impl DebuggerContext<'_> {
     /// Draws the TUI layout and subcomponents to the given terminal.
-    pub(crate) fn draw(&self, terminal: &mut super::DebuggerTerminal) -> io::Result<()> {
+    pub(crate) fn draw<B: Backend>(&self, terminal: &mut Terminal<B>) -> io::Result<()> {
         terminal.draw(|f| self.draw_layout(f)).map(drop)
     }
 
@@ -40,6 +41,10 @@ impl DebuggerContext<'_> {
         } else {
             self.vertical_layout(f);
         }
+
+        if self.show_help {
+            self.draw_help(f, size);
+        }
     }
 
     fn size_too_small(&self, f: &mut Frame<'_>, min_width: u16, min_height: u16) {
@@ -100,6 +105,8 @@ impl DebuggerContext<'_> {
             unreachable!()
         };
 
+        let (app, status_bar) = self.split_status_bar(app);
+
         // Split the app in 4 vertically to construct all the panes.
         let [op_pane, stack_pane, memory_pane, src_pane] = Layout::new()
             .direction(Direction::Vertical)
@@ -117,6 +124,13 @@ impl DebuggerContext<'_> {
         if self.show_shortcuts {
             self.draw_footer(f, footer);
         }
+        if let Some(status_bar) = status_bar {
+            self.draw_status_bar(f, status_bar);
+        }
+        let (src_pane, watch_pane) = self.split_watch_pane(src_pane);
+        if let Some(watch_pane) = watch_pane {
+            self.draw_watches(f, watch_pane);
+        }
         self.draw_src(f, src_pane);
         self.draw_op_list(f, op_pane);
         self.draw_stack(f, stack_pane);
@@ -147,6 +161,8 @@ impl DebuggerContext<'_> {
             unreachable!()
         };
 
+        let (app, status_bar) = self.split_status_bar(app);
+
         // Split app in 2 horizontally.
         let [app_left, app_right] = Layout::new()
             .direction(Direction::Horizontal)
@@ -177,15 +193,54 @@ impl DebuggerContext<'_> {
         if self.show_shortcuts {
             self.draw_footer(f, footer);
         }
+        if let Some(status_bar) = status_bar {
+            self.draw_status_bar(f, status_bar);
+        }
+        let (src_pane, watch_pane) = self.split_watch_pane(src_pane);
+        if let Some(watch_pane) = watch_pane {
+            self.draw_watches(f, watch_pane);
+        }
         self.draw_src(f, src_pane);
         self.draw_op_list(f, op_pane);
         self.draw_stack(f, stack_pane);
         self.draw_memory(f, memory_pane);
     }
 
+    /// Splits off a line at the bottom of the area for the command being entered or the result of
+    /// the last command.
+    fn split_status_bar(&self, area: Rect) -> (Rect, Option<Rect>) {
+        if self.command.is_none() && self.message.is_none() {
+            return (area, None);
+        }
+        let [area, status_bar] = Layout::new()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(area)[..]
+        else {
+            unreachable!()
+        };
+        (area, Some(status_bar))
+    }
+
+    /// Splits off the watch pane at the bottom of the source pane if there are any watches.
+    fn split_watch_pane(&self, area: Rect) -> (Rect, Option<Rect>) {
+        if self.watches.is_empty() {
+            return (area, None);
+        }
+        let height = self.watches.len() as u16 + 2;
+        let [area, watch_pane] = Layout::new()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(height)])
+            .split(area)[..]
+        else {
+            unreachable!()
+        };
+        (area, Some(watch_pane))
+    }
+
     fn draw_footer(&self, f: &mut Frame<'_>, area: Rect) {
-        let l1 = "[q]: quit | [k/j]: prev/next op | [a/s]: prev/next jump | [c/C]: prev/next call | [g/G]: start/end";
-        let l2 = "[t]: stack labels | [m]: memory decoding | [shift + j/k]: scroll stack | [ctrl + j/k]: scroll memory | ['<char>]: goto breakpoint | [h] toggle help";
+        let l1 = "[q]: quit | [k/j]: prev/next op | [p/n]: prev/next line | [a/s]: prev/next jump | [c/C]: prev/next call | [g/G]: start/end | [R/r]: prev/next breakpoint";
+        let l2 = "[t]: stack labels | [m]: memory decoding | [shift + j/k]: scroll stack | [ctrl + j/k]: scroll memory | ['<char>]: goto breakpoint | [:]: command | [?]: all keys | [h] toggle help";
         let dimmed = Style::new().add_modifier(Modifier::DIM);
         let lines =
             vec![Line::from(Span::styled(l1, dimmed)), Line::from(Span::styled(l2, dimmed))];
@@ -194,6 +249,71 @@ impl DebuggerContext<'_> {
         f.render_widget(paragraph, area);
     }
 
+    fn draw_status_bar(&self, f: &mut Frame<'_>, area: Rect) {
+        let line = match (&self.command, &self.message) {
+            (Some(command), _) => Line::from(format!(":{command}")),
+            (None, Some(Ok(message))) => Line::from(message.as_str()),
+            (None, Some(Err(error))) => {
+                Line::from(Span::styled(error.as_str(), Style::new().fg(Color::Red)))
+            }
+            (None, None) => return,
+        };
+        f.render_widget(Paragraph::new(line), area);
+    }
+
+    fn draw_help(&self, f: &mut Frame<'_>, area: Rect) {
+        let key_style = Style::new().fg(Color::Cyan);
+        let lines = KEYBINDINGS
+            .iter()
+            .map(|(keys, description)| {
+                Line::from(vec![
+                    Span::styled(format!("{keys: <22}"), key_style),
+                    Span::raw(*description),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let width = area.width.min(80);
+        let height = area.height.min(lines.len() as u16 + 2);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let block =
+            Block::default().title("Keybindings (press any key to close)").borders(Borders::ALL);
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+    }
+
+    fn draw_watches(&self, f: &mut Frame<'_>, area: Rect) {
+        let position = self.position();
+        let prev_position = self.prev_position(position);
+        let lines = self
+            .watches
+            .iter()
+            .map(|&watch| {
+                let value = self.watch_value(watch, position);
+                let prev_value = prev_position.and_then(|prev| self.watch_value(watch, prev));
+                let changed = prev_position.is_some() && value != prev_value;
+                let value =
+                    value.map_or_else(|| "unknown".to_string(), |value| format!("{value:#x}"));
+                let style = if changed {
+                    Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::new().fg(Color::White)
+                };
+                let label = format!("{watch}: ");
+                Line::from(vec![Span::raw(label), Span::styled(value, style)])
+            })
+            .collect::<Vec<_>>();
+
+        let block = Block::default().title("Watch").borders(Borders::ALL);
+        let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+    }
+
     fn draw_src(&self, f: &mut Frame<'_>, area: Rect) {
         let text_output = self.src_text(area);
         let title = match self.call_kind() {
@@ -322,40 +442,8 @@ impl DebuggerContext<'_> {
     }
 
     fn src_map(&self) -> Result<(SourceElement, &str), String> {
-        let address = self.address();
-        let Some(contract_name) = self.debugger.identified_contracts.get(address) else {
-            return Err(format!("Unknown contract at address {address}"));
-        };
-
-        let Some(files_source_code) = self.debugger.contracts_sources.0.get(contract_name) else {
-            return Err(format!("No source map index for contract {contract_name}"));
-        };
-
-        let Some((create_map, rt_map)) = self.debugger.pc_ic_maps.get(contract_name) else {
-            return Err(format!("No PC-IC maps for contract {contract_name}"));
-        };
-
-        let is_create = matches!(self.call_kind(), CallKind::Create | CallKind::Create2);
-        let pc = self.current_step().pc;
-        let Some((source_element, source_code)) =
-            files_source_code.iter().find_map(|(file_id, (source_code, contract_source))| {
-                let bytecode = if is_create {
-                    &contract_source.bytecode
-                } else {
-                    contract_source.deployed_bytecode.bytecode.as_ref()?
-                };
-                let mut source_map = bytecode.source_map()?.ok()?;
-
-                let pc_ic_map = if is_create { create_map } else { rt_map };
-                let ic = pc_ic_map.get(pc)?;
-                let source_element = source_map.swap_remove(ic);
-                (*file_id == source_element.index?).then_some((source_element, source_code))
-            })
-        else {
-            return Err(format!("No source map for contract {contract_name}"));
-        };
-
-        Ok((source_element, source_code))
+        let location = self.debugger.source_location(self.debug_call(), self.current_step().pc)?;
+        Ok((location.element.clone(), location.source))
     }
 
     fn draw_op_list(&self, f: &mut Frame<'_>, area: Rect) {
@@ -575,6 +663,30 @@ impl DebuggerContext<'_> {
     }
 }
 
+/// The keybindings shown in the help overlay.
+const KEYBINDINGS: &[(&str, &str)] = &[
+    ("q", "quit"),
+    ("k/j, up/down", "previous/next opcode"),
+    ("p/n", "previous/next source line"),
+    ("a/s", "previous/next jump"),
+    ("c/C", "previous/next call"),
+    ("g/G", "start/end"),
+    ("R/r", "run to the previous/next breakpoint"),
+    ("'<char>", "go to the `vm.breakpoint` with the given char"),
+    ("<n><key>", "repeat a movement n times, e.g. `10k`"),
+    ("shift + j/k", "scroll the stack"),
+    ("ctrl + j/k", "scroll the memory"),
+    ("t", "toggle stack labels"),
+    ("m", "toggle memory decoding"),
+    ("h", "toggle the shortcuts footer"),
+    ("?", "show this help"),
+    (":b <file>:<line>", "set a breakpoint, e.g. `:b src/Vault.sol:87`"),
+    (":d [<file>:<line>]", "delete a breakpoint, or all breakpoints"),
+    (":w stack <n>", "watch the stack item at depth n, 0 is the top"),
+    (":w slot <slot>", "watch a storage slot of the current address"),
+    (":w clear", "remove all watches"),
+];
+
 /// Wrapper around a list of [`Line`]s that prepends the line number on each new line.
 struct SourceLines<'a> {
     lines: Vec<Line<'a>>,
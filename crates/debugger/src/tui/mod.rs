@@ -8,16 +8,19 @@ use crossterm::{
 };
 use eyre::Result;
 use foundry_common::{compile::ContractSources, evm::Breakpoints};
+use foundry_compilers::sourcemap::{SourceElement, SourceMap};
 use foundry_evm_core::{debug::DebugNodeFlat, utils::PcIcMap};
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     Terminal,
 };
 use revm::primitives::SpecId;
+use revm_inspectors::tracing::types::CallKind;
 use std::{
     collections::{BTreeMap, HashMap},
     io,
     ops::ControlFlow,
+    path::Path,
     sync::{mpsc, Arc},
     thread,
     time::{Duration, Instant},
@@ -33,6 +36,23 @@ mod draw;
 
 type DebuggerTerminal = Terminal<CrosstermBackend<io::Stdout>>;
 
+/// The parsed source maps of a contract's creation and runtime code, and the byte offsets of the
+/// lines of its source file.
+struct SourceMaps {
+    create: Option<SourceMap>,
+    runtime: Option<SourceMap>,
+    line_offsets: Vec<usize>,
+}
+
+/// The location in the source code an instruction maps to.
+struct SourceLocation<'a> {
+    element: &'a SourceElement,
+    path: &'a Path,
+    source: &'a str,
+    /// The 1-based line number the element starts on.
+    line: usize,
+}
+
 /// Debugger exit reason.
 #[derive(Debug)]
 pub enum ExitReason {
@@ -48,6 +68,8 @@ pub struct Debugger {
     contracts_sources: ContractSources,
     /// A mapping of source -> (PC -> IC map for deploy code, PC -> IC map for runtime code)
     pc_ic_maps: BTreeMap<String, (PcIcMap, PcIcMap)>,
+    /// A mapping of source -> file_id -> parsed source maps
+    source_maps: HashMap<String, HashMap<u32, SourceMaps>>,
     breakpoints: Breakpoints,
 }
 
@@ -69,7 +91,7 @@ impl Debugger {
             .0
             .iter()
             .flat_map(|(contract_name, files_sources)| {
-                files_sources.iter().filter_map(|(_, (_, contract))| {
+                files_sources.iter().filter_map(|(_, (_, _, contract))| {
                     Some((
                         contract_name.clone(),
                         (
@@ -80,7 +102,37 @@ impl Debugger {
                 })
             })
             .collect();
-        Self { debug_arena, identified_contracts, contracts_sources, pc_ic_maps, breakpoints }
+        let source_maps = contracts_sources
+            .0
+            .iter()
+            .map(|(contract_name, files_sources)| {
+                let maps = files_sources
+                    .iter()
+                    .map(|(file_id, (_, source_code, contract))| {
+                        let create = contract.bytecode.source_map().and_then(Result::ok);
+                        let runtime = contract
+                            .deployed_bytecode
+                            .bytecode
+                            .as_ref()
+                            .and_then(|bytecode| bytecode.source_map())
+                            .and_then(Result::ok);
+                        let line_offsets = std::iter::once(0)
+                            .chain(source_code.match_indices('\n').map(|(i, _)| i + 1))
+                            .collect();
+                        (*file_id, SourceMaps { create, runtime, line_offsets })
+                    })
+                    .collect();
+                (contract_name.clone(), maps)
+            })
+            .collect();
+        Self {
+            debug_arena,
+            identified_contracts,
+            contracts_sources,
+            pc_ic_maps,
+            source_maps,
+            breakpoints,
+        }
     }
 
     /// Starts the debugger TUI. Terminates the current process on failure or user exit.
@@ -130,6 +182,44 @@ impl Debugger {
         }
     }
 
+    /// Returns the source location the given program counter of the call maps to.
+    fn source_location(
+        &self,
+        call: &DebugNodeFlat,
+        pc: usize,
+    ) -> Result<SourceLocation<'_>, String> {
+        let address = &call.address;
+        let Some(contract_name) = self.identified_contracts.get(address) else {
+            return Err(format!("Unknown contract at address {address}"));
+        };
+
+        let Some(files_source_code) = self.contracts_sources.0.get(contract_name) else {
+            return Err(format!("No source map index for contract {contract_name}"));
+        };
+
+        let Some((create_map, rt_map)) = self.pc_ic_maps.get(contract_name) else {
+            return Err(format!("No PC-IC maps for contract {contract_name}"));
+        };
+
+        let is_create = matches!(call.kind, CallKind::Create | CallKind::Create2);
+        let pc_ic_map = if is_create { create_map } else { rt_map };
+        let source_maps = &self.source_maps[contract_name];
+        files_source_code
+            .iter()
+            .find_map(|(file_id, (path, source_code, _))| {
+                let maps = source_maps.get(file_id)?;
+                let source_map = if is_create { &maps.create } else { &maps.runtime };
+                let element = source_map.as_ref()?.get(pc_ic_map.get(pc)?)?;
+                (*file_id == element.index?).then(|| SourceLocation {
+                    element,
+                    path,
+                    source: source_code,
+                    line: maps.line_offsets.partition_point(|&offset| offset <= element.offset),
+                })
+            })
+            .ok_or_else(|| format!("No source map for contract {contract_name}"))
+    }
+
     fn event_listener(tx: mpsc::Sender<Event>) {
         // This is the recommend tick rate from `ratatui`, based on their examples
         let tick_rate = Duration::from_millis(200);
@@ -213,3 +303,147 @@ impl<B: Backend + io::Write> Drop for TerminalGuard<'_, B> {
         self.restore();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use foundry_common::compact_to_contract;
+    use foundry_compilers::artifacts::CompactContractBytecode;
+    use foundry_evm_core::debug::{DebugStep, Instruction};
+    use ratatui::backend::TestBackend;
+    use revm::interpreter::opcode;
+
+    const SOURCE: &str = r#"contract Counter {
+    uint256 x;
+
+    function f() public {
+        x = 42;
+        uint256 y = x;
+    }
+}
+"#;
+
+    /// `x = 42; uint256 y = x;` compiled by hand: `PUSH1 42 PUSH1 0 SSTORE PUSH1 0 SLOAD STOP`.
+    fn debugger() -> Debugger {
+        let offset = |s| SOURCE.find(s).unwrap();
+        let source_map = format!(
+            "{}:7:0;;;{}:14:0;;{}:50:0",
+            offset("x = 42"),
+            offset("uint256 y"),
+            offset("function f")
+        );
+        let bytecode =
+            serde_json::json!({ "object": "0x602a60005560005400", "sourceMap": source_map });
+        let contract: CompactContractBytecode = serde_json::from_value(serde_json::json!({
+            "abi": [],
+            "bytecode": bytecode,
+            "deployedBytecode": bytecode,
+        }))
+        .unwrap();
+        let contract = compact_to_contract(contract).unwrap();
+
+        let mut sources = ContractSources::default();
+        sources
+            .0
+            .entry("Counter".to_string())
+            .or_default()
+            .insert(0, ("src/Counter.sol".into(), SOURCE.to_string(), contract));
+
+        let step = |pc, op, stack: &[u64]| DebugStep {
+            stack: stack.iter().copied().map(U256::from).collect(),
+            instruction: Instruction::OpCode(op),
+            pc,
+            ..Default::default()
+        };
+        let steps = vec![
+            step(0, opcode::PUSH1, &[]),
+            step(2, opcode::PUSH1, &[42]),
+            step(4, opcode::SSTORE, &[42, 0]),
+            step(5, opcode::PUSH1, &[]),
+            step(7, opcode::SLOAD, &[0]),
+            step(8, opcode::STOP, &[42]),
+        ];
+        let address = Address::repeat_byte(1);
+        let arena = vec![DebugNodeFlat::new(address, CallKind::Call, steps)];
+
+        Debugger::new(arena, [(address, "Counter".to_string())].into(), sources, Default::default())
+    }
+
+    fn press(cx: &mut DebuggerContext<'_>, code: KeyCode) -> ControlFlow<ExitReason> {
+        cx.handle_event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+    }
+
+    fn type_keys(cx: &mut DebuggerContext<'_>, keys: &str) {
+        for c in keys.chars() {
+            let _ = press(cx, KeyCode::Char(c));
+        }
+    }
+
+    fn command(cx: &mut DebuggerContext<'_>, command: &str) -> Result<String, String> {
+        type_keys(cx, &format!(":{command}"));
+        let _ = press(cx, KeyCode::Enter);
+        cx.message.clone().unwrap()
+    }
+
+    fn screen(cx: &DebuggerContext<'_>, terminal: &mut Terminal<TestBackend>) -> String {
+        cx.draw(terminal).unwrap();
+        terminal.backend().buffer().content.iter().map(|cell| cell.symbol.as_str()).collect()
+    }
+
+    #[test]
+    fn can_drive_debugger() {
+        let mut debugger = debugger();
+        let mut cx = DebuggerContext::new(&mut debugger);
+        cx.init();
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        assert!(screen(&cx, &mut terminal).contains("x = 42"));
+
+        // breakpoints are resolved through the source map
+        assert!(command(&mut cx, "b src/Counter.sol:3").is_err());
+        assert!(command(&mut cx, "b Counter.sol:6").unwrap().contains("2 instructions"));
+        assert!(command(&mut cx, "frobnicate").is_err());
+
+        type_keys(&mut cx, "r");
+        assert_eq!(cx.position(), (0, 3));
+        type_keys(&mut cx, "r");
+        assert_eq!(cx.position(), (0, 3));
+        assert!(cx.message.clone().unwrap().is_err());
+
+        // step back one source line, one opcode and forward one source line
+        type_keys(&mut cx, "p");
+        assert_eq!(cx.position(), (0, 0));
+        type_keys(&mut cx, "n");
+        assert_eq!(cx.position(), (0, 3));
+        type_keys(&mut cx, "k");
+        assert_eq!(cx.position(), (0, 2));
+        type_keys(&mut cx, "R");
+        assert_eq!(cx.position(), (0, 2));
+        // running back from within a hit skips the rest of it
+        type_keys(&mut cx, "jjR");
+        assert_eq!(cx.position(), (0, 4));
+        type_keys(&mut cx, "GR");
+        assert_eq!(cx.position(), (0, 3));
+
+        // watches
+        command(&mut cx, "w slot 0").unwrap();
+        command(&mut cx, "w stack 0").unwrap();
+        assert_eq!(cx.watch_value(cx.watches[0], (0, 2)), None);
+        assert_eq!(cx.watch_value(cx.watches[0], (0, 3)), Some(U256::from(42)));
+        assert_eq!(cx.watch_value(cx.watches[1], (0, 3)), None);
+        assert_eq!(cx.watch_value(cx.watches[1], (0, 5)), Some(U256::from(42)));
+        let screen_text = screen(&cx, &mut terminal);
+        assert!(screen_text.contains("slot 0x0: 0x2a"), "{screen_text}");
+        assert!(screen_text.contains("stack[0]: unknown"), "{screen_text}");
+
+        // help overlay
+        type_keys(&mut cx, "?");
+        assert!(screen(&cx, &mut terminal).contains("run to the previous/next breakpoint"));
+        type_keys(&mut cx, "j");
+        assert!(!cx.show_help);
+        assert_eq!(cx.position(), (0, 3));
+
+        assert!(matches!(press(&mut cx, KeyCode::Char('q')), ControlFlow::Break(_)));
+    }
+}
@@ -1,16 +1,25 @@
 use super::{Preprocessor, PreprocessorId};
-use crate::{document::DocumentContent, Document, ParseSource, PreprocessorOutput};
+use crate::{document::DocumentContent, Document, ParseItem, ParseSource, PreprocessorOutput};
 use forge_fmt::solang_ext::SafeUnwrap;
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
+};
 
 /// [ContractInheritance] preprocessor id.
 pub const CONTRACT_INHERITANCE_ID: PreprocessorId = PreprocessorId("contract_inheritance");
 
+/// [ContractInheritance] preprocessor id of the inheritance graph output.
+pub const INHERITANCE_GRAPH_ID: PreprocessorId = PreprocessorId("inheritance_graph");
+
 /// The contract inheritance preprocessor.
 /// It matches the documents with inner [`ParseSource::Contract`](crate::ParseSource) elements,
 /// iterates over their [Base](solang_parser::pt::Base)s and attempts
 /// to link them with the paths of the other contract documents.
 ///
+/// The links include all the contracts of the inheritance hierarchy, not only the direct bases.
+/// The edges of the hierarchy are written to the context under [INHERITANCE_GRAPH_ID].
+///
 /// This preprocessor writes to [Document]'s context.
 #[derive(Debug, Default)]
 pub struct ContractInheritance {
@@ -24,16 +33,20 @@ impl Preprocessor for ContractInheritance {
     }
 
     fn preprocess(&self, documents: Vec<Document>) -> Result<Vec<Document>, eyre::Error> {
+        let bases = contract_bases(&documents);
         for document in documents.iter() {
             if let DocumentContent::Single(ref item) = document.content {
                 if let ParseSource::Contract(ref contract) = item.source {
+                    let edges = inheritance_edges(&contract.name.safe_unwrap().name, &bases);
                     let mut links = HashMap::default();
 
                     // Attempt to match bases to other contracts
-                    for base in contract.base.iter() {
-                        let base_ident = base.name.identifiers.last().unwrap().name.clone();
-                        if let Some(linked) = self.try_link_base(&base_ident, &documents) {
-                            links.insert(base_ident, linked);
+                    for (_, base_ident) in edges.iter() {
+                        if links.contains_key(base_ident) {
+                            continue
+                        }
+                        if let Some(linked) = self.try_link_base(base_ident, &documents) {
+                            links.insert(base_ident.clone(), linked);
                         }
                     }
 
@@ -42,6 +55,12 @@ impl Preprocessor for ContractInheritance {
                         document
                             .add_context(self.id(), PreprocessorOutput::ContractInheritance(links));
                     }
+                    if !edges.is_empty() {
+                        document.add_context(
+                            INHERITANCE_GRAPH_ID,
+                            PreprocessorOutput::InheritanceGraph(edges),
+                        );
+                    }
                 }
             }
         }
@@ -67,3 +86,151 @@ impl ContractInheritance {
         None
     }
 }
+
+/// Returns the first contract item with the given name.
+pub(crate) fn find_contract<'a>(name: &str, documents: &'a [Document]) -> Option<&'a ParseItem> {
+    documents.iter().find_map(|document| match document.content {
+        DocumentContent::Single(ref item) => match item.source {
+            ParseSource::Contract(ref contract) if contract.name.safe_unwrap().name == name => {
+                Some(item)
+            }
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Returns the names of the direct bases of every contract in the documents, in the order of
+/// declaration.
+pub(crate) fn contract_bases(documents: &[Document]) -> HashMap<String, Vec<String>> {
+    let mut bases = HashMap::new();
+    for document in documents {
+        if let DocumentContent::Single(ref item) = document.content {
+            if let ParseSource::Contract(ref contract) = item.source {
+                let names = contract
+                    .base
+                    .iter()
+                    .map(|base| base.name.identifiers.last().unwrap().name.clone())
+                    .collect();
+                bases.entry(contract.name.safe_unwrap().name.clone()).or_insert(names);
+            }
+        }
+    }
+    bases
+}
+
+/// Returns the `(contract, base)` edges of the inheritance hierarchy of the contract.
+fn inheritance_edges(
+    contract: &str,
+    bases: &HashMap<String, Vec<String>>,
+) -> Vec<(String, String)> {
+    let mut edges = Vec::new();
+    let mut visited = HashSet::from([contract]);
+    let mut queue = VecDeque::from([contract]);
+    while let Some(current) = queue.pop_front() {
+        for base in bases.get(current).into_iter().flatten() {
+            edges.push((current.to_owned(), base.clone()));
+            if visited.insert(base) {
+                queue.push_back(base);
+            }
+        }
+    }
+    edges
+}
+
+/// Returns the C3 linearization of the contract: the contract itself followed by its bases, from
+/// the most derived to the most base-like one. This is the order in which Solidity looks up
+/// overridden functions.
+///
+/// Returns [None] if the hierarchy is cyclic or can't be linearized.
+pub(crate) fn linearize(
+    contract: &str,
+    bases: &HashMap<String, Vec<String>>,
+) -> Option<Vec<String>> {
+    linearize_inner(contract, bases, &mut Vec::new())
+}
+
+fn linearize_inner(
+    contract: &str,
+    bases: &HashMap<String, Vec<String>>,
+    stack: &mut Vec<String>,
+) -> Option<Vec<String>> {
+    if stack.iter().any(|c| c == contract) {
+        return None
+    }
+    stack.push(contract.to_owned());
+
+    // Bases are declared from the most base-like to the most derived one
+    let direct = bases.get(contract).map(Vec::as_slice).unwrap_or_default();
+    let mut sequences = direct
+        .iter()
+        .rev()
+        .map(|base| linearize_inner(base, bases, stack))
+        .collect::<Option<Vec<_>>>()?;
+    sequences.push(direct.iter().rev().cloned().collect());
+    stack.pop();
+
+    let mut linearized = vec![contract.to_owned()];
+    loop {
+        sequences.retain(|seq| !seq.is_empty());
+        if sequences.is_empty() {
+            return Some(linearized)
+        }
+
+        // Take the first head that doesn't appear in the tail of any other sequence
+        let head = sequences
+            .iter()
+            .map(|seq| &seq[0])
+            .find(|head| !sequences.iter().any(|seq| seq[1..].contains(head)))?
+            .clone();
+        for seq in sequences.iter_mut() {
+            if seq[0] == head {
+                seq.remove(0);
+            }
+        }
+        linearized.push(head);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(graph: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        graph
+            .iter()
+            .map(|(name, bases)| {
+                (name.to_string(), bases.iter().map(|base| base.to_string()).collect())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn can_linearize_diamond() {
+        let bases = graph(&[
+            ("Base", &[]),
+            ("Left", &["Base"]),
+            ("Right", &["Base"]),
+            ("Diamond", &["Left", "Right"]),
+        ]);
+        assert_eq!(linearize("Diamond", &bases).unwrap(), ["Diamond", "Right", "Left", "Base"]);
+        assert_eq!(
+            inheritance_edges("Diamond", &bases),
+            [
+                ("Diamond".to_string(), "Left".to_string()),
+                ("Diamond".to_string(), "Right".to_string()),
+                ("Left".to_string(), "Base".to_string()),
+                ("Right".to_string(), "Base".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn cannot_linearize_inconsistent_hierarchy() {
+        let bases = graph(&[("A", &[]), ("B", &["A"]), ("C", &["B", "A"]), ("X", &["C"])]);
+        assert_eq!(linearize("X", &bases), None);
+
+        let cyclic = graph(&[("A", &["B"]), ("B", &["A"])]);
+        assert_eq!(linearize("A", &cyclic), None);
+    }
+}
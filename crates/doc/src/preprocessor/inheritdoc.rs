@@ -1,8 +1,12 @@
-use super::{Preprocessor, PreprocessorId};
+use super::{
+    contract_inheritance::{contract_bases, find_contract, linearize},
+    Preprocessor, PreprocessorId,
+};
 use crate::{
-    document::DocumentContent, Comments, Document, ParseItem, ParseSource, PreprocessorOutput,
+    document::DocumentContent, CommentTag, Comments, Document, ParseItem, ParseSource,
+    PreprocessorOutput,
 };
-use forge_fmt::solang_ext::SafeUnwrap;
+use foundry_common::shell;
 use std::collections::HashMap;
 
 /// [ContractInheritance] preprocessor id.
@@ -12,6 +16,10 @@ pub const INHERITDOC_ID: PreprocessorId = PreprocessorId("inheritdoc");
 /// Traverses the documents and attempts to find inherited
 /// comments for inheritdoc comment tags.
 ///
+/// The item is looked up in the referenced base and, if the base doesn't define it, in the bases
+/// of the referenced base in the order of their C3 linearization. A warning is emitted for the
+/// references that can't be resolved.
+///
 /// This preprocessor writes to [Document]'s context.
 #[derive(Debug, Default)]
 #[non_exhaustive]
@@ -23,9 +31,10 @@ impl Preprocessor for Inheritdoc {
     }
 
     fn preprocess(&self, documents: Vec<Document>) -> Result<Vec<Document>, eyre::Error> {
+        let bases = contract_bases(&documents);
         for document in documents.iter() {
             if let DocumentContent::Single(ref item) = document.content {
-                let context = self.visit_item(item, &documents);
+                let context = self.visit_item(item, &documents, &bases);
                 if !context.is_empty() {
                     document.add_context(self.id(), PreprocessorOutput::Inheritdoc(context));
                 }
@@ -37,26 +46,36 @@ impl Preprocessor for Inheritdoc {
 }
 
 impl Inheritdoc {
-    fn visit_item(&self, item: &ParseItem, documents: &Vec<Document>) -> HashMap<String, Comments> {
+    fn visit_item(
+        &self,
+        item: &ParseItem,
+        documents: &[Document],
+        bases: &HashMap<String, Vec<String>>,
+    ) -> HashMap<String, Comments> {
         let mut context = HashMap::default();
 
         // Match for the item first.
-        let matched = item
-            .comments
-            .find_inheritdoc_base()
-            .and_then(|base| self.try_match_inheritdoc(base, &item.source, documents));
-        if let Some((key, comments)) = matched {
-            context.insert(key, comments);
+        if let Some(base) = item.comments.find_inheritdoc_base() {
+            let key = format!("{}.{}", base, item.source.ident());
+            match self.try_match_inheritdoc(base, &item.source, documents, bases, &mut Vec::new()) {
+                Some(comments) => {
+                    context.insert(key, comments);
+                }
+                None => warn_unresolved(base, &item.source.ident()),
+            }
         }
 
         // Match item's children.
         for ch in item.children.iter() {
-            let matched = ch
-                .comments
-                .find_inheritdoc_base()
-                .and_then(|base| self.try_match_inheritdoc(base, &ch.source, documents));
-            if let Some((key, comments)) = matched {
-                context.insert(key, comments);
+            let Some(base) = ch.comments.find_inheritdoc_base() else { continue };
+            let key = format!("{}.{}", base, ch.source.ident());
+            match self.try_match_inheritdoc(base, &ch.source, documents, bases, &mut Vec::new()) {
+                Some(comments) => {
+                    context.insert(key, comments);
+                }
+                None => {
+                    warn_unresolved(base, &format!("{}.{}", item.source.ident(), ch.source.ident()))
+                }
             }
         }
 
@@ -67,26 +86,57 @@ impl Inheritdoc {
         &self,
         base: &str,
         source: &ParseSource,
-        documents: &Vec<Document>,
-    ) -> Option<(String, Comments)> {
-        for candidate in documents {
-            if let DocumentContent::Single(ref item) = candidate.content {
-                if let ParseSource::Contract(ref contract) = item.source {
-                    if base == contract.name.safe_unwrap().name {
-                        // Not matched for the contract because it's a noop
-                        // https://docs.soliditylang.org/en/v0.8.17/natspec-format.html#tags
+        documents: &[Document],
+        bases: &HashMap<String, Vec<String>>,
+        visited: &mut Vec<String>,
+    ) -> Option<Comments> {
+        let linearized = linearize(base, bases).unwrap_or_else(|| vec![base.to_owned()]);
+        for contract in linearized {
+            // Not matched for the contract because it's a noop
+            // https://docs.soliditylang.org/en/v0.8.17/natspec-format.html#tags
+            let Some(item) = find_contract(&contract, documents) else { continue };
 
-                        for children in item.children.iter() {
-                            // TODO: improve matching logic
-                            if source.ident() == children.source.ident() {
-                                let key = format!("{}.{}", base, source.ident());
-                                return Some((key, children.comments.clone()))
-                            }
+            // TODO: improve matching logic
+            let Some(children) =
+                item.children.iter().find(|ch| source.ident() == ch.source.ident())
+            else {
+                continue
+            };
+
+            let mut comments = Comments::default();
+            for comment in children.comments.iter() {
+                if comment.tag != CommentTag::Inheritdoc {
+                    comments.push(comment.clone());
+                }
+            }
+
+            // The matched item may inherit its docs as well
+            if let Some(inherited_base) = children.comments.find_inheritdoc_base() {
+                let key = format!("{}.{}", inherited_base, source.ident());
+                if !visited.contains(&key) {
+                    visited.push(key);
+                    let inherited = self.try_match_inheritdoc(
+                        inherited_base,
+                        source,
+                        documents,
+                        bases,
+                        visited,
+                    );
+                    for comment in inherited.iter().flat_map(|inherited| inherited.iter()) {
+                        if !comments.contains_tag(comment) {
+                            comments.push(comment.clone());
                         }
                     }
                 }
             }
+
+            return Some(comments)
         }
         None
     }
 }
+
+/// Warns that the `@inheritdoc` of the item can't be resolved.
+fn warn_unresolved(base: &str, item: &str) {
+    let _ = shell::eprintln(format!("Warning: unable to resolve `@inheritdoc {base}` for {item}"));
+}
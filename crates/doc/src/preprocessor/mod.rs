@@ -4,7 +4,9 @@ use crate::{Comments, Document};
 use std::{collections::HashMap, fmt::Debug, path::PathBuf};
 
 mod contract_inheritance;
-pub use contract_inheritance::{ContractInheritance, CONTRACT_INHERITANCE_ID};
+pub use contract_inheritance::{
+    ContractInheritance, CONTRACT_INHERITANCE_ID, INHERITANCE_GRAPH_ID,
+};
 
 mod inheritdoc;
 pub use inheritdoc::{Inheritdoc, INHERITDOC_ID};
@@ -28,8 +30,11 @@ pub struct PreprocessorId(&'static str);
 #[derive(Clone, Debug)]
 pub enum PreprocessorOutput {
     /// The contract inheritance output.
-    /// The map of contract base idents to the path of the base contract.
+    /// The map of idents of the inherited contracts to the paths of their documents.
     ContractInheritance(HashMap<String, PathBuf>),
+    /// The inheritance graph output.
+    /// The list of `(contract, base)` edges of the contract inheritance hierarchy.
+    InheritanceGraph(Vec<(String, String)>),
     /// The inheritdoc output.
     /// The map of inherited item keys to their comments.
     Inheritdoc(HashMap<String, Comments>),
@@ -1,14 +1,17 @@
 use crate::{
     document::{read_context, DocumentContent},
     parser::ParseSource,
-    writer::BufWriter,
+    writer::{diagram::inheritance_svg, BufWriter},
     CommentTag, Comments, CommentsRef, Document, Markdown, PreprocessorOutput,
-    CONTRACT_INHERITANCE_ID, DEPLOYMENTS_ID, GIT_SOURCE_ID, INHERITDOC_ID,
+    CONTRACT_INHERITANCE_ID, DEPLOYMENTS_ID, GIT_SOURCE_ID, INHERITANCE_GRAPH_ID, INHERITDOC_ID,
 };
 use forge_fmt::solang_ext::SafeUnwrap;
 use itertools::Itertools;
 use solang_parser::pt::{Base, FunctionDefinition};
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 /// The result of [Asdoc::as_doc] method.
 pub type AsDocResult = Result<String, std::fmt::Error>;
@@ -158,6 +161,18 @@ impl AsDoc for Document {
 
                             writer.writeln_raw(bases.join(", "))?;
                             writer.writeln()?;
+
+                            if let Some(edges) =
+                                read_context!(self, INHERITANCE_GRAPH_ID, InheritanceGraph)
+                            {
+                                let diagram = self.inheritance_diagram(
+                                    &item.source.ident(),
+                                    &edges,
+                                    linked.as_ref(),
+                                )?;
+                                writer.writeln_raw(diagram)?;
+                                writer.writeln()?;
+                            }
                         }
 
                         writer.writeln_doc(&item.comments)?;
@@ -277,6 +292,29 @@ impl Document {
         self.out_target_dir.join("src")
     }
 
+    /// Returns the SVG diagram of the contract inheritance hierarchy. Every inherited contract
+    /// with a document links to its page, relative to the page of this document.
+    fn inheritance_diagram(
+        &self,
+        contract: &str,
+        edges: &[(String, String)],
+        linked: Option<&HashMap<String, PathBuf>>,
+    ) -> Result<String, std::fmt::Error> {
+        let src_target_dir = self.target_src_dir();
+        // the number of directories between this page and the root of the book
+        let depth = self.relative_output_path().parent().map_or(0, |dir| dir.components().count());
+        inheritance_svg(contract, edges, |name| {
+            if name == contract {
+                return None
+            }
+            let path = linked?.get(name)?;
+            // the diagram is embedded as html, link to the html page
+            let path =
+                path.strip_prefix(&src_target_dir).ok().unwrap_or(path).with_extension("html");
+            Some(format!("{}{}", "../".repeat(depth), path.display()))
+        })
+    }
+
     /// Writes a function to the buffer.
     fn write_function(
         &self,
@@ -312,3 +350,46 @@ impl Document {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ContractInheritance, DocBuilder, Inheritdoc, Parser, Preprocessor};
+    use forge_fmt::Visitable;
+    use std::fs;
+
+    fn parse_documents(root: &Path, file: &str) -> Vec<Document> {
+        let path = root.join(file);
+        let source = fs::read_to_string(&path).unwrap();
+        let (mut source_unit, comments) = solang_parser::parse(&source, 0).unwrap();
+        let mut parser = Parser::new(comments, source);
+        source_unit.visit(&mut parser).unwrap();
+
+        let out = PathBuf::from("docs");
+        parser
+            .items()
+            .into_iter()
+            .map(|item| {
+                let target_path = out.join(DocBuilder::SRC).join(file).join(item.filename());
+                let ident = item.source.ident();
+                Document::new(path.clone(), target_path, false, out.clone())
+                    .with_content(DocumentContent::Single(item), ident)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn can_write_inheritance() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/Diamond");
+        let documents = parse_documents(&root, "src/Diamond.sol");
+        let documents = ContractInheritance::default().preprocess(documents).unwrap();
+        let documents = Inheritdoc::default().preprocess(documents).unwrap();
+
+        for filename in ["contract.Diamond.md", "contract.Top.md"] {
+            let document =
+                documents.iter().find(|doc| doc.target_path.ends_with(filename)).unwrap();
+            let expected = fs::read_to_string(root.join(filename)).unwrap();
+            assert_eq!(document.as_doc().unwrap(), expected, "{filename}");
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Rendering of the contract inheritance diagrams.
+//!
+//! The diagrams are rendered to inline SVG when the docs are generated, so the book doesn't need
+//! any script to display them.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+};
+
+/// The approximate width of a character of the monospace font of the node labels.
+const CHAR_WIDTH: usize = 8;
+/// The horizontal padding of a node label.
+const NODE_PADDING: usize = 12;
+/// The height of a node.
+const NODE_HEIGHT: usize = 30;
+/// The offset of the baseline of a node label from the top of the node.
+const LABEL_BASELINE: usize = 20;
+/// The horizontal space between the nodes of a row.
+const NODE_GAP: usize = 20;
+/// The vertical space between the rows.
+const ROW_GAP: usize = 40;
+/// The space around the diagram.
+const MARGIN: usize = 10;
+
+/// A node of the diagram, positioned by its top left corner.
+struct Node<'a> {
+    name: &'a str,
+    x: usize,
+    y: usize,
+    width: usize,
+}
+
+impl Node<'_> {
+    fn center(&self) -> usize {
+        self.x + self.width / 2
+    }
+}
+
+/// Renders the inheritance hierarchy of `contract` as SVG, wrapped in a `div` so that it's passed
+/// through as HTML block by the markdown renderer.
+///
+/// The `edges` go from a contract to its direct bases. Every contract is placed one row above
+/// the lowest contract inheriting from it, the given contract is at the bottom. The contracts for
+/// which `link` returns a URL link to it.
+pub fn inheritance_svg(
+    contract: &str,
+    edges: &[(String, String)],
+    link: impl Fn(&str) -> Option<String>,
+) -> Result<String, fmt::Error> {
+    // the contracts in the order of their first appearance
+    let mut names = vec![contract];
+    for (_, base) in edges {
+        if !names.contains(&base.as_str()) {
+            names.push(base);
+        }
+    }
+
+    // the depth of a contract is the length of the longest path to it
+    let mut depths = HashMap::from([(contract, 0)]);
+    for _ in 0..names.len() {
+        let mut changed = false;
+        for (child, base) in edges {
+            let Some(&depth) = depths.get(child.as_str()) else { continue };
+            let base_depth = depths.entry(base.as_str()).or_insert(0);
+            if *base_depth < depth + 1 {
+                *base_depth = depth + 1;
+                changed = true;
+            }
+        }
+        if !changed {
+            break
+        }
+    }
+    let max_depth = depths.values().copied().max().unwrap_or_default();
+
+    let node_width = |name: &str| name.len() * CHAR_WIDTH + 2 * NODE_PADDING;
+    let rows = (0..=max_depth)
+        .map(|depth| names.iter().copied().filter(|name| depths[name] == depth).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let row_width = |row: &[&str]| {
+        row.iter().map(|name| node_width(name)).sum::<usize>() +
+            NODE_GAP * row.len().saturating_sub(1)
+    };
+    let max_row_width = rows.iter().map(|row| row_width(row)).max().unwrap_or_default();
+
+    let mut nodes = HashMap::new();
+    for (depth, row) in rows.iter().enumerate() {
+        let y = MARGIN + (max_depth - depth) * (NODE_HEIGHT + ROW_GAP);
+        let mut x = MARGIN + (max_row_width - row_width(row)) / 2;
+        for &name in row {
+            let width = node_width(name);
+            nodes.insert(name, Node { name, x, y, width });
+            x += width + NODE_GAP;
+        }
+    }
+
+    let width = max_row_width + 2 * MARGIN;
+    let height = 2 * MARGIN + (max_depth + 1) * NODE_HEIGHT + max_depth * ROW_GAP;
+    let mut svg = String::new();
+    writeln!(svg, "<div class=\"inheritance-diagram\">")?;
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">"
+    )?;
+    writeln!(
+        svg,
+        "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" \
+         markerWidth=\"8\" markerHeight=\"8\" orient=\"auto\"><path d=\"M 0 0 L 10 5 L 0 10 z\"/>\
+         </marker></defs>"
+    )?;
+    for (child, base) in edges {
+        let (child, base) = (&nodes[child.as_str()], &nodes[base.as_str()]);
+        writeln!(
+            svg,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" marker-end=\"url(#arrow)\"/>",
+            child.center(),
+            child.y,
+            base.center(),
+            base.y + NODE_HEIGHT
+        )?;
+    }
+    for &name in &names {
+        let node = &nodes[name];
+        let class = if name == contract { " class=\"root\"" } else { "" };
+        let group = format!(
+            "<g{class}><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{NODE_HEIGHT}\" rx=\"4\"/>\
+             <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text></g>",
+            node.x,
+            node.y,
+            node.width,
+            node.center(),
+            node.y + LABEL_BASELINE,
+            node.name
+        );
+        match link(name) {
+            Some(url) => writeln!(svg, "<a href=\"{url}\">{group}</a>")?,
+            None => writeln!(svg, "{group}")?,
+        }
+    }
+    writeln!(svg, "</svg>")?;
+    write!(svg, "</div>")?;
+    Ok(svg)
+}
@@ -2,6 +2,7 @@
 
 mod as_doc;
 mod buf_writer;
+mod diagram;
 mod markdown;
 
 pub use as_doc::{AsDoc, AsDocResult};
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+interface IBase {
+    /// @notice Returns the name of the contract.
+    function name() external view returns (string memory);
+
+    /// @notice Returns the version of the contract.
+    function version() external view returns (uint256);
+}
+
+contract Left is IBase {
+    /// @notice Returns the name of the left contract.
+    function name() public view virtual returns (string memory) {
+        return "left";
+    }
+
+    /// @notice Returns the version of the left contract.
+    function version() public view virtual returns (uint256) {
+        return 1;
+    }
+}
+
+abstract contract Right is IBase {
+    /// @inheritdoc IBase
+    /// @dev Overridden by the right contract.
+    function name() public view virtual returns (string memory) {
+        return "right";
+    }
+}
+
+contract Diamond is Left, Right {
+    /// @inheritdoc Right
+    function name() public view override(Left, Right) returns (string memory) {
+        return "diamond";
+    }
+
+    /// @inheritdoc Missing
+    function missing() public {}
+}
+
+contract Top is Diamond {
+    /// @inheritdoc Diamond
+    function version() public view override returns (uint256) {
+        return 2;
+    }
+}
@@ -39,6 +39,8 @@ pub enum DatabaseError {
     GetTransaction(B256, Arc<eyre::Error>),
     #[error("transaction {0} not found")]
     TransactionNotFound(B256),
+    #[error("missing cache entry: {0}")]
+    MissingCacheEntry(String),
     #[error(
         "CREATE2 Deployer (0x4e59b44847b379578588920ca78fbf26c0b4956c) not present on this chain.\n\
          For a production environment, you can deploy it using the pre-signed transaction from \
@@ -75,6 +77,7 @@ impl DatabaseError {
             Self::Message(_) |
             Self::BlockNotFound(_) |
             Self::TransactionNotFound(_) |
+            Self::MissingCacheEntry(_) |
             Self::MissingCreate2Deployer => None,
         }
     }
@@ -11,7 +11,7 @@ use alloy_primitives::{Address, B256, U256};
 use ethers_core::utils::GenesisAccount;
 use revm::{
     db::DatabaseRef,
-    primitives::{AccountInfo, Bytecode, Env, ResultAndState},
+    primitives::{AccountInfo, Bytecode, Env, ExecutionResult, ResultAndState},
     Database, Inspector, JournaledState,
 };
 use std::{borrow::Cow, collections::HashMap};
@@ -183,6 +183,20 @@ impl<'a> DatabaseExt for FuzzBackendWrapper<'a> {
         self.backend_mut(env).transact(id, transaction, env, journaled_state, inspector)
     }
 
+    fn transact_env<I: Inspector<Backend>>(
+        &mut self,
+        env: &mut Env,
+        journaled_state: &mut JournaledState,
+        inspector: &mut I,
+    ) -> eyre::Result<ExecutionResult> {
+        trace!("fuzz: execute isolated transaction");
+        self.backend_mut(env).transact_env(env, journaled_state, inspector)
+    }
+
+    fn set_blockhash(&mut self, block_number: U256, block_hash: B256) {
+        self.backend.to_mut().set_blockhash(block_number, block_hash)
+    }
+
     fn active_fork_id(&self) -> Option<LocalForkId> {
         self.backend.active_fork_id()
     }
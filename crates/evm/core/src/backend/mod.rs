@@ -11,12 +11,12 @@ use alloy_rpc_types::{Block, BlockNumberOrTag, BlockTransactions, Transaction};
 use ethers_core::utils::GenesisAccount;
 use foundry_common::{is_known_system_sender, types::ToAlloy, SYSTEM_TRANSACTION_TYPE};
 use revm::{
-    db::{CacheDB, DatabaseRef},
+    db::{CacheDB, DatabaseRef, DbAccount},
     inspectors::NoOpInspector,
     precompile::{Precompiles, SpecId},
     primitives::{
-        Account, AccountInfo, Bytecode, CreateScheme, Env, HashMap as Map, Log, ResultAndState,
-        StorageSlot, TransactTo, KECCAK_EMPTY,
+        Account, AccountInfo, Bytecode, CreateScheme, Env, ExecutionResult, HashMap as Map, Log,
+        ResultAndState, StorageSlot, TransactTo, KECCAK_EMPTY,
     },
     Database, DatabaseCommit, Inspector, JournaledState, EVM,
 };
@@ -195,6 +195,24 @@ pub trait DatabaseExt: Database<Error = DatabaseError> {
         inspector: &mut I,
     ) -> eyre::Result<()>;
 
+    /// Executes the given environment as a standalone transaction on top of the current
+    /// `journaled_state`, and merges the resulting state changes back into the DB and the
+    /// journaled state.
+    ///
+    /// The transaction starts with an empty access list and is charged like any other transaction,
+    /// with intrinsic gas, refunds capped per transaction, and a nonce increment of the sender.
+    /// Changes made to the `Env` during execution, e.g. by cheatcodes, are written back to `env`.
+    fn transact_env<I: Inspector<Backend>>(
+        &mut self,
+        env: &mut Env,
+        journaled_state: &mut JournaledState,
+        inspector: &mut I,
+    ) -> eyre::Result<ExecutionResult>;
+
+    /// Sets the hash returned by the `BLOCKHASH` opcode for the given block number in the active
+    /// database, shadowing the hash of the forked chain if fork mode is on.
+    fn set_blockhash(&mut self, block_number: U256, block_hash: B256);
+
     /// Returns the `ForkId` that's currently used in the database, if fork mode is on
     fn active_fork_id(&self) -> Option<LocalForkId>;
 
@@ -340,10 +358,17 @@ pub trait DatabaseExt: Database<Error = DatabaseError> {
 /// Each `Fork`, identified by a unique id, uses completely separate storage, write operations are
 /// performed only in the fork's own database, `ForkDB`.
 ///
-/// A `ForkDB` consists of 2 halves:
-///   - everything fetched from the remote is readonly
-///   - all local changes (instructed by the contract) are written to the backend's `db` and don't
-///     alter the state of the remote client.
+/// A `ForkDB` consists of 2 layers:
+///   - everything fetched from the remote is readonly and kept in the `SharedBackend`, this base
+///     layer is shared by all the forks of the same `(endpoint, block number)` pair, across all
+///     `Backend`s
+///   - all local changes (instructed by the contract) are written to the fork's own copy-on-write
+///     overlay, the `CacheDB`, and don't alter the state of the remote client.
+///
+/// Reads are served by the overlay if it holds the account, and by the base layer otherwise,
+/// without copying the fetched data into the overlay. This way creating another fork of the same
+/// block, or cloning the `Backend`, only copies the local changes. Rolling a fork
+/// (`Backend::roll_fork()`) switches it to the base layer of the new block.
 ///
 /// # Fork swapping
 ///
@@ -829,7 +854,9 @@ impl Backend {
 
                 // otherwise we need to replace the account's info with the one from the fork's
                 // database
-                let fork_account = Database::basic(&mut fork.db, loaded_account)?
+                let fork_account = fork
+                    .db
+                    .basic_ref(loaded_account)?
                     .ok_or(DatabaseError::MissingAccount(loaded_account))?;
                 init_account.info = fork_account;
             }
@@ -1065,7 +1092,9 @@ impl DatabaseExt for Backend {
             if target_fork.journaled_state.depth == 0 {
                 // Initialize caller with its fork info
                 if let Some(mut acc) = caller_account {
-                    let fork_account = Database::basic(&mut target_fork.db, caller)?
+                    let fork_account = target_fork
+                        .db
+                        .basic_ref(caller)?
                         .ok_or(DatabaseError::MissingAccount(caller))?;
 
                     acc.info = fork_account;
@@ -1177,7 +1206,9 @@ impl DatabaseExt for Backend {
                             &mut active.journaled_state,
                         );
                     } else {
-                        let _ = active.journaled_state.load_account(*addr, &mut active.db);
+                        let _ = active
+                            .journaled_state
+                            .load_account(*addr, &mut ForkDbReader(&active.db));
                     }
                 }
 
@@ -1241,6 +1272,43 @@ impl DatabaseExt for Backend {
         commit_transaction(tx, env, journaled_state, fork, &fork_id, inspector)
     }
 
+    fn transact_env<I: Inspector<Backend>>(
+        &mut self,
+        env: &mut Env,
+        journaled_state: &mut JournaledState,
+        inspector: &mut I,
+    ) -> eyre::Result<ExecutionResult> {
+        trace!(caller=?env.tx.caller, "execute isolated transaction");
+
+        // the changes of the ongoing transaction must be visible to the new one
+        self.commit(journaled_state.state.clone());
+
+        let res = {
+            let mut evm = EVM::new();
+            evm.env = env.clone();
+            evm.database(self.clone());
+            let res = evm.inspect(inspector);
+            *env = evm.env;
+            res
+        };
+        let ResultAndState { result, state } = match res {
+            Ok(res) => res,
+            Err(e) => eyre::bail!("backend: failed transacting isolated transaction: {e}"),
+        };
+
+        self.commit(state.clone());
+        merge_state_changeset(state, journaled_state);
+        Ok(result)
+    }
+
+    fn set_blockhash(&mut self, block_number: U256, block_hash: B256) {
+        if let Some(db) = self.active_fork_db_mut() {
+            db.block_hashes.insert(block_number, block_hash);
+        } else {
+            self.mem_db.block_hashes.insert(block_number, block_hash);
+        }
+    }
+
     fn active_fork_id(&self) -> Option<LocalForkId> {
         self.active_fork_ids.map(|(id, _)| id)
     }
@@ -1438,41 +1506,67 @@ impl DatabaseCommit for Backend {
     }
 }
 
+// Note: the reads of the active fork don't go through `Database`, so that what is fetched from the
+// remote stays in the shared base layer instead of being copied into the fork's overlay
 impl Database for Backend {
     type Error = DatabaseError;
     fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
-        if let Some(db) = self.active_fork_db_mut() {
-            db.basic(address)
+        if let Some(db) = self.active_fork_db() {
+            db.basic_ref(address)
         } else {
             Ok(self.mem_db.basic(address)?)
         }
     }
 
     fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
-        if let Some(db) = self.active_fork_db_mut() {
-            db.code_by_hash(code_hash)
+        if let Some(db) = self.active_fork_db() {
+            db.code_by_hash_ref(code_hash)
         } else {
             Ok(self.mem_db.code_by_hash(code_hash)?)
         }
     }
 
     fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
-        if let Some(db) = self.active_fork_db_mut() {
-            Database::storage(db, address, index)
+        if let Some(db) = self.active_fork_db() {
+            DatabaseRef::storage_ref(db, address, index)
         } else {
             Ok(Database::storage(&mut self.mem_db, address, index)?)
         }
     }
 
     fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
-        if let Some(db) = self.active_fork_db_mut() {
-            db.block_hash(number)
+        if let Some(db) = self.active_fork_db() {
+            db.block_hash_ref(number)
         } else {
             Ok(self.mem_db.block_hash(number)?)
         }
     }
 }
 
+/// A [Database] over a [ForkDB] which reads it like [DatabaseRef], so that the reads which are
+/// served by the remote are not copied into the fork's overlay.
+struct ForkDbReader<'a>(&'a ForkDB);
+
+impl Database for ForkDbReader<'_> {
+    type Error = DatabaseError;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.0.basic_ref(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.0.code_by_hash_ref(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.0.storage_ref(address, index)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.0.block_hash_ref(number)
+    }
+}
+
 /// Variants of a [revm::Database]
 #[derive(Clone, Debug)]
 pub enum BackendDatabaseSnapshot {
@@ -1779,7 +1873,7 @@ pub(crate) fn update_current_env_with_fork_env(current: &mut Env, fork: Env) {
 
 /// Clones the data of the given `accounts` from the `active` database into the `fork_db`
 /// This includes the data held in storage (`CacheDB`) and kept in the `JournaledState`.
-pub(crate) fn merge_account_data<ExtDB: DatabaseRef>(
+pub(crate) fn merge_account_data<ExtDB: DatabaseRef + FetchedAccounts>(
     accounts: impl IntoIterator<Item = Address>,
     active: &CacheDB<ExtDB>,
     active_journaled_state: &mut JournaledState,
@@ -1818,7 +1912,10 @@ fn merge_journaled_state_data(
 }
 
 /// Clones the account data from the `active` db into the `ForkDB`
-fn merge_db_account_data<ExtDB: DatabaseRef>(
+///
+/// Accounts which were only read are not part of the `active` db's accounts but of the data it
+/// fetched, see [FetchedAccounts], which is used instead.
+fn merge_db_account_data<ExtDB: DatabaseRef + FetchedAccounts>(
     addr: Address,
     active: &CacheDB<ExtDB>,
     fork_db: &mut ForkDB,
@@ -1827,6 +1924,9 @@ fn merge_db_account_data<ExtDB: DatabaseRef>(
 
     let mut acc = if let Some(acc) = active.accounts.get(&addr).cloned() {
         acc
+    } else if let Some(acc) = active.db.fetched_account(addr) {
+        trace!(?addr, "merging fetched account data");
+        acc
     } else {
         // Account does not exist
         return;
@@ -1834,6 +1934,8 @@ fn merge_db_account_data<ExtDB: DatabaseRef>(
 
     if let Some(code) = active.contracts.get(&acc.info.code_hash).cloned() {
         fork_db.contracts.insert(acc.info.code_hash, code);
+    } else {
+        fork_db.insert_contract(&mut acc.info);
     }
 
     if let Some(fork_account) = fork_db.accounts.get_mut(&addr) {
@@ -1846,6 +1948,31 @@ fn merge_db_account_data<ExtDB: DatabaseRef>(
     fork_db.accounts.insert(addr, acc);
 }
 
+/// The data a database, that backs a [CacheDB], has already fetched.
+///
+/// The reads of a fork are kept in its `SharedBackend` and don't reach the fork's own `CacheDB`,
+/// so accounts which were only read have to be looked up here when they're merged into another
+/// fork.
+pub(crate) trait FetchedAccounts {
+    /// Returns the fetched account, with the storage fetched so far.
+    fn fetched_account(&self, address: Address) -> Option<DbAccount>;
+}
+
+impl FetchedAccounts for SharedBackend {
+    fn fetched_account(&self, address: Address) -> Option<DbAccount> {
+        let data = self.data();
+        let info = data.accounts.read().get(&address).cloned()?;
+        let storage = data.storage.read().get(&address).cloned().unwrap_or_default();
+        Some(DbAccount { storage: storage.into_iter().collect(), ..info.into() })
+    }
+}
+
+impl FetchedAccounts for EmptyDBWrapper {
+    fn fetched_account(&self, _address: Address) -> Option<DbAccount> {
+        None
+    }
+}
+
 /// Returns true of the address is a contract
 fn is_contract_in_state(journaled_state: &JournaledState, acc: Address) -> bool {
     journaled_state
@@ -1900,6 +2027,28 @@ fn commit_transaction<I: Inspector<Backend>>(
     Ok(())
 }
 
+/// Merges the changeset of a transaction executed on top of the given journaled state into it.
+///
+/// Storage slots keep their original values, so that the outer transaction is still charged as if
+/// the changes were made by itself.
+fn merge_state_changeset(state: Map<Address, Account>, journaled_state: &mut JournaledState) {
+    for (addr, acc) in state {
+        let Some(existing) = journaled_state.state.get_mut(&addr) else {
+            journaled_state.state.insert(addr, acc);
+            continue
+        };
+        existing.info = acc.info;
+        existing.status |= acc.status;
+        for (key, slot) in acc.storage {
+            existing
+                .storage
+                .entry(key)
+                .and_modify(|existing| existing.present_value = slot.present_value)
+                .or_insert(slot);
+        }
+    }
+}
+
 /// Applies the changeset of a transaction to the active journaled state and also commits it in the
 /// forked db
 fn apply_state_changeset(
@@ -1915,10 +2064,161 @@ fn apply_state_changeset(
         // reload all changed accounts by removing them from the journaled state and reloading them
         // from the now updated database
         if journaled_state.state.remove(&addr).is_some() {
-            let _ = journaled_state.load_account(addr, &mut fork.db);
+            let _ = journaled_state.load_account(addr, &mut ForkDbReader(&fork.db));
         }
         if fork.journaled_state.state.remove(&addr).is_some() {
-            let _ = fork.journaled_state.load_account(addr, &mut fork.db);
+            let _ = fork.journaled_state.load_account(addr, &mut ForkDbReader(&fork.db));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fork::{BlockchainDb, BlockchainDbMeta};
+    use foundry_common::provider::alloy::get_http_provider;
+    use std::{collections::BTreeSet, sync::Arc};
+
+    const ENDPOINT: &str = "http://localhost:8545";
+    const ACCOUNTS: u8 = 100;
+
+    /// Returns a `SharedBackend` whose base layer already holds `ACCOUNTS` accounts with a balance
+    /// and a storage slot of `i + offset` each, so that nothing is fetched from the endpoint.
+    fn prefilled_backend(offset: u64) -> SharedBackend {
+        let meta = BlockchainDbMeta {
+            cfg_env: Default::default(),
+            block_env: Default::default(),
+            hosts: BTreeSet::from([ENDPOINT.to_string()]),
+        };
+        let db = BlockchainDb::new(meta, None);
+        for i in 0..ACCOUNTS {
+            let value = U256::from(i as u64 + offset);
+            let info = AccountInfo { balance: value, ..Default::default() };
+            db.accounts().write().insert(Address::repeat_byte(i + 1), info);
+            let storage = [(U256::ZERO, value)].into_iter().collect();
+            db.storage().write().insert(Address::repeat_byte(i + 1), storage);
+        }
+        let (backend, _handler) =
+            SharedBackend::new(Arc::new(get_http_provider(ENDPOINT)), db, None);
+        backend
+    }
+
+    /// Inserts `count` forks of the same block, as issued by the `MultiFork`.
+    fn insert_forks(
+        backend: &mut Backend,
+        shared: &SharedBackend,
+        count: usize,
+    ) -> Vec<(LocalForkId, ForkLookupIndex)> {
+        (0..count)
+            .map(|i| {
+                let fork_id = ForkId::from(format!("{ENDPOINT}@1-{i}"));
+                let journaled_state = backend.fork_init_journaled_state.clone();
+                backend.inner.insert_new_fork(fork_id, ForkDB::new(shared.clone()), journaled_state)
+            })
+            .collect()
+    }
+
+    /// Reads all the prefilled accounts and their storage through the active fork.
+    fn read_all(backend: &mut Backend, offset: u64) {
+        for i in 0..ACCOUNTS {
+            let address = Address::repeat_byte(i + 1);
+            let value = U256::from(i as u64 + offset);
+            assert_eq!(Database::basic(backend, address).unwrap().unwrap().balance, value);
+            assert_eq!(Database::storage(backend, address, U256::ZERO).unwrap(), value);
+        }
+    }
+
+    /// Returns the number of accounts held by the overlays of all the forks.
+    fn overlay_accounts(backend: &mut Backend) -> usize {
+        backend.inner.forks_iter_mut().map(|fork| fork.db.accounts.len()).sum()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn forks_share_base_layer() {
+        let shared = prefilled_backend(0);
+        let mut backend = Backend::spawn(None).await;
+        for ids in insert_forks(&mut backend, &shared, 50) {
+            backend.active_fork_ids = Some(ids);
+            read_all(&mut backend, 0);
         }
+
+        // the reads of the 50 forks are only held once, by the shared base layer
+        assert_eq!(overlay_accounts(&mut backend), 0);
+        assert_eq!(overlay_accounts(&mut backend.clone()), 0);
+        assert_eq!(shared.data().accounts.read().len(), ACCOUNTS as usize);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fork_overlays_are_copy_on_write() {
+        let shared = prefilled_backend(0);
+        let mut backend = Backend::spawn(None).await;
+        let forks = insert_forks(&mut backend, &shared, 2);
+        let address = Address::repeat_byte(1);
+
+        backend.active_fork_ids = Some(forks[0]);
+        let info = AccountInfo { balance: U256::from(1000), ..Default::default() };
+        backend.insert_account_info(address, info);
+        backend.insert_account_storage(address, U256::ZERO, U256::from(1000)).unwrap();
+        assert_eq!(overlay_accounts(&mut backend), 1);
+
+        // the other fork still reads the base layer
+        backend.active_fork_ids = Some(forks[1]);
+        read_all(&mut backend, 0);
+        assert_eq!(overlay_accounts(&mut backend), 1);
+
+        // switching back keeps the overlay of the first fork
+        backend.active_fork_ids = Some(forks[0]);
+        assert_eq!(
+            Database::basic(&mut backend, address).unwrap().unwrap().balance,
+            U256::from(1000)
+        );
+        assert_eq!(Database::storage(&mut backend, address, U256::ZERO).unwrap(), U256::from(1000));
+        assert_eq!(shared.data().accounts.read()[&address].balance, U256::ZERO);
+
+        // rolling the fork switches it to the base layer of the new block
+        let rolled = prefilled_backend(1);
+        let (id, _) = forks[0];
+        let idx =
+            backend.inner.roll_fork(id, ForkId::from(format!("{ENDPOINT}@2")), rolled).unwrap();
+        backend.active_fork_ids = Some((id, idx));
+        read_all(&mut backend, 1);
+        assert_eq!(overlay_accounts(&mut backend), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn persistent_read_only_accounts_are_merged_across_forks() {
+        let mut backend = Backend::spawn(None).await;
+        let shared = prefilled_backend(0);
+        let other = prefilled_backend(1);
+        let fork = insert_forks(&mut backend, &shared, 1)[0];
+        let (other_id, other_idx) = backend.inner.insert_new_fork(
+            ForkId::from(format!("{ENDPOINT}@2")),
+            ForkDB::new(other),
+            backend.fork_init_journaled_state.clone(),
+        );
+
+        // the account is only read on the active fork, it stays in the base layer
+        let address = Address::repeat_byte(1);
+        backend.active_fork_ids = Some(fork);
+        read_all(&mut backend, 0);
+        assert_eq!(overlay_accounts(&mut backend), 0);
+        backend.add_persistent_account(address);
+
+        let mut target = backend.inner.take_fork(other_idx);
+        let mut journaled_state = backend.fork_init_journaled_state.clone();
+        backend.update_fork_db_contracts([address], &mut journaled_state, &mut target);
+        backend.inner.set_fork(other_idx, target);
+
+        // the persistent account keeps the data of the fork it was read on
+        backend.active_fork_ids = Some((other_id, other_idx));
+        assert_eq!(Database::basic(&mut backend, address).unwrap().unwrap().balance, U256::ZERO);
+        assert_eq!(Database::storage(&mut backend, address, U256::ZERO).unwrap(), U256::ZERO);
+
+        // the other accounts are still read from the base layer of the other fork
+        let other_address = Address::repeat_byte(2);
+        assert_eq!(
+            Database::basic(&mut backend, other_address).unwrap().unwrap().balance,
+            U256::from(2)
+        );
     }
 }
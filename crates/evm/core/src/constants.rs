@@ -1,4 +1,4 @@
-use alloy_primitives::{address, hex, Address};
+use alloy_primitives::{address, hex, Address, B256};
 
 /// The cheatcode handler address.
 ///
@@ -29,6 +29,17 @@ pub const MAGIC_ASSUME: &[u8] = b"FOUNDRY::ASSUME";
 /// Magic return value returned by the `skip` cheatcode.
 pub const MAGIC_SKIP: &[u8] = b"FOUNDRY::SKIP";
 
+/// Magic topic of the logs that record transient storage writes in call traces.
+///
+/// `TSTORE`s aren't traced, so the tracer records each one as a log of the call with the topics
+/// `[TRANSIENT_STORE_TOPIC, slot, value]`.
+pub const TRANSIENT_STORE_TOPIC: B256 = B256::new(*b"FOUNDRY::TRANSIENT_STORAGE_WRITE");
+
+/// Magic topic of the logs that record transient storage reads in call traces.
+///
+/// Same as [`TRANSIENT_STORE_TOPIC`] for `TLOAD`s, with the loaded value as the last topic.
+pub const TRANSIENT_LOAD_TOPIC: B256 = B256::new(*b"FOUNDRY::TRANSIENT_STORAGE_READ_");
+
 /// The default CREATE2 deployer.
 pub const DEFAULT_CREATE2_DEPLOYER: Address = address!("4e59b44847b379578588920ca78fbf26c0b4956c");
 /// The initcode of the default CREATE2 deployer.
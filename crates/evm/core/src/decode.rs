@@ -1,11 +1,13 @@
 //! Various utilities to decode test results.
 
 use crate::abi::{Console, Vm};
-use alloy_dyn_abi::JsonAbiExt;
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
 use alloy_json_abi::JsonAbi;
-use alloy_primitives::Log;
-use alloy_sol_types::{SolCall, SolError, SolEventInterface, SolInterface, SolValue};
-use foundry_common::SELECTOR_LEN;
+use alloy_primitives::{Bytes, Log, U256};
+use alloy_sol_types::{
+    GenericContractError, PanicKind, SolCall, SolError, SolEventInterface, SolInterface, SolValue,
+};
+use foundry_common::{fmt::format_token, SELECTOR_LEN};
 use itertools::Itertools;
 use revm::interpreter::InstructionResult;
 
@@ -41,10 +43,28 @@ pub fn decode_revert(
     })
 }
 
+/// The maximum depth up to which revert data nested in other revert data is decoded
+pub const MAX_NESTED_REVERT_DEPTH: usize = 8;
+
+/// Tries to decode an error message from the given revert bytes, returning `None` if there are
+/// none.
+///
+/// Revert data nested in another error, like an `Error(string)` containing the hex of a custom
+/// error, or a custom error with a `bytes` parameter that wraps the revert data of a failed call,
+/// is decoded as well, up to [MAX_NESTED_REVERT_DEPTH] levels deep.
 pub fn maybe_decode_revert(
     err: &[u8],
     maybe_abi: Option<&JsonAbi>,
     status: Option<InstructionResult>,
+) -> Option<String> {
+    decode_revert_nested(err, maybe_abi, status, 0)
+}
+
+fn decode_revert_nested(
+    err: &[u8],
+    maybe_abi: Option<&JsonAbi>,
+    status: Option<InstructionResult>,
+    depth: usize,
 ) -> Option<String> {
     if err.len() < SELECTOR_LEN {
         if let Some(status) = status {
@@ -64,9 +84,25 @@ pub fn maybe_decode_revert(
         return Some("SKIPPED".to_string());
     }
 
+    // Decodes revert data nested in the given bytes, if they are an ABI-encoded error
+    let decode_nested = |data: &[u8]| {
+        if depth >= MAX_NESTED_REVERT_DEPTH || !is_abi_encoded_error(data, maybe_abi) {
+            return None
+        }
+        decode_revert_nested(data, maybe_abi, None, depth + 1)
+    };
+
     // Solidity's `Error(string)` or `Panic(uint256)`
     if let Ok(e) = alloy_sol_types::GenericContractError::abi_decode(err, false) {
-        return Some(e.to_string());
+        return Some(match e {
+            GenericContractError::Revert(revert) => revert
+                .reason
+                .strip_prefix("0x")
+                .and_then(|data| hex::decode(data).ok())
+                .and_then(|data| decode_nested(&data[..]))
+                .unwrap_or_else(|| format!("revert: {}", revert.reason)),
+            GenericContractError::Panic(panic) => decode_panic(panic.code),
+        });
     }
 
     let (selector, data) = err.split_at(SELECTOR_LEN);
@@ -81,12 +117,22 @@ pub fn maybe_decode_revert(
         // `expectRevert(bytes)`
         Vm::expectRevert_2Call::SELECTOR => {
             let e = Vm::expectRevert_2Call::abi_decode_raw(data, false).ok()?;
-            return maybe_decode_revert(&e.revertData[..], maybe_abi, status);
+            return decode_revert_nested(&e.revertData[..], maybe_abi, status, depth + 1);
         }
         // `expectRevert(bytes4)`
         Vm::expectRevert_1Call::SELECTOR => {
             let e = Vm::expectRevert_1Call::abi_decode_raw(data, false).ok()?;
-            return maybe_decode_revert(&e.revertData[..], maybe_abi, status);
+            return decode_revert_nested(&e.revertData[..], maybe_abi, status, depth + 1);
+        }
+        // `expectRevert(bytes4,uint256,bytes32)`
+        Vm::expectRevert_3Call::SELECTOR => {
+            let e = Vm::expectRevert_3Call::abi_decode_raw(data, false).ok()?;
+            return decode_revert_nested(&e.revertData[..], maybe_abi, status, depth + 1);
+        }
+        // `expectPartialRevert(bytes4)`
+        Vm::expectPartialRevertCall::SELECTOR => {
+            let e = Vm::expectPartialRevertCall::abi_decode_raw(data, false).ok()?;
+            return decode_revert_nested(&e.revertData[..], maybe_abi, status, depth + 1);
         }
         _ => {}
     }
@@ -96,11 +142,14 @@ pub fn maybe_decode_revert(
         if let Some(abi_error) = abi.errors().find(|e| selector == e.selector()) {
             // if we don't decode, don't return an error, try to decode as a string later
             if let Ok(decoded) = abi_error.abi_decode_input(data, false) {
-                return Some(format!(
-                    "{}({})",
-                    abi_error.name,
-                    decoded.iter().map(foundry_common::fmt::format_token).format(", ")
-                ));
+                // `bytes` parameters usually wrap the revert data of a failed call
+                let params = decoded.iter().map(|token| match token {
+                    DynSolValue::Bytes(bytes) => {
+                        decode_nested(&bytes[..]).unwrap_or_else(|| format_token(token))
+                    }
+                    token => format_token(token),
+                });
+                return Some(format!("{}({})", abi_error.name, params.format(", ")));
             }
         }
     }
@@ -115,6 +164,13 @@ pub fn maybe_decode_revert(
         return Some(s.to_string());
     }
 
+    // Custom error wrapping the revert data of a failed call
+    if let Some(nested) =
+        Bytes::abi_decode(data, true).ok().and_then(|data| decode_nested(&data[..]))
+    {
+        return Some(format!("custom error {}: {nested}", hex::encode(selector)));
+    }
+
     // Generic custom error
     Some(format!(
         "custom error {}:{}",
@@ -123,6 +179,30 @@ pub fn maybe_decode_revert(
     ))
 }
 
+/// Returns true if the data is an `Error(string)`, a `Panic(uint256)`, a custom error of the ABI,
+/// or a selector followed by ABI-encoded parameters, so that arbitrary bytes or hex strings aren't
+/// decoded as revert data.
+fn is_abi_encoded_error(data: &[u8], maybe_abi: Option<&JsonAbi>) -> bool {
+    if data.len() < SELECTOR_LEN {
+        return false
+    }
+    if GenericContractError::abi_decode(data, true).is_ok() {
+        return true
+    }
+    let (selector, params) = data.split_at(SELECTOR_LEN);
+    if let Some(error) = maybe_abi.and_then(|abi| abi.errors().find(|e| selector == e.selector())) {
+        return error.abi_decode_input(params, true).is_ok()
+    }
+    !params.is_empty() && params.len() % 32 == 0
+}
+
+/// Returns the description of the code of a Solidity `Panic(uint256)`.
+pub fn decode_panic(code: U256) -> String {
+    let reason =
+        PanicKind::from_number(code.saturating_to()).map_or("unknown code", |kind| kind.as_str());
+    format!("panic: {reason} ({code:#x})")
+}
+
 fn trimmed_hex(s: &[u8]) -> String {
     let s = hex::encode(s);
     let n = 32 * 2;
@@ -132,3 +212,95 @@ fn trimmed_hex(s: &[u8]) -> String {
         format!("{}…{} ({} bytes)", &s[..n / 2], &s[s.len() - n / 2..], s.len())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_json_abi::Error;
+    use alloy_sol_types::{sol, Panic, Revert};
+
+    sol! {
+        error InsufficientBalance(uint256 available, uint256 required);
+        error CallFailed(bytes reason);
+    }
+
+    fn errors() -> JsonAbi {
+        let mut abi = JsonAbi::new();
+        for error in [
+            "error InsufficientBalance(uint256 available, uint256 required)",
+            "error CallFailed(bytes reason)",
+        ] {
+            let error = Error::parse(error).unwrap();
+            abi.errors.entry(error.name.clone()).or_default().push(error);
+        }
+        abi
+    }
+
+    fn wrap(reason: Vec<u8>) -> Vec<u8> {
+        CallFailed { reason: reason.into() }.abi_encode()
+    }
+
+    #[test]
+    fn decodes_panics() {
+        let decode =
+            |code: u64| decode_revert(&Panic { code: U256::from(code) }.abi_encode(), None, None);
+        assert_eq!(decode(0x01), "panic: assertion failed (0x1)");
+        assert_eq!(decode(0x11), "panic: arithmetic underflow or overflow (0x11)");
+        assert_eq!(decode(0x32), "panic: array out-of-bounds access (0x32)");
+        assert_eq!(decode(0x99), "panic: unknown code (0x99)");
+    }
+
+    #[test]
+    fn decodes_nested_reverts() {
+        let errors = errors();
+        let insufficient =
+            InsufficientBalance { available: U256::from(2), required: U256::from(3) }.abi_encode();
+        let panic = Panic { code: U256::from(0x12) }.abi_encode();
+
+        let wrapped = wrap(wrap(panic));
+        assert_eq!(
+            decode_revert(&wrapped, Some(&errors), None),
+            "CallFailed(CallFailed(panic: division or modulo by zero (0x12)))"
+        );
+
+        let stringified = Revert { reason: hex::encode_prefixed(&insufficient) }.abi_encode();
+        assert_eq!(decode_revert(&stringified, Some(&errors), None), "InsufficientBalance(2, 3)");
+        assert_eq!(
+            decode_revert(&wrap(stringified), Some(&errors), None),
+            "CallFailed(InsufficientBalance(2, 3))"
+        );
+
+        // errors that aren't known are still unwrapped
+        let revert = Revert { reason: "not enough".to_string() }.abi_encode();
+        assert_eq!(
+            decode_revert(&wrap(revert), None, None),
+            format!("custom error {}: revert: not enough", hex::encode(CallFailed::SELECTOR))
+        );
+    }
+
+    #[test]
+    fn only_decodes_nested_errors() {
+        // hex strings which aren't revert data are kept as they are
+        for reason in ["0xdeadbeef", "0x1234567890", &hex::encode_prefixed([0xab; 32])] {
+            let revert = Revert { reason: reason.to_string() }.abi_encode();
+            assert_eq!(decode_revert(&revert, Some(&errors()), None), format!("revert: {reason}"));
+        }
+
+        // nor are the bytes of a custom error which don't match its parameters in the ABI
+        let selector = &InsufficientBalance::SELECTOR[..];
+        let err = wrap([selector, &[0; 32]].concat());
+        let decoded = decode_revert(&err, Some(&errors()), None);
+        assert!(decoded.starts_with("CallFailed(0x"), "{decoded}");
+    }
+
+    #[test]
+    fn limits_nested_revert_depth() {
+        let mut err = Revert { reason: "innermost".to_string() }.abi_encode();
+        for _ in 0..2 * MAX_NESTED_REVERT_DEPTH {
+            err = wrap(err);
+        }
+        let decoded = decode_revert(&err, Some(&errors()), None);
+        assert_eq!(decoded.matches("CallFailed(").count(), MAX_NESTED_REVERT_DEPTH + 1);
+        assert!(!decoded.contains("innermost"));
+    }
+}
@@ -1,7 +1,10 @@
 //! Smart caching and deduplication of requests when using a forking provider
 use crate::{
     backend::{DatabaseError, DatabaseResult},
-    fork::{cache::FlushJsonBlockCacheDB, BlockchainDb},
+    fork::{
+        cache::{FlushJsonBlockCacheDB, MemDb},
+        BlockchainDb,
+    },
 };
 use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
 use alloy_providers::provider::TempProvider;
@@ -94,6 +97,8 @@ pub struct BackendHandler<P> {
     /// The block to fetch data from.
     // This is an `Option` so that we can have less code churn in the functions below
     block_id: Option<BlockId>,
+    /// Whether the provider must not be used, missing values are errors instead.
+    offline: bool,
 }
 
 impl<P> BackendHandler<P>
@@ -116,9 +121,26 @@ where
             queued_requests: Default::default(),
             incoming: rx,
             block_id,
+            offline: false,
         }
     }
 
+    /// Sets whether the data is only served from the `db`, requests for values that are not
+    /// cached fail with [DatabaseError::MissingCacheEntry] instead of being sent to the provider.
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    /// Returns the error for a request that can't be served from the `db` in offline mode.
+    fn missing_cache_entry(&self, method: &str, params: impl std::fmt::Display) -> DatabaseError {
+        let block = match self.block_id {
+            Some(BlockId::Number(BlockNumberOrTag::Number(number))) => number.to_string(),
+            Some(block_id) => format!("{block_id:?}"),
+            None => self.db.meta().read().block_env.number.to_string(),
+        };
+        DatabaseError::MissingCacheEntry(format!("{method}({params}, {block})"))
+    }
+
     /// handle the request in queue in the future.
     ///
     /// We always check:
@@ -132,6 +154,8 @@ where
                 let acc = self.db.accounts().read().get(&addr).cloned();
                 if let Some(basic) = acc {
                     let _ = sender.send(Ok(basic));
+                } else if self.offline {
+                    let _ = sender.send(Err(self.missing_cache_entry("eth_getAccount", addr)));
                 } else {
                     self.request_account(addr, sender);
                 }
@@ -140,15 +164,34 @@ where
                 let hash = self.db.block_hashes().read().get(&U256::from(number)).cloned();
                 if let Some(hash) = hash {
                     let _ = sender.send(Ok(hash));
+                } else if self.offline {
+                    let err = DatabaseError::MissingCacheEntry(format!(
+                        "eth_getBlockByNumber({number}, false)"
+                    ));
+                    let _ = sender.send(Err(err));
                 } else {
                     self.request_hash(number, sender);
                 }
             }
             BackendRequest::FullBlock(number, sender) => {
-                self.request_full_block(number, sender);
+                if self.offline {
+                    // full blocks and transactions are never cached
+                    let err = DatabaseError::MissingCacheEntry(format!(
+                        "eth_getBlockByNumber({number:?}, true)"
+                    ));
+                    let _ = sender.send(Err(err));
+                } else {
+                    self.request_full_block(number, sender);
+                }
             }
             BackendRequest::Transaction(tx, sender) => {
-                self.request_transaction(tx, sender);
+                if self.offline {
+                    let err =
+                        DatabaseError::MissingCacheEntry(format!("eth_getTransactionByHash({tx})"));
+                    let _ = sender.send(Err(err));
+                } else {
+                    self.request_transaction(tx, sender);
+                }
             }
             BackendRequest::Storage(addr, idx, sender) => {
                 // account is already stored in the cache
@@ -156,6 +199,9 @@ where
                     self.db.storage().read().get(&addr).and_then(|acc| acc.get(&idx).copied());
                 if let Some(value) = value {
                     let _ = sender.send(Ok(value));
+                } else if self.offline {
+                    let params = format!("{addr}, {idx:#x}");
+                    let _ = sender.send(Err(self.missing_cache_entry("eth_getStorageAt", params)));
                 } else {
                     // account present but not storage -> fetch storage
                     self.request_account_storage(addr, idx, sender);
@@ -594,7 +640,17 @@ impl SharedBackend {
         })
     }
 
+    /// Returns the data fetched so far, the read-only base layer shared by all the `SharedBackend`s
+    /// of the same handler, i.e. of the same endpoint and block.
+    pub fn data(&self) -> &Arc<MemDb> {
+        self.cache.0.db()
+    }
+
     fn do_get_basic(&self, address: Address) -> DatabaseResult<Option<AccountInfo>> {
+        // the fetched data is read directly, without a roundtrip to the handler
+        if let Some(basic) = self.data().accounts.read().get(&address).cloned() {
+            return Ok(Some(basic))
+        }
         tokio::task::block_in_place(|| {
             let (sender, rx) = oneshot_channel();
             let req = BackendRequest::Basic(address, sender);
@@ -604,6 +660,11 @@ impl SharedBackend {
     }
 
     fn do_get_storage(&self, address: Address, index: U256) -> DatabaseResult<U256> {
+        let value =
+            self.data().storage.read().get(&address).and_then(|slots| slots.get(&index).copied());
+        if let Some(value) = value {
+            return Ok(value)
+        }
         tokio::task::block_in_place(|| {
             let (sender, rx) = oneshot_channel();
             let req = BackendRequest::Storage(address, index, sender);
@@ -613,6 +674,9 @@ impl SharedBackend {
     }
 
     fn do_get_block_hash(&self, number: u64) -> DatabaseResult<B256> {
+        if let Some(hash) = self.data().block_hashes.read().get(&U256::from(number)).copied() {
+            return Ok(hash)
+        }
         tokio::task::block_in_place(|| {
             let (sender, rx) = oneshot_channel();
             let req = BackendRequest::BlockHash(number, sender);
@@ -729,6 +793,40 @@ mod tests {
         assert_eq!(slots.len() as u64, max_slots);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn offline_backend_reports_missing_cache_entries() {
+        // the endpoint is never requested
+        let provider = get_http_provider("http://uncached.invalid:8545");
+        let meta = BlockchainDbMeta {
+            cfg_env: Default::default(),
+            block_env: Default::default(),
+            hosts: Default::default(),
+        };
+        let db = BlockchainDb::new(meta, None);
+        let address = Address::with_last_byte(1);
+        db.accounts().write().insert(address, AccountInfo::default());
+        db.storage().write().entry(address).or_default().insert(U256::from(1), U256::from(42));
+
+        let (backend, mut handler) =
+            SharedBackend::new(Arc::new(provider), db, Some(BlockId::from(15u64)));
+        handler.set_offline(true);
+        tokio::spawn(handler);
+
+        assert_eq!(backend.storage_ref(address, U256::from(1)).unwrap(), U256::from(42));
+        assert!(backend.basic_ref(address).unwrap().is_some());
+
+        let err = backend.storage_ref(address, U256::from(2)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("missing cache entry: eth_getStorageAt({address}, 0x2, 15)")
+        );
+        let uncached = Address::with_last_byte(2);
+        let err = backend.basic_ref(uncached).unwrap_err();
+        assert_eq!(err.to_string(), format!("missing cache entry: eth_getAccount({uncached}, 15)"));
+        let err = backend.block_hash_ref(U256::from(10)).unwrap_err();
+        assert_eq!(err.to_string(), "missing cache entry: eth_getBlockByNumber(10, false)");
+    }
+
     #[test]
     fn can_read_cache() {
         let cache_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-data/storage.json");
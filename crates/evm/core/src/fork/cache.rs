@@ -1,6 +1,7 @@
 //! Cache related abstraction
 use crate::backend::StateSnapshot;
 use alloy_primitives::{Address, B256, U256};
+use foundry_config::Config;
 use parking_lot::RwLock;
 use revm::{
     primitives::{Account, AccountInfo, AccountStatus, HashMap as Map, KECCAK_EMPTY},
@@ -10,8 +11,8 @@ use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer}
 use std::{
     collections::BTreeSet,
     fs,
-    io::{BufWriter, Write},
-    path::PathBuf,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
     sync::Arc,
 };
 use url::Url;
@@ -127,16 +128,85 @@ pub struct BlockchainDbMeta {
 impl BlockchainDbMeta {
     /// Creates a new instance
     pub fn new(env: revm::primitives::Env, url: String) -> Self {
-        let host = Url::parse(&url)
-            .ok()
-            .and_then(|url| url.host().map(|host| host.to_string()))
-            .unwrap_or(url);
-
         BlockchainDbMeta {
             cfg_env: env.cfg.clone(),
             block_env: env.block,
-            hosts: BTreeSet::from([host]),
+            hosts: BTreeSet::from([url_host(url)]),
+        }
+    }
+}
+
+/// Returns the host of the url that's recorded in the [BlockchainDbMeta], or the url itself if it
+/// has none.
+fn url_host(url: String) -> String {
+    Url::parse(&url).ok().and_then(|url| url.host().map(|host| host.to_string())).unwrap_or(url)
+}
+
+/// A fork stored in the RPC cache, `~/.foundry/cache/rpc/<chain>/<block>`.
+#[derive(Clone, Debug)]
+pub struct CachedFork {
+    /// The number of the block the fork is pinned to
+    pub number: u64,
+    /// The path of the cache file
+    pub path: PathBuf,
+    /// The metadata stored in the cache file
+    pub meta: BlockchainDbMeta,
+}
+
+impl CachedFork {
+    /// Returns the cached fork of the `url` endpoint at the `block`, or at the latest cached block
+    /// if it's `None`.
+    ///
+    /// Only forks that were created with an endpoint on the same host are considered, on the
+    /// `chain_id` if any.
+    pub fn find(url: &str, chain_id: Option<u64>, block: Option<u64>) -> eyre::Result<Self> {
+        let host = url_host(url.to_string());
+        let chain_dirs: Vec<PathBuf> = match chain_id {
+            Some(chain_id) => Config::foundry_chain_cache_dir(chain_id).into_iter().collect(),
+            None => Config::foundry_rpc_cache_dir()
+                .and_then(|dir| fs::read_dir(dir).ok())
+                .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+                .unwrap_or_default(),
+        };
+
+        let mut found: Option<Self> = None;
+        for entry in chain_dirs.iter().filter_map(|dir| fs::read_dir(dir).ok()).flatten().flatten()
+        {
+            let Some(number) = entry.file_name().to_str().and_then(|name| name.parse().ok()) else {
+                continue
+            };
+            if block.map_or(false, |block| block != number) ||
+                found.as_ref().map_or(false, |found| found.number >= number)
+            {
+                continue
+            }
+            let path = entry.path();
+            if !path.is_file() {
+                continue
+            }
+            match Self::read_meta(&path) {
+                Ok(meta) if meta.hosts.contains(&host) => found = Some(Self { number, path, meta }),
+                Ok(_) => {}
+                Err(err) => warn!(target: "cache", ?err, ?path, "Failed to read cache metadata"),
+            }
         }
+
+        found.ok_or_else(|| match block {
+            Some(block) => eyre::eyre!("missing cache entry: no cached fork of {host} at {block}"),
+            None => eyre::eyre!("missing cache entry: no cached fork of {host}"),
+        })
+    }
+
+    /// Reads only the metadata of the cache file.
+    fn read_meta(path: &Path) -> eyre::Result<BlockchainDbMeta> {
+        #[derive(Deserialize)]
+        struct Data {
+            meta: BlockchainDbMeta,
+        }
+
+        let file = fs::File::open(path)?;
+        let data: Data = serde_json::from_reader(BufReader::new(file))?;
+        Ok(data.meta)
     }
 }
 
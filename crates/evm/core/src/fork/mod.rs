@@ -8,7 +8,7 @@ mod init;
 pub use init::environment;
 
 mod cache;
-pub use cache::{BlockchainDb, BlockchainDbMeta, JsonBlockCacheDB, MemDb};
+pub use cache::{BlockchainDb, BlockchainDbMeta, CachedFork, JsonBlockCacheDB, MemDb};
 
 pub mod database;
 
@@ -489,9 +489,24 @@ async fn create_fork(mut fork: CreateFork) -> eyre::Result<(CreatedFork, Handler
             .maybe_max_retry(fork.evm_opts.fork_retries)
             .maybe_initial_backoff(fork.evm_opts.fork_retry_backoff)
             .compute_units_per_second(fork.evm_opts.get_compute_units_per_second())
+            .rpc_config(&fork.evm_opts.rpc)
             .build()?,
     );
 
+    // in offline mode the fork is served from the RPC cache only
+    if fork.evm_opts.offline {
+        if !fork.enable_caching {
+            eyre::bail!("can't fork {} in offline mode, storage caching is disabled", fork.url);
+        }
+        let (env, cached) = fork.evm_opts.cached_fork_evm_env(&fork.url)?;
+        fork.env = env;
+        let db = BlockchainDb::new_skip_check(cached.meta, Some(cached.path));
+        let (backend, mut handler) = SharedBackend::new(provider, db, Some(cached.number.into()));
+        handler.set_offline(true);
+        let fork = CreatedFork::new(fork, backend);
+        return Ok((fork, handler))
+    }
+
     // initialise the fork environment
     let (env, block) = fork.evm_opts.fork_evm_env(&fork.url).await?;
     fork.env = env;
@@ -1,5 +1,5 @@
 use super::fork::environment;
-use crate::fork::CreateFork;
+use crate::fork::{CachedFork, CreateFork};
 use alloy_primitives::{Address, B256, U256};
 use alloy_providers::provider::TempProvider;
 use alloy_rpc_types::Block;
@@ -10,7 +10,7 @@ use foundry_common::{
     ALCHEMY_FREE_TIER_CUPS,
 };
 use foundry_compilers::utils::RuntimeOrHandle;
-use foundry_config::{Chain, Config};
+use foundry_config::{Chain, Config, RpcConfig};
 use revm::primitives::{BlockEnv, CfgEnv, SpecId, TxEnv};
 use serde::{Deserialize, Deserializer, Serialize};
 
@@ -41,9 +41,16 @@ pub struct EvmOpts {
     /// Disables RPC rate limiting entirely.
     pub no_rpc_rate_limit: bool,
 
+    /// The retry settings of RPC requests.
+    #[serde(default)]
+    pub rpc: RpcConfig,
+
     /// Disables storage caching entirely.
     pub no_storage_caching: bool,
 
+    /// Serves forks from the RPC cache only, without accessing the endpoints.
+    pub offline: bool,
+
     /// The initial balance of each deployed test contract.
     pub initial_balance: U256,
 
@@ -59,15 +66,21 @@ pub struct EvmOpts {
     /// The memory limit per EVM execution in bytes.
     /// If this limit is exceeded, a `MemoryLimitOOG` result is thrown.
     pub memory_limit: u64,
+
+    /// Whether to enable isolation of calls.
+    pub isolate: bool,
 }
 
 impl EvmOpts {
     /// Configures a new `revm::Env`
     ///
     /// If a `fork_url` is set, it gets configured with settings fetched from the endpoint (chain
-    /// id, )
+    /// id, ), or from the RPC cache in `offline` mode
     pub async fn evm_env(&self) -> eyre::Result<revm::primitives::Env> {
         if let Some(ref fork_url) = self.fork_url {
+            if self.offline {
+                return Ok(self.cached_fork_evm_env(fork_url)?.0)
+            }
             Ok(self.fork_evm_env(fork_url).await?.0)
         } else {
             Ok(self.local_evm_env())
@@ -83,6 +96,7 @@ impl EvmOpts {
         let fork_url = fork_url.as_ref();
         let provider = ProviderBuilder::new(fork_url)
             .compute_units_per_second(self.get_compute_units_per_second())
+            .rpc_config(&self.rpc)
             .build()?;
         environment(
             &provider,
@@ -98,6 +112,29 @@ impl EvmOpts {
         })
     }
 
+    /// Returns the `revm::Env` of the fork of the endpoint that's stored in the RPC cache, without
+    /// accessing the endpoint, and the cached fork.
+    ///
+    /// Uses the cached fork at `fork_block_number` if set, or the latest cached block otherwise.
+    pub fn cached_fork_evm_env(
+        &self,
+        fork_url: impl AsRef<str>,
+    ) -> eyre::Result<(revm::primitives::Env, CachedFork)> {
+        let cached =
+            CachedFork::find(fork_url.as_ref(), self.env.chain_id, self.fork_block_number)?;
+        let mut cfg = cached.meta.cfg_env.clone();
+        cfg.memory_limit = self.memory_limit;
+        let block = cached.meta.block_env.clone();
+        let tx = TxEnv {
+            caller: self.sender,
+            gas_price: self.env.gas_price.map(U256::from).unwrap_or(block.basefee),
+            chain_id: Some(cfg.chain_id),
+            gas_limit: block.gas_limit.saturating_to(),
+            ..Default::default()
+        };
+        Ok((revm::primitives::Env { cfg, block, tx }, cached))
+    }
+
     /// Returns the `revm::Env` configured with only local settings
     pub fn local_evm_env(&self) -> revm::primitives::Env {
         let mut cfg = CfgEnv::default();
@@ -182,15 +219,22 @@ impl EvmOpts {
     }
 
     /// Returns the chain ID from the RPC, if any.
+    ///
+    /// In `offline` mode the chain ID is read from the RPC cache instead.
     pub fn get_remote_chain_id(&self) -> Option<Chain> {
         if let Some(ref url) = self.fork_url {
             if url.contains("mainnet") {
                 trace!(?url, "auto detected mainnet chain");
                 return Some(Chain::mainnet());
             }
+            if self.offline {
+                let cached = CachedFork::find(url, None, self.fork_block_number).ok()?;
+                return Some(Chain::from(cached.meta.cfg_env.chain_id));
+            }
             trace!(?url, "retrieving chain via eth_chainId");
             let provider = ProviderBuilder::new(url.as_str())
                 .compute_units_per_second(self.get_compute_units_per_second())
+                .rpc_config(&self.rpc)
                 .build()
                 .ok()
                 .unwrap_or_else(|| panic!("Failed to establish provider to {url}"));
@@ -4,6 +4,9 @@ use foundry_compilers::artifacts::ast::{self, Ast, Node, NodeType};
 use semver::Version;
 use std::collections::{HashMap, HashSet};
 
+/// The comment that excludes the function below it from coverage reports.
+pub const COVERAGE_IGNORE_MARKER: &str = "forge-coverage: ignore";
+
 /// A visitor that walks the AST of a single contract and finds coverage items.
 #[derive(Clone, Debug)]
 pub struct ContractVisitor<'a> {
@@ -22,6 +25,8 @@ pub struct ContractVisitor<'a> {
 
     /// Coverage items
     pub items: Vec<CoverageItem>,
+    /// The indices in `items` of the items of functions marked with [COVERAGE_IGNORE_MARKER]
+    pub ignored_items: Vec<usize>,
 
     /// Node IDs of this contract's base contracts, as well as IDs for referenced contracts such as
     /// libraries
@@ -37,6 +42,7 @@ impl<'a> ContractVisitor<'a> {
             branch_id: 0,
             last_line: 0,
             items: Vec::new(),
+            ignored_items: Vec::new(),
             base_contract_node_ids: HashSet::new(),
         }
     }
@@ -78,17 +84,37 @@ impl<'a> ContractVisitor<'a> {
 
         match node.body.take() {
             Some(body) => {
+                let first_item = self.items.len();
                 self.push_item(CoverageItem {
                     kind: CoverageItemKind::Function { name },
                     loc: self.source_location_for(&node.src),
                     hits: 0,
                 });
-                self.visit_block(*body)
+                self.visit_block(*body)?;
+                if self.is_ignored(&node.src) {
+                    self.ignored_items.extend(first_item..self.items.len());
+                }
+                Ok(())
             }
             _ => Ok(()),
         }
     }
 
+    /// Returns whether the comments right above the item at `loc` contain the
+    /// [COVERAGE_IGNORE_MARKER].
+    fn is_ignored(&self, loc: &ast::LowFidelitySourceLocation) -> bool {
+        // the lines before the line the item starts on
+        let before = &self.source[..loc.start];
+        before[..before.rfind('\n').unwrap_or(0)]
+            .lines()
+            .rev()
+            .map(str::trim)
+            .take_while(|line| {
+                line.starts_with("//") || line.starts_with("/*") || line.starts_with('*')
+            })
+            .any(|line| line.contains(COVERAGE_IGNORE_MARKER))
+    }
+
     fn visit_block(&mut self, node: Node) -> eyre::Result<()> {
         let statements: Vec<Node> = node.attribute("statements").unwrap_or_default();
 
@@ -437,6 +463,8 @@ pub struct SourceAnalysis {
     /// A mapping of contract IDs to item IDs relevant to the contract (including items in base
     /// contracts).
     pub contract_items: HashMap<ContractId, Vec<usize>>,
+    /// The IDs of the items of functions marked with [COVERAGE_IGNORE_MARKER].
+    pub ignored_items: HashSet<usize>,
 }
 
 /// Analyzes a set of sources to find coverage items.
@@ -454,6 +482,8 @@ pub struct SourceAnalyzer {
     contract_items: HashMap<ContractId, Vec<usize>>,
     /// A map of contracts to their base contracts
     contract_bases: HashMap<ContractId, Vec<ContractId>>,
+    /// The IDs of the items of functions marked with [COVERAGE_IGNORE_MARKER].
+    ignored_items: HashSet<usize>,
 }
 
 impl SourceAnalyzer {
@@ -570,31 +600,36 @@ impl SourceAnalyzer {
             }
         }
 
-        Ok(SourceAnalysis { items: self.items.clone(), contract_items: flattened })
+        Ok(SourceAnalysis {
+            items: self.items.clone(),
+            contract_items: flattened,
+            ignored_items: self.ignored_items,
+        })
     }
 
     fn analyze_contracts(&mut self) -> eyre::Result<()> {
         for contract_id in self.contracts.keys() {
             // Find this contract's coverage items if we haven't already
             if self.contract_items.get(contract_id).is_none() {
-                let ContractVisitor { items, base_contract_node_ids, .. } = ContractVisitor::new(
-                    contract_id.source_id,
-                    self.sources.get(&contract_id.source_id).unwrap_or_else(|| {
-                        panic!(
-                            "We should have the source code for source ID {}",
-                            contract_id.source_id
-                        )
-                    }),
-                    contract_id.contract_name.clone(),
-                )
-                .visit(
-                    self.contracts
-                        .get(contract_id)
-                        .unwrap_or_else(|| {
-                            panic!("We should have the AST of contract: {contract_id:?}")
-                        })
-                        .clone(),
-                )?;
+                let ContractVisitor { items, ignored_items, base_contract_node_ids, .. } =
+                    ContractVisitor::new(
+                        contract_id.source_id,
+                        self.sources.get(&contract_id.source_id).unwrap_or_else(|| {
+                            panic!(
+                                "We should have the source code for source ID {}",
+                                contract_id.source_id
+                            )
+                        }),
+                        contract_id.contract_name.clone(),
+                    )
+                    .visit(
+                        self.contracts
+                            .get(contract_id)
+                            .unwrap_or_else(|| {
+                                panic!("We should have the AST of contract: {contract_id:?}")
+                            })
+                            .clone(),
+                    )?;
 
                 let is_test = items.iter().any(|item| {
                     if let CoverageItemKind::Function { name } = &item.kind {
@@ -625,6 +660,7 @@ impl SourceAnalyzer {
                 } else {
                     let item_ids: Vec<usize> =
                         (self.items.len()..self.items.len() + items.len()).collect();
+                    self.ignored_items.extend(ignored_items.iter().map(|id| self.items.len() + id));
                     self.items.extend(items);
                     self.contract_items.insert(contract_id.clone(), item_ids.clone());
                 }
@@ -11,7 +11,7 @@ use alloy_primitives::{Bytes, B256};
 use foundry_compilers::sourcemap::SourceElement;
 use semver::Version;
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::Display,
     ops::{AddAssign, Deref, DerefMut},
 };
@@ -36,6 +36,10 @@ pub struct CoverageReport {
     pub source_paths_to_ids: HashMap<(Version, String), usize>,
     /// All coverage items for the codebase, keyed by the compiler version.
     pub items: HashMap<Version, Vec<CoverageItem>>,
+    /// The IDs of the items excluded from the report, keyed by the compiler version.
+    pub excluded_items: HashMap<Version, HashSet<usize>>,
+    /// The contracts excluded from the report.
+    pub excluded_contracts: HashSet<ContractId>,
     /// All item anchors for the codebase, keyed by their contract ID.
     pub anchors: HashMap<ContractId, Vec<ItemAnchor>>,
     /// All the bytecode hits for the codebase
@@ -74,27 +78,48 @@ impl CoverageReport {
         self.anchors.extend(anchors);
     }
 
-    /// Get coverage summaries by source file path
-    pub fn summary_by_file(&self) -> impl Iterator<Item = (String, CoverageSummary)> {
-        let mut summaries: BTreeMap<String, CoverageSummary> = BTreeMap::new();
+    /// Excludes the items of the given IDs from the report
+    pub fn exclude_items(&mut self, version: Version, item_ids: impl IntoIterator<Item = usize>) {
+        self.excluded_items.entry(version).or_default().extend(item_ids);
+    }
 
+    /// Excludes the items for which `filter` returns true, given their source file path, from the
+    /// report
+    pub fn exclude_items_by(&mut self, mut filter: impl FnMut(&str, &CoverageItem) -> bool) {
+        let mut excluded: Vec<(Version, usize)> = Vec::new();
         for (version, items) in self.items.iter() {
-            for item in items {
-                let mut summary = summaries
-                    .entry(
-                        self.source_paths
-                            .get(&(version.clone(), item.loc.source_id))
-                            .cloned()
-                            .unwrap_or_else(|| {
-                                format!("Unknown (ID: {}, solc: {version})", item.loc.source_id)
-                            }),
-                    )
-                    .or_default();
-                summary += item;
+            for (item_id, item) in items.iter().enumerate() {
+                if filter(&self.source_path(version, item.loc.source_id), item) {
+                    excluded.push((version.clone(), item_id));
+                }
             }
         }
+        for (version, item_id) in excluded {
+            self.excluded_items.entry(version).or_default().insert(item_id);
+        }
+    }
 
-        summaries.into_iter()
+    /// Excludes the contracts for which `filter` returns true, given their source file path and
+    /// name, from the report
+    pub fn exclude_contracts_by(&mut self, mut filter: impl FnMut(&str, &str) -> bool) {
+        let excluded = self
+            .anchors
+            .keys()
+            .chain(self.source_maps.keys())
+            .filter(|id| filter(&self.source_path(&id.version, id.source_id), &id.contract_name))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.excluded_contracts.extend(excluded);
+    }
+
+    /// Get coverage summaries by source file path
+    pub fn summary_by_file(&self) -> impl Iterator<Item = (String, CoverageSummary)> {
+        self.summaries(false)
+    }
+
+    /// Get the coverage summaries of the excluded items by source file path
+    pub fn excluded_summary_by_file(&self) -> impl Iterator<Item = (String, CoverageSummary)> {
+        self.summaries(true)
     }
 
     /// Get coverage items by source file path
@@ -102,16 +127,12 @@ impl CoverageReport {
         let mut items_by_source: BTreeMap<String, Vec<CoverageItem>> = BTreeMap::new();
 
         for (version, items) in self.items.iter() {
-            for item in items {
+            for (item_id, item) in items.iter().enumerate() {
+                if self.is_excluded(version, item_id) {
+                    continue
+                }
                 items_by_source
-                    .entry(
-                        self.source_paths
-                            .get(&(version.clone(), item.loc.source_id))
-                            .cloned()
-                            .unwrap_or_else(|| {
-                                format!("Unknown (ID: {}, solc: {version})", item.loc.source_id)
-                            }),
-                    )
+                    .entry(self.source_path(version, item.loc.source_id))
                     .or_default()
                     .push(item.clone());
             }
@@ -120,6 +141,36 @@ impl CoverageReport {
         items_by_source.into_iter()
     }
 
+    /// Returns the summaries of either the reported or the excluded items by source file path
+    fn summaries(&self, excluded: bool) -> impl Iterator<Item = (String, CoverageSummary)> {
+        let mut summaries: BTreeMap<String, CoverageSummary> = BTreeMap::new();
+
+        for (version, items) in self.items.iter() {
+            for (item_id, item) in items.iter().enumerate() {
+                if self.is_excluded(version, item_id) != excluded {
+                    continue
+                }
+                let mut summary =
+                    summaries.entry(self.source_path(version, item.loc.source_id)).or_default();
+                summary += item;
+            }
+        }
+
+        summaries.into_iter()
+    }
+
+    /// Returns whether the item of the given ID is excluded from the report.
+    pub fn is_excluded(&self, version: &Version, item_id: usize) -> bool {
+        self.excluded_items.get(version).map_or(false, |ids| ids.contains(&item_id))
+    }
+
+    fn source_path(&self, version: &Version, source_id: usize) -> String {
+        self.source_paths
+            .get(&(version.clone(), source_id))
+            .cloned()
+            .unwrap_or_else(|| format!("Unknown (ID: {source_id}, solc: {version})"))
+    }
+
     /// Processes data from a [HitMap] and sets hit counts for coverage items in this coverage map.
     ///
     /// This function should only be called *after* all the relevant sources have been processed and
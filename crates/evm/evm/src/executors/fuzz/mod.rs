@@ -1,4 +1,4 @@
-use crate::executors::{Executor, RawCallResult};
+use crate::executors::{Executor, RawCallResult, TestStopper, INTERRUPTED};
 use alloy_dyn_abi::JsonAbiExt;
 use alloy_json_abi::{Function, JsonAbi};
 use alloy_primitives::{Address, Bytes, U256};
@@ -11,14 +11,14 @@ use foundry_evm_core::{
 use foundry_evm_coverage::HitMaps;
 use foundry_evm_fuzz::{
     strategies::{
-        build_initial_state, collect_state_from_call, fuzz_calldata, fuzz_calldata_from_state,
-        EvmFuzzState,
+        build_initial_state, collect_state_from_call, collect_state_from_fork, fuzz_calldata,
+        fuzz_calldata_from_state, EvmFuzzState,
     },
     BaseCounterExample, CounterExample, FuzzCase, FuzzError, FuzzTestResult,
 };
 use foundry_evm_traces::CallTraceArena;
 use proptest::test_runner::{TestCaseError, TestError, TestRunner};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 mod types;
 pub use types::{CaseOutcome, CounterExampleOutcome, FuzzOutcome};
@@ -37,6 +37,8 @@ pub struct FuzzedExecutor {
     sender: Address,
     /// The fuzz configuration
     config: FuzzConfig,
+    /// Cancels the campaign if the test run is interrupted
+    stopper: TestStopper,
 }
 
 impl FuzzedExecutor {
@@ -47,7 +49,13 @@ impl FuzzedExecutor {
         sender: Address,
         config: FuzzConfig,
     ) -> Self {
-        Self { executor, runner, sender, config }
+        Self { executor, runner, sender, config, stopper: TestStopper::default() }
+    }
+
+    /// Cancels the campaign before its next run once the `stopper` is interrupted.
+    pub fn with_stopper(mut self, stopper: TestStopper) -> Self {
+        self.stopper = stopper;
+        self
     }
 
     /// Fuzzes the provided function, assuming it is available at the contract at `address`
@@ -78,6 +86,9 @@ impl FuzzedExecutor {
         let coverage: RefCell<Option<HitMaps>> = RefCell::default();
 
         let state = self.build_fuzz_state();
+        if self.config.dictionary.dump_dictionary {
+            eprintln!("Dictionary of {}:\n{}", func.signature(), state.read().dump());
+        }
 
         let mut weights = vec![];
         let dictionary_weight = self.config.dictionary.dictionary_weight.min(100);
@@ -92,8 +103,14 @@ impl FuzzedExecutor {
         }
 
         let strat = proptest::strategy::Union::new_weighted(weights);
+        // Whether the campaign was cancelled before one of its runs
+        let interrupted = Cell::new(false);
         debug!(func=?func.name, should_fail, "fuzzing");
         let run_result = self.runner.clone().run(&strat, |calldata| {
+            if self.stopper.is_interrupted() {
+                interrupted.set(true);
+                return Err(TestCaseError::fail(INTERRUPTED))
+            }
             let fuzz_res = self.single_fuzz(&state, address, should_fail, calldata)?;
 
             match fuzz_res {
@@ -151,6 +168,10 @@ impl FuzzedExecutor {
         };
 
         match run_result {
+            // The campaign was cancelled, before finding or while shrinking a counterexample
+            Err(_) if interrupted.get() => {
+                result.reason = Some(INTERRUPTED.to_string());
+            }
             // Currently the only operation that can trigger proptest global rejects is the
             // `vm.assume` cheatcode, thus we surface this info to the user when the fuzz test
             // aborts due to too many global rejects, making the error message more actionable.
@@ -247,7 +268,9 @@ impl FuzzedExecutor {
     /// Stores fuzz state for use with [fuzz_calldata_from_state]
     pub fn build_fuzz_state(&self) -> EvmFuzzState {
         if let Some(fork_db) = self.executor.backend.active_fork_db() {
-            build_initial_state(fork_db, &self.config.dictionary)
+            let state = build_initial_state(fork_db, &self.config.dictionary);
+            collect_state_from_fork(fork_db.db.data(), &state, &self.config.dictionary);
+            state
         } else {
             build_initial_state(self.executor.backend.mem_db(), &self.config.dictionary)
         }
@@ -4,7 +4,7 @@ use alloy_json_abi::Function;
 use alloy_primitives::{Address, Bytes, Log};
 use eyre::Result;
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
-use foundry_evm_core::{constants::CALLER, decode::decode_revert};
+use foundry_evm_core::{constants::CALLER, debug::DebugArena, decode::decode_revert};
 use foundry_evm_fuzz::{BaseCounterExample, CounterExample, FuzzedCases, Reason};
 use foundry_evm_traces::{load_contracts, CallTraceArena, TraceKind, Traces};
 use itertools::Itertools;
@@ -50,6 +50,8 @@ pub struct InvariantFuzzTestResult {
     /// The entire inputs of the last run of the invariant campaign, used for
     /// replaying the run for collecting traces.
     pub last_run_inputs: Vec<BasicTxDetails>,
+    /// Whether the campaign was cancelled before completing all of its runs
+    pub interrupted: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -91,7 +93,7 @@ impl InvariantFuzzError {
         };
         let revert_reason = decode_revert(
             call_result.result.as_ref(),
-            Some(invariant_contract.abi),
+            Some(invariant_contract.errors.unwrap_or(invariant_contract.abi)),
             Some(call_result.exit_reason),
         );
 
@@ -113,6 +115,9 @@ impl InvariantFuzzError {
     }
 
     /// Replays the error case and collects all necessary traces.
+    ///
+    /// If `debug_calls` is set, the (shrunk) sequence is replayed with the debugger enabled and
+    /// the debug nodes of every call and invariant check are collected into it.
     pub fn replay(
         &self,
         mut executor: Executor,
@@ -120,6 +125,7 @@ impl InvariantFuzzError {
         mut ided_contracts: ContractsByAddress,
         logs: &mut Vec<Log>,
         traces: &mut Traces,
+        mut debug_calls: Option<&mut Vec<(String, DebugArena)>>,
     ) -> Result<Option<CounterExample>> {
         let mut counterexample_sequence = vec![];
         let mut calls = match self.test_error {
@@ -136,6 +142,9 @@ impl InvariantFuzzError {
 
         // We want traces for a failed case.
         executor.set_tracing(true);
+        if debug_calls.is_some() {
+            executor.set_debugger(true);
+        }
 
         set_up_inner_replay(&mut executor, &self.inner_sequence);
 
@@ -154,13 +163,17 @@ impl InvariantFuzzError {
                 known_contracts,
             ));
 
-            counterexample_sequence.push(BaseCounterExample::create(
+            let counterexample = BaseCounterExample::create(
                 *sender,
                 *addr,
                 bytes,
                 &ided_contracts,
                 call_result.traces,
-            ));
+            );
+            if let (Some(debug_calls), Some(debug)) = (debug_calls.as_mut(), call_result.debug) {
+                debug_calls.push((counterexample.to_string(), debug));
+            }
+            counterexample_sequence.push(counterexample);
 
             // Checks the invariant.
             if let Some(func) = &self.func {
@@ -169,6 +182,11 @@ impl InvariantFuzzError {
                     .expect("bad call to evm");
 
                 traces.push((TraceKind::Execution, error_call_result.traces.clone().unwrap()));
+                if let (Some(debug_calls), Some(debug)) =
+                    (debug_calls.as_mut(), error_call_result.debug)
+                {
+                    debug_calls.push(("invariant check".to_string(), debug));
+                }
 
                 logs.extend(error_call_result.logs);
                 if error_call_result.reverted {
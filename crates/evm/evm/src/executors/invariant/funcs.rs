@@ -4,9 +4,12 @@ use alloy_dyn_abi::JsonAbiExt;
 use alloy_json_abi::Function;
 use alloy_primitives::Log;
 use foundry_common::{ContractsByAddress, ContractsByArtifact};
-use foundry_evm_core::constants::CALLER;
+use foundry_evm_core::{constants::CALLER, debug::DebugArena};
 use foundry_evm_coverage::HitMaps;
-use foundry_evm_fuzz::invariant::{BasicTxDetails, InvariantContract};
+use foundry_evm_fuzz::{
+    invariant::{BasicTxDetails, InvariantContract},
+    BaseCounterExample,
+};
 use foundry_evm_traces::{load_contracts, TraceKind, Traces};
 use revm::primitives::U256;
 
@@ -20,6 +23,49 @@ pub fn assert_invariants(
     invariant_failures: &mut InvariantFailures,
     shrink_sequence: bool,
     shrink_run_limit: usize,
+) -> Option<RawCallResult> {
+    assert_function(
+        invariant_contract,
+        invariant_contract.invariant_function,
+        executor,
+        calldata,
+        invariant_failures,
+        shrink_sequence,
+        shrink_run_limit,
+    )
+}
+
+/// Calls the `afterInvariant` function at the end of a run, if the test contract has one, and
+/// records the failure like a broken invariant if it reverts or fails an assertion.
+pub fn assert_after_invariant(
+    invariant_contract: &InvariantContract<'_>,
+    executor: &Executor,
+    calldata: &[BasicTxDetails],
+    invariant_failures: &mut InvariantFailures,
+    shrink_sequence: bool,
+    shrink_run_limit: usize,
+) {
+    let Some(func) = invariant_contract.after_invariant_function else { return };
+    assert_function(
+        invariant_contract,
+        func,
+        executor,
+        calldata,
+        invariant_failures,
+        shrink_sequence,
+        shrink_run_limit,
+    );
+}
+
+/// Calls `func` of the test contract without committing, recording an error if it fails.
+fn assert_function(
+    invariant_contract: &InvariantContract<'_>,
+    func: &Function,
+    executor: &Executor,
+    calldata: &[BasicTxDetails],
+    invariant_failures: &mut InvariantFailures,
+    shrink_sequence: bool,
+    shrink_run_limit: usize,
 ) -> Option<RawCallResult> {
     let mut inner_sequence = vec![];
 
@@ -29,12 +75,11 @@ pub fn assert_invariants(
         }
     }
 
-    let func = invariant_contract.invariant_function;
     let mut call_result = executor
         .call_raw(
             CALLER,
             invariant_contract.address,
-            func.abi_encode_input(&[]).expect("invariant should have no inputs").into(),
+            func.abi_encode_input(&[]).expect("function should have no inputs").into(),
             U256::ZERO,
         )
         .expect("EVM error");
@@ -76,11 +121,15 @@ pub fn replay_run(
     logs: &mut Vec<Log>,
     traces: &mut Traces,
     coverage: &mut Option<HitMaps>,
+    mut debug_calls: Option<&mut Vec<(String, DebugArena)>>,
     func: Function,
     inputs: Vec<BasicTxDetails>,
 ) {
     // We want traces for a failed case.
     executor.set_tracing(true);
+    if debug_calls.is_some() {
+        executor.set_debugger(true);
+    }
 
     // set_up_inner_replay(&mut executor, &inputs);
 
@@ -113,6 +162,11 @@ pub fn replay_run(
             known_contracts,
         ));
 
+        if let (Some(debug_calls), Some(debug)) = (debug_calls.as_mut(), call_result.debug) {
+            let call = BaseCounterExample::create(*sender, *addr, bytes, &ided_contracts, None);
+            debug_calls.push((call.to_string(), debug));
+        }
+
         // Checks the invariant.
         let error_call_result = executor
             .call_raw(
@@ -124,7 +178,31 @@ pub fn replay_run(
             .expect("bad call to evm");
 
         traces.push((TraceKind::Execution, error_call_result.traces.clone().unwrap()));
+        if let (Some(debug_calls), Some(debug)) = (debug_calls.as_mut(), error_call_result.debug) {
+            debug_calls.push(("invariant check".to_string(), debug));
+        }
 
         logs.extend(error_call_result.logs);
     }
+
+    // Calls `afterInvariant` at the end of the run.
+    if let Some(after_invariant) = invariant_contract.after_invariant_function {
+        let after_invariant_result = executor
+            .call_raw(
+                CALLER,
+                invariant_contract.address,
+                after_invariant.abi_encode_input(&[]).expect("should have no inputs").into(),
+                U256::ZERO,
+            )
+            .expect("bad call to evm");
+
+        traces.push((TraceKind::Execution, after_invariant_result.traces.clone().unwrap()));
+        if let (Some(debug_calls), Some(debug)) =
+            (debug_calls.as_mut(), after_invariant_result.debug)
+        {
+            debug_calls.push(("afterInvariant".to_string(), debug));
+        }
+
+        logs.extend(after_invariant_result.logs);
+    }
 }
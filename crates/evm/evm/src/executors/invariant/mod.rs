@@ -1,5 +1,5 @@
 use crate::{
-    executors::{Executor, RawCallResult},
+    executors::{Executor, RawCallResult, TestStopper, INTERRUPTED},
     inspectors::Fuzzer,
 };
 use alloy_dyn_abi::DynSolValue;
@@ -29,13 +29,17 @@ use proptest::{
     test_runner::{TestCaseError, TestRunner},
 };
 use revm::{primitives::HashMap, DatabaseCommit};
-use std::{cell::RefCell, collections::BTreeMap, sync::Arc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    sync::Arc,
+};
 
 mod error;
 pub use error::{InvariantFailures, InvariantFuzzError, InvariantFuzzTestResult};
 
 mod funcs;
-pub use funcs::{assert_invariants, replay_run};
+pub use funcs::{assert_after_invariant, assert_invariants, replay_run};
 
 /// Alias for (Dictionary for fuzzing, initial contracts to fuzz and an InvariantStrategy).
 type InvariantPreparation =
@@ -73,6 +77,8 @@ pub struct InvariantExecutor<'a> {
     project_contracts: &'a ContractsByArtifact,
     /// Filters contracts to be fuzzed through their artifact identifiers.
     artifact_filters: ArtifactFilters,
+    /// Cancels the campaign if the test run is interrupted
+    stopper: TestStopper,
 }
 
 impl<'a> InvariantExecutor<'a> {
@@ -91,9 +97,16 @@ impl<'a> InvariantExecutor<'a> {
             setup_contracts,
             project_contracts,
             artifact_filters: ArtifactFilters::default(),
+            stopper: TestStopper::default(),
         }
     }
 
+    /// Cancels the campaign before its next run once the `stopper` is interrupted.
+    pub fn with_stopper(mut self, stopper: TestStopper) -> Self {
+        self.stopper = stopper;
+        self
+    }
+
     /// Fuzzes any deployed contract and checks any broken invariant at `invariant_address`.
     pub fn invariant_fuzz(
         &mut self,
@@ -105,6 +118,13 @@ impl<'a> InvariantExecutor<'a> {
         }
 
         let (fuzz_state, targeted_contracts, strat) = self.prepare_fuzzing(&invariant_contract)?;
+        if self.config.dictionary.dump_dictionary {
+            eprintln!(
+                "Dictionary of {}:\n{}",
+                invariant_contract.invariant_function.signature(),
+                fuzz_state.read().dump()
+            );
+        }
 
         // Stores the consumed gas and calldata of every successful fuzz call.
         let fuzz_cases: RefCell<Vec<FuzzedCases>> = RefCell::new(Default::default());
@@ -115,6 +135,9 @@ impl<'a> InvariantExecutor<'a> {
         // Stores the calldata in the last run.
         let last_run_calldata: RefCell<Vec<BasicTxDetails>> = RefCell::new(vec![]);
 
+        // The executor and calls of the previous runs, if the state is preserved across runs.
+        let preserved: RefCell<Option<(Executor, Vec<BasicTxDetails>)>> = RefCell::new(None);
+
         // Let's make sure the invariant is sound before actually starting the run:
         // We'll assert the invariant in its initial state, and if it fails, we'll
         // already know if we can early exit the invariant run.
@@ -137,14 +160,28 @@ impl<'a> InvariantExecutor<'a> {
         // during the run. We need another proptest runner to query for random
         // values.
         let branch_runner = RefCell::new(self.runner.clone());
+        // Whether the campaign was cancelled before one of its runs.
+        let interrupted = Cell::new(false);
         let _ = self.runner.run(&strat, |mut inputs| {
+            if self.stopper.is_interrupted() {
+                interrupted.set(true);
+                return Err(TestCaseError::fail(INTERRUPTED))
+            }
+
             // We stop the run immediately if we have reverted, and `fail_on_revert` is set.
             if self.config.fail_on_revert && failures.borrow().reverts > 0 {
                 return Err(TestCaseError::fail("Revert occurred."))
             }
 
-            // Before each run, we must reset the backend state.
-            let mut executor = self.executor.clone();
+            // Before each run, we must reset the backend state, unless it's preserved. The calls
+            // of the previous runs are then prepended to the sequence, so that failures can be
+            // replayed from the initial state.
+            let (mut executor, previous_calls) = match preserved.borrow_mut().take() {
+                Some((executor, calls)) => (executor, calls),
+                None => (self.executor.clone(), vec![]),
+            };
+            inputs.splice(0..0, previous_calls);
+            let mut executed_calls = inputs.len() - 1;
 
             // Used for stat reports (eg. gas usage).
             let mut fuzz_runs = Vec::with_capacity(self.config.depth as usize);
@@ -186,6 +223,7 @@ impl<'a> InvariantExecutor<'a> {
 
                 // Commit changes to the database.
                 executor.backend.commit(state_changeset.clone());
+                executed_calls += 1;
 
                 fuzz_runs.push(FuzzCase {
                     calldata: calldata.clone(),
@@ -227,8 +265,26 @@ impl<'a> InvariantExecutor<'a> {
                 );
             }
 
-            // We clear all the targeted contracts created during this run.
-            if !created_contracts.is_empty() {
+            // Drops the next call, which is generated before knowing whether it will be executed.
+            inputs.truncate(executed_calls);
+
+            // Calls `afterInvariant` once the run completed without failures.
+            if failures.borrow().error.is_none() {
+                assert_after_invariant(
+                    &invariant_contract,
+                    &executor,
+                    &inputs,
+                    &mut failures.borrow_mut(),
+                    self.config.shrink_sequence,
+                    self.config.shrink_run_limit,
+                );
+            }
+
+            // The contracts created during the run stay targeted when the state is preserved.
+            if self.config.preserve_state {
+                *preserved.borrow_mut() = Some((executor, inputs));
+            } else if !created_contracts.is_empty() {
+                // We clear all the targeted contracts created during this run.
                 let mut writable_targeted = targeted_contracts.lock();
                 for addr in created_contracts.iter() {
                     writable_targeted.remove(addr);
@@ -249,6 +305,7 @@ impl<'a> InvariantExecutor<'a> {
             cases: fuzz_cases.into_inner(),
             reverts,
             last_run_inputs: last_run_calldata.take(),
+            interrupted: interrupted.get(),
         })
     }
 
@@ -43,6 +43,9 @@ pub use fuzz::FuzzedExecutor;
 pub mod invariant;
 pub use invariant::InvariantExecutor;
 
+mod stop;
+pub use stop::{TestStopper, INTERRUPTED};
+
 mod tracing;
 pub use tracing::TracingExecutor;
 
@@ -0,0 +1,41 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// The reason of the results of fuzz and invariant campaigns that were interrupted.
+pub const INTERRUPTED: &str = "interrupted";
+
+/// Stops a test run early, shared by all tests of the run.
+///
+/// A stopped run doesn't start new tests, but lets the tests in flight complete, e.g. after the
+/// first failure with `--fail-fast`. An interrupted run, e.g. with Ctrl-C, also cancels the fuzz
+/// and invariant campaigns in flight before their next run.
+#[derive(Clone, Debug, Default)]
+pub struct TestStopper {
+    stopped: Arc<AtomicBool>,
+    interrupted: Arc<AtomicBool>,
+}
+
+impl TestStopper {
+    /// Stops starting new tests.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// Stops starting new tests and cancels the fuzz and invariant campaigns in flight.
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::Relaxed);
+        self.stop();
+    }
+
+    /// Returns true if no new tests should be started.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if the fuzz and invariant campaigns in flight should be cancelled.
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::Relaxed)
+    }
+}
@@ -3,15 +3,24 @@ use super::{
     StackSnapshotType, TracePrinter, TracingInspector, TracingInspectorConfig,
 };
 use alloy_primitives::{Address, Bytes, Log, B256, U256};
+use alloy_sol_types::{Revert, SolError};
 use ethers_signers::LocalWallet;
-use foundry_evm_core::{backend::DatabaseExt, debug::DebugArena};
+use foundry_evm_core::{
+    backend::DatabaseExt,
+    constants::{
+        CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS, TRANSIENT_LOAD_TOPIC, TRANSIENT_STORE_TOPIC,
+    },
+    debug::DebugArena,
+    utils::{eval_to_instruction_result, halt_to_instruction_result},
+};
 use foundry_evm_coverage::HitMaps;
 use foundry_evm_traces::CallTraceArena;
 use revm::{
     interpreter::{
-        return_revert, CallInputs, CreateInputs, Gas, InstructionResult, Interpreter, Stack,
+        opcode, return_revert, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult,
+        Interpreter, Stack,
     },
-    primitives::{BlockEnv, Env},
+    primitives::{BlockEnv, Env, ExecutionResult, TransactTo},
     EVMData, Inspector,
 };
 use std::{collections::HashMap, sync::Arc};
@@ -45,6 +54,10 @@ pub struct InspectorStackBuilder {
     pub print: Option<bool>,
     /// The chisel state inspector.
     pub chisel_state: Option<usize>,
+    /// Whether to enable call isolation.
+    /// In isolation mode all top-level calls are executed as a separate transaction in a separate
+    /// EVM context, enabling more precise gas accounting and transaction state changes.
+    pub enable_isolation: Option<bool>,
 }
 
 impl InspectorStackBuilder {
@@ -124,6 +137,14 @@ impl InspectorStackBuilder {
         self
     }
 
+    /// Set whether to enable the call isolation.
+    /// For description of call isolation, see [`InspectorStack::enable_isolation`].
+    #[inline]
+    pub fn enable_isolation(mut self, yes: bool) -> Self {
+        self.enable_isolation = Some(yes);
+        self
+    }
+
     /// Builds the stack of inspectors to use when transacting/committing on the EVM.
     ///
     /// See also [`revm::Evm::inspect_ref`] and [`revm::Evm::commit_ref`].
@@ -139,6 +160,7 @@ impl InspectorStackBuilder {
             coverage,
             print,
             chisel_state,
+            enable_isolation,
         } = self;
         let mut stack = InspectorStack::new();
 
@@ -157,6 +179,7 @@ impl InspectorStackBuilder {
         stack.enable_debugger(debug.unwrap_or(false));
         stack.print(print.unwrap_or(false));
         stack.tracing(trace.unwrap_or(false));
+        stack.enable_isolation(enable_isolation.unwrap_or(false));
 
         // environment, must come after all of the inspectors
         if let Some(block) = block {
@@ -193,6 +216,14 @@ pub struct InspectorData {
     pub chisel_state: Option<(Stack, Vec<u8>, InstructionResult)>,
 }
 
+/// Contains data about the state of the outer transaction while an isolated transaction is being
+/// executed.
+#[derive(Clone, Debug)]
+pub struct InnerContextData {
+    /// Origin of the outer transaction.
+    original_origin: Address,
+}
+
 /// An inspector that calls multiple inspectors in sequence.
 ///
 /// If a call to an inspector returns a value other than [InstructionResult::Continue] (or
@@ -207,6 +238,9 @@ pub struct InspectorStack {
     pub log_collector: Option<LogCollector>,
     pub printer: Option<TracePrinter>,
     pub tracer: Option<TracingInspector>,
+    pub enable_isolation: bool,
+    /// Set while an isolated transaction is executed.
+    pub inner_context_data: Option<InnerContextData>,
 }
 
 impl InspectorStack {
@@ -301,6 +335,18 @@ impl InspectorStack {
         });
     }
 
+    /// Set whether to enable call isolation.
+    ///
+    /// In isolation mode every top-level call, i.e. every call made by the contract the
+    /// transaction was sent to (the test contract), is executed as a separate transaction: it's
+    /// charged the intrinsic gas, starts with an empty access list, has its refunds capped on
+    /// its own and increments the nonce of its sender. This makes gas reports match what a real
+    /// chain would charge.
+    #[inline]
+    pub fn enable_isolation(&mut self, yes: bool) {
+        self.enable_isolation = yes;
+    }
+
     /// Collects all the data gathered during inspection into a single struct.
     #[inline]
     pub fn collect(self) -> InspectorData {
@@ -361,6 +407,67 @@ impl InspectorStack {
 
         (status, remaining_gas, retdata)
     }
+
+    /// Executes the call as a separate transaction, see [`InspectorStack::enable_isolation`].
+    fn transact_inner<DB: DatabaseExt>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &CallInputs,
+    ) -> (InstructionResult, Gas, Bytes) {
+        let cached_env = data.env.clone();
+
+        // gas is not paid for, only accounted
+        data.env.block.basefee = U256::ZERO;
+        data.env.tx.gas_price = U256::ZERO;
+        data.env.tx.gas_priority_fee = None;
+        data.env.tx.caller = call.context.caller;
+        data.env.tx.transact_to = TransactTo::Call(call.contract);
+        data.env.tx.data = call.input.clone();
+        data.env.tx.value = call.transfer.value;
+        data.env.tx.gas_limit = call.gas_limit;
+        data.env.tx.access_list = Vec::new();
+        data.env.tx.nonce = None;
+
+        // the transaction runs in a new EVM, so it starts with empty transient storage and its
+        // transient storage is discarded when it ends, as per EIP-1153
+        self.inner_context_data = Some(InnerContextData { original_origin: cached_env.tx.caller });
+        let res = data.db.transact_env(data.env, &mut data.journaled_state, self);
+        self.inner_context_data = None;
+
+        data.env.tx = cached_env.tx;
+        data.env.block.basefee = cached_env.block.basefee;
+
+        let (status, gas_used, retdata) = match res {
+            Ok(ExecutionResult::Success { reason, gas_used, output, .. }) => {
+                (eval_to_instruction_result(reason), gas_used, output.into_data())
+            }
+            Ok(ExecutionResult::Revert { gas_used, output }) => {
+                (InstructionResult::Revert, gas_used, output)
+            }
+            Ok(ExecutionResult::Halt { reason, gas_used }) => {
+                (halt_to_instruction_result(reason), gas_used, Bytes::new())
+            }
+            // the transaction is invalid, e.g. the gas limit doesn't cover the intrinsic gas
+            Err(err) => {
+                let revert = Revert::from(err.to_string()).abi_encode();
+                (InstructionResult::Revert, call.gas_limit, revert.into())
+            }
+        };
+
+        let mut gas = Gas::new(call.gas_limit);
+        gas.record_cost(gas_used.min(call.gas_limit));
+        (status, gas, retdata)
+    }
+
+    /// Makes the top-level call of an isolated transaction look like it's made by the outer
+    /// transaction.
+    fn adjust_evm_data_for_inner_context<DB: DatabaseExt>(&mut self, data: &mut EVMData<'_, DB>) {
+        let inner_context_data =
+            self.inner_context_data.as_ref().expect("should be called in inner context");
+        // the sender's nonce was bumped already, so the origin can be restored
+        data.env.tx.caller = inner_context_data.original_origin;
+        data.journaled_state.depth = 1;
+    }
 }
 
 impl<DB: DatabaseExt> Inspector<DB> for InspectorStack {
@@ -389,6 +496,30 @@ impl<DB: DatabaseExt> Inspector<DB> for InspectorStack {
 
     fn step(&mut self, interpreter: &mut Interpreter<'_>, data: &mut EVMData<'_, DB>) {
         let res = interpreter.instruction_result;
+
+        // Record transient storage accesses as logs of the call, see `TRANSIENT_STORE_TOPIC`
+        if let Some(tracer) = &mut self.tracer {
+            let address = interpreter.contract().address;
+            let topics = match interpreter.current_opcode() {
+                opcode::TSTORE if !interpreter.is_static => {
+                    match (interpreter.stack().peek(0), interpreter.stack().peek(1)) {
+                        (Ok(slot), Ok(value)) => {
+                            Some([TRANSIENT_STORE_TOPIC, slot.into(), value.into()])
+                        }
+                        _ => None,
+                    }
+                }
+                opcode::TLOAD => interpreter.stack().peek(0).ok().map(|slot| {
+                    let value = data.journaled_state.tload(address, slot);
+                    [TRANSIENT_LOAD_TOPIC, slot.into(), value.into()]
+                }),
+                _ => None,
+            };
+            if let Some(topics) = topics {
+                tracer.log(data, &address, &topics, &Bytes::new());
+            }
+        }
+
         call_inspectors!(
             [
                 &mut self.fuzzer,
@@ -454,6 +585,13 @@ impl<DB: DatabaseExt> Inspector<DB> for InspectorStack {
         data: &mut EVMData<'_, DB>,
         call: &mut CallInputs,
     ) -> (InstructionResult, Gas, Bytes) {
+        // the top-level call of an isolated transaction was already passed to the inspectors by the
+        // outer transaction
+        if self.inner_context_data.is_some() && data.journaled_state.depth == 0 {
+            self.adjust_evm_data_for_inner_context(data);
+            return (InstructionResult::Continue, Gas::new(call.gas_limit), Bytes::new())
+        }
+
         call_inspectors!(
             [
                 &mut self.fuzzer,
@@ -475,6 +613,16 @@ impl<DB: DatabaseExt> Inspector<DB> for InspectorStack {
             }
         );
 
+        if self.enable_isolation &&
+            self.inner_context_data.is_none() &&
+            data.journaled_state.depth == 1 &&
+            call.context.scheme == CallScheme::Call &&
+            call.contract != CHEATCODE_ADDRESS &&
+            call.contract != HARDHAT_CONSOLE_ADDRESS
+        {
+            return self.transact_inner(data, call)
+        }
+
         (InstructionResult::Continue, Gas::new(call.gas_limit), Bytes::new())
     }
 
@@ -486,6 +634,11 @@ impl<DB: DatabaseExt> Inspector<DB> for InspectorStack {
         status: InstructionResult,
         retdata: Bytes,
     ) -> (InstructionResult, Gas, Bytes) {
+        // the result of an isolated transaction is passed to the inspectors by the outer call
+        if self.inner_context_data.is_some() && data.journaled_state.depth == 1 {
+            return (status, remaining_gas, retdata)
+        }
+
         let res = self.do_call_end(data, call, remaining_gas, status, retdata);
 
         if matches!(res.0, return_revert!()) {
@@ -24,4 +24,8 @@ pub struct InvariantContract<'a> {
     pub invariant_function: &'a Function,
     /// ABI of the test contract.
     pub abi: &'a JsonAbi,
+    /// All known errors, used to decode reverts.
+    pub errors: Option<&'a JsonAbi>,
+    /// The `afterInvariant` function of the test contract, called once at the end of every run.
+    pub after_invariant_function: Option<&'a Function>,
 }
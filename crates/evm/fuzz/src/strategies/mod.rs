@@ -13,7 +13,7 @@ pub use calldata::fuzz_calldata;
 mod state;
 pub use state::{
     build_initial_state, collect_created_contracts, collect_state_from_call,
-    fuzz_calldata_from_state, EvmFuzzState,
+    collect_state_from_fork, fuzz_calldata_from_state, EvmFuzzState,
 };
 
 mod invariants;
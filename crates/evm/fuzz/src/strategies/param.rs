@@ -81,9 +81,23 @@ pub fn fuzz_param_from_state(
 
     // Convert the value based on the parameter type
     match param {
-        DynSolType::Address => value
-            .prop_map(move |value| DynSolValue::Address(Address::from_word(value.into())))
-            .boxed(),
+        DynSolType::Address => {
+            let value =
+                value.prop_map(move |value| DynSolValue::Address(Address::from_word(value.into())));
+            let addresses_len = arc_state.read().address_values().len();
+            if addresses_len == 0 {
+                return value.boxed()
+            }
+
+            // Address parameters mostly draw from the values that are likely addresses
+            let st = arc_state.clone();
+            let address = any::<prop::sample::Index>().prop_map(move |index| {
+                let value =
+                    *st.read().address_values().iter().nth(index.index(addresses_len)).unwrap();
+                DynSolValue::Address(Address::from_word(value.into()))
+            });
+            prop_oneof![3 => address, 1 => value].boxed()
+        }
         DynSolType::FixedBytes(size) => value
             .prop_map(move |v| {
                 let mut buf: [u8; 32] = [0; 32];
@@ -165,9 +179,17 @@ pub fn fuzz_param_from_state(
 
 #[cfg(test)]
 mod tests {
-    use crate::strategies::{build_initial_state, fuzz_calldata, fuzz_calldata_from_state};
+    use crate::strategies::{
+        build_initial_state, fuzz_calldata, fuzz_calldata_from_state, fuzz_param_from_state,
+        EvmFuzzState,
+    };
+    use alloy_dyn_abi::{DynSolType, DynSolValue};
     use foundry_common::abi::get_func;
     use foundry_config::FuzzDictionaryConfig;
+    use proptest::{
+        strategy::{Strategy, ValueTree},
+        test_runner::TestRunner,
+    };
     use revm::db::{CacheDB, EmptyDB};
 
     #[test]
@@ -184,4 +206,22 @@ mod tests {
         let mut runner = proptest::test_runner::TestRunner::new(cfg);
         let _ = runner.run(&strat, |_| Ok(()));
     }
+
+    #[test]
+    fn draws_the_same_values_for_a_seed() {
+        let draw = |values: &[u8]| {
+            let state = EvmFuzzState::default();
+            for &value in values {
+                state.write().values_mut().insert([value; 32]);
+                state.write().address_values_mut().insert([value; 32]);
+            }
+            let strat = fuzz_param_from_state(&DynSolType::Address, state);
+            let mut runner = TestRunner::deterministic();
+            (0..20)
+                .map(|_| strat.new_tree(&mut runner).unwrap().current())
+                .collect::<Vec<DynSolValue>>()
+        };
+        // the values are drawn regardless of the order they were collected in
+        assert_eq!(draw(&[1, 2, 3, 4, 5]), draw(&[5, 3, 1, 4, 2]));
+    }
 }
@@ -2,19 +2,19 @@ use super::fuzz_param_from_state;
 use crate::invariant::{ArtifactFilters, FuzzRunIdentifiedContracts};
 use alloy_dyn_abi::{DynSolType, JsonAbiExt};
 use alloy_json_abi::Function;
-use alloy_primitives::{Address, Bytes, Log, B256, U256};
+use alloy_primitives::{hex, Address, Bytes, Log, B256, U256};
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
 use foundry_config::FuzzDictionaryConfig;
-use foundry_evm_core::utils::StateChangeset;
+use foundry_evm_core::{fork::MemDb, utils::StateChangeset};
 use hashbrown::HashSet;
 use parking_lot::RwLock;
 use proptest::prelude::{BoxedStrategy, Strategy};
 use revm::{
     db::{CacheDB, DatabaseRef},
     interpreter::opcode::{self, spec_opcode_gas},
-    primitives::SpecId,
+    primitives::{Bytecode, SpecId},
 };
-use std::{fmt, io::Write, str::FromStr, sync::Arc};
+use std::{collections::BTreeSet, fmt, io::Write, str::FromStr, sync::Arc};
 
 /// A set of arbitrary 32 byte data from the VM used to generate values for the strategy.
 ///
@@ -24,7 +24,11 @@ pub type EvmFuzzState = Arc<RwLock<FuzzDictionary>>;
 #[derive(Default)]
 pub struct FuzzDictionary {
     /// Collected state values.
-    state_values: HashSet<[u8; 32]>,
+    ///
+    /// The values are ordered, so that the values drawn by their index are the same for a seed.
+    state_values: BTreeSet<[u8; 32]>,
+    /// Collected state values that are likely addresses: accounts and PUSH20 constants.
+    address_values: BTreeSet<[u8; 32]>,
     /// Addresses that already had their PUSH bytes collected.
     addresses: HashSet<Address>,
 }
@@ -33,6 +37,7 @@ impl fmt::Debug for FuzzDictionary {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("FuzzDictionary")
             .field("state_values", &self.state_values.len())
+            .field("address_values", &self.address_values.len())
             .field("addresses", &self.addresses)
             .finish()
     }
@@ -40,15 +45,25 @@ impl fmt::Debug for FuzzDictionary {
 
 impl FuzzDictionary {
     #[inline]
-    pub fn values(&self) -> &HashSet<[u8; 32]> {
+    pub fn values(&self) -> &BTreeSet<[u8; 32]> {
         &self.state_values
     }
 
     #[inline]
-    pub fn values_mut(&mut self) -> &mut HashSet<[u8; 32]> {
+    pub fn values_mut(&mut self) -> &mut BTreeSet<[u8; 32]> {
         &mut self.state_values
     }
 
+    #[inline]
+    pub fn address_values(&self) -> &BTreeSet<[u8; 32]> {
+        &self.address_values
+    }
+
+    #[inline]
+    pub fn address_values_mut(&mut self) -> &mut BTreeSet<[u8; 32]> {
+        &mut self.address_values
+    }
+
     #[inline]
     pub fn addresses(&mut self) -> &HashSet<Address> {
         &self.addresses
@@ -58,6 +73,26 @@ impl FuzzDictionary {
     pub fn addresses_mut(&mut self) -> &mut HashSet<Address> {
         &mut self.addresses
     }
+
+    /// Inserts an address, as a state value and as an address value.
+    fn insert_address(&mut self, address: Address) {
+        let word = address.into_word().0;
+        self.state_values.insert(word);
+        self.address_values.insert(word);
+    }
+
+    /// Returns all collected values as sorted hex strings, one per line, with the address values
+    /// marked as such.
+    pub fn dump(&self) -> String {
+        self.state_values
+            .iter()
+            .map(|value| {
+                let kind = if self.address_values.contains(value) { " (address)" } else { "" };
+                format!("{}{kind}", hex::encode_prefixed(value))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 /// Given a function and some state, it returns a strategy which generated valid calldata for the
@@ -97,37 +132,7 @@ pub fn build_initial_state<DB: DatabaseRef>(
     let mut state = FuzzDictionary::default();
 
     for (address, account) in db.accounts.iter() {
-        let address: Address = *address;
-        // Insert basic account information
-        state.values_mut().insert(address.into_word().into());
-
-        // Insert push bytes
-        if config.include_push_bytes {
-            if let Some(code) = &account.info.code {
-                if state.addresses_mut().insert(address) {
-                    for push_byte in collect_push_bytes(code.bytes()) {
-                        state.values_mut().insert(push_byte);
-                    }
-                }
-            }
-        }
-
-        if config.include_storage {
-            // Insert storage
-            for (slot, value) in &account.storage {
-                state.values_mut().insert(B256::from(*slot).0);
-                state.values_mut().insert(B256::from(*value).0);
-                // also add the value below and above the storage value to the dictionary.
-                if *value != U256::ZERO {
-                    let below_value = value - U256::from(1);
-                    state.values_mut().insert(B256::from(below_value).0);
-                }
-                if *value != U256::MAX {
-                    let above_value = value + U256::from(1);
-                    state.values_mut().insert(B256::from(above_value).0);
-                }
-            }
-        }
+        collect_account(&mut state, config, *address, account.info.code.as_ref(), &account.storage);
     }
 
     // need at least some state data if db is empty otherwise we can't select random data for state
@@ -140,6 +145,91 @@ pub fn build_initial_state<DB: DatabaseRef>(
     Arc::new(RwLock::new(state))
 }
 
+/// Collects the accounts fetched from the remote of a fork into an [EvmFuzzState] according to the
+/// given [FuzzDictionaryConfig].
+///
+/// The database of a fork only holds its local changes, the accounts it reads from the remote are
+/// held by this base layer, which is shared by all the forks of the same block and may hold the
+/// whole persistent cache of the block. Only the accounts the state already refers to are
+/// collected, e.g. the ones whose address the target stores or pushes, up to
+/// `max_fuzz_dictionary_addresses` accounts and `max_fuzz_dictionary_values` values.
+pub fn collect_state_from_fork(base: &MemDb, state: &EvmFuzzState, config: &FuzzDictionaryConfig) {
+    let mut state = state.write();
+    let accounts = base.accounts.read();
+    let storage = base.storage.read();
+
+    let mut referenced = accounts
+        .keys()
+        .filter(|address| state.values().contains(&address.into_word().0))
+        .copied()
+        .collect::<Vec<_>>();
+    // the base layer is unordered
+    referenced.sort_unstable();
+    for address in referenced.into_iter().take(config.max_fuzz_dictionary_addresses) {
+        if state.values().len() >= config.max_fuzz_dictionary_values {
+            break
+        }
+        let slots = storage.get(&address).into_iter().flatten();
+        collect_account(&mut state, config, address, accounts[&address].code.as_ref(), slots);
+    }
+}
+
+/// Collects the address, the push bytes of the code and the storage of an account into a
+/// [FuzzDictionary] according to the given [FuzzDictionaryConfig].
+fn collect_account<'a>(
+    state: &mut FuzzDictionary,
+    config: &FuzzDictionaryConfig,
+    address: Address,
+    code: Option<&Bytecode>,
+    storage: impl IntoIterator<Item = (&'a U256, &'a U256)>,
+) {
+    // Insert basic account information
+    state.insert_address(address);
+
+    // Insert push bytes
+    if config.include_push_bytes {
+        if let Some(code) = code {
+            if state.addresses_mut().insert(address) {
+                collect_push_bytes(state, code.bytes());
+            }
+        }
+    }
+
+    if config.include_storage {
+        // Insert storage
+        for (slot, value) in storage {
+            collect_storage_slot(state, *slot, *value);
+        }
+    }
+}
+
+/// Inserts a storage slot and its value, along with small mutations of the value, into a
+/// [FuzzDictionary].
+fn collect_storage_slot(state: &mut FuzzDictionary, slot: U256, value: U256) {
+    state.values_mut().insert(B256::from(slot).0);
+    state.values_mut().insert(B256::from(value).0);
+    insert_mutations(state.values_mut(), value, true);
+}
+
+/// The number of low bits flipped, one at a time, by [insert_mutations].
+const FLIPPED_BITS: usize = 8;
+
+/// Inserts the values below and above `value` and, if `bitflips` is set, the values with one of
+/// its low bits flipped, which are often compared against values close to it.
+fn insert_mutations(values: &mut BTreeSet<[u8; 32]>, value: U256, bitflips: bool) {
+    if value != U256::ZERO {
+        values.insert((value - U256::from(1)).to_be_bytes());
+    }
+    if value != U256::MAX {
+        values.insert((value + U256::from(1)).to_be_bytes());
+    }
+    if bitflips {
+        for bit in 0..FLIPPED_BITS {
+            values.insert((value ^ (U256::from(1) << bit)).to_be_bytes());
+        }
+    }
+}
+
 /// Collects state changes from a [StateChangeset] and logs into an [EvmFuzzState] according to the
 /// given [FuzzDictionaryConfig].
 pub fn collect_state_from_call(
@@ -152,16 +242,14 @@ pub fn collect_state_from_call(
 
     for (address, account) in state_changeset {
         // Insert basic account information
-        state.values_mut().insert(address.into_word().into());
+        state.insert_address(*address);
 
         if config.include_push_bytes && state.addresses.len() < config.max_fuzz_dictionary_addresses
         {
             // Insert push bytes
             if let Some(code) = &account.info.code {
                 if state.addresses_mut().insert(*address) {
-                    for push_byte in collect_push_bytes(code.bytes()) {
-                        state.values_mut().insert(push_byte);
-                    }
+                    collect_push_bytes(&mut state, code.bytes());
                 }
             }
         }
@@ -169,18 +257,7 @@ pub fn collect_state_from_call(
         if config.include_storage && state.state_values.len() < config.max_fuzz_dictionary_values {
             // Insert storage
             for (slot, value) in &account.storage {
-                let value = value.present_value;
-                state.values_mut().insert(B256::from(*slot).0);
-                state.values_mut().insert(B256::from(value).0);
-                // also add the value below and above the storage value to the dictionary.
-                if value != U256::ZERO {
-                    let below_value = value - U256::from(1);
-                    state.values_mut().insert(B256::from(below_value).0);
-                }
-                if value != U256::MAX {
-                    let above_value = value + U256::from(1);
-                    state.values_mut().insert(B256::from(above_value).0);
-                }
+                collect_storage_slot(&mut state, *slot, value.present_value);
             }
         } else {
             return;
@@ -208,9 +285,11 @@ pub fn collect_state_from_call(
 /// bytecode (as is the case with Solmate).
 const PUSH_BYTE_ANALYSIS_LIMIT: usize = 24 * 1024;
 
-/// Collects all push bytes from the given bytecode.
-fn collect_push_bytes(code: &[u8]) -> Vec<[u8; 32]> {
-    let mut bytes: Vec<[u8; 32]> = Vec::new();
+/// Collects all push bytes from the given bytecode into a [FuzzDictionary].
+///
+/// `PUSH20` constants are likely addresses, `PUSH20` and `PUSH32` constants also have their low
+/// bits flipped, as they are often magic values, hashes or addresses compared against inputs.
+fn collect_push_bytes(state: &mut FuzzDictionary, code: &[u8]) {
     // We use [SpecId::LATEST] since we do not really care what spec it is - we are not interested
     // in gas costs.
     let opcode_infos = spec_opcode_gas(SpecId::LATEST);
@@ -224,24 +303,21 @@ fn collect_push_bytes(code: &[u8]) -> Vec<[u8; 32]> {
             // As a precaution, if a fuzz test deploys malformed bytecode (such as using `CREATE2`)
             // this will terminate the loop early.
             if push_start > code.len() || push_end > code.len() {
-                return bytes;
+                return
             }
 
             let push_value = U256::try_from_be_slice(&code[push_start..push_end]).unwrap();
-            bytes.push(push_value.to_be_bytes());
-            // also add the value below and above the push value to the dictionary.
-            if push_value != U256::ZERO {
-                bytes.push((push_value - U256::from(1)).to_be_bytes());
-            }
-            if push_value != U256::MAX {
-                bytes.push((push_value + U256::from(1)).to_be_bytes());
+            state.values_mut().insert(push_value.to_be_bytes());
+            if push_size == 20 {
+                state.address_values_mut().insert(push_value.to_be_bytes());
             }
+            // also add small mutations of the push value to the dictionary.
+            insert_mutations(state.values_mut(), push_value, push_size == 20 || push_size == 32);
 
             i += push_size;
         }
         i += 1;
     }
-    bytes
 }
 
 /// Collects all created contracts from a StateChangeset which haven't been discovered yet. Stores
@@ -5,14 +5,14 @@ use crate::{
     CallTrace, CallTraceArena, CallTraceNode, DecodedCallData, DecodedCallLog, DecodedCallTrace,
 };
 use alloy_dyn_abi::{DecodedEvent, DynSolValue, EventExt, FunctionExt, JsonAbiExt};
-use alloy_json_abi::{Event, Function, JsonAbi};
+use alloy_json_abi::{Error, Event, Function, JsonAbi};
 use alloy_primitives::{Address, LogData, Selector, B256};
 use foundry_common::{abi::get_indexed_event, fmt::format_token, SELECTOR_LEN};
 use foundry_evm_core::{
     abi::{Console, HardhatConsole, Vm, HARDHAT_CONSOLE_SELECTOR_PATCHES},
     constants::{
         CALLER, CHEATCODE_ADDRESS, DEFAULT_CREATE2_DEPLOYER, HARDHAT_CONSOLE_ADDRESS,
-        TEST_CONTRACT_ADDRESS,
+        TEST_CONTRACT_ADDRESS, TRANSIENT_LOAD_TOPIC, TRANSIENT_STORE_TOPIC,
     },
     decode,
 };
@@ -65,10 +65,23 @@ impl CallTraceDecoderBuilder {
         self
     }
 
+    /// Add known errors to the decoder.
+    #[inline]
+    pub fn with_errors(mut self, errors: impl IntoIterator<Item = Error>) -> Self {
+        for error in errors {
+            let known = self.decoder.errors.errors.entry(error.name.clone()).or_default();
+            if !known.contains(&error) {
+                known.push(error);
+            }
+        }
+        self
+    }
+
     #[inline]
     pub fn with_local_identifier_abis(self, identifier: &LocalTraceIdentifier<'_>) -> Self {
         self.with_events(identifier.events().cloned())
             .with_functions(identifier.functions().cloned())
+            .with_errors(identifier.errors().cloned())
     }
 
     /// Sets the verbosity level of the decoder.
@@ -512,6 +525,7 @@ impl CallTraceDecoder {
         let events_it = nodes
             .iter()
             .flat_map(|node| node.logs.iter().filter_map(|log| log.topics().first()))
+            .filter(|topic| **topic != TRANSIENT_STORE_TOPIC && **topic != TRANSIENT_LOAD_TOPIC)
             .unique();
         identifier.write().await.identify_events(events_it).await;
 
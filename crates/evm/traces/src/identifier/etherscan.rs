@@ -4,7 +4,10 @@ use foundry_block_explorers::{
     contract::{ContractMetadata, Metadata},
     errors::EtherscanError,
 };
-use foundry_common::compile::{self, ContractSources};
+use foundry_common::{
+    compile::{self, ContractSources},
+    etherscan::EtherscanPool,
+};
 use foundry_config::{Chain, Config};
 use foundry_evm_core::utils::RuntimeOrHandle;
 use futures::{
@@ -27,8 +30,8 @@ use tokio::time::{Duration, Interval};
 /// A trace identifier that tries to identify addresses using Etherscan.
 #[derive(Default)]
 pub struct EtherscanIdentifier {
-    /// The Etherscan client
-    client: Option<Arc<foundry_block_explorers::Client>>,
+    /// The Etherscan clients of the API keys of the chain
+    pool: Option<EtherscanPool>,
     /// Tracks whether the API key provides was marked as invalid
     ///
     /// After the first [EtherscanError::InvalidApiKey] this will get set to true, so we can
@@ -48,7 +51,7 @@ impl EtherscanIdentifier {
         if let Some(config) = config.get_etherscan_config_with_chain(chain)? {
             trace!(target: "etherscanidentifier", chain=?config.chain, url=?config.api_url, "using etherscan identifier");
             Ok(Self {
-                client: Some(Arc::new(config.into_client()?)),
+                pool: Some(EtherscanPool::new(config)?),
                 invalid_api_key: Arc::new(Default::default()),
                 contracts: BTreeMap::new(),
                 sources: BTreeMap::new(),
@@ -91,11 +94,16 @@ impl EtherscanIdentifier {
                 .0
                 .entry(artifact_id.clone().name)
                 .or_default()
-                .insert(file_id, (metadata.source_code(), bytecode));
+                .insert(file_id, (artifact_id.source, metadata.source_code(), bytecode));
         }
 
         Ok(sources)
     }
+
+    /// Returns the usage of the Etherscan API keys, or `None` if no request was sent.
+    pub fn usage_report(&self) -> Option<String> {
+        self.pool.as_ref()?.usage_report()
+    }
 }
 
 impl TraceIdentifier for EtherscanIdentifier {
@@ -105,7 +113,7 @@ impl TraceIdentifier for EtherscanIdentifier {
     {
         trace!(target: "etherscanidentifier", "identify {:?} addresses", addresses.size_hint().1);
 
-        let Some(client) = self.client.clone() else {
+        let Some(pool) = self.pool.clone() else {
             // no client was configured
             return Vec::new()
         };
@@ -116,7 +124,7 @@ impl TraceIdentifier for EtherscanIdentifier {
         }
 
         let mut fetcher = EtherscanFetcher::new(
-            client,
+            pool,
             Duration::from_secs(1),
             5,
             Arc::clone(&self.invalid_api_key),
@@ -149,14 +157,14 @@ impl TraceIdentifier for EtherscanIdentifier {
 }
 
 type EtherscanFuture =
-    Pin<Box<dyn Future<Output = (Address, Result<ContractMetadata, EtherscanError>)>>>;
+    Pin<Box<dyn Future<Output = (Address, Result<ContractMetadata, Arc<EtherscanError>>)>>>;
 
 /// A rate limit aware Etherscan client.
 ///
 /// Fetches information about multiple addresses concurrently, while respecting rate limits.
 struct EtherscanFetcher {
-    /// The Etherscan client
-    client: Arc<foundry_block_explorers::Client>,
+    /// The Etherscan clients
+    pool: EtherscanPool,
     /// The time we wait if we hit the rate limit
     timeout: Duration,
     /// The interval we are currently waiting for before making a new request
@@ -173,13 +181,13 @@ struct EtherscanFetcher {
 
 impl EtherscanFetcher {
     fn new(
-        client: Arc<foundry_block_explorers::Client>,
+        pool: EtherscanPool,
         timeout: Duration,
         concurrency: usize,
         invalid_api_key: Arc<AtomicBool>,
     ) -> Self {
         Self {
-            client,
+            pool,
             timeout,
             backoff: None,
             concurrency,
@@ -196,10 +204,10 @@ impl EtherscanFetcher {
     fn queue_next_reqs(&mut self) {
         while self.in_progress.len() < self.concurrency {
             if let Some(addr) = self.queue.pop() {
-                let client = Arc::clone(&self.client);
+                let pool = self.pool.clone();
                 trace!(target: "etherscanidentifier", "fetching info for {:?}", addr);
                 self.in_progress.push(Box::pin(async move {
-                    let res = client.contract_source_code(addr).await;
+                    let res = pool.contract_source_code(addr).await;
                     (addr, res)
                 }));
             } else {
@@ -237,26 +245,29 @@ impl Stream for EtherscanFetcher {
                                 return Poll::Ready(Some((addr, item)))
                             }
                         }
-                        Err(EtherscanError::RateLimitExceeded) => {
-                            warn!(target: "etherscanidentifier", "rate limit exceeded on attempt");
-                            pin.backoff = Some(tokio::time::interval(pin.timeout));
-                            pin.queue.push(addr);
-                        }
-                        Err(EtherscanError::InvalidApiKey) => {
-                            warn!(target: "etherscanidentifier", "invalid api key");
-                            // mark key as invalid
-                            pin.invalid_api_key.store(true, Ordering::Relaxed);
-                            return Poll::Ready(None)
-                        }
-                        Err(EtherscanError::BlockedByCloudflare) => {
-                            warn!(target: "etherscanidentifier", "blocked by cloudflare");
-                            // mark key as invalid
-                            pin.invalid_api_key.store(true, Ordering::Relaxed);
-                            return Poll::Ready(None)
-                        }
-                        Err(err) => {
-                            warn!(target: "etherscanidentifier", "could not get etherscan info: {:?}", err);
-                        }
+                        Err(err) => match *err {
+                            EtherscanError::RateLimitExceeded => {
+                                // all the keys of the pool hit the rate limit
+                                warn!(target: "etherscanidentifier", "rate limit exceeded on attempt");
+                                pin.backoff = Some(tokio::time::interval(pin.timeout));
+                                pin.queue.push(addr);
+                            }
+                            EtherscanError::InvalidApiKey => {
+                                warn!(target: "etherscanidentifier", "invalid api key");
+                                // mark key as invalid
+                                pin.invalid_api_key.store(true, Ordering::Relaxed);
+                                return Poll::Ready(None)
+                            }
+                            EtherscanError::BlockedByCloudflare => {
+                                warn!(target: "etherscanidentifier", "blocked by cloudflare");
+                                // mark key as invalid
+                                pin.invalid_api_key.store(true, Ordering::Relaxed);
+                                return Poll::Ready(None)
+                            }
+                            _ => {
+                                warn!(target: "etherscanidentifier", "could not get etherscan info: {:?}", err);
+                            }
+                        },
                     }
                 }
             }
@@ -1,5 +1,5 @@
 use super::{AddressIdentity, TraceIdentifier};
-use alloy_json_abi::{Event, Function};
+use alloy_json_abi::{Error, Event, Function};
 use alloy_primitives::Address;
 use foundry_common::contracts::{diff_score, ContractsByArtifact};
 use ordered_float::OrderedFloat;
@@ -24,6 +24,11 @@ impl<'a> LocalTraceIdentifier<'a> {
     pub fn events(&self) -> impl Iterator<Item = &Event> {
         self.known_contracts.iter().flat_map(|(_, (abi, _))| abi.events())
     }
+
+    /// Get all the errors of the local contracts.
+    pub fn errors(&self) -> impl Iterator<Item = &Error> {
+        self.known_contracts.iter().flat_map(|(_, (abi, _))| abi.errors())
+    }
 }
 
 impl TraceIdentifier for LocalTraceIdentifier<'_> {
@@ -9,7 +9,7 @@ extern crate tracing;
 
 use alloy_primitives::LogData;
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
-use foundry_evm_core::constants::CHEATCODE_ADDRESS;
+use foundry_evm_core::constants::{CHEATCODE_ADDRESS, TRANSIENT_LOAD_TOPIC, TRANSIENT_STORE_TOPIC};
 use futures::{future::BoxFuture, FutureExt};
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, fmt::Write};
@@ -64,6 +64,25 @@ const BRANCH: &str = "  ├─ ";
 const CALL: &str = "→ ";
 const RETURN: &str = "← ";
 
+/// Options of how call traces are rendered.
+///
+/// These only affect the presentation, the traces themselves, e.g. in the JSON output, are always
+/// complete.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TraceRenderOptions {
+    /// Whether to collapse consecutive calls to the same contract with the same calldata, which
+    /// succeeded or failed alike and returned the same data, into the first one of them, annotated
+    /// with the number of calls, e.g. `×500`.
+    pub collapse_repeated: bool,
+    /// The depth from which the calls are hidden, the top-level call being at depth 0.
+    ///
+    /// The hidden calls are summarized by their number and gas.
+    pub max_depth: Option<usize>,
+    /// Whether to render the traces without colors, regardless of whether they are enabled
+    /// globally.
+    pub no_color: bool,
+}
+
 /// Render a collection of call traces.
 ///
 /// The traces will be decoded using the given decoder, if possible.
@@ -71,30 +90,87 @@ pub async fn render_trace_arena(
     arena: &CallTraceArena,
     decoder: &CallTraceDecoder,
 ) -> Result<String, std::fmt::Error> {
-    decoder.prefetch_signatures(arena.nodes()).await;
+    render_trace_arena_with_options(arena, decoder, &TraceRenderOptions::default()).await
+}
+
+/// Render a collection of call traces with the given [TraceRenderOptions].
+///
+/// The traces will be decoded using the given decoder, if possible.
+pub async fn render_trace_arena_with_options(
+    arena: &CallTraceArena,
+    decoder: &CallTraceDecoder,
+    options: &TraceRenderOptions,
+) -> Result<String, std::fmt::Error> {
+    render_trace_nodes(arena.nodes(), decoder, options).await
+}
+
+async fn render_trace_nodes(
+    nodes: &[CallTraceNode],
+    decoder: &CallTraceDecoder,
+    options: &TraceRenderOptions,
+) -> Result<String, std::fmt::Error> {
+    decoder.prefetch_signatures(nodes).await;
 
-    fn inner<'a>(
-        arena: &'a [CallTraceNode],
-        decoder: &'a CallTraceDecoder,
-        s: &'a mut String,
+    let mut s = String::new();
+    let renderer = TraceRenderer { nodes, decoder, options };
+    renderer.render_node(&mut s, 0, 0, 1, "  ", "  ").await?;
+    if options.no_color {
+        s = strip_colors(&s);
+    }
+    Ok(s)
+}
+
+/// Removes the ANSI escape sequences [Paint] wraps the painted items in.
+fn strip_colors(s: &str) -> String {
+    let mut stripped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+struct TraceRenderer<'a> {
+    nodes: &'a [CallTraceNode],
+    decoder: &'a CallTraceDecoder,
+    options: &'a TraceRenderOptions,
+}
+
+impl TraceRenderer<'_> {
+    /// Renders the node at `idx` and its subcalls, `repeats` being the number of identical
+    /// consecutive calls it stands for.
+    fn render_node<'b>(
+        &'b self,
+        s: &'b mut String,
         idx: usize,
-        left: &'a str,
-        child: &'a str,
-    ) -> BoxFuture<'a, Result<(), std::fmt::Error>> {
+        depth: usize,
+        repeats: usize,
+        left: &'b str,
+        child: &'b str,
+    ) -> BoxFuture<'b, Result<(), std::fmt::Error>> {
         async move {
-            let node = &arena[idx];
+            let node = &self.nodes[idx];
 
             // Display trace header
-            let (trace, return_data) = render_trace(&node.trace, decoder).await?;
+            let (mut trace, return_data) = render_trace(&node.trace, self.decoder).await?;
+            if repeats > 1 {
+                write!(trace, " {}", Paint::yellow(format!("×{repeats}")))?;
+            }
             writeln!(s, "{left}{}", trace)?;
 
             // Display logs and subcalls
             let left_prefix = format!("{child}{BRANCH}");
             let right_prefix = format!("{child}{PIPE}");
-            for child in &node.ordering {
-                match child {
+            let hide_calls = self.options.max_depth.is_some_and(|max| depth >= max);
+            let mut ordering = node.ordering.iter().peekable();
+            while let Some(order) = ordering.next() {
+                match order {
                     LogCallOrder::Log(index) => {
-                        let log = render_trace_log(&node.logs[*index], decoder).await?;
+                        let log = render_trace_log(&node.logs[*index], self.decoder).await?;
 
                         // Prepend our tree structure symbols to each line of the displayed log
                         log.lines().enumerate().try_for_each(|(i, line)| {
@@ -106,20 +182,34 @@ pub async fn render_trace_arena(
                             )
                         })?;
                     }
+                    LogCallOrder::Call(_) if hide_calls => {}
                     LogCallOrder::Call(index) => {
-                        inner(
-                            arena,
-                            decoder,
-                            s,
-                            node.children[*index],
-                            &left_prefix,
-                            &right_prefix,
-                        )
-                        .await?;
+                        let call = node.children[*index];
+                        let mut repeats = 1;
+                        if self.options.collapse_repeated {
+                            while let Some(LogCallOrder::Call(next)) = ordering.peek() {
+                                let next = node.children[*next];
+                                if !is_repeated_call(&self.nodes[call], &self.nodes[next]) {
+                                    break
+                                }
+                                ordering.next();
+                                repeats += 1;
+                            }
+                        }
+                        self.render_node(s, call, depth + 1, repeats, &left_prefix, &right_prefix)
+                            .await?;
                     }
                 }
             }
 
+            if hide_calls && !node.children.is_empty() {
+                let gas: u64 = node.children.iter().map(|&i| self.nodes[i].trace.gas_used).sum();
+                let calls = self.count_calls(idx);
+                let summary =
+                    format!("… {calls} nested call{} hidden", if calls == 1 { "" } else { "s" });
+                writeln!(s, "{left_prefix}[{gas}] {}", Paint::new(summary).dimmed())?;
+            }
+
             // Display trace return data
             let color = trace_color(&node.trace);
             write!(s, "{child}{EDGE}{}", color.paint(RETURN))?;
@@ -145,9 +235,23 @@ pub async fn render_trace_arena(
         .boxed()
     }
 
-    let mut s = String::new();
-    inner(arena.nodes(), decoder, &mut s, 0, "  ", "  ").await?;
-    Ok(s)
+    /// Returns the number of calls nested in the node at `idx`.
+    fn count_calls(&self, idx: usize) -> usize {
+        self.nodes[idx].children.iter().map(|&i| 1 + self.count_calls(i)).sum()
+    }
+}
+
+/// Returns whether the `next` call repeats the `previous` one, i.e. it calls the same contract with
+/// the same calldata and has the same outcome.
+fn is_repeated_call(previous: &CallTraceNode, next: &CallTraceNode) -> bool {
+    let (previous, next) = (&previous.trace, &next.trace);
+    !previous.kind.is_any_create() &&
+        previous.kind == next.kind &&
+        previous.address == next.address &&
+        previous.value == next.value &&
+        previous.data == next.data &&
+        previous.success == next.success &&
+        previous.output == next.output
 }
 
 /// Render a call trace.
@@ -220,6 +324,17 @@ async fn render_trace_log(
     decoder: &CallTraceDecoder,
 ) -> Result<String, std::fmt::Error> {
     let mut s = String::new();
+    if let [topic, slot, value] = log.topics() {
+        if *topic == TRANSIENT_STORE_TOPIC {
+            write!(s, "{} {slot} = {value}", Paint::magenta("[tstore]"))?;
+            return Ok(s)
+        }
+        if *topic == TRANSIENT_LOAD_TOPIC {
+            write!(s, "{} {slot} => {value}", Paint::magenta("[tload]"))?;
+            return Ok(s)
+        }
+    }
+
     let decoded = decoder.decode_event(log).await;
 
     match decoded {
@@ -317,3 +432,135 @@ pub fn load_contracts(
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, Bytes};
+
+    fn call(address: u8, data: &[u8], output: &[u8], gas_used: u64) -> CallTrace {
+        CallTrace {
+            address: Address::repeat_byte(address),
+            data: Bytes::copy_from_slice(data),
+            output: Bytes::copy_from_slice(output),
+            gas_used,
+            success: true,
+            ..Default::default()
+        }
+    }
+
+    fn node(idx: usize, trace: CallTrace, children: Vec<usize>) -> CallTraceNode {
+        let ordering = (0..children.len()).map(LogCallOrder::Call).collect();
+        CallTraceNode { idx, trace, children, ordering, ..Default::default() }
+    }
+
+    // a root call which calls the same function four times, twice with the same arguments and
+    // result, then with other arguments and the same result, and then with the same arguments and
+    // another result, and then another function with a nested call
+    fn nodes() -> Vec<CallTraceNode> {
+        let (one, two) = ([0x12, 0x34, 0x56, 0x78, 1], [0x12, 0x34, 0x56, 0x78, 2]);
+        vec![
+            node(0, call(0xaa, &[0xaa, 0xbb, 0xcc, 0xdd], &[], 100_000), vec![1, 2, 3, 4, 5]),
+            node(1, call(0xbb, &one, &[1], 100), vec![]),
+            node(2, call(0xbb, &one, &[1], 100), vec![]),
+            node(3, call(0xbb, &two, &[1], 100), vec![]),
+            node(4, call(0xbb, &two, &[2], 100), vec![]),
+            node(5, call(0xbb, &[0x99; 4], &[], 300), vec![6]),
+            node(6, call(0xcc, &[0x12, 0x34, 0x56, 0x78], &[], 50), vec![]),
+        ]
+    }
+
+    fn render(options: TraceRenderOptions) -> String {
+        let decoder = CallTraceDecoderBuilder::new()
+            .with_labels([
+                (Address::repeat_byte(0xaa), "Root".to_string()),
+                (Address::repeat_byte(0xbb), "Counter".to_string()),
+                (Address::repeat_byte(0xcc), "Leaf".to_string()),
+            ])
+            .build();
+        let options = TraceRenderOptions { no_color: true, ..options };
+        futures::executor::block_on(render_trace_nodes(&nodes(), &decoder, &options)).unwrap()
+    }
+
+    #[test]
+    fn collapses_repeated_calls() {
+        let expected = "  [100000] Root::aabbccdd()
+    ├─ [100] Counter::12345678(01)
+    │   └─ ← 0x01
+    ├─ [100] Counter::12345678(01)
+    │   └─ ← 0x01
+    ├─ [100] Counter::12345678(02)
+    │   └─ ← 0x01
+    ├─ [100] Counter::12345678(02)
+    │   └─ ← 0x02
+    ├─ [300] Counter::99999999()
+    │   ├─ [50] Leaf::12345678()
+    │   │   └─ ← ()
+    │   └─ ← ()
+    └─ ← ()
+";
+        assert_eq!(render(TraceRenderOptions::default()), expected);
+
+        let options = TraceRenderOptions { collapse_repeated: true, ..Default::default() };
+        let expected = "  [100000] Root::aabbccdd()
+    ├─ [100] Counter::12345678(01) ×2
+    │   └─ ← 0x01
+    ├─ [100] Counter::12345678(02)
+    │   └─ ← 0x01
+    ├─ [100] Counter::12345678(02)
+    │   └─ ← 0x02
+    ├─ [300] Counter::99999999()
+    │   ├─ [50] Leaf::12345678()
+    │   │   └─ ← ()
+    │   └─ ← ()
+    └─ ← ()
+";
+        assert_eq!(render(options), expected);
+    }
+
+    #[test]
+    fn hides_deeper_calls() {
+        let options = TraceRenderOptions {
+            collapse_repeated: true,
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let expected = "  [100000] Root::aabbccdd()
+    ├─ [100] Counter::12345678(01) ×2
+    │   └─ ← 0x01
+    ├─ [100] Counter::12345678(02)
+    │   └─ ← 0x01
+    ├─ [100] Counter::12345678(02)
+    │   └─ ← 0x02
+    ├─ [300] Counter::99999999()
+    │   ├─ [50] … 1 nested call hidden
+    │   └─ ← ()
+    └─ ← ()
+";
+        assert_eq!(render(options), expected);
+
+        let options = TraceRenderOptions { max_depth: Some(0), ..Default::default() };
+        let expected = "  [100000] Root::aabbccdd()
+    ├─ [700] … 6 nested calls hidden
+    └─ ← ()
+";
+        assert_eq!(render(options), expected);
+    }
+
+    #[test]
+    fn renders_colors_unless_disabled() {
+        let options = TraceRenderOptions { collapse_repeated: true, ..Default::default() };
+        let nodes = nodes();
+        let decoder = CallTraceDecoderBuilder::new().build();
+        let render = |options: TraceRenderOptions| {
+            futures::executor::block_on(render_trace_nodes(&nodes, &decoder, &options)).unwrap()
+        };
+        let colored = render(options);
+        let plain = render(TraceRenderOptions { no_color: true, ..options });
+        if Paint::is_enabled() {
+            assert!(colored.contains('\x1b'));
+        }
+        assert!(!plain.contains('\x1b'));
+        assert_eq!(strip_colors(&colored), plain);
+    }
+}
@@ -7,6 +7,7 @@ use crate::{
         CommentPosition, CommentState, CommentStringExt, CommentType, CommentWithMetadata, Comments,
     },
     helpers::import_path_string,
+    imports,
     macros::*,
     solang_ext::{pt::*, *},
     string::{QuoteState, QuotedStringExt},
@@ -14,7 +15,9 @@ use crate::{
     FormatterConfig, InlineConfig, IntTypes,
 };
 use alloy_primitives::Address;
-use foundry_config::fmt::{HexUnderscore, MultilineFuncHeaderStyle, SingleLineBlockStyle};
+use foundry_config::fmt::{
+    CallArgWrapping, HexUnderscore, MultilineFuncHeaderStyle, SingleLineBlockStyle,
+};
 use itertools::{Either, Itertools};
 use solang_parser::pt::ImportPath;
 use std::{fmt::Write, str::FromStr};
@@ -747,6 +750,41 @@ impl<'a, W: Write> Formatter<'a, W> {
         Ok(())
     }
 
+    /// Write chunks separated by a separator. Each chunk is written to the current line if it fits
+    /// together with the separator following it, otherwise it is put on to the next line
+    fn write_chunks_filled<'b>(
+        &mut self,
+        chunks: impl IntoIterator<Item = &'b Chunk>,
+        separator: &str,
+    ) -> Result<()> {
+        let mut chunks = chunks.into_iter().peekable();
+        while let Some(chunk) = chunks.next() {
+            let mut chunk = chunk.clone();
+
+            // handle postfixes before and add newline if the chunk doesn't fit
+            self.write_comments(&std::mem::take(&mut chunk.postfixes_before))?;
+            let is_last = chunks.peek().is_none();
+            if !self.is_beginning_of_line() {
+                let format_string =
+                    if is_last { "{}".to_string() } else { format!("{{}}{separator}") };
+                if !self.will_chunk_fit(&format_string, &chunk)? {
+                    writeln!(self.buf())?;
+                }
+            }
+
+            // remove postfixes so we can add separator between
+            let postfixes = std::mem::take(&mut chunk.postfixes);
+
+            self.write_chunk(&chunk)?;
+
+            if !is_last {
+                write!(self.buf(), "{separator}")?;
+            }
+            self.write_comments(&postfixes)?;
+        }
+        Ok(())
+    }
+
     /// Apply the callback indented by the indent size
     fn indented(&mut self, delta: usize, fun: impl FnMut(&mut Self) -> Result<()>) -> Result<()> {
         self.indented_if(true, delta, fun)
@@ -1125,6 +1163,45 @@ impl<'a, W: Write> Formatter<'a, W> {
         end_offset: Option<usize>,
         paren_required: bool,
     ) -> Result<()>
+    where
+        T: Visitable + CodeLocation,
+    {
+        self.visit_list_wrapped(
+            prefix,
+            items,
+            start_offset,
+            end_offset,
+            paren_required,
+            CallArgWrapping::Compact,
+        )
+    }
+
+    /// Visit the list of call arguments, wrapping them according to `config.call_arg_wrapping`
+    /// if they don't fit on the current line.
+    fn visit_call_args<T>(
+        &mut self,
+        items: &mut [T],
+        start_offset: Option<usize>,
+        end_offset: Option<usize>,
+    ) -> Result<()>
+    where
+        T: Visitable + CodeLocation,
+    {
+        let wrapping = self.config.call_arg_wrapping;
+        self.visit_list_wrapped("", items, start_offset, end_offset, true, wrapping)
+    }
+
+    /// Visit the list of comma separated items and wrap them with the given style if they don't
+    /// fit on a single line. See [Self::visit_list].
+    fn visit_list_wrapped<T>(
+        &mut self,
+        prefix: &str,
+        items: &mut [T],
+        start_offset: Option<usize>,
+        end_offset: Option<usize>,
+        paren_required: bool,
+        wrapping: CallArgWrapping,
+    ) -> Result<()>
     where
         T: Visitable + CodeLocation,
     {
@@ -1147,9 +1224,16 @@ impl<'a, W: Write> Formatter<'a, W> {
             self.surrounded(first_surrounding, last_surronding, |fmt, multiline| {
                 let args =
                     fmt.items_to_chunks(end_offset, items.iter_mut().map(|arg| (arg.loc(), arg)))?;
-                let multiline =
-                    multiline && fmt.are_chunks_separated_multiline("{}", &args, ",")?;
-                fmt.write_chunks_separated(&args, ",", multiline)?;
+                match wrapping {
+                    CallArgWrapping::Compact => {
+                        let multiline =
+                            multiline && fmt.are_chunks_separated_multiline("{}", &args, ",")?;
+                        fmt.write_chunks_separated(&args, ",", multiline)?;
+                    }
+                    CallArgWrapping::All => fmt.write_chunks_separated(&args, ",", multiline)?,
+                    CallArgWrapping::Fill if multiline => fmt.write_chunks_filled(&args, ",")?,
+                    CallArgWrapping::Fill => fmt.write_chunks_separated(&args, ",", false)?,
+                }
                 Ok(())
             })?;
         }
@@ -1490,13 +1574,20 @@ impl<'a, W: Write> Formatter<'a, W> {
                             fmt.config.multiline_func_header,
                             MultilineFuncHeaderStyle::ParamsFirst | MultilineFuncHeaderStyle::All
                         );
+                    let single_line_params = fmt.config.multiline_func_header ==
+                        MultilineFuncHeaderStyle::ParamsSingleLine;
                     params_multiline = should_multiline ||
-                        multiline ||
-                        fmt.are_chunks_separated_multiline(
-                            &format!("{{}}){after_params}"),
-                            &params,
-                            ",",
-                        )?;
+                        if multiline && single_line_params {
+                            // the params are only split if they don't fit on their own line
+                            fmt.are_chunks_separated_multiline("{}", &params, ",")?
+                        } else {
+                            multiline ||
+                                fmt.are_chunks_separated_multiline(
+                                    &format!("{{}}){after_params}"),
+                                    &params,
+                                    ",",
+                                )?
+                        };
                     fmt.write_chunks_separated(&params, ",", params_multiline)?;
                     Ok(())
                 },
@@ -1578,7 +1669,8 @@ impl<'a, W: Write> Formatter<'a, W> {
             } else {
                 matches!(
                     self.config.multiline_func_header,
-                    MultilineFuncHeaderStyle::AttributesFirst
+                    MultilineFuncHeaderStyle::AttributesFirst |
+                        MultilineFuncHeaderStyle::ParamsSingleLine
                 )
             };
         let attrs_multiline = should_multiline ||
@@ -1695,11 +1787,7 @@ impl<'a, W: Write> Formatter<'a, W> {
             }
 
             import_directives.sort_by_cached_key(|item| match item {
-                SourceUnitPart::ImportDirective(import) => match import {
-                    Import::Plain(path, _) => path.to_string(),
-                    Import::GlobalSymbol(path, _, _) => path.to_string(),
-                    Import::Rename(path, _, _) => path.to_string(),
-                },
+                SourceUnitPart::ImportDirective(import) => imports::sort_key(import),
                 _ => {
                     unreachable!("import group contains non-import statement")
                 }
@@ -2492,7 +2580,7 @@ impl<'a, W: Write> Visitor for Formatter<'a, W> {
             }
             Expression::FunctionCall(loc, expr, exprs) => {
                 self.visit_expr(expr.loc(), expr)?;
-                self.visit_list("", exprs, Some(expr.loc().end()), Some(loc.end()), true)?;
+                self.visit_call_args(exprs, Some(expr.loc().end()), Some(loc.end()))?;
             }
             Expression::NamedFunctionCall(loc, expr, args) => {
                 self.visit_expr(expr.loc(), expr)?;
@@ -2691,7 +2779,7 @@ impl<'a, W: Write> Visitor for Formatter<'a, W> {
         if let Some(error) = error {
             error.visit(self)?;
         }
-        self.visit_list("", args, None, Some(loc.end()), true)?;
+        self.visit_call_args(args, None, Some(loc.end()))?;
         self.write_semicolon()?;
 
         Ok(())
@@ -3555,7 +3643,7 @@ impl<'a, W: Write> Visitor for Formatter<'a, W> {
     fn visit_yul_function_call(&mut self, stmt: &mut YulFunctionCall) -> Result<(), Self::Error> {
         return_source_if_disabled!(self, stmt.loc);
         write_chunk!(self, stmt.loc.start(), "{}", stmt.id.name)?;
-        self.visit_list("", &mut stmt.arguments, None, Some(stmt.loc.end()), true)
+        self.visit_call_args(&mut stmt.arguments, None, Some(stmt.loc.end()))
     }
 
     #[instrument(name = "yul_fun_def", skip_all)]
@@ -1,4 +1,5 @@
 use crate::{
+    imports::group_imports,
     inline_config::{InlineConfig, InvalidInlineConfigItem},
     Comments, Formatter, FormatterConfig, FormatterError, Visitable,
 };
@@ -35,10 +36,21 @@ pub fn parse(src: &str) -> Result<Parsed, Vec<Diagnostic>> {
 /// Format parsed code
 pub fn format_to<W: Write>(
     writer: W,
-    mut parsed: Parsed,
+    parsed: Parsed,
     config: FormatterConfig,
 ) -> Result<(), FormatterError> {
     trace!(?parsed, ?config, "Formatting");
+
+    // imports are regrouped in the source, so that comments move along with them
+    let grouped = config.sort_imports.then(|| group_imports(&parsed, &config)).flatten();
+    let mut parsed = match &grouped {
+        Some(src) => parse(src).map_err(|err| {
+            debug!(?err, "Parse error");
+            FormatterError::Fmt(std::fmt::Error)
+        })?,
+        None => parsed,
+    };
+
     let mut formatter =
         Formatter::new(writer, parsed.src, parsed.comments, parsed.inline_config, config);
     parsed.pt.visit(&mut formatter)
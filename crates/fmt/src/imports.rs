@@ -0,0 +1,219 @@
+//! Grouping of import directives, see [`FormatterConfig::sort_imports`].
+
+use crate::{helpers::import_path_string, FormatterConfig, Parsed};
+use solang_parser::pt::{CodeLocation, Import, Loc, SourceUnitPart};
+
+/// Prefixes of project absolute import paths.
+const PROJECT_PREFIXES: &[&str] = &["src/", "test/", "script/"];
+
+/// An import directive together with the comment on the same line after it.
+#[derive(Debug)]
+struct ImportChunk {
+    /// The index of the group the import belongs to
+    group: usize,
+    /// The key the import is sorted by within its group
+    key: String,
+    /// The start of the chunk in the source
+    start: usize,
+    /// The end of the chunk in the source
+    end: usize,
+}
+
+/// Returns the key imports are sorted by.
+pub(crate) fn sort_key(import: &Import) -> String {
+    match import {
+        Import::Plain(path, _) => path.to_string(),
+        Import::GlobalSymbol(path, _, _) => path.to_string(),
+        Import::Rename(path, _, _) => path.to_string(),
+    }
+}
+
+/// Returns the index of the group the import path belongs to.
+fn group_index(path: &str, groups: &[String]) -> usize {
+    let is_relative = path.starts_with("./") || path.starts_with("../");
+    if groups.is_empty() {
+        if is_relative {
+            2
+        } else if PROJECT_PREFIXES.iter().any(|prefix| path.starts_with(prefix)) {
+            1
+        } else {
+            0
+        }
+    } else if is_relative {
+        groups.len() + 1
+    } else {
+        groups.iter().position(|prefix| path.starts_with(prefix.as_str())).unwrap_or(groups.len())
+    }
+}
+
+/// Returns the source with the imports of every group sorted and split by kind, or `None` if all
+/// imports are in order already.
+///
+/// A group is a set of consecutive import directives, which is ended by a blank line or a comment
+/// on its own line, so the groups of the source are kept. Within a group, imports are sorted by
+/// kind, see [`FormatterConfig::import_groups`], and by path, and a blank line is inserted where
+/// the kind changes. Comments on the same line after an import move with the import. Groups that
+/// contain inline config items are left as is.
+pub(crate) fn group_imports(parsed: &Parsed, config: &FormatterConfig) -> Option<String> {
+    let src = parsed.src;
+
+    let mut groups = Vec::new();
+    let mut current: Vec<ImportChunk> = Vec::new();
+    for part in &parsed.pt.0 {
+        let SourceUnitPart::ImportDirective(import) = part else {
+            groups.push(std::mem::take(&mut current));
+            continue
+        };
+
+        let loc = part.loc();
+        if let Some(last) = current.last() {
+            // a blank line or a comment between two imports ends the group
+            let between = &src[last.end..loc.start()];
+            if !between.trim().is_empty() || between.matches('\n').count() > 1 {
+                groups.push(std::mem::take(&mut current));
+            }
+        }
+
+        // the loc doesn't include the semicolon
+        let mut end = src[loc.end()..].find(';').map_or(loc.end(), |semi| loc.end() + semi + 1);
+        if let Some(comment) = parsed.comments.iter().find(|comment| comment.loc.start() >= end) {
+            if !src[end..comment.loc.start()].contains('\n') {
+                end = comment.loc.end();
+            }
+        }
+
+        let (Import::Plain(path, _) |
+        Import::GlobalSymbol(path, _, _) |
+        Import::Rename(path, _, _)) = import;
+        current.push(ImportChunk {
+            group: group_index(&import_path_string(path), &config.import_groups),
+            key: sort_key(import),
+            start: loc.start(),
+            end,
+        });
+    }
+    groups.push(current);
+
+    let mut grouped = String::with_capacity(src.len());
+    let mut last_end = 0;
+    let mut changed = false;
+    for mut group in groups {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else { continue };
+        let (start, end) = (first.start, last.end);
+
+        let has_inline_config = parsed.comments.iter().any(|comment| {
+            comment.loc.start() >= start &&
+                comment.loc.end() <= end &&
+                comment.contents().trim_start().starts_with("forgefmt:")
+        });
+        if has_inline_config || parsed.inline_config.is_disabled(Loc::File(0, start, end)) {
+            continue
+        }
+
+        let is_sorted = group
+            .windows(2)
+            .all(|pair| pair[0].group == pair[1].group && pair[0].key <= pair[1].key);
+        if is_sorted {
+            continue
+        }
+        changed = true;
+
+        group.sort_by(|a, b| a.group.cmp(&b.group).then_with(|| a.key.cmp(&b.key)));
+        grouped.push_str(&src[last_end..start]);
+        for (i, chunk) in group.iter().enumerate() {
+            if i > 0 {
+                grouped.push_str(if group[i - 1].group == chunk.group { "\n" } else { "\n\n" });
+            }
+            grouped.push_str(&src[chunk.start..chunk.end]);
+        }
+        last_end = end;
+    }
+    grouped.push_str(&src[last_end..]);
+
+    changed.then_some(grouped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn group(src: &str, config: &FormatterConfig) -> Option<String> {
+        let parsed = parse(src).unwrap();
+        group_imports(&parsed, config)
+    }
+
+    #[test]
+    fn can_group_imports() {
+        let config = FormatterConfig { sort_imports: true, ..Default::default() };
+        let src = r#"import "./Local.sol";
+import {B, A} from "src/B.sol";
+import "forge-std/Test.sol"; // trailing
+import "@openzeppelin/token/ERC20.sol";
+"#;
+        let expected = r#"import "@openzeppelin/token/ERC20.sol";
+import "forge-std/Test.sol"; // trailing
+
+import {B, A} from "src/B.sol";
+
+import "./Local.sol";
+"#;
+        let grouped = group(src, &config).unwrap();
+        assert_eq!(grouped, expected);
+        assert_eq!(group(&grouped, &config), None);
+    }
+
+    #[test]
+    fn keeps_groups_of_the_source() {
+        let config = FormatterConfig { sort_imports: true, ..Default::default() };
+        let src = r#"import "./Local.sol";
+
+import "b.sol";
+// separates the groups
+import "a.sol";
+import "src/A.sol";
+"#;
+        let expected = r#"import "./Local.sol";
+
+import "b.sol";
+// separates the groups
+import "a.sol";
+
+import "src/A.sol";
+"#;
+        assert_eq!(group(src, &config).unwrap(), expected);
+    }
+
+    #[test]
+    fn can_group_imports_by_custom_prefix() {
+        let config = FormatterConfig {
+            sort_imports: true,
+            import_groups: vec!["forge-std/".to_string(), "src/".to_string()],
+            ..Default::default()
+        };
+        let src = r#"import "../Parent.sol";
+import "src/A.sol";
+import "solmate/Token.sol";
+import "forge-std/Test.sol";
+"#;
+        let expected = r#"import "forge-std/Test.sol";
+
+import "src/A.sol";
+
+import "solmate/Token.sol";
+
+import "../Parent.sol";
+"#;
+        assert_eq!(group(src, &config).unwrap(), expected);
+    }
+
+    #[test]
+    fn skips_blocks_with_inline_config() {
+        let config = FormatterConfig { sort_imports: true, ..Default::default() };
+        let src = r#"import "b.sol";
+// forgefmt: disable-next-line
+import "a.sol";
+"#;
+        assert_eq!(group(src, &config), None);
+    }
+}
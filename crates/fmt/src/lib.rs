@@ -9,6 +9,7 @@ pub mod chunk;
 mod comments;
 mod formatter;
 mod helpers;
+mod imports;
 pub mod inline_config;
 mod macros;
 pub mod solang_ext;
@@ -0,0 +1,41 @@
+// config: line_length = 120
+// config: call_arg_wrapping = "fill"
+contract FunctionCall {
+    function foo() public pure {
+        bar(1111111111111111111111111111111111111111111111111111, 111111111111111111111111111111111111111111111111111);
+        bar(1111111111111111111111111111111111111111111111111112, 1111111111111111111111111111111111111111111111111112);
+        bar(1111111111111111111111111111111111111111111111111113, 11111111111111111111111111111111111111111111111111113); // the semicolon is not considered when determining line break
+        bar(
+            1111111111111111111111111111111111111111111111111114, 111111111111111111111111111111111111111111111111111114
+        );
+        bar(
+            111111111111111111111111111111111115, 11111111111111111111111111111111115,
+            11111111111111111111111111111111115
+        );
+        bar(
+            111111111111111111111111111111111111111111111111111116,
+            111111111111111111111111111111111111111111111111111116
+        );
+        bar(
+            111111111111111111111111111111111111111111111111111117,
+            1111111111111111111111111111111111111111111111111111117
+        );
+    }
+
+    function bar(uint256, uint256) private pure {
+        return;
+    }
+}
+
+function a(uint256 foo) {
+    foo;
+    MyContract c = new MyContract(address(0), hex"beef");
+}
+
+function b() {
+    a({foo: 5});
+}
+
+contract MyContract {
+    constructor(address arg, bytes memory data) {}
+}
@@ -0,0 +1,20 @@
+// config: line_length = 60
+// config: multiline_func_header = "attributes_first"
+contract FunctionHeaders {
+    function transfer(address to, uint256 amount)
+        external
+        returns (bool)
+    {}
+
+    function transferFrom(
+        address from,
+        address to,
+        uint256 amount
+    ) external returns (bool) {}
+
+    function batch(
+        address[] memory targets,
+        uint256[] memory values,
+        bytes[] memory data
+    ) external {}
+}
@@ -0,0 +1,7 @@
+contract FunctionHeaders {
+    function transfer(address to, uint256 amount) external returns (bool) {}
+
+    function transferFrom(address from, address to, uint256 amount) external returns (bool) {}
+
+    function batch(address[] memory targets, uint256[] memory values, bytes[] memory data) external {}
+}
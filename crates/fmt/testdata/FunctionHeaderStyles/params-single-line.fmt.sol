@@ -0,0 +1,21 @@
+// config: line_length = 60
+// config: multiline_func_header = "params_single_line"
+contract FunctionHeaders {
+    function transfer(address to, uint256 amount)
+        external
+        returns (bool)
+    {}
+
+    function transferFrom(
+        address from, address to, uint256 amount
+    )
+        external
+        returns (bool)
+    {}
+
+    function batch(
+        address[] memory targets,
+        uint256[] memory values,
+        bytes[] memory data
+    ) external {}
+}
@@ -0,0 +1,19 @@
+// config: sort_imports = true
+// config: import_groups = ["forge-std/", "src/", "@openzeppelin/"]
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+// dependencies and project contracts
+import {Test, console2} from "forge-std/Test.sol";
+
+import {Strategy} from "src/Strategy.sol";
+
+import {ERC20, IERC20} from "@openzeppelin/contracts/token/ERC20/ERC20.sol";
+
+import {Math} from "solmate/utils/Math.sol";
+import {Base} from "test/Base.t.sol";
+
+import {Vault} from "../src/Vault.sol";
+import "./utils/Helpers.sol"; // test helpers
+
+contract VaultTest is Test {}
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+// dependencies and project contracts
+import {Vault} from "../src/Vault.sol";
+import {Test, console2} from "forge-std/Test.sol";
+import "./utils/Helpers.sol"; // test helpers
+import {ERC20, IERC20} from "@openzeppelin/contracts/token/ERC20/ERC20.sol";
+import {Strategy} from "src/Strategy.sol";
+import {Math} from "solmate/utils/Math.sol";
+import {Base} from "test/Base.t.sol";
+
+contract VaultTest is Test {}
@@ -189,6 +189,7 @@ test_directories! {
     EventDefinition,
     FunctionDefinition,
     FunctionDefinitionWithFunctionReturns,
+    FunctionHeaderStyles,
     FunctionType,
     ImportDirective,
     ModifierDefinition,
@@ -235,3 +236,19 @@ test_directories! {
 }
 
 test_dir!(SortedImports, TestConfig::skip_compare_ast_eq());
+test_dir!(ImportGroups, TestConfig::skip_compare_ast_eq());
+
+#[test]
+fn sorted_imports_are_idempotent() {
+    let config = FormatterConfig { sort_imports: true, ..Default::default() };
+    for dir in ["SortedImports", "ImportGroups"] {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata").join(dir);
+        let source = fs::read_to_string(path.join("original.sol")).unwrap();
+
+        let mut once = String::new();
+        format_to(&mut once, parse(&source).unwrap(), config.clone()).unwrap();
+        let mut twice = String::new();
+        format_to(&mut twice, parse(&once).unwrap(), config.clone()).unwrap();
+        pretty_assertions::assert_eq!(once, twice, "formatting is not idempotent in {dir}");
+    }
+}
@@ -0,0 +1,377 @@
+//! Generation of [alloy](https://github.com/alloy-rs/core) bindings based on the `sol!` macro.
+
+use alloy_json_abi::JsonAbi;
+use alloy_primitives::hex;
+use ethers_contract::ContractFilter;
+use eyre::{Result, WrapErr};
+use foundry_cli::utils::abi_to_solidity;
+use foundry_common::fs::{files_with_ext, json_files};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The header of every generated file.
+const GENERATED_HEADER: &str = "\
+//! This is autogenerated code.
+//! Do not manually edit these files.
+//! These files may be overwritten by the codegen system at any time.";
+
+/// The `sol!` bindings of a single contract.
+#[derive(Clone, Debug)]
+pub struct SolMacroGen {
+    /// The name of the contract.
+    pub name: String,
+    /// The ABI of the contract.
+    pub abi: JsonAbi,
+    /// The creation bytecode, if the contract is deployable.
+    pub bytecode: Option<Vec<u8>>,
+    /// The runtime bytecode, if the contract is deployable.
+    pub deployed_bytecode: Option<Vec<u8>>,
+}
+
+impl SolMacroGen {
+    /// Loads the bindings of the contract from its artifact.
+    ///
+    /// Returns `None` if the file is not a contract artifact.
+    pub fn from_artifact(path: &Path) -> Result<Option<Self>> {
+        let artifact: serde_json::Value = foundry_common::fs::read_json_file(path)?;
+        if artifact.get("abi").is_none() {
+            return Ok(None)
+        }
+
+        // artifacts of contracts compiled with multiple solc versions are suffixed with the
+        // version
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.split('.').next())
+            .ok_or_else(|| eyre::eyre!("invalid artifact path: {}", path.display()))?
+            .to_string();
+        let abi = serde_json::from_value(artifact["abi"].clone())
+            .wrap_err_with(|| format!("failed to parse ABI from file: {}", path.display()))?;
+        let bytecode = |key: &str| {
+            artifact[key]["object"]
+                .as_str()
+                .and_then(|code| hex::decode(code).ok())
+                .filter(|code| !code.is_empty())
+        };
+        Ok(Some(Self {
+            name,
+            abi,
+            bytecode: bytecode("bytecode"),
+            deployed_bytecode: bytecode("deployedBytecode"),
+        }))
+    }
+
+    /// Returns the name of the module of the bindings.
+    pub fn module_name(&self) -> String {
+        module_name(&self.name)
+    }
+
+    /// Returns the Rust source of the bindings, without the file header.
+    pub fn expand(&self) -> Result<String> {
+        let sol = abi_to_solidity(&self.abi, &self.name)?;
+
+        let mut out = String::new();
+        writeln!(out, "alloy_sol_types::sol! {{")?;
+        for line in sol.trim_end().lines() {
+            if line.is_empty() {
+                writeln!(out)?;
+            } else {
+                writeln!(out, "    {line}")?;
+            }
+        }
+        writeln!(out, "}}")?;
+
+        let constants = [
+            ("BYTECODE", "creation", &self.bytecode),
+            ("DEPLOYED_BYTECODE", "runtime", &self.deployed_bytecode),
+        ];
+        for (constant, kind, code) in constants {
+            if let Some(code) = code {
+                writeln!(out)?;
+                writeln!(out, "/// The {kind} bytecode of the `{}` contract.", self.name)?;
+                writeln!(
+                    out,
+                    "pub static {constant}: alloy_primitives::Bytes = \
+                     alloy_primitives::bytes!(\"{}\");",
+                    hex::encode(code)
+                )?;
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// The `sol!` bindings of multiple contracts.
+#[derive(Clone, Debug, Default)]
+pub struct MultiSolMacroGen {
+    /// The bindings, sorted by contract name.
+    pub contracts: Vec<SolMacroGen>,
+}
+
+impl MultiSolMacroGen {
+    /// Loads the bindings of all contract artifacts in the directory that match the filter.
+    ///
+    /// Fails if contracts of different source files share a name, since their bindings would
+    /// share a module.
+    pub fn from_artifacts(artifacts: &Path, filter: &ContractFilter) -> Result<Self> {
+        let mut contracts = BTreeMap::<String, (PathBuf, SolMacroGen)>::new();
+        let mut paths = json_files(artifacts)
+            .into_iter()
+            .filter(|path| {
+                // we don't want `.metadata.json files
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .map_or(false, |stem| !stem.ends_with(".metadata"))
+            })
+            .collect::<Vec<_>>();
+        // sort so the same contract is selected on every run
+        paths.sort();
+        for path in paths {
+            trace!(?path, "parsing bindings from file");
+            let Some(contract) = SolMacroGen::from_artifact(&path)? else { continue };
+            if !filter.is_match(&contract.name) {
+                continue
+            }
+            match contracts.entry(contract.name.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert((path, contract));
+                }
+                // the artifacts of a contract compiled with multiple solc versions are in the
+                // same directory
+                Entry::Occupied(entry) => {
+                    let (existing, _) = entry.get();
+                    eyre::ensure!(
+                        existing.parent() == path.parent(),
+                        "multiple contracts named `{}` found: {} and {}. Use --select or --skip to generate bindings for only one of them",
+                        entry.key(),
+                        existing.display(),
+                        path.display()
+                    );
+                }
+            }
+        }
+        Ok(Self { contracts: contracts.into_values().map(|(_, contract)| contract).collect() })
+    }
+
+    /// Returns the number of contracts.
+    pub fn len(&self) -> usize {
+        self.contracts.len()
+    }
+
+    /// Returns true if there are no contracts.
+    pub fn is_empty(&self) -> bool {
+        self.contracts.is_empty()
+    }
+
+    /// Returns the generated files of a crate, relative to its root.
+    pub fn crate_files(
+        &self,
+        name: &str,
+        version: &str,
+        alloy_version: &str,
+        single_file: bool,
+    ) -> Result<BTreeMap<PathBuf, String>> {
+        let mut files = self
+            .module_files("lib.rs", single_file)?
+            .into_iter()
+            .map(|(path, content)| (Path::new("src").join(path), content))
+            .collect::<BTreeMap<_, _>>();
+
+        let cargo_toml = format!(
+            r#"[package]
+name = "{name}"
+version = "{version}"
+edition = "2021"
+
+[dependencies]
+alloy-primitives = "{alloy_version}"
+alloy-sol-types = "{alloy_version}"
+"#
+        );
+        files.insert(PathBuf::from("Cargo.toml"), cargo_toml);
+        Ok(files)
+    }
+
+    /// Returns the generated files of a module, relative to the module directory. The root of
+    /// the module is written to `root_file`.
+    pub fn module_files(
+        &self,
+        root_file: &str,
+        single_file: bool,
+    ) -> Result<BTreeMap<PathBuf, String>> {
+        let mut files = BTreeMap::new();
+
+        let mut root = String::new();
+        writeln!(root, "#![allow(clippy::all)]")?;
+        writeln!(
+            root,
+            "//! This module contains `sol!` generated bindings for solidity contracts."
+        )?;
+        writeln!(root, "{GENERATED_HEADER}")?;
+        if !single_file && !self.is_empty() {
+            writeln!(root)?;
+        }
+        for contract in &self.contracts {
+            let module = contract.module_name();
+            if single_file {
+                writeln!(root)?;
+                writeln!(root, "pub mod {module} {{")?;
+                for line in contract.expand()?.lines() {
+                    if line.is_empty() {
+                        writeln!(root)?;
+                    } else {
+                        writeln!(root, "    {line}")?;
+                    }
+                }
+                writeln!(root, "}}")?;
+            } else {
+                writeln!(root, "pub mod {module};")?;
+                let content = format!(
+                    "//! Bindings for the `{}` contract.\n{GENERATED_HEADER}\n\n{}",
+                    contract.name,
+                    contract.expand()?
+                );
+                files.insert(PathBuf::from(format!("{module}.rs")), content);
+            }
+        }
+        files.insert(PathBuf::from(root_file), root);
+
+        Ok(files)
+    }
+
+    /// Writes the files to the directory.
+    pub fn write_files(files: &BTreeMap<PathBuf, String>, dir: &Path) -> Result<()> {
+        for (path, content) in files {
+            let path = dir.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, content)
+                .wrap_err_with(|| format!("failed to write bindings to {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the files in the directory match the generated files, and that there are no
+    /// other Rust files, e.g. the bindings of a contract which no longer exists.
+    ///
+    /// The `target` directory of the crate is ignored.
+    pub fn ensure_consistent_files(files: &BTreeMap<PathBuf, String>, dir: &Path) -> Result<()> {
+        for (path, content) in files {
+            let path = dir.join(path);
+            let existing = fs::read_to_string(&path)
+                .wrap_err_with(|| format!("failed to read bindings from {}", path.display()))?;
+            eyre::ensure!(
+                existing == *content,
+                "the contents of `{}` do not match the expected output of the newest `forge bind` command",
+                path.display()
+            );
+        }
+        for path in files_with_ext(dir, "rs") {
+            let relative = path.strip_prefix(dir)?;
+            if relative.starts_with("target") {
+                continue
+            }
+            eyre::ensure!(
+                files.contains_key(relative),
+                "`{}` is not part of the expected output of the newest `forge bind` command",
+                path.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Returns the snake case module name of the contract.
+fn module_name(name: &str) -> String {
+    let mut module = String::with_capacity(name.len());
+    let chars = name.chars().collect::<Vec<_>>();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            // start a new word before an uppercase letter that follows a lowercase letter or that
+            // is followed by a lowercase letter within an acronym, e.g. `ERC20Token`
+            let prev_lower =
+                i > 0 && (chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit());
+            let next_lower = chars.get(i + 1).map_or(false, |next| next.is_lowercase());
+            let prev_upper = i > 0 && chars[i - 1].is_uppercase();
+            if prev_lower || (prev_upper && next_lower) {
+                module.push('_');
+            }
+            module.extend(c.to_lowercase());
+        } else {
+            module.push(*c);
+        }
+    }
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_module_names() {
+        assert_eq!(module_name("Counter"), "counter");
+        assert_eq!(module_name("MyToken"), "my_token");
+        assert_eq!(module_name("ERC20"), "erc20");
+        assert_eq!(module_name("ERC20Token"), "erc20_token");
+        assert_eq!(module_name("IUniswapV2Pair"), "i_uniswap_v2_pair");
+    }
+
+    fn write_artifact(path: &Path) {
+        let abi = r#"[{"type":"function","name":"increment","inputs":[],"outputs":[],"stateMutability":"nonpayable"}]"#;
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, format!(r#"{{"abi":{abi},"bytecode":{{"object":"0x6080"}}}}"#)).unwrap();
+    }
+
+    #[test]
+    fn can_expand_bindings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Counter.sol/Counter.json");
+        write_artifact(&path);
+        let contract = SolMacroGen::from_artifact(&path).unwrap().unwrap();
+
+        let expanded = contract.expand().unwrap();
+        assert!(expanded.contains("alloy_sol_types::sol! {"), "{expanded}");
+        assert!(expanded.contains("function increment()"), "{expanded}");
+        assert!(expanded.contains("pub static BYTECODE: alloy_primitives::Bytes"), "{expanded}");
+    }
+
+    #[test]
+    fn fails_on_duplicate_contract_names() {
+        let dir = tempfile::tempdir().unwrap();
+        write_artifact(&dir.path().join("Counter.sol/Counter.0.8.19.json"));
+        write_artifact(&dir.path().join("Counter.sol/Counter.0.8.20.json"));
+        let bindings = MultiSolMacroGen::from_artifacts(dir.path(), &ContractFilter::All).unwrap();
+        assert_eq!(bindings.len(), 1);
+
+        write_artifact(&dir.path().join("Other.sol/Counter.json"));
+        let err = MultiSolMacroGen::from_artifacts(dir.path(), &ContractFilter::All).unwrap_err();
+        assert!(err.to_string().contains("multiple contracts named `Counter`"), "{err}");
+    }
+
+    #[test]
+    fn fails_on_stale_bindings() {
+        let dir = tempfile::tempdir().unwrap();
+        write_artifact(&dir.path().join("Counter.sol/Counter.json"));
+        let bindings = MultiSolMacroGen::from_artifacts(dir.path(), &ContractFilter::All).unwrap();
+        let files = bindings.module_files("mod.rs", false, false).unwrap();
+
+        let root = dir.path().join("bindings");
+        MultiSolMacroGen::write_files(&files, &root).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("target/build.rs"), "").unwrap();
+        MultiSolMacroGen::ensure_consistent_files(&files, &root).unwrap();
+
+        // bindings of a contract that no longer exists
+        fs::write(root.join("removed.rs"), "").unwrap();
+        let err = MultiSolMacroGen::ensure_consistent_files(&files, &root).unwrap_err();
+        assert!(err.to_string().contains("removed.rs"), "{err}");
+    }
+}
@@ -1,3 +1,4 @@
+use alloy::MultiSolMacroGen;
 use clap::{Parser, ValueHint};
 use ethers_contract::{Abigen, ContractFilter, ExcludeContracts, MultiAbigen, SelectContracts};
 use eyre::{Result, WrapErr};
@@ -9,10 +10,13 @@ use std::{
     path::{Path, PathBuf},
 };
 
+mod alloy;
+
 impl_figment_convert!(BindArgs, build_args);
 
 const DEFAULT_CRATE_NAME: &str = "foundry-contracts";
 const DEFAULT_CRATE_VERSION: &str = "0.1.0";
+const DEFAULT_ALLOY_VERSION: &str = "0.6";
 
 /// CLI arguments for `forge bind`.
 #[derive(Clone, Debug, Parser)]
@@ -77,6 +81,22 @@ pub struct BindArgs {
     #[clap(long)]
     skip_build: bool,
 
+    /// Generate bindings for alloy instead of ethers-rs, using the `sol!` macro.
+    ///
+    /// The bindings of every contract include its events, errors and bytecode. When generated as
+    /// a module, the parent crate needs to depend on `alloy-primitives` and `alloy-sol-types`.
+    #[clap(long)]
+    alloy: bool,
+
+    /// The version of the alloy dependencies of the generated crate.
+    #[clap(
+        long,
+        default_value = DEFAULT_ALLOY_VERSION,
+        value_name = "VERSION",
+        requires = "alloy"
+    )]
+    alloy_version: String,
+
     #[clap(flatten)]
     build_args: CoreBuildArgs,
 }
@@ -91,6 +111,10 @@ impl BindArgs {
 
         let artifacts = self.try_load_config_emit_warnings()?.out;
 
+        if self.alloy {
+            return self.run_alloy(&artifacts)
+        }
+
         if !self.overwrite && self.bindings_exist(&artifacts) {
             println!("Bindings found. Checking for consistency.");
             return self.check_existing_bindings(&artifacts)
@@ -110,6 +134,55 @@ impl BindArgs {
         Ok(())
     }
 
+    /// Generates or checks the alloy bindings
+    fn run_alloy(&self, artifacts: &Path) -> Result<()> {
+        let bindings = MultiSolMacroGen::from_artifacts(artifacts, &self.get_filter())?;
+        eyre::ensure!(
+            !bindings.is_empty(),
+            "No contract artifacts found. Hint: Have you built your contracts yet?"
+        );
+
+        let mut files = if self.module {
+            bindings.module_files("mod.rs", self.single_file)?
+        } else {
+            bindings.crate_files(
+                &self.crate_name,
+                &self.crate_version,
+                &self.alloy_version,
+                self.single_file,
+            )?
+        };
+        let root = self.bindings_root(artifacts);
+
+        if !self.overwrite && self.bindings_exist(artifacts) {
+            println!("Bindings found. Checking for consistency.");
+            if self.skip_cargo_toml {
+                files.remove(Path::new("Cargo.toml"));
+            }
+            println!("Checking bindings for {} contracts.", bindings.len());
+            MultiSolMacroGen::ensure_consistent_files(&files, &root).map_err(|err| {
+                if !self.skip_cargo_toml && err.to_string().contains("Cargo.toml") {
+                    err.wrap_err("To skip Cargo.toml consistency check, pass --skip-cargo-toml")
+                } else {
+                    err
+                }
+            })?;
+            println!("OK.");
+            return Ok(())
+        }
+
+        if self.overwrite && self.bindings_exist(artifacts) {
+            trace!(?artifacts, "Removing existing bindings");
+            fs::remove_dir_all(&root)?;
+        }
+
+        println!("Generating alloy bindings for {} contracts", bindings.len());
+        MultiSolMacroGen::write_files(&files, &root)?;
+
+        println!("Bindings have been output to {}", root.display());
+        Ok(())
+    }
+
     /// Get the path to the root of the autogenerated crate
     fn bindings_root(&self, artifacts: impl AsRef<Path>) -> PathBuf {
         self.bindings.clone().unwrap_or_else(|| artifacts.as_ref().join("bindings"))
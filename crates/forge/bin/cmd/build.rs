@@ -1,9 +1,15 @@
-use super::{install, watch::WatchArgs};
-use clap::Parser;
+use super::{install, lint, watch::WatchArgs};
+use clap::{Parser, ValueHint};
 use eyre::Result;
 use foundry_cli::{opts::CoreBuildArgs, utils::LoadConfig};
-use foundry_common::compile::{ProjectCompiler, SkipBuildFilter, SkipBuildFilters};
-use foundry_compilers::{Project, ProjectCompileOutput};
+use foundry_common::{
+    compile::{
+        compilation_override_projects, CacheReport, CompilationOverrideFilter, ProjectCompiler,
+        SkipBuildFilter, SkipBuildFilters,
+    },
+    fs, vyper,
+};
+use foundry_compilers::{FileFilter, Project, ProjectCompileOutput};
 use foundry_config::{
     figment::{
         self,
@@ -13,7 +19,14 @@ use foundry_config::{
     },
     Config,
 };
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::Serialize;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use watchexec::config::{InitConfig, RuntimeConfig};
 
 foundry_config::merge_impl_figment_convert!(BuildArgs, args);
@@ -42,16 +55,32 @@ foundry_config::merge_impl_figment_convert!(BuildArgs, args);
 #[derive(Clone, Debug, Default, Serialize, Parser)]
 #[clap(next_help_heading = "Build options", about = None, long_about = None)] // override doc
 pub struct BuildArgs {
+    /// Build only the given source files or contracts, and the files they import.
+    ///
+    /// Contracts can be referenced by name or as `<path>:<name>`. Artifacts of all other files
+    /// are kept as is.
+    #[clap(value_hint = ValueHint::FilePath, value_name = "PATH_OR_CONTRACT")]
+    #[serde(skip)]
+    pub targets: Vec<String>,
+
     /// Print compiled contract names.
     #[clap(long)]
     #[serde(skip)]
     pub names: bool,
 
     /// Print compiled contract sizes.
+    ///
+    /// Fails if a contract exceeds the runtime or initcode size limit, or gets closer to them than
+    /// the configured `size_limit_margin`, which is also checked without this flag.
     #[clap(long)]
     #[serde(skip)]
     pub sizes: bool,
 
+    /// Print the contract sizes as JSON.
+    #[clap(long, requires = "sizes", conflicts_with = "format_json")]
+    #[serde(skip)]
+    pub json: bool,
+
     /// Skip building files whose names contain the given filter.
     ///
     /// `test` and `script` are aliases for `.t.sol` and `.s.sol`.
@@ -59,6 +88,11 @@ pub struct BuildArgs {
     #[serde(skip)]
     pub skip: Option<Vec<SkipBuildFilter>>,
 
+    /// Print whether each source file is reused from the cache, and why it is recompiled if not.
+    #[clap(long, conflicts_with_all = ["format_json", "json"])]
+    #[serde(skip)]
+    pub cache_report: bool,
+
     #[clap(flatten)]
     #[serde(flatten)]
     pub args: CoreBuildArgs,
@@ -87,16 +121,58 @@ impl BuildArgs {
             project = config.project()?;
         }
 
+        let skip = SkipBuildFilters(self.skip.unwrap_or_default());
+        let files = if self.targets.is_empty() {
+            None
+        } else {
+            let files = resolve_targets(&project, &self.targets)?;
+            trace!(target: "forge::build", ?files, "building targets");
+            Some(files)
+        };
+        let filter = TargetsFilter { files, skip: skip.clone() };
+
+        let mut cache_report =
+            if self.cache_report { Some(CacheReport::solc(&project)?) } else { None };
+        let vyper = vyper::compile_project(&config, Some(&filter), self.format_json || self.json)?;
+        if let (Some(report), Some(vyper)) = (&mut cache_report, vyper) {
+            report.extend(vyper.cache);
+        }
+
+        // the sources matching a compilation override are compiled by separate projects
+        let (filter, overrides): (Box<dyn FileFilter>, _) = if config
+            .compilation_overrides
+            .is_empty()
+        {
+            (Box::new(filter), Vec::new())
+        } else {
+            let config = Arc::new(config.clone());
+            let overrides = compilation_override_projects(&config, &filter)?;
+            (
+                Box::new(CompilationOverrideFilter { inner: filter, config, settings_hash: None }),
+                overrides,
+            )
+        };
+
         let output = ProjectCompiler::new()
             .print_names(self.names)
             .print_sizes(self.sizes)
-            .quiet(self.format_json)
+            .sizes_json(self.json)
+            .size_limit_margin(config.build.size_limit_margin)
+            .quiet(self.format_json || self.json)
             .bail(!self.format_json)
-            .filter(Box::new(SkipBuildFilters(self.skip.unwrap_or_default())))
+            .filter(filter)
+            .overrides(overrides)
+            .size_filter(Box::new(skip))
             .compile(&project)?;
         if self.format_json {
             println!("{}", serde_json::to_string_pretty(&output.clone().output())?);
         }
+        if let Some(report) = cache_report {
+            println!("{report}");
+        }
+        if let Some(fail_on) = config.lint.fail_on {
+            lint::lint_build(&config, fail_on)?;
+        }
         Ok(output)
     }
 
@@ -125,6 +201,72 @@ impl BuildArgs {
     }
 }
 
+/// A filter that only matches the given target files, unless they are skipped.
+///
+/// Used for sparse compilation, which compiles the matching files and the files they import, and
+/// keeps the cached artifacts of all other files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TargetsFilter {
+    /// The canonicalized target files, all files are targets if `None`
+    pub files: Option<BTreeSet<PathBuf>>,
+    /// The skip filters
+    pub skip: SkipBuildFilters,
+}
+
+impl FileFilter for TargetsFilter {
+    fn is_match(&self, file: &Path) -> bool {
+        let is_target = self.files.as_ref().map_or(true, |files| {
+            files.contains(file) ||
+                dunce::canonicalize(file).map_or(false, |file| files.contains(&file))
+        });
+        is_target && self.skip.is_match(file)
+    }
+}
+
+/// Resolves the given paths and contract names to the source files of the project.
+///
+/// Paths are resolved relative to the current dir first, then relative to the project root.
+/// Everything else is treated as the name of a contract, which is looked up in all sources, tests
+/// and scripts of the project.
+fn resolve_targets(project: &Project, targets: &[String]) -> Result<BTreeSet<PathBuf>> {
+    static RE_CONTRACT: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?m)^\s*(?:abstract\s+)?(?:contract|library|interface)\s+(\w+)").unwrap()
+    });
+
+    let root = &project.paths.root;
+    let mut files = BTreeSet::new();
+    let mut names = Vec::new();
+    for target in targets {
+        // `<path>:<name>` only needs the path
+        let path = target.rsplit_once(':').map_or(target.as_str(), |(path, _)| path);
+        let mut candidates =
+            [target.as_str(), path].into_iter().flat_map(|p| [PathBuf::from(p), root.join(p)]);
+        if let Some(file) = candidates.find(|p| p.is_file()) {
+            files.insert(dunce::canonicalize(file)?);
+        } else {
+            names.push(target.as_str());
+        }
+    }
+
+    if !names.is_empty() {
+        let mut found = BTreeSet::new();
+        for file in project.paths.input_files() {
+            let content = fs::read_to_string(&file)?;
+            for cap in RE_CONTRACT.captures_iter(&content) {
+                if let Some(name) = names.iter().copied().find(|name| *name == &cap[1]) {
+                    found.insert(name);
+                    files.insert(dunce::canonicalize(&file)?);
+                }
+            }
+        }
+        if let Some(name) = names.iter().find(|name| !found.contains(*name)) {
+            eyre::bail!("No source file or contract named `{name}` found in the project")
+        }
+    }
+
+    Ok(files)
+}
+
 // Make this args a `figment::Provider` so that it can be merged into the `Config`
 impl Provider for BuildArgs {
     fn metadata(&self) -> Metadata {
@@ -168,11 +310,25 @@ mod tests {
         assert_eq!(args.skip, Some(vec![SkipBuildFilter::Tests, SkipBuildFilter::Scripts]));
     }
 
+    #[test]
+    fn can_parse_targets() {
+        let args: BuildArgs = BuildArgs::parse_from(["foundry-cli", "src/Vault.sol", "Counter"]);
+        assert_eq!(args.targets, vec!["src/Vault.sol", "Counter"]);
+
+        let args: BuildArgs = BuildArgs::parse_from(["foundry-cli", "--skip", "tests"]);
+        assert!(args.targets.is_empty());
+    }
+
     #[test]
     fn check_conflicts() {
         let args: std::result::Result<BuildArgs, clap::Error> =
             BuildArgs::try_parse_from(["foundry-cli", "--format-json", "--silent"]);
         assert!(args.is_err());
         assert!(args.unwrap_err().kind() == clap::error::ErrorKind::ArgumentConflict);
+
+        let args: std::result::Result<BuildArgs, clap::Error> =
+            BuildArgs::try_parse_from(["foundry-cli", "--json"]);
+        assert!(args.is_err());
+        assert!(args.unwrap_err().kind() == clap::error::ErrorKind::MissingRequiredArgument);
     }
 }
@@ -1,11 +1,12 @@
 use cache::Cache;
 use clap::{
     builder::{PossibleValuesParser, TypedValueParser},
-    Arg, Command, Parser, Subcommand,
+    Arg, Command, Parser, Subcommand, ValueHint,
 };
 use eyre::Result;
+use forge::traces::identifier::SignaturesIdentifier;
 use foundry_config::{cache, Chain, Config, NamedChain};
-use std::{ffi::OsStr, str::FromStr};
+use std::{ffi::OsStr, path::PathBuf, str::FromStr};
 use strum::VariantNames;
 
 /// CLI arguments for `forge cache`.
@@ -22,6 +23,14 @@ pub enum CacheSubcommands {
 
     /// Shows cached data from the global foundry directory.
     Ls(LsArgs),
+
+    /// Imports function, error and event signatures from a JSON file into the signatures cache.
+    ///
+    /// The file either has the format of the cache, `{"functions": {selector: signature},
+    /// "events": {selector: signature}}`, or is a list of signatures, with the events prefixed
+    /// with `event`.
+    #[clap(visible_alias = "is")]
+    ImportSignatures(ImportSignaturesArgs),
 }
 
 /// CLI arguments for `forge clean`.
@@ -88,11 +97,15 @@ pub struct LsArgs {
         value_parser = ChainOrAllValueParser::default(),
     )]
     chains: Vec<ChainOrAll>,
+
+    /// Print the cached chains and blocks with their sizes in bytes as JSON.
+    #[clap(long)]
+    json: bool,
 }
 
 impl LsArgs {
     pub fn run(self) -> Result<()> {
-        let LsArgs { chains } = self;
+        let LsArgs { chains, json } = self;
         let mut cache = Cache::default();
         for chain_or_all in chains {
             match chain_or_all {
@@ -102,7 +115,28 @@ impl LsArgs {
                 ChainOrAll::All => cache = Config::list_foundry_cache()?,
             }
         }
-        print!("{cache}");
+        if json {
+            println!("{}", serde_json::to_string_pretty(&cache)?);
+        } else {
+            print!("{cache}");
+        }
+        Ok(())
+    }
+}
+
+/// CLI arguments for `forge cache import-signatures`.
+#[derive(Debug, Parser)]
+pub struct ImportSignaturesArgs {
+    /// The JSON file to import the signatures of.
+    #[clap(value_hint = ValueHint::FilePath)]
+    path: PathBuf,
+}
+
+impl ImportSignaturesArgs {
+    pub fn run(self) -> Result<()> {
+        let signatures = SignaturesIdentifier::new(Config::foundry_cache_dir(), true)?;
+        let added = signatures.blocking_write().import(&self.path)?;
+        println!("Imported {added} new signatures");
         Ok(())
     }
 }
@@ -186,4 +220,15 @@ mod tests {
         let args: CacheArgs = CacheArgs::parse_from(["cache", "ls"]);
         assert!(matches!(args.sub, CacheSubcommands::Ls(_)));
     }
+
+    #[test]
+    fn can_parse_cache_import_signatures() {
+        let args: CacheArgs = CacheArgs::parse_from(["cache", "import-signatures", "sigs.json"]);
+        match args.sub {
+            CacheSubcommands::ImportSignatures(args) => {
+                assert_eq!(args.path, PathBuf::from("sigs.json"))
+            }
+            _ => unreachable!(),
+        }
+    }
 }
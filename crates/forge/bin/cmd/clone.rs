@@ -0,0 +1,431 @@
+use super::verify::bytecode::{BytecodeMatch, VerifyBytecodeArgs};
+use alloy_primitives::{Address, Bytes, B256};
+use clap::{Parser, ValueHint};
+use eyre::{Context, Result};
+use foundry_block_explorers::{contract::Metadata, errors::EtherscanError, Client};
+use foundry_cli::{
+    opts::{CoreBuildArgs, EtherscanOpts, RpcOpts},
+    p_println,
+};
+use foundry_common::fs;
+use foundry_compilers::{info::ContractInfo, remappings::Remapping};
+use foundry_config::Config;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    path::{Component, Path, PathBuf},
+};
+use yansi::Paint;
+
+/// The file the deployment metadata of a cloned contract is stored in.
+pub const CLONE_METADATA_FILE: &str = ".clone.meta";
+
+/// The directory the cloned sources are written to.
+const SRC_DIR: &str = "src";
+
+/// CLI arguments for `forge clone`.
+#[derive(Clone, Debug, Parser)]
+pub struct CloneArgs {
+    /// The address of the verified contract to clone.
+    pub address: Address,
+
+    /// The root directory of the cloned project.
+    #[clap(value_hint = ValueHint::DirPath, default_value = ".", value_name = "PATH")]
+    pub root: PathBuf,
+
+    /// Do not check that the bytecode of the cloned project matches the on-chain bytecode.
+    #[clap(long)]
+    pub no_verify: bool,
+
+    /// Suppress all output except errors.
+    #[clap(long, short)]
+    pub quiet: bool,
+
+    #[clap(flatten)]
+    pub etherscan: EtherscanOpts,
+
+    #[clap(flatten)]
+    pub rpc: RpcOpts,
+}
+
+impl CloneArgs {
+    pub async fn run(self) -> Result<()> {
+        let CloneArgs { address, root, no_verify, quiet, etherscan, rpc } = self;
+
+        let config = Config::from(&etherscan);
+        let chain = config.chain.unwrap_or_default();
+        let api_key = config.get_etherscan_api_key(Some(chain)).unwrap_or_default();
+        let client = Client::new(chain, api_key)?;
+
+        p_println!(!quiet => "Downloading the source code of {address} from Etherscan...");
+        let meta = match client.contract_source_code(address).await {
+            Ok(meta) => meta,
+            Err(EtherscanError::InvalidApiKey) => {
+                eyre::bail!("Invalid Etherscan API key. Did you set it correctly? You may be using an API key for another Etherscan API chain (e.g. Etherscan API key for Polygonscan).")
+            }
+            Err(EtherscanError::ContractCodeNotVerified(address)) => {
+                eyre::bail!("Contract source code at {:?} on {} not verified. Maybe you have selected the wrong chain?", address, chain)
+            }
+            Err(err) => eyre::bail!(err),
+        };
+        let meta = meta
+            .items
+            .into_iter()
+            .next()
+            .ok_or_else(|| eyre::eyre!("No source code found for {address}"))?;
+
+        if meta.is_vyper() {
+            p_println!(!quiet => "{}", Paint::yellow(format!(
+                "Skipping {address}: `{}` is a Vyper contract, only Solidity contracts can be cloned.",
+                meta.contract_name
+            )));
+            return Ok(())
+        }
+
+        // create the root dir if it does not exist
+        if !root.exists() {
+            fs::create_dir_all(&root)?;
+        }
+        let root = dunce::canonicalize(root)?;
+        if root.read_dir().map_or(false, |mut i| i.next().is_some()) {
+            eyre::bail!("Cannot run `clone` on a non-empty directory.")
+        }
+
+        p_println!(!quiet => "Cloning `{}` into {}...", meta.contract_name, root.display());
+        let target = dump_sources(&meta, &root)?;
+        write_config(&meta, &root)?;
+
+        // the creation transaction is optional, not all explorers support the endpoint
+        let creation = client.contract_creation_data(address).await.ok();
+        let clone_meta = CloneMetadata {
+            path: target.clone(),
+            target_contract: meta.contract_name.clone(),
+            address,
+            chain_id: chain.id(),
+            creation_transaction: creation.as_ref().map(|c| c.transaction_hash),
+            deployer: creation.as_ref().map(|c| c.contract_creator),
+            constructor_arguments: meta.constructor_arguments.clone(),
+            compiler_version: meta.compiler_version.clone(),
+        };
+        clone_meta.write(&root)?;
+
+        let name = &meta.contract_name;
+        if no_verify {
+            p_println!(!quiet => "    {} {name} into {}", Paint::green("Cloned"), root.display());
+            return Ok(())
+        }
+        if rpc.url(Some(&Config::load_with_root(&root)))?.is_none() {
+            p_println!(!quiet => "    {} {name} into {}", Paint::green("Cloned"), root.display());
+            p_println!(!quiet => "{}", Paint::yellow(
+                "No RPC URL provided, skipping the bytecode check. Pass `--rpc-url` to run it."
+            ));
+            return Ok(())
+        }
+
+        p_println!(!quiet => "Checking the bytecode of the cloned project...");
+        let mut build = CoreBuildArgs::default();
+        build.project_paths.root = Some(root.clone());
+        let verify = VerifyBytecodeArgs {
+            address,
+            contract: ContractInfo {
+                path: target.map(|path| root.join(path).to_string_lossy().to_string()),
+                name: name.clone(),
+            },
+            constructor_args: Some(hex::encode(&meta.constructor_arguments)),
+            creation_tx: clone_meta.creation_transaction,
+            block: None,
+            json: false,
+            quiet,
+            rpc,
+            etherscan,
+            build,
+        };
+        match verify.run().await? {
+            BytecodeMatch::Full | BytecodeMatch::Partial => {
+                let cloned = Paint::green("Cloned");
+                p_println!(!quiet => "    {cloned} {name} into {}", root.display());
+                Ok(())
+            }
+            BytecodeMatch::Mismatch => {
+                eyre::bail!(
+                    "The bytecode of the cloned project doesn't match the bytecode of {address}"
+                )
+            }
+        }
+    }
+}
+
+/// The deployment metadata of a cloned contract, stored in [`CLONE_METADATA_FILE`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneMetadata {
+    /// The path of the file declaring the target contract, relative to the project root.
+    pub path: Option<PathBuf>,
+    /// The name of the cloned contract.
+    pub target_contract: String,
+    /// The address of the cloned contract.
+    pub address: Address,
+    /// The chain the contract is deployed on.
+    pub chain_id: u64,
+    /// The hash of the transaction that deployed the contract.
+    pub creation_transaction: Option<B256>,
+    /// The account that deployed the contract.
+    pub deployer: Option<Address>,
+    /// The ABI-encoded constructor arguments.
+    pub constructor_arguments: Bytes,
+    /// The compiler version the contract was verified with.
+    pub compiler_version: String,
+}
+
+impl CloneMetadata {
+    /// Writes the metadata to [`CLONE_METADATA_FILE`] in `root`.
+    pub fn write(&self, root: &Path) -> Result<()> {
+        fs::write(root.join(CLONE_METADATA_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Reads the metadata from [`CLONE_METADATA_FILE`] in `root`.
+    pub fn read(root: &Path) -> Result<Self> {
+        Ok(fs::read_json_file(&root.join(CLONE_METADATA_FILE))?)
+    }
+}
+
+/// Writes all sources of the verified contract to the `src` dir of `root`.
+///
+/// Returns the path of the file that declares the target contract, relative to `root`.
+fn dump_sources(meta: &Metadata, root: &Path) -> Result<Option<PathBuf>> {
+    static RE_CONTRACT: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?m)^\s*(?:abstract\s+)?(?:contract|library|interface)\s+(\w+)").unwrap()
+    });
+
+    let mut target = None;
+    for entry in meta.source_tree().entries {
+        let path = clone_source_path(&entry.path)?;
+        let file = root.join(&path);
+        eyre::ensure!(!file.exists(), "Multiple sources are written to `{}`", path.display());
+        fs::create_dir_all(file.parent().unwrap())?;
+        fs::write(&file, &entry.contents)?;
+
+        let declares_target =
+            RE_CONTRACT.captures_iter(&entry.contents).any(|cap| cap[1] == *meta.contract_name);
+        if target.is_none() && declares_target {
+            target = Some(path);
+        }
+    }
+    Ok(target)
+}
+
+/// Writes a `foundry.toml` with the compiler settings of the verified contract.
+fn write_config(meta: &Metadata, root: &Path) -> Result<()> {
+    let settings = meta.settings().wrap_err("Failed to read the compiler settings")?;
+    let version = meta.compiler_version()?;
+
+    let config = Config::load_with_root(root);
+    fs::write(root.join(Config::FILE_NAME), config.into_basic().to_string_pretty()?)?;
+
+    let remappings = clone_remappings(
+        &settings.remappings,
+        meta.source_tree().entries.iter().map(|entry| entry.path.as_path()),
+    )
+    .into_iter()
+    .map(|remapping| remapping.to_string())
+    .collect::<toml_edit::Array>();
+    let libraries = settings
+        .libraries
+        .libs
+        .iter()
+        .flat_map(|(file, libs)| {
+            let file = clone_source_path(file).unwrap_or_else(|_| file.clone());
+            libs.iter().map(move |(name, address)| format!("{}:{name}:{address}", file.display()))
+        })
+        .collect::<toml_edit::Array>();
+
+    let mut error = None;
+    Config::update_at(root, |_, doc| {
+        let profile = &mut doc[Config::PROFILE_SECTION][Config::DEFAULT_PROFILE.as_str().as_str()];
+        profile["solc"] =
+            toml_edit::value(format!("{}.{}.{}", version.major, version.minor, version.patch));
+        profile["auto_detect_remappings"] = toml_edit::value(false);
+        profile["remappings"] = toml_edit::value(remappings);
+        profile["optimizer"] = toml_edit::value(settings.optimizer.enabled.unwrap_or_default());
+        profile["optimizer_runs"] = toml_edit::value(settings.optimizer.runs.unwrap_or(200) as i64);
+        profile["via_ir"] = toml_edit::value(settings.via_ir.unwrap_or_default());
+        if let Some(evm_version) = settings.evm_version {
+            profile["evm_version"] = toml_edit::value(evm_version.to_string());
+        }
+        if !libraries.is_empty() {
+            profile["libraries"] = toml_edit::value(libraries);
+        }
+        if let Some(metadata) = &settings.metadata {
+            match metadata.bytecode_hash.map(serde_json::to_value).transpose() {
+                Ok(Some(serde_json::Value::String(hash))) => {
+                    profile["bytecode_hash"] = toml_edit::value(hash)
+                }
+                Ok(_) => {}
+                Err(err) => error = Some(err),
+            }
+            if let Some(cbor_metadata) = metadata.cbor_metadata {
+                profile["cbor_metadata"] = toml_edit::value(cbor_metadata);
+            }
+        }
+        true
+    })?;
+
+    match error {
+        Some(err) => Err(err.into()),
+        None => Ok(()),
+    }
+}
+
+/// Returns the path, relative to the project root, that the source with the given path is
+/// written to.
+///
+/// Sources are written to the `src` dir, sources which already are in a `src` dir keep their path.
+fn clone_source_path(path: &Path) -> Result<PathBuf> {
+    let path = sanitize_path(path)?;
+    if path.starts_with(SRC_DIR) {
+        Ok(path)
+    } else {
+        Ok(Path::new(SRC_DIR).join(path))
+    }
+}
+
+/// Returns the remappings for the sources written to the `src` dir.
+///
+/// The original remappings are rebased onto `src`, and every top level directory of the source
+/// paths is remapped, so that direct imports like `@openzeppelin/contracts/...` still resolve.
+/// Sources in the `src` dir keep their path, so they don't need to be remapped.
+fn clone_remappings<'a>(
+    remappings: &[Remapping],
+    paths: impl IntoIterator<Item = &'a Path>,
+) -> Vec<Remapping> {
+    let mut cloned = remappings
+        .iter()
+        .map(|remapping| Remapping {
+            context: remapping.context.clone(),
+            name: remapping.name.clone(),
+            path: if Path::new(&remapping.path).starts_with(SRC_DIR) {
+                remapping.path.clone()
+            } else {
+                format!("{SRC_DIR}/{}", remapping.path)
+            },
+        })
+        .collect::<Vec<_>>();
+
+    let top_level_dirs = paths
+        .into_iter()
+        .filter_map(|path| {
+            let path = sanitize_path(path).ok()?;
+            let mut components = path.iter();
+            let first = components.next()?;
+            // files at the top level are imported relatively
+            components.next()?;
+            (first != SRC_DIR).then(|| first.to_string_lossy().to_string())
+        })
+        .collect::<BTreeSet<_>>();
+    for dir in top_level_dirs {
+        let name = format!("{dir}/");
+        if !cloned.iter().any(|remapping| remapping.name == name) {
+            cloned.push(Remapping { context: None, name, path: format!("{SRC_DIR}/{dir}/") });
+        }
+    }
+
+    cloned
+}
+
+/// Returns the path without root or parent components, so that it can't escape the project.
+fn sanitize_path(path: &Path) -> Result<PathBuf> {
+    let mut sanitized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => sanitized.push(part),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+            Component::ParentDir => {
+                eyre::bail!("Invalid source path `{}`", path.display())
+            }
+        }
+    }
+    Ok(sanitized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn can_sanitize_paths() {
+        assert_eq!(
+            sanitize_path(Path::new("/contracts/./Token.sol")).unwrap(),
+            PathBuf::from("contracts/Token.sol")
+        );
+        assert!(sanitize_path(Path::new("../Token.sol")).is_err());
+    }
+
+    #[test]
+    fn can_clone_remappings() {
+        let remappings = vec![
+            "forge-std/=lib/forge-std/src/".parse::<Remapping>().unwrap(),
+            "utils/=src/utils/".parse::<Remapping>().unwrap(),
+        ];
+        let paths = [
+            Path::new("src/Counter.sol"),
+            Path::new("lib/forge-std/src/Test.sol"),
+            Path::new("@openzeppelin/contracts/token/ERC20/ERC20.sol"),
+            Path::new("Flattened.sol"),
+        ];
+        let remappings = clone_remappings(&remappings, paths)
+            .into_iter()
+            .map(|remapping| remapping.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            remappings,
+            vec![
+                "forge-std/=src/lib/forge-std/src/",
+                "utils/=src/utils/",
+                "@openzeppelin/=src/@openzeppelin/",
+                "lib/=src/lib/",
+            ]
+        );
+    }
+
+    #[test]
+    fn can_get_clone_source_paths() {
+        assert_eq!(
+            clone_source_path(Path::new("src/Counter.sol")).unwrap(),
+            PathBuf::from("src/Counter.sol")
+        );
+        assert_eq!(
+            clone_source_path(Path::new("/lib/forge-std/src/Test.sol")).unwrap(),
+            PathBuf::from("src/lib/forge-std/src/Test.sol")
+        );
+        assert_eq!(
+            clone_source_path(Path::new("Flattened.sol")).unwrap(),
+            PathBuf::from("src/Flattened.sol")
+        );
+    }
+
+    #[test]
+    fn can_write_and_read_clone_metadata() {
+        let root = tempdir().unwrap();
+        let meta = CloneMetadata {
+            path: Some(PathBuf::from("src/contracts/Token.sol")),
+            target_contract: "Token".to_string(),
+            address: Address::repeat_byte(0x11),
+            chain_id: 1,
+            creation_transaction: Some(B256::repeat_byte(0x22)),
+            deployer: Some(Address::repeat_byte(0x33)),
+            constructor_arguments: Bytes::from_static(&[0, 1]),
+            compiler_version: "v0.8.19+commit.7dd6d404".to_string(),
+        };
+        meta.write(root.path()).unwrap();
+
+        let json: serde_json::Value =
+            fs::read_json_file(&root.path().join(CLONE_METADATA_FILE)).unwrap();
+        assert_eq!(json["targetContract"], "Token");
+        assert_eq!(json["constructorArguments"], "0x0001");
+        assert_eq!(CloneMetadata::read(root.path()).unwrap(), meta);
+    }
+}
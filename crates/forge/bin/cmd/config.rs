@@ -3,7 +3,8 @@ use clap::Parser;
 use eyre::Result;
 use foundry_cli::utils::LoadConfig;
 use foundry_common::{evm::EvmArgs, term::cli_warn};
-use foundry_config::fix::fix_tomls;
+use foundry_config::{figment::Figment, fix::fix_tomls};
+use itertools::Itertools;
 
 foundry_config::impl_figment_convert!(ConfigArgs, opts, evm_opts);
 
@@ -22,6 +23,11 @@ pub struct ConfigArgs {
     #[clap(long)]
     fix: bool,
 
+    /// Print where each config value is set: the config file, the environment variable or the
+    /// argument it comes from, or the defaults.
+    #[clap(long, conflicts_with = "basic")]
+    origins: bool,
+
     // support nested build arguments
     #[clap(flatten)]
     opts: BuildArgs,
@@ -41,7 +47,14 @@ impl ConfigArgs {
 
         let config = self.try_load_config_unsanitized_emit_warnings()?;
 
-        let s = if self.basic {
+        let s = if self.origins {
+            let origins = config.origins(&Figment::from(&self))?;
+            if self.json {
+                serde_json::to_string_pretty(&origins)?
+            } else {
+                origins.iter().map(|(key, origin)| format!("{key} = {origin}")).join("\n")
+            }
+        } else if self.basic {
             let config = config.into_basic();
             if self.json {
                 serde_json::to_string_pretty(&config)?
@@ -5,7 +5,8 @@ use eyre::{Context, Result};
 use forge::{
     coverage::{
         analysis::SourceAnalyzer, anchors::find_anchors, BytecodeReporter, ContractId,
-        CoverageReport, CoverageReporter, DebugReporter, ItemAnchor, LcovReporter, SummaryReporter,
+        CoverageFilter, CoverageReport, CoverageReporter, CoverageSummary, DebugReporter,
+        ItemAnchor, LcovReporter, SummaryReporter,
     },
     inspectors::CheatsConfig,
     opts::EvmOpts,
@@ -276,12 +277,22 @@ impl CoverageArgs {
                     ))
                 })
                 .collect();
+            report.exclude_items(version.clone(), source_analysis.ignored_items);
             report.add_items(version, source_analysis.items);
             report.add_anchors(anchors);
         }
 
         report.add_source_maps(source_maps);
 
+        // Exclude the sources and contracts of the coverage config
+        let filter = CoverageFilter::new(&config.coverage)?;
+        if !filter.is_empty() {
+            report.exclude_items_by(|path, item| filter.is_excluded(path, &item.loc.contract_name));
+            report.exclude_contracts_by(|path, contract_name| {
+                filter.is_excluded(path, contract_name)
+            });
+        }
+
         Ok(report)
     }
 
@@ -311,6 +322,7 @@ impl CoverageArgs {
             })
             .set_coverage(true)
             .build(root.clone(), output, env, evm_opts)?;
+        runner.threads = config.threads;
 
         // Run tests
         let known_contracts = runner.known_contracts.clone();
@@ -362,13 +374,8 @@ impl CoverageArgs {
             match report_kind {
                 CoverageReportKind::Summary => SummaryReporter::default().report(&report),
                 CoverageReportKind::Lcov => {
-                    if let Some(report_file) = self.report_file {
-                        return LcovReporter::new(&mut fs::create_file(root.join(report_file))?)
-                            .report(&report)
-                    } else {
-                        return LcovReporter::new(&mut fs::create_file(root.join("lcov.info"))?)
-                            .report(&report)
-                    }
+                    let report_file = self.report_file.as_deref().unwrap_or("lcov.info".as_ref());
+                    LcovReporter::new(&mut fs::create_file(root.join(report_file))?).report(&report)
                 }
                 CoverageReportKind::Bytecode => {
                     let destdir = root.join("bytecode-coverage");
@@ -379,6 +386,24 @@ impl CoverageArgs {
                 CoverageReportKind::Debug => DebugReporter.report(&report),
             }?;
         }
+
+        // Report the excluded totals, so that the numbers of the report add up
+        let mut excluded_files = 0;
+        let mut excluded = CoverageSummary::default();
+        for (_, summary) in report.excluded_summary_by_file() {
+            excluded_files += 1;
+            excluded += &summary;
+        }
+        if excluded_files > 0 {
+            p_println!(!self.opts.silent =>
+                "Excluded from the report: {} lines, {} statements, {} branches and {} functions \
+                 in {excluded_files} files.",
+                excluded.line_count,
+                excluded.statement_count,
+                excluded.branch_count,
+                excluded.function_count
+            );
+        }
         Ok(())
     }
 
@@ -16,17 +16,23 @@ use ethers_providers::Middleware;
 use eyre::{Context, Result};
 use foundry_cli::{
     opts::{CoreBuildArgs, EthereumOpts, EtherscanOpts, TransactionOpts},
-    utils::{self, read_constructor_args_file, remove_contract, LoadConfig},
+    utils::{self, read_constructor_args, remove_contract, LoadConfig},
 };
 use foundry_common::{
     compile::ProjectCompiler,
     fmt::parse_tokens,
     provider::ethers::estimate_eip1559_fees,
     types::{ToAlloy, ToEthers},
+    vyper,
 };
 use foundry_compilers::{artifacts::BytecodeObject, info::ContractInfo, utils::canonicalized};
 use serde_json::json;
-use std::{borrow::Borrow, marker::PhantomData, path::PathBuf, sync::Arc};
+use std::{
+    borrow::Borrow,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 /// CLI arguments for `forge create`.
 #[derive(Clone, Debug, Parser)]
@@ -44,6 +50,10 @@ pub struct CreateArgs {
     constructor_args: Vec<String>,
 
     /// The path to a file containing the constructor arguments.
+    ///
+    /// A `.json` file contains either an array of values or an object keyed by parameter name,
+    /// e.g. `{"owners": ["0x..."], "config": {"threshold": 2}}`. Any other file contains the
+    /// whitespace separated arguments.
     #[clap(
         long,
         value_hint = ValueHint::FilePath,
@@ -91,28 +101,45 @@ impl CreateArgs {
     pub async fn run(mut self) -> Result<()> {
         // Find Project & Compile
         let project = self.opts.project()?;
-        let mut output =
-            ProjectCompiler::new().quiet_if(self.json || self.opts.silent).compile(&project)?;
 
         if let Some(ref mut path) = self.contract.path {
             // paths are absolute in the project's output
             *path = canonicalized(project.root().join(&path)).to_string_lossy().to_string();
         }
 
-        let (abi, bin, _) = remove_contract(&mut output, &self.contract)?;
-
-        let bin = match bin.object {
-            BytecodeObject::Bytecode(_) => bin.object,
-            _ => {
-                let link_refs = bin
-                    .link_references
-                    .iter()
-                    .flat_map(|(path, names)| {
-                        names.keys().map(move |name| format!("\t{name}: {path}"))
-                    })
-                    .collect::<Vec<String>>()
-                    .join("\n");
-                eyre::bail!("Dynamic linking not supported in `create` command - deploy the following library contracts first, then provide the address to link at compile time\n{}", link_refs)
+        let (abi, bin) = match self.contract.path.clone().filter(vyper::is_vyper_file) {
+            Some(path) => {
+                let config = self.opts.try_load_config_emit_warnings()?;
+                let output = vyper::compile_project(&config, None, self.json || self.opts.silent)?
+                    .unwrap_or_default();
+                let artifact = output
+                    .artifacts
+                    .into_iter()
+                    .find(|(source, _)| canonicalized(source) == Path::new(&path))
+                    .map(|(_, artifact)| artifact)
+                    .ok_or_else(|| eyre::eyre!("`{path}` is not a Vyper source of the project"))?;
+                (artifact.abi, BytecodeObject::Bytecode(artifact.bytecode.object))
+            }
+            None => {
+                let mut output = ProjectCompiler::new()
+                    .quiet_if(self.json || self.opts.silent)
+                    .compile(&project)?;
+                let (abi, bin, _) = remove_contract(&mut output, &self.contract)?;
+                let bin = match bin.object {
+                    BytecodeObject::Bytecode(_) => bin.object,
+                    _ => {
+                        let link_refs = bin
+                            .link_references
+                            .iter()
+                            .flat_map(|(path, names)| {
+                                names.keys().map(move |name| format!("\t{name}: {path}"))
+                            })
+                            .collect::<Vec<String>>()
+                            .join("\n");
+                        eyre::bail!("Dynamic linking not supported in `create` command - deploy the following library contracts first, then provide the address to link at compile time\n{}", link_refs)
+                    }
+                };
+                (abi, bin)
             }
         };
 
@@ -121,13 +148,11 @@ impl CreateArgs {
         let provider = utils::get_provider(&config)?;
         let params = match abi.constructor {
             Some(ref v) => {
-                let constructor_args =
-                    if let Some(ref constructor_args_path) = self.constructor_args_path {
-                        read_constructor_args_file(constructor_args_path.to_path_buf())?
-                    } else {
-                        self.constructor_args.clone()
-                    };
-                self.parse_constructor_args(v, &constructor_args)?
+                if let Some(ref constructor_args_path) = self.constructor_args_path {
+                    read_constructor_args(constructor_args_path, v)?
+                } else {
+                    self.parse_constructor_args(v, &self.constructor_args)?
+                }
             }
             None => vec![],
         };
@@ -193,10 +218,13 @@ impl CreateArgs {
         };
 
         // Check config for Etherscan API Keys to avoid preflight check failing if no
-        // ETHERSCAN_API_KEY value set.
+        // ETHERSCAN_API_KEY value set. All the keys of the chain are passed on, separated by
+        // commas like the `--etherscan-api-key` argument, the requests of the verification are
+        // spread across them.
         let config = verify.load_config_emit_warnings();
-        verify.etherscan.key =
-            config.get_etherscan_config_with_chain(Some(chain.into()))?.map(|c| c.key);
+        verify.etherscan.key = config
+            .get_etherscan_config_with_chain(Some(chain.into()))?
+            .map(|c| c.api_keys().join(","));
 
         verify.verification_provider()?.preflight_check(verify).await?;
         Ok(())
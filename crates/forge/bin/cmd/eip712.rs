@@ -0,0 +1,153 @@
+use clap::{Parser, ValueHint};
+use eyre::{eyre, Result};
+use forge::eip712::Eip712Structs;
+use foundry_cli::{
+    opts::{CoreBuildArgs, ProjectPathsArgs},
+    utils::LoadConfig,
+};
+use foundry_common::fs;
+use foundry_compilers::remappings::Remapping;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+use yansi::Paint;
+
+/// CLI arguments for `forge eip712`.
+#[derive(Clone, Debug, Parser)]
+pub struct Eip712Args {
+    /// The path of the file declaring the structs, optionally followed by `:<struct>` to only
+    /// output that struct.
+    ///
+    /// Structs declared in contracts can be referred to as `<contract>.<struct>`.
+    #[clap(value_hint = ValueHint::FilePath, value_name = "PATH[:STRUCT]")]
+    pub target: String,
+
+    /// Output a Solidity library with the typehash constants and `hashStruct` functions of the
+    /// structs.
+    #[clap(long, conflicts_with = "json")]
+    pub library: bool,
+
+    /// Output the encoded types and typehashes as JSON.
+    #[clap(long)]
+    pub json: bool,
+
+    #[clap(flatten)]
+    project_paths: ProjectPathsArgs,
+}
+
+impl Eip712Args {
+    pub fn run(self) -> Result<()> {
+        let Eip712Args { target, library, json, project_paths } = self;
+
+        // eip712 is a subset of `BuildArgs` so we can reuse that to get the config
+        let build_args = CoreBuildArgs { project_paths, ..Default::default() };
+        let config = build_args.try_load_config_emit_warnings()?;
+        let root = &config.__root.0;
+
+        let (target_path, struct_name) = match target.rsplit_once(':') {
+            Some((path, name)) if !Path::new(&target).exists() => (path, Some(name)),
+            _ => (target.as_str(), None),
+        };
+        let target_path = dunce::canonicalize(target_path)
+            .map_err(|err| eyre!("failed to read {target_path}: {err}"))?;
+
+        // the structs may reference types declared in imported files
+        let remappings = config.get_all_remappings();
+        let mut structs = Eip712Structs::default();
+        let mut target_structs = vec![];
+        let mut visited = HashSet::new();
+        let mut queue = vec![target_path.clone()];
+        while let Some(file) = queue.pop() {
+            if !visited.insert(file.clone()) {
+                continue
+            }
+            let parsed = structs
+                .add_source(&fs::read_to_string(&file)?)
+                .map_err(|err| eyre!("{}: {err}", file.display()))?;
+            if file == target_path {
+                target_structs = parsed.structs;
+            }
+            for import in parsed.imports {
+                match resolve_import(root, &remappings, &file, &import) {
+                    Some(path) => queue.push(path),
+                    None => warn!(%import, file = %file.display(), "failed to resolve import"),
+                }
+            }
+        }
+
+        let names = match struct_name {
+            Some(name) => vec![name.to_string()],
+            None => {
+                // skip the structs which can't be encoded, e.g. storage structs with mappings
+                let mut names = vec![];
+                for name in target_structs {
+                    match structs.encode_type(&name) {
+                        Ok(_) => names.push(name),
+                        Err(err) => eprintln!(
+                            "{}",
+                            Paint::yellow(format!("Warning: skipping `{name}`: {err}"))
+                        ),
+                    }
+                }
+                names
+            }
+        };
+        if names.is_empty() {
+            eyre::bail!("no EIP-712 compatible structs found in {}", target_path.display());
+        }
+
+        if library {
+            let import = target_path.strip_prefix(root).unwrap_or(&target_path);
+            print!("{}", structs.library(&names, &import.display().to_string())?);
+            return Ok(())
+        }
+
+        let encodings =
+            names.iter().map(|name| structs.encoding(name)).collect::<Result<Vec<_>>>()?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&encodings)?);
+            return Ok(())
+        }
+        for (i, encoding) in encodings.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            println!("{}:", encoding.name);
+            println!("  encodeType: {}", encoding.encode_type);
+            println!("  typehash:   {}", encoding.type_hash);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the path of a file imported by `file`, relative imports are resolved from its
+/// directory and the others with the remappings, or from the root.
+fn resolve_import(
+    root: &Path,
+    remappings: &[Remapping],
+    file: &Path,
+    import: &str,
+) -> Option<PathBuf> {
+    let path = if import.starts_with("./") || import.starts_with("../") {
+        file.parent()?.join(import)
+    } else {
+        let relative_file = file.strip_prefix(root).unwrap_or(file);
+        let remapping = remappings
+            .iter()
+            .filter(|remapping| import.starts_with(&remapping.name))
+            .filter(|remapping| {
+                remapping.context.as_ref().map_or(true, |context| {
+                    relative_file.starts_with(context) || file.starts_with(context)
+                })
+            })
+            .max_by_key(|remapping| remapping.name.len());
+        match remapping {
+            Some(remapping) => {
+                PathBuf::from(format!("{}{}", remapping.path, &import[remapping.name.len()..]))
+            }
+            None => root.join(import),
+        }
+    };
+    dunce::canonicalize(path).ok()
+}
@@ -1,8 +1,17 @@
+use alloy_primitives::Address;
 use clap::Parser;
 use comfy_table::{presets::ASCII_MARKDOWN, Table};
 use eyre::Result;
-use foundry_cli::opts::{CompilerArgs, CoreBuildArgs};
-use foundry_common::compile::ProjectCompiler;
+use foundry_block_explorers::Client;
+use foundry_cli::{
+    opts::{CompilerArgs, CoreBuildArgs, EtherscanOpts},
+    utils,
+};
+use foundry_common::{
+    abi::find_source,
+    compile::{etherscan_project, ProjectCompiler},
+    storage_diff::{print_storage_layout_diff, StorageLayoutDiff},
+};
 use foundry_compilers::{
     artifacts::{
         output_selection::{
@@ -14,6 +23,7 @@ use foundry_compilers::{
     info::ContractInfo,
     utils::canonicalize,
 };
+use foundry_config::Config;
 use std::fmt;
 
 /// CLI arguments for `forge inspect`.
@@ -30,6 +40,25 @@ pub struct InspectArgs {
     #[clap(long)]
     pub pretty: bool,
 
+    /// Compare the storage layout with the one of another contract, in the form
+    /// `(<path>:)?<contractname>`.
+    ///
+    /// The inspected contract is the new implementation of an upgradeable contract, the compared
+    /// one the deployed implementation. Exits with an error if variables of the compared contract
+    /// were moved, resized, retyped or deleted.
+    #[clap(long, value_name = "CONTRACT", conflicts_with = "compare_address")]
+    pub compare: Option<ContractInfo>,
+
+    /// Compare the storage layout with the one of the verified contract at the address, fetched
+    /// from Etherscan.
+    ///
+    /// See `--compare`.
+    #[clap(long, value_name = "ADDRESS")]
+    pub compare_address: Option<Address>,
+
+    #[clap(flatten)]
+    etherscan: EtherscanOpts,
+
     /// All build arguments are supported
     #[clap(flatten)]
     build: CoreBuildArgs,
@@ -37,10 +66,25 @@ pub struct InspectArgs {
 
 impl InspectArgs {
     pub fn run(self) -> Result<()> {
-        let InspectArgs { mut contract, field, build, pretty } = self;
+        let InspectArgs {
+            mut contract,
+            field,
+            build,
+            pretty,
+            mut compare,
+            compare_address,
+            etherscan,
+        } = self;
 
         trace!(target: "forge", ?field, ?contract, "running forge inspect");
 
+        let is_compare = compare.is_some() || compare_address.is_some();
+        if is_compare && field != ContractArtifactField::StorageLayout {
+            eyre::bail!(
+                "`--compare` and `--compare-address` are only supported for `storage-layout`"
+            );
+        }
+
         // Map field to ContractOutputSelection
         let mut cos = build.compiler.extra_output;
         if !field.is_default() && !cos.iter().any(|selected| field == *selected) {
@@ -63,10 +107,20 @@ impl InspectArgs {
         // Build the project
         let project = modified_build_args.project()?;
         let mut compiler = ProjectCompiler::new().quiet(true);
-        if let Some(contract_path) = &mut contract.path {
-            let target_path = canonicalize(&*contract_path)?;
-            *contract_path = target_path.to_string_lossy().to_string();
-            compiler = compiler.files([target_path]);
+        let mut files = Vec::new();
+        let mut has_paths = true;
+        for target in std::iter::once(&mut contract).chain(compare.as_mut()) {
+            if let Some(contract_path) = &mut target.path {
+                let target_path = canonicalize(&*contract_path)?;
+                *contract_path = target_path.to_string_lossy().to_string();
+                files.push(target_path);
+            } else {
+                has_paths = false;
+            }
+        }
+        // compile the whole project if any of the contracts has no path
+        if has_paths {
+            compiler = compiler.files(files);
         }
         let output = compiler.compile(&project)?;
 
@@ -75,6 +129,30 @@ impl InspectArgs {
             eyre::eyre!("Could not find artifact `{contract}` in the compiled artifacts")
         })?;
 
+        if is_compare {
+            let Some(new) = &artifact.storage_layout else {
+                eyre::bail!("Could not get storage layout of `{contract}`");
+            };
+            let old = if let Some(compare) = &compare {
+                let artifact = output.find_contract(compare).ok_or_else(|| {
+                    eyre::eyre!("Could not find artifact `{compare}` in the compiled artifacts")
+                })?;
+                artifact
+                    .storage_layout
+                    .clone()
+                    .ok_or_else(|| eyre::eyre!("Could not get storage layout of `{compare}`"))?
+            } else {
+                let address = compare_address.expect("is compare");
+                utils::block_on(fetch_storage_layout(address, &etherscan))?
+            };
+            let diff = StorageLayoutDiff::new(&old, new);
+            print_storage_layout_diff(&diff, pretty)?;
+            if !diff.is_compatible() {
+                std::process::exit(1);
+            }
+            return Ok(())
+        }
+
         // Match on ContractArtifactFields and pretty-print
         match field {
             ContractArtifactField::Abi => {
@@ -193,6 +271,44 @@ pub fn print_storage_layout(storage_layout: Option<&StorageLayout>, pretty: bool
     Ok(())
 }
 
+/// Compiles the verified source of the contract at the address and returns its storage layout.
+async fn fetch_storage_layout(
+    address: Address,
+    etherscan: &EtherscanOpts,
+) -> Result<StorageLayout> {
+    let config = Config::from(etherscan);
+    let chain = config.chain.unwrap_or_default();
+    let api_key = config.get_etherscan_api_key(Some(chain)).unwrap_or_default();
+    let client = Client::new(chain, api_key)?;
+
+    let source = find_source(client, address).await?;
+    let metadata =
+        source.items.first().ok_or_else(|| eyre::eyre!("No source code found for {address}"))?;
+    if metadata.is_vyper() {
+        eyre::bail!(
+            "`{}` at {address} is a Vyper contract, only Solidity storage layouts can be compared",
+            metadata.contract_name
+        );
+    }
+
+    let root = tempfile::tempdir()?;
+    let mut project = etherscan_project(metadata, root.path())?;
+    project.artifacts.additional_values.storage_layout = true;
+    let output_selection = project.artifacts.output_selection();
+    project.solc_config.settings.push_all(output_selection);
+
+    let output = ProjectCompiler::new().quiet(true).compile(&project)?;
+    let layout = output
+        .artifacts()
+        .find(|(name, _)| name == &metadata.contract_name)
+        .and_then(|(_, artifact)| artifact.storage_layout.clone())
+        .ok_or_else(|| {
+            eyre::eyre!("Could not get storage layout of `{}` at {address}", metadata.contract_name)
+        })?;
+    root.close()?;
+    Ok(layout)
+}
+
 /// Contract level output selection
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ContractArtifactField {
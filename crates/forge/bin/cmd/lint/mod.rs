@@ -0,0 +1,258 @@
+use clap::{Parser, ValueEnum, ValueHint};
+use eyre::Result;
+use forge_fmt::{offset_to_line_column, print_diagnostics_report};
+use foundry_cli::utils::{FoundryPathExt, LoadConfig};
+use foundry_common::{fs, glob::expand_globs, term::cli_warn};
+use foundry_config::{impl_figment_convert_basic, Config, LintSeverity};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use yansi::Paint;
+
+mod rules;
+use rules::{lint_source, Fix, Rule};
+
+/// CLI arguments for `forge lint`.
+#[derive(Clone, Debug, Parser)]
+pub struct LintArgs {
+    /// Paths to the files or directories to lint.
+    ///
+    /// By default the sources of the project.
+    #[clap(value_hint = ValueHint::FilePath, value_name = "PATH", num_args(1..))]
+    paths: Vec<PathBuf>,
+
+    /// The project's root path.
+    ///
+    /// By default root of the Git repository, if in one,
+    /// or the current working directory.
+    #[clap(long, value_hint = ValueHint::DirPath, value_name = "PATH")]
+    root: Option<PathBuf>,
+
+    /// Fix the findings that have a mechanical fix, e.g. remove unused imports.
+    #[clap(long)]
+    fix: bool,
+
+    /// The format of the findings.
+    #[clap(long, value_enum, default_value = "text")]
+    format: LintFormat,
+}
+
+impl_figment_convert_basic!(LintArgs);
+
+/// The output formats of `forge lint`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum LintFormat {
+    Text,
+    Json,
+    /// The Static Analysis Results Interchange Format, for annotations in CI.
+    Sarif,
+}
+
+/// A finding of a rule in a file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub rule: Rule,
+    pub severity: LintSeverity,
+    /// The path of the file, relative to the root of the project.
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl LintArgs {
+    pub fn run(self) -> Result<()> {
+        let config = self.try_load_config_emit_warnings()?;
+        let files = if self.paths.is_empty() {
+            project_sources(&config)?
+        } else {
+            let mut files = Vec::new();
+            for path in &self.paths {
+                if path.is_dir() {
+                    files.extend(foundry_compilers::utils::source_files_iter(path));
+                } else if path.is_sol() {
+                    files.push(path.clone());
+                } else {
+                    warn!("Cannot lint path {}", path.display());
+                }
+            }
+            files
+        };
+
+        let findings = lint_files(&config, &files, self.fix, false)?;
+        match self.format {
+            LintFormat::Text => print!("{}", report(&findings)),
+            LintFormat::Json => println!("{}", serde_json::to_string_pretty(&findings)?),
+            LintFormat::Sarif => println!("{}", serde_json::to_string_pretty(&sarif(&findings))?),
+        }
+        check_findings(&findings, config.lint.fail_on.unwrap_or(LintSeverity::Error))
+    }
+}
+
+/// Lints the sources of the project after `forge build`, which fails on the findings of at least
+/// the `fail_on` severity.
+///
+/// Sources that can't be parsed are skipped, the compiler reports their errors.
+pub fn lint_build(config: &Config, fail_on: LintSeverity) -> Result<()> {
+    let findings = lint_files(config, &project_sources(config)?, false, true)?;
+    eprint!("{}", report(&findings));
+    check_findings(&findings, fail_on)
+}
+
+/// Returns the sources of the project, without the ones ignored by `lint.ignore`.
+fn project_sources(config: &Config) -> Result<Vec<PathBuf>> {
+    let ignored = expand_globs(&config.__root.0, config.lint.ignore.iter())?
+        .iter()
+        .flat_map(fs::canonicalize_path)
+        .collect::<Vec<_>>();
+    Ok(foundry_compilers::utils::source_files_iter(&config.src)
+        .filter(|path| fs::canonicalize_path(path).map_or(true, |path| !ignored.contains(&path)))
+        .collect())
+}
+
+/// Lints the files, and fixes the findings that have a fix if `fix` is set, which are then not
+/// reported.
+fn lint_files(
+    config: &Config,
+    files: &[PathBuf],
+    fix: bool,
+    skip_unparsed: bool,
+) -> Result<Vec<Finding>> {
+    let root = &config.__root.0;
+    let findings = files
+        .par_iter()
+        .map(|file| -> Result<Vec<Finding>> {
+            let src = fs::read_to_string(file)?;
+            let name = file.strip_prefix(root).unwrap_or(file);
+            let mut findings = match lint_source(&src, &config.lint) {
+                Ok(findings) => findings,
+                Err(_) if skip_unparsed => {
+                    cli_warn!("Skipped linting {}, which can't be parsed", name.display());
+                    return Ok(Vec::new())
+                }
+                Err(diagnostics) => {
+                    let _ = print_diagnostics_report(&src, Some(file), diagnostics);
+                    eyre::bail!("Failed to parse Solidity code for {}", name.display())
+                }
+            };
+
+            if fix {
+                let fixes =
+                    findings.iter().filter_map(|finding| finding.fix.as_ref()).collect::<Vec<_>>();
+                if !fixes.is_empty() {
+                    fs::write(file, Fix::apply_all(&src, &fixes))?;
+                    findings.retain(|finding| finding.fix.is_none());
+                }
+            }
+
+            Ok(findings
+                .into_iter()
+                .map(|finding| {
+                    let (line, column) = offset_to_line_column(&src, finding.range.start);
+                    Finding {
+                        rule: finding.rule,
+                        severity: finding.severity,
+                        file: name.to_path_buf(),
+                        line,
+                        column,
+                        message: finding.message,
+                    }
+                })
+                .collect())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut findings = findings.into_iter().flatten().collect::<Vec<_>>();
+    findings.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+    Ok(findings)
+}
+
+/// Fails if any finding has at least the `fail_on` severity, unless it's `off`.
+fn check_findings(findings: &[Finding], fail_on: LintSeverity) -> Result<()> {
+    if fail_on == LintSeverity::Off {
+        return Ok(())
+    }
+    let failing = findings.iter().filter(|finding| finding.severity >= fail_on).count();
+    if failing > 0 {
+        eyre::bail!("{failing} lint finding(s) with a severity of `{fail_on}` or higher")
+    }
+    Ok(())
+}
+
+/// Formats the findings like the diagnostics of the compiler.
+fn report(findings: &[Finding]) -> String {
+    let mut out = String::new();
+    for finding in findings {
+        let severity = match finding.severity {
+            LintSeverity::Error => Paint::red("error"),
+            LintSeverity::Warning => Paint::yellow("warning"),
+            _ => Paint::cyan("info"),
+        };
+        out.push_str(&format!(
+            "{severity}[{}]: {}\n  --> {}:{}:{}\n",
+            finding.rule,
+            finding.message,
+            finding.file.display(),
+            finding.line,
+            finding.column
+        ));
+    }
+    out
+}
+
+/// Returns the findings as a SARIF 2.1.0 log.
+fn sarif(findings: &[Finding]) -> serde_json::Value {
+    let level = |severity: LintSeverity| match severity {
+        LintSeverity::Error => "error",
+        LintSeverity::Warning => "warning",
+        _ => "note",
+    };
+    let rules = Rule::ALL
+        .iter()
+        .map(|rule| {
+            serde_json::json!({
+                "id": rule.id(),
+                "shortDescription": { "text": rule.description() },
+                "defaultConfiguration": { "level": level(rule.default_severity()) },
+            })
+        })
+        .collect::<Vec<_>>();
+    let results = findings
+        .iter()
+        .map(|finding| {
+            serde_json::json!({
+                "ruleId": finding.rule.id(),
+                "level": level(finding.severity),
+                "message": { "text": finding.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": uri(&finding.file) },
+                        "region": { "startLine": finding.line, "startColumn": finding.column },
+                    },
+                }],
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "forge-lint",
+                    "informationUri": "https://github.com/foundry-rs/foundry",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// Returns the relative URI of the path, with forward slashes.
+fn uri(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,792 @@
+use foundry_config::{LintConfig, LintSeverity};
+use serde::Serialize;
+use solang_parser::{
+    diagnostics::Diagnostic,
+    lexer::{Lexer, Token},
+    pt::{
+        Base, CatchClause, ContractDefinition, ContractPart, Expression, FunctionAttribute,
+        FunctionDefinition, FunctionTy, Identifier, Import, Loc, Mutability, SourceUnitPart,
+        Statement, VariableAttribute, VariableDefinition, Visibility,
+    },
+};
+use std::{collections::HashSet, fmt, ops::Range};
+
+/// The rules of `forge lint`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    UnusedImport,
+    ShadowedStateVariable,
+    UncheckedCall,
+    MissingEvent,
+    TxOrigin,
+    LiteralAddress,
+}
+
+impl Rule {
+    /// All rules, in the order of their checks.
+    pub const ALL: [Rule; 6] = [
+        Rule::UnusedImport,
+        Rule::ShadowedStateVariable,
+        Rule::UncheckedCall,
+        Rule::MissingEvent,
+        Rule::TxOrigin,
+        Rule::LiteralAddress,
+    ];
+
+    /// The id of the rule, which configures its severity in `[lint.severity]`.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::UnusedImport => "unused-import",
+            Rule::ShadowedStateVariable => "shadowed-state-variable",
+            Rule::UncheckedCall => "unchecked-call",
+            Rule::MissingEvent => "missing-event",
+            Rule::TxOrigin => "tx-origin",
+            Rule::LiteralAddress => "literal-address",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::UnusedImport => "Imported symbols that are never used",
+            Rule::ShadowedStateVariable => {
+                "Local variables and parameters named like a state variable"
+            }
+            Rule::UncheckedCall => "Low-level calls whose success isn't checked",
+            Rule::MissingEvent => "State-changing external functions that emit no event",
+            Rule::TxOrigin => "Authorization with `tx.origin`",
+            Rule::LiteralAddress => "Literal addresses outside of constants and immutables",
+        }
+    }
+
+    pub fn default_severity(&self) -> LintSeverity {
+        match self {
+            Rule::UnusedImport | Rule::ShadowedStateVariable | Rule::LiteralAddress => {
+                LintSeverity::Warning
+            }
+            Rule::UncheckedCall | Rule::TxOrigin => LintSeverity::Error,
+            Rule::MissingEvent => LintSeverity::Info,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl Serialize for Rule {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
+}
+
+/// A finding of a rule in a source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceFinding {
+    pub rule: Rule,
+    pub severity: LintSeverity,
+    /// The byte range of the finding in the source.
+    pub range: Range<usize>,
+    pub message: String,
+    /// The edit that fixes the finding, if it's mechanical.
+    pub fix: Option<Fix>,
+}
+
+/// Replaces a byte range of a source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fix {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl Fix {
+    /// Applies the fixes, which must not overlap, to the source.
+    pub fn apply_all(src: &str, fixes: &[&Fix]) -> String {
+        let mut fixes = fixes.to_vec();
+        fixes.sort_by_key(|fix| std::cmp::Reverse(fix.range.start));
+        // the symbols of a directive share the fix of the directive
+        fixes.dedup();
+        let mut fixed = src.to_string();
+        for fix in fixes {
+            fixed.replace_range(fix.range.clone(), &fix.replacement);
+        }
+        fixed
+    }
+}
+
+/// Runs all enabled rules on the source.
+pub fn lint_source(src: &str, config: &LintConfig) -> Result<Vec<SourceFinding>, Vec<Diagnostic>> {
+    let (pt, _) = solang_parser::parse(src, 0)?;
+    let mut linter = Linter { src, config, findings: Vec::new() };
+
+    let contracts =
+        pt.0.iter()
+            .filter_map(|part| match part {
+                SourceUnitPart::ContractDefinition(contract) => Some(&**contract),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+    linter.unused_imports(&pt.0);
+    for part in &pt.0 {
+        match part {
+            SourceUnitPart::ContractDefinition(contract) => linter.contract(contract, &contracts),
+            SourceUnitPart::FunctionDefinition(function) => linter.function(function, &[]),
+            SourceUnitPart::VariableDefinition(variable) => linter.state_variable(variable),
+            _ => {}
+        }
+    }
+
+    linter.findings.sort_by_key(|finding| (finding.range.start, finding.rule));
+    Ok(linter.findings)
+}
+
+struct Linter<'a> {
+    src: &'a str,
+    config: &'a LintConfig,
+    findings: Vec<SourceFinding>,
+}
+
+impl<'a> Linter<'a> {
+    fn report(&mut self, rule: Rule, loc: Loc, message: String, fix: Option<Fix>) {
+        let severity = self.config.severity_of(rule.id(), rule.default_severity());
+        if severity != LintSeverity::Off {
+            let range = loc.start()..loc.end();
+            self.findings.push(SourceFinding { rule, severity, range, message, fix });
+        }
+    }
+
+    /// Reports the imported symbols which aren't used outside of the imports.
+    fn unused_imports(&mut self, parts: &[SourceUnitPart]) {
+        let imports = parts
+            .iter()
+            .filter_map(|part| match part {
+                SourceUnitPart::ImportDirective(import) => {
+                    let loc = match import {
+                        Import::Plain(_, loc) |
+                        Import::GlobalSymbol(_, _, loc) |
+                        Import::Rename(_, _, loc) => *loc,
+                    };
+                    Some((import, self.directive_range(loc)))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if imports.is_empty() {
+            return
+        }
+
+        let mut comments = Vec::new();
+        let mut errors = Vec::new();
+        let used = Lexer::new(self.src, 0, &mut comments, &mut errors)
+            .filter(|(start, _, _)| !imports.iter().any(|(_, range)| range.contains(start)))
+            .filter_map(|(_, token, _)| match token {
+                Token::Identifier(name) => Some(name.to_string()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+
+        for (import, range) in imports {
+            match import {
+                Import::GlobalSymbol(_, alias, _) if !used.contains(&alias.name) => {
+                    let fix = Some(self.removal(range));
+                    let message = format!("`{}` is imported but never used", alias.name);
+                    self.report(Rule::UnusedImport, alias.loc, message, fix);
+                }
+                Import::Rename(_, symbols, _) => {
+                    let (unused, kept): (Vec<_>, Vec<_>) =
+                        symbols.iter().partition(|symbol| !used.contains(&local_name(symbol).name));
+                    let fix = if unused.is_empty() {
+                        continue
+                    } else if kept.is_empty() {
+                        self.removal(range)
+                    } else {
+                        self.kept_symbols(range, &kept)
+                    };
+                    for symbol in unused {
+                        let local = local_name(symbol);
+                        let message = format!("`{}` is imported but never used", local.name);
+                        self.report(Rule::UnusedImport, local.loc, message, Some(fix.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the range of an import directive, its `;` included.
+    fn directive_range(&self, loc: Loc) -> Range<usize> {
+        let start = loc.start();
+        let end = self.src[start..].find(';').map_or(loc.end(), |i| start + i + 1).max(loc.end());
+        start..end
+    }
+
+    /// Returns the fix that removes the directive, with the rest of its line if it's blank.
+    fn removal(&self, range: Range<usize>) -> Fix {
+        let rest = &self.src[range.end..];
+        let end = match rest.find('\n') {
+            Some(i) if rest[..i].trim().is_empty() => range.end + i + 1,
+            _ => range.end,
+        };
+        Fix { range: range.start..end, replacement: String::new() }
+    }
+
+    /// Returns the fix that only imports the `kept` symbols of the directive.
+    fn kept_symbols(&self, range: Range<usize>, kept: &[&(Identifier, Option<Identifier>)]) -> Fix {
+        let directive = &self.src[range.clone()];
+        let (Some(open), Some(close)) = (directive.find('{'), directive.find('}')) else {
+            return Fix { range: range.clone(), replacement: directive.to_string() }
+        };
+        let spaced = directive[open + 1..].starts_with(' ');
+        let symbols = kept
+            .iter()
+            .map(|(symbol, alias)| match alias {
+                Some(alias) => format!("{} as {}", symbol.name, alias.name),
+                None => symbol.name.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let symbols = if spaced { format!(" {symbols} ") } else { symbols };
+        Fix { range: range.start + open + 1..range.start + close, replacement: symbols }
+    }
+
+    fn contract(&mut self, contract: &ContractDefinition, contracts: &[&ContractDefinition]) {
+        let state_variables = state_variables(contract, contracts);
+        for base in &contract.base {
+            self.base(base);
+        }
+        for part in &contract.parts {
+            match part {
+                ContractPart::VariableDefinition(variable) => self.state_variable(variable),
+                ContractPart::FunctionDefinition(function) => {
+                    self.function(function, &state_variables)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn base(&mut self, base: &Base) {
+        for arg in base.args.iter().flatten() {
+            self.expression(arg);
+        }
+    }
+
+    fn state_variable(&mut self, variable: &VariableDefinition) {
+        let constant = variable.attrs.iter().any(|attr| {
+            matches!(attr, VariableAttribute::Constant(_) | VariableAttribute::Immutable(_))
+        });
+        if let Some(initializer) = &variable.initializer {
+            let mut literal_addresses = !constant;
+            for_each_expression(initializer, &mut |expr| {
+                self.check_expression(expr, &mut literal_addresses)
+            });
+        }
+    }
+
+    /// Checks the function of a contract with the `state_variables`, the ones of its bases
+    /// included, or a free function.
+    fn function(&mut self, function: &FunctionDefinition, state_variables: &[&VariableDefinition]) {
+        for attr in &function.attributes {
+            if let FunctionAttribute::BaseOrModifier(_, base) = attr {
+                self.base(base);
+            }
+        }
+        let Some(body) = &function.body else { return };
+
+        // shadowed state variables
+        let mut locals = function
+            .params
+            .iter()
+            .chain(&function.returns)
+            .filter_map(|(_, param)| param.as_ref()?.name.as_ref())
+            .collect::<Vec<_>>();
+        for_each_statement(body, &mut |stmt| {
+            if let Statement::VariableDefinition(_, decl, _) = stmt {
+                locals.extend(&decl.name);
+            }
+        });
+        for local in locals {
+            let shadowed = state_variables
+                .iter()
+                .any(|var| var.name.as_ref().map_or(false, |name| name.name == local.name));
+            if shadowed {
+                let message = format!("`{}` shadows a state variable", local.name);
+                self.report(Rule::ShadowedStateVariable, local.loc, message, None);
+            }
+        }
+
+        let mut writes_state = false;
+        let mut emits = false;
+        for_each_statement(body, &mut |stmt| {
+            match stmt {
+                Statement::Emit(..) => emits = true,
+                Statement::Expression(loc, expr) if is_unchecked_call(expr) => {
+                    let message = "the success of the low-level call isn't checked".to_string();
+                    self.report(Rule::UncheckedCall, *loc, message, None);
+                }
+                _ => {}
+            }
+            for expr in statement_expressions(stmt) {
+                for_each_expression(expr, &mut |expr| {
+                    writes_state |= written_variable(expr).map_or(false, |name| {
+                        state_variables.iter().any(|var| {
+                            var.name.as_ref().map_or(false, |var| var.name == name) &&
+                                !is_constant(var)
+                        })
+                    });
+                    self.check_expression(expr, &mut true);
+                });
+            }
+        });
+
+        let external = function.attributes.iter().any(|attr| {
+            matches!(
+                attr,
+                FunctionAttribute::Visibility(Visibility::External(_) | Visibility::Public(_))
+            )
+        });
+        let read_only = function.attributes.iter().any(|attr| {
+            matches!(
+                attr,
+                FunctionAttribute::Mutability(
+                    Mutability::Pure(_) | Mutability::View(_) | Mutability::Constant(_)
+                )
+            )
+        });
+        if function.ty == FunctionTy::Function && external && !read_only && writes_state && !emits {
+            if let Some(name) = &function.name {
+                let message =
+                    format!("`{}` changes the state without emitting an event", name.name);
+                self.report(Rule::MissingEvent, name.loc, message, None);
+            }
+        }
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        for_each_expression(expr, &mut |expr| self.check_expression(expr, &mut true));
+    }
+
+    /// Checks a single expression, without the expressions it contains.
+    fn check_expression(&mut self, expr: &Expression, literal_addresses: &mut bool) {
+        match expr {
+            Expression::Equal(loc, lhs, rhs) | Expression::NotEqual(loc, lhs, rhs) => {
+                let (lhs, rhs) = (&**lhs, &**rhs);
+                let auth = (is_member(lhs, "tx", "origin") && !is_member(rhs, "msg", "sender")) ||
+                    (is_member(rhs, "tx", "origin") && !is_member(lhs, "msg", "sender"));
+                if auth {
+                    let message = "`tx.origin` is used for authorization, use `msg.sender`";
+                    self.report(Rule::TxOrigin, *loc, message.to_string(), None);
+                }
+            }
+            Expression::AddressLiteral(loc, _) if *literal_addresses => {
+                let message = "literal address, declare it as a constant or immutable";
+                self.report(Rule::LiteralAddress, *loc, message.to_string(), None);
+            }
+            Expression::HexNumberLiteral(loc, value, None) if *literal_addresses => {
+                let digits = value.trim_start_matches("0x").replace('_', "");
+                if digits.len() == 40 {
+                    let message = "literal address, declare it as a constant or immutable";
+                    self.report(Rule::LiteralAddress, *loc, message.to_string(), None);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the name an imported symbol is used with, its alias if it has one.
+fn local_name((symbol, alias): &(Identifier, Option<Identifier>)) -> &Identifier {
+    alias.as_ref().unwrap_or(symbol)
+}
+
+/// Returns the state variables of the contract and of its bases in the same source.
+fn state_variables<'a>(
+    contract: &'a ContractDefinition,
+    contracts: &[&'a ContractDefinition],
+) -> Vec<&'a VariableDefinition> {
+    let mut variables = Vec::new();
+    let mut visited = HashSet::new();
+    let mut queue = vec![contract];
+    while let Some(contract) = queue.pop() {
+        if !visited.insert(contract.loc) {
+            continue
+        }
+        variables.extend(contract.parts.iter().filter_map(|part| match part {
+            ContractPart::VariableDefinition(variable) => Some(&**variable),
+            _ => None,
+        }));
+        for base in &contract.base {
+            let Some(name) = base.name.identifiers.last() else { continue };
+            queue.extend(contracts.iter().filter(|contract| {
+                contract.name.as_ref().map_or(false, |contract| contract.name == name.name)
+            }));
+        }
+    }
+    variables
+}
+
+fn is_constant(variable: &VariableDefinition) -> bool {
+    variable.attrs.iter().any(|attr| {
+        matches!(attr, VariableAttribute::Constant(_) | VariableAttribute::Immutable(_))
+    })
+}
+
+/// Returns true if the expression is `object.member`, e.g. `tx.origin`.
+fn is_member(expr: &Expression, object: &str, member: &str) -> bool {
+    match expr {
+        Expression::MemberAccess(_, expr, ident) if ident.name == member => {
+            matches!(&**expr, Expression::Variable(ident) if ident.name == object)
+        }
+        Expression::Parenthesis(_, expr) => is_member(expr, object, member),
+        _ => false,
+    }
+}
+
+/// Returns true if the expression is a low-level call, or its result is only partially assigned,
+/// so that its success is never checked.
+fn is_unchecked_call(expr: &Expression) -> bool {
+    match expr {
+        Expression::FunctionCall(_, callee, _) => {
+            let callee = match &**callee {
+                Expression::FunctionCallBlock(_, callee, _) => &**callee,
+                callee => callee,
+            };
+            let Expression::MemberAccess(_, _, member) = callee else { return false };
+            matches!(member.name.as_str(), "call" | "delegatecall" | "staticcall" | "send")
+        }
+        // `(, bytes memory data) = target.call(...)`
+        Expression::Assign(_, lhs, rhs) => {
+            let Expression::List(_, params) = &**lhs else { return false };
+            params.first().map_or(false, |(_, param)| param.is_none()) && is_unchecked_call(rhs)
+        }
+        _ => false,
+    }
+}
+
+/// Returns the name of the variable the expression writes to, e.g. `balances` of
+/// `balances[to] += amount`.
+fn written_variable(expr: &Expression) -> Option<&str> {
+    let target = match expr {
+        Expression::Assign(_, target, _) |
+        Expression::AssignOr(_, target, _) |
+        Expression::AssignAnd(_, target, _) |
+        Expression::AssignXor(_, target, _) |
+        Expression::AssignShiftLeft(_, target, _) |
+        Expression::AssignShiftRight(_, target, _) |
+        Expression::AssignAdd(_, target, _) |
+        Expression::AssignSubtract(_, target, _) |
+        Expression::AssignMultiply(_, target, _) |
+        Expression::AssignDivide(_, target, _) |
+        Expression::AssignModulo(_, target, _) |
+        Expression::PreIncrement(_, target) |
+        Expression::PostIncrement(_, target) |
+        Expression::PreDecrement(_, target) |
+        Expression::PostDecrement(_, target) |
+        Expression::Delete(_, target) => target,
+        _ => return None,
+    };
+    let mut target = &**target;
+    loop {
+        target = match target {
+            Expression::Variable(ident) => return Some(&ident.name),
+            Expression::MemberAccess(_, expr, _) |
+            Expression::ArraySubscript(_, expr, _) |
+            Expression::Parenthesis(_, expr) => expr,
+            _ => return None,
+        };
+    }
+}
+
+/// Calls `f` with the statement and all the statements it contains.
+fn for_each_statement<'a>(stmt: &'a Statement, f: &mut dyn FnMut(&'a Statement)) {
+    f(stmt);
+    match stmt {
+        Statement::Block { statements, .. } => {
+            for stmt in statements {
+                for_each_statement(stmt, f);
+            }
+        }
+        Statement::If(_, _, then, otherwise) => {
+            for_each_statement(then, f);
+            if let Some(otherwise) = otherwise {
+                for_each_statement(otherwise, f);
+            }
+        }
+        Statement::While(_, _, body) | Statement::DoWhile(_, body, _) => {
+            for_each_statement(body, f)
+        }
+        Statement::For(_, init, _, _, body) => {
+            for stmt in init.iter().chain(body) {
+                for_each_statement(stmt, f);
+            }
+        }
+        Statement::Try(_, _, returns, clauses) => {
+            if let Some((_, body)) = returns {
+                for_each_statement(body, f);
+            }
+            for clause in clauses {
+                match clause {
+                    CatchClause::Simple(_, _, body) | CatchClause::Named(_, _, _, body) => {
+                        for_each_statement(body, f)
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the expressions of the statement, without the ones of the statements it contains.
+fn statement_expressions(stmt: &Statement) -> Vec<&Expression> {
+    match stmt {
+        Statement::If(_, cond, ..) | Statement::While(_, cond, _) => vec![cond],
+        Statement::DoWhile(_, _, cond) => vec![cond],
+        Statement::Expression(_, expr) | Statement::Emit(_, expr) => vec![expr],
+        Statement::Try(_, expr, ..) => vec![expr],
+        Statement::VariableDefinition(_, _, init) | Statement::Return(_, init) => {
+            init.iter().collect()
+        }
+        Statement::For(_, _, cond, update, _) => {
+            cond.iter().chain(update).map(|expr| &**expr).collect()
+        }
+        Statement::Revert(_, _, args) => args.iter().collect(),
+        Statement::RevertNamedArgs(_, _, args) | Statement::Args(_, args) => {
+            args.iter().map(|arg| &arg.expr).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Calls `f` with the expression and all the expressions it contains.
+fn for_each_expression<'a>(expr: &'a Expression, f: &mut dyn FnMut(&'a Expression)) {
+    f(expr);
+    match expr {
+        Expression::MemberAccess(_, expr, _) => for_each_expression(expr, f),
+        Expression::ArraySubscript(_, array, index) => {
+            for_each_expression(array, f);
+            if let Some(index) = index {
+                for_each_expression(index, f);
+            }
+        }
+        Expression::ArraySlice(_, array, from, to) => {
+            for_each_expression(array, f);
+            for expr in from.iter().chain(to) {
+                for_each_expression(expr, f);
+            }
+        }
+        Expression::ConditionalOperator(_, cond, then, otherwise) => {
+            for expr in [cond, then, otherwise] {
+                for_each_expression(expr, f);
+            }
+        }
+        Expression::FunctionCall(_, callee, args) => {
+            for_each_expression(callee, f);
+            for arg in args {
+                for_each_expression(arg, f);
+            }
+        }
+        Expression::FunctionCallBlock(_, callee, block) => {
+            for_each_expression(callee, f);
+            for expr in statement_expressions(block) {
+                for_each_expression(expr, f);
+            }
+        }
+        Expression::NamedFunctionCall(_, callee, args) => {
+            for_each_expression(callee, f);
+            for arg in args {
+                for_each_expression(&arg.expr, f);
+            }
+        }
+        Expression::ArrayLiteral(_, items) => {
+            for item in items {
+                for_each_expression(item, f);
+            }
+        }
+        Expression::List(..) => {}
+        expr => {
+            let (lhs, rhs) = expr.components();
+            for expr in lhs.into_iter().chain(rhs) {
+                for_each_expression(expr, f);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(src: &str) -> Vec<(Rule, String)> {
+        lint_source(src, &LintConfig::default())
+            .unwrap()
+            .into_iter()
+            .map(|finding| (finding.rule, src[finding.range].to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn finds_unused_imports() {
+        let src = r#"
+import {A, B as C, D} from "./A.sol";
+import "./E.sol" as E;
+import "./F.sol";
+
+contract G is A {
+    D d;
+}
+"#;
+        assert_eq!(
+            lint(src),
+            vec![(Rule::UnusedImport, "C".to_string()), (Rule::UnusedImport, "E".to_string())]
+        );
+
+        let findings = lint_source(src, &LintConfig::default()).unwrap();
+        let fixes = findings.iter().filter_map(|finding| finding.fix.as_ref()).collect::<Vec<_>>();
+        let fixed = Fix::apply_all(src, &fixes);
+        assert_eq!(
+            fixed,
+            r#"
+import {A, D} from "./A.sol";
+import "./F.sol";
+
+contract G is A {
+    D d;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn finds_shadowed_state_variables() {
+        let src = r#"
+contract A {
+    uint256 owner;
+}
+
+contract B is A {
+    uint256 total;
+
+    function f(uint256 total) internal returns (uint256) {
+        uint256 owner = total;
+        return owner;
+    }
+}
+"#;
+        assert_eq!(
+            lint(src),
+            vec![
+                (Rule::ShadowedStateVariable, "total".to_string()),
+                (Rule::ShadowedStateVariable, "owner".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_unchecked_calls() {
+        let src = r#"
+contract A {
+    function f(address target) internal {
+        target.call{value: 1}("");
+        (, bytes memory data) = target.delegatecall("");
+        (bool ok,) = target.call("");
+        require(ok);
+        payable(target).send(1);
+    }
+}
+"#;
+        let findings = lint(src);
+        assert_eq!(findings.len(), 3);
+        assert!(findings.iter().all(|(rule, _)| *rule == Rule::UncheckedCall));
+        assert!(findings[0].1.starts_with("target.call{value: 1}"));
+        assert!(findings[1].1.starts_with("(, bytes memory data)"));
+        assert!(findings[2].1.starts_with("payable(target).send"));
+    }
+
+    #[test]
+    fn finds_missing_events() {
+        let src = r#"
+contract A {
+    event Set(uint256 value);
+
+    uint256 value;
+    uint256 constant MAX = 1;
+
+    function set(uint256 v) external {
+        value = v;
+    }
+
+    function setAndEmit(uint256 v) external {
+        value = v;
+        emit Set(v);
+    }
+
+    function get() external view returns (uint256) {
+        return value;
+    }
+
+    function update(uint256 v) internal {
+        value = v;
+    }
+}
+"#;
+        assert_eq!(lint(src), vec![(Rule::MissingEvent, "set".to_string())]);
+    }
+
+    #[test]
+    fn finds_tx_origin_authorization() {
+        let src = r#"
+contract A {
+    address owner;
+
+    function f() internal view {
+        require(tx.origin == owner);
+        require(msg.sender == tx.origin);
+    }
+}
+"#;
+        assert_eq!(lint(src), vec![(Rule::TxOrigin, "tx.origin == owner".to_string())]);
+    }
+
+    #[test]
+    fn finds_literal_addresses() {
+        let src = r#"
+address constant WETH = 0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2;
+
+contract A {
+    address immutable router = 0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D;
+    address feeTo = 0x5FbDB2315678afecb367f032d93F642f64180aa3;
+
+    function f() internal pure returns (address) {
+        return address(0x70997970C51812dc3A010C7d01b50e0d17dc79C8);
+    }
+}
+"#;
+        assert_eq!(
+            lint(src),
+            vec![
+                (Rule::LiteralAddress, "0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string()),
+                (Rule::LiteralAddress, "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn configures_severities() {
+        let src = r#"
+contract A {
+    function f() internal view {
+        require(tx.origin == address(1));
+    }
+}
+"#;
+        let mut config = LintConfig::default();
+        config.severity.insert("tx-origin".to_string(), LintSeverity::Warning);
+        let findings = lint_source(src, &config).unwrap();
+        assert_eq!(findings[0].severity, LintSeverity::Warning);
+
+        config.severity.insert("tx-origin".to_string(), LintSeverity::Off);
+        assert!(lint_source(src, &config).unwrap().is_empty());
+    }
+}
@@ -42,11 +42,13 @@
 pub mod bind;
 pub mod build;
 pub mod cache;
+pub mod clone;
 pub mod config;
 pub mod coverage;
 pub mod create;
 pub mod debug;
 pub mod doc;
+pub mod eip712;
 pub mod flatten;
 pub mod fmt;
 pub mod geiger;
@@ -54,6 +56,7 @@ pub mod generate;
 pub mod init;
 pub mod inspect;
 pub mod install;
+pub mod lint;
 pub mod remappings;
 pub mod remove;
 pub mod retry;
@@ -14,6 +14,7 @@ use foundry_cli::{
     utils::{has_batch_support, has_different_gas_calc},
 };
 use foundry_common::{
+    fee_bump::{fee_of, wait_with_fee_bumps, BumpOutcome, FeeBump, SendAttempt},
     provider::ethers::{estimate_eip1559_fees, try_get_http_provider, RetryProvider},
     shell,
     types::{ToAlloy, ToEthers},
@@ -21,6 +22,9 @@ use foundry_common::{
 use futures::StreamExt;
 use std::{cmp::min, collections::HashSet, ops::Mul, sync::Arc};
 
+/// The estimated max fee per gas and max priority fee per gas of EIP1559 transactions.
+type Eip1559Fees = (ethers_core::types::U256, ethers_core::types::U256);
+
 impl ScriptArgs {
     /// Sends the transactions which haven't been broadcasted yet.
     pub async fn send_transactions(
@@ -66,22 +70,16 @@ impl ScriptArgs {
             // We only wait for a transaction receipt before sending the next transaction, if there
             // is more than one signer. There would be no way of assuring their order
             // otherwise. Or if the chain does not support batched transactions (eg. Arbitrum).
-            let sequential_broadcast =
-                send_kind.signers_count() != 1 || self.slow || !has_batch_support(chain);
+            // Replacing stuck transactions also requires sending them one at a time.
+            let fee_bump = self.fee_bump();
+            let sequential_broadcast = send_kind.signers_count() != 1 ||
+                self.slow ||
+                !has_batch_support(chain) ||
+                fee_bump.is_some();
 
             // Make a one-time gas price estimation
-            let (gas_price, eip1559_fees) = {
-                match deployment_sequence.transactions.front().unwrap().typed_tx() {
-                    TypedTransaction::Eip1559(_) => {
-                        let fees = estimate_eip1559_fees(&provider, Some(chain))
-                            .await
-                            .wrap_err("Failed to estimate EIP1559 fees. This chain might not support EIP1559, try adding --legacy to your command.")?;
-
-                        (None, Some(fees))
-                    }
-                    _ => (provider.get_gas_price().await.ok(), None),
-                }
-            };
+            let (gas_price, eip1559_fees) =
+                self.estimate_fees(&provider, chain, deployment_sequence).await?;
 
             // Iterate through transactions, matching the `from` field with the associated
             // wallet. Then send the transaction. Panics if we find a unknown `from`
@@ -97,35 +95,20 @@ impl ScriptArgs {
                     let is_fixed_gas_limit = tx_with_metadata.is_fixed_gas_limit;
 
                     let mut tx = tx.clone();
-
                     tx.set_chain_id(chain);
-
-                    if let Some(gas_price) = self.with_gas_price {
-                        tx.set_gas_price(gas_price.to_ethers());
-                    } else {
-                        // fill gas price
-                        match tx {
-                            TypedTransaction::Eip1559(ref mut inner) => {
-                                let eip1559_fees =
-                                    eip1559_fees.expect("Could not get eip1559 fee estimation.");
-                                if let Some(priority_gas_price) = self.priority_gas_price {
-                                    inner.max_priority_fee_per_gas =
-                                        Some(priority_gas_price.to_ethers());
-                                } else {
-                                    inner.max_priority_fee_per_gas = Some(eip1559_fees.1);
-                                }
-                                inner.max_fee_per_gas = Some(eip1559_fees.0);
-                            }
-                            _ => {
-                                tx.set_gas_price(gas_price.expect("Could not get gas_price."));
-                            }
-                        }
-                    }
+                    self.fill_fees(&mut tx, gas_price, eip1559_fees);
 
                     Ok((tx, kind, is_fixed_gas_limit))
                 })
                 .collect::<Result<Vec<_>>>()?;
 
+            if self.skip_safety_checks {
+                shell::println("\nSKIPPING SAFETY CHECKS.")?;
+            } else {
+                let txs = sequence.iter().map(|(tx, ..)| tx).collect::<Vec<_>>();
+                self.check_broadcast_safety(&provider, fork_url, deployment_sequence, &txs).await?;
+            }
+
             let pb = init_progress!(deployment_sequence.transactions, "txes");
 
             // We send transactions and wait for receipts in batches of 100, since some networks
@@ -143,18 +126,31 @@ impl ScriptArgs {
                     batch_number * batch_size + min(batch_size, batch.len()) - 1
                 ))?;
                 for (tx, kind, is_fixed_gas_limit) in batch.into_iter() {
-                    let tx_hash = self.send_transaction(
-                        provider.clone(),
-                        tx,
-                        kind,
-                        sequential_broadcast,
-                        fork_url,
-                        is_fixed_gas_limit,
-                    );
-
                     if sequential_broadcast {
-                        let tx_hash = tx_hash.await?;
-                        deployment_sequence.add_pending(index, tx_hash);
+                        let tx_hash = if let Some(bump) = &fee_bump {
+                            self.send_with_fee_bumps(
+                                provider.clone(),
+                                tx,
+                                kind,
+                                fork_url,
+                                is_fixed_gas_limit,
+                                bump,
+                                deployment_sequence,
+                                already_broadcasted + index,
+                            )
+                            .await?
+                        } else {
+                            self.send_transaction(
+                                provider.clone(),
+                                tx,
+                                kind,
+                                true,
+                                fork_url,
+                                is_fixed_gas_limit,
+                            )
+                            .await?
+                        };
+                        deployment_sequence.add_pending(already_broadcasted + index, tx_hash);
 
                         update_progress!(pb, (index + already_broadcasted));
                         index += 1;
@@ -162,7 +158,14 @@ impl ScriptArgs {
                         clear_pendings(provider.clone(), deployment_sequence, Some(vec![tx_hash]))
                             .await?;
                     } else {
-                        pending_transactions.push(tx_hash);
+                        pending_transactions.push(self.send_transaction(
+                            provider.clone(),
+                            tx,
+                            kind,
+                            false,
+                            fork_url,
+                            is_fixed_gas_limit,
+                        ));
                     }
                 }
 
@@ -171,7 +174,7 @@ impl ScriptArgs {
 
                     while let Some(tx_hash) = buffer.next().await {
                         let tx_hash = tx_hash?;
-                        deployment_sequence.add_pending(index, tx_hash);
+                        deployment_sequence.add_pending(already_broadcasted + index, tx_hash);
 
                         update_progress!(pb, (index + already_broadcasted));
                         index += 1;
@@ -212,6 +215,7 @@ impl ScriptArgs {
             total_gas,
             avg_gas_price.trim_end_matches('0').trim_end_matches('.')
         ))?;
+        shell::println(format!("\n{}", deployment_sequence.summary().table()))?;
 
         Ok(())
     }
@@ -262,6 +266,166 @@ impl ScriptArgs {
         }
     }
 
+    /// Estimates the fees of the transactions of the sequence once, the gas price or the EIP1559
+    /// fees depending on the type of the first transaction.
+    async fn estimate_fees(
+        &self,
+        provider: &RetryProvider,
+        chain: u64,
+        deployment_sequence: &ScriptSequence,
+    ) -> Result<(Option<ethers_core::types::U256>, Option<Eip1559Fees>)> {
+        match deployment_sequence.transactions.front().unwrap().typed_tx() {
+            TypedTransaction::Eip1559(_) => {
+                let fees = estimate_eip1559_fees(provider, Some(chain))
+                    .await
+                    .wrap_err("Failed to estimate EIP1559 fees. This chain might not support EIP1559, try adding --legacy to your command.")?;
+
+                Ok((None, Some(fees)))
+            }
+            _ => Ok((provider.get_gas_price().await.ok(), None)),
+        }
+    }
+
+    /// Sets the fees of the transaction to the ones passed on the command line, or to the
+    /// estimated ones.
+    fn fill_fees(
+        &self,
+        tx: &mut TypedTransaction,
+        gas_price: Option<ethers_core::types::U256>,
+        eip1559_fees: Option<Eip1559Fees>,
+    ) {
+        if let Some(gas_price) = self.with_gas_price {
+            tx.set_gas_price(gas_price.to_ethers());
+        } else {
+            // fill gas price
+            match *tx {
+                TypedTransaction::Eip1559(ref mut inner) => {
+                    let eip1559_fees = eip1559_fees.expect("Could not get eip1559 fee estimation.");
+                    if let Some(priority_gas_price) = self.priority_gas_price {
+                        inner.max_priority_fee_per_gas = Some(priority_gas_price.to_ethers());
+                    } else {
+                        inner.max_priority_fee_per_gas = Some(eip1559_fees.1);
+                    }
+                    inner.max_fee_per_gas = Some(eip1559_fees.0);
+                }
+                _ => {
+                    tx.set_gas_price(gas_price.expect("Could not get gas_price."));
+                }
+            }
+        }
+    }
+
+    /// Prints the fees the transactions of the sequence, which weren't broadcasted yet, would be
+    /// sent with over time, see `--print-fee-schedule`.
+    ///
+    /// Nothing is signed or sent, only the fees are estimated.
+    pub async fn print_fee_schedule(
+        &self,
+        deployment_sequence: &ScriptSequence,
+        fork_url: &str,
+    ) -> Result<()> {
+        let bump = self.fee_bump().wrap_err("--print-fee-schedule requires a bump interval")?;
+        let already_broadcasted = deployment_sequence.receipts.len();
+        if already_broadcasted == deployment_sequence.transactions.len() {
+            return Ok(())
+        }
+
+        let provider = try_get_http_provider(fork_url)?;
+        let chain = provider.get_chainid().await?.as_u64();
+        let (gas_price, eip1559_fees) =
+            self.estimate_fees(&provider, chain, deployment_sequence).await?;
+        let txs = deployment_sequence
+            .transactions
+            .iter()
+            .skip(already_broadcasted)
+            .map(|tx_with_metadata| {
+                let mut tx = tx_with_metadata.typed_tx().clone();
+                tx.set_chain_id(chain);
+                self.fill_fees(&mut tx, gas_price, eip1559_fees);
+                tx
+            })
+            .collect::<Vec<_>>();
+        print_fee_schedule(&bump, &txs, already_broadcasted)
+    }
+
+    /// Returns the replacement policy for transactions that aren't mined in time, if any.
+    pub fn fee_bump(&self) -> Option<FeeBump> {
+        Some(FeeBump {
+            interval: self.gas_price_bump_interval?,
+            percent: self.gas_price_bump_percent,
+            max_fee: self.max_fee_cap,
+        })
+    }
+
+    /// Sends the transaction and replaces it with one with bumped fees whenever none of the
+    /// attempts so far got mined within the interval of the [FeeBump].
+    ///
+    /// Every attempt is saved to the transaction at `index` of the sequence as soon as it's sent,
+    /// so that a resumed broadcast can find the mined one. Returns the hash of the mined attempt,
+    /// which is moved last.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_with_fee_bumps(
+        &self,
+        provider: Arc<RetryProvider>,
+        mut tx: TypedTransaction,
+        kind: SendTransactionKind<'_>,
+        fork_url: &str,
+        is_fixed_gas_limit: bool,
+        bump: &FeeBump,
+        deployment_sequence: &mut ScriptSequence,
+        index: usize,
+    ) -> Result<TxHash> {
+        let hash = self
+            .send_transaction(
+                provider.clone(),
+                tx.clone(),
+                kind.clone(),
+                true,
+                fork_url,
+                is_fixed_gas_limit,
+            )
+            .await?;
+        // the replacements keep the gas limit of the first attempt
+        if let Some(gas) = self.sent_gas_limit(&provider, hash).await {
+            tx.set_gas(gas);
+        }
+        let mut attempts = vec![SendAttempt { hash, fee: fee_of(&tx) }];
+        save_attempts(deployment_sequence, index, &attempts)?;
+
+        let send = |tx| {
+            let provider = provider.clone();
+            let kind = kind.clone();
+            async move { self.send_transaction(provider, tx, kind, false, fork_url, true).await }
+        };
+        let on_attempt = |attempts: &[SendAttempt]| -> Result<()> {
+            if let [.., previous, attempt] = attempts {
+                shell::println(format!(
+                    "Transaction {:?} wasn't mined within {:?}, replaced it with {:?} at {} gwei.",
+                    previous.hash,
+                    bump.interval,
+                    attempt.hash,
+                    format_units(attempt.fee, 9).unwrap_or_else(|_| attempt.fee.to_string())
+                ))?;
+            }
+            save_attempts(deployment_sequence, index, attempts)
+        };
+        let outcome =
+            wait_with_fee_bumps(&*provider, tx, &mut attempts, Some(*bump), None, send, on_attempt)
+                .await?;
+        let BumpOutcome::Mined(hash) = outcome else { unreachable!("no timeout") };
+        save_attempts(deployment_sequence, index, &attempts)?;
+        Ok(hash)
+    }
+
+    /// Returns the gas limit the transaction was sent with, if the node knows it.
+    async fn sent_gas_limit(
+        &self,
+        provider: &RetryProvider,
+        hash: TxHash,
+    ) -> Option<ethers_core::types::U256> {
+        provider.get_transaction(hash.to_ethers()).await.ok().flatten().map(|tx| tx.gas)
+    }
+
     /// Executes the created transactions, and if no error has occurred, broadcasts
     /// them.
     pub async fn handle_broadcastable_transactions(
@@ -277,6 +441,12 @@ impl ScriptArgs {
             script_config.check_multi_chain_constraints(&libraries)?;
             script_config.check_shanghai_support().await?;
 
+            // Make sure every sender can be signed for, before simulating the transactions.
+            if self.broadcast && !self.unlocked && !self.print_fee_schedule {
+                let senders = txs.iter().filter_map(|tx| tx.transaction.from).collect();
+                self.wallets.ensure_signers(senders, &result.script_wallets).await?;
+            }
+
             if !script_config.missing_rpc {
                 trace!(target: "script", "creating deployments");
 
@@ -290,6 +460,16 @@ impl ScriptArgs {
                     )
                     .await?;
 
+                // nothing is signed, sent, saved or verified
+                if self.print_fee_schedule {
+                    for sequence in &deployments {
+                        if let Some((rpc, _)) = sequence.typed_transactions().into_iter().next() {
+                            self.print_fee_schedule(sequence, &rpc).await?;
+                        }
+                    }
+                    return Ok(())
+                }
+
                 if script_config.has_multiple_rpcs() {
                     trace!(target: "script", "broadcasting multi chain deployment");
 
@@ -352,6 +532,9 @@ impl ScriptArgs {
         deployment_sequence.add_libraries(libraries);
 
         self.send_transactions(deployment_sequence, &rpc, &result.script_wallets).await?;
+        deployment_sequence
+            .save_deployments(&script_config.config, &verify.known_contracts, &rpc)
+            .await?;
 
         if self.verify {
             return deployment_sequence.verify_contracts(&script_config.config, verify).await;
@@ -378,15 +561,20 @@ impl ScriptArgs {
                 .await?;
 
             let returns = self.get_returns(&*script_config, &script_result.returned)?;
+            let redactor = script_config.redactor();
 
-            return self
+            let mut sequences = self
                 .bundle_transactions(
                     gas_filled_txs,
                     &script_config.target_contract().clone(),
                     &mut script_config.config,
                     returns,
                 )
-                .await;
+                .await?;
+            for sequence in &mut sequences {
+                sequence.redactor = redactor.clone();
+            }
+            return Ok(sequences);
         } else if self.broadcast {
             eyre::bail!("No onchain transactions generated in script");
         }
@@ -520,7 +708,8 @@ impl ScriptArgs {
                 &self.sig,
                 target,
                 config,
-                self.broadcast,
+                // printing the fee schedule is a dry run
+                self.broadcast && !self.print_fee_schedule,
                 is_multi_deployment,
             )?;
 
@@ -589,15 +778,18 @@ impl ScriptArgs {
             self.estimate_gas(&mut legacy_or_1559, &provider).await?;
         }
 
-        // Signing manually so we skip `fill_transaction` and its `eth_createAccessList`
-        // request.
-        let signature = signer
-            .sign_transaction(&legacy_or_1559)
-            .await
-            .wrap_err("Failed to sign transaction")?;
+        if let Some(device) = signer.device_description() {
+            shell::println(format!(
+                "Confirm transaction with nonce {} from {:?} on your {device}...",
+                legacy_or_1559.nonce().map(|nonce| nonce.to_string()).unwrap_or_default(),
+                signer.address(),
+            ))?;
+        }
+
+        let raw = signer.sign_raw_transaction(&legacy_or_1559).await?;
 
         // Submit the raw transaction
-        let pending = provider.send_raw_transaction(legacy_or_1559.rlp_signed(&signature)).await?;
+        let pending = provider.send_raw_transaction(raw).await?;
 
         Ok(pending.tx_hash().to_alloy())
     }
@@ -667,3 +859,38 @@ impl SendTransactionsKind {
         }
     }
 }
+
+/// The number of attempts printed by [print_fee_schedule] if there's no cap.
+const UNCAPPED_SCHEDULE_LEN: usize = 10;
+
+/// Prints the fees the transactions are sent with over time.
+fn print_fee_schedule(
+    bump: &FeeBump,
+    txs: &[TypedTransaction],
+    already_broadcasted: usize,
+) -> Result<()> {
+    let limit = if bump.max_fee.is_some() { usize::MAX } else { UNCAPPED_SCHEDULE_LEN };
+    shell::println("##\nFee schedule, in gwei:")?;
+    for (i, tx) in txs.iter().enumerate() {
+        let nonce = tx.nonce().map(|nonce| nonce.to_string()).unwrap_or_default();
+        shell::println(format!("Transaction {} (nonce {nonce}):", already_broadcasted + i))?;
+        for (attempt, fee) in bump.schedule(tx, limit).into_iter().enumerate() {
+            shell::println(format!(
+                "  after {:?}: {}",
+                bump.interval * attempt as u32,
+                format_units(fee, 9).unwrap_or_else(|_| fee.to_string())
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Records the attempts of sending the transaction at `index` and saves the sequence.
+fn save_attempts(
+    deployment_sequence: &mut ScriptSequence,
+    index: usize,
+    attempts: &[SendAttempt],
+) -> Result<()> {
+    deployment_sequence.transactions[index].attempts = attempts.to_vec();
+    deployment_sequence.save()
+}
@@ -1,4 +1,7 @@
-use super::{multi::MultiChainSequence, sequence::ScriptSequence, verify::VerifyBundle, *};
+use super::{
+    multi::MultiChainSequence, runner::SimulationStage, sequence::ScriptSequence,
+    verify::VerifyBundle, *,
+};
 use alloy_primitives::Bytes;
 
 use ethers_providers::Middleware;
@@ -6,9 +9,12 @@ use ethers_signers::Signer;
 use eyre::Result;
 use foundry_cli::utils::LoadConfig;
 use foundry_common::{
-    contracts::flatten_contracts, provider::ethers::try_get_http_provider, types::ToAlloy,
+    contracts::flatten_contracts,
+    provider::ethers::{try_get_http_provider, RetryProvider},
+    types::ToAlloy,
 };
 use foundry_debugger::Debugger;
+use revm_inspectors::tracing::types::CallKind;
 use std::sync::Arc;
 
 /// Helper alias type for the collection of data changed due to the new sender.
@@ -26,6 +32,8 @@ impl ScriptArgs {
             config,
             evm_opts,
             debug: self.debug,
+            show_sensitive: self.show_sensitive,
+            non_interactive: self.non_interactive,
             ..Default::default()
         };
 
@@ -191,13 +199,18 @@ impl ScriptArgs {
         verify: VerifyBundle,
     ) -> Result<()> {
         if self.multi {
+            let mut sequence = MultiChainSequence::load(
+                &script_config.config,
+                &self.sig,
+                script_config.target_contract(),
+            )?;
+            let redactor = script_config.redactor();
+            for deployment in &mut sequence.deployments {
+                deployment.redactor = redactor.clone();
+            }
             return self
                 .multi_chain_deployment(
-                    MultiChainSequence::load(
-                        &script_config.config,
-                        &self.sig,
-                        script_config.target_contract(),
-                    )?,
+                    sequence,
                     libraries,
                     &script_config.config,
                     result.script_wallets,
@@ -221,7 +234,7 @@ impl ScriptArgs {
     /// Resumes the deployment and/or verification of a single RPC script.
     async fn resume_single_deployment(
         &mut self,
-        script_config: ScriptConfig,
+        mut script_config: ScriptConfig,
         project: Project,
         default_known_contracts: ArtifactContracts,
         result: ScriptResult,
@@ -232,9 +245,9 @@ impl ScriptArgs {
         let fork_url = script_config
             .evm_opts
             .fork_url
-            .as_deref()
+            .clone()
             .ok_or_else(|| eyre::eyre!("Missing `--fork-url` field."))?;
-        let provider = Arc::new(try_get_http_provider(fork_url)?);
+        let provider = Arc::new(try_get_http_provider(&fork_url)?);
 
         let chain = provider.get_chainid().await?.as_u64();
         verify.set_chain(&script_config.config, chain.into());
@@ -259,15 +272,35 @@ impl ScriptArgs {
             )?,
             Err(err) => eyre::bail!(err),
         };
+        deployment_sequence.redactor = script_config.redactor();
+
+        if self.print_fee_schedule {
+            return self.print_fee_schedule(&deployment_sequence, &fork_url).await
+        }
 
         if self.verify {
             deployment_sequence.verify_preflight_check(&script_config.config, &verify)?;
         }
 
-        receipts::wait_for_pending(provider, &mut deployment_sequence).await?;
+        receipts::wait_for_pending(provider.clone(), &mut deployment_sequence).await?;
+        receipts::reconcile_broadcasted(provider.clone(), &mut deployment_sequence).await?;
 
         if self.resume {
-            self.send_transactions(&mut deployment_sequence, fork_url, &result.script_wallets)
+            if let Some(new_sender) = self.evm_opts.sender {
+                self.replace_resume_sender(
+                    &mut script_config,
+                    &mut deployment_sequence,
+                    provider,
+                    new_sender,
+                    &fork_url,
+                )
+                .await?;
+            }
+
+            self.send_transactions(&mut deployment_sequence, &fork_url, &result.script_wallets)
+                .await?;
+            deployment_sequence
+                .save_deployments(&script_config.config, &verify.known_contracts, &fork_url)
                 .await?;
         }
 
@@ -291,6 +324,78 @@ impl ScriptArgs {
         Ok(())
     }
 
+    /// Reassigns the transactions of a resumed sequence which haven't been broadcasted yet to
+    /// `new_sender`, with fresh nonces.
+    ///
+    /// Transactions the original sender already sent are waited for first, see
+    /// [`receipts::wait_for_sent_nonces`]. The remaining transactions are simulated again from the
+    /// new sender, so that any failure shows up before broadcasting.
+    async fn replace_resume_sender(
+        &self,
+        script_config: &mut ScriptConfig,
+        deployment_sequence: &mut ScriptSequence,
+        provider: Arc<RetryProvider>,
+        new_sender: Address,
+        fork_url: &str,
+    ) -> Result<()> {
+        let Some(original_sender) = deployment_sequence.remaining_sender()? else {
+            return Ok(());
+        };
+        if original_sender == new_sender {
+            return Ok(());
+        }
+
+        // transactions sent from the original sender may still be mined, so their nonces need to
+        // be used up before the sequence is reassigned
+        receipts::wait_for_sent_nonces(provider, deployment_sequence, original_sender).await?;
+        if deployment_sequence.remaining_sender()?.is_none() {
+            return Ok(());
+        }
+
+        let nonce = forge::next_nonce(new_sender, fork_url, None).await?;
+        let already_broadcasted = deployment_sequence.receipts.len();
+
+        shell::println(format!(
+            "##\nReplacing sender {original_sender} with {new_sender} for the remaining {} transactions, starting at nonce {nonce}.",
+            deployment_sequence.transactions.len() - already_broadcasted
+        ))?;
+
+        let changed_addresses = deployment_sequence.replace_sender(new_sender, nonce);
+
+        let mut runner =
+            self.prepare_runner(script_config, new_sender, SimulationStage::OnChain).await?;
+        for (index, tx) in
+            deployment_sequence.transactions.iter_mut().enumerate().skip(already_broadcasted)
+        {
+            let typed_tx = tx.typed_tx();
+            let result = runner.simulate(
+                new_sender,
+                typed_tx.to().and_then(|to| to.as_address()).map(|to| to.to_alloy()),
+                typed_tx.data().map(|data| data.clone().to_alloy()),
+                typed_tx.value().map(|value| value.to_alloy()),
+            )?;
+
+            if !result.success {
+                eyre::bail!(
+                    "Transaction {index} failed when simulating it from the new sender {new_sender}."
+                );
+            }
+
+            if tx.opcode == CallKind::Create && result.address != tx.contract_address {
+                tx.contract_address = result.address;
+            }
+        }
+
+        for (old_address, new_address) in changed_addresses {
+            let msg = format!(
+                "WARNING: contract previously expected at {old_address} will now be deployed at {new_address}. Calldata referencing the old address is NOT updated."
+            );
+            shell::println(Paint::red(msg).bold())?;
+        }
+
+        Ok(())
+    }
+
     /// Reruns the execution with a new sender and relinks the libraries accordingly
     async fn rerun_with_new_deployer(
         &mut self,
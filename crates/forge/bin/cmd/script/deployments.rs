@@ -0,0 +1,309 @@
+//! The registry of the contracts deployed by broadcasted scripts, see `Config::save_deployments`.
+
+use super::{sequence::ScriptSequence, transaction::AdditionalContract};
+use alloy_primitives::{b256, Address, Bytes, B256};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{
+    fs,
+    provider::ethers::try_get_http_provider,
+    shell,
+    types::{ToAlloy, ToEthers},
+    ContractsByArtifact,
+};
+use foundry_config::Config;
+use revm_inspectors::tracing::types::CallKind;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// The storage slot of the implementation of EIP-1967 proxies,
+/// `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`.
+const IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// A contract deployed by a broadcasted script.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deployment {
+    pub contract_name: String,
+    /// The identifier of the artifact, `<path>:<name>`, if the bytecode matched a local contract
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<String>,
+    pub address: Address,
+    #[serde(default)]
+    pub constructor_args: Bytes,
+    pub transaction_hash: B256,
+    pub block_number: u64,
+    /// The IPFS or Swarm hash of the compiler metadata, from the bytecode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_hash: Option<Bytes>,
+    /// The libraries linked into the contract, `<path>:<name> -> address`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub libraries: BTreeMap<String, Address>,
+    /// The implementation of the contract if it's an EIP-1967 proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementation: Option<Address>,
+    pub timestamp: u64,
+}
+
+impl Deployment {
+    fn is_same(&self, other: &Self) -> bool {
+        self.address == other.address && self.transaction_hash == other.transaction_hash
+    }
+}
+
+/// The registry file of a contract on a chain, `<deployments>/<chain id>/<contract name>.json`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeploymentRecord {
+    /// The latest deployment
+    #[serde(flatten)]
+    pub latest: Deployment,
+    /// The previous deployments, oldest first
+    #[serde(default)]
+    pub history: Vec<Deployment>,
+}
+
+impl DeploymentRecord {
+    /// Makes the deployment the latest one, the previous latest deployment is moved to the history.
+    ///
+    /// Deployments which are already recorded, e.g. when resuming a script, are only updated.
+    pub fn push(&mut self, deployment: Deployment) {
+        if self.latest.is_same(&deployment) {
+            self.latest = deployment;
+        } else if !self.history.iter().any(|previous| previous.is_same(&deployment)) {
+            let previous = std::mem::replace(&mut self.latest, deployment);
+            self.history.push(previous);
+        }
+    }
+
+    /// Adds the deployment to the registry file, creating it if it doesn't exist.
+    pub fn update(path: &Path, deployment: Deployment) -> Result<()> {
+        let record = if path.exists() {
+            let mut record: Self = fs::read_json_file(path)?;
+            record.push(deployment);
+            record
+        } else {
+            Self { latest: deployment, history: vec![] }
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&record)?)?;
+        Ok(())
+    }
+}
+
+impl ScriptSequence {
+    /// Returns the contracts created by the broadcasted transactions of the sequence, directly or
+    /// during their execution.
+    ///
+    /// Contracts whose bytecode doesn't match any of `known_contracts` are only included if they
+    /// were created directly and their name is known.
+    pub fn deployments(
+        &self,
+        known_contracts: &ContractsByArtifact,
+        root: &Path,
+    ) -> Vec<Deployment> {
+        let libraries: Vec<(String, Address)> = self
+            .libraries
+            .iter()
+            .filter_map(|library| {
+                let (name, address) = library.rsplit_once(':')?;
+                Some((name.to_string(), address.parse().ok()?))
+            })
+            .collect();
+
+        let mut deployments = vec![];
+        for tx in &self.transactions {
+            let Some(hash) = tx.hash else { continue };
+            let Some(receipt) =
+                self.receipts.iter().find(|receipt| receipt.transaction_hash.to_alloy() == hash)
+            else {
+                continue
+            };
+            if receipt.status.map_or(false, |status| status.as_u64() == 0) {
+                continue
+            }
+
+            let mut created = vec![];
+            if matches!(tx.opcode, CallKind::Create | CallKind::Create2) {
+                if let (Some(address), Some(data)) = (tx.contract_address, tx.typed_tx().data()) {
+                    // CREATE2 transactions are prefixed by the 32 byte salt
+                    let offset = if tx.is_create2() { 32 } else { 0 };
+                    let init_code = data.0.get(offset..).unwrap_or_default();
+                    created.push((address, init_code, tx.contract_name.as_deref()));
+                }
+            }
+            for AdditionalContract { address, init_code, .. } in &tx.additional_contracts {
+                created.push((*address, &init_code[..], None));
+            }
+
+            for (address, init_code, name) in created {
+                let artifact = known_contracts
+                    .iter()
+                    .find(|(_, (_, code))| !code.is_empty() && init_code.starts_with(code));
+                let (contract_name, artifact, constructor_args, metadata_hash) = match artifact {
+                    Some((id, (_, code))) => {
+                        let source = id.source.strip_prefix(root).unwrap_or(&id.source);
+                        (
+                            id.name.clone(),
+                            Some(format!("{}:{}", source.display(), id.name)),
+                            Bytes::copy_from_slice(&init_code[code.len()..]),
+                            metadata_hash(code),
+                        )
+                    }
+                    None => match name.filter(|name| !name.is_empty()) {
+                        Some(name) => (name.to_string(), None, Bytes::new(), None),
+                        None => continue,
+                    },
+                };
+
+                deployments.push(Deployment {
+                    contract_name,
+                    artifact,
+                    address,
+                    constructor_args,
+                    transaction_hash: hash,
+                    block_number: receipt.block_number.map_or(0, |number| number.as_u64()),
+                    metadata_hash,
+                    libraries: libraries
+                        .iter()
+                        .filter(|(_, library)| {
+                            init_code.windows(20).any(|window| window == library.as_slice())
+                        })
+                        .cloned()
+                        .collect(),
+                    implementation: None,
+                    timestamp: self.timestamp,
+                });
+            }
+        }
+        deployments
+    }
+
+    /// Records the contracts deployed by the broadcasted transactions in the registry, if
+    /// `save_deployments` is enabled.
+    pub async fn save_deployments(
+        &self,
+        config: &Config,
+        known_contracts: &ContractsByArtifact,
+        fork_url: &str,
+    ) -> Result<()> {
+        if !config.save_deployments {
+            return Ok(())
+        }
+
+        let mut deployments = self.deployments(known_contracts, &config.__root.0);
+        if deployments.is_empty() {
+            return Ok(())
+        }
+
+        let provider = try_get_http_provider(fork_url)?;
+        for deployment in &mut deployments {
+            let address = deployment.address.to_ethers();
+            match provider.get_storage_at(address, IMPLEMENTATION_SLOT.to_ethers(), None).await {
+                Ok(implementation) if !implementation.is_zero() => {
+                    deployment.implementation = Some(Address::from_word(implementation.to_alloy()));
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    let address = deployment.address;
+                    warn!(?err, %address, "failed to read proxy implementation")
+                }
+            }
+        }
+
+        let dir = config.deployments.join(self.chain.to_string());
+        for deployment in deployments {
+            let path = dir.join(format!("{}.json", deployment.contract_name));
+            DeploymentRecord::update(&path, deployment)?;
+        }
+        shell::println(format!("Deployments saved to: {}\n", dir.display()))?;
+        Ok(())
+    }
+}
+
+/// Returns the hash of the compiler metadata from the CBOR encoded trailer of the bytecode, the
+/// IPFS multihash or the Swarm hash.
+fn metadata_hash(code: &[u8]) -> Option<Bytes> {
+    let len_start = code.len().checked_sub(2)?;
+    let len = u16::from_be_bytes([code[len_start], code[len_start + 1]]) as usize;
+    let cbor = code.get(len_start.checked_sub(len)?..len_start)?;
+    for (key, size) in [(&b"ipfs"[..], 34), (&b"bzzr1"[..], 32), (&b"bzzr0"[..], 32)] {
+        // a text string key followed by a byte string of `size` bytes
+        let mut pattern = vec![0x60 + key.len() as u8];
+        pattern.extend_from_slice(key);
+        pattern.extend_from_slice(&[0x58, size as u8]);
+        if let Some(start) = cbor.windows(pattern.len()).position(|window| window == pattern) {
+            let start = start + pattern.len();
+            return cbor.get(start..start + size).map(Bytes::copy_from_slice)
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deployment(address: u8, hash: u8) -> Deployment {
+        Deployment {
+            contract_name: "Counter".to_string(),
+            artifact: None,
+            address: Address::repeat_byte(address),
+            constructor_args: Bytes::new(),
+            transaction_hash: B256::repeat_byte(hash),
+            block_number: hash as u64,
+            metadata_hash: None,
+            libraries: Default::default(),
+            implementation: None,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn can_extract_metadata_hash() {
+        let hash = [0x12; 34];
+        let mut cbor = vec![0xa2, 0x64];
+        cbor.extend_from_slice(b"ipfs");
+        cbor.extend_from_slice(&[0x58, 0x22]);
+        cbor.extend_from_slice(&hash);
+        cbor.push(0x64);
+        cbor.extend_from_slice(b"solc");
+        cbor.extend_from_slice(&[0x43, 0x00, 0x08, 0x13]);
+
+        let mut code = vec![0x60, 0x80, 0x60, 0x40];
+        code.extend_from_slice(&cbor);
+        code.extend_from_slice(&(cbor.len() as u16).to_be_bytes());
+        assert_eq!(metadata_hash(&code), Some(Bytes::copy_from_slice(&hash)));
+
+        assert_eq!(metadata_hash(&[0x60, 0x80, 0x60, 0x40]), None);
+        assert_eq!(metadata_hash(&[]), None);
+    }
+
+    #[test]
+    fn redeployments_are_appended_to_history() {
+        let mut record = DeploymentRecord { latest: deployment(1, 1), history: vec![] };
+
+        // the same deployment, e.g. when resuming, is updated in place
+        let mut updated = deployment(1, 1);
+        updated.block_number = 10;
+        record.push(updated.clone());
+        assert_eq!(record.latest, updated);
+        assert!(record.history.is_empty());
+
+        record.push(deployment(2, 2));
+        record.push(deployment(3, 3));
+        assert_eq!(record.latest, deployment(3, 3));
+        assert_eq!(record.history, vec![updated, deployment(2, 2)]);
+
+        // already recorded deployments aren't added again
+        record.push(deployment(2, 2));
+        assert_eq!(record.latest, deployment(3, 3));
+        assert_eq!(record.history.len(), 2);
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["address"], Address::repeat_byte(3).to_string());
+        assert_eq!(json["history"][0]["blockNumber"], 10);
+    }
+}
@@ -201,6 +201,7 @@ impl ScriptArgs {
             })
             .collect::<Vec<_>>();
 
+        let redactor = script_config.redactor();
         let mut abort = false;
         for res in join_all(futs).await {
             let (tx, traces) = res?;
@@ -215,7 +216,7 @@ impl ScriptArgs {
                 }
 
                 for (_, trace) in &traces {
-                    println!("{}", render_trace_arena(trace, decoder).await?);
+                    println!("{}", redactor.redact(&render_trace_arena(trace, decoder).await?));
                 }
             }
 
@@ -263,7 +264,7 @@ impl ScriptArgs {
     }
 
     /// Creates the Runner that drives script execution
-    async fn prepare_runner(
+    pub async fn prepare_runner(
         &self,
         script_config: &mut ScriptConfig,
         sender: Address,
@@ -299,11 +300,13 @@ impl ScriptArgs {
             .gas_limit(script_config.evm_opts.gas_limit());
 
         if let SimulationStage::Local = stage {
-            builder = builder.inspectors(|stack| {
-                stack.debug(self.debug).cheatcodes(
-                    CheatsConfig::new(&script_config.config, script_config.evm_opts.clone()).into(),
-                )
-            });
+            let mut cheats_config =
+                CheatsConfig::new(&script_config.config, script_config.evm_opts.clone());
+            // all runners share the report, so the sensitive values of every run are masked
+            cheats_config.env_report = script_config.env_report.clone();
+            cheats_config.non_interactive = script_config.non_interactive;
+            builder = builder
+                .inspectors(|stack| stack.debug(self.debug).cheatcodes(cheats_config.into()));
         }
 
         Ok(ScriptRunner::new(
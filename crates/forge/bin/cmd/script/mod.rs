@@ -27,6 +27,7 @@ use foundry_common::{
     evm::{Breakpoints, EvmArgs},
     fmt::{format_token, format_token_raw},
     provider::ethers::RpcUrl,
+    redact::Redactor,
     shell, ContractsByArtifact, CONTRACT_MAX_SIZE, SELECTOR_LEN,
 };
 use foundry_compilers::{
@@ -45,24 +46,31 @@ use foundry_config::{
 use foundry_evm::{
     constants::DEFAULT_CREATE2_DEPLOYER,
     decode,
-    inspectors::cheatcodes::{BroadcastableTransaction, BroadcastableTransactions},
+    inspectors::cheatcodes::{BroadcastableTransaction, BroadcastableTransactions, EnvReport},
 };
 use futures::future;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use yansi::Paint;
 
 mod artifacts;
 mod broadcast;
 mod build;
 mod cmd;
+mod deployments;
 mod executor;
 mod multi;
 mod providers;
 mod receipts;
 mod runner;
+mod safety;
 mod sequence;
+mod summary;
 pub mod transaction;
 mod verify;
 
@@ -121,7 +129,15 @@ pub struct ScriptArgs {
     #[clap(
         long,
         requires = "sender",
-        conflicts_with_all = &["private_key", "private_keys", "froms", "ledger", "trezor", "aws"],
+        conflicts_with_all = &[
+            "private_key",
+            "private_keys",
+            "froms",
+            "ledger",
+            "trezor",
+            "aws",
+            "gcp_kms",
+        ],
     )]
     pub unlocked: bool,
 
@@ -131,6 +147,9 @@ pub struct ScriptArgs {
     ///
     /// Example: If transaction N has a nonce of 22, then the account should have a nonce of 22,
     /// otherwise it fails.
+    ///
+    /// If `--sender` is passed and differs from the sender of the remaining transactions, these
+    /// are simulated again from the new sender and sent with its nonces instead.
     #[clap(long)]
     pub resume: bool,
 
@@ -149,7 +168,8 @@ pub struct ScriptArgs {
     #[clap(long)]
     pub slow: bool,
 
-    /// Disables interactive prompts that might appear when deploying big contracts.
+    /// Disables interactive prompts that might appear when deploying big contracts, and makes the
+    /// `vm.prompt*` cheatcodes fail instead of waiting for input.
     ///
     /// For more info on the contract size limit, see EIP-170: <https://eips.ethereum.org/EIPS/eip-170>
     #[clap(long)]
@@ -167,6 +187,13 @@ pub struct ScriptArgs {
     #[clap(long)]
     pub json: bool,
 
+    /// Print the values of sensitive environment variables instead of masking them.
+    ///
+    /// By default, the values of the variables matching `sensitive_env` and of the ones read with
+    /// `vm.envSensitive*` are masked in the console output, traces and broadcast files.
+    #[clap(long)]
+    pub show_sensitive: bool,
+
     /// Gas price for legacy transactions, or max fee per gas for EIP1559 transactions.
     #[clap(
         long,
@@ -176,6 +203,47 @@ pub struct ScriptArgs {
     )]
     pub with_gas_price: Option<U256>,
 
+    /// Replaces transactions that aren't mined within this interval, e.g. `30s`.
+    ///
+    /// The replacement has the same nonce and fees bumped by `--gas-price-bump-percent`.
+    /// Transactions are sent one at a time if set.
+    #[clap(long, value_parser = foundry_cli::utils::parse_delay, value_name = "DURATION")]
+    pub gas_price_bump_interval: Option<Duration>,
+
+    /// The percentage the fees of a replacement transaction are increased by.
+    #[clap(
+        long,
+        default_value = "15",
+        requires = "gas_price_bump_interval",
+        value_name = "PERCENT"
+    )]
+    pub gas_price_bump_percent: u64,
+
+    /// The maximum fee per gas, or gas price for legacy transactions, of replacement
+    /// transactions, e.g. `200gwei`.
+    #[clap(
+        long,
+        requires = "gas_price_bump_interval",
+        value_parser = foundry_cli::utils::parse_ether_value,
+        value_name = "PRICE"
+    )]
+    pub max_fee_cap: Option<U256>,
+
+    /// Prints the fees every transaction would be replaced with, without signing, sending or
+    /// saving them.
+    #[clap(long, requires = "gas_price_bump_interval")]
+    pub print_fee_schedule: bool,
+
+    /// Broadcasts without the safety checks of the chain ID, the balances of the senders and the
+    /// simulation drift.
+    #[clap(long)]
+    pub skip_safety_checks: bool,
+
+    /// The percentage a sender's balance must exceed the worst-case cost of its transactions by
+    /// before broadcasting.
+    #[clap(long, default_value = "10", value_name = "PERCENT")]
+    pub balance_headroom: u64,
+
     #[clap(flatten)]
     pub opts: BuildArgs,
 
@@ -280,6 +348,7 @@ impl ScriptArgs {
     ) -> Result<()> {
         let verbosity = script_config.evm_opts.verbosity;
         let func = script_config.called_function.as_ref().expect("There should be a function.");
+        let redactor = script_config.redactor();
 
         if !result.success || verbosity > 3 {
             if result.traces.is_empty() {
@@ -295,7 +364,7 @@ impl ScriptArgs {
                 } || !result.success;
 
                 if should_include {
-                    shell::println(render_trace_arena(trace, decoder).await?)?;
+                    shell::println(redactor.redact(&render_trace_arena(trace, decoder).await?))?;
                 }
             }
             shell::println(String::new())?;
@@ -325,11 +394,11 @@ impl ScriptArgs {
                         } else {
                             index.to_string()
                         };
-                        shell::println(format!(
+                        shell::println(redactor.redact(&format!(
                             "{}: {internal_type} {}",
                             label.trim_end(),
                             format_token(token)
-                        ))?;
+                        )))?;
                     }
                 }
                 Err(_) => {
@@ -342,14 +411,27 @@ impl ScriptArgs {
         if !console_logs.is_empty() {
             shell::println("\n== Logs ==")?;
             for log in console_logs {
-                shell::println(format!("  {log}"))?;
+                shell::println(format!("  {}", redactor.redact(&log)))?;
+            }
+        }
+
+        let env_report = script_config.env_report.lock().unwrap().clone();
+        if !env_report.is_empty() {
+            shell::println("\n== Environment ==")?;
+            shell::println(env_report.to_string().trim_end())?;
+            if script_config.show_sensitive {
+                shell::println("Sensitive values are not masked (`--show-sensitive`).")?;
             }
         }
 
         if !result.success {
             return Err(eyre::eyre!(
                 "script failed: {}",
-                decode::decode_revert(&result.returned[..], None, None)
+                redactor.redact(&decode::decode_revert(
+                    &result.returned[..],
+                    Some(&decoder.errors),
+                    None
+                ))
             ));
         }
 
@@ -361,8 +443,9 @@ impl ScriptArgs {
 
         let console_logs = decode_console_logs(&result.logs);
         let output = JsonResult { logs: console_logs, gas_used: result.gas_used, returns };
-        let j = serde_json::to_string(&output)?;
-        shell::println(j)?;
+        let mut j = serde_json::to_value(&output)?;
+        script_config.redactor().redact_json(&mut j, &[]);
+        shell::println(j.to_string())?;
 
         Ok(())
     }
@@ -620,9 +703,27 @@ pub struct ScriptConfig {
     pub missing_rpc: bool,
     /// Should return some debug information
     pub debug: bool,
+    /// The environment variables read by the script
+    pub env_report: Arc<Mutex<EnvReport>>,
+    /// Whether sensitive values are printed instead of masked
+    pub show_sensitive: bool,
+    /// Whether the `vm.prompt*` cheatcodes fail instead of reading from the terminal
+    pub non_interactive: bool,
 }
 
 impl ScriptConfig {
+    /// Returns the redactor of the sensitive environment variables, see `Config::sensitive_env`,
+    /// of the ones read with `vm.envSensitive*` and of the values entered with `vm.promptSecret`.
+    /// Masks nothing with `--show-sensitive`.
+    fn redactor(&self) -> Redactor {
+        if self.show_sensitive {
+            return Redactor::default()
+        }
+        let mut redactor = Redactor::from_env(&self.config.sensitive_env);
+        redactor.extend(&self.env_report.lock().unwrap().redactor);
+        redactor
+    }
+
     fn collect_rpcs(&mut self, txs: &BroadcastableTransactions) {
         self.missing_rpc = txs.iter().any(|tx| tx.rpc.is_none());
 
@@ -958,4 +1059,28 @@ mod tests {
         ]);
         assert!(args.with_gas_price.unwrap().is_zero());
     }
+
+    #[test]
+    fn can_parse_fee_bump_args() {
+        let args: ScriptArgs = ScriptArgs::parse_from([
+            "foundry-cli",
+            "Contract",
+            "--gas-price-bump-interval",
+            "30s",
+            "--gas-price-bump-percent",
+            "20",
+            "--max-fee-cap",
+            "200gwei",
+        ]);
+        let bump = args.fee_bump().unwrap();
+        assert_eq!(bump.interval, Duration::from_secs(30));
+        assert_eq!(bump.percent, 20);
+        assert_eq!(bump.max_fee, Some(U256::from(200_000_000_000u64)));
+
+        let args: ScriptArgs = ScriptArgs::parse_from(["foundry-cli", "Contract"]);
+        assert!(args.fee_bump().is_none());
+        assert!(
+            ScriptArgs::try_parse_from(["foundry-cli", "Contract", "--max-fee-cap", "1"]).is_err()
+        );
+    }
 }
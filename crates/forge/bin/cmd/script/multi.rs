@@ -35,12 +35,6 @@ pub struct SensitiveMultiChainSequence {
     pub deployments: Vec<SensitiveScriptSequence>,
 }
 
-fn to_sensitive(sequence: &mut MultiChainSequence) -> SensitiveMultiChainSequence {
-    SensitiveMultiChainSequence {
-        deployments: sequence.deployments.iter_mut().map(|sequence| sequence.into()).collect(),
-    }
-}
-
 impl Drop for MultiChainSequence {
     fn drop(&mut self) {
         self.deployments.iter_mut().for_each(|sequence| sequence.sort_receipts());
@@ -118,12 +112,19 @@ impl MultiChainSequence {
             target,
             true,
         )?;
-        let mut sequence: MultiChainSequence = foundry_compilers::utils::read_json_file(&path)
+        let mut json: serde_json::Value = foundry_compilers::utils::read_json_file(&path)
             .wrap_err("Multi-chain deployment not found.")?;
         let sensitive_sequence: SensitiveMultiChainSequence =
             foundry_compilers::utils::read_json_file(&sensitive_path)
                 .wrap_err("Multi-chain deployment sensitive details not found.")?;
 
+        for (i, sensitive) in sensitive_sequence.deployments.iter().enumerate() {
+            if let Some(deployment) = json["deployments"].get_mut(i) {
+                sensitive.restore(deployment);
+            }
+        }
+        let mut sequence: MultiChainSequence =
+            serde_json::from_value(json).wrap_err("Invalid multi-chain deployment.")?;
         sequence.deployments.iter_mut().enumerate().for_each(|(i, sequence)| {
             sequence.fill_sensitive(&sensitive_sequence.deployments[i]);
         });
@@ -138,12 +139,22 @@ impl MultiChainSequence {
     pub fn save(&mut self) -> Result<()> {
         self.timestamp = now().as_secs();
 
-        let sensitive_sequence: SensitiveMultiChainSequence = to_sensitive(self);
+        let (deployments, sensitive_deployments): (Vec<_>, Vec<_>) = self
+            .deployments
+            .iter()
+            .map(ScriptSequence::redacted)
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        let sensitive_sequence = SensitiveMultiChainSequence { deployments: sensitive_deployments };
+
+        let mut json = serde_json::to_value(&*self)?;
+        json["deployments"] = deployments.into();
 
         // broadcast writes
         //../Contract-latest/run.json
         let mut writer = BufWriter::new(fs::create_file(&self.path)?);
-        serde_json::to_writer_pretty(&mut writer, &self)?;
+        serde_json::to_writer_pretty(&mut writer, &json)?;
         writer.flush()?;
 
         //../Contract-[timestamp]/run.json
@@ -164,6 +175,8 @@ impl MultiChainSequence {
         fs::create_dir_all(file.parent().unwrap())?;
         fs::copy(&self.sensitive_path, &file)?;
 
+        self.save_summary()?;
+
         println!("\nTransactions saved to: {}\n", self.path.display());
         println!("Sensitive details saved to: {}\n", self.sensitive_path.display());
 
@@ -186,6 +199,14 @@ impl ScriptArgs {
             eyre::bail!("Libraries are currently not supported on multi deployment setups.");
         }
 
+        if self.print_fee_schedule {
+            for sequence in &deployments.deployments {
+                let rpc = sequence.typed_transactions().first().unwrap().0.clone();
+                self.print_fee_schedule(sequence, &rpc).await?;
+            }
+            return Ok(())
+        }
+
         if self.verify {
             for sequence in &deployments.deployments {
                 sequence.verify_preflight_check(config, &verify)?;
@@ -219,18 +240,16 @@ impl ScriptArgs {
         let mut results: Vec<Result<(), Report>> = Vec::new();
 
         for sequence in deployments.deployments.iter_mut() {
-            let result = match self
-                .send_transactions(
-                    sequence,
-                    &sequence.typed_transactions().first().unwrap().0.clone(),
-                    &script_wallets,
-                )
-                .await
-            {
-                Ok(_) if self.verify => sequence.verify_contracts(config, verify.clone()).await,
-                Ok(_) => Ok(()),
-                Err(err) => Err(err),
-            };
+            let rpc = sequence.typed_transactions().first().unwrap().0.clone();
+            let result = async {
+                self.send_transactions(sequence, &rpc, &script_wallets).await?;
+                sequence.save_deployments(config, &verify.known_contracts, &rpc).await?;
+                if self.verify {
+                    sequence.verify_contracts(config, verify.clone()).await?;
+                }
+                Ok(())
+            }
+            .await;
             results.push(result);
         }
 
@@ -1,15 +1,23 @@
 use super::sequence::ScriptSequence;
-use alloy_primitives::TxHash;
+use alloy_primitives::{Address, TxHash};
 use ethers_core::types::TransactionReceipt;
 use ethers_providers::{Middleware, PendingTransaction};
 use eyre::Result;
 use foundry_cli::{init_progress, update_progress, utils::print_receipt};
 use foundry_common::{
     provider::ethers::RetryProvider,
+    shell,
     types::{ToAlloy, ToEthers},
 };
 use futures::StreamExt;
-use std::sync::Arc;
+use itertools::Itertools;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// How long to wait for the nonces of previously sent transactions to be used up before bailing.
+const SENT_NONCES_TIMEOUT: Duration = Duration::from_secs(120);
 
 /// Convenience enum for internal signalling of transaction status
 enum TxStatus {
@@ -38,10 +46,106 @@ pub async fn wait_for_pending(
     if deployment_sequence.pending.is_empty() {
         return Ok(());
     }
-    println!("##\nChecking previously pending transactions.");
+    shell::println("##\nChecking previously pending transactions.")?;
     clear_pendings(provider, deployment_sequence, None).await
 }
 
+/// Looks up the receipts of transactions which were sent on a previous run, but are no longer
+/// tracked as pending, e.g. because they got mined while the script wasn't running.
+///
+/// Any attempt of a transaction which was replaced with higher fees may have been mined, the mined
+/// one becomes the hash of the transaction.
+///
+/// Stops at the first transaction without a receipt, so that only the missing tail of the sequence
+/// gets broadcasted again.
+pub async fn reconcile_broadcasted(
+    provider: Arc<RetryProvider>,
+    deployment_sequence: &mut ScriptSequence,
+) -> Result<()> {
+    let sent: Vec<Vec<TxHash>> = deployment_sequence
+        .transactions
+        .iter()
+        .skip(deployment_sequence.receipts.len())
+        .map(|tx| {
+            let attempts = tx.attempts.iter().map(|attempt| attempt.hash);
+            tx.hash.into_iter().chain(attempts).unique().collect::<Vec<_>>()
+        })
+        .take_while(|hashes| !hashes.is_empty())
+        .collect();
+
+    if sent.is_empty() {
+        return Ok(());
+    }
+    shell::println("##\nChecking previously sent transactions.")?;
+
+    for hashes in sent {
+        let mut mined = None;
+        for tx_hash in hashes {
+            if let Some(receipt) = provider.get_transaction_receipt(tx_hash.to_ethers()).await? {
+                mined = Some((tx_hash, receipt));
+                break
+            }
+        }
+        let Some((tx_hash, receipt)) = mined else { break };
+        trace!(tx_hash=?tx_hash, "found receipt of previously sent tx");
+
+        let tx = &mut deployment_sequence.transactions[deployment_sequence.receipts.len()];
+        tx.hash = Some(tx_hash);
+        // the mined attempt goes last
+        if let Some(i) = tx.attempts.iter().position(|attempt| attempt.hash == tx_hash) {
+            let attempt = tx.attempts.remove(i);
+            tx.attempts.push(attempt);
+        }
+        print_receipt(deployment_sequence.chain.into(), &receipt);
+        deployment_sequence.add_receipt(receipt);
+    }
+
+    Ok(())
+}
+
+/// Waits until the nonces of the transactions `sender` sent on a previous run, but which aren't
+/// mined yet, are used up, so that none of them can still land once the rest of the sequence is
+/// sent from another address.
+///
+/// Bails if that doesn't happen within [`SENT_NONCES_TIMEOUT`]; the transactions then need to be
+/// cancelled first. Whatever got mined in the meantime is added to the sequence.
+pub async fn wait_for_sent_nonces(
+    provider: Arc<RetryProvider>,
+    deployment_sequence: &mut ScriptSequence,
+    sender: Address,
+) -> Result<()> {
+    let Some(last_nonce) = deployment_sequence
+        .transactions
+        .iter()
+        .skip(deployment_sequence.receipts.len())
+        .filter(|tx| tx.hash.is_some() || !tx.attempts.is_empty())
+        .filter_map(|tx| tx.typed_tx().nonce().map(|nonce| nonce.as_u64()))
+        .max()
+    else {
+        return Ok(());
+    };
+
+    shell::println(format!(
+        "##\nWaiting for the transactions previously sent from {sender} to be mined, up to nonce {last_nonce}."
+    ))?;
+
+    let start = Instant::now();
+    loop {
+        let nonce = provider.get_transaction_count(sender.to_ethers(), None).await?.as_u64();
+        if nonce > last_nonce {
+            break
+        }
+        if start.elapsed() > SENT_NONCES_TIMEOUT {
+            eyre::bail!(
+                "Transactions sent from {sender} up to nonce {last_nonce} are still pending. Cancel them, e.g. by sending transactions with the same nonces and higher fees, before resuming with a new sender."
+            )
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    reconcile_broadcasted(provider, deployment_sequence).await
+}
+
 /// Traverses a set of pendings and either finds receipts, or clears them from
 /// the deployment sequence.
 ///
@@ -0,0 +1,183 @@
+//! The safety checks of the transactions right before they're broadcasted, see
+//! `--skip-safety-checks`.
+
+use super::{sequence::ScriptSequence, ScriptArgs};
+use alloy_primitives::{utils::format_units, Address, U256};
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_providers::Middleware;
+use eyre::{bail, Result};
+use foundry_common::{
+    fee_bump::fee_of,
+    provider::ethers::RetryProvider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use revm_inspectors::tracing::types::CallKind;
+use std::collections::BTreeMap;
+
+impl ScriptArgs {
+    /// Checks that the transactions, which are about to be broadcasted, are sent to the chain
+    /// they were simulated on, can be afforded by their senders, and that the first one still
+    /// has the simulated result on the latest state of the chain.
+    ///
+    /// The transactions have their fees set, the ones of the sequence which were already
+    /// broadcasted are skipped.
+    pub(super) async fn check_broadcast_safety(
+        &self,
+        provider: &RetryProvider,
+        fork_url: &str,
+        sequence: &ScriptSequence,
+        txs: &[&TypedTransaction],
+    ) -> Result<()> {
+        check_chain_id(provider, sequence.chain).await?;
+        check_balances(provider, txs, self.balance_headroom).await?;
+        // without a simulation there's nothing to compare to
+        if !self.skip_simulation {
+            if let (Some(metadata), Some(tx)) =
+                (sequence.transactions.get(sequence.receipts.len()), txs.first())
+            {
+                check_drift(provider, fork_url, tx, metadata.opcode, metadata.contract_address)
+                    .await?;
+            }
+        }
+        shell::println("\nSafety checks passed.")?;
+        Ok(())
+    }
+}
+
+/// Checks that the RPC is on the chain the transactions were simulated on.
+async fn check_chain_id(provider: &RetryProvider, simulated: u64) -> Result<()> {
+    let chain = provider.get_chainid().await?.as_u64();
+    if chain != simulated {
+        bail!(
+            "Chain ID check failed: the RPC is on chain {chain}, but the transactions were \
+             simulated on chain {simulated}. Pass the --rpc-url of chain {simulated}, or run the \
+             script again to simulate it on chain {chain}."
+        );
+    }
+    Ok(())
+}
+
+/// Checks that the balance of every sender covers the worst-case cost of its transactions, the
+/// gas limit at the max fee plus the value, increased by `headroom` percent.
+async fn check_balances(
+    provider: &RetryProvider,
+    txs: &[&TypedTransaction],
+    headroom: u64,
+) -> Result<()> {
+    for (sender, cost) in worst_case_costs(txs) {
+        let required =
+            cost.saturating_add(cost.saturating_mul(U256::from(headroom)) / U256::from(100));
+        let balance = provider.get_balance(sender.to_ethers(), None).await?.to_alloy();
+        if balance < required {
+            bail!(
+                "Balance check failed: {sender} has {} ETH, but its transactions cost up to {} \
+                 ETH, with a headroom of {headroom}%. Fund the sender, lower the fees with \
+                 --with-gas-price, or lower the headroom with --balance-headroom.",
+                format_ether(balance),
+                format_ether(required)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns the worst-case cost of the transactions of every sender.
+///
+/// Transactions without a gas limit, which is estimated when they're sent, only cost their value.
+fn worst_case_costs(txs: &[&TypedTransaction]) -> BTreeMap<Address, U256> {
+    let mut costs = BTreeMap::<Address, U256>::new();
+    for tx in txs {
+        let Some(from) = tx.from() else { continue };
+        let gas = tx.gas().copied().unwrap_or_default().to_alloy();
+        let value = tx.value().copied().unwrap_or_default().to_alloy();
+        let cost = costs.entry(from.to_alloy()).or_default();
+        *cost = cost.saturating_add(gas.saturating_mul(fee_of(tx))).saturating_add(value);
+    }
+    costs
+}
+
+/// Simulates the transaction on the latest state of the chain, and checks that it succeeds and
+/// creates the contract at the simulated address, like the earlier simulation did.
+async fn check_drift(
+    provider: &RetryProvider,
+    fork_url: &str,
+    tx: &TypedTransaction,
+    kind: CallKind,
+    simulated_address: Option<Address>,
+) -> Result<()> {
+    let from = tx.from().copied().unwrap_or_default().to_alloy();
+    // the nonce of the latest state is used
+    let mut call = tx.clone();
+    match &mut call {
+        TypedTransaction::Legacy(tx) => tx.nonce = None,
+        TypedTransaction::Eip2930(tx) => tx.tx.nonce = None,
+        TypedTransaction::Eip1559(tx) => tx.nonce = None,
+    }
+    let output = match provider.call(&call, None).await {
+        Ok(output) => output,
+        Err(err) => bail!(
+            "Simulation drift check failed: the first transaction from {from} succeeded in the \
+             simulation, but reverts on the latest state of the chain: {err}. The state of the \
+             chain changed since the simulation, run the script again to simulate it on the \
+             latest state."
+        ),
+    };
+
+    let created = match kind {
+        CallKind::Create => Some(from.create(forge::next_nonce(from, fork_url, None).await?)),
+        // the CREATE2 deployer returns the address
+        CallKind::Create2 => (output.len() == 20).then(|| Address::from_slice(&output)),
+        _ => None,
+    };
+    if let (Some(created), Some(simulated)) = (created, simulated_address) {
+        if created != simulated {
+            bail!(
+                "Simulation drift check failed: the first transaction from {from} creates a \
+                 contract at {created} on the latest state of the chain, but at {simulated} in \
+                 the simulation. The nonce of {from} or the state of the chain changed since the \
+                 simulation, run the script again to simulate it on the latest state."
+            );
+        }
+    }
+    Ok(())
+}
+
+fn format_ether(wei: U256) -> String {
+    format_units(wei, 18).unwrap_or_else(|_| wei.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Eip1559TransactionRequest, TransactionRequest};
+
+    #[test]
+    fn sums_worst_case_costs_per_sender() {
+        let (alice, bob) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let legacy: TypedTransaction = TransactionRequest::new()
+            .from(alice.to_ethers())
+            .gas(21_000)
+            .gas_price(10)
+            .value(5)
+            .into();
+        let eip1559: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(alice.to_ethers())
+            .gas(50_000)
+            .max_fee_per_gas(20)
+            .max_priority_fee_per_gas(1)
+            .into();
+        // the gas limit is estimated when sent
+        let unestimated: TypedTransaction =
+            TransactionRequest::new().from(bob.to_ethers()).value(7).into();
+
+        let costs = worst_case_costs(&[&legacy, &eip1559, &unestimated]);
+        assert_eq!(
+            costs,
+            BTreeMap::from([
+                (alice, U256::from(21_000 * 10 + 5 + 50_000 * 20)),
+                (bob, U256::from(7))
+            ])
+        );
+    }
+}
@@ -8,19 +8,25 @@ use crate::cmd::{
     verify::provider::VerificationProviderType,
 };
 use alloy_primitives::{Address, TxHash};
-use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionReceipt};
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, NameOrAddress, TransactionReceipt,
+};
 use eyre::{ContextCompat, Result, WrapErr};
 use foundry_cli::utils::now;
 use foundry_common::{
-    fs, shell,
+    fs,
+    redact::{self, Redactor},
+    shell,
     types::{ToAlloy, ToEthers},
     SELECTOR_LEN,
 };
 use foundry_compilers::{artifacts::Libraries, ArtifactId};
 use foundry_config::Config;
+use itertools::Itertools;
+use revm_inspectors::tracing::types::CallKind;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, VecDeque},
     io::{BufWriter, Write},
     path::{Path, PathBuf},
 };
@@ -47,6 +53,22 @@ pub struct ScriptSequence {
     /// If `True`, the sequence belongs to a `MultiChainSequence` and won't save to disk as usual.
     pub multi: bool,
     pub commit: Option<String>,
+    /// Transactions that were reassigned to a different sender when resuming.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replaced: Vec<ReplacedTransaction>,
+    /// Masks the sensitive values in the saved sequence.
+    #[serde(skip)]
+    pub redactor: Redactor,
+}
+
+/// A transaction of the sequence as it was recorded before its sender got replaced on `--resume`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplacedTransaction {
+    /// Position of the transaction in the sequence.
+    pub index: usize,
+    /// The original transaction. The replacement lives at the same position in `transactions`.
+    pub original: TransactionWithMetadata,
 }
 
 /// Sensitive values from the transactions in a script sequence
@@ -59,17 +81,16 @@ pub struct SensitiveTransactionMetadata {
 #[derive(Clone, Default, Serialize, Deserialize)]
 pub struct SensitiveScriptSequence {
     pub transactions: VecDeque<SensitiveTransactionMetadata>,
+    /// The original values of the strings which are masked in the broadcast file, by their JSON
+    /// pointer.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub redacted: BTreeMap<String, String>,
 }
 
-impl From<&mut ScriptSequence> for SensitiveScriptSequence {
-    fn from(sequence: &mut ScriptSequence) -> Self {
-        SensitiveScriptSequence {
-            transactions: sequence
-                .transactions
-                .iter()
-                .map(|tx| SensitiveTransactionMetadata { rpc: tx.rpc.clone() })
-                .collect(),
-        }
+impl SensitiveScriptSequence {
+    /// Puts the redacted values back into the JSON of the saved sequence.
+    pub fn restore(&self, json: &mut serde_json::Value) {
+        redact::restore_json(json, &self.redacted);
     }
 }
 
@@ -108,6 +129,8 @@ impl ScriptSequence {
             chain,
             multi: is_multi,
             commit,
+            replaced: vec![],
+            redactor: Default::default(),
         })
     }
 
@@ -128,7 +151,7 @@ impl ScriptSequence {
             broadcasted,
         )?;
 
-        let mut script_sequence: Self = foundry_compilers::utils::read_json_file(&path)
+        let mut json: serde_json::Value = foundry_compilers::utils::read_json_file(&path)
             .wrap_err(format!("Deployment not found for chain `{chain_id}`."))?;
 
         let sensitive_script_sequence: SensitiveScriptSequence =
@@ -136,6 +159,9 @@ impl ScriptSequence {
                 "Deployment's sensitive details not found for chain `{chain_id}`."
             ))?;
 
+        sensitive_script_sequence.restore(&mut json);
+        let mut script_sequence: Self = serde_json::from_value(json)
+            .wrap_err(format!("Invalid deployment for chain `{chain_id}`."))?;
         script_sequence.fill_sensitive(&sensitive_script_sequence);
 
         script_sequence.path = path;
@@ -153,12 +179,12 @@ impl ScriptSequence {
         self.timestamp = now().as_secs();
         let ts_name = format!("run-{}.json", self.timestamp);
 
-        let sensitive_script_sequence: SensitiveScriptSequence = self.into();
+        let (json, sensitive_script_sequence) = self.redacted()?;
 
         // broadcast folder writes
         //../run-latest.json
         let mut writer = BufWriter::new(fs::create_file(&self.path)?);
-        serde_json::to_writer_pretty(&mut writer, &self)?;
+        serde_json::to_writer_pretty(&mut writer, &json)?;
         writer.flush()?;
         //../run-[timestamp].json
         fs::copy(&self.path, self.path.with_file_name(&ts_name))?;
@@ -171,12 +197,33 @@ impl ScriptSequence {
         //../run-[timestamp].json
         fs::copy(&self.sensitive_path, self.sensitive_path.with_file_name(&ts_name))?;
 
+        self.save_summary()?;
+
         shell::println(format!("\nTransactions saved to: {}\n", self.path.display()))?;
         shell::println(format!("Sensitive values saved to: {}\n", self.sensitive_path.display()))?;
 
         Ok(())
     }
 
+    /// Returns the sequence as JSON with the sensitive values masked, including the ones in the
+    /// calldata and the arguments of the transactions, and the sensitive values to save in the
+    /// cache folder, which restore the masked ones on [`ScriptSequence::load`].
+    ///
+    /// The receipts are kept as is, they are public anyway.
+    pub fn redacted(&self) -> Result<(serde_json::Value, SensitiveScriptSequence)> {
+        let mut json = serde_json::to_value(self)?;
+        let redacted = self.redactor.redact_json_reversibly(&mut json, &["receipts"]);
+        let sensitive = SensitiveScriptSequence {
+            transactions: self
+                .transactions
+                .iter()
+                .map(|tx| SensitiveTransactionMetadata { rpc: tx.rpc.clone() })
+                .collect(),
+            redacted,
+        };
+        Ok((json, sensitive))
+    }
+
     pub fn add_receipt(&mut self, receipt: TransactionReceipt) {
         self.receipts.push(receipt);
     }
@@ -197,6 +244,67 @@ impl ScriptSequence {
         self.pending.retain(|element| element != &tx_hash);
     }
 
+    /// Returns the sender of the transactions which haven't been broadcasted yet.
+    ///
+    /// Fails if they are sent from more than one address, since there is no way of telling which
+    /// of them should be replaced.
+    pub fn remaining_sender(&self) -> Result<Option<Address>> {
+        let mut senders = self
+            .transactions
+            .iter()
+            .skip(self.receipts.len())
+            .filter_map(|tx| tx.typed_tx().from().map(|from| from.to_alloy()))
+            .unique();
+
+        let sender = senders.next();
+        if senders.next().is_some() {
+            eyre::bail!(
+                "The remaining transactions are sent from multiple addresses, `--sender` cannot be used to replace them."
+            )
+        }
+        Ok(sender)
+    }
+
+    /// Reassigns every transaction which hasn't been broadcasted yet to `new_sender`, using
+    /// consecutive nonces starting at `nonce`.
+    ///
+    /// The original transactions are kept in [`ScriptSequence::replaced`]. Returns the contract
+    /// addresses which changed as a result, as `(old, new)` pairs. Calls made to one of these
+    /// contracts later in the sequence are redirected to the new address.
+    pub fn replace_sender(&mut self, new_sender: Address, nonce: u64) -> Vec<(Address, Address)> {
+        let mut changed_addresses = vec![];
+        let already_broadcasted = self.receipts.len();
+
+        for (index, tx) in self.transactions.iter_mut().enumerate().skip(already_broadcasted) {
+            self.replaced.push(ReplacedTransaction { index, original: tx.clone() });
+
+            let tx_nonce = nonce + (index - already_broadcasted) as u64;
+            tx.hash = None;
+            tx.transaction.set_from(new_sender.to_ethers());
+            tx.transaction.set_nonce(tx_nonce);
+
+            // CREATE2 addresses don't depend on the sender, only CREATE ones need to be updated.
+            if tx.opcode == CallKind::Create {
+                let new_address = new_sender.create(tx_nonce);
+                if let Some(old_address) = tx.contract_address.filter(|addr| *addr != new_address) {
+                    changed_addresses.push((old_address, new_address));
+                }
+                tx.contract_address = Some(new_address);
+            } else if let Some(NameOrAddress::Address(to)) = tx.transaction.to().cloned() {
+                if let Some((_, new_to)) =
+                    changed_addresses.iter().find(|(old, _)| *old == to.to_alloy())
+                {
+                    tx.transaction.set_to(new_to.to_ethers());
+                    if tx.contract_address == Some(to.to_alloy()) {
+                        tx.contract_address = Some(*new_to);
+                    }
+                }
+            }
+        }
+
+        changed_addresses
+    }
+
     pub fn add_libraries(&mut self, libraries: Libraries) {
         self.libraries = libraries
             .libs
@@ -400,6 +508,7 @@ pub fn sig_to_file_name(sig: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ethers_core::types::TransactionRequest;
 
     #[test]
     fn can_convert_sig() {
@@ -412,4 +521,61 @@ mod tests {
             "522bb704"
         );
     }
+
+    #[test]
+    fn can_replace_remaining_sender() {
+        let old_sender = Address::repeat_byte(1);
+        let new_sender = Address::repeat_byte(2);
+        let old_deployment = old_sender.create(5);
+        let new_deployment = new_sender.create(10);
+
+        let tx = |to: Option<Address>, nonce: u64| {
+            TypedTransaction::Legacy(TransactionRequest {
+                from: Some(old_sender.to_ethers()),
+                to: to.map(|to| to.to_ethers().into()),
+                nonce: Some(nonce.into()),
+                ..Default::default()
+            })
+        };
+
+        let mut sequence = ScriptSequence {
+            transactions: VecDeque::from([
+                TransactionWithMetadata {
+                    opcode: CallKind::Create,
+                    contract_address: Some(old_deployment),
+                    transaction: tx(None, 5),
+                    ..Default::default()
+                },
+                TransactionWithMetadata {
+                    opcode: CallKind::Call,
+                    contract_address: Some(old_deployment),
+                    transaction: tx(Some(old_deployment), 6),
+                    ..Default::default()
+                },
+            ]),
+            // avoid writing to disk on drop
+            multi: true,
+            ..Default::default()
+        };
+
+        assert_eq!(sequence.remaining_sender().unwrap(), Some(old_sender));
+
+        let changed = sequence.replace_sender(new_sender, 10);
+        assert_eq!(changed, vec![(old_deployment, new_deployment)]);
+        assert_eq!(sequence.replaced.len(), 2);
+        assert_eq!(sequence.replaced[1].index, 1);
+
+        let create = &sequence.transactions[0];
+        assert_eq!(create.typed_tx().from(), Some(&new_sender.to_ethers()));
+        assert_eq!(create.typed_tx().nonce(), Some(&10u64.into()));
+        assert_eq!(create.contract_address, Some(new_deployment));
+
+        let call = &sequence.transactions[1];
+        assert_eq!(call.typed_tx().nonce(), Some(&11u64.into()));
+        assert_eq!(
+            call.typed_tx().to().and_then(|to| to.as_address()),
+            Some(&new_deployment.to_ethers())
+        );
+        assert_eq!(call.contract_address, Some(new_deployment));
+    }
 }
@@ -0,0 +1,396 @@
+//! The machine-readable summary of a broadcast, `<sig>-summary.json` next to the broadcast log.
+//!
+//! The summary is derived from the whole sequence of the run on every save, so a run that was
+//! resumed is summarized as a whole, and a multi-chain run has one entry per chain.
+
+use super::{multi::MultiChainSequence, sequence::ScriptSequence};
+use alloy_primitives::{utils::format_units, Address, B256, U256};
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use eyre::Result;
+use foundry_common::{fs, types::ToAlloy};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// The version of the schema of the summary, bumped on breaking changes.
+pub const SUMMARY_SCHEMA_VERSION: u32 = 1;
+
+/// The summary of a broadcast.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    pub version: u32,
+    /// The summaries of the chains, ordered by chain id
+    pub chains: Vec<ChainSummary>,
+}
+
+impl RunSummary {
+    pub fn new(mut chains: Vec<ChainSummary>) -> Self {
+        chains.sort_by_key(|chain| chain.chain_id);
+        Self { version: SUMMARY_SCHEMA_VERSION, chains }
+    }
+
+    /// Writes the summary as pretty JSON.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// The summary of the transactions of a chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSummary {
+    pub chain_id: u64,
+    /// The gas used by the mined transactions
+    pub total_gas_used: u64,
+    /// The fees paid for the mined transactions, in wei, as a decimal string
+    pub total_cost_wei: String,
+    /// The fees paid for the mined transactions, in the native token, e.g. `0.0012`
+    pub total_cost: String,
+    pub transactions: Vec<TransactionSummary>,
+    /// The contracts created by the mined transactions by name. Further contracts of the same
+    /// name are suffixed with `#2`, `#3`, etc.
+    pub deployments: BTreeMap<String, DeploymentSummary>,
+}
+
+/// The summary of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionSummary {
+    /// The hash, if the transaction was sent
+    pub hash: Option<B256>,
+    /// The recipient, or none for a contract creation
+    pub to: Option<Address>,
+    /// The address of the contract created by the transaction
+    pub contract_address: Option<Address>,
+    pub contract_name: Option<String>,
+    /// The signature of the function called
+    pub function: Option<String>,
+    /// The gas used, if the transaction was mined
+    pub gas_used: Option<u64>,
+    pub status: TransactionStatus,
+}
+
+/// The status of a transaction of the sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionStatus {
+    /// Mined and succeeded
+    Success,
+    /// Mined and reverted
+    Failed,
+    /// Sent but not mined yet
+    Pending,
+    /// Not sent yet
+    Unsent,
+}
+
+/// A contract created by a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentSummary {
+    pub address: Address,
+    pub transaction_hash: B256,
+    /// The salt of contracts created with the CREATE2 deployer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<B256>,
+}
+
+impl ScriptSequence {
+    /// Summarizes the transactions of the sequence.
+    pub fn summary(&self) -> ChainSummary {
+        let mut total_gas_used = 0u64;
+        let mut total_cost = U256::ZERO;
+        let mut transactions = Vec::with_capacity(self.transactions.len());
+        let mut deployments = BTreeMap::new();
+
+        for tx in &self.transactions {
+            let receipt = tx.hash.and_then(|hash| {
+                self.receipts.iter().find(|receipt| receipt.transaction_hash.to_alloy() == hash)
+            });
+            let status = match receipt {
+                Some(receipt) if receipt.status.map_or(false, |status| status.as_u64() == 0) => {
+                    TransactionStatus::Failed
+                }
+                Some(_) => TransactionStatus::Success,
+                None if tx.hash.is_some() => TransactionStatus::Pending,
+                None => TransactionStatus::Unsent,
+            };
+
+            let gas_used = receipt.map(|receipt| {
+                let gas_used = receipt.gas_used.unwrap_or_default().to_alloy();
+                let gas_price = receipt.effective_gas_price.unwrap_or_default().to_alloy();
+                total_cost += gas_used * gas_price;
+                gas_used.saturating_to::<u64>()
+            });
+            total_gas_used += gas_used.unwrap_or_default();
+
+            let created = tx.contract_address.filter(|_| tx.opcode.is_any_create());
+            if let (Some(address), Some(hash), TransactionStatus::Success) =
+                (created, tx.hash, status)
+            {
+                // CREATE2 transactions are prefixed by the 32 byte salt
+                let salt = if tx.is_create2() {
+                    tx.typed_tx().data().and_then(|data| data.get(..32)).map(B256::from_slice)
+                } else {
+                    None
+                };
+                let name = tx
+                    .contract_name
+                    .clone()
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| address.to_string());
+                let mut key = name.clone();
+                let mut count = 1;
+                while deployments.contains_key(&key) {
+                    count += 1;
+                    key = format!("{name}#{count}");
+                }
+                deployments
+                    .insert(key, DeploymentSummary { address, transaction_hash: hash, salt });
+            }
+
+            transactions.push(TransactionSummary {
+                hash: tx.hash,
+                to: tx.typed_tx().to().and_then(|to| to.as_address()).map(|to| to.to_alloy()),
+                contract_address: created,
+                contract_name: tx.contract_name.clone().filter(|name| !name.is_empty()),
+                function: tx.function.clone().filter(|function| !function.is_empty()),
+                gas_used,
+                status,
+            });
+        }
+
+        ChainSummary {
+            chain_id: self.chain,
+            total_gas_used,
+            total_cost_wei: total_cost.to_string(),
+            total_cost: format_native(total_cost),
+            transactions,
+            deployments,
+        }
+    }
+
+    /// Writes the summary of a standalone sequence next to its broadcast log.
+    pub fn save_summary(&self) -> Result<()> {
+        RunSummary::new(vec![self.summary()]).write(&summary_path(&self.path))
+    }
+}
+
+impl MultiChainSequence {
+    /// Writes the summary of all chains next to the broadcast log.
+    pub fn save_summary(&self) -> Result<()> {
+        RunSummary::new(self.deployments.iter().map(ScriptSequence::summary).collect())
+            .write(&summary_path(&self.path))
+    }
+}
+
+impl ChainSummary {
+    /// Returns the table of the transactions printed after a broadcast.
+    pub fn table(&self) -> Table {
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header(["Hash", "Contract", "Function", "Gas used", "Status"]);
+        for tx in &self.transactions {
+            let contract = match (&tx.contract_name, tx.contract_address.or(tx.to)) {
+                (Some(name), Some(address)) => format!("{name} ({address})"),
+                (None, Some(address)) => address.to_string(),
+                (Some(name), None) => name.clone(),
+                (None, None) => String::new(),
+            };
+            table.add_row([
+                tx.hash.map(|hash| hash.to_string()).unwrap_or_default(),
+                contract,
+                tx.function.clone().unwrap_or_default(),
+                tx.gas_used.map(|gas| gas.to_string()).unwrap_or_default(),
+                format!("{:?}", tx.status),
+            ]);
+        }
+        table
+    }
+}
+
+/// Returns the path of the summary of the broadcast log at `path`, `<sig>-summary.json`.
+fn summary_path(path: &Path) -> std::path::PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let sig = stem.strip_suffix("-latest").unwrap_or(&stem);
+    path.with_file_name(format!("{sig}-summary.json"))
+}
+
+/// Formats an amount of wei in the native token, without trailing zeros.
+fn format_native(wei: U256) -> String {
+    let formatted = format_units(wei, 18).unwrap_or_else(|_| wei.to_string());
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    if formatted.is_empty() {
+        "0".to_string()
+    } else {
+        formatted.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::script::transaction::TransactionWithMetadata;
+    use ethers_core::types::{
+        transaction::eip2718::TypedTransaction, TransactionReceipt, TransactionRequest,
+    };
+    use foundry_common::types::ToEthers;
+    use foundry_evm::constants::DEFAULT_CREATE2_DEPLOYER;
+    use revm_inspectors::tracing::types::CallKind;
+    use std::collections::VecDeque;
+
+    fn receipt(hash: B256, gas_used: u64, status: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            transaction_hash: hash.to_ethers(),
+            gas_used: Some(gas_used.into()),
+            effective_gas_price: Some(1_000_000_000u64.into()),
+            status: Some(status.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn summarizes_sequence() {
+        let salt = B256::repeat_byte(0xaa);
+        let mut create2_data = salt.to_vec();
+        create2_data.extend_from_slice(&[0x60, 0x80]);
+        let tx = |to: Option<Address>, data: Vec<u8>| {
+            TypedTransaction::Legacy(TransactionRequest {
+                to: to.map(|to| to.to_ethers().into()),
+                data: Some(data.into()),
+                ..Default::default()
+            })
+        };
+
+        let sequence = ScriptSequence {
+            transactions: VecDeque::from([
+                TransactionWithMetadata {
+                    hash: Some(B256::repeat_byte(1)),
+                    opcode: CallKind::Create,
+                    contract_name: Some("Counter".to_string()),
+                    contract_address: Some(Address::repeat_byte(0x11)),
+                    transaction: tx(None, vec![0x60, 0x80]),
+                    ..Default::default()
+                },
+                TransactionWithMetadata {
+                    hash: Some(B256::repeat_byte(2)),
+                    opcode: CallKind::Create2,
+                    contract_name: Some("Counter".to_string()),
+                    contract_address: Some(Address::repeat_byte(0x22)),
+                    transaction: tx(Some(DEFAULT_CREATE2_DEPLOYER), create2_data),
+                    ..Default::default()
+                },
+                TransactionWithMetadata {
+                    hash: Some(B256::repeat_byte(3)),
+                    opcode: CallKind::Call,
+                    contract_name: Some("Counter".to_string()),
+                    contract_address: Some(Address::repeat_byte(0x11)),
+                    function: Some("increment()".to_string()),
+                    transaction: tx(Some(Address::repeat_byte(0x11)), vec![]),
+                    ..Default::default()
+                },
+                TransactionWithMetadata {
+                    opcode: CallKind::Call,
+                    function: Some("increment()".to_string()),
+                    transaction: tx(Some(Address::repeat_byte(0x11)), vec![]),
+                    ..Default::default()
+                },
+            ]),
+            receipts: vec![
+                receipt(B256::repeat_byte(1), 100_000, 1),
+                receipt(B256::repeat_byte(2), 50_000, 1),
+                receipt(B256::repeat_byte(3), 30_000, 0),
+            ],
+            chain: 1,
+            // avoid writing to disk on drop
+            multi: true,
+            ..Default::default()
+        };
+
+        let summary = sequence.summary();
+        assert_eq!(summary.total_gas_used, 180_000);
+        assert_eq!(summary.total_cost_wei, "180000000000000");
+        assert_eq!(summary.total_cost, "0.00018");
+
+        let statuses: Vec<_> = summary.transactions.iter().map(|tx| tx.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                TransactionStatus::Success,
+                TransactionStatus::Success,
+                TransactionStatus::Failed,
+                TransactionStatus::Unsent
+            ]
+        );
+        assert_eq!(summary.transactions[0].to, None);
+        assert_eq!(summary.transactions[1].to, Some(DEFAULT_CREATE2_DEPLOYER));
+        assert_eq!(summary.transactions[2].contract_address, None);
+
+        assert_eq!(
+            summary.deployments,
+            BTreeMap::from([
+                (
+                    "Counter".to_string(),
+                    DeploymentSummary {
+                        address: Address::repeat_byte(0x11),
+                        transaction_hash: B256::repeat_byte(1),
+                        salt: None,
+                    }
+                ),
+                (
+                    "Counter#2".to_string(),
+                    DeploymentSummary {
+                        address: Address::repeat_byte(0x22),
+                        transaction_hash: B256::repeat_byte(2),
+                        salt: Some(salt),
+                    }
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn serializes_stable_schema() {
+        let summary = RunSummary::new(vec![ChainSummary {
+            chain_id: 10,
+            total_gas_used: 21_000,
+            total_cost_wei: "21000".to_string(),
+            total_cost: "0.000000000000021".to_string(),
+            transactions: vec![TransactionSummary {
+                hash: Some(B256::ZERO),
+                to: Some(Address::ZERO),
+                contract_address: None,
+                contract_name: None,
+                function: Some("run()".to_string()),
+                gas_used: Some(21_000),
+                status: TransactionStatus::Success,
+            }],
+            deployments: BTreeMap::new(),
+        }]);
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["version"], 1);
+        let chain = &json["chains"][0];
+        assert_eq!(chain["chainId"], 10);
+        assert_eq!(chain["totalGasUsed"], 21_000);
+        assert_eq!(chain["totalCostWei"], "21000");
+        assert_eq!(chain["transactions"][0]["status"], "success");
+        assert_eq!(chain["transactions"][0]["gasUsed"], 21_000);
+        assert_eq!(chain["transactions"][0]["contractAddress"], serde_json::Value::Null);
+
+        let decoded: RunSummary = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, summary);
+    }
+
+    #[test]
+    fn can_get_summary_path() {
+        assert_eq!(
+            summary_path(Path::new("broadcast/A.s.sol/1/run-latest.json")),
+            Path::new("broadcast/A.s.sol/1/run-summary.json")
+        );
+        assert_eq!(
+            summary_path(Path::new("broadcast/multi/A.s.sol-latest/run.json")),
+            Path::new("broadcast/multi/A.s.sol-latest/run-summary.json")
+        );
+    }
+}
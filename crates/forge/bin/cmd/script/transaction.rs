@@ -9,6 +9,7 @@ use ethers_core::types::{
 };
 use eyre::{ContextCompat, Result, WrapErr};
 use foundry_common::{
+    fee_bump::SendAttempt,
     fmt::format_token_raw,
     provider::ethers::RpcUrl,
     types::{ToAlloy, ToEthers},
@@ -48,6 +49,10 @@ pub struct TransactionWithMetadata {
     pub transaction: TypedTransaction,
     pub additional_contracts: Vec<AdditionalContract>,
     pub is_fixed_gas_limit: bool,
+    /// All attempts of sending the transaction if it was replaced with higher fees, the mined one
+    /// being the last
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<SendAttempt>,
 }
 
 fn default_string() -> Option<String> {
@@ -1,16 +1,29 @@
+use alloy_json_abi::JsonAbi;
+use alloy_primitives::Selector;
 use clap::Parser;
 use comfy_table::Table;
 use eyre::Result;
+use forge::traces::identifier::SignaturesIdentifier;
 use foundry_cli::{
     opts::{CompilerArgs, CoreBuildArgs, ProjectPathsArgs},
-    utils::FoundryPathExt,
+    utils::{FoundryPathExt, LoadConfig},
 };
 use foundry_common::{
     compile::ProjectCompiler,
     selectors::{import_selectors, SelectorImportData},
 };
-use foundry_compilers::{artifacts::output_selection::ContractOutputSelection, info::ContractInfo};
-use std::fs::canonicalize;
+use foundry_compilers::{
+    artifacts::{
+        ast::{NodeType, SourceFile},
+        output_selection::ContractOutputSelection,
+    },
+    info::ContractInfo,
+};
+use foundry_config::Config;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::canonicalize,
+};
 
 /// CLI arguments for `forge selectors`.
 #[derive(Clone, Debug, Parser)]
@@ -45,6 +58,21 @@ pub enum SelectorsSubcommands {
         project_paths: ProjectPathsArgs,
     },
 
+    /// Check for selector collisions within every contract of the project and within every set
+    /// of facets configured in `[selectors]`
+    #[clap(visible_alias = "ck")]
+    Check {
+        #[clap(flatten)]
+        project_paths: ProjectPathsArgs,
+    },
+
+    /// Cache the selectors of all contracts in the project in the local signature database that
+    /// is used for decoding traces
+    Cache {
+        #[clap(flatten)]
+        project_paths: ProjectPathsArgs,
+    },
+
     /// List selectors from current workspace
     #[clap(visible_alias = "ls")]
     List {
@@ -170,6 +198,41 @@ impl SelectorsSubcommands {
                     println!("{table}");
                 }
             }
+            SelectorsSubcommands::Check { project_paths } => {
+                let (config, abis, inheritance) = compile_abis(project_paths, false)?;
+                let collisions = find_collisions(&abis, &inheritance, &config.selectors.facets)?;
+
+                if collisions.is_empty() {
+                    println!("No selector collisions found.");
+                } else {
+                    let mut table = Table::new();
+                    table.set_header(["Selector", "Group", "Contract", "Signature"]);
+                    for collision in collisions.iter() {
+                        for (contract, signature) in collision.entries.iter() {
+                            table.add_row([
+                                &collision.selector,
+                                &collision.group,
+                                contract,
+                                signature,
+                            ]);
+                        }
+                    }
+                    println!("{} collisions found:", collisions.len());
+                    println!("{table}");
+                    eyre::bail!("selector collisions found");
+                }
+            }
+            SelectorsSubcommands::Cache { project_paths } => {
+                let (_, abis, _) = compile_abis(project_paths, true)?;
+
+                let identifier = SignaturesIdentifier::new(Config::foundry_cache_dir(), true)?;
+                let mut identifier = identifier.write().await;
+                let added =
+                    abis.iter().map(|(_, abi)| identifier.extend_from_abi(abi)).sum::<usize>();
+                identifier.save();
+
+                println!("Cached {added} new selectors of {} contracts.", abis.len());
+            }
             SelectorsSubcommands::List { contract, project_paths } => {
                 println!("Listing selectors for contracts in the project...");
                 let build_args = CoreBuildArgs {
@@ -260,3 +323,254 @@ impl SelectorsSubcommands {
         Ok(())
     }
 }
+
+/// Compiles the project and returns the ABIs of its contracts and their linearized base
+/// contracts. Unless `include_all`, only the ABIs of the contracts in the sources directory that
+/// are not tests are returned.
+fn compile_abis(
+    project_paths: ProjectPathsArgs,
+    include_all: bool,
+) -> Result<(Config, Vec<(String, JsonAbi)>, BTreeMap<String, Vec<String>>)> {
+    let build_args = CoreBuildArgs {
+        project_paths,
+        compiler: CompilerArgs {
+            extra_output: vec![ContractOutputSelection::Abi],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let config = build_args.try_load_config_emit_warnings()?;
+    let project = config.project()?;
+    let output = ProjectCompiler::new().quiet(true).compile(&project)?;
+    let (artifacts, sources) = output.into_artifacts_with_sources();
+    let inheritance =
+        linearized_bases(sources.into_sources_with_version().map(|(_, source, _)| source));
+    let sources_dir = project.paths.sources.to_string_lossy().to_string();
+    let abis = artifacts
+        .filter(|(id, _)| {
+            let is_sources_path = id.source.to_string_lossy().starts_with(&sources_dir);
+            include_all || (is_sources_path && !id.source.is_sol_test())
+        })
+        .filter_map(|(id, artifact)| artifact.abi.map(|abi| (id.name, abi.abi)))
+        .collect();
+    Ok((config, abis, inheritance))
+}
+
+/// Returns the names of the linearized base contracts of every contract in the sources, from the
+/// most derived to the most base one, the contract itself excluded.
+fn linearized_bases(
+    sources: impl IntoIterator<Item = SourceFile>,
+) -> BTreeMap<String, Vec<String>> {
+    let contracts = sources
+        .into_iter()
+        .filter_map(|source| source.ast)
+        .flat_map(|ast| ast.nodes)
+        .filter(|node| node.node_type == NodeType::ContractDefinition)
+        .collect::<Vec<_>>();
+    let names = contracts
+        .iter()
+        .filter_map(|node| Some((node.id?, node.attribute::<String>("name")?)))
+        .collect::<HashMap<_, _>>();
+    contracts
+        .iter()
+        .filter_map(|node| {
+            let name = node.attribute::<String>("name")?;
+            let bases: Vec<usize> = node.attribute("linearizedBaseContracts")?;
+            Some((name, bases.iter().skip(1).filter_map(|id| names.get(id).cloned()).collect()))
+        })
+        .collect()
+}
+
+/// A selector that is defined multiple times within a group of selectors.
+#[derive(Debug, PartialEq)]
+struct SelectorCollision {
+    /// The selector
+    selector: String,
+    /// The contract or the set of facets the selector collides in
+    group: String,
+    /// The contracts and signatures that define the selector
+    entries: Vec<(String, String)>,
+}
+
+/// Returns the selectors that collide within the inheritance tree of a contract or within a set
+/// of facets.
+///
+/// Functions, or errors, of the inheritance tree of a contract collide if they have the same
+/// selector but different signatures, each signature is attributed to the most base contract of
+/// `inheritance` that defines it. Functions of facets collide if they are defined by more than one
+/// facet of a set.
+fn find_collisions(
+    abis: &[(String, JsonAbi)],
+    inheritance: &BTreeMap<String, Vec<String>>,
+    facets: &[Vec<String>],
+) -> Result<Vec<SelectorCollision>> {
+    let mut collisions = Vec::new();
+
+    for (contract, _) in abis {
+        let bases = inheritance.get(contract).into_iter().flatten();
+        let tree = std::iter::once(contract).chain(bases).collect::<Vec<_>>();
+
+        // functions and errors are never dispatched on the same selector, so they are checked
+        // separately
+        let mut functions: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+        let mut errors: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+        // the most base contracts first
+        for &member in tree.iter().rev() {
+            let Some((_, abi)) = abis.iter().find(|(name, _)| name == member) else { continue };
+            for func in abi.functions() {
+                insert_selector(&mut functions, member, func.selector(), func.signature());
+            }
+            for error in abi.errors() {
+                insert_selector(&mut errors, member, error.selector(), error.signature());
+            }
+        }
+        for (selector, entries) in functions.into_iter().chain(errors) {
+            if entries.len() > 1 {
+                collisions.push(SelectorCollision { selector, group: contract.clone(), entries });
+            }
+        }
+    }
+
+    for set in facets {
+        let mut selectors: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+        for facet in set {
+            let (_, abi) = abis
+                .iter()
+                .find(|(contract, _)| contract == facet)
+                .ok_or_else(|| eyre::eyre!("Could not find artifact for facet `{facet}`"))?;
+            for func in abi.functions() {
+                selectors
+                    .entry(hex::encode_prefixed(func.selector()))
+                    .or_default()
+                    .push((facet.clone(), func.signature()));
+            }
+        }
+        for (selector, entries) in selectors {
+            if entries.len() > 1 {
+                collisions.push(SelectorCollision { selector, group: set.join(", "), entries });
+            }
+        }
+    }
+
+    Ok(collisions)
+}
+
+/// Adds the signature of a member of an inheritance tree to the entries of its selector, unless
+/// it's already defined by a more base contract.
+fn insert_selector(
+    selectors: &mut BTreeMap<String, Vec<(String, String)>>,
+    member: &str,
+    selector: Selector,
+    signature: String,
+) {
+    let entries = selectors.entry(hex::encode_prefixed(selector)).or_default();
+    if !entries.iter().any(|(_, sig)| *sig == signature) {
+        entries.push((member.to_string(), signature));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_json_abi::Error;
+    use foundry_common::abi::get_func;
+
+    fn abi(functions: &[&str]) -> JsonAbi {
+        let mut abi = JsonAbi::default();
+        for func in functions {
+            let func = get_func(func).unwrap();
+            abi.functions.entry(func.name.clone()).or_default().push(func);
+        }
+        abi
+    }
+
+    #[test]
+    fn can_find_facet_collisions() {
+        let abis = vec![
+            ("OwnershipFacet".to_string(), abi(&["owner()", "transferOwnership(address)"])),
+            ("TokenFacet".to_string(), abi(&["owner()", "transfer(address,uint256)"])),
+            ("Other".to_string(), abi(&["transferOwnership(address)"])),
+        ];
+        assert!(find_collisions(&abis, &BTreeMap::new(), &[]).unwrap().is_empty());
+
+        let facets = vec![vec!["OwnershipFacet".to_string(), "TokenFacet".to_string()]];
+        assert_eq!(
+            find_collisions(&abis, &BTreeMap::new(), &facets).unwrap(),
+            vec![SelectorCollision {
+                selector: "0x8da5cb5b".to_string(),
+                group: "OwnershipFacet, TokenFacet".to_string(),
+                entries: vec![
+                    ("OwnershipFacet".to_string(), "owner()".to_string()),
+                    ("TokenFacet".to_string(), "owner()".to_string()),
+                ],
+            }]
+        );
+
+        let unknown = vec![vec!["Missing".to_string()]];
+        assert!(find_collisions(&abis, &BTreeMap::new(), &unknown).is_err());
+    }
+
+    #[test]
+    fn can_find_contract_collisions() {
+        // both functions have the selector `0x42966c68`
+        let abis = vec![(
+            "Token".to_string(),
+            abi(&["burn(uint256)", "collate_propagate_storage(bytes16)"]),
+        )];
+        let collisions = find_collisions(&abis, &BTreeMap::new(), &[]).unwrap();
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].selector, "0x42966c68");
+        assert_eq!(collisions[0].group, "Token");
+    }
+
+    #[test]
+    fn can_find_inherited_collisions() {
+        // the ABI of a contract contains the functions and errors of its bases
+        let abis = vec![
+            ("Burner".to_string(), abi(&["burn(uint256)"])),
+            ("Storage".to_string(), abi(&["collate_propagate_storage(bytes16)"])),
+            (
+                "Token".to_string(),
+                abi(&["burn(uint256)", "collate_propagate_storage(bytes16)", "name()"]),
+            ),
+        ];
+        let inheritance = BTreeMap::from([
+            ("Burner".to_string(), vec![]),
+            ("Storage".to_string(), vec![]),
+            ("Token".to_string(), vec!["Storage".to_string(), "Burner".to_string()]),
+        ]);
+        assert_eq!(
+            find_collisions(&abis, &inheritance, &[]).unwrap(),
+            vec![SelectorCollision {
+                selector: "0x42966c68".to_string(),
+                group: "Token".to_string(),
+                entries: vec![
+                    ("Burner".to_string(), "burn(uint256)".to_string()),
+                    ("Storage".to_string(), "collate_propagate_storage(bytes16)".to_string()),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn ignores_collisions_of_functions_and_errors() {
+        // the function and the error have the selector `0x42966c68`
+        let mut token = abi(&["burn(uint256)"]);
+        let error = Error::parse("collate_propagate_storage(bytes16)").unwrap();
+        token.errors.entry(error.name.clone()).or_default().push(error);
+        let abis = vec![("Token".to_string(), token)];
+        assert!(find_collisions(&abis, &BTreeMap::new(), &[]).unwrap().is_empty());
+
+        // errors still collide with each other
+        let mut token = JsonAbi::default();
+        for sig in ["burn(uint256)", "collate_propagate_storage(bytes16)"] {
+            let error = Error::parse(sig).unwrap();
+            token.errors.entry(error.name.clone()).or_default().push(error);
+        }
+        let abis = vec![("Token".to_string(), token)];
+        let collisions = find_collisions(&abis, &BTreeMap::new(), &[]).unwrap();
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].selector, "0x42966c68");
+    }
+}
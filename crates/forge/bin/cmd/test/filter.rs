@@ -191,7 +191,10 @@ impl FileFilter for ProjectPathsAwareFilter {
     /// If no file regex is set this returns true if the file ends with `.t.sol`, see
     /// [FoundryPathExr::is_sol_test()]
     fn is_match(&self, file: &Path) -> bool {
-        self.args_filter.is_match(file)
+        // the compiler passes absolute paths, the patterns are usually relative to the root
+        self.args_filter.is_match(file) ||
+            file.strip_prefix(&self.paths.root)
+                .map_or(false, |relative| self.args_filter.is_match(relative))
     }
 }
 
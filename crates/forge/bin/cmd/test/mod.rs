@@ -1,12 +1,16 @@
 use super::{install, test::filter::ProjectPathsAwareFilter, watch::WatchArgs};
 use alloy_primitives::U256;
-use clap::Parser;
+use clap::{Parser, ValueHint};
+use dialoguer::Select;
 use eyre::Result;
 use forge::{
     decode::decode_console_logs,
+    executors::TestStopper,
     gas_report::GasReport,
     inspectors::CheatsConfig,
     result::{SuiteResult, TestResult, TestStatus},
+    shard::{ShardMetadata, ShardSpec, TestShard, TestTimings, TEST_TIMINGS_FILENAME},
+    stream::{millis, TestEvent},
     traces::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier},
         CallTraceDecoderBuilder, TraceKind,
@@ -21,7 +25,9 @@ use foundry_common::{
     compact_to_contract,
     compile::{ContractSources, ProjectCompiler},
     evm::EvmArgs,
-    get_contract_name, get_file_name, shell,
+    get_contract_name, get_file_name,
+    provider::tower::RetryStats,
+    shell, vyper,
 };
 use foundry_config::{
     figment,
@@ -33,7 +39,14 @@ use foundry_config::{
 };
 use foundry_debugger::Debugger;
 use regex::Regex;
-use std::{collections::BTreeMap, fs, sync::mpsc::channel, time::Duration};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    sync::mpsc::channel,
+    time::{Duration, Instant},
+};
 use watchexec::config::{InitConfig, RuntimeConfig};
 use yansi::Paint;
 
@@ -42,7 +55,7 @@ mod summary;
 use summary::TestSummaryReporter;
 
 pub use filter::FilterArgs;
-use forge::traces::render_trace_arena;
+use forge::traces::{render_trace_arena_with_options, TraceRenderOptions};
 
 // Loads project's figment and merges the build cli arguments into it
 foundry_config::merge_impl_figment_convert!(TestArgs, opts, evm_opts);
@@ -65,6 +78,10 @@ pub struct TestArgs {
     /// case.
     /// If the fuzz test does not fail, it will open the debugger on the last fuzz case.
     ///
+    /// If the matching test is an invariant test, then the failing call sequence (or the last run
+    /// if no invariant is broken) is replayed and the call to debug can be selected from the
+    /// sequence.
+    ///
     /// For more fine-grained control of which fuzz case is run, see forge run.
     #[clap(long, value_name = "TEST_FUNCTION")]
     debug: Option<Regex>,
@@ -81,7 +98,21 @@ pub struct TestArgs {
     #[clap(long, short, help_heading = "Display options")]
     json: bool,
 
-    /// Stop running tests after the first failure.
+    /// Stream the progress of the run as newline-delimited JSON events, instead of the human
+    /// readable output.
+    ///
+    /// Every event is tagged with the version of its schema, see the `forge::stream` module.
+    #[clap(
+        long,
+        help_heading = "Display options",
+        conflicts_with_all = ["json", "list", "debug", "gas_report", "summary", "fail_fast"]
+    )]
+    stream_json: bool,
+
+    /// Stop starting new tests after the first failure.
+    ///
+    /// The tests already running complete, and the run is reported as aborted with the count of
+    /// the tests that were not executed.
     #[clap(long)]
     pub fail_fast: bool,
 
@@ -100,6 +131,26 @@ pub struct TestArgs {
     #[clap(long, env = "FOUNDRY_FUZZ_RUNS", value_name = "RUNS")]
     pub fuzz_runs: Option<u64>,
 
+    /// Only run the tests of a shard, e.g. `2/5` runs the second of five shards.
+    ///
+    /// Test functions are split by the hash of their contract and name, unless
+    /// `--shard-timings` is passed.
+    #[clap(long, value_name = "INDEX/COUNT", conflicts_with_all = ["debug", "list"])]
+    pub shard: Option<ShardSpec>,
+
+    /// Balance the shards by the test durations of this file, e.g. the `cache/test-timings.json`
+    /// of an earlier run. Tests without a duration weigh their number of fuzz/invariant runs.
+    ///
+    /// All shards must be passed the same file to agree on the split.
+    #[clap(long, requires = "shard", value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub shard_timings: Option<PathBuf>,
+
+    /// Run the tests one at a time, ordered by the names of their suites and signatures.
+    ///
+    /// Helps to reproduce failures that depend on the order in which tests run.
+    #[clap(long)]
+    pub deterministic_scheduling: bool,
+
     #[clap(flatten)]
     filter: FilterArgs,
 
@@ -119,6 +170,21 @@ pub struct TestArgs {
     /// Print detailed test summary table.
     #[clap(long, help_heading = "Display options", requires = "summary")]
     pub detailed: bool,
+
+    /// Print the configured `ffi` and `fs_permissions` permissions and what they were used for.
+    #[clap(long, help_heading = "Display options")]
+    pub show_permissions: bool,
+
+    /// Collapse consecutive calls to the same contract with the same calldata and result in the
+    /// traces into one, annotated with the number of calls.
+    #[clap(long, help_heading = "Display options")]
+    pub collapse_repeated_calls: bool,
+
+    /// Hide the calls nested deeper than this in the traces, summarizing their number and gas.
+    ///
+    /// The top-level call is at depth 0.
+    #[clap(long, value_name = "DEPTH", help_heading = "Display options")]
+    pub max_trace_depth: Option<usize>,
 }
 
 impl TestArgs {
@@ -129,7 +195,7 @@ impl TestArgs {
 
     pub async fn run(self) -> Result<TestOutcome> {
         trace!(target: "forge::test", "executing test command");
-        shell::set_shell(shell::Shell::from_args(self.opts.silent, self.json))?;
+        shell::set_shell(shell::Shell::from_args(self.opts.silent, self.json || self.stream_json))?;
         self.execute_tests().await
     }
 
@@ -158,11 +224,25 @@ impl TestArgs {
         let mut filter = self.filter(&config);
         trace!(target: "forge::test", ?filter, "using filter");
 
-        let mut compiler = ProjectCompiler::new().quiet_if(self.json || self.opts.silent);
-        if config.sparse_mode {
+        let quiet = self.json || self.stream_json || self.opts.silent;
+        let compile_start = Instant::now();
+        if self.stream_json {
+            emit(&TestEvent::CompilationStarted)?;
+        }
+
+        // Vyper contracts are deployed from their artifacts, so all of them are compiled
+        vyper::compile_project(&config, None, quiet)?;
+
+        let mut compiler = ProjectCompiler::new().quiet_if(quiet);
+        // `--match-path` only needs the matching test files and their imports, artifacts of all
+        // other files are kept
+        if config.sparse_mode || filter.args().path_pattern.is_some() {
             compiler = compiler.filter(Box::new(filter.clone()));
         }
         let output = compiler.compile(&project)?;
+        if self.stream_json {
+            emit(&TestEvent::CompilationFinished { duration_ms: millis(compile_start.elapsed()) })?;
+        }
 
         // Create test options from general project settings and compiler output.
         let project_root = &project.paths.root;
@@ -195,13 +275,32 @@ impl TestArgs {
             .with_cheats_config(CheatsConfig::new(&config, evm_opts.clone()))
             .with_test_options(test_options.clone());
 
-        let runner = runner_builder.clone().build(
+        let mut runner = runner_builder.clone().build(
             project_root,
             output.clone(),
             env.clone(),
             evm_opts.clone(),
         )?;
 
+        let timings_path = config.cache_path.join(TEST_TIMINGS_FILENAME);
+        let mut timings = TestTimings::read(&timings_path);
+        if let Some(spec) = self.shard {
+            let shared = match &self.shard_timings {
+                Some(path) if !path.exists() => {
+                    eyre::bail!("shard timings file {} doesn't exist", path.display())
+                }
+                Some(path) => Some(TestTimings::read(path)),
+                None => None,
+            };
+            runner.shard = Some(TestShard::new(spec, &runner, &filter, shared.as_ref()));
+        }
+        runner.threads = if self.deterministic_scheduling { Some(1) } else { config.threads };
+        runner.fail_fast = self.fail_fast;
+        if verbosity >= 2 && !self.json && !self.stream_json {
+            let threads = runner.threads.unwrap_or_else(rayon::current_num_threads);
+            eprintln!("Threads: {} compiler jobs, {threads} test threads", project.solc_jobs);
+        }
+
         if should_debug {
             filter.args_mut().test_pattern = self.debug.clone();
             let num_filtered = runner.matching_test_function_count(&filter);
@@ -221,6 +320,13 @@ impl TestArgs {
             .run_tests(runner, config.clone(), verbosity, &filter, test_options.clone())
             .await?;
 
+        if config.cache && !outcome.results.is_empty() {
+            timings.update(&outcome.results);
+            if let Err(err) = timings.write(&timings_path) {
+                warn!(target: "forge::test", ?err, "failed to write test timings");
+            }
+        }
+
         if should_debug {
             let tests = outcome.clone().into_tests();
             // todo(onbjerg): why do we bother decoding everything and having multiple decoders if
@@ -276,12 +382,24 @@ impl TestArgs {
                         .0
                         .entry(id.name.clone())
                         .or_default()
-                        .insert(source.id, (source_code, source_contract));
+                        .insert(source.id, (path.into(), source_code, source_contract));
                 }
             }
 
             let test = outcome.clone().into_tests().next().unwrap();
-            let result = test.result;
+            let mut result = test.result;
+            // Invariant tests record every call of the replayed sequence, so let the user pick the
+            // call to debug
+            if !result.debug_calls.is_empty() {
+                let labels = result.debug_calls.iter().map(|(label, _)| label).collect::<Vec<_>>();
+                let selection = Select::new()
+                    .with_prompt("Select the call of the sequence to debug")
+                    .items(&labels)
+                    .default(labels.len() - 1)
+                    .interact()?;
+                result.debug = Some(result.debug_calls.swap_remove(selection).1);
+            }
+
             // Run the debugger
             let mut debugger = Debugger::builder()
                 // TODO: `Option::as_slice` in 1.75
@@ -309,6 +427,10 @@ impl TestArgs {
             return list(runner, filter, self.json);
         }
 
+        let stopper = runner.stopper.clone();
+        interrupt_on_ctrl_c(stopper.clone());
+        let expected = runner.matching_suites(filter);
+
         if let Some(debug_regex) = self.debug.as_ref() {
             let mut filter = filter.clone();
             filter.args_mut().test_pattern = Some(debug_regex.clone());
@@ -316,6 +438,11 @@ impl TestArgs {
             return Ok(TestOutcome::new(results, self.allow_failure));
         }
 
+        if self.stream_json {
+            let outcome = self.stream_tests(runner, filter, test_options).await?;
+            return Ok(outcome.with_stopper(&stopper, &expected));
+        }
+
         trace!(target: "forge::test", "running all tests");
 
         if runner.matching_test_function_count(filter) == 0 {
@@ -339,9 +466,25 @@ impl TestArgs {
         }
 
         if self.json {
+            let shard = runner.shard.as_ref().map(TestShard::metadata);
             let results = runner.test_collect(filter, test_options).await;
-            println!("{}", serde_json::to_string(&results)?);
-            return Ok(TestOutcome::new(results, self.allow_failure));
+            let outcome =
+                TestOutcome::new(results, self.allow_failure).with_stopper(&stopper, &expected);
+            // the shape only depends on `--shard`, an aborted run without it is reported on stderr
+            let json = match shard {
+                Some(shard) => serde_json::to_string(&ShardedResults {
+                    shard,
+                    aborted: outcome.aborted,
+                    unexecuted: outcome.unexecuted,
+                    results: &outcome.results,
+                })?,
+                None => serde_json::to_string(&outcome.results)?,
+            };
+            println!("{json}");
+            if outcome.aborted {
+                eprintln!("{}", outcome.aborted_summary());
+            }
+            return Ok(outcome);
         }
 
         // Set up identifiers
@@ -354,6 +497,9 @@ impl TestArgs {
         // Set up test reporter channel
         let (tx, rx) = channel::<(String, SuiteResult)>();
 
+        let cheats_config = runner.cheats_config.clone();
+        let shard = runner.shard.clone();
+
         // Run tests
         let handle = tokio::task::spawn({
             let filter = filter.clone();
@@ -361,7 +507,8 @@ impl TestArgs {
         });
 
         let mut results = BTreeMap::new();
-        let mut gas_report = GasReport::new(config.gas_reports, config.gas_reports_ignore);
+        let mut gas_report = GasReport::new(config.gas_reports, config.gas_reports_ignore)
+            .with_config(&config.gas_report)?;
         let sig_identifier =
             SignaturesIdentifier::new(Config::foundry_cache_dir(), config.offline)?;
 
@@ -370,7 +517,7 @@ impl TestArgs {
         let mut total_skipped = 0;
         let mut suite_results: Vec<TestOutcome> = Vec::new();
 
-        'outer: for (contract_name, suite_result) in rx {
+        for (contract_name, suite_result) in rx {
             results.insert(contract_name.clone(), suite_result.clone());
 
             let mut tests = suite_result.test_results.clone();
@@ -414,6 +561,11 @@ impl TestArgs {
                 }
 
                 let mut decoder = builder.build();
+                let render_options = TraceRenderOptions {
+                    collapse_repeated: self.collapse_repeated_calls,
+                    max_depth: self.max_trace_depth,
+                    ..Default::default()
+                };
 
                 // Decode the traces
                 let mut decoded_traces = Vec::with_capacity(result.traces.len());
@@ -437,7 +589,10 @@ impl TestArgs {
                     };
 
                     if should_include {
-                        decoded_traces.push(render_trace_arena(arena, &decoder).await?);
+                        decoded_traces.push(
+                            render_trace_arena_with_options(arena, &decoder, &render_options)
+                                .await?,
+                        );
                     }
                 }
 
@@ -449,11 +604,6 @@ impl TestArgs {
                 if self.gas_report {
                     gas_report.analyze(&result.traces, &decoder).await;
                 }
-
-                // If the test failed, we want to stop processing the rest of the tests
-                if self.fail_fast && result.status == TestStatus::Failure {
-                    break 'outer
-                }
             }
             let block_outcome = TestOutcome::new(
                 [(contract_name.clone(), suite_result)].into(),
@@ -475,6 +625,25 @@ impl TestArgs {
             shell::println(gas_report.finalize())?;
         }
 
+        if verbosity >= 3 {
+            if let Some(report) = etherscan_identifier.usage_report() {
+                shell::println(format!("\n{report}"))?;
+            }
+            let retries = RetryStats::global();
+            if retries.retries() > 0 {
+                shell::println(format!("\nForks: {retries}"))?;
+            }
+        }
+
+        if self.show_permissions {
+            let report = cheats_config.permissions_report();
+            if report.is_empty() {
+                shell::println("\nPermissions: none configured")?;
+            } else {
+                shell::println(format!("\nPermissions:\n{report}"))?;
+            }
+        }
+
         let num_test_suites = results.len();
 
         if num_test_suites > 0 {
@@ -492,6 +661,15 @@ impl TestArgs {
             }
         }
 
+        if let Some(shard) = shard {
+            shell::println(format!(
+                "Ran shard {}: {} tests in shard, {} tests skipped as out-of-shard",
+                shard.spec(),
+                shard.len(),
+                Paint::yellow(shard.skipped())
+            ))?;
+        }
+
         // Reattach the task.
         if let Err(e) = handle.await {
             match e.try_into_panic() {
@@ -502,7 +680,12 @@ impl TestArgs {
 
         trace!(target: "forge::test", "received {} results", results.len());
 
-        Ok(TestOutcome::new(results, self.allow_failure))
+        let outcome =
+            TestOutcome::new(results, self.allow_failure).with_stopper(&stopper, &expected);
+        if outcome.aborted {
+            shell::println(outcome.aborted_summary())?;
+        }
+        Ok(outcome)
     }
 
     /// Returns the flattened [`FilterArgs`] arguments merged with [`Config`].
@@ -523,6 +706,40 @@ impl TestArgs {
             vec![config.src, config.test]
         })
     }
+
+    /// Runs the tests and streams the events of the run to stdout, see [forge::stream].
+    async fn stream_tests(
+        &self,
+        mut runner: MultiContractRunner,
+        filter: &ProjectPathsAwareFilter,
+        test_options: TestOptions,
+    ) -> Result<TestOutcome> {
+        trace!(target: "forge::test", "streaming all tests");
+        let start = Instant::now();
+        for (suite, tests) in runner.matching_suites(filter) {
+            emit(&TestEvent::SuiteDiscovered { suite, tests })?;
+        }
+
+        let (tx, rx) = channel();
+        runner.events = Some(tx);
+        let handle = tokio::task::spawn({
+            let filter = filter.clone();
+            async move { runner.test_collect(&filter, test_options).await }
+        });
+        // the runner and with it the sender are dropped once all tests ran
+        for event in rx {
+            emit(&event)?;
+        }
+
+        let outcome = TestOutcome::new(handle.await?, self.allow_failure);
+        emit(&TestEvent::RunFinished {
+            duration_ms: millis(start.elapsed()),
+            passed: outcome.successes().count(),
+            failed: outcome.failures().count(),
+            skipped: outcome.skips().count(),
+        })?;
+        Ok(outcome)
+    }
 }
 
 impl Provider for TestArgs {
@@ -578,6 +795,17 @@ impl Test {
     }
 }
 
+/// The `--json` output of a run with `--shard`
+#[derive(Serialize)]
+struct ShardedResults<'a> {
+    shard: ShardMetadata,
+    /// Whether the run stopped before all the tests were executed
+    aborted: bool,
+    /// The number of tests that were not executed
+    unexecuted: usize,
+    results: &'a BTreeMap<String, SuiteResult>,
+}
+
 /// Represents the bundled results of all tests
 #[derive(Clone, Debug)]
 pub struct TestOutcome {
@@ -585,11 +813,37 @@ pub struct TestOutcome {
     pub allow_failure: bool,
     /// Results for each suite of tests `contract -> SuiteResult`
     pub results: BTreeMap<String, SuiteResult>,
+    /// Whether the run was stopped before all tests ran, or interrupted
+    pub aborted: bool,
+    /// The number of matching tests that were not executed
+    pub unexecuted: usize,
 }
 
 impl TestOutcome {
     fn new(results: BTreeMap<String, SuiteResult>, allow_failure: bool) -> Self {
-        Self { results, allow_failure }
+        Self { results, allow_failure, aborted: false, unexecuted: 0 }
+    }
+
+    /// Marks the outcome as aborted if the run was interrupted, or stopped before all `expected`
+    /// tests `suite -> signatures` ran.
+    fn with_stopper(
+        mut self,
+        stopper: &TestStopper,
+        expected: &BTreeMap<String, Vec<String>>,
+    ) -> Self {
+        self.unexecuted = expected
+            .iter()
+            .map(|(suite, tests)| match self.results.get(suite) {
+                None => tests.len(),
+                // a failed `setUp()` is the only result of its suite
+                Some(result) if result.test_results.contains_key("setUp()") => 0,
+                Some(result) => {
+                    tests.iter().filter(|test| !result.test_results.contains_key(*test)).count()
+                }
+            })
+            .sum();
+        self.aborted = stopper.is_interrupted() || (stopper.is_stopped() && self.unexecuted > 0);
+        self
     }
 
     /// Returns an iterator over all succeeding tests and their names.
@@ -623,9 +877,11 @@ impl TestOutcome {
     }
 
     /// Checks if there are any failures and failures are disallowed
+    ///
+    /// An aborted run always fails.
     pub fn ensure_ok(&self) -> Result<()> {
         let failures = self.failures().count();
-        if self.allow_failure || failures == 0 {
+        if (self.allow_failure || failures == 0) && !self.aborted {
             return Ok(());
         }
 
@@ -634,6 +890,11 @@ impl TestOutcome {
             std::process::exit(1);
         }
 
+        if failures == 0 {
+            // the run is aborted, which was already reported
+            std::process::exit(1);
+        }
+
         shell::println("")?;
         shell::println("Failing tests:")?;
         for (suite_name, suite) in self.results.iter() {
@@ -677,12 +938,42 @@ impl TestOutcome {
             self.duration()
         )
     }
+
+    /// Returns the line reporting the run as aborted.
+    pub fn aborted_summary(&self) -> String {
+        let term = if self.unexecuted == 1 { "test" } else { "tests" };
+        format!(
+            "{} {} {term} not executed",
+            Paint::red("Test run aborted:"),
+            Paint::yellow(self.unexecuted)
+        )
+    }
+}
+
+/// Interrupts the run on the first Ctrl-C, and exits on the second one.
+fn interrupt_on_ctrl_c(stopper: TestStopper) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return
+        }
+        eprintln!("\nInterrupted, waiting for the running tests. Press Ctrl-C again to exit.");
+        stopper.interrupt();
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
 }
 
 fn short_test_result(name: &str, result: &TestResult) {
     shell::println(format!("{result} {name} {}", result.kind.report())).unwrap();
 }
 
+/// Writes the event of `--stream-json` as a line to stdout.
+fn emit(event: &TestEvent) -> Result<()> {
+    println!("{}", event.to_json_line()?);
+    Ok(())
+}
+
 /// Formats the aggregated summary of all test suites into a string (for printing).
 fn format_aggregated_summary(
     num_test_suites: usize,
@@ -0,0 +1,520 @@
+use alloy_primitives::{Address, B256, U256};
+use clap::Parser;
+use ethers_core::types::Transaction;
+use ethers_providers::Middleware;
+use eyre::{eyre, Context, Result};
+use forge::{executors::TracingExecutor, opts::EvmOpts, revm::DatabaseRef};
+use foundry_block_explorers::Client;
+use foundry_cli::{
+    opts::{CoreBuildArgs, EtherscanOpts, RpcOpts},
+    utils::{self, LoadConfig},
+};
+use foundry_common::{
+    compile::ProjectCompiler,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_compilers::{info::ContractInfo, utils::canonicalize};
+use foundry_config::{impl_figment_convert, Chain, Config};
+use serde::Serialize;
+use std::{fmt, ops::Range};
+use yansi::Paint;
+
+/// The maximum number of differing byte ranges that are printed.
+const MAX_PRINTED_RANGES: usize = 10;
+
+/// CLI arguments for `forge verify-bytecode`.
+///
+/// Exits with `0` if the bytecode fully matches, with `2` if only the metadata hash differs and
+/// with `3` if the bytecode doesn't match.
+#[derive(Clone, Debug, Parser)]
+pub struct VerifyBytecodeArgs {
+    /// The address of the contract to verify.
+    pub address: Address,
+
+    /// The contract identifier in the form `<path>:<contractname>`.
+    pub contract: ContractInfo,
+
+    /// The ABI-encoded constructor arguments, used to fill the immutables.
+    ///
+    /// If not provided, they are read from the creation transaction.
+    #[clap(long, value_name = "ARGS", visible_alias = "encoded-constructor-args")]
+    pub constructor_args: Option<String>,
+
+    /// The hash of the transaction that deployed the contract.
+    ///
+    /// The creation bytecode is checked against the input of the transaction. If not provided, the
+    /// transaction is looked up with the contract creation API of the explorer, if an Etherscan
+    /// API key is available.
+    #[clap(long, value_name = "TX_HASH")]
+    pub creation_tx: Option<B256>,
+
+    /// The block to fetch the deployed bytecode at.
+    #[clap(long, short = 'B', value_name = "BLOCK")]
+    pub block: Option<u64>,
+
+    /// Print the result as JSON.
+    #[clap(long)]
+    pub json: bool,
+
+    /// Suppress all output except errors.
+    #[clap(long, short, conflicts_with = "json")]
+    pub quiet: bool,
+
+    #[clap(flatten)]
+    pub rpc: RpcOpts,
+
+    #[clap(flatten)]
+    pub etherscan: EtherscanOpts,
+
+    /// The local build is compiled with the same settings as `forge build`.
+    #[clap(flatten)]
+    pub build: CoreBuildArgs,
+}
+
+impl_figment_convert!(VerifyBytecodeArgs, build, rpc, etherscan);
+
+impl VerifyBytecodeArgs {
+    /// Compiles the contract and compares its bytecode with the bytecode deployed at the address.
+    pub async fn run(mut self) -> Result<BytecodeMatch> {
+        let (config, evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
+        let provider = utils::get_provider(&config)?;
+
+        let mut project = config.project()?;
+        let mut compiler = ProjectCompiler::new().quiet(self.json || self.quiet);
+        if let Some(contract_path) = &mut self.contract.path {
+            let target_path = canonicalize(&*contract_path)?;
+            *contract_path = target_path.to_string_lossy().to_string();
+            // the contract is compiled with the settings of its compilation override, if any
+            project = config.for_source(&target_path).project()?;
+            compiler = compiler.files([target_path]).overrides(Vec::new());
+        }
+        let output = compiler.compile(&project)?;
+        let artifact = output.find_contract(&self.contract).ok_or_else(|| {
+            eyre!("Could not find artifact `{}` in the compiled artifacts", self.contract)
+        })?;
+
+        let unlinked = || {
+            eyre!(
+                "Contract `{}` has no bytecode or references unlinked libraries, link them with `--libraries`",
+                self.contract.name
+            )
+        };
+        let creation_code = artifact
+            .bytecode
+            .as_ref()
+            .and_then(|bytecode| bytecode.object.as_bytes())
+            .ok_or_else(unlinked)?
+            .to_vec();
+        let deployed_bytecode = artifact.deployed_bytecode.as_ref();
+        let deployed_code = deployed_bytecode
+            .and_then(|deployed| deployed.bytecode.as_ref())
+            .and_then(|bytecode| bytecode.object.as_bytes())
+            .ok_or_else(unlinked)?
+            .to_vec();
+        let immutables = deployed_bytecode
+            .map(|deployed| {
+                deployed
+                    .immutable_references
+                    .values()
+                    .flatten()
+                    .map(|offsets| {
+                        offsets.start as usize..(offsets.start + offsets.length) as usize
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let has_constructor_args = artifact
+            .abi
+            .as_ref()
+            .and_then(|abi| abi.constructor())
+            .map_or(false, |constructor| !constructor.inputs.is_empty());
+
+        let onchain_code =
+            provider.get_code(self.address.to_ethers(), self.block.map(Into::into)).await?.to_vec();
+        if onchain_code.is_empty() {
+            eyre::bail!("No bytecode found at address {}", self.address)
+        }
+
+        if self.creation_tx.is_none() {
+            let chain = Chain::from_id(provider.get_chainid().await?.as_u64());
+            if let Some(hash) = self.find_creation_tx(&config, chain).await {
+                self.note(format!("Found the creation transaction {hash} with the explorer."));
+                self.creation_tx = Some(hash);
+            }
+        }
+
+        let mut constructor_args = self
+            .constructor_args
+            .as_deref()
+            .map(hex::decode)
+            .transpose()
+            .wrap_err("Invalid constructor arguments")?;
+
+        // the creation check, with the expected and the on-chain creation code
+        let mut creation = None;
+        let mut creation_tx = None;
+        if let Some(hash) = self.creation_tx {
+            let tx = provider
+                .get_transaction(hash.to_ethers())
+                .await?
+                .ok_or_else(|| eyre!("Transaction {hash} not found"))?;
+
+            if tx.to.is_some() {
+                self.note(format!(
+                    "Transaction {hash} is not a contract creation, skipping the creation bytecode check."
+                ));
+            } else {
+                let input = tx.input.to_vec();
+                let (code, args) = input.split_at(creation_code.len().min(input.len()));
+                let mut comparison = Comparison::new(&creation_code, code);
+                let args = constructor_args.get_or_insert_with(|| args.to_vec());
+                let mut expected = creation_code.clone();
+                expected.extend_from_slice(args);
+                if expected[code.len()..] != input[code.len()..] {
+                    comparison.kind = BytecodeMatch::Mismatch;
+                    comparison.diff.push(code.len()..expected.len().max(input.len()));
+                }
+                creation = Some((comparison, expected, input));
+            }
+            creation_tx = Some(tx);
+        }
+
+        if !has_constructor_args {
+            constructor_args.get_or_insert_with(Vec::new);
+        }
+
+        let expected_runtime = if immutables.is_empty() {
+            deployed_code
+        } else if let Some(args) = constructor_args {
+            let mut code = creation_code;
+            code.extend(args);
+            match self.deploy(&config, evm_opts, creation_tx.as_ref(), code).await {
+                Ok(runtime) => runtime,
+                Err(err) => {
+                    self.note(format!(
+                        "Failed to run the constructor locally, immutables are not checked: {err}"
+                    ));
+                    fill_immutables(&deployed_code, &immutables, &onchain_code)
+                }
+            }
+        } else {
+            self.note(
+                "No constructor arguments provided, immutables are not checked. Provide them with `--constructor-args` or `--creation-tx`.",
+            );
+            fill_immutables(&deployed_code, &immutables, &onchain_code)
+        };
+
+        let runtime = Comparison::new(&expected_runtime, &onchain_code);
+        let outcome = creation
+            .as_ref()
+            .map_or(runtime.kind, |(creation, ..)| creation.kind.max(runtime.kind));
+
+        if self.json {
+            let report = Report {
+                outcome,
+                runtime: &runtime,
+                creation: creation.as_ref().map(|(creation, ..)| creation),
+            };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else if !self.quiet {
+            println!("Runtime bytecode: {}", runtime.kind);
+            runtime.print_diff(&expected_runtime, &onchain_code);
+            if let Some((creation, expected, input)) = &creation {
+                println!("Creation bytecode: {}", creation.kind);
+                creation.print_diff(expected, input);
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Looks up the hash of the transaction that deployed the contract with the contract creation
+    /// API of the explorer.
+    ///
+    /// Returns `None` if no Etherscan API key is available or the lookup fails, e.g. because the
+    /// explorer doesn't support the API.
+    async fn find_creation_tx(&self, config: &Config, chain: Chain) -> Option<B256> {
+        let key = config.get_etherscan_api_key(Some(chain))?;
+        let client = Client::new(chain, key).ok()?;
+        match client.contract_creation_data(self.address).await {
+            Ok(creation) => Some(creation.transaction_hash),
+            Err(err) => {
+                trace!(target: "forge::verify", %err, "failed to look up the creation transaction");
+                None
+            }
+        }
+    }
+
+    /// Prints a note to stderr, unless JSON output is requested or the output is suppressed.
+    fn note(&self, msg: impl AsRef<str>) {
+        if !self.json && !self.quiet {
+            eprintln!("{}", Paint::yellow(msg.as_ref()));
+        }
+    }
+
+    /// Runs the creation code on a fork and returns the resulting runtime bytecode.
+    ///
+    /// If the creation transaction is known, this forks off its parent block and uses its sender,
+    /// so immutables depending on the environment are filled like on-chain.
+    async fn deploy(
+        &self,
+        config: &Config,
+        evm_opts: EvmOpts,
+        creation_tx: Option<&Transaction>,
+        code: Vec<u8>,
+    ) -> Result<Vec<u8>> {
+        let mut config = config.clone();
+        let block_number = creation_tx.and_then(|tx| tx.block_number).map(|n| n.as_u64());
+        config.fork_block_number = block_number.map(|n| n.saturating_sub(1)).or(self.block);
+
+        let (mut env, fork, _) = TracingExecutor::get_fork_material(&config, evm_opts).await?;
+        if let Some(number) = block_number {
+            env.block.number = U256::from(number);
+            let provider = utils::get_provider(&config)?;
+            if let Some(block) = provider.get_block(number).await? {
+                env.block.timestamp = block.timestamp.to_alloy();
+                env.block.coinbase = block.author.unwrap_or_default().to_alloy();
+                env.block.gas_limit = block.gas_limit.to_alloy();
+            }
+        }
+        // the constructor is executed without paying for gas
+        env.block.basefee = U256::ZERO;
+
+        let mut executor = TracingExecutor::new(env, fork, Some(config.evm_version), false).await;
+        let sender = creation_tx.map_or(config.sender, |tx| tx.from.to_alloy());
+        let deployed = executor
+            .deploy(sender, code.into(), U256::ZERO, None)
+            .map_err(|err| eyre!("constructor reverted: {err}"))?;
+
+        let code = executor
+            .backend
+            .basic_ref(deployed.address)?
+            .and_then(|account| account.code)
+            .map(|code| code.original_bytes().to_vec())
+            .unwrap_or_default();
+        Ok(code)
+    }
+}
+
+/// How well the local bytecode matches the on-chain bytecode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BytecodeMatch {
+    /// The bytecode is identical.
+    Full,
+    /// The bytecode is identical except for the metadata hash.
+    Partial,
+    /// The bytecode differs.
+    Mismatch,
+}
+
+impl BytecodeMatch {
+    /// Returns the exit code of `forge verify-bytecode` for this outcome.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Full => 0,
+            Self::Partial => 2,
+            Self::Mismatch => 3,
+        }
+    }
+}
+
+impl fmt::Display for BytecodeMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full => write!(f, "{}", Paint::green("full match")),
+            Self::Partial => write!(f, "{}", Paint::yellow("partial match (metadata differs)")),
+            Self::Mismatch => write!(f, "{}", Paint::red("mismatch")),
+        }
+    }
+}
+
+/// The comparison of a local and an on-chain bytecode.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+struct Comparison {
+    #[serde(rename = "match")]
+    kind: BytecodeMatch,
+    /// The byte ranges that differ, these are within the metadata for a partial match.
+    diff: Vec<Range<usize>>,
+}
+
+impl Comparison {
+    fn new(local: &[u8], onchain: &[u8]) -> Self {
+        if local == onchain {
+            return Self { kind: BytecodeMatch::Full, diff: vec![] }
+        }
+
+        let (local_code, local_metadata) = split_metadata(local);
+        let (onchain_code, onchain_metadata) = split_metadata(onchain);
+        if local_code == onchain_code && local_metadata.len() == onchain_metadata.len() {
+            return Self {
+                kind: BytecodeMatch::Partial,
+                diff: diff_ranges(local_metadata, onchain_metadata)
+                    .into_iter()
+                    .map(|range| range.start + local_code.len()..range.end + local_code.len())
+                    .collect(),
+            }
+        }
+
+        Self { kind: BytecodeMatch::Mismatch, diff: diff_ranges(local, onchain) }
+    }
+
+    /// Prints the differing byte ranges of both bytecodes.
+    fn print_diff(&self, local: &[u8], onchain: &[u8]) {
+        if self.kind != BytecodeMatch::Mismatch {
+            return
+        }
+        for range in self.diff.iter().take(MAX_PRINTED_RANGES) {
+            println!(
+                "  bytes {}..{}:\n    local:    0x{}\n    on-chain: 0x{}",
+                range.start,
+                range.end,
+                hex::encode(slice(local, range)),
+                hex::encode(slice(onchain, range))
+            );
+        }
+        if self.diff.len() > MAX_PRINTED_RANGES {
+            println!("  ... and {} more", self.diff.len() - MAX_PRINTED_RANGES);
+        }
+    }
+}
+
+/// The JSON output of `forge verify-bytecode`.
+#[derive(Serialize)]
+struct Report<'a> {
+    #[serde(rename = "match")]
+    outcome: BytecodeMatch,
+    runtime: &'a Comparison,
+    creation: Option<&'a Comparison>,
+}
+
+/// Returns the part of `code` in `range`, clamped to the length of `code`.
+fn slice<'a>(code: &'a [u8], range: &Range<usize>) -> &'a [u8] {
+    &code[range.start.min(code.len())..range.end.min(code.len())]
+}
+
+/// Splits the bytecode into code and the CBOR encoded metadata appended by solc.
+///
+/// The last two bytes of the bytecode are the big-endian length of the metadata.
+fn split_metadata(code: &[u8]) -> (&[u8], &[u8]) {
+    if code.len() < 2 {
+        return (code, &[])
+    }
+    let len = u16::from_be_bytes([code[code.len() - 2], code[code.len() - 1]]) as usize;
+    match code.len().checked_sub(len + 2) {
+        // the metadata is a CBOR map
+        Some(start) if len > 0 && (0xa0..=0xbf).contains(&code[start]) => code.split_at(start),
+        _ => (code, &[]),
+    }
+}
+
+/// Returns the ranges of bytes that differ, a length difference is reported as trailing range.
+fn diff_ranges(a: &[u8], b: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for i in 0..a.len().max(b.len()) {
+        if a.get(i) == b.get(i) {
+            continue
+        }
+        match ranges.last_mut() {
+            Some(range) if range.end == i => range.end = i + 1,
+            _ => ranges.push(i..i + 1),
+        }
+    }
+    ranges
+}
+
+/// Copies the immutables from the on-chain bytecode into the local bytecode, so that they are
+/// ignored by the comparison.
+fn fill_immutables(local: &[u8], immutables: &[Range<usize>], onchain: &[u8]) -> Vec<u8> {
+    let mut code = local.to_vec();
+    for range in immutables {
+        if range.end <= code.len() && range.end <= onchain.len() {
+            code[range.clone()].copy_from_slice(&onchain[range.clone()]);
+        }
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: [u8; 7] = [0xa1, 0x64, 0x73, 0x6f, 0x6c, 0x63, 0x00];
+
+    fn with_metadata(code: &[u8], metadata: &[u8]) -> Vec<u8> {
+        let mut code = code.to_vec();
+        code.extend_from_slice(metadata);
+        code.extend_from_slice(&(metadata.len() as u16).to_be_bytes());
+        code
+    }
+
+    #[test]
+    fn can_split_metadata() {
+        let code = with_metadata(&[0x60, 0x80], &METADATA);
+        let (code, metadata) = split_metadata(&code);
+        assert_eq!(code, &[0x60, 0x80]);
+        assert_eq!(metadata.len(), METADATA.len() + 2);
+
+        let code = [0x60, 0x80, 0x00, 0x01];
+        assert_eq!(split_metadata(&code), (&code[..], &[][..]));
+    }
+
+    #[test]
+    fn can_compare_bytecode() {
+        let local = with_metadata(&[0x60, 0x80, 0x60, 0x40], &METADATA);
+        assert_eq!(Comparison::new(&local, &local).kind, BytecodeMatch::Full);
+
+        let mut metadata = METADATA;
+        metadata[6] = 0x01;
+        let onchain = with_metadata(&[0x60, 0x80, 0x60, 0x40], &metadata);
+        let comparison = Comparison::new(&local, &onchain);
+        assert_eq!(comparison.kind, BytecodeMatch::Partial);
+        assert_eq!(comparison.diff, vec![10..11]);
+
+        let onchain = with_metadata(&[0x60, 0x81, 0x60, 0x41], &METADATA);
+        let comparison = Comparison::new(&local, &onchain);
+        assert_eq!(comparison.kind, BytecodeMatch::Mismatch);
+        assert_eq!(comparison.diff, vec![1..2, 3..4]);
+    }
+
+    #[test]
+    fn can_diff_different_lengths() {
+        assert_eq!(diff_ranges(&[1, 2, 3], &[1, 2, 3, 4, 5]), vec![3..5]);
+        assert_eq!(diff_ranges(&[1, 2, 3], &[0, 0, 3]), vec![0..2]);
+    }
+
+    #[test]
+    fn can_fill_immutables() {
+        let local = [0x60, 0x00, 0x00, 0x60];
+        let onchain = [0x60, 0xaa, 0xbb, 0x60];
+        assert_eq!(fill_immutables(&local, &[1..3], &onchain), onchain.to_vec());
+    }
+
+    #[test]
+    fn can_parse_verify_bytecode() {
+        let args = VerifyBytecodeArgs::parse_from([
+            "foundry-cli",
+            "0xd8509bee9c9bf012282ad33aba0d87241baf5064",
+            "src/Counter.sol:Counter",
+            "--creation-tx",
+            "0x1f8c5ee2e8b4a9e6f3a3e4d7f2c7d6b5a4938271605f4e3d2c1b0a9f8e7d6c5b",
+            "--constructor-args",
+            "0x01",
+        ]);
+        assert!(args.creation_tx.is_some());
+        assert_eq!(args.constructor_args.as_deref(), Some("0x01"));
+        assert!(!args.quiet);
+
+        // without a creation transaction, it's looked up with the explorer's API key
+        let args = VerifyBytecodeArgs::parse_from([
+            "foundry-cli",
+            "0xd8509bee9c9bf012282ad33aba0d87241baf5064",
+            "src/Counter.sol:Counter",
+            "--etherscan-api-key",
+            "key",
+        ]);
+        assert!(args.creation_tx.is_none());
+        assert_eq!(args.etherscan.key.as_deref(), Some("key"));
+        assert_eq!(BytecodeMatch::Partial.max(BytecodeMatch::Full), BytecodeMatch::Partial);
+    }
+}
@@ -0,0 +1,729 @@
+use super::{
+    etherscan::EtherscanVerificationProvider,
+    provider::VerificationProvider,
+    sourcify::{SourcifyVerificationProvider, SOURCIFY_URL},
+    VerifyArgs, VerifyCheckArgs,
+};
+use crate::cmd::retry::{RetryArgs, RETRY_CHECK_ON_VERIFY};
+use async_trait::async_trait;
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::verify::{CodeFormat, VerifyContract};
+use foundry_cli::utils::LoadConfig;
+use foundry_common::retry::Retry;
+use foundry_compilers::artifacts::Libraries;
+use foundry_config::{
+    chains::etherscan_urls, Chain, Config, VerifierApi, VerifierConfig, VerifierRequestEncoding,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{fmt::Write, time::Duration};
+
+/// Name of the built-in profile for the Etherscan v1 API of the chain.
+pub const ETHERSCAN_V1: &str = "etherscan-v1";
+
+/// Name of the built-in profile for the multichain Etherscan v2 API.
+pub const ETHERSCAN_V2: &str = "etherscan-v2";
+
+/// Name of the built-in profile for the Etherscan compatible API of a Blockscout instance.
+pub const BLOCKSCOUT_API: &str = "blockscout-api";
+
+/// Name of the built-in profile for the Sourcify server.
+pub const SOURCIFY_API: &str = "sourcify-api";
+
+/// The Etherscan v2 API endpoint, the chain is selected with the `chainid` query parameter.
+pub static ETHERSCAN_V2_URL: &str = "https://api.etherscan.io/v2/api";
+
+/// The type that can verify a contract on any verifier described by a [`VerifierConfig`]
+/// profile.
+///
+/// Profiles are looked up in the `[verifiers]` table of the config first, and then among the
+/// built-in profiles.
+#[derive(Clone, Debug)]
+pub struct CustomVerificationProvider {
+    /// Name of the verifier profile
+    name: String,
+}
+
+#[async_trait]
+impl VerificationProvider for CustomVerificationProvider {
+    async fn preflight_check(&mut self, args: VerifyArgs) -> Result<()> {
+        let config = args.try_load_config_emit_warnings()?;
+        let profile = self.profile(&config, &args)?;
+        if profile.api == VerifierApi::Sourcify {
+            return SourcifyVerificationProvider::default()
+                .preflight_check(with_sourcify_url(args, &profile))
+                .await
+        }
+        let _ = self.prepare_request(&args, &profile, config).await?;
+        Ok(())
+    }
+
+    async fn verify(&mut self, args: VerifyArgs) -> Result<()> {
+        let config = args.try_load_config_emit_warnings()?;
+        let profile = self.profile(&config, &args)?;
+        if profile.api == VerifierApi::Sourcify {
+            return SourcifyVerificationProvider::default()
+                .verify(with_sourcify_url(args, &profile))
+                .await
+        }
+
+        let fields = self.prepare_request(&args, &profile, config).await?;
+        let client = VerifierClient::new(profile);
+
+        trace!(target: "forge::verify", verifier = %self.name, ?fields, "submitting verification");
+
+        let retry: Retry = args.retry.into();
+        let guid = retry
+            .run_async(|| async {
+                println!(
+                    "\nSubmitting verification for [{}] {} to {}.",
+                    args.contract.name, args.address, self.name
+                );
+                client.submit(&fields).await
+            })
+            .await?;
+
+        let Some(guid) = guid else {
+            println!("Contract source code already verified");
+            return Ok(())
+        };
+
+        println!("Submitted contract for verification:\n\tGUID: `{guid}`");
+
+        if args.watch {
+            let retry = client.poll_retry(RETRY_CHECK_ON_VERIFY);
+            return client.check(&guid, retry).await
+        }
+
+        Ok(())
+    }
+
+    async fn check(&self, args: VerifyCheckArgs) -> Result<()> {
+        let config = args.try_load_config_emit_warnings()?;
+        let profile = self.resolve_profile(
+            &config,
+            args.etherscan.chain.unwrap_or_default(),
+            args.etherscan.key.as_deref(),
+            args.verifier.verifier_url.as_deref(),
+        )?;
+        self.check_with_profile(args, profile).await
+    }
+}
+
+impl CustomVerificationProvider {
+    /// Creates a provider for the verifier profile with the given name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+
+    /// Returns the resolved profile for the given [`VerifyArgs`].
+    fn profile(&self, config: &Config, args: &VerifyArgs) -> Result<VerifierConfig> {
+        self.resolve_profile(
+            config,
+            args.etherscan.chain.unwrap_or_default(),
+            args.etherscan.key.as_deref(),
+            args.verifier.verifier_url.as_deref(),
+        )
+    }
+
+    /// Looks up the profile in the config or among the built-in profiles and resolves it.
+    ///
+    /// The `--verifier-url` takes precedence over the configured url and the etherscan key is used
+    /// if the profile doesn't configure a key.
+    fn resolve_profile(
+        &self,
+        config: &Config,
+        chain: Chain,
+        key: Option<&str>,
+        verifier_url: Option<&str>,
+    ) -> Result<VerifierConfig> {
+        let profile = if let Some(profile) = config.verifiers.get(&self.name) {
+            profile.clone()
+        } else if let Some(profile) = builtin_profile(&self.name, chain) {
+            profile
+        } else {
+            eyre::bail!(
+                "Unknown verifier `{}`. Custom verifiers must be configured in the `[verifiers]` section of `foundry.toml`",
+                self.name
+            )
+        };
+
+        let mut profile = profile
+            .resolve()
+            .wrap_err_with(|| format!("Failed to resolve verifier profile `{}`", self.name))?;
+        if let Some(url) = verifier_url {
+            profile.url = url.to_string();
+        }
+        if profile.key.is_none() {
+            profile.key = key.filter(|key| !key.is_empty()).map(str::to_string);
+        }
+        if profile.url.is_empty() {
+            eyre::bail!(
+                "Verifier `{}` has no URL for chain {chain}, pass it with `--verifier-url`",
+                self.name
+            )
+        }
+        if (self.name == ETHERSCAN_V1 || self.name == ETHERSCAN_V2) && profile.key.is_none() {
+            eyre::bail!("ETHERSCAN_API_KEY must be set")
+        }
+        Ok(profile)
+    }
+
+    /// Checks the verification status with an already resolved profile.
+    async fn check_with_profile(
+        &self,
+        mut args: VerifyCheckArgs,
+        profile: VerifierConfig,
+    ) -> Result<()> {
+        if profile.api == VerifierApi::Sourcify {
+            args.verifier.verifier_url.get_or_insert(profile.url);
+            return SourcifyVerificationProvider::default().check(args).await
+        }
+        let client = VerifierClient::new(profile);
+        let retry = client.poll_retry(args.retry);
+        client.check(&args.id, retry).await.wrap_err("Checking verification result failed:")
+    }
+
+    /// Creates the fields of the verify request, named as the Etherscan API names them.
+    ///
+    /// Constructor arguments and via-IR are taken from the Etherscan request. Libraries are part of
+    /// the standard json input, but need to be sent as separate fields for flattened sources.
+    async fn prepare_request(
+        &self,
+        args: &VerifyArgs,
+        profile: &VerifierConfig,
+        config: Config,
+    ) -> Result<Vec<(String, Value)>> {
+        let mut libraries = config.libraries.clone();
+        libraries.extend(args.libraries.clone());
+        let libraries = Libraries::parse(&libraries)?;
+
+        let verify_args = EtherscanVerificationProvider::default()
+            .create_verify_request(args, Some(config))
+            .await?;
+
+        let mut fields = vec![
+            ("module".to_string(), "contract".into()),
+            ("action".to_string(), "verifysourcecode".into()),
+        ];
+        if profile.auth_header.is_none() {
+            if let Some(key) = &profile.key {
+                fields.push(("apikey".to_string(), key.clone().into()));
+            }
+        }
+        fields.extend(request_fields(&verify_args)?);
+
+        if verify_args.code_format == CodeFormat::SingleFile {
+            let libraries = libraries.libs.into_values().flatten();
+            for (i, (name, address)) in libraries.enumerate() {
+                fields.push((format!("libraryname{}", i + 1), name.into()));
+                fields.push((format!("libraryaddress{}", i + 1), address.into()));
+            }
+        }
+
+        Ok(fields)
+    }
+}
+
+/// Returns the built-in profile with the given name, if any.
+///
+/// `etherscan`, `blockscout` and `sourcify` select their dedicated providers, the built-in
+/// profiles of these explorers are named `etherscan-v1`, `blockscout-api` and `sourcify-api`.
+///
+/// The URL of the profile is empty if the chain has no explorer of that kind, it must then be
+/// passed with `--verifier-url`.
+pub fn builtin_profile(name: &str, chain: Chain) -> Option<VerifierConfig> {
+    let explorer_api = || etherscan_urls(chain).map(|(api_url, _)| api_url);
+    match name {
+        ETHERSCAN_V1 => Some(VerifierConfig::new(
+            VerifierApi::Etherscan,
+            explorer_api().filter(|url| !url.contains("blockscout")).unwrap_or_default(),
+        )),
+        ETHERSCAN_V2 => Some(VerifierConfig::new(
+            VerifierApi::Etherscan,
+            format!("{ETHERSCAN_V2_URL}?chainid={}", chain.id()),
+        )),
+        BLOCKSCOUT_API => Some(VerifierConfig::new(
+            VerifierApi::Blockscout,
+            explorer_api().filter(|url| url.contains("blockscout")).unwrap_or_default(),
+        )),
+        SOURCIFY_API => Some(VerifierConfig::new(VerifierApi::Sourcify, SOURCIFY_URL.to_string())),
+        _ => None,
+    }
+}
+
+/// Returns the args with the url of the profile as `--verifier-url` if none was provided.
+fn with_sourcify_url(mut args: VerifyArgs, profile: &VerifierConfig) -> VerifyArgs {
+    args.verifier.verifier_url.get_or_insert_with(|| profile.url.clone());
+    args
+}
+
+/// Returns all fields of the Etherscan request, with the JSON types they're serialized as.
+fn request_fields(verify_args: &VerifyContract) -> Result<Vec<(String, Value)>> {
+    let Value::Object(map) = serde_json::to_value(verify_args)? else {
+        eyre::bail!("Verify request is not an object")
+    };
+    Ok(map.into_iter().filter(|(_, value)| !value.is_null()).collect())
+}
+
+/// Returns the fields with their values as strings, for the encodings without types.
+fn string_fields(fields: &[(String, Value)]) -> Vec<(String, String)> {
+    fields
+        .iter()
+        .map(|(name, value)| match value {
+            Value::String(value) => (name.clone(), value.clone()),
+            value => (name.clone(), value.to_string()),
+        })
+        .collect()
+}
+
+/// The response of an Etherscan-like verifier API.
+#[derive(Debug, Deserialize)]
+struct VerifierResponse {
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    result: Value,
+}
+
+impl VerifierResponse {
+    /// Returns the result as string
+    fn result_str(&self) -> String {
+        match &self.result {
+            Value::String(result) => result.clone(),
+            result => result.to_string(),
+        }
+    }
+}
+
+/// Sends requests to a verifier according to its profile.
+#[derive(Clone, Debug)]
+struct VerifierClient {
+    profile: VerifierConfig,
+    client: reqwest::Client,
+}
+
+impl VerifierClient {
+    fn new(profile: VerifierConfig) -> Self {
+        Self { profile, client: reqwest::Client::new() }
+    }
+
+    /// Returns the retry used to poll the verification status, the profile's polling settings
+    /// take precedence over the given default.
+    fn poll_retry(&self, default: RetryArgs) -> Retry {
+        Retry::new(
+            self.profile.poll_retries.unwrap_or(default.retries),
+            Some(Duration::from_secs(self.profile.poll_interval.unwrap_or(default.delay as u64))),
+        )
+    }
+
+    /// Renames the fields as configured in the profile and drops the removed ones.
+    fn map_fields<T: Clone>(&self, fields: &[(String, T)]) -> Vec<(String, T)> {
+        fields
+            .iter()
+            .filter_map(|(name, value)| {
+                self.profile.field_name(name).map(|name| (name.to_string(), value.clone()))
+            })
+            .collect()
+    }
+
+    /// Adds the auth header, if configured.
+    fn authenticate(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match (&self.profile.auth_header, &self.profile.key) {
+            (Some(header), Some(key)) => request.header(header.as_str(), key.as_str()),
+            _ => request,
+        }
+    }
+
+    /// Submits the verification request.
+    ///
+    /// Returns the GUID to check the verification status with, or `None` if the contract is
+    /// already verified.
+    async fn submit(&self, fields: &[(String, Value)]) -> Result<Option<String>> {
+        let fields = self.map_fields(fields);
+        let request = self.client.post(&self.profile.url);
+        let request = match self.profile.encoding {
+            VerifierRequestEncoding::Form => request.form(&string_fields(&fields)),
+            VerifierRequestEncoding::Json => {
+                request.json(&fields.into_iter().collect::<serde_json::Map<_, _>>())
+            }
+            VerifierRequestEncoding::Multipart => {
+                let fields = string_fields(&fields);
+                let boundary = multipart_boundary(&fields);
+                request
+                    .header(
+                        reqwest::header::CONTENT_TYPE,
+                        format!("multipart/form-data; boundary={boundary}"),
+                    )
+                    .body(encode_multipart(&fields, &boundary))
+            }
+        };
+
+        let resp = self.send(request).await.wrap_err("Failed to submit contract verification")?;
+
+        if resp.status == "0" {
+            let result = resp.result_str();
+            if result == "Contract source code already verified" ||
+                result == "Smart-contract already verified."
+            {
+                return Ok(None)
+            }
+            if result.starts_with("Unable to locate ContractCode at") {
+                warn!("{result}");
+                return Err(eyre!("The verifier could not detect the deployment."))
+            }
+            eyre::bail!(
+                "Encountered an error verifying this contract:\nResponse: `{}`\nDetails: `{result}`",
+                resp.message
+            )
+        }
+
+        Ok(Some(resp.result_str()))
+    }
+
+    /// Polls the verification status of the given GUID until it's no longer pending.
+    async fn check(&self, guid: &str, retry: Retry) -> Result<()> {
+        let mut query = vec![
+            ("module".to_string(), "contract".to_string()),
+            ("action".to_string(), "checkverifystatus".to_string()),
+            ("guid".to_string(), guid.to_string()),
+        ];
+        if self.profile.auth_header.is_none() {
+            if let Some(key) = &self.profile.key {
+                query.push(("apikey".to_string(), key.clone()));
+            }
+        }
+        let query = self.map_fields(&query);
+
+        retry
+            .run_async(|| async {
+                let resp = self
+                    .send(self.client.get(&self.profile.url).query(&query))
+                    .await
+                    .wrap_err("Failed to request verification status")?;
+                let result = resp.result_str();
+
+                eprintln!(
+                    "Contract verification status:\nResponse: `{}`\nDetails: `{result}`",
+                    resp.message
+                );
+
+                if result.to_lowercase().contains("pending") {
+                    return Err(eyre!("Verification is still pending..."))
+                }
+
+                if result == "Already Verified" {
+                    println!("Contract source code already verified");
+                    return Ok(())
+                }
+
+                if resp.status == "0" {
+                    eyre::bail!("Contract failed to verify: {result}")
+                }
+
+                println!("Contract successfully verified");
+                Ok(())
+            })
+            .await
+    }
+
+    /// Sends the request and parses the response.
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<VerifierResponse> {
+        let response = self.authenticate(request).send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        trace!(target: "forge::verify", %status, %text, "Received verifier response");
+        if !status.is_success() {
+            eyre::bail!("Verifier responded with status code {status}\nDetails: {text}")
+        }
+        serde_json::from_str(&text).wrap_err_with(|| format!("Invalid verifier response: {text}"))
+    }
+}
+
+/// Returns a multipart boundary that doesn't appear in any of the field values.
+fn multipart_boundary(fields: &[(String, String)]) -> String {
+    (0..)
+        .map(|i| format!("----foundry-verify-{i}"))
+        .find(|boundary| fields.iter().all(|(_, value)| !value.contains(boundary.as_str())))
+        .unwrap()
+}
+
+/// Encodes the fields as `multipart/form-data` body.
+fn encode_multipart(fields: &[(String, String)], boundary: &str) -> String {
+    let mut body = String::new();
+    for (name, value) in fields {
+        let _ = write!(
+            body,
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+        );
+    }
+    let _ = write!(body, "--{boundary}--\r\n");
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::State,
+        http::{HeaderMap, Uri},
+        Json, Router,
+    };
+    use clap::Parser;
+    use std::sync::{Arc, Mutex};
+
+    /// A request received by the mocked verifier.
+    #[derive(Clone, Debug)]
+    struct Recorded {
+        uri: Uri,
+        headers: HeaderMap,
+        body: String,
+    }
+
+    type Requests = Arc<Mutex<Vec<Recorded>>>;
+
+    async fn record(
+        State(requests): State<Requests>,
+        uri: Uri,
+        headers: HeaderMap,
+        body: String,
+    ) -> Json<Value> {
+        let query = uri.query().unwrap_or_default().to_string();
+        requests.lock().unwrap().push(Recorded { uri: uri.clone(), headers, body });
+
+        let response = if uri.path().ends_with("check-by-addresses") {
+            serde_json::json!([{ "status": "perfect" }])
+        } else if query.contains("checkverifystatus") {
+            serde_json::json!({ "status": "1", "message": "OK", "result": "Pass - Verified" })
+        } else {
+            serde_json::json!({ "status": "1", "message": "OK", "result": "guid-1" })
+        };
+        Json(response)
+    }
+
+    /// Spawns a mocked verifier and returns its base url.
+    fn spawn_verifier() -> (String, Requests) {
+        let requests = Requests::default();
+        let app = Router::new().fallback(record).with_state(requests.clone());
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service());
+        tokio::spawn(server);
+        (format!("http://{addr}"), requests)
+    }
+
+    fn verify_args() -> VerifyContract {
+        VerifyContract::new(
+            "0xd8509bee9c9bf012282ad33aba0d87241baf5064".parse().unwrap(),
+            "src/Counter.sol:Counter".to_string(),
+            "{}".to_string(),
+            "v0.8.23+commit.f704f362".to_string(),
+        )
+        .constructor_arguments(Some(
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        ))
+        .code_format(CodeFormat::StandardJsonInput)
+        .via_ir(true)
+    }
+
+    fn fields() -> Vec<(String, Value)> {
+        let mut fields = vec![
+            ("module".to_string(), "contract".into()),
+            ("action".to_string(), "verifysourcecode".into()),
+            ("apikey".to_string(), "key".into()),
+        ];
+        fields.extend(request_fields(&verify_args()).unwrap());
+        fields
+    }
+
+    fn field<'a>(fields: &'a [(String, Value)], name: &str) -> Option<&'a Value> {
+        fields.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    #[test]
+    fn request_fields_contain_constructor_args_and_via_ir() {
+        let fields = fields();
+        assert_eq!(
+            field(&fields, "contractaddress").unwrap(),
+            "0xd8509bee9c9bf012282ad33aba0d87241baf5064"
+        );
+        assert_eq!(field(&fields, "codeformat").unwrap(), "solidity-standard-json-input");
+        assert_eq!(
+            field(&fields, "constructorArguements").unwrap(),
+            "0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(field(&fields, "viaIR"), Some(&Value::Bool(true)));
+
+        let fields = string_fields(&fields);
+        assert!(fields.contains(&("viaIR".to_string(), "true".to_string())));
+    }
+
+    #[test]
+    fn can_encode_multipart() {
+        let fields = vec![
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "----foundry-verify-0".to_string()),
+        ];
+        let boundary = multipart_boundary(&fields);
+        assert_eq!(boundary, "----foundry-verify-1");
+        assert_eq!(
+            encode_multipart(&fields, &boundary),
+            "------foundry-verify-1\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n1\r\n------foundry-verify-1\r\nContent-Disposition: form-data; name=\"b\"\r\n\r\n----foundry-verify-0\r\n------foundry-verify-1--\r\n"
+        );
+    }
+
+    #[test]
+    fn can_lookup_builtin_profiles() {
+        let profile = builtin_profile(ETHERSCAN_V2, Chain::from_id(10)).unwrap();
+        assert_eq!(profile.api, VerifierApi::Etherscan);
+        assert_eq!(profile.url, "https://api.etherscan.io/v2/api?chainid=10");
+        assert!(builtin_profile("mycorp", Chain::from_id(10)).is_none());
+
+        let profile = builtin_profile(ETHERSCAN_V1, Chain::mainnet()).unwrap();
+        assert_eq!(profile.url, "https://api.etherscan.io/api");
+        let profile = builtin_profile(SOURCIFY_API, Chain::mainnet()).unwrap();
+        assert_eq!(profile.api, VerifierApi::Sourcify);
+        assert_eq!(profile.url, SOURCIFY_URL);
+        // the explorer of mainnet is not a Blockscout instance
+        let profile = builtin_profile(BLOCKSCOUT_API, Chain::mainnet()).unwrap();
+        assert_eq!(profile.api, VerifierApi::Blockscout);
+        assert!(profile.url.is_empty());
+
+        let provider = CustomVerificationProvider::new(ETHERSCAN_V2);
+        let config = Config::default();
+        assert!(provider.resolve_profile(&config, Chain::from_id(10), None, None).is_err());
+        let profile =
+            provider.resolve_profile(&config, Chain::from_id(10), Some("key"), None).unwrap();
+        assert_eq!(profile.key.as_deref(), Some("key"));
+
+        let provider = CustomVerificationProvider::new(BLOCKSCOUT_API);
+        assert!(provider.resolve_profile(&config, Chain::from_id(10), None, None).is_err());
+        let profile = provider
+            .resolve_profile(&config, Chain::from_id(10), None, Some("https://blockscout.net/api"))
+            .unwrap();
+        assert_eq!(profile.url, "https://blockscout.net/api");
+
+        let provider = CustomVerificationProvider::new("mycorp");
+        assert!(provider.resolve_profile(&config, Chain::from_id(10), None, None).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_verify_with_etherscan_v2_profile() {
+        let (url, requests) = spawn_verifier();
+        let mut profile = builtin_profile(ETHERSCAN_V2, Chain::from_id(10)).unwrap();
+        profile.url = profile.url.replace(ETHERSCAN_V2_URL, &format!("{url}/v2/api"));
+        profile.key = Some("key".to_string());
+        let client = VerifierClient::new(profile);
+
+        let guid = client.submit(&fields()).await.unwrap();
+        assert_eq!(guid.as_deref(), Some("guid-1"));
+        client.check("guid-1", Retry::new(0, None)).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let submit = &requests[0];
+        assert_eq!(submit.uri.query(), Some("chainid=10"));
+        assert_eq!(
+            submit.headers["content-type"].to_str().unwrap(),
+            "application/x-www-form-urlencoded"
+        );
+        assert!(submit.body.starts_with("module=contract&action=verifysourcecode&apikey=key&"));
+        assert!(submit.body.contains("viaIR=true"));
+
+        let check = requests[1].uri.query().unwrap();
+        assert!(check.starts_with("chainid=10&"));
+        assert!(check.contains("action=checkverifystatus&guid=guid-1&apikey=key"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_verify_with_blockscout_multipart_profile() {
+        let (url, requests) = spawn_verifier();
+        let mut profile = VerifierConfig::new(VerifierApi::Blockscout, format!("{url}/api"));
+        profile.encoding = VerifierRequestEncoding::Multipart;
+        profile.auth_header = Some("X-Api-Key".to_string());
+        profile.key = Some("secret".to_string());
+        let client = VerifierClient::new(profile);
+
+        client.submit(&fields()).await.unwrap();
+        client.check("guid-1", Retry::new(0, None)).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        let submit = &requests[0];
+        assert_eq!(submit.headers["x-api-key"].to_str().unwrap(), "secret");
+        assert_eq!(
+            submit.headers["content-type"].to_str().unwrap(),
+            "multipart/form-data; boundary=----foundry-verify-0"
+        );
+        assert!(submit.body.contains("name=\"constructorArguements\"\r\n\r\n0000"));
+        assert!(submit.body.ends_with("------foundry-verify-0--\r\n"));
+
+        let check = &requests[1];
+        assert_eq!(check.headers["x-api-key"].to_str().unwrap(), "secret");
+        assert!(!check.uri.query().unwrap().contains("apikey"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_verify_with_custom_json_profile() {
+        let (url, requests) = spawn_verifier();
+        let mut profile = VerifierConfig::new(VerifierApi::Etherscan, format!("{url}/api"));
+        profile.encoding = VerifierRequestEncoding::Json;
+        profile.fields = [
+            ("contractaddress".to_string(), "address".to_string()),
+            ("guid".to_string(), "id".to_string()),
+            ("module".to_string(), String::new()),
+        ]
+        .into_iter()
+        .collect();
+        let client = VerifierClient::new(profile);
+
+        client.submit(&fields()).await.unwrap();
+        client.check("guid-1", Retry::new(0, None)).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        let submit = &requests[0];
+        assert_eq!(submit.headers["content-type"].to_str().unwrap(), "application/json");
+        let mut body: Value = serde_json::from_str(&submit.body).unwrap();
+        assert_eq!(body["address"], "0xd8509bee9c9bf012282ad33aba0d87241baf5064");
+        assert_eq!(body["viaIR"], true);
+        assert!(body.get("contractaddress").is_none());
+        assert!(body.get("module").is_none());
+
+        // undoing the renames gives back the request with the same JSON types
+        let body = body.as_object_mut().unwrap();
+        let address = body.remove("address").unwrap();
+        body.insert("contractaddress".to_string(), address);
+        body.remove("action");
+        body.remove("apikey");
+        let mut request = serde_json::to_value(verify_args()).unwrap();
+        request.as_object_mut().unwrap().retain(|_, value| !value.is_null());
+        assert_eq!(Value::Object(body.clone()), request);
+
+        let check = requests[1].uri.query().unwrap();
+        assert_eq!(check, "action=checkverifystatus&id=guid-1");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_check_with_sourcify_profile() {
+        let (url, requests) = spawn_verifier();
+        let profile = VerifierConfig::new(VerifierApi::Sourcify, format!("{url}/"));
+        let args = VerifyCheckArgs::parse_from([
+            "foundry-cli",
+            "0xd8509bee9c9bf012282ad33aba0d87241baf5064",
+            "--verifier",
+            "mycorp",
+            "--chain",
+            "10",
+        ]);
+
+        CustomVerificationProvider::new("mycorp").check_with_profile(args, profile).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].uri.path(), "/check-by-addresses");
+        assert_eq!(
+            requests[0].uri.query(),
+            Some("addresses=0xd8509bee9c9bf012282ad33aba0d87241baf5064&chainIds=10")
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use super::{provider::VerificationProvider, VerifyArgs, VerifyCheckArgs};
 use crate::cmd::retry::RETRY_CHECK_ON_VERIFY;
-use alloy_json_abi::Function;
+use alloy_dyn_abi::JsonAbiExt;
 use eyre::{eyre, Context, Result};
 use foundry_block_explorers::{
     errors::EtherscanError,
@@ -8,10 +8,10 @@ use foundry_block_explorers::{
     verify::{CodeFormat, VerifyContract},
     Client,
 };
-use foundry_cli::utils::{get_cached_entry_by_name, read_constructor_args_file, LoadConfig};
-use foundry_common::{abi::encode_function_args, retry::Retry};
+use foundry_cli::utils::{get_cached_entry_by_name, read_constructor_args, LoadConfig};
+use foundry_common::{etherscan::EtherscanPool, retry::Retry};
 use foundry_compilers::{artifacts::CompactContract, cache::CacheEntry, Project, Solc};
-use foundry_config::{Chain, Config, SolcReq};
+use foundry_config::{chains::etherscan_urls, Chain, Config, ResolvedEtherscanConfig, SolcReq};
 use futures::FutureExt;
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -55,11 +55,9 @@ impl VerificationProvider for EtherscanVerificationProvider {
     }
 
     async fn verify(&mut self, args: VerifyArgs) -> Result<()> {
-        let (etherscan, verify_args) = self.prepare_request(&args).await?;
+        let (etherscan, pool, verify_args) = self.prepare_request(&args).await?;
 
-        if !args.skip_is_verified_check &&
-            self.is_contract_verified(&etherscan, &verify_args).await?
-        {
+        if !args.skip_is_verified_check && self.is_contract_verified(&pool, &verify_args).await? {
             println!(
                 "\nContract [{}] {:?} is already verified. Skipping verification.",
                 verify_args.contract_name,
@@ -224,31 +222,39 @@ impl EtherscanVerificationProvider {
     }
 
     /// Configures the API request to the etherscan API using the given [`VerifyArgs`].
-    async fn prepare_request(&mut self, args: &VerifyArgs) -> Result<(Client, VerifyContract)> {
+    ///
+    /// Returns the client the verification is submitted with, and the pool of all the API keys
+    /// of the chain the other requests are spread across.
+    async fn prepare_request(
+        &mut self,
+        args: &VerifyArgs,
+    ) -> Result<(Client, EtherscanPool, VerifyContract)> {
         let config = args.try_load_config_emit_warnings()?;
-        let etherscan = self.client(
+        let etherscan_config = self.etherscan_config(
             args.etherscan.chain.unwrap_or_default(),
             args.verifier.verifier_url.as_deref(),
             args.etherscan.key.as_deref(),
             &config,
         )?;
+        let etherscan = Self::client_of(&etherscan_config)?;
+        let pool = EtherscanPool::new(etherscan_config)?;
         let verify_args = self.create_verify_request(args, Some(config)).await?;
 
-        Ok((etherscan, verify_args))
+        Ok((etherscan, pool, verify_args))
     }
 
     /// Queries the etherscan API to verify if the contract is already verified.
     async fn is_contract_verified(
         &self,
-        etherscan: &Client,
+        pool: &EtherscanPool,
         verify_contract: &VerifyContract,
     ) -> Result<bool> {
-        let check = etherscan.contract_abi(verify_contract.address).await;
+        let check = pool.contract_abi(verify_contract.address).await;
 
         if let Err(err) = check {
-            match err {
+            match *err {
                 EtherscanError::ContractCodeNotVerified(_) => return Ok(false),
-                error => return Err(error.into()),
+                _ => return Err(err.into()),
             }
         }
 
@@ -263,35 +269,64 @@ impl EtherscanVerificationProvider {
         etherscan_key: Option<&str>,
         config: &Config,
     ) -> Result<Client> {
+        Self::client_of(&self.etherscan_config(chain, verifier_url, etherscan_key, config)?)
+    }
+
+    /// Creates a client of the API with the first key of the config.
+    fn client_of(etherscan_config: &ResolvedEtherscanConfig) -> Result<Client> {
+        let api_url = etherscan_config.api_url.as_str();
+        Client::builder()
+            .with_api_url(api_url)?
+            .with_url(etherscan_config.browser_url.as_deref().unwrap_or(api_url))?
+            .with_api_key(etherscan_config.api_key())
+            .build()
+            .wrap_err("Failed to create etherscan client")
+    }
+
+    /// Resolves the API of the verifier URL or of the Etherscan explorer of the chain, with the
+    /// API keys of the chain.
+    ///
+    /// The key can hold several comma separated keys, e.g. `ETHERSCAN_API_KEY=KEY1,KEY2`.
+    fn etherscan_config(
+        &self,
+        chain: Chain,
+        verifier_url: Option<&str>,
+        etherscan_key: Option<&str>,
+        config: &Config,
+    ) -> Result<ResolvedEtherscanConfig> {
         let etherscan_config = config.get_etherscan_config_with_chain(Some(chain))?;
 
-        let etherscan_api_url = verifier_url
+        // includes the endpoints of the custom chains of `~/.foundry/chains.toml`
+        let chain_urls = etherscan_urls(chain);
+        let api_url = verifier_url
             .or_else(|| etherscan_config.as_ref().map(|c| c.api_url.as_str()))
-            .map(str::to_owned);
-
-        let api_url = etherscan_api_url.as_deref();
-        let base_url = etherscan_config
+            .or_else(|| chain_urls.as_ref().map(|(api_url, _)| api_url.as_str()))
+            .ok_or_else(|| eyre!("No Etherscan API found for {chain}, set `--verifier-url`"))?;
+        // we don't want any trailing slashes because this can cause cloudflare issues: <https://github.com/foundry-rs/foundry/pull/6079>
+        let api_url = api_url.trim_end_matches('/').to_string();
+        let browser_url = etherscan_config
             .as_ref()
-            .and_then(|c| c.browser_url.as_deref())
-            .or_else(|| chain.etherscan_urls().map(|(_, url)| url));
-
-        let etherscan_key =
-            etherscan_key.or_else(|| etherscan_config.as_ref().map(|c| c.key.as_str()));
-
-        let mut builder = Client::builder();
-
-        builder = if let Some(api_url) = api_url {
-            // we don't want any trailing slashes because this can cause cloudflare issues: <https://github.com/foundry-rs/foundry/pull/6079>
-            let api_url = api_url.trim_end_matches('/');
-            builder.with_api_url(api_url)?.with_url(base_url.unwrap_or(api_url))?
-        } else {
-            builder.chain(chain)?
+            .and_then(|c| c.browser_url.clone())
+            .or_else(|| chain_urls.map(|(_, url)| url));
+
+        // the sources of another verifier aren't cached with the ones of the chain's explorer
+        let chain = verifier_url.is_none().then_some(chain);
+        let config = ResolvedEtherscanConfig {
+            api_url,
+            browser_url,
+            key: String::new(),
+            keys: vec![],
+            chain,
         };
-
-        builder
-            .with_api_key(etherscan_key.unwrap_or_default())
-            .build()
-            .wrap_err("Failed to create etherscan client")
+        Ok(match (etherscan_key, etherscan_config) {
+            (Some(key), _) => config.with_api_keys(key),
+            (None, Some(etherscan_config)) => ResolvedEtherscanConfig {
+                key: etherscan_config.key,
+                keys: etherscan_config.keys,
+                ..config
+            },
+            (None, None) => config,
+        })
     }
 
     /// Creates the `VerifyContract` etherscan request in order to verify the contract
@@ -311,6 +346,9 @@ impl EtherscanVerificationProvider {
         let project = config.project()?;
 
         let contract_path = self.contract_path(args, &project)?;
+        // the contract is verified with the settings it was compiled with
+        let config = config.for_source(&contract_path);
+        let project = config.project()?;
         let compiler_version = self.compiler_version(args, &config, &project)?;
         let (source, contract_name, code_format) =
             self.source_provider(args).source(args, &project, &contract_path, &compiler_version)?;
@@ -426,19 +464,10 @@ impl EtherscanVerificationProvider {
             let constructor = abi
                 .constructor()
                 .ok_or_else(|| eyre!("Can't retrieve constructor info from artifact ABI."))?;
-            #[allow(deprecated)]
-            let func = Function {
-                name: "constructor".to_string(),
-                inputs: constructor.inputs.clone(),
-                outputs: vec![],
-                state_mutability: alloy_json_abi::StateMutability::NonPayable,
-            };
-            let encoded_args = encode_function_args(
-                &func,
-                read_constructor_args_file(constructor_args_path.to_path_buf())?,
-            )?;
-            let encoded_args = hex::encode(encoded_args);
-            return Ok(Some(encoded_args[8..].into()))
+            // same as `forge create --constructor-args-path`
+            let args = read_constructor_args(constructor_args_path, constructor)?;
+            let encoded_args = constructor.abi_encode_input(&args)?;
+            return Ok(Some(hex::encode(encoded_args)))
         }
 
         Ok(args.constructor_args.clone())
@@ -17,11 +17,19 @@ use provider::VerificationProvider;
 
 mod sourcify;
 
+mod custom;
+
+pub mod bytecode;
+
 /// Verification provider arguments
 #[derive(Clone, Debug, Parser)]
 pub struct VerifierArgs {
     /// The contract verification provider to use.
-    #[clap(long, help_heading = "Verifier options", default_value = "etherscan", value_enum)]
+    ///
+    /// One of `etherscan`, `sourcify`, `blockscout`, the built-in profiles `etherscan-v1`,
+    /// `etherscan-v2`, `blockscout-api` and `sourcify-api`, or the name of a verifier profile
+    /// configured in the `[verifiers]` section of `foundry.toml`.
+    #[clap(long, help_heading = "Verifier options", default_value = "etherscan")]
     pub verifier: VerificationProviderType,
 
     /// The verifier URL, if using a custom provider
@@ -54,6 +62,8 @@ pub struct VerifyArgs {
     pub constructor_args: Option<String>,
 
     /// The path to a file containing the constructor arguments.
+    ///
+    /// Read the same way as `forge create --constructor-args-path`.
     #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
     pub constructor_args_path: Option<PathBuf>,
 
@@ -146,7 +156,8 @@ impl VerifyArgs {
         let config = self.load_config_emit_warnings();
         let chain = config.chain.unwrap_or_default();
         self.etherscan.chain = Some(chain);
-        self.etherscan.key = config.get_etherscan_config_with_chain(Some(chain))?.map(|c| c.key);
+        self.etherscan.key =
+            config.get_etherscan_config_with_chain(Some(chain))?.map(|c| c.api_key().to_string());
 
         if self.show_standard_json_input {
             let args =
@@ -256,4 +267,25 @@ mod tests {
         ]);
         assert!(args.via_ir);
     }
+
+    #[test]
+    fn can_parse_custom_verifier() {
+        let args: VerifyArgs = VerifyArgs::parse_from([
+            "foundry-cli",
+            "0x0000000000000000000000000000000000000000",
+            "src/Domains.sol:Domains",
+            "--verifier",
+            "mycorp",
+        ]);
+        assert_eq!(args.verifier.verifier, VerificationProviderType::Custom("mycorp".to_string()));
+
+        let args: VerifyArgs = VerifyArgs::parse_from([
+            "foundry-cli",
+            "0x0000000000000000000000000000000000000000",
+            "src/Domains.sol:Domains",
+            "--verifier",
+            "b",
+        ]);
+        assert_eq!(args.verifier.verifier, VerificationProviderType::Blockscout);
+    }
 }
@@ -1,6 +1,6 @@
 use super::{
-    etherscan::EtherscanVerificationProvider, sourcify::SourcifyVerificationProvider, VerifyArgs,
-    VerifyCheckArgs,
+    custom::CustomVerificationProvider, etherscan::EtherscanVerificationProvider,
+    sourcify::SourcifyVerificationProvider, VerifyArgs, VerifyCheckArgs,
 };
 use async_trait::async_trait;
 use eyre::Result;
@@ -33,7 +33,8 @@ impl FromStr for VerificationProviderType {
             "e" | "etherscan" => Ok(VerificationProviderType::Etherscan),
             "s" | "sourcify" => Ok(VerificationProviderType::Sourcify),
             "b" | "blockscout" => Ok(VerificationProviderType::Blockscout),
-            _ => Err(format!("Unknown provider: {s}")),
+            "" => Err("Verifier name must not be empty".to_string()),
+            s => Ok(VerificationProviderType::Custom(s.to_string())),
         }
     }
 }
@@ -50,17 +51,23 @@ impl fmt::Display for VerificationProviderType {
             VerificationProviderType::Blockscout => {
                 write!(f, "blockscout")?;
             }
+            VerificationProviderType::Custom(name) => {
+                write!(f, "{name}")?;
+            }
         };
         Ok(())
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum VerificationProviderType {
     #[default]
     Etherscan,
     Sourcify,
     Blockscout,
+    /// A verifier profile from the `[verifiers]` config section, or a built-in profile such as
+    /// `etherscan-v2`.
+    Custom(String),
 }
 
 impl VerificationProviderType {
@@ -79,6 +86,9 @@ impl VerificationProviderType {
             VerificationProviderType::Blockscout => {
                 Ok(Box::<EtherscanVerificationProvider>::default())
             }
+            VerificationProviderType::Custom(name) => {
+                Ok(Box::new(CustomVerificationProvider::new(name.clone())))
+            }
         }
     }
 }
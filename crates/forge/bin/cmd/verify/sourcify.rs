@@ -99,6 +99,10 @@ impl SourcifyVerificationProvider {
     fn prepare_request(&self, args: &VerifyArgs) -> Result<SourcifyVerifyRequest> {
         let mut config = args.try_load_config_emit_warnings()?;
         config.libraries.extend(args.libraries.clone());
+        // the contract is compiled with the settings of its compilation override, if any
+        if let Some(path) = &args.contract.path {
+            config = config.for_source(&config.__root.0.join(path));
+        }
 
         let project = config.project()?;
 
@@ -19,6 +19,9 @@ fn main() -> Result<()> {
     utils::enable_paint();
 
     let opts = Opts::parse();
+    if let Some(threads) = opts.threads {
+        init_threads(threads)?;
+    }
     match opts.sub {
         Subcommands::Test(cmd) => {
             if cmd.is_watch() {
@@ -48,9 +51,18 @@ fn main() -> Result<()> {
         Subcommands::Debug(cmd) => utils::block_on(cmd.run()),
         Subcommands::VerifyContract(args) => utils::block_on(args.run()),
         Subcommands::VerifyCheck(args) => utils::block_on(args.run()),
+        Subcommands::VerifyBytecode(args) => {
+            let outcome = utils::block_on(args.run())?;
+            let code = outcome.exit_code();
+            if code != 0 {
+                std::process::exit(code);
+            }
+            Ok(())
+        }
         Subcommands::Cache(cmd) => match cmd.sub {
             CacheSubcommands::Clean(cmd) => cmd.run(),
             CacheSubcommands::Ls(cmd) => cmd.run(),
+            CacheSubcommands::ImportSignatures(cmd) => cmd.run(),
         },
         Subcommands::Create(cmd) => utils::block_on(cmd.run()),
         Subcommands::Update(cmd) => cmd.run(),
@@ -58,6 +70,7 @@ fn main() -> Result<()> {
         Subcommands::Remove(cmd) => cmd.run(),
         Subcommands::Remappings(cmd) => cmd.run(),
         Subcommands::Init(cmd) => cmd.run(),
+        Subcommands::Clone(cmd) => utils::block_on(cmd.run()),
         Subcommands::Completions { shell } => {
             generate(shell, &mut Opts::command(), "forge", &mut std::io::stdout());
             Ok(())
@@ -84,6 +97,7 @@ fn main() -> Result<()> {
             }
         }
         Subcommands::Fmt(cmd) => cmd.run(),
+        Subcommands::Lint(cmd) => cmd.run(),
         Subcommands::Config(cmd) => cmd.run(),
         Subcommands::Flatten(cmd) => cmd.run(),
         Subcommands::Inspect(cmd) => cmd.run(),
@@ -101,5 +115,15 @@ fn main() -> Result<()> {
         Subcommands::Generate(cmd) => match cmd.sub {
             GenerateSubcommands::Test(cmd) => cmd.run(),
         },
+        Subcommands::Eip712(cmd) => cmd.run(),
     }
 }
+
+/// Sizes the global thread pool and passes the number of threads to the config as
+/// `FOUNDRY_THREADS`, so that the compiler and the test runner honor it as well.
+fn init_threads(threads: usize) -> Result<()> {
+    let threads = threads.max(1);
+    std::env::set_var("FOUNDRY_THREADS", threads.to_string());
+    rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()?;
+    Ok(())
+}
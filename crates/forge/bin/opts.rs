@@ -2,22 +2,25 @@ use crate::cmd::{
     bind::BindArgs,
     build::BuildArgs,
     cache::CacheArgs,
+    clone::CloneArgs,
     config, coverage,
     create::CreateArgs,
     debug::DebugArgs,
     doc::DocArgs,
+    eip712::Eip712Args,
     flatten,
     fmt::FmtArgs,
     geiger, generate,
     init::InitArgs,
     inspect,
     install::InstallArgs,
+    lint,
     remappings::RemappingArgs,
     remove::RemoveArgs,
     script::ScriptArgs,
     selectors::SelectorsSubcommands,
     snapshot, test, tree, update,
-    verify::{VerifyArgs, VerifyCheckArgs},
+    verify::{bytecode::VerifyBytecodeArgs, VerifyArgs, VerifyCheckArgs},
 };
 use clap::{Parser, Subcommand, ValueHint};
 use std::path::PathBuf;
@@ -40,6 +43,13 @@ const VERSION_MESSAGE: &str = concat!(
     next_display_order = None,
 )]
 pub struct Opts {
+    /// The number of threads to use for compiling and running tests.
+    ///
+    /// Overrides the `threads` of the config, all available parallelism is used if neither is
+    /// set.
+    #[clap(long, global = true, env = "FOUNDRY_THREADS", value_name = "THREADS")]
+    pub threads: Option<usize>,
+
     #[clap(subcommand)]
     pub sub: Subcommands,
 }
@@ -97,6 +107,12 @@ pub enum Subcommands {
     #[clap(visible_alias = "vc")]
     VerifyCheck(VerifyCheckArgs),
 
+    /// Check that the bytecode deployed at an address matches the local build of a contract.
+    ///
+    /// Exits with 0 on a full match, 2 if only the metadata hash differs and 3 on a mismatch.
+    #[clap(visible_alias = "vb")]
+    VerifyBytecode(VerifyBytecodeArgs),
+
     /// Deploy a smart contract.
     #[clap(visible_alias = "c")]
     Create(CreateArgs),
@@ -104,6 +120,9 @@ pub enum Subcommands {
     /// Create a new Forge project.
     Init(InitArgs),
 
+    /// Clone a verified contract from Etherscan into a new Forge project.
+    Clone(CloneArgs),
+
     /// Generate shell completions script.
     #[clap(visible_alias = "com")]
     Completions {
@@ -144,6 +163,9 @@ pub enum Subcommands {
     /// Format Solidity source files.
     Fmt(FmtArgs),
 
+    /// Lint Solidity source files with static checks.
+    Lint(lint::LintArgs),
+
     /// Get specialized information about a smart contract.
     #[clap(visible_alias = "in")]
     Inspect(inspect::InspectArgs),
@@ -167,4 +189,7 @@ pub enum Subcommands {
 
     /// Generate scaffold files.
     Generate(generate::GenerateArgs),
+
+    /// Generate the EIP-712 encoded types and typehashes of Solidity structs.
+    Eip712(Eip712Args),
 }
@@ -2,7 +2,8 @@
 
 use comfy_table::{presets::ASCII_MARKDOWN, Attribute, Cell, Color, Row, Table};
 use evm_disassembler::disassemble_bytes;
-use foundry_common::fs;
+use foundry_common::{fs, glob::GlobMatcher};
+use foundry_config::CoverageConfig;
 pub use foundry_evm::coverage::*;
 use std::{
     collections::{hash_map, HashMap},
@@ -15,6 +16,50 @@ pub trait CoverageReporter {
     fn report(self, report: &CoverageReport) -> eyre::Result<()>;
 }
 
+/// Decides which source files and contracts are excluded from coverage reports, per the
+/// `[coverage]` config.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageFilter {
+    exclude: Vec<GlobMatcher>,
+    include: Vec<GlobMatcher>,
+    exclude_contracts: Vec<GlobMatcher>,
+}
+
+impl CoverageFilter {
+    /// Compiles the globs of the config.
+    pub fn new(config: &CoverageConfig) -> eyre::Result<Self> {
+        fn globs(patterns: &[String]) -> eyre::Result<Vec<GlobMatcher>> {
+            patterns
+                .iter()
+                .map(|pattern| {
+                    pattern.parse().map_err(|err| {
+                        eyre::eyre!("Invalid glob `{pattern}` in the coverage config: {err}")
+                    })
+                })
+                .collect()
+        }
+        Ok(Self {
+            exclude: globs(&config.exclude)?,
+            include: globs(&config.include)?,
+            exclude_contracts: globs(&config.exclude_contracts)?,
+        })
+    }
+
+    /// Returns whether nothing is excluded.
+    pub fn is_empty(&self) -> bool {
+        self.exclude.is_empty() && self.exclude_contracts.is_empty()
+    }
+
+    /// Returns whether the contract of the source file at `path` is excluded.
+    ///
+    /// A path that matches a glob of `include` is only excluded by the name of the contract.
+    pub fn is_excluded(&self, path: &str, contract_name: &str) -> bool {
+        let path_excluded = self.exclude.iter().any(|glob| glob.is_match(path)) &&
+            !self.include.iter().any(|glob| glob.is_match(path));
+        path_excluded || self.exclude_contracts.iter().any(|glob| glob.is_match(contract_name))
+    }
+}
+
 /// A simple summary reporter that prints the coverage results in a table.
 pub struct SummaryReporter {
     /// The summary table.
@@ -158,8 +203,14 @@ impl CoverageReporter for DebugReporter {
         }
 
         for (contract_id, anchors) in &report.anchors {
+            if report.excluded_contracts.contains(contract_id) {
+                continue
+            }
             println!("Anchors for {contract_id}:");
-            anchors.iter().for_each(|anchor| {
+            for anchor in anchors {
+                if report.is_excluded(&contract_id.version, anchor.item_id) {
+                    continue
+                }
                 println!("- {anchor}");
                 println!(
                     "  - Refers to item: {}",
@@ -169,7 +220,7 @@ impl CoverageReporter for DebugReporter {
                         .and_then(|items| items.get(anchor.item_id))
                         .map_or("None".to_owned(), |item| item.to_string())
                 );
-            });
+            }
             println!();
         }
 
@@ -196,6 +247,9 @@ impl CoverageReporter for BytecodeReporter {
         let mut line_number_cache = LineNumberCache::new(self.root.clone());
 
         for (contract_id, hits) in &report.bytecode_hits {
+            if report.excluded_contracts.contains(contract_id) {
+                continue
+            }
             let ops = disassemble_bytes(hits.bytecode.to_vec())?;
             let mut formatted = String::new();
 
@@ -0,0 +1,742 @@
+//! EIP-712 encoding of Solidity struct types, see `forge eip712`.
+//!
+//! See <https://eips.ethereum.org/EIPS/eip-712#definition-of-encodetype>
+
+use alloy_primitives::{keccak256, B256};
+use eyre::{bail, eyre, Result};
+use serde::Serialize;
+use solang_parser::pt::{
+    ContractPart, Expression, Import, ImportPath, SourceUnitPart, Type, VariableDeclaration,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    fmt::Write,
+};
+
+/// The name of the library generated by [Eip712Structs::library].
+pub const LIBRARY_NAME: &str = "EIP712Hashes";
+
+/// The EIP-712 type of a struct member.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Eip712Type {
+    /// A type encoded as its value, e.g. `address` or `uint256`
+    Atomic(String),
+    /// `bytes` or `string`, encoded as the hash of their contents
+    Dynamic(String),
+    /// A struct, encoded as its `hashStruct`
+    Struct {
+        /// The name of the struct
+        name: String,
+        /// The Solidity path of the struct, `<contract>.<name>` for structs declared in contracts
+        path: String,
+    },
+    /// A fixed or dynamically sized array, encoded as the hash of the encoding of its elements
+    Array(Box<Eip712Type>, Option<usize>),
+}
+
+impl Eip712Type {
+    /// Returns the struct of the type or of the elements of the array.
+    fn referenced_struct(&self) -> Option<(&str, &str)> {
+        match self {
+            Self::Struct { name, path } => Some((name, path)),
+            Self::Array(ty, _) => ty.referenced_struct(),
+            _ => None,
+        }
+    }
+
+    /// Returns the Solidity type, which refers to structs by their path.
+    fn sol_type(&self) -> String {
+        match self {
+            Self::Atomic(ty) | Self::Dynamic(ty) => ty.clone(),
+            Self::Struct { path, .. } => path.clone(),
+            Self::Array(ty, len) => format!("{}[{}]", ty.sol_type(), DisplayLen(*len)),
+        }
+    }
+
+    /// Returns an identifier for the type, used for the names of the generated array helpers.
+    fn mangled(&self) -> String {
+        match self {
+            Self::Atomic(ty) | Self::Dynamic(ty) => {
+                let mut chars = ty.chars();
+                chars.next().map(|c| c.to_ascii_uppercase()).into_iter().chain(chars).collect()
+            }
+            Self::Struct { path, .. } => path.replace('.', ""),
+            Self::Array(ty, len) => format!("{}Array{}", ty.mangled(), DisplayLen(*len)),
+        }
+    }
+}
+
+impl fmt::Display for Eip712Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Atomic(ty) | Self::Dynamic(ty) => f.write_str(ty),
+            Self::Struct { name, .. } => f.write_str(name),
+            Self::Array(ty, len) => write!(f, "{ty}[{}]", DisplayLen(*len)),
+        }
+    }
+}
+
+struct DisplayLen(Option<usize>);
+
+impl fmt::Display for DisplayLen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(len) => write!(f, "{len}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The encoded type and typehash of a struct.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip712Encoding {
+    pub name: String,
+    pub encode_type: String,
+    pub type_hash: B256,
+}
+
+/// The declarations of a source file, see [Eip712Structs::add_source].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParsedSource {
+    /// The paths of the declared structs, in declaration order
+    pub structs: Vec<String>,
+    /// The imported files, as written in the import directives
+    pub imports: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+enum Declaration {
+    Struct(Vec<VariableDeclaration>),
+    Enum,
+    UserType(Expression),
+}
+
+/// The struct, enum and user defined value type declarations of a set of sources.
+#[derive(Clone, Debug, Default)]
+pub struct Eip712Structs {
+    /// `path -> (contract, declaration)`
+    declarations: BTreeMap<String, (Option<String>, Declaration)>,
+    /// The paths which are declared more than once
+    duplicates: BTreeSet<String>,
+    /// The names of all contracts, interfaces and libraries, encoded as `address`
+    contracts: BTreeSet<String>,
+}
+
+impl Eip712Structs {
+    /// Parses the source and adds its declarations.
+    pub fn add_source(&mut self, src: &str) -> Result<ParsedSource> {
+        let (unit, _) = solang_parser::parse(src, 0).map_err(|diagnostics| {
+            let messages: Vec<_> = diagnostics.into_iter().map(|d| d.message).collect();
+            eyre!("failed to parse source: {}", messages.join(", "))
+        })?;
+
+        let mut parsed = ParsedSource::default();
+        for part in unit.0 {
+            let (name, declaration) = match part {
+                SourceUnitPart::ContractDefinition(contract) => {
+                    let Some(contract_name) = contract.name else { continue };
+                    self.contracts.insert(contract_name.name.clone());
+                    for part in contract.parts {
+                        let (name, declaration) = match part {
+                            ContractPart::StructDefinition(def) => match def.name {
+                                Some(name) => (name.name, Declaration::Struct(def.fields)),
+                                None => continue,
+                            },
+                            ContractPart::EnumDefinition(def) => match def.name {
+                                Some(name) => (name.name, Declaration::Enum),
+                                None => continue,
+                            },
+                            ContractPart::TypeDefinition(def) => {
+                                (def.name.name, Declaration::UserType(def.ty))
+                            }
+                            _ => continue,
+                        };
+                        let path = format!("{}.{name}", contract_name.name);
+                        self.declare(&mut parsed, path, Some(&contract_name.name), declaration);
+                    }
+                    continue
+                }
+                SourceUnitPart::StructDefinition(def) => match def.name {
+                    Some(name) => (name.name, Declaration::Struct(def.fields)),
+                    None => continue,
+                },
+                SourceUnitPart::EnumDefinition(def) => match def.name {
+                    Some(name) => (name.name, Declaration::Enum),
+                    None => continue,
+                },
+                SourceUnitPart::TypeDefinition(def) => {
+                    (def.name.name, Declaration::UserType(def.ty))
+                }
+                SourceUnitPart::ImportDirective(import) => {
+                    let path = match import {
+                        Import::Plain(path, _) |
+                        Import::GlobalSymbol(path, _, _) |
+                        Import::Rename(path, _, _) => path,
+                    };
+                    if let ImportPath::Filename(file) = path {
+                        parsed.imports.push(file.string);
+                    }
+                    continue
+                }
+                _ => continue,
+            };
+            self.declare(&mut parsed, name, None, declaration);
+        }
+        Ok(parsed)
+    }
+
+    fn declare(
+        &mut self,
+        parsed: &mut ParsedSource,
+        path: String,
+        contract: Option<&str>,
+        declaration: Declaration,
+    ) {
+        if matches!(declaration, Declaration::Struct(_)) {
+            parsed.structs.push(path.clone());
+        }
+        if self.declarations.contains_key(&path) {
+            self.duplicates.insert(path.clone());
+        }
+        self.declarations.insert(path, (contract.map(str::to_string), declaration));
+    }
+
+    /// Returns the encoded type and the typehash of the struct.
+    pub fn encoding(&self, name: &str) -> Result<Eip712Encoding> {
+        let (name, path) = self.resolve_struct(name)?;
+        let encode_type = self.encode_type(&path)?;
+        Ok(Eip712Encoding { type_hash: keccak256(&encode_type), name, encode_type })
+    }
+
+    /// Returns the `encodeType` of the struct: its members, followed by the members of all the
+    /// structs it references, sorted by name.
+    pub fn encode_type(&self, name: &str) -> Result<String> {
+        let (name, path) = self.resolve_struct(name)?;
+        let referenced = self.referenced_structs(&name, &path)?;
+        let mut encoded = self.encode_struct(&name, &path)?;
+        for (referenced_name, referenced_path) in &referenced {
+            if *referenced_name != name {
+                encoded.push_str(&self.encode_struct(referenced_name, referenced_path)?);
+            }
+        }
+        Ok(encoded)
+    }
+
+    /// Returns the typehash of the struct, `keccak256(encodeType)`.
+    pub fn type_hash(&self, name: &str) -> Result<B256> {
+        Ok(keccak256(self.encode_type(name)?))
+    }
+
+    /// Returns a Solidity library with the typehash constants and `hashStruct` functions of the
+    /// structs and all the structs they reference.
+    ///
+    /// `import` is the path of the file to import the structs from.
+    pub fn library(&self, names: &[String], import: &str) -> Result<String> {
+        let mut structs = BTreeSet::new();
+        for name in names {
+            let (name, path) = self.resolve_struct(name)?;
+            structs.extend(self.referenced_structs(&name, &path)?.into_values());
+        }
+
+        let mut constants = String::new();
+        let mut functions = String::new();
+        let mut helpers = BTreeMap::new();
+        for path in &structs {
+            let constant = type_hash_constant(path);
+            writeln!(
+                constants,
+                "    bytes32 internal constant {constant} = keccak256(\"{}\");",
+                self.encode_type(path)?
+            )?;
+
+            let mut encoded = vec![constant];
+            for (member, ty) in self.members(path)? {
+                encoded.push(encode_value(&ty, &format!("value.{member}"), &mut helpers));
+            }
+            write!(
+                functions,
+                "
+    function hashStruct({path} memory value) internal pure returns (bytes32) {{
+        return keccak256(abi.encode({}));
+    }}
+",
+                encoded.join(", ")
+            )?;
+        }
+        for helper in helpers.values() {
+            functions.push('\n');
+            functions.push_str(helper);
+        }
+
+        Ok(format!(
+            "// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.0;
+
+import \"{import}\";
+
+/// The EIP-712 typehashes and `hashStruct` functions, generated by `forge eip712`.
+library {LIBRARY_NAME} {{
+{constants}{functions}}}
+"
+        ))
+    }
+
+    /// Returns all the structs referenced by the struct, including itself, `name -> path`.
+    fn referenced_structs(&self, name: &str, path: &str) -> Result<BTreeMap<String, String>> {
+        let mut referenced = BTreeMap::from([(name.to_string(), path.to_string())]);
+        let mut queue = vec![path.to_string()];
+        while let Some(path) = queue.pop() {
+            for (_, ty) in self.members(&path)? {
+                let Some((name, path)) = ty.referenced_struct() else { continue };
+                match referenced.get(name) {
+                    Some(existing) if existing == path => {}
+                    Some(existing) => {
+                        bail!("`{existing}` and `{path}` have the same EIP-712 type name `{name}`")
+                    }
+                    None => {
+                        referenced.insert(name.to_string(), path.to_string());
+                        queue.push(path.to_string());
+                    }
+                }
+            }
+        }
+        Ok(referenced)
+    }
+
+    fn encode_struct(&self, name: &str, path: &str) -> Result<String> {
+        let members: Vec<_> =
+            self.members(path)?.into_iter().map(|(member, ty)| format!("{ty} {member}")).collect();
+        Ok(format!("{name}({})", members.join(",")))
+    }
+
+    /// Returns the members of the struct.
+    fn members(&self, path: &str) -> Result<Vec<(String, Eip712Type)>> {
+        let Some((contract, Declaration::Struct(fields))) = self.declarations.get(path) else {
+            bail!("`{path}` is not a struct")
+        };
+        fields
+            .iter()
+            .map(|field| {
+                let name = field.name.as_ref().map(|name| name.name.clone()).unwrap_or_default();
+                let ty = self
+                    .resolve_type(&field.ty, contract.as_deref())
+                    .map_err(|err| eyre!("`{path}.{name}`: {err}"))?;
+                Ok((name, ty))
+            })
+            .collect()
+    }
+
+    /// Returns the name and the path of the struct.
+    fn resolve_struct(&self, name: &str) -> Result<(String, String)> {
+        match self.resolve_name(name, None)? {
+            Eip712Type::Struct { name, path } => Ok((name, path)),
+            _ => bail!("`{name}` is not a struct"),
+        }
+    }
+
+    fn resolve_type(&self, ty: &Expression, contract: Option<&str>) -> Result<Eip712Type> {
+        let atomic = |ty: String| Ok(Eip712Type::Atomic(ty));
+        match ty {
+            Expression::Type(_, ty) => match ty {
+                Type::Address | Type::AddressPayable => atomic("address".to_string()),
+                Type::Bool => atomic("bool".to_string()),
+                Type::Int(bits) => atomic(format!("int{bits}")),
+                Type::Uint(bits) => atomic(format!("uint{bits}")),
+                Type::Bytes(len) => atomic(format!("bytes{len}")),
+                Type::String => Ok(Eip712Type::Dynamic("string".to_string())),
+                Type::DynamicBytes => Ok(Eip712Type::Dynamic("bytes".to_string())),
+                Type::Mapping { .. } => bail!("mappings are not supported by EIP-712"),
+                Type::Function { .. } => bail!("function types are not supported by EIP-712"),
+                _ => bail!("unsupported type"),
+            },
+            Expression::Variable(ident) => self.resolve_name(&ident.name, contract),
+            Expression::MemberAccess(_, expr, ident) => match &**expr {
+                Expression::Variable(scope) => {
+                    self.resolve_name(&format!("{}.{}", scope.name, ident.name), None)
+                }
+                _ => bail!("unsupported type"),
+            },
+            Expression::ArraySubscript(_, ty, len) => {
+                let ty = self.resolve_type(ty, contract)?;
+                let len = match len.as_deref() {
+                    None => None,
+                    Some(Expression::NumberLiteral(_, len, exp, None)) if exp.is_empty() => {
+                        Some(len.replace('_', "").parse()?)
+                    }
+                    Some(_) => bail!("array lengths must be number literals"),
+                };
+                Ok(Eip712Type::Array(Box::new(ty), len))
+            }
+            _ => bail!("unsupported type"),
+        }
+    }
+
+    /// Resolves a type name, declared in the contract or at the file level.
+    fn resolve_name(&self, name: &str, contract: Option<&str>) -> Result<Eip712Type> {
+        let path = contract
+            .map(|contract| format!("{contract}.{name}"))
+            .filter(|path| self.declarations.contains_key(path))
+            .or_else(|| self.declarations.contains_key(name).then(|| name.to_string()));
+        let path = match path {
+            Some(path) => path,
+            None if self.contracts.contains(name) => {
+                return Ok(Eip712Type::Atomic("address".to_string()))
+            }
+            None if !name.contains('.') => {
+                // declared in another contract, e.g. an inherited interface
+                let mut candidates = self
+                    .declarations
+                    .keys()
+                    .filter(|path| path.rsplit_once('.').map(|(_, n)| n) == Some(name));
+                match (candidates.next(), candidates.next()) {
+                    (Some(path), None) => path.clone(),
+                    (Some(_), Some(_)) => bail!("`{name}` is ambiguous, qualify it"),
+                    (None, _) => bail!("unknown type `{name}`"),
+                }
+            }
+            None => bail!("unknown type `{name}`"),
+        };
+        if self.duplicates.contains(&path) {
+            bail!("`{path}` is declared more than once");
+        }
+
+        let (contract, declaration) = &self.declarations[&path];
+        match declaration {
+            Declaration::Struct(_) => {
+                let name = path.rsplit('.').next().unwrap_or(&path).to_string();
+                Ok(Eip712Type::Struct { name, path })
+            }
+            Declaration::Enum => Ok(Eip712Type::Atomic("uint8".to_string())),
+            Declaration::UserType(ty) => self.resolve_type(ty, contract.as_deref()),
+        }
+    }
+}
+
+/// Returns the name of the typehash constant of the struct, e.g. `PERMIT_DETAILS_TYPEHASH`.
+fn type_hash_constant(path: &str) -> String {
+    let chars: Vec<char> = path.chars().collect();
+    let mut constant = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '.' {
+            constant.push('_');
+            continue
+        }
+        // split words at case changes, keeping acronyms together, e.g. `IERC20Permit`
+        let previous = i.checked_sub(1).map(|i| chars[i]).filter(|&p| p != '.');
+        let next = chars.get(i + 1);
+        if c.is_ascii_uppercase() &&
+            previous.map_or(false, |p| {
+                !p.is_ascii_uppercase() || next.map_or(false, |n| n.is_ascii_lowercase())
+            })
+        {
+            constant.push('_');
+        }
+        constant.push(c.to_ascii_uppercase());
+    }
+    constant.push_str("_TYPEHASH");
+    constant
+}
+
+/// Returns the Solidity expression of the EIP-712 encoding of the value, adding the helper
+/// functions it uses for arrays.
+fn encode_value(ty: &Eip712Type, value: &str, helpers: &mut BTreeMap<String, String>) -> String {
+    match ty {
+        Eip712Type::Atomic(_) => value.to_string(),
+        Eip712Type::Dynamic(ty) if ty == "string" => format!("keccak256(bytes({value}))"),
+        Eip712Type::Dynamic(_) => format!("keccak256({value})"),
+        Eip712Type::Struct { .. } => format!("hashStruct({value})"),
+        // the elements of arrays are padded to 32 bytes by `abi.encodePacked`
+        Eip712Type::Array(element, _) if matches!(**element, Eip712Type::Atomic(_)) => {
+            format!("keccak256(abi.encodePacked({value}))")
+        }
+        Eip712Type::Array(element, _) => {
+            let helper = format!("hash{}", ty.mangled());
+            if !helpers.contains_key(&helper) {
+                // reserve the name, the element may be an array of the same type
+                helpers.insert(helper.clone(), String::new());
+                let element = encode_value(element, "values[i]", helpers);
+                let code = format!(
+                    "    function {helper}({} memory values) private pure returns (bytes32) {{
+        bytes32[] memory hashes = new bytes32[](values.length);
+        for (uint256 i = 0; i < values.length; i++) {{
+            hashes[i] = {element};
+        }}
+        return keccak256(abi.encodePacked(hashes));
+    }}
+",
+                    ty.sol_type()
+                );
+                helpers.insert(helper.clone(), code);
+            }
+            format!("{helper}({value})")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::b256;
+
+    const PERMIT2: &str = r#"
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.17;
+
+interface IAllowanceTransfer {
+    struct PermitDetails {
+        address token;
+        uint160 amount;
+        uint48 expiration;
+        uint48 nonce;
+    }
+
+    struct PermitSingle {
+        PermitDetails details;
+        address spender;
+        uint256 sigDeadline;
+    }
+
+    struct PermitBatch {
+        PermitDetails[] details;
+        address spender;
+        uint256 sigDeadline;
+    }
+}
+
+interface ISignatureTransfer {
+    struct TokenPermissions {
+        address token;
+        uint256 amount;
+    }
+
+    struct PermitTransferFrom {
+        TokenPermissions permitted;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    struct PermitBatchTransferFrom {
+        TokenPermissions[] permitted;
+        uint256 nonce;
+        uint256 deadline;
+    }
+}
+"#;
+
+    fn structs(src: &str) -> Eip712Structs {
+        let mut structs = Eip712Structs::default();
+        structs.add_source(src).unwrap();
+        structs
+    }
+
+    #[test]
+    fn can_encode_permit2_types() {
+        let structs = structs(PERMIT2);
+        assert_eq!(
+            structs.type_hash("PermitDetails").unwrap(),
+            b256!("65626cad6cb96493bf6f5ebea28756c966f023ab9e8a83a7101849d5573b3678")
+        );
+        assert_eq!(
+            structs.encode_type("IAllowanceTransfer.PermitSingle").unwrap(),
+            "PermitSingle(PermitDetails details,address spender,uint256 sigDeadline)\
+             PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)"
+        );
+        assert_eq!(
+            structs.type_hash("PermitSingle").unwrap(),
+            b256!("f3841cd1ff0085026a6327b620b67997ce40f282c88a8e905a7a5626e310f3d0")
+        );
+        assert_eq!(
+            structs.type_hash("PermitBatch").unwrap(),
+            b256!("af1b0d30d2cab0380e68f0689007e3254993c596f2fdd0aaa7f4d04f79440863")
+        );
+        assert_eq!(
+            structs.type_hash("TokenPermissions").unwrap(),
+            b256!("618358ac3db8dc274f0cd8829da7e234bd48cd73c4a740aede1adec9846d06a1")
+        );
+    }
+
+    #[test]
+    fn can_encode_permit2_witness_types() {
+        // the `spender` of `PermitTransferFrom` is the caller, it's only part of the signed type
+        let structs = structs(
+            r#"
+struct TokenPermissions {
+    address token;
+    uint256 amount;
+}
+struct PermitTransferFrom {
+    TokenPermissions permitted;
+    address spender;
+    uint256 nonce;
+    uint256 deadline;
+}
+struct PermitBatchTransferFrom {
+    TokenPermissions[] permitted;
+    address spender;
+    uint256 nonce;
+    uint256 deadline;
+}
+"#,
+        );
+        assert_eq!(
+            structs.type_hash("PermitTransferFrom").unwrap(),
+            b256!("939c21a48a8dbe3a9a2404a1d46691e4d39f6583d6ec6b35714604c986d80106")
+        );
+        assert_eq!(
+            structs.type_hash("PermitBatchTransferFrom").unwrap(),
+            b256!("fcf35f5ac6a2c28868dc44c302166470266239195f02b0ee408334829333b766")
+        );
+    }
+
+    #[test]
+    fn sorts_referenced_types() {
+        // <https://eips.ethereum.org/EIPS/eip-712#specification-of-the-eth_signtypeddata-json-rpc>
+        let structs = structs(
+            r#"
+struct Mail {
+    Person from;
+    Person to;
+    string contents;
+    Attachment[2][] attachments;
+}
+struct Person {
+    string name;
+    address wallet;
+}
+struct Attachment {
+    bytes data;
+}
+"#,
+        );
+        assert_eq!(
+            structs.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents,Attachment[2][] attachments)\
+             Attachment(bytes data)Person(string name,address wallet)"
+        );
+        assert_eq!(structs.encode_type("Person").unwrap(), "Person(string name,address wallet)");
+    }
+
+    #[test]
+    fn can_encode_eip712_example() {
+        let structs = structs(
+            r#"
+struct Person {
+    string name;
+    address wallet;
+}
+struct Mail {
+    Person from;
+    Person to;
+    string contents;
+}
+"#,
+        );
+        assert_eq!(
+            structs.type_hash("Mail").unwrap(),
+            b256!("a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2")
+        );
+    }
+
+    #[test]
+    fn resolves_enums_contracts_and_value_types() {
+        let structs = structs(
+            r#"
+type Amount is uint128;
+interface IERC20 {}
+contract Orders {
+    enum Side { Buy, Sell }
+    type Price is uint64;
+    struct Order {
+        IERC20 token;
+        Side side;
+        Amount amount;
+        Price price;
+        bytes32[] tags;
+        uint256[3] fees;
+    }
+}
+"#,
+        );
+        assert_eq!(
+            structs.encode_type("Order").unwrap(),
+            "Order(address token,uint8 side,uint128 amount,uint64 price,bytes32[] tags,\
+             uint256[3] fees)"
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_types() {
+        let structs = structs(
+            r#"
+struct Balances {
+    mapping(address => uint256) balances;
+}
+struct Holder {
+    Balances balances;
+}
+struct Unknown {
+    Missing missing;
+}
+"#,
+        );
+        let err = structs.encode_type("Holder").unwrap_err().to_string();
+        assert_eq!(err, "`Balances.balances`: mappings are not supported by EIP-712");
+        let err = structs.encode_type("Unknown").unwrap_err().to_string();
+        assert_eq!(err, "`Unknown.missing`: unknown type `Missing`");
+        let err = structs.encode_type("Missing").unwrap_err().to_string();
+        assert_eq!(err, "unknown type `Missing`");
+    }
+
+    #[test]
+    fn rejects_conflicting_names() {
+        let structs = structs(
+            r#"
+contract A {
+    struct Asset { address token; }
+}
+contract B {
+    struct Asset { uint256 id; }
+}
+struct Swap {
+    A.Asset give;
+    B.Asset take;
+}
+"#,
+        );
+        let err = structs.encode_type("Swap").unwrap_err().to_string();
+        assert!(err.contains("have the same EIP-712 type name `Asset`"), "{err}");
+        assert!(structs.encode_type("Asset").is_err());
+        assert!(structs.encode_type("B.Asset").is_ok());
+    }
+
+    #[test]
+    fn can_generate_library() {
+        let structs = structs(PERMIT2);
+        let library = structs.library(&["PermitBatch".to_string()], "src/Permit2.sol").unwrap();
+        assert!(library.contains("import \"src/Permit2.sol\";"), "{library}");
+        assert!(library.contains(
+            "bytes32 internal constant I_ALLOWANCE_TRANSFER_PERMIT_DETAILS_TYPEHASH = keccak256(\
+             \"PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)\");"
+        ));
+        assert!(
+            library.contains("function hashStruct(IAllowanceTransfer.PermitBatch memory value)")
+        );
+        assert!(library.contains(
+            "return keccak256(abi.encode(I_ALLOWANCE_TRANSFER_PERMIT_BATCH_TYPEHASH, \
+             hashIAllowanceTransferPermitDetailsArray(value.details), value.spender, \
+             value.sigDeadline));"
+        ));
+        assert!(library.contains("hashes[i] = hashStruct(values[i]);"));
+        assert!(!library.contains("PERMIT_SINGLE"));
+    }
+
+    #[test]
+    fn can_name_type_hash_constants() {
+        assert_eq!(type_hash_constant("Mail"), "MAIL_TYPEHASH");
+        assert_eq!(type_hash_constant("PermitDetails"), "PERMIT_DETAILS_TYPEHASH");
+        assert_eq!(type_hash_constant("IOrders.Order2Side"), "I_ORDERS_ORDER2_SIDE_TYPEHASH");
+        assert_eq!(type_hash_constant("IERC20Permit"), "IERC20_PERMIT_TYPEHASH");
+    }
+}
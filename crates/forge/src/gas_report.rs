@@ -3,11 +3,14 @@
 use crate::{
     constants::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
     hashbrown::HashSet,
-    traces::{CallTraceArena, CallTraceDecoder, CallTraceNode, DecodedCallData, TraceKind},
+    traces::{
+        CallKind, CallTraceArena, CallTraceDecoder, CallTraceNode, DecodedCallData, TraceKind,
+    },
 };
 use alloy_primitives::U256;
 use comfy_table::{presets::ASCII_MARKDOWN, *};
-use foundry_common::{calc, TestFunctionExt};
+use foundry_common::{calc, glob::GlobMatcher, TestFunctionExt};
+use foundry_config::GasReportConfig;
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, fmt::Display};
 
@@ -20,6 +23,14 @@ pub struct GasReport {
     report_for: HashSet<String>,
     /// Contracts to ignore when generating the report.
     ignore: HashSet<String>,
+    /// Whether to ignore the calls made in `setUp()`.
+    ignore_setup: bool,
+    /// Contracts or functions to report, everything is reported if empty.
+    #[serde(skip)]
+    include: Vec<GasReportFilter>,
+    /// Contracts or functions to leave out of the report.
+    #[serde(skip)]
+    exclude: Vec<GasReportFilter>,
     /// All contracts that were analyzed grouped by their identifier
     /// ``test/Counter.t.sol:CounterTest
     contracts: BTreeMap<String, ContractInfo>,
@@ -36,6 +47,14 @@ impl GasReport {
         Self { report_any, report_for, ignore, ..Default::default() }
     }
 
+    /// Applies the `[gas_report]` settings.
+    pub fn with_config(mut self, config: &GasReportConfig) -> eyre::Result<Self> {
+        self.ignore_setup = config.ignore_setup;
+        self.include = config.include.iter().map(|s| s.parse()).collect::<Result<_, _>>()?;
+        self.exclude = config.exclude.iter().map(|s| s.parse()).collect::<Result<_, _>>()?;
+        Ok(self)
+    }
+
     /// Whether the given contract should be reported.
     fn should_report(&self, contract_name: &str) -> bool {
         if self.ignore.contains(contract_name) {
@@ -45,25 +64,63 @@ impl GasReport {
         self.report_any || self.report_for.contains(contract_name)
     }
 
+    /// Whether the given function, or the deployment of the contract if `None`, passes the
+    /// `include` and `exclude` filters.
+    fn is_included(&self, contract_name: &str, function: Option<&str>) -> bool {
+        let included = self.include.is_empty() ||
+            self.include.iter().any(|filter| filter.is_match(contract_name, function));
+        // excluding some functions doesn't exclude the deployment
+        let excluded = self.exclude.iter().any(|filter| {
+            (function.is_some() || filter.function.is_none()) &&
+                filter.is_match(contract_name, function)
+        });
+        included && !excluded
+    }
+
     /// Analyzes the given traces and generates a gas report.
     pub async fn analyze(
         &mut self,
         traces: &[(TraceKind, CallTraceArena)],
         decoder: &CallTraceDecoder,
     ) {
-        for node in traces.iter().flat_map(|(_, arena)| arena.nodes()) {
-            self.analyze_node(node, decoder).await;
+        for (kind, arena) in traces {
+            let ignore_calls = self.ignore_setup && kind.is_setup();
+            for node in arena.nodes() {
+                self.analyze_node(node, arena.nodes(), ignore_calls, decoder).await;
+            }
         }
     }
 
-    async fn analyze_node(&mut self, node: &CallTraceNode, decoder: &CallTraceDecoder) {
+    async fn analyze_node(
+        &mut self,
+        node: &CallTraceNode,
+        nodes: &[CallTraceNode],
+        ignore_calls: bool,
+        decoder: &CallTraceDecoder,
+    ) {
         let trace = &node.trace;
-        let decoded = decoder.decode_function(&node.trace).await;
 
         if trace.address == CHEATCODE_ADDRESS || trace.address == HARDHAT_CONSOLE_ADDRESS {
             return
         }
 
+        if !trace.kind.is_any_create() {
+            if ignore_calls {
+                return
+            }
+            // A call that is forwarded to an implementation contract is reported as a call of the
+            // implementation, which is the child `DELEGATECALL` trace
+            let is_forwarded = node.children.iter().any(|&child| {
+                let child = &nodes[child].trace;
+                child.kind == CallKind::DelegateCall && child.data == trace.data
+            });
+            if is_forwarded {
+                return
+            }
+        }
+
+        let decoded = decoder.decode_function(&node.trace).await;
+
         if let Some(name) = &decoded.contract {
             let contract_name = name.rsplit(':').next().unwrap_or(name.as_str());
             // If the user listed the contract in 'gas_reports' (the foundry.toml field) a
@@ -80,21 +137,25 @@ impl GasReport {
             }
 
             if self.should_report(contract_name) {
-                let contract_info = self.contracts.entry(name.to_string()).or_default();
-
                 if trace.kind.is_any_create() {
-                    contract_info.gas = U256::from(trace.gas_used);
-                    contract_info.size = U256::from(trace.data.len());
+                    if self.is_included(contract_name, None) {
+                        let contract_info = self.contracts.entry(name.to_string()).or_default();
+                        contract_info.gas = U256::from(trace.gas_used);
+                        contract_info.size = U256::from(trace.data.len());
+                    }
                 } else if let Some(DecodedCallData { signature, .. }) = decoded.func {
-                    let name = signature.split('(').next().unwrap();
+                    let fn_name = signature.split('(').next().unwrap();
                     // ignore any test/setup functions
                     let should_include =
-                        !(name.is_test() || name.is_invariant_test() || name.is_setup());
-                    if should_include {
-                        let gas_info = contract_info
-                            .functions
+                        !(fn_name.is_test() || fn_name.is_invariant_test() || fn_name.is_setup());
+                    if should_include && self.is_included(contract_name, Some(fn_name)) {
+                        let gas_info = self
+                            .contracts
                             .entry(name.to_string())
                             .or_default()
+                            .functions
+                            .entry(fn_name.to_string())
+                            .or_default()
                             .entry(signature.clone())
                             .or_default();
                         gas_info.calls.push(U256::from(trace.gas_used));
@@ -115,6 +176,7 @@ impl GasReport {
                     func.max = func.calls.last().copied().unwrap_or_default();
                     func.mean = calc::mean(&func.calls);
                     func.median = U256::from(calc::median_sorted(func.calls.as_slice()));
+                    func.sampled = func.calls.len();
                 });
             });
         });
@@ -160,7 +222,7 @@ impl Display for GasReport {
                         Cell::new(gas_info.mean.to_string()).fg(Color::Yellow),
                         Cell::new(gas_info.median.to_string()).fg(Color::Yellow),
                         Cell::new(gas_info.max.to_string()).fg(Color::Red),
-                        Cell::new(gas_info.calls.len().to_string()),
+                        Cell::new(gas_info.sampled.to_string()),
                     ]);
                 })
             });
@@ -181,8 +243,48 @@ pub struct ContractInfo {
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct GasInfo {
     pub calls: Vec<U256>,
+    /// The number of calls the statistics are computed from
+    pub sampled: usize,
     pub min: U256,
     pub mean: U256,
     pub median: U256,
     pub max: U256,
 }
+
+/// A filter of the gas report, in the form `<contract glob>[.<function glob>]`
+#[derive(Clone, Debug)]
+struct GasReportFilter {
+    contract: GlobMatcher,
+    function: Option<GlobMatcher>,
+}
+
+impl GasReportFilter {
+    /// Matches the function of the contract, or the contract itself if `function` is `None`.
+    ///
+    /// Filters without a function part match all functions of the contract.
+    fn is_match(&self, contract: &str, function: Option<&str>) -> bool {
+        if !self.contract.is_match(contract) {
+            return false
+        }
+        match (&self.function, function) {
+            (Some(filter), Some(function)) => filter.is_match(function),
+            _ => true,
+        }
+    }
+}
+
+impl std::str::FromStr for GasReportFilter {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (contract, function) = match s.split_once('.') {
+            Some((contract, function)) => (contract, Some(function)),
+            None => (s, None),
+        };
+        let parse = |glob: &str| {
+            glob.parse::<GlobMatcher>()
+                .map_err(|err| eyre::eyre!("invalid gas report filter `{s}`: {err}"))
+        };
+        Ok(Self { contract: parse(contract)?, function: function.map(parse).transpose()? })
+    }
+}
@@ -5,7 +5,7 @@ use alloy_primitives::B256;
 use foundry_compilers::ProjectCompileOutput;
 use foundry_config::{
     validate_profiles, Config, FuzzConfig, InlineConfig, InlineConfigError, InlineConfigParser,
-    InvariantConfig, NatSpec,
+    InlineEvmConfig, InvariantConfig, NatSpec,
 };
 
 use proptest::test_runner::{RngAlgorithm, TestRng, TestRunner};
@@ -13,6 +13,8 @@ use std::path::Path;
 
 pub mod coverage;
 
+pub mod eip712;
+
 pub mod gas_report;
 
 pub mod link;
@@ -25,6 +27,12 @@ pub use runner::ContractRunner;
 
 pub mod result;
 
+pub mod shard;
+
+pub use foundry_common::storage_diff;
+
+pub mod stream;
+
 // TODO: remove
 pub use foundry_common::traits::TestFilter;
 pub use foundry_evm::*;
@@ -42,6 +50,9 @@ pub struct TestOptions {
     pub inline_fuzz: InlineConfig<FuzzConfig>,
     /// Contains per-test specific "invariant" configurations.
     pub inline_invariant: InlineConfig<InvariantConfig>,
+    /// Contains per-contract and per-test specific EVM settings. Contract settings are stored
+    /// with an empty function name.
+    pub inline_evm: InlineConfig<InlineEvmConfig>,
 }
 
 impl TestOptions {
@@ -57,6 +68,7 @@ impl TestOptions {
         let natspecs: Vec<NatSpec> = NatSpec::parse(output, root);
         let mut inline_invariant = InlineConfig::<InvariantConfig>::default();
         let mut inline_fuzz = InlineConfig::<FuzzConfig>::default();
+        let mut inline_evm = InlineConfig::<InlineEvmConfig>::default();
 
         for natspec in natspecs {
             // Perform general validation
@@ -81,9 +93,21 @@ impl TestOptions {
                 Ok(None) => { /* No inline config found, do nothing */ }
                 Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
             }
+
+            match InlineEvmConfig::try_parse(&configs) {
+                Ok(Some(conf)) => inline_evm.insert(c, f, conf),
+                Ok(None) => { /* No inline config found, do nothing */ }
+                Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
+            }
         }
 
-        Ok(Self { fuzz: base_fuzz, invariant: base_invariant, inline_fuzz, inline_invariant })
+        Ok(Self {
+            fuzz: base_fuzz,
+            invariant: base_invariant,
+            inline_fuzz,
+            inline_invariant,
+            inline_evm,
+        })
     }
 
     /// Returns a "fuzz" test runner instance. Parameters are used to select tight scoped fuzz
@@ -144,6 +168,32 @@ impl TestOptions {
         self.inline_invariant.get(contract_id, test_fn).unwrap_or(&self.invariant)
     }
 
+    /// Returns the inline EVM settings that apply to a contract-function pair. Settings of the
+    /// function take precedence over the settings of the contract.
+    ///
+    /// - `contract_id` is the id of the test contract, expressed as a relative path from the
+    ///   project root.
+    /// - `test_fn` is the name of the test function declared inside the test contract.
+    pub fn evm_config(&self, contract_id: &str, test_fn: &str) -> InlineEvmConfig {
+        let contract = self.inline_evm.get(contract_id, "");
+        let function = self.inline_evm.get(contract_id, test_fn);
+        match (function, contract) {
+            (Some(function), Some(contract)) => {
+                if function.conflicts_with(contract) {
+                    debug!(
+                        target: "forge::test",
+                        contract = contract_id,
+                        function = test_fn,
+                        "inline EVM settings of the function override the settings of the contract"
+                    );
+                }
+                function.or(contract)
+            }
+            (Some(config), None) | (None, Some(config)) => config.clone(),
+            (None, None) => InlineEvmConfig::default(),
+        }
+    }
+
     pub fn fuzzer_with_cases(&self, cases: u32) -> TestRunner {
         // TODO: Add Options to modify the persistence
         let cfg = proptest::test_runner::Config {
@@ -2,7 +2,9 @@
 
 use crate::{
     link::{link_with_nonce_or_address, PostLinkInput, ResolvedDependency},
-    result::SuiteResult,
+    result::{SuiteResult, TestResult},
+    shard::TestShard,
+    stream::TestEvent,
     ContractRunner, TestFilter, TestOptions,
 };
 use alloy_json_abi::{Function, JsonAbi};
@@ -13,21 +15,23 @@ use foundry_compilers::{
     artifacts::CompactContractBytecode, contracts::ArtifactContracts, Artifact, ArtifactId,
     ArtifactOutput, ProjectCompileOutput,
 };
+use foundry_config::InlineEvmConfig;
 use foundry_evm::{
-    backend::Backend,
-    executors::{Executor, ExecutorBuilder},
+    backend::{Backend, DatabaseExt},
+    executors::{Executor, ExecutorBuilder, TestStopper},
     fork::CreateFork,
     inspectors::CheatsConfig,
     opts::EvmOpts,
     revm,
 };
 use rayon::prelude::*;
-use revm::primitives::SpecId;
+use revm::{primitives::SpecId, JournaledState};
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
     iter::Iterator,
     path::Path,
     sync::{mpsc, Arc},
+    time::Duration,
 };
 
 pub type DeployableContracts = BTreeMap<ArtifactId, (JsonAbi, Bytes, Vec<Bytes>)>;
@@ -62,6 +66,19 @@ pub struct MultiContractRunner {
     pub debug: bool,
     /// Settings related to fuzz and/or invariant tests
     pub test_options: TestOptions,
+    /// The shard of the tests to run, all matching tests are run if not set
+    pub shard: Option<TestShard>,
+    /// Receives the events of the tests and suites as they start and finish, if set
+    pub events: Option<mpsc::Sender<TestEvent>>,
+    /// The number of threads to run the tests with, the global thread pool is used if not set.
+    ///
+    /// Suites and their tests are scheduled in the order of their names, so a single thread runs
+    /// them deterministically one after another.
+    pub threads: Option<usize>,
+    /// Stops the run early, no new suites or tests are started once it's stopped
+    pub stopper: TestStopper,
+    /// Whether to stop the run after the first failure
+    pub fail_fast: bool,
 }
 
 impl MultiContractRunner {
@@ -83,6 +100,36 @@ impl MultiContractRunner {
             })
     }
 
+    /// Returns the signatures of the tests to run of each matching suite, by suite identifier
+    ///
+    /// Suites without any tests to run, e.g. of other shards, are omitted.
+    pub fn matching_suites(&self, filter: &dyn TestFilter) -> BTreeMap<String, Vec<String>> {
+        self.contracts
+            .iter()
+            .filter(|(id, _)| filter.matches_path(&id.source) && filter.matches_contract(&id.name))
+            .filter_map(|(id, (abi, _, _))| {
+                let name = id.identifier();
+                let tests: Vec<_> =
+                    self.suite_tests(&name, abi, filter).map(|func| func.signature()).collect();
+                (!tests.is_empty()).then_some((name, tests))
+            })
+            .collect()
+    }
+
+    /// Returns the test functions of a suite to run
+    fn suite_tests<'a>(
+        &'a self,
+        name: &'a str,
+        contract: &'a JsonAbi,
+        filter: &'a dyn TestFilter,
+    ) -> impl Iterator<Item = &'a Function> {
+        contract.functions().filter(move |func| {
+            (func.is_test() || func.is_invariant_test()) &&
+                filter.matches_test(&func.signature()) &&
+                self.shard.as_ref().map_or(true, |shard| shard.contains(name, &func.signature()))
+        })
+    }
+
     /// Get an iterator over all test contract functions that matches the filter path and contract
     /// name
     fn filtered_tests<'a>(&'a self, filter: &'a dyn TestFilter) -> impl Iterator<Item = &Function> {
@@ -169,38 +216,196 @@ impl MultiContractRunner {
         // the db backend that serves all the data, each contract gets its own instance
         let db = Backend::spawn(self.fork.take()).await;
 
-        self.contracts
-            .par_iter()
+        let mut contracts: Vec<_> = self
+            .contracts
+            .iter()
             .filter(|(id, _)| filter.matches_path(&id.source) && filter.matches_contract(&id.name))
             .filter(|(_, (abi, _, _))| abi.functions().any(|func| filter.matches_test(&func.name)))
-            .for_each_with(stream_result, |stream_result, (id, (abi, deploy_code, libs))| {
-                let executor = ExecutorBuilder::new()
-                    .inspectors(|stack| {
-                        stack
-                            .cheatcodes(self.cheats_config.clone())
-                            .trace(self.evm_opts.verbosity >= 3 || self.debug)
-                            .debug(self.debug)
-                            .coverage(self.coverage)
-                    })
-                    .spec(self.evm_spec)
-                    .gas_limit(self.evm_opts.gas_limit())
-                    .build(self.env.clone(), db.clone());
-                let identifier = id.identifier();
-                trace!(contract=%identifier, "start executing all tests in contract");
-
-                let result = self.run_tests(
-                    &identifier,
-                    abi,
+            .filter(|(id, _)| {
+                self.shard.as_ref().map_or(true, |shard| shard.contains_contract(&id.identifier()))
+            })
+            .collect();
+        contracts.sort_by_cached_key(|(id, _)| id.identifier());
+
+        self.install(|| {
+            contracts.par_iter().for_each_with(
+                stream_result,
+                |stream_result, (id, (abi, deploy_code, libs))| {
+                    if self.stopper.is_stopped() {
+                        return
+                    }
+                    let identifier = id.identifier();
+                    trace!(contract=%identifier, "start executing all tests in contract");
+
+                    let result = self.run_suite(
+                        &identifier,
+                        abi,
+                        &db,
+                        deploy_code.clone(),
+                        libs,
+                        filter,
+                        &test_options,
+                    );
+                    trace!(contract=?identifier, "executed all tests in contract");
+                    if self.fail_fast && result.failures().next().is_some() {
+                        self.stopper.stop();
+                    }
+
+                    if let Some(events) = &self.events {
+                        let _ = events.send(TestEvent::suite_finished(&identifier, &result));
+                    }
+                    let _ = stream_result.send((identifier, result));
+                },
+            )
+        })
+    }
+
+    /// Runs `f` in a thread pool of the configured number of threads, or in the global pool.
+    fn install<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        let pool = self.threads.and_then(|threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads.max(1))
+                .build()
+                .map_err(|err| warn!(%err, "failed to create the thread pool of the tests"))
+                .ok()
+        });
+        match pool {
+            Some(pool) => pool.install(f),
+            None => f(),
+        }
+    }
+
+    /// Runs the tests of a contract, tests with different inline EVM settings are run with
+    /// separate executors.
+    #[allow(clippy::too_many_arguments)]
+    fn run_suite(
+        &self,
+        name: &str,
+        contract: &JsonAbi,
+        db: &Backend,
+        deploy_code: Bytes,
+        libs: &[Bytes],
+        filter: &dyn TestFilter,
+        test_options: &TestOptions,
+    ) -> SuiteResult {
+        let mut groups: Vec<(InlineEvmConfig, BTreeSet<String>)> = Vec::new();
+        for func in self.suite_tests(name, contract, filter) {
+            let evm_config = test_options.evm_config(name, &func.name);
+            match groups.iter_mut().find(|(config, _)| *config == evm_config) {
+                Some((_, signatures)) => {
+                    signatures.insert(func.signature());
+                }
+                None => groups.push((evm_config, BTreeSet::from([func.signature()]))),
+            }
+        }
+
+        // all tests share the same settings, no need to filter unless tests of other shards have
+        // to be skipped
+        if groups.len() <= 1 && self.shard.is_none() {
+            let evm_config = groups.pop().map(|(config, _)| config).unwrap_or_default();
+            return match self.executor(db, &evm_config) {
+                Ok(executor) => self.run_tests(
+                    name,
+                    contract,
                     executor,
-                    deploy_code.clone(),
+                    deploy_code,
                     libs,
                     filter,
                     test_options.clone(),
-                );
-                trace!(contract=?identifier, "executed all tests in contract");
+                ),
+                Err(err) => SuiteResult::new(
+                    Duration::ZERO,
+                    [("setUp()".to_string(), TestResult::fail(err.to_string()))].into(),
+                    vec![],
+                ),
+            }
+        }
 
-                let _ = stream_result.send((identifier, result));
+        let mut suite = SuiteResult::new(Duration::ZERO, BTreeMap::new(), vec![]);
+        for (evm_config, signatures) in groups {
+            trace!(contract=%name, ?evm_config, "running tests with inline EVM settings");
+            let result = match self.executor(db, &evm_config) {
+                Ok(executor) => self.run_tests(
+                    name,
+                    contract,
+                    executor,
+                    deploy_code.clone(),
+                    libs,
+                    &GroupFilter { filter, signatures: &signatures },
+                    test_options.clone(),
+                ),
+                Err(err) => {
+                    let results = signatures
+                        .into_iter()
+                        .map(|signature| (signature, TestResult::fail(err.to_string())))
+                        .collect();
+                    SuiteResult::new(Duration::ZERO, results, vec![])
+                }
+            };
+            suite.duration += result.duration;
+            suite.test_results.extend(result.test_results);
+            for warning in result.warnings {
+                if !suite.warnings.contains(&warning) {
+                    suite.warnings.push(warning);
+                }
+            }
+        }
+        suite
+    }
+
+    /// Creates the executor for tests with the given inline EVM settings.
+    ///
+    /// If the settings configure a fork, it's created and selected before the test contract is
+    /// deployed, like the launch fork.
+    fn executor(&self, db: &Backend, evm_config: &InlineEvmConfig) -> Result<Executor> {
+        let spec = evm_config.spec_id.unwrap_or(self.evm_spec);
+        let mut env = self.env.clone();
+        let mut db = db.clone();
+
+        let mut cheats_config = self.cheats_config.clone();
+        if evm_config.is_storage_caching_disabled() {
+            let mut config = (*cheats_config).clone();
+            config.no_storage_caching = true;
+            cheats_config = Arc::new(config);
+        }
+
+        // the launch fork is created again if its storage must not be cached
+        let uncached_launch_fork =
+            evm_config.is_storage_caching_disabled() && self.evm_opts.fork_url.is_some();
+        if evm_config.is_fork() || uncached_launch_fork {
+            let url = match &evm_config.fork_url {
+                Some(url_or_alias) => self.cheats_config.rpc_url(url_or_alias)?,
+                None => self.evm_opts.fork_url.clone().ok_or_else(|| {
+                    eyre::eyre!("inline `fork_block_number` requires a `fork_url` or `eth_rpc_url`")
+                })?,
+            };
+            let mut evm_opts = self.evm_opts.clone();
+            evm_opts.fork_url = Some(url.clone());
+            if evm_config.is_fork() {
+                evm_opts.fork_block_number = evm_config.fork_block_number;
+            }
+            let fork = CreateFork {
+                enable_caching: cheats_config.enable_caching(&url),
+                url,
+                env: env.clone(),
+                evm_opts,
+            };
+            let mut journaled_state = JournaledState::new(spec, Default::default());
+            db.create_select_fork(fork, &mut env, &mut journaled_state)?;
+        }
+
+        Ok(ExecutorBuilder::new()
+            .inspectors(|stack| {
+                stack
+                    .cheatcodes(cheats_config)
+                    .trace(self.evm_opts.verbosity >= 3 || self.debug)
+                    .debug(self.debug)
+                    .coverage(self.coverage)
+                    .enable_isolation(self.evm_opts.isolate)
             })
+            .spec(spec)
+            .gas_limit(self.evm_opts.gas_limit())
+            .build(env, db))
     }
 
     #[instrument(skip_all, fields(name = %name))]
@@ -215,7 +420,7 @@ impl MultiContractRunner {
         filter: &dyn TestFilter,
         test_options: TestOptions,
     ) -> SuiteResult {
-        let runner = ContractRunner::new(
+        let mut runner = ContractRunner::new(
             name,
             executor,
             contract,
@@ -226,10 +431,34 @@ impl MultiContractRunner {
             libs,
             self.debug,
         );
+        runner.events = self.events.as_ref();
+        runner.stopper = self.stopper.clone();
+        runner.fail_fast = self.fail_fast;
         runner.run_tests(filter, test_options, Some(&self.known_contracts))
     }
 }
 
+/// A [TestFilter] that only matches the tests of a group of tests with the same inline EVM
+/// settings.
+struct GroupFilter<'a> {
+    filter: &'a dyn TestFilter,
+    signatures: &'a BTreeSet<String>,
+}
+
+impl TestFilter for GroupFilter<'_> {
+    fn matches_test(&self, test_name: &str) -> bool {
+        self.signatures.contains(test_name) && self.filter.matches_test(test_name)
+    }
+
+    fn matches_contract(&self, contract_name: &str) -> bool {
+        self.filter.matches_contract(contract_name)
+    }
+
+    fn matches_path(&self, path: &Path) -> bool {
+        self.filter.matches_path(path)
+    }
+}
+
 /// Builder used for instantiating the multi-contract runner
 #[derive(Clone, Debug, Default)]
 pub struct MultiContractRunnerBuilder {
@@ -348,6 +577,8 @@ impl MultiContractRunnerBuilder {
         )?;
 
         let execution_info = known_contracts.flatten();
+        let mut cheats_config = self.cheats_config.unwrap_or_default();
+        cheats_config.errors = Some(execution_info.2.clone());
         Ok(MultiContractRunner {
             contracts: deployable_contracts,
             known_contracts,
@@ -358,10 +589,15 @@ impl MultiContractRunnerBuilder {
             errors: Some(execution_info.2),
             source_paths,
             fork: self.fork,
-            cheats_config: self.cheats_config.unwrap_or_default().into(),
+            cheats_config: cheats_config.into(),
             coverage: self.coverage,
             debug: self.debug,
             test_options: self.test_options.unwrap_or_default(),
+            shard: None,
+            events: None,
+            threads: None,
+            stopper: TestStopper::default(),
+            fail_fast: false,
         })
     }
 
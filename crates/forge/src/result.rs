@@ -47,6 +47,11 @@ impl SuiteResult {
         self.tests().filter(|(_, t)| t.status == TestStatus::Failure)
     }
 
+    /// Iterator over all skipped tests and their names
+    pub fn skips(&self) -> impl Iterator<Item = (&String, &TestResult)> {
+        self.tests().filter(|(_, t)| t.status == TestStatus::Skipped)
+    }
+
     /// Iterator over all tests and their names
     pub fn tests(&self) -> impl Iterator<Item = (&String, &TestResult)> {
         self.test_results.iter()
@@ -131,8 +136,15 @@ pub struct TestResult {
     /// The debug nodes of the call
     pub debug: Option<DebugArena>,
 
+    /// The debug nodes of every call of a replayed invariant sequence, labeled by the call
+    #[serde(skip)]
+    pub debug_calls: Vec<(String, DebugArena)>,
+
     /// pc breakpoint char map
     pub breakpoints: Breakpoints,
+
+    /// The time it took to run the test, not including `setUp`
+    pub duration: Duration,
 }
 
 impl fmt::Display for TestResult {
@@ -2,6 +2,7 @@
 
 use crate::{
     result::{SuiteResult, TestKind, TestResult, TestSetup, TestStatus},
+    stream::TestEvent,
     TestFilter, TestOptions,
 };
 use alloy_json_abi::{Function, JsonAbi};
@@ -19,7 +20,7 @@ use foundry_evm::{
     executors::{
         fuzz::{CaseOutcome, CounterExampleOutcome, FuzzOutcome, FuzzedExecutor},
         invariant::{replay_run, InvariantExecutor, InvariantFuzzError, InvariantFuzzTestResult},
-        CallResult, EvmError, ExecutionErr, Executor,
+        CallResult, EvmError, ExecutionErr, Executor, TestStopper, INTERRUPTED,
     },
     fuzz::{invariant::InvariantContract, CounterExample},
     traces::{load_contracts, TraceKind},
@@ -28,6 +29,7 @@ use proptest::test_runner::{TestError, TestRunner};
 use rayon::prelude::*;
 use std::{
     collections::{BTreeMap, HashMap},
+    sync::mpsc,
     time::Instant,
 };
 
@@ -51,6 +53,12 @@ pub struct ContractRunner<'a> {
     pub sender: Address,
     /// Should generate debug traces
     pub debug: bool,
+    /// Receives the events of the tests as they start and finish
+    pub events: Option<&'a mpsc::Sender<TestEvent>>,
+    /// Stops the run early, no new tests are started once it's stopped
+    pub stopper: TestStopper,
+    /// Whether to stop the run after the first failure
+    pub fail_fast: bool,
 }
 
 impl<'a> ContractRunner<'a> {
@@ -76,6 +84,9 @@ impl<'a> ContractRunner<'a> {
             errors,
             predeploy_libs,
             debug,
+            events: None,
+            stopper: TestStopper::default(),
+            fail_fast: false,
         }
     }
 }
@@ -243,20 +254,30 @@ impl<'a> ContractRunner<'a> {
             )
         }
 
-        let functions: Vec<_> = self.contract.functions().collect();
+        // sorted, so that a single thread runs the tests in the order of their signatures
+        let mut functions: Vec<_> = self.contract.functions().collect();
+        functions.sort_by_cached_key(|func| func.signature());
         let mut test_results = functions
             .par_iter()
             .filter(|&&func| func.is_test() && filter.matches_test(&func.signature()))
-            .map(|&func| {
+            .filter_map(|&func| {
+                if self.stopper.is_stopped() {
+                    return None
+                }
+                self.emit_started(func);
                 let should_fail = func.is_test_fail();
-                let res = if func.is_fuzz_test() {
+                let start = Instant::now();
+                let mut res = if func.is_fuzz_test() {
                     let runner = test_options.fuzz_runner(self.name, &func.name);
                     let fuzz_config = test_options.fuzz_config(self.name, &func.name);
                     self.run_fuzz_test(func, should_fail, runner, setup.clone(), *fuzz_config)
                 } else {
                     self.run_test(func, should_fail, setup.clone())
                 };
-                (func.signature(), res)
+                res.duration = start.elapsed();
+                self.emit_finished(func, &res);
+                self.stop_on_failure(&res);
+                Some((func.signature(), res))
             })
             .collect::<BTreeMap<_, _>>();
 
@@ -265,10 +286,15 @@ impl<'a> ContractRunner<'a> {
             let results: Vec<_> = functions
                 .par_iter()
                 .filter(|&&func| func.is_invariant_test() && filter.matches_test(&func.signature()))
-                .map(|&func| {
+                .filter_map(|&func| {
+                    if self.stopper.is_stopped() {
+                        return None
+                    }
+                    self.emit_started(func);
                     let runner = test_options.invariant_runner(self.name, &func.name);
                     let invariant_config = test_options.invariant_config(self.name, &func.name);
-                    let res = self.run_invariant_test(
+                    let start = Instant::now();
+                    let mut res = self.run_invariant_test(
                         runner,
                         setup.clone(),
                         *invariant_config,
@@ -276,7 +302,10 @@ impl<'a> ContractRunner<'a> {
                         known_contracts,
                         &identified_contracts,
                     );
-                    (func.signature(), res)
+                    res.duration = start.elapsed();
+                    self.emit_finished(func, &res);
+                    self.stop_on_failure(&res);
+                    Some((func.signature(), res))
                 })
                 .collect();
             test_results.extend(results);
@@ -297,6 +326,29 @@ impl<'a> ContractRunner<'a> {
         SuiteResult::new(duration, test_results, warnings)
     }
 
+    /// Sends the event of the started test, if events are streamed.
+    fn emit_started(&self, func: &Function) {
+        if let Some(events) = self.events {
+            let event =
+                TestEvent::TestStarted { suite: self.name.to_string(), test: func.signature() };
+            let _ = events.send(event);
+        }
+    }
+
+    /// Sends the event of the finished test, if events are streamed.
+    fn emit_finished(&self, func: &Function, result: &TestResult) {
+        if let Some(events) = self.events {
+            let _ = events.send(TestEvent::test_finished(self.name, &func.signature(), result));
+        }
+    }
+
+    /// Stops the run if the test failed and the run stops after the first failure.
+    fn stop_on_failure(&self, result: &TestResult) {
+        if self.fail_fast && result.status == TestStatus::Failure {
+            self.stopper.stop();
+        }
+    }
+
     /// Runs a single test
     ///
     /// Calls the given functions and returns the `TestResult`.
@@ -413,6 +465,7 @@ impl<'a> ContractRunner<'a> {
             labeled_addresses,
             debug: debug_arena,
             breakpoints,
+            ..Default::default()
         }
     }
 
@@ -458,36 +511,50 @@ impl<'a> ContractRunner<'a> {
             invariant_config,
             identified_contracts,
             project_contracts,
-        );
+        )
+        .with_stopper(self.stopper.clone());
 
-        let invariant_contract =
-            InvariantContract { address, invariant_function: func, abi: self.contract };
+        let invariant_contract = InvariantContract {
+            address,
+            invariant_function: func,
+            abi: self.contract,
+            errors: self.errors,
+            after_invariant_function: self
+                .contract
+                .functions()
+                .find(|func| func.is_after_invariant() && func.inputs.is_empty()),
+        };
 
-        let InvariantFuzzTestResult { error, cases, reverts, last_run_inputs } = match evm
-            .invariant_fuzz(invariant_contract.clone())
-        {
-            Ok(x) => x,
-            Err(e) => {
-                return TestResult {
-                    status: TestStatus::Failure,
-                    reason: Some(format!("failed to set up invariant testing environment: {e}")),
-                    decoded_logs: decode_console_logs(&logs),
-                    traces,
-                    labeled_addresses,
-                    kind: TestKind::Invariant { runs: 0, calls: 0, reverts: 0 },
-                    ..Default::default()
+        let InvariantFuzzTestResult { error, cases, reverts, last_run_inputs, interrupted } =
+            match evm.invariant_fuzz(invariant_contract.clone()) {
+                Ok(x) => x,
+                Err(e) => {
+                    return TestResult {
+                        status: TestStatus::Failure,
+                        reason: Some(format!(
+                            "failed to set up invariant testing environment: {e}"
+                        )),
+                        decoded_logs: decode_console_logs(&logs),
+                        traces,
+                        labeled_addresses,
+                        kind: TestKind::Invariant { runs: 0, calls: 0, reverts: 0 },
+                        ..Default::default()
+                    }
                 }
-            }
-        };
+            };
 
         let mut counterexample = None;
         let mut logs = logs.clone();
         let mut traces = traces.clone();
-        let success = error.is_none();
-        let reason = error
+        let success = error.is_none() && !interrupted;
+        let mut reason = error
             .as_ref()
             .and_then(|err| (!err.revert_reason.is_empty()).then(|| err.revert_reason.clone()));
+        if interrupted && error.is_none() {
+            reason = Some(INTERRUPTED.to_string());
+        }
         let mut coverage = coverage.clone();
+        let mut debug_calls = Vec::new();
         match error {
             // If invariants were broken, replay the error to collect logs and traces
             Some(error @ InvariantFuzzError { test_error: TestError::Fail(_, _), .. }) => {
@@ -497,6 +564,7 @@ impl<'a> ContractRunner<'a> {
                     identified_contracts.clone(),
                     &mut logs,
                     &mut traces,
+                    self.debug.then_some(&mut debug_calls),
                 ) {
                     Ok(c) => counterexample = c,
                     Err(err) => {
@@ -516,6 +584,7 @@ impl<'a> ContractRunner<'a> {
                     &mut logs,
                     &mut traces,
                     &mut coverage,
+                    self.debug.then_some(&mut debug_calls),
                     func.clone(),
                     last_run_inputs.clone(),
                 );
@@ -541,7 +610,8 @@ impl<'a> ContractRunner<'a> {
             coverage,
             traces,
             labeled_addresses: labeled_addresses.clone(),
-            ..Default::default() // TODO collect debug traces on the last run or error
+            debug_calls,
+            ..Default::default()
         }
     }
 
@@ -561,7 +631,8 @@ impl<'a> ContractRunner<'a> {
         // Run fuzz test
         let start = Instant::now();
         let fuzzed_executor =
-            FuzzedExecutor::new(self.executor.clone(), runner.clone(), self.sender, fuzz_config);
+            FuzzedExecutor::new(self.executor.clone(), runner.clone(), self.sender, fuzz_config)
+                .with_stopper(self.stopper.clone());
         let state = fuzzed_executor.build_fuzz_state();
         let result = fuzzed_executor.fuzz(func, address, should_fail, self.errors);
 
@@ -657,6 +728,7 @@ impl<'a> ContractRunner<'a> {
             labeled_addresses,
             debug,
             breakpoints,
+            ..Default::default()
         }
     }
 }
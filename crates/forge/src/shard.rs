@@ -0,0 +1,352 @@
+//! Splitting the tests of a run into shards, see `forge test --shard`.
+
+use crate::{result::SuiteResult, MultiContractRunner, TestFilter};
+use alloy_primitives::{keccak256, B256};
+use foundry_common::{fs, TestFunctionExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
+
+/// The name of the file in the cache directory the test durations are stored in.
+pub const TEST_TIMINGS_FILENAME: &str = "test-timings.json";
+
+/// The weight of a single run of a test without a recorded duration.
+const RUN_WEIGHT: Duration = Duration::from_millis(1);
+
+/// A shard of the tests, `<index>/<count>` with a 1-based index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct ShardSpec {
+    /// The 1-based index of the shard
+    pub index: usize,
+    /// The number of shards
+    pub count: usize,
+}
+
+impl FromStr for ShardSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("invalid shard `{s}`, expected `<index>/<count>`, e.g. `2/5`");
+        let (index, count) = s.split_once('/').ok_or_else(err)?;
+        let index: usize = index.trim().parse().map_err(|_| err())?;
+        let count: usize = count.trim().parse().map_err(|_| err())?;
+        if index == 0 || index > count {
+            return Err(format!("invalid shard `{s}`, the index must be between 1 and {count}"))
+        }
+        Ok(Self { index, count })
+    }
+}
+
+impl fmt::Display for ShardSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// The tests that belong to a shard.
+#[derive(Clone, Debug)]
+pub struct TestShard {
+    spec: ShardSpec,
+    /// `contract identifier -> test signatures` of the tests in the shard
+    tests: BTreeMap<String, HashSet<String>>,
+    /// The number of matching tests that belong to other shards
+    skipped: usize,
+}
+
+impl TestShard {
+    /// Partitions the tests that match the filter and returns the shard of `spec`.
+    ///
+    /// Without `timings`, a test belongs to the shard given by the hash of its contract identifier
+    /// and signature, so the shards agree on the split no matter which tests they've run before.
+    ///
+    /// The `timings` must be shared by all shards, the tests are then balanced by weight: its
+    /// duration in `timings` or, if none was recorded, its number of runs: `runs` of a fuzz test,
+    /// `runs * depth` of an invariant test and 1 otherwise.
+    pub fn new(
+        spec: ShardSpec,
+        runner: &MultiContractRunner,
+        filter: &dyn TestFilter,
+        timings: Option<&TestTimings>,
+    ) -> Self {
+        let tests = runner
+            .contracts
+            .iter()
+            .filter(|(id, _)| filter.matches_path(&id.source) && filter.matches_contract(&id.name))
+            .flat_map(|(id, (abi, _, _))| {
+                let identifier = id.identifier();
+                abi.functions()
+                    .filter(|func| func.is_test() || func.is_invariant_test())
+                    .filter(|func| filter.matches_test(&func.signature()))
+                    .map(move |func| {
+                        let signature = func.signature();
+                        let recorded =
+                            timings.and_then(|timings| timings.get(&identifier, &signature));
+                        let weight = recorded.unwrap_or_else(|| {
+                            let options = &runner.test_options;
+                            let runs = if func.is_invariant_test() {
+                                let config = options.invariant_config(&identifier, &func.name);
+                                config.runs.saturating_mul(config.depth)
+                            } else if func.is_fuzz_test() {
+                                options.fuzz_config(&identifier, &func.name).runs
+                            } else {
+                                1
+                            };
+                            RUN_WEIGHT * runs
+                        });
+                        (identifier.clone(), signature, weight)
+                    })
+            })
+            .collect::<Vec<_>>();
+        if timings.is_some() {
+            Self::from_weights(spec, tests)
+        } else {
+            Self::from_hashes(spec, tests.into_iter().map(|(contract, test, _)| (contract, test)))
+        }
+    }
+
+    /// Assigns every test to the shard given by the hash of its contract identifier and signature.
+    fn from_hashes(spec: ShardSpec, tests: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut shard = Self { spec, tests: BTreeMap::new(), skipped: 0 };
+        for (contract, test) in tests {
+            let hash = test_hash(&contract, &test);
+            let index = u64::from_be_bytes(hash[..8].try_into().unwrap()) % spec.count as u64;
+            if index as usize + 1 == spec.index {
+                shard.tests.entry(contract).or_default().insert(test);
+            } else {
+                shard.skipped += 1;
+            }
+        }
+        shard
+    }
+
+    /// Assigns the tests heaviest first to the shard with the lowest total weight.
+    ///
+    /// Tests of equal weight are ordered by the hash of their contract identifier and signature,
+    /// so all shards agree on the split as long as they see the same tests and timings.
+    fn from_weights(spec: ShardSpec, tests: Vec<(String, String, Duration)>) -> Self {
+        let mut tests: Vec<(B256, String, String, Duration)> = tests
+            .into_iter()
+            .map(|(contract, test, weight)| (test_hash(&contract, &test), contract, test, weight))
+            .collect();
+        tests.sort_by(|a, b| b.3.cmp(&a.3).then_with(|| a.0.cmp(&b.0)));
+
+        let mut loads = vec![Duration::ZERO; spec.count];
+        let mut shard = Self { spec, tests: BTreeMap::new(), skipped: 0 };
+        for (_, contract, test, weight) in tests {
+            let (index, load) = loads
+                .iter_mut()
+                .enumerate()
+                .min_by_key(|(_, load)| **load)
+                .expect("at least one shard");
+            *load += weight;
+            if index + 1 == spec.index {
+                shard.tests.entry(contract).or_default().insert(test);
+            } else {
+                shard.skipped += 1;
+            }
+        }
+        shard
+    }
+
+    /// Returns the spec of the shard.
+    pub fn spec(&self) -> ShardSpec {
+        self.spec
+    }
+
+    /// Returns true if the shard contains tests of the contract.
+    pub fn contains_contract(&self, contract: &str) -> bool {
+        self.tests.contains_key(contract)
+    }
+
+    /// Returns true if the shard contains the test of the contract.
+    pub fn contains(&self, contract: &str, signature: &str) -> bool {
+        self.tests.get(contract).map_or(false, |tests| tests.contains(signature))
+    }
+
+    /// Returns the number of tests in the shard.
+    pub fn len(&self) -> usize {
+        self.tests.values().map(HashSet::len).sum()
+    }
+
+    /// Returns true if the shard contains no tests.
+    pub fn is_empty(&self) -> bool {
+        self.tests.is_empty()
+    }
+
+    /// Returns the number of matching tests that belong to other shards.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Returns the shard metadata of the JSON output.
+    pub fn metadata(&self) -> ShardMetadata {
+        ShardMetadata { spec: self.spec, tests: self.len(), skipped: self.skipped }
+    }
+}
+
+/// Returns the hash of the test of the contract, which doesn't change across runs and machines.
+fn test_hash(contract: &str, test: &str) -> B256 {
+    keccak256(format!("{contract}:{test}"))
+}
+
+/// The shard of a `forge test --shard --json` run.
+#[derive(Clone, Debug, Serialize)]
+pub struct ShardMetadata {
+    #[serde(flatten)]
+    pub spec: ShardSpec,
+    /// The number of tests in the shard
+    pub tests: usize,
+    /// The number of matching tests that belong to other shards
+    pub skipped: usize,
+}
+
+/// The durations of the tests of previous runs in microseconds, `contract identifier -> test
+/// signature -> duration`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TestTimings(BTreeMap<String, BTreeMap<String, u64>>);
+
+impl TestTimings {
+    /// Reads the timings from the file, returns empty timings if it is missing or invalid.
+    pub fn read(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default()
+        }
+        fs::read_json_file(path).unwrap_or_else(|err| {
+            warn!(?err, ?path, "failed to read test timings");
+            Self::default()
+        })
+    }
+
+    /// Writes the timings to the file.
+    pub fn write(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(fs::write_json_file(path, self)?)
+    }
+
+    /// Returns the recorded duration of the test of the contract.
+    pub fn get(&self, contract: &str, signature: &str) -> Option<Duration> {
+        self.0.get(contract)?.get(signature).map(|micros| Duration::from_micros(*micros))
+    }
+
+    /// Records the durations of the tests of the results, keeps the durations of other tests.
+    pub fn update(&mut self, results: &BTreeMap<String, SuiteResult>) {
+        for (contract, suite) in results {
+            let timings = self.0.entry(contract.clone()).or_default();
+            for (signature, result) in suite.tests() {
+                timings.insert(signature.clone(), result.duration.as_micros() as u64);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weights(tests: &[(&str, &str, u64)]) -> Vec<(String, String, Duration)> {
+        tests
+            .iter()
+            .map(|(contract, test, millis)| {
+                (contract.to_string(), test.to_string(), Duration::from_millis(*millis))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn can_parse_shard_spec() {
+        assert_eq!("2/5".parse::<ShardSpec>().unwrap(), ShardSpec { index: 2, count: 5 });
+        assert_eq!("1/1".parse::<ShardSpec>().unwrap().to_string(), "1/1");
+        assert!("0/5".parse::<ShardSpec>().is_err());
+        assert!("6/5".parse::<ShardSpec>().is_err());
+        assert!("2".parse::<ShardSpec>().is_err());
+        assert!("a/b".parse::<ShardSpec>().is_err());
+    }
+
+    #[test]
+    fn shards_partition_tests() {
+        let tests: Vec<_> = (0..20u64)
+            .map(|i| ("A:T".to_string(), format!("test{i}()"), Duration::from_millis(i % 3)))
+            .collect();
+        let shards: Vec<_> = (1..=3)
+            .map(|index| TestShard::from_weights(ShardSpec { index, count: 3 }, tests.clone()))
+            .collect();
+        for (contract, signature, _) in &tests {
+            let owners = shards.iter().filter(|shard| shard.contains(contract, signature)).count();
+            assert_eq!(owners, 1, "{signature}");
+        }
+        for shard in &shards {
+            assert_eq!(shard.len() + shard.skipped(), tests.len());
+        }
+
+        // the split doesn't depend on the order of the tests
+        let mut reversed = tests;
+        reversed.reverse();
+        let shard = TestShard::from_weights(ShardSpec { index: 2, count: 3 }, reversed);
+        assert_eq!(shard.tests, shards[1].tests);
+    }
+
+    #[test]
+    fn shards_are_split_by_hash() {
+        let tests: Vec<_> = (0..20u64).map(|i| ("A:T".to_string(), format!("test{i}()"))).collect();
+        let shards: Vec<_> = (1..=3)
+            .map(|index| TestShard::from_hashes(ShardSpec { index, count: 3 }, tests.clone()))
+            .collect();
+        for (contract, signature) in &tests {
+            let owners = shards.iter().filter(|shard| shard.contains(contract, signature)).count();
+            assert_eq!(owners, 1, "{signature}");
+        }
+
+        // adding a test doesn't move the others
+        let mut more = tests.clone();
+        more.push(("B:T".to_string(), "testNew()".to_string()));
+        for (index, shard) in shards.iter().enumerate() {
+            let with_new =
+                TestShard::from_hashes(ShardSpec { index: index + 1, count: 3 }, more.clone());
+            for (contract, signature) in &tests {
+                assert_eq!(
+                    with_new.contains(contract, signature),
+                    shard.contains(contract, signature)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shards_are_balanced_by_weight() {
+        let tests = weights(&[
+            ("A:T", "testSlow()", 100),
+            ("A:T", "testA()", 10),
+            ("B:T", "testB()", 10),
+            ("B:T", "testC()", 10),
+        ]);
+        let first = TestShard::from_weights(ShardSpec { index: 1, count: 2 }, tests.clone());
+        let second = TestShard::from_weights(ShardSpec { index: 2, count: 2 }, tests);
+        assert_eq!(first.len(), 1);
+        assert!(first.contains("A:T", "testSlow()"));
+        assert!(!first.contains_contract("B:T"));
+        assert_eq!(second.len(), 3);
+        assert_eq!(second.skipped(), 1);
+    }
+
+    #[test]
+    fn can_update_timings() {
+        let mut timings = TestTimings::default();
+        timings.0.entry("A:T".to_string()).or_default().insert("testOld()".to_string(), 5);
+        let result =
+            crate::result::TestResult { duration: Duration::from_millis(2), ..Default::default() };
+        let suite =
+            SuiteResult::new(Duration::ZERO, [("testNew()".to_string(), result)].into(), vec![]);
+        timings.update(&[("A:T".to_string(), suite)].into());
+        assert_eq!(timings.get("A:T", "testOld()"), Some(Duration::from_micros(5)));
+        assert_eq!(timings.get("A:T", "testNew()"), Some(Duration::from_millis(2)));
+        assert_eq!(timings.get("B:T", "testNew()"), None);
+    }
+}
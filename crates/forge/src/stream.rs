@@ -0,0 +1,212 @@
+//! Events of `forge test --stream-json`.
+//!
+//! Every event is written as a single line of JSON, tagged with its kind in `event` and the
+//! [STREAM_SCHEMA_VERSION] in `version`, e.g.:
+//!
+//! ```json
+//! {"version":1,"event":"test_started","suite":"test/A.t.sol:ATest","test":"testA()"}
+//! ```
+//!
+//! The events of a run are emitted in this order:
+//! - `compilation_started` and `compilation_finished`
+//! - `suite_discovered` for each suite that has tests to run
+//! - `test_started` and `test_finished` for each test, interleaved across suites and tests that run
+//!   in parallel, followed by `suite_finished` once all tests of the suite ran
+//! - `run_finished`
+//!
+//! Fields are only ever added to the events of a version, a breaking change of the schema bumps
+//! the version.
+
+use crate::result::{SuiteResult, TestKindReport, TestResult, TestStatus};
+use foundry_evm::fuzz::CounterExample;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The version of the schema of the streamed events.
+pub const STREAM_SCHEMA_VERSION: u32 = 1;
+
+/// An event of a test run.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TestEvent {
+    /// The compilation of the project started.
+    CompilationStarted,
+    /// The project was compiled successfully, a failed compilation aborts the run.
+    CompilationFinished {
+        /// The duration of the compilation, in milliseconds.
+        duration_ms: u64,
+    },
+    /// A test suite, i.e. a test contract, was discovered.
+    SuiteDiscovered {
+        /// The identifier of the suite, `<path>:<contract>`.
+        suite: String,
+        /// The signatures of the tests that will run.
+        tests: Vec<String>,
+    },
+    /// A test started.
+    TestStarted {
+        /// The identifier of the suite.
+        suite: String,
+        /// The signature of the test.
+        test: String,
+    },
+    /// A test finished.
+    TestFinished {
+        /// The identifier of the suite.
+        suite: String,
+        /// The signature of the test.
+        test: String,
+        /// The status of the test.
+        status: TestStatus,
+        /// The reason of the failure, if any.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+        /// The duration of the test, in milliseconds.
+        duration_ms: u64,
+        /// The gas of the test: the gas of a standard test, the median gas of a fuzz test and
+        /// none for an invariant test.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        gas: Option<u64>,
+        /// The counterexample of a failed fuzz or invariant test.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        counterexample: Option<CounterExample>,
+        /// The arguments of `forge test` which run the test alone and display its traces.
+        trace: Vec<String>,
+    },
+    /// All tests of a suite finished.
+    ///
+    /// If the suite couldn't be set up, e.g. because its `setUp` function reverted, no tests
+    /// were run and the failure is reported as the only failed test of the suite.
+    SuiteFinished {
+        /// The identifier of the suite.
+        suite: String,
+        /// The duration of the suite, in milliseconds.
+        duration_ms: u64,
+        /// The number of tests that passed.
+        passed: usize,
+        /// The number of tests that failed.
+        failed: usize,
+        /// The number of tests that were skipped.
+        skipped: usize,
+        /// The warnings of the suite.
+        warnings: Vec<String>,
+    },
+    /// The run finished.
+    RunFinished {
+        /// The duration of the run, in milliseconds.
+        duration_ms: u64,
+        /// The number of tests that passed.
+        passed: usize,
+        /// The number of tests that failed.
+        failed: usize,
+        /// The number of tests that were skipped.
+        skipped: usize,
+    },
+}
+
+impl TestEvent {
+    /// Returns the event of a finished test.
+    pub fn test_finished(suite: &str, test: &str, result: &TestResult) -> Self {
+        let gas = match result.kind.report() {
+            TestKindReport::Standard { gas } => Some(gas),
+            TestKindReport::Fuzz { median_gas, .. } => Some(median_gas),
+            TestKindReport::Invariant { .. } => None,
+        };
+        let (path, contract) = suite.rsplit_once(':').unwrap_or(("", suite));
+        let name = test.split('(').next().unwrap_or(test);
+        let mut trace = vec!["test".to_string()];
+        if !path.is_empty() {
+            trace.extend(["--match-path".to_string(), path.to_string()]);
+        }
+        trace.extend([
+            "--match-contract".to_string(),
+            format!("^{contract}$"),
+            "--match-test".to_string(),
+            format!("^{name}$"),
+            "-vvvv".to_string(),
+        ]);
+        Self::TestFinished {
+            suite: suite.to_string(),
+            test: test.to_string(),
+            status: result.status,
+            reason: result.reason.clone(),
+            duration_ms: millis(result.duration),
+            gas,
+            counterexample: result.counterexample.clone(),
+            trace,
+        }
+    }
+
+    /// Returns the event of a finished suite.
+    pub fn suite_finished(suite: &str, result: &SuiteResult) -> Self {
+        Self::SuiteFinished {
+            suite: suite.to_string(),
+            duration_ms: millis(result.duration),
+            passed: result.successes().count(),
+            failed: result.failures().count(),
+            skipped: result.skips().count(),
+            warnings: result.warnings.clone(),
+        }
+    }
+
+    /// Returns the line of JSON this event is streamed as.
+    pub fn to_json_line(&self) -> serde_json::Result<String> {
+        #[derive(Serialize)]
+        struct Line<'a> {
+            version: u32,
+            #[serde(flatten)]
+            event: &'a TestEvent,
+        }
+        serde_json::to_string(&Line { version: STREAM_SCHEMA_VERSION, event: self })
+    }
+}
+
+/// Returns the milliseconds of the duration, saturating at `u64::MAX`.
+pub fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::TestKind;
+
+    #[test]
+    fn serializes_versioned_events() {
+        let line =
+            TestEvent::TestStarted { suite: "test/A.t.sol:A".into(), test: "testA()".into() }
+                .to_json_line()
+                .unwrap();
+        assert_eq!(
+            line,
+            r#"{"version":1,"event":"test_started","suite":"test/A.t.sol:A","test":"testA()"}"#
+        );
+
+        let result = TestResult {
+            status: TestStatus::Success,
+            kind: TestKind::Standard(42),
+            duration: Duration::from_millis(7),
+            ..Default::default()
+        };
+        let event = TestEvent::test_finished("test/A.t.sol:A", "testA()", &result);
+        let json: serde_json::Value = serde_json::from_str(&event.to_json_line().unwrap()).unwrap();
+        assert_eq!(json["event"], "test_finished");
+        assert_eq!(json["status"], "Success");
+        assert_eq!(json["duration_ms"], 7);
+        assert_eq!(json["gas"], 42);
+        assert!(json.get("reason").is_none());
+        assert_eq!(
+            json["trace"],
+            serde_json::json!([
+                "test",
+                "--match-path",
+                "test/A.t.sol",
+                "--match-contract",
+                "^A$",
+                "--match-test",
+                "^testA$",
+                "-vvvv"
+            ])
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use foundry_config::{
+    fs_permissions::PathPermission, Config, FsPermissions, SettingsOverrides, VyperConfig,
+};
 use foundry_test_utils::{forgetest, util::OutputExt};
 use std::path::PathBuf;
 
@@ -45,3 +48,173 @@ Compiler run successful!
         assert_eq!(expected, output, "expected: {}, output: {}", expected, output);
     }
 });
+
+// tests that Vyper sources are compiled and can be deployed from Solidity tests
+forgetest_init!(
+    #[ignore = "downloads vyper"]
+    can_build_and_test_vyper,
+    |prj, cmd| {
+        prj.wipe_contracts();
+        let config = Config {
+            vyper: VyperConfig { version: Some("0.3.10".parse().unwrap()), ..Default::default() },
+            fs_permissions: FsPermissions::new(vec![PathPermission::read("./out")]),
+            ..Default::default()
+        };
+        prj.write_config(config);
+
+        std::fs::write(
+            prj.root().join("src/Token.vy"),
+            r"# @version ^0.3.10
+
+totalSupply: public(uint256)
+balanceOf: public(HashMap[address, uint256])
+
+@external
+def __init__(supply: uint256):
+    self.totalSupply = supply
+    self.balanceOf[msg.sender] = supply
+
+@external
+def transfer(to: address, amount: uint256) -> bool:
+    self.balanceOf[msg.sender] -= amount
+    self.balanceOf[to] += amount
+    return True
+",
+        )
+        .unwrap();
+        prj.add_test(
+            "Token.t.sol",
+            r#"
+import "forge-std/Test.sol";
+
+interface IToken {
+    function totalSupply() external view returns (uint256);
+    function balanceOf(address) external view returns (uint256);
+    function transfer(address, uint256) external returns (bool);
+}
+
+contract TokenTest is Test {
+    IToken token;
+
+    function setUp() public {
+        token = IToken(deployCode("Token.vy", abi.encode(1000)));
+    }
+
+    function testTransfer() public {
+        assertEq(token.totalSupply(), 1000);
+        assertTrue(token.transfer(address(1), 100));
+        assertEq(token.balanceOf(address(1)), 100);
+        assertEq(token.balanceOf(address(this)), 900);
+    }
+}
+"#,
+        )
+        .unwrap();
+
+        cmd.args(["build", "--skip", "Token.vy"]);
+        cmd.assert_non_empty_stdout();
+        assert!(!prj.root().join("out/Token.vy/Token.json").exists());
+
+        cmd.forge_fuse().arg("build");
+        assert!(cmd.stdout_lossy().contains("Compiling 1 Vyper files with vyper 0.3.10"));
+        assert!(prj.root().join("out/Token.vy/Token.json").exists());
+
+        cmd.forge_fuse().arg("build");
+        assert!(cmd.stdout_lossy().contains("No Vyper files changed, compilation skipped"));
+
+        cmd.forge_fuse().args(["test", "--match-contract", "TokenTest"]);
+        assert!(cmd.stdout_lossy().contains("[PASS] testTransfer()"));
+    }
+);
+
+// tests that the artifacts are reused after the project is moved and its remappings change
+forgetest_init!(can_build_moved_project_from_cache, |prj, cmd| {
+    cmd.args(["build", "--cache-report"]);
+    let stdout = cmd.stdout_lossy();
+    let line = stdout.lines().find(|line| line.contains("src/Counter.sol")).unwrap();
+    assert!(line.contains("miss: new file"), "{stdout}");
+
+    let moved = tempfile::tempdir().unwrap();
+    prj.copy_to(moved.path());
+    // a remapping that resolves to the same sources
+    std::fs::write(moved.path().join("remappings.txt"), "forge-std-copy/=lib/forge-std/src/\n")
+        .unwrap();
+
+    cmd.forge_fuse().current_dir(moved.path()).args(["build", "--cache-report"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("No files changed, compilation skipped"), "{stdout}");
+    assert!(stdout.contains(" 0 misses"), "{stdout}");
+    assert!(!stdout.contains("miss:"), "{stdout}");
+});
+
+// tests that the files importing a changed file are reported as recompiled
+forgetest_init!(reports_changed_dependencies_in_cache_report, |prj, cmd| {
+    cmd.args(["build"]);
+    cmd.assert_non_empty_stdout();
+
+    let counter = prj.root().join("src/Counter.sol");
+    let source = std::fs::read_to_string(&counter).unwrap();
+    std::fs::write(&counter, format!("{source}\n// changed\n")).unwrap();
+
+    cmd.forge_fuse().args(["build", "--cache-report"]);
+    let stdout = cmd.stdout_lossy();
+    let status = |file: &str| stdout.lines().find(|line| line.contains(file)).unwrap().to_string();
+    assert!(status("src/Counter.sol").contains("miss: content changed"), "{stdout}");
+    // the test imports the counter, the script doesn't
+    assert!(status("test/Counter.t.sol").contains("miss: dependency changed"), "{stdout}");
+    assert!(status("script/Counter.s.sol").contains("hit"), "{stdout}");
+});
+
+// tests that the sources matching a compilation override are compiled with its settings, using the
+// most specific glob
+forgetest!(can_build_with_compilation_overrides, |prj, cmd| {
+    prj.add_source(
+        "core/Vault",
+        r"
+pragma solidity >=0.8.0;
+contract Vault {}
+",
+    )
+    .unwrap();
+    prj.add_source(
+        "Token",
+        r"
+pragma solidity >=0.8.0;
+contract Token {}
+",
+    )
+    .unwrap();
+
+    let via_ir = SettingsOverrides { via_ir: Some(true), ..Default::default() };
+    let no_optimizer = SettingsOverrides { optimizer: Some(false), ..Default::default() };
+    let config = Config {
+        compilation_overrides: [
+            ("src/**".to_string(), no_optimizer.clone()),
+            ("src/core/*.sol".to_string(), via_ir.clone()),
+        ]
+        .into(),
+        ..Default::default()
+    };
+    prj.write_config(config);
+
+    cmd.args(["build", "--sizes"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Vault") && stdout.contains("Token"), "{stdout}");
+
+    let overrides = prj.artifacts().join("overrides");
+    let vault = overrides.join(via_ir.settings_hash()).join("Vault.sol/Vault.json");
+    assert!(vault.exists(), "{}", vault.display());
+    let token = overrides.join(no_optimizer.settings_hash()).join("Token.sol/Token.json");
+    assert!(token.exists(), "{}", token.display());
+    assert!(!prj.artifacts().join("Vault.sol").exists());
+    let (_, stderr) = cmd.unchecked_output_lossy();
+    assert!(!stderr.contains("`compilation_overrides` are only supported"), "{stderr}");
+
+    // the other commands would compile with the settings of the profile, so they fail
+    for args in [&["test"][..], &["script", "src/Token.sol"], &["create", "src/Token.sol:Token"]] {
+        cmd.forge_fuse().args(args);
+        let (_, stderr) = cmd.unchecked_output_lossy();
+        assert!(stderr.contains("`compilation_overrides` are only supported"), "{stderr}");
+        cmd.assert_err();
+    }
+});
@@ -24,3 +24,9 @@ forgetest_init!(can_test_no_cache, |prj, cmd| {
     cmd.forge_fuse().arg("test").assert_success();
     assert!(prj.cache().exists(), "cache file should exist");
 });
+
+forgetest!(can_list_cache_json, |_prj, cmd| {
+    cmd.args(["cache", "ls", "--json"]);
+    let out: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert!(out["chains"].is_array(), "{out}");
+});
@@ -2,7 +2,9 @@
 
 use crate::constants::*;
 use foundry_compilers::{artifacts::Metadata, remappings::Remapping, ConfigurableContractArtifact};
-use foundry_config::{parse_with_profile, BasicConfig, Chain, Config, SolidityErrorCode};
+use foundry_config::{
+    parse_with_profile, BasicConfig, BuildConfig, Chain, Config, SelectorsConfig, SolidityErrorCode,
+};
 use foundry_test_utils::{
     foundry_compilers::PathStyle,
     util::{pretty_err, read_string, OutputExt, TestCommand},
@@ -1417,6 +1419,101 @@ contract ContractThreeTest is DSTest {
     assert!(third_out.contains("foo") && third_out.contains("bar") && third_out.contains("baz"));
 });
 
+forgetest!(gas_report_attributes_delegatecalls_and_filters_functions, |prj, cmd| {
+    prj.insert_ds_test();
+    prj.add_source(
+        "Contracts.sol",
+        r#"
+//SPDX-license-identifier: MIT
+
+import "./test.sol";
+
+contract Vault {
+    uint256 public total;
+
+    function deposit(uint256 amount) public {
+        total += amount;
+    }
+
+    function withdraw(uint256 amount) public {
+        total -= amount;
+    }
+}
+
+contract VaultProxy {
+    address immutable implementation;
+
+    constructor(address _implementation) {
+        implementation = _implementation;
+    }
+
+    fallback() external {
+        address impl = implementation;
+        assembly {
+            calldatacopy(0, 0, calldatasize())
+            let success := delegatecall(gas(), impl, 0, calldatasize(), 0, 0)
+            returndatacopy(0, 0, returndatasize())
+            if iszero(success) { revert(0, returndatasize()) }
+            return(0, returndatasize())
+        }
+    }
+}
+
+contract VaultTest is DSTest {
+    Vault vault;
+
+    function setUp() public {
+        vault = Vault(address(new VaultProxy(address(new Vault()))));
+        vault.deposit(1);
+    }
+
+    function testDeposit() public {
+        vault.deposit(10);
+        vault.withdraw(5);
+    }
+}
+    "#,
+    )
+    .unwrap();
+
+    // returns the `# calls` of the function's row
+    let calls = |out: &str, function: &str| {
+        let row = out.lines().find(|line| line.contains(&format!("| {function} ")))?;
+        row.split('|').map(str::trim).filter(|cell| !cell.is_empty()).last().map(str::to_string)
+    };
+
+    // calls through the proxy are attributed to the implementation
+    prj.write_config(Config { gas_reports: vec!["*".to_string()], ..Default::default() });
+    let out = cmd.args(["test", "--gas-report"]).stdout_lossy();
+    assert!(out.contains("Vault contract"));
+    assert!(!out.contains("VaultProxy contract"));
+    assert_eq!(calls(&out, "deposit").as_deref(), Some("2"));
+    assert_eq!(calls(&out, "withdraw").as_deref(), Some("1"));
+
+    // calls in `setUp()` are ignored
+    let mut config = Config { gas_reports: vec!["*".to_string()], ..Default::default() };
+    config.gas_report.ignore_setup = true;
+    prj.write_config(config);
+    let out = cmd.forge_fuse().args(["test", "--gas-report"]).stdout_lossy();
+    assert_eq!(calls(&out, "deposit").as_deref(), Some("1"));
+
+    // only the included functions are reported
+    let mut config = Config { gas_reports: vec!["*".to_string()], ..Default::default() };
+    config.gas_report.include = vec!["Vault.dep*".to_string()];
+    prj.write_config(config);
+    let out = cmd.forge_fuse().args(["test", "--gas-report"]).stdout_lossy();
+    assert_eq!(calls(&out, "deposit").as_deref(), Some("2"));
+    assert_eq!(calls(&out, "withdraw"), None);
+
+    // excluded functions are not reported
+    let mut config = Config { gas_reports: vec!["*".to_string()], ..Default::default() };
+    config.gas_report.exclude = vec!["Vault.deposit".to_string()];
+    prj.write_config(config);
+    let out = cmd.forge_fuse().args(["test", "--gas-report"]).stdout_lossy();
+    assert_eq!(calls(&out, "deposit"), None);
+    assert_eq!(calls(&out, "withdraw").as_deref(), Some("1"));
+});
+
 forgetest_init!(can_use_absolute_imports, |prj, cmd| {
     let remapping = prj.paths().libraries[0].join("myDependency");
     let config = Config {
@@ -1516,6 +1613,31 @@ forgetest_init!(can_bind, |_prj, cmd| {
     cmd.assert_non_empty_stdout();
 });
 
+// checks forge bind generates deterministic alloy bindings
+forgetest_init!(can_bind_alloy, |prj, cmd| {
+    cmd.args(["bind", "--alloy"]);
+    cmd.assert_non_empty_stdout();
+
+    let bindings = prj.paths().artifacts.join("bindings");
+    let cargo_toml = fs::read_to_string(bindings.join("Cargo.toml")).unwrap();
+    assert!(cargo_toml.contains(r#"alloy-sol-types = "0.6""#), "{cargo_toml}");
+    let lib = fs::read_to_string(bindings.join("src/lib.rs")).unwrap();
+    assert!(lib.contains("pub mod counter;"), "{lib}");
+    let counter = fs::read_to_string(bindings.join("src/counter.rs")).unwrap();
+    assert!(counter.contains("alloy_sol_types::sol! {"), "{counter}");
+    assert!(counter.contains("interface Counter {"), "{counter}");
+    assert!(counter.contains("pub static BYTECODE: alloy_primitives::Bytes"), "{counter}");
+
+    // the regenerated bindings match the existing ones
+    cmd.forge_fuse().args(["bind", "--alloy"]);
+    assert!(cmd.stdout_lossy().contains("OK."));
+
+    // bindings of removed contracts fail the consistency check
+    fs::write(bindings.join("src/removed.rs"), "").unwrap();
+    cmd.forge_fuse().args(["bind", "--alloy"]);
+    cmd.assert_err();
+});
+
 // checks missing dependencies are auto installed
 forgetest_init!(can_install_missing_deps_test, |prj, cmd| {
     // wipe forge-std
@@ -1596,6 +1718,45 @@ forgetest_init!(can_build_sizes_repeatedly, |prj, cmd| {
     assert!(unchanged.contains(table), "{}", table);
 });
 
+// checks that build --sizes --json outputs the sizes and margins of all contracts
+forgetest_init!(can_build_sizes_json, |prj, cmd| {
+    prj.clear_cache();
+
+    cmd.args(["build", "--sizes", "--json"]);
+    let out = cmd.stdout_lossy();
+    let sizes: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+    let counter = &sizes[TEMPLATE_CONTRACT];
+    let runtime_size = counter["runtime_size"].as_u64().unwrap();
+    assert!(runtime_size > 0);
+    assert_eq!(counter["runtime_margin"].as_u64().unwrap(), 24576 - runtime_size);
+    assert!(counter["init_size"].as_u64().unwrap() > runtime_size);
+});
+
+// checks that build --sizes fails if a contract violates the configured size limit margin
+forgetest_init!(can_fail_build_sizes_margin, |prj, cmd| {
+    prj.clear_cache();
+    let config =
+        Config { build: BuildConfig { size_limit_margin: Some(24576) }, ..Default::default() };
+    prj.write_config(config);
+
+    cmd.args(["build", "--sizes"]);
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    let err = String::from_utf8_lossy(&output.stderr);
+    assert!(err.contains("Contracts within 24576 bytes of the code size limits"), "{err}");
+    assert!(err.contains(TEMPLATE_CONTRACT), "{err}");
+
+    // the margin also applies without printing the sizes
+    prj.clear_cache();
+    cmd.forge_fuse().arg("build");
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    let err = String::from_utf8_lossy(&output.stderr);
+    assert!(err.contains("Contracts within 24576 bytes of the code size limits"), "{err}");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Initcode Margin (kB)"));
+});
+
 // checks that build --names includes all contracts even if unchanged
 forgetest_init!(can_build_names_repeatedly, |prj, cmd| {
     prj.clear_cache();
@@ -1612,6 +1773,104 @@ forgetest_init!(can_build_names_repeatedly, |prj, cmd| {
     assert!(unchanged.contains(list), "{}", list);
 });
 
+// checks that `forge build <target>` only compiles the target and its imports, and keeps all other
+// artifacts
+forgetest!(can_build_targets, |prj, cmd| {
+    prj.add_source(
+        "VaultMath",
+        r"
+library VaultMath {
+    function double(uint256 x) internal pure returns (uint256) {
+        return x * 2;
+    }
+}
+",
+    )
+    .unwrap();
+    prj.add_source(
+        "Vault",
+        r#"
+import {VaultMath} from "./VaultMath.sol";
+contract Vault {
+    function double(uint256 x) external pure returns (uint256) {
+        return VaultMath.double(x);
+    }
+}
+"#,
+    )
+    .unwrap();
+    let add_others = |version: u32| {
+        for i in 0..30 {
+            prj.add_source(
+                &format!("Other{i}"),
+                &format!(
+                    r"
+contract Other{i} {{
+    uint256 public value = {version};
+    function set(uint256 x) external {{ value = x * {i}; }}
+}}
+"
+                ),
+            )
+            .unwrap();
+        }
+    };
+    add_others(1);
+
+    // only the target and its imports end up in the artifacts
+    cmd.args(["build", "src/Vault.sol"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("Compiling 2 files"), "{out}");
+    let mut artifacts = fs::read_dir(&prj.paths().artifacts)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    artifacts.sort();
+    assert_eq!(artifacts, ["Vault.sol", "VaultMath.sol"]);
+
+    cmd.forge_fuse().arg("build");
+    cmd.assert_non_empty_stdout();
+
+    let other_artifact = prj.paths().artifacts.join("Other0.sol/Other0.json");
+    let other_modified = fs::metadata(&other_artifact).unwrap().modified().unwrap();
+
+    // touch everything, but only build the vault by path
+    add_others(2);
+    prj.add_source(
+        "Vault",
+        r#"
+import {VaultMath} from "./VaultMath.sol";
+contract Vault {
+    function quadruple(uint256 x) external pure returns (uint256) {
+        return VaultMath.double(VaultMath.double(x));
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.forge_fuse().args(["build", "src/Vault.sol"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("Compiling 2 files"), "{out}");
+    let vault: serde_json::Value =
+        serde_json::from_str(&read_string(prj.paths().artifacts.join("Vault.sol/Vault.json")))
+            .unwrap();
+    assert!(vault["methodIdentifiers"].get("quadruple(uint256)").is_some());
+
+    // artifacts outside of the target closure are kept as is
+    assert!(other_artifact.exists());
+    assert_eq!(fs::metadata(&other_artifact).unwrap().modified().unwrap(), other_modified);
+
+    // contracts can also be referenced by name
+    cmd.forge_fuse().args(["build", "Other3"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("Compiling 1 files"), "{out}");
+    assert!(other_artifact.exists());
+
+    cmd.forge_fuse().args(["build", "Missing"]);
+    cmd.assert_err();
+});
+
 // <https://github.com/foundry-rs/foundry/issues/6816>
 forgetest_init!(can_inspect_counter_pretty, |prj, cmd| {
     cmd.args(["inspect", "src/Counter.sol:Counter", "abi", "--pretty"]);
@@ -1625,3 +1884,215 @@ forgetest_init!(can_inspect_counter_pretty, |prj, cmd| {
 }"
     );
 });
+
+forgetest_init!(can_compare_storage_layouts, |prj, cmd| {
+    prj.add_source(
+        "Vault.sol",
+        r#"
+contract Ownable {
+    address public owner;
+}
+
+contract Pausable {
+    bool public paused;
+}
+
+contract VaultV1 is Ownable, Pausable {
+    struct Position {
+        address owner;
+        bool active;
+        uint256 amount;
+    }
+
+    mapping(address => Position) public positions;
+    uint256[50] private __gap;
+}
+
+contract VaultV2 is Ownable, Pausable {
+    struct Position {
+        address owner;
+        bool active;
+        uint64 expiry;
+        uint256 amount;
+    }
+
+    mapping(address => Position) public positions;
+    uint256 public fee;
+    uint256[49] private __gap;
+}
+
+contract VaultV3 is Pausable, Ownable {
+    struct Position {
+        address owner;
+        bool active;
+        uint256 amount;
+    }
+
+    mapping(address => Position) public positions;
+    uint256[50] private __gap;
+}
+"#,
+    )
+    .unwrap();
+
+    // a packed struct member, a variable added in the gap
+    cmd.args(["inspect", "VaultV2", "storage-layout", "--compare", "VaultV1"]);
+    let diff: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(diff["compatible"], true, "{diff}");
+    let changes: Vec<_> = diff["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|change| (change["kind"].as_str().unwrap(), change["severity"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        changes,
+        [("gapShrunk", "info"), ("addedInGap", "info"), ("appended", "info")],
+        "{diff}"
+    );
+
+    // reordered base contracts
+    cmd.forge_fuse().args(["inspect", "VaultV3", "storage", "--compare", "VaultV1", "--pretty"]);
+    cmd.assert_err();
+    let (out, _) = cmd.unchecked_output_lossy();
+    assert!(out.contains("The storage layouts are incompatible: 2 errors."), "{out}");
+    assert!(out.contains("address at slot 0 offset 1"), "{out}");
+
+    cmd.forge_fuse().args(["inspect", "VaultV1", "abi", "--compare", "VaultV2"]);
+    cmd.assert_err();
+});
+
+forgetest_init!(can_generate_eip712_types, |prj, cmd| {
+    prj.add_source(
+        "Mail.sol",
+        r#"
+struct Person {
+    string name;
+    address wallet;
+}
+
+contract Mailbox {
+    struct Mail {
+        Person from;
+        Person to;
+        string contents;
+    }
+
+    struct Inbox {
+        mapping(address => Mail[]) mails;
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["eip712", "src/Mail.sol", "--json"]);
+    let encodings: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(
+        encodings,
+        serde_json::json!([
+            {
+                "name": "Person",
+                "encodeType": "Person(string name,address wallet)",
+                "typeHash": "0xb9d8c78acf9b987311de6c7b45bb6a9c8e1bf361fa7fd3467a2163f994c79500"
+            },
+            {
+                "name": "Mail",
+                "encodeType": "Mail(Person from,Person to,string contents)Person(string name,address wallet)",
+                "typeHash": "0xa0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2"
+            }
+        ])
+    );
+
+    cmd.forge_fuse().args(["eip712", "src/Mail.sol:Mailbox.Inbox"]);
+    cmd.assert_err();
+    let (_, err) = cmd.unchecked_output_lossy();
+    assert!(err.contains("mappings are not supported by EIP-712"), "{err}");
+
+    // the generated library matches the example of the EIP
+    cmd.forge_fuse().args(["eip712", "src/Mail.sol:Mail", "--library"]);
+    prj.add_raw_source("EIP712Hashes.sol", &cmd.stdout_lossy()).unwrap();
+    prj.add_test(
+        "EIP712Hashes.t.sol",
+        r#"
+import "forge-std/Test.sol";
+import "src/EIP712Hashes.sol";
+
+contract EIP712HashesTest is Test {
+    function testHashStruct() public {
+        Mailbox.Mail memory mail = Mailbox.Mail({
+            from: Person({name: "Cow", wallet: 0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826}),
+            to: Person({name: "Bob", wallet: 0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB}),
+            contents: "Hello, Bob!"
+        });
+        assertEq(
+            EIP712Hashes.hashStruct(mail),
+            0xc52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e
+        );
+    }
+}
+"#,
+    )
+    .unwrap();
+    cmd.forge_fuse().args(["test", "--mc", "EIP712HashesTest"]);
+    assert!(cmd.stdout_lossy().contains("[PASS] testHashStruct()"));
+});
+
+forgetest_init!(can_check_selector_collisions, |prj, cmd| {
+    prj.add_source(
+        "Facets.sol",
+        r#"
+contract OwnershipFacet {
+    function owner() external view returns (address) {}
+}
+
+contract TokenFacet {
+    function owner() external view returns (address) {}
+}
+
+contract Burner {
+    error burn(uint256);
+}
+
+contract Storage {
+    error collate_propagate_storage(bytes16);
+}
+
+contract Colliding is Burner, Storage {}
+"#,
+    )
+    .unwrap();
+
+    // `Colliding` inherits two errors with the selector `0x42966c68`, which solc doesn't reject
+    cmd.args(["selectors", "check"]);
+    let (out, err) = cmd.unchecked_output_lossy();
+    assert!(err.contains("selector collisions found"), "{err}");
+    assert!(out.contains("1 collisions found"), "{out}");
+    assert!(out.contains("0x42966c68"), "{out}");
+
+    prj.add_source(
+        "Facets.sol",
+        r#"
+contract OwnershipFacet {
+    function owner() external view returns (address) {}
+}
+
+contract TokenFacet {
+    function owner() external view returns (address) {}
+}
+"#,
+    )
+    .unwrap();
+    assert!(cmd.stdout_lossy().contains("No selector collisions found"));
+
+    prj.write_config(Config {
+        selectors: SelectorsConfig {
+            facets: vec![vec!["OwnershipFacet".to_string(), "TokenFacet".to_string()]],
+        },
+        ..Default::default()
+    });
+    cmd.assert_err();
+    let (out, _) = cmd.unchecked_output_lossy();
+    assert!(out.contains("0x8da5cb5b"), "{out}");
+    assert!(out.contains("OwnershipFacet, TokenFacet"), "{out}");
+});
@@ -14,7 +14,7 @@ use foundry_test_utils::{
 };
 use path_slash::PathBufExt;
 use pretty_assertions::assert_eq;
-use std::{fs, path::PathBuf, str::FromStr};
+use std::{collections::BTreeMap, fs, path::PathBuf, str::FromStr};
 
 // tests all config values that are in use
 forgetest!(can_extract_config_values, |prj, cmd| {
@@ -30,14 +30,18 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         cache: true,
         cache_path: "test-cache".into(),
         broadcast: "broadcast".into(),
+        deployments: "test-deployments".into(),
+        save_deployments: true,
         force: true,
         evm_version: EvmVersion::Byzantium,
         gas_reports: vec!["Contract".to_string()],
         gas_reports_ignore: vec![],
+        gas_report: Default::default(),
         solc: Some(SolcReq::Local(PathBuf::from("custom-solc"))),
         auto_detect_solc: false,
         auto_detect_remappings: true,
         offline: true,
+        threads: None,
         optimizer: false,
         optimizer_runs: 1000,
         optimizer_details: Some(OptimizerDetails {
@@ -64,6 +68,8 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         },
         invariant: InvariantConfig { runs: 256, ..Default::default() },
         ffi: true,
+        allowed_ffi: vec!["python3 scripts/".to_string()],
+        sensitive_env: vec!["*_TOKEN".to_string()],
         sender: "00a329c0648769A73afAc7F9381D08FB43dBEA72".parse().unwrap(),
         tx_origin: "00a329c0648769A73afAc7F9F81E08FB43dBEA72".parse().unwrap(),
         initial_balance: U256::from(0xffffffffffffffffffffffffu128),
@@ -80,10 +86,12 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         block_prevrandao: B256::random(),
         block_gas_limit: Some(100u64.into()),
         memory_limit: 1 << 27,
+        isolate: true,
         eth_rpc_url: Some("localhost".to_string()),
         eth_rpc_jwt: None,
         etherscan_api_key: None,
         etherscan: Default::default(),
+        verifiers: Default::default(),
         verbosity: 4,
         remappings: vec![Remapping::from_str("forge-std=lib/forge-std/").unwrap().into()],
         libraries: vec![
@@ -92,6 +100,7 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         ignored_error_codes: vec![],
         deny_warnings: false,
         via_ir: true,
+        compilation_overrides: Default::default(),
         rpc_storage_caching: StorageCachingConfig {
             chains: CachedChains::None,
             endpoints: CachedEndpoints::Remote,
@@ -108,8 +117,17 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         rpc_endpoints: Default::default(),
         build_info: false,
         build_info_path: None,
+        build: Default::default(),
+        selectors: Default::default(),
+        chisel: Default::default(),
         fmt: Default::default(),
         doc: Default::default(),
+        lint: Default::default(),
+        hooks: Default::default(),
+        vyper: Default::default(),
+        rpc: Default::default(),
+        wallets: Default::default(),
+        coverage: Default::default(),
         fs_permissions: Default::default(),
         labels: Default::default(),
         cancun: true,
@@ -610,6 +628,31 @@ forgetest!(config_emit_warnings, |prj, cmd| {
     );
 });
 
+// test that included config files are merged and that their values are attributed to them
+forgetest!(can_show_config_origins, |prj, cmd| {
+    fs::write(
+        prj.root().join("foundry.shared.toml"),
+        "[profile.default]\noptimizer_runs = 1337\nsrc = 'shared-src'",
+    )
+    .unwrap();
+    fs::write(
+        prj.root().join("foundry.toml"),
+        "include = ['foundry.shared.toml']\n[profile.default]\nsrc = 'local-src'",
+    )
+    .unwrap();
+
+    let config = cmd.config();
+    assert_eq!(config.optimizer_runs, 1337);
+    assert_eq!(config.src, PathBuf::from("local-src"));
+
+    cmd.forge_fuse().args(["config", "--origins", "--json"]);
+    let origins: BTreeMap<String, String> = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert!(origins["optimizer_runs"].ends_with("foundry.shared.toml"), "{origins:?}");
+    assert!(origins["src"].ends_with("foundry.toml"), "{origins:?}");
+    assert!(!origins["src"].ends_with("foundry.shared.toml"), "{origins:?}");
+    assert_eq!(origins["via_ir"], "Foundry Config");
+});
+
 forgetest_init!(can_skip_remappings_auto_detection, |prj, cmd| {
     // explicitly set remapping and libraries
     let config = Config {
@@ -1,3 +1,4 @@
+use foundry_config::{Config, CoverageConfig};
 use regex::Regex;
 
 forgetest!(basic_coverage, |_prj, cmd| {
@@ -78,3 +79,114 @@ contract AContractTest is DSTest {
         .unwrap() >
         0)));
 });
+
+forgetest!(coverage_exclusions, |prj, cmd| {
+    prj.insert_ds_test();
+    prj.write_config(Config {
+        coverage: CoverageConfig {
+            exclude: vec!["src/vendor/**".to_string()],
+            include: vec!["src/vendor/Kept.sol".to_string()],
+            exclude_contracts: vec!["*Mock*".to_string()],
+        },
+        ..Default::default()
+    });
+    prj.add_source(
+        "Counter.sol",
+        r#"
+contract Counter {
+    uint256 public number;
+
+    function increment() public {
+        number++;
+    }
+
+    /// forge-coverage: ignore
+    function debugReset() public {
+        number = 0;
+    }
+}
+    "#,
+    )
+    .unwrap();
+    prj.add_source(
+        "CounterMock.sol",
+        r#"
+contract CounterMock {
+    function mocked() public pure returns (uint256) {
+        return 1;
+    }
+}
+    "#,
+    )
+    .unwrap();
+    prj.add_source(
+        "vendor/Vendored.sol",
+        r#"
+contract Vendored {
+    function vendored() public pure returns (uint256) {
+        return 2;
+    }
+}
+    "#,
+    )
+    .unwrap();
+    prj.add_source(
+        "vendor/Kept.sol",
+        r#"
+contract Kept {
+    function kept() public pure returns (uint256) {
+        return 3;
+    }
+}
+    "#,
+    )
+    .unwrap();
+    prj.add_source(
+        "CounterTest.sol",
+        r#"
+import "./test.sol";
+import {Counter} from "./Counter.sol";
+import {CounterMock} from "./CounterMock.sol";
+import {Vendored} from "./vendor/Vendored.sol";
+
+contract CounterTest is DSTest {
+    function testIncrement() public {
+        Counter counter = new Counter();
+        counter.increment();
+        new CounterMock().mocked();
+        new Vendored().vendored();
+    }
+}
+    "#,
+    )
+    .unwrap();
+
+    let lcov_info = prj.root().join("lcov.info");
+    cmd.arg("coverage").args([
+        "--report".to_string(),
+        "lcov".to_string(),
+        "--report-file".to_string(),
+        lcov_info.to_str().unwrap().to_string(),
+    ]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Excluded from the report:"), "{stdout}");
+
+    let lcov_data = std::fs::read_to_string(lcov_info).unwrap();
+    assert!(lcov_data.contains("Counter.increment"), "{lcov_data}");
+    assert!(!lcov_data.contains("Counter.debugReset"), "{lcov_data}");
+    assert!(!lcov_data.contains("CounterMock.sol"), "{lcov_data}");
+    assert!(!lcov_data.contains("Vendored.sol"), "{lcov_data}");
+    assert!(lcov_data.contains("SF:src/vendor/Kept.sol"), "{lcov_data}");
+
+    // the exclusions apply to the bytecode and debug reports too
+    cmd.forge_fuse().args(["coverage", "--report", "bytecode", "--report", "debug"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Anchors for Contract \"Counter\""), "{stdout}");
+    assert!(!stdout.contains("CounterMock"), "{stdout}");
+    assert!(!stdout.contains("Vendored"), "{stdout}");
+    assert!(!stdout.contains("debugReset"), "{stdout}");
+    let bytecode_coverage = prj.root().join("bytecode-coverage");
+    assert!(bytecode_coverage.join("Counter.asm").exists());
+    assert!(!bytecode_coverage.join("CounterMock.asm").exists());
+    assert!(!bytecode_coverage.join("Vendored.asm").exists());
+});
@@ -324,3 +324,82 @@ contract UniswapV2Swap {
         assert!(stdout.contains("Deployed to: 0x5FbDB2315678afecb367f032d93F642f64180aa3"));
     }
 );
+
+// tests that constructor args are coerced to the constructor's types when read from a json file
+forgetest_async!(
+    #[serial_test::serial]
+    can_create_with_json_constructor_args,
+    |prj, cmd| {
+        foundry_test_utils::util::initialize(prj.root());
+
+        let (_api, handle) = spawn(NodeConfig::test()).await;
+        let rpc = handle.http_endpoint();
+        let wallet = handle.dev_wallets().next().unwrap();
+        let pk = hex::encode(wallet.signer().to_bytes());
+
+        prj.add_source(
+            "JsonConstructorContract",
+            r#"
+struct Config {
+    uint256 threshold;
+    string label;
+}
+
+contract JsonConstructorContract {
+    constructor(address[] memory owners, Config memory config) {
+        require(owners.length == 2);
+        require(owners[1] == 0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed);
+        require(config.threshold == 2);
+        require(keccak256(bytes(config.label)) == keccak256("gm"));
+    }
+}
+"#,
+        )
+        .unwrap();
+
+        let args = prj.root().join("args.json");
+        let create = |cmd: &mut TestCommand| {
+            cmd.forge_fuse().args([
+                "create",
+                "./src/JsonConstructorContract.sol:JsonConstructorContract",
+                "--rpc-url",
+                rpc.as_str(),
+                "--private-key",
+                pk.as_str(),
+                "--constructor-args-path",
+                args.to_str().unwrap(),
+            ]);
+        };
+
+        std::fs::write(
+            &args,
+            r#"[
+                ["0x0000000000000000000000000000000000000001",
+                 "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"],
+                [2, "gm"]
+            ]"#,
+        )
+        .unwrap();
+        create(&mut cmd);
+        let (stdout, _) = cmd.output_lossy();
+        assert!(stdout.contains("Deployed to:"), "{stdout}");
+
+        std::fs::write(
+            &args,
+            r#"{
+                "config": { "threshold": "2", "label": "gm" },
+                "owners": ["0x0000000000000000000000000000000000000001",
+                           "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"]
+            }"#,
+        )
+        .unwrap();
+        create(&mut cmd);
+        let (stdout, _) = cmd.output_lossy();
+        assert!(stdout.contains("Deployed to:"), "{stdout}");
+
+        std::fs::write(&args, r#"{ "config": [2, "gm"], "owners": ["0x01"] }"#).unwrap();
+        create(&mut cmd);
+        let stderr = cmd.stderr_lossy();
+        assert!(stderr.contains("$.owners[0]: could not parse `0x01` as address"), "{stderr}");
+    }
+);
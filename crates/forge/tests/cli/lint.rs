@@ -0,0 +1,95 @@
+use foundry_config::{Config, LintConfig, LintSeverity};
+
+const VAULT: &str = r#"
+import {Ownable, Pausable} from "./Ownable.sol";
+
+contract Vault is Ownable {
+    function withdraw() external {
+        require(tx.origin == owner);
+    }
+}
+"#;
+
+const OWNABLE: &str = r"
+contract Ownable {
+    address owner;
+}
+
+contract Pausable {}
+";
+
+forgetest!(can_lint_and_fix_unused_imports, |prj, cmd| {
+    prj.add_source("Ownable", OWNABLE).unwrap();
+    let vault = prj.add_source("Vault", VAULT).unwrap();
+
+    cmd.args(["lint", "--format", "json"]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    let findings: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let rules = findings
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|finding| (finding["rule"].as_str().unwrap(), finding["file"].as_str().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(rules, [("unused-import", "src/Vault.sol"), ("tx-origin", "src/Vault.sol")]);
+    // `tx-origin` is an error
+    cmd.assert_err();
+
+    cmd.forge_fuse().args(["lint", "--fix"]);
+    cmd.assert_err();
+    let fixed = std::fs::read_to_string(vault).unwrap();
+    assert!(fixed.contains(r#"import {Ownable} from "./Ownable.sol";"#), "{fixed}");
+
+    // the remaining finding is only a warning
+    let lint = LintConfig {
+        severity: [("tx-origin".to_string(), LintSeverity::Warning)].into(),
+        ..Default::default()
+    };
+    prj.write_config(Config { lint, ..Default::default() });
+    cmd.forge_fuse().args(["lint"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("warning[tx-origin]"), "{stdout}");
+    assert!(stdout.contains("src/Vault.sol:8:17"), "{stdout}");
+});
+
+forgetest!(can_lint_to_sarif, |prj, cmd| {
+    prj.add_source("Ownable", OWNABLE).unwrap();
+    prj.add_source("Vault", VAULT).unwrap();
+
+    cmd.args(["lint", "--format", "sarif"]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    let sarif: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(sarif["version"], "2.1.0");
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[1]["ruleId"], "tx-origin");
+    assert_eq!(results[1]["level"], "error");
+    let location = &results[1]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/Vault.sol");
+    assert_eq!(location["region"]["startLine"], 8);
+});
+
+forgetest!(build_fails_on_lint_findings, |prj, cmd| {
+    prj.add_source("Ownable", OWNABLE).unwrap();
+    prj.add_source("Vault", VAULT).unwrap();
+
+    // the sources are only linted if `fail_on` is set
+    cmd.arg("build");
+    cmd.assert_success();
+
+    let lint = LintConfig { fail_on: Some(LintSeverity::Error), ..Default::default() };
+    prj.write_config(Config { lint, ..Default::default() });
+    cmd.forge_fuse().arg("build");
+    let stderr = cmd.stderr_lossy();
+    assert!(stderr.contains("error[tx-origin]"), "{stderr}");
+    cmd.assert_err();
+
+    let lint = LintConfig {
+        fail_on: Some(LintSeverity::Error),
+        ignore: vec!["src/Vault.sol".to_string()],
+        ..Default::default()
+    };
+    prj.write_config(Config { lint, ..Default::default() });
+    cmd.forge_fuse().arg("build");
+    cmd.assert_success();
+});
@@ -12,6 +12,7 @@ mod coverage;
 mod create;
 mod debug;
 mod doc;
+mod lint;
 mod multi_script;
 mod script;
 mod svm;
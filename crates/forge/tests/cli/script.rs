@@ -1,13 +1,16 @@
 //! Contains various tests related to `forge script`.
 
 use crate::constants::TEMPLATE_CONTRACT;
-use alloy_primitives::{Address, Bytes};
+use alloy_primitives::{Address, Bytes, U256};
 use anvil::{spawn, NodeConfig};
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_providers::Middleware;
 use foundry_common::rpc;
-use foundry_test_utils::{util::OutputExt, ScriptOutcome, ScriptTester};
+use foundry_config::{fs_permissions::PathPermission, Config, FsPermissions};
+use foundry_test_utils::{util::OutputExt, ScriptOutcome, ScriptTester, TestCommand};
 use regex::Regex;
 use serde_json::Value;
-use std::{env, path::PathBuf, str::FromStr};
+use std::{env, path::PathBuf, str::FromStr, time::Duration};
 
 // Tests that fork cheat codes can be used in script
 forgetest_init!(
@@ -513,6 +516,123 @@ forgetest_async!(can_resume_script, |prj, cmd| {
         .await;
 });
 
+// tests that a sequence isn't broadcasted if a sender can't afford its transactions
+forgetest_async!(fails_broadcast_without_balance, |prj, cmd| {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let mut tester = ScriptTester::new_broadcast(cmd, &handle.http_endpoint(), prj.root());
+    api.anvil_set_balance(tester.accounts_pub[1], U256::from(1)).await.unwrap();
+
+    tester
+        .load_private_keys(&[0, 1])
+        .await
+        .add_sig("BroadcastTest", "deploy()")
+        .simulate(ScriptOutcome::OkSimulation)
+        .broadcast(ScriptOutcome::BalanceCheckFailed)
+        .assert_nonce_increment(&[(0, 0), (1, 0)])
+        .await;
+});
+
+// tests that a broadcast sender without a signer is reported before the transactions are
+// simulated on chain, so that none of them is sent
+forgetest_async!(fails_broadcast_without_signer, |prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let mut tester = ScriptTester::new_broadcast(cmd, &handle.http_endpoint(), prj.root());
+
+    // `deploy()` broadcasts from the accounts 0 and 1, only the first one has a signer
+    let unconfigured = tester.accounts_pub[1];
+    tester
+        .load_private_keys(&[0])
+        .await
+        .load_addresses(&[unconfigured])
+        .await
+        .add_sig("BroadcastTest", "deploy()")
+        .broadcast(ScriptOutcome::MissingSigner)
+        .assert_nonce_increment(&[(0, 0)])
+        .await
+        .assert_nonce_increment_addresses(&[(unconfigured, 0)])
+        .await;
+
+    // nothing was sent, so no sequence was saved
+    assert!(!prj.root().join("broadcast/Broadcast.t.sol/31337/deploy-latest.json").exists());
+});
+
+// tests that a sequence isn't broadcasted if the state of the chain changed since the simulation
+forgetest_async!(fails_broadcast_on_simulation_drift, |prj, cmd| {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let mut tester = ScriptTester::new_broadcast(cmd, &handle.http_endpoint(), prj.root());
+
+    tester
+        .load_private_keys(&[0, 1])
+        .await
+        .add_sig("BroadcastTest", "deploy()")
+        .simulate(ScriptOutcome::OkSimulation);
+
+    // the first transaction is a deployment, whose address changes with the nonce of the sender
+    api.anvil_set_nonce(tester.accounts_pub[0], U256::from(5)).await.unwrap();
+    tester.resume(ScriptOutcome::DriftCheckFailed).assert_nonce_increment(&[(0, 5), (1, 0)]).await;
+});
+
+// tests that resuming with a new sender waits for the transactions the original sender already
+// sent, so that none of them lands twice
+forgetest_async!(can_resume_script_with_new_sender_after_pending_tx, |prj, cmd| {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let mut tester = ScriptTester::new_broadcast(cmd, &handle.http_endpoint(), prj.root());
+
+    tester
+        .add_deployer(0)
+        .load_private_keys(&[0])
+        .await
+        .add_sig("BroadcastTest", "deployNoArgs()")
+        .simulate(ScriptOutcome::OkSimulation);
+
+    // send the first transaction of the sequence from the original sender, without mining it
+    let sequence_path =
+        prj.root().join("broadcast/Broadcast.t.sol/31337/dry-run/deployNoArgs-latest.json");
+    let mut sequence: Value =
+        serde_json::from_str(&std::fs::read_to_string(&sequence_path).unwrap()).unwrap();
+    let tx: TypedTransaction =
+        serde_json::from_value(sequence["transactions"][0]["transaction"].clone()).unwrap();
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let tx_hash = handle.ethers_http_provider().send_transaction(tx, None).await.unwrap().tx_hash();
+    sequence["transactions"][0]["hash"] = serde_json::json!(tx_hash);
+    std::fs::write(&sequence_path, serde_json::to_string_pretty(&sequence).unwrap()).unwrap();
+
+    // the pending transaction only gets mined once the resumed script is already waiting for it
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        api.mine_one().await;
+        api.anvil_set_auto_mine(true).await.unwrap();
+    });
+
+    let mut resumer =
+        ScriptTester::new_broadcast(prj.forge_command(), &handle.http_endpoint(), prj.root());
+    resumer
+        .sender(resumer.accounts_pub[1])
+        .load_private_keys(&[0, 1])
+        .await
+        .add_sig("BroadcastTest", "deployNoArgs()")
+        .resume(ScriptOutcome::OkBroadcast)
+        .assert_nonce_increment(&[(0, 1), (1, 2)])
+        .await;
+});
+
+// tests that printing the fee schedule neither sends nor saves the transactions
+forgetest_async!(can_print_fee_schedule_without_broadcasting, |prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let mut tester = ScriptTester::new_broadcast(cmd, &handle.http_endpoint(), prj.root());
+
+    tester
+        .load_private_keys(&[0, 1])
+        .await
+        .add_sig("BroadcastTest", "deploy()")
+        .args(&["--gas-price-bump-interval", "30s", "--max-fee-cap", "10gwei"])
+        .arg("--print-fee-schedule")
+        .broadcast(ScriptOutcome::OkFeeSchedule)
+        .assert_nonce_increment(&[(0, 0), (1, 0)])
+        .await;
+    assert!(!prj.root().join("broadcast/Broadcast.t.sol/31337/run-latest.json").exists());
+});
+
 forgetest_async!(can_deploy_broadcast_wrap, |prj, cmd| {
     let (_api, handle) = spawn(NodeConfig::test()).await;
     let mut tester = ScriptTester::new_broadcast(cmd, &handle.http_endpoint(), prj.root());
@@ -1086,3 +1206,238 @@ forgetest_async!(assert_can_resume_with_additional_contracts, |prj, cmd| {
         .await
         .resume(ScriptOutcome::OkBroadcast);
 });
+
+// Tests that sensitive environment values are masked in the script output
+forgetest_async!(can_redact_sensitive_env_values, |prj, cmd| {
+    cmd.args(["init", "--force"]).arg(prj.root());
+    cmd.assert_non_empty_stdout();
+    cmd.forge_fuse();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let script = prj
+        .add_script(
+            "Secrets.s.sol",
+            r#"
+import "forge-std/Script.sol";
+
+interface SensitiveVm {
+    function envSensitiveString(string calldata name) external view returns (string memory value);
+}
+
+contract Labels {
+    string public label;
+
+    function setLabel(string memory _label) external {
+        label = _label;
+    }
+}
+
+contract SecretsScript is Script {
+    function run() external {
+        string memory apiKey = vm.envString("REDACT_TEST_API_KEY");
+        string memory password = SensitiveVm(address(vm)).envSensitiveString("REDACT_TEST_PASSWORD");
+        console.log(apiKey);
+        console.log(password);
+        console.log(vm.envString("REDACT_TEST_NETWORK"));
+
+        vm.startBroadcast();
+        Labels labels = new Labels();
+        labels.setLabel(apiKey);
+        labels.setLabel(password);
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.set_env("REDACT_TEST_API_KEY", "api-key-value");
+    cmd.set_env("REDACT_TEST_PASSWORD", "password-value");
+    cmd.set_env("REDACT_TEST_NETWORK", "sepolia");
+    cmd.arg("script").arg(&script).args(["--rpc-url", &handle.http_endpoint()]);
+    let output = cmd.stdout_lossy();
+    assert!(!output.contains("api-key-value"), "{output}");
+    assert!(!output.contains("password-value"), "{output}");
+    assert!(output.contains(&foundry_common::redact::mask("api-key-value")), "{output}");
+    assert!(output.contains(&foundry_common::redact::mask("password-value")), "{output}");
+    assert!(output.contains("sepolia"), "{output}");
+    assert!(output.contains("== Environment =="), "{output}");
+    assert!(output.contains("REDACT_TEST_API_KEY (sensitive)"), "{output}");
+    assert!(output.contains("REDACT_TEST_PASSWORD (sensitive)"), "{output}");
+    assert!(output.contains("REDACT_TEST_NETWORK\n"), "{output}");
+
+    // the values are masked in the arguments and the calldata of the saved transactions, the
+    // sensitive file in the cache keeps them to resume
+    let saved = |dir: &str| {
+        let path = foundry_common::fs::json_files(prj.root().join(dir))
+            .into_iter()
+            .find(|file| file.ends_with("run-latest.json"))
+            .expect("no saved sequence");
+        foundry_common::fs::read_to_string(path).unwrap()
+    };
+    let broadcast = saved("broadcast");
+    let sensitive = saved("cache");
+    for secret in ["api-key-value", "password-value"] {
+        assert!(!broadcast.contains(secret), "{broadcast}");
+        assert!(!broadcast.contains(&hex::encode(secret)), "{broadcast}");
+        assert!(broadcast.contains(&foundry_common::redact::mask(secret)), "{broadcast}");
+        assert!(sensitive.contains(&hex::encode(secret)), "{sensitive}");
+    }
+
+    cmd.arg("--show-sensitive");
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("api-key-value"), "{output}");
+    assert!(output.contains("password-value"), "{output}");
+});
+
+// Tests that the prompt cheatcodes read scripted input, re-prompt on invalid input and mask secrets
+forgetest_async!(can_prompt_for_input, |prj, cmd| {
+    cmd.args(["init", "--force"]).arg(prj.root());
+    cmd.assert_non_empty_stdout();
+    cmd.forge_fuse();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let script = prj
+        .add_script(
+            "Prompt.s.sol",
+            r#"
+import "forge-std/Script.sol";
+
+interface PromptVm {
+    function prompt(string calldata promptText) external returns (string memory input);
+    function promptSecret(string calldata promptText) external returns (string memory input);
+    function promptAddress(string calldata promptText) external returns (address);
+    function promptUint(string calldata promptText) external returns (uint256);
+}
+
+contract Vault {
+    bytes32 public passwordHash;
+
+    function setPassword(string memory password) external {
+        passwordHash = keccak256(bytes(password));
+    }
+}
+
+contract PromptScript is Script {
+    function run() external {
+        PromptVm pvm = PromptVm(address(vm));
+        console.log(pvm.promptAddress("Multisig"));
+        console.log(pvm.promptUint("Amount"));
+        console.log(pvm.prompt("Label"));
+        string memory password = pvm.promptSecret("Password");
+        console.log(password);
+
+        vm.startBroadcast();
+        new Vault().setPassword(password);
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.arg("script").arg(&script).args(["--rpc-url", &handle.http_endpoint()]);
+    cmd.stdin(|mut stdin| {
+        use std::io::Write;
+        stdin
+            .write_all(
+                b"not-an-address\n0x5FbDB2315678afecb367f032d93F642f64180aa3\n42\nhello\nsecret-value\n",
+            )
+            .unwrap();
+    });
+    let (stdout, stderr) = cmd.output_lossy();
+    assert!(stderr.contains("Invalid input"), "{stderr}");
+    assert!(stdout.contains("0x5FbDB2315678afecb367f032d93F642f64180aa3"), "{stdout}");
+    assert!(stdout.contains("42"), "{stdout}");
+    assert!(stdout.contains("hello"), "{stdout}");
+    assert!(!stdout.contains("secret-value"), "{stdout}");
+    assert!(stdout.contains(&foundry_common::redact::mask("secret-value")), "{stdout}");
+
+    // the secret is also masked in the calldata of the saved transactions
+    let path = foundry_common::fs::json_files(prj.root().join("broadcast"))
+        .into_iter()
+        .find(|file| file.ends_with("run-latest.json"))
+        .expect("no saved sequence");
+    let broadcast = foundry_common::fs::read_to_string(path).unwrap();
+    assert!(!broadcast.contains("secret-value"), "{broadcast}");
+    assert!(!broadcast.contains(&hex::encode("secret-value")), "{broadcast}");
+    assert!(broadcast.contains(&foundry_common::redact::mask("secret-value")), "{broadcast}");
+
+    cmd.arg("--non-interactive");
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(format!("{stdout}{stderr}").contains("non-interactive mode"), "{stdout}{stderr}");
+});
+
+forgetest_async!(can_save_deployments, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    prj.write_config(Config {
+        save_deployments: true,
+        fs_permissions: FsPermissions::new(vec![PathPermission::read("./deployments")]),
+        ..Config::default()
+    });
+    let script = prj
+        .add_script(
+            "Deploy.s.sol",
+            r#"
+import "forge-std/Script.sol";
+
+contract Registered {
+    uint256 public value;
+
+    constructor(uint256 _value) {
+        value = _value;
+    }
+}
+
+interface DeploymentsVm {
+    function getDeployment(string calldata contractName, uint256 chainId) external view returns (address);
+}
+
+contract DeployScript is Script {
+    function run() external {
+        vm.broadcast();
+        new Registered(1);
+    }
+
+    function check(address expected) external view {
+        require(DeploymentsVm(address(vm)).getDeployment("Registered", block.chainid) == expected);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let rpc = handle.http_endpoint();
+    let deploy = |cmd: &mut TestCommand| {
+        cmd.forge_fuse().arg("script").arg(&script).args([
+            "--fork-url",
+            &rpc,
+            "--broadcast",
+            "--unlocked",
+            "--sender",
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266",
+        ]);
+        assert!(cmd.stdout_lossy().contains("Deployments saved to:"));
+    };
+
+    deploy(cmd);
+    deploy(cmd);
+
+    let path = prj.root().join("deployments/31337/Registered.json");
+    let record: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(record["contractName"], "Registered");
+    assert_eq!(record["artifact"], "script/Deploy.s.sol:Registered");
+    assert_eq!(record["constructorArgs"], Bytes::from(U256::from(1).to_be_bytes_vec()).to_string());
+    assert!(record["transactionHash"].is_string());
+    assert!(record["blockNumber"].as_u64().unwrap() > 0);
+    assert!(record["metadataHash"].is_string());
+    let history = record["history"].as_array().unwrap();
+    assert_eq!(history.len(), 1);
+    assert_ne!(history[0]["address"], record["address"]);
+
+    // the latest deployment can be read with `vm.getDeployment`
+    let address = record["address"].as_str().unwrap();
+    cmd.forge_fuse().arg("script").arg(&script).args(["--sig", "check(address)", address]);
+    assert!(cmd.stdout_lossy().contains("Script ran successfully."));
+});
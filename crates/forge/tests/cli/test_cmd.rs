@@ -1,6 +1,9 @@
 //! Contains various tests for checking `forge test`
+use anvil::{spawn, NodeConfig};
+use ethers_core::types::TransactionRequest;
+use ethers_providers::Middleware;
 use foundry_common::rpc;
-use foundry_config::Config;
+use foundry_config::{fs_permissions::PathPermission, Config, FsPermissions};
 use foundry_test_utils::util::{OutputExt, OTHER_SOLC_VERSION, SOLC_VERSION};
 use std::{path::PathBuf, process::Command, str::FromStr};
 
@@ -368,6 +371,46 @@ forgetest_init!(exit_code_error_on_fail_fast_with_json, |prj, cmd| {
     cmd.assert_err();
 });
 
+forgetest_init!(fail_fast_reports_unexecuted_tests, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_source("failing_test", FAILING_TEST).unwrap();
+    prj.add_source(
+        "passing_test",
+        r#"
+import "forge-std/Test.sol";
+
+contract PassingTest is Test {
+    function testShouldPass() public {}
+}
+"#,
+    )
+    .unwrap();
+
+    // the results keep their shape, the abort is reported on stderr
+    cmd.args(["test", "--fail-fast", "--json", "--deterministic-scheduling"]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    let json: serde_json::Value = serde_json::from_str(stdout.trim()).unwrap();
+    assert!(json.get("aborted").is_none());
+    assert!(json.as_object().unwrap().keys().any(|suite| suite.contains("FailingTest")));
+    assert!(stderr.contains("test not executed"), "{stderr}");
+    cmd.assert_err();
+
+    // the sharded results always report whether the run was aborted
+    cmd.forge_fuse().args([
+        "test",
+        "--fail-fast",
+        "--json",
+        "--deterministic-scheduling",
+        "--shard",
+        "1/1",
+    ]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    let json: serde_json::Value = serde_json::from_str(stdout.trim()).unwrap();
+    assert_eq!(json["aborted"], true);
+    assert_eq!(json["unexecuted"], 1);
+    cmd.assert_err();
+});
+
 // <https://github.com/foundry-rs/foundry/issues/6531>
 forgetest_init!(repro_6531, |prj, cmd| {
     prj.wipe_contracts();
@@ -432,3 +475,664 @@ contract CustomTypesTest is Test {
             .join("tests/fixtures/include_custom_types_in_traces.stdout"),
     );
 });
+
+/// Returns the gas of the first traced call of `call` in the output of `forge test -vvvv`.
+fn traced_gas(output: &str, call: &str) -> u64 {
+    let line = output.lines().find(|line| line.contains(call)).unwrap();
+    let gas = line.split_once('[').unwrap().1.split_once(']').unwrap().0;
+    gas.parse().unwrap()
+}
+
+// tests that calls in isolation mode are charged like the same transactions mined by anvil
+forgetest_async!(can_isolate_calls_with_onchain_gas, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    prj.add_source(
+        "IsolatedGas.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract GasCounter {
+    uint256 public number;
+
+    function setNumber(uint256 newNumber) public {
+        number = newNumber;
+    }
+
+    function increment() public {
+        number++;
+    }
+}
+
+interface GasVm {
+    struct Gas {
+        uint64 gasLimit;
+        uint64 gasTotalUsed;
+        uint64 gasMemoryUsed;
+        int64 gasRefunded;
+        uint64 gasRemaining;
+    }
+
+    function lastCallGas() external view returns (Gas memory gas);
+}
+
+contract IsolatedGasTest is Test {
+    function testGas() public {
+        GasCounter counter = new GasCounter();
+        counter.setNumber(1);
+        counter.increment();
+        emit log_named_uint("lastCallGas", GasVm(address(vm)).lastCallGas().gasTotalUsed);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--match-contract", "IsolatedGasTest", "-vvvv"]);
+    let output = cmd.stdout_lossy();
+    let shared_increment = traced_gas(&output, "GasCounter::increment()");
+
+    cmd.arg("--isolate");
+    let output = cmd.stdout_lossy();
+    let isolated = [
+        traced_gas(&output, "GasCounter::setNumber(1)"),
+        traced_gas(&output, "GasCounter::increment()"),
+    ];
+    // intrinsic gas and a cold storage slot
+    assert!(isolated[1] > shared_increment + 21_000);
+    // `vm.lastCallGas` reports the gas of the transaction
+    assert!(output.contains(&format!("lastCallGas: {}", isolated[1])), "{output}");
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.ethers_http_provider();
+    let from = handle.dev_accounts().next().unwrap();
+
+    let artifact = prj.artifacts().join("IsolatedGas.sol/GasCounter.json");
+    let artifact: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(artifact).unwrap()).unwrap();
+    let bytecode = hex::decode(artifact["bytecode"]["object"].as_str().unwrap()).unwrap();
+    let deploy = TransactionRequest::new().from(from).data(bytecode);
+    let receipt = provider.send_transaction(deploy, None).await.unwrap().await.unwrap().unwrap();
+    let counter = receipt.contract_address.unwrap();
+
+    // `setNumber(1)` and `increment()`
+    let calls = [
+        "0x3fb5c1cb0000000000000000000000000000000000000000000000000000000000000001",
+        "0xd09de08a",
+    ];
+    for (calldata, isolated) in calls.into_iter().zip(isolated) {
+        let tx =
+            TransactionRequest::new().from(from).to(counter).data(hex::decode(calldata).unwrap());
+        let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+        let mined = receipt.gas_used.unwrap().as_u64();
+        assert!(
+            mined.abs_diff(isolated) <= 100,
+            "{calldata}: isolated {isolated} != mined {mined}"
+        );
+    }
+});
+
+// transient storage is cleared between top-level calls in isolation mode
+forgetest_init!(can_clear_transient_storage_in_isolation, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.write_config(Config { cancun: true, ..Default::default() });
+
+    prj.add_test(
+        "Transient.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract TransientTest is Test {
+    // TSTOREs 42 to slot 0 without calldata, returns TLOAD of slot 0 otherwise
+    bytes constant CODE = hex"36600a57602a60005d005b60005c60005260206000f3";
+
+    address target = address(0x1234);
+
+    function load() internal returns (uint256) {
+        (, bytes memory data) = target.call(hex"01");
+        return abi.decode(data, (uint256));
+    }
+
+    function loadAfterStore() internal returns (uint256) {
+        vm.etch(target, CODE);
+        (bool success,) = target.call("");
+        assertTrue(success);
+        return load();
+    }
+
+    // the value stored by the test itself, in the transaction of the test
+    function loadAfterTstore() internal returns (uint256) {
+        vm.etch(target, CODE);
+        vm.tstore(target, bytes32(0), bytes32(uint256(7)));
+        return load();
+    }
+
+    function testSharedStore() public {
+        assertEq(loadAfterStore(), 42);
+    }
+
+    function testSharedTstore() public {
+        assertEq(loadAfterTstore(), 7);
+    }
+
+    function testIsolatedStore() public {
+        assertEq(loadAfterStore(), 0);
+    }
+
+    function testIsolatedTstore() public {
+        assertEq(loadAfterTstore(), 0);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--match-test", "testShared"]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("[PASS] testSharedStore()"), "{output}");
+    assert!(output.contains("[PASS] testSharedTstore()"), "{output}");
+
+    cmd.forge_fuse().args(["test", "--isolate", "--match-test", "testIsolated"]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("[PASS] testIsolatedStore()"), "{output}");
+    assert!(output.contains("[PASS] testIsolatedTstore()"), "{output}");
+});
+
+// transient storage accesses are shown in the traces
+forgetest_init!(can_trace_transient_storage, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.write_config(Config { cancun: true, ..Default::default() });
+
+    prj.add_test(
+        "TransientTrace.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract TransientTraceTest is Test {
+    // TSTOREs 42 to slot 0 without calldata, returns TLOAD of slot 0 otherwise
+    bytes constant CODE = hex"36600a57602a60005d005b60005c60005260206000f3";
+
+    function testTransientTrace() public {
+        address target = address(0x1234);
+        vm.etch(target, CODE);
+        (bool success,) = target.call("");
+        assertTrue(success);
+        (, bytes memory data) = target.call(hex"01");
+        assertEq(abi.decode(data, (uint256)), 42);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "-vvvv", "--match-test", "testTransientTrace"]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("[PASS] testTransientTrace()"), "{output}");
+
+    let value = "0x000000000000000000000000000000000000000000000000000000000000002a";
+    let tstore = output.lines().find(|line| line.contains("[tstore]")).expect(&output);
+    assert!(tstore.contains(value), "{output}");
+    let tload = output.lines().find(|line| line.contains("[tload]")).expect(&output);
+    assert!(tload.contains(value), "{output}");
+    // the accesses aren't decoded as events
+    assert!(!output.contains("emit topic 0"), "{output}");
+});
+
+// tests that state diffs label the slots of project contracts with their storage layout
+forgetest_init!(can_label_state_diff_slots, |prj, cmd| {
+    prj.wipe_contracts();
+
+    prj.add_source(
+        "Vault.sol",
+        r#"
+contract Vault {
+    address public owner;
+    uint256 private _totalSupply;
+    mapping(address => uint256) public balanceOf;
+
+    constructor() {
+        owner = msg.sender;
+    }
+
+    function deposit() external payable {
+        _totalSupply += msg.value;
+        balanceOf[msg.sender] += msg.value;
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    prj.add_test(
+        "Vault.t.sol",
+        r#"
+import {Test, console2} from "forge-std/Test.sol";
+import {Vault} from "../src/Vault.sol";
+
+interface StateDiffs {
+    function getStateDiff() external view returns (string memory);
+    function getStateDiffJson() external view returns (string memory);
+}
+
+contract VaultTest is Test {
+    StateDiffs constant diffs = StateDiffs(address(vm));
+
+    function testDeposit() public {
+        Vault vault = new Vault();
+        vm.startStateDiffRecording();
+        vault.deposit{value: 100}();
+        console2.log(diffs.getStateDiff());
+        console2.log(diffs.getStateDiffJson());
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--extra-output", "storageLayout", "-vv"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("[PASS] testDeposit()"), "{stdout}");
+    let slot = format!("{:#066x}", 1);
+    assert!(stdout.contains(" (Vault):\n"), "{stdout}");
+    assert!(stdout.contains(&format!("  {slot} (Vault._totalSupply): ")), "{stdout}");
+    assert!(stdout.contains("  balance: 0 -> 100\n"), "{stdout}");
+
+    let json = stdout.lines().find(|line| line.trim_start().starts_with('{')).unwrap();
+    let json: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+    let vault = json.as_object().unwrap().values().find(|account| account["label"] == "Vault");
+    let vault = vault.unwrap();
+    assert_eq!(vault["balanceDiff"]["newValue"], "0x64");
+    assert_eq!(vault["stateDiff"][&slot]["label"], "Vault._totalSupply");
+    // mapping entries aren't labeled
+    let labels =
+        vault["stateDiff"].as_object().unwrap().values().filter(|slot| slot.get("label").is_some());
+    assert_eq!(labels.count(), 1);
+});
+
+forgetest_init!(can_restrict_ffi_commands, |prj, cmd| {
+    prj.wipe_contracts();
+    let config = Config { allowed_ffi: vec!["echo ".to_string()], ..Default::default() };
+    prj.write_config(config);
+
+    prj.add_test(
+        "Ffi.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract FfiTest is Test {
+    function exec(string memory command) internal returns (bytes memory) {
+        string[] memory inputs = new string[](2);
+        inputs[0] = command;
+        inputs[1] = "gm";
+        return vm.ffi(inputs);
+    }
+
+    function testAllowed() public {
+        assertEq(string(exec("echo")), "gm");
+    }
+
+    function testRejected() public {
+        exec("printf");
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    let (out, _) = cmd.args(["test", "--show-permissions"]).unchecked_output_lossy();
+    assert!(out.contains("[PASS] testAllowed()"), "{out}");
+    assert!(out.contains("the command `printf gm` is not allowed by `allowed_ffi`"), "{out}");
+    assert!(out.contains("`echo `:\n    echo gm"), "{out}");
+});
+
+forgetest_init!(can_run_test_shards, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_test(
+        "Shard.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract ShardTest is Test {
+    function testA() public {}
+    function testB() public {}
+    function testC() public {}
+    function testFuzz(uint256) public {}
+}
+
+contract OtherShardTest is Test {
+    function testD() public {}
+    function testE() public {}
+}
+   "#,
+    )
+    .unwrap();
+
+    // the timings recorded by the first shard don't change the split of the second one
+    let mut run_shards = |extra_args: &[&str]| {
+        let mut passed = Vec::new();
+        for shard in ["1/2", "2/2"] {
+            cmd.forge_fuse().args(["test", "--shard", shard]).args(extra_args);
+            let out = cmd.stdout_lossy();
+            assert!(out.contains(&format!("Ran shard {shard}")), "{out}");
+            passed.extend(
+                out.lines()
+                    .filter(|line| line.starts_with("[PASS]"))
+                    .map(|line| line.split_whitespace().nth(1).unwrap().to_string()),
+            );
+        }
+        passed.sort();
+        assert_eq!(
+            passed,
+            ["testA()", "testB()", "testC()", "testD()", "testE()", "testFuzz(uint256)"]
+        );
+    };
+    run_shards(&[]);
+
+    // the shards share a copy of the recorded timings
+    let timings = prj.root().join("timings.json");
+    std::fs::copy(prj.root().join("cache").join("test-timings.json"), &timings).unwrap();
+    run_shards(&["--shard-timings", timings.to_str().unwrap()]);
+
+    cmd.forge_fuse().args(["test", "--shard", "1/2", "--json"]);
+    let out: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(out["shard"]["index"], 1);
+    assert_eq!(out["shard"]["count"], 2);
+    assert_eq!(out["aborted"], false);
+    assert_eq!(out["unexecuted"], 0);
+    assert_eq!(
+        out["shard"]["tests"].as_u64().unwrap() + out["shard"]["skipped"].as_u64().unwrap(),
+        6
+    );
+    assert!(prj.root().join("cache").join("test-timings.json").exists());
+
+    cmd.forge_fuse().args(["test", "--shard", "3/2"]);
+    cmd.assert_err();
+
+    cmd.forge_fuse().args(["test", "--shard", "1/2", "--shard-timings", "missing.json"]);
+    cmd.assert_err();
+});
+
+forgetest_init!(can_not_fork_uncached_endpoint_offline, |_prj, cmd| {
+    // install the compiler first, it can't be installed in offline mode
+    cmd.arg("build").assert_success();
+
+    cmd.forge_fuse().args(["test", "--offline", "--fork-url", "http://uncached.invalid:8545"]);
+    let (_, err) = cmd.unchecked_output_lossy();
+    assert!(err.contains("missing cache entry: no cached fork of uncached.invalid"), "{err}");
+});
+
+forgetest!(can_stream_json_events, |prj, cmd| {
+    prj.insert_ds_test();
+    prj.add_source(
+        "StreamTest.t.sol",
+        r#"
+import "./test.sol";
+contract StreamTest is DSTest {
+    function testPass() public {
+        assertTrue(true);
+    }
+
+    function testFuzzFail(uint256 x) public {
+        require(x < 100, "too large");
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--stream-json"]);
+    let (out, _) = cmd.unchecked_output_lossy();
+    let events: Vec<serde_json::Value> =
+        out.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert!(events.iter().all(|event| event["version"] == 1), "{out}");
+    let kinds: Vec<_> = events.iter().map(|event| event["event"].as_str().unwrap()).collect();
+
+    assert_eq!(kinds[..3], ["compilation_started", "compilation_finished", "suite_discovered"]);
+    assert_eq!(events[2]["suite"], "src/StreamTest.t.sol:StreamTest");
+    assert_eq!(events[2]["tests"], serde_json::json!(["testFuzzFail(uint256)", "testPass()"]));
+    assert_eq!(kinds[kinds.len() - 2..], ["suite_finished", "run_finished"]);
+
+    // each test finishes after it started
+    for test in ["testPass()", "testFuzzFail(uint256)"] {
+        let position = |kind: &str| {
+            events.iter().position(|event| event["event"] == kind && event["test"] == test).unwrap()
+        };
+        assert!(position("test_started") < position("test_finished"), "{out}");
+    }
+
+    let failed = events
+        .iter()
+        .find(|event| event["event"] == "test_finished" && event["test"] == "testFuzzFail(uint256)")
+        .unwrap();
+    assert_eq!(failed["status"], "Failure");
+    assert_eq!(failed["reason"], "too large");
+    assert!(failed["counterexample"].is_object(), "{failed}");
+
+    let summary = &events[events.len() - 1];
+    assert_eq!(summary["passed"], 1);
+    assert_eq!(summary["failed"], 1);
+    assert_eq!(summary["skipped"], 0);
+});
+
+// tests that deployed contracts are matched to their artifacts, ignoring their immutables
+forgetest!(can_get_artifact_by_deployed_code, |prj, cmd| {
+    prj.insert_ds_test();
+    prj.write_config(Config {
+        fs_permissions: FsPermissions::new(vec![PathPermission::read("./out")]),
+        ..Default::default()
+    });
+    prj.add_source(
+        "Artifacts.t.sol",
+        r#"
+import "./test.sol";
+
+interface Vm {
+    function getArtifactByDeployedCode(bytes calldata) external view returns (string memory);
+    function getDeployedCode(string calldata) external view returns (bytes memory);
+}
+
+contract Immutable {
+    uint256 public immutable value;
+
+    constructor(uint256 _value) {
+        value = _value;
+    }
+}
+
+contract Mutable {
+    uint256 public value;
+
+    constructor(uint256 _value) {
+        value = _value;
+    }
+}
+
+contract Twin {
+    function get() public pure returns (uint256) {
+        return 1;
+    }
+}
+
+contract OtherTwin {
+    function get() public pure returns (uint256) {
+        return 1;
+    }
+}
+
+contract ArtifactsTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testImmutables() public {
+        bytes memory seven = address(new Immutable(7)).code;
+        bytes memory eight = address(new Immutable(8)).code;
+        assertTrue(keccak256(seven) != keccak256(eight));
+        assertEq(vm.getArtifactByDeployedCode(seven), "Artifacts.t.sol:Immutable");
+        assertEq(vm.getArtifactByDeployedCode(eight), "Artifacts.t.sol:Immutable");
+        bytes memory mutableCode = address(new Mutable(7)).code;
+        assertEq(vm.getArtifactByDeployedCode(mutableCode), "Artifacts.t.sol:Mutable");
+
+        // the expected code has zeros in place of the immutables
+        bytes memory expected = vm.getDeployedCode("Artifacts.t.sol:Immutable");
+        assertEq(expected.length, seven.length);
+        assertTrue(keccak256(expected) != keccak256(seven));
+        assertEq(vm.getArtifactByDeployedCode(expected), "Artifacts.t.sol:Immutable");
+    }
+
+    function testAmbiguousArtifacts() public {
+        vm.getArtifactByDeployedCode(address(new Twin()).code);
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["test"]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("[PASS] testImmutables()"), "{stdout}");
+    let ambiguous = "matches multiple artifacts: Artifacts.t.sol:OtherTwin, Artifacts.t.sol:Twin";
+    assert!(stdout.contains(ambiguous), "{stdout}");
+    assert!(stdout.contains("testAmbiguousArtifacts()"), "{stdout}");
+});
+
+// tests that memory safety violations name the violating opcode and its program counter
+forgetest!(can_diagnose_memory_safety_violations, |prj, cmd| {
+    prj.insert_ds_test();
+    prj.write_config(Config { cancun: true, ..Default::default() });
+    prj.add_source(
+        "MemSafety.t.sol",
+        r#"
+import "./test.sol";
+
+interface Vm {
+    function etch(address target, bytes calldata code) external;
+    function expectSafeMemoryCall(uint64 min, uint64 max) external;
+}
+
+contract MemSafetyTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    /// Calls `code` with writes to [0x80, 0xA0) allowed, and checks the revert of the violation.
+    function assertViolation(bytes memory code, string memory write, string memory violation) internal {
+        address target = address(0x1234);
+        vm.etch(target, code);
+        vm.expectSafeMemoryCall(0x80, 0xA0);
+        (bool success, bytes memory data) = target.call("");
+        assertTrue(!success);
+        string memory reason = string(
+            abi.encodePacked(
+                "memory write at ",
+                write,
+                unicode" not allowed; safe range: (0x00, 0x60] ∪ (0x80, 0xA0]; violated by ",
+                violation
+            )
+        );
+        assertEq0(data, abi.encodeWithSignature("Error(string)", reason));
+    }
+
+    function testMstoreComputedPointer() public {
+        // mstore(add(0x40, 0x60), 1)
+        assertViolation(hex"600160406060015200", "offset 0xA0 of size 0x20", "MSTORE at pc 0x07");
+    }
+
+    function testMcopyDestination() public {
+        // mcopy(0xA0, 0x00, 0x20)
+        assertViolation(hex"6020600060a05e00", "offset 0xA0 of size 0x20", "MCOPY at pc 0x06");
+    }
+
+    function testMcopySource() public {
+        // mcopy(0x80, 0x100, 0x20), which expands memory with the source
+        assertViolation(hex"602061010060805e00", "offset 0x100 of size 0x20", "MCOPY at pc 0x07");
+    }
+
+    function testCalldatacopy() public {
+        // calldatacopy(0xA0, 0x00, 0x20)
+        assertViolation(hex"6020600060a03700", "offset 0xA0 of size 0x20", "CALLDATACOPY at pc 0x06");
+    }
+
+    function testCodecopy() public {
+        // codecopy(0xA0, 0x00, 0x20)
+        assertViolation(hex"6020600060a03900", "offset 0xA0 of size 0x20", "CODECOPY at pc 0x06");
+    }
+
+    function testReturndatacopy() public {
+        // returndatacopy(0xA0, 0x00, 0x20)
+        assertViolation(hex"6020600060a03e00", "offset 0xA0 of size 0x20", "RETURNDATACOPY at pc 0x06");
+    }
+
+    function testExtcodecopy() public {
+        // extcodecopy(address(), 0xA0, 0x00, 0x20)
+        assertViolation(hex"6020600060a0303c00", "offset 0xA0 of size 0x20", "EXTCODECOPY at pc 0x07");
+    }
+
+    function testLog() public {
+        // log0(0xA0, 0x20), which expands memory
+        assertViolation(hex"602060a0a000", "offset 0xA0 of size 0x20", "LOG0 at pc 0x04");
+    }
+
+    function testZeroSize() public {
+        // codecopy(0xA0, 0x00, 0x00) doesn't touch memory
+        vm.etch(address(0x1234), hex"6000600060a03900");
+        vm.expectSafeMemoryCall(0x80, 0xA0);
+        (bool success,) = address(0x1234).call("");
+        assertTrue(success);
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["test"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("9 passed; 0 failed"), "{stdout}");
+});
+
+// tests that memory safety violations are located in the sources of the project
+forgetest!(can_locate_memory_safety_violations, |prj, cmd| {
+    prj.insert_ds_test();
+    prj.add_source(
+        "Writer.sol",
+        r#"// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity >=0.8.0;
+
+contract Writer {
+    function write(uint256 offset) external {
+        assembly {
+            mstore(offset, 1)
+        }
+    }
+}
+"#,
+    )
+    .unwrap();
+    prj.add_source(
+        "Writer.t.sol",
+        r#"
+import "./test.sol";
+import "./Writer.sol";
+
+interface Vm {
+    function expectSafeMemoryCall(uint64 min, uint64 max) external;
+}
+
+contract WriterTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+    Writer writer;
+
+    function setUp() public {
+        writer = new Writer();
+    }
+
+    function testWrite() public {
+        vm.expectSafeMemoryCall(0x80, 0xA0);
+        writer.write(0xA0);
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["test"]);
+    let (stdout, _) = cmd.unchecked_output_lossy();
+    assert!(stdout.contains("memory write at offset 0xA0 of size 0x20 not allowed"), "{stdout}");
+    assert!(stdout.contains("violated by MSTORE at pc 0x"), "{stdout}");
+    assert!(stdout.contains("(src/Writer.sol:7)"), "{stdout}");
+});
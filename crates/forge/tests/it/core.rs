@@ -1,10 +1,10 @@
 //! Forge tests for core functionality.
 
 use crate::config::*;
-use forge::result::SuiteResult;
+use forge::{result::SuiteResult, stream::TestEvent};
 use foundry_evm::traces::TraceKind;
 use foundry_test_utils::Filter;
-use std::{collections::BTreeMap, env};
+use std::{collections::BTreeMap, env, sync::mpsc};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_core() {
@@ -71,6 +71,49 @@ async fn test_core() {
                     None,
                 )],
             ),
+            (
+                "core/NestedReverts.t.sol:NestedRevertsTest",
+                vec![
+                    (
+                        "testDependencyError()",
+                        false,
+                        Some("InsufficientBalance(0, 1)".to_string()),
+                        None,
+                        None,
+                    ),
+                    (
+                        "testPanic()",
+                        false,
+                        Some("panic: division or modulo by zero (0x12)".to_string()),
+                        None,
+                        None,
+                    ),
+                    (
+                        "testWrappedError()",
+                        false,
+                        Some("CallFailed(InsufficientBalance(0, 1))".to_string()),
+                        None,
+                        None,
+                    ),
+                    (
+                        "testDeeplyWrappedPanic()",
+                        false,
+                        Some(
+                            "CallFailed(CallFailed(panic: division or modulo by zero (0x12)))"
+                                .to_string(),
+                        ),
+                        None,
+                        None,
+                    ),
+                    (
+                        "testHexStringError()",
+                        false,
+                        Some("InsufficientBalance(2, 3)".to_string()),
+                        None,
+                        None,
+                    ),
+                ],
+            ),
         ]),
     );
 }
@@ -698,6 +741,32 @@ async fn test_doesnt_run_abstract_contract() {
     assert!(results.get("core/Abstract.t.sol:AbstractTest").is_some());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deterministic_scheduling() {
+    let mut runner = runner().await;
+    let (tx, rx) = mpsc::channel();
+    runner.events = Some(tx);
+    runner.threads = Some(1);
+    let results = runner.test_collect(&Filter::new(".*", ".*", ".*core"), test_opts()).await;
+
+    // the runner is dropped with the sender once all tests ran
+    drop(runner);
+    let started: Vec<_> = rx
+        .into_iter()
+        .filter_map(|event| match event {
+            TestEvent::TestStarted { suite, test } => Some((suite, test)),
+            _ => None,
+        })
+        .collect();
+    let mut sorted = started.clone();
+    sorted.sort();
+    assert_eq!(started, sorted);
+    assert!(started.iter().any(|(suite, _)| suite != &started[0].0), "{started:?}");
+    for (suite, test) in &started {
+        assert!(results[suite].test_results.contains_key(test), "{suite}:{test}");
+    }
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_trace() {
     let mut runner = tracing_runner().await;
@@ -14,7 +14,7 @@ async fn test_fuzz() {
         .test_collect(
             &Filter::new(".*", ".*", ".*fuzz/")
                 .exclude_tests(r"invariantCounter|testIncrement\(address\)|testNeedle\(uint256\)")
-                .exclude_paths("invariant"),
+                .exclude_paths("invariant|FuzzDictionary"),
             test_opts(),
         )
         .await;
@@ -46,6 +46,37 @@ async fn test_fuzz() {
     }
 }
 
+/// Test that an address constant of the code deployed in `setUp()` is only found with the
+/// dictionary.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fuzz_dictionary() {
+    let mut runner = runner().await;
+    let filter = Filter::new(".*", ".*", ".*fuzz/FuzzDictionary.t.sol");
+
+    let mut opts = test_opts();
+    opts.fuzz.seed = Some(U256::from(7u32));
+    runner.test_options = opts.clone();
+    let results = runner.test_collect(&filter, opts.clone()).await;
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "fuzz/FuzzDictionary.t.sol:FuzzDictionaryTest",
+            vec![("testFuzzNotOwner(address)", false, Some("owner found".into()), None, None)],
+        )]),
+    );
+
+    opts.fuzz.dictionary.dictionary_weight = 0;
+    runner.test_options = opts.clone();
+    let results = runner.test_collect(&filter, opts).await;
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "fuzz/FuzzDictionary.t.sol:FuzzDictionaryTest",
+            vec![("testFuzzNotOwner(address)", true, None, None, None)],
+        )]),
+    );
+}
+
 /// Test that showcases PUSH collection on normal fuzzing. Ignored until we collect them in a
 /// smarter way.
 #[tokio::test(flavor = "multi_thread")]
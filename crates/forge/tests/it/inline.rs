@@ -5,7 +5,7 @@ use crate::{
     test_helpers::{COMPILED, PROJECT},
 };
 use forge::{
-    result::{SuiteResult, TestKind, TestResult},
+    result::{SuiteResult, TestKind, TestResult, TestStatus},
     TestOptions, TestOptionsBuilder,
 };
 use foundry_config::{FuzzConfig, InvariantConfig};
@@ -72,6 +72,21 @@ async fn inline_config_run_invariant() {
     }
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn inline_config_evm_version() {
+    let opts = default_test_options();
+    let filter = Filter::new(".*", ".*", ".*inline/EvmInlineConf.t.sol");
+    let mut runner = runner().await;
+    runner.test_options = opts.clone();
+
+    let result = runner.test_collect(&filter, opts).await;
+    let suite_result = result.get("inline/EvmInlineConf.t.sol:EvmInlineConf").unwrap();
+    assert_eq!(suite_result.len(), 4);
+    for (name, result) in suite_result.tests() {
+        assert_eq!(result.status, TestStatus::Success, "{name} failed: {:?}", result.reason);
+    }
+}
+
 #[test]
 fn build_test_options() {
     let root = &PROJECT.paths.root;
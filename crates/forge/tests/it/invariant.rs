@@ -20,6 +20,20 @@ async fn test_invariant() {
     assert_multiple(
         &results,
         BTreeMap::from([
+            (
+                "fuzz/invariant/common/InvariantAfterInvariant.t.sol:InvariantAfterInvariant",
+                vec![("invariant_countBelowDepth()", true, None, None, None)],
+            ),
+            (
+                "fuzz/invariant/common/InvariantAfterInvariant.t.sol:InvariantAfterInvariantFailure",
+                vec![(
+                    "invariant_true()",
+                    false,
+                    Some("revert: afterInvariant failure".into()),
+                    None,
+                    None,
+                )],
+            ),
             (
                 "fuzz/invariant/common/InvariantHandlerFailure.t.sol:InvariantHandlerFailure",
                 vec![("statefulFuzz_BrokenInvariant()", true, None, None, None)],
@@ -38,6 +52,10 @@ async fn test_invariant() {
                 "fuzz/invariant/common/InvariantReentrancy.t.sol:InvariantReentrancy",
                 vec![("invariantNotStolen()", true, None, None, None)],
             ),
+            (
+                "fuzz/invariant/common/InvariantPreserveState.t.sol:InvariantPreserveState",
+                vec![("invariant_countBelowDepth()", true, None, None, None)],
+            ),
             (
                 "fuzz/invariant/common/InvariantTest1.t.sol:InvariantTest",
                 vec![
@@ -178,6 +196,36 @@ async fn test_invariant_fail_on_revert() {
     );
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invariant_preserve_state() {
+    let mut runner = runner().await;
+
+    let mut opts = test_opts();
+    opts.invariant.preserve_state = true;
+    runner.test_options = opts.clone();
+
+    let results = runner
+        .test_collect(
+            &Filter::new(".*", ".*", ".*fuzz/invariant/common/InvariantPreserveState.t.sol"),
+            opts,
+        )
+        .await;
+
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "fuzz/invariant/common/InvariantPreserveState.t.sol:InvariantPreserveState",
+            vec![(
+                "invariant_countBelowDepth()",
+                false,
+                Some("revert: count exceeds depth".into()),
+                None,
+                None,
+            )],
+        )]),
+    );
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_invariant_storage() {
@@ -244,3 +292,36 @@ async fn test_invariant_shrink() {
         }
     };
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invariant_debug_calls() {
+    let mut runner = runner().await;
+    runner.debug = true;
+
+    let mut opts = test_opts();
+    opts.fuzz.seed = Some(U256::from(119u32));
+    runner.test_options = opts.clone();
+
+    let results = runner
+        .test_collect(
+            &Filter::new(".*", ".*", ".*fuzz/invariant/common/InvariantInnerContract.t.sol"),
+            opts,
+        )
+        .await;
+
+    let results =
+        results.values().last().expect("`InvariantInnerContract.t.sol` should be testable.");
+    let result =
+        results.test_results.values().last().expect("`InvariantInnerContract` should be testable.");
+
+    let Some(CounterExample::Sequence(sequence)) = &result.counterexample else {
+        panic!("`InvariantInnerContract` should have failed with a counterexample sequence.");
+    };
+
+    // every call of the shrunk sequence is followed by an invariant check
+    assert_eq!(result.debug_calls.len(), sequence.len() * 2);
+    for (call, (label, _)) in sequence.iter().zip(result.debug_calls.iter().step_by(2)) {
+        assert_eq!(*label, call.to_string());
+    }
+    assert_eq!(result.debug_calls.last().unwrap().0, "invariant check");
+}
@@ -251,13 +251,17 @@ pub enum ScriptOutcome {
     OkNoEndpoint,
     OkSimulation,
     OkBroadcast,
+    OkFeeSchedule,
     WarnSpecifyDeployer,
     MissingSender,
     MissingWallet,
+    MissingSigner,
     StaticCallNotAllowed,
     ScriptFailed,
     UnsupportedLibraries,
     ErrorSelectForkOnBroadcast,
+    BalanceCheckFailed,
+    DriftCheckFailed,
 }
 
 impl ScriptOutcome {
@@ -266,13 +270,17 @@ impl ScriptOutcome {
             Self::OkNoEndpoint => "If you wish to simulate on-chain transactions pass a RPC URL.",
             Self::OkSimulation => "SIMULATION COMPLETE. To broadcast these",
             Self::OkBroadcast => "ONCHAIN EXECUTION COMPLETE & SUCCESSFUL",
+            Self::OkFeeSchedule => "Fee schedule, in gwei:",
             Self::WarnSpecifyDeployer => "You have more than one deployer who could predeploy libraries. Using `--sender` instead.",
             Self::MissingSender => "You seem to be using Foundry's default sender. Be sure to set your own --sender",
             Self::MissingWallet => "No associated wallet",
+            Self::MissingSigner => "No signer configured for broadcast sender(s)",
             Self::StaticCallNotAllowed => "staticcall`s are not allowed after `broadcast`; use `startBroadcast` instead",
             Self::ScriptFailed => "script failed: ",
             Self::UnsupportedLibraries => "Multi chain deployment does not support library linking at the moment.",
             Self::ErrorSelectForkOnBroadcast => "cannot select forks during a broadcast",
+            Self::BalanceCheckFailed => "Balance check failed: ",
+            Self::DriftCheckFailed => "Simulation drift check failed: ",
         }
     }
 
@@ -281,12 +289,16 @@ impl ScriptOutcome {
             ScriptOutcome::OkNoEndpoint |
             ScriptOutcome::OkSimulation |
             ScriptOutcome::OkBroadcast |
+            ScriptOutcome::OkFeeSchedule |
             ScriptOutcome::WarnSpecifyDeployer => false,
             ScriptOutcome::MissingSender |
             ScriptOutcome::MissingWallet |
+            ScriptOutcome::MissingSigner |
             ScriptOutcome::StaticCallNotAllowed |
             ScriptOutcome::UnsupportedLibraries |
             ScriptOutcome::ErrorSelectForkOnBroadcast |
+            ScriptOutcome::BalanceCheckFailed |
+            ScriptOutcome::DriftCheckFailed |
             ScriptOutcome::ScriptFailed => true,
         }
     }
@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract Counter {
+    uint256 public count;
+
+    function increment() external returns (uint256) {
+        return ++count;
+    }
+
+    function current() external view returns (address) {
+        return address(this);
+    }
+}
+
+contract DelegationTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    uint256 constant PRIVATE_KEY = 0xa11ce;
+
+    address authority;
+    Counter implementation;
+
+    function setUp() public {
+        authority = vm.addr(PRIVATE_KEY);
+        implementation = new Counter();
+    }
+
+    function testCallThroughDelegation() public {
+        Vm.SignedDelegation memory delegation = vm.signDelegation(address(implementation), PRIVATE_KEY);
+        assertEq(delegation.implementation, address(implementation));
+        assertEq(delegation.nonce, 0);
+
+        vm.attachDelegation(delegation);
+        assertEq(vm.getNonce(authority), 1);
+        assertEq(authority.code, abi.encodePacked(hex"ef0100", address(implementation)));
+
+        // the code runs on the storage of the authority
+        assertEq(Counter(authority).increment(), 1);
+        assertEq(Counter(authority).increment(), 2);
+        assertEq(Counter(authority).count(), 2);
+        assertEq(Counter(authority).current(), authority);
+        assertEq(implementation.count(), 0);
+    }
+
+    function testSignAndAttachDelegation() public {
+        vm.signAndAttachDelegation(address(implementation), PRIVATE_KEY);
+        assertEq(Counter(authority).increment(), 1);
+
+        // delegating again replaces the delegation
+        Counter other = new Counter();
+        Vm.SignedDelegation memory delegation = vm.signAndAttachDelegation(address(other), PRIVATE_KEY);
+        assertEq(delegation.nonce, 1);
+        assertEq(authority.code, abi.encodePacked(hex"ef0100", address(other)));
+        // the storage of the authority is kept
+        assertEq(Counter(authority).count(), 1);
+        assertEq(Counter(authority).increment(), 2);
+    }
+
+    function testDelegateCallThroughDelegation() public {
+        vm.signAndAttachDelegation(address(implementation), PRIVATE_KEY);
+        (bool success, bytes memory data) = authority.delegatecall(abi.encodeCall(Counter.current, ()));
+        assertTrue(success);
+        assertEq(abi.decode(data, (address)), address(this));
+        assertEq(authority.code.length, 23);
+    }
+
+    function attachAndRevert(Vm.SignedDelegation memory delegation) external {
+        vm.attachDelegation(delegation);
+        assertEq(Counter(authority).increment(), 1);
+        revert("reverted");
+    }
+
+    function incrementAndRevert() external {
+        Counter(authority).increment();
+        revert("reverted");
+    }
+
+    function testRevertUndoesDelegation() public {
+        Vm.SignedDelegation memory delegation = vm.signDelegation(address(implementation), PRIVATE_KEY);
+        (bool success,) = address(this).call(abi.encodeCall(this.attachAndRevert, (delegation)));
+        assertTrue(!success);
+        assertEq(authority.code.length, 0);
+        assertEq(vm.getNonce(authority), 0);
+        assertEq(vm.load(authority, bytes32(0)), bytes32(0));
+    }
+
+    function testRevertedCallKeepsDelegation() public {
+        vm.signAndAttachDelegation(address(implementation), PRIVATE_KEY);
+        (bool success,) = address(this).call(abi.encodeCall(this.incrementAndRevert, ()));
+        assertTrue(!success);
+        assertEq(authority.code, abi.encodePacked(hex"ef0100", address(implementation)));
+        assertEq(Counter(authority).count(), 0);
+        assertEq(Counter(authority).increment(), 1);
+    }
+
+    function testRevertsWithStaleNonce() public {
+        Vm.SignedDelegation memory delegation = vm.signDelegation(address(implementation), PRIVATE_KEY);
+        vm.setNonce(authority, 1);
+        vm._expectCheatcodeRevert();
+        vm.attachDelegation(delegation);
+    }
+
+    function testRevertsWithoutImplementationCode() public {
+        vm._expectCheatcodeRevert();
+        vm.signAndAttachDelegation(address(0xdead), PRIVATE_KEY);
+    }
+}
@@ -6,6 +6,7 @@ import "./Vm.sol";
 
 contract Reverter {
     error CustomError();
+    error CustomErrorWithArgs(address account, uint256 amount);
 
     function revertWithMessage(string memory message) public pure {
         revert(message);
@@ -21,6 +22,21 @@ contract Reverter {
         revert CustomError();
     }
 
+    function revertWithCustomErrorArgs(address account, uint256 amount) public pure {
+        revert CustomErrorWithArgs(account, amount);
+    }
+
+    function nestedCustomErrorRevert(Reverter inner, address account, uint256 amount) public pure {
+        inner.revertWithCustomErrorArgs(account, amount);
+    }
+
+    function catchThenRevert(Reverter inner, address account, uint256 amount) public view {
+        try inner.revertWithCustomErrorArgs(account, amount) {}
+        catch {
+            revert CustomErrorWithArgs(address(this), amount + 1);
+        }
+    }
+
     function nestedRevert(Reverter inner, string memory message) public pure {
         inner.revertWithMessage(message);
     }
@@ -113,6 +129,70 @@ contract ExpectRevertTest is DSTest {
         reverter.revertWithCustomError();
     }
 
+    function testExpectPartialRevert() public {
+        Reverter reverter = new Reverter();
+        vm.expectPartialRevert(Reverter.CustomErrorWithArgs.selector);
+        reverter.revertWithCustomErrorArgs(address(1), 100);
+    }
+
+    function testFailExpectPartialRevertWrongSelector() public {
+        Reverter reverter = new Reverter();
+        vm.expectPartialRevert(Reverter.CustomError.selector);
+        reverter.revertWithCustomErrorArgs(address(1), 100);
+    }
+
+    function testFailExpectRevertSelectorOnlyWithArgs() public {
+        Reverter reverter = new Reverter();
+        vm.expectRevert(Reverter.CustomErrorWithArgs.selector);
+        reverter.revertWithCustomErrorArgs(address(1), 100);
+    }
+
+    function testExpectRevertArg() public {
+        Reverter reverter = new Reverter();
+        vm.expectRevert(Reverter.CustomErrorWithArgs.selector, 1, bytes32(uint256(100)));
+        reverter.revertWithCustomErrorArgs(address(1), 100);
+
+        vm.expectRevert(Reverter.CustomErrorWithArgs.selector, 0, bytes32(uint256(uint160(address(1)))));
+        reverter.revertWithCustomErrorArgs(address(1), 100);
+    }
+
+    function testFailExpectRevertWrongArg() public {
+        Reverter reverter = new Reverter();
+        vm.expectRevert(Reverter.CustomErrorWithArgs.selector, 1, bytes32(uint256(101)));
+        reverter.revertWithCustomErrorArgs(address(1), 100);
+    }
+
+    function testFailExpectRevertArgOutOfBounds() public {
+        Reverter reverter = new Reverter();
+        vm.expectRevert(Reverter.CustomErrorWithArgs.selector, 2, bytes32(0));
+        reverter.revertWithCustomErrorArgs(address(1), 100);
+    }
+
+    function testExpectPartialRevertNested() public {
+        Reverter reverter = new Reverter();
+        Reverter inner = new Reverter();
+        vm.expectPartialRevert(Reverter.CustomErrorWithArgs.selector);
+        reverter.nestedCustomErrorRevert(inner, address(1), 100);
+
+        vm.expectRevert(Reverter.CustomErrorWithArgs.selector, 1, bytes32(uint256(100)));
+        reverter.nestedCustomErrorRevert(inner, address(1), 100);
+    }
+
+    function testExpectRevertArgAfterCatch() public {
+        Reverter reverter = new Reverter();
+        Reverter inner = new Reverter();
+        // the caught inner revert must not be matched, only the one of the outer call
+        vm.expectRevert(Reverter.CustomErrorWithArgs.selector, 0, bytes32(uint256(uint160(address(reverter)))));
+        reverter.catchThenRevert(inner, address(1), 100);
+    }
+
+    function testFailExpectRevertArgCaughtRevert() public {
+        Reverter reverter = new Reverter();
+        Reverter inner = new Reverter();
+        vm.expectRevert(Reverter.CustomErrorWithArgs.selector, 1, bytes32(uint256(100)));
+        reverter.catchThenRevert(inner, address(1), 100);
+    }
+
     function testExpectRevertNested() public {
         Reverter reverter = new Reverter();
         Reverter inner = new Reverter();
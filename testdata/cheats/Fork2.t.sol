@@ -228,6 +228,22 @@ contract ForkTest is DSTest {
         bytes memory result = vm.rpc("eth_getBalance", file);
         assertEq(hex"10b7c11bcb51e6", result);
     }
+
+    // test that a blockhash of the fork can be overridden
+    function testCanSetBlockhashOnFork() public {
+        vm.selectFork(mainnetFork);
+        uint256 number = block.number - 1;
+        bytes32 original = blockhash(number);
+        assertTrue(original != 0);
+
+        bytes32 hash = keccak256("overridden");
+        vm.setBlockhash(number, hash);
+        assertEq(blockhash(number), hash);
+
+        // other forks are not affected
+        vm.selectFork(optimismFork);
+        assertTrue(blockhash(block.number - 1) != hash);
+    }
 }
 
 contract DummyContract {
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract StateDiffCounter {
+    uint256 public number;
+
+    function set(uint256 value) public {
+        number = value;
+    }
+
+    function deploy() public returns (StateDiffCounter) {
+        return new StateDiffCounter();
+    }
+}
+
+contract GetStateDiffTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    StateDiffCounter counter;
+
+    function setUp() public {
+        counter = new StateDiffCounter();
+        counter.set(1);
+    }
+
+    function testGetStateDiff() public {
+        vm.label(address(counter), "counter");
+        vm.startStateDiffRecording();
+        counter.set(42);
+        // unchanged slots and reads are not part of the diff
+        counter.number();
+        string memory expected = string.concat(
+            vm.toString(address(counter)),
+            " (counter):\n  ",
+            vm.toString(bytes32(0)),
+            ": ",
+            vm.toString(bytes32(uint256(1))),
+            " -> ",
+            vm.toString(bytes32(uint256(42))),
+            "\n"
+        );
+        assertEq(vm.getStateDiff(), expected);
+
+        // the recording continues
+        counter.set(1);
+        assertEq(vm.getStateDiff(), "");
+    }
+
+    function testGetStateDiffJson() public {
+        vm.startStateDiffRecording();
+        StateDiffCounter deployed = counter.deploy();
+        deployed.set(7);
+
+        string memory json = vm.getStateDiffJson();
+        string[] memory accounts = vm.parseJsonKeys(json, "$");
+        assertEq(accounts.length, 2);
+
+        string memory key = string.concat(".", vm.toString(address(deployed)));
+        string[] memory keys = vm.parseJsonKeys(json, key);
+        assertEq(keys.length, 3);
+        assertTrue(vm.keyExists(json, string.concat(key, ".codeDiff")));
+        assertTrue(vm.keyExists(json, string.concat(key, ".nonceDiff")));
+        assertTrue(vm.keyExists(json, string.concat(key, ".stateDiff")));
+        assertEq(vm.parseJsonBytes(json, string.concat(key, ".codeDiff.previousValue")), "");
+        assertEq(
+            vm.parseJsonBytes(json, string.concat(key, ".codeDiff.newValue")), address(deployed).code
+        );
+        assertEq(vm.parseJsonUint(json, string.concat(key, ".nonceDiff.previousValue")), 0);
+        assertEq(vm.parseJsonUint(json, string.concat(key, ".nonceDiff.newValue")), 1);
+
+        string memory slot = string.concat(key, ".stateDiff.", vm.toString(bytes32(0)));
+        assertEq(vm.parseJsonBytes32(json, string.concat(slot, ".previousValue")), bytes32(0));
+        assertEq(vm.parseJsonBytes32(json, string.concat(slot, ".newValue")), bytes32(uint256(7)));
+
+        // the factory's nonce was bumped by the CREATE
+        key = string.concat(".", vm.toString(address(counter)));
+        keys = vm.parseJsonKeys(json, key);
+        assertEq(keys.length, 2);
+        assertTrue(vm.keyExists(json, string.concat(key, ".nonceDiff")));
+        assertTrue(vm.keyExists(json, string.concat(key, ".stateDiff")));
+        assertEq(vm.parseJsonUint(json, string.concat(key, ".nonceDiff.newValue")), 2);
+    }
+
+    function testGetStateDiffRequiresRecording() public {
+        vm._expectCheatcodeRevert("no state diff recording in progress; call `vm.startStateDiffRecording` first");
+        vm.getStateDiff();
+    }
+}
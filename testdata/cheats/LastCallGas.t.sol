@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract Target {
+    uint256 public slot0;
+
+    function expandMemory() public pure returns (uint256) {
+        uint256[] memory arr = new uint256[](1000);
+        return arr.length;
+    }
+
+    function setValue(uint256 value) public {
+        slot0 = value;
+    }
+
+    function resetValue() public {
+        slot0 = 0;
+    }
+}
+
+contract LastCallGasTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+    Target public target;
+
+    function setUp() public {
+        target = new Target();
+    }
+
+    function testRecordLastCallGas() public {
+        target.setValue(1);
+        Vm.Gas memory gas = vm.lastCallGas();
+        assertEq(gas.gasLimit, gas.gasTotalUsed + gas.gasRemaining);
+        assertGt(gas.gasTotalUsed, 20_000);
+        assertEq(gas.gasRefunded, 0);
+    }
+
+    function testRecordGasMemory() public {
+        target.expandMemory();
+        assertGt(vm.lastCallGas().gasMemoryUsed, 0);
+    }
+
+    function testRecordGasRefund() public {
+        target.setValue(1);
+        target.resetValue();
+        assertGt(vm.lastCallGas().gasRefunded, 0);
+    }
+
+    function testCheatcodesAreNotRecorded() public {
+        target.setValue(1);
+        uint64 used = vm.lastCallGas().gasTotalUsed;
+        vm.roll(10);
+        assertEq(vm.lastCallGas().gasTotalUsed, used);
+    }
+}
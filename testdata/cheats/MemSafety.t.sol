@@ -670,6 +670,21 @@ contract MemSafetyTest is DSTest {
         }
     }
 
+    /// @dev Tests that the `expectSafeMemoryCall` cheatcode only applies to the next call.
+    function testExpectSafeMemoryCall_onlyNextCall() public {
+        // Create a new SubContext contract
+        SubContext sc = new SubContext();
+        bytes memory allowed = abi.encodeWithSelector(SubContext.doMstore.selector, 0x80, 0xc0ffee);
+        bytes memory disallowed = abi.encodeWithSelector(SubContext.doMstore.selector, 0xA0, 0xc0ffee);
+
+        // Allow memory writes in the range of [0x80, 0xA0) within the next created subcontext
+        vm.expectSafeMemoryCall(0x80, 0xA0);
+        require(_doCall(address(sc), allowed), "Expected call to succeed");
+
+        // Should not revert- the allowed range doesn't apply to the calls after the next one.
+        require(_doCall(address(sc), disallowed), "Expected call to succeed");
+    }
+
     ////////////////////////////////////////////////////////////////
     //                          HELPERS                           //
     ////////////////////////////////////////////////////////////////
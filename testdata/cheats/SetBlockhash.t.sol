@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract SetBlockhash is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testSetBlockhash() public {
+        vm.roll(1000);
+        bytes32 hash = keccak256("block 999");
+        vm.setBlockhash(999, hash);
+        assertEq(blockhash(999), hash);
+        assertEq(blockhash(block.number - 1), hash);
+    }
+
+    function testSetBlockhashAtWindowEdge() public {
+        vm.roll(1000);
+        vm.setBlockhash(744, bytes32(uint256(1)));
+        assertEq(blockhash(744), bytes32(uint256(1)));
+    }
+
+    function testRollPreservesBlockhash() public {
+        vm.roll(1000);
+        bytes32 hash = keccak256("block 990");
+        vm.setBlockhash(990, hash);
+
+        vm.roll(1200);
+        assertEq(blockhash(990), hash);
+
+        // no longer in the window
+        vm.roll(1300);
+        assertEq(blockhash(990), 0);
+
+        vm.roll(1000);
+        assertEq(blockhash(990), hash);
+    }
+
+    function testSetBlockhashOutsideWindow() public {
+        vm.roll(1000);
+        vm._expectCheatcodeRevert(
+            "block number 743 must be within the last 256 blocks before the current block number 1000"
+        );
+        vm.setBlockhash(743, bytes32(uint256(1)));
+    }
+
+    function testSetBlockhashOfCurrentBlock() public {
+        vm.roll(1000);
+        vm._expectCheatcodeRevert(
+            "block number 1000 must be within the last 256 blocks before the current block number 1000"
+        );
+        vm.setBlockhash(1000, bytes32(uint256(1)));
+    }
+}
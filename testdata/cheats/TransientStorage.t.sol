@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+/// forge-config: default.evm_version = "cancun"
+contract TransientStorageTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    // Returns TLOAD of slot 0
+    bytes constant TLOAD_CODE = hex"60005c60005260206000f3";
+
+    // A reentrancy guard in transient slot 0: reverts if the slot is set, otherwise sets it, calls
+    // back the caller, clears it and returns whether the callback succeeded
+    bytes constant GUARD_CODE =
+        hex"60005c15600b57600080fd5b600160005d600080808080335af1600052600060005d60206000f3";
+
+    address guard = address(0x1153);
+    address setUpTarget = address(0x5e7);
+    bool reentered;
+    bool reentrySucceeded;
+    uint256 guardSlotOnReentry;
+
+    fallback() external {
+        reentered = true;
+        guardSlotOnReentry = uint256(vm.tload(guard, bytes32(0)));
+        (reentrySucceeded,) = guard.call("");
+    }
+
+    function setUp() public {
+        vm.tstore(setUpTarget, bytes32(0), bytes32(uint256(1)));
+    }
+
+    function testSetUpTransientStorageIsCleared() public {
+        // `setUp` runs in its own transaction
+        assertEq(vm.tload(setUpTarget, bytes32(0)), bytes32(0));
+    }
+
+    function testTstoreAndTload() public {
+        address target = address(0x1234);
+        assertEq(vm.tload(target, bytes32(uint256(1))), bytes32(0));
+        vm.tstore(target, bytes32(uint256(1)), bytes32(uint256(42)));
+        assertEq(vm.tload(target, bytes32(uint256(1))), bytes32(uint256(42)));
+        // transient storage is separate from persistent storage
+        assertEq(vm.load(target, bytes32(uint256(1))), bytes32(0));
+    }
+
+    function testTstoreIsVisibleToTarget() public {
+        address target = address(0x1234);
+        vm.etch(target, TLOAD_CODE);
+        vm.tstore(target, bytes32(0), bytes32(uint256(42)));
+        (bool success, bytes memory data) = target.call("");
+        assertTrue(success);
+        assertEq(abi.decode(data, (uint256)), 42);
+    }
+
+    function testReentrancyGuard() public {
+        vm.etch(guard, GUARD_CODE);
+
+        (bool success, bytes memory data) = guard.call("");
+        assertTrue(success);
+        // the callback succeeded, but the reentrant call was rejected
+        assertEq(abi.decode(data, (uint256)), 1);
+        assertTrue(reentered);
+        assertTrue(!reentrySucceeded);
+        assertEq(guardSlotOnReentry, 1);
+
+        // the guard is released
+        assertEq(vm.tload(guard, bytes32(0)), bytes32(0));
+        reentered = false;
+        (success,) = guard.call("");
+        assertTrue(success);
+        assertTrue(reentered);
+    }
+
+    function testReentrancyGuardLockedWithTstore() public {
+        vm.etch(guard, GUARD_CODE);
+        vm.tstore(guard, bytes32(0), bytes32(uint256(1)));
+        (bool success,) = guard.call("");
+        assertTrue(!success);
+        assertTrue(!reentered);
+    }
+
+    function testRecordsTransientStorageAccesses() public {
+        vm.etch(guard, GUARD_CODE);
+
+        // the guard has no persistent storage
+        vm.startStateDiffRecording();
+        (bool success,) = guard.call("");
+        Vm.AccountAccess[] memory accesses = vm.stopAndReturnStateDiff();
+        assertTrue(success);
+        for (uint256 i = 0; i < accesses.length; i++) {
+            for (uint256 j = 0; j < accesses[i].storageAccesses.length; j++) {
+                assertTrue(accesses[i].storageAccesses[j].account != guard);
+            }
+        }
+
+        vm.startStateDiffRecording();
+        (success,) = guard.call("");
+        accesses = vm.stopAndReturnTransientStateDiff();
+        assertTrue(success);
+
+        uint256 reads;
+        Vm.StorageAccess[] memory writes = new Vm.StorageAccess[](2);
+        uint256 numWrites;
+        for (uint256 i = 0; i < accesses.length; i++) {
+            for (uint256 j = 0; j < accesses[i].storageAccesses.length; j++) {
+                Vm.StorageAccess memory access = accesses[i].storageAccesses[j];
+                if (access.account != guard) continue;
+                assertEq(access.slot, bytes32(0));
+                if (access.isWrite) {
+                    writes[numWrites++] = access;
+                } else {
+                    reads++;
+                }
+            }
+        }
+        // the guarded call and the rejected reentrant call
+        assertEq(reads, 2);
+        assertEq(numWrites, 2);
+        assertEq(writes[0].previousValue, bytes32(0));
+        assertEq(writes[0].newValue, bytes32(uint256(1)));
+        assertEq(writes[1].previousValue, bytes32(uint256(1)));
+        assertEq(writes[1].newValue, bytes32(0));
+    }
+}
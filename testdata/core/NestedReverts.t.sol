@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+
+library VaultErrors {
+    error InsufficientBalance(uint256 available, uint256 required);
+}
+
+contract Vault {
+    function withdraw(uint256 amount) external pure {
+        revert VaultErrors.InsufficientBalance(0, amount);
+    }
+
+    function divide(uint256 a, uint256 b) external pure returns (uint256) {
+        return a / b;
+    }
+}
+
+contract Forwarder {
+    error CallFailed(bytes reason);
+
+    function forward(address target, bytes calldata data) external {
+        (bool success, bytes memory reason) = target.call(data);
+        if (!success) {
+            revert CallFailed(reason);
+        }
+    }
+}
+
+contract NestedRevertsTest is DSTest {
+    Vault vault;
+    Forwarder forwarder;
+
+    function setUp() public {
+        vault = new Vault();
+        forwarder = new Forwarder();
+    }
+
+    function testDependencyError() public view {
+        vault.withdraw(1);
+    }
+
+    function testPanic() public view {
+        vault.divide(1, 0);
+    }
+
+    function testWrappedError() public {
+        forwarder.forward(address(vault), abi.encodeCall(Vault.withdraw, (1)));
+    }
+
+    function testDeeplyWrappedPanic() public {
+        bytes memory divide = abi.encodeCall(Vault.divide, (1, 0));
+        bytes memory forward = abi.encodeCall(Forwarder.forward, (address(vault), divide));
+        forwarder.forward(address(forwarder), forward);
+    }
+
+    function testHexStringError() public pure {
+        revert(toHexString(abi.encodeWithSelector(VaultErrors.InsufficientBalance.selector, 2, 3)));
+    }
+
+    function toHexString(bytes memory data) internal pure returns (string memory) {
+        bytes memory alphabet = "0123456789abcdef";
+        bytes memory str = new bytes(2 + data.length * 2);
+        str[0] = "0";
+        str[1] = "x";
+        for (uint256 i = 0; i < data.length; i++) {
+            str[2 + i * 2] = alphabet[uint8(data[i] >> 4)];
+            str[3 + i * 2] = alphabet[uint8(data[i] & 0x0f)];
+        }
+        return string(str);
+    }
+}
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+
+contract Vault {
+    address constant OWNER = 0x5FbDB2315678afecb367f032d93F642f64180aa3;
+
+    function isOwner(address who) external pure returns (bool) {
+        return who == OWNER;
+    }
+}
+
+contract FuzzDictionaryTest is DSTest {
+    Vault vault;
+
+    function setUp() public {
+        vault = new Vault();
+    }
+
+    // The owner is only found with the constants of the deployed code in the dictionary.
+    function testFuzzNotOwner(address who) public {
+        require(!vault.isOwner(who), "owner found");
+    }
+}
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+
+contract AfterInvariantHandler {
+    uint256 public count;
+
+    function inc() external {
+        count += 1;
+    }
+}
+
+contract InvariantAfterInvariant is DSTest {
+    AfterInvariantHandler handler;
+
+    function setUp() public {
+        handler = new AfterInvariantHandler();
+    }
+
+    function invariant_countBelowDepth() public {
+        require(handler.count() <= 15, "count exceeds depth");
+    }
+
+    function afterInvariant() public {
+        require(handler.count() <= 15, "afterInvariant failure");
+    }
+}
+
+contract InvariantAfterInvariantFailure is DSTest {
+    AfterInvariantHandler handler;
+
+    function setUp() public {
+        handler = new AfterInvariantHandler();
+    }
+
+    function invariant_true() public {
+        require(true, "false");
+    }
+
+    function afterInvariant() public {
+        require(handler.count() == 0, "afterInvariant failure");
+    }
+}
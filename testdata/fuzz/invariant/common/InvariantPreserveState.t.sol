@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+
+contract PreserveStateHandler {
+    uint256 public count;
+
+    function inc() external {
+        count += 1;
+    }
+}
+
+contract InvariantPreserveState is DSTest {
+    PreserveStateHandler handler;
+
+    function setUp() public {
+        handler = new PreserveStateHandler();
+    }
+
+    // Holds while every run starts from the state after `setUp`, as no run makes more than
+    // `depth` calls, and breaks once the state is preserved across runs.
+    function invariant_countBelowDepth() public {
+        require(handler.count() <= 15, "count exceeds depth");
+    }
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity >=0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+/// forge-config: default.evm_version = london
+contract EvmInlineConf is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    // TSTORE 42 at slot 0, then returns TLOAD of slot 0
+    bytes constant TRANSIENT_STORAGE_CODE = hex"602a60005d60005c60005260206000f3";
+
+    function testLondonDifficulty() public {
+        vm.difficulty(7);
+        assertEq(block.difficulty, 7);
+    }
+
+    function testLondonNoTransientStorage() public {
+        address target = address(0x1234);
+        vm.etch(target, TRANSIENT_STORAGE_CODE);
+        (bool success,) = target.call("");
+        assertTrue(!success);
+    }
+
+    /// forge-config: default.evm_version = paris
+    function testParisPrevrandao() public {
+        vm.prevrandao(bytes32(uint256(42)));
+        assertEq(block.prevrandao, 42);
+    }
+
+    /// forge-config: default.evm_version = cancun
+    function testCancunTransientStorage() public {
+        address target = address(0x1234);
+        vm.etch(target, TRANSIENT_STORAGE_CODE);
+        (bool success, bytes memory data) = target.call("");
+        assertTrue(success);
+        assertEq(abi.decode(data, (uint256)), 42);
+    }
+}